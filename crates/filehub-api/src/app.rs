@@ -1,7 +1,6 @@
 //! Application builder — wires router + middleware + state into an Axum app.
 
 use axum::Router;
-use sqlx::PgPool;
 use std::sync::Arc;
 use tokio::sync::watch;
 use tower_http::trace::TraceLayer;
@@ -9,32 +8,50 @@ use tower_http::trace::TraceLayer;
 use filehub_core::config::AppConfig;
 use filehub_core::error::AppError;
 use filehub_database::repositories::{
-    audit, file, folder, job, license, notification, permission, pool_snapshot, session,
-    session_limit, share, storage, user,
+    access_request, api_usage, audit, file, folder, folder_preference, job, license,
+    login_location, notification, outbox, permission, permission_template, pool_snapshot,
+    public_resource, retention, saved_search, session, session_limit, share, storage,
+    storage_migration, tag, user,
 };
 use filehub_worker::jobs::cleanup::{
     ChunkCleanupHandler, SessionCleanupHandler, TempCleanupHandler, VersionCleanupHandler,
 };
 
+use crate::middleware::client_ip::TrustedProxies;
 use crate::middleware::compression::build_compression_layer;
-use crate::middleware::cors::build_cors_layer;
+use crate::middleware::impersonation::BlockedRoutes;
 use crate::router::build_router;
 use crate::state::AppState;
 
 /// Builds the complete Axum application with all routes and middleware.
-pub fn build_app(state: AppState, cors_config: &filehub_core::config::app::CorsConfig) -> Router {
-    build_router(state)
-        .layer(build_compression_layer())
-        .layer(build_cors_layer(cors_config))
+pub fn build_app(state: AppState) -> Router {
+    let compression = build_compression_layer(&state.config.server.compression);
+    build_router(state.clone())
+        .layer(compression)
+        .layer(axum::middleware::from_fn_with_state(
+            state,
+            crate::middleware::compression::negotiate_encoding,
+        ))
         .layer(TraceLayer::new_for_http())
 }
 
 /// Runs the FileHub server with the given configuration and database pool.
-pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppError> {
+///
+/// Report and search queries are routed to the pool's read replicas, if any.
+///
+/// With `reload` set, a `SIGHUP` re-reads the configuration and applies its
+/// hot-reloadable fields.
+pub async fn run_server(
+    config: AppConfig,
+    database: filehub_database::DatabasePool,
+    reload: Option<crate::reload::ReloadHooks>,
+) -> Result<(), AppError> {
     tracing::info!("Starting FileHub server...");
+    let health = Arc::new(crate::health::HealthMonitor::new());
 
     // ── Step 1: Create data directories ──────────────────────────
     create_data_directories(&config).await?;
+    filehub_core::types::cursor::init_signing_key(config.auth.jwt_secret.expose());
 
     // ── Step 2: Initialize cache ─────────────────────────────────
     tracing::info!(
@@ -45,27 +62,60 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
         .await
         .map_err(|e| AppError::internal(format!("Cache init failed: {}", e)))?;
     let cache = Arc::new(cache);
+    let http_client = filehub_http::HttpClient::new(&config.http_client)?;
 
     // ── Step 3: Initialize storage providers ─────────────────────
-    let storage_manager = Arc::new(filehub_storage::manager::StorageManager::new());
+    let storage_manager = Arc::new(filehub_storage::manager::StorageManager::with_retry(
+        config.storage.retry.clone(),
+    ));
+    Arc::clone(&storage_manager).spawn_health_prober(
+        std::time::Duration::from_secs(config.storage.health_check.interval_seconds),
+        std::time::Duration::from_secs(config.storage.health_check.timeout_seconds),
+    );
 
     // ── Step 4: Initialize repositories ──────────────────────────
-    let user_repo = Arc::new(user::UserRepository::new(db_pool.clone()));
+    let db_pool = database.pool().clone();
+    let user_repo =
+        Arc::new(user::UserRepository::new(db_pool.clone()).with_read_replicas(database.clone()));
     let session_repo = Arc::new(session::SessionRepository::new(db_pool.clone()));
-    let file_repo = Arc::new(file::FileRepository::new(db_pool.clone()));
+    let file_repo =
+        Arc::new(file::FileRepository::new(db_pool.clone()).with_read_replicas(database.clone()));
     let folder_repo = Arc::new(folder::FolderRepository::new(db_pool.clone()));
-    let storage_repo = Arc::new(storage::StorageRepository::new(db_pool.clone()));
+    let folder_preference_repo = Arc::new(folder_preference::FolderPreferenceRepository::new(
+        db_pool.clone(),
+    ));
+    let storage_repo = Arc::new(
+        storage::StorageRepository::new(db_pool.clone()).with_read_replicas(database.clone()),
+    );
     let permission_repo = Arc::new(permission::AclRepository::new(db_pool.clone()));
+    let permission_template_repo = Arc::new(
+        permission_template::PermissionTemplateRepository::new(db_pool.clone()),
+    );
+    let retention_repo = Arc::new(retention::RetentionPolicyRepository::new(db_pool.clone()));
     let share_repo = Arc::new(share::ShareRepository::new(db_pool.clone()));
+    let access_request_repo = Arc::new(access_request::AccessRequestRepository::new(
+        db_pool.clone(),
+    ));
     let job_repo = Arc::new(job::JobRepository::new(db_pool.clone()));
     let notification_repo = Arc::new(notification::NotificationRepository::new(db_pool.clone()));
-    let audit_repo = Arc::new(audit::AuditLogRepository::new(db_pool.clone()));
+    let outbox_repo = Arc::new(outbox::OutboxRepository::new(db_pool.clone()));
+    let audit_repo = Arc::new(
+        audit::AuditLogRepository::new(db_pool.clone()).with_read_replicas(database.clone()),
+    );
     let license_repo = Arc::new(license::LicenseCheckoutRepository::new(db_pool.clone()));
     let snapshot_repo = Arc::new(pool_snapshot::PoolSnapshotRepository::new(db_pool.clone()));
     let session_limit_repo = Arc::new(session_limit::SessionLimitRepository::new(db_pool.clone()));
+    let saved_search_repo = Arc::new(saved_search::SavedSearchRepository::new(db_pool.clone()));
+    let tag_repo = Arc::new(tag::TagRepository::new(db_pool.clone()));
+    let storage_migration_repo = Arc::new(storage_migration::StorageMigrationRepository::new(
+        db_pool.clone(),
+    ));
 
     // ── Step 5: Initialize auth system ───────────────────────────
-    let password_hasher = Arc::new(filehub_auth::password::hasher::PasswordHasher::new());
+    let password_hasher = Arc::new(
+        filehub_auth::password::hasher::PasswordHasher::from_config(&config.auth.password_hash)?
+            .with_pepper(&config.auth.password_pepper)?,
+    );
     let jwt_encoder = Arc::new(filehub_auth::jwt::encoder::JwtEncoder::new(&config.auth));
     let jwt_decoder = Arc::new(filehub_auth::jwt::decoder::JwtDecoder::new(
         &config.auth,
@@ -82,13 +132,12 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
         config.session.clone(),
     ));
 
-    let seat_allocator = Arc::new(filehub_auth::seat::allocator::SeatAllocatorDispatch::new(
-        &config.session,
-        Arc::clone(&cache),
-        Arc::clone(&session_repo),
-    ));
+    let seat_allocator = Arc::new(
+        filehub_auth::seat::allocator::SeatAllocatorDispatch::new(&config.session, &config.cache)
+            .await,
+    );
 
-    let session_manager = Arc::new(filehub_auth::session::manager::SessionManager::new(
+    let session_manager = filehub_auth::session::manager::SessionManager::new(
         Arc::clone(&jwt_encoder),
         Arc::clone(&jwt_decoder),
         Arc::clone(&session_store),
@@ -99,7 +148,20 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
         Arc::clone(&cache),
         config.auth.clone(),
         config.session.clone(),
-    ));
+    );
+    let session_manager = if config.auth.login_anomaly.enabled {
+        let detector = filehub_auth::LoginAnomalyDetector::new(
+            config.auth.login_anomaly.clone(),
+            Arc::new(login_location::LoginLocationRepository::new(
+                db_pool.clone(),
+            )),
+            Arc::clone(&audit_repo),
+            Arc::clone(&notification_repo),
+        );
+        Arc::new(session_manager.with_anomaly_detector(Arc::new(detector)))
+    } else {
+        Arc::new(session_manager)
+    };
 
     let rbac_enforcer = Arc::new(filehub_auth::rbac::enforcer::RbacEnforcer::new());
     let acl_checker = Arc::new(filehub_auth::acl::checker::AclChecker::new(Arc::clone(
@@ -122,9 +184,24 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
         ),
     );
 
+    let seat_reconciler = Arc::new(filehub_auth::seat::reconciler::SeatReconciler::new(
+        Arc::clone(&seat_allocator) as Arc<dyn filehub_auth::SeatAllocator>,
+        Arc::clone(&session_store),
+        Arc::clone(&snapshot_repo),
+    ));
+    if let Err(e) = seat_reconciler.startup_recovery().await {
+        tracing::warn!(error = %e, "Startup seat pool recovery failed");
+    }
+    if config.session.seat_reconcile_interval_seconds > 0 {
+        seat_reconciler.spawn(std::time::Duration::from_secs(
+            config.session.seat_reconcile_interval_seconds,
+        ));
+    }
+
     // ── Step 6: Initialize plugin manager ────────────────────────
     let plugin_manager = Arc::new(filehub_plugin::manager::PluginManager::new());
 
+    let mut license_manager = None;
     if config.license.enabled {
         let dll_path = if config.license.license_file.is_empty() {
             None
@@ -133,7 +210,7 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
         };
 
         let flexnet_plugin = plugin_flexnet::FlexNetPlugin::new();
-        flexnet_plugin
+        let manager = flexnet_plugin
             .initialize(
                 config.license.clone(),
                 dll_path,
@@ -141,6 +218,7 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
                 Arc::clone(&snapshot_repo),
             )
             .await?;
+        license_manager = Some(manager);
 
         flexnet_plugin
             .register_hooks(plugin_manager.hook_registry())
@@ -153,9 +231,10 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
             .map_err(|e| AppError::internal(format!("Failed to register FlexNet: {}", e)))?;
     }
 
+    let mut conversion_processor = None;
     if config.storage.conversions.enabled {
         let cad_plugin = Arc::new(plugin_cad_converter::CadConverterPlugin::new());
-        cad_plugin.initialize().await?;
+        conversion_processor = Some(cad_plugin.initialize().await?);
         cad_plugin
             .register_hooks(plugin_manager.hook_registry())
             .await;
@@ -167,116 +246,325 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
             .map_err(|e| AppError::internal(format!("Failed to register CAD converter: {}", e)))?;
     }
 
+    if config.storage.antivirus.enabled {
+        let scanner = plugin_antivirus::UploadScanner::new(
+            config.storage.antivirus.clone(),
+            Arc::clone(&storage_manager),
+            Arc::clone(&file_repo),
+            Arc::clone(&audit_repo),
+            Arc::clone(&notification_repo),
+        );
+        let antivirus_plugin = Arc::new(plugin_antivirus::AntivirusPlugin::new(scanner));
+        antivirus_plugin
+            .register_hooks(plugin_manager.hook_registry())
+            .await;
+
+        plugin_manager
+            .plugin_registry()
+            .register(antivirus_plugin)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to register antivirus: {}", e)))?;
+    }
+
     // ── Step 7: Initialize services ──────────────────────────────
-    let file_service = Arc::new(filehub_service::file::service::FileService::new(
-        Arc::clone(&file_repo),
-        Arc::clone(&folder_repo),
-        Arc::clone(&permission_resolver),
-    ));
-    let upload_service = Arc::new(filehub_service::file::upload::UploadService::new(
-        Arc::clone(&file_repo),
+    let mut audit_service = filehub_service::session::SessionAudit::new(Arc::clone(&audit_repo));
+    if !config.audit.sinks.is_empty() {
+        if !config.worker.enabled {
+            tracing::warn!(
+                "audit.sinks are configured but the worker is disabled; events are queued until it runs"
+            );
+        }
+        audit_service = audit_service.with_shipper(filehub_service::session::AuditShipper::new(
+            Arc::clone(&job_repo),
+            &config.audit,
+        ));
+    }
+    let audit_service = Arc::new(audit_service);
+    let event_bus = filehub_core::events::EventBus::default();
+    let tree_cache = filehub_service::folder::FolderTreeCache::new(Arc::clone(&cache));
+    let folder_preferences =
+        filehub_service::folder::FolderPreferenceStore::new(folder_preference_repo)
+            .with_cache(Arc::clone(&cache));
+    let retention_service = Arc::new(filehub_service::folder::RetentionService::new(
+        Arc::clone(&retention_repo),
         Arc::clone(&folder_repo),
-        Arc::clone(&storage_manager),
-        Arc::clone(&permission_resolver),
-        config.storage.clone(),
-        Arc::clone(&plugin_manager),
+        Arc::clone(&audit_service),
     ));
-    let folder_service = Arc::new(filehub_service::folder::service::FolderService::new(
-        Arc::clone(&folder_repo),
-        Arc::clone(&storage_repo),
-        Arc::clone(&permission_resolver),
+    let file_service = Arc::new(
+        filehub_service::file::service::FileService::new(
+            Arc::clone(&file_repo),
+            Arc::clone(&folder_repo),
+            Arc::clone(&tag_repo),
+            Arc::clone(&permission_resolver),
+            Arc::clone(&plugin_manager),
+            Arc::clone(&audit_service),
+        )
+        .with_tree_cache(tree_cache.clone())
+        .with_name_collision(
+            config.storage.name_collision,
+            config.storage.max_versions_per_file > 0,
+        )
+        .with_retention((*retention_service).clone())
+        .with_folder_preferences(folder_preferences.clone()),
+    );
+    let upload_service = Arc::new(
+        filehub_service::file::upload::UploadService::new(
+            Arc::clone(&file_repo),
+            Arc::clone(&folder_repo),
+            Arc::clone(&storage_manager),
+            Arc::clone(&permission_resolver),
+            config.storage.clone(),
+            Arc::clone(&plugin_manager),
+        )
+        .with_thumbnail_jobs(Arc::clone(&job_repo))
+        .with_tree_cache(tree_cache.clone()),
+    );
+    let permission_template_service = Arc::new(
+        filehub_service::permission::template::PermissionTemplateService::new(
+            permission_template_repo,
+            Arc::clone(&folder_repo),
+            Arc::clone(&permission_repo),
+            Arc::clone(&permission_resolver),
+        ),
+    );
+    let folder_service = Arc::new(
+        filehub_service::folder::service::FolderService::new(
+            Arc::clone(&folder_repo),
+            Arc::clone(&storage_repo),
+            Arc::clone(&permission_resolver),
+            Arc::clone(&audit_service),
+        )
+        .with_tree_cache(tree_cache.clone())
+        .with_permission_templates((*permission_template_service).clone())
+        .with_retention((*retention_service).clone())
+        .with_preferences(folder_preferences),
+    );
+    let link_service = Arc::new(filehub_service::share::LinkService::new(
+        config.auth.jwt_secret.expose(),
     ));
-    let link_service = Arc::new(filehub_service::share::LinkService::new());
     let share_service = Arc::new(filehub_service::share::service::ShareService::new(
         Arc::clone(&share_repo),
+        Arc::clone(&file_repo),
+        Arc::clone(&folder_repo),
+        Arc::clone(&permission_repo),
+        Arc::clone(&permission_resolver),
         Arc::clone(&link_service),
         Arc::clone(&password_hasher),
     ));
-    let notification_service = Arc::new(
-        filehub_service::notification::service::NotificationService::new(Arc::clone(
-            &notification_repo,
-        )),
+    let notification_service = filehub_service::notification::service::NotificationService::new(
+        Arc::clone(&notification_repo),
     );
+    #[cfg(feature = "email")]
+    let notification_service = if config.email.enabled {
+        notification_service.with_email(Arc::clone(&job_repo), config.email.max_attempts)
+    } else {
+        notification_service
+    };
+    #[cfg(not(feature = "email"))]
+    if config.email.enabled {
+        tracing::warn!(
+            "email.enabled is set but this build lacks the `email` feature; emails are off"
+        );
+    }
+    let notification_service = Arc::new(notification_service);
     let storage_service = Arc::new(filehub_service::storage::service::StorageService::new(
         Arc::clone(&storage_repo),
         Arc::clone(&rbac_enforcer),
     ));
+    let transfer_service = Arc::new(filehub_service::storage::TransferService::new(
+        Arc::clone(&storage_manager),
+        Arc::clone(&rbac_enforcer),
+        Arc::clone(&storage_repo),
+        Arc::clone(&file_repo),
+        storage_migration_repo,
+        Arc::clone(&job_repo),
+        Arc::clone(&audit_service),
+        config.storage.migration.clone(),
+    ));
     let permission_service = Arc::new(
         filehub_service::permission::service::PermissionService::new(
             Arc::clone(&permission_repo),
+            Arc::clone(&folder_repo),
+            Arc::clone(&file_repo),
+            Arc::clone(&rbac_enforcer),
+            Arc::clone(&permission_resolver),
+        ),
+    );
+    let permission_explainer = Arc::new(
+        filehub_service::permission::explain::PermissionExplainer::new(
+            Arc::clone(&user_repo),
+            Arc::clone(&file_repo),
+            Arc::clone(&folder_repo),
+            Arc::clone(&storage_repo),
+            Arc::clone(&share_repo),
             Arc::clone(&rbac_enforcer),
             Arc::clone(&permission_resolver),
         ),
     );
     let session_service = Arc::new(filehub_service::session::service::SessionService::new(
         Arc::clone(&session_store),
+        Arc::clone(&session_manager),
         Arc::clone(&rbac_enforcer),
     ));
 
-    let access_service = Arc::new(filehub_service::share::AccessService::new(
-        Arc::clone(&share_repo),
-        Arc::clone(&password_hasher),
-    ));
-    let admin_user_service = Arc::new(filehub_service::user::AdminUserService::new(
-        Arc::clone(&user_repo),
-        Arc::clone(&password_hasher),
-        Arc::clone(&password_validator),
-        Arc::clone(&rbac_enforcer),
+    let access_service = Arc::new(
+        filehub_service::share::AccessService::new(
+            Arc::clone(&share_repo),
+            Arc::clone(&link_service),
+            Arc::clone(&password_hasher),
+            Arc::clone(&audit_service),
+        )
+        .with_analytics(Arc::clone(&job_repo), config.shares.analytics.clone()),
+    );
+    let access_request_service = Arc::new(filehub_service::share::AccessRequestService::new(
+        access_request_repo,
+        Arc::clone(&share_service),
+        Arc::clone(&access_service),
+        Arc::clone(&permission_resolver),
     ));
+    let admin_user_service = Arc::new(
+        filehub_service::user::AdminUserService::new(
+            Arc::clone(&user_repo),
+            Arc::clone(&password_hasher),
+            Arc::clone(&password_validator),
+            Arc::clone(&rbac_enforcer),
+            Arc::clone(&audit_service),
+        )
+        .with_session_manager(Arc::clone(&session_manager)),
+    );
     let user_service = Arc::new(filehub_service::user::UserService::new(
         Arc::clone(&user_repo),
         Arc::clone(&password_hasher),
         Arc::clone(&password_validator),
+        Arc::clone(&audit_service),
     ));
     let report_service = Arc::new(filehub_service::report::WeeklyReportService::new(
         Arc::clone(&user_repo),
         Arc::clone(&file_repo),
         Arc::clone(&audit_repo),
     ));
-    let download_service = Arc::new(filehub_service::file::DownloadService::new(
+    let download_service = Arc::new(
+        filehub_service::file::DownloadService::new(
+            Arc::clone(&file_repo),
+            Arc::clone(&storage_manager),
+            Arc::clone(&permission_resolver),
+            Arc::clone(&folder_repo),
+            Arc::clone(&audit_service),
+            config.storage.zip_download.clone(),
+        )
+        .with_direct_transfer(config.storage.direct_transfer.clone()),
+    );
+    let public_access_service = Arc::new(filehub_service::share::PublicAccessService::new(
+        Arc::new(public_resource::PublicResourceRepository::new(
+            db_pool.clone(),
+        )),
+        Arc::clone(&folder_repo),
         Arc::clone(&file_repo),
-        Arc::clone(&storage_manager),
+        Arc::clone(&download_service),
         Arc::clone(&permission_resolver),
+        Arc::clone(&audit_service),
+    ));
+    let quota_service = Arc::new(filehub_service::user::QuotaService::new(
+        Arc::new(api_usage::ApiUsageRepository::new(db_pool.clone())),
+        Arc::clone(&user_repo),
+        Arc::clone(&rbac_enforcer),
+        Arc::clone(&audit_service),
+        config.server.quotas.clone(),
     ));
-    let preview_service = Arc::new(filehub_service::file::PreviewService::new(
+    let mut preview_service = filehub_service::file::PreviewService::new(
         Arc::clone(&file_repo),
         Arc::clone(&storage_manager),
         Arc::clone(&permission_resolver),
         Arc::clone(&cache),
+    )
+    .with_thumbnails(&config.storage.thumbnails);
+    if config.storage.document_preview.enabled {
+        preview_service = preview_service.with_documents(
+            Arc::clone(&job_repo),
+            &config.storage.document_preview,
+            format!("{}/cache/previews", config.storage.data_root),
+        );
+    }
+    let preview_service = Arc::new(preview_service);
+    let conversion_service = Arc::new(filehub_service::file::ConversionRequestService::new(
+        Arc::clone(&file_repo),
+        Arc::clone(&storage_manager),
+        Arc::clone(&permission_resolver),
+        Arc::clone(&job_repo),
+        config.storage.conversions.clone(),
+        &config.storage.data_root,
+    ));
+    let search_service = Arc::new(filehub_service::file::SearchService::new(
+        Arc::clone(&file_repo),
+        Arc::clone(&saved_search_repo),
+        Arc::clone(&permission_resolver),
     ));
-    let search_service = Arc::new(filehub_service::file::SearchService::new(Arc::clone(
-        &file_repo,
-    )));
     let version_service = Arc::new(filehub_service::file::VersionService::new(
         Arc::clone(&file_repo),
         Arc::clone(&permission_resolver),
     ));
-    let tree_service = Arc::new(filehub_service::folder::TreeService::new(Arc::clone(
-        &folder_repo,
-    )));
+    let tree_service = Arc::new(
+        filehub_service::folder::TreeService::new(Arc::clone(&folder_repo)).with_cache(tree_cache),
+    );
     let termination_service = Arc::new(filehub_service::session::TerminationService::new(
         Arc::clone(&session_manager),
         Arc::clone(&rbac_enforcer),
     ));
-    let audit_service = Arc::new(filehub_service::session::SessionAudit::new(Arc::clone(
-        &audit_repo,
-    )));
+    let impersonation_service = Arc::new(filehub_service::session::ImpersonationService::new(
+        Arc::clone(&session_manager),
+        Arc::clone(&rbac_enforcer),
+        Arc::clone(&audit_service),
+        config.auth.impersonation.clone(),
+    ));
 
     // ── Step 8: Initialize realtime engine ───────────────────────
+    let notification_templates = Arc::new(
+        filehub_entity::notification::NotificationTemplates::from_config(
+            &config.realtime.notifications.templates,
+        )?,
+    );
     let realtime_engine = Arc::new(
         filehub_realtime::server::RealtimeEngine::new(
             &config.realtime,
             Arc::clone(&jwt_decoder),
             Arc::clone(&session_repo),
+            Arc::clone(&notification_repo),
+            Arc::clone(&job_repo),
             Arc::clone(&notification_service),
+            Arc::clone(&audit_service),
+            notification_templates,
         )
         .await,
     );
 
-    // ── Step 9: Shutdown channel & worker ────────────────────────
+    filehub_realtime::notification::spawn_event_bridge(
+        &event_bus,
+        Arc::clone(&realtime_engine.notifications),
+        config.realtime.notifications.upload_coalescing.clone(),
+    );
+    Arc::clone(&audit_service).spawn_event_sink(&event_bus);
+
+    // ── Step 9: Shutdown/reload channels & worker ────────────────
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let (live_config_tx, live_config) = watch::channel(Arc::new(config.clone()));
 
-    let _worker_handle = if config.worker.enabled {
+    let outbox_relay = filehub_worker::relay::OutboxRelay::new(
+        Arc::clone(&outbox_repo),
+        event_bus.clone(),
+        config.worker.outbox.clone(),
+    );
+    let relay_cancel = shutdown_rx.clone();
+    tokio::spawn(async move {
+        outbox_relay.run(relay_cancel).await;
+    });
+
+    let flusher = Arc::clone(&quota_service);
+    let flusher_cancel = shutdown_rx.clone();
+    tokio::spawn(async move {
+        flusher.run_flusher(flusher_cancel).await;
+    });
+
+    let worker_handle = if config.worker.enabled {
         let worker_id = format!("worker-{}", &uuid::Uuid::new_v4().to_string()[..8]);
         let job_queue = Arc::new(filehub_worker::queue::JobQueue::new(
             Arc::clone(&job_repo),
@@ -289,7 +577,9 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
         let cleanup_handler = Arc::new(filehub_worker::jobs::cleanup::CleanupJobHandler::new(
             Arc::clone(&session_repo),
             Arc::clone(&file_repo),
+            Arc::clone(&storage_manager),
             std::path::PathBuf::from(&config.storage.data_root),
+            config.storage.max_versions_per_file,
         ));
 
         job_executor.register(Arc::new(SessionCleanupHandler::new(Arc::clone(
@@ -305,6 +595,40 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
             &cleanup_handler,
         ))));
 
+        let expiry_handler = Arc::new(filehub_worker::jobs::expiry::FileExpiryJobHandler::new(
+            Arc::clone(&file_repo),
+            Arc::clone(&retention_repo),
+            Arc::clone(&notification_repo),
+            Arc::clone(&audit_repo),
+            config.storage.expiry_notice_hours,
+            Some(Arc::clone(&realtime_engine.notifications) as _),
+        ));
+        job_executor.register(expiry_handler);
+
+        let audit_retention_handler = Arc::new(
+            filehub_worker::jobs::audit_retention::AuditRetentionJobHandler::new(
+                Arc::clone(&audit_repo),
+                Arc::clone(&storage_manager),
+                config.audit.retention.clone(),
+            ),
+        );
+        job_executor.register(audit_retention_handler);
+
+        let outbox_cleanup_handler = Arc::new(
+            filehub_worker::jobs::outbox_cleanup::OutboxCleanupJobHandler::new(
+                Arc::clone(&outbox_repo),
+                config.worker.outbox.clone(),
+            ),
+        );
+        job_executor.register(outbox_cleanup_handler);
+
+        let api_usage_rollup_handler = Arc::new(
+            filehub_worker::jobs::api_usage::ApiUsageRollupJobHandler::new(Arc::new(
+                api_usage::ApiUsageRepository::new(db_pool.clone()),
+            )),
+        );
+        job_executor.register(api_usage_rollup_handler);
+
         let report_handler = Arc::new(filehub_worker::jobs::report::ReportJobHandler::new(
             Arc::clone(&user_repo),
             Arc::clone(&file_repo),
@@ -327,10 +651,38 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
                 Arc::clone(&notification_repo),
                 config.realtime.notifications.cleanup_after_days as i64,
                 config.realtime.notifications.max_stored_per_user as i64,
+                Arc::clone(&notification_service),
+                Some(Arc::clone(&realtime_engine.notifications) as _),
             ),
         );
         job_executor.register(notification_handler);
 
+        let broadcast_handler =
+            Arc::new(filehub_worker::jobs::broadcast::BroadcastJobHandler::new(
+                Arc::clone(&notification_repo),
+                Arc::clone(&realtime_engine.session_monitor) as _,
+                config.realtime.broadcast_pending_minutes,
+            ));
+        job_executor.register(broadcast_handler);
+
+        if !config.audit.sinks.is_empty() {
+            let audit_ship_handler = filehub_worker::jobs::audit_ship::AuditShipJobHandler::new(
+                filehub_worker::audit_sink::build_sinks(&config.audit, &http_client)?,
+                Arc::clone(&notification_repo),
+                config.audit.alert_interval_minutes,
+            );
+            job_executor.register(Arc::new(audit_ship_handler));
+        }
+
+        #[cfg(feature = "email")]
+        if config.email.enabled {
+            let email_handler = filehub_worker::jobs::email::NotificationEmailHandler::new(
+                &config.email,
+                Arc::clone(&user_repo),
+            )?;
+            job_executor.register(Arc::new(email_handler));
+        }
+
         let license_handler = Arc::new(filehub_worker::jobs::license::LicenseJobHandler::new(None));
         job_executor.register(license_handler);
 
@@ -344,17 +696,61 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
         let idle_handler = Arc::new(
             filehub_worker::jobs::presence::IdleSessionCheckHandler::new(
                 Arc::clone(&session_repo),
-                config.session.idle_timeout_minutes as i64,
+                Some(Arc::clone(&realtime_engine.connections) as _),
+                &config.session,
             ),
         );
         job_executor.register(idle_handler);
+
+        let thumbnail_handler =
+            Arc::new(filehub_worker::jobs::thumbnail::ThumbnailJobHandler::new(
+                Arc::clone(&preview_service),
+                &config.storage.thumbnail_pregen,
+            ));
+        job_executor.register(thumbnail_handler);
+
+        if config.storage.document_preview.enabled {
+            let document_preview_handler = Arc::new(
+                filehub_worker::jobs::document_preview::DocumentPreviewJobHandler::new(
+                    Arc::clone(&preview_service),
+                    &config.storage.document_preview,
+                ),
+            );
+            job_executor.register(document_preview_handler);
+        }
+
+        if let Some(processor) = conversion_processor {
+            let conversion_handler = Arc::new(filehub_worker::jobs::CadConversionJobHandler::new(
+                Arc::new(plugin_cad_converter::ProcessorConversionService::new(
+                    processor,
+                )),
+                config.storage.conversions.clone(),
+            ));
+            job_executor.register(conversion_handler);
+        }
+
+        let share_access_handler = Arc::new(
+            filehub_worker::jobs::share::ShareAccessJobHandler::new(Arc::clone(&share_repo)),
+        );
+        job_executor.register(share_access_handler);
+
+        let storage_migration_handler = Arc::new(
+            filehub_worker::jobs::storage_migration::StorageMigrationJobHandler::new(Arc::clone(
+                &transfer_service,
+            )),
+        );
+        job_executor.register(storage_migration_handler);
         let job_executor = Arc::new(job_executor);
         let worker_runner = filehub_worker::runner::WorkerRunner::new(
             Arc::clone(&job_queue),
             Arc::clone(&job_executor),
             config.worker.clone(),
             worker_id,
-        );
+        )
+        .with_live_config(live_config.clone())
+        .with_drain_timeout(std::time::Duration::from_secs(
+            config.server.shutdown_grace_seconds,
+        ));
 
         let worker_cancel = shutdown_rx.clone();
         Some(tokio::spawn(async move {
@@ -365,11 +761,19 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
     };
 
     // ── Step 10: Build and start HTTP server ─────────────────────
+    let metrics = Arc::new(crate::metrics::ApiMetrics::new()?);
+
+    if let Some(hooks) = reload {
+        crate::reload::spawn_reloader(hooks, live_config_tx, Arc::clone(&cache));
+    }
+
     let app_state = AppState {
         config: Arc::new(config.clone()),
+        live_config,
         db_pool: db_pool.clone(),
         cache,
         storage_manager,
+        http_client,
         jwt_encoder,
         jwt_decoder,
         password_hasher,
@@ -378,7 +782,14 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
         rbac_enforcer,
         permission_resolver,
         plugin_manager,
+        license_manager,
         realtime: realtime_engine,
+        event_bus,
+        metrics,
+        health,
+        drain: Arc::new(crate::drain::DrainState::new()),
+        trusted_proxies: Arc::new(TrustedProxies::from_config(&config.server.proxy)?),
+        impersonation_blocked: Arc::new(BlockedRoutes::from_config(&config.auth.impersonation)?),
         user_repo,
         session_repo,
         file_repo,
@@ -389,6 +800,7 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
         notification_repo,
         audit_repo,
         job_repo,
+        outbox_repo,
         license_repo,
         snapshot_repo,
         file_service,
@@ -397,7 +809,11 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
         share_service,
         notification_service,
         storage_service,
+        transfer_service,
         permission_service,
+        permission_template_service,
+        retention_service,
+        permission_explainer,
         session_service,
         audit_service,
         admin_user_service,
@@ -405,14 +821,37 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
         report_service,
         download_service,
         preview_service,
+        conversion_service,
         version_service,
         tree_service,
         termination_service,
+        impersonation_service,
         search_service,
         access_service,
+        access_request_service,
+        public_access_service,
+        quota_service: Arc::clone(&quota_service),
     };
 
-    let app = build_app(app_state, &config.server.cors);
+    if config.server.metrics.enabled
+        && let Some(port) = config.server.metrics.admin_port
+    {
+        spawn_metrics_listener(app_state.clone(), &config.server.metrics.admin_host, port).await?;
+    }
+
+    #[cfg(feature = "grpc")]
+    if config.server.grpc.enabled {
+        crate::grpc::spawn_listener(app_state.clone(), &config.server.grpc).await?;
+    }
+    #[cfg(not(feature = "grpc"))]
+    if config.server.grpc.enabled {
+        tracing::warn!(
+            "server.grpc.enabled is set but this build lacks the `grpc` feature; gRPC is off"
+        );
+    }
+
+    let drain_state = app_state.clone();
+    let app = build_app(app_state);
     let addr = format!("{}:{}", config.server.host, config.server.port);
     let listener = tokio::net::TcpListener::bind(&addr)
         .await
@@ -420,15 +859,46 @@ pub async fn run_server(config: AppConfig, db_pool: PgPool) -> Result<(), AppErr
 
     tracing::info!("FileHub server listening on {}", addr);
 
-    let server = axum::serve(listener, app).with_graceful_shutdown(async move {
+    let server = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown(async move {
         shutdown_signal().await;
-        let _ = shutdown_tx.send(true);
+        crate::drain::drain(&drain_state, &shutdown_tx, worker_handle.as_ref()).await;
     });
 
     server
         .await
         .map_err(|e| AppError::internal(format!("Server error: {}", e)))?;
 
+    // Requests have finished; keep the usage they counted.
+    if quota_service.enabled()
+        && let Err(e) = quota_service.flush().await
+    {
+        tracing::warn!(error = %e, "Failed to flush API usage on shutdown");
+    }
+
+    Ok(())
+}
+
+/// Serves the metrics endpoint on its own listener so it can be kept off
+/// the public interface.
+async fn spawn_metrics_listener(state: AppState, host: &str, port: u16) -> Result<(), AppError> {
+    let addr = format!("{host}:{port}");
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to bind metrics {}: {}", addr, e)))?;
+
+    tracing::info!("Metrics listening on {}", addr);
+
+    let app = crate::router::build_metrics_router(state);
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            tracing::error!("Metrics server error: {}", e);
+        }
+    });
+
     Ok(())
 }
 
@@ -436,6 +906,7 @@ async fn create_data_directories(config: &AppConfig) -> Result<(), AppError> {
     let dirs = [
         format!("{}/storage/local", config.storage.data_root),
         format!("{}/cache/thumbnails", config.storage.data_root),
+        format!("{}/cache/previews", config.storage.data_root),
         format!("{}/cache/conversions", config.storage.data_root),
         format!("{}/temp", config.storage.data_root),
         format!("{}/logs", config.storage.data_root),
@@ -451,8 +922,26 @@ async fn create_data_directories(config: &AppConfig) -> Result<(), AppError> {
     Ok(())
 }
 
+/// Resolves on Ctrl+C or, on Unix, SIGTERM (what orchestrators send).
 async fn shutdown_signal() {
-    tokio::signal::ctrl_c()
-        .await
-        .expect("Failed to install Ctrl+C handler");
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
 }
@@ -0,0 +1,263 @@
+//! What this deployment supports, behind `/api/capabilities`.
+//!
+//! Built from the compiled features and the live configuration on every
+//! request, so it cannot drift from what the server actually does. The
+//! ETag is a hash of the report: clients revalidate cheaply and see a
+//! change as soon as a config reload makes one.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use filehub_core::config::AppConfig;
+use filehub_storage::providers::COMPILED_PROVIDERS;
+
+/// Version of the HTTP API, raised on incompatible changes.
+pub const API_VERSION: u32 = 1;
+
+/// Output format of CAD conversions.
+const CONVERSION_OUTPUT_FORMAT: &str = "vtfx";
+
+/// Everything a client may adapt to.
+#[derive(Debug, Clone, Serialize)]
+pub struct Capabilities {
+    /// Versions, for compatibility checks.
+    pub version: VersionInfo,
+    /// Optional features compiled into this build.
+    pub features: BuildFeatures,
+    /// Protocols served.
+    pub protocols: Protocols,
+    /// Storage backends and transfer limits.
+    pub storage: StorageCapabilities,
+    /// Sign-in options.
+    pub auth: AuthCapabilities,
+    /// Realtime updates.
+    pub realtime: RealtimeCapabilities,
+    /// CAD conversion.
+    pub conversion: ConversionCapabilities,
+    /// License enforcement.
+    pub license: LicenseCapabilities,
+}
+
+/// Server and API versions.
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionInfo {
+    /// Server release.
+    pub server: &'static str,
+    /// HTTP API version.
+    pub api: u32,
+}
+
+/// Optional cargo features of this build.
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildFeatures {
+    /// S3 storage backend.
+    pub s3: bool,
+    /// SMB storage backend.
+    pub smb: bool,
+    /// Outgoing email.
+    pub email: bool,
+    /// IP geolocation for login anomaly detection.
+    pub geoip: bool,
+    /// gRPC API.
+    pub grpc: bool,
+}
+
+/// Protocols clients can reach the server with.
+#[derive(Debug, Clone, Serialize)]
+pub struct Protocols {
+    /// gRPC alongside the HTTP API.
+    pub grpc: bool,
+    /// WebSocket realtime updates.
+    pub websocket: bool,
+    /// WebDAV; not supported by this server.
+    pub webdav: bool,
+}
+
+/// Storage backends and transfer limits.
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageCapabilities {
+    /// Provider types storages can be created with.
+    pub providers: Vec<&'static str>,
+    /// Largest accepted upload.
+    pub max_upload_size_bytes: u64,
+    /// Chunk size of chunked uploads.
+    pub chunk_size_bytes: u64,
+    /// Presigned uploads and downloads straight to the backend.
+    pub direct_transfer: bool,
+    /// Uploads are scanned for viruses.
+    pub antivirus: bool,
+    /// Page previews of documents.
+    pub document_preview: bool,
+}
+
+/// Sign-in options.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthCapabilities {
+    /// Logins from unusual locations are flagged.
+    pub login_anomaly_detection: bool,
+    /// Admins may act as other users.
+    pub impersonation: bool,
+    /// Second-factor sign-in; not supported by this server.
+    pub mfa: bool,
+    /// OpenID Connect sign-in; not supported by this server.
+    pub oidc: bool,
+    /// Content marked public can be read without signing in.
+    pub public_access: bool,
+}
+
+/// Realtime updates.
+#[derive(Debug, Clone, Serialize)]
+pub struct RealtimeCapabilities {
+    /// WebSocket endpoint available.
+    pub enabled: bool,
+    /// Who is online is shared.
+    pub presence: bool,
+    /// Connections a user may hold at once.
+    pub max_connections_per_user: usize,
+}
+
+/// CAD conversion.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversionCapabilities {
+    /// Conversions are accepted.
+    pub enabled: bool,
+    /// Extensions of convertible files; empty when disabled.
+    pub input_formats: Vec<&'static str>,
+    /// Format conversions produce; `None` when disabled.
+    pub output_format: Option<&'static str>,
+    /// Profiles a conversion may name.
+    pub profiles: Vec<String>,
+    /// Profile used when a conversion names none.
+    pub default_profile: Option<String>,
+}
+
+/// License enforcement.
+#[derive(Debug, Clone, Serialize)]
+pub struct LicenseCapabilities {
+    /// Sessions take license seats.
+    pub enabled: bool,
+}
+
+impl Capabilities {
+    /// The capabilities of this build running with `config`.
+    pub fn new(config: &AppConfig) -> Self {
+        let features = BuildFeatures {
+            s3: COMPILED_PROVIDERS.contains(&"s3"),
+            smb: COMPILED_PROVIDERS.contains(&"smb"),
+            email: cfg!(feature = "email"),
+            geoip: cfg!(feature = "geoip"),
+            grpc: cfg!(feature = "grpc"),
+        };
+        let storage = &config.storage;
+        let providers = COMPILED_PROVIDERS
+            .iter()
+            .copied()
+            .filter(|p| *p != "s3" || storage.s3.enabled)
+            .collect();
+        let conversions = &storage.conversions;
+
+        Self {
+            version: VersionInfo {
+                server: env!("CARGO_PKG_VERSION"),
+                api: API_VERSION,
+            },
+            protocols: Protocols {
+                grpc: features.grpc,
+                websocket: true,
+                webdav: false,
+            },
+            storage: StorageCapabilities {
+                providers,
+                max_upload_size_bytes: storage.max_upload_size_bytes,
+                chunk_size_bytes: storage.chunk_size_bytes,
+                direct_transfer: storage.direct_transfer.enabled,
+                antivirus: storage.antivirus.enabled,
+                document_preview: storage.document_preview.enabled,
+            },
+            auth: AuthCapabilities {
+                login_anomaly_detection: config.auth.login_anomaly.enabled && features.geoip,
+                impersonation: config.auth.impersonation.enabled,
+                mfa: false,
+                oidc: false,
+                public_access: config.shares.public_access.enabled,
+            },
+            realtime: RealtimeCapabilities {
+                enabled: true,
+                presence: true,
+                max_connections_per_user: config.realtime.max_connections_per_user,
+            },
+            conversion: if conversions.enabled {
+                ConversionCapabilities {
+                    enabled: true,
+                    input_formats: plugin_cad_converter::FileType::SUPPORTED_EXTENSIONS.to_vec(),
+                    output_format: Some(CONVERSION_OUTPUT_FORMAT),
+                    profiles: conversions.profiles.keys().cloned().collect(),
+                    default_profile: Some(conversions.default_profile.clone()),
+                }
+            } else {
+                ConversionCapabilities {
+                    enabled: false,
+                    input_formats: Vec::new(),
+                    output_format: None,
+                    profiles: Vec::new(),
+                    default_profile: None,
+                }
+            },
+            license: LicenseCapabilities {
+                enabled: config.license.enabled,
+            },
+            features,
+        }
+    }
+
+    /// Strong ETag of the report.
+    pub fn etag(&self) -> String {
+        let body = serde_json::to_vec(self).unwrap_or_default();
+        let digest = format!("{:x}", Sha256::digest(body));
+        format!("\"{}\"", &digest[..32])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_config() -> AppConfig {
+        config::Config::builder()
+            .add_source(config::File::from_str(
+                include_str!("../../../config/default.toml"),
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .and_then(|c| c.try_deserialize())
+            .expect("default.toml deserializes")
+    }
+
+    #[test]
+    fn test_reflects_config() {
+        let mut config = default_config();
+        config.storage.conversions.enabled = false;
+        let disabled = Capabilities::new(&config);
+        assert!(!disabled.conversion.enabled);
+        assert!(disabled.conversion.input_formats.is_empty());
+        assert!(disabled.storage.providers.contains(&"local"));
+
+        config.storage.conversions.enabled = true;
+        config.storage.max_upload_size_bytes = 1024;
+        let enabled = Capabilities::new(&config);
+        assert!(enabled.conversion.input_formats.contains(&"step"));
+        assert_eq!(enabled.conversion.output_format, Some("vtfx"));
+        assert_eq!(enabled.storage.max_upload_size_bytes, 1024);
+        assert_eq!(enabled.version.api, API_VERSION);
+    }
+
+    #[test]
+    fn test_etag_changes_with_report() {
+        let mut config = default_config();
+        let before = Capabilities::new(&config).etag();
+        assert_eq!(Capabilities::new(&config).etag(), before);
+
+        config.license.enabled = !config.license.enabled;
+        assert_ne!(Capabilities::new(&config).etag(), before);
+    }
+}
@@ -0,0 +1,124 @@
+//! Drain mode for rolling deploys.
+//!
+//! On a shutdown signal the instance keeps serving while it drains:
+//! readiness fails so the load balancer stops routing to it, new WebSocket
+//! upgrades are refused, connected clients are told to reconnect (which
+//! lands them on another node), and the worker stops dequeuing and lets
+//! in-flight jobs finish. The HTTP listener closes once everything has
+//! left or `server.shutdown_grace_seconds` has passed, whichever is first.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use filehub_realtime::message::OutboundMessage;
+
+use crate::state::AppState;
+
+/// Suggested delay before a drained WebSocket client reconnects.
+const RECONNECT_AFTER_MS: u64 = 1000;
+
+/// How often drain progress is logged.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Whether the instance is draining.
+#[derive(Debug, Default)]
+pub struct DrainState {
+    draining: AtomicBool,
+}
+
+impl DrainState {
+    /// Creates a state that is not draining.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether a drain has started.
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Relaxed)
+    }
+
+    /// Starts draining. Returns false if a drain had already started.
+    pub fn start(&self) -> bool {
+        !self.draining.swap(true, Ordering::Relaxed)
+    }
+}
+
+/// Drains the instance, returning once it is safe to stop.
+///
+/// `shutdown_tx` stops the worker from dequeuing; `worker` is its task,
+/// which finishes once its in-flight jobs are done or requeued.
+pub async fn drain(
+    state: &AppState,
+    shutdown_tx: &watch::Sender<bool>,
+    worker: Option<&JoinHandle<()>>,
+) {
+    if !state.drain.start() {
+        return;
+    }
+
+    let grace = Duration::from_secs(state.live_config.borrow().server.shutdown_grace_seconds);
+    let deadline = Instant::now() + grace;
+    tracing::info!(
+        grace_seconds = grace.as_secs(),
+        "Shutdown requested; draining"
+    );
+
+    let connections = &state.realtime.connections;
+    connections
+        .broadcast(OutboundMessage::ServerDraining {
+            reconnect_after_ms: RECONNECT_AFTER_MS,
+            deadline: chrono::Utc::now()
+                + chrono::Duration::from_std(grace).unwrap_or(chrono::Duration::zero()),
+        })
+        .await;
+    let _ = shutdown_tx.send(true);
+
+    let mut last_progress = Instant::now();
+    loop {
+        let ws = connections.total_connections();
+        let worker_running = worker.is_some_and(|h| !h.is_finished());
+        if ws == 0 && !worker_running {
+            tracing::info!("Drain complete; safe to stop");
+            return;
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            tracing::warn!(
+                websocket_connections = ws,
+                worker_running,
+                "Drain deadline reached; stopping anyway"
+            );
+            return;
+        }
+        if now.duration_since(last_progress) >= PROGRESS_INTERVAL {
+            tracing::info!(
+                websocket_connections = ws,
+                worker_running,
+                seconds_left = deadline.saturating_duration_since(now).as_secs(),
+                "Draining"
+            );
+            last_progress = now;
+        }
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drain_starts_once() {
+        let drain = DrainState::new();
+        assert!(!drain.is_draining());
+        assert!(drain.start());
+        assert!(drain.is_draining());
+        assert!(!drain.start());
+    }
+}
@@ -1,7 +1,10 @@
 //! Request DTOs with validation.
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+
+use filehub_core::types::filter::FilterNode;
 use validator::Validate;
 
 /// Login request body.
@@ -87,6 +90,23 @@ pub struct MoveFileRequest {
     pub target_folder_id: Uuid,
 }
 
+/// Set file expiry request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetFileExpiryRequest {
+    /// When the file is deleted; `None` clears the expiry.
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// CAD conversion request.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConvertFileRequest {
+    /// Conversion profile; the configured default if unset.
+    pub profile: Option<String>,
+    /// Output formats; the converter's own if empty.
+    #[serde(default)]
+    pub targets: Vec<String>,
+}
+
 /// Copy file request.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CopyFileRequest {
@@ -96,6 +116,16 @@ pub struct CopyFileRequest {
     pub new_name: Option<String>,
 }
 
+/// Bulk file operation request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkFileRequest {
+    /// Files to apply the action to.
+    pub file_ids: Vec<Uuid>,
+    /// Action and its parameters (`delete`, `move`, `copy`).
+    #[serde(flatten)]
+    pub action: filehub_service::file::service::BulkAction,
+}
+
 /// Initiate chunked upload request.
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct InitiateUploadRequest {
@@ -112,6 +142,20 @@ pub struct InitiateUploadRequest {
     pub checksum_sha256: Option<String>,
 }
 
+/// Presigned direct upload request.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct PresignUploadRequest {
+    /// Target folder ID.
+    pub folder_id: Uuid,
+    /// File name.
+    #[validate(length(min = 1, max = 255))]
+    pub file_name: String,
+    /// Exact file size in bytes.
+    pub file_size: i64,
+    /// MIME type.
+    pub mime_type: Option<String>,
+}
+
 /// Create share request.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateShareRequest {
@@ -140,6 +184,34 @@ fn default_true() -> bool {
     true
 }
 
+/// Request for more access to a file or folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestAccessRequest {
+    /// Resource type: `file` or `folder`.
+    pub resource_type: String,
+    /// Resource ID.
+    pub resource_id: Uuid,
+    /// Permission level asked for.
+    #[serde(default = "default_requested_permission")]
+    pub permission: String,
+    /// Note to the owner.
+    pub message: Option<String>,
+    /// Share link the resource was viewed through.
+    pub share_token: Option<String>,
+}
+
+fn default_requested_permission() -> String {
+    "editor".to_string()
+}
+
+/// Filter for the access requests made to the current user.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccessRequestQuery {
+    /// Only pending requests (default `true`).
+    #[serde(default = "default_true")]
+    pub pending: bool,
+}
+
 /// Update share request.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateShareRequest {
@@ -172,6 +244,24 @@ pub struct CreateAclEntryRequest {
     pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+/// Grant a permission over a folder subtree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecursiveGrantRequest {
+    /// User ID.
+    pub user_id: Option<Uuid>,
+    /// Public access.
+    #[serde(default)]
+    pub is_anyone: bool,
+    /// Permission.
+    pub permission: String,
+    /// Expiration.
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Write an entry on every folder and file instead of one inherited
+    /// entry on the root.
+    #[serde(default)]
+    pub explicit: bool,
+}
+
 fn default_inherit() -> String {
     "inherit".to_string()
 }
@@ -183,6 +273,16 @@ pub struct TerminateSessionRequest {
     pub reason: String,
 }
 
+/// Impersonate user request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpersonateUserRequest {
+    /// Reason.
+    pub reason: String,
+    /// Duration in minutes.
+    #[serde(default)]
+    pub duration_minutes: Option<u32>,
+}
+
 /// Bulk terminate request.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BulkTerminateRequest {
@@ -195,9 +295,12 @@ pub struct BulkTerminateRequest {
 /// Admin broadcast request.
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct BroadcastRequest {
-    /// Target ("all" or channel name).
+    /// Target: "all", "role:<role>" or "users".
     #[validate(length(min = 1))]
     pub target: String,
+    /// Recipients when `target` is "users".
+    #[serde(default)]
+    pub user_ids: Vec<Uuid>,
     /// Title.
     #[validate(length(min = 1, max = 255))]
     pub title: String,
@@ -210,12 +313,18 @@ pub struct BroadcastRequest {
     /// Persistent.
     #[serde(default)]
     pub persistent: bool,
+    /// Whether recipients must acknowledge it.
+    #[serde(default)]
+    pub require_ack: bool,
+    /// Send at this time instead of now.
+    pub scheduled_at: Option<DateTime<Utc>>,
 }
 
 /// Search files request.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchFilesRequest {
     /// Search query.
+    #[serde(default)]
     pub query: String,
     /// Folder filter.
     pub folder_id: Option<Uuid>,
@@ -229,6 +338,29 @@ pub struct SearchFilesRequest {
     pub min_size: Option<i64>,
     /// Max size.
     pub max_size: Option<i64>,
+    /// Structured filter tree (JSON body only).
+    #[serde(default)]
+    pub filter: Option<FilterNode>,
+}
+
+/// Save a named search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveSearchRequest {
+    /// Display name.
+    pub name: String,
+    /// Full-text query.
+    #[serde(default)]
+    pub query: String,
+    /// Structured filter tree.
+    #[serde(default)]
+    pub filter: Option<FilterNode>,
+}
+
+/// Create, rename or attach a tag.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagRequest {
+    /// Tag name.
+    pub name: String,
 }
 
 /// Share password verification.
@@ -238,6 +370,13 @@ pub struct ShareVerifyRequest {
     pub password: String,
 }
 
+/// Query of a public share download.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShareDownloadQuery {
+    /// Access grant from password verification, for protected links.
+    pub access_token: Option<String>,
+}
+
 /// Update notification preferences.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdatePreferencesRequest {
@@ -245,6 +384,18 @@ pub struct UpdatePreferencesRequest {
     pub preferences: serde_json::Value,
 }
 
+/// Set the delivery channels of one notification event type. Omitted
+/// channels follow the category setting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventTypePreferenceRequest {
+    /// Deliver via WebSocket.
+    pub realtime: Option<bool>,
+    /// Keep in the stored notification list.
+    pub stored: Option<bool>,
+    /// Deliver via email.
+    pub email: Option<bool>,
+}
+
 /// Update presence status.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdatePresenceRequest {
@@ -266,6 +417,14 @@ pub struct ChangeStatusRequest {
     pub status: String,
 }
 
+/// Quota tier assignment request; `null` puts the user back on the
+/// default tier.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetQuotaTierRequest {
+    /// Tier name.
+    pub tier: Option<String>,
+}
+
 /// Reset password request (admin).
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct ResetPasswordRequest {
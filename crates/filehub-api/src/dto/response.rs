@@ -4,6 +4,9 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use filehub_database::connection::PoolStats;
+use filehub_entity::audit::{AuditDiff, AuditLogEntry};
+
 /// Standard success response wrapper.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiResponse<T: Serialize> {
@@ -74,6 +77,29 @@ pub struct UserResponse {
     pub last_login_at: Option<DateTime<Utc>>,
 }
 
+/// Audit log entry with its field changes spelled out.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntryResponse {
+    /// The stored entry.
+    #[serde(flatten)]
+    pub entry: AuditLogEntry,
+    /// `field: old → new` per changed field, for update entries.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<String>,
+}
+
+impl From<AuditLogEntry> for AuditEntryResponse {
+    fn from(entry: AuditLogEntry) -> Self {
+        let changes = entry
+            .details
+            .as_ref()
+            .and_then(AuditDiff::from_details)
+            .map(|diff| diff.summary())
+            .unwrap_or_default();
+        Self { entry, changes }
+    }
+}
+
 /// Simple message response.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageResponse {
@@ -106,6 +132,8 @@ pub struct DetailedHealthResponse {
     pub status: String,
     /// Database status.
     pub database: String,
+    /// Database connection pool usage.
+    pub database_pool: PoolStats,
     /// Cache status.
     pub cache: String,
     /// Storage status.
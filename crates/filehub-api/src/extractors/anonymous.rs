@@ -0,0 +1,47 @@
+//! `AnonymousUser` extractor — context of a visitor reading public content
+//! without signing in.
+
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+
+use filehub_core::error::AppError;
+use filehub_core::types::RequestId;
+use filehub_service::context::RequestContext;
+
+use crate::state::AppState;
+
+/// Anonymous request context, available only while
+/// `shares.public_access.enabled` is set. No token is read and no session
+/// or seat is required.
+#[derive(Debug, Clone)]
+pub struct AnonymousUser(pub RequestContext);
+
+impl std::ops::Deref for AnonymousUser {
+    type Target = RequestContext;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromRequestParts<AppState> for AnonymousUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        if !state.config.shares.public_access.enabled {
+            return Err(AppError::not_found("Public access is disabled"));
+        }
+
+        let client = super::ClientInfo::from_request_parts(parts, state)
+            .await
+            .unwrap_or_else(|e| match e {});
+        let mut ctx = RequestContext::anonymous(client.ip.to_string(), client.user_agent);
+        if let Some(request_id) = parts.extensions.get::<RequestId>() {
+            ctx = ctx.with_request_id(request_id.clone());
+        }
+
+        Ok(AnonymousUser(ctx))
+    }
+}
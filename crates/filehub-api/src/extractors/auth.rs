@@ -1,11 +1,14 @@
-//! `AuthUser` extractor — pulls JWT from the Authorization header, validates, and injects context.
+//! `AuthUser` extractor — takes the decoded JWT claims, validates the session, and injects context.
 
 use axum::extract::FromRequestParts;
 use axum::http::request::Parts;
+use axum::http::{Extensions, HeaderMap};
 
 use filehub_core::error::AppError;
+use filehub_core::types::RequestId;
 use filehub_service::context::RequestContext;
 
+use crate::middleware::auth::BearerClaims;
 use crate::state::AppState;
 
 /// Extracted authenticated user context available in handlers.
@@ -26,26 +29,20 @@ impl std::ops::Deref for AuthUser {
     }
 }
 
-impl FromRequestParts<AppState> for AuthUser {
-    type Rejection = AppError;
-
-    async fn from_request_parts(
-        parts: &mut Parts,
+impl AuthUser {
+    /// Authenticates a request from its headers and extensions, for callers
+    /// outside Axum's extractor machinery such as the gRPC services.
+    pub async fn authenticate(
         state: &AppState,
-    ) -> Result<Self, Self::Rejection> {
-        // Extract Bearer token from Authorization header
-        let auth_header = parts
-            .headers
-            .get("authorization")
-            .and_then(|v| v.to_str().ok())
-            .ok_or_else(|| AppError::unauthorized("Missing Authorization header"))?;
-
-        let token = auth_header
-            .strip_prefix("Bearer ")
-            .ok_or_else(|| AppError::unauthorized("Invalid Authorization header format"))?;
-
-        // Decode and validate JWT
-        let claims = state.jwt_decoder.decode_access_token(token).await?;
+        headers: &HeaderMap,
+        extensions: &Extensions,
+    ) -> Result<Self, AppError> {
+        // Decoded once per request by `middleware::auth::bearer_claims`
+        let claims = extensions
+            .get::<BearerClaims>()
+            .cloned()
+            .ok_or_else(|| AppError::unauthorized("Missing Authorization header"))?
+            .into_result()?;
 
         // Validate session is still active
         let _session = state
@@ -54,28 +51,40 @@ impl FromRequestParts<AppState> for AuthUser {
             .await?;
 
         // Extract IP and User-Agent
-        let ip_address = parts
-            .headers
-            .get("x-forwarded-for")
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("unknown")
-            .to_string();
+        let ip_address = super::client::client_ip(extensions).to_string();
 
-        let user_agent = parts
-            .headers
+        let user_agent = headers
             .get("user-agent")
             .and_then(|v| v.to_str().ok())
             .map(String::from);
 
-        let ctx = RequestContext::new(
+        let mut ctx = RequestContext::new(
             claims.user_id(),
             claims.session_id(),
             claims.role,
             claims.username,
             ip_address,
             user_agent,
-        );
+        )
+        .with_tenant(claims.tid);
+        if let Some(admin_id) = claims.act {
+            ctx = ctx.with_impersonator(admin_id);
+        }
+        if let Some(request_id) = extensions.get::<RequestId>() {
+            ctx = ctx.with_request_id(request_id.clone());
+        }
 
         Ok(AuthUser(ctx))
     }
 }
+
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        Self::authenticate(state, &parts.headers, &parts.extensions).await
+    }
+}
@@ -0,0 +1,57 @@
+//! Client address and User-Agent of the request.
+
+use std::net::{IpAddr, SocketAddr};
+
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::request::Parts;
+use axum::http::{Extensions, HeaderMap, header};
+
+use crate::middleware::client_ip::ClientIp;
+
+/// Where a request came from.
+#[derive(Debug, Clone)]
+pub struct ClientInfo {
+    /// Client address, `0.0.0.0` if it cannot be determined.
+    pub ip: IpAddr,
+    /// `User-Agent` header, if sent.
+    pub user_agent: Option<String>,
+}
+
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_parts(&parts.headers, &parts.extensions))
+    }
+}
+
+impl ClientInfo {
+    /// Reads the client of a request from its headers and extensions.
+    pub fn from_parts(headers: &HeaderMap, extensions: &Extensions) -> Self {
+        Self {
+            ip: client_ip(extensions),
+            user_agent: headers
+                .get(header::USER_AGENT)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty()),
+        }
+    }
+}
+
+/// Client address as resolved by `middleware::client_ip`, falling back to
+/// the socket peer, or `0.0.0.0` outside of a connection.
+pub(crate) fn client_ip(extensions: &Extensions) -> IpAddr {
+    extensions
+        .get::<ClientIp>()
+        .map(|ClientIp(ip)| *ip)
+        .or_else(|| {
+            extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip())
+        })
+        .unwrap_or(IpAddr::from([0, 0, 0, 0]))
+}
@@ -0,0 +1,208 @@
+//! Conditional request headers (RFC 9110 §13) for cache validation.
+
+use axum::extract::FromRequestParts;
+use axum::http::header;
+use axum::http::request::Parts;
+use chrono::{DateTime, Utc};
+
+/// Outcome of evaluating request preconditions against a representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precondition {
+    /// No precondition blocks the request; serve the representation.
+    Proceed,
+    /// The client's cached copy is current; respond `304 Not Modified`.
+    NotModified,
+    /// A state-changing precondition failed; respond `412 Precondition Failed`.
+    Failed,
+}
+
+/// Validator headers sent by the client, parsed once per request.
+///
+/// Unparseable dates are ignored, as required by the RFC.
+#[derive(Debug, Clone, Default)]
+pub struct ConditionalHeaders {
+    /// `If-Match` entity tags.
+    pub if_match: Option<String>,
+    /// `If-None-Match` entity tags.
+    pub if_none_match: Option<String>,
+    /// `If-Modified-Since` timestamp.
+    pub if_modified_since: Option<DateTime<Utc>>,
+    /// `If-Unmodified-Since` timestamp.
+    pub if_unmodified_since: Option<DateTime<Utc>>,
+}
+
+impl<S> FromRequestParts<S> for ConditionalHeaders
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let text = |name: header::HeaderName| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.trim().to_string())
+        };
+
+        Ok(Self {
+            if_match: text(header::IF_MATCH),
+            if_none_match: text(header::IF_NONE_MATCH),
+            if_modified_since: text(header::IF_MODIFIED_SINCE).and_then(|v| parse_http_date(&v)),
+            if_unmodified_since: text(header::IF_UNMODIFIED_SINCE)
+                .and_then(|v| parse_http_date(&v)),
+        })
+    }
+}
+
+impl ConditionalHeaders {
+    /// Evaluates preconditions for a GET/HEAD request in RFC order.
+    ///
+    /// `If-None-Match` takes precedence over `If-Modified-Since`, and
+    /// `If-Match` over `If-Unmodified-Since`.
+    pub fn evaluate(&self, etag: &str, last_modified: DateTime<Utc>) -> Precondition {
+        let last_modified = truncate_to_seconds(last_modified);
+
+        if let Some(if_match) = &self.if_match {
+            if !etag_list_matches(if_match, etag, false) {
+                return Precondition::Failed;
+            }
+        } else if let Some(since) = self.if_unmodified_since
+            && last_modified > since
+        {
+            return Precondition::Failed;
+        }
+
+        if let Some(if_none_match) = &self.if_none_match {
+            if etag_list_matches(if_none_match, etag, true) {
+                return Precondition::NotModified;
+            }
+        } else if let Some(since) = self.if_modified_since
+            && last_modified <= since
+        {
+            return Precondition::NotModified;
+        }
+
+        Precondition::Proceed
+    }
+
+    /// Whether `If-None-Match` names `etag`, for representations that
+    /// have no modification time.
+    pub fn none_match(&self, etag: &str) -> bool {
+        self.if_none_match
+            .as_deref()
+            .is_some_and(|list| etag_list_matches(list, etag, true))
+    }
+}
+
+/// Formats a timestamp as an IMF-fixdate (`Sun, 06 Nov 1994 08:49:37 GMT`).
+pub fn http_date(dt: DateTime<Utc>) -> String {
+    dt.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Parses an HTTP date header value.
+pub fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Checks whether `etag` appears in a comma-separated entity-tag list.
+///
+/// Weak comparison ignores the `W/` prefix; strong comparison never
+/// matches a weak tag. `*` matches any current representation.
+fn etag_list_matches(list: &str, etag: &str, weak: bool) -> bool {
+    if list.trim() == "*" {
+        return true;
+    }
+
+    let opaque = |tag: &str| tag.strip_prefix("W/").unwrap_or(tag).to_string();
+    let target_is_weak = etag.starts_with("W/");
+
+    list.split(',').map(str::trim).any(|candidate| {
+        if weak {
+            opaque(candidate) == opaque(etag)
+        } else {
+            !target_is_weak && !candidate.starts_with("W/") && candidate == etag
+        }
+    })
+}
+
+/// HTTP dates have second precision, so sub-second parts must not make a
+/// resource look newer than the client's copy.
+fn truncate_to_seconds(dt: DateTime<Utc>) -> DateTime<Utc> {
+    DateTime::from_timestamp(dt.timestamp(), 0).unwrap_or(dt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).unwrap()
+    }
+
+    #[test]
+    fn test_http_date_roundtrip() {
+        let dt = at(784_111_777);
+        assert_eq!(http_date(dt), "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(parse_http_date(&http_date(dt)), Some(dt));
+        assert_eq!(parse_http_date("not a date"), None);
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let headers = ConditionalHeaders {
+            if_none_match: Some("\"a\", W/\"b\"".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(headers.evaluate("\"b\"", at(0)), Precondition::NotModified);
+        assert_eq!(headers.evaluate("\"c\"", at(0)), Precondition::Proceed);
+        assert!(headers.none_match("\"a\""));
+        assert!(!headers.none_match("\"c\""));
+        assert!(!ConditionalHeaders::default().none_match("\"a\""));
+    }
+
+    #[test]
+    fn test_if_none_match_takes_precedence_over_date() {
+        let headers = ConditionalHeaders {
+            if_none_match: Some("\"old\"".to_string()),
+            if_modified_since: Some(at(2_000)),
+            ..Default::default()
+        };
+        assert_eq!(
+            headers.evaluate("\"new\"", at(1_000)),
+            Precondition::Proceed
+        );
+    }
+
+    #[test]
+    fn test_if_modified_since() {
+        let headers = ConditionalHeaders {
+            if_modified_since: Some(at(1_000)),
+            ..Default::default()
+        };
+        let sub_second = at(1_000) + chrono::Duration::milliseconds(500);
+        assert_eq!(
+            headers.evaluate("\"x\"", sub_second),
+            Precondition::NotModified
+        );
+        assert_eq!(headers.evaluate("\"x\"", at(1_001)), Precondition::Proceed);
+    }
+
+    #[test]
+    fn test_if_match_uses_strong_comparison() {
+        let headers = ConditionalHeaders {
+            if_match: Some("W/\"a\"".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(headers.evaluate("\"a\"", at(0)), Precondition::Failed);
+
+        let headers = ConditionalHeaders {
+            if_match: Some("*".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(headers.evaluate("\"a\"", at(0)), Precondition::Proceed);
+    }
+}
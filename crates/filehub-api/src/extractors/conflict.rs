@@ -0,0 +1,78 @@
+//! Name collision strategy chosen by the request.
+
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+
+use filehub_core::config::NameCollision;
+use filehub_core::error::AppError;
+
+/// Header naming the strategy.
+pub const ON_CONFLICT_HEADER: &str = "x-on-conflict";
+
+/// Strategy from the `on_conflict` query parameter or, failing that, the
+/// `X-On-Conflict` header; `None` leaves the server default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OnConflict(pub Option<NameCollision>);
+
+impl<S> FromRequestParts<S> for OnConflict
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let from_query = parts.uri.query().and_then(|query| {
+            query.split('&').find_map(|pair| {
+                pair.strip_prefix("on_conflict=")
+                    .map(|value| value.to_string())
+            })
+        });
+        let from_header = || {
+            parts
+                .headers
+                .get(ON_CONFLICT_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+
+        from_query
+            .or_else(from_header)
+            .map(|value| value.parse().map_err(AppError::validation))
+            .transpose()
+            .map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    async fn extract(uri: &str, header: Option<&str>) -> Result<OnConflict, AppError> {
+        let mut request = Request::builder().uri(uri);
+        if let Some(value) = header {
+            request = request.header(ON_CONFLICT_HEADER, value);
+        }
+        let (mut parts, _) = request.body(()).unwrap().into_parts();
+        OnConflict::from_request_parts(&mut parts, &()).await
+    }
+
+    #[tokio::test]
+    async fn test_query_wins_over_header() {
+        let OnConflict(strategy) = extract("/upload?x=1&on_conflict=rename", Some("overwrite"))
+            .await
+            .unwrap();
+        assert_eq!(strategy, Some(NameCollision::Rename));
+
+        let OnConflict(strategy) = extract("/upload", Some("Overwrite")).await.unwrap();
+        assert_eq!(strategy, Some(NameCollision::Overwrite));
+
+        let OnConflict(strategy) = extract("/upload", None).await.unwrap();
+        assert_eq!(strategy, None);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_strategy_is_rejected() {
+        assert!(extract("/upload?on_conflict=merge", None).await.is_err());
+    }
+}
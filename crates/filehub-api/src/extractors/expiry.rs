@@ -0,0 +1,104 @@
+//! File expiry chosen by an upload request.
+
+use axum::extract::{FromRequestParts, Query};
+use axum::http::request::Parts;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use filehub_core::error::AppError;
+
+/// Header carrying the expiry.
+pub const EXPIRES_AT_HEADER: &str = "x-expires-at";
+
+#[derive(Debug, Deserialize)]
+struct ExpiryQuery {
+    expires_at: Option<String>,
+}
+
+/// Expiry from the `expires_at` query parameter or, failing that, the
+/// `X-Expires-At` header, as an RFC 3339 time in the future; `None` if
+/// neither is given.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExpiresAt(pub Option<DateTime<Utc>>);
+
+impl<S> FromRequestParts<S> for ExpiresAt
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let from_query = Query::<ExpiryQuery>::try_from_uri(&parts.uri)
+            .ok()
+            .and_then(|Query(query)| query.expires_at);
+        let from_header = || {
+            parts
+                .headers
+                .get(EXPIRES_AT_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+
+        from_query
+            .or_else(from_header)
+            .map(|value| {
+                DateTime::parse_from_rfc3339(value.trim())
+                    .map(|at| at.with_timezone(&Utc))
+                    .map_err(|e| AppError::validation(format!("Invalid expires_at '{value}': {e}")))
+            })
+            .transpose()?
+            .map(|at| {
+                if at <= Utc::now() {
+                    Err(AppError::validation("Expiry must be in the future"))
+                } else {
+                    Ok(at)
+                }
+            })
+            .transpose()
+            .map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    async fn extract(uri: &str, header: Option<&str>) -> Result<ExpiresAt, AppError> {
+        let mut request = Request::builder().uri(uri);
+        if let Some(value) = header {
+            request = request.header(EXPIRES_AT_HEADER, value);
+        }
+        let (mut parts, _) = request.body(()).unwrap().into_parts();
+        ExpiresAt::from_request_parts(&mut parts, &()).await
+    }
+
+    #[tokio::test]
+    async fn test_query_wins_over_header() {
+        let ExpiresAt(at) = extract(
+            "/upload?expires_at=2030-01-02T03%3A04%3A05%2B01%3A00",
+            Some("2031-01-01T00:00:00Z"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(at.unwrap().to_rfc3339(), "2030-01-02T02:04:05+00:00");
+
+        let ExpiresAt(at) = extract("/upload", Some("2031-01-01T00:00:00Z"))
+            .await
+            .unwrap();
+        assert_eq!(at.unwrap().to_rfc3339(), "2031-01-01T00:00:00+00:00");
+
+        let ExpiresAt(at) = extract("/upload?on_conflict=rename", None).await.unwrap();
+        assert_eq!(at, None);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_time_is_rejected() {
+        assert!(extract("/upload?expires_at=tomorrow", None).await.is_err());
+        assert!(
+            extract("/upload", Some("2001-01-01T00:00:00Z"))
+                .await
+                .is_err()
+        );
+    }
+}
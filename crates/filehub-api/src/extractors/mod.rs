@@ -1,8 +1,22 @@
 //! Custom Axum extractors.
 
+pub mod anonymous;
 pub mod auth;
+pub mod client;
+pub mod conditional;
+pub mod conflict;
+pub mod expiry;
 pub mod pagination;
 pub mod path;
+pub mod range;
+pub mod upload_form;
 
+pub use anonymous::AnonymousUser;
 pub use auth::AuthUser;
-pub use pagination::PaginationParams;
+pub use client::ClientInfo;
+pub use conditional::ConditionalHeaders;
+pub use conflict::OnConflict;
+pub use expiry::ExpiresAt;
+pub use pagination::{PaginationParams, SortParams};
+pub use range::RangeHeaders;
+pub use upload_form::UploadForm;
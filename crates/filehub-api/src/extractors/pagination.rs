@@ -2,7 +2,25 @@
 
 use serde::{Deserialize, Serialize};
 
+use filehub_core::error::AppError;
 use filehub_core::types::pagination::PageRequest;
+use filehub_entity::folder::FolderSort;
+
+/// Sort query parameters of folder listings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SortParams {
+    /// Sort field: name, size, created_at or updated_at.
+    pub sort_by: Option<String>,
+    /// Sort direction: "asc" or "desc".
+    pub sort_dir: Option<String>,
+}
+
+impl SortParams {
+    /// The order asked for, or `None` to use the caller's saved one.
+    pub fn folder_sort(&self) -> Result<Option<FolderSort>, AppError> {
+        FolderSort::from_params(self.sort_by.as_deref(), self.sort_dir.as_deref())
+    }
+}
 
 /// Query parameters for paginated endpoints.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +35,9 @@ pub struct PaginationParams {
     pub sort_by: Option<String>,
     /// Sort direction: "asc" or "desc".
     pub sort_dir: Option<String>,
+    /// Keyset cursor; presence (even empty) selects cursor paging on
+    /// listings that support it.
+    pub cursor: Option<String>,
 }
 
 fn default_page() -> u64 {
@@ -28,6 +49,12 @@ fn default_per_page() -> u64 {
 }
 
 impl PaginationParams {
+    /// The folder listing order asked for, or `None` to use the caller's
+    /// saved one.
+    pub fn folder_sort(&self) -> Result<Option<FolderSort>, AppError> {
+        FolderSort::from_params(self.sort_by.as_deref(), self.sort_dir.as_deref())
+    }
+
     /// Converts to a `PageRequest`.
     pub fn into_page_request(self) -> PageRequest {
         let per_page = self.per_page.min(100).max(1);
@@ -36,6 +63,7 @@ impl PaginationParams {
         PageRequest {
             page,
             page_size: per_page,
+            cursor: self.cursor,
         }
     }
 }
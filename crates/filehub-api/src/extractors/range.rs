@@ -0,0 +1,236 @@
+//! `Range` / `If-Range` request headers (RFC 9110 §14) for partial downloads.
+
+use axum::extract::FromRequestParts;
+use axum::http::header;
+use axum::http::request::Parts;
+use chrono::{DateTime, Utc};
+
+use super::conditional::parse_http_date;
+
+/// An inclusive byte range within a representation of known size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    /// First byte offset.
+    pub start: u64,
+    /// Last byte offset (inclusive).
+    pub end: u64,
+}
+
+impl ByteRange {
+    /// Number of bytes covered by the range.
+    pub fn length(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    /// Formats the `Content-Range` header value for this range.
+    pub fn content_range(&self, size: u64) -> String {
+        format!("bytes {}-{}/{}", self.start, self.end, size)
+    }
+}
+
+/// How a request's `Range` header applies to a representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeOutcome {
+    /// Serve the full representation with `200 OK`.
+    Full,
+    /// Serve a single part with `206 Partial Content`.
+    Partial(ByteRange),
+    /// No requested range overlaps the representation; respond `416`.
+    Unsatisfiable,
+}
+
+/// Range headers sent by the client.
+#[derive(Debug, Clone, Default)]
+pub struct RangeHeaders {
+    /// Raw `Range` header value.
+    pub range: Option<String>,
+    /// Raw `If-Range` header value (entity tag or HTTP date).
+    pub if_range: Option<String>,
+}
+
+impl<S> FromRequestParts<S> for RangeHeaders
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let text = |name: header::HeaderName| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.trim().to_string())
+        };
+
+        Ok(Self {
+            range: text(header::RANGE),
+            if_range: text(header::IF_RANGE),
+        })
+    }
+}
+
+impl RangeHeaders {
+    /// Resolves the requested range against a representation.
+    ///
+    /// The `Range` header is ignored (full response) when it is malformed,
+    /// uses a unit other than `bytes`, or when `If-Range` does not match the
+    /// current validator. Multiple ranges are coalesced into a single span
+    /// covering all of them, which RFC 9110 permits in place of a
+    /// `multipart/byteranges` response.
+    pub fn resolve(&self, size: u64, etag: &str, last_modified: DateTime<Utc>) -> RangeOutcome {
+        let Some(range) = &self.range else {
+            return RangeOutcome::Full;
+        };
+
+        if let Some(if_range) = &self.if_range
+            && !if_range_matches(if_range, etag, last_modified)
+        {
+            return RangeOutcome::Full;
+        }
+
+        let Some(specs) = range.strip_prefix("bytes=") else {
+            return RangeOutcome::Full;
+        };
+
+        let mut merged: Option<ByteRange> = None;
+        for spec in specs.split(',') {
+            let parsed = match parse_range_spec(spec.trim(), size) {
+                Ok(parsed) => parsed,
+                Err(()) => return RangeOutcome::Full,
+            };
+            if let Some(r) = parsed {
+                merged = Some(match merged {
+                    Some(m) => ByteRange {
+                        start: m.start.min(r.start),
+                        end: m.end.max(r.end),
+                    },
+                    None => r,
+                });
+            }
+        }
+
+        match merged {
+            Some(r) => RangeOutcome::Partial(r),
+            None => RangeOutcome::Unsatisfiable,
+        }
+    }
+}
+
+/// Parses one `first-last`, `first-`, or `-suffix` spec.
+///
+/// Returns `Err` for syntax errors, `Ok(None)` for a well-formed spec that
+/// does not overlap the representation.
+fn parse_range_spec(spec: &str, size: u64) -> Result<Option<ByteRange>, ()> {
+    let (first, last) = spec.split_once('-').ok_or(())?;
+    let first = first.trim();
+    let last = last.trim();
+
+    if first.is_empty() {
+        let suffix: u64 = last.parse().map_err(|_| ())?;
+        if suffix == 0 || size == 0 {
+            return Ok(None);
+        }
+        return Ok(Some(ByteRange {
+            start: size.saturating_sub(suffix),
+            end: size - 1,
+        }));
+    }
+
+    let start: u64 = first.parse().map_err(|_| ())?;
+    let end = if last.is_empty() {
+        None
+    } else {
+        Some(last.parse::<u64>().map_err(|_| ())?)
+    };
+
+    if let Some(end) = end
+        && end < start
+    {
+        return Err(());
+    }
+
+    if start >= size {
+        return Ok(None);
+    }
+
+    Ok(Some(ByteRange {
+        start,
+        end: end.map_or(size - 1, |e| e.min(size - 1)),
+    }))
+}
+
+/// `If-Range` uses strong comparison for entity tags and exact match for dates.
+fn if_range_matches(if_range: &str, etag: &str, last_modified: DateTime<Utc>) -> bool {
+    if if_range.starts_with('"') {
+        return if_range == etag;
+    }
+    if if_range.starts_with("W/") {
+        return false;
+    }
+    parse_http_date(if_range).is_some_and(|date| date.timestamp() == last_modified.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(range: &str) -> RangeHeaders {
+        RangeHeaders {
+            range: Some(range.to_string()),
+            if_range: None,
+        }
+    }
+
+    fn resolve(range: &str, size: u64) -> RangeOutcome {
+        headers(range).resolve(size, "\"e\"", DateTime::from_timestamp(0, 0).unwrap())
+    }
+
+    #[test]
+    fn test_single_ranges() {
+        let partial = |start, end| RangeOutcome::Partial(ByteRange { start, end });
+        assert_eq!(resolve("bytes=0-499", 1000), partial(0, 499));
+        assert_eq!(resolve("bytes=500-", 1000), partial(500, 999));
+        assert_eq!(resolve("bytes=-200", 1000), partial(800, 999));
+        assert_eq!(resolve("bytes=900-5000", 1000), partial(900, 999));
+        assert_eq!(resolve("bytes=-5000", 1000), partial(0, 999));
+    }
+
+    #[test]
+    fn test_unsatisfiable_and_ignored() {
+        assert_eq!(resolve("bytes=1000-", 1000), RangeOutcome::Unsatisfiable);
+        assert_eq!(resolve("bytes=-0", 1000), RangeOutcome::Unsatisfiable);
+        assert_eq!(resolve("bytes=5-1", 1000), RangeOutcome::Full);
+        assert_eq!(resolve("items=0-1", 1000), RangeOutcome::Full);
+        assert_eq!(resolve("bytes=abc", 1000), RangeOutcome::Full);
+    }
+
+    #[test]
+    fn test_multi_range_coalesces() {
+        assert_eq!(
+            resolve("bytes=0-9, 50-59, 2000-", 1000),
+            RangeOutcome::Partial(ByteRange { start: 0, end: 59 })
+        );
+    }
+
+    #[test]
+    fn test_if_range() {
+        let modified = DateTime::from_timestamp(784_111_777, 0).unwrap();
+        let mut h = headers("bytes=0-9");
+
+        h.if_range = Some("\"e\"".to_string());
+        assert!(matches!(
+            h.resolve(100, "\"e\"", modified),
+            RangeOutcome::Partial(_)
+        ));
+        assert_eq!(h.resolve(100, "\"other\"", modified), RangeOutcome::Full);
+
+        h.if_range = Some("Sun, 06 Nov 1994 08:49:37 GMT".to_string());
+        assert!(matches!(
+            h.resolve(100, "\"e\"", modified),
+            RangeOutcome::Partial(_)
+        ));
+        let later = DateTime::from_timestamp(784_111_778, 0).unwrap();
+        assert_eq!(h.resolve(100, "\"e\"", later), RangeOutcome::Full);
+    }
+}
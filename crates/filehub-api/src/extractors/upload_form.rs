@@ -0,0 +1,360 @@
+//! `multipart/form-data` upload form.
+//!
+//! The file part is streamed as it arrives: the caller's upload policy is
+//! enforced while reading, so an oversized or disallowed file is refused
+//! without being received in full. Parts up to one upload chunk stay in
+//! memory; larger parts are spooled to a temporary file and later sent
+//! through a chunked upload session.
+
+use std::path::{Path, PathBuf};
+
+use axum::extract::{FromRequest, FromRequestParts, Multipart, Request};
+use bytes::{Bytes, BytesMut};
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+use filehub_core::error::AppError;
+use filehub_entity::tag::model::Tag;
+use filehub_service::file::upload::FormContent;
+use filehub_storage::upload_policy::UploadRules;
+
+use crate::extractors::AuthUser;
+use crate::state::AppState;
+
+/// Largest accepted text field, in bytes.
+const MAX_TEXT_FIELD_BYTES: usize = 64 * 1024;
+
+/// Most tags one upload may add.
+const MAX_TAGS: usize = 32;
+
+/// Bytes read before the content is checked against the upload policy.
+const SNIFF_BYTES: usize = 512;
+
+/// An upload form: exactly one file part plus the sibling fields
+/// `folder_id` (required), `description` and `tags` (repeated, or
+/// comma-separated). Other text fields are ignored. The fields may come
+/// before or after the file part.
+#[derive(Debug)]
+pub struct UploadForm {
+    /// The authenticated uploader, whose policy the file was checked against.
+    pub auth: AuthUser,
+    /// Target folder.
+    pub folder_id: Uuid,
+    /// Description for the file's metadata.
+    pub description: Option<String>,
+    /// Tags to add to the file.
+    pub tags: Vec<String>,
+    /// The file part.
+    pub file: FilePart,
+}
+
+/// The file part of an upload form.
+#[derive(Debug)]
+pub struct FilePart {
+    /// File name from the part's `Content-Disposition`.
+    pub file_name: String,
+    /// The part's `Content-Type`.
+    pub content_type: Option<String>,
+    /// Size in bytes.
+    pub size_bytes: u64,
+    /// Where the part was received to.
+    pub content: PartContent,
+}
+
+/// Received content of a file part.
+#[derive(Debug)]
+pub enum PartContent {
+    /// Held in memory.
+    Memory(Bytes),
+    /// Spooled to a temporary file.
+    Spooled(SpooledFile),
+}
+
+impl FilePart {
+    /// The content for the upload service. A spooled file must outlive
+    /// the upload, so it stays with the part.
+    pub fn content(&self) -> FormContent {
+        match &self.content {
+            PartContent::Memory(data) => FormContent::Bytes(data.clone()),
+            PartContent::Spooled(spooled) => FormContent::Spooled {
+                path: spooled.path().to_path_buf(),
+                size_bytes: self.size_bytes,
+            },
+        }
+    }
+}
+
+/// A temporary file removed when dropped.
+#[derive(Debug)]
+pub struct SpooledFile {
+    path: PathBuf,
+}
+
+impl SpooledFile {
+    /// Path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for SpooledFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+impl FromRequest<AppState> for UploadForm {
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &AppState) -> Result<Self, Self::Rejection> {
+        let (mut parts, body) = req.into_parts();
+        let auth = AuthUser::from_request_parts(&mut parts, state).await?;
+        let mut multipart = Multipart::from_request(Request::from_parts(parts, body), state)
+            .await
+            .map_err(|e| {
+                AppError::validation(format!("Expected a multipart/form-data body: {e}"))
+            })?;
+
+        let rules = state.upload_service.upload_rules(&auth);
+        let memory_limit = state
+            .config
+            .storage
+            .chunk_size_bytes
+            .max(SNIFF_BYTES as u64);
+
+        let mut folder_id = None;
+        let mut description = None;
+        let mut tags = Vec::new();
+        let mut file = None;
+
+        while let Some(mut field) = multipart.next_field().await.map_err(malformed)? {
+            // Browsers send an empty file input as a part with an empty file name
+            if let Some(file_name) = field.file_name().filter(|n| !n.is_empty()) {
+                if file.is_some() {
+                    return Err(AppError::validation(
+                        "Exactly one file part is allowed per upload",
+                    ));
+                }
+                let file_name = file_name.to_string();
+                let content_type = field.content_type().map(str::to_string);
+                rules.check_declared(&file_name, content_type.as_deref(), 0)?;
+
+                let mut receiver = PartReceiver::new(&rules, &file_name, content_type.as_deref());
+                while let Some(chunk) = field.chunk().await.map_err(malformed)? {
+                    receiver.push(chunk, memory_limit).await?;
+                }
+                let (size_bytes, content) = receiver.finish().await?;
+                file = Some(FilePart {
+                    file_name,
+                    content_type,
+                    size_bytes,
+                    content,
+                });
+                continue;
+            }
+
+            let name = field.name().unwrap_or_default().to_string();
+            if !matches!(name.as_str(), "folder_id" | "description" | "tags") {
+                continue;
+            }
+            let value = read_text(&mut field, &name).await?;
+            match name.as_str() {
+                "folder_id" => {
+                    folder_id = Some(
+                        Uuid::parse_str(value.trim())
+                            .map_err(|_| AppError::validation("Invalid folder_id"))?,
+                    );
+                }
+                "description" => {
+                    description = Some(value.trim().to_string()).filter(|d| !d.is_empty());
+                }
+                _ => tags.extend(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|t| !t.is_empty())
+                        .map(str::to_string),
+                ),
+            }
+        }
+
+        let folder_id = folder_id.ok_or_else(|| AppError::validation("folder_id is required"))?;
+        let file = file.ok_or_else(|| AppError::validation("The form has no file part"))?;
+        if tags.len() > MAX_TAGS {
+            return Err(AppError::validation(format!(
+                "At most {MAX_TAGS} tags may be added per upload"
+            )));
+        }
+        if let Some(tag) = tags.iter().find(|t| Tag::normalize(t).is_none()) {
+            return Err(AppError::validation(format!("Invalid tag '{tag}'")));
+        }
+        tags.sort();
+        tags.dedup();
+
+        Ok(Self {
+            auth,
+            folder_id,
+            description,
+            tags,
+            file,
+        })
+    }
+}
+
+/// Receives a file part, enforcing the upload policy as bytes arrive.
+struct PartReceiver<'a> {
+    rules: &'a UploadRules,
+    file_name: &'a str,
+    content_type: Option<&'a str>,
+    size_bytes: u64,
+    buffer: BytesMut,
+    content_checked: bool,
+    spool: Option<(SpooledFile, tokio::fs::File)>,
+}
+
+impl<'a> PartReceiver<'a> {
+    fn new(rules: &'a UploadRules, file_name: &'a str, content_type: Option<&'a str>) -> Self {
+        Self {
+            rules,
+            file_name,
+            content_type,
+            size_bytes: 0,
+            buffer: BytesMut::new(),
+            content_checked: false,
+            spool: None,
+        }
+    }
+
+    /// Takes the next chunk, spooling once more than `memory_limit`
+    /// bytes have arrived.
+    async fn push(&mut self, chunk: Bytes, memory_limit: u64) -> Result<(), AppError> {
+        self.size_bytes += chunk.len() as u64;
+        self.rules
+            .check_declared(self.file_name, self.content_type, self.size_bytes)?;
+
+        if let Some((_, file)) = &mut self.spool {
+            return file.write_all(&chunk).await.map_err(spool_error);
+        }
+
+        self.buffer.extend_from_slice(&chunk);
+        if !self.content_checked && self.buffer.len() >= SNIFF_BYTES {
+            self.rules.check_content(&self.buffer)?;
+            self.content_checked = true;
+        }
+        if self.size_bytes > memory_limit {
+            let path = std::env::temp_dir().join(format!("filehub-upload-{}", Uuid::new_v4()));
+            let spooled = SpooledFile { path };
+            let mut file = tokio::fs::File::create(spooled.path())
+                .await
+                .map_err(spool_error)?;
+            file.write_all(&self.buffer).await.map_err(spool_error)?;
+            self.buffer = BytesMut::new();
+            self.spool = Some((spooled, file));
+        }
+        Ok(())
+    }
+
+    /// The size and content of the complete part.
+    async fn finish(self) -> Result<(u64, PartContent), AppError> {
+        match self.spool {
+            Some((spooled, mut file)) => {
+                file.flush().await.map_err(spool_error)?;
+                Ok((self.size_bytes, PartContent::Spooled(spooled)))
+            }
+            None => {
+                if !self.content_checked {
+                    self.rules.check_content(&self.buffer)?;
+                }
+                Ok((self.size_bytes, PartContent::Memory(self.buffer.freeze())))
+            }
+        }
+    }
+}
+
+/// Reads a text field, refusing oversized values.
+async fn read_text(
+    field: &mut axum::extract::multipart::Field<'_>,
+    name: &str,
+) -> Result<String, AppError> {
+    let mut value = Vec::new();
+    while let Some(chunk) = field.chunk().await.map_err(malformed)? {
+        if value.len() + chunk.len() > MAX_TEXT_FIELD_BYTES {
+            return Err(AppError::validation(format!(
+                "Field '{name}' exceeds {MAX_TEXT_FIELD_BYTES} bytes"
+            )));
+        }
+        value.extend_from_slice(&chunk);
+    }
+    String::from_utf8(value)
+        .map_err(|_| AppError::validation(format!("Field '{name}' is not valid UTF-8")))
+}
+
+fn malformed(e: axum::extract::multipart::MultipartError) -> AppError {
+    AppError::validation(format!("Malformed multipart body: {e}"))
+}
+
+fn spool_error(e: std::io::Error) -> AppError {
+    AppError::internal(format!("Failed to spool upload: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use filehub_core::config::StorageConfig;
+
+    fn rules(max_bytes: u64) -> UploadRules {
+        let config: StorageConfig = serde_json::from_value(serde_json::json!({
+            "max_upload_size_bytes": max_bytes
+        }))
+        .unwrap();
+        UploadRules::for_role(&config, "creator")
+    }
+
+    #[tokio::test]
+    async fn test_small_part_stays_in_memory() {
+        let rules = rules(1024);
+        let mut receiver = PartReceiver::new(&rules, "a.txt", Some("text/plain"));
+        receiver
+            .push(Bytes::from_static(b"hello "), 64)
+            .await
+            .unwrap();
+        receiver
+            .push(Bytes::from_static(b"world"), 64)
+            .await
+            .unwrap();
+        let (size, content) = receiver.finish().await.unwrap();
+        assert_eq!(size, 11);
+        assert!(matches!(content, PartContent::Memory(data) if data == "hello world"));
+    }
+
+    #[tokio::test]
+    async fn test_large_part_is_spooled_and_removed_on_drop() {
+        let rules = rules(1024);
+        let mut receiver = PartReceiver::new(&rules, "a.txt", None);
+        receiver
+            .push(Bytes::from(vec![b'a'; 40]), 64)
+            .await
+            .unwrap();
+        receiver
+            .push(Bytes::from(vec![b'b'; 40]), 64)
+            .await
+            .unwrap();
+        let (size, content) = receiver.finish().await.unwrap();
+        assert_eq!(size, 80);
+        let PartContent::Spooled(spooled) = content else {
+            panic!("expected a spooled part");
+        };
+        let path = spooled.path().to_path_buf();
+        assert_eq!(std::fs::read(&path).unwrap().len(), 80);
+        drop(spooled);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_size_limit_is_enforced_while_streaming() {
+        let rules = rules(100);
+        let mut receiver = PartReceiver::new(&rules, "a.txt", None);
+        receiver.push(Bytes::from(vec![0; 60]), 1024).await.unwrap();
+        assert!(receiver.push(Bytes::from(vec![0; 60]), 1024).await.is_err());
+    }
+}
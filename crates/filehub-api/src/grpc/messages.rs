@@ -0,0 +1,304 @@
+//! Messages of `proto/filehub/v1/filehub.proto`, derived with prost.
+//!
+//! Field numbers here must match the proto file.
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use filehub_core::error::AppError;
+use filehub_entity::file::File;
+
+/// `google.protobuf.Timestamp`.
+#[derive(Clone, Copy, PartialEq, Eq, prost::Message)]
+pub struct Timestamp {
+    /// Seconds since the Unix epoch.
+    #[prost(int64, tag = "1")]
+    pub seconds: i64,
+    /// Sub-second nanoseconds.
+    #[prost(int32, tag = "2")]
+    pub nanos: i32,
+}
+
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(at: DateTime<Utc>) -> Self {
+        Self {
+            seconds: at.timestamp(),
+            nanos: at.timestamp_subsec_nanos() as i32,
+        }
+    }
+}
+
+/// `filehub.v1.LoginRequest`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct LoginRequest {
+    /// Username.
+    #[prost(string, tag = "1")]
+    pub username: String,
+    /// Password.
+    #[prost(string, tag = "2")]
+    pub password: String,
+}
+
+/// `filehub.v1.LoginResponse`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct LoginResponse {
+    /// Access token.
+    #[prost(string, tag = "1")]
+    pub access_token: String,
+    /// Refresh token.
+    #[prost(string, tag = "2")]
+    pub refresh_token: String,
+    /// Access token expiry.
+    #[prost(message, optional, tag = "3")]
+    pub access_expires_at: Option<Timestamp>,
+    /// Refresh token expiry.
+    #[prost(message, optional, tag = "4")]
+    pub refresh_expires_at: Option<Timestamp>,
+    /// Signed-in user.
+    #[prost(string, tag = "5")]
+    pub user_id: String,
+    /// Role of the user.
+    #[prost(string, tag = "6")]
+    pub role: String,
+}
+
+/// `filehub.v1.FileInfo`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct FileInfo {
+    /// File ID.
+    #[prost(string, tag = "1")]
+    pub id: String,
+    /// Containing folder.
+    #[prost(string, tag = "2")]
+    pub folder_id: String,
+    /// File name.
+    #[prost(string, tag = "3")]
+    pub name: String,
+    /// MIME type, empty if unknown.
+    #[prost(string, tag = "4")]
+    pub mime_type: String,
+    /// Size in bytes.
+    #[prost(int64, tag = "5")]
+    pub size_bytes: i64,
+    /// SHA-256 of the content, empty if unknown.
+    #[prost(string, tag = "6")]
+    pub checksum_sha256: String,
+    /// Current version number.
+    #[prost(int32, tag = "7")]
+    pub current_version: i32,
+    /// Owner.
+    #[prost(string, tag = "8")]
+    pub owner_id: String,
+    /// Whether the file is locked for editing.
+    #[prost(bool, tag = "9")]
+    pub locked: bool,
+    /// Creation time.
+    #[prost(message, optional, tag = "10")]
+    pub created_at: Option<Timestamp>,
+    /// Last update time.
+    #[prost(message, optional, tag = "11")]
+    pub updated_at: Option<Timestamp>,
+}
+
+impl From<&File> for FileInfo {
+    fn from(file: &File) -> Self {
+        Self {
+            id: file.id.to_string(),
+            folder_id: file.folder_id.to_string(),
+            name: file.name.clone(),
+            mime_type: file.mime_type.clone().unwrap_or_default(),
+            size_bytes: file.size_bytes,
+            checksum_sha256: file.checksum_sha256.clone().unwrap_or_default(),
+            current_version: file.current_version,
+            owner_id: file.owner_id.to_string(),
+            locked: file.is_file_locked(),
+            created_at: Some(file.created_at.into()),
+            updated_at: Some(file.updated_at.into()),
+        }
+    }
+}
+
+/// `filehub.v1.GetFileRequest`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetFileRequest {
+    /// File ID.
+    #[prost(string, tag = "1")]
+    pub file_id: String,
+}
+
+/// `filehub.v1.ListFilesRequest`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListFilesRequest {
+    /// Folder to list.
+    #[prost(string, tag = "1")]
+    pub folder_id: String,
+    /// 1-based page number.
+    #[prost(uint64, tag = "2")]
+    pub page: u64,
+    /// Items per page.
+    #[prost(uint64, tag = "3")]
+    pub page_size: u64,
+    /// Keyset cursor; `-` requests the first keyset page.
+    #[prost(string, tag = "4")]
+    pub cursor: String,
+}
+
+/// `filehub.v1.ListFilesResponse`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListFilesResponse {
+    /// Files on this page.
+    #[prost(message, repeated, tag = "1")]
+    pub files: Vec<FileInfo>,
+    /// Page number (0 for keyset pages).
+    #[prost(uint64, tag = "2")]
+    pub page: u64,
+    /// Files in the folder (0 for keyset pages).
+    #[prost(uint64, tag = "3")]
+    pub total_items: u64,
+    /// Number of pages (0 for keyset pages).
+    #[prost(uint64, tag = "4")]
+    pub total_pages: u64,
+    /// Cursor of the next keyset page, empty on the last one.
+    #[prost(string, tag = "5")]
+    pub next_cursor: String,
+}
+
+/// `filehub.v1.UpdateFileRequest`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct UpdateFileRequest {
+    /// File ID.
+    #[prost(string, tag = "1")]
+    pub file_id: String,
+    /// New name.
+    #[prost(string, tag = "2")]
+    pub name: String,
+}
+
+/// `filehub.v1.DeleteFileRequest`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DeleteFileRequest {
+    /// File ID.
+    #[prost(string, tag = "1")]
+    pub file_id: String,
+}
+
+/// `filehub.v1.DeleteFileResponse`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DeleteFileResponse {}
+
+/// `filehub.v1.UploadMetadata`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct UploadMetadata {
+    /// Target folder.
+    #[prost(string, tag = "1")]
+    pub folder_id: String,
+    /// File name.
+    #[prost(string, tag = "2")]
+    pub file_name: String,
+    /// Total size in bytes.
+    #[prost(int64, tag = "3")]
+    pub file_size: i64,
+    /// MIME type, empty to detect.
+    #[prost(string, tag = "4")]
+    pub mime_type: String,
+    /// Expected SHA-256 of the content, empty to skip the check.
+    #[prost(string, tag = "5")]
+    pub checksum_sha256: String,
+}
+
+/// `filehub.v1.UploadRequest`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct UploadRequest {
+    /// Metadata in the first message, content in the rest.
+    #[prost(oneof = "upload_request::Payload", tags = "1, 2")]
+    pub payload: Option<upload_request::Payload>,
+}
+
+/// Nested types of [`UploadRequest`].
+pub mod upload_request {
+    /// `filehub.v1.UploadRequest.payload`.
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum Payload {
+        /// The first message of an upload.
+        #[prost(message, tag = "1")]
+        Metadata(super::UploadMetadata),
+        /// File content.
+        #[prost(bytes = "bytes", tag = "2")]
+        Data(bytes::Bytes),
+    }
+}
+
+/// `filehub.v1.DownloadRequest`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DownloadRequest {
+    /// File ID.
+    #[prost(string, tag = "1")]
+    pub file_id: String,
+    /// First byte to send.
+    #[prost(uint64, tag = "2")]
+    pub offset: u64,
+    /// Bytes to send; 0 sends the rest of the file.
+    #[prost(uint64, tag = "3")]
+    pub length: u64,
+}
+
+/// `filehub.v1.DownloadResponse`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DownloadResponse {
+    /// File details, in the first message only.
+    #[prost(message, optional, tag = "1")]
+    pub file: Option<FileInfo>,
+    /// File content.
+    #[prost(bytes = "bytes", tag = "2")]
+    pub data: Bytes,
+}
+
+/// Parses an ID field.
+pub fn parse_id(field: &str, value: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(value).map_err(|_| AppError::validation(format!("Invalid {field}: '{value}'")))
+}
+
+/// An optional string field: proto3 sends unset strings as empty.
+pub fn non_empty(value: String) -> Option<String> {
+    (!value.is_empty()).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use prost::Message;
+
+    #[test]
+    fn test_upload_request_wire_format() {
+        let data = UploadRequest {
+            payload: Some(upload_request::Payload::Data(Bytes::from_static(b"hi"))),
+        };
+        assert_eq!(data.encode_to_vec(), [0x12, 2, b'h', b'i']);
+
+        let metadata = UploadRequest {
+            payload: Some(upload_request::Payload::Metadata(UploadMetadata {
+                file_size: 3,
+                ..Default::default()
+            })),
+        };
+        assert_eq!(metadata.encode_to_vec(), [0x0a, 2, 0x18, 3]);
+        assert_eq!(
+            UploadRequest::decode(&[0x0a, 2, 0x18, 3][..]).unwrap(),
+            metadata
+        );
+    }
+
+    #[test]
+    fn test_timestamp_from_datetime() {
+        let at = DateTime::from_timestamp(1_700_000_000, 5).unwrap();
+        assert_eq!(
+            Timestamp::from(at),
+            Timestamp {
+                seconds: 1_700_000_000,
+                nanos: 5
+            }
+        );
+    }
+}
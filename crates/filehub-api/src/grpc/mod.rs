@@ -0,0 +1,68 @@
+//! gRPC API surface, built with the `grpc` feature.
+//!
+//! Serves a subset of the REST API — login, file CRUD and streaming
+//! upload/download — as the `filehub.v1` services described in
+//! `proto/filehub/v1/filehub.proto`. Calls run on their own plaintext
+//! HTTP/2 listener (`server.grpc`) and reuse the REST authentication and
+//! services.
+//!
+//! Calls are served by tonic. The prost message types in [`messages`] are
+//! derived by hand rather than generated, so they must be kept in step
+//! with the proto file.
+
+pub mod messages;
+pub mod server;
+pub mod service;
+
+use std::sync::Arc;
+
+use axum::Router;
+
+use filehub_core::config::GrpcConfig;
+use filehub_core::error::AppError;
+
+use crate::state::AppState;
+
+/// Builds the router for the gRPC services.
+pub fn build_grpc_router(state: AppState) -> Router {
+    let max_message_bytes = state.config.server.grpc.max_message_bytes;
+    let calls = Arc::new(service::FileHubGrpc::new(state.clone()));
+
+    tonic::service::Routes::new(server::AuthServer::new(
+        Arc::clone(&calls),
+        max_message_bytes,
+    ))
+    .add_service(server::FilesServer::new(calls, max_message_bytes))
+    .into_axum_router()
+    .layer(axum::middleware::from_fn_with_state(
+        state.clone(),
+        crate::middleware::auth::bearer_claims,
+    ))
+    .layer(axum::middleware::from_fn_with_state(
+        state,
+        crate::middleware::client_ip::client_ip,
+    ))
+}
+
+/// Serves the gRPC services on their own listener.
+pub async fn spawn_listener(state: AppState, config: &GrpcConfig) -> Result<(), AppError> {
+    let addr = format!("{}:{}", config.host, config.port);
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to bind gRPC {}: {}", addr, e)))?;
+
+    tracing::info!("gRPC listening on {}", addr);
+
+    let app = build_grpc_router(state);
+    tokio::spawn(async move {
+        let server = axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+        );
+        if let Err(e) = server.await {
+            tracing::error!("gRPC server error: {}", e);
+        }
+    });
+
+    Ok(())
+}
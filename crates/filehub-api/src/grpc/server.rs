@@ -0,0 +1,159 @@
+//! tonic services for `filehub.v1.Auth` and `filehub.v1.Files`.
+//!
+//! The dispatch `tonic-build` would generate from the proto file: each
+//! service routes a call by its method path to tonic's unary or streaming
+//! handler, with the prost codec and the configured message size limit.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use prost::Message;
+use tonic::body::Body;
+use tonic::codegen::{BoxFuture, Service, http};
+use tonic::server::{Grpc, NamedService};
+use tonic::{Request, Status};
+use tonic_prost::ProstCodec;
+use tower::service_fn;
+
+use super::service::FileHubGrpc;
+
+/// A gRPC handler for messages `Req` answered with `Resp`.
+fn grpc<Resp, Req>(max_message_bytes: usize) -> Grpc<ProstCodec<Resp, Req>>
+where
+    Resp: Message + Send + 'static,
+    Req: Message + Default + Send + 'static,
+{
+    Grpc::new(ProstCodec::default()).max_decoding_message_size(max_message_bytes)
+}
+
+fn unimplemented() -> BoxFuture<http::Response<Body>, Infallible> {
+    Box::pin(async { Ok(Status::unimplemented("Unknown gRPC method").into_http()) })
+}
+
+/// `filehub.v1.Auth`.
+#[derive(Debug, Clone)]
+pub struct AuthServer {
+    inner: Arc<FileHubGrpc>,
+    max_message_bytes: usize,
+}
+
+impl AuthServer {
+    /// Serves `inner`, refusing request messages over `max_message_bytes`.
+    pub fn new(inner: Arc<FileHubGrpc>, max_message_bytes: usize) -> Self {
+        Self {
+            inner,
+            max_message_bytes,
+        }
+    }
+}
+
+impl NamedService for AuthServer {
+    const NAME: &'static str = "filehub.v1.Auth";
+}
+
+impl Service<http::Request<Body>> for AuthServer {
+    type Response = http::Response<Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+        let inner = Arc::clone(&self.inner);
+        let max = self.max_message_bytes;
+        match req.uri().path() {
+            "/filehub.v1.Auth/Login" => Box::pin(async move {
+                let method = service_fn(move |r: Request<_>| {
+                    let inner = Arc::clone(&inner);
+                    async move { inner.login(r).await }
+                });
+                Ok(grpc(max).unary(method, req).await)
+            }),
+            _ => unimplemented(),
+        }
+    }
+}
+
+/// `filehub.v1.Files`.
+#[derive(Debug, Clone)]
+pub struct FilesServer {
+    inner: Arc<FileHubGrpc>,
+    max_message_bytes: usize,
+}
+
+impl FilesServer {
+    /// Serves `inner`, refusing request messages over `max_message_bytes`.
+    pub fn new(inner: Arc<FileHubGrpc>, max_message_bytes: usize) -> Self {
+        Self {
+            inner,
+            max_message_bytes,
+        }
+    }
+}
+
+impl NamedService for FilesServer {
+    const NAME: &'static str = "filehub.v1.Files";
+}
+
+impl Service<http::Request<Body>> for FilesServer {
+    type Response = http::Response<Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+        let inner = Arc::clone(&self.inner);
+        let max = self.max_message_bytes;
+        match req.uri().path() {
+            "/filehub.v1.Files/GetFile" => Box::pin(async move {
+                let method = service_fn(move |r: Request<_>| {
+                    let inner = Arc::clone(&inner);
+                    async move { inner.get_file(r).await }
+                });
+                Ok(grpc(max).unary(method, req).await)
+            }),
+            "/filehub.v1.Files/ListFiles" => Box::pin(async move {
+                let method = service_fn(move |r: Request<_>| {
+                    let inner = Arc::clone(&inner);
+                    async move { inner.list_files(r).await }
+                });
+                Ok(grpc(max).unary(method, req).await)
+            }),
+            "/filehub.v1.Files/UpdateFile" => Box::pin(async move {
+                let method = service_fn(move |r: Request<_>| {
+                    let inner = Arc::clone(&inner);
+                    async move { inner.update_file(r).await }
+                });
+                Ok(grpc(max).unary(method, req).await)
+            }),
+            "/filehub.v1.Files/DeleteFile" => Box::pin(async move {
+                let method = service_fn(move |r: Request<_>| {
+                    let inner = Arc::clone(&inner);
+                    async move { inner.delete_file(r).await }
+                });
+                Ok(grpc(max).unary(method, req).await)
+            }),
+            "/filehub.v1.Files/Upload" => Box::pin(async move {
+                let method = service_fn(move |r: Request<_>| {
+                    let inner = Arc::clone(&inner);
+                    async move { inner.upload(r).await }
+                });
+                Ok(grpc(max).client_streaming(method, req).await)
+            }),
+            "/filehub.v1.Files/Download" => Box::pin(async move {
+                let method = service_fn(move |r: Request<_>| {
+                    let inner = Arc::clone(&inner);
+                    async move { inner.download(r).await }
+                });
+                Ok(grpc(max).server_streaming(method, req).await)
+            }),
+            _ => unimplemented(),
+        }
+    }
+}
@@ -0,0 +1,412 @@
+//! `filehub.v1.Auth` and `filehub.v1.Files` calls.
+//!
+//! Each call authenticates like the REST [`AuthUser`] extractor and then
+//! goes through the same services as the REST handlers, so permissions,
+//! quotas and auditing are identical on both surfaces.
+
+use std::pin::Pin;
+
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt};
+use tonic::metadata::{MetadataMap, MetadataValue};
+use tonic::{Code, Request, Response, Status, Streaming};
+
+use filehub_core::error::{AppError, ErrorKind};
+use filehub_core::types::pagination::PageRequest;
+use filehub_entity::file::File;
+use filehub_service::context::RequestContext;
+use filehub_service::file::service::UpdateFileRequest as UpdateFile;
+use filehub_service::file::upload::InitiateUploadRequest;
+
+use super::messages::upload_request::Payload;
+use super::messages::{
+    DeleteFileRequest, DeleteFileResponse, DownloadRequest, DownloadResponse, FileInfo,
+    GetFileRequest, ListFilesRequest, ListFilesResponse, LoginRequest, LoginResponse,
+    UpdateFileRequest, UploadMetadata, UploadRequest, non_empty, parse_id,
+};
+use crate::extractors::{AuthUser, ClientInfo};
+use crate::state::AppState;
+
+/// Largest `data` field sent in one download message.
+const DOWNLOAD_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Metadata entry carrying the FileHub error code of a failed call.
+const ERROR_CODE_TRAILER: &str = "filehub-error-code";
+
+/// Messages of a `Files/Download` call.
+pub type DownloadStream = Pin<Box<dyn Stream<Item = Result<DownloadResponse, Status>> + Send>>;
+
+/// Implementation of the `filehub.v1` calls over the application state.
+#[derive(Debug, Clone)]
+pub struct FileHubGrpc {
+    state: AppState,
+}
+
+impl FileHubGrpc {
+    /// Creates the calls over `state`.
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    /// The authenticated caller and message of a request.
+    async fn caller<T>(&self, request: Request<T>) -> Result<(AuthUser, T), AppError> {
+        let (metadata, extensions, message) = request.into_parts();
+        let auth =
+            AuthUser::authenticate(&self.state, &metadata.into_headers(), &extensions).await?;
+        Ok((auth, message))
+    }
+
+    /// `Auth/Login`
+    pub async fn login(
+        &self,
+        request: Request<LoginRequest>,
+    ) -> Result<Response<LoginResponse>, Status> {
+        let (metadata, extensions, req) = request.into_parts();
+        let client = ClientInfo::from_parts(&metadata.into_headers(), &extensions);
+        let result = async {
+            let result = self
+                .state
+                .session_manager
+                .login(
+                    &req.username,
+                    &req.password,
+                    client.ip,
+                    client.user_agent.as_deref(),
+                    None,
+                )
+                .await?;
+
+            Ok(LoginResponse {
+                access_token: result.tokens.access_token,
+                refresh_token: result.tokens.refresh_token,
+                access_expires_at: Some(result.tokens.access_expires_at.into()),
+                refresh_expires_at: Some(result.tokens.refresh_expires_at.into()),
+                user_id: result.user.id.to_string(),
+                role: result.user.role.to_string(),
+            })
+        };
+        reply(result.await)
+    }
+
+    /// `Files/GetFile`
+    pub async fn get_file(
+        &self,
+        request: Request<GetFileRequest>,
+    ) -> Result<Response<FileInfo>, Status> {
+        let result = async {
+            let (auth, req) = self.caller(request).await?;
+            let file_id = parse_id("file_id", &req.file_id)?;
+            let file = self.state.file_service.get_file(&auth, file_id).await?;
+            Ok(FileInfo::from(&file))
+        };
+        reply(result.await)
+    }
+
+    /// `Files/ListFiles`
+    pub async fn list_files(
+        &self,
+        request: Request<ListFilesRequest>,
+    ) -> Result<Response<ListFilesResponse>, Status> {
+        let result = async {
+            let (auth, req) = self.caller(request).await?;
+            let folder_id = parse_id("folder_id", &req.folder_id)?;
+            let page_size = if req.page_size == 0 {
+                PageRequest::default().page_size
+            } else {
+                req.page_size
+            };
+            let page = match req.cursor.as_str() {
+                "" => PageRequest::new(req.page, page_size),
+                "-" => PageRequest::with_cursor(page_size, None),
+                cursor => PageRequest::with_cursor(page_size, Some(cursor.to_string())),
+            };
+
+            let page = self
+                .state
+                .file_service
+                .list_files(&auth, folder_id, page, None)
+                .await?;
+            Ok(ListFilesResponse {
+                files: page.items.iter().map(FileInfo::from).collect(),
+                page: page.page,
+                total_items: page.total_items,
+                total_pages: page.total_pages,
+                next_cursor: page.next_cursor.unwrap_or_default(),
+            })
+        };
+        reply(result.await)
+    }
+
+    /// `Files/UpdateFile`
+    pub async fn update_file(
+        &self,
+        request: Request<UpdateFileRequest>,
+    ) -> Result<Response<FileInfo>, Status> {
+        let result = async {
+            let (auth, req) = self.caller(request).await?;
+            let file_id = parse_id("file_id", &req.file_id)?;
+            let name =
+                non_empty(req.name).ok_or_else(|| AppError::validation("name is required"))?;
+
+            // The service replaces metadata wholesale; keep what is there.
+            let current = self.state.file_service.get_file(&auth, file_id).await?;
+            let file = self
+                .state
+                .file_service
+                .update_file(
+                    &auth,
+                    file_id,
+                    UpdateFile {
+                        name: Some(name),
+                        metadata: current.metadata,
+                    },
+                )
+                .await?;
+            Ok(FileInfo::from(&file))
+        };
+        reply(result.await)
+    }
+
+    /// `Files/DeleteFile`
+    pub async fn delete_file(
+        &self,
+        request: Request<DeleteFileRequest>,
+    ) -> Result<Response<DeleteFileResponse>, Status> {
+        let result = async {
+            let (auth, req) = self.caller(request).await?;
+            let file_id = parse_id("file_id", &req.file_id)?;
+            self.state.file_service.delete_file(&auth, file_id).await?;
+            Ok(DeleteFileResponse {})
+        };
+        reply(result.await)
+    }
+
+    /// `Files/Upload`
+    ///
+    /// Runs a chunked upload session: content is cut into the session's
+    /// chunks as it arrives and the file is assembled once the client
+    /// finishes sending. If the call breaks off, the session stays open
+    /// until it expires, like an abandoned REST upload.
+    pub async fn upload(
+        &self,
+        request: Request<Streaming<UploadRequest>>,
+    ) -> Result<Response<FileInfo>, Status> {
+        let result = async {
+            let (auth, mut messages) = self.caller(request).await?;
+            let metadata = match next_message(&mut messages).await?.and_then(|m| m.payload) {
+                Some(Payload::Metadata(metadata)) => metadata,
+                _ => {
+                    return Err(AppError::validation(
+                        "The first upload message must carry metadata",
+                    ));
+                }
+            };
+            let file = self.upload_content(&auth, metadata, messages).await?;
+            Ok(FileInfo::from(&file))
+        };
+        reply(result.await)
+    }
+
+    async fn upload_content(
+        &self,
+        ctx: &RequestContext,
+        metadata: UploadMetadata,
+        mut messages: Streaming<UploadRequest>,
+    ) -> Result<File, AppError> {
+        if metadata.file_size < 0 {
+            return Err(AppError::validation("file_size cannot be negative"));
+        }
+        let upload_service = &self.state.upload_service;
+        let session = upload_service
+            .initiate_chunked_upload(
+                ctx,
+                InitiateUploadRequest {
+                    folder_id: parse_id("folder_id", &metadata.folder_id)?,
+                    file_name: metadata.file_name,
+                    file_size: metadata.file_size,
+                    mime_type: non_empty(metadata.mime_type),
+                    checksum_sha256: non_empty(metadata.checksum_sha256),
+                },
+            )
+            .await?;
+
+        let chunk_size = session.chunk_size as usize;
+        let mut pending = BytesMut::with_capacity(chunk_size);
+        let mut received: i64 = 0;
+        let mut chunk_number = 0;
+
+        while let Some(message) = next_message(&mut messages).await? {
+            let Some(Payload::Data(data)) = message.payload else {
+                return Err(AppError::validation(
+                    "Only the first upload message may carry metadata",
+                ));
+            };
+            received += data.len() as i64;
+            if received > metadata.file_size {
+                return Err(AppError::validation(format!(
+                    "Upload exceeds the declared size of {} bytes",
+                    metadata.file_size
+                )));
+            }
+
+            pending.extend_from_slice(&data);
+            while pending.len() >= chunk_size {
+                let chunk = pending.split_to(chunk_size).freeze();
+                upload_service
+                    .upload_chunk(ctx, session.upload_id, chunk_number, chunk, None)
+                    .await?;
+                chunk_number += 1;
+            }
+        }
+
+        if received != metadata.file_size {
+            return Err(AppError::validation(format!(
+                "Upload ended after {received} of {} bytes",
+                metadata.file_size
+            )));
+        }
+        // The last (or only, for an empty file) chunk.
+        if chunk_number < session.total_chunks {
+            upload_service
+                .upload_chunk(ctx, session.upload_id, chunk_number, pending.freeze(), None)
+                .await?;
+        }
+
+        upload_service
+            .complete_chunked_upload(ctx, session.upload_id, None)
+            .await
+    }
+
+    /// `Files/Download`
+    ///
+    /// Streams the requested range of the file from storage.
+    pub async fn download(
+        &self,
+        request: Request<DownloadRequest>,
+    ) -> Result<Response<DownloadStream>, Status> {
+        let result = async {
+            let (auth, req) = self.caller(request).await?;
+            let file_id = parse_id("file_id", &req.file_id)?;
+            let download_service = &self.state.download_service;
+            let file = download_service.resolve(&auth, file_id).await?;
+
+            let size = file.size_bytes.max(0) as u64;
+            if req.offset > size {
+                return Err(AppError::validation(format!(
+                    "offset {} is past the end of the file ({size} bytes)",
+                    req.offset
+                )));
+            }
+            let remaining = size - req.offset;
+            let len = match req.length {
+                0 => remaining,
+                length => length.min(remaining),
+            };
+            let content = download_service.read_range(&file, req.offset, len).await?;
+            Ok((FileInfo::from(&file), content))
+        };
+        let (info, content) = result.await.map_err(status)?;
+
+        let header = futures::stream::iter([Ok(DownloadResponse {
+            file: Some(info),
+            data: Bytes::new(),
+        })]);
+        let data = content.flat_map(|chunk| {
+            let messages: Vec<Result<DownloadResponse, Status>> = match chunk {
+                Ok(chunk) => split(chunk)
+                    .map(|data| Ok(DownloadResponse { file: None, data }))
+                    .collect(),
+                Err(e) => vec![Err(status(AppError::internal(format!(
+                    "Storage read failed: {e}"
+                ))))],
+            };
+            futures::stream::iter(messages)
+        });
+        let messages: DownloadStream = Box::pin(header.chain(data));
+        Ok(Response::new(messages))
+    }
+}
+
+/// The next message of a client stream, or `None` once the client has
+/// finished sending.
+async fn next_message<T>(messages: &mut Streaming<T>) -> Result<Option<T>, AppError> {
+    messages
+        .message()
+        .await
+        .map_err(|s| AppError::bad_request(format!("Failed to read request: {}", s.message())))
+}
+
+fn reply<T>(result: Result<T, AppError>) -> Result<Response<T>, Status> {
+    result.map(Response::new).map_err(status)
+}
+
+/// Cuts a storage chunk into message-sized pieces.
+fn split(chunk: Bytes) -> impl Iterator<Item = Bytes> {
+    (0..chunk.len())
+        .step_by(DOWNLOAD_CHUNK_BYTES)
+        .map(move |start| chunk.slice(start..(start + DOWNLOAD_CHUNK_BYTES).min(chunk.len())))
+}
+
+/// gRPC status code for an error.
+pub fn status_code(kind: ErrorKind) -> Code {
+    match kind {
+        ErrorKind::Validation | ErrorKind::BadRequest | ErrorKind::UnsupportedMediaType => {
+            Code::InvalidArgument
+        }
+        ErrorKind::Timeout => Code::DeadlineExceeded,
+        ErrorKind::NotFound | ErrorKind::Gone => Code::NotFound,
+        ErrorKind::Authorization | ErrorKind::Forbidden => Code::PermissionDenied,
+        ErrorKind::RateLimit | ErrorKind::License | ErrorKind::PayloadTooLarge => {
+            Code::ResourceExhausted
+        }
+        ErrorKind::Conflict => Code::Aborted,
+        ErrorKind::NotImplemented => Code::Unimplemented,
+        ErrorKind::ServiceUnavailable => Code::Unavailable,
+        ErrorKind::Authentication | ErrorKind::Unauthorized | ErrorKind::Session => {
+            Code::Unauthenticated
+        }
+        _ => Code::Internal,
+    }
+}
+
+/// Status of a failed call, with the FileHub error code alongside.
+pub fn status(error: AppError) -> Status {
+    if error.kind == ErrorKind::Internal {
+        tracing::error!(error = %error.message, "Internal gRPC error");
+    }
+    let mut metadata = MetadataMap::new();
+    metadata.insert(ERROR_CODE_TRAILER, MetadataValue::from_static(error.code));
+    Status::with_metadata(status_code(error.kind), error.message, metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_codes() {
+        assert_eq!(status_code(ErrorKind::NotFound), Code::NotFound);
+        assert_eq!(status_code(ErrorKind::Unauthorized), Code::Unauthenticated);
+        assert_eq!(status_code(ErrorKind::Forbidden), Code::PermissionDenied);
+        assert_eq!(status_code(ErrorKind::Validation), Code::InvalidArgument);
+        assert_eq!(status_code(ErrorKind::Database), Code::Internal);
+    }
+
+    #[test]
+    fn test_status_carries_error_code() {
+        let status = status(AppError::not_found("File not found"));
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(status.message(), "File not found");
+        assert_eq!(
+            status.metadata().get(ERROR_CODE_TRAILER).unwrap(),
+            AppError::not_found("").code
+        );
+    }
+
+    #[test]
+    fn test_split_download_chunks() {
+        let chunk = Bytes::from(vec![0u8; DOWNLOAD_CHUNK_BYTES * 2 + 1]);
+        let sizes: Vec<usize> = split(chunk).map(|piece| piece.len()).collect();
+        assert_eq!(sizes, [DOWNLOAD_CHUNK_BYTES, DOWNLOAD_CHUNK_BYTES, 1]);
+    }
+}
@@ -1,11 +1,21 @@
 //! Audit log handlers.
 
+use std::collections::HashMap;
+
 use axum::Json;
+use axum::body::Body;
 use axum::extract::{Query, State};
+use axum::http::header;
+use axum::response::Response;
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
 use filehub_core::error::AppError;
+use filehub_core::types::pagination::PageResponse;
+use filehub_database::repositories::audit::AuditFilter;
+use filehub_service::session::AuditExportFormat;
 
+use crate::dto::response::AuditEntryResponse;
 use crate::extractors::{AuthUser, PaginationParams};
 use crate::middleware::rbac::require_admin;
 use crate::state::AppState;
@@ -15,7 +25,7 @@ pub async fn search_audit(
     State(state): State<AppState>,
     auth: AuthUser,
     Query(params): Query<PaginationParams>,
-    Query(filters): Query<std::collections::HashMap<String, String>>,
+    Query(filters): Query<HashMap<String, String>>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     require_admin(&auth)?;
 
@@ -39,17 +49,80 @@ pub async fn search_audit(
             params.into_page_request(),
         )
         .await?;
+    let result = PageResponse {
+        items: result
+            .items
+            .into_iter()
+            .map(AuditEntryResponse::from)
+            .collect(),
+        page: result.page,
+        page_size: result.page_size,
+        total_items: result.total_items,
+        total_pages: result.total_pages,
+        has_next: result.has_next,
+        has_previous: result.has_previous,
+        next_cursor: result.next_cursor,
+    };
 
     Ok(Json(serde_json::json!({ "success": true, "data": result })))
 }
 
-/// GET /api/admin/audit/export
+/// GET /api/admin/audit/export?format=csv|ndjson
+///
+/// Accepts the same filters as [`search_audit`] plus `since` and `until`
+/// (RFC 3339). The body is streamed as entries are read.
 pub async fn export_audit(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     auth: AuthUser,
-) -> Result<Json<serde_json::Value>, AppError> {
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Response, AppError> {
     require_admin(&auth)?;
-    Ok(Json(
-        serde_json::json!({ "success": true, "data": { "message": "Export started" } }),
-    ))
+
+    let format: AuditExportFormat = params
+        .get("format")
+        .map(|f| f.parse())
+        .transpose()?
+        .unwrap_or(AuditExportFormat::Csv);
+    let filter = AuditFilter {
+        actor_id: parse_param(&params, "actor_id", |s| Uuid::parse_str(s).ok())?,
+        action: params.get("action").cloned(),
+        target_type: params.get("target_type").cloned(),
+        target_id: parse_param(&params, "target_id", |s| Uuid::parse_str(s).ok())?,
+        since: parse_param(&params, "since", parse_time)?,
+        until: parse_param(&params, "until", parse_time)?,
+    };
+
+    let stream = state.audit_service.export(&auth, filter, format);
+    Response::builder()
+        .header(header::CONTENT_TYPE, format.content_type())
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"audit-export.{}\"",
+                format.extension()
+            ),
+        )
+        .header(header::CACHE_CONTROL, "no-store")
+        .body(Body::from_stream(stream))
+        .map_err(|e| AppError::internal(format!("Response build failed: {e}")))
+}
+
+/// Parses an optional query parameter, rejecting values `parse` refuses.
+fn parse_param<T>(
+    params: &HashMap<String, String>,
+    name: &str,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<Option<T>, AppError> {
+    params
+        .get(name)
+        .map(|value| {
+            parse(value).ok_or_else(|| AppError::bad_request(format!("Invalid {name}: '{value}'")))
+        })
+        .transpose()
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
 }
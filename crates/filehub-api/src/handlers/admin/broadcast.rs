@@ -1,9 +1,12 @@
 //! Admin broadcast handlers.
 
 use axum::Json;
-use axum::extract::State;
+use axum::extract::{Path, State};
+use chrono::Utc;
+use uuid::Uuid;
 
 use filehub_core::error::AppError;
+use filehub_entity::notification::{BroadcastMessage, BroadcastTarget};
 
 use crate::dto::request::BroadcastRequest;
 use crate::extractors::AuthUser;
@@ -18,23 +21,45 @@ pub async fn send_broadcast(
 ) -> Result<Json<serde_json::Value>, AppError> {
     require_admin(&auth)?;
 
-    state
-        .realtime
-        .notifications
-        .broadcast_admin_message(
-            uuid::Uuid::new_v4(),
-            &req.title,
-            &req.message,
-            &req.severity,
-            req.persistent,
-        )
-        .await;
+    let target = BroadcastTarget::parse(&req.target, &req.user_ids)?;
+    let message = BroadcastMessage {
+        title: req.title,
+        message: req.message,
+        severity: req.severity,
+        persistent: req.persistent,
+    };
+    let monitor = &state.realtime.session_monitor;
+
+    let broadcast = match req.scheduled_at {
+        Some(at) if at > Utc::now() => {
+            monitor
+                .schedule(auth.user_id, target, message, req.require_ack, at)
+                .await?
+        }
+        _ => {
+            monitor
+                .broadcast(auth.user_id, target, message, req.require_ack)
+                .await?
+        }
+    };
 
     Ok(Json(
-        serde_json::json!({ "success": true, "data": { "message": "Broadcast sent" } }),
+        serde_json::json!({ "success": true, "data": broadcast }),
     ))
 }
 
+/// GET /api/admin/broadcast/{id}/acks
+pub async fn broadcast_acks(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+
+    let report = state.realtime.session_monitor.ack_report(id).await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": report })))
+}
+
 /// GET /api/admin/broadcast/history
 pub async fn broadcast_history(
     State(_state): State<AppState>,
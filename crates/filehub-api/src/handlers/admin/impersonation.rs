@@ -0,0 +1,78 @@
+//! Admin impersonation handlers.
+
+use axum::Json;
+use axum::extract::{Path, State};
+use uuid::Uuid;
+
+use filehub_core::error::AppError;
+use filehub_service::session::impersonation::StartImpersonationRequest;
+
+use crate::dto::request::{ImpersonateUserRequest, TerminateSessionRequest};
+use crate::extractors::AuthUser;
+use crate::middleware::rbac::require_admin;
+use crate::state::AppState;
+
+/// POST /api/admin/users/:id/impersonate
+pub async fn start_impersonation(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<ImpersonateUserRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    let result = state
+        .impersonation_service
+        .start(
+            &auth,
+            id,
+            StartImpersonationRequest {
+                reason: req.reason,
+                duration_minutes: req.duration_minutes,
+            },
+        )
+        .await?;
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "data": {
+            "access_token": result.access_token,
+            "token_type": "Bearer",
+            "expires_at": result.expires_at,
+            "session_id": result.session.id,
+            "user": {
+                "id": result.user.id,
+                "username": result.user.username,
+                "display_name": result.user.display_name,
+                "role": result.user.role,
+            },
+        }
+    })))
+}
+
+/// GET /api/admin/impersonations
+pub async fn list_impersonations(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    let sessions = state.impersonation_service.list(&auth).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": sessions }),
+    ))
+}
+
+/// POST /api/admin/impersonations/:id/end
+pub async fn end_impersonation(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<TerminateSessionRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    state
+        .impersonation_service
+        .end(&auth, id, &req.reason)
+        .await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": { "message": "Impersonation ended" } }),
+    ))
+}
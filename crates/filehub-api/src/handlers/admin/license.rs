@@ -1,41 +1,135 @@
 //! License pool handlers.
 
 use axum::Json;
-use axum::extract::State;
+use axum::extract::{Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
 
 use filehub_core::error::AppError;
+use filehub_entity::license::PoolUtilization;
 
 use crate::extractors::AuthUser;
 use crate::middleware::rbac::require_admin;
 use crate::state::AppState;
 
+/// Number of snapshots returned by the pool history endpoint
+const POOL_HISTORY_LIMIT: i64 = 100;
+
+/// Most snapshots returned by the utilization endpoint
+const POOL_UTILIZATION_LIMIT: i64 = 10_000;
+
+/// Window used when the utilization request gives no `from`
+const DEFAULT_UTILIZATION_WINDOW_HOURS: i64 = 24;
+
+/// Query for [`pool_utilization`].
+#[derive(Debug, Deserialize)]
+pub struct UtilizationQuery {
+    /// Window start (RFC 3339); defaults to 24 hours before `to`.
+    pub from: Option<DateTime<Utc>>,
+    /// Window end (RFC 3339); defaults to now.
+    pub to: Option<DateTime<Utc>>,
+    /// `json` (default) or `csv`.
+    pub format: Option<String>,
+}
+
 /// GET /api/admin/license/pool
+///
+/// Totals across features plus per-feature utilization in `features`.
 pub async fn pool_status(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     auth: AuthUser,
 ) -> Result<Json<serde_json::Value>, AppError> {
     require_admin(&auth)?;
-    Ok(Json(
-        serde_json::json!({ "success": true, "data": { "status": "not_configured" } }),
-    ))
+    let Some(manager) = &state.license_manager else {
+        return Ok(Json(
+            serde_json::json!({ "success": true, "data": { "status": "not_configured" } }),
+        ));
+    };
+    let status = manager.pool_status().await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": status })))
 }
 
 /// GET /api/admin/license/pool/history
 pub async fn pool_history(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     auth: AuthUser,
 ) -> Result<Json<serde_json::Value>, AppError> {
     require_admin(&auth)?;
-    Ok(Json(serde_json::json!({ "success": true, "data": [] })))
+    let history = match &state.license_manager {
+        Some(manager) => manager.pool_history(POOL_HISTORY_LIMIT).await?,
+        None => Vec::new(),
+    };
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": history }),
+    ))
 }
 
 /// POST /api/admin/license/pool/reconcile
 pub async fn pool_reconcile(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     auth: AuthUser,
 ) -> Result<Json<serde_json::Value>, AppError> {
     require_admin(&auth)?;
-    Ok(Json(
-        serde_json::json!({ "success": true, "data": { "message": "Reconciliation triggered" } }),
-    ))
+    let Some(manager) = &state.license_manager else {
+        return Err(AppError::service_unavailable(
+            "License management is not configured",
+        ));
+    };
+    let status = manager.reconcile().await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": status })))
+}
+
+/// GET /api/admin/license/pool/utilization?from=&to=&format=json|csv
+///
+/// Current allocation plus the snapshots taken in the window, oldest first.
+/// As CSV, one row per snapshot with a column group per feature.
+pub async fn pool_utilization(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(query): Query<UtilizationQuery>,
+) -> Result<Response, AppError> {
+    require_admin(&auth)?;
+    let csv = match query.format.as_deref() {
+        None | Some("json") => false,
+        Some("csv") => true,
+        Some(other) => {
+            return Err(AppError::validation(format!(
+                "Unknown format '{other}'; expected json or csv"
+            )));
+        }
+    };
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query
+        .from
+        .unwrap_or(to - Duration::hours(DEFAULT_UTILIZATION_WINDOW_HOURS));
+    if from > to {
+        return Err(AppError::validation("'from' must not be after 'to'"));
+    }
+
+    let snapshots = state
+        .snapshot_repo
+        .find_between(from, to, POOL_UTILIZATION_LIMIT)
+        .await?;
+    let current = match &state.license_manager {
+        Some(manager) => Some(manager.pool_status().await?),
+        None => None,
+    };
+    let utilization = PoolUtilization::new(from, to, current, snapshots);
+
+    if csv {
+        return Ok((
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+                (
+                    header::CONTENT_DISPOSITION,
+                    "attachment; filename=\"pool-utilization.csv\"",
+                ),
+            ],
+            utilization.to_csv(),
+        )
+            .into_response());
+    }
+    Ok(Json(serde_json::json!({ "success": true, "data": utilization })).into_response())
 }
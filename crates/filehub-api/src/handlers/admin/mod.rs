@@ -2,9 +2,13 @@
 
 pub mod audit;
 pub mod broadcast;
+pub mod impersonation;
 pub mod jobs;
 pub mod license;
+pub mod permission_templates;
+pub mod permissions;
 pub mod reports;
+pub mod retention;
 pub mod sessions;
 pub mod storages;
 pub mod users;
@@ -0,0 +1,128 @@
+//! Admin permission template handlers.
+
+use axum::Json;
+use axum::extract::{Path, State};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use filehub_core::error::AppError;
+use filehub_service::permission::template::TemplateRequest;
+
+use crate::extractors::AuthUser;
+use crate::middleware::rbac::require_admin;
+use crate::state::AppState;
+
+/// Body of an apply request.
+#[derive(Debug, Deserialize)]
+pub struct ApplyTemplateRequest {
+    /// Folder to apply the template to.
+    pub folder_id: Uuid,
+    /// Also apply it to every folder below.
+    #[serde(default)]
+    pub recursive: bool,
+}
+
+/// Body of an attach request.
+#[derive(Debug, Deserialize)]
+pub struct AttachTemplateRequest {
+    /// Template to attach, or null to detach.
+    pub template_id: Option<Uuid>,
+}
+
+/// GET /api/admin/permission-templates
+pub async fn list_templates(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    let templates = state.permission_template_service.list(&auth).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": templates }),
+    ))
+}
+
+/// POST /api/admin/permission-templates
+pub async fn create_template(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(req): Json<TemplateRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    let template = state.permission_template_service.create(&auth, req).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": template }),
+    ))
+}
+
+/// GET /api/admin/permission-templates/{id}
+pub async fn get_template(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    let template = state.permission_template_service.get(&auth, id).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": template }),
+    ))
+}
+
+/// PUT /api/admin/permission-templates/{id}
+pub async fn update_template(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<TemplateRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    let template = state
+        .permission_template_service
+        .update(&auth, id, req)
+        .await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": template }),
+    ))
+}
+
+/// DELETE /api/admin/permission-templates/{id}
+pub async fn delete_template(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    state.permission_template_service.delete(&auth, id).await?;
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+/// POST /api/admin/permission-templates/{id}/apply
+pub async fn apply_template(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<ApplyTemplateRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    let summary = state
+        .permission_template_service
+        .apply(&auth, id, req.folder_id, req.recursive)
+        .await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": summary }),
+    ))
+}
+
+/// PUT /api/admin/folders/{id}/permission-template
+pub async fn attach_template(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(folder_id): Path<Uuid>,
+    Json(req): Json<AttachTemplateRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    let folder = state
+        .permission_template_service
+        .attach(&auth, folder_id, req.template_id)
+        .await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": folder })))
+}
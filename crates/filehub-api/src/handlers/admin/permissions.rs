@@ -0,0 +1,57 @@
+//! Permission introspection handlers.
+
+use axum::Json;
+use axum::extract::{Query, State};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use filehub_auth::rbac::policies::SystemPermission;
+use filehub_core::error::AppError;
+use filehub_entity::permission::{AclPermission, ResourceType};
+use filehub_service::permission::explain::ExplainRequest;
+
+use crate::extractors::AuthUser;
+use crate::middleware::rbac::require_admin;
+use crate::state::AppState;
+
+/// Query parameters for a permission explanation.
+#[derive(Debug, Deserialize)]
+pub struct ExplainQuery {
+    /// The user whose access is in question.
+    pub user_id: Uuid,
+    /// `file`, `folder` or `storage`.
+    pub resource_type: ResourceType,
+    /// The target resource.
+    pub resource_id: Uuid,
+    /// Permission level to check for (default `viewer`).
+    pub required: Option<AclPermission>,
+    /// System permission to check the user's role for, e.g. `file_upload`.
+    pub system_permission: Option<SystemPermission>,
+}
+
+/// GET /api/admin/permissions/explain
+///
+/// Returns the decision trace as `data` and a plain-text rendering of it
+/// as `text`. Read-only; the permission cache is neither used nor updated.
+pub async fn explain_permission(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(query): Query<ExplainQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    let trace = state
+        .permission_explainer
+        .explain(&ExplainRequest {
+            user_id: query.user_id,
+            resource_type: query.resource_type,
+            resource_id: query.resource_id,
+            required: query.required.unwrap_or(AclPermission::Viewer),
+            system_permission: query.system_permission,
+        })
+        .await?;
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "data": trace,
+        "text": trace.to_string(),
+    })))
+}
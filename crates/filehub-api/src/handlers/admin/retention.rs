@@ -0,0 +1,50 @@
+//! Admin folder retention policy handlers.
+
+use axum::Json;
+use axum::extract::{Path, State};
+use uuid::Uuid;
+
+use filehub_core::error::AppError;
+use filehub_service::folder::retention::SetRetentionRequest;
+
+use crate::extractors::AuthUser;
+use crate::middleware::rbac::require_admin;
+use crate::state::AppState;
+
+/// GET /api/admin/folders/:id/retention
+pub async fn get_retention(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(folder_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    let retention = state.retention_service.get(&auth, folder_id).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": retention }),
+    ))
+}
+
+/// PUT /api/admin/folders/:id/retention
+pub async fn set_retention(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(folder_id): Path<Uuid>,
+    Json(req): Json<SetRetentionRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    let policy = state.retention_service.set(&auth, folder_id, req).await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": policy })))
+}
+
+/// DELETE /api/admin/folders/:id/retention
+pub async fn remove_retention(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(folder_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    state.retention_service.remove(&auth, folder_id).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": { "message": "Retention policy removed" } }),
+    ))
+}
@@ -109,6 +109,7 @@ pub async fn send_message(
         message: req.message,
         severity: "info".to_string(),
         persistent: false,
+        require_ack: false,
         action_payload: None,
         action_type: None,
         timestamp: chrono::Utc::now(),
@@ -5,6 +5,7 @@ use axum::extract::{Path, State};
 use uuid::Uuid;
 
 use filehub_core::error::AppError;
+use filehub_service::storage::StartMigrationRequest;
 
 use crate::extractors::AuthUser;
 use crate::middleware::rbac::require_admin;
@@ -82,3 +83,69 @@ pub async fn sync_storage(
         serde_json::json!({ "success": true, "data": { "message": "Sync started" } }),
     ))
 }
+
+/// GET /api/admin/storages/migrations
+pub async fn list_migrations(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    let migrations = state.transfer_service.list_migrations(&auth).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": migrations }),
+    ))
+}
+
+/// POST /api/admin/storages/migrations
+///
+/// With `"dry_run": true` only reports the files and bytes on the source.
+pub async fn start_migration(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(req): Json<StartMigrationRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    let started = state.transfer_service.start_migration(&auth, req).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": started }),
+    ))
+}
+
+/// GET /api/admin/storages/migrations/:id
+pub async fn get_migration(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    let migration = state.transfer_service.get_migration(&auth, id).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": migration }),
+    ))
+}
+
+/// POST /api/admin/storages/migrations/:id/cancel
+pub async fn cancel_migration(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    let migration = state.transfer_service.cancel_migration(&auth, id).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": migration }),
+    ))
+}
+
+/// POST /api/admin/storages/migrations/:id/resume
+pub async fn resume_migration(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    let migration = state.transfer_service.resume_migration(&auth, id).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": migration }),
+    ))
+}
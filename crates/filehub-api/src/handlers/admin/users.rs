@@ -10,6 +10,7 @@ use filehub_service::user::admin::{AdminUpdateUserRequest, CreateUserRequest as
 
 use crate::dto::request::{
     ChangeRoleRequest, ChangeStatusRequest, CreateUserRequest, ResetPasswordRequest,
+    SetQuotaTierRequest,
 };
 use crate::extractors::{AuthUser, PaginationParams};
 use crate::middleware::rbac::require_admin;
@@ -116,11 +117,28 @@ pub async fn change_status(
     let status = parse_status(&req.status)?;
     let user = state
         .admin_user_service
-        .change_status(&auth, id, status)
+        .set_status(&auth, id, status)
         .await?;
     Ok(Json(serde_json::json!({ "success": true, "data": user })))
 }
 
+/// PUT /api/admin/users/:id/quota-tier
+pub async fn set_quota_tier(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SetQuotaTierRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    require_admin(&auth)?;
+    state
+        .quota_service
+        .set_tier(&auth, id, req.tier.clone())
+        .await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": { "id": id, "tier": req.tier } }),
+    ))
+}
+
 /// PUT /api/admin/users/:id/reset-password
 pub async fn reset_password(
     State(state): State<AppState>,
@@ -164,7 +182,7 @@ fn parse_role(s: &str) -> Result<UserRole, AppError> {
 fn parse_status(s: &str) -> Result<UserStatus, AppError> {
     match s {
         "active" => Ok(UserStatus::Active),
-        "inactive" => Ok(UserStatus::Inactive),
+        "inactive" | "disabled" => Ok(UserStatus::Inactive),
         "locked" => Ok(UserStatus::Locked),
         _ => Err(AppError::validation(format!("Invalid status: {s}"))),
     }
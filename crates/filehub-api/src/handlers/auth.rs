@@ -2,27 +2,29 @@
 
 use axum::Json;
 use axum::extract::State;
-use std::net::IpAddr;
 
 use filehub_core::error::AppError;
 
 use crate::dto::request::{LoginRequest, RefreshRequest};
 use crate::dto::response::{ApiResponse, LoginResponse, UserResponse};
-use crate::extractors::AuthUser;
+use crate::extractors::{AuthUser, ClientInfo};
 use crate::state::AppState;
 
 /// POST /api/auth/login
 pub async fn login(
     State(state): State<AppState>,
+    client: ClientInfo,
     Json(req): Json<LoginRequest>,
 ) -> Result<Json<ApiResponse<LoginResponse>>, AppError> {
-    let ip: IpAddr = "127.0.0.1"
-        .parse()
-        .unwrap_or_else(|_| "0.0.0.0".parse().unwrap());
-
     let result = state
         .session_manager
-        .login(&req.username, &req.password, ip, None, None)
+        .login(
+            &req.username,
+            &req.password,
+            client.ip,
+            client.user_agent.as_deref(),
+            None,
+        )
         .await?;
 
     let user_resp = UserResponse {
@@ -54,9 +56,13 @@ pub async fn logout(
     // In practice, you'd pass the raw token. For now, we use session_manager directly.
     // The session manager's logout requires Claims, which we already validated.
     // We'll use admin_terminate with self as admin for simplicity.
+    let (ended_by, reason) = match auth.impersonator_id {
+        Some(admin_id) => (admin_id, "Impersonation ended"),
+        None => (auth.user_id, "User logout"),
+    };
     state
         .session_manager
-        .admin_terminate(auth.session_id, auth.user_id, "User logout")
+        .admin_terminate(auth.session_id, ended_by, reason)
         .await?;
 
     Ok(Json(ApiResponse::ok(
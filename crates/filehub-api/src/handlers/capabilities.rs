@@ -0,0 +1,48 @@
+//! Capability negotiation handler.
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{StatusCode, header};
+use axum::response::Response;
+
+use filehub_core::error::AppError;
+
+use crate::capabilities::Capabilities;
+use crate::dto::response::ApiResponse;
+use crate::extractors::{AuthUser, ConditionalHeaders};
+use crate::state::AppState;
+
+/// How long clients may reuse the report without revalidating.
+const MAX_AGE_SECONDS: u32 = 300;
+
+/// GET /api/capabilities
+///
+/// Enabled features, limits and versions of this deployment; see
+/// [`Capabilities`]. Revalidates with `If-None-Match`.
+pub async fn capabilities(
+    State(state): State<AppState>,
+    _auth: AuthUser,
+    conditional: ConditionalHeaders,
+) -> Result<Response, AppError> {
+    let capabilities = Capabilities::new(&state.live_config.borrow().clone());
+    let etag = capabilities.etag();
+    let cache_control = format!("private, max-age={MAX_AGE_SECONDS}");
+
+    let response = if conditional.none_match(&etag) {
+        Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, etag)
+            .header(header::CACHE_CONTROL, cache_control)
+            .body(Body::empty())
+    } else {
+        let body = serde_json::to_vec(&ApiResponse::ok(capabilities))
+            .map_err(|e| AppError::internal(format!("Failed to serialize capabilities: {e}")))?;
+        Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::ETAG, etag)
+            .header(header::CACHE_CONTROL, cache_control)
+            .body(Body::from(body))
+    };
+    response.map_err(|e| AppError::internal(format!("Response build failed: {e}")))
+}
@@ -2,19 +2,29 @@
 
 use axum::Json;
 use axum::body::Body;
-use axum::extract::{Multipart, Path, Query, State};
-use axum::http::{StatusCode, header};
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode, header};
 use axum::response::Response;
 use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
 use filehub_core::error::AppError;
-use filehub_service::file::upload::{InitiateUploadRequest as SvcInitUpload, SimpleUploadParams};
+use filehub_core::traits::storage::ByteStream;
+use filehub_realtime::channel::types::ChannelType;
+use filehub_service::file::upload::{
+    DirectUploadRequest, FormUploadParams, InitiateUploadRequest as SvcInitUpload,
+};
 
 use crate::dto::request::{
-    CopyFileRequest, InitiateUploadRequest, MoveFileRequest, UpdateFileRequest,
+    BulkFileRequest, ConvertFileRequest, CopyFileRequest, InitiateUploadRequest, MoveFileRequest,
+    PresignUploadRequest, SetFileExpiryRequest, UpdateFileRequest,
+};
+use crate::extractors::conditional::{Precondition, http_date};
+use crate::extractors::range::{ByteRange, RangeOutcome};
+use crate::extractors::{
+    AuthUser, ConditionalHeaders, ExpiresAt, OnConflict, PaginationParams, RangeHeaders, UploadForm,
 };
-use crate::extractors::{AuthUser, PaginationParams};
 use crate::state::AppState;
 
 /// GET /api/files?folder_id=...
@@ -30,10 +40,11 @@ pub async fn list_files(
         .parse::<Uuid>()
         .map_err(|_| AppError::validation("Invalid folder_id"))?;
 
+    let sort = params.folder_sort()?;
     let page = params.into_page_request();
     let result = state
         .file_service
-        .list_files(&auth, folder_id, page)
+        .list_files(&auth, folder_id, page, sort)
         .await?;
 
     Ok(Json(serde_json::json!({
@@ -58,13 +69,67 @@ pub async fn get_file(
     Ok(Json(serde_json::json!({ "success": true, "data": file })))
 }
 
+/// GET /api/files/:id/viewers — online users with the file open, limited
+/// to those who may view it
+pub async fn list_viewers(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let file = state.file_service.get_file(&auth, id).await?;
+    let mut viewers = Vec::new();
+    for viewer in state.realtime.active_viewers(&ChannelType::File(id)).await {
+        if state
+            .file_service
+            .can_view(&file, viewer.user_id, &viewer.role)
+            .await
+        {
+            viewers.push(viewer);
+        }
+    }
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": viewers }),
+    ))
+}
+
 /// GET /api/files/:id/download
 pub async fn download_file(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(id): Path<Uuid>,
+    conditional: ConditionalHeaders,
+    range: RangeHeaders,
 ) -> Result<Response, AppError> {
-    let result = state.download_service.download(&auth, id).await?;
+    let file = state.download_service.resolve(&auth, id).await?;
+    let etag = file.etag();
+    let last_modified = file.updated_at;
+    let size = file.size_bytes.max(0) as u64;
+
+    if let Some(response) = precondition_response(&conditional, &etag, last_modified)? {
+        return Ok(response);
+    }
+
+    match range.resolve(size, &etag, last_modified) {
+        RangeOutcome::Full => {}
+        RangeOutcome::Unsatisfiable => return range_not_satisfiable(size),
+        RangeOutcome::Partial(r) => {
+            let stream = state
+                .download_service
+                .read_range(&file, r.start, r.length())
+                .await?;
+            return partial_content(
+                stream,
+                r,
+                size,
+                file.mime_type.as_deref(),
+                &file.name,
+                &etag,
+                last_modified,
+            );
+        }
+    }
+
+    let result = state.download_service.read(file).await?;
 
     let response = Response::builder()
         .status(StatusCode::OK)
@@ -74,55 +139,209 @@ pub async fn download_file(
             format!("attachment; filename=\"{}\"", result.filename),
         )
         .header(header::CONTENT_LENGTH, result.data.len())
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, etag)
+        .header(header::LAST_MODIFIED, http_date(last_modified))
+        .header(header::CACHE_CONTROL, "private, no-cache")
         .body(Body::from(result.data))
         .map_err(|e| AppError::internal(format!("Response build failed: {e}")))?;
 
     Ok(response)
 }
 
-/// GET /api/files/:id/preview
+/// HEAD /api/files/:id/download — validators and size without the body
+pub async fn head_file(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    conditional: ConditionalHeaders,
+) -> Result<Response, AppError> {
+    let file = state.download_service.resolve(&auth, id).await?;
+    let etag = file.etag();
+    let last_modified = file.updated_at;
+
+    if let Some(response) = precondition_response(&conditional, &etag, last_modified)? {
+        return Ok(response);
+    }
+
+    let content_type = file
+        .mime_type
+        .clone()
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CONTENT_LENGTH, file.size_bytes)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, etag)
+        .header(header::LAST_MODIFIED, http_date(last_modified))
+        .header(header::CACHE_CONTROL, "private, no-cache")
+        .body(Body::empty())
+        .map_err(|e| AppError::internal(format!("Response build failed: {e}")))?;
+
+    Ok(response)
+}
+
+/// GET /api/files/:id/preview?size=<name>
+///
+/// Thumbnail of an image in one of the configured sizes (`icon`, `grid`,
+/// ...). The format (WebP, AVIF, JPEG) is negotiated from `Accept`.
 pub async fn preview_file(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
     Query(params): Query<std::collections::HashMap<String, String>>,
 ) -> Result<Response, AppError> {
-    let size = params.get("size").and_then(|s| s.parse::<u32>().ok());
+    let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok());
 
-    let result = state.preview_service.get_preview(&auth, id, size).await?;
+    let result = state
+        .preview_service
+        .get_preview(&auth, id, params.get("size").map(String::as_str), accept)
+        .await?;
 
     let response = Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, result.content_type)
         .header(header::CACHE_CONTROL, "public, max-age=3600")
+        .header(header::VARY, "Accept")
+        .body(Body::from(result.data))
+        .map_err(|e| AppError::internal(format!("Response build failed: {e}")))?;
+
+    Ok(response)
+}
+
+/// GET /api/files/:id/preview/pages
+///
+/// Page previews of a file: `status` is `ready` with one URL per page,
+/// `pending` while the pages are rendered, or `unavailable` with a reason.
+pub async fn document_preview(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let preview = state.preview_service.document_preview(&auth, id).await?;
+    let urls: Vec<String> = (1..=preview.pages)
+        .map(|page| format!("/api/files/{id}/preview/pages/{page}"))
+        .collect();
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "data": {
+            "status": preview.status,
+            "pages": urls,
+            "reason": preview.reason,
+        }
+    })))
+}
+
+/// GET /api/files/:id/preview/pages/:page
+pub async fn document_preview_page(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((id, page)): Path<(Uuid, u32)>,
+) -> Result<Response, AppError> {
+    let result = state.preview_service.document_page(&auth, id, page).await?;
+
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, result.content_type)
+        .header(header::CACHE_CONTROL, "private, max-age=3600")
         .body(Body::from(result.data))
         .map_err(|e| AppError::internal(format!("Response build failed: {e}")))?;
 
     Ok(response)
 }
 
+/// POST /api/files/:id/conversions
+///
+/// Queues a CAD conversion of a file with the named profile, or the
+/// configured default; an unknown profile is rejected.
+pub async fn convert_file(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<ConvertFileRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let queued = state
+        .conversion_service
+        .request(
+            &auth,
+            id,
+            filehub_service::file::conversion::ConversionRequest {
+                profile: req.profile,
+                targets: req.targets,
+            },
+        )
+        .await?;
+
+    Ok(Json(serde_json::json!({ "success": true, "data": queued })))
+}
+
 /// GET /api/files/:id/versions
 pub async fn list_versions(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(id): Path<Uuid>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let versions = state.version_service.list_versions(&auth, id).await?;
+    let versions = state.version_service.list(&auth, id).await?;
     Ok(Json(
         serde_json::json!({ "success": true, "data": versions }),
     ))
 }
 
+/// POST /api/files/:id/versions/:version_id/restore
+pub async fn restore_version(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((id, version_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let file = state.version_service.restore(&auth, id, version_id).await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": file })))
+}
+
 /// GET /api/files/:id/versions/:ver
 pub async fn download_version(
     State(state): State<AppState>,
     auth: AuthUser,
     Path((id, ver)): Path<(Uuid, i32)>,
+    conditional: ConditionalHeaders,
+    range: RangeHeaders,
 ) -> Result<Response, AppError> {
-    let result = state
+    let (file, version) = state
         .download_service
-        .download_version(&auth, id, ver)
+        .resolve_version(&auth, id, ver)
         .await?;
+    let etag = version.etag();
+    let last_modified = version.created_at;
+    let size = version.size_bytes.max(0) as u64;
+
+    if let Some(response) = precondition_response(&conditional, &etag, last_modified)? {
+        return Ok(response);
+    }
+
+    match range.resolve(size, &etag, last_modified) {
+        RangeOutcome::Full => {}
+        RangeOutcome::Unsatisfiable => return range_not_satisfiable(size),
+        RangeOutcome::Partial(r) => {
+            let stream = state
+                .download_service
+                .read_version_range(&file, &version, r.start, r.length())
+                .await?;
+            return partial_content(
+                stream,
+                r,
+                size,
+                file.mime_type.as_deref(),
+                &file.name,
+                &etag,
+                last_modified,
+            );
+        }
+    }
+
+    let result = state.download_service.read_version(file, &version).await?;
 
     let response = Response::builder()
         .status(StatusCode::OK)
@@ -131,71 +350,118 @@ pub async fn download_version(
             header::CONTENT_DISPOSITION,
             format!("attachment; filename=\"{}\"", result.filename),
         )
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, etag)
+        .header(header::LAST_MODIFIED, http_date(last_modified))
+        .header(header::CACHE_CONTROL, "private, no-cache")
         .body(Body::from(result.data))
         .map_err(|e| AppError::internal(format!("Response build failed: {e}")))?;
 
     Ok(response)
 }
 
-/// POST /api/files/upload — simple multipart upload
+/// Builds a `206 Partial Content` response streaming a single byte range.
+fn partial_content(
+    stream: ByteStream,
+    range: ByteRange,
+    size: u64,
+    mime_type: Option<&str>,
+    filename: &str,
+    etag: &str,
+    last_modified: DateTime<Utc>,
+) -> Result<Response, AppError> {
+    Response::builder()
+        .status(StatusCode::PARTIAL_CONTENT)
+        .header(
+            header::CONTENT_TYPE,
+            mime_type.unwrap_or("application/octet-stream"),
+        )
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{filename}\""),
+        )
+        .header(header::CONTENT_RANGE, range.content_range(size))
+        .header(header::CONTENT_LENGTH, range.length())
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, etag)
+        .header(header::LAST_MODIFIED, http_date(last_modified))
+        .header(header::CACHE_CONTROL, "private, no-cache")
+        .body(Body::from_stream(stream))
+        .map_err(|e| AppError::internal(format!("Response build failed: {e}")))
+}
+
+/// Builds a `416 Range Not Satisfiable` response.
+fn range_not_satisfiable(size: u64) -> Result<Response, AppError> {
+    Response::builder()
+        .status(StatusCode::RANGE_NOT_SATISFIABLE)
+        .header(header::CONTENT_RANGE, format!("bytes */{size}"))
+        .body(Body::empty())
+        .map_err(|e| AppError::internal(format!("Response build failed: {e}")))
+}
+
+/// Builds an empty `304`/`412` response when the client's preconditions
+/// short-circuit the request, or `None` if the body should be served.
+fn precondition_response(
+    conditional: &ConditionalHeaders,
+    etag: &str,
+    last_modified: DateTime<Utc>,
+) -> Result<Option<Response>, AppError> {
+    let status = match conditional.evaluate(etag, last_modified) {
+        Precondition::Proceed => return Ok(None),
+        Precondition::NotModified => StatusCode::NOT_MODIFIED,
+        Precondition::Failed => StatusCode::PRECONDITION_FAILED,
+    };
+
+    Response::builder()
+        .status(status)
+        .header(header::ETAG, etag)
+        .header(header::LAST_MODIFIED, http_date(last_modified))
+        .body(Body::empty())
+        .map(Some)
+        .map_err(|e| AppError::internal(format!("Response build failed: {e}")))
+}
+
+/// POST /api/files/upload — `multipart/form-data` upload
+///
+/// The form has one file part and the fields `folder_id`, `description`
+/// and `tags`; see [`UploadForm`]. Files larger than one upload chunk go
+/// through a chunked upload session. A taken name is handled as
+/// `?on_conflict=` or the `X-On-Conflict` header say (`reject`,
+/// `overwrite`, `rename`); the stored name is the `name` of the returned
+/// file. `?expires_at=` or the `X-Expires-At` header (RFC 3339) have the
+/// file deleted automatically at that time.
 pub async fn upload_file(
     State(state): State<AppState>,
-    auth: AuthUser,
-    mut multipart: Multipart,
+    OnConflict(on_conflict): OnConflict,
+    ExpiresAt(expires_at): ExpiresAt,
+    form: UploadForm,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let mut folder_id: Option<Uuid> = None;
-    let mut file_name: Option<String> = None;
-    let mut mime_type: Option<String> = None;
-    let mut data: Option<Bytes> = None;
-
-    while let Some(field) = multipart
-        .next_field()
-        .await
-        .map_err(|e| AppError::validation(format!("Multipart error: {e}")))?
-    {
-        let name = field.name().unwrap_or("").to_string();
-        match name.as_str() {
-            "folder_id" => {
-                let text = field
-                    .text()
-                    .await
-                    .map_err(|e| AppError::validation(format!("Read error: {e}")))?;
-                folder_id = Some(
-                    Uuid::parse_str(&text)
-                        .map_err(|_| AppError::validation("Invalid folder_id"))?,
-                );
-            }
-            "file" => {
-                file_name = field.file_name().map(String::from);
-                mime_type = field.content_type().map(String::from);
-                data = Some(
-                    field
-                        .bytes()
-                        .await
-                        .map_err(|e| AppError::validation(format!("Read error: {e}")))?,
-                );
-            }
-            _ => {}
-        }
-    }
-
-    let folder_id = folder_id.ok_or_else(|| AppError::validation("folder_id is required"))?;
-    let file_name = file_name.ok_or_else(|| AppError::validation("file is required"))?;
-    let data = data.ok_or_else(|| AppError::validation("file data is required"))?;
-
-    let file = state
+    let auth = &form.auth;
+    let mut file = state
         .upload_service
-        .simple_upload(
-            &auth,
-            SimpleUploadParams {
-                folder_id,
-                file_name,
-                mime_type,
-                data,
+        .form_upload(
+            auth,
+            FormUploadParams {
+                folder_id: form.folder_id,
+                file_name: form.file.file_name.clone(),
+                mime_type: form.file.content_type.clone(),
+                content: form.file.content(),
+                description: form.description.clone(),
+                on_conflict,
             },
         )
         .await?;
 
+    for tag in &form.tags {
+        state.file_service.add_tag(auth, file.id, tag).await?;
+    }
+    if expires_at.is_some() {
+        file = state
+            .file_service
+            .set_expiry(auth, file.id, expires_at)
+            .await?;
+    }
+
     Ok(Json(serde_json::json!({ "success": true, "data": file })))
 }
 
@@ -222,16 +488,35 @@ pub async fn initiate_chunked_upload(
     Ok(Json(serde_json::json!({ "success": true, "data": result })))
 }
 
+/// GET /api/files/upload/:id
+pub async fn upload_status(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(upload_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let status = state.upload_service.upload_status(&auth, upload_id).await?;
+
+    Ok(Json(serde_json::json!({ "success": true, "data": status })))
+}
+
 /// PUT /api/files/upload/:id/chunk/:n
+///
+/// An optional `X-Chunk-Sha256` header is checked against the body.
 pub async fn upload_chunk(
     State(state): State<AppState>,
     auth: AuthUser,
     Path((upload_id, chunk_n)): Path<(Uuid, i32)>,
+    headers: HeaderMap,
     body: Bytes,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    let checksum = headers
+        .get("x-chunk-sha256")
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim);
+
     state
         .upload_service
-        .upload_chunk(&auth, upload_id, chunk_n, body)
+        .upload_chunk(&auth, upload_id, chunk_n, body, checksum)
         .await?;
 
     Ok(Json(
@@ -240,19 +525,92 @@ pub async fn upload_chunk(
 }
 
 /// POST /api/files/upload/:id/complete
+///
+/// Takes the same `on_conflict` and `expires_at` choices as a simple
+/// upload.
 pub async fn complete_chunked_upload(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(upload_id): Path<Uuid>,
+    OnConflict(on_conflict): OnConflict,
+    ExpiresAt(expires_at): ExpiresAt,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let file = state
+    let mut file = state
         .upload_service
-        .complete_chunked_upload(&auth, upload_id)
+        .complete_chunked_upload(&auth, upload_id, on_conflict)
         .await?;
+    if expires_at.is_some() {
+        file = state
+            .file_service
+            .set_expiry(&auth, file.id, expires_at)
+            .await?;
+    }
 
     Ok(Json(serde_json::json!({ "success": true, "data": file })))
 }
 
+/// POST /api/files/upload/presign — signed URL to upload straight to storage
+pub async fn presign_upload(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    OnConflict(on_conflict): OnConflict,
+    Json(req): Json<PresignUploadRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let result = state
+        .upload_service
+        .presign_upload(
+            &auth,
+            DirectUploadRequest {
+                folder_id: req.folder_id,
+                file_name: req.file_name,
+                file_size: req.file_size,
+                mime_type: req.mime_type,
+                on_conflict,
+            },
+        )
+        .await?;
+
+    Ok(Json(serde_json::json!({ "success": true, "data": result })))
+}
+
+/// POST /api/files/upload/:id/finalize
+///
+/// Takes the same `expires_at` choice as a simple upload.
+pub async fn finalize_direct_upload(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(upload_id): Path<Uuid>,
+    OnConflict(on_conflict): OnConflict,
+    ExpiresAt(expires_at): ExpiresAt,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let mut file = state
+        .upload_service
+        .finalize_direct_upload(&auth, upload_id, on_conflict)
+        .await?;
+    if expires_at.is_some() {
+        file = state
+            .file_service
+            .set_expiry(&auth, file.id, expires_at)
+            .await?;
+    }
+
+    Ok(Json(serde_json::json!({ "success": true, "data": file })))
+}
+
+/// GET /api/files/:id/download/presign — signed URL to download straight
+/// from storage
+pub async fn presign_download(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let request = state.download_service.presign_download(&auth, id).await?;
+
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": request }),
+    ))
+}
+
 /// PUT /api/files/:id
 pub async fn update_file(
     State(state): State<AppState>,
@@ -301,6 +659,7 @@ pub async fn copy_file(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(id): Path<Uuid>,
+    OnConflict(on_conflict): OnConflict,
     Json(req): Json<CopyFileRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     let file = state
@@ -311,6 +670,7 @@ pub async fn copy_file(
             filehub_service::file::service::CopyFileRequest {
                 target_folder_id: req.target_folder_id,
                 new_name: req.new_name,
+                on_conflict,
             },
         )
         .await?;
@@ -318,6 +678,33 @@ pub async fn copy_file(
     Ok(Json(serde_json::json!({ "success": true, "data": file })))
 }
 
+/// PUT /api/files/:id/expiry — set when the file is deleted
+/// automatically; a `null` `expires_at` clears it
+pub async fn set_file_expiry(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SetFileExpiryRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let file = state
+        .file_service
+        .set_expiry(&auth, id, req.expires_at)
+        .await?;
+
+    Ok(Json(serde_json::json!({ "success": true, "data": file })))
+}
+
+/// DELETE /api/files/:id/expiry
+pub async fn clear_file_expiry(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let file = state.file_service.set_expiry(&auth, id, None).await?;
+
+    Ok(Json(serde_json::json!({ "success": true, "data": file })))
+}
+
 /// DELETE /api/files/:id
 pub async fn delete_file(
     State(state): State<AppState>,
@@ -330,6 +717,19 @@ pub async fn delete_file(
     ))
 }
 
+/// POST /api/files/bulk
+pub async fn bulk_operation(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(req): Json<BulkFileRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let result = state
+        .file_service
+        .bulk_operation(&auth, req.file_ids, req.action)
+        .await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": result })))
+}
+
 /// POST /api/files/:id/lock
 pub async fn lock_file(
     State(state): State<AppState>,
@@ -1,17 +1,24 @@
 //! Folder CRUD and tree handlers.
 
 use axum::Json;
+use axum::body::Body;
 use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::Response;
+use bytes::Bytes;
 use filehub_core::types::PageRequest;
+use filehub_realtime::channel::types::ChannelType;
+use futures::StreamExt;
 use uuid::Uuid;
 
 use filehub_core::error::AppError;
+use filehub_entity::folder::{FolderMetadata, UpdateFolderPreferences};
 use filehub_service::folder::service::{
     CreateFolderRequest as SvcCreateFolder, MoveFolderRequest as SvcMoveFolder,
 };
 
 use crate::dto::request::CreateFolderRequest;
-use crate::extractors::AuthUser;
+use crate::extractors::{AuthUser, SortParams};
 use crate::state::AppState;
 
 /// GET /api/folders?storage_id=...
@@ -46,19 +53,119 @@ pub async fn get_folder(
     Ok(Json(serde_json::json!({ "success": true, "data": folder })))
 }
 
+/// GET /api/folders/:id/viewers — online users with the folder open,
+/// limited to those who may view it
+pub async fn list_viewers(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let folder = state.folder_service.get_folder(&auth, id).await?;
+    let mut viewers = Vec::new();
+    for viewer in state
+        .realtime
+        .active_viewers(&ChannelType::Folder(id))
+        .await
+    {
+        if state
+            .folder_service
+            .can_view(&folder, viewer.user_id, &viewer.role)
+            .await
+        {
+            viewers.push(viewer);
+        }
+    }
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": viewers }),
+    ))
+}
+
 /// GET /api/folders/:id/children
 pub async fn list_children(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(id): Path<Uuid>,
     Query(page): Query<PageRequest>,
+    Query(sort): Query<SortParams>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let children = state.folder_service.list_children(&auth, id, page).await?;
+    let children = state
+        .folder_service
+        .list_children(&auth, id, page, sort.folder_sort()?)
+        .await?;
     Ok(Json(
         serde_json::json!({ "success": true, "data": children }),
     ))
 }
 
+/// GET /api/folders/:id/metadata
+pub async fn get_folder_metadata(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let metadata = state.folder_service.get_metadata(&auth, id).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": metadata }),
+    ))
+}
+
+/// PUT /api/folders/:id/metadata — replace the color label, icon and
+/// pinned flag
+pub async fn set_folder_metadata(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(metadata): Json<FolderMetadata>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let folder = state
+        .folder_service
+        .set_metadata(&auth, id, metadata)
+        .await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": folder })))
+}
+
+/// GET /api/folders/:id/preferences — the caller's saved view of the
+/// folder, `null` if none
+pub async fn get_folder_preferences(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let preferences = state.folder_service.get_preferences(&auth, id).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": preferences }),
+    ))
+}
+
+/// PUT /api/folders/:id/preferences — save the caller's sort order and
+/// view mode; omitted fields keep their saved value
+pub async fn set_folder_preferences(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(update): Json<UpdateFolderPreferences>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let preferences = state
+        .folder_service
+        .set_preferences(&auth, id, update)
+        .await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": preferences }),
+    ))
+}
+
+/// DELETE /api/folders/:id/preferences
+pub async fn clear_folder_preferences(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let cleared = state.folder_service.clear_preferences(&auth, id).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": { "cleared": cleared } }),
+    ))
+}
+
 /// GET /api/folders/:id/tree
 pub async fn get_tree(
     State(state): State<AppState>,
@@ -69,6 +176,28 @@ pub async fn get_tree(
     Ok(Json(serde_json::json!({ "success": true, "data": tree })))
 }
 
+/// GET /api/folders/:id/tree/levels — the tree as newline-delimited JSON,
+/// one array of folders per level, for trees too large for `/tree`
+pub async fn get_tree_levels(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    let levels = state.tree_service.tree_levels(&auth, id).await?;
+    let body = levels.map(|level| {
+        let mut line = serde_json::to_vec(&level?)
+            .map_err(|e| AppError::internal(format!("Failed to encode tree level: {e}")))?;
+        line.push(b'\n');
+        Ok::<_, AppError>(Bytes::from(line))
+    });
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/x-ndjson")
+        .header(header::CACHE_CONTROL, "no-store")
+        .body(Body::from_stream(body))
+        .map_err(|e| AppError::internal(format!("Response build failed: {e}")))
+}
+
 /// POST /api/folders
 pub async fn create_folder(
     State(state): State<AppState>,
@@ -138,3 +267,22 @@ pub async fn delete_folder(
         serde_json::json!({ "success": true, "data": { "message": "Folder deleted" } }),
     ))
 }
+
+/// GET /api/folders/:id/download — the folder and its contents as a ZIP
+pub async fn download_folder(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    let archive = state.download_service.zip_folder(&auth, id).await?;
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/zip")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", archive.filename),
+        )
+        .header(header::CACHE_CONTROL, "no-store")
+        .body(Body::from_stream(archive.stream))
+        .map_err(|e| AppError::internal(format!("Response build failed: {e}")))
+}
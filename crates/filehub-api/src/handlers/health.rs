@@ -1,33 +1,217 @@
 //! Health check handlers.
 
+use std::time::Duration;
+
 use axum::Json;
 use axum::extract::State;
+use axum::http::StatusCode;
+use chrono::Utc;
+
+use filehub_core::error::{AppError, ErrorKind};
+use filehub_database::connection::PoolStats;
+use filehub_storage::manager::ProviderHealth;
 
 use crate::dto::response::{ApiResponse, DetailedHealthResponse, HealthResponse};
+use crate::health::{CheckOutcome, HealthReport};
 use crate::state::AppState;
 
 /// GET /api/health
-pub async fn health() -> Json<ApiResponse<HealthResponse>> {
+///
+/// Status of every dependency; `503` if the instance is not ready.
+pub async fn health(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<ApiResponse<HealthReport>>) {
+    report_response(run_checks(&state, false).await)
+}
+
+/// GET /api/health/live
+///
+/// Liveness probe: answers as long as the server can serve requests at
+/// all, without touching any dependency.
+pub async fn health_live(State(state): State<AppState>) -> Json<ApiResponse<HealthResponse>> {
     Json(ApiResponse::ok(HealthResponse {
         status: "ok".to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
-        uptime_seconds: 0, // Would come from a global start time
+        uptime_seconds: state.health.uptime_seconds(),
     }))
 }
 
+/// GET /api/health/ready
+///
+/// Readiness probe: runs only the critical checks (database and
+/// migrations); `503` until they pass.
+pub async fn health_ready(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<ApiResponse<HealthReport>>) {
+    report_response(run_checks(&state, true).await)
+}
+
+fn report_response(report: HealthReport) -> (StatusCode, Json<ApiResponse<HealthReport>>) {
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(ApiResponse::ok(report)))
+}
+
+/// Runs the critical checks and, unless `critical_only`, the others,
+/// all concurrently.
+async fn run_checks(state: &AppState, critical_only: bool) -> HealthReport {
+    let (timeout, backlog) = {
+        let config = state.live_config.borrow();
+        (
+            Duration::from_millis(config.server.health.check_timeout_ms),
+            config.server.health.worker_backlog_seconds,
+        )
+    };
+    let monitor = &state.health;
+
+    let (database, migrations) = tokio::join!(
+        monitor.check("database", true, timeout, check_database(state)),
+        monitor.check("migrations", true, timeout, check_migrations(state)),
+    );
+    let mut checks = vec![database, migrations];
+
+    if !critical_only {
+        let (cache, storage, realtime, worker, license) = tokio::join!(
+            monitor.check("cache", false, timeout, check_cache(state)),
+            monitor.check("storage", false, timeout, check_storage(state)),
+            monitor.check("realtime", false, timeout, check_realtime(state)),
+            monitor.check("worker", false, timeout, check_worker(state, backlog)),
+            monitor.check("license", false, timeout, check_license(state)),
+        );
+        checks.extend([cache, storage, realtime, worker, license]);
+    }
+
+    HealthReport::new(checks, monitor.uptime_seconds()).with_draining(state.drain.is_draining())
+}
+
+async fn check_database(state: &AppState) -> Result<CheckOutcome, AppError> {
+    sqlx::query_scalar::<_, i32>("SELECT 1")
+        .fetch_one(&state.db_pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Database unreachable", e))?;
+    let pool = PoolStats::of(&state.db_pool);
+    Ok(CheckOutcome::up(format!(
+        "{} of {} connections, {} idle, {} waiting",
+        pool.size, pool.max, pool.idle, pool.waiting
+    )))
+}
+
+async fn check_migrations(state: &AppState) -> Result<CheckOutcome, AppError> {
+    let pending = filehub_database::migration::pending_migrations(&state.db_pool).await?;
+    Ok(match pending.len() {
+        0 => CheckOutcome::up(None),
+        n => CheckOutcome::down(format!("{n} migrations pending")),
+    })
+}
+
+async fn check_cache(state: &AppState) -> Result<CheckOutcome, AppError> {
+    // Straight to the backend: the manager itself hides failures
+    // behind its circuit breaker.
+    state.cache.provider().exists("filehub:health").await?;
+    Ok(if state.cache.circuit_breaker().is_open() {
+        CheckOutcome::degraded("Circuit breaker open; serving uncached")
+    } else {
+        CheckOutcome::up(None)
+    })
+}
+
+async fn check_storage(state: &AppState) -> Result<CheckOutcome, AppError> {
+    let report = state.storage_manager.health_report().await;
+    let failing: Vec<String> = report
+        .iter()
+        .filter(|h| !h.healthy)
+        .map(|h| match &h.error {
+            Some(error) => format!("{}: {}", h.storage_id, error),
+            None => h.storage_id.to_string(),
+        })
+        .collect();
+    Ok(if failing.is_empty() {
+        CheckOutcome::up(format!("{} providers", report.len()))
+    } else if failing.len() == report.len() {
+        CheckOutcome::down(failing.join("; "))
+    } else {
+        CheckOutcome::degraded(failing.join("; "))
+    })
+}
+
+async fn check_realtime(state: &AppState) -> Result<CheckOutcome, AppError> {
+    let connections = &state.realtime.connections;
+    Ok(CheckOutcome::up(format!(
+        "{} connections, {} users",
+        connections.total_connections(),
+        connections.unique_users()
+    )))
+}
+
+async fn check_worker(state: &AppState, backlog_seconds: u64) -> Result<CheckOutcome, AppError> {
+    let Some(due_since) = state.job_repo.oldest_due_pending().await? else {
+        return Ok(CheckOutcome::up("No jobs waiting".to_string()));
+    };
+    let waiting = (Utc::now() - due_since).num_seconds().max(0);
+    Ok(if waiting as u64 > backlog_seconds {
+        CheckOutcome::degraded(format!("Oldest due job has waited {waiting}s"))
+    } else {
+        CheckOutcome::up(format!("Oldest due job has waited {waiting}s"))
+    })
+}
+
+async fn check_license(state: &AppState) -> Result<CheckOutcome, AppError> {
+    let Some(manager) = &state.license_manager else {
+        return Ok(CheckOutcome::up("Not configured".to_string()));
+    };
+    // Without the license server only CAD features stop working.
+    Ok(match manager.pool_status().await {
+        Ok(pool) => CheckOutcome::up(format!(
+            "{}/{} seats available",
+            pool.available, pool.total_seats
+        )),
+        Err(e) => CheckOutcome::degraded(e.to_string()),
+    })
+}
+
 /// GET /api/health/detailed
 pub async fn health_detailed(
     State(state): State<AppState>,
 ) -> Json<ApiResponse<DetailedHealthResponse>> {
     let ws_connections = state.realtime.connections.total_connections();
     let online_users = state.realtime.connections.unique_users();
+    let storage = if state
+        .storage_manager
+        .health_report()
+        .await
+        .iter()
+        .all(|h| h.healthy)
+    {
+        "available"
+    } else {
+        "degraded"
+    };
 
     Json(ApiResponse::ok(DetailedHealthResponse {
         status: "ok".to_string(),
         database: "connected".to_string(),
+        database_pool: PoolStats::of(&state.db_pool),
         cache: "connected".to_string(),
-        storage: "available".to_string(),
+        storage: storage.to_string(),
         ws_connections,
         online_users,
     }))
 }
+
+/// GET /api/health/storage
+///
+/// Last probe result of every storage provider; 503 if any is unhealthy.
+pub async fn health_storage(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<ApiResponse<Vec<ProviderHealth>>>) {
+    let report = state.storage_manager.health_report().await;
+    let status = if report.iter().all(|h| h.healthy) {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(ApiResponse::ok(report)))
+}
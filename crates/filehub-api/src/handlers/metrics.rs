@@ -0,0 +1,21 @@
+//! Prometheus metrics handler.
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{StatusCode, header};
+use axum::response::Response;
+
+use filehub_core::error::AppError;
+
+use crate::state::AppState;
+
+/// GET /metrics — Prometheus text exposition format
+pub async fn metrics(State(state): State<AppState>) -> Result<Response, AppError> {
+    let body = state.metrics.render(&state).await?;
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)
+        .body(Body::from(body))
+        .map_err(|e| AppError::internal(format!("Response build failed: {e}")))
+}
@@ -2,13 +2,17 @@
 
 pub mod admin;
 pub mod auth;
+pub mod capabilities;
 pub mod file;
 pub mod folder;
 pub mod health;
+pub mod metrics;
 pub mod notification;
 pub mod permission;
+pub mod public;
 pub mod search;
 pub mod share;
 pub mod storage;
+pub mod tag;
 pub mod user;
 pub mod ws;
@@ -5,8 +5,9 @@ use axum::extract::{Path, State};
 use uuid::Uuid;
 
 use filehub_core::error::AppError;
+use filehub_entity::notification::preference::EventTypePreference;
 
-use crate::dto::request::UpdatePreferencesRequest;
+use crate::dto::request::{EventTypePreferenceRequest, UpdatePreferencesRequest};
 use crate::dto::response::{ApiResponse, CountResponse};
 use crate::extractors::{AuthUser, PaginationParams};
 use crate::state::AppState;
@@ -90,3 +91,35 @@ pub async fn update_preferences(
         .await?;
     Ok(Json(serde_json::json!({ "success": true, "data": prefs })))
 }
+
+/// PUT /api/notifications/preferences/event-types/:event_type
+pub async fn set_event_type_preference(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(event_type): Path<String>,
+    Json(req): Json<EventTypePreferenceRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let preference = EventTypePreference {
+        realtime: req.realtime,
+        stored: req.stored,
+        email: req.email,
+    };
+    let prefs = state
+        .notification_service
+        .set_event_type_preference(&auth, &event_type, Some(preference))
+        .await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": prefs })))
+}
+
+/// DELETE /api/notifications/preferences/event-types/:event_type
+pub async fn reset_event_type_preference(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(event_type): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let prefs = state
+        .notification_service
+        .set_event_type_preference(&auth, &event_type, None)
+        .await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": prefs })))
+}
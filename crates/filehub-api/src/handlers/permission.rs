@@ -7,7 +7,7 @@ use uuid::Uuid;
 use filehub_core::error::AppError;
 use filehub_entity::permission::{AclInheritance, AclPermission, ResourceType};
 
-use crate::dto::request::CreateAclEntryRequest;
+use crate::dto::request::{CreateAclEntryRequest, RecursiveGrantRequest};
 use crate::extractors::AuthUser;
 use crate::state::AppState;
 
@@ -106,6 +106,46 @@ pub async fn remove_permission(
     ))
 }
 
+/// POST /api/permissions/folder/:id/recursive
+pub async fn grant_recursive(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(folder_id): Path<Uuid>,
+    Json(req): Json<RecursiveGrantRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let permission = parse_permission(&req.permission)?;
+
+    let report = state
+        .permission_service
+        .grant_recursive(
+            &auth,
+            folder_id,
+            filehub_service::permission::service::RecursiveGrantRequest {
+                user_id: req.user_id,
+                is_anyone: req.is_anyone,
+                permission,
+                expires_at: req.expires_at,
+                explicit: req.explicit,
+            },
+        )
+        .await?;
+
+    Ok(Json(serde_json::json!({ "success": true, "data": report })))
+}
+
+/// POST /api/permissions/folder/:id/recompute
+pub async fn recompute_effective(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(folder_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let report = state
+        .permission_service
+        .recompute_effective(&auth, folder_id)
+        .await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": report })))
+}
+
 fn parse_resource_type(s: &str) -> Result<ResourceType, AppError> {
     match s {
         "file" => Ok(ResourceType::File),
@@ -0,0 +1,142 @@
+//! Public access handlers — anonymous reads of public content, and marking
+//! content public.
+
+use axum::Json;
+use axum::body::Body;
+use axum::extract::{Path, Query, State};
+use axum::http::{StatusCode, header};
+use axum::response::Response;
+use uuid::Uuid;
+
+use filehub_core::error::AppError;
+use filehub_core::types::PageRequest;
+use filehub_entity::permission::ResourceType;
+
+use crate::extractors::{AnonymousUser, AuthUser, SortParams};
+use crate::state::AppState;
+
+/// GET /api/public/folders/:id
+pub async fn get_folder(
+    State(state): State<AppState>,
+    anon: AnonymousUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let folder = state.public_access_service.get_folder(&anon, id).await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": folder })))
+}
+
+/// GET /api/public/folders/:id/children
+pub async fn list_children(
+    State(state): State<AppState>,
+    anon: AnonymousUser,
+    Path(id): Path<Uuid>,
+    Query(page): Query<PageRequest>,
+    Query(sort): Query<SortParams>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let children = state
+        .public_access_service
+        .list_folders(&anon, id, page, sort.folder_sort()?)
+        .await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": children }),
+    ))
+}
+
+/// GET /api/public/folders/:id/files
+pub async fn list_files(
+    State(state): State<AppState>,
+    anon: AnonymousUser,
+    Path(id): Path<Uuid>,
+    Query(page): Query<PageRequest>,
+    Query(sort): Query<SortParams>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let files = state
+        .public_access_service
+        .list_files(&anon, id, page, sort.folder_sort()?)
+        .await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": files })))
+}
+
+/// GET /api/public/files/:id
+pub async fn get_file(
+    State(state): State<AppState>,
+    anon: AnonymousUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let file = state.public_access_service.get_file(&anon, id).await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": file })))
+}
+
+/// GET /api/public/files/:id/download
+pub async fn download_file(
+    State(state): State<AppState>,
+    anon: AnonymousUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    let result = state.public_access_service.download(&anon, id).await?;
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, result.content_type)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", result.filename),
+        )
+        .header(header::CONTENT_LENGTH, result.data.len())
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(Body::from(result.data))
+        .map_err(|e| AppError::internal(format!("Response build failed: {e}")))
+}
+
+/// PUT /api/files/:id/public — make a file public
+pub async fn publish_file(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    set_public(&state, &auth, ResourceType::File, id, true).await
+}
+
+/// DELETE /api/files/:id/public — stop a file being public
+pub async fn unpublish_file(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    set_public(&state, &auth, ResourceType::File, id, false).await
+}
+
+/// PUT /api/folders/:id/public — make a folder and its contents public
+pub async fn publish_folder(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    set_public(&state, &auth, ResourceType::Folder, id, true).await
+}
+
+/// DELETE /api/folders/:id/public — stop a folder being public
+pub async fn unpublish_folder(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    set_public(&state, &auth, ResourceType::Folder, id, false).await
+}
+
+async fn set_public(
+    state: &AppState,
+    auth: &AuthUser,
+    resource_type: ResourceType,
+    id: Uuid,
+    public: bool,
+) -> Result<Json<serde_json::Value>, AppError> {
+    state
+        .public_access_service
+        .set_public(auth, resource_type, id, public)
+        .await?;
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "data": { "id": id, "public": public }
+    })))
+}
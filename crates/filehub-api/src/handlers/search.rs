@@ -1,12 +1,18 @@
-//! File search handler.
+//! File search handlers.
 
 use axum::Json;
-use axum::extract::{Query, State};
+use axum::body::Body;
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::Response;
+use serde::Deserialize;
+use uuid::Uuid;
 
 use filehub_core::error::AppError;
-use filehub_service::file::search::SearchRequest;
+use filehub_service::file::FileExportFormat;
+use filehub_service::file::search::{FileExportRequest, SearchRequest};
 
-use crate::dto::request::SearchFilesRequest;
+use crate::dto::request::{SaveSearchRequest, SearchFilesRequest};
 use crate::extractors::{AuthUser, PaginationParams};
 use crate::state::AppState;
 
@@ -16,11 +22,102 @@ pub async fn search_files(
     auth: AuthUser,
     Query(params): Query<PaginationParams>,
     Query(req): Query<SearchFilesRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    run_search(&state, &auth, params, req).await
+}
+
+/// POST /api/files/search — search with a structured filter tree in the body.
+pub async fn search_files_advanced(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(params): Query<PaginationParams>,
+    Json(req): Json<SearchFilesRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    run_search(&state, &auth, params, req).await
+}
+
+/// Query parameters of an export besides the filters.
+#[derive(Debug, Deserialize)]
+pub struct ExportParams {
+    /// `csv` (default) or `ndjson`.
+    pub format: Option<String>,
+}
+
+/// GET /api/files/export?format=csv|ndjson
+///
+/// Accepts the same filters as [`search_files`], none of them required,
+/// and streams every matching file the caller can view.
+pub async fn export_files(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(params): Query<ExportParams>,
+    Query(req): Query<SearchFilesRequest>,
+) -> Result<Response, AppError> {
+    run_export(&state, &auth, params, req)
+}
+
+/// POST /api/files/export — export with a structured filter tree in the body.
+pub async fn export_files_advanced(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(params): Query<ExportParams>,
+    Json(req): Json<SearchFilesRequest>,
+) -> Result<Response, AppError> {
+    run_export(&state, &auth, params, req)
+}
+
+fn run_export(
+    state: &AppState,
+    auth: &AuthUser,
+    params: ExportParams,
+    req: SearchFilesRequest,
+) -> Result<Response, AppError> {
+    let format: FileExportFormat = params
+        .format
+        .as_deref()
+        .map(str::parse)
+        .transpose()?
+        .unwrap_or(FileExportFormat::Csv);
+    let query = Some(req.query).filter(|q| !q.trim().is_empty());
+    let stream = state.search_service.export(
+        auth,
+        FileExportRequest {
+            query,
+            folder_id: req.folder_id,
+            storage_id: req.storage_id,
+            mime_type: req.mime_type,
+            owner_id: req.owner_id,
+            min_size: req.min_size,
+            max_size: req.max_size,
+            filter: req.filter,
+        },
+        format,
+    )?;
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, format.content_type())
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"files-export.{}\"",
+                format.extension()
+            ),
+        )
+        .header(header::CACHE_CONTROL, "no-store")
+        .body(Body::from_stream(stream))
+        .map_err(|e| AppError::internal(format!("Response build failed: {e}")))
+}
+
+async fn run_search(
+    state: &AppState,
+    auth: &AuthUser,
+    params: PaginationParams,
+    req: SearchFilesRequest,
 ) -> Result<Json<serde_json::Value>, AppError> {
     let result = state
         .search_service
         .search(
-            &auth,
+            auth,
             SearchRequest {
                 query: req.query,
                 folder_id: req.folder_id,
@@ -29,6 +126,7 @@ pub async fn search_files(
                 owner_id: req.owner_id,
                 min_size: req.min_size,
                 max_size: req.max_size,
+                filter: req.filter,
             },
             params.into_page_request(),
         )
@@ -36,3 +134,51 @@ pub async fn search_files(
 
     Ok(Json(serde_json::json!({ "success": true, "data": result })))
 }
+
+/// GET /api/files/search/saved
+pub async fn list_saved(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let saved = state.search_service.list_saved(&auth).await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": saved })))
+}
+
+/// POST /api/files/search/saved
+pub async fn save_search(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(req): Json<SaveSearchRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let saved = state
+        .search_service
+        .save_search(&auth, &req.name, &req.query, req.filter)
+        .await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": saved })))
+}
+
+/// DELETE /api/files/search/saved/:id
+pub async fn delete_saved(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    state.search_service.delete_saved(&auth, id).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": { "message": "Saved search deleted" } }),
+    ))
+}
+
+/// GET /api/files/search/saved/:id/results
+pub async fn run_saved(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Query(params): Query<PaginationParams>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let result = state
+        .search_service
+        .run_saved(&auth, id, params.into_page_request())
+        .await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": result })))
+}
@@ -1,13 +1,20 @@
 //! Share CRUD and public access handlers.
 
 use axum::Json;
+use axum::body::Body;
 use axum::extract::{Path, Query, State};
+use axum::http::{StatusCode, header};
+use axum::response::Response;
 use uuid::Uuid;
 
 use filehub_core::error::AppError;
+use filehub_service::share::access::Visitor;
 
-use crate::dto::request::{CreateShareRequest, ShareVerifyRequest, UpdateShareRequest};
-use crate::extractors::{AuthUser, PaginationParams};
+use crate::dto::request::{
+    AccessRequestQuery, CreateShareRequest, RequestAccessRequest, ShareDownloadQuery,
+    ShareVerifyRequest, UpdateShareRequest,
+};
+use crate::extractors::{AuthUser, ClientInfo, PaginationParams};
 use crate::state::AppState;
 
 /// GET /api/shares
@@ -108,26 +115,170 @@ pub async fn revoke_share(
     ))
 }
 
+/// GET /api/shares/:id/stats
+pub async fn share_stats(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let stats = state.access_service.share_stats(&auth, id).await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": stats })))
+}
+
+/// POST /api/access-requests — ask the owner of a resource for more access
+pub async fn request_access(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(req): Json<RequestAccessRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let request = state
+        .access_request_service
+        .request_access(
+            &auth,
+            filehub_service::share::request::RequestAccess {
+                resource_type: parse_resource_type(&req.resource_type)?,
+                resource_id: req.resource_id,
+                permission: parse_acl_permission(&req.permission)?,
+                message: req.message,
+                share_token: req.share_token,
+            },
+        )
+        .await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": request }),
+    ))
+}
+
+/// GET /api/access-requests — requests made to the current user
+pub async fn list_access_requests(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(query): Query<AccessRequestQuery>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let requests = state
+        .access_request_service
+        .incoming(&auth, query.pending)
+        .await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": requests }),
+    ))
+}
+
+/// GET /api/access-requests/mine — requests made by the current user
+pub async fn my_access_requests(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let requests = state.access_request_service.outgoing(&auth).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": requests }),
+    ))
+}
+
+/// POST /api/access-requests/:id/approve
+pub async fn approve_access_request(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let request = state.access_request_service.decide(&auth, id, true).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": request }),
+    ))
+}
+
+/// POST /api/access-requests/:id/deny
+pub async fn deny_access_request(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let request = state
+        .access_request_service
+        .decide(&auth, id, false)
+        .await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": request }),
+    ))
+}
+
 /// GET /api/s/:token — public share access
 pub async fn access_share(
     State(state): State<AppState>,
+    client: ClientInfo,
     Path(token): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let share = state.access_service.validate_token(&token).await?;
-    Ok(Json(serde_json::json!({ "success": true, "data": share })))
+    let ip = client.ip.to_string();
+    let share = state
+        .access_service
+        .validate_token(&token, visitor(&ip, &client))
+        .await?;
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "data": {
+            "share": share,
+            "password_required": share.password_hash.is_some(),
+        }
+    })))
 }
 
 /// POST /api/s/:token/verify — verify share password
 pub async fn verify_share(
     State(state): State<AppState>,
+    client: ClientInfo,
     Path(token): Path<String>,
     Json(req): Json<ShareVerifyRequest>,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    let ip = client.ip.to_string();
+    let unlocked = state
+        .access_service
+        .verify_password(&token, &req.password, visitor(&ip, &client))
+        .await?;
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "data": {
+            "share": unlocked.share,
+            "access_token": unlocked.access_token,
+            "expires_at": unlocked.expires_at,
+        }
+    })))
+}
+
+/// GET /api/s/:token/download — download the file behind a share link
+pub async fn download_share(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Path(token): Path<String>,
+    Query(query): Query<ShareDownloadQuery>,
+) -> Result<Response, AppError> {
+    let ip = client.ip.to_string();
     let share = state
         .access_service
-        .verify_password(&token, &req.password)
+        .authorize_download(&token, query.access_token.as_deref(), visitor(&ip, &client))
         .await?;
-    Ok(Json(serde_json::json!({ "success": true, "data": share })))
+    let result = state
+        .download_service
+        .read_shared(share.resource_id)
+        .await?;
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, result.content_type)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", result.filename),
+        )
+        .header(header::CONTENT_LENGTH, result.data.len())
+        .header(header::CACHE_CONTROL, "private, no-store")
+        .body(Body::from(result.data))
+        .map_err(|e| AppError::internal(format!("Response build failed: {e}")))
+}
+
+fn visitor<'a>(ip: &'a str, client: &'a ClientInfo) -> Visitor<'a> {
+    Visitor {
+        ip_address: ip,
+        user_agent: client.user_agent.as_deref(),
+    }
 }
 
 fn parse_share_type(s: &str) -> Result<filehub_entity::share::ShareType, AppError> {
@@ -0,0 +1,100 @@
+//! Tag handlers.
+
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use uuid::Uuid;
+
+use filehub_core::error::AppError;
+
+use crate::dto::request::TagRequest;
+use crate::extractors::{AuthUser, PaginationParams};
+use crate::state::AppState;
+
+/// GET /api/tags
+pub async fn list_tags(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let tags = state.file_service.list_tags(&auth).await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": tags })))
+}
+
+/// POST /api/tags
+pub async fn create_tag(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(req): Json<TagRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let tag = state.file_service.create_tag(&auth, &req.name).await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": tag })))
+}
+
+/// PUT /api/tags/:id
+pub async fn rename_tag(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<TagRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let tag = state.file_service.rename_tag(&auth, id, &req.name).await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": tag })))
+}
+
+/// DELETE /api/tags/:id
+pub async fn delete_tag(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    state.file_service.delete_tag(&auth, id).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": { "message": "Tag deleted" } }),
+    ))
+}
+
+/// GET /api/tags/:id/files
+pub async fn list_tagged_files(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Query(params): Query<PaginationParams>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let result = state
+        .file_service
+        .files_by_tag(&auth, id, params.into_page_request())
+        .await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": result })))
+}
+
+/// GET /api/files/:id/tags
+pub async fn list_file_tags(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let tags = state.file_service.file_tags(&auth, id).await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": tags })))
+}
+
+/// POST /api/files/:id/tags
+pub async fn add_file_tag(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(req): Json<TagRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let tag = state.file_service.add_tag(&auth, id, &req.name).await?;
+    Ok(Json(serde_json::json!({ "success": true, "data": tag })))
+}
+
+/// DELETE /api/files/:id/tags/:tag_id
+pub async fn remove_file_tag(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((id, tag_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    state.file_service.remove_tag(&auth, id, tag_id).await?;
+    Ok(Json(
+        serde_json::json!({ "success": true, "data": { "message": "Tag removed" } }),
+    ))
+}
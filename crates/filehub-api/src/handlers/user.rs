@@ -1,9 +1,14 @@
 //! User self-service handlers.
 
 use axum::Json;
-use axum::extract::State;
+use axum::extract::{Path, State};
+use uuid::Uuid;
 
 use filehub_core::error::AppError;
+use filehub_core::types::id::SessionId;
+use filehub_realtime::session_control::terminator::terminate_session_ws;
+use filehub_service::session::service::UserSessionInfo;
+use filehub_service::user::UsageReport;
 use filehub_service::user::service::UpdateProfileRequest as SvcUpdateProfile;
 
 use crate::dto::request::{ChangePasswordRequest, UpdateProfileRequest};
@@ -74,3 +79,40 @@ pub async fn change_password(
         message: "Password changed successfully".to_string(),
     })))
 }
+
+/// GET /api/users/me/sessions
+pub async fn list_sessions(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<ApiResponse<Vec<UserSessionInfo>>>, AppError> {
+    let sessions = state.session_service.list_for_user(&auth).await?;
+    Ok(Json(ApiResponse::ok(sessions)))
+}
+
+/// GET /api/users/me/usage — API quota tier and consumption
+pub async fn get_usage(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<ApiResponse<UsageReport>>, AppError> {
+    let usage = state.quota_service.usage(&auth).await?;
+    Ok(Json(ApiResponse::ok(usage)))
+}
+
+/// DELETE /api/users/me/sessions/:id
+pub async fn revoke_session(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<MessageResponse>>, AppError> {
+    state.session_service.revoke(&auth, id).await?;
+    terminate_session_ws(
+        &state.realtime.connections,
+        SessionId::from(id),
+        "Revoked by user",
+    )
+    .await;
+
+    Ok(Json(ApiResponse::ok(MessageResponse {
+        message: "Session revoked".to_string(),
+    })))
+}
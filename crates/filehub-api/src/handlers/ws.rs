@@ -1,41 +1,77 @@
 //! WebSocket upgrade handler.
 
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
 use axum::extract::ws::{Message, WebSocket};
 use axum::extract::{Query, State, WebSocketUpgrade};
 use axum::response::Response;
-use filehub_realtime::connection::authenticator::WsAuthenticator;
-use filehub_realtime::message::OutboundMessage;
+use filehub_realtime::channel::types::ChannelType;
+use filehub_realtime::connection::authenticator::{WsAuthUser, WsAuthenticator};
+use filehub_realtime::connection::handle::ConnectionId;
+use filehub_realtime::message::serializer::{Frame, WireFormat};
+use filehub_realtime::message::{InboundMessage, OutboundMessage, ProtocolVersion};
+use filehub_realtime::presence::status::PresenceStatus;
 use futures::{SinkExt, StreamExt};
 use tracing::{info, warn};
 
 use filehub_core::error::AppError;
+use filehub_service::context::RequestContext;
 
+use crate::extractors::client::ClientInfo;
 use crate::state::AppState;
 
+/// Minimum time between session activity writes for one connection.
+const ACTIVITY_WRITE_INTERVAL: Duration = Duration::from_secs(30);
+
 /// Query parameter for WebSocket authentication.
 #[derive(Debug, serde::Deserialize)]
 pub struct WsQuery {
     /// JWT access token.
     pub token: String,
+    /// Newest message schema version the client understands.
+    pub version: Option<u16>,
 }
 
-/// GET /ws?token={jwt} — WebSocket upgrade
+/// GET /ws?token={jwt}&version={n} — WebSocket upgrade
+///
+/// Messages are JSON unless the client offers the `filehub.msgpack`
+/// subprotocol. They follow the newest schema version both sides know;
+/// clients that name none get version 1.
 pub async fn ws_handler(
     State(state): State<AppState>,
     ws: WebSocketUpgrade,
     Query(query): Query<WsQuery>,
+    client: ClientInfo,
 ) -> Result<Response, AppError> {
+    // Send clients of a draining instance to another node
+    if state.drain.is_draining() {
+        return Err(AppError::service_unavailable(
+            "Server is shutting down; reconnect shortly",
+        ));
+    }
+
     // Authenticate before upgrade
     let authenticator = WsAuthenticator::new(state.jwt_decoder.clone());
     let auth_info = authenticator.authenticate(&query.token).await?;
+    let protocol = ProtocolVersion::negotiate(query.version)
+        .map_err(|e| AppError::validation(e.to_string()))?;
+
+    let ws = ws.protocols(WireFormat::PROTOCOLS);
+    let format = WireFormat::from_protocol(ws.selected_protocol().and_then(|p| p.to_str().ok()));
 
-    Ok(ws.on_upgrade(move |socket| handle_ws_connection(state, auth_info, socket)))
+    Ok(ws.on_upgrade(move |socket| {
+        handle_ws_connection(state, auth_info, client.ip, format, protocol, socket)
+    }))
 }
 
 /// Handles an established WebSocket connection.
 async fn handle_ws_connection(
     state: AppState,
-    auth: filehub_realtime::connection::authenticator::WsAuthUser,
+    auth: WsAuthUser,
+    ip: IpAddr,
+    format: WireFormat,
+    protocol: ProtocolVersion,
     socket: WebSocket,
 ) {
     let (mut ws_tx, mut ws_rx) = socket.split();
@@ -48,6 +84,7 @@ async fn handle_ws_connection(
         auth.role.clone(),
         auth.username.clone(),
         tx,
+        protocol,
     ) {
         Some(h) => h,
         None => {
@@ -57,19 +94,46 @@ async fn handle_ws_connection(
     };
 
     let conn_id = handle.id;
+    let presence_channel = ChannelType::PresenceGlobal.to_channel_name();
+    let realtime = &state.realtime;
+
+    if let Some(online) =
+        realtime
+            .presence
+            .set_online(auth.user_id.into_uuid(), conn_id, &auth.username)
+    {
+        realtime
+            .connections
+            .send_to_channel(&presence_channel, online)
+            .await;
+    }
+
+    // Broadcasts sent shortly before this connection, or still awaiting
+    // this user's acknowledgement.
+    state
+        .realtime
+        .session_monitor
+        .deliver_pending(&handle)
+        .await;
 
     info!(
         conn_id = %conn_id,
         user_id = %auth.user_id,
+        %format,
+        %protocol,
         "WebSocket connection established"
     );
 
     // Spawn outbound message forwarder
     let outbound_task = tokio::spawn(async move {
         while let Some(msg) = rx.recv().await {
-            match serde_json::to_string(&msg) {
-                Ok(text) => {
-                    if ws_tx.send(Message::Text(text.into())).await.is_err() {
+            match format.encode_message(&msg, protocol) {
+                Ok(frame) => {
+                    let message = match frame {
+                        Frame::Text(text) => Message::Text(text.into()),
+                        Frame::Binary(bytes) => Message::Binary(bytes.into()),
+                    };
+                    if ws_tx.send(message).await.is_err() {
                         break;
                     }
                 }
@@ -81,32 +145,74 @@ async fn handle_ws_connection(
     });
 
     // Process inbound messages
+    let mut activity_written: Option<Instant> = None;
     while let Some(result) = ws_rx.next().await {
-        match result {
-            Ok(Message::Text(text)) => {
-                state
-                    .realtime
-                    .connections
-                    .handle_inbound(&conn_id, &text)
-                    .await;
-            }
-            Ok(Message::Close(_)) => {
-                break;
-            }
-            Ok(Message::Ping(_)) => {
-                // Ping is handled by axum automatically
-            }
-            Ok(_) => {}
+        let frame = match result {
+            Ok(Message::Text(text)) => Frame::Text(text.to_string()),
+            Ok(Message::Binary(bytes)) => Frame::Binary(bytes.to_vec()),
+            Ok(Message::Close(_)) => break,
+            // Pings are answered by axum
+            Ok(_) => continue,
             Err(e) => {
                 warn!(conn_id = %conn_id, error = %e, "WebSocket error");
                 break;
             }
+        };
+        let unhandled = state
+            .realtime
+            .connections
+            .handle_inbound(&conn_id, &frame)
+            .await;
+        match unhandled {
+            Some(InboundMessage::Ack { message_id }) => {
+                acknowledge(&state, &auth, &message_id).await;
+            }
+            Some(InboundMessage::Subscribe { channel }) => {
+                subscribe_resource(&state, &auth, ip, conn_id, &channel).await;
+            }
+            Some(InboundMessage::PresenceUpdate { status }) => {
+                let realtime = &state.realtime;
+                if let Some(changed) = realtime.presence.update_status(
+                    auth.user_id.into_uuid(),
+                    conn_id,
+                    PresenceStatus::from_str_value(&status),
+                ) {
+                    realtime
+                        .connections
+                        .send_to_channel(&presence_channel, changed)
+                        .await;
+                }
+            }
+            _ => {}
+        }
+
+        // Any message, heartbeats included, keeps the session from
+        // idle termination.
+        if activity_written.is_none_or(|at| at.elapsed() >= ACTIVITY_WRITE_INTERVAL) {
+            activity_written = Some(Instant::now());
+            if let Err(e) = state
+                .session_manager
+                .record_activity(auth.session_id.into())
+                .await
+            {
+                warn!(conn_id = %conn_id, error = %e, "Failed to record session activity");
+            }
         }
     }
 
     // Cleanup
     outbound_task.abort();
-    state.realtime.connections.unregister(conn_id);
+    let realtime = &state.realtime;
+    realtime.connections.unregister(conn_id).await;
+    if let Some(offline) = realtime
+        .presence
+        .set_offline(auth.user_id.into_uuid(), conn_id)
+    {
+        realtime
+            .connections
+            .send_to_channel(&presence_channel, offline)
+            .await;
+    }
 
     info!(
         conn_id = %conn_id,
@@ -114,3 +220,63 @@ async fn handle_ws_connection(
         "WebSocket connection closed"
     );
 }
+
+/// Records an `ack` from a client. Broadcasts are the only messages that
+/// are acknowledged; other ids are ignored.
+async fn acknowledge(state: &AppState, auth: &WsAuthUser, message_id: &str) {
+    let Ok(broadcast_id) = message_id.parse::<uuid::Uuid>() else {
+        return;
+    };
+    if let Err(e) = state
+        .realtime
+        .session_monitor
+        .acknowledge(broadcast_id, auth.user_id.into_uuid())
+        .await
+    {
+        warn!(user_id = %auth.user_id, error = %e, "Failed to record broadcast acknowledgement");
+    }
+}
+
+/// Subscribes a connection to a file or folder channel if the user may view
+/// the file or folder; otherwise tells the client it was denied.
+async fn subscribe_resource(
+    state: &AppState,
+    auth: &WsAuthUser,
+    ip: IpAddr,
+    conn_id: ConnectionId,
+    channel: &str,
+) {
+    let ctx = RequestContext::new(
+        auth.user_id.into_uuid(),
+        auth.session_id.into_uuid(),
+        auth.role,
+        auth.username.clone(),
+        ip.to_string(),
+        None,
+    );
+    let allowed = match ChannelType::parse(channel) {
+        Some(ChannelType::File(id)) => state.file_service.get_file(&ctx, id).await.map(|_| ()),
+        Some(ChannelType::Folder(id)) => {
+            state.folder_service.get_folder(&ctx, id).await.map(|_| ())
+        }
+        _ => Err(AppError::validation("Not a file or folder channel")),
+    };
+
+    let connections = &state.realtime.connections;
+    let result = match allowed {
+        Ok(()) => connections
+            .subscribe(conn_id, channel)
+            .await
+            .map(|_| ())
+            .map_err(String::from),
+        Err(e) => Err(e.message),
+    };
+    if let Err(reason) = result {
+        warn!(conn_id = %conn_id, channel, reason, "Subscription denied");
+        let denied = OutboundMessage::SubscriptionDenied {
+            channel: channel.to_string(),
+            reason,
+        };
+        connections.send_to_connection(conn_id, denied).await;
+    }
+}
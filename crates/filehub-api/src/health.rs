@@ -0,0 +1,283 @@
+//! Dependency health checks behind `/api/health`.
+//!
+//! Every check runs under a short timeout, so a probe answers even when a
+//! dependency hangs. The overall status is the worst status of any check;
+//! the instance is ready unless a critical check is down.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use filehub_core::error::AppError;
+
+/// Status of one check, ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    /// Working.
+    Up,
+    /// Working with reduced function.
+    Degraded,
+    /// Not working.
+    Down,
+}
+
+/// What a check found.
+#[derive(Debug, Clone)]
+pub struct CheckOutcome {
+    /// Status.
+    pub status: CheckStatus,
+    /// Short description of the state, e.g. connection counts.
+    pub detail: Option<String>,
+    /// What is wrong, for anything but `Up`.
+    pub error: Option<String>,
+}
+
+impl CheckOutcome {
+    /// The dependency works.
+    pub fn up(detail: impl Into<Option<String>>) -> Self {
+        Self {
+            status: CheckStatus::Up,
+            detail: detail.into(),
+            error: None,
+        }
+    }
+
+    /// The dependency works with reduced function.
+    pub fn degraded(error: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Degraded,
+            detail: None,
+            error: Some(error.into()),
+        }
+    }
+
+    /// The dependency does not work.
+    pub fn down(error: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Down,
+            detail: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Result of one check as reported.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckReport {
+    /// Subsystem name.
+    pub name: String,
+    /// Status.
+    pub status: CheckStatus,
+    /// Whether the instance is not ready while this check is down.
+    pub critical: bool,
+    /// Time the check took.
+    pub latency_ms: u64,
+    /// Short description of the state.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Most recent failure of this check, even if it has since recovered.
+    pub last_error: Option<String>,
+    /// When `last_error` happened.
+    pub last_error_at: Option<DateTime<Utc>>,
+}
+
+/// Health of the instance and of each of its dependencies.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Worst status of any check.
+    pub status: CheckStatus,
+    /// Whether the instance should receive traffic.
+    pub ready: bool,
+    /// Whether the instance is draining before shutdown.
+    pub draining: bool,
+    /// Server version.
+    pub version: String,
+    /// Seconds since the server started.
+    pub uptime_seconds: u64,
+    /// Individual checks.
+    pub checks: Vec<CheckReport>,
+}
+
+impl HealthReport {
+    /// Derives the overall status and readiness from `checks`.
+    pub fn new(checks: Vec<CheckReport>, uptime_seconds: u64) -> Self {
+        let status = checks
+            .iter()
+            .map(|c| c.status)
+            .max()
+            .unwrap_or(CheckStatus::Up);
+        let ready = !checks
+            .iter()
+            .any(|c| c.critical && c.status == CheckStatus::Down);
+        Self {
+            status,
+            ready,
+            draining: false,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            uptime_seconds,
+            checks,
+        }
+    }
+
+    /// Marks the report as draining; a draining instance is never ready.
+    pub fn with_draining(mut self, draining: bool) -> Self {
+        self.draining = draining;
+        self.ready &= !draining;
+        self
+    }
+}
+
+/// Runs checks and remembers each check's last failure.
+#[derive(Debug)]
+pub struct HealthMonitor {
+    /// When the server started.
+    started_at: Instant,
+    /// Last failure of each check.
+    last_errors: Mutex<HashMap<String, (String, DateTime<Utc>)>>,
+}
+
+impl Default for HealthMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthMonitor {
+    /// Creates a monitor; uptime counts from now.
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            last_errors: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Seconds since the monitor was created.
+    pub fn uptime_seconds(&self) -> u64 {
+        self.started_at.elapsed().as_secs()
+    }
+
+    /// Runs `check`, counting an error or a run past `timeout` as down.
+    pub async fn check<F>(
+        &self,
+        name: &str,
+        critical: bool,
+        timeout: Duration,
+        check: F,
+    ) -> CheckReport
+    where
+        F: Future<Output = Result<CheckOutcome, AppError>>,
+    {
+        let started = Instant::now();
+        let outcome = match tokio::time::timeout(timeout, check).await {
+            Ok(Ok(outcome)) => outcome,
+            Ok(Err(e)) => CheckOutcome::down(e.to_string()),
+            Err(_) => CheckOutcome::down(format!("timed out after {} ms", timeout.as_millis())),
+        };
+        let latency_ms = started.elapsed().as_millis() as u64;
+
+        let mut last_errors = self
+            .last_errors
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(error) = &outcome.error {
+            last_errors.insert(name.to_string(), (error.clone(), Utc::now()));
+        }
+        let (last_error, last_error_at) = last_errors
+            .get(name)
+            .cloned()
+            .map_or((None, None), |(error, at)| (Some(error), Some(at)));
+
+        CheckReport {
+            name: name.to_string(),
+            status: outcome.status,
+            critical,
+            latency_ms,
+            detail: outcome.detail,
+            last_error,
+            last_error_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(status: CheckStatus, critical: bool) -> CheckReport {
+        CheckReport {
+            name: "test".to_string(),
+            status,
+            critical,
+            latency_ms: 0,
+            detail: None,
+            last_error: None,
+            last_error_at: None,
+        }
+    }
+
+    #[test]
+    fn test_overall_status_is_worst_check() {
+        let health = HealthReport::new(
+            vec![
+                report(CheckStatus::Up, true),
+                report(CheckStatus::Down, false),
+                report(CheckStatus::Degraded, true),
+            ],
+            0,
+        );
+        assert_eq!(health.status, CheckStatus::Down);
+        assert!(health.ready);
+
+        let health = HealthReport::new(vec![report(CheckStatus::Up, true)], 0);
+        assert_eq!(health.status, CheckStatus::Up);
+        assert!(health.ready);
+    }
+
+    #[test]
+    fn test_not_ready_when_critical_check_down() {
+        let health = HealthReport::new(
+            vec![
+                report(CheckStatus::Up, false),
+                report(CheckStatus::Down, true),
+            ],
+            0,
+        );
+        assert_eq!(health.status, CheckStatus::Down);
+        assert!(!health.ready);
+    }
+
+    #[test]
+    fn test_not_ready_while_draining() {
+        let health = HealthReport::new(vec![report(CheckStatus::Up, true)], 0).with_draining(true);
+        assert_eq!(health.status, CheckStatus::Up);
+        assert!(health.draining);
+        assert!(!health.ready);
+    }
+
+    #[tokio::test]
+    async fn test_check_times_out_and_keeps_last_error() {
+        let monitor = HealthMonitor::new();
+        let hung = monitor
+            .check("db", true, Duration::from_millis(10), async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(CheckOutcome::up(None))
+            })
+            .await;
+        assert_eq!(hung.status, CheckStatus::Down);
+        assert!(hung.last_error.unwrap().contains("timed out"));
+
+        let recovered = monitor
+            .check("db", true, Duration::from_millis(10), async {
+                Ok(CheckOutcome::up(None))
+            })
+            .await;
+        assert_eq!(recovered.status, CheckStatus::Up);
+        assert!(recovered.last_error.is_some());
+        assert!(recovered.last_error_at.is_some());
+    }
+}
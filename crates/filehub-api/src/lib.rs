@@ -3,13 +3,21 @@
 //! HTTP API layer for FileHub built on Axum.
 //!
 //! Provides all REST endpoints, WebSocket upgrade, middleware (auth, RBAC,
-//! rate limiting, CORS, logging), extractors, DTOs, and error mapping.
+//! rate limiting, CORS, logging, metrics), extractors, DTOs, and error mapping.
+//! With the `grpc` feature, part of the API is also served over gRPC.
 
 pub mod app;
+pub mod capabilities;
+pub mod drain;
 pub mod dto;
 pub mod extractors;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 pub mod handlers;
+pub mod health;
+pub mod metrics;
 pub mod middleware;
+pub mod reload;
 pub mod router;
 pub mod state;
 
@@ -0,0 +1,457 @@
+//! Prometheus metrics registry for the HTTP API.
+//!
+//! Request counters and latency histograms are recorded by the
+//! [`track_metrics`](crate::middleware::metrics::track_metrics) middleware.
+//! Gauges for the database pool, cache, sessions, seats, jobs, realtime
+//! connections, the event bus, and outbound HTTP destinations are
+//! refreshed from their sources on every scrape.
+
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use chrono::Utc;
+use prometheus::{
+    Encoder, Gauge, GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec,
+    Opts, Registry, TextEncoder,
+};
+
+use filehub_auth::seat::allocator::SeatAllocator;
+use filehub_core::error::AppError;
+use filehub_database::connection::PoolStats;
+use filehub_entity::job::JobStatus;
+
+use crate::state::AppState;
+
+/// Prometheus collectors exported at the metrics endpoint.
+pub struct ApiMetrics {
+    /// Registry holding every collector below.
+    registry: Registry,
+    /// Requests by method, matched route, and status.
+    http_requests: IntCounterVec,
+    /// Request latency by method and matched route.
+    http_latency: HistogramVec,
+    /// Open database connections.
+    db_pool_size: IntGauge,
+    /// Open database connections not in use.
+    db_pool_idle: IntGauge,
+    /// Database connections in use.
+    db_pool_in_use: IntGauge,
+    /// Most database connections the pool opens.
+    db_pool_max: IntGauge,
+    /// Statements waiting for a database connection (lower bound).
+    db_pool_waiting: IntGauge,
+    /// Cache lookups that returned a value.
+    cache_hits: IntGauge,
+    /// Cache lookups that found nothing.
+    cache_misses: IntGauge,
+    /// Cache hit ratio (0.0–1.0).
+    cache_hit_ratio: Gauge,
+    /// Cache operations answered in degraded mode.
+    cache_degraded: IntGauge,
+    /// 1 while the cache circuit breaker is open.
+    cache_circuit_open: IntGauge,
+    /// Times the cache circuit breaker has opened.
+    cache_circuit_trips: IntGauge,
+    /// 1 while seats are allocated from the local fallback pool.
+    seat_circuit_open: IntGauge,
+    /// Times the seat allocator circuit breaker has opened.
+    seat_circuit_trips: IntGauge,
+    /// Active sessions in the database.
+    active_sessions: IntGauge,
+    /// Total seats in the license pool.
+    seats_total: IntGauge,
+    /// Seats currently checked out.
+    seats_checked_out: IntGauge,
+    /// Seat utilization (0.0–1.0).
+    seat_utilization: Gauge,
+    /// Jobs waiting in the queue.
+    jobs_pending: IntGauge,
+    /// Jobs currently running.
+    jobs_running: IntGauge,
+    /// Uploads being assembled or run through `AfterUpload` processing.
+    uploads_processing: IntGauge,
+    /// Uploads waiting for a processing slot.
+    uploads_processing_queued: IntGauge,
+    /// Open WebSocket connections.
+    ws_connections: IntGauge,
+    /// WebSocket messages sent since startup.
+    ws_messages_sent: IntGauge,
+    /// WebSocket messages received since startup.
+    ws_messages_received: IntGauge,
+    /// Domain events recorded but not yet delivered.
+    outbox_pending: IntGauge,
+    /// Age of the oldest undelivered domain event, in seconds.
+    outbox_lag: Gauge,
+    /// Domain events dropped by lagging bus subscribers.
+    events_dropped: IntGauge,
+    /// Outbound HTTP requests by destination host.
+    outbound_requests: IntGaugeVec,
+    /// Outbound HTTP requests that failed, by destination host.
+    outbound_failures: IntGaugeVec,
+    /// Outbound HTTP retries by destination host.
+    outbound_retries: IntGaugeVec,
+    /// Outbound HTTP requests delayed by rate limiting, by destination host.
+    outbound_throttled: IntGaugeVec,
+    /// Total outbound HTTP latency in seconds by destination host.
+    outbound_latency: GaugeVec,
+    /// Storage operation retries by storage ID.
+    storage_retries: IntGaugeVec,
+    /// Storage operations that ran out of retries, by storage ID.
+    storage_retries_exhausted: IntGaugeVec,
+    /// Storage operation attempts that timed out, by storage ID.
+    storage_timeouts: IntGaugeVec,
+}
+
+impl std::fmt::Debug for ApiMetrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiMetrics").finish()
+    }
+}
+
+impl ApiMetrics {
+    /// Creates the registry and registers all collectors.
+    pub fn new() -> Result<Self, AppError> {
+        let registry = Registry::new_custom(Some("filehub".to_string()), None)
+            .map_err(|e| AppError::internal(format!("Metrics registry init failed: {e}")))?;
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests handled"),
+            &["method", "route", "status"],
+        )
+        .map_err(metric_error)?;
+        let http_latency = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency in seconds",
+            ),
+            &["method", "route"],
+        )
+        .map_err(metric_error)?;
+
+        let metrics = Self {
+            registry,
+            http_requests,
+            http_latency,
+            db_pool_size: int_gauge("db_pool_connections", "Open database connections")?,
+            db_pool_idle: int_gauge("db_pool_idle", "Open database connections not in use")?,
+            db_pool_in_use: int_gauge("db_pool_in_use", "Database connections in use")?,
+            db_pool_max: int_gauge("db_pool_max", "Most database connections the pool opens")?,
+            db_pool_waiting: int_gauge(
+                "db_pool_waiting",
+                "Statements waiting for a database connection",
+            )?,
+            cache_hits: int_gauge("cache_hits", "Cache lookups that returned a value")?,
+            cache_misses: int_gauge("cache_misses", "Cache lookups that found nothing")?,
+            cache_hit_ratio: gauge("cache_hit_ratio", "Cache hit ratio")?,
+            cache_degraded: int_gauge(
+                "cache_degraded_operations",
+                "Cache operations answered in degraded mode",
+            )?,
+            cache_circuit_open: int_gauge(
+                "cache_circuit_open",
+                "Whether the cache circuit breaker is open",
+            )?,
+            cache_circuit_trips: int_gauge(
+                "cache_circuit_trips",
+                "Times the cache circuit breaker has opened",
+            )?,
+            seat_circuit_open: int_gauge(
+                "seat_circuit_open",
+                "Whether seats are allocated from the local fallback pool",
+            )?,
+            seat_circuit_trips: int_gauge(
+                "seat_circuit_trips",
+                "Times the seat allocator circuit breaker has opened",
+            )?,
+            active_sessions: int_gauge("sessions_active", "Active sessions")?,
+            seats_total: int_gauge("seats_total", "Seats in the license pool")?,
+            seats_checked_out: int_gauge("seats_checked_out", "Seats currently checked out")?,
+            seat_utilization: gauge("seat_utilization", "Seat pool utilization")?,
+            jobs_pending: int_gauge("jobs_pending", "Jobs waiting in the queue")?,
+            jobs_running: int_gauge("jobs_running", "Jobs currently running")?,
+            uploads_processing: int_gauge(
+                "uploads_processing",
+                "Uploads being processed after they were received",
+            )?,
+            uploads_processing_queued: int_gauge(
+                "uploads_processing_queued",
+                "Uploads waiting for a processing slot",
+            )?,
+            ws_connections: int_gauge("ws_connections", "Open WebSocket connections")?,
+            ws_messages_sent: int_gauge("ws_messages_sent", "WebSocket messages sent")?,
+            ws_messages_received: int_gauge("ws_messages_received", "WebSocket messages received")?,
+            outbox_pending: int_gauge("outbox_pending", "Domain events awaiting delivery")?,
+            outbox_lag: gauge(
+                "outbox_lag_seconds",
+                "Age of the oldest domain event awaiting delivery",
+            )?,
+            events_dropped: int_gauge(
+                "events_dropped",
+                "Domain events dropped by lagging subscribers",
+            )?,
+            outbound_requests: host_gauge("outbound_http_requests", "Outbound HTTP requests")?,
+            outbound_failures: host_gauge(
+                "outbound_http_failures",
+                "Outbound HTTP requests that failed",
+            )?,
+            outbound_retries: host_gauge("outbound_http_retries", "Outbound HTTP retries")?,
+            outbound_throttled: host_gauge(
+                "outbound_http_throttled",
+                "Outbound HTTP requests delayed by rate limiting",
+            )?,
+            outbound_latency: GaugeVec::new(
+                Opts::new(
+                    "outbound_http_latency_seconds_sum",
+                    "Total outbound HTTP latency in seconds",
+                ),
+                &["host"],
+            )
+            .map_err(metric_error)?,
+            storage_retries: storage_gauge("storage_retries", "Storage operation retries")?,
+            storage_retries_exhausted: storage_gauge(
+                "storage_retries_exhausted",
+                "Storage operations that failed after all retries",
+            )?,
+            storage_timeouts: storage_gauge(
+                "storage_timeouts",
+                "Storage operation attempts that timed out",
+            )?,
+        };
+
+        metrics.register_all()?;
+        Ok(metrics)
+    }
+
+    fn register_all(&self) -> Result<(), AppError> {
+        let r = &self.registry;
+        r.register(Box::new(self.http_requests.clone()))
+            .map_err(metric_error)?;
+        r.register(Box::new(self.http_latency.clone()))
+            .map_err(metric_error)?;
+        for g in [
+            &self.db_pool_size,
+            &self.db_pool_idle,
+            &self.db_pool_in_use,
+            &self.db_pool_max,
+            &self.db_pool_waiting,
+            &self.cache_hits,
+            &self.cache_misses,
+            &self.cache_degraded,
+            &self.cache_circuit_open,
+            &self.cache_circuit_trips,
+            &self.seat_circuit_open,
+            &self.seat_circuit_trips,
+            &self.active_sessions,
+            &self.seats_total,
+            &self.seats_checked_out,
+            &self.jobs_pending,
+            &self.jobs_running,
+            &self.uploads_processing,
+            &self.uploads_processing_queued,
+            &self.ws_connections,
+            &self.ws_messages_sent,
+            &self.ws_messages_received,
+            &self.outbox_pending,
+            &self.events_dropped,
+        ] {
+            r.register(Box::new(g.clone())).map_err(metric_error)?;
+        }
+        for g in [
+            &self.cache_hit_ratio,
+            &self.seat_utilization,
+            &self.outbox_lag,
+        ] {
+            r.register(Box::new(g.clone())).map_err(metric_error)?;
+        }
+        for g in [
+            &self.outbound_requests,
+            &self.outbound_failures,
+            &self.outbound_retries,
+            &self.outbound_throttled,
+            &self.storage_retries,
+            &self.storage_retries_exhausted,
+            &self.storage_timeouts,
+        ] {
+            r.register(Box::new(g.clone())).map_err(metric_error)?;
+        }
+        r.register(Box::new(self.outbound_latency.clone()))
+            .map_err(metric_error)?;
+        Ok(())
+    }
+
+    /// Records one completed HTTP request.
+    pub fn observe_request(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+        self.http_requests
+            .with_label_values(&[method, route, &status.to_string()])
+            .inc();
+        self.http_latency
+            .with_label_values(&[method, route])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Refreshes scrape-time gauges and renders the Prometheus text format.
+    ///
+    /// A failing source is logged and leaves its gauges at the last value
+    /// rather than failing the whole scrape.
+    pub async fn render(&self, state: &AppState) -> Result<String, AppError> {
+        self.refresh(state).await;
+
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .map_err(|e| AppError::internal(format!("Metrics encoding failed: {e}")))?;
+        String::from_utf8(buffer)
+            .map_err(|e| AppError::internal(format!("Metrics encoding failed: {e}")))
+    }
+
+    async fn refresh(&self, state: &AppState) {
+        let pool = PoolStats::of(&state.db_pool);
+        self.db_pool_size.set(pool.size as i64);
+        self.db_pool_idle.set(pool.idle as i64);
+        self.db_pool_in_use.set(pool.in_use as i64);
+        self.db_pool_max.set(pool.max as i64);
+        self.db_pool_waiting.set(pool.waiting as i64);
+
+        let cache = state.cache.stats();
+        self.cache_hits
+            .set(cache.hits.load(Ordering::Relaxed) as i64);
+        self.cache_misses
+            .set(cache.misses.load(Ordering::Relaxed) as i64);
+        self.cache_hit_ratio.set(cache.hit_ratio());
+        self.cache_degraded
+            .set(cache.degraded.load(Ordering::Relaxed) as i64);
+        let breaker = state.cache.circuit_breaker();
+        self.cache_circuit_open.set(breaker.is_open() as i64);
+        self.cache_circuit_trips.set(breaker.trips() as i64);
+        if let Some(breaker) = state.seat_allocator.circuit_breaker() {
+            self.seat_circuit_open.set(breaker.is_open() as i64);
+            self.seat_circuit_trips.set(breaker.trips() as i64);
+        }
+
+        match state.session_repo.count_all_active().await {
+            Ok(count) => self.active_sessions.set(count),
+            Err(e) => tracing::warn!(error = %e, "Metrics: failed to count active sessions"),
+        }
+
+        match state.seat_allocator.pool_state().await {
+            Ok(pool) => {
+                self.seats_total.set(pool.total_seats as i64);
+                self.seats_checked_out.set(pool.checked_out as i64);
+                let utilization = if pool.total_seats == 0 {
+                    0.0
+                } else {
+                    pool.checked_out as f64 / pool.total_seats as f64
+                };
+                self.seat_utilization.set(utilization);
+            }
+            Err(e) => tracing::warn!(error = %e, "Metrics: failed to read seat pool"),
+        }
+
+        match state.job_repo.count_by_status(JobStatus::Pending).await {
+            Ok(count) => self.jobs_pending.set(count),
+            Err(e) => tracing::warn!(error = %e, "Metrics: failed to count pending jobs"),
+        }
+        match state.job_repo.count_by_status(JobStatus::Running).await {
+            Ok(count) => self.jobs_running.set(count),
+            Err(e) => tracing::warn!(error = %e, "Metrics: failed to count running jobs"),
+        }
+
+        match state.outbox_repo.backlog().await {
+            Ok(backlog) => {
+                self.outbox_pending.set(backlog.pending);
+                let lag = backlog.oldest.map_or(0.0, |oldest| {
+                    (Utc::now() - oldest).num_milliseconds().max(0) as f64 / 1000.0
+                });
+                self.outbox_lag.set(lag);
+            }
+            Err(e) => tracing::warn!(error = %e, "Metrics: failed to read outbox backlog"),
+        }
+
+        let processing = state.upload_service.processing_stats();
+        self.uploads_processing.set(processing.in_flight as i64);
+        self.uploads_processing_queued.set(processing.queued as i64);
+
+        let engine = state.realtime.metrics.snapshot();
+        self.ws_connections
+            .set(state.realtime.connections.total_connections() as i64);
+        self.ws_messages_sent.set(engine.messages_sent as i64);
+        self.ws_messages_received
+            .set(engine.messages_received as i64);
+        self.events_dropped.set(state.event_bus.dropped() as i64);
+
+        for dest in state.http_client.stats().snapshot() {
+            let host = [dest.host.as_str()];
+            self.outbound_requests
+                .with_label_values(&host)
+                .set(dest.requests as i64);
+            self.outbound_failures
+                .with_label_values(&host)
+                .set(dest.failures as i64);
+            self.outbound_retries
+                .with_label_values(&host)
+                .set(dest.retries as i64);
+            self.outbound_throttled
+                .with_label_values(&host)
+                .set(dest.throttled as i64);
+            self.outbound_latency
+                .with_label_values(&host)
+                .set(dest.latency_seconds);
+        }
+
+        for (storage_id, retries) in state.storage_manager.retry_report().await {
+            let storage_id = storage_id.to_string();
+            let label = [storage_id.as_str()];
+            self.storage_retries
+                .with_label_values(&label)
+                .set(retries.retries as i64);
+            self.storage_retries_exhausted
+                .with_label_values(&label)
+                .set(retries.exhausted as i64);
+            self.storage_timeouts
+                .with_label_values(&label)
+                .set(retries.timeouts as i64);
+        }
+    }
+}
+
+fn int_gauge(name: &str, help: &str) -> Result<IntGauge, AppError> {
+    IntGauge::new(name, help).map_err(metric_error)
+}
+
+fn gauge(name: &str, help: &str) -> Result<Gauge, AppError> {
+    Gauge::new(name, help).map_err(metric_error)
+}
+
+fn host_gauge(name: &str, help: &str) -> Result<IntGaugeVec, AppError> {
+    IntGaugeVec::new(Opts::new(name, help), &["host"]).map_err(metric_error)
+}
+
+fn storage_gauge(name: &str, help: &str) -> Result<IntGaugeVec, AppError> {
+    IntGaugeVec::new(Opts::new(name, help), &["storage_id"]).map_err(metric_error)
+}
+
+fn metric_error(e: prometheus::Error) -> AppError {
+    AppError::internal(format!("Metric registration failed: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_metrics_are_exported() {
+        let metrics = ApiMetrics::new().expect("collectors register without conflicts");
+        metrics.observe_request("GET", "/api/files/{id}", 200, Duration::from_millis(12));
+
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&metrics.registry.gather(), &mut buffer)
+            .unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+
+        assert!(text.contains(
+            "filehub_http_requests_total{method=\"GET\",route=\"/api/files/{id}\",status=\"200\"} 1"
+        ));
+        assert!(text.contains("filehub_http_request_duration_seconds_count"));
+    }
+}
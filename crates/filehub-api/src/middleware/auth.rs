@@ -1,13 +1,20 @@
 //! JWT authentication middleware (tower layer).
 
 // Authentication is handled via the `AuthUser` extractor.
-// This module provides a tower layer for routes that need blanket auth.
+// This module provides a tower layer for routes that need blanket auth,
+// and the layer that decodes the bearer token once for everything after it.
 
 use axum::body::Body;
-use axum::http::{Request, StatusCode};
+use axum::extract::State;
+use axum::http::{Extensions, Request, StatusCode, header};
 use axum::middleware::Next;
 use axum::response::Response;
 
+use filehub_auth::jwt::Claims;
+use filehub_core::error::{AppError, ErrorKind};
+
+use crate::state::AppState;
+
 /// Middleware that rejects requests without a valid Authorization header.
 ///
 /// This is a lightweight check — full validation happens in the `AuthUser` extractor.
@@ -25,3 +32,107 @@ pub async fn require_auth(request: Request<Body>, next: Next) -> Result<Response
 
     Ok(next.run(request).await)
 }
+
+/// Outcome of decoding a request's bearer token, stored in the request
+/// extensions by [`bearer_claims`].
+#[derive(Debug, Clone)]
+pub enum BearerClaims {
+    /// A valid access token.
+    Valid(Claims),
+    /// A token was presented but refused.
+    Rejected {
+        /// Kind of the refusal.
+        kind: ErrorKind,
+        /// Error code of the refusal.
+        code: &'static str,
+        /// Why the token was refused.
+        message: String,
+    },
+}
+
+impl BearerClaims {
+    /// The claims of the request's access token, if it carried a valid one.
+    pub fn valid(extensions: &Extensions) -> Option<&Claims> {
+        match extensions.get::<Self>() {
+            Some(Self::Valid(claims)) => Some(claims),
+            _ => None,
+        }
+    }
+
+    /// The claims of a valid token, or the error that refused the token.
+    pub fn into_result(self) -> Result<Claims, AppError> {
+        match self {
+            Self::Valid(claims) => Ok(claims),
+            Self::Rejected {
+                kind,
+                code,
+                message,
+            } => Err(AppError::new(kind, message).with_code(code)),
+        }
+    }
+}
+
+impl From<Result<Claims, AppError>> for BearerClaims {
+    fn from(result: Result<Claims, AppError>) -> Self {
+        match result {
+            Ok(claims) => Self::Valid(claims),
+            Err(e) => Self::Rejected {
+                kind: e.kind,
+                code: e.code,
+                message: e.message,
+            },
+        }
+    }
+}
+
+/// Decodes the bearer access token once per request and stores the
+/// outcome as [`BearerClaims`] for the middleware after it and the
+/// `AuthUser` extractor. Requests without an `Authorization` header get
+/// no extension and are never refused here.
+pub async fn bearer_claims(
+    State(state): State<AppState>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response {
+    if let Some(value) = request.headers().get(header::AUTHORIZATION) {
+        let result = match value.to_str().ok().and_then(|v| v.strip_prefix("Bearer ")) {
+            Some(token) => state.jwt_decoder.decode_access_token(token).await,
+            None => Err(AppError::unauthorized(
+                "Invalid Authorization header format",
+            )),
+        };
+        request.extensions_mut().insert(BearerClaims::from(result));
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use filehub_core::error::codes;
+
+    #[test]
+    fn test_rejection_round_trips() {
+        let error =
+            AppError::unauthorized("Token has expired").with_code(codes::AUTH_TOKEN_EXPIRED);
+        let mut extensions = Extensions::new();
+        extensions.insert(BearerClaims::from(Err(error)));
+
+        assert!(BearerClaims::valid(&extensions).is_none());
+        let error = extensions
+            .get::<BearerClaims>()
+            .cloned()
+            .unwrap()
+            .into_result()
+            .unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Unauthorized);
+        assert_eq!(error.code, codes::AUTH_TOKEN_EXPIRED);
+        assert_eq!(error.message, "Token has expired");
+    }
+
+    #[test]
+    fn test_missing_extension_is_not_valid() {
+        assert!(BearerClaims::valid(&Extensions::new()).is_none());
+    }
+}
@@ -0,0 +1,280 @@
+//! Client address resolution behind trusted reverse proxies.
+//!
+//! Runs ahead of everything that looks at the client address (rate
+//! limiting, login lockout and GeoIP, audit entries) and stores the
+//! resolved address as a [`ClientIp`] extension for them to read. See
+//! [`ProxyConfig`] for how the address is chosen.
+
+use std::net::{IpAddr, SocketAddr};
+
+use axum::body::Body;
+use axum::extract::{ConnectInfo, State};
+use axum::http::{HeaderMap, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use ipnet::IpNet;
+
+use filehub_core::config::{ForwardedHeader, ProxyConfig};
+use filehub_core::error::AppError;
+
+use crate::state::AppState;
+
+/// The resolved client address of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+/// Trusted proxies and the header they report the client in.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    networks: Vec<IpNet>,
+    header: ForwardedHeader,
+}
+
+impl TrustedProxies {
+    /// Builds the trust list from `server.proxy`.
+    pub fn from_config(config: &ProxyConfig) -> Result<Self, AppError> {
+        let networks = config
+            .trusted_networks()
+            .map_err(|entry| AppError::configuration(format!("Invalid trusted proxy '{entry}'")))?;
+        Ok(Self {
+            networks,
+            header: config.header,
+        })
+    }
+
+    /// Whether `ip` is a trusted proxy.
+    pub fn is_trusted(&self, ip: IpAddr) -> bool {
+        let ip = canonical(ip);
+        self.networks.iter().any(|net| net.contains(&ip))
+    }
+
+    /// Client address of a request received from `peer`.
+    pub fn resolve(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let peer = canonical(peer);
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+
+        // Nearest hop last. Each trusted hop vouches for the one before
+        // it; the first untrusted hop is the client. An unreadable hop
+        // ends the walk, leaving the last address that was vouched for.
+        let mut client = peer;
+        for hop in self.chain(headers).into_iter().rev() {
+            let Some(ip) = hop else {
+                break;
+            };
+            client = canonical(ip);
+            if !self.is_trusted(client) {
+                break;
+            }
+        }
+        client
+    }
+
+    /// Addresses in the forwarding header, farthest first; `None` for a
+    /// hop that is not an address.
+    fn chain(&self, headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+        let values = |name: &str| {
+            headers
+                .get_all(name)
+                .iter()
+                .map(|v| v.to_str().ok())
+                .collect::<Vec<_>>()
+        };
+        match self.header {
+            ForwardedHeader::XForwardedFor => values("x-forwarded-for")
+                .into_iter()
+                .flat_map(|v| match v {
+                    Some(v) => v.split(',').map(parse_hop).collect(),
+                    None => vec![None],
+                })
+                .collect(),
+            ForwardedHeader::Forwarded => values("forwarded")
+                .into_iter()
+                .flat_map(|v| match v {
+                    Some(v) => v.split(',').map(forwarded_for).collect(),
+                    None => vec![None],
+                })
+                .collect(),
+            // A single proxy sets it, so only the last value counts
+            ForwardedHeader::XRealIp => values("x-real-ip")
+                .last()
+                .map(|v| vec![v.and_then(parse_hop)])
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Stores the client address of the request as a [`ClientIp`].
+pub async fn client_ip(
+    State(state): State<AppState>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response {
+    if let Some(ConnectInfo(peer)) = request.extensions().get::<ConnectInfo<SocketAddr>>() {
+        let ip = state.trusted_proxies.resolve(peer.ip(), request.headers());
+        request.extensions_mut().insert(ClientIp(ip));
+    }
+    next.run(request).await
+}
+
+/// IPv4-mapped IPv6 addresses as plain IPv4, so they match IPv4 networks.
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        IpAddr::V4(_) => ip,
+    }
+}
+
+/// An address from `X-Forwarded-For` or `X-Real-IP`, which some proxies
+/// write with a port.
+fn parse_hop(hop: &str) -> Option<IpAddr> {
+    let hop = hop.trim();
+    hop.parse()
+        .ok()
+        .or_else(|| hop.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+}
+
+/// The `for=` address of one `Forwarded` element (RFC 7239): quoted,
+/// IPv6 in brackets, optionally with a port.
+fn forwarded_for(element: &str) -> Option<IpAddr> {
+    let value = element.split(';').find_map(|pair| {
+        let (name, value) = pair.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("for")
+            .then(|| value.trim().trim_matches('"'))
+    })?;
+    match value.strip_prefix('[') {
+        Some(rest) => rest.split_once(']')?.0.parse().ok(),
+        None => parse_hop(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn proxies(header: ForwardedHeader) -> TrustedProxies {
+        TrustedProxies::from_config(&ProxyConfig {
+            trusted: vec!["10.0.0.0/8".to_string(), "fd00::1".to_string()],
+            header,
+        })
+        .unwrap()
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_untrusted_peer_headers_are_ignored() {
+        let proxies = proxies(ForwardedHeader::XForwardedFor);
+        let spoofed = headers(&[("x-forwarded-for", "1.2.3.4"), ("x-real-ip", "1.2.3.4")]);
+        assert_eq!(
+            proxies.resolve(ip("203.0.113.9"), &spoofed),
+            ip("203.0.113.9")
+        );
+
+        let none = TrustedProxies::default();
+        assert_eq!(none.resolve(ip("10.0.0.1"), &spoofed), ip("10.0.0.1"));
+    }
+
+    #[test]
+    fn test_trusted_proxy_reports_the_client() {
+        let proxies = proxies(ForwardedHeader::XForwardedFor);
+        let forwarded = headers(&[("x-forwarded-for", "203.0.113.9")]);
+        assert_eq!(
+            proxies.resolve(ip("10.0.0.1"), &forwarded),
+            ip("203.0.113.9")
+        );
+        // Without a header the proxy itself is all there is
+        assert_eq!(
+            proxies.resolve(ip("10.0.0.1"), &HeaderMap::new()),
+            ip("10.0.0.1")
+        );
+    }
+
+    #[test]
+    fn test_client_supplied_hops_are_not_believed() {
+        let proxies = proxies(ForwardedHeader::XForwardedFor);
+        // The client sent "X-Forwarded-For: 1.2.3.4" and the proxy
+        // appended the address it saw
+        let spoofed = headers(&[("x-forwarded-for", "1.2.3.4, 203.0.113.9")]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &spoofed), ip("203.0.113.9"));
+
+        // Claiming to be a trusted proxy does not help either
+        let spoofed = headers(&[("x-forwarded-for", "1.2.3.4, 10.9.9.9, 203.0.113.9")]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &spoofed), ip("203.0.113.9"));
+
+        // Split across repeated headers
+        let spoofed = headers(&[
+            ("x-forwarded-for", "1.2.3.4"),
+            ("x-forwarded-for", "203.0.113.9"),
+        ]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &spoofed), ip("203.0.113.9"));
+    }
+
+    #[test]
+    fn test_chain_of_trusted_proxies() {
+        let proxies = proxies(ForwardedHeader::XForwardedFor);
+        let chain = headers(&[("x-forwarded-for", "203.0.113.9, 10.1.1.1, 10.2.2.2")]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &chain), ip("203.0.113.9"));
+
+        // Every hop trusted: the farthest one is the best we know
+        let internal = headers(&[("x-forwarded-for", "10.1.1.1, 10.2.2.2")]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &internal), ip("10.1.1.1"));
+    }
+
+    #[test]
+    fn test_unreadable_hop_stops_the_walk() {
+        let proxies = proxies(ForwardedHeader::XForwardedFor);
+        let garbage = headers(&[("x-forwarded-for", "1.2.3.4, not-an-ip, 10.2.2.2")]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &garbage), ip("10.2.2.2"));
+
+        let ported = headers(&[("x-forwarded-for", "203.0.113.9:5050")]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &ported), ip("203.0.113.9"));
+    }
+
+    #[test]
+    fn test_forwarded_header() {
+        let proxies = proxies(ForwardedHeader::Forwarded);
+        let forwarded = headers(&[(
+            "forwarded",
+            r#"for=1.2.3.4, for="[2001:db8::7]:4711";proto=https, for=10.1.1.1"#,
+        )]);
+        assert_eq!(
+            proxies.resolve(ip("fd00::1"), &forwarded),
+            ip("2001:db8::7")
+        );
+
+        // X-Forwarded-For is not read when Forwarded is configured
+        let other = headers(&[("x-forwarded-for", "203.0.113.9")]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &other), ip("10.0.0.1"));
+
+        let hidden = headers(&[("forwarded", "for=_hidden, for=10.1.1.1")]);
+        assert_eq!(proxies.resolve(ip("10.0.0.1"), &hidden), ip("10.1.1.1"));
+    }
+
+    #[test]
+    fn test_x_real_ip_and_mapped_peers() {
+        let proxies = proxies(ForwardedHeader::XRealIp);
+        let real = headers(&[("x-real-ip", "203.0.113.9")]);
+        assert_eq!(
+            proxies.resolve(ip("::ffff:10.0.0.1"), &real),
+            ip("203.0.113.9")
+        );
+        assert_eq!(
+            proxies.resolve(ip("::ffff:203.0.113.1"), &real),
+            ip("203.0.113.1")
+        );
+    }
+}
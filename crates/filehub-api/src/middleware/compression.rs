@@ -1,8 +1,199 @@
 //! Response compression layer.
+//!
+//! Compression itself is done by `tower_http`'s `CompressionLayer`, which
+//! rewrites `Content-Length`/`Content-Encoding`, drops `Accept-Ranges` on
+//! compressed bodies, and never touches `206` responses. This module adds
+//! a configurable size/content-type predicate and a request middleware that
+//! applies the server's algorithm preference to `Accept-Encoding`.
 
-use tower_http::compression::CompressionLayer;
+use std::sync::Arc;
+
+use axum::body::{Body, HttpBody};
+use axum::extract::State;
+use axum::http::{HeaderMap, HeaderValue, Request, Response, header};
+use axum::middleware::Next;
+use tower_http::compression::{CompressionLayer, Predicate};
+
+use filehub_core::config::CompressionConfig;
+
+use crate::state::AppState;
+
+/// Encodings the compression layer is built with.
+const SUPPORTED: &[&str] = &["br", "gzip"];
+
+/// Builds a compression layer from configuration.
+pub fn build_compression_layer(config: &CompressionConfig) -> CompressionLayer<ShouldCompress> {
+    let enabled = |name: &str| config.enabled && config.algorithms.iter().any(|a| a == name);
 
-/// Builds a compression layer (gzip).
-pub fn build_compression_layer() -> CompressionLayer {
     CompressionLayer::new()
+        .br(enabled("br"))
+        .gzip(enabled("gzip"))
+        .no_deflate()
+        .no_zstd()
+        .compress_when(ShouldCompress {
+            min_size: config.min_size_bytes,
+            skip_content_types: config.skip_content_types.clone().into(),
+        })
+}
+
+/// Decides whether a response body is worth compressing.
+#[derive(Debug, Clone)]
+pub struct ShouldCompress {
+    /// Bodies with a known size below this are sent as-is.
+    min_size: u64,
+    /// Content-type prefixes that are already compressed.
+    skip_content_types: Arc<[String]>,
+}
+
+impl Predicate for ShouldCompress {
+    fn should_compress<B>(&self, response: &Response<B>) -> bool
+    where
+        B: HttpBody,
+    {
+        let headers = response.headers();
+
+        // File downloads stream with a known length for progress reporting
+        // and byte-range resumption.
+        if is_attachment(headers) {
+            return false;
+        }
+
+        if let Some(content_type) = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            && self
+                .skip_content_types
+                .iter()
+                .any(|prefix| content_type.starts_with(prefix.as_str()))
+        {
+            return false;
+        }
+
+        let size = headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .or_else(|| response.body().size_hint().exact());
+
+        size.is_none_or(|size| size >= self.min_size)
+    }
+}
+
+fn is_attachment(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_DISPOSITION)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.trim_start().starts_with("attachment"))
+}
+
+/// Narrows `Accept-Encoding` to the single encoding preferred by the server
+/// among those the client accepts, so the configured order wins over the
+/// compression layer's built-in tie-breaking.
+pub async fn negotiate_encoding(
+    State(state): State<AppState>,
+    mut request: Request<Body>,
+    next: Next,
+) -> axum::response::Response {
+    let config = &state.config.server.compression;
+    if config.enabled
+        && let Some(accept) = request
+            .headers()
+            .get(header::ACCEPT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+    {
+        let chosen = choose_encoding(accept, &config.algorithms).unwrap_or("identity");
+        request
+            .headers_mut()
+            .insert(header::ACCEPT_ENCODING, HeaderValue::from_static(chosen));
+    }
+
+    next.run(request).await
+}
+
+/// Picks the first configured algorithm the client accepts with `q > 0`.
+///
+/// `*` accepts any encoding not listed explicitly.
+fn choose_encoding(accept: &str, preference: &[String]) -> Option<&'static str> {
+    let offers: Vec<(String, bool)> = accept
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let name = parts.next()?.trim().to_ascii_lowercase();
+            if name.is_empty() {
+                return None;
+            }
+            let acceptable = parts
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .all(|q| q.trim().parse::<f32>().map(|q| q > 0.0).unwrap_or(false));
+            Some((name, acceptable))
+        })
+        .collect();
+
+    let accepts = |name: &str| {
+        offers
+            .iter()
+            .find(|(offered, _)| offered == name)
+            .or_else(|| offers.iter().find(|(offered, _)| offered == "*"))
+            .is_some_and(|(_, acceptable)| *acceptable)
+    };
+
+    preference
+        .iter()
+        .filter_map(|name| SUPPORTED.iter().find(|s| **s == name.as_str()).copied())
+        .find(|name| accepts(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefs(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_server_preference_wins() {
+        let accept = "gzip, deflate, br";
+        assert_eq!(choose_encoding(accept, &prefs(&["br", "gzip"])), Some("br"));
+        assert_eq!(
+            choose_encoding(accept, &prefs(&["gzip", "br"])),
+            Some("gzip")
+        );
+    }
+
+    #[test]
+    fn test_rejected_and_wildcard_encodings() {
+        let prefs = prefs(&["br", "gzip"]);
+        assert_eq!(choose_encoding("br;q=0, gzip", &prefs), Some("gzip"));
+        assert_eq!(choose_encoding("*", &prefs), Some("br"));
+        assert_eq!(choose_encoding("*;q=0, gzip;q=0.5", &prefs), Some("gzip"));
+        assert_eq!(choose_encoding("deflate", &prefs), None);
+    }
+
+    #[test]
+    fn test_predicate_skips_small_attachments_and_compressed_types() {
+        let predicate = ShouldCompress {
+            min_size: 1024,
+            skip_content_types: vec!["image/".to_string(), "application/zip".to_string()].into(),
+        };
+        let response = |content_type: &str, body: String| {
+            Response::builder()
+                .header(header::CONTENT_TYPE, content_type)
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let large = || "x".repeat(2048);
+
+        assert!(predicate.should_compress(&response("application/json", large())));
+        assert!(!predicate.should_compress(&response("application/json", "{}".to_string())));
+        assert!(!predicate.should_compress(&response("image/png", large())));
+        assert!(!predicate.should_compress(&response("application/zip", large())));
+
+        let mut attachment = response("text/plain", large());
+        attachment.headers_mut().insert(
+            header::CONTENT_DISPOSITION,
+            HeaderValue::from_static("attachment; filename=\"a.txt\""),
+        );
+        assert!(!predicate.should_compress(&attachment));
+    }
 }
@@ -1,42 +1,57 @@
 //! CORS layer configuration.
+//!
+//! Each route group (see [`CorsGroup`](filehub_core::config::CorsGroup))
+//! gets its own layer, so preflight requests are answered with the policy
+//! of the group the path belongs to.
 
-use axum::http::{HeaderValue, Method};
-use tower_http::cors::{Any, CorsLayer};
+use axum::http::{HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowHeaders, AllowOrigin, Any, CorsLayer};
 
-use filehub_core::config::CorsConfig;
+use filehub_core::config::CorsPolicy;
 
-/// Builds a CORS tower layer from configuration.
-pub fn build_cors_layer(config: &CorsConfig) -> CorsLayer {
+/// Builds a CORS tower layer from a route group's policy.
+pub fn build_cors_layer(policy: &CorsPolicy) -> CorsLayer {
+    let wildcard = |values: &[String]| values.iter().any(|v| v == "*");
     let mut layer = CorsLayer::new();
 
     // Origins
-    if config.allowed_origins.contains(&"*".to_string()) {
+    if wildcard(&policy.allowed_origins) {
         layer = layer.allow_origin(Any);
     } else {
-        let origins: Vec<HeaderValue> = config
+        let origins: Vec<HeaderValue> = policy
             .allowed_origins
             .iter()
             .filter_map(|o| o.parse().ok())
             .collect();
-        layer = layer.allow_origin(origins);
+        layer = layer.allow_origin(AllowOrigin::list(origins));
     }
 
     // Methods
-    let methods: Vec<Method> = config
+    let methods: Vec<Method> = policy
         .allowed_methods
         .iter()
         .filter_map(|m| m.parse().ok())
         .collect();
     layer = layer.allow_methods(methods);
 
-    // Headers
-    if config.allowed_headers.contains(&"*".to_string()) {
-        layer = layer.allow_headers(Any);
+    // Headers. Browsers do not accept a literal `*` on credentialed
+    // requests, so the requested headers are echoed back instead.
+    if wildcard(&policy.allowed_headers) {
+        layer = if policy.allow_credentials {
+            layer.allow_headers(AllowHeaders::mirror_request())
+        } else {
+            layer.allow_headers(Any)
+        };
+    } else {
+        let headers: Vec<HeaderName> = policy
+            .allowed_headers
+            .iter()
+            .filter_map(|h| h.parse().ok())
+            .collect();
+        layer = layer.allow_headers(headers);
     }
 
-    layer = layer.max_age(std::time::Duration::from_secs(
-        config.max_age_seconds as u64,
-    ));
-
     layer
+        .allow_credentials(policy.allow_credentials)
+        .max_age(std::time::Duration::from_secs(policy.max_age_seconds))
 }
@@ -0,0 +1,326 @@
+//! `Idempotency-Key` handling for unsafe POST requests.
+//!
+//! A client that retries a POST after a dropped connection cannot tell
+//! whether the first attempt went through. By sending the same
+//! `Idempotency-Key` header on every attempt it gets the first attempt's
+//! response back instead of creating a second folder, share or file.
+//!
+//! Semantics:
+//!
+//! - Only the routes in [`is_covered`] are handled, and only for
+//!   authenticated callers; keys are scoped per user and per route, so two
+//!   users (or two routes) never share a key.
+//! - The first response with a status below 500 is kept in the cache for
+//!   `server.idempotency.ttl_seconds` (24 hours by default) and replayed
+//!   with an `Idempotent-Replayed: true` header. Server errors are not kept,
+//!   so the request can be retried with the same key.
+//! - Reusing a key with a different request body is rejected with
+//!   `409 IDEMPOTENCY_KEY_REUSED`; retrying while the first attempt is
+//!   still running is rejected with `409 IDEMPOTENCY_REQUEST_IN_PROGRESS`.
+//!
+//! Cache failures fail open: the request runs as if it carried no key.
+
+use std::time::Duration;
+
+use axum::body::{Body, Bytes};
+use axum::extract::{OriginalUri, State};
+use axum::http::{HeaderValue, Method, Request, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use filehub_auth::jwt::Claims;
+use filehub_cache::keys;
+use filehub_core::config::IdempotencyConfig;
+use filehub_core::error::{AppError, codes};
+use filehub_core::traits::cache::CacheProvider;
+
+use crate::middleware::auth::BearerClaims;
+use crate::state::AppState;
+
+/// Request header carrying the client's key.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Response header marking a replayed response.
+pub const REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Longest accepted key.
+const MAX_KEY_LEN: usize = 255;
+
+/// How long an unfinished request holds its key. Bounds the time a key
+/// stays locked if the node handling it goes away mid-request.
+const PENDING_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// What the cache holds for a key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+enum Record {
+    /// The first request is still running.
+    Pending { fingerprint: String },
+    /// The first request finished with this response.
+    Complete {
+        fingerprint: String,
+        status: u16,
+        content_type: Option<String>,
+        body: String,
+    },
+}
+
+impl Record {
+    fn fingerprint(&self) -> &str {
+        match self {
+            Self::Pending { fingerprint } | Self::Complete { fingerprint, .. } => fingerprint,
+        }
+    }
+}
+
+/// Whether `Idempotency-Key` is honoured for a request.
+pub fn is_covered(method: &Method, path: &str) -> bool {
+    if method != Method::POST {
+        return false;
+    }
+    let segments: Vec<&str> = path
+        .trim_end_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+    matches!(
+        segments.as_slice(),
+        ["api", "folders"]
+            | ["api", "shares"]
+            | ["api", "files", "upload"]
+            | ["api", "files", "upload", "initiate"]
+            | ["api", "files", "upload", _, "complete"]
+            | ["api", "files", "upload", _, "finalize"]
+            | ["api", "files", _, "copy"]
+    )
+}
+
+/// Whether a covered request carries file content, and so is bounded by
+/// the upload size limit rather than `max_body_bytes`.
+fn carries_content(path: &str) -> bool {
+    path.trim_end_matches('/') == "/api/files/upload"
+}
+
+/// Checks a key: 1 to 255 visible ASCII characters.
+pub fn validate_key(key: &str) -> Result<(), AppError> {
+    if key.is_empty() || key.len() > MAX_KEY_LEN || !key.bytes().all(|b| b.is_ascii_graphic()) {
+        return Err(AppError::validation(format!(
+            "Idempotency-Key must be 1 to {MAX_KEY_LEN} visible ASCII characters"
+        ))
+        .with_code(codes::IDEMPOTENCY_KEY_INVALID));
+    }
+    Ok(())
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// Stores and replays responses to requests carrying `Idempotency-Key`.
+pub async fn idempotency(
+    State(state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let config = state.live_config.borrow().server.idempotency.clone();
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return next.run(request).await;
+    };
+    // Inside a nested router the URI has lost its `/api` prefix.
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or_else(|| request.uri().path(), |uri| uri.path())
+        .to_string();
+    if !config.enabled || !is_covered(request.method(), &path) {
+        return next.run(request).await;
+    }
+
+    let key = key.to_str().unwrap_or_default().to_string();
+    if let Err(e) = validate_key(&key) {
+        return e.into_response();
+    }
+    let Some(user_id) = BearerClaims::valid(request.extensions()).map(Claims::user_id) else {
+        // Unauthenticated requests are refused by the handler anyway.
+        return next.run(request).await;
+    };
+
+    let limit = if carries_content(&path) {
+        state.config.storage.max_upload_size_bytes as usize
+    } else {
+        config.max_body_bytes
+    };
+    let (parts, body) = request.into_parts();
+    let body = match axum::body::to_bytes(body, limit).await {
+        Ok(body) => body,
+        Err(_) => {
+            return AppError::bad_request(format!(
+                "Requests with an Idempotency-Key are limited to {limit} bytes"
+            ))
+            .into_response();
+        }
+    };
+    let fingerprint = sha256_hex(&body);
+    let cache_key = keys::idempotency(user_id, &sha256_hex(format!("{path}\n{key}").as_bytes()));
+
+    let pending = Record::Pending {
+        fingerprint: fingerprint.clone(),
+    };
+    let claimed = match serde_json::to_string(&pending) {
+        Ok(value) => state.cache.set_nx(&cache_key, &value, PENDING_TTL).await,
+        Err(e) => Err(AppError::internal(e.to_string())),
+    };
+    let request = Request::from_parts(parts, Body::from(body));
+    match claimed {
+        Ok(true) => {}
+        Ok(false) => return existing(&state, &cache_key, &fingerprint).await,
+        Err(e) => {
+            tracing::warn!(error = %e, "Idempotency store unavailable");
+            return next.run(request).await;
+        }
+    }
+
+    let response = next.run(request).await;
+    store(&state, &cache_key, fingerprint, &config, response).await
+}
+
+/// Answers a request whose key was already claimed.
+async fn existing(state: &AppState, cache_key: &str, fingerprint: &str) -> Response {
+    let record = match state.cache.get_json::<Record>(cache_key).await {
+        Ok(Some(record)) => record,
+        // Expired or released between the claim and the read.
+        Ok(None) => return in_progress(),
+        Err(e) => {
+            tracing::warn!(error = %e, "Idempotency store unavailable");
+            return in_progress();
+        }
+    };
+
+    if record.fingerprint() != fingerprint {
+        return AppError::conflict("Idempotency-Key was already used for a different request")
+            .with_code(codes::IDEMPOTENCY_KEY_REUSED)
+            .into_response();
+    }
+    match record {
+        Record::Pending { .. } => in_progress(),
+        Record::Complete {
+            status,
+            content_type,
+            body,
+            ..
+        } => {
+            let mut response = Response::new(Body::from(body));
+            *response.status_mut() = StatusCode::from_u16(status).unwrap_or(StatusCode::OK);
+            if let Some(content_type) = content_type.and_then(|c| HeaderValue::from_str(&c).ok()) {
+                response
+                    .headers_mut()
+                    .insert(header::CONTENT_TYPE, content_type);
+            }
+            response
+                .headers_mut()
+                .insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+            response
+        }
+    }
+}
+
+fn in_progress() -> Response {
+    AppError::conflict("A request with this Idempotency-Key is still in progress")
+        .with_code(codes::IDEMPOTENCY_REQUEST_IN_PROGRESS)
+        .into_response()
+}
+
+/// Keeps the response for replay, or releases the key if it cannot be
+/// replayed.
+async fn store(
+    state: &AppState,
+    cache_key: &str,
+    fingerprint: String,
+    config: &IdempotencyConfig,
+    response: Response,
+) -> Response {
+    let (parts, body) = response.into_parts();
+    let body: Bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            release(state, cache_key).await;
+            return AppError::internal(format!("Failed to read response: {e}")).into_response();
+        }
+    };
+
+    let record = (parts.status.as_u16() < 500 && body.len() <= config.max_body_bytes)
+        .then(|| std::str::from_utf8(&body).ok())
+        .flatten()
+        .map(|text| Record::Complete {
+            fingerprint,
+            status: parts.status.as_u16(),
+            content_type: parts
+                .headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from),
+            body: text.to_string(),
+        });
+    match record {
+        Some(record) => {
+            let ttl = Duration::from_secs(config.ttl_seconds);
+            if let Err(e) = state.cache.set_json(cache_key, &record, ttl).await {
+                tracing::warn!(error = %e, "Failed to store idempotent response");
+                release(state, cache_key).await;
+            }
+        }
+        None => release(state, cache_key).await,
+    }
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+async fn release(state: &AppState, cache_key: &str) {
+    if let Err(e) = state.cache.delete(cache_key).await {
+        tracing::warn!(error = %e, "Failed to release idempotency key");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_covered_routes() {
+        assert!(is_covered(&Method::POST, "/api/folders"));
+        assert!(is_covered(&Method::POST, "/api/shares/"));
+        assert!(is_covered(&Method::POST, "/api/files/upload/initiate"));
+        assert!(is_covered(&Method::POST, "/api/files/upload/abc/complete"));
+        assert!(is_covered(&Method::POST, "/api/files/upload/abc/finalize"));
+        assert!(is_covered(&Method::POST, "/api/files/upload"));
+        assert!(is_covered(&Method::POST, "/api/files/abc/copy"));
+
+        assert!(!is_covered(&Method::GET, "/api/folders"));
+        assert!(!is_covered(&Method::POST, "/api/files/upload/presign"));
+        assert!(!is_covered(&Method::PUT, "/api/files/upload/abc/chunk/1"));
+        assert!(!is_covered(&Method::POST, "/api/files/abc/lock"));
+        assert!(!is_covered(&Method::POST, "/api/auth/login"));
+    }
+
+    #[test]
+    fn test_only_single_shot_upload_carries_content() {
+        assert!(carries_content("/api/files/upload"));
+        assert!(carries_content("/api/files/upload/"));
+        assert!(!carries_content("/api/files/upload/abc/finalize"));
+        assert!(!carries_content("/api/folders"));
+    }
+
+    #[test]
+    fn test_validate_key() {
+        assert!(validate_key("3f1c-retry_01").is_ok());
+        assert!(validate_key("").is_err());
+        assert!(validate_key("has space").is_err());
+        assert!(validate_key(&"k".repeat(256)).is_err());
+        assert_eq!(
+            validate_key("é").unwrap_err().code,
+            codes::IDEMPOTENCY_KEY_INVALID
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! Impersonation guard.
+//!
+//! Requests made with an impersonation token are refused on
+//! `auth.impersonation.blocked_routes`, and the rest run inside
+//! [`Impersonator::scope`] so everything they record names the
+//! administrator who really acted.
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{Method, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use filehub_auth::jwt::Claims;
+use filehub_core::config::{ImpersonationConfig, RoutePattern};
+use filehub_core::error::{AppError, codes};
+use filehub_core::types::Impersonator;
+
+use crate::middleware::auth::BearerClaims;
+use crate::state::AppState;
+
+/// Routes an impersonation session may not use.
+#[derive(Debug, Clone, Default)]
+pub struct BlockedRoutes {
+    patterns: Vec<RoutePattern>,
+}
+
+impl BlockedRoutes {
+    /// Parses `auth.impersonation.blocked_routes`.
+    pub fn from_config(config: &ImpersonationConfig) -> Result<Self, AppError> {
+        let patterns = config
+            .blocked_routes
+            .iter()
+            .map(|route| {
+                RoutePattern::parse(route)
+                    .map_err(|e| AppError::configuration(format!("Invalid blocked route: {e}")))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// Whether `method path` is blocked.
+    pub fn blocks(&self, method: &Method, path: &str) -> bool {
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.matches(method.as_str(), path))
+    }
+}
+
+/// Applies the impersonation guard to requests carrying an impersonation
+/// token, as decoded by [`bearer_claims`](super::auth::bearer_claims).
+/// Other requests, including ones whose token does not decode, pass
+/// through untouched for the `AuthUser` extractor to judge.
+pub async fn impersonation(
+    State(state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some(admin_id) =
+        BearerClaims::valid(request.extensions()).and_then(Claims::impersonator_id)
+    else {
+        return next.run(request).await;
+    };
+
+    if state
+        .impersonation_blocked
+        .blocks(request.method(), request.uri().path())
+    {
+        tracing::warn!(
+            admin_id = %admin_id,
+            method = %request.method(),
+            path = %request.uri().path(),
+            "Blocked request from impersonation session"
+        );
+        return AppError::forbidden("Not allowed while impersonating")
+            .with_code(codes::AUTH_IMPERSONATION_BLOCKED)
+            .into_response();
+    }
+
+    Impersonator(admin_id).scope(next.run(request)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_routes_block_sensitive_actions() {
+        let blocked = BlockedRoutes::from_config(&ImpersonationConfig::default()).unwrap();
+        assert!(blocked.blocks(&Method::PUT, "/api/users/me/password"));
+        assert!(blocked.blocks(&Method::DELETE, "/api/files/7b0f"));
+        assert!(blocked.blocks(&Method::POST, "/api/permissions/acl/x"));
+        assert!(!blocked.blocks(&Method::GET, "/api/files/7b0f"));
+        assert!(!blocked.blocks(&Method::GET, "/api/users/me"));
+    }
+
+    #[test]
+    fn test_invalid_route_is_rejected() {
+        let config = ImpersonationConfig {
+            blocked_routes: vec!["".to_string()],
+            ..Default::default()
+        };
+        assert!(BlockedRoutes::from_config(&config).is_err());
+    }
+}
@@ -2,8 +2,15 @@
 
 pub mod activity;
 pub mod auth;
+pub mod client_ip;
 pub mod compression;
 pub mod cors;
+pub mod idempotency;
+pub mod impersonation;
 pub mod logging;
+pub mod metrics;
+pub mod quota;
 pub mod rate_limit;
 pub mod rbac;
+pub mod request_id;
+pub mod timeout;
@@ -0,0 +1,204 @@
+//! Per-user API quota middleware.
+//!
+//! Counts every authenticated call and the body bytes it transferred
+//! against the caller's quota tier, and refuses calls with
+//! `429 Too Many Requests` once the tier's daily or monthly allowance is
+//! used up. Refused calls are not counted. The usage endpoint is counted
+//! but never refused, so users can always see why they were cut off.
+//! Requests without a valid access token are left to the handler to
+//! reject.
+//!
+//! Bytes are counted as the request and response bodies actually yield
+//! them, so streamed and chunked bodies are counted in full. A call is
+//! recorded once its response body is finished or dropped.
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+
+use axum::body::{Body, Bytes, HttpBody};
+use axum::extract::{OriginalUri, State};
+use axum::http::{HeaderValue, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use http_body::{Frame, SizeHint};
+use uuid::Uuid;
+
+use filehub_auth::jwt::Claims;
+use filehub_core::error::{AppError, codes};
+use filehub_service::user::{QuotaExceeded, QuotaService};
+
+use crate::middleware::auth::BearerClaims;
+use crate::state::AppState;
+
+/// Path of the usage introspection endpoint.
+const USAGE_PATH: &str = "/api/users/me/usage";
+
+/// Enforces and counts per-user API quotas.
+pub async fn quota(State(state): State<AppState>, request: Request<Body>, next: Next) -> Response {
+    if !state.quota_service.enabled() {
+        return next.run(request).await;
+    }
+    let Some(user_id) = BearerClaims::valid(request.extensions()).map(Claims::user_id) else {
+        return next.run(request).await;
+    };
+
+    // Inside a nested router the URI has lost its `/api` prefix.
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or_else(|| request.uri().path(), |uri| uri.path());
+    if path != USAGE_PATH
+        && let Some(exceeded) = state.quota_service.check(user_id).await
+    {
+        tracing::debug!(%user_id, period = exceeded.period.as_str(), "API quota exhausted");
+        return exceeded_response(&exceeded);
+    }
+
+    let bytes = Arc::new(AtomicU64::new(0));
+    let request = request.map(|body| Body::new(Counted::new(body, Arc::clone(&bytes), None)));
+    let usage = Usage {
+        quota: Arc::clone(&state.quota_service),
+        user_id,
+        bytes: Arc::clone(&bytes),
+    };
+    next.run(request)
+        .await
+        .map(|body| Body::new(Counted::new(body, bytes, Some(usage))))
+}
+
+/// Records one call with the bytes counted for it when dropped.
+struct Usage {
+    quota: Arc<QuotaService>,
+    user_id: Uuid,
+    bytes: Arc<AtomicU64>,
+}
+
+impl Drop for Usage {
+    fn drop(&mut self) {
+        self.quota
+            .record(self.user_id, self.bytes.load(Ordering::Relaxed));
+    }
+}
+
+/// A body that adds the data bytes it yields to a shared count.
+struct Counted {
+    inner: Body,
+    bytes: Arc<AtomicU64>,
+    /// Held by the response body, so the call is recorded once the
+    /// response is finished or abandoned.
+    _usage: Option<Usage>,
+}
+
+impl Counted {
+    fn new(inner: Body, bytes: Arc<AtomicU64>, usage: Option<Usage>) -> Self {
+        Self {
+            inner,
+            bytes,
+            _usage: usage,
+        }
+    }
+}
+
+impl HttpBody for Counted {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, axum::Error>>> {
+        let polled = Pin::new(&mut self.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &polled
+            && let Some(data) = frame.data_ref()
+        {
+            self.bytes.fetch_add(data.len() as u64, Ordering::Relaxed);
+        }
+        polled
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// `429` naming the exhausted period and when it resets.
+fn exceeded_response(exceeded: &QuotaExceeded) -> Response {
+    let retry_after = exceeded.retry_after();
+    let mut response = AppError::rate_limit(format!(
+        "API quota for the {} is used up on tier '{}'; it resets at {}",
+        exceeded.period.as_str(),
+        exceeded.tier,
+        exceeded.resets_at.to_rfc3339()
+    ))
+    .with_code(codes::API_QUOTA_EXCEEDED)
+    .into_response();
+
+    let headers = response.headers_mut();
+    headers.insert("retry-after", HeaderValue::from(retry_after));
+    headers.insert(
+        "x-quota-reset",
+        HeaderValue::from(exceeded.resets_at.timestamp().max(0) as u64),
+    );
+    if let Ok(period) = HeaderValue::from_str(exceeded.period.as_str()) {
+        headers.insert("x-quota-period", period);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::http::StatusCode;
+    use chrono::{Duration, Utc};
+    use filehub_entity::user::{Usage as Allowance, UsagePeriod};
+    use futures::stream;
+
+    #[test]
+    fn test_exceeded_response_carries_reset() {
+        let resets_at = Utc::now() + Duration::hours(2);
+        let response = exceeded_response(&QuotaExceeded {
+            period: UsagePeriod::Day,
+            tier: "standard".to_string(),
+            limit: Allowance::new(10, 0),
+            resets_at,
+        });
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let headers = response.headers();
+        assert_eq!(headers["x-quota-period"], "day");
+        assert_eq!(
+            headers["x-quota-reset"],
+            resets_at.timestamp().to_string().as_str()
+        );
+        let retry_after: u64 = headers["retry-after"].to_str().unwrap().parse().unwrap();
+        assert!((7100..=7200).contains(&retry_after));
+    }
+
+    #[tokio::test]
+    async fn test_counts_streamed_body() {
+        let chunks = ["abc", "defgh", ""].map(|c| Ok::<_, std::io::Error>(Bytes::from(c)));
+        let body = Body::from_stream(stream::iter(chunks));
+        assert_eq!(body.size_hint().exact(), None);
+
+        let bytes = Arc::new(AtomicU64::new(0));
+        let counted = Body::new(Counted::new(body, Arc::clone(&bytes), None));
+        let read = axum::body::to_bytes(counted, usize::MAX).await.unwrap();
+
+        assert_eq!(read.len(), 8);
+        assert_eq!(bytes.load(Ordering::Relaxed), 8);
+    }
+
+    #[test]
+    fn test_keeps_exact_size_hint() {
+        let bytes = Arc::new(AtomicU64::new(0));
+        let counted = Counted::new(Body::from("hello"), bytes, None);
+        assert_eq!(counted.size_hint().exact(), Some(5));
+    }
+}
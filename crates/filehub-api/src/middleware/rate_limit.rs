@@ -1,59 +1,306 @@
-//! Token bucket rate limiter middleware.
+//! Per-user / per-IP rate limiting middleware.
+//!
+//! Each subject gets a token bucket per route group, kept in the shared
+//! [`CacheManager`](filehub_cache::provider::CacheManager) so every node
+//! enforces the same budget. A bucket holds up to `requests` tokens and
+//! refills from empty to full over `window_seconds`, so bursts are capped
+//! at `requests` however requests line up in time. Each take is one atomic
+//! step on the cache, so concurrent requests on different nodes never
+//! double-spend the same token.
 
-use std::collections::HashMap;
-use std::sync::Arc;
-use std::time::Instant;
+use std::time::Duration;
 
-use tokio::sync::Mutex;
+use axum::body::Body;
+use axum::extract::{OriginalUri, State};
+use axum::http::{HeaderMap, HeaderValue, Method, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use sha2::{Digest, Sha256};
+use subtle::{Choice, ConstantTimeEq};
+use uuid::Uuid;
 
-/// Simple in-memory token bucket rate limiter.
-#[derive(Debug, Clone)]
-pub struct RateLimiter {
-    /// IP → bucket state.
-    buckets: Arc<Mutex<HashMap<String, TokenBucket>>>,
-    /// Maximum tokens per bucket.
-    max_tokens: u32,
-    /// Token refill rate per second.
-    refill_rate: f64,
+use filehub_auth::jwt::Claims;
+use filehub_cache::keys;
+use filehub_core::config::{RateLimitConfig, RateLimitRule};
+use filehub_core::error::AppError;
+use filehub_core::traits::cache::{CacheProvider, TokenBucket};
+
+use crate::middleware::auth::BearerClaims;
+use crate::state::AppState;
+
+/// Route groups that carry their own limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteGroup {
+    /// Login and token refresh.
+    Auth,
+    /// File uploads, single and chunked.
+    Upload,
+    /// Other state-changing requests.
+    Write,
+    /// GET / HEAD requests.
+    Read,
+    /// Anonymous reads of public content, always counted per IP.
+    Public,
+}
+
+impl RouteGroup {
+    /// Classifies a request by method and path.
+    pub fn classify(method: &Method, path: &str) -> Self {
+        if path.starts_with("/api/public/") {
+            Self::Public
+        } else if path.starts_with("/api/auth/login") || path.starts_with("/api/auth/refresh") {
+            Self::Auth
+        } else if path.starts_with("/api/files/upload") {
+            Self::Upload
+        } else if method == Method::GET || method == Method::HEAD {
+            Self::Read
+        } else {
+            Self::Write
+        }
+    }
+
+    /// Name used in cache keys.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auth => "auth",
+            Self::Upload => "upload",
+            Self::Write => "write",
+            Self::Read => "read",
+            Self::Public => "public",
+        }
+    }
+
+    /// Looks up this group's limit.
+    pub fn rule(&self, config: &RateLimitConfig) -> RateLimitRule {
+        match self {
+            Self::Auth => config.auth,
+            Self::Upload => config.upload,
+            Self::Write => config.write,
+            Self::Read => config.read,
+            Self::Public => config.public,
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
-struct TokenBucket {
-    tokens: f64,
-    last_refill: Instant,
+/// Outcome of taking one request's token from a bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    /// Bucket size: requests allowed in a burst.
+    pub limit: u32,
+    /// Whole tokens left in the bucket.
+    pub remaining: u32,
+    /// Seconds until the bucket is full again.
+    pub reset_after: u64,
+    /// Seconds until the next token, when refused.
+    pub retry_after: u64,
+    /// Whether the request is within the limit.
+    pub allowed: bool,
 }
 
-impl RateLimiter {
-    /// Creates a new rate limiter.
-    pub fn new(max_tokens: u32, refill_rate: f64) -> Self {
+impl RateLimitDecision {
+    /// Computes the decision from the bucket left after the take.
+    pub fn new(rule: RateLimitRule, bucket: TokenBucket) -> Self {
+        let capacity = f64::from(rule.requests);
+        let tokens = bucket.tokens.clamp(0.0, capacity);
+        // Seconds for one token to come back.
+        let per_token = rule.window_seconds.max(1) as f64 / capacity.max(1.0);
+        let retry_after = if bucket.allowed {
+            0
+        } else {
+            ((1.0 - tokens) * per_token).ceil().max(1.0) as u64
+        };
         Self {
-            buckets: Arc::new(Mutex::new(HashMap::new())),
-            max_tokens,
-            refill_rate,
+            limit: rule.requests,
+            remaining: tokens.floor() as u32,
+            reset_after: ((capacity - tokens) * per_token).ceil() as u64,
+            retry_after,
+            allowed: bucket.allowed,
         }
     }
 
-    /// Attempts to consume a token for the given key.
-    pub async fn check(&self, key: &str) -> bool {
-        let mut buckets = self.buckets.lock().await;
-        let now = Instant::now();
+    /// Writes the `X-RateLimit-*` headers.
+    fn apply(&self, headers: &mut HeaderMap) {
+        headers.insert("x-ratelimit-limit", HeaderValue::from(self.limit));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from(self.remaining));
+        headers.insert("x-ratelimit-reset", HeaderValue::from(self.reset_after));
+    }
+}
 
-        let bucket = buckets.entry(key.to_string()).or_insert(TokenBucket {
-            tokens: self.max_tokens as f64,
-            last_refill: now,
-        });
+/// Enforces per-group request limits.
+///
+/// Limits are read from the live configuration, so a reload takes effect
+/// on the next request. Requests carrying a bypass API key skip the limiter entirely. Cache
+/// failures fail open so an unavailable cache never takes the API down.
+pub async fn rate_limit(
+    State(state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let config = state.live_config.borrow().server.rate_limit.clone();
+    if !config.enabled || has_bypass_key(&config, request.headers()) {
+        return next.run(request).await;
+    }
 
-        // Refill tokens
-        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
-        bucket.tokens = (bucket.tokens + elapsed * self.refill_rate).min(self.max_tokens as f64);
-        bucket.last_refill = now;
+    // Inside a nested router the URI has lost its `/api` prefix.
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map_or_else(|| request.uri().path(), |uri| uri.path());
+    let group = RouteGroup::classify(request.method(), path);
+    let rule = group.rule(&config);
+    let user_id = BearerClaims::valid(request.extensions())
+        .filter(|_| group != RouteGroup::Public)
+        .map(Claims::user_id);
+    let subject = subject(user_id, client_ip(&request));
 
-        // Try to consume
-        if bucket.tokens >= 1.0 {
-            bucket.tokens -= 1.0;
-            true
-        } else {
-            false
+    let decision = match consume(&state, group, rule, &subject).await {
+        Ok(decision) => decision,
+        Err(e) => {
+            tracing::warn!(error = %e, group = group.as_str(), "Rate limiter unavailable");
+            return next.run(request).await;
         }
+    };
+
+    if !decision.allowed {
+        tracing::debug!(subject = %subject, group = group.as_str(), "Rate limit exceeded");
+        let mut response = AppError::rate_limit(format!(
+            "Too many requests, retry in {} seconds",
+            decision.retry_after
+        ))
+        .into_response();
+        decision.apply(response.headers_mut());
+        response
+            .headers_mut()
+            .insert("retry-after", HeaderValue::from(decision.retry_after));
+        return response;
+    }
+
+    let mut response = next.run(request).await;
+    decision.apply(response.headers_mut());
+    response
+}
+
+/// Takes one token from the subject's bucket for the group.
+async fn consume(
+    state: &AppState,
+    group: RouteGroup,
+    rule: RateLimitRule,
+    subject: &str,
+) -> Result<RateLimitDecision, AppError> {
+    let key = keys::rate_limit(group.as_str(), subject);
+    let refill = Duration::from_secs(rule.window_seconds.max(1));
+    let bucket = state.cache.take_token(&key, rule.requests, refill).await?;
+    Ok(RateLimitDecision::new(rule, bucket))
+}
+
+/// Identifies the caller: the user id from a valid access token, otherwise
+/// the client IP.
+fn subject(user_id: Option<Uuid>, client_ip: String) -> String {
+    match user_id {
+        Some(user_id) => format!("user:{user_id}"),
+        None => format!("ip:{client_ip}"),
+    }
+}
+
+/// The client address resolved through trusted proxies.
+fn client_ip(request: &Request<Body>) -> String {
+    crate::extractors::client::client_ip(request.extensions()).to_string()
+}
+
+/// Whether the request carries one of the bypass API keys.
+///
+/// Keys are compared as SHA-256 digests in constant time, and every
+/// configured key is checked, so response timing says nothing about how
+/// close a guess came.
+fn has_bypass_key(config: &RateLimitConfig, headers: &HeaderMap) -> bool {
+    if config.bypass_api_keys.is_empty() {
+        return false;
+    }
+    let Some(key) = headers.get(config.api_key_header.as_str()) else {
+        return false;
+    };
+    let presented = Sha256::digest(key.as_bytes());
+    config
+        .bypass_api_keys
+        .iter()
+        .fold(Choice::from(0), |found, k| {
+            found | Sha256::digest(k.as_bytes()).ct_eq(&presented)
+        })
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_route_groups() {
+        assert_eq!(
+            RouteGroup::classify(&Method::POST, "/api/auth/login"),
+            RouteGroup::Auth
+        );
+        assert_eq!(
+            RouteGroup::classify(&Method::PUT, "/api/files/upload/abc/chunk/1"),
+            RouteGroup::Upload
+        );
+        assert_eq!(
+            RouteGroup::classify(&Method::GET, "/api/files/abc"),
+            RouteGroup::Read
+        );
+        assert_eq!(
+            RouteGroup::classify(&Method::DELETE, "/api/files/abc"),
+            RouteGroup::Write
+        );
+        assert_eq!(
+            RouteGroup::classify(&Method::GET, "/api/public/files/abc/download"),
+            RouteGroup::Public
+        );
+    }
+
+    #[test]
+    fn test_decision_from_bucket() {
+        // One token every 30 seconds.
+        let rule = RateLimitRule {
+            requests: 2,
+            window_seconds: 60,
+        };
+
+        let first = RateLimitDecision::new(
+            rule,
+            TokenBucket {
+                allowed: true,
+                tokens: 1.0,
+            },
+        );
+        assert!(first.allowed);
+        assert_eq!(first.remaining, 1);
+        assert_eq!(first.reset_after, 30);
+        assert_eq!(first.retry_after, 0);
+
+        let refused = RateLimitDecision::new(
+            rule,
+            TokenBucket {
+                allowed: false,
+                tokens: 0.5,
+            },
+        );
+        assert!(!refused.allowed);
+        assert_eq!(refused.remaining, 0);
+        assert_eq!(refused.retry_after, 15);
+        assert_eq!(refused.reset_after, 45);
+    }
+
+    #[test]
+    fn test_bypass_key() {
+        let config = RateLimitConfig {
+            bypass_api_keys: vec!["svc-key".to_string()],
+            ..Default::default()
+        };
+        let mut headers = HeaderMap::new();
+        assert!(!has_bypass_key(&config, &headers));
+        headers.insert("x-api-key", HeaderValue::from_static("svc-key"));
+        assert!(has_bypass_key(&config, &headers));
+        headers.insert("x-api-key", HeaderValue::from_static("other"));
+        assert!(!has_bypass_key(&config, &headers));
     }
 }
@@ -0,0 +1,77 @@
+//! `X-Request-Id` assignment and propagation.
+
+use axum::body::Body;
+use axum::http::{HeaderValue, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use tracing::Instrument;
+
+use filehub_core::telemetry::{self, TRACEPARENT_HEADER};
+use filehub_core::types::RequestId;
+
+/// Reads or generates the request id and makes it visible everywhere
+/// downstream.
+///
+/// The id is stored in the request extensions, recorded on a `request`
+/// tracing span wrapping the rest of the stack (so every log line emitted
+/// while handling the request carries it), exposed through
+/// [`RequestId::current`], and echoed back in the response header.
+///
+/// When traces are exported the request also gets a server span, which
+/// continues the caller's trace if it sent a `traceparent` header.
+pub async fn request_id(mut request: Request<Body>, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(RequestId::HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(RequestId::parse)
+        .unwrap_or_else(RequestId::generate);
+
+    request.extensions_mut().insert(id.clone());
+
+    let http_span = tracing::info_span!(
+        target: telemetry::TARGET,
+        "http.server",
+        otel.name = %format!("{} {}", request.method(), request.uri().path()),
+        otel.kind = "server",
+        otel.status_code = tracing::field::Empty,
+        http.request.method = %request.method(),
+        url.path = %request.uri().path(),
+        http.response.status_code = tracing::field::Empty,
+        request_id = %id,
+    );
+    if let Some(traceparent) = request
+        .headers()
+        .get(TRACEPARENT_HEADER)
+        .and_then(|v| v.to_str().ok())
+    {
+        telemetry::set_parent(&http_span, traceparent);
+    }
+
+    let span = http_span.in_scope(|| {
+        tracing::info_span!(
+            "request",
+            request_id = %id,
+            method = %request.method(),
+            path = %request.uri().path(),
+        )
+    });
+
+    let mut response = id
+        .clone()
+        .scope(next.run(request).instrument(span))
+        .instrument(http_span.clone())
+        .await;
+
+    let status = response.status();
+    http_span.record("http.response.status_code", status.as_u16());
+    if status.is_server_error() {
+        http_span.record("otel.status_code", "ERROR");
+    }
+
+    if let Ok(value) = HeaderValue::from_str(id.as_str()) {
+        response.headers_mut().insert(RequestId::HEADER, value);
+    }
+
+    response
+}
@@ -0,0 +1,144 @@
+//! Per-route-group request timeouts.
+//!
+//! A request whose handler has not produced a response in time is answered
+//! with `504 Gateway Timeout`. The handler's future is dropped at that
+//! point, so its pending database and storage calls are cancelled rather
+//! than left running. Only the time to produce the response counts: a
+//! streamed response body is not cut off once it has started.
+
+use std::time::Duration;
+
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::{Method, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use filehub_core::config::ServerConfig;
+use filehub_core::error::AppError;
+
+use crate::state::AppState;
+
+/// Route groups with their own timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutGroup {
+    /// Uploads, downloads, exports and the WebSocket upgrade.
+    Transfer,
+    /// `/api/admin` requests.
+    Admin,
+    /// Everything else.
+    Default,
+}
+
+impl TimeoutGroup {
+    /// Classifies a request by method and path.
+    pub fn classify(method: &Method, path: &str) -> Self {
+        if path == "/ws"
+            || path.starts_with("/api/files/upload")
+            || path.ends_with("/download")
+            || path.ends_with("/export")
+            || (method == Method::GET && is_version_download(path))
+        {
+            Self::Transfer
+        } else if path.starts_with("/api/admin") {
+            Self::Admin
+        } else {
+            Self::Default
+        }
+    }
+
+    /// Name used in logs.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Transfer => "transfer",
+            Self::Admin => "admin",
+            Self::Default => "default",
+        }
+    }
+
+    /// This group's timeout, `None` if disabled.
+    pub fn timeout(&self, config: &ServerConfig) -> Option<Duration> {
+        let seconds = match self {
+            Self::Transfer => config.timeouts.transfer_seconds,
+            Self::Admin => config.timeouts.admin_seconds,
+            Self::Default => config.request_timeout_seconds,
+        };
+        (seconds > 0).then(|| Duration::from_secs(seconds))
+    }
+}
+
+/// Whether `path` is `/api/files/{id}/versions/{ver}`.
+fn is_version_download(path: &str) -> bool {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    matches!(segments.as_slice(), ["api", "files", _, "versions", _])
+}
+
+/// Middleware abandoning requests that exceed their group's timeout.
+pub async fn request_timeout(
+    State(state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let group = TimeoutGroup::classify(request.method(), request.uri().path());
+    let Some(limit) = group.timeout(&state.live_config.borrow().server) else {
+        return next.run(request).await;
+    };
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    match tokio::time::timeout(limit, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            tracing::warn!(
+                method = %method,
+                path = %path,
+                group = group.as_str(),
+                timeout_seconds = limit.as_secs(),
+                "Request timed out"
+            );
+            AppError::timeout(format!(
+                "The request did not complete within {} seconds",
+                limit.as_secs()
+            ))
+            .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_timeout_groups() {
+        let transfers = [
+            (Method::POST, "/api/files/upload"),
+            (Method::PUT, "/api/files/upload/abc/chunk/1"),
+            (Method::GET, "/api/files/abc/download"),
+            (Method::GET, "/api/folders/abc/download"),
+            (Method::GET, "/api/s/token/download"),
+            (Method::GET, "/api/files/abc/versions/2"),
+            (Method::GET, "/api/admin/audit/export"),
+            (Method::GET, "/ws"),
+        ];
+        for (method, path) in transfers {
+            assert_eq!(
+                TimeoutGroup::classify(&method, path),
+                TimeoutGroup::Transfer,
+                "{path}"
+            );
+        }
+        assert_eq!(
+            TimeoutGroup::classify(&Method::POST, "/api/files/abc/versions/2/restore"),
+            TimeoutGroup::Default
+        );
+        assert_eq!(
+            TimeoutGroup::classify(&Method::GET, "/api/files/abc/versions"),
+            TimeoutGroup::Default
+        );
+        assert_eq!(
+            TimeoutGroup::classify(&Method::GET, "/api/admin/users"),
+            TimeoutGroup::Admin
+        );
+    }
+}
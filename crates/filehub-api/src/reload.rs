@@ -0,0 +1,106 @@
+//! `SIGHUP`-triggered configuration reload.
+//!
+//! See [`filehub_core::config::reload`] for which fields are applied at
+//! runtime. The new configuration is published on a `watch` channel that
+//! the rate limiter and worker read from; the log filter and cache TTL are
+//! pushed directly.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+use filehub_cache::provider::CacheManager;
+use filehub_core::config::AppConfig;
+use filehub_core::config::reload::apply_reloadable;
+use filehub_core::error::AppError;
+
+/// Re-reads and validates the configuration from its original source.
+pub type ConfigLoader = Box<dyn Fn() -> Result<AppConfig, AppError> + Send + Sync>;
+
+/// Replaces the active tracing filter with the given directive.
+pub type LogLevelSetter = Box<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+/// Process-level hooks the server needs to reload configuration.
+pub struct ReloadHooks {
+    /// Loads the candidate configuration.
+    pub load: ConfigLoader,
+    /// Swaps the log filter.
+    pub set_log_level: LogLevelSetter,
+}
+
+impl std::fmt::Debug for ReloadHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReloadHooks").finish()
+    }
+}
+
+/// Spawns a task that reloads configuration on every `SIGHUP`.
+///
+/// A configuration that fails to load or validate is rejected as a whole
+/// and the live configuration stays in place.
+pub fn spawn_reloader(
+    hooks: ReloadHooks,
+    live: watch::Sender<Arc<AppConfig>>,
+    cache: Arc<CacheManager>,
+) {
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::warn!("Configuration reload disabled: {}", e);
+                return;
+            }
+        };
+
+        while hangup.recv().await.is_some() {
+            tracing::info!("SIGHUP received, reloading configuration");
+            reload(&hooks, &live, &cache);
+        }
+    });
+
+    #[cfg(not(unix))]
+    {
+        let _ = (hooks, live, cache);
+        tracing::debug!("Configuration reload on SIGHUP is only available on Unix");
+    }
+}
+
+/// Applies the hot-reloadable subset of a freshly loaded configuration.
+fn reload(hooks: &ReloadHooks, live: &watch::Sender<Arc<AppConfig>>, cache: &CacheManager) {
+    let candidate = match (hooks.load)() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!(
+                "Configuration reload rejected, keeping current config: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    let (next, outcome) = apply_reloadable(&live.borrow(), &candidate);
+
+    for field in &outcome.restart_required {
+        tracing::warn!(field = %field, "Configuration change ignored: restart required");
+    }
+    if outcome.applied.is_empty() {
+        tracing::info!("Configuration reloaded: no hot-reloadable changes");
+        return;
+    }
+
+    if outcome.applied("logging.level")
+        && let Err(e) = (hooks.set_log_level)(&next.logging.level)
+    {
+        tracing::error!(level = %next.logging.level, "Failed to apply log level: {}", e);
+    }
+    if outcome.applied("cache.default_ttl_seconds") {
+        cache.set_default_ttl(Duration::from_secs(next.cache.default_ttl_seconds));
+    }
+
+    live.send_replace(Arc::new(next));
+    tracing::info!(applied = ?outcome.applied, "Configuration reloaded");
+}
@@ -9,12 +9,13 @@ use axum::{
     middleware as axum_middleware,
     routing::{delete, get, post, put},
 };
-use tower_http::compression::CompressionLayer;
-use tower_http::cors::CorsLayer;
 use tower_http::trace::TraceLayer;
 
+use filehub_core::config::CorsGroup;
+
 use crate::handlers;
 use crate::middleware;
+use crate::middleware::cors::build_cors_layer;
 use crate::state::AppState;
 
 /// Build the complete Axum router with all routes and middleware.
@@ -24,8 +25,26 @@ use crate::state::AppState;
 pub fn build_router(state: AppState) -> Router {
     let max_upload = state.config.storage.max_upload_size_bytes as usize;
 
-    let api_routes = Router::new()
+    // CORS sits outside rate limiting so that rejected requests still
+    // carry the group's CORS headers.
+    let cors = &state.config.server.cors;
+    let group = |routes: Router<AppState>, group: CorsGroup| {
+        routes
+            .layer(axum_middleware::from_fn_with_state(
+                state.clone(),
+                middleware::rate_limit::rate_limit,
+            ))
+            .layer(build_cors_layer(cors.policy(group)))
+    };
+
+    let mut public_routes = Router::new()
+        .merge(public_share_routes())
+        .merge(health_routes());
+
+    // Retried POSTs replay their first response; see `middleware::idempotency`.
+    let mut authenticated_routes = Router::new()
         .merge(auth_routes())
+        .merge(capabilities_routes())
         .merge(user_routes())
         .merge(file_routes())
         .merge(folder_routes())
@@ -35,24 +54,86 @@ pub fn build_router(state: AppState) -> Router {
         .merge(notification_routes())
         .merge(presence_routes())
         .merge(search_routes())
-        .merge(admin_routes())
-        .merge(health_routes());
+        .merge(tag_routes());
 
-    let ws_routes = Router::new().route("/ws", get(handlers::ws::ws_handler));
+    // Public access is off unless enabled; its routes are then not even
+    // mounted.
+    if state.config.shares.public_access.enabled {
+        public_routes = public_routes.merge(public_access_routes());
+        authenticated_routes = authenticated_routes.merge(public_mark_routes());
+    }
+    let authenticated_routes = authenticated_routes
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            middleware::idempotency::idempotency,
+        ))
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            middleware::quota::quota,
+        ));
 
-    let cors = build_cors_layer(&state);
+    let api_routes = Router::new()
+        .merge(group(public_routes, CorsGroup::Public))
+        .merge(group(authenticated_routes, CorsGroup::Authenticated))
+        .merge(group(admin_routes(), CorsGroup::Admin));
 
-    Router::new()
+    let ws_routes = group(
+        Router::new().route("/ws", get(handlers::ws::ws_handler)),
+        CorsGroup::Authenticated,
+    );
+
+    let metrics_config = &state.config.server.metrics;
+
+    // Inside metrics and logging, so timed-out requests are still counted
+    // and logged with their 504.
+    let mut router = Router::new()
         .nest("/api", api_routes)
         .merge(ws_routes)
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            middleware::timeout::request_timeout,
+        ));
+
+    if metrics_config.enabled {
+        if metrics_config.public {
+            router = router.route(&metrics_config.path, get(handlers::metrics::metrics));
+        }
+        router = router.layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            middleware::metrics::track_metrics,
+        ));
+    }
+
+    router
         .layer(DefaultBodyLimit::max(max_upload))
-        .layer(CompressionLayer::new())
         .layer(TraceLayer::new_for_http())
-        .layer(cors)
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            middleware::impersonation::impersonation,
+        ))
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            middleware::auth::bearer_claims,
+        ))
         .layer(axum_middleware::from_fn_with_state(
             state.clone(),
             middleware::logging::request_logging,
         ))
+        .layer(axum_middleware::from_fn_with_state(
+            state.clone(),
+            middleware::client_ip::client_ip,
+        ))
+        .layer(axum_middleware::from_fn(middleware::request_id::request_id))
+        .with_state(state)
+}
+
+/// Build the router for the separate metrics listener (`server.metrics.admin_port`).
+pub fn build_metrics_router(state: AppState) -> Router {
+    Router::new()
+        .route(
+            &state.config.server.metrics.path,
+            get(handlers::metrics::metrics),
+        )
         .with_state(state)
 }
 
@@ -65,12 +146,23 @@ fn auth_routes() -> Router<AppState> {
         .route("/auth/me", get(handlers::auth::me))
 }
 
+/// Capability negotiation
+fn capabilities_routes() -> Router<AppState> {
+    Router::new().route("/capabilities", get(handlers::capabilities::capabilities))
+}
+
 /// User self-service endpoints
 fn user_routes() -> Router<AppState> {
     Router::new()
         .route("/users/me", get(handlers::user::get_profile))
         .route("/users/me", put(handlers::user::update_profile))
         .route("/users/me/password", put(handlers::user::change_password))
+        .route("/users/me/sessions", get(handlers::user::list_sessions))
+        .route("/users/me/usage", get(handlers::user::get_usage))
+        .route(
+            "/users/me/sessions/{id}",
+            delete(handlers::user::revoke_session),
+        )
 }
 
 /// File CRUD, upload, download, versions
@@ -80,18 +172,47 @@ fn file_routes() -> Router<AppState> {
         .route("/files/{id}", get(handlers::file::get_file))
         .route("/files/{id}", put(handlers::file::update_file))
         .route("/files/{id}", delete(handlers::file::delete_file))
-        .route("/files/{id}/download", get(handlers::file::download_file))
+        .route("/files/{id}/viewers", get(handlers::file::list_viewers))
+        .route(
+            "/files/{id}/download",
+            get(handlers::file::download_file).head(handlers::file::head_file),
+        )
+        .route(
+            "/files/{id}/download/presign",
+            get(handlers::file::presign_download),
+        )
         .route("/files/{id}/preview", get(handlers::file::preview_file))
+        .route(
+            "/files/{id}/preview/pages",
+            get(handlers::file::document_preview),
+        )
+        .route(
+            "/files/{id}/preview/pages/{page}",
+            get(handlers::file::document_preview_page),
+        )
+        .route(
+            "/files/{id}/conversions",
+            post(handlers::file::convert_file),
+        )
         .route("/files/{id}/versions", get(handlers::file::list_versions))
         .route(
             "/files/{id}/versions/{ver}",
             get(handlers::file::download_version),
         )
+        .route(
+            "/files/{id}/versions/{ver}/restore",
+            post(handlers::file::restore_version),
+        )
         .route("/files/upload", post(handlers::file::upload_file))
         .route(
             "/files/upload/initiate",
             post(handlers::file::initiate_chunked_upload),
         )
+        .route(
+            "/files/upload/presign",
+            post(handlers::file::presign_upload),
+        )
+        .route("/files/upload/{id}", get(handlers::file::upload_status))
         .route(
             "/files/upload/{id}/chunk/{n}",
             put(handlers::file::upload_chunk),
@@ -100,8 +221,18 @@ fn file_routes() -> Router<AppState> {
             "/files/upload/{id}/complete",
             post(handlers::file::complete_chunked_upload),
         )
+        .route(
+            "/files/upload/{id}/finalize",
+            post(handlers::file::finalize_direct_upload),
+        )
+        .route("/files/bulk", post(handlers::file::bulk_operation))
         .route("/files/{id}/move", put(handlers::file::move_file))
         .route("/files/{id}/copy", post(handlers::file::copy_file))
+        .route("/files/{id}/expiry", put(handlers::file::set_file_expiry))
+        .route(
+            "/files/{id}/expiry",
+            delete(handlers::file::clear_file_expiry),
+        )
         .route("/files/{id}/lock", post(handlers::file::lock_file))
         .route("/files/{id}/unlock", post(handlers::file::unlock_file))
 }
@@ -118,11 +249,40 @@ fn folder_routes() -> Router<AppState> {
             "/folders/{id}/children",
             get(handlers::folder::list_children),
         )
+        .route(
+            "/folders/{id}/metadata",
+            get(handlers::folder::get_folder_metadata),
+        )
+        .route(
+            "/folders/{id}/metadata",
+            put(handlers::folder::set_folder_metadata),
+        )
+        .route(
+            "/folders/{id}/preferences",
+            get(handlers::folder::get_folder_preferences),
+        )
+        .route(
+            "/folders/{id}/preferences",
+            put(handlers::folder::set_folder_preferences),
+        )
+        .route(
+            "/folders/{id}/preferences",
+            delete(handlers::folder::clear_folder_preferences),
+        )
         .route("/folders/{id}/tree", get(handlers::folder::get_tree))
+        .route(
+            "/folders/{id}/tree/levels",
+            get(handlers::folder::get_tree_levels),
+        )
+        .route("/folders/{id}/viewers", get(handlers::folder::list_viewers))
         .route("/folders/{id}/move", put(handlers::folder::move_folder))
+        .route(
+            "/folders/{id}/download",
+            get(handlers::folder::download_folder),
+        )
 }
 
-/// Share CRUD and public access
+/// Share CRUD
 fn share_routes() -> Router<AppState> {
     Router::new()
         .route("/shares", get(handlers::share::list_shares))
@@ -130,8 +290,68 @@ fn share_routes() -> Router<AppState> {
         .route("/shares/{id}", get(handlers::share::get_share))
         .route("/shares/{id}", put(handlers::share::update_share))
         .route("/shares/{id}", delete(handlers::share::revoke_share))
+        .route("/shares/{id}/stats", get(handlers::share::share_stats))
+        .route(
+            "/access-requests",
+            get(handlers::share::list_access_requests).post(handlers::share::request_access),
+        )
+        .route(
+            "/access-requests/mine",
+            get(handlers::share::my_access_requests),
+        )
+        .route(
+            "/access-requests/{id}/approve",
+            post(handlers::share::approve_access_request),
+        )
+        .route(
+            "/access-requests/{id}/deny",
+            post(handlers::share::deny_access_request),
+        )
+}
+
+/// Public share link access (no auth required)
+fn public_share_routes() -> Router<AppState> {
+    Router::new()
         .route("/s/{token}", get(handlers::share::access_share))
         .route("/s/{token}/verify", post(handlers::share::verify_share))
+        .route("/s/{token}/download", get(handlers::share::download_share))
+}
+
+/// Anonymous, read-only access to public content (no auth required)
+fn public_access_routes() -> Router<AppState> {
+    Router::new()
+        .route("/public/folders/{id}", get(handlers::public::get_folder))
+        .route(
+            "/public/folders/{id}/children",
+            get(handlers::public::list_children),
+        )
+        .route(
+            "/public/folders/{id}/files",
+            get(handlers::public::list_files),
+        )
+        .route("/public/files/{id}", get(handlers::public::get_file))
+        .route(
+            "/public/files/{id}/download",
+            get(handlers::public::download_file),
+        )
+}
+
+/// Marking files and folders public
+fn public_mark_routes() -> Router<AppState> {
+    Router::new()
+        .route("/files/{id}/public", put(handlers::public::publish_file))
+        .route(
+            "/files/{id}/public",
+            delete(handlers::public::unpublish_file),
+        )
+        .route(
+            "/folders/{id}/public",
+            put(handlers::public::publish_folder),
+        )
+        .route(
+            "/folders/{id}/public",
+            delete(handlers::public::unpublish_folder),
+        )
 }
 
 /// Permission/ACL management
@@ -153,6 +373,14 @@ fn permission_routes() -> Router<AppState> {
             "/permissions/entry/{id}",
             delete(handlers::permission::remove_permission),
         )
+        .route(
+            "/permissions/folder/{id}/recursive",
+            post(handlers::permission::grant_recursive),
+        )
+        .route(
+            "/permissions/folder/{id}/recompute",
+            post(handlers::permission::recompute_effective),
+        )
 }
 
 /// Storage listing and usage
@@ -198,6 +426,14 @@ fn notification_routes() -> Router<AppState> {
             "/notifications/preferences",
             put(handlers::notification::update_preferences),
         )
+        .route(
+            "/notifications/preferences/event-types/{event_type}",
+            put(handlers::notification::set_event_type_preference),
+        )
+        .route(
+            "/notifications/preferences/event-types/{event_type}",
+            delete(handlers::notification::reset_event_type_preference),
+        )
 }
 
 /// Presence endpoints
@@ -207,9 +443,45 @@ fn presence_routes() -> Router<AppState> {
     // .route("/presence/status", put(handlers::presence::update_presence))
 }
 
+/// Tag CRUD and tagged-file listing
+fn tag_routes() -> Router<AppState> {
+    Router::new()
+        .route("/tags", get(handlers::tag::list_tags))
+        .route("/tags", post(handlers::tag::create_tag))
+        .route("/tags/{id}", put(handlers::tag::rename_tag))
+        .route("/tags/{id}", delete(handlers::tag::delete_tag))
+        .route("/tags/{id}/files", get(handlers::tag::list_tagged_files))
+        .route("/files/{id}/tags", get(handlers::tag::list_file_tags))
+        .route("/files/{id}/tags", post(handlers::tag::add_file_tag))
+        .route(
+            "/files/{id}/tags/{tag_id}",
+            delete(handlers::tag::remove_file_tag),
+        )
+}
+
 /// Search endpoints
 fn search_routes() -> Router<AppState> {
-    Router::new().route("/files/search", get(handlers::search::search_files))
+    Router::new()
+        .route("/files/search", get(handlers::search::search_files))
+        .route(
+            "/files/search",
+            post(handlers::search::search_files_advanced),
+        )
+        .route("/files/export", get(handlers::search::export_files))
+        .route(
+            "/files/export",
+            post(handlers::search::export_files_advanced),
+        )
+        .route("/files/search/saved", get(handlers::search::list_saved))
+        .route("/files/search/saved", post(handlers::search::save_search))
+        .route(
+            "/files/search/saved/{id}",
+            delete(handlers::search::delete_saved),
+        )
+        .route(
+            "/files/search/saved/{id}/results",
+            get(handlers::search::run_saved),
+        )
 }
 
 /// Admin-only endpoints
@@ -231,6 +503,14 @@ fn admin_routes() -> Router<AppState> {
             "/admin/users/{id}/status",
             put(handlers::admin::users::change_status),
         )
+        .route(
+            "/admin/users/{id}/quota-tier",
+            put(handlers::admin::users::set_quota_tier),
+        )
+        .route(
+            "/admin/users/{id}/impersonate",
+            post(handlers::admin::impersonation::start_impersonation),
+        )
         .route(
             "/admin/users/{id}/reset-password",
             put(handlers::admin::users::reset_password),
@@ -264,6 +544,26 @@ fn admin_routes() -> Router<AppState> {
             "/admin/storages/{id}/sync",
             post(handlers::admin::storages::sync_storage),
         )
+        .route(
+            "/admin/storages/migrations",
+            get(handlers::admin::storages::list_migrations),
+        )
+        .route(
+            "/admin/storages/migrations",
+            post(handlers::admin::storages::start_migration),
+        )
+        .route(
+            "/admin/storages/migrations/{id}",
+            get(handlers::admin::storages::get_migration),
+        )
+        .route(
+            "/admin/storages/migrations/{id}/cancel",
+            post(handlers::admin::storages::cancel_migration),
+        )
+        .route(
+            "/admin/storages/migrations/{id}/resume",
+            post(handlers::admin::storages::resume_migration),
+        )
         // Session management
         .route(
             "/admin/sessions",
@@ -277,6 +577,14 @@ fn admin_routes() -> Router<AppState> {
             "/admin/sessions/{id}/terminate",
             post(handlers::admin::sessions::terminate_session),
         )
+        .route(
+            "/admin/impersonations",
+            get(handlers::admin::impersonation::list_impersonations),
+        )
+        .route(
+            "/admin/impersonations/{id}/end",
+            post(handlers::admin::impersonation::end_impersonation),
+        )
         .route(
             "/admin/sessions/terminate-bulk",
             post(handlers::admin::sessions::terminate_bulk),
@@ -319,6 +627,10 @@ fn admin_routes() -> Router<AppState> {
             "/admin/broadcast/history",
             get(handlers::admin::broadcast::broadcast_history),
         )
+        .route(
+            "/admin/broadcast/{id}/acks",
+            get(handlers::admin::broadcast::broadcast_acks),
+        )
         // License
         .route(
             "/admin/license/pool",
@@ -328,10 +640,59 @@ fn admin_routes() -> Router<AppState> {
             "/admin/license/pool/history",
             get(handlers::admin::license::pool_history),
         )
+        .route(
+            "/admin/license/pool/utilization",
+            get(handlers::admin::license::pool_utilization),
+        )
         .route(
             "/admin/license/pool/reconcile",
             post(handlers::admin::license::pool_reconcile),
         )
+        // Permissions
+        .route(
+            "/admin/permissions/explain",
+            get(handlers::admin::permissions::explain_permission),
+        )
+        .route(
+            "/admin/permission-templates",
+            get(handlers::admin::permission_templates::list_templates),
+        )
+        .route(
+            "/admin/permission-templates",
+            post(handlers::admin::permission_templates::create_template),
+        )
+        .route(
+            "/admin/permission-templates/{id}",
+            get(handlers::admin::permission_templates::get_template),
+        )
+        .route(
+            "/admin/permission-templates/{id}",
+            put(handlers::admin::permission_templates::update_template),
+        )
+        .route(
+            "/admin/permission-templates/{id}",
+            delete(handlers::admin::permission_templates::delete_template),
+        )
+        .route(
+            "/admin/permission-templates/{id}/apply",
+            post(handlers::admin::permission_templates::apply_template),
+        )
+        .route(
+            "/admin/folders/{id}/permission-template",
+            put(handlers::admin::permission_templates::attach_template),
+        )
+        .route(
+            "/admin/folders/{id}/retention",
+            get(handlers::admin::retention::get_retention),
+        )
+        .route(
+            "/admin/folders/{id}/retention",
+            put(handlers::admin::retention::set_retention),
+        )
+        .route(
+            "/admin/folders/{id}/retention",
+            delete(handlers::admin::retention::remove_retention),
+        )
         // Jobs
         .route("/admin/jobs", get(handlers::admin::jobs::list_jobs))
         .route("/admin/jobs/{id}", get(handlers::admin::jobs::get_job))
@@ -364,41 +725,8 @@ fn admin_routes() -> Router<AppState> {
 fn health_routes() -> Router<AppState> {
     Router::new()
         .route("/health", get(handlers::health::health))
+        .route("/health/live", get(handlers::health::health_live))
+        .route("/health/ready", get(handlers::health::health_ready))
         .route("/health/detailed", get(handlers::health::health_detailed))
-}
-
-/// Build CORS layer from configuration
-fn build_cors_layer(state: &AppState) -> CorsLayer {
-    use axum::http::Method;
-    use tower_http::cors::Any;
-
-    let cors_config = &state.config.server.cors;
-
-    let mut cors = CorsLayer::new();
-
-    if cors_config.allowed_origins.contains(&"*".to_string()) {
-        cors = cors.allow_origin(Any);
-    } else {
-        let origins: Vec<axum::http::HeaderValue> = cors_config
-            .allowed_origins
-            .iter()
-            .filter_map(|o| o.parse().ok())
-            .collect();
-        cors = cors.allow_origin(origins);
-    }
-
-    let methods: Vec<Method> = cors_config
-        .allowed_methods
-        .iter()
-        .filter_map(|m| m.parse().ok())
-        .collect();
-    cors = cors.allow_methods(methods);
-
-    if cors_config.allowed_headers.contains(&"*".to_string()) {
-        cors = cors.allow_headers(Any);
-    }
-
-    cors = cors.max_age(std::time::Duration::from_secs(cors_config.max_age_seconds));
-
-    cors
+        .route("/health/storage", get(handlers::health::health_storage))
 }
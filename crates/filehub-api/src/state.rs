@@ -2,9 +2,13 @@
 
 use std::sync::Arc;
 
+use tokio::sync::watch;
+
 use filehub_service::{
-    AccessService, AdminUserService, DownloadService, PreviewService, SearchService, SessionAudit,
-    TerminationService, TreeService, UserService, VersionService, WeeklyReportService,
+    AccessRequestService, AccessService, AdminUserService, ConversionRequestService,
+    DownloadService, ImpersonationService, PreviewService, PublicAccessService, QuotaService,
+    RetentionService, SearchService, SessionAudit, TerminationService, TreeService, UserService,
+    VersionService, WeeklyReportService,
 };
 use sqlx::PgPool;
 
@@ -18,9 +22,12 @@ use filehub_auth::session::manager::SessionManager;
 
 use filehub_cache::provider::CacheManager;
 use filehub_core::config::AppConfig;
+use filehub_core::events::EventBus;
+use filehub_http::HttpClient;
 use filehub_plugin::manager::PluginManager;
 use filehub_realtime::server::RealtimeEngine;
 use filehub_storage::manager::StorageManager;
+use plugin_flexnet::license::LicenseManager;
 
 use filehub_database::repositories::audit::AuditLogRepository;
 use filehub_database::repositories::file::FileRepository;
@@ -28,6 +35,7 @@ use filehub_database::repositories::folder::FolderRepository;
 use filehub_database::repositories::job::JobRepository;
 use filehub_database::repositories::license::LicenseCheckoutRepository;
 use filehub_database::repositories::notification::NotificationRepository;
+use filehub_database::repositories::outbox::OutboxRepository;
 use filehub_database::repositories::permission::AclRepository;
 use filehub_database::repositories::pool_snapshot::PoolSnapshotRepository;
 use filehub_database::repositories::session::SessionRepository;
@@ -39,10 +47,19 @@ use filehub_service::file::service::FileService;
 use filehub_service::file::upload::UploadService;
 use filehub_service::folder::service::FolderService;
 use filehub_service::notification::service::NotificationService;
+use filehub_service::permission::explain::PermissionExplainer;
 use filehub_service::permission::service::PermissionService;
+use filehub_service::permission::template::PermissionTemplateService;
 use filehub_service::session::service::SessionService;
 use filehub_service::share::service::ShareService;
 use filehub_service::storage::service::StorageService;
+use filehub_service::storage::transfer::TransferService;
+
+use crate::drain::DrainState;
+use crate::health::HealthMonitor;
+use crate::metrics::ApiMetrics;
+use crate::middleware::client_ip::TrustedProxies;
+use crate::middleware::impersonation::BlockedRoutes;
 
 /// Application state containing all shared dependencies.
 ///
@@ -51,8 +68,11 @@ use filehub_service::storage::service::StorageService;
 #[derive(Debug, Clone)]
 pub struct AppState {
     // ── Configuration ────────────────────────────────────────
-    /// Application configuration
+    /// Application configuration as loaded at startup
     pub config: Arc<AppConfig>,
+    /// Configuration with hot-reloaded fields applied; read this for
+    /// settings listed in `filehub_core::config::reload::HOT_RELOADABLE`
+    pub live_config: watch::Receiver<Arc<AppConfig>>,
 
     // ── Infrastructure ───────────────────────────────────────
     /// PostgreSQL connection pool
@@ -61,6 +81,8 @@ pub struct AppState {
     pub cache: Arc<CacheManager>,
     /// Storage provider manager
     pub storage_manager: Arc<StorageManager>,
+    /// Shared outbound HTTP client for integrations
+    pub http_client: HttpClient,
 
     // ── Auth ─────────────────────────────────────────────────
     /// JWT token encoder
@@ -81,8 +103,22 @@ pub struct AppState {
     // ── Plugins & Realtime ───────────────────────────────────
     /// Plugin manager (registry + dispatcher)
     pub plugin_manager: Arc<PluginManager>,
+    /// FlexNet license manager, when license enforcement is enabled
+    pub license_manager: Option<Arc<LicenseManager>>,
     /// WebSocket realtime engine
     pub realtime: Arc<RealtimeEngine>,
+    /// Domain event bus feeding realtime and audit subscribers
+    pub event_bus: EventBus,
+    /// Prometheus metrics registry
+    pub metrics: Arc<ApiMetrics>,
+    /// Dependency health checks
+    pub health: Arc<HealthMonitor>,
+    /// Set while the instance drains before shutdown
+    pub drain: Arc<DrainState>,
+    /// Reverse proxies whose forwarding headers are believed
+    pub trusted_proxies: Arc<TrustedProxies>,
+    /// Routes impersonation sessions may not use
+    pub impersonation_blocked: Arc<BlockedRoutes>,
 
     // ── Repositories ─────────────────────────────────────────
     /// User repository
@@ -105,6 +141,8 @@ pub struct AppState {
     pub audit_repo: Arc<AuditLogRepository>,
     /// Job repository
     pub job_repo: Arc<JobRepository>,
+    /// Domain event outbox repository
+    pub outbox_repo: Arc<OutboxRepository>,
     /// License checkout repository
     pub license_repo: Arc<LicenseCheckoutRepository>,
     /// Pool snapshot repository
@@ -123,8 +161,16 @@ pub struct AppState {
     pub notification_service: Arc<NotificationService>,
     /// Storage management service
     pub storage_service: Arc<StorageService>,
+    /// Cross-storage transfer and migration service
+    pub transfer_service: Arc<TransferService>,
     /// Permission management service
     pub permission_service: Arc<PermissionService>,
+    /// Permission template management
+    pub permission_template_service: Arc<PermissionTemplateService>,
+    /// Folder retention policies
+    pub retention_service: Arc<RetentionService>,
+    /// Permission decision introspection
+    pub permission_explainer: Arc<PermissionExplainer>,
     /// Session management service
     pub session_service: Arc<SessionService>,
     /// Audit service
@@ -139,14 +185,24 @@ pub struct AppState {
     pub download_service: Arc<DownloadService>,
     /// Preview service
     pub preview_service: Arc<PreviewService>,
+    /// CAD conversion request service
+    pub conversion_service: Arc<ConversionRequestService>,
     /// Version service
     pub version_service: Arc<VersionService>,
     /// Tree service
     pub tree_service: Arc<TreeService>,
     /// Termination service
     pub termination_service: Arc<TerminationService>,
+    /// Impersonation service
+    pub impersonation_service: Arc<ImpersonationService>,
     /// Search service
     pub search_service: Arc<SearchService>,
     /// Access service
     pub access_service: Arc<AccessService>,
+    /// Access request service
+    pub access_request_service: Arc<AccessRequestService>,
+    /// Public (anonymous) access service
+    pub public_access_service: Arc<PublicAccessService>,
+    /// API usage quota service
+    pub quota_service: Arc<QuotaService>,
 }
@@ -6,7 +6,84 @@ use uuid::Uuid;
 
 use filehub_core::error::AppError;
 use filehub_database::repositories::permission::AclRepository;
-use filehub_entity::permission::{AclEntry, AclPermission, ResourceType};
+use filehub_entity::permission::{AclEntry, AclInheritance, AclPermission, ResourceType};
+
+/// ACL entries that apply to a user on one resource: the user's own
+/// entries followed by public (anyone) entries.
+#[derive(Debug, Clone)]
+pub struct AclLevel {
+    /// Type of the resource the entries are on.
+    pub resource_type: ResourceType,
+    /// The resource the entries are on.
+    pub resource_id: Uuid,
+    /// The entries.
+    pub entries: Vec<AclEntry>,
+    /// Whether a blocking entry here stopped inheritance from further up.
+    pub stops_inheritance: bool,
+}
+
+impl AclLevel {
+    /// Loads the entries that apply to `user_id` on a resource.
+    pub(crate) async fn load(
+        repo: &AclRepository,
+        resource_type: ResourceType,
+        resource_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Self, AppError> {
+        let mut entries = repo
+            .find_for_user(resource_type, resource_id, user_id)
+            .await
+            .map_err(|e| AppError::internal(format!("ACL lookup failed: {e}")))?;
+        let public_entries = repo
+            .find_public_entries(resource_type, resource_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Public ACL lookup failed: {e}")))?;
+        entries.extend(public_entries);
+
+        Ok(Self {
+            resource_type,
+            resource_id,
+            entries,
+            stops_inheritance: false,
+        })
+    }
+
+    /// Entries that have not expired at `now`.
+    pub fn active(&self, now: chrono::DateTime<chrono::Utc>) -> impl Iterator<Item = &AclEntry> {
+        self.entries
+            .iter()
+            .filter(move |e| e.expires_at.map(|exp| exp > now).unwrap_or(true))
+    }
+
+    /// Whether an active entry here blocks inheritance.
+    pub fn has_block(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.active(now)
+            .any(|e| e.inheritance == AclInheritance::Block)
+    }
+}
+
+/// The ACL permission resolved for a user, with the levels that were
+/// consulted, nearest first.
+#[derive(Debug, Clone, Default)]
+pub struct AclResolution {
+    /// The highest permission found, if any.
+    pub permission: Option<AclPermission>,
+    /// The entry that granted `permission`.
+    pub applied_entry: Option<Uuid>,
+    /// Consulted levels: the resource itself, then inherited folders.
+    pub levels: Vec<AclLevel>,
+}
+
+impl AclResolution {
+    /// Raises the resolved permission to `entry`'s if it is higher.
+    pub(crate) fn consider(&mut self, entry: &AclEntry) {
+        let current = self.permission.as_ref().map(permission_level);
+        if current.is_none_or(|level| permission_level(&entry.permission) > level) {
+            self.permission = Some(entry.permission);
+            self.applied_entry = Some(entry.id);
+        }
+    }
+}
 
 /// Checks resource-level ACL permissions from the database.
 #[derive(Debug, Clone)]
@@ -103,26 +180,27 @@ impl AclChecker {
         resource_id: Uuid,
         user_id: Uuid,
     ) -> Result<Option<AclPermission>, AppError> {
-        let entries = self
-            .get_entries_for_user(resource_type, resource_id, user_id)
-            .await?;
-
-        let public_entries = self
-            .repo
-            .find_public_entries(resource_type, resource_id)
-            .await
-            .map_err(|e| AppError::internal(format!("Failed to query public ACL: {e}")))?;
-
-        let now = chrono::Utc::now();
-
-        let highest = entries
-            .iter()
-            .chain(public_entries.iter())
-            .filter(|e| e.expires_at.map(|exp| exp > now).unwrap_or(true))
-            .map(|e| &e.permission)
-            .max_by_key(|p| permission_level(p));
+        Ok(self
+            .explain_permission(resource_type, resource_id, user_id)
+            .await?
+            .permission)
+    }
 
-        Ok(highest.cloned())
+    /// Like [`get_highest_permission`](Self::get_highest_permission), but
+    /// also returns the entries that were consulted.
+    pub async fn explain_permission(
+        &self,
+        resource_type: ResourceType,
+        resource_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<AclResolution, AppError> {
+        let level = AclLevel::load(&self.repo, resource_type, resource_id, user_id).await?;
+        let mut resolution = AclResolution::default();
+        for entry in level.active(chrono::Utc::now()) {
+            resolution.consider(entry);
+        }
+        resolution.levels.push(level);
+        Ok(resolution)
     }
 }
 
@@ -5,6 +5,7 @@
 //! - An explicit entry on a child overrides inherited entries.
 //! - A "block" inheritance entry stops the cascade from propagating further.
 
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use uuid::Uuid;
@@ -12,9 +13,9 @@ use uuid::Uuid;
 use filehub_core::error::AppError;
 use filehub_database::repositories::folder::FolderRepository;
 use filehub_database::repositories::permission::AclRepository;
-use filehub_entity::permission::{AclEntry, AclInheritance, AclPermission, ResourceType};
+use filehub_entity::permission::{AclPermission, ResourceType};
 
-use super::checker::permission_level;
+use super::checker::{AclLevel, AclResolution};
 
 /// Resolves ACL permissions with folder hierarchy inheritance.
 #[derive(Debug, Clone)]
@@ -48,62 +49,43 @@ impl AclInheritanceResolver {
         folder_id: Uuid,
         user_id: Uuid,
     ) -> Result<Option<AclPermission>, AppError> {
+        Ok(self
+            .explain_folder_permission(folder_id, user_id)
+            .await?
+            .permission)
+    }
+
+    /// Like [`resolve_folder_permission`](Self::resolve_folder_permission),
+    /// but also returns every level of the chain that was consulted.
+    pub async fn explain_folder_permission(
+        &self,
+        folder_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<AclResolution, AppError> {
         let ancestors = self.get_folder_ancestry(folder_id).await?;
         let now = chrono::Utc::now();
 
-        let mut highest_permission: Option<AclPermission> = None;
+        let mut resolution = AclResolution::default();
 
         for ancestor_id in &ancestors {
-            let entries = self
-                .acl_repo
-                .find_for_user(ResourceType::Folder, *ancestor_id, user_id)
-                .await
-                .map_err(|e| AppError::internal(format!("ACL lookup failed: {e}")))?;
-
-            let public_entries = self
-                .acl_repo
-                .find_public_entries(ResourceType::Folder, *ancestor_id)
-                .await
-                .map_err(|e| AppError::internal(format!("Public ACL lookup failed: {e}")))?;
-
-            let all_entries: Vec<&AclEntry> = entries
-                .iter()
-                .chain(public_entries.iter())
-                .filter(|e| e.expires_at.map(|exp| exp > now).unwrap_or(true))
-                .collect();
-
-            // Check for block inheritance
-            let has_block = all_entries
-                .iter()
-                .any(|e| e.inheritance == AclInheritance::Block);
+            let mut level =
+                AclLevel::load(&self.acl_repo, ResourceType::Folder, *ancestor_id, user_id).await?;
 
             // Find highest permission at this level
-            for entry in &all_entries {
-                let level = permission_level(&entry.permission);
-                let current_level = highest_permission
-                    .as_ref()
-                    .map(|p| permission_level(p))
-                    .unwrap_or(0);
-
-                if highest_permission.is_none() || level > current_level {
-                    highest_permission = Some(entry.permission.clone());
-                }
-            }
-
-            // If this is the target folder itself and has direct entries,
-            // those take priority (but we still check ancestors unless blocked)
-            if *ancestor_id == folder_id && !all_entries.is_empty() {
-                // Direct entry found — it overrides inherited
-                // But we still return the highest we found
+            for entry in level.active(now) {
+                resolution.consider(entry);
             }
 
             // Stop if inheritance is blocked at this level
-            if has_block && *ancestor_id != folder_id {
+            level.stops_inheritance = *ancestor_id != folder_id && level.has_block(now);
+            let blocked = level.stops_inheritance;
+            resolution.levels.push(level);
+            if blocked {
                 break;
             }
         }
 
-        Ok(highest_permission)
+        Ok(resolution)
     }
 
     /// Resolves the effective ACL permission for a user on a file,
@@ -114,38 +96,39 @@ impl AclInheritanceResolver {
         folder_id: Uuid,
         user_id: Uuid,
     ) -> Result<Option<AclPermission>, AppError> {
+        Ok(self
+            .explain_file_permission(file_id, folder_id, user_id)
+            .await?
+            .permission)
+    }
+
+    /// Like [`resolve_file_permission`](Self::resolve_file_permission), but
+    /// also returns the file's entries and every folder level consulted.
+    pub async fn explain_file_permission(
+        &self,
+        file_id: Uuid,
+        folder_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<AclResolution, AppError> {
         // First check direct file-level entries
         let now = chrono::Utc::now();
+        let file_level =
+            AclLevel::load(&self.acl_repo, ResourceType::File, file_id, user_id).await?;
 
-        let file_entries = self
-            .acl_repo
-            .find_for_user(ResourceType::File, file_id, user_id)
-            .await
-            .map_err(|e| AppError::internal(format!("File ACL lookup failed: {e}")))?;
-
-        let file_public = self
-            .acl_repo
-            .find_public_entries(ResourceType::File, file_id)
-            .await
-            .map_err(|e| AppError::internal(format!("File public ACL lookup failed: {e}")))?;
-
-        let all_file: Vec<&AclEntry> = file_entries
-            .iter()
-            .chain(file_public.iter())
-            .filter(|e| e.expires_at.map(|exp| exp > now).unwrap_or(true))
-            .collect();
-
-        if !all_file.is_empty() {
+        if file_level.active(now).next().is_some() {
             // Direct file-level permission takes precedence
-            let highest = all_file
-                .iter()
-                .max_by_key(|e| permission_level(&e.permission))
-                .map(|e| e.permission.clone());
-            return Ok(highest);
+            let mut resolution = AclResolution::default();
+            for entry in file_level.active(now) {
+                resolution.consider(entry);
+            }
+            resolution.levels.push(file_level);
+            return Ok(resolution);
         }
 
         // No direct file entry — inherit from folder
-        self.resolve_folder_permission(folder_id, user_id).await
+        let mut resolution = self.explain_folder_permission(folder_id, user_id).await?;
+        resolution.levels.insert(0, file_level);
+        Ok(resolution)
     }
 
     /// Gets the ancestry chain for a folder, starting with the folder itself
@@ -157,3 +140,83 @@ impl AclInheritanceResolver {
             .map_err(|e| AppError::internal(format!("Failed to get folder ancestry: {e}")))
     }
 }
+
+/// Which folders of a subtree inherit an entry placed on its root.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubtreeReach {
+    /// Folders that inherit the entry, the root included.
+    pub reached: Vec<Uuid>,
+    /// Folders cut off by a blocking entry above them.
+    pub cut_off: Vec<Uuid>,
+}
+
+/// Works out which folders below `root` inherit an entry on it.
+///
+/// `folders` lists the subtree's folders below the root with their
+/// parents, parents before children; `blocking` holds those with an
+/// active blocking entry for the principal. As in
+/// [`AclInheritanceResolver::explain_folder_permission`], a blocking folder
+/// still inherits, but nothing below it does.
+pub fn subtree_reach(
+    root: Uuid,
+    folders: &[(Uuid, Option<Uuid>)],
+    blocking: &HashSet<Uuid>,
+) -> SubtreeReach {
+    // Whether entries from the root pass on to a folder's children.
+    let mut passes = HashMap::from([(root, true)]);
+    let mut reach = SubtreeReach {
+        reached: vec![root],
+        cut_off: Vec::new(),
+    };
+    for (folder_id, parent_id) in folders {
+        let inherits = parent_id
+            .and_then(|parent_id| passes.get(&parent_id).copied())
+            .unwrap_or(false);
+        passes.insert(*folder_id, inherits && !blocking.contains(folder_id));
+        if inherits {
+            reach.reached.push(*folder_id);
+        } else {
+            reach.cut_off.push(*folder_id);
+        }
+    }
+    reach
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subtree_reach_stops_below_blocking_folders() {
+        // root
+        // ├── a
+        // │   └── a1 (blocks)
+        // │       └── a1x
+        // │           └── a1xy
+        // └── b (blocks)
+        //     └── b1
+        let id = |n: u128| Uuid::from_u128(n);
+        let (root, a, a1, a1x, a1xy, b, b1) = (id(1), id(2), id(3), id(4), id(5), id(6), id(7));
+        let folders = [
+            (a, Some(root)),
+            (b, Some(root)),
+            (a1, Some(a)),
+            (b1, Some(b)),
+            (a1x, Some(a1)),
+            (a1xy, Some(a1x)),
+        ];
+        let blocking = HashSet::from([a1, b]);
+
+        let reach = subtree_reach(root, &folders, &blocking);
+        assert_eq!(reach.reached, [root, a, b, a1]);
+        assert_eq!(reach.cut_off, [b1, a1x, a1xy]);
+
+        let open = subtree_reach(root, &folders, &HashSet::new());
+        assert_eq!(open.reached.len(), 7);
+        assert!(open.cut_off.is_empty());
+
+        // A block on the root itself does not matter to its own entries.
+        let root_blocks = subtree_reach(root, &folders, &HashSet::from([root]));
+        assert_eq!(root_blocks.reached.len(), 7);
+    }
+}
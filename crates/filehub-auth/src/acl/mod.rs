@@ -3,7 +3,9 @@
 pub mod checker;
 pub mod inheritance;
 pub mod resolver;
+pub mod trace;
 
 pub use checker::AclChecker;
-pub use inheritance::AclInheritanceResolver;
+pub use inheritance::{AclInheritanceResolver, SubtreeReach, subtree_reach};
 pub use resolver::EffectivePermissionResolver;
+pub use trace::PermissionTrace;
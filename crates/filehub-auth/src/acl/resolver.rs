@@ -11,6 +11,7 @@ use std::sync::Arc;
 
 use uuid::Uuid;
 
+use filehub_cache::keys;
 use filehub_cache::provider::CacheManager;
 use filehub_core::error::AppError;
 use filehub_core::traits::CacheProvider;
@@ -21,6 +22,12 @@ use crate::rbac::RbacEnforcer;
 
 use super::checker::AclChecker;
 use super::inheritance::AclInheritanceResolver;
+use super::trace::{PermissionTrace, TraceAclEntry, TraceOutcome, TraceStage, TraceStep};
+
+/// Above this many resources, [`EffectivePermissionResolver::invalidate_resources`]
+/// drops every cached permission in one pass rather than one pattern per
+/// resource.
+const BULK_INVALIDATION_THRESHOLD: usize = 256;
 
 /// Result of resolving effective permissions.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -100,9 +107,11 @@ impl EffectivePermissionResolver {
         required_permission: AclPermission,
     ) -> Result<EffectivePermission, AppError> {
         // Check cache first
-        let cache_key = format!(
-            "perm:{}:{}:{}:{}",
-            user_id, resource_type, resource_id, required_permission
+        let cache_key = keys::permission_check(
+            &resource_type.to_string(),
+            resource_id,
+            user_id,
+            &required_permission.to_string(),
         );
 
         if let Ok(Some(cached)) = self.cache.get(&cache_key).await {
@@ -123,12 +132,8 @@ impl EffectivePermissionResolver {
             )
             .await?;
 
-        // Cache the result for 5 minutes
         if let Ok(serialized) = serde_json::to_string(&result) {
-            let _ = self
-                .cache
-                .set(&cache_key, &serialized, std::time::Duration::from_secs(300))
-                .await;
+            let _ = self.cache.put(&cache_key, &serialized).await;
         }
 
         Ok(result)
@@ -145,67 +150,157 @@ impl EffectivePermissionResolver {
         parent_folder_id: Option<Uuid>,
         required_permission: AclPermission,
     ) -> Result<EffectivePermission, AppError> {
+        let trace = self
+            .explain(
+                user_id,
+                user_role,
+                resource_type,
+                resource_id,
+                owner_id,
+                parent_folder_id,
+                required_permission,
+            )
+            .await?;
+        Ok(trace.decision)
+    }
+
+    /// Resolves the effective permission and records every check made.
+    ///
+    /// Reaches the same decision as [`resolve`](Self::resolve) but never
+    /// reads or writes the permission cache.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn explain(
+        &self,
+        user_id: Uuid,
+        user_role: &UserRole,
+        resource_type: ResourceType,
+        resource_id: Uuid,
+        owner_id: Uuid,
+        parent_folder_id: Option<Uuid>,
+        required_permission: AclPermission,
+    ) -> Result<PermissionTrace, AppError> {
+        let mut trace = PermissionTrace {
+            user_id,
+            role: *user_role,
+            resource_type,
+            resource_id,
+            required: required_permission,
+            steps: Vec::new(),
+            decision: EffectivePermission {
+                granted: false,
+                acl_permission: None,
+                source: PermissionSource::Denied,
+            },
+        };
+
         // 1. Admin bypass
         if self.rbac.is_admin(user_role) {
-            return Ok(EffectivePermission {
+            trace.steps.push(TraceStep::new(
+                TraceStage::Rbac,
+                TraceOutcome::Granted,
+                format!("role '{user_role}' bypasses resource permissions"),
+            ));
+            trace.decision = EffectivePermission {
                 granted: true,
                 acl_permission: Some(AclPermission::Owner),
                 source: PermissionSource::AdminBypass,
-            });
+            };
+            return Ok(trace);
         }
+        trace.steps.push(TraceStep::new(
+            TraceStage::Rbac,
+            TraceOutcome::Skipped,
+            format!("role '{user_role}' has no resource bypass"),
+        ));
 
         // 2. Owner check
         if user_id == owner_id {
-            return Ok(EffectivePermission {
+            trace.steps.push(TraceStep::new(
+                TraceStage::Owner,
+                TraceOutcome::Granted,
+                "user owns the resource",
+            ));
+            trace.decision = EffectivePermission {
                 granted: true,
                 acl_permission: Some(AclPermission::Owner),
                 source: PermissionSource::Owner,
-            });
+            };
+            return Ok(trace);
         }
+        trace.steps.push(TraceStep::new(
+            TraceStage::Owner,
+            TraceOutcome::Skipped,
+            format!("resource is owned by {owner_id}"),
+        ));
 
         // 3. ACL check (with inheritance for files/folders)
-        let acl_permission = match resource_type {
+        let resolution = match resource_type {
             ResourceType::File => {
                 if let Some(folder_id) = parent_folder_id {
                     self.inheritance
-                        .resolve_file_permission(resource_id, folder_id, user_id)
+                        .explain_file_permission(resource_id, folder_id, user_id)
                         .await?
                 } else {
                     self.acl_checker
-                        .get_highest_permission(resource_type, resource_id, user_id)
+                        .explain_permission(resource_type, resource_id, user_id)
                         .await?
                 }
             }
             ResourceType::Folder => {
                 self.inheritance
-                    .resolve_folder_permission(resource_id, user_id)
+                    .explain_folder_permission(resource_id, user_id)
                     .await?
             }
             ResourceType::Storage => {
                 self.acl_checker
-                    .get_highest_permission(resource_type, resource_id, user_id)
+                    .explain_permission(resource_type, resource_id, user_id)
                     .await?
             }
         };
 
-        if let Some(ref perm) = acl_permission {
-            if super::checker::permission_level(perm)
+        let acl_permission = resolution.permission;
+        let granted = acl_permission.as_ref().is_some_and(|perm| {
+            super::checker::permission_level(perm)
                 >= super::checker::permission_level(&required_permission)
-            {
-                return Ok(EffectivePermission {
-                    granted: true,
-                    acl_permission: Some(perm.clone()),
-                    source: PermissionSource::Acl,
-                });
-            }
+        });
+        let mut detail = match &acl_permission {
+            Some(perm) if granted => format!("'{perm}' meets required '{required_permission}'"),
+            Some(perm) => format!("highest is '{perm}', below required '{required_permission}'"),
+            None => "no active entry applies to the user".to_string(),
+        };
+        if let Some(level) = resolution.levels.iter().find(|l| l.stops_inheritance) {
+            detail.push_str(&format!(
+                "; inheritance blocked at {} {}",
+                level.resource_type, level.resource_id
+            ));
         }
+        let mut step = TraceStep::new(
+            TraceStage::Acl,
+            if granted {
+                TraceOutcome::Granted
+            } else {
+                TraceOutcome::Denied
+            },
+            detail,
+        );
+        step.acl_entries = TraceAclEntry::from_resolution(&resolution, resource_id);
+        trace.steps.push(step);
 
-        // 4. Denied
-        Ok(EffectivePermission {
-            granted: false,
-            acl_permission,
-            source: PermissionSource::Denied,
-        })
+        trace.decision = if granted {
+            EffectivePermission {
+                granted: true,
+                acl_permission,
+                source: PermissionSource::Acl,
+            }
+        } else {
+            // 4. Denied
+            EffectivePermission {
+                granted: false,
+                acl_permission,
+                source: PermissionSource::Denied,
+            }
+        };
+        Ok(trace)
     }
 
     /// Checks and returns an error if the user doesn't have the required permission.
@@ -254,23 +349,55 @@ impl EffectivePermissionResolver {
             AclPermission::Editor,
             AclPermission::Owner,
         ] {
-            let key = format!(
-                "perm:{}:{}:{}:{}",
-                user_id, resource_type, resource_id, perm
+            let key = keys::permission_check(
+                &resource_type.to_string(),
+                resource_id,
+                user_id,
+                &perm.to_string(),
             );
             let _ = self.cache.delete(&key).await;
         }
         Ok(())
     }
 
+    /// Invalidates every cached permission of a user, such as after a
+    /// grant on a folder that its contents inherit.
+    pub async fn invalidate_user_cache(&self, user_id: Uuid) -> Result<(), AppError> {
+        let _ = self
+            .cache
+            .delete_pattern(&keys::permission_user_pattern(user_id))
+            .await;
+        Ok(())
+    }
+
     /// Invalidates all cached permissions for a specific resource.
     pub async fn invalidate_resource_cache(
         &self,
         resource_type: ResourceType,
         resource_id: Uuid,
     ) -> Result<(), AppError> {
-        let pattern = format!("perm:*:{}:{}:*", resource_type, resource_id);
+        let pattern = keys::permission_resource_pattern(&resource_type.to_string(), resource_id);
         let _ = self.cache.delete_pattern(&pattern).await;
         Ok(())
     }
+
+    /// Invalidates the cached permissions of many resources, such as a
+    /// folder subtree. Large sets drop the whole permission cache at once,
+    /// which is cheaper than matching each resource. Returns whether the
+    /// whole cache was dropped.
+    pub async fn invalidate_resources(&self, resources: &[(ResourceType, Uuid)]) -> bool {
+        if resources.len() > BULK_INVALIDATION_THRESHOLD {
+            let _ = self
+                .cache
+                .delete_pattern(&keys::permission_all_pattern())
+                .await;
+            return true;
+        }
+        for (resource_type, resource_id) in resources {
+            let pattern =
+                keys::permission_resource_pattern(&resource_type.to_string(), *resource_id);
+            let _ = self.cache.delete_pattern(&pattern).await;
+        }
+        false
+    }
 }
@@ -0,0 +1,291 @@
+//! Decision traces for effective permission resolution.
+//!
+//! A [`PermissionTrace`] records every check the resolver made for one
+//! user and resource: the role bypass, ownership, the ACL entries on the
+//! resource and its inherited folders, and user shares. It serializes to
+//! JSON for tooling and implements `Display` for people.
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use filehub_entity::permission::{AclEntry, AclInheritance, AclPermission, ResourceType};
+use filehub_entity::share::ShareType;
+use filehub_entity::user::UserRole;
+
+use super::checker::AclResolution;
+use super::resolver::EffectivePermission;
+
+/// Full record of how an effective permission was decided.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionTrace {
+    /// The user the permission was resolved for.
+    pub user_id: Uuid,
+    /// The user's role.
+    pub role: UserRole,
+    /// Type of the target resource.
+    pub resource_type: ResourceType,
+    /// The target resource.
+    pub resource_id: Uuid,
+    /// The permission level that was asked for.
+    pub required: AclPermission,
+    /// Checks in the order they were made.
+    pub steps: Vec<TraceStep>,
+    /// The final decision, as `resolve` would return it.
+    pub decision: EffectivePermission,
+}
+
+/// One check made during resolution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceStep {
+    /// Which check this was.
+    pub stage: TraceStage,
+    /// What it concluded.
+    pub outcome: TraceOutcome,
+    /// Human-readable explanation.
+    pub detail: String,
+    /// ACL entries consulted by an `acl` step.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub acl_entries: Vec<TraceAclEntry>,
+    /// Shares found by a `share` step.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shares: Vec<TraceShare>,
+}
+
+/// The checks a trace can contain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceStage {
+    /// Role-based checks: the admin bypass and system permissions.
+    Rbac,
+    /// Resource ownership.
+    Owner,
+    /// ACL entries on the resource and inherited folders.
+    Acl,
+    /// Shares with the user.
+    Share,
+}
+
+impl TraceStage {
+    /// Name used in JSON and in the text rendering.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rbac => "rbac",
+            Self::Owner => "owner",
+            Self::Acl => "acl",
+            Self::Share => "share",
+        }
+    }
+}
+
+/// What a check concluded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceOutcome {
+    /// The check granted access.
+    Granted,
+    /// The check applied but did not grant access.
+    Denied,
+    /// The check did not apply, or was not part of the decision.
+    Skipped,
+}
+
+impl TraceOutcome {
+    /// Name used in JSON and in the text rendering.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Granted => "granted",
+            Self::Denied => "denied",
+            Self::Skipped => "skipped",
+        }
+    }
+}
+
+/// An ACL entry as seen by the resolver.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceAclEntry {
+    /// ACL entry ID.
+    pub entry_id: Uuid,
+    /// Type of the resource the entry is on.
+    pub resource_type: ResourceType,
+    /// Resource the entry is on; differs from the target when inherited.
+    pub resource_id: Uuid,
+    /// Whether the entry is on the target itself rather than inherited.
+    pub direct: bool,
+    /// `user:<id>` or `anyone`.
+    pub subject: String,
+    /// Permission the entry grants.
+    pub permission: AclPermission,
+    /// Inheritance mode of the entry.
+    pub inheritance: AclInheritance,
+    /// When the entry expires.
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Whether this entry supplied the resolved permission.
+    pub applied: bool,
+}
+
+/// A share of the resource (or an ancestor folder) with the user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceShare {
+    /// Share ID.
+    pub share_id: Uuid,
+    /// Type of share.
+    pub share_type: ShareType,
+    /// Type of the shared resource.
+    pub resource_type: ResourceType,
+    /// The shared resource.
+    pub resource_id: Uuid,
+    /// Permission the share grants.
+    pub permission: AclPermission,
+    /// Whether the share is active, unexpired and under its download limit.
+    pub valid: bool,
+}
+
+impl TraceStep {
+    /// A step with no entries or shares.
+    pub fn new(stage: TraceStage, outcome: TraceOutcome, detail: impl Into<String>) -> Self {
+        Self {
+            stage,
+            outcome,
+            detail: detail.into(),
+            acl_entries: Vec::new(),
+            shares: Vec::new(),
+        }
+    }
+}
+
+impl TraceAclEntry {
+    /// Lists the entries consulted for `resolution`, nearest level first.
+    pub fn from_resolution(resolution: &AclResolution, target_id: Uuid) -> Vec<Self> {
+        let now = Utc::now();
+        resolution
+            .levels
+            .iter()
+            .flat_map(|level| level.active(now))
+            .map(|entry| Self::new(entry, target_id, resolution.applied_entry))
+            .collect()
+    }
+
+    fn new(entry: &AclEntry, target_id: Uuid, applied_entry: Option<Uuid>) -> Self {
+        let subject = match entry.user_id {
+            Some(user_id) if !entry.is_anyone.unwrap_or(false) => format!("user:{user_id}"),
+            _ => "anyone".to_string(),
+        };
+        Self {
+            entry_id: entry.id,
+            resource_type: entry.resource_type,
+            resource_id: entry.resource_id,
+            direct: entry.resource_id == target_id,
+            subject,
+            permission: entry.permission,
+            inheritance: entry.inheritance,
+            expires_at: entry.expires_at,
+            applied: applied_entry == Some(entry.id),
+        }
+    }
+}
+
+impl fmt::Display for PermissionTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "user {} (role {}) -> {} {}, requires {}",
+            self.user_id, self.role, self.resource_type, self.resource_id, self.required
+        )?;
+
+        for step in &self.steps {
+            writeln!(
+                f,
+                "  [{:<7}] {:<5} {}",
+                step.outcome.as_str(),
+                step.stage.as_str(),
+                step.detail
+            )?;
+            for entry in &step.acl_entries {
+                let mut notes = vec![if entry.direct { "direct" } else { "inherited" }];
+                if entry.applied {
+                    notes.push("applied");
+                }
+                writeln!(
+                    f,
+                    "              acl {} on {} {}: {} for {}, {} ({})",
+                    entry.entry_id,
+                    entry.resource_type,
+                    entry.resource_id,
+                    entry.permission,
+                    entry.subject,
+                    entry.inheritance,
+                    notes.join(", ")
+                )?;
+            }
+            for share in &step.shares {
+                writeln!(
+                    f,
+                    "              share {} on {} {}: {} ({})",
+                    share.share_id,
+                    share.resource_type,
+                    share.resource_id,
+                    share.permission,
+                    if share.valid { "valid" } else { "inactive" }
+                )?;
+            }
+        }
+
+        let permission = self
+            .decision
+            .acl_permission
+            .as_ref()
+            .map_or_else(|| "none".to_string(), ToString::to_string);
+        write!(
+            f,
+            "decision: {} via {} (effective permission: {})",
+            if self.decision.granted {
+                "GRANTED"
+            } else {
+                "DENIED"
+            },
+            serde_json::to_value(&self.decision.source)
+                .ok()
+                .and_then(|v| v.as_str().map(String::from))
+                .unwrap_or_default(),
+            permission
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acl::resolver::PermissionSource;
+
+    #[test]
+    fn test_trace_renders_and_serializes() {
+        let trace = PermissionTrace {
+            user_id: Uuid::nil(),
+            role: UserRole::Viewer,
+            resource_type: ResourceType::Folder,
+            resource_id: Uuid::nil(),
+            required: AclPermission::Editor,
+            steps: vec![
+                TraceStep::new(TraceStage::Rbac, TraceOutcome::Skipped, "no admin bypass"),
+                TraceStep::new(TraceStage::Acl, TraceOutcome::Denied, "viewer < editor"),
+            ],
+            decision: EffectivePermission {
+                granted: false,
+                acl_permission: Some(AclPermission::Viewer),
+                source: PermissionSource::Denied,
+            },
+        };
+
+        let text = trace.to_string();
+        assert!(text.contains("[skipped] rbac"));
+        assert!(text.ends_with("decision: DENIED via denied (effective permission: viewer)"));
+
+        let json = serde_json::to_value(&trace).unwrap();
+        assert_eq!(json["steps"][1]["stage"], "acl");
+        assert_eq!(json["steps"][1]["outcome"], "denied");
+        assert!(json["steps"][0].get("acl_entries").is_none());
+    }
+}
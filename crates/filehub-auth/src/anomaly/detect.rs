@@ -0,0 +1,238 @@
+//! Deciding whether a login location is anomalous.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use filehub_core::config::LoginAnomalyConfig;
+use filehub_entity::session::LoginLocation;
+
+use super::geoip::GeoLocation;
+
+/// Mean Earth radius in kilometres.
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// Why a login was flagged.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LoginAnomaly {
+    /// The previous login was too far away to have travelled from since.
+    ImpossibleTravel {
+        /// Country of the previous login.
+        previous_country: String,
+        /// Great-circle distance between the two logins, in km.
+        distance_km: f64,
+        /// Speed the travel would have needed, in km/h.
+        speed_kmh: f64,
+    },
+    /// None of the recent logins were from this country.
+    NewCountry {
+        /// Countries of the recent logins, most recent first.
+        known_countries: Vec<String>,
+    },
+}
+
+impl LoginAnomaly {
+    /// Short machine-readable kind.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::ImpossibleTravel { .. } => "impossible_travel",
+            Self::NewCountry { .. } => "new_country",
+        }
+    }
+
+    /// One-line description for the user.
+    pub fn describe(&self, current: &GeoLocation) -> String {
+        match self {
+            Self::ImpossibleTravel {
+                previous_country,
+                distance_km,
+                ..
+            } => format!(
+                "New login from {} only shortly after a login from {previous_country}, \
+                 {distance_km:.0} km away.",
+                current.country_code
+            ),
+            Self::NewCountry { .. } => format!(
+                "New login from {}, a country you have not recently logged in from.",
+                current.country_code
+            ),
+        }
+    }
+}
+
+/// Compares a login at `current` against `history` (most recent first).
+///
+/// A user without history is never flagged; their first located login
+/// becomes the baseline. Impossible travel is checked against the most
+/// recent located login and takes precedence over a new country.
+pub fn detect_anomaly(
+    current: &GeoLocation,
+    now: DateTime<Utc>,
+    history: &[LoginLocation],
+    config: &LoginAnomalyConfig,
+) -> Option<LoginAnomaly> {
+    if history.is_empty() {
+        return None;
+    }
+
+    let here = coordinates(current.latitude, current.longitude);
+    let previous = history
+        .iter()
+        .find_map(|login| Some((login, coordinates(login.latitude, login.longitude)?)));
+    if let (Some(here), Some((login, there))) = (here, previous) {
+        let distance_km = haversine_km(here, there);
+        if distance_km > config.min_distance_km {
+            // Clamp so that simultaneous logins count as infinitely fast
+            // without dividing by zero.
+            let elapsed_seconds = (now - login.created_at).num_seconds().max(1) as f64;
+            let speed_kmh = distance_km / (elapsed_seconds / 3600.0);
+            if speed_kmh > config.max_speed_kmh {
+                return Some(LoginAnomaly::ImpossibleTravel {
+                    previous_country: login.country_code.clone(),
+                    distance_km,
+                    speed_kmh,
+                });
+            }
+        }
+    }
+
+    if config.alert_on_new_country
+        && !history
+            .iter()
+            .any(|login| login.country_code == current.country_code)
+    {
+        let mut known_countries: Vec<String> = Vec::new();
+        for login in history {
+            if !known_countries.contains(&login.country_code) {
+                known_countries.push(login.country_code.clone());
+            }
+        }
+        return Some(LoginAnomaly::NewCountry { known_countries });
+    }
+
+    None
+}
+
+/// Latitude and longitude, if both are known.
+fn coordinates(latitude: Option<f64>, longitude: Option<f64>) -> Option<(f64, f64)> {
+    Some((latitude?, longitude?))
+}
+
+/// Great-circle distance between two `(latitude, longitude)` points in
+/// degrees, in km.
+pub fn haversine_km((lat1, lon1): (f64, f64), (lat2, lon2): (f64, f64)) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_phi = (lat2 - lat1).to_radians();
+    let d_lambda = (lon2 - lon1).to_radians();
+    let a = (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::Duration;
+    use uuid::Uuid;
+
+    const PARIS: (f64, f64) = (48.8566, 2.3522);
+    const BERLIN: (f64, f64) = (52.52, 13.405);
+    const NEW_YORK: (f64, f64) = (40.7128, -74.006);
+
+    fn here(country: &str, (lat, lon): (f64, f64)) -> GeoLocation {
+        GeoLocation {
+            country_code: country.to_string(),
+            region: None,
+            latitude: Some(lat),
+            longitude: Some(lon),
+        }
+    }
+
+    fn login(country: &str, (lat, lon): (f64, f64), at: DateTime<Utc>) -> LoginLocation {
+        LoginLocation {
+            id: Uuid::new_v4(),
+            user_id: Uuid::nil(),
+            session_id: None,
+            ip_address: "203.0.113.1".parse().unwrap(),
+            country_code: country.to_string(),
+            region: None,
+            latitude: Some(lat),
+            longitude: Some(lon),
+            created_at: at,
+        }
+    }
+
+    #[test]
+    fn test_haversine_distance() {
+        let d = haversine_km(PARIS, NEW_YORK);
+        assert!((d - 5837.0).abs() < 10.0, "{d}");
+        assert_eq!(haversine_km(PARIS, PARIS), 0.0);
+    }
+
+    #[test]
+    fn test_first_login_is_baseline() {
+        let config = LoginAnomalyConfig::default();
+        assert!(detect_anomaly(&here("FR", PARIS), Utc::now(), &[], &config).is_none());
+    }
+
+    #[test]
+    fn test_impossible_travel() {
+        let config = LoginAnomalyConfig::default();
+        let now = Utc::now();
+        let history = [login("FR", PARIS, now - Duration::hours(1))];
+
+        let anomaly = detect_anomaly(&here("US", NEW_YORK), now, &history, &config).unwrap();
+        assert_eq!(anomaly.kind(), "impossible_travel");
+        let LoginAnomaly::ImpossibleTravel { speed_kmh, .. } = anomaly else {
+            unreachable!()
+        };
+        assert!(speed_kmh > 5000.0);
+
+        // A day is long enough to fly; still a new country though.
+        let history = [login("FR", PARIS, now - Duration::hours(24))];
+        let anomaly = detect_anomaly(&here("US", NEW_YORK), now, &history, &config).unwrap();
+        assert_eq!(anomaly.kind(), "new_country");
+    }
+
+    #[test]
+    fn test_nearby_logins_are_not_travel() {
+        let config = LoginAnomalyConfig::default();
+        let now = Utc::now();
+        // Same instant, but within the geolocation noise floor.
+        let history = [login("FR", PARIS, now)];
+        let nearby = here("FR", (48.9, 2.5));
+        assert!(detect_anomaly(&nearby, now, &history, &config).is_none());
+    }
+
+    #[test]
+    fn test_new_country() {
+        let mut config = LoginAnomalyConfig::default();
+        let now = Utc::now();
+        let history = [
+            login("FR", PARIS, now - Duration::days(2)),
+            login("FR", PARIS, now - Duration::days(3)),
+        ];
+
+        let anomaly = detect_anomaly(&here("DE", BERLIN), now, &history, &config).unwrap();
+        assert_eq!(
+            anomaly,
+            LoginAnomaly::NewCountry {
+                known_countries: vec!["FR".to_string()]
+            }
+        );
+        assert!(detect_anomaly(&here("FR", PARIS), now, &history, &config).is_none());
+
+        config.alert_on_new_country = false;
+        assert!(detect_anomaly(&here("DE", BERLIN), now, &history, &config).is_none());
+    }
+
+    #[test]
+    fn test_logins_without_coordinates_only_compare_countries() {
+        let config = LoginAnomalyConfig::default();
+        let now = Utc::now();
+        let mut previous = login("FR", PARIS, now);
+        previous.latitude = None;
+        let anomaly = detect_anomaly(&here("US", NEW_YORK), now, &[previous], &config).unwrap();
+        assert_eq!(anomaly.kind(), "new_country");
+    }
+}
@@ -0,0 +1,206 @@
+//! Login-time anomaly checks and their side effects.
+
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use chrono::Utc;
+use tracing::warn;
+use uuid::Uuid;
+
+use filehub_core::config::{AnomalyAction, LoginAnomalyConfig};
+use filehub_core::error::{AppError, codes};
+use filehub_database::repositories::audit::AuditLogRepository;
+use filehub_database::repositories::login_location::LoginLocationRepository;
+use filehub_database::repositories::notification::NotificationRepository;
+use filehub_entity::audit::model::CreateAuditLogEntry;
+use filehub_entity::notification::NotificationCategory;
+use filehub_entity::session::CreateLoginLocation;
+use filehub_entity::user::User;
+
+use super::detect::{LoginAnomaly, detect_anomaly};
+use super::geoip::{GeoIpResolver, GeoLocation};
+
+/// Checks each login against the user's recent login locations.
+///
+/// An anomalous login is reported to the user with a high-priority
+/// `security` notification and recorded as an `auth.login_anomaly` audit
+/// entry. Under [`AnomalyAction::RequireMfa`] it is also refused with
+/// `AUTH_MFA_REQUIRED`, for the client to re-verify with a second factor.
+///
+/// Every failure here — no database, an unknown address, a repository
+/// error — lets the login through unchecked.
+#[derive(Debug)]
+pub struct LoginAnomalyDetector {
+    /// Address resolver.
+    resolver: GeoIpResolver,
+    /// Login location history.
+    locations: Arc<LoginLocationRepository>,
+    /// Audit log repository.
+    audit_repo: Arc<AuditLogRepository>,
+    /// Notification repository.
+    notif_repo: Arc<NotificationRepository>,
+    /// Detection settings.
+    config: LoginAnomalyConfig,
+}
+
+impl LoginAnomalyDetector {
+    /// Creates a detector, loading the configured GeoIP database.
+    pub fn new(
+        config: LoginAnomalyConfig,
+        locations: Arc<LoginLocationRepository>,
+        audit_repo: Arc<AuditLogRepository>,
+        notif_repo: Arc<NotificationRepository>,
+    ) -> Self {
+        Self::with_resolver(
+            GeoIpResolver::open(&config.geoip_database),
+            config,
+            locations,
+            audit_repo,
+            notif_repo,
+        )
+    }
+
+    /// Creates a detector around an already loaded resolver.
+    pub fn with_resolver(
+        resolver: GeoIpResolver,
+        config: LoginAnomalyConfig,
+        locations: Arc<LoginLocationRepository>,
+        audit_repo: Arc<AuditLogRepository>,
+        notif_repo: Arc<NotificationRepository>,
+    ) -> Self {
+        Self {
+            resolver,
+            locations,
+            audit_repo,
+            notif_repo,
+            config,
+        }
+    }
+
+    /// Checks a login by `user` from `ip` whose credentials were verified.
+    ///
+    /// Returns where the login is from, to be [`record`](Self::record)ed
+    /// once the session exists, or `None` if it could not be located.
+    /// Fails only when the login is anomalous and must be re-verified.
+    pub async fn check(
+        &self,
+        user: &User,
+        ip: IpAddr,
+        user_agent: Option<&str>,
+    ) -> Result<Option<GeoLocation>, AppError> {
+        let Some(location) = self.resolver.lookup(ip) else {
+            return Ok(None);
+        };
+
+        let history = match self
+            .locations
+            .find_recent_by_user(user.id, i64::from(self.config.history_size))
+            .await
+        {
+            Ok(history) => history,
+            Err(e) => {
+                warn!(user_id = %user.id, error = %e, "Failed to load login history");
+                return Ok(Some(location));
+            }
+        };
+
+        let Some(anomaly) = detect_anomaly(&location, Utc::now(), &history, &self.config) else {
+            return Ok(Some(location));
+        };
+
+        warn!(
+            user_id = %user.id,
+            ip = %ip,
+            country = %location.country_code,
+            kind = anomaly.kind(),
+            "Anomalous login location"
+        );
+        self.report(user, ip, user_agent, &location, &anomaly).await;
+
+        match self.config.action {
+            AnomalyAction::Alert => Ok(Some(location)),
+            AnomalyAction::RequireMfa => Err(AppError::unauthorized(
+                "Login from an unusual location must be re-verified with a second factor",
+            )
+            .with_code(codes::AUTH_MFA_REQUIRED)),
+        }
+    }
+
+    /// Adds a successful login to the user's history.
+    pub async fn record(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+        ip: IpAddr,
+        location: &GeoLocation,
+    ) {
+        let entry = CreateLoginLocation {
+            user_id,
+            session_id: Some(session_id),
+            ip_address: ip,
+            country_code: location.country_code.clone(),
+            region: location.region.clone(),
+            latitude: location.latitude,
+            longitude: location.longitude,
+        };
+        if let Err(e) = self.locations.create(&entry).await {
+            warn!(user_id = %user_id, error = %e, "Failed to record login location");
+        }
+    }
+
+    /// Audits the anomaly and notifies the user.
+    async fn report(
+        &self,
+        user: &User,
+        ip: IpAddr,
+        user_agent: Option<&str>,
+        location: &GeoLocation,
+        anomaly: &LoginAnomaly,
+    ) {
+        let mut details = serde_json::json!({
+            "country": location.country_code,
+            "region": location.region,
+            "action": self.config.action,
+        });
+        if let (Some(details), Ok(serde_json::Value::Object(anomaly))) =
+            (details.as_object_mut(), serde_json::to_value(anomaly))
+        {
+            details.extend(anomaly);
+        }
+
+        let entry = CreateAuditLogEntry {
+            actor_id: Some(user.id),
+            impersonator_id: None,
+            action: "auth.login_anomaly".to_string(),
+            target_type: "user".to_string(),
+            target_id: Some(user.id),
+            details: Some(details.clone()),
+            ip_address: Some(ip.to_string()),
+            user_agent: user_agent.map(String::from),
+        };
+        if let Err(e) = self.audit_repo.create(&entry).await {
+            warn!(user_id = %user.id, error = %e, "Failed to audit login anomaly");
+        }
+
+        let mut message = anomaly.describe(location);
+        message.push_str(" If this wasn't you, change your password.");
+        if let Err(e) = self
+            .notif_repo
+            .create(
+                user.id,
+                NotificationCategory::Security.as_str(),
+                "login_anomaly",
+                "Unusual login",
+                &message,
+                Some(&details),
+                Some("high"),
+                None,
+                Some("user"),
+                Some(user.id),
+            )
+            .await
+        {
+            warn!(user_id = %user.id, error = %e, "Failed to notify user of login anomaly");
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Client IP geolocation with a MaxMind GeoLite2/GeoIP2 City database.
+//!
+//! Lookups are only compiled in with the `geoip` feature. Without it, or
+//! when the database cannot be opened, the resolver is inert and every
+//! lookup returns `None`, so login checks fail open.
+
+use std::net::IpAddr;
+use std::path::Path;
+
+#[cfg(feature = "geoip")]
+use tracing::info;
+use tracing::warn;
+
+/// Where an IP address is, as far as the database knows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoLocation {
+    /// ISO 3166-1 alpha-2 country code.
+    pub country_code: String,
+    /// First-level subdivision name, if known.
+    pub region: Option<String>,
+    /// Approximate latitude in degrees.
+    pub latitude: Option<f64>,
+    /// Approximate longitude in degrees.
+    pub longitude: Option<f64>,
+}
+
+/// Resolves client addresses to locations.
+pub struct GeoIpResolver {
+    #[cfg(feature = "geoip")]
+    reader: Option<maxminddb::Reader<Vec<u8>>>,
+}
+
+impl std::fmt::Debug for GeoIpResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GeoIpResolver")
+            .field("available", &self.is_available())
+            .finish()
+    }
+}
+
+impl GeoIpResolver {
+    /// Loads the database at `path`. Never fails: a missing or unreadable
+    /// database is logged and yields a resolver that finds nothing.
+    pub fn open(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+
+        #[cfg(feature = "geoip")]
+        {
+            match maxminddb::Reader::open_readfile(path) {
+                Ok(reader) => {
+                    info!(
+                        path = %path.display(),
+                        database = %reader.metadata.database_type,
+                        "GeoIP database loaded"
+                    );
+                    Self {
+                        reader: Some(reader),
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        path = %path.display(),
+                        error = %e,
+                        "Cannot load GeoIP database, login locations will not be checked"
+                    );
+                    Self { reader: None }
+                }
+            }
+        }
+
+        #[cfg(not(feature = "geoip"))]
+        {
+            warn!(
+                path = %path.display(),
+                "Built without the `geoip` feature, login locations will not be checked"
+            );
+            Self {}
+        }
+    }
+
+    /// A resolver without a database.
+    pub fn disabled() -> Self {
+        Self {
+            #[cfg(feature = "geoip")]
+            reader: None,
+        }
+    }
+
+    /// Whether a database is loaded.
+    pub fn is_available(&self) -> bool {
+        #[cfg(feature = "geoip")]
+        {
+            self.reader.is_some()
+        }
+        #[cfg(not(feature = "geoip"))]
+        {
+            false
+        }
+    }
+
+    /// Looks up `ip`. Returns `None` when no database is loaded or the
+    /// address is not in it (private and loopback ranges never are).
+    pub fn lookup(&self, ip: IpAddr) -> Option<GeoLocation> {
+        #[cfg(feature = "geoip")]
+        {
+            let reader = self.reader.as_ref()?;
+            let city: maxminddb::geoip2::City<'_> = match reader.lookup(ip) {
+                Ok(Some(city)) => city,
+                Ok(None) => return None,
+                Err(e) => {
+                    warn!(ip = %ip, error = %e, "GeoIP lookup failed");
+                    return None;
+                }
+            };
+            let country_code = city.country.as_ref()?.iso_code?.to_string();
+            let region = city
+                .subdivisions
+                .as_ref()
+                .and_then(|subdivisions| subdivisions.first())
+                .and_then(|subdivision| {
+                    subdivision
+                        .names
+                        .as_ref()
+                        .and_then(|names| names.get("en").copied())
+                        .or(subdivision.iso_code)
+                })
+                .map(str::to_string);
+            let location = city.location.as_ref();
+            Some(GeoLocation {
+                country_code,
+                region,
+                latitude: location.and_then(|l| l.latitude),
+                longitude: location.and_then(|l| l.longitude),
+            })
+        }
+        #[cfg(not(feature = "geoip"))]
+        {
+            let _ = ip;
+            None
+        }
+    }
+}
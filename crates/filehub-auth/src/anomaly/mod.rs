@@ -0,0 +1,10 @@
+//! Login anomaly detection — GeoIP resolution of client addresses and
+//! comparison against the user's recent login locations.
+
+pub mod detect;
+pub mod detector;
+pub mod geoip;
+
+pub use detect::{LoginAnomaly, detect_anomaly};
+pub use detector::LoginAnomalyDetector;
+pub use geoip::{GeoIpResolver, GeoLocation};
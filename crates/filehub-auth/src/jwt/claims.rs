@@ -4,6 +4,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use filehub_core::types::TenantId;
 use filehub_entity::user::UserRole;
 
 /// JWT claims payload embedded in every access token.
@@ -13,6 +14,10 @@ pub struct Claims {
     pub sub: Uuid,
     /// Session ID this token belongs to.
     pub sid: Uuid,
+    /// Tenant of the user. Tokens issued before tenants existed lack it
+    /// and belong to the default tenant.
+    #[serde(default = "TenantId::default_tenant")]
+    pub tid: TenantId,
     /// User role at the time of token issuance.
     pub role: UserRole,
     /// Username for convenience.
@@ -25,6 +30,10 @@ pub struct Claims {
     pub jti: Uuid,
     /// Token type: "access" or "refresh".
     pub token_type: TokenType,
+    /// Actor — the administrator acting as `sub` in an impersonation
+    /// session. Absent otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Uuid>,
 }
 
 /// Distinguishes access tokens from refresh tokens.
@@ -48,6 +57,17 @@ impl Claims {
         self.sid
     }
 
+    /// Returns the administrator acting as the user, if this token
+    /// belongs to an impersonation session.
+    pub fn impersonator_id(&self) -> Option<Uuid> {
+        self.act
+    }
+
+    /// Returns the tenant ID.
+    pub fn tenant_id(&self) -> TenantId {
+        self.tid
+    }
+
     /// Returns the expiration as a `DateTime<Utc>`.
     pub fn expires_at(&self) -> DateTime<Utc> {
         DateTime::from_timestamp(self.exp, 0).unwrap_or_else(|| Utc::now())
@@ -7,7 +7,7 @@ use uuid::Uuid;
 
 use filehub_cache::provider::CacheManager;
 use filehub_core::config::AuthConfig;
-use filehub_core::error::AppError;
+use filehub_core::error::{AppError, codes};
 use filehub_core::traits::CacheProvider;
 
 use super::claims::{Claims, TokenType};
@@ -42,7 +42,7 @@ impl JwtDecoder {
         validation.leeway = 5; // 5 seconds leeway for clock skew
 
         Self {
-            decoding_key: DecodingKey::from_secret(config.jwt_secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(config.jwt_secret.expose().as_bytes()),
             validation,
             cache,
         }
@@ -91,12 +91,15 @@ impl JwtDecoder {
                 match e.kind() {
                     jsonwebtoken::errors::ErrorKind::ExpiredSignature => {
                         AppError::unauthorized("Token has expired")
+                            .with_code(codes::AUTH_TOKEN_EXPIRED)
                     }
                     jsonwebtoken::errors::ErrorKind::InvalidToken => {
                         AppError::unauthorized("Invalid token format")
+                            .with_code(codes::AUTH_TOKEN_INVALID)
                     }
                     jsonwebtoken::errors::ErrorKind::InvalidSignature => {
                         AppError::unauthorized("Invalid token signature")
+                            .with_code(codes::AUTH_TOKEN_INVALID)
                     }
                     _ => AppError::unauthorized(format!("Token validation failed: {e}")),
                 }
@@ -110,7 +113,8 @@ impl JwtDecoder {
         let key = format!("{}{}", BLOCKLIST_PREFIX, jti);
         let blocked = self.cache.get(&key).await.ok().flatten();
         if blocked.is_some() {
-            return Err(AppError::unauthorized("Token has been revoked"));
+            return Err(AppError::unauthorized("Token has been revoked")
+                .with_code(codes::AUTH_TOKEN_REVOKED));
         }
         Ok(())
     }
@@ -6,6 +6,7 @@ use uuid::Uuid;
 
 use filehub_core::config::AuthConfig;
 use filehub_core::error::AppError;
+use filehub_core::types::TenantId;
 use filehub_entity::user::UserRole;
 
 use super::claims::{Claims, TokenType};
@@ -38,44 +39,51 @@ impl JwtEncoder {
     /// Creates a new encoder from auth configuration.
     pub fn new(config: &AuthConfig) -> Self {
         Self {
-            encoding_key: EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+            encoding_key: EncodingKey::from_secret(config.jwt_secret.expose().as_bytes()),
             access_ttl_minutes: config.jwt_access_ttl_minutes as i64,
             refresh_ttl_hours: config.jwt_refresh_ttl_hours as i64,
         }
     }
 
-    /// Generates a new access + refresh token pair for the given user and session.
+    /// Generates a new access + refresh token pair for the given user and
+    /// session. Neither token outlives `not_after`, the session's expiry.
     pub fn generate_token_pair(
         &self,
         user_id: Uuid,
+        tenant_id: TenantId,
         session_id: Uuid,
         role: &UserRole,
         username: &str,
+        not_after: chrono::DateTime<Utc>,
     ) -> Result<TokenPair, AppError> {
         let now = Utc::now();
-        let access_exp = now + chrono::Duration::minutes(self.access_ttl_minutes);
-        let refresh_exp = now + chrono::Duration::hours(self.refresh_ttl_hours);
+        let access_exp = (now + chrono::Duration::minutes(self.access_ttl_minutes)).min(not_after);
+        let refresh_exp = (now + chrono::Duration::hours(self.refresh_ttl_hours)).min(not_after);
 
         let access_claims = Claims {
             sub: user_id,
             sid: session_id,
+            tid: tenant_id,
             role: role.clone(),
             username: username.to_string(),
             iat: now.timestamp(),
             exp: access_exp.timestamp(),
             jti: Uuid::new_v4(),
             token_type: TokenType::Access,
+            act: None,
         };
 
         let refresh_claims = Claims {
             sub: user_id,
             sid: session_id,
+            tid: tenant_id,
             role: role.clone(),
             username: username.to_string(),
             iat: now.timestamp(),
             exp: refresh_exp.timestamp(),
             jti: Uuid::new_v4(),
             token_type: TokenType::Refresh,
+            act: None,
         };
 
         let access_token = encode(&Header::default(), &access_claims, &self.encoding_key)
@@ -96,6 +104,7 @@ impl JwtEncoder {
     pub fn generate_access_token(
         &self,
         user_id: Uuid,
+        tenant_id: TenantId,
         session_id: Uuid,
         role: &UserRole,
         username: &str,
@@ -106,12 +115,14 @@ impl JwtEncoder {
         let claims = Claims {
             sub: user_id,
             sid: session_id,
+            tid: tenant_id,
             role: role.clone(),
             username: username.to_string(),
             iat: now.timestamp(),
             exp: exp.timestamp(),
             jti: Uuid::new_v4(),
             token_type: TokenType::Access,
+            act: None,
         };
 
         let token = encode(&Header::default(), &claims, &self.encoding_key)
@@ -119,4 +130,35 @@ impl JwtEncoder {
 
         Ok((token, exp))
     }
+
+    /// Generates the access token of an impersonation session, in which
+    /// `impersonator_id` acts as the user until `expires_at`. There is no
+    /// refresh token; the session ends with the token.
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_impersonation_token(
+        &self,
+        user_id: Uuid,
+        tenant_id: TenantId,
+        session_id: Uuid,
+        role: &UserRole,
+        username: &str,
+        impersonator_id: Uuid,
+        expires_at: chrono::DateTime<Utc>,
+    ) -> Result<String, AppError> {
+        let claims = Claims {
+            sub: user_id,
+            sid: session_id,
+            tid: tenant_id,
+            role: *role,
+            username: username.to_string(),
+            iat: Utc::now().timestamp(),
+            exp: expires_at.timestamp(),
+            jti: Uuid::new_v4(),
+            token_type: TokenType::Access,
+            act: Some(impersonator_id),
+        };
+
+        encode(&Header::default(), &claims, &self.encoding_key)
+            .map_err(|e| AppError::internal(format!("Failed to encode access token: {e}")))
+    }
 }
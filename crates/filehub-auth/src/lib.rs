@@ -11,8 +11,10 @@
 //! - `rbac` — Role-based access control enforcement
 //! - `acl` — Access control list checking with folder inheritance
 //! - `seat` — Concurrent session seat allocation and pool management
+//! - `anomaly` — GeoIP-based detection of unusual login locations
 
 pub mod acl;
+pub mod anomaly;
 pub mod jwt;
 pub mod password;
 pub mod rbac;
@@ -20,6 +22,7 @@ pub mod seat;
 pub mod session;
 
 pub use acl::{AclChecker, AclInheritanceResolver, EffectivePermissionResolver};
+pub use anomaly::LoginAnomalyDetector;
 pub use jwt::{Claims, JwtDecoder, JwtEncoder};
 pub use password::{PasswordHasher, PasswordValidator};
 pub use rbac::{RbacEnforcer, RbacPolicies};
@@ -0,0 +1,66 @@
+//! Random temporary passwords.
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+
+/// Character classes of a generated password. Look-alike characters
+/// (`I`, `O`, `l`, `0`, `1`) are left out so passwords can be read out.
+const CLASSES: [&[u8]; 4] = [
+    b"ABCDEFGHJKLMNPQRSTUVWXYZ",
+    b"abcdefghijkmnopqrstuvwxyz",
+    b"23456789",
+    b"!#%+-=?@",
+];
+
+/// Length of a generated password.
+pub const GENERATED_PASSWORD_LENGTH: usize = 16;
+
+/// Generates a random password that satisfies the password policy: at
+/// least one character of every class, the rest drawn from all of them.
+pub fn generate_password() -> String {
+    let all: Vec<u8> = CLASSES.concat();
+    let mut chars: Vec<u8> = CLASSES.iter().map(|class| pick(class)).collect();
+    while chars.len() < GENERATED_PASSWORD_LENGTH {
+        chars.push(pick(&all));
+    }
+
+    // Fisher-Yates, so the guaranteed characters are not always first.
+    for i in (1..chars.len()).rev() {
+        let j = uniform(i as u32 + 1) as usize;
+        chars.swap(i, j);
+    }
+    String::from_utf8(chars).unwrap_or_default()
+}
+
+fn pick(set: &[u8]) -> u8 {
+    set[uniform(set.len() as u32) as usize]
+}
+
+/// A uniformly distributed number below `bound`.
+fn uniform(bound: u32) -> u32 {
+    // Reject the top of the range that would bias the modulo.
+    let zone = u32::MAX - u32::MAX % bound;
+    loop {
+        let n = OsRng.next_u32();
+        if n < zone {
+            return n % bound;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::password::PasswordValidator;
+    use filehub_core::config::AuthConfig;
+
+    #[test]
+    fn test_generated_password_passes_policy() {
+        let config: AuthConfig = serde_json::from_str("{}").unwrap();
+        let validator = PasswordValidator::new(&config);
+        for _ in 0..20 {
+            let password = generate_password();
+            assert_eq!(password.len(), GENERATED_PASSWORD_LENGTH);
+            validator.validate(&password).unwrap();
+        }
+    }
+}
@@ -1,28 +1,131 @@
 //! Argon2id password hashing and verification.
+//!
+//! A configured pepper is passed to Argon2 as its secret key, and its
+//! version id is recorded as the hash's `keyid` parameter, so each hash
+//! is verified with the pepper it was made with.
+
+use std::collections::HashMap;
+use std::fmt;
 
 use argon2::{
-    Argon2,
+    Algorithm, Argon2, KeyId, Params, ParamsBuilder, Version,
     password_hash::{
         PasswordHash, PasswordHasher as ArgonHasher, PasswordVerifier, SaltString, rand_core::OsRng,
     },
 };
 
+use filehub_core::config::{PasswordHashConfig, PepperConfig};
 use filehub_core::error::AppError;
 
 /// Handles password hashing and verification using Argon2id.
-#[derive(Debug, Clone)]
-pub struct PasswordHasher;
+#[derive(Clone)]
+pub struct PasswordHasher {
+    /// Cost of new hashes.
+    params: Params,
+    /// Pepper version used for new hashes.
+    current_pepper: Option<String>,
+    /// Pepper secrets by version id.
+    peppers: HashMap<String, Vec<u8>>,
+}
+
+impl fmt::Debug for PasswordHasher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut versions: Vec<&String> = self.peppers.keys().collect();
+        versions.sort();
+        f.debug_struct("PasswordHasher")
+            .field("params", &self.params)
+            .field("current_pepper", &self.current_pepper)
+            .field("pepper_versions", &versions)
+            .finish()
+    }
+}
 
 impl PasswordHasher {
-    /// Creates a new password hasher instance.
+    /// Creates a hasher with Argon2's default cost.
     pub fn new() -> Self {
-        Self
+        Self {
+            params: Params::default(),
+            current_pepper: None,
+            peppers: HashMap::new(),
+        }
+    }
+
+    /// Creates a hasher with the configured cost.
+    pub fn from_config(config: &PasswordHashConfig) -> Result<Self, AppError> {
+        let params = Params::new(
+            config.memory_kib,
+            config.iterations,
+            config.parallelism,
+            None,
+        )
+        .map_err(|e| AppError::internal(format!("Invalid password hash parameters: {e}")))?;
+        Ok(Self {
+            params,
+            ..Self::new()
+        })
+    }
+
+    /// Peppers hashes with the configured versions, whose secrets must
+    /// have been resolved.
+    pub fn with_pepper(mut self, config: &PepperConfig) -> Result<Self, AppError> {
+        self.peppers = config
+            .versions
+            .iter()
+            .map(|(id, secret)| {
+                if secret.expose().is_empty() {
+                    return Err(AppError::configuration(format!(
+                        "Password pepper version '{id}' has no value"
+                    )));
+                }
+                Ok((id.clone(), secret.expose().as_bytes().to_vec()))
+            })
+            .collect::<Result<_, _>>()?;
+        if let Some(current) = &config.current
+            && !self.peppers.contains_key(current)
+        {
+            return Err(AppError::configuration(format!(
+                "Current password pepper version '{current}' is not configured"
+            )));
+        }
+        self.current_pepper = config.current.clone();
+        Ok(self)
     }
 
-    /// Hashes a plaintext password using Argon2id with a random salt.
+    /// Argon2 keyed with the pepper of version `pepper`, if any.
+    fn argon2(&self, pepper: Option<&str>, params: Params) -> Result<Argon2<'_>, AppError> {
+        let Some(id) = pepper else {
+            return Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params));
+        };
+        let secret = self.peppers.get(id).ok_or_else(|| {
+            AppError::internal(format!(
+                "Password hash uses pepper version '{id}', which is not configured"
+            ))
+        })?;
+        Argon2::new_with_secret(secret, Algorithm::Argon2id, Version::V0x13, params)
+            .map_err(|e| AppError::internal(format!("Invalid password pepper: {e}")))
+    }
+
+    /// Hashes a plaintext password using Argon2id with a random salt and
+    /// the current pepper.
     pub fn hash_password(&self, password: &str) -> Result<String, AppError> {
         let salt = SaltString::generate(&mut OsRng);
-        let argon2 = Argon2::default();
+        let params = match &self.current_pepper {
+            Some(id) => {
+                let keyid = KeyId::new(id.as_bytes())
+                    .map_err(|e| AppError::internal(format!("Invalid pepper version: {e}")))?;
+                ParamsBuilder::new()
+                    .m_cost(self.params.m_cost())
+                    .t_cost(self.params.t_cost())
+                    .p_cost(self.params.p_cost())
+                    .keyid(keyid)
+                    .build()
+                    .map_err(|e| {
+                        AppError::internal(format!("Invalid password hash parameters: {e}"))
+                    })?
+            }
+            None => self.params.clone(),
+        };
+        let argon2 = self.argon2(self.current_pepper.as_deref(), params)?;
 
         let hash = argon2
             .hash_password(password.as_bytes(), &salt)
@@ -38,7 +141,10 @@ impl PasswordHasher {
         let parsed_hash = PasswordHash::new(hash)
             .map_err(|e| AppError::internal(format!("Invalid password hash format: {e}")))?;
 
-        let argon2 = Argon2::default();
+        // The cost is read from the hash itself, so hashes made with
+        // older parameters still verify; only the pepper comes from here.
+        let pepper = pepper_version(&parsed_hash);
+        let argon2 = self.argon2(pepper.as_deref(), Params::default())?;
         match argon2.verify_password(password.as_bytes(), &parsed_hash) {
             Ok(()) => Ok(true),
             Err(argon2::password_hash::Error::Password) => Ok(false),
@@ -47,6 +153,32 @@ impl PasswordHasher {
             ))),
         }
     }
+
+    /// Whether `hash` is weaker than the configured cost: not Argon2id, an
+    /// older Argon2 version, any cost parameter below the current one, or
+    /// a pepper version other than the current one.
+    ///
+    /// Unparseable hashes are reported as not needing a rehash; they fail
+    /// verification anyway.
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        let Ok(parsed) = PasswordHash::new(hash) else {
+            return false;
+        };
+        if parsed.algorithm != Algorithm::Argon2id.ident()
+            || parsed.version != Some(Version::V0x13.into())
+        {
+            return true;
+        }
+        if pepper_version(&parsed) != self.current_pepper {
+            return true;
+        }
+        let Ok(params) = Params::try_from(&parsed) else {
+            return true;
+        };
+        params.m_cost() < self.params.m_cost()
+            || params.t_cost() < self.params.t_cost()
+            || params.p_cost() < self.params.p_cost()
+    }
 }
 
 impl Default for PasswordHasher {
@@ -54,14 +186,104 @@ impl Default for PasswordHasher {
         Self::new()
     }
 }
+
+/// Pepper version recorded in `hash`, if it was peppered.
+fn pepper_version(hash: &PasswordHash<'_>) -> Option<String> {
+    let keyid = Params::try_from(hash).ok()?.keyid().to_vec();
+    (!keyid.is_empty()).then(|| String::from_utf8_lossy(&keyid).into_owned())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn hasher(memory_kib: u32, iterations: u32) -> PasswordHasher {
+        PasswordHasher::from_config(&PasswordHashConfig {
+            memory_kib,
+            iterations,
+            parallelism: 1,
+        })
+        .unwrap()
+    }
+
     #[test]
     fn generate_admin_hash() {
         let hasher = PasswordHasher::new();
         let hash = hasher.hash_password("password123").unwrap();
         println!("ADMIN_HASH: {}", hash);
     }
+
+    #[test]
+    fn test_weaker_hash_verifies_and_needs_rehash() {
+        let old = hasher(64, 1);
+        let current = hasher(128, 2);
+        let hash = old.hash_password("s3cret!").unwrap();
+
+        assert!(current.verify_password("s3cret!", &hash).unwrap());
+        assert!(current.needs_rehash(&hash));
+        assert!(!old.needs_rehash(&hash));
+
+        let rehashed = current.hash_password("s3cret!").unwrap();
+        assert!(rehashed.contains("m=128,t=2,p=1"));
+        assert!(current.verify_password("s3cret!", &rehashed).unwrap());
+        assert!(!current.needs_rehash(&rehashed));
+    }
+
+    fn pepper(current: &str, versions: &[(&str, &str)]) -> PepperConfig {
+        PepperConfig {
+            current: Some(current.to_string()),
+            versions: versions
+                .iter()
+                .map(|(id, secret)| (id.to_string(), (*secret).into()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_pepper_rotation() {
+        let unpeppered = hasher(64, 1);
+        let v1 = hasher(64, 1)
+            .with_pepper(&pepper("1", &[("1", "pepper-one")]))
+            .unwrap();
+        let v2 = hasher(64, 1)
+            .with_pepper(&pepper("2", &[("1", "pepper-one"), ("2", "pepper-two")]))
+            .unwrap();
+
+        let plain = unpeppered.hash_password("s3cret!").unwrap();
+        let hash = v1.hash_password("s3cret!").unwrap();
+        assert!(hash.contains("keyid="));
+        assert!(v1.verify_password("s3cret!", &hash).unwrap());
+        assert!(!v1.verify_password("wrong", &hash).unwrap());
+        assert!(!v1.needs_rehash(&hash));
+        assert!(v1.needs_rehash(&plain));
+        assert!(v1.verify_password("s3cret!", &plain).unwrap());
+
+        // Rotated: the old version still verifies but is replaced
+        assert!(v2.verify_password("s3cret!", &hash).unwrap());
+        assert!(v2.needs_rehash(&hash));
+        let rehashed = v2.hash_password("s3cret!").unwrap();
+        assert!(!v2.needs_rehash(&rehashed));
+
+        // A retired version can no longer be verified
+        assert!(v1.verify_password("s3cret!", &rehashed).is_err());
+        assert!(unpeppered.verify_password("s3cret!", &hash).is_err());
+    }
+
+    #[test]
+    fn test_pepper_is_part_of_the_hash() {
+        let a = hasher(64, 1)
+            .with_pepper(&pepper("1", &[("1", "pepper-a")]))
+            .unwrap();
+        let b = hasher(64, 1)
+            .with_pepper(&pepper("1", &[("1", "pepper-b")]))
+            .unwrap();
+        let hash = a.hash_password("s3cret!").unwrap();
+        assert!(!b.verify_password("s3cret!", &hash).unwrap());
+    }
+
+    #[test]
+    fn test_stronger_hash_is_kept() {
+        let hash = hasher(128, 2).hash_password("s3cret!").unwrap();
+        assert!(!hasher(64, 1).needs_rehash(&hash));
+    }
 }
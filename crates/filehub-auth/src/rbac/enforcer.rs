@@ -5,6 +5,17 @@ use filehub_entity::user::UserRole;
 
 use super::policies::{RbacPolicies, SystemPermission};
 
+/// Outcome of checking a role for a system permission.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RbacDecision {
+    /// The role that was checked.
+    pub role: UserRole,
+    /// The permission that was checked.
+    pub permission: SystemPermission,
+    /// Whether the role's policy includes the permission.
+    pub granted: bool,
+}
+
 /// Enforces role-based access control for system-level operations.
 #[derive(Debug, Clone)]
 pub struct RbacEnforcer {
@@ -47,6 +58,15 @@ impl RbacEnforcer {
         self.policies.has_permission(role, permission)
     }
 
+    /// Checks the role for a permission and reports the decision.
+    pub fn explain(&self, role: &UserRole, permission: &SystemPermission) -> RbacDecision {
+        RbacDecision {
+            role: *role,
+            permission: permission.clone(),
+            granted: self.policies.has_permission(role, permission),
+        }
+    }
+
     /// Checks whether the given role is at least the specified minimum role.
     ///
     /// Role hierarchy: Admin > Manager > Creator > Viewer
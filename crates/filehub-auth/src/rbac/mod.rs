@@ -3,5 +3,5 @@
 pub mod enforcer;
 pub mod policies;
 
-pub use enforcer::RbacEnforcer;
+pub use enforcer::{RbacDecision, RbacEnforcer};
 pub use policies::RbacPolicies;
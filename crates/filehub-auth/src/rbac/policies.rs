@@ -23,6 +23,8 @@ pub enum SystemPermission {
     UserChangeRole,
     /// Reset user passwords.
     UserResetPassword,
+    /// Act as another user in an impersonation session.
+    UserImpersonate,
 
     // File operations
     /// Upload files.
@@ -163,6 +165,7 @@ impl RbacPolicies {
             SystemPermission::UserDelete,
             SystemPermission::UserChangeRole,
             SystemPermission::UserResetPassword,
+            SystemPermission::UserImpersonate,
             SystemPermission::FileUpload,
             SystemPermission::FileDownload,
             SystemPermission::FileDelete,
@@ -61,11 +61,10 @@ pub trait SeatAllocator: Send + Sync + std::fmt::Debug {
 
 use crate::seat::memory::MemorySeatAllocator;
 #[cfg(feature = "redis-seat")]
-use crate::seat::redis::RedisSeatAllocator;
-use filehub_cache::provider::CacheManager;
+use crate::seat::{failover::FailoverSeatAllocator, redis::RedisSeatAllocator};
+use filehub_cache::breaker::CircuitBreaker;
 use filehub_core::config::SessionConfig;
-use filehub_database::repositories::session::SessionRepository;
-use std::sync::Arc;
+use filehub_core::config::cache::CacheConfig;
 
 /// Dispatcher for seat allocation strategies.
 ///
@@ -74,28 +73,52 @@ use std::sync::Arc;
 pub enum SeatAllocatorDispatch {
     /// In-memory allocator (single node).
     Memory(MemorySeatAllocator),
-    /// Redis-based allocator (multi-node).
+    /// Redis-based allocator (multi-node), with a local fallback.
     #[cfg(feature = "redis-seat")]
-    Redis(RedisSeatAllocator),
+    Redis(FailoverSeatAllocator),
 }
 
 impl SeatAllocatorDispatch {
     /// Creates a new seat allocator dispatcher.
-    pub fn new(
-        config: &SessionConfig,
-        _cache: Arc<CacheManager>,
-        _repo: Arc<SessionRepository>,
-    ) -> Self {
+    ///
+    /// Seats are allocated in Redis when the cache uses Redis, so that
+    /// every node shares one pool. If Redis cannot be reached at startup
+    /// the node falls back to a local pool for its lifetime.
+    pub async fn new(config: &SessionConfig, cache: &CacheConfig) -> Self {
         // Default to a large number of seats; actual limit is set by LicenseManager
         let total_seats = 1000;
         let reserved = config.admin_reservation.reserved_seats;
 
-        // TODO: inspect cache manager to see if we should use Redis allocator
-        // For now, we default to memory allocator to fix compilation.
-        // To support Redis properly, we need the Redis URL which isn't exposed by CacheManager currently.
+        #[cfg(feature = "redis-seat")]
+        if matches!(cache.provider.as_str(), "redis" | "layered") {
+            match RedisSeatAllocator::new(&cache.redis.url, total_seats, reserved).await {
+                Ok(redis) => {
+                    return SeatAllocatorDispatch::Redis(FailoverSeatAllocator::new(
+                        redis,
+                        total_seats,
+                        reserved,
+                        &cache.circuit_breaker,
+                    ));
+                }
+                Err(e) => tracing::error!(
+                    error = %e,
+                    "Redis seat allocator unavailable, using a single-node seat pool"
+                ),
+            }
+        }
+        #[cfg(not(feature = "redis-seat"))]
+        let _ = cache;
+
+        SeatAllocatorDispatch::Memory(MemorySeatAllocator::new(total_seats, reserved))
+    }
 
-        let allocator = MemorySeatAllocator::new(total_seats, reserved);
-        SeatAllocatorDispatch::Memory(allocator)
+    /// The breaker guarding Redis calls, if seats are allocated in Redis.
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        match self {
+            Self::Memory(_) => None,
+            #[cfg(feature = "redis-seat")]
+            Self::Redis(inner) => Some(inner.circuit_breaker()),
+        }
     }
 }
 
@@ -0,0 +1,148 @@
+//! Redis seat allocation with a single-node fallback.
+//!
+//! Calls go to Redis through a circuit breaker. While Redis is
+//! unavailable, seats are allocated from a local in-memory pool instead,
+//! so logins keep working. Each node then enforces the seat limit on its
+//! own, so the cluster as a whole can exceed it until Redis returns;
+//! seats allocated locally are released locally, and the reconciler
+//! corrects any remaining drift.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tracing::{error, warn};
+
+use filehub_cache::breaker::CircuitBreaker;
+use filehub_core::config::cache::CircuitBreakerConfig;
+use filehub_core::error::AppError;
+
+use super::allocator::{AllocationResult, PoolState, SeatAllocator};
+use super::memory::MemorySeatAllocator;
+use super::redis::RedisSeatAllocator;
+
+/// Redis allocator that degrades to a local pool when Redis is down.
+#[derive(Debug, Clone)]
+pub struct FailoverSeatAllocator {
+    /// Shared allocator.
+    redis: RedisSeatAllocator,
+    /// Pool used while Redis is unavailable.
+    local: MemorySeatAllocator,
+    /// Breaker around Redis calls.
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl FailoverSeatAllocator {
+    /// Creates a failover allocator; the local pool starts with the same
+    /// limits as Redis.
+    pub fn new(
+        redis: RedisSeatAllocator,
+        total_seats: u32,
+        admin_reserved: u32,
+        breaker: &CircuitBreakerConfig,
+    ) -> Self {
+        Self {
+            redis,
+            local: MemorySeatAllocator::new(total_seats, admin_reserved),
+            breaker: Arc::new(CircuitBreaker::new("seat allocator", breaker)),
+        }
+    }
+
+    /// The breaker guarding Redis calls.
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+
+    /// Runs `call` against Redis if the breaker allows it, recording the
+    /// outcome. `None` means the caller should use the local pool.
+    async fn redis_call<T>(
+        &self,
+        operation: &str,
+        call: impl Future<Output = Result<T, AppError>>,
+    ) -> Option<T> {
+        if !self.breaker.allow() {
+            return None;
+        }
+        match call.await {
+            Ok(value) => {
+                self.breaker.record_success();
+                Some(value)
+            }
+            Err(e) => {
+                error!(
+                    operation = operation,
+                    error = %e,
+                    "Redis seat allocator failed, using local seat pool"
+                );
+                self.breaker.record_failure();
+                None
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl SeatAllocator for FailoverSeatAllocator {
+    async fn try_allocate(&self, user_key: &str, role: &str) -> Result<AllocationResult, AppError> {
+        match self
+            .redis_call("allocate", self.redis.try_allocate(user_key, role))
+            .await
+        {
+            Some(result) => Ok(result),
+            None => {
+                warn!(user_key = %user_key, "Allocating seat from local pool (degraded)");
+                self.local.try_allocate(user_key, role).await
+            }
+        }
+    }
+
+    async fn release(&self, user_key: &str) -> Result<(), AppError> {
+        if self.local.holds(user_key).await {
+            return self.local.release(user_key).await;
+        }
+        if self
+            .redis_call("release", self.redis.release(user_key))
+            .await
+            .is_none()
+        {
+            warn!(
+                user_key = %user_key,
+                "Seat not released in Redis (degraded); reconciliation will correct it"
+            );
+        }
+        Ok(())
+    }
+
+    async fn pool_state(&self) -> Result<PoolState, AppError> {
+        match self.redis_call("pool_state", self.redis.pool_state()).await {
+            Some(state) => Ok(state),
+            None => self.local.pool_state().await,
+        }
+    }
+
+    async fn set_total_seats(&self, total: u32) -> Result<(), AppError> {
+        self.local.set_total_seats(total).await?;
+        self.redis_call("set_total_seats", self.redis.set_total_seats(total))
+            .await;
+        Ok(())
+    }
+
+    async fn set_admin_reserved(&self, count: u32) -> Result<(), AppError> {
+        self.local.set_admin_reserved(count).await?;
+        self.redis_call("set_admin_reserved", self.redis.set_admin_reserved(count))
+            .await;
+        Ok(())
+    }
+
+    async fn reconcile(&self, actual_active_sessions: u32) -> Result<(), AppError> {
+        // The local pool only holds seats taken while degraded, so it
+        // cannot be compared against the database on its own.
+        if self
+            .redis_call("reconcile", self.redis.reconcile(actual_active_sessions))
+            .await
+            .is_none()
+        {
+            warn!("Skipping seat reconciliation while Redis is unavailable");
+        }
+        Ok(())
+    }
+}
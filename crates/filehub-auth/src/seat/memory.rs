@@ -42,6 +42,11 @@ impl MemorySeatAllocator {
             })),
         }
     }
+
+    /// Whether `user_key` holds a seat in this allocator.
+    pub async fn holds(&self, user_key: &str) -> bool {
+        self.state.lock().await.allocated.contains(user_key)
+    }
 }
 
 #[async_trait]
@@ -161,3 +166,29 @@ impl SeatAllocator for MemorySeatAllocator {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_release_frees_seat() {
+        let allocator = MemorySeatAllocator::new(1, 0);
+        assert!(matches!(
+            allocator.try_allocate("u1", "creator").await.unwrap(),
+            AllocationResult::Granted
+        ));
+        assert!(matches!(
+            allocator.try_allocate("u2", "creator").await.unwrap(),
+            AllocationResult::Denied { .. }
+        ));
+
+        allocator.release("u1").await.unwrap();
+        assert!(!allocator.holds("u1").await);
+        assert_eq!(allocator.pool_state().await.unwrap().checked_out, 0);
+        assert!(matches!(
+            allocator.try_allocate("u2", "creator").await.unwrap(),
+            AllocationResult::Granted
+        ));
+    }
+}
@@ -1,10 +1,13 @@
 //! Concurrent session seat allocation and pool management.
 //!
 //! Provides atomic seat allocation using either:
-//! - Redis Lua scripts (for multi-node deployments)
+//! - Redis Lua scripts (for multi-node deployments), falling back to a
+//!   local pool while Redis is unavailable
 //! - In-memory mutex (for single-node deployments)
 
 pub mod allocator;
+#[cfg(feature = "redis-seat")]
+pub mod failover;
 pub mod limiter;
 pub mod memory;
 pub mod reconciler;
@@ -3,11 +3,14 @@
 //! Detects and corrects drift caused by crashes, network partitions, or bugs.
 
 use std::sync::Arc;
+use std::time::Duration;
 
+use tokio::task::JoinHandle;
 use tracing::{error, info, warn};
 
 use filehub_core::error::AppError;
 use filehub_database::repositories::pool_snapshot::PoolSnapshotRepository;
+use filehub_entity::license::pool::CreatePoolSnapshot;
 
 use super::allocator::SeatAllocator;
 
@@ -46,13 +49,15 @@ impl SeatReconciler {
 
     /// Performs a full reconciliation cycle:
     ///
-    /// 1. Query actual active session count from database.
+    /// 1. Query the count of active sessions holding a seat from database
+    ///    (sessions whose seat was lost to a failed license renewal are
+    ///    excluded, so their seats are reclaimed here).
     /// 2. Query pool state from allocator.
     /// 3. Detect drift.
     /// 4. If drift detected, force pool state to match database.
     /// 5. Record a pool snapshot.
     pub async fn reconcile(&self) -> Result<bool, AppError> {
-        let db_active = self.session_store.count_all_active().await? as u32;
+        let db_active = self.session_store.count_seated_active().await? as u32;
         let pool_state = self.allocator.pool_state().await?;
 
         let drift_detected = pool_state.checked_out != db_active;
@@ -70,41 +75,30 @@ impl SeatReconciler {
             info!("Pool reconciliation completed");
         }
 
-        // Record snapshot
-
-        let total_seats = pool_state.total_seats as i32;
-        let checked_out = if drift_detected {
-            db_active as i32
-        } else {
-            pool_state.checked_out as i32
-        };
-        let available = pool_state.total_seats.saturating_sub(db_active) as i32;
-        let admin_reserved = pool_state.admin_reserved as i32;
-        let active_sessions = db_active as i32;
-        let drift_detail = if drift_detected {
-            Some(&serde_json::json!({
-                "pool_checked_out": pool_state.checked_out,
-                "db_active_sessions": db_active,
-                "delta": pool_state.checked_out as i64 - db_active as i64
-            }))
-        } else {
-            None
+        // Record snapshot, with the drift as found before correction
+
+        let drift = pool_state.checked_out as i64 - db_active as i64;
+        let snapshot = CreatePoolSnapshot {
+            total_seats: pool_state.total_seats as i32,
+            checked_out: db_active as i32,
+            available: pool_state.total_seats.saturating_sub(db_active) as i32,
+            admin_reserved: pool_state.admin_reserved as i32,
+            borrowed: 0,
+            active_sessions: db_active as i32,
+            drift: drift as i32,
+            drift_detected,
+            drift_detail: drift_detected.then(|| {
+                serde_json::json!({
+                    "pool_checked_out": pool_state.checked_out,
+                    "db_active_sessions": db_active,
+                    "delta": drift
+                })
+            }),
+            features: Vec::new(),
+            source: "reconciler".to_string(),
         };
 
-        if let Err(e) = self
-            .snapshot_repo
-            .create(
-                total_seats,
-                checked_out,
-                available,
-                admin_reserved,
-                active_sessions,
-                drift_detected,
-                drift_detail,
-                "reconciler",
-            )
-            .await
-        {
+        if let Err(e) = self.snapshot_repo.create(&snapshot).await {
             error!(error = %e, "Failed to save pool snapshot");
         }
 
@@ -127,4 +121,21 @@ impl SeatReconciler {
 
         Ok(())
     }
+
+    /// Reconciles every `interval` in the background, so the pool stays
+    /// corrected and utilization history keeps a steady cadence.
+    pub fn spawn(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The first tick is immediate; startup recovery covers it.
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.reconcile().await {
+                    error!(error = %e, "Seat pool reconciliation failed");
+                }
+            }
+        })
+    }
 }
@@ -5,6 +5,7 @@ use std::sync::Arc;
 use tracing::{error, info};
 
 use filehub_core::error::AppError;
+use filehub_core::traits::{Clock, SystemClock};
 
 use crate::jwt::JwtDecoder;
 use crate::seat::SeatAllocator;
@@ -20,6 +21,8 @@ pub struct SessionCleanup {
     jwt_decoder: Arc<JwtDecoder>,
     /// Seat allocator for releasing seats.
     seat_allocator: Arc<dyn SeatAllocator>,
+    /// Source of the current time.
+    clock: Arc<dyn Clock>,
 }
 
 impl std::fmt::Debug for SessionCleanup {
@@ -39,9 +42,16 @@ impl SessionCleanup {
             session_store,
             jwt_decoder,
             seat_allocator,
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// Reads the current time from `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Runs a cleanup cycle, terminating all expired and idle sessions.
     ///
     /// Returns the number of sessions cleaned up.
@@ -70,11 +80,12 @@ impl SessionCleanup {
                 continue;
             }
 
-            // Release the seat
-            if let Err(e) = self
-                .seat_allocator
-                .release(&session.user_id.to_string())
-                .await
+            // Release the seat; impersonation sessions hold none
+            if !session.is_impersonation()
+                && let Err(e) = self
+                    .seat_allocator
+                    .release(&session.user_id.to_string())
+                    .await
             {
                 error!(
                     session_id = %session.id,
@@ -84,7 +95,7 @@ impl SessionCleanup {
             }
 
             // Terminate in database
-            let reason = if session.expires_at <= chrono::Utc::now() {
+            let reason = if session.expires_at <= self.clock.now() {
                 "Absolute timeout expired"
             } else {
                 "Idle timeout expired"
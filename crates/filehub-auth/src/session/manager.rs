@@ -3,19 +3,22 @@
 use std::net::IpAddr;
 use std::sync::Arc;
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use filehub_core::config::session::OverflowStrategy;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
+use filehub_cache::keys;
 use filehub_cache::provider::CacheManager;
 use filehub_core::config::{AuthConfig, SessionConfig};
-use filehub_core::error::AppError;
-use filehub_core::traits::CacheProvider;
+use filehub_core::error::{AppError, codes};
+use filehub_core::traits::{CacheProvider, Clock, SystemClock};
+use filehub_core::types::TenantId;
 use filehub_database::repositories::user::UserRepository;
-use filehub_entity::session::Session;
-use filehub_entity::user::{User, UserStatus};
+use filehub_entity::session::{DeviceInfo, Session};
+use filehub_entity::user::{User, UserRole, UserStatus};
 
+use crate::anomaly::LoginAnomalyDetector;
 use crate::jwt::encoder::TokenPair;
 use crate::jwt::{Claims, JwtDecoder, JwtEncoder};
 use crate::password::PasswordHasher;
@@ -34,6 +37,19 @@ pub struct LoginResult {
     pub user: User,
 }
 
+/// Result of opening an impersonation session.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ImpersonationResult {
+    /// Access token acting as the user; it cannot be refreshed.
+    pub access_token: String,
+    /// When the token and session expire.
+    pub expires_at: DateTime<Utc>,
+    /// Created session.
+    pub session: Session,
+    /// The impersonated user.
+    pub user: User,
+}
+
 /// Manages the complete session lifecycle.
 #[derive(Clone)]
 pub struct SessionManager {
@@ -57,6 +73,10 @@ pub struct SessionManager {
     auth_config: AuthConfig,
     /// Session configuration.
     session_config: SessionConfig,
+    /// Login location checks, when enabled.
+    anomaly_detector: Option<Arc<LoginAnomalyDetector>>,
+    /// Source of the current time for expiry, idle and lockout checks.
+    clock: Arc<dyn Clock>,
 }
 
 impl std::fmt::Debug for SessionManager {
@@ -94,22 +114,37 @@ impl SessionManager {
             cache,
             auth_config,
             session_config,
+            anomaly_detector: None,
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// Checks each login's location with `detector`.
+    pub fn with_anomaly_detector(mut self, detector: Arc<LoginAnomalyDetector>) -> Self {
+        self.anomaly_detector = Some(detector);
+        self
+    }
+
+    /// Reads the current time from `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Performs the complete login flow:
     ///
     /// 1. Validate credentials
     /// 2. Check user status (active, not locked)
-    /// 3. Resolve session limit for user's role
-    /// 4. Check user's active session count
-    /// 5. Apply overflow strategy if at limit
-    /// 6. Check pool availability (admin reservation)
-    /// 7. Atomic seat allocation
-    /// 8. Create session + generate JWT
-    /// 9. Return tokens
+    /// 3. Check the login location, if anomaly detection is enabled
+    /// 4. Resolve session limit for user's role
+    /// 5. Check user's active session count
+    /// 6. Apply overflow strategy if at limit
+    /// 7. Check pool availability (admin reservation)
+    /// 8. Atomic seat allocation
+    /// 9. Create session + generate JWT
+    /// 10. Return tokens
     ///
-    /// Rolls back seat allocation on any failure after step 7.
+    /// Rolls back seat allocation on any failure after step 8.
     pub async fn login(
         &self,
         username: &str,
@@ -119,7 +154,7 @@ impl SessionManager {
         device_info: Option<serde_json::Value>,
     ) -> Result<LoginResult, AppError> {
         // Step 1: Find user (try cache first)
-        let user = if let Some(cached) = self.get_cached_user_by_name(username).await {
+        let mut user = if let Some(cached) = self.get_cached_user_by_name(username).await {
             cached
         } else {
             let user = self
@@ -127,7 +162,10 @@ impl SessionManager {
                 .find_by_username(username)
                 .await
                 .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-                .ok_or_else(|| AppError::unauthorized("Invalid username or password"))?;
+                .ok_or_else(|| {
+                    AppError::unauthorized("Invalid username or password")
+                        .with_code(codes::AUTH_INVALID_CREDENTIALS)
+                })?;
 
             // Cache for subsequent requests
             self.cache_user(&user).await;
@@ -135,7 +173,7 @@ impl SessionManager {
         };
 
         // Step 2: Check user status
-        self.check_user_status(&user)?;
+        check_user_status(&user, self.clock.now())?;
 
         // Step 3: Verify password
         let password_valid = self
@@ -148,15 +186,25 @@ impl SessionManager {
             // in case password was changed but cache is stale (though password checking uses param vs hash)
             // But strict security might suggest invalidating.
             self.invalidate_user_cache(&user).await;
-            return Err(AppError::unauthorized("Invalid username or password"));
+            return Err(AppError::unauthorized("Invalid username or password")
+                .with_code(codes::AUTH_INVALID_CREDENTIALS));
         }
 
         // Reset failed attempts on successful password verification
         self.reset_failed_attempts(&user).await?;
 
+        // Upgrade hashes made with a lower cost than currently configured.
+        self.rehash_if_needed(&mut user, password).await;
+
         // Setup cache for the fresh user state (e.g. failed attempts reset)
         self.cache_user(&user).await;
 
+        // Refuses the login only under the `require_mfa` anomaly action.
+        let location = match &self.anomaly_detector {
+            Some(detector) => detector.check(&user, ip_address, user_agent).await?,
+            None => None,
+        };
+
         // Step 4: Resolve session limit
         let session_limit = self
             .session_limiter
@@ -205,6 +253,11 @@ impl SessionManager {
             Ok(login_result) => {
                 // Update last login
                 let _ = self.user_repo.update_last_login(user.id).await;
+                if let (Some(detector), Some(location)) = (&self.anomaly_detector, &location) {
+                    detector
+                        .record(user.id, login_result.session.id, ip_address, location)
+                        .await;
+                }
                 info!(
                     user_id = %user.id,
                     session_id = %login_result.session.id,
@@ -225,6 +278,88 @@ impl SessionManager {
         }
     }
 
+    /// Opens a session in which the administrator `admin_id` acts as
+    /// `user_id` for `duration`.
+    ///
+    /// The user must be in `tenant_id`, be able to log in, and not be an
+    /// administrator, so impersonating never grants more than the
+    /// administrator already has. No seat is allocated and the user's
+    /// session limit does not apply. The access token cannot be
+    /// refreshed; the session ends when it expires, on logout, or when an
+    /// administrator terminates it.
+    pub async fn impersonate(
+        &self,
+        admin_id: Uuid,
+        tenant_id: TenantId,
+        user_id: Uuid,
+        duration: chrono::Duration,
+        ip_address: IpAddr,
+        user_agent: Option<&str>,
+    ) -> Result<ImpersonationResult, AppError> {
+        if admin_id == user_id {
+            return Err(AppError::validation("You cannot impersonate yourself"));
+        }
+        let user = self
+            .user_repo
+            .find_by_id(user_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+            .filter(|user| TenantId::from_uuid(user.tenant_id) == tenant_id)
+            .ok_or_else(|| AppError::not_found("User not found"))?;
+        if user.role == UserRole::Admin {
+            return Err(AppError::forbidden("Administrators cannot be impersonated"));
+        }
+        check_user_status(&user, self.clock.now())?;
+
+        let expires_at = self.clock.now() + duration;
+        let token = |session_id| {
+            self.jwt_encoder.generate_impersonation_token(
+                user.id,
+                tenant_id,
+                session_id,
+                &user.role,
+                &user.username,
+                admin_id,
+                expires_at,
+            )
+        };
+
+        // The session ID is only known once the record exists, as on login
+        let preliminary = token(Uuid::new_v4())?;
+        let session = self
+            .session_store
+            .create_impersonation_session(
+                user.id,
+                admin_id,
+                &sha256_hash(&preliminary),
+                ip_address,
+                user_agent,
+                expires_at,
+            )
+            .await?;
+        let access_token = token(session.id)?;
+
+        info!(
+            admin_id = %admin_id,
+            user_id = %user.id,
+            session_id = %session.id,
+            expires_at = %expires_at,
+            "Impersonation session opened"
+        );
+
+        Ok(ImpersonationResult {
+            access_token,
+            expires_at,
+            session,
+            user,
+        })
+    }
+
+    /// Lists the impersonation sessions that are still active.
+    pub async fn active_impersonations(&self) -> Result<Vec<Session>, AppError> {
+        self.session_store.find_active_impersonations().await
+    }
+
     /// Performs the complete logout flow:
     ///
     /// 1. Blocklist the current JWT
@@ -245,8 +380,10 @@ impl SessionManager {
         // Step 2: Blocklist the entire session (prevents refresh token usage)
         self.jwt_decoder.blocklist_session(session_id).await?;
 
-        // Step 3: Release the seat
-        if let Err(e) = self.seat_allocator.release(&user_id.to_string()).await {
+        // Step 3: Release the seat; impersonation sessions hold none
+        if claims.impersonator_id().is_none()
+            && let Err(e) = self.seat_allocator.release(&user_id.to_string()).await
+        {
             error!(
                 user_id = %user_id,
                 error = %e,
@@ -255,8 +392,12 @@ impl SessionManager {
         }
 
         // Step 4: Terminate the session in database
+        let (ended_by, reason) = match claims.impersonator_id() {
+            Some(admin_id) => (admin_id, "Impersonation ended"),
+            None => (user_id, "User logout"),
+        };
         self.session_store
-            .terminate_session(session_id, Some(user_id), "User logout")
+            .terminate_session(session_id, Some(ended_by), reason)
             .await?;
 
         // Invalidate session cache
@@ -270,9 +411,14 @@ impl SessionManager {
     /// Refreshes an access token using a valid refresh token.
     ///
     /// 1. Validate refresh token
-    /// 2. Check session is still active
+    /// 2. Check session is still active, within its idle and absolute
+    ///    lifetimes
     /// 3. Generate new access token
-    /// 4. Optionally rotate refresh token
+    /// 4. Rotate refresh token
+    ///
+    /// Refreshing restarts the idle clock and moves the session's expiry
+    /// out by the refresh token lifetime, but never past the absolute
+    /// cap; the new tokens expire with the session at the latest.
     pub async fn refresh(&self, refresh_token: &str) -> Result<TokenPair, AppError> {
         // Step 1: Decode refresh token
         let claims = self.jwt_decoder.decode_refresh_token(refresh_token).await?;
@@ -294,8 +440,15 @@ impl SessionManager {
             return Err(AppError::unauthorized("Session has been terminated"));
         }
 
-        if session.expires_at <= Utc::now() {
-            return Err(AppError::unauthorized("Session has expired"));
+        let now = self.clock.now();
+        match session_expiry(&session, now, self.session_config.idle_termination_after()) {
+            Some(SessionExpiry::Absolute) => {
+                return Err(
+                    AppError::unauthorized("Session has expired").with_code(codes::SESSION_EXPIRED)
+                );
+            }
+            Some(SessionExpiry::Idle) => return Err(self.expire_idle(&session).await),
+            None => {}
         }
 
         // Step 3: Look up current user (role may have changed)
@@ -313,19 +466,22 @@ impl SessionManager {
             user
         };
 
-        self.check_user_status(&user)?;
+        check_user_status(&user, self.clock.now())?;
 
         // Step 4: Blocklist old refresh token
         self.jwt_decoder
             .blocklist_token(claims.jti, claims.remaining_ttl_seconds())
             .await?;
 
-        // Step 5: Generate new token pair
+        // Step 5: Generate new token pair, expiring with the session
+        let expires_at = self.deadline(session.created_at, now);
         let tokens = self.jwt_encoder.generate_token_pair(
             user.id,
+            TenantId::from_uuid(user.tenant_id),
             session_id,
             &user.role,
             &user.username,
+            expires_at,
         )?;
 
         // Step 6: Update refresh token hash in session
@@ -334,7 +490,10 @@ impl SessionManager {
             .update_refresh_token(session_id, &new_refresh_hash)
             .await?;
 
-        // Step 7: Touch activity
+        // Step 7: Extend the session and touch activity
+        self.session_store
+            .set_expires_at(session_id, expires_at)
+            .await?;
         self.session_store.touch_activity(session_id).await?;
 
         info!(
@@ -357,6 +516,39 @@ impl SessionManager {
         admin_id: Uuid,
         reason: &str,
     ) -> Result<(), AppError> {
+        let session = self.find_active(session_id).await?;
+
+        info!(
+            session_id = %session_id,
+            admin_id = %admin_id,
+            user_id = %session.user_id,
+            reason = %reason,
+            "Admin terminating session"
+        );
+
+        self.end_session(&session, admin_id, &format!("Admin termination: {reason}"))
+            .await
+    }
+
+    /// Ends one of `user_id`'s own sessions, e.g. a lost device.
+    pub async fn revoke(&self, session_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        let session = self.find_active(session_id).await?;
+        if session.user_id != user_id {
+            // Same answer as a missing session: don't reveal other users' sessions.
+            return Err(AppError::not_found("Session not found"));
+        }
+
+        info!(
+            session_id = %session_id,
+            user_id = %user_id,
+            "User revoking session"
+        );
+
+        self.end_session(&session, user_id, "Revoked by user").await
+    }
+
+    /// Loads a session that has not been terminated yet.
+    async fn find_active(&self, session_id: Uuid) -> Result<Session, AppError> {
         let session = self
             .session_store
             .find_by_id(session_id)
@@ -366,38 +558,36 @@ impl SessionManager {
         if session.terminated_at.is_some() {
             return Err(AppError::conflict("Session is already terminated"));
         }
+        Ok(session)
+    }
 
-        info!(
-            session_id = %session_id,
-            admin_id = %admin_id,
-            user_id = %session.user_id,
-            reason = %reason,
-            "Admin terminating session"
-        );
-
+    /// Blocklists, releases the seat of, terminates and uncaches `session`.
+    async fn end_session(
+        &self,
+        session: &Session,
+        terminated_by: Uuid,
+        reason: &str,
+    ) -> Result<(), AppError> {
         // Blocklist the session
-        self.jwt_decoder.blocklist_session(session_id).await?;
+        self.jwt_decoder.blocklist_session(session.id).await?;
 
-        // Release seat
-        if let Err(e) = self
-            .seat_allocator
-            .release(&session.user_id.to_string())
-            .await
+        // Release seat; impersonation sessions hold none
+        if !session.is_impersonation()
+            && let Err(e) = self
+                .seat_allocator
+                .release(&session.user_id.to_string())
+                .await
         {
-            error!(error = %e, "Failed to release seat during admin termination");
+            error!(error = %e, "Failed to release seat during session termination");
         }
 
         // Terminate in database
         self.session_store
-            .terminate_session(
-                session_id,
-                Some(admin_id),
-                &format!("Admin termination: {reason}"),
-            )
+            .terminate_session(session.id, Some(terminated_by), reason)
             .await?;
 
         // Invalidate session cache
-        self.invalidate_session_cache(session_id).await;
+        self.invalidate_session_cache(session.id).await;
 
         Ok(())
     }
@@ -427,6 +617,38 @@ impl SessionManager {
         Ok(terminated)
     }
 
+    /// Brings sessions in line with `user`'s (already saved) status.
+    ///
+    /// The cached copy of the user is dropped either way, so the next login
+    /// sees the new status. If the user may no longer log in, every session
+    /// is terminated, which blocklists its tokens and frees its seat; a
+    /// seat held without any session is released too. Re-enabling does
+    /// not bring terminated sessions back. Returns the number of sessions
+    /// terminated.
+    pub async fn apply_status_change(&self, user: &User, admin_id: Uuid) -> Result<u32, AppError> {
+        self.invalidate_user_cache(user).await;
+        if user.status.can_login() {
+            return Ok(0);
+        }
+
+        let terminated = self
+            .terminate_all_user_sessions(user.id, admin_id, "Account disabled")
+            .await?;
+        if terminated == 0
+            && let Err(e) = self.seat_allocator.release(&user.id.to_string()).await
+        {
+            error!(error = %e, "Failed to release seat of disabled user");
+        }
+
+        info!(
+            user_id = %user.id,
+            status = %user.status,
+            terminated = terminated,
+            "Sessions revoked after status change"
+        );
+        Ok(terminated)
+    }
+
     /// Terminates all non-admin sessions.
     pub async fn terminate_all_non_admin(
         &self,
@@ -465,6 +687,14 @@ impl SessionManager {
         Ok(terminated)
     }
 
+    /// Records activity on a session, such as a WebSocket heartbeat, so it
+    /// is not terminated for idleness.
+    pub async fn record_activity(&self, session_id: Uuid) -> Result<(), AppError> {
+        self.session_store.touch_activity(session_id).await?;
+        self.invalidate_session_cache(session_id).await;
+        Ok(())
+    }
+
     /// Validates that the given session is still valid and active.
     pub async fn validate_session(&self, session_id: Uuid) -> Result<Session, AppError> {
         if self.jwt_decoder.is_session_blocked(&session_id).await? {
@@ -488,58 +718,57 @@ impl SessionManager {
             return Err(AppError::unauthorized("Session has been terminated"));
         }
 
-        if session.expires_at <= Utc::now() {
-            return Err(AppError::unauthorized("Session has expired"));
+        // Check absolute and idle timeouts, including the idle grace window
+        let expiry = session_expiry(
+            &session,
+            self.clock.now(),
+            self.session_config.idle_termination_after(),
+        );
+        if expiry == Some(SessionExpiry::Absolute) {
+            return Err(
+                AppError::unauthorized("Session has expired").with_code(codes::SESSION_EXPIRED)
+            );
+        }
+
+        if expiry == Some(SessionExpiry::Idle) {
+            return Err(self.expire_idle(&session).await);
         }
 
-        // Check idle timeout
-        let idle_cutoff =
-            Utc::now() - chrono::Duration::minutes(self.session_config.idle_timeout_minutes as i64);
+        Ok(session)
+    }
 
-        if session.last_activity < idle_cutoff {
-            // Terminate idle session
-            self.session_store
-                .terminate_session(session.id, None, "Idle timeout")
-                .await?;
+    /// Terminates `session` for idleness, releasing its seat, and returns
+    /// the error to answer with.
+    async fn expire_idle(&self, session: &Session) -> AppError {
+        if let Err(e) = self
+            .session_store
+            .terminate_session(session.id, None, "Idle timeout")
+            .await
+        {
+            return e;
+        }
 
-            // Release seat
+        // Release seat; impersonation sessions hold none
+        if !session.is_impersonation() {
             let _ = self
                 .seat_allocator
                 .release(&session.user_id.to_string())
                 .await;
-
-            return Err(AppError::unauthorized("Session expired due to inactivity"));
         }
 
-        Ok(session)
+        AppError::unauthorized("Session expired due to inactivity")
+            .with_code(codes::SESSION_EXPIRED)
     }
 
-    /// Checks user status and lockout state.
-    fn check_user_status(&self, user: &User) -> Result<(), AppError> {
-        match user.status {
-            UserStatus::Inactive => {
-                return Err(AppError::forbidden(
-                    "Account is deactivated. Contact an administrator.",
-                ));
-            }
-            UserStatus::Locked => {
-                if let Some(locked_until) = user.locked_until {
-                    if locked_until > Utc::now() {
-                        return Err(AppError::forbidden(format!(
-                            "Account is locked until {}",
-                            locked_until.format("%Y-%m-%d %H:%M:%S UTC")
-                        )));
-                    }
-                    // Lock expired, proceed
-                } else {
-                    return Err(AppError::forbidden(
-                        "Account is locked. Contact an administrator.",
-                    ));
-                }
-            }
-            UserStatus::Active => {}
-        }
-        Ok(())
+    /// When a session created at `created_at` and used at `now` expires
+    /// unless it is used again.
+    fn deadline(&self, created_at: DateTime<Utc>, now: DateTime<Utc>) -> DateTime<Utc> {
+        session_deadline(
+            created_at,
+            now,
+            chrono::Duration::hours(self.auth_config.jwt_refresh_ttl_hours as i64),
+            self.session_config.absolute_lifetime(),
+        )
     }
 
     /// Handles a failed login attempt by incrementing the counter and locking if needed.
@@ -547,7 +776,7 @@ impl SessionManager {
         let new_count = user.failed_login_attempts.unwrap_or(0) + 1;
 
         if new_count >= self.auth_config.max_failed_attempts as i32 {
-            let locked_until = Utc::now()
+            let locked_until = self.clock.now()
                 + chrono::Duration::minutes(self.auth_config.lockout_duration_minutes as i64);
 
             self.user_repo
@@ -574,6 +803,30 @@ impl SessionManager {
     }
 
     /// Resets the failed login counter on successful authentication.
+    /// Replaces `user`'s password hash if it is weaker than the configured
+    /// cost. Failures are logged and never fail the login.
+    async fn rehash_if_needed(&self, user: &mut User, password: &str) {
+        if !self.password_hasher.needs_rehash(&user.password_hash) {
+            return;
+        }
+        let hash = match self.password_hasher.hash_password(password) {
+            Ok(hash) => hash,
+            Err(e) => {
+                tracing::warn!(user_id = %user.id, error = %e, "Password rehash failed");
+                return;
+            }
+        };
+        match self.user_repo.update_password(user.id, &hash).await {
+            Ok(()) => {
+                tracing::info!(user_id = %user.id, "Password rehashed with current parameters");
+                user.password_hash = hash;
+            }
+            Err(e) => {
+                tracing::warn!(user_id = %user.id, error = %e, "Failed to store rehashed password");
+            }
+        }
+    }
+
     async fn reset_failed_attempts(&self, user: &User) -> Result<(), AppError> {
         if user.failed_login_attempts.unwrap_or(0) > 0 {
             self.user_repo
@@ -653,19 +906,25 @@ impl SessionManager {
     ) -> Result<LoginResult, AppError> {
         // Generate a preliminary session ID for JWT claims
         let session_id = Uuid::new_v4();
+        let now = self.clock.now();
+        let expires_at = self.deadline(now, now);
 
         // Generate token pair
         let tokens = self.jwt_encoder.generate_token_pair(
             user.id,
+            TenantId::from_uuid(user.tenant_id),
             session_id,
             &user.role,
             &user.username,
+            expires_at,
         )?;
 
         // Hash tokens for storage
         let token_hash = sha256_hash(&tokens.access_token);
         let refresh_hash = sha256_hash(&tokens.refresh_token);
 
+        let device_info = with_device(device_info, user_agent);
+
         // Create session record
         let mut session = self
             .session_store
@@ -676,14 +935,17 @@ impl SessionManager {
                 ip_address,
                 user_agent,
                 device_info,
+                expires_at,
             )
             .await?;
 
         let tokens = self.jwt_encoder.generate_token_pair(
             user.id,
+            TenantId::from_uuid(user.tenant_id),
             session.id,
             &user.role,
             &user.username,
+            expires_at,
         )?;
 
         // Update session with correct hashes
@@ -695,7 +957,7 @@ impl SessionManager {
         // Mark seat as allocated
         self.session_store.set_seat_allocated(session.id).await?;
 
-        session.seat_allocated_at = Some(Utc::now());
+        session.seat_allocated_at = Some(self.clock.now());
 
         Ok(LoginResult {
             tokens,
@@ -705,41 +967,25 @@ impl SessionManager {
     }
     /// Invalidates the user cache (by ID and username if possible).
     async fn invalidate_user_cache(&self, user: &User) {
-        let _ = self.cache.delete(&format!("user:id:{}", user.id)).await;
+        let _ = self.cache.delete(&keys::user_by_id(user.id)).await;
         let _ = self
             .cache
-            .delete(&format!("user:name:{}", user.username))
+            .delete(&keys::user_by_username(&user.username))
             .await;
     }
 
     /// Invalidates the session cache.
     async fn invalidate_session_cache(&self, session_id: Uuid) {
-        let _ = self.cache.delete(&format!("session:{}", session_id)).await;
+        let _ = self.cache.delete(&keys::session_by_id(session_id)).await;
     }
 
     /// Caches the user for faster lookups.
     async fn cache_user(&self, user: &User) {
-        // Cache by ID
         if let Ok(json) = serde_json::to_string(user) {
+            let _ = self.cache.put(&keys::user_by_id(user.id), &json).await;
             let _ = self
                 .cache
-                .set(
-                    &format!("user:id:{}", user.id),
-                    &json,
-                    std::time::Duration::from_secs(900), // 15 min
-                )
-                .await;
-        }
-
-        // Cache by Username
-        if let Ok(json) = serde_json::to_string(user) {
-            let _ = self
-                .cache
-                .set(
-                    &format!("user:name:{}", user.username),
-                    &json,
-                    std::time::Duration::from_secs(900), // 15 min
-                )
+                .put(&keys::user_by_username(&user.username), &json)
                 .await;
         }
     }
@@ -749,46 +995,132 @@ impl SessionManager {
         if let Ok(json) = serde_json::to_string(session) {
             let _ = self
                 .cache
-                .set(
-                    &format!("session:{}", session.id),
-                    &json,
-                    std::time::Duration::from_secs(300), // 5 min
-                )
+                .put(&keys::session_by_id(session.id), &json)
                 .await;
         }
     }
 
     /// Tries to get a cached user by ID.
     async fn get_cached_user(&self, user_id: Uuid) -> Option<User> {
-        if let Ok(Some(json)) = self.cache.get(&format!("user:id:{}", user_id)).await {
-            if let Ok(user) = serde_json::from_str(&json) {
-                return Some(user);
-            }
+        if let Ok(Some(json)) = self.cache.get(&keys::user_by_id(user_id)).await
+            && let Ok(user) = serde_json::from_str(&json)
+        {
+            return Some(user);
         }
         None
     }
 
     /// Tries to get a cached user by Username.
     async fn get_cached_user_by_name(&self, username: &str) -> Option<User> {
-        if let Ok(Some(json)) = self.cache.get(&format!("user:name:{}", username)).await {
-            if let Ok(user) = serde_json::from_str(&json) {
-                return Some(user);
-            }
+        if let Ok(Some(json)) = self.cache.get(&keys::user_by_username(username)).await
+            && let Ok(user) = serde_json::from_str(&json)
+        {
+            return Some(user);
         }
         None
     }
 
     /// Tries to get a cached session.
     async fn get_cached_session(&self, session_id: Uuid) -> Option<Session> {
-        if let Ok(Some(json)) = self.cache.get(&format!("session:{}", session_id)).await {
-            if let Ok(session) = serde_json::from_str(&json) {
-                return Some(session);
-            }
+        if let Ok(Some(json)) = self.cache.get(&keys::session_by_id(session_id)).await
+            && let Ok(session) = serde_json::from_str(&json)
+        {
+            return Some(session);
         }
         None
     }
 }
 
+/// Why a session can no longer be used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SessionExpiry {
+    /// Past its expiry: the absolute cap, or the end of its last refresh.
+    Absolute,
+    /// Unused for longer than `idle_after`.
+    Idle,
+}
+
+/// Whether `session` has expired at `now`.
+fn session_expiry(
+    session: &Session,
+    now: DateTime<Utc>,
+    idle_after: chrono::Duration,
+) -> Option<SessionExpiry> {
+    if session.expires_at <= now {
+        Some(SessionExpiry::Absolute)
+    } else if session.last_activity < now - idle_after {
+        Some(SessionExpiry::Idle)
+    } else {
+        None
+    }
+}
+
+/// When a session created at `created_at` and used at `now` expires
+/// unless it is used again: when a refresh token issued now would, but
+/// never past the absolute cap.
+fn session_deadline(
+    created_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+    refresh_ttl: chrono::Duration,
+    absolute: Option<chrono::Duration>,
+) -> DateTime<Utc> {
+    let renewed = now + refresh_ttl;
+    match absolute {
+        Some(lifetime) => renewed.min(created_at + lifetime),
+        None => renewed,
+    }
+}
+
+/// Checks that `user`'s status allows logging in or refreshing a session
+/// at `now`.
+fn check_user_status(user: &User, now: DateTime<Utc>) -> Result<(), AppError> {
+    match user.status {
+        UserStatus::Inactive => {
+            return Err(
+                AppError::forbidden("Account is deactivated. Contact an administrator.")
+                    .with_code(codes::AUTH_ACCOUNT_DISABLED),
+            );
+        }
+        UserStatus::Locked => {
+            if let Some(locked_until) = user.locked_until {
+                if locked_until > now {
+                    return Err(AppError::forbidden(format!(
+                        "Account is locked until {}",
+                        locked_until.format("%Y-%m-%d %H:%M:%S UTC")
+                    )));
+                }
+                // Lock expired, proceed
+            } else {
+                return Err(AppError::forbidden(
+                    "Account is locked. Contact an administrator.",
+                ));
+            }
+        }
+        UserStatus::Active => {}
+    }
+    Ok(())
+}
+
+/// Adds the device derived from `user_agent` to client-supplied device
+/// info. Fields the client sent win.
+fn with_device(
+    device_info: Option<serde_json::Value>,
+    user_agent: Option<&str>,
+) -> Option<serde_json::Value> {
+    let derived = serde_json::to_value(DeviceInfo::from_user_agent(user_agent)).ok()?;
+    match device_info {
+        Some(serde_json::Value::Object(mut supplied)) => {
+            if let serde_json::Value::Object(derived) = derived {
+                for (key, value) in derived {
+                    supplied.entry(key).or_insert(value);
+                }
+            }
+            Some(serde_json::Value::Object(supplied))
+        }
+        _ => Some(derived),
+    }
+}
+
 /// Computes a SHA-256 hash of the input string and returns it as a hex string.
 fn sha256_hash(input: &str) -> String {
     use sha2::{Digest, Sha256};
@@ -797,3 +1129,161 @@ fn sha256_hash(input: &str) -> String {
     let result = hasher.finalize();
     format!("{:x}", result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use filehub_core::traits::MockClock;
+
+    fn user(status: UserStatus) -> User {
+        User {
+            id: Uuid::new_v4(),
+            tenant_id: TenantId::DEFAULT.into_uuid(),
+            username: "dana".to_string(),
+            email: None,
+            password_hash: String::new(),
+            display_name: None,
+            role: UserRole::Creator,
+            status,
+            failed_login_attempts: None,
+            locked_until: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_login_at: None,
+            created_by: None,
+        }
+    }
+
+    fn session() -> Session {
+        Session {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            token_hash: String::new(),
+            refresh_token_hash: None,
+            ip_address: IpAddr::from([127, 0, 0, 1]),
+            user_agent: None,
+            device_info: None,
+            license_checkout_id: None,
+            seat_allocated_at: None,
+            overflow_kicked: None,
+            impersonator_id: None,
+            presence_status: None,
+            ws_connected: None,
+            ws_connected_at: None,
+            idle_warned_at: None,
+            terminated_by: None,
+            terminated_reason: None,
+            terminated_at: None,
+            created_at: Utc::now(),
+            expires_at: Utc::now(),
+            last_activity: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_disabled_user_cannot_authenticate() {
+        let err = check_user_status(&user(UserStatus::Inactive), Utc::now()).unwrap_err();
+        assert_eq!(err.code, codes::AUTH_ACCOUNT_DISABLED);
+
+        assert!(check_user_status(&user(UserStatus::Active), Utc::now()).is_ok());
+    }
+
+    #[test]
+    fn test_lock_lifts_when_clock_passes_it() {
+        let clock = MockClock::default();
+        let locked = User {
+            locked_until: Some(clock.now() + chrono::Duration::minutes(15)),
+            ..user(UserStatus::Locked)
+        };
+        assert!(check_user_status(&locked, clock.now()).is_err());
+
+        clock.advance(chrono::Duration::minutes(16));
+        assert!(check_user_status(&locked, clock.now()).is_ok());
+    }
+
+    #[test]
+    fn test_session_expiry_follows_clock() {
+        let clock = MockClock::default();
+        let idle_after = chrono::Duration::minutes(30);
+        let session = Session {
+            expires_at: clock.now() + chrono::Duration::hours(8),
+            last_activity: clock.now(),
+            ..session()
+        };
+        assert_eq!(session_expiry(&session, clock.now(), idle_after), None);
+
+        clock.advance(chrono::Duration::minutes(31));
+        assert_eq!(
+            session_expiry(&session, clock.now(), idle_after),
+            Some(SessionExpiry::Idle)
+        );
+
+        clock.advance(chrono::Duration::hours(8));
+        assert_eq!(
+            session_expiry(&session, clock.now(), idle_after),
+            Some(SessionExpiry::Absolute)
+        );
+    }
+
+    #[test]
+    fn test_active_session_still_hits_absolute_cap() {
+        let clock = MockClock::default();
+        let (refresh_ttl, idle_after) =
+            (chrono::Duration::hours(24), chrono::Duration::minutes(35));
+        let lifetime = chrono::Duration::hours(12);
+        let created_at = clock.now();
+        let mut session = Session {
+            created_at,
+            expires_at: session_deadline(created_at, clock.now(), refresh_ttl, Some(lifetime)),
+            last_activity: clock.now(),
+            ..session()
+        };
+        assert_eq!(session.expires_at, created_at + lifetime);
+
+        // Used and refreshed every 20 minutes, the session never idles,
+        // and refreshing never moves its expiry past the cap.
+        while clock.now() + chrono::Duration::minutes(20) < created_at + lifetime {
+            clock.advance(chrono::Duration::minutes(20));
+            assert_eq!(session_expiry(&session, clock.now(), idle_after), None);
+            session.last_activity = clock.now();
+            session.expires_at =
+                session_deadline(created_at, clock.now(), refresh_ttl, Some(lifetime));
+            assert_eq!(session.expires_at, created_at + lifetime);
+        }
+
+        clock.advance(chrono::Duration::minutes(20));
+        assert_eq!(
+            session_expiry(&session, clock.now(), idle_after),
+            Some(SessionExpiry::Absolute)
+        );
+    }
+
+    #[test]
+    fn test_uncapped_session_lives_while_used() {
+        let clock = MockClock::default();
+        let (refresh_ttl, idle_after) =
+            (chrono::Duration::hours(24), chrono::Duration::minutes(35));
+        let created_at = clock.now();
+        let mut session = Session {
+            created_at,
+            expires_at: session_deadline(created_at, clock.now(), refresh_ttl, None),
+            last_activity: clock.now(),
+            ..session()
+        };
+
+        for _ in 0..(3 * 24 * 3) {
+            clock.advance(chrono::Duration::minutes(20));
+            assert_eq!(session_expiry(&session, clock.now(), idle_after), None);
+            session.last_activity = clock.now();
+            session.expires_at = session_deadline(created_at, clock.now(), refresh_ttl, None);
+        }
+        assert_eq!(session.expires_at, clock.now() + refresh_ttl);
+
+        // Unused, it still ends on the idle lifetime.
+        clock.advance(chrono::Duration::minutes(36));
+        assert_eq!(
+            session_expiry(&session, clock.now(), idle_after),
+            Some(SessionExpiry::Idle)
+        );
+    }
+}
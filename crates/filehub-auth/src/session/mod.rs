@@ -5,5 +5,5 @@ pub mod manager;
 pub mod store;
 
 pub use cleanup::SessionCleanup;
-pub use manager::SessionManager;
+pub use manager::{ImpersonationResult, SessionManager};
 pub use store::SessionStore;
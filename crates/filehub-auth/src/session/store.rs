@@ -3,15 +3,15 @@
 use std::net::IpAddr;
 use std::sync::Arc;
 
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
 use filehub_core::config::SessionConfig;
 use filehub_core::error::AppError;
 use filehub_database::repositories::session::SessionRepository;
 use filehub_entity::presence::PresenceStatus;
-use filehub_entity::session::Session;
 use filehub_entity::session::model::CreateSession;
+use filehub_entity::session::{DeviceInfo, Session};
 
 /// Abstracts session persistence operations.
 #[derive(Debug, Clone)]
@@ -28,7 +28,9 @@ impl SessionStore {
         Self { repo, config }
     }
 
-    /// Creates a new session record in the database.
+    /// Creates a new session record in the database, expiring at
+    /// `expires_at` unless it is extended.
+    #[allow(clippy::too_many_arguments)]
     pub async fn create_session(
         &self,
         user_id: Uuid,
@@ -37,10 +39,8 @@ impl SessionStore {
         ip_address: IpAddr,
         user_agent: Option<&str>,
         device_info: Option<serde_json::Value>,
+        expires_at: DateTime<Utc>,
     ) -> Result<Session, AppError> {
-        let now = Utc::now();
-        let expires_at = now + Duration::hours(self.config.absolute_timeout_hours as i64);
-
         let session = CreateSession {
             user_id,
             token_hash: token_hash.to_string(),
@@ -49,6 +49,7 @@ impl SessionStore {
             user_agent: user_agent.map(String::from),
             device_info,
             expires_at,
+            impersonator_id: None,
         };
 
         let result = self
@@ -60,6 +61,34 @@ impl SessionStore {
         Ok(result)
     }
 
+    /// Creates the record of an impersonation session, in which
+    /// `impersonator_id` acts as `user_id` until `expires_at`.
+    pub async fn create_impersonation_session(
+        &self,
+        user_id: Uuid,
+        impersonator_id: Uuid,
+        token_hash: &str,
+        ip_address: IpAddr,
+        user_agent: Option<&str>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Session, AppError> {
+        let session = CreateSession {
+            user_id,
+            token_hash: token_hash.to_string(),
+            refresh_token_hash: None,
+            ip_address,
+            user_agent: user_agent.map(String::from),
+            device_info: serde_json::to_value(DeviceInfo::from_user_agent(user_agent)).ok(),
+            expires_at,
+            impersonator_id: Some(impersonator_id),
+        };
+
+        self.repo
+            .create(&session)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to create session: {e}")))
+    }
+
     /// Finds a session by ID.
     pub async fn find_by_id(&self, session_id: Uuid) -> Result<Option<Session>, AppError> {
         self.repo
@@ -100,7 +129,8 @@ impl SessionStore {
             .map_err(|e| AppError::internal(format!("Failed to find active sessions: {e}")))
     }
 
-    /// Updates session's last activity timestamp.
+    /// Updates session's last activity timestamp, cancelling any pending
+    /// idle termination.
     pub async fn touch_activity(&self, session_id: Uuid) -> Result<(), AppError> {
         self.repo
             .update_last_activity(session_id)
@@ -108,6 +138,18 @@ impl SessionStore {
             .map_err(|e| AppError::internal(format!("Failed to update activity: {e}")))
     }
 
+    /// Moves the expiry of a session.
+    pub async fn set_expires_at(
+        &self,
+        session_id: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        self.repo
+            .update_expires_at(session_id, expires_at)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to update session expiry: {e}")))
+    }
+
     /// Updates session license checkout info.
     pub async fn set_license_checkout(
         &self,
@@ -154,7 +196,7 @@ impl SessionStore {
     /// Finds all expired or idle sessions eligible for cleanup.
     pub async fn find_expired_sessions(&self) -> Result<Vec<Session>, AppError> {
         let now = Utc::now();
-        let idle_cutoff = now - Duration::minutes(self.config.idle_timeout_minutes as i64);
+        let idle_cutoff = now - self.config.idle_termination_after();
 
         self.repo
             .find_expired_or_idle(now, idle_cutoff)
@@ -170,6 +212,14 @@ impl SessionStore {
             .map_err(|e| AppError::internal(format!("Failed to count all active sessions: {e}")))
     }
 
+    /// Counts active sessions that still hold a license seat.
+    pub async fn count_seated_active(&self) -> Result<i64, AppError> {
+        self.repo
+            .count_seated_active()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to count seated sessions: {e}")))
+    }
+
     /// Finds all active sessions (for admin view).
     pub async fn find_all_active(&self) -> Result<Vec<Session>, AppError> {
         self.repo
@@ -178,6 +228,14 @@ impl SessionStore {
             .map_err(|e| AppError::internal(format!("Failed to find all active sessions: {e}")))
     }
 
+    /// Finds all active impersonation sessions.
+    pub async fn find_active_impersonations(&self) -> Result<Vec<Session>, AppError> {
+        self.repo
+            .find_active_impersonations()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to find impersonation sessions: {e}")))
+    }
+
     /// Updates WebSocket connection state.
     pub async fn set_ws_connected(
         &self,
@@ -0,0 +1,184 @@
+//! Circuit breaker for calls to a shared backend such as Redis.
+//!
+//! After `failure_threshold` consecutive failures the breaker opens and
+//! callers skip the backend (degraded mode) instead of waiting on it.
+//! Once `open_for` has passed, the next call is let through as a probe:
+//! success closes the breaker, failure keeps it open for another period.
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use tokio::time::Instant;
+use tracing::{error, info};
+
+use filehub_core::config::cache::CircuitBreakerConfig;
+
+/// Breaker position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Calls go through; counts consecutive failures.
+    Closed { failures: u32 },
+    /// Calls are skipped until `until`.
+    Open { until: Instant },
+    /// One probe call is in flight; another may start at `until`.
+    HalfOpen { until: Instant },
+}
+
+/// Circuit breaker shared by every caller of one backend.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    /// Backend name for logs.
+    name: &'static str,
+    /// Consecutive failures that open the breaker.
+    failure_threshold: u32,
+    /// How long the breaker stays open before probing.
+    open_for: Duration,
+    /// Current position.
+    state: Mutex<State>,
+    /// Times the breaker opened.
+    trips: AtomicU64,
+    /// Calls skipped while open.
+    short_circuited: AtomicU64,
+}
+
+impl CircuitBreaker {
+    /// Create a closed breaker for the backend called `name`.
+    pub fn new(name: &'static str, config: &CircuitBreakerConfig) -> Self {
+        Self {
+            name,
+            failure_threshold: config.failure_threshold.max(1),
+            open_for: Duration::from_secs(config.open_seconds),
+            state: Mutex::new(State::Closed { failures: 0 }),
+            trips: AtomicU64::new(0),
+            short_circuited: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether a call may go to the backend. A skipped call is counted.
+    pub fn allow(&self) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        match *state {
+            State::Closed { .. } => true,
+            State::Open { until } | State::HalfOpen { until } if now >= until => {
+                // A probe that never reported back (its caller was
+                // cancelled) is replaced after another `open_for`.
+                *state = State::HalfOpen {
+                    until: now + self.open_for,
+                };
+                true
+            }
+            State::Open { .. } | State::HalfOpen { .. } => {
+                self.short_circuited.fetch_add(1, Ordering::Relaxed);
+                false
+            }
+        }
+    }
+
+    /// Record a successful backend call. Returns `true` if this closed
+    /// the breaker, i.e. the backend just recovered.
+    pub fn record_success(&self) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let recovered = !matches!(*state, State::Closed { .. });
+        if recovered {
+            info!(
+                backend = self.name,
+                "Circuit breaker closed: backend recovered"
+            );
+        }
+        *state = State::Closed { failures: 0 };
+        recovered
+    }
+
+    /// Record a failed backend call.
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let until = Instant::now() + self.open_for;
+        match *state {
+            State::Closed { failures } if failures + 1 < self.failure_threshold => {
+                *state = State::Closed {
+                    failures: failures + 1,
+                };
+            }
+            State::Closed { .. } => {
+                self.trips.fetch_add(1, Ordering::Relaxed);
+                error!(
+                    backend = self.name,
+                    failures = self.failure_threshold,
+                    retry_in_secs = self.open_for.as_secs(),
+                    "Circuit breaker opened: backend unavailable, running degraded"
+                );
+                *state = State::Open { until };
+            }
+            State::HalfOpen { .. } | State::Open { .. } => {
+                error!(
+                    backend = self.name,
+                    "Circuit breaker probe failed: backend still unavailable"
+                );
+                *state = State::Open { until };
+            }
+        }
+    }
+
+    /// Whether callers are currently running degraded.
+    pub fn is_open(&self) -> bool {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        !matches!(*state, State::Closed { .. })
+    }
+
+    /// Times the breaker has opened since startup.
+    pub fn trips(&self) -> u64 {
+        self.trips.load(Ordering::Relaxed)
+    }
+
+    /// Calls skipped while the breaker was open.
+    pub fn short_circuited(&self) -> u64 {
+        self.short_circuited.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(
+            "test",
+            &CircuitBreakerConfig {
+                failure_threshold: 3,
+                open_seconds: 10,
+            },
+        )
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_opens_after_threshold_and_probes() {
+        let breaker = breaker();
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(!breaker.record_success());
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+
+        breaker.record_failure();
+        assert!(breaker.is_open());
+        assert_eq!(breaker.trips(), 1);
+        assert!(!breaker.allow());
+        assert_eq!(breaker.short_circuited(), 1);
+
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert!(breaker.allow(), "first call after the open period probes");
+        assert!(!breaker.allow(), "only one probe at a time");
+
+        breaker.record_failure();
+        assert!(!breaker.allow());
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert!(breaker.allow());
+        assert!(breaker.record_success());
+        assert!(!breaker.is_open());
+        assert!(breaker.allow());
+        assert_eq!(breaker.trips(), 1);
+    }
+}
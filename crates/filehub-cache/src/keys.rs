@@ -1,35 +1,222 @@
 //! Cache key builders for all FileHub cache entries.
 //!
 //! Centralising key construction prevents typos and makes it easy
-//! to find every key the application uses.
+//! to find every key the application uses. Keys of cached entities are
+//! returned as [`CacheKey`]s, which carry the [`KeyCategory`] whose TTL
+//! from `[cache.ttl]` they are stored with. Keys of counters, locks and
+//! other entries whose lifetime is part of their meaning stay plain
+//! strings.
+
+use std::fmt;
+use std::ops::Deref;
+use std::time::Duration;
 
 use uuid::Uuid;
 
+use filehub_core::config::CacheTtlConfig;
+use filehub_core::types::TenantId;
+
 /// Prefix applied to all FileHub cache keys.
 const PREFIX: &str = "filehub";
 
+// ── Categories ─────────────────────────────────────────────
+
+/// What a cached entry holds, which decides how long it is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyCategory {
+    /// Users, by id or name.
+    User,
+    /// Sessions and session counts.
+    Session,
+    /// Resolved permissions.
+    Permission,
+    /// File entities.
+    File,
+    /// Folder entities and listings.
+    Folder,
+    /// Loaded folder subtrees.
+    FolderTree,
+    /// Per-storage generations the folder subtrees are cached under.
+    FolderTreeGeneration,
+    /// Storage entities and the storage list.
+    Storage,
+    /// Shares, by id or token.
+    Share,
+    /// License pool and seat counts.
+    License,
+    /// Presence state.
+    Presence,
+    /// Notification counts and preferences.
+    Notification,
+    /// Rendered thumbnails.
+    Thumbnail,
+}
+
+impl KeyCategory {
+    /// Every category.
+    pub const ALL: [Self; 13] = [
+        Self::User,
+        Self::Session,
+        Self::Permission,
+        Self::File,
+        Self::Folder,
+        Self::FolderTree,
+        Self::FolderTreeGeneration,
+        Self::Storage,
+        Self::Share,
+        Self::License,
+        Self::Presence,
+        Self::Notification,
+        Self::Thumbnail,
+    ];
+
+    /// How long entries of this category are kept under `ttls`.
+    pub fn ttl(self, ttls: &CacheTtlConfig) -> Duration {
+        let seconds = match self {
+            Self::User => ttls.user,
+            Self::Session => ttls.session,
+            Self::Permission => ttls.permission,
+            Self::File => ttls.file,
+            Self::Folder => ttls.folder,
+            Self::FolderTree => ttls.folder_tree,
+            Self::FolderTreeGeneration => ttls.folder_tree_generation,
+            Self::Storage => ttls.storage,
+            Self::Share => ttls.share,
+            Self::License => ttls.license,
+            Self::Presence => ttls.presence,
+            Self::Notification => ttls.notification,
+            Self::Thumbnail => ttls.thumbnail,
+        };
+        Duration::from_secs(seconds)
+    }
+}
+
+/// A cache key together with the category it is stored under.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    key: String,
+    category: KeyCategory,
+}
+
+impl CacheKey {
+    fn new(category: KeyCategory, key: String) -> Self {
+        Self { key, category }
+    }
+
+    /// The key itself.
+    pub fn as_str(&self) -> &str {
+        &self.key
+    }
+
+    /// The category the key is stored under.
+    pub fn category(&self) -> KeyCategory {
+        self.category
+    }
+
+    /// How long the entry is kept under `ttls`.
+    pub fn ttl(&self, ttls: &CacheTtlConfig) -> Duration {
+        self.category.ttl(ttls)
+    }
+
+    /// The same key in a tenant's namespace; see [`tenant_scoped`].
+    pub fn for_tenant(&self, tenant_id: TenantId) -> Self {
+        Self::new(self.category, tenant_scoped(tenant_id, &self.key))
+    }
+}
+
+impl Deref for CacheKey {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.key
+    }
+}
+
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.key)
+    }
+}
+
+impl PartialEq<str> for CacheKey {
+    fn eq(&self, other: &str) -> bool {
+        self.key == other
+    }
+}
+
+impl PartialEq<&str> for CacheKey {
+    fn eq(&self, other: &&str) -> bool {
+        self.key == *other
+    }
+}
+
+impl PartialEq<String> for CacheKey {
+    fn eq(&self, other: &String) -> bool {
+        &self.key == other
+    }
+}
+
+impl PartialEq<CacheKey> for String {
+    fn eq(&self, other: &CacheKey) -> bool {
+        *self == other.key
+    }
+}
+
+impl From<CacheKey> for String {
+    fn from(key: CacheKey) -> Self {
+        key.key
+    }
+}
+
+// ── Tenant namespace ───────────────────────────────────────
+
+/// Moves a key built by this module into a tenant's namespace
+/// (`filehub:t:{tenant}:...`). Keys of the default tenant are left as
+/// they are, so upgrading a single-tenant deployment keeps its cache.
+/// No key outside a tenant namespace starts with `filehub:t:`, so keys of
+/// different tenants never collide.
+pub fn tenant_scoped(tenant_id: TenantId, key: &str) -> String {
+    if tenant_id.is_default() {
+        return key.to_string();
+    }
+    let rest = key
+        .strip_prefix(PREFIX)
+        .and_then(|rest| rest.strip_prefix(':'))
+        .unwrap_or(key);
+    format!("{PREFIX}:t:{tenant_id}:{rest}")
+}
+
 // ── User keys ──────────────────────────────────────────────
 
 /// Cache key for a user entity by ID.
-pub fn user_by_id(user_id: Uuid) -> String {
-    format!("{PREFIX}:user:{user_id}")
+pub fn user_by_id(user_id: Uuid) -> CacheKey {
+    CacheKey::new(KeyCategory::User, format!("{PREFIX}:user:{user_id}"))
 }
 
 /// Cache key for a user entity by username.
-pub fn user_by_username(username: &str) -> String {
-    format!("{PREFIX}:user:name:{}", username.to_lowercase())
+pub fn user_by_username(username: &str) -> CacheKey {
+    CacheKey::new(
+        KeyCategory::User,
+        format!("{PREFIX}:user:name:{}", username.to_lowercase()),
+    )
 }
 
 // ── Session keys ───────────────────────────────────────────
 
 /// Cache key for a session entity by ID.
-pub fn session_by_id(session_id: Uuid) -> String {
-    format!("{PREFIX}:session:{session_id}")
+pub fn session_by_id(session_id: Uuid) -> CacheKey {
+    CacheKey::new(
+        KeyCategory::Session,
+        format!("{PREFIX}:session:{session_id}"),
+    )
 }
 
 /// Cache key for the active session count of a user.
-pub fn user_active_session_count(user_id: Uuid) -> String {
-    format!("{PREFIX}:session:count:{user_id}")
+pub fn user_active_session_count(user_id: Uuid) -> CacheKey {
+    CacheKey::new(
+        KeyCategory::Session,
+        format!("{PREFIX}:session:count:{user_id}"),
+    )
 }
 
 /// Cache key for the JWT blocklist (revoked tokens).
@@ -40,8 +227,25 @@ pub fn jwt_blocklist(token_hash: &str) -> String {
 // ── Permission keys ────────────────────────────────────────
 
 /// Cache key for effective permission of a user on a resource.
-pub fn effective_permission(resource_type: &str, resource_id: Uuid, user_id: Uuid) -> String {
-    format!("{PREFIX}:perm:{resource_type}:{resource_id}:{user_id}")
+pub fn effective_permission(resource_type: &str, resource_id: Uuid, user_id: Uuid) -> CacheKey {
+    CacheKey::new(
+        KeyCategory::Permission,
+        format!("{PREFIX}:perm:{resource_type}:{resource_id}:{user_id}"),
+    )
+}
+
+/// Cache key for the outcome of checking `required` permission of a user
+/// on a resource.
+pub fn permission_check(
+    resource_type: &str,
+    resource_id: Uuid,
+    user_id: Uuid,
+    required: &str,
+) -> CacheKey {
+    CacheKey::new(
+        KeyCategory::Permission,
+        format!("{PREFIX}:perm:{resource_type}:{resource_id}:{user_id}:{required}"),
+    )
 }
 
 /// Pattern to invalidate all permission cache entries for a resource.
@@ -49,62 +253,102 @@ pub fn permission_resource_pattern(resource_type: &str, resource_id: Uuid) -> St
     format!("{PREFIX}:perm:{resource_type}:{resource_id}:*")
 }
 
+/// Pattern to invalidate every cached permission.
+pub fn permission_all_pattern() -> String {
+    format!("{PREFIX}:perm:*")
+}
+
 /// Pattern to invalidate all permission cache entries for a user.
 pub fn permission_user_pattern(user_id: Uuid) -> String {
-    format!("{PREFIX}:perm:*:*:{user_id}")
+    format!("{PREFIX}:perm:*:*:{user_id}*")
 }
 
 // ── File / Folder keys ─────────────────────────────────────
 
 /// Cache key for a file entity by ID.
-pub fn file_by_id(file_id: Uuid) -> String {
-    format!("{PREFIX}:file:{file_id}")
+pub fn file_by_id(file_id: Uuid) -> CacheKey {
+    CacheKey::new(KeyCategory::File, format!("{PREFIX}:file:{file_id}"))
 }
 
 /// Cache key for a folder entity by ID.
-pub fn folder_by_id(folder_id: Uuid) -> String {
-    format!("{PREFIX}:folder:{folder_id}")
+pub fn folder_by_id(folder_id: Uuid) -> CacheKey {
+    CacheKey::new(KeyCategory::Folder, format!("{PREFIX}:folder:{folder_id}"))
 }
 
 /// Cache key for the folder tree of a storage.
-pub fn folder_tree(storage_id: Uuid) -> String {
-    format!("{PREFIX}:tree:{storage_id}")
+pub fn folder_tree(storage_id: Uuid) -> CacheKey {
+    CacheKey::new(
+        KeyCategory::FolderTree,
+        format!("{PREFIX}:tree:{storage_id}"),
+    )
+}
+
+/// Cache key for the generation a storage's folder subtrees are cached
+/// under.
+pub fn folder_tree_generation(storage_id: Uuid) -> CacheKey {
+    CacheKey::new(
+        KeyCategory::FolderTreeGeneration,
+        format!("{PREFIX}:tree:gen:{storage_id}"),
+    )
+}
+
+/// Cache key for the rows of the subtree at `root_id`, loaded under
+/// `generation`.
+pub fn folder_tree_rows(generation: &str, root_id: Uuid) -> CacheKey {
+    CacheKey::new(
+        KeyCategory::FolderTree,
+        format!("{PREFIX}:tree:{generation}:{root_id}"),
+    )
 }
 
 /// Cache key for files in a folder listing.
-pub fn folder_files(folder_id: Uuid, page: u64) -> String {
-    format!("{PREFIX}:folder_files:{folder_id}:p{page}")
+pub fn folder_files(folder_id: Uuid, page: u64) -> CacheKey {
+    CacheKey::new(
+        KeyCategory::Folder,
+        format!("{PREFIX}:folder_files:{folder_id}:p{page}"),
+    )
+}
+
+/// Cache key for a user's saved view of a folder.
+pub fn folder_preferences(user_id: Uuid, folder_id: Uuid) -> CacheKey {
+    CacheKey::new(
+        KeyCategory::Folder,
+        format!("{PREFIX}:folder_prefs:{user_id}:{folder_id}"),
+    )
 }
 
 // ── Storage keys ───────────────────────────────────────────
 
 /// Cache key for a storage entity by ID.
-pub fn storage_by_id(storage_id: Uuid) -> String {
-    format!("{PREFIX}:storage:{storage_id}")
+pub fn storage_by_id(storage_id: Uuid) -> CacheKey {
+    CacheKey::new(
+        KeyCategory::Storage,
+        format!("{PREFIX}:storage:{storage_id}"),
+    )
 }
 
 /// Cache key for the list of all storages.
-pub fn storage_list() -> String {
-    format!("{PREFIX}:storages:all")
+pub fn storage_list() -> CacheKey {
+    CacheKey::new(KeyCategory::Storage, format!("{PREFIX}:storages:all"))
 }
 
 // ── Share keys ─────────────────────────────────────────────
 
 /// Cache key for a share entity by token.
-pub fn share_by_token(token: &str) -> String {
-    format!("{PREFIX}:share:token:{token}")
+pub fn share_by_token(token: &str) -> CacheKey {
+    CacheKey::new(KeyCategory::Share, format!("{PREFIX}:share:token:{token}"))
 }
 
 /// Cache key for a share entity by ID.
-pub fn share_by_id(share_id: Uuid) -> String {
-    format!("{PREFIX}:share:{share_id}")
+pub fn share_by_id(share_id: Uuid) -> CacheKey {
+    CacheKey::new(KeyCategory::Share, format!("{PREFIX}:share:{share_id}"))
 }
 
 // ── License / Seat keys ────────────────────────────────────
 
 /// Cache key for the license pool status.
-pub fn license_pool_status() -> String {
-    format!("{PREFIX}:license:pool")
+pub fn license_pool_status() -> CacheKey {
+    CacheKey::new(KeyCategory::License, format!("{PREFIX}:license:pool"))
 }
 
 /// Cache key for the seat allocation lock.
@@ -113,42 +357,65 @@ pub fn seat_allocation_lock() -> String {
 }
 
 /// Cache key for checked-out seat count.
-pub fn seat_checked_out() -> String {
-    format!("{PREFIX}:seat:checked_out")
+pub fn seat_checked_out() -> CacheKey {
+    CacheKey::new(KeyCategory::License, format!("{PREFIX}:seat:checked_out"))
 }
 
 /// Cache key for total seat count.
-pub fn seat_total() -> String {
-    format!("{PREFIX}:seat:total")
+pub fn seat_total() -> CacheKey {
+    CacheKey::new(KeyCategory::License, format!("{PREFIX}:seat:total"))
 }
 
 /// Cache key for admin-reserved seat count.
-pub fn seat_admin_reserved() -> String {
-    format!("{PREFIX}:seat:admin_reserved")
+pub fn seat_admin_reserved() -> CacheKey {
+    CacheKey::new(
+        KeyCategory::License,
+        format!("{PREFIX}:seat:admin_reserved"),
+    )
 }
 
 // ── Presence keys ──────────────────────────────────────────
 
 /// Cache key for user presence state.
-pub fn presence(user_id: Uuid) -> String {
-    format!("{PREFIX}:presence:{user_id}")
+pub fn presence(user_id: Uuid) -> CacheKey {
+    CacheKey::new(
+        KeyCategory::Presence,
+        format!("{PREFIX}:presence:{user_id}"),
+    )
 }
 
 /// Cache key for the set of all online users.
-pub fn online_users() -> String {
-    format!("{PREFIX}:presence:online")
+pub fn online_users() -> CacheKey {
+    CacheKey::new(KeyCategory::Presence, format!("{PREFIX}:presence:online"))
 }
 
 // ── Notification keys ──────────────────────────────────────
 
 /// Cache key for unread notification count.
-pub fn unread_notification_count(user_id: Uuid) -> String {
-    format!("{PREFIX}:notif:unread:{user_id}")
+pub fn unread_notification_count(user_id: Uuid) -> CacheKey {
+    CacheKey::new(
+        KeyCategory::Notification,
+        format!("{PREFIX}:notif:unread:{user_id}"),
+    )
 }
 
 /// Cache key for notification preferences.
-pub fn notification_preferences(user_id: Uuid) -> String {
-    format!("{PREFIX}:notif:prefs:{user_id}")
+pub fn notification_preferences(user_id: Uuid) -> CacheKey {
+    CacheKey::new(
+        KeyCategory::Notification,
+        format!("{PREFIX}:notif:prefs:{user_id}"),
+    )
+}
+
+// ── Thumbnail keys ─────────────────────────────────────────
+
+/// Cache key for a rendered thumbnail. The version keeps a file
+/// overwritten in place from serving its old thumbnails.
+pub fn thumbnail(file_id: Uuid, version: i32, edge: u32, format: impl fmt::Display) -> CacheKey {
+    CacheKey::new(
+        KeyCategory::Thumbnail,
+        format!("{PREFIX}:thumb:{file_id}:v{version}:{edge}:{format}"),
+    )
 }
 
 // ── Rate limiting keys ─────────────────────────────────────
@@ -158,6 +425,14 @@ pub fn rate_limit(endpoint: &str, identifier: &str) -> String {
     format!("{PREFIX}:rate:{endpoint}:{identifier}")
 }
 
+// ── Idempotency keys ───────────────────────────────────────
+
+/// Cache key for the stored outcome of an idempotent request.
+/// `key_hash` covers the route and the client's `Idempotency-Key`.
+pub fn idempotency(user_id: Uuid, key_hash: &str) -> String {
+    format!("{PREFIX}:idempotency:{user_id}:{key_hash}")
+}
+
 // ── Dedup keys ─────────────────────────────────────────────
 
 /// Cache key for notification deduplication.
@@ -187,4 +462,64 @@ mod tests {
             "filehub:perm:folder:00000000-0000-0000-0000-000000000000:00000000-0000-0000-0000-000000000000"
         );
     }
+
+    #[test]
+    fn test_every_category_has_a_ttl() {
+        let ttls = CacheTtlConfig::default();
+        for category in KeyCategory::ALL {
+            assert!(!category.ttl(&ttls).is_zero(), "{category:?}");
+        }
+        assert_eq!(
+            user_by_id(Uuid::nil()).ttl(&ttls),
+            Duration::from_secs(ttls.user)
+        );
+    }
+
+    #[test]
+    fn test_configured_ttl_is_respected() {
+        let ttls = CacheTtlConfig {
+            thumbnail: 10,
+            permission: 42,
+            ..Default::default()
+        };
+        let key = thumbnail(Uuid::nil(), 3, 256, "webp");
+        assert_eq!(key.category(), KeyCategory::Thumbnail);
+        assert_eq!(key.ttl(&ttls), Duration::from_secs(10));
+        assert_eq!(
+            permission_check("file", Uuid::nil(), Uuid::nil(), "viewer").ttl(&ttls),
+            Duration::from_secs(42)
+        );
+    }
+
+    #[test]
+    fn test_permission_patterns_cover_checks() {
+        let key = permission_check("folder", Uuid::nil(), Uuid::nil(), "editor");
+        let by_resource = permission_resource_pattern("folder", Uuid::nil());
+        assert!(key.starts_with(by_resource.trim_end_matches('*')));
+        assert!(permission_user_pattern(Uuid::nil()).ends_with('*'));
+        assert!(key.starts_with(permission_all_pattern().trim_end_matches('*')));
+    }
+
+    #[test]
+    fn test_tenant_scoped_keys_do_not_collide() {
+        let user = Uuid::nil();
+        let (a, b) = (TenantId::new(), TenantId::new());
+
+        assert_eq!(
+            tenant_scoped(TenantId::DEFAULT, &user_by_id(user)),
+            user_by_id(user)
+        );
+        assert_eq!(
+            tenant_scoped(a, &user_by_id(user)),
+            format!("filehub:t:{a}:user:00000000-0000-0000-0000-000000000000")
+        );
+        assert_ne!(
+            tenant_scoped(a, &user_by_id(user)),
+            tenant_scoped(b, &user_by_id(user))
+        );
+        assert_ne!(
+            tenant_scoped(a, &user_by_id(user)),
+            tenant_scoped(TenantId::DEFAULT, &user_by_id(user))
+        );
+    }
 }
@@ -8,6 +8,7 @@
 //!
 //! The provider is selected at runtime based on configuration.
 
+pub mod breaker;
 pub mod keys;
 #[cfg(feature = "memory")]
 pub mod memory;
@@ -6,11 +6,12 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 use moka::future::Cache;
+use tokio::time::Instant;
 use tracing::debug;
 
 use filehub_core::config::cache::MemoryCacheConfig;
 use filehub_core::result::AppResult;
-use filehub_core::traits::cache::CacheProvider;
+use filehub_core::traits::cache::{CacheProvider, TokenBucket};
 
 /// In-memory cache provider using moka.
 #[derive(Debug, Clone)]
@@ -21,6 +22,15 @@ pub struct MemoryCacheProvider {
     default_ttl: Duration,
     /// Counters stored separately for atomic incr/decr.
     counters: Arc<dashmap::DashMap<String, AtomicI64>>,
+    /// Token buckets, updated under the map's per-key lock.
+    buckets: Arc<dashmap::DashMap<String, Bucket>>,
+}
+
+/// A token bucket's level when it was last touched.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
 }
 
 impl MemoryCacheProvider {
@@ -35,6 +45,7 @@ impl MemoryCacheProvider {
             cache,
             default_ttl: Duration::from_secs(default_ttl_seconds),
             counters: Arc::new(dashmap::DashMap::new()),
+            buckets: Arc::new(dashmap::DashMap::new()),
         }
     }
 }
@@ -59,6 +70,7 @@ impl CacheProvider for MemoryCacheProvider {
     async fn delete(&self, key: &str) -> AppResult<()> {
         self.cache.remove(key).await;
         self.counters.remove(key);
+        self.buckets.remove(key);
         Ok(())
     }
 
@@ -85,6 +97,7 @@ impl CacheProvider for MemoryCacheProvider {
             self.counters.remove(&key);
             count += 1;
         }
+        self.buckets.retain(|key, _| !key.starts_with(prefix));
 
         debug!(pattern, count, "Deleted keys matching pattern");
         Ok(count)
@@ -140,6 +153,32 @@ impl CacheProvider for MemoryCacheProvider {
         }
     }
 
+    async fn take_token(
+        &self,
+        key: &str,
+        capacity: u32,
+        refill: Duration,
+    ) -> AppResult<TokenBucket> {
+        let now = Instant::now();
+        let capacity = f64::from(capacity);
+        let per_second = capacity / refill.max(Duration::from_millis(1)).as_secs_f64();
+
+        let mut bucket = self.buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        let tokens = (bucket.tokens + elapsed * per_second).min(capacity);
+        let allowed = tokens >= 1.0;
+        bucket.tokens = if allowed { tokens - 1.0 } else { tokens };
+        bucket.updated = now;
+
+        Ok(TokenBucket {
+            allowed,
+            tokens: bucket.tokens,
+        })
+    }
+
     async fn health_check(&self) -> AppResult<bool> {
         Ok(true)
     }
@@ -147,6 +186,7 @@ impl CacheProvider for MemoryCacheProvider {
     async fn flush_all(&self) -> AppResult<()> {
         self.cache.invalidate_all();
         self.counters.clear();
+        self.buckets.clear();
         Ok(())
     }
 }
@@ -217,6 +257,49 @@ mod tests {
         assert_eq!(v3, 1);
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn test_take_token_refills() {
+        let provider = make_provider();
+        let refill = Duration::from_secs(10);
+        for left in [1.0, 0.0] {
+            let bucket = provider.take_token("bucket", 2, refill).await.unwrap();
+            assert!(bucket.allowed);
+            assert_eq!(bucket.tokens, left);
+        }
+        let empty = provider.take_token("bucket", 2, refill).await.unwrap();
+        assert!(!empty.allowed);
+
+        // One token back every 5 seconds, never more than the capacity.
+        tokio::time::advance(Duration::from_secs(5)).await;
+        let bucket = provider.take_token("bucket", 2, refill).await.unwrap();
+        assert!(bucket.allowed);
+        assert!(bucket.tokens < 0.01);
+        tokio::time::advance(Duration::from_secs(60)).await;
+        let bucket = provider.take_token("bucket", 2, refill).await.unwrap();
+        assert_eq!(bucket.tokens, 1.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_take_token_caps_bursts() {
+        let provider = make_provider();
+        let refill = Duration::from_secs(60);
+        let burst = || async {
+            let mut allowed = 0;
+            for _ in 0..20 {
+                allowed += u32::from(provider.take_token("b", 10, refill).await.unwrap().allowed);
+            }
+            allowed
+        };
+
+        assert_eq!(burst().await, 10);
+        // Unlike a fixed window rolling over, a second later only a
+        // second's worth of tokens is back.
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_eq!(burst().await, 0);
+        tokio::time::advance(Duration::from_secs(6)).await;
+        assert_eq!(burst().await, 1);
+    }
+
     #[tokio::test]
     async fn test_set_nx() {
         let provider = make_provider();
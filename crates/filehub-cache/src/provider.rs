@@ -1,23 +1,69 @@
 //! Cache manager that dispatches to the configured provider.
 
+use std::future::Future;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 
 use async_trait::async_trait;
-use tracing::info;
+use tracing::{Instrument, info, warn};
 
-use filehub_core::config::cache::CacheConfig;
-use filehub_core::error::AppError;
+use filehub_core::config::cache::{CacheConfig, CacheTtlConfig, CircuitBreakerConfig};
+use filehub_core::error::{AppError, ErrorKind};
 use filehub_core::result::AppResult;
-use filehub_core::traits::cache::CacheProvider;
+use filehub_core::telemetry;
+use filehub_core::traits::cache::{CacheProvider, TokenBucket};
+
+use crate::breaker::CircuitBreaker;
+use crate::keys::CacheKey;
 
 /// Cache manager that wraps the configured cache provider.
 ///
 /// The provider is selected at construction time based on configuration.
+///
+/// Calls go through a circuit breaker. While the backend is unavailable
+/// the cache runs degraded: lookups miss, writes and deletes are dropped,
+/// and counters start from scratch. Every degraded answer is counted in
+/// [`CacheStats::degraded`]. The cache is flushed when the backend
+/// recovers, since invalidations may have been missed meanwhile.
 #[derive(Debug, Clone)]
 pub struct CacheManager {
     /// The inner cache provider.
     inner: Arc<dyn CacheProvider>,
+    /// Hit/miss counters for `get` lookups.
+    stats: Arc<CacheStats>,
+    /// TTL in seconds for `set_default`, adjustable at runtime; `0` defers
+    /// to the provider's own default.
+    default_ttl_seconds: Arc<AtomicU64>,
+    /// Breaker around calls to the provider.
+    breaker: Arc<CircuitBreaker>,
+    /// Lifetime of each key category, for `put` and `put_nx`.
+    ttls: Arc<CacheTtlConfig>,
+}
+
+/// Lookup counters shared by all clones of a [`CacheManager`].
+#[derive(Debug, Default)]
+pub struct CacheStats {
+    /// Lookups that returned a value.
+    pub hits: AtomicU64,
+    /// Lookups that found nothing.
+    pub misses: AtomicU64,
+    /// Operations answered in degraded mode because the backend failed or
+    /// the circuit breaker was open.
+    pub degraded: AtomicU64,
+}
+
+impl CacheStats {
+    /// Fraction of lookups that were hits, or `0.0` before any lookup.
+    pub fn hit_ratio(&self) -> f64 {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let total = hits + self.misses.load(Ordering::Relaxed);
+        if total == 0 {
+            0.0
+        } else {
+            hits as f64 / total as f64
+        }
+    }
 }
 
 impl CacheManager {
@@ -48,67 +94,359 @@ impl CacheManager {
             }
         };
 
-        Ok(Self { inner })
+        Ok(Self {
+            inner,
+            stats: Arc::new(CacheStats::default()),
+            default_ttl_seconds: Arc::new(AtomicU64::new(config.default_ttl_seconds)),
+            breaker: Arc::new(CircuitBreaker::new("cache", &config.circuit_breaker)),
+            ttls: Arc::new(config.ttl.clone()),
+        })
     }
 
     /// Create a cache manager from an existing provider (for testing).
     pub fn from_provider(provider: Arc<dyn CacheProvider>) -> Self {
-        Self { inner: provider }
+        Self::from_provider_with_breaker(provider, &CircuitBreakerConfig::default())
+    }
+
+    /// Create a cache manager from an existing provider with the given
+    /// circuit breaker settings.
+    pub fn from_provider_with_breaker(
+        provider: Arc<dyn CacheProvider>,
+        breaker: &CircuitBreakerConfig,
+    ) -> Self {
+        Self {
+            inner: provider,
+            stats: Arc::new(CacheStats::default()),
+            default_ttl_seconds: Arc::new(AtomicU64::new(0)),
+            breaker: Arc::new(CircuitBreaker::new("cache", breaker)),
+            ttls: Arc::new(CacheTtlConfig::default()),
+        }
     }
 
     /// Get a reference to the inner provider.
     pub fn provider(&self) -> &dyn CacheProvider {
         self.inner.as_ref()
     }
+
+    /// Get the lookup hit/miss counters.
+    pub fn stats(&self) -> &CacheStats {
+        &self.stats
+    }
+
+    /// Get the circuit breaker guarding the provider.
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+
+    /// Runs `call` through the circuit breaker. A backend failure, or an
+    /// open breaker, yields `degraded` instead of an error; so does the
+    /// call that finds the backend recovered, as the cache is flushed.
+    /// `operation` names the call's span when traces are exported.
+    async fn guarded<T>(
+        &self,
+        operation: &'static str,
+        degraded: T,
+        call: impl Future<Output = AppResult<T>>,
+    ) -> AppResult<T> {
+        let span = tracing::info_span!(
+            target: telemetry::TARGET,
+            "cache",
+            otel.name = %format_args!("cache {operation}"),
+            otel.kind = "client",
+            cache.operation = operation,
+        );
+        async move {
+            if !self.breaker.allow() {
+                self.stats.degraded.fetch_add(1, Ordering::Relaxed);
+                return Ok(degraded);
+            }
+            match call.await {
+                Ok(value) => {
+                    if !self.breaker.record_success() {
+                        return Ok(value);
+                    }
+                    // Deletes dropped while degraded may have left stale
+                    // entries behind, including whatever this call read.
+                    if let Err(e) = self.inner.flush_all().await {
+                        warn!(error = %e, "Failed to flush cache after backend recovery");
+                    }
+                    Ok(degraded)
+                }
+                Err(e) if e.kind == ErrorKind::Cache => {
+                    warn!(error = %e, "Cache backend call failed, answering degraded");
+                    self.breaker.record_failure();
+                    self.stats.degraded.fetch_add(1, Ordering::Relaxed);
+                    Ok(degraded)
+                }
+                Err(e) => Err(e),
+            }
+        }
+        .instrument(span)
+        .await
+    }
+
+    /// How long an entry under `key` is kept.
+    pub fn ttl(&self, key: &CacheKey) -> Duration {
+        key.ttl(&self.ttls)
+    }
+
+    /// Stores `value` under `key` for the TTL of its category.
+    pub async fn put(&self, key: &CacheKey, value: &str) -> AppResult<()> {
+        self.set(key, value, self.ttl(key)).await
+    }
+
+    /// Stores `value` under `key` for the TTL of its category, unless the
+    /// key is already set. Returns whether it was stored.
+    pub async fn put_nx(&self, key: &CacheKey, value: &str) -> AppResult<bool> {
+        self.set_nx(key, value, self.ttl(key)).await
+    }
+
+    /// Change the TTL used by `set_default` on every clone of this manager.
+    ///
+    /// Entries already stored keep their original expiry.
+    pub fn set_default_ttl(&self, ttl: Duration) {
+        self.default_ttl_seconds
+            .store(ttl.as_secs(), Ordering::Relaxed);
+    }
 }
 
 #[async_trait]
 impl CacheProvider for CacheManager {
     async fn get(&self, key: &str) -> AppResult<Option<String>> {
-        self.inner.get(key).await
+        let value = self.guarded("get", None, self.inner.get(key)).await?;
+        let counter = if value.is_some() {
+            &self.stats.hits
+        } else {
+            &self.stats.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        Ok(value)
     }
 
     async fn set(&self, key: &str, value: &str, ttl: Duration) -> AppResult<()> {
-        self.inner.set(key, value, ttl).await
+        self.guarded("set", (), self.inner.set(key, value, ttl))
+            .await
     }
 
     async fn set_default(&self, key: &str, value: &str) -> AppResult<()> {
-        self.inner.set_default(key, value).await
+        match self.default_ttl_seconds.load(Ordering::Relaxed) {
+            0 => {
+                self.guarded("set_default", (), self.inner.set_default(key, value))
+                    .await
+            }
+            ttl => self.set(key, value, Duration::from_secs(ttl)).await,
+        }
     }
 
     async fn delete(&self, key: &str) -> AppResult<()> {
-        self.inner.delete(key).await
+        self.guarded("delete", (), self.inner.delete(key)).await
     }
 
     async fn exists(&self, key: &str) -> AppResult<bool> {
-        self.inner.exists(key).await
+        self.guarded("exists", false, self.inner.exists(key)).await
     }
 
     async fn delete_pattern(&self, pattern: &str) -> AppResult<u64> {
-        self.inner.delete_pattern(pattern).await
+        self.guarded("delete_pattern", 0, self.inner.delete_pattern(pattern))
+            .await
     }
 
     async fn set_nx(&self, key: &str, value: &str, ttl: Duration) -> AppResult<bool> {
-        self.inner.set_nx(key, value, ttl).await
+        self.guarded("set_nx", true, self.inner.set_nx(key, value, ttl))
+            .await
     }
 
     async fn incr(&self, key: &str) -> AppResult<i64> {
-        self.inner.incr(key).await
+        self.guarded("incr", 1, self.inner.incr(key)).await
     }
 
     async fn decr(&self, key: &str) -> AppResult<i64> {
-        self.inner.decr(key).await
+        self.guarded("decr", 0, self.inner.decr(key)).await
     }
 
     async fn expire(&self, key: &str, ttl: Duration) -> AppResult<bool> {
-        self.inner.expire(key, ttl).await
+        self.guarded("expire", false, self.inner.expire(key, ttl))
+            .await
+    }
+
+    async fn take_token(
+        &self,
+        key: &str,
+        capacity: u32,
+        refill: Duration,
+    ) -> AppResult<TokenBucket> {
+        // Degraded: every request is let through, as with `incr`.
+        let full = TokenBucket {
+            allowed: true,
+            tokens: f64::from(capacity.saturating_sub(1)),
+        };
+        self.guarded(
+            "take_token",
+            full,
+            self.inner.take_token(key, capacity, refill),
+        )
+        .await
     }
 
     async fn health_check(&self) -> AppResult<bool> {
-        self.inner.health_check().await
+        self.guarded("health_check", false, self.inner.health_check())
+            .await
     }
 
     async fn flush_all(&self) -> AppResult<()> {
-        self.inner.flush_all().await
+        self.guarded("flush_all", (), self.inner.flush_all()).await
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use super::*;
+    use crate::memory::MemoryCacheProvider;
+    use filehub_core::config::cache::MemoryCacheConfig;
+
+    /// Memory cache that fails every call like a lost connection while
+    /// `down` is set.
+    #[derive(Debug)]
+    struct FlakyProvider {
+        store: MemoryCacheProvider,
+        down: AtomicBool,
+    }
+
+    impl FlakyProvider {
+        fn check(&self) -> AppResult<()> {
+            if self.down.load(Ordering::Relaxed) {
+                Err(AppError::new(ErrorKind::Cache, "Connection refused"))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[async_trait]
+    impl CacheProvider for FlakyProvider {
+        async fn get(&self, key: &str) -> AppResult<Option<String>> {
+            self.check()?;
+            self.store.get(key).await
+        }
+        async fn set(&self, key: &str, value: &str, ttl: Duration) -> AppResult<()> {
+            self.check()?;
+            self.store.set(key, value, ttl).await
+        }
+        async fn set_default(&self, key: &str, value: &str) -> AppResult<()> {
+            self.check()?;
+            self.store.set_default(key, value).await
+        }
+        async fn delete(&self, key: &str) -> AppResult<()> {
+            self.check()?;
+            self.store.delete(key).await
+        }
+        async fn exists(&self, key: &str) -> AppResult<bool> {
+            self.check()?;
+            self.store.exists(key).await
+        }
+        async fn delete_pattern(&self, pattern: &str) -> AppResult<u64> {
+            self.check()?;
+            self.store.delete_pattern(pattern).await
+        }
+        async fn set_nx(&self, key: &str, value: &str, ttl: Duration) -> AppResult<bool> {
+            self.check()?;
+            self.store.set_nx(key, value, ttl).await
+        }
+        async fn incr(&self, key: &str) -> AppResult<i64> {
+            self.check()?;
+            self.store.incr(key).await
+        }
+        async fn decr(&self, key: &str) -> AppResult<i64> {
+            self.check()?;
+            self.store.decr(key).await
+        }
+        async fn expire(&self, key: &str, ttl: Duration) -> AppResult<bool> {
+            self.check()?;
+            self.store.expire(key, ttl).await
+        }
+        async fn take_token(
+            &self,
+            key: &str,
+            capacity: u32,
+            refill: Duration,
+        ) -> AppResult<TokenBucket> {
+            self.check()?;
+            self.store.take_token(key, capacity, refill).await
+        }
+        async fn health_check(&self) -> AppResult<bool> {
+            self.check()?;
+            Ok(true)
+        }
+        async fn flush_all(&self) -> AppResult<()> {
+            self.check()?;
+            self.store.flush_all().await
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_degrades_while_backend_down_and_recovers() {
+        let flaky = Arc::new(FlakyProvider {
+            store: MemoryCacheProvider::new(
+                &MemoryCacheConfig {
+                    max_capacity: 100,
+                    time_to_live_seconds: 60,
+                },
+                60,
+            ),
+            down: AtomicBool::new(false),
+        });
+        let cache = CacheManager::from_provider_with_breaker(
+            flaky.clone(),
+            &CircuitBreakerConfig {
+                failure_threshold: 2,
+                open_seconds: 5,
+            },
+        );
+        let ttl = Duration::from_secs(60);
+        cache.set("a", "1", ttl).await.unwrap();
+
+        flaky.down.store(true, Ordering::Relaxed);
+        assert_eq!(cache.get("a").await.unwrap(), None);
+        cache.set("b", "2", ttl).await.unwrap();
+        assert!(cache.circuit_breaker().is_open());
+        assert_eq!(cache.circuit_breaker().trips(), 1);
+
+        // Short-circuited without touching the backend.
+        assert_eq!(cache.incr("counter").await.unwrap(), 1);
+        assert_eq!(cache.circuit_breaker().short_circuited(), 1);
+        assert_eq!(cache.stats().degraded.load(Ordering::Relaxed), 3);
+
+        flaky.down.store(false, Ordering::Relaxed);
+        assert_eq!(cache.get("a").await.unwrap(), None, "still open");
+        tokio::time::advance(Duration::from_secs(5)).await;
+
+        // The probe succeeds, closes the breaker and flushes entries that
+        // may have missed an invalidation.
+        assert_eq!(cache.get("a").await.unwrap(), None);
+        assert!(!flaky.store.exists("a").await.unwrap());
+        assert!(!cache.circuit_breaker().is_open());
+        cache.set("a", "3", ttl).await.unwrap();
+        assert_eq!(cache.get("a").await.unwrap(), Some("3".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_put_uses_configured_category_ttl() {
+        let config: CacheConfig = serde_json::from_value(serde_json::json!({
+            "provider": "memory",
+            "ttl": { "thumbnail": 10 }
+        }))
+        .unwrap();
+        let cache = CacheManager::new(&config).await.unwrap();
+
+        let thumb = crate::keys::thumbnail(uuid::Uuid::nil(), 1, 256, "png");
+        assert_eq!(cache.ttl(&thumb), Duration::from_secs(10));
+        let user = crate::keys::user_by_id(uuid::Uuid::nil());
+        assert_eq!(cache.ttl(&user), Duration::from_secs(config.ttl.user));
+
+        cache.put(&thumb, "data").await.unwrap();
+        assert_eq!(cache.get(&thumb).await.unwrap(), Some("data".to_string()));
+        assert!(!cache.put_nx(&thumb, "other").await.unwrap());
     }
 }
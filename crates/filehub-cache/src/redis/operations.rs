@@ -1,17 +1,47 @@
 //! Redis cache provider implementation.
 
+use std::sync::LazyLock;
 use std::time::Duration;
 
 use async_trait::async_trait;
-use redis::AsyncCommands;
+use redis::{AsyncCommands, Script};
 use tracing::debug;
 
 use filehub_core::error::{AppError, ErrorKind};
 use filehub_core::result::AppResult;
-use filehub_core::traits::cache::CacheProvider;
+use filehub_core::traits::cache::{CacheProvider, TokenBucket};
 
 use super::client::RedisClient;
 
+/// Refills and takes from a token bucket stored as a hash of `tokens` and
+/// `ts` (milliseconds, Redis server time, so node clocks never matter).
+/// ARGV: capacity, refill duration in milliseconds.
+/// Returns `{allowed, tokens}`, tokens as a string to keep the fraction.
+static TAKE_TOKEN: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r"
+local capacity = tonumber(ARGV[1])
+local refill_ms = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+
+local state = redis.call('HMGET', KEYS[1], 'tokens', 'ts')
+local tokens = tonumber(state[1]) or capacity
+local ts = tonumber(state[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - ts) * capacity / refill_ms)
+
+local allowed = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+    allowed = 1
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'ts', now)
+redis.call('PEXPIRE', KEYS[1], refill_ms)
+return {allowed, tostring(tokens)}
+",
+    )
+});
+
 /// Redis-backed cache provider.
 #[derive(Debug, Clone)]
 pub struct RedisCacheProvider {
@@ -139,6 +169,30 @@ impl CacheProvider for RedisCacheProvider {
         Ok(result)
     }
 
+    async fn take_token(
+        &self,
+        key: &str,
+        capacity: u32,
+        refill: Duration,
+    ) -> AppResult<TokenBucket> {
+        let full_key = self.client.prefixed_key(key);
+        let mut conn = self.client.conn_mut();
+        // A missing bucket is full, so it may expire once it would be.
+        let refill_ms = refill.as_millis().clamp(1, u64::MAX as u128) as u64;
+        let (allowed, tokens): (i64, String) = TAKE_TOKEN
+            .key(&full_key)
+            .arg(capacity)
+            .arg(refill_ms)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(Self::map_err)?;
+
+        Ok(TokenBucket {
+            allowed: allowed == 1,
+            tokens: tokens.parse().unwrap_or(0.0),
+        })
+    }
+
     async fn health_check(&self) -> AppResult<bool> {
         let mut conn = self.client.conn_mut();
         let pong: String = redis::cmd("PING")
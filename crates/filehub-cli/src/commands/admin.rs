@@ -55,7 +55,8 @@ pub async fn execute(
     let config = super::load_config(config_path).await?;
     let pool: PgPool = super::create_db_pool(&config).await?;
     let user_repo = UserRepository::new(pool.clone());
-    let hasher = PasswordHasher::new();
+    let hasher = PasswordHasher::from_config(&config.auth.password_hash)?
+        .with_pepper(&config.auth.password_pepper)?;
 
     match &args.command {
         AdminCommand::Create {
@@ -1,13 +1,19 @@
 //! Audit log CLI commands.
 
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
 use clap::{Args, Subcommand};
+use futures::StreamExt;
 use serde::Serialize;
 use tabled::Tabled;
 
 use crate::output::{self, OutputFormat};
 use filehub_core::error::AppError;
 use filehub_core::types::pagination::PageRequest;
-use filehub_database::repositories::audit::AuditLogRepository;
+use filehub_database::repositories::audit::{AuditFilter, AuditLogRepository};
+use filehub_entity::audit::AuditDiff;
+use filehub_service::session::AuditExportFormat;
 
 /// Arguments for audit commands
 #[derive(Debug, Args)]
@@ -32,14 +38,32 @@ pub enum AuditCommand {
         #[arg(short, long, default_value = "50")]
         limit: i64,
     },
-    /// Export audit log to JSON file
+    /// Export audit log as CSV (or NDJSON with `--format json`)
     Export {
-        /// Output file path
-        #[arg(short, long, default_value = "audit_export.json")]
+        /// Output file path, `-` for stdout
+        #[arg(short, long, default_value = "-")]
         output: String,
-        /// Days of history to export
+        /// Days of history to export, ignored when --since is given
         #[arg(short, long, default_value = "30")]
         days: i64,
+        /// Only entries at or after this time (RFC 3339)
+        #[arg(long)]
+        since: Option<String>,
+        /// Only entries before this time (RFC 3339)
+        #[arg(long)]
+        until: Option<String>,
+        /// Filter by actor (user ID)
+        #[arg(long)]
+        actor: Option<String>,
+        /// Filter by action
+        #[arg(short, long)]
+        action: Option<String>,
+        /// Filter by target type
+        #[arg(long)]
+        target_type: Option<String>,
+        /// Filter by target ID
+        #[arg(long)]
+        target_id: Option<String>,
     },
 }
 
@@ -56,6 +80,8 @@ struct AuditRow {
     target_type: String,
     /// IP
     ip: String,
+    /// Changed fields
+    changes: String,
 }
 
 /// Execute audit commands
@@ -94,7 +120,10 @@ pub async fn execute(
                 .iter()
                 .map(|e| AuditRow {
                     time: e.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
-                    actor: e.actor_id.to_string()[..8].to_string(),
+                    actor: e.actor_id.map_or_else(
+                        || "anonymous".to_string(),
+                        |id| id.to_string()[..8].to_string(),
+                    ),
                     action: e.action.clone(),
                     target_type: e.target_type.clone(),
                     ip: e
@@ -102,6 +131,12 @@ pub async fn execute(
                         .as_ref()
                         .map(|i| i.to_string())
                         .unwrap_or_default(),
+                    changes: e
+                        .details
+                        .as_ref()
+                        .and_then(AuditDiff::from_details)
+                        .map(|diff| diff.summary().join("; "))
+                        .unwrap_or_default(),
                 })
                 .collect();
 
@@ -110,27 +145,72 @@ pub async fn execute(
         AuditCommand::Export {
             output: out_path,
             days,
+            since,
+            until,
+            actor,
+            action,
+            target_type,
+            target_id,
         } => {
-            let since = chrono::Utc::now() - chrono::Duration::days(*days);
-            let entries = audit_repo
-                .find_since(since)
-                .await
-                .map_err(|e| AppError::internal(format!("Failed to export audit: {}", e)))?;
+            let since = match since {
+                Some(since) => parse_time(since)?,
+                None => chrono::Utc::now() - chrono::Duration::days(*days),
+            };
+            let filter = AuditFilter {
+                actor_id: actor.as_deref().map(parse_uuid).transpose()?,
+                action: action.clone(),
+                target_type: target_type.clone(),
+                target_id: target_id.as_deref().map(parse_uuid).transpose()?,
+                since: Some(since),
+                until: until.as_deref().map(parse_time).transpose()?,
+            };
+            let export_format = match format {
+                OutputFormat::Json => AuditExportFormat::Ndjson,
+                OutputFormat::Table | OutputFormat::Csv => AuditExportFormat::Csv,
+            };
 
-            let json = serde_json::to_string_pretty(&entries)
-                .map_err(|e| AppError::internal(format!("Serialization error: {}", e)))?;
+            let out: Box<dyn Write> = if out_path == "-" {
+                Box::new(io::stdout().lock())
+            } else {
+                let file = File::create(out_path).map_err(|e| {
+                    AppError::internal(format!("Failed to create '{}': {}", out_path, e))
+                })?;
+                Box::new(file)
+            };
+            let mut out = BufWriter::new(out);
+            let write_error = |e: io::Error| AppError::internal(format!("Failed to write: {}", e));
 
-            tokio::fs::write(out_path, json)
-                .await
-                .map_err(|e| AppError::internal(format!("Failed to write file: {}", e)))?;
+            out.write_all(export_format.header().as_bytes())
+                .map_err(write_error)?;
+            let mut entries = std::pin::pin!(audit_repo.export(filter));
+            let mut count = 0u64;
+            while let Some(entry) = entries.next().await {
+                let entry = entry
+                    .map_err(|e| AppError::internal(format!("Failed to export audit: {}", e)))?;
+                out.write_all(export_format.encode(&entry)?.as_bytes())
+                    .map_err(write_error)?;
+                count += 1;
+            }
+            out.flush().map_err(write_error)?;
 
-            output::print_success(&format!(
-                "Exported {} audit entries to '{}'",
-                entries.len(),
-                out_path
-            ));
+            if out_path != "-" {
+                output::print_success(&format!(
+                    "Exported {} audit entries to '{}'",
+                    count, out_path
+                ));
+            }
         }
     }
 
     Ok(())
 }
+
+fn parse_uuid(value: &str) -> Result<uuid::Uuid, AppError> {
+    uuid::Uuid::parse_str(value).map_err(|e| AppError::bad_request(format!("Invalid UUID: {}", e)))
+}
+
+fn parse_time(value: &str) -> Result<chrono::DateTime<chrono::Utc>, AppError> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&chrono::Utc))
+        .map_err(|e| AppError::bad_request(format!("Invalid time '{}': {}", value, e)))
+}
@@ -0,0 +1,416 @@
+//! Backup and restore CLI commands.
+//!
+//! A backup is a single ZIP archive:
+//!
+//! - `tables/<name>.copy` — each application table in `COPY` binary format
+//! - `blobs/<storage_id>/<storage_path>` — stored objects of local storages
+//! - `manifest.json` — schema version, table order and the blob index
+//!
+//! An incremental backup still contains every table but only the blobs that
+//! changed since the backup it is based on; the manifest records which
+//! archive holds each unchanged blob, so restoring it needs that archive
+//! (and its own bases) passed with `--base`.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use chrono::{DateTime, Utc};
+use clap::{Args, Subcommand};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::output;
+use filehub_core::config::AppConfig;
+use filehub_core::error::AppError;
+use filehub_database::backup::{self, TableImport};
+use filehub_database::migration;
+use filehub_database::repositories::storage::StorageRepository;
+use filehub_entity::storage::StorageProviderType;
+
+/// Version of the archive layout written by this build.
+const FORMAT_VERSION: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+
+/// Arguments for backup commands
+#[derive(Debug, Args)]
+pub struct BackupArgs {
+    /// Backup subcommand
+    #[command(subcommand)]
+    pub command: BackupCommand,
+}
+
+/// Backup subcommands
+#[derive(Debug, Subcommand)]
+pub enum BackupCommand {
+    /// Write the database and stored files to an archive
+    Create {
+        /// Archive to create
+        #[arg(short, long)]
+        out: PathBuf,
+        /// Only copy files changed since this earlier backup
+        #[arg(long, value_name = "PRIOR_ARCHIVE")]
+        incremental: Option<PathBuf>,
+    },
+    /// Restore an archive into this instance
+    Restore {
+        /// Archive to restore
+        path: PathBuf,
+        /// Earlier archives an incremental backup is based on
+        #[arg(long = "base")]
+        bases: Vec<PathBuf>,
+        /// Replace existing data instead of requiring an empty database
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// Contents of `manifest.json`
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    format_version: u32,
+    backup_id: Uuid,
+    created_at: DateTime<Utc>,
+    /// Newest applied migration of the source database
+    schema_version: i64,
+    /// Backup this one is incremental to
+    base_backup_id: Option<Uuid>,
+    /// Tables in restore order
+    tables: Vec<TableEntry>,
+    blobs: Vec<BlobEntry>,
+    /// Referenced objects not included (missing or on non-local storage)
+    skipped_blobs: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TableEntry {
+    name: String,
+    bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BlobEntry {
+    storage_id: Uuid,
+    path: String,
+    size_bytes: u64,
+    /// File modification time, seconds since the epoch
+    modified: Option<u64>,
+    checksum_sha256: Option<String>,
+    /// Archive containing the data
+    backup_id: Uuid,
+}
+
+impl BlobEntry {
+    fn entry_name(&self) -> String {
+        blob_entry_name(self.storage_id, &self.path)
+    }
+
+    fn unchanged_since(&self, prior: &BlobEntry) -> bool {
+        self.size_bytes == prior.size_bytes
+            && self.modified.is_some()
+            && self.modified == prior.modified
+            && self.checksum_sha256 == prior.checksum_sha256
+    }
+}
+
+/// Execute backup commands
+pub async fn execute(args: &BackupArgs, config_path: &str) -> Result<(), AppError> {
+    let config = super::load_config(config_path).await?;
+
+    match &args.command {
+        BackupCommand::Create { out, incremental } => {
+            create(&config, out, incremental.as_deref()).await
+        }
+        BackupCommand::Restore { path, bases, force } => {
+            restore(&config, path, bases, *force).await
+        }
+    }
+}
+
+async fn create(config: &AppConfig, out: &Path, prior: Option<&Path>) -> Result<(), AppError> {
+    if out.exists() {
+        return Err(AppError::conflict(format!(
+            "'{}' already exists",
+            out.display()
+        )));
+    }
+    let prior = prior.map(|p| open_archive(p).map(|(_, m)| m)).transpose()?;
+
+    let pool = super::create_db_pool(config).await?;
+    let schema_version = migration::schema_version(&pool)
+        .await?
+        .ok_or_else(|| AppError::validation("Database has no migrations applied"))?;
+
+    // Write next to the target and rename at the end, so an interrupted
+    // backup never leaves a valid-looking archive behind.
+    let partial = out.with_extension("partial");
+    let file = File::create(&partial).map_err(|e| io_error("create", &partial, e))?;
+    let mut zip = ZipWriter::new(io::BufWriter::new(file));
+
+    let mut manifest = Manifest {
+        format_version: FORMAT_VERSION,
+        backup_id: Uuid::new_v4(),
+        created_at: Utc::now(),
+        schema_version,
+        base_backup_id: prior.as_ref().map(|m| m.backup_id),
+        tables: Vec::new(),
+        blobs: Vec::new(),
+        skipped_blobs: Vec::new(),
+    };
+
+    let table_options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+    for table in backup::backup_tables(&pool).await? {
+        zip.start_file(table_entry_name(&table), table_options)
+            .map_err(zip_error)?;
+        let bytes = backup::export_table(&pool, &table, &mut zip).await?;
+        println!("  table {:<32} {:>12} bytes", table, bytes);
+        manifest.tables.push(TableEntry { name: table, bytes });
+    }
+
+    let prior_blobs: HashMap<String, &BlobEntry> = prior
+        .iter()
+        .flat_map(|m| m.blobs.iter())
+        .map(|b| (b.entry_name(), b))
+        .collect();
+    let roots = local_roots(config, &pool).await?;
+    let (mut copied, mut reused) = (0usize, 0usize);
+
+    for blob in backup::referenced_blobs(&pool).await? {
+        let name = blob_entry_name(blob.storage_id, &blob.storage_path);
+        let Some(source) = roots
+            .get(&blob.storage_id)
+            .and_then(|root| safe_join(root, &blob.storage_path))
+        else {
+            manifest.skipped_blobs.push(name);
+            continue;
+        };
+        let Ok(metadata) = fs::metadata(&source) else {
+            output::print_warning(&format!("Missing stored file {}", source.display()));
+            manifest.skipped_blobs.push(name);
+            continue;
+        };
+
+        let mut entry = BlobEntry {
+            storage_id: blob.storage_id,
+            path: blob.storage_path,
+            size_bytes: metadata.len(),
+            modified: metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs()),
+            checksum_sha256: blob.checksum_sha256,
+            backup_id: manifest.backup_id,
+        };
+
+        if let Some(prior) = prior_blobs.get(&name).filter(|p| entry.unchanged_since(p)) {
+            entry.backup_id = prior.backup_id;
+            reused += 1;
+        } else {
+            let options = SimpleFileOptions::default()
+                .compression_method(CompressionMethod::Stored)
+                .large_file(entry.size_bytes >= u64::from(u32::MAX));
+            zip.start_file(&name, options).map_err(zip_error)?;
+            let mut reader = File::open(&source).map_err(|e| io_error("open", &source, e))?;
+            io::copy(&mut reader, &mut zip).map_err(|e| io_error("read", &source, e))?;
+            copied += 1;
+        }
+        manifest.blobs.push(entry);
+    }
+
+    zip.start_file(MANIFEST, SimpleFileOptions::default())
+        .map_err(zip_error)?;
+    serde_json::to_writer_pretty(&mut zip, &manifest)
+        .map_err(|e| AppError::internal(format!("Failed to write manifest: {}", e)))?;
+    zip.finish()
+        .map_err(zip_error)?
+        .flush()
+        .map_err(|e| io_error("write", &partial, e))?;
+    fs::rename(&partial, out).map_err(|e| io_error("rename", &partial, e))?;
+
+    output::print_success(&format!(
+        "Backup {} written to {}",
+        manifest.backup_id,
+        out.display()
+    ));
+    output::print_kv("Schema version", &schema_version.to_string());
+    output::print_kv("Tables", &manifest.tables.len().to_string());
+    output::print_kv("Files copied", &copied.to_string());
+    if manifest.base_backup_id.is_some() {
+        output::print_kv("Files unchanged", &reused.to_string());
+    }
+    if !manifest.skipped_blobs.is_empty() {
+        output::print_warning(&format!(
+            "{} stored file(s) not included (missing or not on local storage)",
+            manifest.skipped_blobs.len()
+        ));
+    }
+    Ok(())
+}
+
+async fn restore(
+    config: &AppConfig,
+    path: &Path,
+    bases: &[PathBuf],
+    force: bool,
+) -> Result<(), AppError> {
+    let (mut archive, manifest) = open_archive(path)?;
+
+    // Every archive holding blobs of this backup, by backup id.
+    let mut archives: HashMap<Uuid, ZipArchive<File>> = HashMap::new();
+    for base in bases {
+        let (base_archive, base_manifest) = open_archive(base)?;
+        archives.insert(base_manifest.backup_id, base_archive);
+    }
+    let mut missing: Vec<Uuid> = manifest
+        .blobs
+        .iter()
+        .map(|b| b.backup_id)
+        .filter(|id| *id != manifest.backup_id && !archives.contains_key(id))
+        .collect();
+    missing.sort();
+    missing.dedup();
+    if !missing.is_empty() {
+        let ids: Vec<String> = missing.iter().map(Uuid::to_string).collect();
+        return Err(AppError::validation(format!(
+            "Incremental backup needs its base archive(s); pass them with --base: {}",
+            ids.join(", ")
+        )));
+    }
+
+    let pool = super::create_db_pool(config).await?;
+    let target_version = migration::schema_version(&pool).await?;
+    if target_version != Some(manifest.schema_version) {
+        return Err(AppError::validation(format!(
+            "Backup is at schema version {} but this database is at {}; \
+             migrate the target to the same version first",
+            manifest.schema_version,
+            target_version.map_or("none".to_string(), |v| v.to_string())
+        )));
+    }
+
+    let tables: Vec<String> = manifest.tables.iter().map(|t| t.name.clone()).collect();
+    let non_empty = backup::non_empty_tables(&pool, &tables).await?;
+    if !non_empty.is_empty() && !force {
+        return Err(AppError::conflict(format!(
+            "Target database is not empty ({}); use --force to replace its data",
+            non_empty.join(", ")
+        )));
+    }
+
+    let truncate = if force { tables } else { Vec::new() };
+    let mut import = TableImport::begin(&pool, &truncate).await?;
+    for table in &manifest.tables {
+        let mut entry = archive
+            .by_name(&table_entry_name(&table.name))
+            .map_err(zip_error)?;
+        let rows = import.import_table(&table.name, &mut entry).await?;
+        println!("  table {:<32} {:>12} rows", table.name, rows);
+    }
+    import.commit().await?;
+
+    let roots = local_roots(config, &pool).await?;
+    archives.insert(manifest.backup_id, archive);
+    let mut written = 0usize;
+    for blob in &manifest.blobs {
+        let Some(dest) = roots
+            .get(&blob.storage_id)
+            .and_then(|root| safe_join(root, &blob.path))
+        else {
+            output::print_warning(&format!("Skipping {}: no local storage", blob.path));
+            continue;
+        };
+        let Some(source) = archives.get_mut(&blob.backup_id) else {
+            continue;
+        };
+        let mut entry = source.by_name(&blob.entry_name()).map_err(zip_error)?;
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent).map_err(|e| io_error("create", parent, e))?;
+        }
+        let mut out = File::create(&dest).map_err(|e| io_error("create", &dest, e))?;
+        io::copy(&mut entry, &mut out).map_err(|e| io_error("write", &dest, e))?;
+        written += 1;
+    }
+
+    output::print_success(&format!(
+        "Restored backup {} from {}",
+        manifest.backup_id,
+        manifest.created_at.to_rfc3339()
+    ));
+    output::print_kv("Tables", &manifest.tables.len().to_string());
+    output::print_kv("Files", &written.to_string());
+    Ok(())
+}
+
+/// Open an archive and read its manifest.
+fn open_archive(path: &Path) -> Result<(ZipArchive<File>, Manifest), AppError> {
+    let file = File::open(path).map_err(|e| io_error("open", path, e))?;
+    let mut archive = ZipArchive::new(file).map_err(zip_error)?;
+    let manifest: Manifest = {
+        let entry = archive.by_name(MANIFEST).map_err(|_| {
+            AppError::validation(format!("'{}' is not a FileHub backup", path.display()))
+        })?;
+        serde_json::from_reader(entry)
+            .map_err(|e| AppError::validation(format!("Invalid backup manifest: {}", e)))?
+    };
+    if manifest.format_version != FORMAT_VERSION {
+        return Err(AppError::validation(format!(
+            "Unsupported backup format {} (expected {})",
+            manifest.format_version, FORMAT_VERSION
+        )));
+    }
+    Ok((archive, manifest))
+}
+
+/// Root directory of every local storage.
+async fn local_roots(
+    config: &AppConfig,
+    pool: &sqlx::PgPool,
+) -> Result<HashMap<Uuid, PathBuf>, AppError> {
+    let storages = StorageRepository::new(pool.clone()).find_all().await?;
+    Ok(storages
+        .into_iter()
+        .filter(|s| s.provider_type == StorageProviderType::Local)
+        .map(|s| {
+            let root = s
+                .config
+                .get("root_path")
+                .and_then(|v| v.as_str())
+                .unwrap_or(&config.storage.local.root_path);
+            (s.id, PathBuf::from(root))
+        })
+        .collect())
+}
+
+/// Join a provider-relative path onto `root`, rejecting anything that could
+/// escape it.
+fn safe_join(root: &Path, relative: &str) -> Option<PathBuf> {
+    let relative = Path::new(relative.trim_start_matches('/'));
+    relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+        .then(|| root.join(relative))
+}
+
+fn table_entry_name(table: &str) -> String {
+    format!("tables/{}.copy", table)
+}
+
+fn blob_entry_name(storage_id: Uuid, path: &str) -> String {
+    format!("blobs/{}/{}", storage_id, path.trim_start_matches('/'))
+}
+
+fn zip_error(e: zip::result::ZipError) -> AppError {
+    AppError::internal(format!("Backup archive error: {}", e))
+}
+
+fn io_error(action: &str, path: &Path, e: io::Error) -> AppError {
+    AppError::internal(format!("Failed to {} '{}': {}", action, path.display(), e))
+}
@@ -53,6 +53,7 @@ pub async fn execute(args: &BroadcastArgs, config_path: &str) -> Result<(), AppE
                 id: uuid::Uuid::new_v4(),
                 admin_id: uuid::Uuid::nil(), // TODO: Get actual admin ID
                 target: "all".to_string(),
+                target_user_ids: None,
                 title: title.clone(),
                 message: message.clone(),
                 severity: severity.clone(),
@@ -60,6 +61,10 @@ pub async fn execute(args: &BroadcastArgs, config_path: &str) -> Result<(), AppE
                 action_type: None,
                 action_payload: None,
                 delivered_count: 0,
+                require_ack: false,
+                scheduled_at: None,
+                sent_at: None,
+                expires_at: None,
                 created_at: chrono::Utc::now(),
             };
 
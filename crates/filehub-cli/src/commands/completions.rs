@@ -0,0 +1,35 @@
+//! Shell completion scripts.
+//!
+//! Scripts are generated from the [`Cli`] definition, so they always match
+//! the installed binary. Install one-liners are in the command's help.
+
+use clap::{Args, CommandFactory};
+use clap_complete::Shell;
+
+use filehub_core::error::AppError;
+
+use super::Cli;
+
+/// Arguments for the completions command
+#[derive(Debug, Args)]
+pub struct CompletionsArgs {
+    /// Shell to generate the script for
+    #[arg(value_enum)]
+    pub shell: Shell,
+
+    /// Name of the installed binary the script completes
+    #[arg(long, default_value = "filehub-cli")]
+    pub bin_name: String,
+}
+
+/// Execute the completions command
+pub async fn execute(args: &CompletionsArgs) -> Result<(), AppError> {
+    let mut command = Cli::command();
+    clap_complete::generate(
+        args.shell,
+        &mut command,
+        &args.bin_name,
+        &mut std::io::stdout(),
+    );
+    Ok(())
+}
@@ -0,0 +1,345 @@
+//! Environment health check.
+//!
+//! `filehub doctor` runs every check a server needs to start cleanly and
+//! prints one row per check. Any `fail` makes the command exit non-zero so
+//! it can gate a deployment.
+
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bytes::Bytes;
+use clap::Args;
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::output::{self, OutputFormat};
+use filehub_core::config::AppConfig;
+use filehub_core::error::AppError;
+use filehub_core::traits::{CacheProvider, StorageProvider};
+use filehub_database::DatabasePool;
+use filehub_storage::providers::local::LocalStorageProvider;
+use plugin_cad_converter::config::ConversionConfig;
+use plugin_flexnet::ffi::LicenseManagerWrapper;
+
+/// Arguments for the doctor command
+#[derive(Debug, Args)]
+pub struct DoctorArgs {
+    /// Timeout for each check, in seconds
+    #[arg(long, default_value = "10")]
+    pub timeout: u64,
+}
+
+/// Result of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum CheckStatus {
+    /// Check passed
+    Pass,
+    /// Degraded but the server can start
+    Warn,
+    /// The server will not work
+    Fail,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Pass => "pass",
+            Self::Warn => "warn",
+            Self::Fail => "fail",
+        })
+    }
+}
+
+/// Check display row for table output
+#[derive(Debug, Serialize, Tabled)]
+struct CheckRow {
+    /// Check name
+    check: &'static str,
+    /// Outcome
+    status: CheckStatus,
+    /// What was found
+    detail: String,
+    /// How to fix a warning or failure
+    hint: String,
+}
+
+/// Outcome of a check before it is labelled
+struct Outcome {
+    status: CheckStatus,
+    detail: String,
+    hint: &'static str,
+}
+
+impl Outcome {
+    fn pass(detail: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            hint: "",
+        }
+    }
+
+    fn warn(detail: impl Into<String>, hint: &'static str) -> Self {
+        Self {
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            hint,
+        }
+    }
+
+    fn fail(detail: impl Into<String>, hint: &'static str) -> Self {
+        Self {
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            hint,
+        }
+    }
+}
+
+/// Execute the doctor command
+pub async fn execute(
+    args: &DoctorArgs,
+    config_path: &str,
+    format: OutputFormat,
+) -> Result<(), AppError> {
+    let timeout = Duration::from_secs(args.timeout.max(1));
+    let mut rows = Vec::new();
+
+    let config = match super::load_config(config_path).await {
+        Ok(config) => {
+            rows.push(row("config", Outcome::pass(config_path)));
+            config
+        }
+        Err(e) => {
+            rows.push(row(
+                "config",
+                Outcome::fail(
+                    e.to_string(),
+                    "Fix the listed settings (see `filehub-cli config validate`)",
+                ),
+            ));
+            return finish(rows, format);
+        }
+    };
+
+    rows.extend(check_database(&config, timeout).await);
+    rows.push(run("cache", timeout, check_cache(&config)).await);
+    rows.push(run("storage", timeout, check_storage(&config)).await);
+    rows.push(run("cad_converter", timeout, check_jupiter(&config)).await);
+    rows.push(run("license", timeout, check_license(&config)).await);
+
+    finish(rows, format)
+}
+
+/// Print the results and fail if any check failed.
+fn finish(rows: Vec<CheckRow>, format: OutputFormat) -> Result<(), AppError> {
+    output::print_list(&rows, format);
+
+    let failed = rows
+        .iter()
+        .filter(|r| r.status == CheckStatus::Fail)
+        .count();
+    if failed > 0 {
+        return Err(AppError::service_unavailable(format!(
+            "{} check(s) failed",
+            failed
+        )));
+    }
+    Ok(())
+}
+
+fn row(check: &'static str, outcome: Outcome) -> CheckRow {
+    CheckRow {
+        check,
+        status: outcome.status,
+        detail: outcome.detail,
+        hint: outcome.hint.to_string(),
+    }
+}
+
+/// Run a check, failing it if it does not finish within `timeout`.
+async fn run(
+    check: &'static str,
+    timeout: Duration,
+    future: impl Future<Output = Outcome>,
+) -> CheckRow {
+    let outcome = tokio::time::timeout(timeout, future)
+        .await
+        .unwrap_or_else(|_| {
+            Outcome::fail(
+                format!("No answer within {}s", timeout.as_secs()),
+                "Check network access to the service or raise --timeout",
+            )
+        });
+    row(check, outcome)
+}
+
+/// Connectivity, then pending migrations. One attempt, no warmup: the
+/// check reports the database as it is now.
+async fn check_database(config: &AppConfig, timeout: Duration) -> Vec<CheckRow> {
+    let database = filehub_core::config::DatabaseConfig {
+        connect_attempts: 1,
+        warmup: false,
+        ..config.database.clone()
+    };
+    let connected = tokio::time::timeout(timeout, DatabasePool::connect(&database)).await;
+    let database = match connected {
+        Ok(Ok(database)) => database,
+        Ok(Err(e)) => {
+            return vec![row(
+                "database",
+                Outcome::fail(
+                    e.to_string(),
+                    "Check database.url and that PostgreSQL is running",
+                ),
+            )];
+        }
+        Err(_) => {
+            return vec![row(
+                "database",
+                Outcome::fail(
+                    format!("No connection within {}s", timeout.as_secs()),
+                    "Check database.url and network access to PostgreSQL",
+                ),
+            )];
+        }
+    };
+
+    let mut rows = vec![row("database", Outcome::pass("Connected"))];
+    rows.push(
+        run("migrations", timeout, async {
+            match filehub_database::migration::pending_migrations(database.pool()).await {
+                Ok(pending) if pending.is_empty() => Outcome::pass("Up to date"),
+                Ok(pending) => Outcome::warn(
+                    format!("{} pending", pending.len()),
+                    "Run `filehub-cli migrate run`",
+                ),
+                Err(e) => Outcome::fail(
+                    e.to_string(),
+                    "Check that the database user can read _sqlx_migrations",
+                ),
+            }
+        })
+        .await,
+    );
+    database.close().await;
+    rows
+}
+
+async fn check_cache(config: &AppConfig) -> Outcome {
+    const HINT: &str = "Check cache.provider and that the cache server is reachable";
+
+    let cache = match filehub_cache::provider::CacheManager::new(&config.cache).await {
+        Ok(cache) => cache,
+        Err(e) => return Outcome::fail(e.to_string(), HINT),
+    };
+    match cache.health_check().await {
+        Ok(true) => Outcome::pass(format!("{} reachable", config.cache.provider)),
+        Ok(false) => Outcome::fail(format!("{} unhealthy", config.cache.provider), HINT),
+        Err(e) => Outcome::fail(e.to_string(), HINT),
+    }
+}
+
+/// Write and remove a probe object on the default storage provider.
+async fn check_storage(config: &AppConfig) -> Outcome {
+    let storage = &config.storage;
+    let provider: Box<dyn StorageProvider> = match storage.default_provider.as_str() {
+        "local" => match LocalStorageProvider::new(&storage.local.root_path).await {
+            Ok(provider) => Box::new(provider),
+            Err(e) => {
+                return Outcome::fail(
+                    e.to_string(),
+                    "Create storage.local.root_path or fix its permissions",
+                );
+            }
+        },
+        other => {
+            return Outcome::fail(
+                format!("Provider '{}' is not available in this build", other),
+                "Set storage.default_provider to 'local'",
+            );
+        }
+    };
+
+    let probe = format!(".doctor-{}", uuid::Uuid::new_v4());
+    if let Err(e) = provider
+        .write(&probe, Bytes::from_static(b"filehub doctor"))
+        .await
+    {
+        return Outcome::fail(
+            e.to_string(),
+            "Grant the server write access to the storage location",
+        );
+    }
+    if let Err(e) = provider.delete(&probe).await {
+        return Outcome::warn(
+            format!("Probe '{}' written but not removed: {}", probe, e),
+            "Grant delete permission on the storage location",
+        );
+    }
+    Outcome::pass(format!("{} writable", provider.provider_type()))
+}
+
+/// Jupiter-Web discovery for the CAD converter plugin.
+async fn check_jupiter(config: &AppConfig) -> Outcome {
+    if !config.storage.conversions.enabled {
+        return Outcome::pass("Conversions disabled");
+    }
+
+    let resolved = tokio::task::spawn_blocking(|| {
+        let mut conversion = ConversionConfig::default();
+        conversion.resolve_jupiter_path()
+    })
+    .await;
+    match resolved {
+        Ok(Ok(path)) => Outcome::pass(format!("Jupiter-Web at {}", path.display())),
+        // The plugin still loads without Jupiter; conversions fail later.
+        Ok(Err(e)) => Outcome::warn(
+            e.to_string(),
+            "Install Jupiter-Web or set jupiter_path in the CAD converter config",
+        ),
+        Err(e) => Outcome::fail(e.to_string(), "Re-run with RUST_LOG=debug for details"),
+    }
+}
+
+/// License server reachability through the FlexNet proxy.
+async fn check_license(config: &AppConfig) -> Outcome {
+    const HINT: &str = "Check license.license_file and that the license server is reachable";
+
+    if !config.license.enabled {
+        return Outcome::pass("Licensing disabled");
+    }
+
+    let license = config.license.clone();
+    let probed = tokio::task::spawn_blocking(move || {
+        let dll_path =
+            (!license.license_file.is_empty()).then(|| PathBuf::from("license_proxy.dll"));
+        let wrapper = LicenseManagerWrapper::create(dll_path)?;
+        let override_path =
+            (!license.license_file.is_empty()).then_some(license.license_file.as_str());
+        wrapper.initialize(override_path)?;
+
+        let mut pools = Vec::new();
+        for feature in license.feature_names() {
+            let (total, used) = wrapper.get_token_pool(feature)?;
+            pools.push(format!("{} {}/{}", feature, used, total));
+        }
+        Ok::<_, anyhow::Error>((wrapper.is_mock(), wrapper.get_server_info(), pools))
+    })
+    .await;
+
+    match probed {
+        Ok(Ok((true, _, _))) => Outcome::warn(
+            "license_proxy.dll not found, mock license manager in use",
+            "Install license_proxy.dll next to the server binary",
+        ),
+        Ok(Ok((false, server, pools))) => {
+            Outcome::pass(format!("{} ({})", server, pools.join(", ")))
+        }
+        Ok(Err(e)) => Outcome::fail(e.to_string(), HINT),
+        Err(e) => Outcome::fail(e.to_string(), HINT),
+    }
+}
@@ -0,0 +1,126 @@
+//! File inventory CLI commands.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use clap::{Args, Subcommand};
+use futures::StreamExt;
+
+use crate::output::{self, OutputFormat};
+use filehub_core::error::AppError;
+use filehub_database::repositories::file::FileRepository;
+use filehub_service::file::FileExportFormat;
+use filehub_service::file::search::FileExportRequest;
+
+/// Arguments for file commands
+#[derive(Debug, Args)]
+pub struct FileArgs {
+    /// File subcommand
+    #[command(subcommand)]
+    pub command: FileCommand,
+}
+
+/// File subcommands
+#[derive(Debug, Subcommand)]
+pub enum FileCommand {
+    /// Export the file inventory as CSV (or NDJSON with `--format json`)
+    Export {
+        /// Output file path, `-` for stdout
+        #[arg(short, long, default_value = "-")]
+        output: String,
+        /// Full-text query
+        #[arg(short, long)]
+        query: Option<String>,
+        /// Filter by folder ID
+        #[arg(long)]
+        folder_id: Option<String>,
+        /// Filter by storage ID
+        #[arg(long)]
+        storage_id: Option<String>,
+        /// Filter by owner (user ID)
+        #[arg(long)]
+        owner_id: Option<String>,
+        /// Filter by MIME type prefix (e.g., "image/")
+        #[arg(long)]
+        mime_type: Option<String>,
+        /// Minimum file size in bytes
+        #[arg(long)]
+        min_size: Option<i64>,
+        /// Maximum file size in bytes
+        #[arg(long)]
+        max_size: Option<i64>,
+    },
+}
+
+/// Execute file commands
+pub async fn execute(
+    args: &FileArgs,
+    config_path: &str,
+    format: OutputFormat,
+) -> Result<(), AppError> {
+    let config = super::load_config(config_path).await?;
+    let pool = super::create_db_pool(&config).await?;
+    let file_repo = FileRepository::new(pool);
+
+    match &args.command {
+        FileCommand::Export {
+            output: out_path,
+            query,
+            folder_id,
+            storage_id,
+            owner_id,
+            mime_type,
+            min_size,
+            max_size,
+        } => {
+            let criteria = FileExportRequest {
+                query: query.clone(),
+                folder_id: folder_id.as_deref().map(parse_uuid).transpose()?,
+                storage_id: storage_id.as_deref().map(parse_uuid).transpose()?,
+                mime_type: mime_type.clone(),
+                owner_id: owner_id.as_deref().map(parse_uuid).transpose()?,
+                min_size: *min_size,
+                max_size: *max_size,
+                filter: None,
+            }
+            .criteria()?;
+            let export_format = match format {
+                OutputFormat::Json => FileExportFormat::Ndjson,
+                OutputFormat::Table | OutputFormat::Csv => FileExportFormat::Csv,
+            };
+
+            let out: Box<dyn Write> = if out_path == "-" {
+                Box::new(io::stdout().lock())
+            } else {
+                let file = File::create(out_path).map_err(|e| {
+                    AppError::internal(format!("Failed to create '{}': {}", out_path, e))
+                })?;
+                Box::new(file)
+            };
+            let mut out = BufWriter::new(out);
+            let write_error = |e: io::Error| AppError::internal(format!("Failed to write: {}", e));
+
+            out.write_all(export_format.header().as_bytes())
+                .map_err(write_error)?;
+            let mut files = std::pin::pin!(file_repo.export(criteria));
+            let mut count = 0u64;
+            while let Some(file) = files.next().await {
+                let file = file?;
+                out.write_all(export_format.encode(&file)?.as_bytes())
+                    .map_err(write_error)?;
+                count += 1;
+            }
+            out.flush().map_err(write_error)?;
+
+            if out_path != "-" {
+                output::print_success(&format!("Exported {} files to '{}'", count, out_path));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_uuid(value: &str) -> Result<uuid::Uuid, AppError> {
+    uuid::Uuid::parse_str(value).map_err(|e| AppError::bad_request(format!("Invalid UUID: {}", e)))
+}
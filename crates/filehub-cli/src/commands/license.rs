@@ -1,10 +1,14 @@
 //! License management CLI commands.
 
+use chrono::{DateTime, Duration, Utc};
 use clap::{Args, Subcommand};
+use serde::Serialize;
+use tabled::Tabled;
 
 use filehub_core::{error::AppError, types::PageRequest};
 use filehub_database::repositories::license::LicenseCheckoutRepository;
 use filehub_database::repositories::pool_snapshot::PoolSnapshotRepository;
+use filehub_entity::license::{FeatureSnapshot, PoolUtilization};
 
 use crate::output::{self, OutputFormat};
 
@@ -27,6 +31,18 @@ pub enum LicenseCommand {
         #[arg(short, long, default_value = "20")]
         limit: i64,
     },
+    /// Show seat utilization over a time window
+    Utilization {
+        /// Window length in hours, ending at `--to`
+        #[arg(long, default_value = "24")]
+        hours: i64,
+        /// Window start (RFC 3339); overrides `--hours`
+        #[arg(long)]
+        from: Option<String>,
+        /// Window end (RFC 3339); defaults to now
+        #[arg(long)]
+        to: Option<String>,
+    },
     /// Release all active checkouts (emergency)
     ReleaseAll {
         /// Skip confirmation
@@ -35,6 +51,66 @@ pub enum LicenseCommand {
     },
 }
 
+/// Most snapshots read for a utilization window
+const UTILIZATION_LIMIT: i64 = 10_000;
+
+/// Per-feature seat counts row
+#[derive(Debug, Serialize, Tabled)]
+struct FeatureRow {
+    #[tabled(rename = "Feature")]
+    feature: String,
+    #[tabled(rename = "Total")]
+    total_seats: i32,
+    #[tabled(rename = "Used")]
+    checked_out: i32,
+    #[tabled(rename = "Available")]
+    available: i32,
+    #[tabled(rename = "Borrowed")]
+    borrowed: i32,
+    #[tabled(rename = "Sessions")]
+    active_sessions: i32,
+}
+
+impl From<&FeatureSnapshot> for FeatureRow {
+    fn from(f: &FeatureSnapshot) -> Self {
+        Self {
+            feature: f.feature.clone(),
+            total_seats: f.total_seats,
+            checked_out: f.checked_out,
+            available: f.available,
+            borrowed: f.borrowed,
+            active_sessions: f.active_sessions,
+        }
+    }
+}
+
+/// One snapshot of a utilization window
+#[derive(Debug, Serialize, Tabled)]
+struct UtilizationRow {
+    #[tabled(rename = "Time")]
+    at: String,
+    #[tabled(rename = "Total")]
+    total_seats: i32,
+    #[tabled(rename = "Used")]
+    checked_out: i32,
+    #[tabled(rename = "Available")]
+    available: i32,
+    #[tabled(rename = "Borrowed")]
+    borrowed: i32,
+    #[tabled(rename = "Drift")]
+    drift: i32,
+    #[tabled(rename = "Usage %")]
+    usage_percent: String,
+    #[tabled(rename = "Source")]
+    source: String,
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>, AppError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| AppError::bad_request(format!("Invalid time '{}': {}", value, e)))
+}
+
 /// Execute license commands
 pub async fn execute(
     args: &LicenseArgs,
@@ -66,11 +142,20 @@ pub async fn execute(
                 output::print_kv("Available", &snap.available.to_string());
                 output::print_kv("Checked Out", &snap.checked_out.to_string());
                 output::print_kv("Admin Reserved", &snap.admin_reserved.to_string());
+                output::print_kv("Borrowed", &snap.borrowed.to_string());
                 output::print_kv(
                     "Drift Detected",
                     &snap.drift_detected.unwrap_or(false).to_string(),
                 );
+                output::print_kv("Drift", &snap.drift.to_string());
                 output::print_kv("Last Sync", &snap.created_at.to_rfc3339());
+
+                if !snap.features.is_empty() {
+                    println!();
+                    let rows: Vec<FeatureRow> =
+                        snap.features.iter().map(FeatureRow::from).collect();
+                    output::print_list(&rows, format);
+                }
             } else {
                 output::print_warning("No pool snapshots available");
             }
@@ -80,6 +165,7 @@ pub async fn execute(
                 .find_recent(&PageRequest {
                     page: 1,
                     page_size: *limit as u64,
+                    cursor: None,
                 })
                 .await
                 .map_err(|e| AppError::internal(format!("Failed to get history: {}", e)))?;
@@ -88,6 +174,48 @@ pub async fn execute(
                 output::print_item(&snap, format);
             }
         }
+        LicenseCommand::Utilization { hours, from, to } => {
+            let to = to
+                .as_deref()
+                .map(parse_time)
+                .transpose()?
+                .unwrap_or_else(Utc::now);
+            let from = match from {
+                Some(from) => parse_time(from)?,
+                None => to - Duration::hours(*hours),
+            };
+            if from > to {
+                return Err(AppError::bad_request("--from must not be after --to"));
+            }
+
+            let snapshots = snapshot_repo
+                .find_between(from, to, UTILIZATION_LIMIT)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to get snapshots: {}", e)))?;
+            let utilization = PoolUtilization::new(from, to, None, snapshots);
+
+            match format {
+                OutputFormat::Json => output::print_item(&utilization, format),
+                OutputFormat::Csv => print!("{}", utilization.to_csv()),
+                OutputFormat::Table => {
+                    let rows: Vec<UtilizationRow> = utilization
+                        .points
+                        .iter()
+                        .map(|p| UtilizationRow {
+                            at: p.at.format("%Y-%m-%d %H:%M:%S").to_string(),
+                            total_seats: p.total_seats,
+                            checked_out: p.checked_out,
+                            available: p.available,
+                            borrowed: p.borrowed,
+                            drift: p.drift,
+                            usage_percent: format!("{:.1}", p.usage_percent),
+                            source: p.source.clone(),
+                        })
+                        .collect();
+                    output::print_list(&rows, format);
+                }
+            }
+        }
         LicenseCommand::ReleaseAll { force } => {
             if !force {
                 let confirm = dialoguer::Confirm::new()
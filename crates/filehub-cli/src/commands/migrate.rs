@@ -17,9 +17,31 @@ pub struct MigrateArgs {
 #[derive(Debug, Subcommand)]
 pub enum MigrateCommand {
     /// Run all pending migrations
-    Run,
+    Run {
+        /// Print the SQL that would run without executing it
+        #[arg(long)]
+        dry_run: bool,
+    },
     /// Show migration status
     Status,
+    /// Roll back the most recently applied migrations
+    Down {
+        /// Number of migrations to roll back
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+        /// Allow rolling back migrations that drop data
+        #[arg(long)]
+        force: bool,
+        /// Print the SQL that would run without executing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show applied and reverted migrations
+    History {
+        /// Maximum number of entries to show
+        #[arg(long, default_value_t = 50)]
+        limit: i64,
+    },
     /// Reset database (drop all tables and re-run)
     Reset {
         /// Skip confirmation prompt
@@ -34,7 +56,18 @@ pub async fn execute(args: &MigrateArgs, config_path: &str) -> Result<(), AppErr
     let pool = super::create_db_pool(&config).await?;
 
     match &args.command {
-        MigrateCommand::Run => {
+        MigrateCommand::Run { dry_run: true } => {
+            let pending = filehub_database::migration::pending_migrations(&pool)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to list migrations: {}", e)))?;
+            if pending.is_empty() {
+                println!("No pending migrations.");
+            }
+            for step in &pending {
+                print_step(step);
+            }
+        }
+        MigrateCommand::Run { dry_run: false } => {
             println!("Running database migrations...");
             filehub_database::migration::run_migrations(&pool)
                 .await
@@ -42,16 +75,68 @@ pub async fn execute(args: &MigrateArgs, config_path: &str) -> Result<(), AppErr
             output::print_success("All migrations applied successfully.");
         }
         MigrateCommand::Status => {
-            // println!("Migration status:");
-            // let status = filehub_database::migration::migration_status(&pool)
-            //     .await
-            //     .map_err(|e| AppError::internal(format!("Failed to get status: {}", e)))?;
-            // for entry in &status {
-            //     println!(
-            //         "  {} - {} ({})",
-            //         entry.version, entry.description, entry.status
-            //     );
-            // }
+            println!("Migration status:");
+            let status = filehub_database::migration::migration_status(&pool)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to get status: {}", e)))?;
+            for entry in &status {
+                let state = match entry.applied_at {
+                    Some(at) => format!("applied {}", at.format("%Y-%m-%d %H:%M:%S")),
+                    None => "pending".to_string(),
+                };
+                let reversible = if entry.reversible { "" } else { ", no down" };
+                println!(
+                    "  {} - {} ({}{})",
+                    entry.version, entry.description, state, reversible
+                );
+            }
+        }
+        MigrateCommand::Down {
+            steps,
+            force,
+            dry_run,
+        } => {
+            let plan = filehub_database::migration::plan_rollback(&pool, *steps).await?;
+            if *dry_run {
+                for step in &plan {
+                    print_step(step);
+                }
+                return Ok(());
+            }
+
+            if plan.iter().any(|s| s.destructive) && !force {
+                output::print_error("These migrations drop data:");
+                for step in plan.iter().filter(|s| s.destructive) {
+                    println!("  {} - {}", step.version, step.description);
+                }
+                return Err(AppError::validation(
+                    "Refusing to roll back destructive migrations without --force",
+                ));
+            }
+
+            let reverted = filehub_database::migration::rollback(&pool, *steps, *force).await?;
+            for step in &reverted {
+                println!("  Reverted {} - {}", step.version, step.description);
+            }
+            output::print_success(&format!("Rolled back {} migration(s).", reverted.len()));
+        }
+        MigrateCommand::History { limit } => {
+            let history = filehub_database::migration::migration_history(&pool, *limit)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to get history: {}", e)))?;
+            if history.is_empty() {
+                println!("No migration history recorded.");
+            }
+            for entry in &history {
+                println!(
+                    "  {}  {:<4} {} - {} ({} ms)",
+                    entry.executed_at.format("%Y-%m-%d %H:%M:%S"),
+                    entry.direction,
+                    entry.version,
+                    entry.description,
+                    entry.execution_ms
+                );
+            }
         }
         MigrateCommand::Reset { force } => {
             if !force {
@@ -77,3 +162,20 @@ pub async fn execute(args: &MigrateArgs, config_path: &str) -> Result<(), AppErr
 
     Ok(())
 }
+
+/// Print a migration step and its SQL for a dry run
+fn print_step(step: &filehub_database::migration::MigrationStep) {
+    let destructive = if step.destructive {
+        " [destructive]"
+    } else {
+        ""
+    };
+    println!(
+        "-- {} {} - {}{}",
+        step.direction.as_str(),
+        step.version,
+        step.description,
+        destructive
+    );
+    println!("{}\n", step.sql.trim_end());
+}
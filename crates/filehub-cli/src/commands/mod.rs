@@ -2,16 +2,24 @@
 
 pub mod admin;
 pub mod audit;
+pub mod backup;
 pub mod broadcast;
+pub mod completions;
 pub mod config;
+pub mod doctor;
+pub mod file;
 pub mod folder;
 pub mod license;
 pub mod migrate;
+pub mod permission;
 pub mod serve;
+pub mod session;
+pub mod share;
+pub mod shell;
 pub mod user;
 pub mod worker;
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueHint};
 
 use crate::output::OutputFormat;
 use filehub_core::error::AppError;
@@ -21,7 +29,7 @@ use filehub_core::error::AppError;
 #[command(name = "filehub", version, about, long_about = None)]
 pub struct Cli {
     /// Path to configuration file
-    #[arg(short, long, default_value = "config/default.toml")]
+    #[arg(short, long, default_value = "config/default.toml", value_hint = ValueHint::FilePath)]
     pub config: String,
 
     /// Output format
@@ -44,6 +52,14 @@ pub enum Commands {
     Admin(admin::AdminArgs),
     /// User management
     User(user::UserArgs),
+    /// Session inspection and revocation
+    Session(session::SessionArgs),
+    /// Share link inspection and access statistics
+    Share(share::ShareArgs),
+    /// Permission decision introspection
+    Permission(permission::PermissionArgs),
+    /// File inventory export
+    File(file::FileArgs),
     /// Storage management
     /// Folder management
     Folder(folder::FolderArgs),
@@ -57,6 +73,20 @@ pub enum Commands {
     Audit(audit::AuditArgs),
     /// Worker management
     Worker(worker::WorkerArgs),
+    /// Backup and restore of the database and stored files
+    Backup(backup::BackupArgs),
+    /// Check the environment before serving
+    Doctor(doctor::DoctorArgs),
+    /// Interactive shell that keeps the config and database pool open
+    Shell(shell::ShellArgs),
+    /// Print a shell completion script (bash, zsh, fish, powershell)
+    #[command(long_about = "Print a shell completion script to stdout.\n\n\
+        Install with, for example:\n  \
+        bash:       filehub-cli completions bash > /etc/bash_completion.d/filehub-cli\n  \
+        zsh:        filehub-cli completions zsh > \"${fpath[1]}/_filehub-cli\"\n  \
+        fish:       filehub-cli completions fish > ~/.config/fish/completions/filehub-cli.fish\n  \
+        PowerShell: filehub-cli completions powershell >> $PROFILE")]
+    Completions(completions::CompletionsArgs),
 }
 
 impl Cli {
@@ -67,26 +97,78 @@ impl Cli {
             Commands::Migrate(args) => migrate::execute(args, &self.config).await,
             Commands::Admin(args) => admin::execute(args, &self.config, self.format).await,
             Commands::User(args) => user::execute(args, &self.config, self.format).await,
+            Commands::Session(args) => session::execute(args, &self.config, self.format).await,
+            Commands::Share(args) => share::execute(args, &self.config, self.format).await,
+            Commands::Permission(args) => {
+                permission::execute(args, &self.config, self.format).await
+            }
+            Commands::File(args) => file::execute(args, &self.config, self.format).await,
             Commands::Folder(args) => folder::execute(args, &self.config, self.format).await,
             Commands::Config(args) => config::execute(args, &self.config, self.format).await,
             Commands::License(args) => license::execute(args, &self.config, self.format).await,
             Commands::Broadcast(args) => broadcast::execute(args, &self.config).await,
             Commands::Audit(args) => audit::execute(args, &self.config, self.format).await,
             Commands::Worker(args) => worker::execute(args, &self.config).await,
+            Commands::Backup(args) => backup::execute(args, &self.config).await,
+            Commands::Doctor(args) => doctor::execute(args, &self.config, self.format).await,
+            Commands::Shell(args) => shell::execute(args, &self.config, self.format).await,
+            Commands::Completions(args) => completions::execute(args).await,
         }
     }
 }
 
-/// Helper: load configuration from file
+/// Helper: load configuration from file, check it for consistency and
+/// resolve the secrets it references
+///
+/// Inside `filehub shell` the configuration is read once and reused.
 pub async fn load_config(config_path: &str) -> Result<filehub_core::config::AppConfig, AppError> {
-    filehub_core::config::AppConfig::load(config_path)
-        .map_err(|e| AppError::internal(format!("Failed to load config: {}", e)))
+    if let Some(config) = shell::cached_config(config_path) {
+        return Ok(config);
+    }
+    let mut config = read_config(config_path)?;
+    filehub_http::secrets::resolve_config_secrets(&mut config).await?;
+    shell::cache_config(config_path, &config);
+    Ok(config)
+}
+
+/// Helper: synchronous [`load_config`], also used for reloads
+pub fn read_config(config_path: &str) -> Result<filehub_core::config::AppConfig, AppError> {
+    let config = filehub_core::config::AppConfig::load(config_path)
+        .map_err(|e| AppError::internal(format!("Failed to load config: {}", e)))?;
+
+    config.validate().map_err(|issues| {
+        let list: Vec<String> = issues.iter().map(|i| format!("  - {}", i)).collect();
+        AppError::configuration(format!(
+            "Invalid configuration ({} problem(s)):\n{}",
+            issues.len(),
+            list.join("\n")
+        ))
+    })?;
+    filehub_entity::notification::NotificationTemplates::from_config(
+        &config.realtime.notifications.templates,
+    )?;
+
+    Ok(config)
 }
 
 /// Helper: create database pool from config
+///
+/// Inside `filehub shell` the pool opened by the first command is reused.
+/// Commands open connections as they need them rather than warming the
+/// pool up.
 pub async fn create_db_pool(
     config: &filehub_core::config::AppConfig,
 ) -> Result<sqlx::PgPool, AppError> {
-    let pool = filehub_database::connection::DatabasePool::connect(&config.database).await?;
-    Ok(pool.into_pool())
+    if let Some(pool) = shell::cached_pool() {
+        return Ok(pool);
+    }
+    let database = filehub_core::config::DatabaseConfig {
+        warmup: false,
+        ..config.database.clone()
+    };
+    let pool = filehub_database::connection::DatabasePool::connect(&database)
+        .await?
+        .into_pool();
+    shell::cache_pool(&pool);
+    Ok(pool)
 }
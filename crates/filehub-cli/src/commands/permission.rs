@@ -0,0 +1,367 @@
+//! Permission introspection CLI commands.
+
+use std::sync::Arc;
+
+use clap::{Args, Subcommand, ValueEnum};
+use serde::Serialize;
+use tabled::Tabled;
+use uuid::Uuid;
+
+use crate::output::{self, OutputFormat};
+use filehub_auth::acl::{AclChecker, AclInheritanceResolver, EffectivePermissionResolver};
+use filehub_auth::rbac::RbacEnforcer;
+use filehub_auth::rbac::policies::SystemPermission;
+use filehub_cache::CacheManager;
+use filehub_cache::memory::MemoryCacheProvider;
+use filehub_core::config::AppConfig;
+use filehub_core::error::AppError;
+use filehub_core::types::TenantId;
+use filehub_database::repositories::file::FileRepository;
+use filehub_database::repositories::folder::FolderRepository;
+use filehub_database::repositories::permission::AclRepository;
+use filehub_database::repositories::permission_template::PermissionTemplateRepository;
+use filehub_database::repositories::share::ShareRepository;
+use filehub_database::repositories::storage::StorageRepository;
+use filehub_database::repositories::user::UserRepository;
+use filehub_entity::permission::{AclPermission, ResourceType, TemplateEntry};
+use filehub_service::context::RequestContext;
+use filehub_service::permission::explain::{ExplainRequest, PermissionExplainer};
+use filehub_service::permission::template::{PermissionTemplateService, TemplateRequest};
+
+/// Arguments for permission commands
+#[derive(Debug, Args)]
+pub struct PermissionArgs {
+    /// Permission subcommand
+    #[command(subcommand)]
+    pub command: PermissionCommand,
+}
+
+/// Permission subcommands
+#[derive(Debug, Subcommand)]
+pub enum PermissionCommand {
+    /// Explain how a user's permission on a resource is decided
+    Explain {
+        /// Username
+        #[arg(short, long)]
+        user: String,
+        /// Resource type
+        #[arg(short = 't', long, value_enum)]
+        resource_type: ResourceKind,
+        /// Resource ID
+        #[arg(short, long)]
+        resource_id: Uuid,
+        /// Permission level to check for
+        #[arg(long, value_enum, default_value = "viewer")]
+        required: PermissionLevel,
+        /// System permission to check the user's role for, e.g. file_upload
+        #[arg(short, long)]
+        system_permission: Option<String>,
+    },
+    /// Manage folder permission templates
+    Template {
+        /// Admin account the changes are made by
+        #[arg(short, long)]
+        admin: String,
+        /// Template subcommand
+        #[command(subcommand)]
+        command: TemplateCommand,
+    },
+}
+
+/// Permission template subcommands
+#[derive(Debug, Subcommand)]
+pub enum TemplateCommand {
+    /// List templates
+    List,
+    /// Create a template
+    Create {
+        /// Template name
+        name: String,
+        /// Entries as a JSON array, e.g.
+        /// '[{"user_id":"...","permission":"editor"},{"is_anyone":true,"permission":"viewer","inheritance":"block"}]'
+        #[arg(short, long)]
+        entries: String,
+        /// Description
+        #[arg(short, long)]
+        description: Option<String>,
+    },
+    /// Delete a template
+    Delete {
+        /// Template name
+        name: String,
+    },
+    /// Attach a template to a folder so it is applied to new child folders
+    Attach {
+        /// Template name
+        name: String,
+        /// Folder ID
+        #[arg(short, long)]
+        folder_id: Uuid,
+    },
+    /// Detach a folder's template
+    Detach {
+        /// Folder ID
+        #[arg(short, long)]
+        folder_id: Uuid,
+    },
+    /// Apply a template to an existing folder
+    Apply {
+        /// Template name
+        name: String,
+        /// Folder ID
+        #[arg(short, long)]
+        folder_id: Uuid,
+        /// Also apply it to every folder below
+        #[arg(short, long)]
+        recursive: bool,
+    },
+}
+
+/// Template display row for table output
+#[derive(Debug, Serialize, Tabled)]
+struct TemplateRow {
+    /// Template ID
+    id: String,
+    /// Name
+    name: String,
+    /// Number of entries
+    entries: usize,
+    /// Description
+    description: String,
+}
+
+/// Resource type argument
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ResourceKind {
+    /// A file
+    File,
+    /// A folder
+    Folder,
+    /// A storage backend
+    Storage,
+}
+
+/// Permission level argument
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum PermissionLevel {
+    /// View
+    Viewer,
+    /// Comment
+    Commenter,
+    /// Edit
+    Editor,
+    /// Full control
+    Owner,
+}
+
+/// Execute permission commands
+pub async fn execute(
+    args: &PermissionArgs,
+    config_path: &str,
+    format: OutputFormat,
+) -> Result<(), AppError> {
+    let config = super::load_config(config_path).await?;
+    let pool = super::create_db_pool(&config).await?;
+
+    match &args.command {
+        PermissionCommand::Explain {
+            user,
+            resource_type,
+            resource_id,
+            required,
+            system_permission,
+        } => {
+            let user_repo = Arc::new(UserRepository::new(pool.clone()));
+            let user = user_repo
+                .find_by_username(user)
+                .await?
+                .ok_or_else(|| AppError::not_found(format!("User '{}' not found", user)))?;
+            let system_permission = system_permission
+                .as_deref()
+                .map(|name| {
+                    serde_json::from_value::<SystemPermission>(serde_json::Value::from(name))
+                        .map_err(|_| {
+                            AppError::validation(format!("Unknown system permission '{name}'"))
+                        })
+                })
+                .transpose()?;
+
+            let folder_repo = Arc::new(FolderRepository::new(pool.clone()));
+            let acl_repo = Arc::new(AclRepository::new(pool.clone()));
+            let rbac = Arc::new(RbacEnforcer::new());
+            // Explaining never reads or writes the permission cache, so a
+            // private in-memory one is enough.
+            let cache = Arc::new(CacheManager::from_provider(Arc::new(
+                MemoryCacheProvider::new(&config.cache.memory, 0),
+            )));
+            let resolver = Arc::new(EffectivePermissionResolver::new(
+                Arc::clone(&rbac),
+                Arc::new(AclChecker::new(Arc::clone(&acl_repo))),
+                Arc::new(AclInheritanceResolver::new(
+                    Arc::clone(&folder_repo),
+                    acl_repo,
+                )),
+                cache,
+            ));
+            let explainer = PermissionExplainer::new(
+                user_repo,
+                Arc::new(FileRepository::new(pool.clone())),
+                folder_repo,
+                Arc::new(StorageRepository::new(pool.clone())),
+                Arc::new(ShareRepository::new(pool)),
+                rbac,
+                resolver,
+            );
+
+            let trace = explainer
+                .explain(&ExplainRequest {
+                    user_id: user.id,
+                    resource_type: match resource_type {
+                        ResourceKind::File => ResourceType::File,
+                        ResourceKind::Folder => ResourceType::Folder,
+                        ResourceKind::Storage => ResourceType::Storage,
+                    },
+                    resource_id: *resource_id,
+                    required: match required {
+                        PermissionLevel::Viewer => AclPermission::Viewer,
+                        PermissionLevel::Commenter => AclPermission::Commenter,
+                        PermissionLevel::Editor => AclPermission::Editor,
+                        PermissionLevel::Owner => AclPermission::Owner,
+                    },
+                    system_permission,
+                })
+                .await?;
+
+            match format {
+                OutputFormat::Table | OutputFormat::Csv => println!("{trace}"),
+                OutputFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&trace).unwrap_or_else(|_| "{}".to_string())
+                ),
+            }
+        }
+        PermissionCommand::Template { admin, command } => {
+            execute_template(command, admin, &config, pool, format).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Execute permission template commands as `admin`.
+async fn execute_template(
+    command: &TemplateCommand,
+    admin: &str,
+    config: &AppConfig,
+    pool: sqlx::PgPool,
+    format: OutputFormat,
+) -> Result<(), AppError> {
+    let admin = UserRepository::new(pool.clone())
+        .find_by_username(admin)
+        .await?
+        .ok_or_else(|| AppError::not_found(format!("User '{}' not found", admin)))?;
+    let ctx = RequestContext::new(
+        admin.id,
+        Uuid::nil(),
+        admin.role,
+        admin.username,
+        "127.0.0.1".to_string(),
+        None,
+    )
+    .with_tenant(TenantId::from_uuid(admin.tenant_id));
+
+    let folder_repo = Arc::new(FolderRepository::new(pool.clone()));
+    let acl_repo = Arc::new(AclRepository::new(pool.clone()));
+    // The server's cache, so that applied entries take effect immediately.
+    let cache = Arc::new(CacheManager::new(&config.cache).await?);
+    let resolver = Arc::new(EffectivePermissionResolver::new(
+        Arc::new(RbacEnforcer::new()),
+        Arc::new(AclChecker::new(Arc::clone(&acl_repo))),
+        Arc::new(AclInheritanceResolver::new(
+            Arc::clone(&folder_repo),
+            Arc::clone(&acl_repo),
+        )),
+        cache,
+    ));
+    let service = PermissionTemplateService::new(
+        Arc::new(PermissionTemplateRepository::new(pool)),
+        folder_repo,
+        acl_repo,
+        resolver,
+    );
+
+    match command {
+        TemplateCommand::List => {
+            let rows: Vec<TemplateRow> = service
+                .list(&ctx)
+                .await?
+                .into_iter()
+                .map(|t| TemplateRow {
+                    id: t.id.to_string(),
+                    name: t.name,
+                    entries: t.entries.len(),
+                    description: t.description.unwrap_or_default(),
+                })
+                .collect();
+            output::print_list(&rows, format);
+        }
+        TemplateCommand::Create {
+            name,
+            entries,
+            description,
+        } => {
+            let entries: Vec<TemplateEntry> = serde_json::from_str(entries)
+                .map_err(|e| AppError::validation(format!("Invalid entries: {e}")))?;
+            let template = service
+                .create(
+                    &ctx,
+                    TemplateRequest {
+                        name: name.clone(),
+                        description: description.clone(),
+                        entries,
+                    },
+                )
+                .await?;
+            output::print_success(&format!(
+                "Permission template '{}' created ({})",
+                template.name, template.id
+            ));
+        }
+        TemplateCommand::Delete { name } => {
+            let template = service.get_by_name(&ctx, name).await?;
+            service.delete(&ctx, template.id).await?;
+            output::print_success(&format!("Permission template '{}' deleted", name));
+        }
+        TemplateCommand::Attach { name, folder_id } => {
+            let template = service.get_by_name(&ctx, name).await?;
+            service.attach(&ctx, *folder_id, Some(template.id)).await?;
+            output::print_success(&format!(
+                "Permission template '{}' attached to folder {}",
+                name, folder_id
+            ));
+        }
+        TemplateCommand::Detach { folder_id } => {
+            service.attach(&ctx, *folder_id, None).await?;
+            output::print_success(&format!(
+                "Permission template detached from folder {}",
+                folder_id
+            ));
+        }
+        TemplateCommand::Apply {
+            name,
+            folder_id,
+            recursive,
+        } => {
+            let template = service.get_by_name(&ctx, name).await?;
+            let summary = service
+                .apply(&ctx, template.id, *folder_id, *recursive)
+                .await?;
+            output::print_success(&format!(
+                "Applied '{}' to {} folder(s): {} entries created, {} updated",
+                name, summary.folders, summary.created, summary.updated
+            ));
+        }
+    }
+
+    Ok(())
+}
@@ -35,15 +35,21 @@ pub async fn execute(args: &ServeArgs, config_path: &str) -> Result<(), AppError
     println!("  Host: {}", config.server.host);
     println!("  Port: {}", config.server.port);
 
-    let pool = super::create_db_pool(&config).await?;
+    let database = filehub_database::DatabasePool::connect(&config.database).await?;
 
     if args.auto_migrate {
         println!("Running database migrations...");
-        filehub_database::migration::run_migrations(&pool)
+        filehub_database::migration::run_migrations(database.pool())
             .await
             .map_err(|e| AppError::internal(format!("Migration failed: {}", e)))?;
         println!("  Migrations applied successfully.");
     }
 
-    filehub_api::app::run_server(config, pool).await
+    let reload_path = config_path.to_string();
+    let reload = filehub_api::reload::ReloadHooks {
+        load: Box::new(move || super::read_config(&reload_path)),
+        set_log_level: Box::new(crate::logging::set_level),
+    };
+
+    filehub_api::app::run_server(config, database, Some(reload)).await
 }
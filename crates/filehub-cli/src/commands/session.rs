@@ -0,0 +1,111 @@
+//! Session inspection and revocation CLI commands.
+
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use tabled::Tabled;
+use uuid::Uuid;
+
+use crate::output::{self, OutputFormat};
+use filehub_core::error::AppError;
+use filehub_core::traits::CacheProvider;
+use filehub_database::repositories::session::SessionRepository;
+use filehub_database::repositories::user::UserRepository;
+
+/// Arguments for session commands
+#[derive(Debug, Args)]
+pub struct SessionArgs {
+    /// Session subcommand
+    #[command(subcommand)]
+    pub command: SessionCommand,
+}
+
+/// Session subcommands
+#[derive(Debug, Subcommand)]
+pub enum SessionCommand {
+    /// List a user's active sessions with their devices
+    List {
+        /// Username
+        #[arg(short, long)]
+        user: String,
+    },
+    /// End one session
+    Revoke {
+        /// Session ID
+        session_id: Uuid,
+    },
+}
+
+/// Session display row for table output
+#[derive(Debug, Serialize, Tabled)]
+struct SessionRow {
+    /// Session ID
+    id: String,
+    /// Device label
+    device: String,
+    /// Client IP
+    ip_address: String,
+    /// Login time
+    created_at: String,
+    /// Last activity
+    last_activity: String,
+}
+
+/// Execute session commands
+pub async fn execute(
+    args: &SessionArgs,
+    config_path: &str,
+    format: OutputFormat,
+) -> Result<(), AppError> {
+    let config = super::load_config(config_path).await?;
+    let pool = super::create_db_pool(&config).await?;
+    let session_repo = SessionRepository::new(pool.clone());
+
+    match &args.command {
+        SessionCommand::List { user } => {
+            let user = UserRepository::new(pool)
+                .find_by_username(user)
+                .await?
+                .ok_or_else(|| AppError::not_found(format!("User '{}' not found", user)))?;
+
+            let mut sessions = session_repo.find_active_by_user(user.id).await?;
+            sessions.sort_by_key(|s| std::cmp::Reverse(s.last_activity));
+
+            let rows: Vec<SessionRow> = sessions
+                .iter()
+                .map(|s| SessionRow {
+                    id: s.id.to_string(),
+                    device: s.device().label,
+                    ip_address: s.ip_address.to_string(),
+                    created_at: s.created_at.format("%Y-%m-%d %H:%M").to_string(),
+                    last_activity: s.last_activity.format("%Y-%m-%d %H:%M").to_string(),
+                })
+                .collect();
+
+            output::print_list(&rows, format);
+        }
+        SessionCommand::Revoke { session_id } => {
+            let session = session_repo
+                .find_by_id(*session_id)
+                .await?
+                .filter(|s| s.terminated_at.is_none())
+                .ok_or_else(|| {
+                    AppError::not_found(format!("Active session {} not found", session_id))
+                })?;
+
+            session_repo
+                .terminate(session.id, session.user_id, "Revoked by administrator")
+                .await?;
+
+            // The server caches validated sessions; drop the entry so the
+            // revocation takes effect on the next request.
+            let cache = filehub_cache::provider::CacheManager::new(&config.cache).await?;
+            cache
+                .delete(&filehub_cache::keys::session_by_id(session.id))
+                .await?;
+
+            output::print_success(&format!("Session {} revoked", session.id));
+        }
+    }
+
+    Ok(())
+}
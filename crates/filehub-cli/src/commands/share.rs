@@ -0,0 +1,155 @@
+//! Share link inspection CLI commands.
+
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use tabled::Tabled;
+use uuid::Uuid;
+
+use crate::output::{self, OutputFormat};
+use filehub_core::error::AppError;
+use filehub_core::types::pagination::PageRequest;
+use filehub_database::repositories::share::ShareRepository;
+use filehub_database::repositories::user::UserRepository;
+
+/// Arguments for share commands
+#[derive(Debug, Args)]
+pub struct ShareArgs {
+    /// Share subcommand
+    #[command(subcommand)]
+    pub command: ShareCommand,
+}
+
+/// Share subcommands
+#[derive(Debug, Subcommand)]
+pub enum ShareCommand {
+    /// List the shares a user has created
+    List {
+        /// Username
+        #[arg(short, long)]
+        user: String,
+    },
+    /// Show view and download counts and recent accesses of a share link
+    Stats {
+        /// Share ID
+        share_id: Uuid,
+    },
+}
+
+/// Share display row for table output
+#[derive(Debug, Serialize, Tabled)]
+struct ShareRow {
+    /// Share ID
+    id: String,
+    /// Share type
+    share_type: String,
+    /// Shared resource
+    resource: String,
+    /// Downloads / limit
+    downloads: String,
+    /// Expiry
+    expires_at: String,
+    /// Active flag
+    active: bool,
+}
+
+/// Access display row for table output
+#[derive(Debug, Serialize, Tabled)]
+struct AccessRow {
+    /// Access time
+    accessed_at: String,
+    /// View or download
+    kind: String,
+    /// Client IP
+    ip_address: String,
+    /// Client country
+    country: String,
+    /// Client User-Agent
+    user_agent: String,
+}
+
+/// Execute share commands
+pub async fn execute(
+    args: &ShareArgs,
+    config_path: &str,
+    format: OutputFormat,
+) -> Result<(), AppError> {
+    let config = super::load_config(config_path).await?;
+    let pool = super::create_db_pool(&config).await?;
+    let share_repo = ShareRepository::new(pool.clone());
+
+    match &args.command {
+        ShareCommand::List { user } => {
+            let user = UserRepository::new(pool)
+                .find_by_username(user)
+                .await?
+                .ok_or_else(|| AppError::not_found(format!("User '{}' not found", user)))?;
+
+            let shares = share_repo
+                .find_by_creator(user.id, &PageRequest::new(1, 100))
+                .await?;
+
+            let rows: Vec<ShareRow> = shares
+                .items
+                .iter()
+                .map(|s| ShareRow {
+                    id: s.id.to_string(),
+                    share_type: format!("{:?}", s.share_type),
+                    resource: format!("{} {}", s.resource_type, s.resource_id),
+                    downloads: match s.max_downloads {
+                        Some(max) => format!("{}/{}", s.download_count.unwrap_or(0), max),
+                        None => s.download_count.unwrap_or(0).to_string(),
+                    },
+                    expires_at: s
+                        .expires_at
+                        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_else(|| "never".to_string()),
+                    active: s.is_active.unwrap_or(true),
+                })
+                .collect();
+
+            output::print_list(&rows, format);
+        }
+        ShareCommand::Stats { share_id } => {
+            share_repo
+                .find_by_id(*share_id)
+                .await?
+                .ok_or_else(|| AppError::not_found(format!("Share {} not found", share_id)))?;
+
+            let stats = share_repo
+                .access_stats(*share_id, i64::from(config.shares.analytics.recent_limit))
+                .await?;
+
+            if format == OutputFormat::Json {
+                output::print_item(&stats, format);
+                return Ok(());
+            }
+
+            println!("Share {}:", stats.share_id);
+            output::print_kv("Views", &stats.views.to_string());
+            output::print_kv("Downloads", &stats.downloads.to_string());
+            output::print_kv("Unique visitors", &stats.unique_visitors.to_string());
+            output::print_kv(
+                "Last access",
+                &stats
+                    .last_accessed_at
+                    .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|| "never".to_string()),
+            );
+
+            let rows: Vec<AccessRow> = stats
+                .recent
+                .iter()
+                .map(|a| AccessRow {
+                    accessed_at: a.accessed_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    kind: format!("{:?}", a.kind).to_lowercase(),
+                    ip_address: a.ip_address.clone().unwrap_or_else(|| "-".to_string()),
+                    country: a.country.clone().unwrap_or_else(|| "-".to_string()),
+                    user_agent: a.user_agent.clone().unwrap_or_else(|| "-".to_string()),
+                })
+                .collect();
+            output::print_list(&rows, format);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,285 @@
+//! Interactive shell.
+//!
+//! `filehub shell` reads commands in a loop and runs them through the same
+//! [`Commands`] dispatch as the one-shot CLI. The configuration and the
+//! database pool are loaded once and reused by every command (see
+//! [`super::load_config`] and [`super::create_db_pool`]).
+
+use std::io::{self, IsTerminal};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use clap::{CommandFactory, Parser};
+use dialoguer::{BasicHistory, Completion, Input};
+use sqlx::PgPool;
+use tokio::sync::Notify;
+
+use crate::output::{self, OutputFormat};
+use filehub_core::config::AppConfig;
+use filehub_core::error::AppError;
+
+use super::{Cli, Commands};
+
+/// Arguments for the shell command
+#[derive(Debug, clap::Args)]
+pub struct ShellArgs {
+    /// Number of history entries kept
+    #[arg(long, default_value = "500")]
+    pub history_size: usize,
+}
+
+/// One line typed at the shell prompt.
+#[derive(Debug, Parser)]
+#[command(
+    name = "",
+    no_binary_name = true,
+    disable_version_flag = true,
+    about = "Shell commands. Also: reload (re-read config, reconnect), exit"
+)]
+struct ShellLine {
+    /// Output format for this command
+    #[arg(short, long, value_enum)]
+    format: Option<OutputFormat>,
+
+    /// Command to execute
+    #[command(subcommand)]
+    command: Commands,
+}
+
+/// Commands handled by the shell itself.
+const BUILTINS: &[&str] = &["exit", "quit", "reload"];
+
+/// Config and pool shared by commands run inside the shell.
+#[derive(Debug)]
+struct Session {
+    config_path: String,
+    config: Mutex<Option<AppConfig>>,
+    pool: Mutex<Option<PgPool>>,
+}
+
+static SESSION: OnceLock<Session> = OnceLock::new();
+
+/// The configuration cached for `config_path`, when running in the shell.
+pub(super) fn cached_config(config_path: &str) -> Option<AppConfig> {
+    let session = SESSION.get().filter(|s| s.config_path == config_path)?;
+    lock(&session.config).clone()
+}
+
+/// Remember the configuration loaded from `config_path`.
+pub(super) fn cache_config(config_path: &str, config: &AppConfig) {
+    if let Some(session) = SESSION.get().filter(|s| s.config_path == config_path) {
+        *lock(&session.config) = Some(config.clone());
+    }
+}
+
+/// The pool opened by an earlier command, when running in the shell.
+pub(super) fn cached_pool() -> Option<PgPool> {
+    lock(&SESSION.get()?.pool).clone()
+}
+
+/// Remember the pool so later commands reuse it.
+pub(super) fn cache_pool(pool: &PgPool) {
+    if let Some(session) = SESSION.get() {
+        *lock(&session.pool) = Some(pool.clone());
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Execute the shell command
+pub async fn execute(
+    args: &ShellArgs,
+    config_path: &str,
+    format: OutputFormat,
+) -> Result<(), AppError> {
+    let session = Session {
+        config_path: config_path.to_string(),
+        config: Mutex::new(None),
+        pool: Mutex::new(None),
+    };
+    if SESSION.set(session).is_err() {
+        return Err(AppError::validation("Already running inside the shell"));
+    }
+
+    // Validate the config up front so a typo is reported before the prompt.
+    super::load_config(config_path).await?;
+
+    let interrupted = Arc::new(Notify::new());
+    let notify = Arc::clone(&interrupted);
+    tokio::spawn(async move {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            notify.notify_waiters();
+        }
+    });
+
+    let mut history = BasicHistory::new()
+        .max_entries(args.history_size)
+        .no_duplicates(true);
+    let completion = CommandCompletion::new();
+
+    let interactive = io::stdin().is_terminal() && io::stderr().is_terminal();
+    if interactive {
+        println!("FileHub shell. Type 'help' for commands, 'exit' to quit.");
+    }
+
+    loop {
+        let line = if interactive {
+            let line = tokio::task::block_in_place(|| {
+                Input::<String>::new()
+                    .with_prompt("filehub")
+                    .allow_empty(true)
+                    .history_with(&mut history)
+                    .completion_with(&completion)
+                    .interact_text()
+            });
+            match line {
+                Ok(line) => line,
+                Err(dialoguer::Error::IO(e)) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+        } else {
+            // Commands piped in, one per line.
+            let mut line = String::new();
+            match io::stdin().read_line(&mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => line,
+            }
+        };
+
+        let words = match shell_words::split(&line) {
+            Ok(words) => words,
+            Err(e) => {
+                output::print_error(&format!("Invalid input: {}", e));
+                continue;
+            }
+        };
+
+        match words.first().map(String::as_str) {
+            None => continue,
+            Some("exit" | "quit") => break,
+            Some("reload") => {
+                reload(config_path).await;
+                continue;
+            }
+            Some(_) => {}
+        }
+
+        let parsed = match ShellLine::try_parse_from(&words) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                let _ = e.print();
+                continue;
+            }
+        };
+        if matches!(parsed.command, Commands::Shell(_)) {
+            output::print_error("Already in the shell");
+            continue;
+        }
+
+        let cli = Cli {
+            config: config_path.to_string(),
+            format: parsed.format.unwrap_or(format),
+            command: parsed.command,
+        };
+
+        // Boxed: `Cli::execute` is what called us.
+        tokio::select! {
+            result = Box::pin(cli.execute()) => {
+                if let Err(e) = result {
+                    output::print_error(&e.to_string());
+                }
+            }
+            _ = interrupted.notified() => {
+                output::print_warning("Cancelled");
+            }
+        }
+    }
+
+    if let Some(pool) = cached_pool() {
+        pool.close().await;
+    }
+    Ok(())
+}
+
+/// Drop the cached config and pool so the next command re-reads both.
+async fn reload(config_path: &str) {
+    let Some(session) = SESSION.get() else {
+        return;
+    };
+    *lock(&session.config) = None;
+    let pool = lock(&session.pool).take();
+    if let Some(pool) = pool {
+        pool.close().await;
+    }
+    match super::load_config(config_path).await {
+        Ok(_) => output::print_success(&format!("Reloaded '{}'", config_path)),
+        Err(e) => output::print_error(&e.to_string()),
+    }
+}
+
+/// Tab completion of subcommand names and long flags.
+struct CommandCompletion {
+    root: clap::Command,
+}
+
+impl CommandCompletion {
+    fn new() -> Self {
+        Self {
+            root: ShellLine::command(),
+        }
+    }
+}
+
+impl Completion for CommandCompletion {
+    fn get(&self, input: &str) -> Option<String> {
+        let (head, partial) = match input.rfind(' ') {
+            Some(i) => input.split_at(i + 1),
+            None => ("", input),
+        };
+
+        let mut command = &self.root;
+        for word in head.split_whitespace() {
+            if let Some(sub) = command.find_subcommand(word) {
+                command = sub;
+            }
+        }
+
+        let candidates: Vec<String> = if partial.starts_with('-') {
+            command
+                .get_arguments()
+                .filter_map(|arg| arg.get_long())
+                .map(|long| format!("--{}", long))
+                .collect()
+        } else {
+            let mut names: Vec<String> = command
+                .get_subcommands()
+                .map(|sub| sub.get_name().to_string())
+                .collect();
+            if head.trim().is_empty() {
+                names.extend(BUILTINS.iter().map(|b| b.to_string()));
+            }
+            names
+        };
+
+        let matches: Vec<&String> = candidates
+            .iter()
+            .filter(|c| c.starts_with(partial))
+            .collect();
+        match matches.as_slice() {
+            [] => None,
+            [only] => Some(format!("{}{} ", head, only)),
+            [first, rest @ ..] => {
+                let common = rest.iter().fold(first.len(), |len, c| {
+                    first
+                        .chars()
+                        .zip(c.chars())
+                        .take(len)
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                });
+                (common > partial.len()).then(|| format!("{}{}", head, &first[..common]))
+            }
+        }
+    }
+}
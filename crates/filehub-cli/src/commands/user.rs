@@ -1,12 +1,27 @@
 //! User management CLI commands.
 
+use std::sync::Arc;
+
 use clap::{Args, Subcommand};
 use serde::Serialize;
 use tabled::Tabled;
+use uuid::Uuid;
 
 use crate::output::{self, OutputFormat};
+use filehub_auth::password::{PasswordHasher, PasswordValidator};
+use filehub_auth::rbac::RbacEnforcer;
+use filehub_cache::keys;
+use filehub_cache::provider::CacheManager;
 use filehub_core::error::AppError;
+use filehub_core::traits::CacheProvider;
+use filehub_core::types::TenantId;
+use filehub_database::repositories::audit::AuditLogRepository;
+use filehub_database::repositories::session::SessionRepository;
 use filehub_database::repositories::user::UserRepository;
+use filehub_entity::user::User;
+use filehub_service::context::RequestContext;
+use filehub_service::session::SessionAudit;
+use filehub_service::user::{AdminUserService, ImportOptions};
 
 /// Arguments for user commands
 #[derive(Debug, Args)]
@@ -35,6 +50,21 @@ pub enum UserCommand {
         /// Username
         username: String,
     },
+    /// Create users from a CSV file with columns email, name, role and
+    /// optionally password and username
+    Import {
+        /// Path to the CSV file
+        file: String,
+        /// Admin account the users are created by
+        #[arg(short, long)]
+        admin: String,
+        /// Validate and report without creating users
+        #[arg(long)]
+        dry_run: bool,
+        /// Users created per transaction
+        #[arg(long, default_value_t = filehub_service::user::import::DEFAULT_IMPORT_BATCH_SIZE)]
+        batch_size: usize,
+    },
 }
 
 /// User display row for table output
@@ -54,6 +84,25 @@ struct UserRow {
     created_at: String,
 }
 
+/// Import result row for table output
+#[derive(Debug, Serialize, Tabled)]
+struct ImportRow {
+    /// Line in the file
+    line: usize,
+    /// Email
+    email: String,
+    /// Username
+    username: String,
+    /// Outcome
+    status: String,
+    /// Created user ID
+    user_id: String,
+    /// Generated temporary password
+    temporary_password: String,
+    /// Error
+    error: String,
+}
+
 /// Execute user commands
 pub async fn execute(
     args: &UserArgs,
@@ -96,6 +145,8 @@ pub async fn execute(
                 .update_status(user.id, filehub_entity::user::UserStatus::Active)
                 .await
                 .map_err(|e| AppError::internal(format!("Failed to enable user: {}", e)))?;
+            let cache = CacheManager::new(&config.cache).await?;
+            forget_cached_user(&cache, &user).await?;
 
             output::print_success(&format!("User '{}' enabled", username));
         }
@@ -111,9 +162,119 @@ pub async fn execute(
                 .await
                 .map_err(|e| AppError::internal(format!("Failed to disable user: {}", e)))?;
 
-            output::print_success(&format!("User '{}' disabled", username));
+            // End the user's sessions and drop what the server has cached
+            // for them, so the change takes effect on the next request. The
+            // server's seat pool catches up on its next reconciliation.
+            let session_repo = SessionRepository::new(pool.clone());
+            let sessions = session_repo.find_active_by_user(user.id).await?;
+            let cache = CacheManager::new(&config.cache).await?;
+            for session in &sessions {
+                session_repo
+                    .terminate(session.id, user.id, "Account disabled")
+                    .await?;
+                cache.delete(&keys::session_by_id(session.id)).await?;
+            }
+            forget_cached_user(&cache, &user).await?;
+
+            output::print_success(&format!(
+                "User '{}' disabled, {} session(s) ended",
+                username,
+                sessions.len()
+            ));
+        }
+        UserCommand::Import {
+            file,
+            admin,
+            dry_run,
+            batch_size,
+        } => {
+            let csv = tokio::fs::read_to_string(file)
+                .await
+                .map_err(|e| AppError::bad_request(format!("Cannot read '{}': {}", file, e)))?;
+            let admin = user_repo
+                .find_by_username(admin)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to find user: {}", e)))?
+                .ok_or_else(|| AppError::not_found(format!("User '{}' not found", admin)))?;
+
+            let service = AdminUserService::new(
+                Arc::new(user_repo),
+                Arc::new(
+                    PasswordHasher::from_config(&config.auth.password_hash)?
+                        .with_pepper(&config.auth.password_pepper)?,
+                ),
+                Arc::new(PasswordValidator::new(&config.auth)),
+                Arc::new(RbacEnforcer::new()),
+                Arc::new(SessionAudit::new(Arc::new(AuditLogRepository::new(
+                    pool.clone(),
+                )))),
+            );
+            let ctx = RequestContext::new(
+                admin.id,
+                Uuid::nil(),
+                admin.role,
+                admin.username,
+                // Audit rows store the address as `inet`.
+                "127.0.0.1".to_string(),
+                None,
+            )
+            .with_tenant(TenantId::from_uuid(admin.tenant_id));
+            let report = service
+                .import_csv(
+                    &ctx,
+                    &csv,
+                    ImportOptions {
+                        dry_run: *dry_run,
+                        batch_size: *batch_size,
+                    },
+                )
+                .await?;
+
+            match format {
+                OutputFormat::Json => output::print_item(&report, format),
+                OutputFormat::Table | OutputFormat::Csv => {
+                    let rows: Vec<ImportRow> = report
+                        .rows
+                        .iter()
+                        .map(|r| ImportRow {
+                            line: r.line,
+                            email: r.email.clone(),
+                            username: r.username.clone(),
+                            status: r.status.as_str().to_string(),
+                            user_id: r.user_id.map(|id| id.to_string()).unwrap_or_default(),
+                            temporary_password: r.temporary_password.clone().unwrap_or_default(),
+                            error: r.error.clone().unwrap_or_default(),
+                        })
+                        .collect();
+                    output::print_list(&rows, format);
+
+                    let verb = if report.dry_run {
+                        "would be created"
+                    } else {
+                        "created"
+                    };
+                    let summary = format!(
+                        "{} of {} users {}, {} failed",
+                        report.created, report.total, verb, report.failed
+                    );
+                    if report.failed > 0 {
+                        output::print_warning(&summary);
+                    } else {
+                        output::print_success(&summary);
+                    }
+                }
+            }
         }
     }
 
     Ok(())
 }
+
+/// Drops the server's cached copies of `user`, which carry its status.
+async fn forget_cached_user(cache: &CacheManager, user: &User) -> Result<(), AppError> {
+    cache.delete(&keys::user_by_id(user.id)).await?;
+    cache
+        .delete(&keys::user_by_username(&user.username))
+        .await?;
+    Ok(())
+}
@@ -1,4 +1,5 @@
 //! FileHub CLI library — command definitions and execution logic.
 
 pub mod commands;
+pub mod logging;
 pub mod output;
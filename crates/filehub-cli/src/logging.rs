@@ -0,0 +1,23 @@
+//! Runtime-adjustable log filter.
+
+use std::sync::OnceLock;
+
+use tracing_subscriber::reload::Handle;
+use tracing_subscriber::{EnvFilter, Registry};
+
+static FILTER_HANDLE: OnceLock<Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// Register the handle of the installed filter layer
+pub fn set_filter_handle(handle: Handle<EnvFilter, Registry>) {
+    let _ = FILTER_HANDLE.set(handle);
+}
+
+/// Replace the active filter with `directive` (e.g. `info` or `filehub_api=debug`)
+pub fn set_level(directive: &str) -> Result<(), String> {
+    let filter = EnvFilter::try_new(directive).map_err(|e| e.to_string())?;
+    FILTER_HANDLE
+        .get()
+        .ok_or_else(|| "log filter is not reloadable".to_string())?
+        .reload(filter)
+        .map_err(|e| e.to_string())
+}
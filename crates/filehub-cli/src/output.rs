@@ -1,4 +1,4 @@
-//! Table and JSON output formatting for CLI commands.
+//! Table, JSON and CSV output formatting for CLI commands.
 
 use serde::Serialize;
 use tabled::{Table, Tabled};
@@ -10,6 +10,8 @@ pub enum OutputFormat {
     Table,
     /// JSON output
     Json,
+    /// Comma-separated values with a header row
+    Csv,
 }
 
 impl Default for OutputFormat {
@@ -33,13 +35,20 @@ pub fn print_list<T: Serialize + Tabled>(items: &[T], format: OutputFormat) {
             let json = serde_json::to_string_pretty(items).unwrap_or_else(|_| "[]".to_string());
             println!("{}", json);
         }
+        OutputFormat::Csv => {
+            println!("{}", csv_row(T::headers()));
+            for item in items {
+                println!("{}", csv_row(item.fields()));
+            }
+        }
     }
 }
 
-/// Print a single item in the selected format
+/// Print a single item in the selected format; CSV falls back to the table
+/// rendering since a lone item has no fixed columns
 pub fn print_item<T: Serialize + std::fmt::Debug>(item: &T, format: OutputFormat) {
     match format {
-        OutputFormat::Table => {
+        OutputFormat::Table | OutputFormat::Csv => {
             println!("{:#?}", item);
         }
         OutputFormat::Json => {
@@ -49,6 +58,22 @@ pub fn print_item<T: Serialize + std::fmt::Debug>(item: &T, format: OutputFormat
     }
 }
 
+/// Join fields into one CSV line, quoting where needed
+pub fn csv_row<S: AsRef<str>>(fields: impl IntoIterator<Item = S>) -> String {
+    fields
+        .into_iter()
+        .map(|field| {
+            let field = field.as_ref();
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 /// Print a success message
 pub fn print_success(msg: &str) {
     println!("✓ {}", msg);
@@ -1,5 +1,9 @@
 //! Server, TLS, and CORS configuration.
 
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+use ipnet::IpNet;
 use serde::{Deserialize, Serialize};
 
 /// HTTP server configuration.
@@ -17,10 +21,17 @@ pub struct ServerConfig {
     /// Maximum concurrent connections.
     #[serde(default = "default_max_connections")]
     pub max_connections: usize,
-    /// Request timeout in seconds.
+    /// Seconds a request may take before it is abandoned with
+    /// `504 Gateway Timeout`; 0 disables. Admin and transfer routes have
+    /// their own limits in `timeouts`.
     #[serde(default = "default_request_timeout")]
     pub request_timeout_seconds: u64,
-    /// Graceful shutdown timeout in seconds.
+    /// Timeouts of route groups that differ from `request_timeout_seconds`.
+    #[serde(default)]
+    pub timeouts: RouteTimeoutConfig,
+    /// Seconds a shutdown drains for: WebSocket clients are told to
+    /// reconnect elsewhere and in-flight jobs may finish; jobs still
+    /// running afterwards are put back in the queue.
     #[serde(default = "default_shutdown_grace")]
     pub shutdown_grace_seconds: u64,
     /// TLS configuration.
@@ -29,6 +40,123 @@ pub struct ServerConfig {
     /// CORS configuration.
     #[serde(default)]
     pub cors: CorsConfig,
+    /// Prometheus metrics endpoint configuration.
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    /// Request rate limiting configuration.
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    /// Per-user API call and bandwidth quotas.
+    #[serde(default)]
+    pub quotas: QuotaConfig,
+    /// Response compression configuration.
+    #[serde(default)]
+    pub compression: CompressionConfig,
+    /// gRPC listener configuration.
+    #[serde(default)]
+    pub grpc: GrpcConfig,
+    /// `Idempotency-Key` handling for unsafe POST requests.
+    #[serde(default)]
+    pub idempotency: IdempotencyConfig,
+    /// Health and probe endpoint configuration.
+    #[serde(default)]
+    pub health: HealthConfig,
+    /// Reverse proxies trusted to report the client address.
+    #[serde(default)]
+    pub proxy: ProxyConfig,
+}
+
+/// Reverse proxies whose forwarding header is believed.
+///
+/// The client address of a request is its socket peer unless the peer is
+/// a trusted proxy. Then the forwarded chain in `header` is walked from
+/// the nearest hop outwards, and the first address that is not a trusted
+/// proxy is the client. Headers from untrusted peers are ignored, so a
+/// client cannot choose its own address.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    /// Trusted proxy networks in CIDR notation (`10.0.0.0/8`), or single
+    /// addresses. Empty trusts no proxy.
+    #[serde(default)]
+    pub trusted: Vec<String>,
+    /// Header the proxies put the client address in.
+    #[serde(default)]
+    pub header: ForwardedHeader,
+}
+
+impl ProxyConfig {
+    /// The trusted networks, or the first entry that is not a network or
+    /// an address.
+    pub fn trusted_networks(&self) -> Result<Vec<IpNet>, String> {
+        self.trusted
+            .iter()
+            .map(|entry| {
+                let entry = entry.trim();
+                entry
+                    .parse::<IpNet>()
+                    .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+                    .map_err(|_| entry.to_string())
+            })
+            .collect()
+    }
+}
+
+/// Header carrying the forwarded client address.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ForwardedHeader {
+    /// `X-Forwarded-For: client, proxy1, proxy2`.
+    #[default]
+    XForwardedFor,
+    /// RFC 7239 `Forwarded: for=client, for=proxy1`.
+    Forwarded,
+    /// `X-Real-IP: client`, set by a single proxy.
+    XRealIp,
+}
+
+/// Health endpoint configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthConfig {
+    /// Milliseconds each dependency check may take before it counts as
+    /// down, so that a probe never hangs on an unreachable dependency.
+    #[serde(default = "default_health_check_timeout")]
+    pub check_timeout_ms: u64,
+    /// Seconds the oldest due job may wait before the worker check
+    /// reports the queue as backed up.
+    #[serde(default = "default_worker_backlog")]
+    pub worker_backlog_seconds: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            check_timeout_ms: default_health_check_timeout(),
+            worker_backlog_seconds: default_worker_backlog(),
+        }
+    }
+}
+
+/// Request timeouts of route groups with longer-running requests. A
+/// value of 0 disables the timeout for that group.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteTimeoutConfig {
+    /// Seconds for `/api/admin` requests, which include reports and
+    /// bulk operations.
+    #[serde(default = "default_admin_timeout")]
+    pub admin_seconds: u64,
+    /// Seconds for uploads, downloads and exports, which stream for as
+    /// long as the transfer lasts.
+    #[serde(default)]
+    pub transfer_seconds: u64,
+}
+
+impl Default for RouteTimeoutConfig {
+    fn default() -> Self {
+        Self {
+            admin_seconds: default_admin_timeout(),
+            transfer_seconds: 0,
+        }
+    }
 }
 
 /// TLS termination configuration.
@@ -46,8 +174,70 @@ pub struct TlsConfig {
 }
 
 /// CORS (Cross-Origin Resource Sharing) configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The top-level policy applies to every route group without its own
+/// policy. A group policy replaces the top-level one entirely; fields it
+/// leaves out take their defaults, not the top-level values.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CorsConfig {
+    /// Policy for groups without their own.
+    #[serde(flatten)]
+    pub default: CorsPolicy,
+    /// Policy for public endpoints: share links and health checks.
+    #[serde(default)]
+    pub public: Option<CorsPolicy>,
+    /// Policy for endpoints used by signed-in users.
+    #[serde(default)]
+    pub authenticated: Option<CorsPolicy>,
+    /// Policy for `/api/admin` endpoints.
+    #[serde(default)]
+    pub admin: Option<CorsPolicy>,
+}
+
+/// Route groups with separate CORS policies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorsGroup {
+    /// Share links and health checks.
+    Public,
+    /// Endpoints used by signed-in users.
+    Authenticated,
+    /// `/api/admin` endpoints.
+    Admin,
+}
+
+impl CorsGroup {
+    /// All groups.
+    pub const ALL: [CorsGroup; 3] = [Self::Public, Self::Authenticated, Self::Admin];
+
+    /// Name of the group's configuration key.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Authenticated => "authenticated",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+impl CorsConfig {
+    /// The policy that applies to `group`.
+    pub fn policy(&self, group: CorsGroup) -> &CorsPolicy {
+        self.group_policy(group).unwrap_or(&self.default)
+    }
+
+    /// The policy configured for `group` itself, if any.
+    pub fn group_policy(&self, group: CorsGroup) -> Option<&CorsPolicy> {
+        match group {
+            CorsGroup::Public => self.public.as_ref(),
+            CorsGroup::Authenticated => self.authenticated.as_ref(),
+            CorsGroup::Admin => self.admin.as_ref(),
+        }
+    }
+}
+
+/// CORS policy of one route group.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorsPolicy {
     /// Allowed origins (use `["*"]` for development only).
     #[serde(default = "default_allowed_origins")]
     pub allowed_origins: Vec<String>,
@@ -60,15 +250,249 @@ pub struct CorsConfig {
     /// Max age for preflight cache in seconds.
     #[serde(default = "default_max_age")]
     pub max_age_seconds: u64,
+    /// Whether browsers may send cookies and HTTP authentication. Cannot
+    /// be combined with a `"*"` origin.
+    #[serde(default)]
+    pub allow_credentials: bool,
 }
 
-impl Default for CorsConfig {
+impl Default for CorsPolicy {
     fn default() -> Self {
         Self {
             allowed_origins: default_allowed_origins(),
             allowed_methods: default_allowed_methods(),
             allowed_headers: default_allowed_headers(),
             max_age_seconds: default_max_age(),
+            allow_credentials: false,
+        }
+    }
+}
+
+/// Prometheus metrics endpoint configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    /// Whether metrics are collected and exposed.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Path the metrics endpoint is served on.
+    #[serde(default = "default_metrics_path")]
+    pub path: String,
+    /// Separate port for the metrics endpoint; `None` disables the
+    /// metrics listener.
+    #[serde(default = "default_metrics_port")]
+    pub admin_port: Option<u16>,
+    /// Bind address for the separate metrics listener.
+    #[serde(default = "default_metrics_host")]
+    pub admin_host: String,
+    /// Also serve the endpoint, unauthenticated, on the public listener.
+    #[serde(default)]
+    pub public: bool,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: default_metrics_path(),
+            admin_port: default_metrics_port(),
+            admin_host: default_metrics_host(),
+            public: false,
+        }
+    }
+}
+
+/// gRPC API configuration. The gRPC surface is only compiled in with
+/// the `grpc` feature and is served on its own plaintext HTTP/2 listener.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcConfig {
+    /// Whether the gRPC listener is started.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Bind address.
+    #[serde(default = "default_host")]
+    pub host: String,
+    /// Bind port.
+    #[serde(default = "default_grpc_port")]
+    pub port: u16,
+    /// Largest single message accepted, in bytes.
+    #[serde(default = "default_grpc_max_message_bytes")]
+    pub max_message_bytes: usize,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: default_host(),
+            port: default_grpc_port(),
+            max_message_bytes: default_grpc_max_message_bytes(),
+        }
+    }
+}
+
+/// `Idempotency-Key` handling.
+///
+/// The first response to a request carrying the header is kept for
+/// `ttl_seconds` and replayed to retries with the same key, per user and
+/// route.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdempotencyConfig {
+    /// Whether the header is honoured.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// How long a response is kept for replay, in seconds.
+    #[serde(default = "default_idempotency_ttl")]
+    pub ttl_seconds: u64,
+    /// Largest request or response body handled; larger requests are
+    /// rejected and larger responses are not kept. Single-shot uploads
+    /// are bounded by `storage.max_upload_size_bytes` instead.
+    #[serde(default = "default_idempotency_max_body")]
+    pub max_body_bytes: usize,
+}
+
+impl Default for IdempotencyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_seconds: default_idempotency_ttl(),
+            max_body_bytes: default_idempotency_max_body(),
+        }
+    }
+}
+
+/// Request rate limiting configuration.
+///
+/// Limits are tracked per authenticated user, or per client IP for
+/// anonymous requests, and shared across nodes through the cache.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// Whether rate limiting is enforced.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Limit for read requests (GET/HEAD).
+    #[serde(default = "default_read_limit")]
+    pub read: RateLimitRule,
+    /// Limit for other state-changing requests.
+    #[serde(default = "default_write_limit")]
+    pub write: RateLimitRule,
+    /// Limit for login and token refresh.
+    #[serde(default = "default_auth_limit")]
+    pub auth: RateLimitRule,
+    /// Limit for file uploads (single and chunked).
+    #[serde(default = "default_upload_limit")]
+    pub upload: RateLimitRule,
+    /// Limit for anonymous access to public files and folders.
+    #[serde(default = "default_public_limit")]
+    pub public: RateLimitRule,
+    /// Header carrying a service-account API key.
+    #[serde(default = "default_api_key_header")]
+    pub api_key_header: String,
+    /// Trusted service-account API keys that bypass rate limiting.
+    #[serde(default)]
+    pub bypass_api_keys: Vec<String>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            read: default_read_limit(),
+            write: default_write_limit(),
+            auth: default_auth_limit(),
+            upload: default_upload_limit(),
+            public: default_public_limit(),
+            api_key_header: default_api_key_header(),
+            bypass_api_keys: Vec::new(),
+        }
+    }
+}
+
+/// A token bucket: up to `requests` requests in a burst, refilled at
+/// `requests` per `window_seconds`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RateLimitRule {
+    /// Bucket size: requests allowed in a burst.
+    pub requests: u32,
+    /// Seconds to refill an empty bucket.
+    pub window_seconds: u64,
+}
+
+/// Daily and monthly API quotas of authenticated users.
+///
+/// Each user is on a tier, the one an admin assigned or `default_tier`.
+/// Calls and bytes transferred are counted per user and UTC day; a user
+/// whose tier allowance for the day or month is used up gets
+/// `429 Too Many Requests` until the period resets. Counts are kept in
+/// memory and flushed to the database every `flush_interval_seconds`, so
+/// at most that much usage is lost in a crash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaConfig {
+    /// Whether quotas are counted and enforced.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Tier of users without an assigned one.
+    #[serde(default = "default_quota_tier")]
+    pub default_tier: String,
+    /// Tiers by name.
+    #[serde(default = "default_quota_tiers")]
+    pub tiers: BTreeMap<String, QuotaTier>,
+    /// Seconds between flushes of counted usage to the database.
+    #[serde(default = "default_quota_flush_interval")]
+    pub flush_interval_seconds: u64,
+}
+
+impl Default for QuotaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_tier: default_quota_tier(),
+            tiers: default_quota_tiers(),
+            flush_interval_seconds: default_quota_flush_interval(),
+        }
+    }
+}
+
+/// Allowances of a quota tier; 0 is unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaTier {
+    /// API calls per UTC day.
+    #[serde(default)]
+    pub daily_calls: u64,
+    /// API calls per UTC calendar month.
+    #[serde(default)]
+    pub monthly_calls: u64,
+    /// Request and response body bytes per UTC day.
+    #[serde(default)]
+    pub daily_bytes: u64,
+    /// Request and response body bytes per UTC calendar month.
+    #[serde(default)]
+    pub monthly_bytes: u64,
+}
+
+/// Response compression configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompressionConfig {
+    /// Whether responses are compressed.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Algorithms in order of server preference (`br`, `gzip`).
+    #[serde(default = "default_compression_algorithms")]
+    pub algorithms: Vec<String>,
+    /// Bodies smaller than this many bytes are sent uncompressed.
+    #[serde(default = "default_compression_min_size")]
+    pub min_size_bytes: u64,
+    /// Content-type prefixes that are already compressed and skipped.
+    #[serde(default = "default_compression_skip_types")]
+    pub skip_content_types: Vec<String>,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            algorithms: default_compression_algorithms(),
+            min_size_bytes: default_compression_min_size(),
+            skip_content_types: default_compression_skip_types(),
         }
     }
 }
@@ -89,6 +513,18 @@ fn default_request_timeout() -> u64 {
     30
 }
 
+fn default_admin_timeout() -> u64 {
+    120
+}
+
+fn default_health_check_timeout() -> u64 {
+    1000
+}
+
+fn default_worker_backlog() -> u64 {
+    600
+}
+
 fn default_shutdown_grace() -> u64 {
     30
 }
@@ -115,3 +551,120 @@ fn default_allowed_headers() -> Vec<String> {
 fn default_max_age() -> u64 {
     3600
 }
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_metrics_path() -> String {
+    "/metrics".to_string()
+}
+
+fn default_metrics_port() -> Option<u16> {
+    Some(9090)
+}
+
+fn default_metrics_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_idempotency_ttl() -> u64 {
+    24 * 60 * 60
+}
+
+fn default_idempotency_max_body() -> usize {
+    1024 * 1024
+}
+
+fn default_grpc_port() -> u16 {
+    50051
+}
+
+fn default_grpc_max_message_bytes() -> usize {
+    4 * 1024 * 1024
+}
+
+fn default_read_limit() -> RateLimitRule {
+    RateLimitRule {
+        requests: 600,
+        window_seconds: 60,
+    }
+}
+
+fn default_write_limit() -> RateLimitRule {
+    RateLimitRule {
+        requests: 120,
+        window_seconds: 60,
+    }
+}
+
+fn default_public_limit() -> RateLimitRule {
+    RateLimitRule {
+        requests: 60,
+        window_seconds: 60,
+    }
+}
+
+fn default_auth_limit() -> RateLimitRule {
+    RateLimitRule {
+        requests: 10,
+        window_seconds: 60,
+    }
+}
+
+fn default_upload_limit() -> RateLimitRule {
+    RateLimitRule {
+        requests: 30,
+        window_seconds: 60,
+    }
+}
+
+fn default_quota_tier() -> String {
+    "standard".to_string()
+}
+
+fn default_quota_tiers() -> BTreeMap<String, QuotaTier> {
+    BTreeMap::from([(
+        default_quota_tier(),
+        QuotaTier {
+            daily_calls: 50_000,
+            monthly_calls: 1_000_000,
+            daily_bytes: 10 * 1024 * 1024 * 1024,
+            monthly_bytes: 100 * 1024 * 1024 * 1024,
+        },
+    )])
+}
+
+fn default_quota_flush_interval() -> u64 {
+    30
+}
+
+fn default_api_key_header() -> String {
+    "x-api-key".to_string()
+}
+
+fn default_compression_algorithms() -> Vec<String> {
+    vec!["br".to_string(), "gzip".to_string()]
+}
+
+fn default_compression_min_size() -> u64 {
+    1024
+}
+
+fn default_compression_skip_types() -> Vec<String> {
+    [
+        "image/",
+        "video/",
+        "audio/",
+        "application/zip",
+        "application/gzip",
+        "application/x-7z-compressed",
+        "application/x-rar-compressed",
+        "application/x-bzip2",
+        "application/grpc",
+        "text/event-stream",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
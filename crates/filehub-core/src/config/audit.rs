@@ -0,0 +1,149 @@
+//! Audit log shipping configuration.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Audit log settings.
+///
+/// Every audit event is stored in the database. Events are also shipped to
+/// each configured sink by the background worker, so shipping needs
+/// `worker.enabled`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditConfig {
+    /// External destinations events are copied to.
+    pub sinks: Vec<AuditSinkConfig>,
+    /// Delivery attempts per event and sink before an admin is alerted.
+    pub max_attempts: u32,
+    /// Minimum minutes between two alerts about the same sink.
+    pub alert_interval_minutes: u64,
+    /// Archival of old entries out of the database.
+    pub retention: AuditRetentionConfig,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            sinks: Vec::new(),
+            max_attempts: 5,
+            alert_interval_minutes: 15,
+            retention: AuditRetentionConfig::default(),
+        }
+    }
+}
+
+/// Audit log retention.
+///
+/// When enabled, the background worker writes entries older than
+/// `retain_days` to the archive as gzipped NDJSON in the audit event
+/// schema, reads each archive back to check it, and only then deletes the
+/// entries from the database. Entries about a file or folder under a
+/// legal hold stay in the database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditRetentionConfig {
+    /// Whether old entries are archived and deleted.
+    pub enabled: bool,
+    /// Days entries stay in the database.
+    pub retain_days: u32,
+    /// Entries per archive.
+    pub batch_size: u32,
+    /// Where archives are written.
+    pub archive: AuditArchiveConfig,
+}
+
+impl Default for AuditRetentionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            retain_days: 365,
+            batch_size: 10_000,
+            archive: AuditArchiveConfig::Storage {
+                storage_id: None,
+                prefix: default_archive_prefix(),
+            },
+        }
+    }
+}
+
+/// Destination of audit archives.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditArchiveConfig {
+    /// A storage backend.
+    Storage {
+        /// Storage ID; the default storage if unset.
+        #[serde(default)]
+        storage_id: Option<Uuid>,
+        /// Path prefix of archives within the storage.
+        #[serde(default = "default_archive_prefix")]
+        prefix: String,
+    },
+    /// A directory on the server, e.g. a mounted archive volume.
+    Directory {
+        /// Directory path.
+        path: String,
+    },
+}
+
+/// One external audit destination. Events are written as JSON in the
+/// versioned audit event schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditSinkConfig {
+    /// POST each event to an HTTP endpoint.
+    Webhook {
+        /// Unique sink name.
+        name: String,
+        /// Endpoint URL.
+        url: String,
+        /// Extra request headers, e.g. an authorization token.
+        #[serde(default)]
+        headers: HashMap<String, String>,
+        /// Request timeout in seconds.
+        #[serde(default = "default_timeout")]
+        timeout_seconds: u64,
+    },
+    /// Send each event as an RFC 5424 syslog message over UDP.
+    Syslog {
+        /// Unique sink name.
+        name: String,
+        /// Collector address, `host:port`.
+        address: String,
+        /// Syslog facility number (13 is "log audit").
+        #[serde(default = "default_facility")]
+        facility: u8,
+    },
+    /// Append each event as a line to a file.
+    File {
+        /// Unique sink name.
+        name: String,
+        /// File path.
+        path: String,
+    },
+}
+
+impl AuditSinkConfig {
+    /// Sink name, used to route queued events.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Webhook { name, .. } | Self::Syslog { name, .. } | Self::File { name, .. } => {
+                name
+            }
+        }
+    }
+}
+
+fn default_timeout() -> u64 {
+    10
+}
+
+fn default_facility() -> u8 {
+    13
+}
+
+fn default_archive_prefix() -> String {
+    "audit-archive".to_string()
+}
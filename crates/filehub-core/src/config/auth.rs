@@ -1,13 +1,17 @@
 //! Authentication configuration.
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+use super::secret::Secret;
+
 /// Authentication and credential configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthConfig {
     /// Secret key for JWT signing (HMAC-SHA256).
     #[serde(default = "default_jwt_secret")]
-    pub jwt_secret: String,
+    pub jwt_secret: Secret,
     /// Access token TTL in minutes.
     #[serde(default = "default_access_ttl")]
     pub jwt_access_ttl_minutes: u64,
@@ -23,10 +27,226 @@ pub struct AuthConfig {
     /// Account lockout duration in minutes.
     #[serde(default = "default_lockout")]
     pub lockout_duration_minutes: u64,
+    /// Argon2id cost parameters for new password hashes.
+    #[serde(default)]
+    pub password_hash: PasswordHashConfig,
+    /// Application-wide pepper for password hashes.
+    #[serde(default)]
+    pub password_pepper: PepperConfig,
+    /// GeoIP-based detection of unusual login locations.
+    #[serde(default)]
+    pub login_anomaly: LoginAnomalyConfig,
+    /// Administrators acting as other users.
+    #[serde(default)]
+    pub impersonation: ImpersonationConfig,
+}
+
+/// Argon2id cost parameters.
+///
+/// Raising any of them makes existing hashes "weaker than configured";
+/// such a hash is replaced with one at the new cost the next time its
+/// owner logs in, so the work factor can be raised without password resets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PasswordHashConfig {
+    /// Memory cost in KiB.
+    #[serde(default = "default_hash_memory")]
+    pub memory_kib: u32,
+    /// Number of passes over memory.
+    #[serde(default = "default_hash_iterations")]
+    pub iterations: u32,
+    /// Degree of parallelism (lanes).
+    #[serde(default = "default_hash_parallelism")]
+    pub parallelism: u32,
+}
+
+impl Default for PasswordHashConfig {
+    fn default() -> Self {
+        Self {
+            memory_kib: default_hash_memory(),
+            iterations: default_hash_iterations(),
+            parallelism: default_hash_parallelism(),
+        }
+    }
+}
+
+/// Application-wide pepper mixed into password hashes as Argon2's secret
+/// key, so a leaked database alone is not enough to test guesses.
+///
+/// Each version is a secret named by a short id, which is recorded in the
+/// hashes made with it. Hashes made with an older version, or before a
+/// pepper was configured, still verify and are rehashed with `current` at
+/// their owner's next login. Retire a version only once no hash uses it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PepperConfig {
+    /// Version used for new hashes; new hashes are not peppered when unset.
+    pub current: Option<String>,
+    /// Pepper secrets by version id (1-8 letters, digits, `-` or `_`).
+    pub versions: BTreeMap<String, Secret>,
+}
+
+/// What happens to a login flagged as anomalous.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyAction {
+    /// Let the login through; only notify the user and audit it.
+    #[default]
+    Alert,
+    /// Also refuse the login with `AUTH_MFA_REQUIRED` until the user
+    /// re-verifies with a second factor.
+    RequireMfa,
+}
+
+/// Login anomaly detection.
+///
+/// Each successful login's client IP is resolved to a location with a
+/// MaxMind GeoLite2/GeoIP2 City database and compared against the user's
+/// recent login locations. A login from a country the user has not logged
+/// in from recently, or one that would have required travelling faster
+/// than `max_speed_kmh` since the previous login, is reported to the user
+/// and the audit log. Requires the `geoip` build feature; without it, or
+/// when the database cannot be opened, logins are not checked.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoginAnomalyConfig {
+    /// Whether logins are checked.
+    pub enabled: bool,
+    /// Path to the `.mmdb` City database.
+    pub geoip_database: String,
+    /// Number of recent login locations compared against.
+    pub history_size: u32,
+    /// Fastest plausible travel speed between two logins, in km/h.
+    pub max_speed_kmh: f64,
+    /// Distance below which two logins are never impossible travel, in
+    /// km. Absorbs the inaccuracy of IP geolocation.
+    pub min_distance_km: f64,
+    /// Whether a login from a country not in the history is an anomaly.
+    pub alert_on_new_country: bool,
+    /// What to do with an anomalous login.
+    pub action: AnomalyAction,
+}
+
+impl Default for LoginAnomalyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            geoip_database: "./data/GeoLite2-City.mmdb".to_string(),
+            history_size: 10,
+            max_speed_kmh: 1000.0,
+            min_distance_km: 300.0,
+            alert_on_new_country: true,
+            action: AnomalyAction::Alert,
+        }
+    }
+}
+
+/// Impersonation: an administrator with the `user_impersonate` permission
+/// opening a time-limited session as another user, to see what they see.
+///
+/// The session's tokens name both users; everything done with it is
+/// audited as the administrator acting as the user. Impersonation sessions
+/// take no license seat, do not count towards the user's session limit,
+/// cannot be refreshed, and can be ended early by any administrator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImpersonationConfig {
+    /// Whether administrators may impersonate users.
+    pub enabled: bool,
+    /// Session length when the administrator does not ask for one.
+    pub default_minutes: u32,
+    /// Longest session an administrator may ask for.
+    pub max_minutes: u32,
+    /// Requests refused in impersonation sessions, as `METHOD /path`
+    /// patterns (see [`RoutePattern`]).
+    pub blocked_routes: Vec<String>,
+}
+
+impl Default for ImpersonationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_minutes: 30,
+            max_minutes: 120,
+            blocked_routes: [
+                "PUT /api/users/me/password",
+                "DELETE /api/users/me/sessions/*",
+                "DELETE /api/files/*",
+                "DELETE /api/folders/*",
+                "DELETE /api/shares/*",
+                "* /api/permissions/**",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        }
+    }
 }
 
-fn default_jwt_secret() -> String {
-    "CHANGE_ME_IN_PRODUCTION".to_string()
+/// A `METHOD /path` request pattern.
+///
+/// The method is matched case-insensitively, `*` matching any. In the
+/// path, a `*` segment matches any one segment and a final `**` segment
+/// any number of them, including none.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutePattern {
+    /// Upper-case method, `None` for any.
+    method: Option<String>,
+    /// Path segments.
+    segments: Vec<String>,
+}
+
+impl RoutePattern {
+    /// Parses `METHOD /path`.
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let (method, path) = pattern
+            .trim()
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| format!("'{pattern}' is not 'METHOD /path'"))?;
+        let path = path.trim();
+        if !path.starts_with('/') {
+            return Err(format!("path of '{pattern}' must start with '/'"));
+        }
+        let method = match method {
+            "*" => None,
+            m if m.chars().all(|c| c.is_ascii_alphabetic()) => Some(m.to_ascii_uppercase()),
+            m => return Err(format!("'{m}' is not an HTTP method")),
+        };
+        let segments: Vec<String> = path
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect();
+        if let Some(i) = segments.iter().position(|s| s == "**")
+            && i + 1 != segments.len()
+        {
+            return Err(format!("'**' must be the last segment of '{pattern}'"));
+        }
+        Ok(Self { method, segments })
+    }
+
+    /// Whether a request for `path` with `method` matches.
+    pub fn matches(&self, method: &str, path: &str) -> bool {
+        if let Some(m) = &self.method
+            && !m.eq_ignore_ascii_case(method)
+        {
+            return false;
+        }
+        let mut path = path.split('/').filter(|s| !s.is_empty());
+        for segment in &self.segments {
+            match (segment.as_str(), path.next()) {
+                ("**", _) => return true,
+                (_, None) => return false,
+                ("*", Some(_)) => {}
+                (expected, Some(actual)) if expected == actual => {}
+                _ => return false,
+            }
+        }
+        path.next().is_none()
+    }
+}
+
+fn default_jwt_secret() -> Secret {
+    Secret::new("CHANGE_ME_IN_PRODUCTION")
 }
 
 fn default_access_ttl() -> u64 {
@@ -48,3 +268,48 @@ fn default_max_failed() -> i32 {
 fn default_lockout() -> u64 {
     30
 }
+
+// Argon2's own defaults (OWASP's minimum recommendation for Argon2id).
+fn default_hash_memory() -> u32 {
+    19 * 1024
+}
+
+fn default_hash_iterations() -> u32 {
+    2
+}
+
+fn default_hash_parallelism() -> u32 {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_pattern() {
+        let delete = RoutePattern::parse("delete /api/files/*").unwrap();
+        assert!(delete.matches("DELETE", "/api/files/42"));
+        assert!(delete.matches("DELETE", "/api/files/42/"));
+        assert!(!delete.matches("GET", "/api/files/42"));
+        assert!(!delete.matches("DELETE", "/api/files"));
+        assert!(!delete.matches("DELETE", "/api/files/42/versions"));
+
+        let any = RoutePattern::parse("* /api/permissions/**").unwrap();
+        assert!(any.matches("POST", "/api/permissions"));
+        assert!(any.matches("DELETE", "/api/permissions/entry/1"));
+        assert!(!any.matches("GET", "/api/files"));
+
+        assert!(RoutePattern::parse("/api/files").is_err());
+        assert!(RoutePattern::parse("GET api/files").is_err());
+        assert!(RoutePattern::parse("G-T /api").is_err());
+        assert!(RoutePattern::parse("GET /api/**/files").is_err());
+    }
+
+    #[test]
+    fn test_default_blocked_routes_parse() {
+        for route in ImpersonationConfig::default().blocked_routes {
+            assert!(RoutePattern::parse(&route).is_ok(), "{route}");
+        }
+    }
+}
@@ -17,6 +17,121 @@ pub struct CacheConfig {
     /// In-memory cache configuration.
     #[serde(default)]
     pub memory: MemoryCacheConfig,
+    /// Circuit breaker around Redis calls (cache and seat allocator).
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// Lifetime of each kind of cached entry.
+    #[serde(default)]
+    pub ttl: CacheTtlConfig,
+}
+
+/// Lifetime in seconds of each kind of cached entry.
+///
+/// Entries whose lifetime follows from what they hold (rate limit
+/// windows, idempotency records, revoked tokens) are not listed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheTtlConfig {
+    /// Users, by id and by username.
+    #[serde(default = "default_user_ttl")]
+    pub user: u64,
+    /// Sessions.
+    #[serde(default = "default_session_ttl")]
+    pub session: u64,
+    /// Resolved permissions.
+    #[serde(default = "default_permission_ttl")]
+    pub permission: u64,
+    /// File entities.
+    #[serde(default = "default_entity_ttl")]
+    pub file: u64,
+    /// Folder entities and folder listings.
+    #[serde(default = "default_entity_ttl")]
+    pub folder: u64,
+    /// Loaded folder subtrees.
+    #[serde(default = "default_folder_tree_ttl")]
+    pub folder_tree: u64,
+    /// A storage's folder tree generation; must outlive the subtrees
+    /// cached under it.
+    #[serde(default = "default_folder_tree_generation_ttl")]
+    pub folder_tree_generation: u64,
+    /// Storage entities and the storage list.
+    #[serde(default = "default_storage_ttl")]
+    pub storage: u64,
+    /// Share entities.
+    #[serde(default = "default_entity_ttl")]
+    pub share: u64,
+    /// License pool and seat counters.
+    #[serde(default = "default_license_ttl")]
+    pub license: u64,
+    /// Presence state.
+    #[serde(default = "default_presence_ttl")]
+    pub presence: u64,
+    /// Unread counts and notification preferences.
+    #[serde(default = "default_entity_ttl")]
+    pub notification: u64,
+    /// Rendered thumbnails.
+    #[serde(default = "default_thumbnail_ttl")]
+    pub thumbnail: u64,
+}
+
+impl Default for CacheTtlConfig {
+    fn default() -> Self {
+        Self {
+            user: default_user_ttl(),
+            session: default_session_ttl(),
+            permission: default_permission_ttl(),
+            file: default_entity_ttl(),
+            folder: default_entity_ttl(),
+            folder_tree: default_folder_tree_ttl(),
+            folder_tree_generation: default_folder_tree_generation_ttl(),
+            storage: default_storage_ttl(),
+            share: default_entity_ttl(),
+            license: default_license_ttl(),
+            presence: default_presence_ttl(),
+            notification: default_entity_ttl(),
+            thumbnail: default_thumbnail_ttl(),
+        }
+    }
+}
+
+impl CacheTtlConfig {
+    /// Every lifetime with its field name.
+    pub fn entries(&self) -> [(&'static str, u64); 13] {
+        [
+            ("user", self.user),
+            ("session", self.session),
+            ("permission", self.permission),
+            ("file", self.file),
+            ("folder", self.folder),
+            ("folder_tree", self.folder_tree),
+            ("folder_tree_generation", self.folder_tree_generation),
+            ("storage", self.storage),
+            ("share", self.share),
+            ("license", self.license),
+            ("presence", self.presence),
+            ("notification", self.notification),
+            ("thumbnail", self.thumbnail),
+        ]
+    }
+}
+
+/// When to stop calling an unavailable Redis and when to retry it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that switch to degraded mode.
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    /// Seconds to stay degraded before probing Redis again.
+    #[serde(default = "default_open_seconds")]
+    pub open_seconds: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: default_failure_threshold(),
+            open_seconds: default_open_seconds(),
+        }
+    }
 }
 
 /// Redis cache backend configuration.
@@ -90,3 +205,51 @@ fn default_max_capacity() -> u64 {
 fn default_memory_ttl() -> u64 {
     300
 }
+
+fn default_failure_threshold() -> u32 {
+    5
+}
+
+fn default_open_seconds() -> u64 {
+    30
+}
+
+fn default_user_ttl() -> u64 {
+    900
+}
+
+fn default_session_ttl() -> u64 {
+    300
+}
+
+fn default_permission_ttl() -> u64 {
+    300
+}
+
+fn default_entity_ttl() -> u64 {
+    300
+}
+
+fn default_folder_tree_ttl() -> u64 {
+    600
+}
+
+fn default_folder_tree_generation_ttl() -> u64 {
+    86_400
+}
+
+fn default_storage_ttl() -> u64 {
+    600
+}
+
+fn default_license_ttl() -> u64 {
+    30
+}
+
+fn default_presence_ttl() -> u64 {
+    120
+}
+
+fn default_thumbnail_ttl() -> u64 {
+    3600
+}
@@ -19,6 +19,44 @@ pub struct DatabaseConfig {
     /// Idle connection timeout in seconds.
     #[serde(default = "default_idle_timeout")]
     pub idle_timeout_seconds: u64,
+    /// Attempts at reaching the database at startup, with exponential
+    /// backoff between them, before giving up.
+    #[serde(default = "default_connect_attempts")]
+    pub connect_attempts: u32,
+    /// Open `min_connections` connections at startup and run a trivial
+    /// query on each, so the first requests do not pay for them.
+    #[serde(default = "default_warmup")]
+    pub warmup: bool,
+    /// Prepared statements cached per connection; 0 disables the cache.
+    #[serde(default = "default_statement_cache_capacity")]
+    pub statement_cache_capacity: usize,
+    /// Read replica connection URLs. Queries that tolerate stale data are
+    /// spread across these; empty sends every query to `url`.
+    #[serde(default)]
+    pub replica_urls: Vec<String>,
+    /// How long an unreachable replica is skipped before it is tried again.
+    #[serde(default = "default_replica_retry")]
+    pub replica_retry_seconds: u64,
+    /// Log repository queries slower than this many milliseconds at warn.
+    /// Unset disables slow-query logging.
+    #[serde(default)]
+    pub slow_query_threshold_ms: Option<u64>,
+}
+
+fn default_connect_attempts() -> u32 {
+    5
+}
+
+fn default_warmup() -> bool {
+    true
+}
+
+fn default_statement_cache_capacity() -> usize {
+    100
+}
+
+fn default_replica_retry() -> u64 {
+    30
 }
 
 fn default_max_connections() -> u32 {
@@ -0,0 +1,84 @@
+//! Email (SMTP) delivery configuration.
+
+use serde::{Deserialize, Serialize};
+
+use super::secret::Secret;
+
+/// Outgoing email settings, used for notification emails.
+///
+/// Sending also requires the server to be built with the `email` feature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailConfig {
+    /// Whether email delivery is enabled.
+    #[serde(default)]
+    pub enabled: bool,
+    /// SMTP server host name.
+    #[serde(default)]
+    pub smtp_host: String,
+    /// SMTP server port.
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16,
+    /// SMTP user name, if the server requires authentication.
+    #[serde(default)]
+    pub smtp_username: Option<String>,
+    /// SMTP password, if the server requires authentication.
+    #[serde(default)]
+    pub smtp_password: Option<Secret>,
+    /// Transport security: "starttls", "tls" or "none".
+    #[serde(default = "default_security")]
+    pub security: String,
+    /// Sender address.
+    #[serde(default)]
+    pub from_address: String,
+    /// Sender display name.
+    #[serde(default = "default_from_name")]
+    pub from_name: String,
+    /// Web UI page where users manage notification preferences; linked
+    /// from every email.
+    #[serde(default)]
+    pub preferences_url: String,
+    /// SMTP command timeout in seconds.
+    #[serde(default = "default_timeout")]
+    pub timeout_seconds: u64,
+    /// Delivery attempts before an email is given up.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            smtp_host: String::new(),
+            smtp_port: default_smtp_port(),
+            smtp_username: None,
+            smtp_password: None,
+            security: default_security(),
+            from_address: String::new(),
+            from_name: default_from_name(),
+            preferences_url: String::new(),
+            timeout_seconds: default_timeout(),
+            max_attempts: default_max_attempts(),
+        }
+    }
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+fn default_security() -> String {
+    "starttls".to_string()
+}
+
+fn default_from_name() -> String {
+    "FileHub".to_string()
+}
+
+fn default_timeout() -> u64 {
+    30
+}
+
+fn default_max_attempts() -> u32 {
+    5
+}
@@ -0,0 +1,84 @@
+//! Outbound HTTP client configuration.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Settings shared by every outbound HTTP call made for integrations
+/// (webhooks, identity providers, converters).
+///
+/// Callers may override the timeout and retry count per request; proxy,
+/// TLS and pooling always come from here.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpClientConfig {
+    /// Total request timeout in seconds.
+    pub timeout_seconds: u64,
+    /// TCP/TLS connect timeout in seconds.
+    pub connect_timeout_seconds: u64,
+    /// Retries after a failed attempt (transport error, 429, 502–504).
+    pub max_retries: u32,
+    /// Delay before the first retry in milliseconds; doubled per retry.
+    pub retry_base_delay_ms: u64,
+    /// Upper bound of a retry delay in milliseconds, including one asked
+    /// for by a `Retry-After` header.
+    pub retry_max_delay_ms: u64,
+    /// Idle connections kept open per host.
+    pub pool_max_idle_per_host: usize,
+    /// Seconds an idle pooled connection is kept.
+    pub pool_idle_timeout_seconds: u64,
+    /// `User-Agent` header sent with every request.
+    pub user_agent: String,
+    /// Proxy URL for every request, e.g. `http://proxy.internal:3128`.
+    pub proxy: Option<String>,
+    /// Hosts reached directly, bypassing `proxy`.
+    pub no_proxy: Vec<String>,
+    /// PEM file with extra root certificates to trust.
+    pub ca_cert_path: Option<String>,
+    /// Accept invalid TLS certificates. For testing only.
+    pub accept_invalid_certs: bool,
+    /// Rate limit applied to each destination host.
+    pub rate_limit: HostRateLimit,
+    /// Rate limits of specific hosts, overriding `rate_limit`.
+    pub hosts: HashMap<String, HostRateLimit>,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            timeout_seconds: 30,
+            connect_timeout_seconds: 10,
+            max_retries: 2,
+            retry_base_delay_ms: 200,
+            retry_max_delay_ms: 10_000,
+            pool_max_idle_per_host: 8,
+            pool_idle_timeout_seconds: 90,
+            user_agent: "FileHub".to_string(),
+            proxy: None,
+            no_proxy: Vec::new(),
+            ca_cert_path: None,
+            accept_invalid_certs: false,
+            rate_limit: HostRateLimit::default(),
+            hosts: HashMap::new(),
+        }
+    }
+}
+
+/// Token-bucket rate limit of one destination host.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HostRateLimit {
+    /// Sustained requests per second; 0 disables the limit.
+    pub requests_per_second: f64,
+    /// Requests that may be sent at once after a quiet period.
+    pub burst: u32,
+}
+
+impl Default for HostRateLimit {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 20.0,
+            burst: 40,
+        }
+    }
+}
@@ -14,12 +14,96 @@ pub struct LicenseConfig {
     /// Path to the license file on disk.
     #[serde(default = "default_license_file")]
     pub license_file: String,
-    /// Licensed feature name to check out.
+    /// Licensed feature name to check out when no entry in `features`
+    /// matches the user's role or requested capability.
     #[serde(default = "default_feature_name")]
     pub feature_name: String,
+    /// Licensed features, each with its own seat pool on the license
+    /// server. Empty means only `feature_name` is used.
+    #[serde(default)]
+    pub features: Vec<LicenseFeatureConfig>,
     /// License pool management configuration.
     #[serde(default)]
     pub pool: LicensePoolConfig,
+    /// Offline license borrowing configuration.
+    #[serde(default)]
+    pub borrow: LicenseBorrowConfig,
+    /// Checkout lease renewal configuration.
+    #[serde(default)]
+    pub lease: LicenseLeaseConfig,
+}
+
+impl LicenseConfig {
+    /// Names of every feature the deployment checks out, the default
+    /// `feature_name` first.
+    pub fn feature_names(&self) -> Vec<&str> {
+        let mut names = vec![self.feature_name.as_str()];
+        for feature in &self.features {
+            if !names.contains(&feature.name.as_str()) {
+                names.push(&feature.name);
+            }
+        }
+        names
+    }
+
+    /// Whether `name` is a configured feature.
+    pub fn has_feature(&self, name: &str) -> bool {
+        self.feature_names().contains(&name)
+    }
+
+    /// Pick the feature to check out for a user.
+    ///
+    /// A requested capability wins over the role; with neither matching,
+    /// the default `feature_name` is used.
+    pub fn feature_for(&self, role: Option<&str>, capability: Option<&str>) -> &str {
+        let by_capability = capability.and_then(|cap| {
+            self.features
+                .iter()
+                .find(|f| f.capabilities.iter().any(|c| c.eq_ignore_ascii_case(cap)))
+        });
+        let by_role = || {
+            role.and_then(|role| {
+                self.features
+                    .iter()
+                    .find(|f| f.roles.iter().any(|r| r.eq_ignore_ascii_case(role)))
+            })
+        };
+
+        by_capability
+            .or_else(by_role)
+            .map(|f| f.name.as_str())
+            .unwrap_or(&self.feature_name)
+    }
+
+    /// Seats reserved for admins in a feature's pool, or 0 when admin
+    /// reservation is disabled.
+    pub fn admin_reserved_seats(&self, feature: &str) -> u32 {
+        if !self.pool.admin_reserved_enabled {
+            return 0;
+        }
+        self.features
+            .iter()
+            .find(|f| f.name == feature)
+            .and_then(|f| f.admin_reserved_seats)
+            .unwrap_or(self.pool.admin_reserved_seats)
+    }
+}
+
+/// A licensed feature with its own seat pool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseFeatureConfig {
+    /// Feature name on the license server (e.g., `"filehub_editor"`).
+    pub name: String,
+    /// User roles that check out this feature (e.g., `["viewer"]`).
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// Capabilities a client can request this feature for (e.g., `["edit"]`).
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    /// Admin-reserved seats in this pool. Defaults to
+    /// `pool.admin_reserved_seats`.
+    #[serde(default)]
+    pub admin_reserved_seats: Option<u32>,
 }
 
 /// License pool management configuration.
@@ -58,6 +142,74 @@ impl Default for LicensePoolConfig {
     }
 }
 
+/// Offline license borrowing configuration.
+///
+/// A borrowed seat stays checked out on the license server without a
+/// session, so a user can keep working off-network until it is returned
+/// or the borrow window ends.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseBorrowConfig {
+    /// Whether users may borrow seats.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Longest borrow allowed, in hours.
+    #[serde(default = "default_max_borrow_hours")]
+    pub max_borrow_hours: u32,
+}
+
+impl Default for LicenseBorrowConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_borrow_hours: default_max_borrow_hours(),
+        }
+    }
+}
+
+/// Checkout lease renewal configuration.
+///
+/// The license server drops a checkout whose lease is not renewed, so
+/// active session checkouts are renewed once `renew_at_percent` of the
+/// lease has elapsed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseLeaseConfig {
+    /// Lease length granted by the license server, in seconds.
+    #[serde(default = "default_lease_duration")]
+    pub duration_seconds: u64,
+    /// Percentage of the lease after which checkouts are renewed (1–99).
+    #[serde(default = "default_renew_at_percent")]
+    pub renew_at_percent: u8,
+}
+
+impl LicenseLeaseConfig {
+    /// How often checkouts are renewed.
+    pub fn renew_interval(&self) -> std::time::Duration {
+        let secs = self.duration_seconds * self.renew_at_percent as u64 / 100;
+        std::time::Duration::from_secs(secs.max(1))
+    }
+}
+
+impl Default for LicenseLeaseConfig {
+    fn default() -> Self {
+        Self {
+            duration_seconds: default_lease_duration(),
+            renew_at_percent: default_renew_at_percent(),
+        }
+    }
+}
+
+fn default_lease_duration() -> u64 {
+    3600
+}
+
+fn default_renew_at_percent() -> u8 {
+    50
+}
+
+fn default_max_borrow_hours() -> u32 {
+    168
+}
+
 fn default_admin_reserved_seats() -> u32 {
     2
 }
@@ -89,3 +241,52 @@ fn default_warning_threshold() -> u8 {
 fn default_critical_threshold() -> u8 {
     95
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LicenseConfig {
+        let feature = |name: &str, roles: &[&str], capabilities: &[&str]| LicenseFeatureConfig {
+            name: name.to_string(),
+            roles: roles.iter().map(|r| r.to_string()).collect(),
+            capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            admin_reserved_seats: Some(1),
+        };
+        LicenseConfig {
+            enabled: true,
+            provider: default_provider(),
+            license_file: default_license_file(),
+            feature_name: "viewer".to_string(),
+            features: vec![
+                feature("viewer", &["viewer"], &["view"]),
+                feature("editor", &["admin", "creator"], &["edit"]),
+            ],
+            pool: LicensePoolConfig::default(),
+            borrow: LicenseBorrowConfig::default(),
+            lease: LicenseLeaseConfig::default(),
+        }
+    }
+
+    #[test]
+    fn test_feature_for_prefers_capability_over_role() {
+        let config = config();
+        assert_eq!(config.feature_for(Some("Creator"), None), "editor");
+        assert_eq!(config.feature_for(Some("creator"), Some("view")), "viewer");
+        assert_eq!(config.feature_for(Some("viewer"), Some("edit")), "editor");
+        assert_eq!(config.feature_for(Some("manager"), None), "viewer");
+        assert_eq!(config.feature_for(None, Some("print")), "viewer");
+        assert_eq!(config.feature_names(), ["viewer", "editor"]);
+    }
+
+    #[test]
+    fn test_admin_reserved_seats_per_feature() {
+        let mut config = config();
+        assert_eq!(config.admin_reserved_seats("editor"), 0);
+
+        config.pool.admin_reserved_enabled = true;
+        config.features[1].admin_reserved_seats = None;
+        assert_eq!(config.admin_reserved_seats("viewer"), 1);
+        assert_eq!(config.admin_reserved_seats("editor"), 2);
+    }
+}
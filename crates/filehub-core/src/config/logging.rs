@@ -23,6 +23,45 @@ pub struct LoggingConfig {
     /// Maximum number of rotated log files to retain.
     #[serde(default = "default_max_files")]
     pub max_files: u32,
+    /// Distributed tracing export.
+    #[serde(default)]
+    pub tracing: TracingConfig,
+}
+
+/// OpenTelemetry trace export over OTLP/HTTP.
+///
+/// When disabled no tracing spans are recorded at all; incoming
+/// `traceparent` headers are ignored and none are sent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TracingConfig {
+    /// Whether spans are exported.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Collector URL for traces, including the `/v1/traces` path.
+    #[serde(default = "default_otlp_endpoint")]
+    pub otlp_endpoint: String,
+    /// Fraction of new traces sampled, from 0.0 to 1.0. Requests that
+    /// arrive with a `traceparent` follow the caller's sampling decision.
+    #[serde(default = "default_sample_ratio")]
+    pub sample_ratio: f64,
+    /// `service.name` reported with every span.
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+    /// Export timeout in seconds.
+    #[serde(default = "default_export_timeout")]
+    pub export_timeout_seconds: u64,
+}
+
+impl Default for TracingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            otlp_endpoint: default_otlp_endpoint(),
+            sample_ratio: default_sample_ratio(),
+            service_name: default_service_name(),
+            export_timeout_seconds: default_export_timeout(),
+        }
+    }
 }
 
 fn default_level() -> String {
@@ -48,3 +87,19 @@ fn default_max_size() -> u64 {
 fn default_max_files() -> u32 {
     10
 }
+
+fn default_otlp_endpoint() -> String {
+    "http://localhost:4318/v1/traces".to_string()
+}
+
+fn default_sample_ratio() -> f64 {
+    1.0
+}
+
+fn default_service_name() -> String {
+    "filehub".to_string()
+}
+
+fn default_export_timeout() -> u64 {
+    10
+}
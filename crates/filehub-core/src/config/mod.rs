@@ -5,30 +5,62 @@
 //! section.
 
 pub mod app;
+pub mod audit;
 pub mod auth;
 pub mod cache;
 pub mod database;
+pub mod email;
+pub mod http_client;
 pub mod license;
 pub mod logging;
 pub mod plugin;
 pub mod realtime;
+pub mod reload;
+pub mod secret;
 pub mod session;
+pub mod share;
 pub mod storage;
+pub mod validate;
 pub mod worker;
 
 use serde::{Deserialize, Serialize};
 
-pub use self::app::{CorsConfig, ServerConfig};
-pub use self::auth::AuthConfig;
-pub use self::cache::CacheConfig;
+pub use self::app::{
+    CompressionConfig, CorsConfig, CorsGroup, CorsPolicy, ForwardedHeader, GrpcConfig,
+    HealthConfig, IdempotencyConfig, MetricsConfig, ProxyConfig, QuotaConfig, QuotaTier,
+    RateLimitConfig, RateLimitRule, RouteTimeoutConfig, ServerConfig,
+};
+pub use self::audit::{AuditArchiveConfig, AuditConfig, AuditRetentionConfig, AuditSinkConfig};
+pub use self::auth::{
+    AnomalyAction, AuthConfig, ImpersonationConfig, LoginAnomalyConfig, PasswordHashConfig,
+    PepperConfig, RoutePattern,
+};
+pub use self::cache::{CacheConfig, CacheTtlConfig};
 pub use self::database::DatabaseConfig;
-pub use self::license::LicenseConfig;
-pub use self::logging::LoggingConfig;
+pub use self::email::EmailConfig;
+pub use self::http_client::{HostRateLimit, HttpClientConfig};
+pub use self::license::{LicenseConfig, LicenseFeatureConfig};
+pub use self::logging::{LoggingConfig, TracingConfig};
 pub use self::plugin::PluginConfig;
-pub use self::realtime::{NotificationRealtimeConfig, RealtimeConfig};
+pub use self::realtime::{
+    NotificationRealtimeConfig, NotificationTemplateConfig, RealtimeConfig, UploadCoalescingConfig,
+};
+pub use self::reload::{HOT_RELOADABLE, ReloadOutcome};
+pub use self::secret::{
+    AwsSecretsConfig, Secret, SecretResolver, SecretSource, SecretsConfig, VaultConfig,
+};
 pub use self::session::SessionConfig;
-pub use self::storage::StorageConfig;
-pub use self::worker::WorkerConfig;
+pub use self::share::{PublicAccessConfig, ShareAnalyticsConfig, ShareConfig};
+pub use self::storage::{
+    AntivirusConfig, ConversionConfig, ConversionProfile, ConversionProfileMode,
+    DirectTransferConfig, DocumentPreviewConfig, NameCollision, ScanFailPolicy, StorageConfig,
+    StorageErrorClass, StorageHealthConfig, StorageMigrationConfig, StorageRetryConfig,
+    StorageRetryOverride, StorageRetryPolicy, ThumbnailConfig, ThumbnailFormat,
+    ThumbnailPregenConfig, UploadPolicy, UploadPolicyConfig, UploadPolicyOverride,
+    UploadProcessingConfig, ZipDownloadConfig,
+};
+pub use self::validate::ConfigIssue;
+pub use self::worker::{OutboxConfig, WorkerConfig};
 
 use crate::error::AppError;
 
@@ -60,6 +92,21 @@ pub struct AppConfig {
     pub plugins: PluginConfig,
     /// Logging settings.
     pub logging: LoggingConfig,
+    /// Outgoing email settings.
+    #[serde(default)]
+    pub email: EmailConfig,
+    /// Share link settings.
+    #[serde(default)]
+    pub shares: ShareConfig,
+    /// Audit log shipping settings.
+    #[serde(default)]
+    pub audit: AuditConfig,
+    /// Outbound HTTP client settings.
+    #[serde(default)]
+    pub http_client: HttpClientConfig,
+    /// Secret backend settings.
+    #[serde(default)]
+    pub secrets: SecretsConfig,
 }
 
 impl AppConfig {
@@ -1,5 +1,7 @@
 //! Real-time WebSocket engine configuration.
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 /// Real-time (WebSocket) engine configuration.
@@ -23,6 +25,10 @@ pub struct RealtimeConfig {
     /// Notification-specific settings.
     #[serde(default)]
     pub notifications: NotificationRealtimeConfig,
+    /// Minutes after it is sent during which an admin broadcast is still
+    /// delivered to targeted users as they connect.
+    #[serde(default = "default_broadcast_pending")]
+    pub broadcast_pending_minutes: u64,
 }
 
 /// Notification delivery settings for the real-time engine.
@@ -40,6 +46,58 @@ pub struct NotificationRealtimeConfig {
     /// Deduplication batch window in milliseconds.
     #[serde(default = "default_batch_window")]
     pub batch_window_ms: u64,
+    /// Coalescing of bulk uploads into one update per folder.
+    #[serde(default)]
+    pub upload_coalescing: UploadCoalescingConfig,
+    /// Wording overrides, keyed by event type (`"share_created"`) or by
+    /// event type and locale (`"share_created.de"`).
+    #[serde(default)]
+    pub templates: HashMap<String, NotificationTemplateConfig>,
+}
+
+/// Coalescing of upload events for realtime updates and notifications.
+///
+/// Uploads by one user to one folder form a burst while each follows the
+/// previous within `window_ms`. The first `threshold` uploads of a burst
+/// are sent one by one; the rest are held and sent as one "N files added"
+/// channel update when the burst ends, or every `max_delay_ms` while it
+/// lasts, and as one stored notification when it ends. Domain events are
+/// published for every upload either way.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadCoalescingConfig {
+    /// Longest gap between uploads of one burst, in milliseconds; 0
+    /// disables coalescing.
+    #[serde(default = "default_coalescing_window")]
+    pub window_ms: u64,
+    /// Uploads of a burst sent individually before the rest are held.
+    #[serde(default = "default_coalescing_threshold")]
+    pub threshold: u32,
+    /// Longest time held uploads wait for a channel update while a burst
+    /// goes on, in milliseconds.
+    #[serde(default = "default_coalescing_max_delay")]
+    pub max_delay_ms: u64,
+}
+
+impl Default for UploadCoalescingConfig {
+    fn default() -> Self {
+        Self {
+            window_ms: default_coalescing_window(),
+            threshold: default_coalescing_threshold(),
+            max_delay_ms: default_coalescing_max_delay(),
+        }
+    }
+}
+
+/// Override of a notification's wording. `{{name}}` placeholders are
+/// filled from the event; unset fields keep the default wording.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationTemplateConfig {
+    /// Title template.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Message template.
+    #[serde(default)]
+    pub message: Option<String>,
 }
 
 impl Default for NotificationRealtimeConfig {
@@ -49,6 +107,8 @@ impl Default for NotificationRealtimeConfig {
             max_stored_per_user: default_max_stored(),
             cleanup_after_days: default_cleanup_days(),
             batch_window_ms: default_batch_window(),
+            upload_coalescing: UploadCoalescingConfig::default(),
+            templates: HashMap::new(),
         }
     }
 }
@@ -73,6 +133,10 @@ fn default_max_subscriptions() -> usize {
     50
 }
 
+fn default_broadcast_pending() -> u64 {
+    60
+}
+
 fn default_true() -> bool {
     true
 }
@@ -88,3 +152,15 @@ fn default_cleanup_days() -> u32 {
 fn default_batch_window() -> u64 {
     500
 }
+
+fn default_coalescing_window() -> u64 {
+    2000
+}
+
+fn default_coalescing_threshold() -> u32 {
+    5
+}
+
+fn default_coalescing_max_delay() -> u64 {
+    10_000
+}
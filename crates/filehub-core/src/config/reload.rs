@@ -0,0 +1,180 @@
+//! Hot reload of the runtime-tunable subset of configuration.
+//!
+//! On `SIGHUP` the server re-reads its configuration files and applies
+//! only the fields listed in [`HOT_RELOADABLE`]; everything else keeps its
+//! startup value until the process is restarted.
+//!
+//! | Field                        | Hot-reloadable | Takes effect                  |
+//! | ---------------------------- | -------------- | ----------------------------- |
+//! | `logging.level`              | yes            | immediately (tracing filter)  |
+//! | `server.rate_limit.*`        | yes            | next request                  |
+//! | `cache.default_ttl_seconds`  | yes            | next default-TTL write        |
+//! | `worker.concurrency`         | yes            | as in-flight jobs finish      |
+//! | everything else              | no             | after restart                 |
+
+use serde_json::Value;
+
+use super::AppConfig;
+
+/// Configuration paths applied on reload. A path covers every field
+/// nested under it.
+pub const HOT_RELOADABLE: &[&str] = &[
+    "logging.level",
+    "server.rate_limit",
+    "cache.default_ttl_seconds",
+    "worker.concurrency",
+];
+
+/// What a reload changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReloadOutcome {
+    /// Changed fields that were applied.
+    pub applied: Vec<String>,
+    /// Changed fields that were ignored because they need a restart.
+    pub restart_required: Vec<String>,
+}
+
+impl ReloadOutcome {
+    /// Whether `path` (or a field nested under it) was applied.
+    pub fn applied(&self, path: &str) -> bool {
+        self.applied.iter().any(|p| covers(path, p))
+    }
+}
+
+/// Builds the next live configuration from `live` and a freshly loaded
+/// `candidate`, copying over only the hot-reloadable fields.
+pub fn apply_reloadable(live: &AppConfig, candidate: &AppConfig) -> (AppConfig, ReloadOutcome) {
+    let mut changed = Vec::new();
+    if let (Ok(a), Ok(b)) = (serde_json::to_value(live), serde_json::to_value(candidate)) {
+        diff_paths(&a, &b, String::new(), &mut changed);
+    }
+
+    let (applied, restart_required) = changed
+        .into_iter()
+        .partition(|path| HOT_RELOADABLE.iter().any(|hot| covers(hot, path)));
+
+    let mut next = live.clone();
+    next.logging.level = candidate.logging.level.clone();
+    next.server.rate_limit = candidate.server.rate_limit.clone();
+    next.cache.default_ttl_seconds = candidate.cache.default_ttl_seconds;
+    next.worker.concurrency = candidate.worker.concurrency;
+
+    (
+        next,
+        ReloadOutcome {
+            applied,
+            restart_required,
+        },
+    )
+}
+
+/// Whether `prefix` equals `path` or is one of its parents.
+fn covers(prefix: &str, path: &str) -> bool {
+    path == prefix
+        || path
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+/// Collects the dotted paths of leaves that differ between `a` and `b`.
+/// Arrays are compared as a whole.
+fn diff_paths(a: &Value, b: &Value, path: String, out: &mut Vec<String>) {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                diff_paths(
+                    a.get(key).unwrap_or(&Value::Null),
+                    b.get(key).unwrap_or(&Value::Null),
+                    child,
+                    out,
+                );
+            }
+        }
+        _ if a != b => out.push(path),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> AppConfig {
+        config::Config::builder()
+            .add_source(config::File::from_str(
+                include_str!("../../../../config/default.toml"),
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .and_then(|c| c.try_deserialize())
+            .expect("default.toml deserializes")
+    }
+
+    #[test]
+    fn test_unchanged_config_is_a_no_op() {
+        let live = base();
+        let (_, outcome) = apply_reloadable(&live, &live.clone());
+        assert_eq!(outcome, ReloadOutcome::default());
+    }
+
+    #[test]
+    fn test_applies_only_hot_fields() {
+        let live = base();
+        let mut candidate = live.clone();
+        candidate.logging.level = "debug".to_string();
+        candidate.server.rate_limit.read.requests += 1;
+        candidate.cache.default_ttl_seconds += 1;
+        candidate.worker.concurrency += 1;
+        candidate.server.port += 1;
+        candidate.database.max_connections += 1;
+
+        let (next, outcome) = apply_reloadable(&live, &candidate);
+
+        assert_eq!(next.logging.level, "debug");
+        assert_eq!(
+            next.server.rate_limit.read.requests,
+            candidate.server.rate_limit.read.requests
+        );
+        assert_eq!(
+            next.cache.default_ttl_seconds,
+            candidate.cache.default_ttl_seconds
+        );
+        assert_eq!(next.worker.concurrency, candidate.worker.concurrency);
+        assert_eq!(next.server.port, live.server.port);
+        assert_eq!(next.database.max_connections, live.database.max_connections);
+
+        assert_eq!(
+            outcome.applied,
+            [
+                "cache.default_ttl_seconds",
+                "logging.level",
+                "server.rate_limit.read.requests",
+                "worker.concurrency",
+            ]
+        );
+        assert_eq!(
+            outcome.restart_required,
+            ["database.max_connections", "server.port"]
+        );
+        assert!(outcome.applied("server.rate_limit"));
+        assert!(!outcome.applied("database"));
+    }
+
+    #[test]
+    fn test_covers_whole_segments_only() {
+        assert!(covers(
+            "server.rate_limit",
+            "server.rate_limit.read.requests"
+        ));
+        assert!(covers("logging.level", "logging.level"));
+        assert!(!covers("logging.level", "logging.level_extra"));
+    }
+}
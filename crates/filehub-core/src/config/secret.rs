@@ -0,0 +1,445 @@
+//! Secrets referenced from configuration.
+//!
+//! A secret-valued setting holds either the secret itself or a reference
+//! to where it is kept:
+//!
+//! | Value                     | Resolved from                                        |
+//! | ------------------------- | ---------------------------------------------------- |
+//! | `env:NAME`                | environment variable `NAME`                          |
+//! | `file:/path/to/secret`    | file contents, without the trailing newline          |
+//! | `vault:path#field`        | Vault KV v2 secret (`vault` build feature)           |
+//! | `aws-sm:secret-id#field`  | AWS Secrets Manager secret (`aws-secrets` feature)   |
+//! | anything else             | taken literally                                      |
+//!
+//! References are resolved once at startup by [`AppConfig::resolve_secrets`];
+//! one that cannot be resolved fails startup. The configuration keeps the
+//! reference, so serializing it, or comparing it on reload, never exposes a
+//! resolved value.
+//!
+//! Secret-valued settings: `auth.jwt_secret`, `auth.password_pepper.versions.*`
+//! and, while email is enabled, `email.smtp_password`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::AppConfig;
+use crate::traits::SecretBackend;
+
+/// Schemes resolved by a [`SecretBackend`] rather than locally.
+pub const REMOTE_SCHEMES: &[&str] = &["vault", "aws-sm"];
+
+/// A secret setting: a literal value or a reference to one.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret {
+    reference: String,
+    resolved: Option<String>,
+}
+
+impl Secret {
+    /// Creates a secret from its configured value.
+    pub fn new(reference: impl Into<String>) -> Self {
+        Self {
+            reference: reference.into(),
+            resolved: None,
+        }
+    }
+
+    /// The configured value: the reference, or the literal secret.
+    pub fn reference(&self) -> &str {
+        &self.reference
+    }
+
+    /// Where the secret is kept.
+    pub fn source(&self) -> Result<SecretSource<'_>, String> {
+        SecretSource::parse(&self.reference)
+    }
+
+    /// Whether the secret is known: a literal, or a resolved reference.
+    pub fn is_resolved(&self) -> bool {
+        self.resolved.is_some() || matches!(self.source(), Ok(SecretSource::Literal(_)))
+    }
+
+    /// The secret value. Empty for a reference that has not been resolved.
+    pub fn expose(&self) -> &str {
+        match (&self.resolved, self.source()) {
+            (Some(value), _) => value,
+            (None, Ok(SecretSource::Literal(value))) => value,
+            (None, _) => "",
+        }
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.source() {
+            Ok(SecretSource::Literal(_)) => f.write_str("Secret(<redacted>)"),
+            _ => f.debug_tuple("Secret").field(&self.reference).finish(),
+        }
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.reference)
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::new)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(reference: &str) -> Self {
+        Self::new(reference)
+    }
+}
+
+/// Where a [`Secret`] is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretSource<'a> {
+    /// The value itself.
+    Literal(&'a str),
+    /// An environment variable.
+    Env(&'a str),
+    /// A file.
+    File(&'a str),
+    /// A [`SecretBackend`].
+    Remote {
+        /// Backend scheme.
+        scheme: &'a str,
+        /// Secret path or id in the backend.
+        path: &'a str,
+        /// Field of a structured secret.
+        field: Option<&'a str>,
+    },
+}
+
+impl<'a> SecretSource<'a> {
+    /// Parses a configured value. Values without a known scheme are
+    /// literals.
+    pub fn parse(value: &'a str) -> Result<Self, String> {
+        let Some((scheme, rest)) = value.split_once(':') else {
+            return Ok(Self::Literal(value));
+        };
+        let source = match scheme {
+            "env" => Self::Env(rest),
+            "file" => Self::File(rest),
+            _ if REMOTE_SCHEMES.contains(&scheme) => {
+                let (path, field) = match rest.split_once('#') {
+                    Some((path, field)) => (path, Some(field)),
+                    None => (rest, None),
+                };
+                if field == Some("") {
+                    return Err(format!("'{scheme}:' reference has an empty field"));
+                }
+                Self::Remote {
+                    scheme,
+                    path,
+                    field,
+                }
+            }
+            _ => return Ok(Self::Literal(value)),
+        };
+        match source {
+            Self::Env("") | Self::File("") | Self::Remote { path: "", .. } => {
+                Err(format!("'{scheme}:' reference names no secret"))
+            }
+            source => Ok(source),
+        }
+    }
+}
+
+/// Secret backend settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecretsConfig {
+    /// HashiCorp Vault, for `vault:` references.
+    pub vault: VaultConfig,
+    /// AWS Secrets Manager, for `aws-sm:` references.
+    pub aws: AwsSecretsConfig,
+}
+
+/// HashiCorp Vault connection, used for `vault:` references.
+///
+/// `vault:path#field` reads `field` of the KV v2 secret at `path` under
+/// `mount`; the field defaults to `value`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VaultConfig {
+    /// Vault address, e.g. `https://vault.internal:8200`.
+    pub address: String,
+    /// Token to authenticate with; an `env:` or `file:` reference, or the
+    /// token itself.
+    pub token: Secret,
+    /// Mount path of the KV v2 engine.
+    pub mount: String,
+    /// Vault Enterprise namespace.
+    pub namespace: Option<String>,
+}
+
+impl Default for VaultConfig {
+    fn default() -> Self {
+        Self {
+            address: String::new(),
+            token: Secret::new("env:VAULT_TOKEN"),
+            mount: "secret".to_string(),
+            namespace: None,
+        }
+    }
+}
+
+/// AWS Secrets Manager, used for `aws-sm:` references.
+///
+/// `aws-sm:secret-id` reads the secret string; `aws-sm:secret-id#field`
+/// reads one field of a JSON secret string. Credentials come from the
+/// standard AWS provider chain.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AwsSecretsConfig {
+    /// Region; the provider chain's region when unset.
+    pub region: Option<String>,
+    /// Endpoint override, for VPC endpoints or local emulators.
+    pub endpoint_url: Option<String>,
+}
+
+/// Resolves secret references: `env:` and `file:` locally, other schemes
+/// through the registered backends.
+#[derive(Clone, Default)]
+pub struct SecretResolver {
+    backends: HashMap<&'static str, Arc<dyn SecretBackend>>,
+}
+
+impl SecretResolver {
+    /// Creates a resolver for local references only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a backend for its scheme.
+    pub fn with_backend(mut self, backend: Arc<dyn SecretBackend>) -> Self {
+        self.backends.insert(backend.scheme(), backend);
+        self
+    }
+
+    /// Resolves `secret` in place. Literals and already resolved secrets
+    /// are left as they are.
+    pub async fn resolve(&self, secret: &mut Secret) -> Result<(), String> {
+        if secret.is_resolved() {
+            return Ok(());
+        }
+        let value = match secret.source()? {
+            SecretSource::Literal(value) => value.to_string(),
+            SecretSource::Env(name) => std::env::var(name)
+                .map_err(|_| format!("environment variable {name} is not set"))?,
+            SecretSource::File(path) => tokio::fs::read_to_string(path)
+                .await
+                .map_err(|e| format!("cannot read {path}: {e}"))?
+                .trim_end_matches(['\r', '\n'])
+                .to_string(),
+            SecretSource::Remote {
+                scheme,
+                path,
+                field,
+            } => {
+                let backend = self.backends.get(scheme).ok_or_else(|| {
+                    format!("no backend for '{scheme}:' references in this build or configuration")
+                })?;
+                backend
+                    .fetch(path, field)
+                    .await
+                    .map_err(|e| format!("{scheme} lookup of {path} failed: {}", e.message))?
+            }
+        };
+        if value.is_empty() {
+            return Err(format!("{} resolved to an empty value", secret.reference));
+        }
+        secret.resolved = Some(value);
+        Ok(())
+    }
+}
+
+impl fmt::Debug for SecretResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut schemes: Vec<&str> = self.backends.keys().copied().collect();
+        schemes.sort();
+        f.debug_struct("SecretResolver")
+            .field("backends", &schemes)
+            .finish()
+    }
+}
+
+impl AppConfig {
+    /// The secret-valued settings, by dotted path.
+    pub fn secrets(&self) -> Vec<(String, &Secret)> {
+        let mut secrets = vec![("auth.jwt_secret".to_string(), &self.auth.jwt_secret)];
+        secrets.extend(
+            self.auth
+                .password_pepper
+                .versions
+                .iter()
+                .map(|(id, secret)| (format!("auth.password_pepper.versions.{id}"), secret)),
+        );
+        if let Some(password) = &self.email.smtp_password
+            && self.email.enabled
+        {
+            secrets.push(("email.smtp_password".to_string(), password));
+        }
+        secrets
+    }
+
+    fn secrets_mut(&mut self) -> Vec<(String, &mut Secret)> {
+        let mut secrets = vec![("auth.jwt_secret".to_string(), &mut self.auth.jwt_secret)];
+        secrets.extend(
+            self.auth
+                .password_pepper
+                .versions
+                .iter_mut()
+                .map(|(id, secret)| (format!("auth.password_pepper.versions.{id}"), secret)),
+        );
+        if let Some(password) = &mut self.email.smtp_password
+            && self.email.enabled
+        {
+            secrets.push(("email.smtp_password".to_string(), password));
+        }
+        secrets
+    }
+
+    /// Whether any secret setting references `scheme`.
+    pub fn references_secret_scheme(&self, scheme: &str) -> bool {
+        self.secrets().iter().any(|(_, secret)| {
+            matches!(secret.source(), Ok(SecretSource::Remote { scheme: s, .. }) if s == scheme)
+        })
+    }
+
+    /// Resolves every secret reference, reporting all that fail at once.
+    pub async fn resolve_secrets(
+        &mut self,
+        resolver: &SecretResolver,
+    ) -> Result<(), crate::error::AppError> {
+        let mut problems = Vec::new();
+        for (path, secret) in self.secrets_mut() {
+            if let Err(e) = resolver.resolve(secret).await {
+                problems.push(format!("  - {path}: {e}"));
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(crate::error::AppError::configuration(format!(
+                "Unresolved secrets ({} problem(s)):\n{}",
+                problems.len(),
+                problems.join("\n")
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    use crate::result::AppResult;
+
+    struct Fixed;
+
+    #[async_trait]
+    impl SecretBackend for Fixed {
+        fn scheme(&self) -> &'static str {
+            "vault"
+        }
+
+        async fn fetch(&self, path: &str, field: Option<&str>) -> AppResult<String> {
+            Ok(format!("{path}/{}", field.unwrap_or("value")))
+        }
+    }
+
+    #[test]
+    fn test_parse_references() {
+        assert_eq!(SecretSource::parse("env:JWT"), Ok(SecretSource::Env("JWT")));
+        assert_eq!(
+            SecretSource::parse("file:/run/secrets/jwt"),
+            Ok(SecretSource::File("/run/secrets/jwt"))
+        );
+        assert_eq!(
+            SecretSource::parse("vault:filehub/auth#pepper"),
+            Ok(SecretSource::Remote {
+                scheme: "vault",
+                path: "filehub/auth",
+                field: Some("pepper"),
+            })
+        );
+        assert_eq!(
+            SecretSource::parse("s3cr:et"),
+            Ok(SecretSource::Literal("s3cr:et"))
+        );
+        assert!(SecretSource::parse("env:").is_err());
+        assert!(SecretSource::parse("aws-sm:id#").is_err());
+    }
+
+    #[test]
+    fn test_literal_is_redacted_and_reference_kept() {
+        let literal = Secret::new("hunter2");
+        assert_eq!(literal.expose(), "hunter2");
+        assert_eq!(format!("{literal:?}"), "Secret(<redacted>)");
+
+        let reference = Secret::new("env:FILEHUB_TEST_UNSET");
+        assert!(!reference.is_resolved());
+        assert_eq!(reference.expose(), "");
+        assert_eq!(
+            serde_json::to_value(&reference).unwrap(),
+            "env:FILEHUB_TEST_UNSET"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolves_file_and_backend_references() {
+        let path = std::env::temp_dir().join(format!("filehub-secret-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "from-file\n").unwrap();
+        let resolver = SecretResolver::new().with_backend(Arc::new(Fixed));
+
+        let mut secret = Secret::new(format!("file:{}", path.display()));
+        resolver.resolve(&mut secret).await.unwrap();
+        assert_eq!(secret.expose(), "from-file");
+        assert_eq!(secret.reference(), format!("file:{}", path.display()));
+        std::fs::remove_file(&path).unwrap();
+
+        let mut secret = Secret::new("vault:filehub/auth#jwt");
+        resolver.resolve(&mut secret).await.unwrap();
+        assert_eq!(secret.expose(), "filehub/auth/jwt");
+
+        let mut secret = Secret::new("aws-sm:filehub");
+        assert!(resolver.resolve(&mut secret).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unresolvable_secrets_fail_together() {
+        let mut config: AppConfig = config::Config::builder()
+            .add_source(config::File::from_str(
+                include_str!("../../../../config/default.toml"),
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .and_then(|c| c.try_deserialize())
+            .unwrap();
+        config.auth.jwt_secret = Secret::new("env:FILEHUB_TEST_UNSET_JWT");
+        config
+            .auth
+            .password_pepper
+            .versions
+            .insert("1".to_string(), Secret::new("vault:filehub#pepper"));
+
+        let err = config
+            .resolve_secrets(&SecretResolver::new())
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("2 problem(s)"));
+        assert!(err.message.contains("auth.jwt_secret"));
+        assert!(err.message.contains("auth.password_pepper.versions.1"));
+    }
+}
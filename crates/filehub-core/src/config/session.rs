@@ -8,9 +8,21 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionConfig {
     /// Idle timeout in minutes before a session is considered inactive.
+    /// With `idle_grace_minutes`, this is the idle lifetime: a session
+    /// unused for both ends, however recently it was created.
     #[serde(default = "default_idle_timeout")]
     pub idle_timeout_minutes: u64,
-    /// Absolute session timeout in hours (regardless of activity).
+    /// Minutes an idle session is kept after `idle_timeout_minutes` before
+    /// it is terminated. Any activity in this window keeps the session.
+    #[serde(default = "default_idle_grace")]
+    pub idle_grace_minutes: u64,
+    /// Minutes before idle termination that the user is warned. At most
+    /// `idle_grace_minutes`; termination waits until the warning is this old.
+    #[serde(default = "default_idle_warning_lead")]
+    pub idle_warning_lead_minutes: u64,
+    /// Absolute session lifetime in hours: the session ends this long
+    /// after login however active it is, and refreshing never extends
+    /// it. `0` sets no cap, so a session lasts while it keeps being used.
     #[serde(default = "default_absolute_timeout")]
     pub absolute_timeout_hours: u64,
     /// WebSocket heartbeat interval in seconds.
@@ -22,6 +34,10 @@ pub struct SessionConfig {
     /// Interval for expired session cleanup in minutes.
     #[serde(default = "default_cleanup_interval")]
     pub cleanup_interval_minutes: u64,
+    /// Seconds between seat pool reconciliations, each of which records a
+    /// pool snapshot. `0` disables the periodic run.
+    #[serde(default = "default_seat_reconcile_interval")]
+    pub seat_reconcile_interval_seconds: u64,
     /// Concurrent session limits configuration.
     #[serde(default)]
     pub limits: SessionLimitsConfig,
@@ -45,6 +61,21 @@ pub struct SessionLimitsConfig {
     pub by_role: HashMap<String, u32>,
 }
 
+impl SessionConfig {
+    /// Idle time after which a session is terminated: the idle timeout
+    /// plus the grace window.
+    pub fn idle_termination_after(&self) -> chrono::Duration {
+        chrono::Duration::minutes((self.idle_timeout_minutes + self.idle_grace_minutes) as i64)
+    }
+
+    /// How long after login a session ends regardless of activity, if
+    /// capped.
+    pub fn absolute_lifetime(&self) -> Option<chrono::Duration> {
+        (self.absolute_timeout_hours > 0)
+            .then(|| chrono::Duration::hours(self.absolute_timeout_hours as i64))
+    }
+}
+
 impl Default for SessionLimitsConfig {
     fn default() -> Self {
         Self {
@@ -110,6 +141,14 @@ fn default_idle_timeout() -> u64 {
     30
 }
 
+fn default_idle_grace() -> u64 {
+    5
+}
+
+fn default_idle_warning_lead() -> u64 {
+    5
+}
+
 fn default_absolute_timeout() -> u64 {
     12
 }
@@ -126,6 +165,10 @@ fn default_cleanup_interval() -> u64 {
     15
 }
 
+fn default_seat_reconcile_interval() -> u64 {
+    60
+}
+
 fn default_true() -> bool {
     true
 }
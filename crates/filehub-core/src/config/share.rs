@@ -0,0 +1,56 @@
+//! Share link and public access configuration.
+
+use serde::{Deserialize, Serialize};
+
+/// Share link settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShareConfig {
+    /// Access analytics shown to link owners.
+    #[serde(default)]
+    pub analytics: ShareAnalyticsConfig,
+    /// Anonymous, read-only access to files and folders marked public.
+    #[serde(default)]
+    pub public_access: PublicAccessConfig,
+}
+
+/// Anonymous access to public files and folders.
+///
+/// Off by default: while disabled, the public routes are not mounted and
+/// nothing can be marked public. Marks made while enabled are kept but
+/// not honoured.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PublicAccessConfig {
+    /// Whether files and folders may be marked public and read without
+    /// signing in.
+    pub enabled: bool,
+}
+
+/// What is recorded when a share link is opened or downloaded.
+///
+/// Records are written by the background worker, so analytics also need
+/// `worker.enabled`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShareAnalyticsConfig {
+    /// Whether accesses are recorded at all.
+    pub enabled: bool,
+    /// Whether the client address is stored with each access.
+    pub record_ip: bool,
+    /// Whether the client country is stored with each access (needs a
+    /// GeoIP lookup).
+    pub record_country: bool,
+    /// Accesses listed in a link's statistics.
+    pub recent_limit: u32,
+}
+
+impl Default for ShareAnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            record_ip: true,
+            record_country: true,
+            recent_limit: 50,
+        }
+    }
+}
@@ -1,5 +1,7 @@
 //! Storage provider configuration.
 
+use std::collections::{BTreeMap, HashMap};
+
 use serde::{Deserialize, Serialize};
 
 /// Top-level storage configuration.
@@ -17,9 +19,58 @@ pub struct StorageConfig {
     /// Chunk size in bytes for chunked uploads (default 5 MB).
     #[serde(default = "default_chunk_size")]
     pub chunk_size_bytes: u64,
-    /// Thumbnail generation sizes.
-    #[serde(default = "default_thumbnail_sizes")]
-    pub thumbnail_sizes: Vec<u32>,
+    /// Hours a chunked upload may sit without receiving a chunk before it
+    /// is expired and its chunks removed.
+    #[serde(default = "default_upload_session_ttl")]
+    pub upload_session_ttl_hours: u64,
+    /// Hours before a file's expiry that its owner is warned.
+    #[serde(default = "default_expiry_notice")]
+    pub expiry_notice_hours: u64,
+    /// Versions kept per file; older ones are pruned by the version
+    /// cleanup job.
+    #[serde(default = "default_max_versions_per_file")]
+    pub max_versions_per_file: u32,
+    /// What an upload or copy does when the target folder already has a
+    /// file of that name, unless the request chooses otherwise.
+    #[serde(default)]
+    pub name_collision: NameCollision,
+    /// Also compute a CRC32C of every upload, alongside the SHA-256, and
+    /// keep it in the file's metadata as `crc32c`.
+    #[serde(default)]
+    pub crc32c: bool,
+    /// Store the type of an upload as detected from its content (then
+    /// from its extension) instead of the type the client declared. The
+    /// declared type is kept alongside either way.
+    #[serde(default = "default_true")]
+    pub sniff_content_type: bool,
+    /// Thumbnail sizes and output formats.
+    #[serde(default)]
+    pub thumbnails: ThumbnailConfig,
+    /// Eager thumbnail generation after upload.
+    #[serde(default)]
+    pub thumbnail_pregen: ThumbnailPregenConfig,
+    /// Limits on work done after an upload is received.
+    #[serde(default)]
+    pub upload_processing: UploadProcessingConfig,
+    /// Page previews of PDFs and office documents.
+    #[serde(default)]
+    pub document_preview: DocumentPreviewConfig,
+    /// Limits on folder downloads as ZIP.
+    #[serde(default)]
+    pub zip_download: ZipDownloadConfig,
+    /// Transfers between clients and the storage backend through
+    /// presigned URLs, bypassing the server.
+    #[serde(default)]
+    pub direct_transfer: DirectTransferConfig,
+    /// Periodic provider health probing.
+    #[serde(default)]
+    pub health_check: StorageHealthConfig,
+    /// Retries and timeouts of provider operations.
+    #[serde(default)]
+    pub retry: StorageRetryConfig,
+    /// Background migration of files between providers.
+    #[serde(default)]
+    pub migration: StorageMigrationConfig,
     /// Local filesystem storage configuration.
     #[serde(default)]
     pub local: LocalStorageConfig,
@@ -29,22 +80,573 @@ pub struct StorageConfig {
     /// Configuration for file conversions (e.g. CAD).
     #[serde(default)]
     pub conversions: ConversionConfig,
+    /// Virus scanning of uploads through clamd.
+    #[serde(default)]
+    pub antivirus: AntivirusConfig,
+    /// Per-role limits on upload size and type.
+    #[serde(default)]
+    pub upload_policy: UploadPolicyConfig,
+}
+
+/// Upload restrictions: a default policy, with per-role overrides.
+///
+/// Type entries are MIME types (`application/pdf`, or `image/*` for a
+/// whole family) or file extensions (`.dwg`). Uploads are checked against
+/// the declared type and name before any bytes are stored, and again
+/// against the type detected from the content.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UploadPolicyConfig {
+    /// Policy of every role without an override.
+    pub default: UploadPolicy,
+    /// Overrides keyed by role name (`admin`, `manager`, `creator`,
+    /// `viewer`). Fields left unset fall back to `default`.
+    pub roles: HashMap<String, UploadPolicyOverride>,
+}
+
+impl UploadPolicyConfig {
+    /// The effective policy of `role`.
+    pub fn for_role(&self, role: &str) -> UploadPolicy {
+        let Some(role) = self.roles.get(role) else {
+            return self.default.clone();
+        };
+        UploadPolicy {
+            max_file_size_bytes: role
+                .max_file_size_bytes
+                .unwrap_or(self.default.max_file_size_bytes),
+            allowed_types: role
+                .allowed_types
+                .clone()
+                .unwrap_or_else(|| self.default.allowed_types.clone()),
+            denied_types: role
+                .denied_types
+                .clone()
+                .unwrap_or_else(|| self.default.denied_types.clone()),
+        }
+    }
+}
+
+/// Limits on what one role may upload.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UploadPolicy {
+    /// Largest file in bytes; 0 leaves only `max_upload_size_bytes`.
+    pub max_file_size_bytes: u64,
+    /// When non-empty, only files of these types may be uploaded.
+    pub allowed_types: Vec<String>,
+    /// Files of these types are refused, even if also allowed.
+    pub denied_types: Vec<String>,
+}
+
+/// A role's deviations from the default [`UploadPolicy`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UploadPolicyOverride {
+    /// Replaces the default size limit.
+    pub max_file_size_bytes: Option<u64>,
+    /// Replaces the default allow list.
+    pub allowed_types: Option<Vec<String>>,
+    /// Replaces the default deny list.
+    pub denied_types: Option<Vec<String>>,
 }
 
 /// Configuration for file conversions.
+///
+/// Conversion requests pick a named profile rather than individual
+/// options, so the same profile always converts the same way and its
+/// results can be cached per profile.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ConversionConfig {
     /// Whether conversion is enabled.
     pub enabled: bool,
+    /// Conversion options, by profile name.
+    pub profiles: BTreeMap<String, ConversionProfile>,
+    /// Profile used when a request names none.
+    pub default_profile: String,
 }
 
 impl Default for ConversionConfig {
     fn default() -> Self {
-        Self { enabled: false }
+        Self {
+            enabled: false,
+            profiles: BTreeMap::from([
+                (
+                    "fast-preview".to_string(),
+                    ConversionProfile {
+                        concurrency: 4,
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "high-fidelity".to_string(),
+                    ConversionProfile {
+                        concurrency: 1,
+                        scan_deeper: true,
+                        ..Default::default()
+                    },
+                ),
+            ]),
+            default_profile: "fast-preview".to_string(),
+        }
+    }
+}
+
+impl ConversionConfig {
+    /// The profile called `name`, or the default profile without a name,
+    /// with its name.
+    pub fn profile<'a>(
+        &'a self,
+        name: Option<&'a str>,
+    ) -> Result<(&'a str, &'a ConversionProfile), String> {
+        let name = name.unwrap_or(&self.default_profile);
+        self.profiles.get(name).map(|p| (name, p)).ok_or_else(|| {
+            format!(
+                "unknown conversion profile '{}' (available: {})",
+                name,
+                self.profiles
+                    .keys()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })
+    }
+}
+
+/// A complete set of conversion options.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConversionProfile {
+    /// How several input files are converted.
+    pub mode: ConversionProfileMode,
+    /// Conversions run at once for one request, 1 to 4.
+    pub concurrency: u8,
+    /// Whether inputs are also looked for in subdirectories.
+    pub scan_deeper: bool,
+    /// Whether source files are deleted after conversion.
+    pub delete_source: bool,
+}
+
+impl Default for ConversionProfile {
+    fn default() -> Self {
+        Self {
+            mode: ConversionProfileMode::Single,
+            concurrency: 1,
+            scan_deeper: false,
+            delete_source: false,
+        }
+    }
+}
+
+/// How a conversion treats several input files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConversionProfileMode {
+    /// Each file converted on its own.
+    Single,
+    /// A primary assembly file with the parts it references.
+    Assembly,
+    /// All inputs combined into one output.
+    Combine,
+}
+
+/// Page previews of PDFs and office documents, rendered by external tools
+/// in the worker.
+///
+/// Office files are converted to PDF with LibreOffice, and PDFs rendered
+/// to one PNG per page with `pdftoppm` (poppler). Rendered pages are kept
+/// under `<data_root>/cache/previews`, keyed by content hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DocumentPreviewConfig {
+    /// Whether document previews are rendered. Without it only images
+    /// have previews, and the tools need not be installed.
+    pub enabled: bool,
+    /// LibreOffice executable.
+    pub office_command: String,
+    /// `pdftoppm` executable.
+    pub pdf_command: String,
+    /// Seconds each tool may run before it is killed.
+    pub timeout_seconds: u64,
+    /// Pages rendered per document.
+    pub max_pages: u32,
+    /// Rendering resolution.
+    pub resolution_dpi: u32,
+    /// Larger files are not previewed.
+    pub max_file_size_bytes: u64,
+    /// Documents rendered at once by each worker.
+    pub concurrency: usize,
+}
+
+impl Default for DocumentPreviewConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            office_command: "soffice".to_string(),
+            pdf_command: "pdftoppm".to_string(),
+            timeout_seconds: 120,
+            max_pages: 20,
+            resolution_dpi: 96,
+            max_file_size_bytes: 100 * 1024 * 1024,
+            concurrency: 1,
+        }
+    }
+}
+
+/// Thumbnail sizes and output formats.
+///
+/// Sizes are named so clients ask for a role (`icon`, `grid`) rather than
+/// pixels; the value is the longest edge. Thumbnails keep the aspect ratio
+/// and never upscale. Each size and format is rendered and cached on its
+/// own, on first request or by [`ThumbnailPregenConfig`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThumbnailConfig {
+    /// Longest edge in pixels, by size name.
+    pub sizes: BTreeMap<String, u32>,
+    /// Size served when the request names none.
+    pub default_size: String,
+    /// Formats offered, most preferred first; each request gets the first
+    /// its `Accept` header allows, and the first listed otherwise.
+    pub formats: Vec<ThumbnailFormat>,
+    /// Encoder quality from 1 to 100 for JPEG and AVIF. WebP and PNG are
+    /// lossless.
+    pub quality: u8,
+}
+
+impl Default for ThumbnailConfig {
+    fn default() -> Self {
+        Self {
+            sizes: [
+                ("icon", 64),
+                ("small", 128),
+                ("grid", 256),
+                ("preview", 512),
+                ("large", 1024),
+            ]
+            .into_iter()
+            .map(|(name, edge)| (name.to_string(), edge))
+            .collect(),
+            default_size: "grid".to_string(),
+            formats: vec![ThumbnailFormat::Webp, ThumbnailFormat::Jpeg],
+            quality: 80,
+        }
+    }
+}
+
+impl ThumbnailConfig {
+    /// Longest edge of a size, given by name or as a pixel count that is
+    /// one of the configured sizes.
+    pub fn edge(&self, size: &str) -> Option<u32> {
+        if let Some(&edge) = self.sizes.get(size) {
+            return Some(edge);
+        }
+        size.parse::<u32>()
+            .ok()
+            .filter(|edge| self.sizes.values().any(|e| e == edge))
+    }
+}
+
+/// Encoding of a thumbnail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThumbnailFormat {
+    /// AVIF; smallest, slowest to encode.
+    Avif,
+    /// Lossless WebP.
+    Webp,
+    /// JPEG; understood everywhere, no transparency.
+    Jpeg,
+    /// PNG.
+    Png,
+}
+
+impl ThumbnailFormat {
+    /// The format as written in configuration.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Avif => "avif",
+            Self::Webp => "webp",
+            Self::Jpeg => "jpeg",
+            Self::Png => "png",
+        }
+    }
+
+    /// MIME type of the encoded image.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Avif => "image/avif",
+            Self::Webp => "image/webp",
+            Self::Jpeg => "image/jpeg",
+            Self::Png => "image/png",
+        }
+    }
+}
+
+impl std::fmt::Display for ThumbnailFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Eager thumbnail generation after upload.
+///
+/// Without it thumbnails are rendered on the first preview request, which
+/// makes the first view of a freshly uploaded folder slow.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThumbnailPregenConfig {
+    /// Whether uploads queue thumbnail generation.
+    pub enabled: bool,
+    /// MIME types generated eagerly; others stay lazy.
+    pub mime_types: Vec<String>,
+    /// Files larger than this are left to lazy generation.
+    pub max_file_size_bytes: u64,
+    /// Thumbnails rendered at the same time per worker.
+    pub concurrency: usize,
+    /// Names of the sizes rendered, in every offered format.
+    pub sizes: Vec<String>,
+}
+
+impl Default for ThumbnailPregenConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            mime_types: ["image/jpeg", "image/png", "image/gif", "image/webp"]
+                .map(String::from)
+                .to_vec(),
+            max_file_size_bytes: 52_428_800, // 50 MB
+            concurrency: 2,
+            sizes: vec!["icon".to_string(), "grid".to_string()],
+        }
+    }
+}
+
+impl ThumbnailPregenConfig {
+    /// Whether a file of this type and size is generated eagerly.
+    pub fn applies_to(&self, mime_type: Option<&str>, size_bytes: u64) -> bool {
+        self.enabled
+            && size_bytes <= self.max_file_size_bytes
+            && mime_type.is_some_and(|mime| self.mime_types.iter().any(|m| m == mime))
+    }
+}
+
+/// Limits on post-upload processing: assembling chunked uploads and
+/// running `AfterUpload` hooks. Uploads beyond the limit wait for a slot;
+/// the limit is independent of how many requests the server accepts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UploadProcessingConfig {
+    /// Uploads processed at the same time per server.
+    pub concurrency: usize,
+}
+
+impl Default for UploadProcessingConfig {
+    fn default() -> Self {
+        Self { concurrency: 4 }
     }
 }
 
+/// Limits on folder downloads as ZIP. A limit of 0 disables it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ZipDownloadConfig {
+    /// Maximum total size of the files in one archive.
+    pub max_total_bytes: u64,
+    /// Maximum number of files in one archive.
+    pub max_files: u64,
+}
+
+impl Default for ZipDownloadConfig {
+    fn default() -> Self {
+        Self {
+            max_total_bytes: 10_737_418_240, // 10 GB
+            max_files: 10_000,
+        }
+    }
+}
+
+/// Presigned direct transfers. Only backends that sign URLs (S3) support
+/// them; others answer with `STORAGE_PRESIGN_UNSUPPORTED`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DirectTransferConfig {
+    /// Whether clients may request presigned URLs.
+    pub enabled: bool,
+    /// Seconds a presigned upload or download URL stays valid.
+    pub url_expiry_seconds: u64,
+    /// Minutes a presigned upload may stay unfinalized before the object
+    /// is deleted. Must cover `url_expiry_seconds`.
+    pub finalize_within_minutes: u64,
+}
+
+impl Default for DirectTransferConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url_expiry_seconds: 900,
+            finalize_within_minutes: 60,
+        }
+    }
+}
+
+/// How often storage providers are probed, and how long a probe may take.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageHealthConfig {
+    /// Seconds between probes.
+    pub interval_seconds: u64,
+    /// Seconds before a probe counts as failed.
+    pub timeout_seconds: u64,
+}
+
+impl Default for StorageHealthConfig {
+    fn default() -> Self {
+        Self {
+            interval_seconds: 30,
+            timeout_seconds: 5,
+        }
+    }
+}
+
+/// Retries and timeouts of storage provider operations: a default
+/// policy, with per-provider-type overrides.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageRetryConfig {
+    /// Policy of every provider type without an override.
+    pub default: StorageRetryPolicy,
+    /// Overrides keyed by provider type (`local`, `s3`, `smb`). Fields
+    /// left unset fall back to `default`.
+    pub providers: HashMap<String, StorageRetryOverride>,
+}
+
+impl Default for StorageRetryConfig {
+    fn default() -> Self {
+        Self {
+            default: StorageRetryPolicy::default(),
+            providers: HashMap::from([(
+                "local".to_string(),
+                StorageRetryOverride {
+                    max_attempts: Some(1),
+                    ..Default::default()
+                },
+            )]),
+        }
+    }
+}
+
+impl StorageRetryConfig {
+    /// The effective policy of providers of `provider_type`.
+    pub fn for_provider(&self, provider_type: &str) -> StorageRetryPolicy {
+        let Some(provider) = self.providers.get(provider_type) else {
+            return self.default.clone();
+        };
+        let default = &self.default;
+        StorageRetryPolicy {
+            max_attempts: provider.max_attempts.unwrap_or(default.max_attempts),
+            base_delay_ms: provider.base_delay_ms.unwrap_or(default.base_delay_ms),
+            max_delay_ms: provider.max_delay_ms.unwrap_or(default.max_delay_ms),
+            retry_on: provider
+                .retry_on
+                .clone()
+                .unwrap_or_else(|| default.retry_on.clone()),
+            read_timeout_seconds: provider
+                .read_timeout_seconds
+                .unwrap_or(default.read_timeout_seconds),
+            write_timeout_seconds: provider
+                .write_timeout_seconds
+                .unwrap_or(default.write_timeout_seconds),
+            delete_timeout_seconds: provider
+                .delete_timeout_seconds
+                .unwrap_or(default.delete_timeout_seconds),
+            list_timeout_seconds: provider
+                .list_timeout_seconds
+                .unwrap_or(default.list_timeout_seconds),
+        }
+    }
+}
+
+/// How one provider type retries failed operations and how long an
+/// operation may take.
+///
+/// Timeouts cover an operation until the provider returns: a whole
+/// write, or the opening of a read stream.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageRetryPolicy {
+    /// Attempts per operation, including the first; 1 disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each further one.
+    pub base_delay_ms: u64,
+    /// Longest delay between retries.
+    pub max_delay_ms: u64,
+    /// Failures that are retried.
+    pub retry_on: Vec<StorageErrorClass>,
+    /// Seconds for reads, existence and metadata checks.
+    pub read_timeout_seconds: u64,
+    /// Seconds for writes, copies, moves and directory creation.
+    pub write_timeout_seconds: u64,
+    /// Seconds for deletes.
+    pub delete_timeout_seconds: u64,
+    /// Seconds for listings and capacity queries.
+    pub list_timeout_seconds: u64,
+}
+
+impl Default for StorageRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 200,
+            max_delay_ms: 5000,
+            retry_on: vec![
+                StorageErrorClass::Timeout,
+                StorageErrorClass::Unavailable,
+                StorageErrorClass::Backend,
+            ],
+            read_timeout_seconds: 30,
+            write_timeout_seconds: 900,
+            delete_timeout_seconds: 30,
+            list_timeout_seconds: 60,
+        }
+    }
+}
+
+/// A provider type's deviations from the default [`StorageRetryPolicy`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageRetryOverride {
+    /// Replaces the default attempt count.
+    pub max_attempts: Option<u32>,
+    /// Replaces the default first delay.
+    pub base_delay_ms: Option<u64>,
+    /// Replaces the default longest delay.
+    pub max_delay_ms: Option<u64>,
+    /// Replaces the default retryable failures.
+    pub retry_on: Option<Vec<StorageErrorClass>>,
+    /// Replaces the default read timeout.
+    pub read_timeout_seconds: Option<u64>,
+    /// Replaces the default write timeout.
+    pub write_timeout_seconds: Option<u64>,
+    /// Replaces the default delete timeout.
+    pub delete_timeout_seconds: Option<u64>,
+    /// Replaces the default listing timeout.
+    pub list_timeout_seconds: Option<u64>,
+}
+
+/// Classes of storage failures a retry policy can name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageErrorClass {
+    /// The operation ran into its timeout.
+    Timeout,
+    /// The backend refused service or was unreachable.
+    Unavailable,
+    /// The backend reported an I/O or protocol failure.
+    Backend,
+}
+
 /// Local filesystem storage configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocalStorageConfig {
@@ -84,6 +686,10 @@ pub struct S3StorageConfig {
     pub secret_key: String,
 }
 
+fn default_true() -> bool {
+    true
+}
+
 fn default_data_root() -> String {
     "./data".to_string()
 }
@@ -100,8 +706,16 @@ fn default_chunk_size() -> u64 {
     5_242_880 // 5 MB
 }
 
-fn default_thumbnail_sizes() -> Vec<u32> {
-    vec![64, 128, 256, 512]
+fn default_upload_session_ttl() -> u64 {
+    24
+}
+
+fn default_expiry_notice() -> u64 {
+    24
+}
+
+fn default_max_versions_per_file() -> u32 {
+    10
 }
 
 fn default_local_root() -> String {
@@ -111,3 +725,130 @@ fn default_local_root() -> String {
 fn default_region() -> String {
     "us-east-1".to_string()
 }
+
+/// Defaults and pacing for migrating files between storage providers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageMigrationConfig {
+    /// Files handled between progress updates, unless the migration sets
+    /// its own.
+    pub batch_size: u32,
+    /// Copy rate limit in bytes per second, unless the migration sets its
+    /// own; 0 is unlimited.
+    pub bytes_per_second: u64,
+    /// Files modified more recently than this are considered in use and
+    /// left for a later pass.
+    pub settle_seconds: u64,
+    /// Seconds a worker spends on a migration before handing it back to
+    /// the queue, so long migrations survive restarts and share workers.
+    pub slice_seconds: u64,
+    /// Seconds to wait before a pass retries files left behind.
+    pub retry_delay_seconds: u64,
+    /// Passes before a migration with files still left behind fails.
+    pub max_passes: u32,
+}
+
+impl Default for StorageMigrationConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 100,
+            bytes_per_second: 0,
+            settle_seconds: 60,
+            slice_seconds: 300,
+            retry_delay_seconds: 300,
+            max_passes: 10,
+        }
+    }
+}
+
+/// What happens when a file is stored under a name the target folder
+/// already uses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameCollision {
+    /// Refuse with `409 Conflict`.
+    #[default]
+    Reject,
+    /// Replace the existing file's content. Its previous content is kept
+    /// as a version unless `max_versions_per_file` is 0.
+    Overwrite,
+    /// Store under the first free name of the form `name (2).ext`.
+    Rename,
+}
+
+impl NameCollision {
+    /// The strategy as written in configuration and requests.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Reject => "reject",
+            Self::Overwrite => "overwrite",
+            Self::Rename => "rename",
+        }
+    }
+}
+
+impl std::fmt::Display for NameCollision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for NameCollision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "reject" => Ok(Self::Reject),
+            "overwrite" => Ok(Self::Overwrite),
+            "rename" => Ok(Self::Rename),
+            other => Err(format!(
+                "Unknown name collision strategy '{other}' (expected reject, overwrite or rename)"
+            )),
+        }
+    }
+}
+
+/// What happens to an upload when it cannot be scanned, e.g. because
+/// clamd is down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanFailPolicy {
+    /// Quarantine the file as if it were infected.
+    Closed,
+    /// Leave the file available and record that it was not scanned.
+    Open,
+}
+
+/// Virus scanning of uploads by a ClamAV daemon.
+///
+/// Files are streamed to clamd, so its `StreamMaxLength` must be at least
+/// `max_upload_size_bytes`; larger files are reported as scan failures.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AntivirusConfig {
+    /// Whether uploads are scanned.
+    pub enabled: bool,
+    /// clamd address: `host:port`, or `unix:/path/to/clamd.sock`.
+    pub clamd_address: String,
+    /// Seconds clamd may stall on connect, a write or its reply.
+    pub timeout_seconds: u64,
+    /// Outcome for files that could not be scanned.
+    pub fail_policy: ScanFailPolicy,
+    /// Storage directory infected files are moved to.
+    pub quarantine_dir: String,
+    /// Scans running at once; further uploads wait their turn.
+    pub max_concurrent_scans: u32,
+}
+
+impl Default for AntivirusConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            clamd_address: "127.0.0.1:3310".to_string(),
+            timeout_seconds: 60,
+            fail_policy: ScanFailPolicy::Closed,
+            quarantine_dir: ".quarantine".to_string(),
+            max_concurrent_scans: 4,
+        }
+    }
+}
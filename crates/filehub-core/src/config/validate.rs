@@ -0,0 +1,1834 @@
+//! Cross-field configuration checks run once at startup.
+//!
+//! Deserialization only guarantees that each value has the right type.
+//! [`AppConfig::validate`] checks the invariants that span fields or
+//! sections, and reports every violation at once so an operator can fix
+//! a config file in one pass.
+
+use std::fmt;
+
+use super::secret::{REMOTE_SCHEMES, SecretSource};
+use super::{AppConfig, AuditArchiveConfig, AuditSinkConfig, CorsGroup, RoutePattern};
+
+/// Cache providers understood by the cache manager.
+const CACHE_PROVIDERS: &[&str] = &["memory", "redis", "layered"];
+
+/// Storage providers understood by the storage manager.
+const STORAGE_PROVIDERS: &[&str] = &["local", "s3"];
+
+/// Provider types a storage retry policy can be set for.
+const STORAGE_PROVIDER_TYPES: &[&str] = &["local", "s3", "smb"];
+
+/// Role names, as used for per-role settings.
+const ROLES: &[&str] = &["admin", "manager", "creator", "viewer"];
+
+/// SMTP transport security modes.
+const EMAIL_SECURITY: &[&str] = &["starttls", "tls", "none"];
+
+/// Compression algorithms the compression layer is built with.
+const COMPRESSION_ALGORITHMS: &[&str] = &["br", "gzip"];
+
+/// Longest pepper version id; ids are stored as the Argon2 `keyid`.
+const MAX_PEPPER_ID_LEN: usize = 8;
+
+/// Largest thumbnail edge in pixels.
+const MAX_THUMBNAIL_EDGE: u32 = 4096;
+
+/// A single configuration problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// Dotted path of the offending key (e.g. `database.min_connections`).
+    pub field: String,
+    /// What is wrong and how to fix it.
+    pub message: String,
+}
+
+impl ConfigIssue {
+    fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl AppConfig {
+    /// Checks cross-field invariants, returning every violation found.
+    pub fn validate(&self) -> Result<(), Vec<ConfigIssue>> {
+        let mut issues = Vec::new();
+        self.validate_server(&mut issues);
+        self.validate_database(&mut issues);
+        self.validate_cache(&mut issues);
+        self.validate_auth(&mut issues);
+        self.validate_session(&mut issues);
+        self.validate_storage(&mut issues);
+        self.validate_realtime(&mut issues);
+        self.validate_license(&mut issues);
+        self.validate_worker(&mut issues);
+        self.validate_email(&mut issues);
+        self.validate_shares(&mut issues);
+        self.validate_audit(&mut issues);
+        self.validate_http_client(&mut issues);
+        self.validate_tracing(&mut issues);
+        self.validate_secrets(&mut issues);
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+
+    fn validate_server(&self, issues: &mut Vec<ConfigIssue>) {
+        let server = &self.server;
+
+        if server.tls.enabled {
+            if server.tls.cert_path.trim().is_empty() {
+                issues.push(ConfigIssue::new(
+                    "server.tls.cert_path",
+                    "required when TLS is enabled",
+                ));
+            }
+            if server.tls.key_path.trim().is_empty() {
+                issues.push(ConfigIssue::new(
+                    "server.tls.key_path",
+                    "required when TLS is enabled",
+                ));
+            }
+        }
+
+        if let Err(entry) = server.proxy.trusted_networks() {
+            issues.push(ConfigIssue::new(
+                "server.proxy.trusted",
+                format!("'{entry}' is not a CIDR network or an IP address"),
+            ));
+        }
+
+        let cors_policies = std::iter::once(("server.cors".to_string(), &server.cors.default))
+            .chain(CorsGroup::ALL.into_iter().filter_map(|group| {
+                let policy = server.cors.group_policy(group)?;
+                Some((format!("server.cors.{}", group.as_str()), policy))
+            }));
+        for (section, policy) in cors_policies {
+            if policy.allow_credentials && policy.allowed_origins.iter().any(|o| o == "*") {
+                issues.push(ConfigIssue::new(
+                    format!("{section}.allowed_origins"),
+                    "cannot contain \"*\" when allow_credentials is set",
+                ));
+            }
+        }
+
+        if server.metrics.enabled && server.metrics.admin_port == Some(server.port) {
+            issues.push(ConfigIssue::new(
+                "server.metrics.admin_port",
+                format!("must differ from server.port ({})", server.port),
+            ));
+        }
+
+        if server.grpc.enabled {
+            if server.grpc.port == server.port {
+                issues.push(ConfigIssue::new(
+                    "server.grpc.port",
+                    format!("must differ from server.port ({})", server.port),
+                ));
+            }
+            if server.grpc.max_message_bytes == 0 {
+                issues.push(ConfigIssue::new(
+                    "server.grpc.max_message_bytes",
+                    "must be greater than 0",
+                ));
+            }
+        }
+
+        if server.idempotency.enabled {
+            if server.idempotency.ttl_seconds == 0 {
+                issues.push(ConfigIssue::new(
+                    "server.idempotency.ttl_seconds",
+                    "must be greater than 0",
+                ));
+            }
+            if server.idempotency.max_body_bytes == 0 {
+                issues.push(ConfigIssue::new(
+                    "server.idempotency.max_body_bytes",
+                    "must be greater than 0",
+                ));
+            }
+        }
+
+        if server.rate_limit.enabled {
+            let rules = [
+                ("read", server.rate_limit.read),
+                ("write", server.rate_limit.write),
+                ("auth", server.rate_limit.auth),
+                ("upload", server.rate_limit.upload),
+            ];
+            for (group, rule) in rules {
+                if rule.requests == 0 || rule.window_seconds == 0 {
+                    issues.push(ConfigIssue::new(
+                        format!("server.rate_limit.{group}"),
+                        "requests and window_seconds must be greater than 0",
+                    ));
+                }
+            }
+        }
+
+        if server.quotas.enabled {
+            if !server
+                .quotas
+                .tiers
+                .contains_key(&server.quotas.default_tier)
+            {
+                issues.push(ConfigIssue::new(
+                    "server.quotas.default_tier",
+                    format!(
+                        "tier '{}' is not defined in server.quotas.tiers",
+                        server.quotas.default_tier
+                    ),
+                ));
+            }
+            if server.quotas.flush_interval_seconds == 0 {
+                issues.push(ConfigIssue::new(
+                    "server.quotas.flush_interval_seconds",
+                    "must be greater than 0",
+                ));
+            }
+        }
+
+        if server.compression.enabled {
+            for algorithm in &server.compression.algorithms {
+                if !COMPRESSION_ALGORITHMS.contains(&algorithm.as_str()) {
+                    issues.push(ConfigIssue::new(
+                        "server.compression.algorithms",
+                        format!(
+                            "unknown algorithm '{algorithm}' (expected one of: {})",
+                            COMPRESSION_ALGORITHMS.join(", ")
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn validate_database(&self, issues: &mut Vec<ConfigIssue>) {
+        let db = &self.database;
+
+        if db.url.trim().is_empty() {
+            issues.push(ConfigIssue::new("database.url", "must not be empty"));
+        }
+        if db.max_connections == 0 {
+            issues.push(ConfigIssue::new(
+                "database.max_connections",
+                "must be greater than 0",
+            ));
+        }
+        if db.min_connections > db.max_connections {
+            issues.push(ConfigIssue::new(
+                "database.min_connections",
+                format!(
+                    "({}) must not exceed database.max_connections ({})",
+                    db.min_connections, db.max_connections
+                ),
+            ));
+        }
+        if db.connect_attempts == 0 {
+            issues.push(ConfigIssue::new(
+                "database.connect_attempts",
+                "must be greater than 0",
+            ));
+        }
+        for (i, url) in db.replica_urls.iter().enumerate() {
+            if url.trim().is_empty() {
+                issues.push(ConfigIssue::new(
+                    format!("database.replica_urls[{}]", i),
+                    "must not be empty",
+                ));
+            } else if url.trim() == db.url.trim() {
+                issues.push(ConfigIssue::new(
+                    format!("database.replica_urls[{}]", i),
+                    "must differ from database.url",
+                ));
+            }
+        }
+        if db.slow_query_threshold_ms == Some(0) {
+            issues.push(ConfigIssue::new(
+                "database.slow_query_threshold_ms",
+                "must be greater than 0 (omit it to disable slow-query logging)",
+            ));
+        }
+    }
+
+    fn validate_cache(&self, issues: &mut Vec<ConfigIssue>) {
+        let cache = &self.cache;
+
+        if !CACHE_PROVIDERS.contains(&cache.provider.as_str()) {
+            issues.push(ConfigIssue::new(
+                "cache.provider",
+                format!(
+                    "unknown provider '{}' (expected one of: {})",
+                    cache.provider,
+                    CACHE_PROVIDERS.join(", ")
+                ),
+            ));
+        }
+
+        if matches!(cache.provider.as_str(), "redis" | "layered") {
+            let url = cache.redis.url.trim();
+            if url.is_empty() {
+                issues.push(ConfigIssue::new(
+                    "cache.redis.url",
+                    format!("required when cache.provider is '{}'", cache.provider),
+                ));
+            } else if !url.starts_with("redis://") && !url.starts_with("rediss://") {
+                issues.push(ConfigIssue::new(
+                    "cache.redis.url",
+                    "must start with redis:// or rediss://",
+                ));
+            }
+            if cache.redis.pool_size == 0 {
+                issues.push(ConfigIssue::new(
+                    "cache.redis.pool_size",
+                    "must be greater than 0",
+                ));
+            }
+        }
+
+        if cache.circuit_breaker.failure_threshold == 0 {
+            issues.push(ConfigIssue::new(
+                "cache.circuit_breaker.failure_threshold",
+                "must be greater than 0",
+            ));
+        }
+        if cache.circuit_breaker.open_seconds == 0 {
+            issues.push(ConfigIssue::new(
+                "cache.circuit_breaker.open_seconds",
+                "must be greater than 0",
+            ));
+        }
+        for (name, seconds) in cache.ttl.entries() {
+            if seconds == 0 {
+                issues.push(ConfigIssue::new(
+                    format!("cache.ttl.{}", name),
+                    "must be greater than 0",
+                ));
+            }
+        }
+        if cache.ttl.folder_tree > 0 && cache.ttl.folder_tree_generation < cache.ttl.folder_tree {
+            issues.push(ConfigIssue::new(
+                "cache.ttl.folder_tree_generation",
+                "must be at least cache.ttl.folder_tree",
+            ));
+        }
+    }
+
+    fn validate_auth(&self, issues: &mut Vec<ConfigIssue>) {
+        let pepper = &self.auth.password_pepper;
+        for id in pepper.versions.keys() {
+            let valid = !id.is_empty()
+                && id.len() <= MAX_PEPPER_ID_LEN
+                && id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid {
+                issues.push(ConfigIssue::new(
+                    format!("auth.password_pepper.versions.{id}"),
+                    format!("version id must be 1-{MAX_PEPPER_ID_LEN} letters, digits, '-' or '_'"),
+                ));
+            }
+        }
+        if let Some(current) = &pepper.current
+            && !pepper.versions.contains_key(current)
+        {
+            issues.push(ConfigIssue::new(
+                "auth.password_pepper.current",
+                format!("'{current}' is not one of auth.password_pepper.versions"),
+            ));
+        }
+
+        let impersonation = &self.auth.impersonation;
+        if impersonation.max_minutes == 0 {
+            issues.push(ConfigIssue::new(
+                "auth.impersonation.max_minutes",
+                "must be greater than 0",
+            ));
+        }
+        if impersonation.default_minutes == 0
+            || impersonation.default_minutes > impersonation.max_minutes
+        {
+            issues.push(ConfigIssue::new(
+                "auth.impersonation.default_minutes",
+                "must be between 1 and auth.impersonation.max_minutes",
+            ));
+        }
+        for route in &impersonation.blocked_routes {
+            if let Err(e) = RoutePattern::parse(route) {
+                issues.push(ConfigIssue::new("auth.impersonation.blocked_routes", e));
+            }
+        }
+
+        let hash = &self.auth.password_hash;
+        if hash.iterations == 0 {
+            issues.push(ConfigIssue::new(
+                "auth.password_hash.iterations",
+                "must be greater than 0",
+            ));
+        }
+        if hash.parallelism == 0 {
+            issues.push(ConfigIssue::new(
+                "auth.password_hash.parallelism",
+                "must be greater than 0",
+            ));
+        }
+        // Argon2 needs at least 8 KiB per lane.
+        if hash.memory_kib < 8 * hash.parallelism.max(1) {
+            issues.push(ConfigIssue::new(
+                "auth.password_hash.memory_kib",
+                format!(
+                    "({}) must be at least 8 KiB per lane of auth.password_hash.parallelism",
+                    hash.memory_kib
+                ),
+            ));
+        }
+
+        let anomaly = &self.auth.login_anomaly;
+        if anomaly.enabled {
+            if anomaly.geoip_database.trim().is_empty() {
+                issues.push(ConfigIssue::new(
+                    "auth.login_anomaly.geoip_database",
+                    "must not be empty when login anomaly detection is enabled",
+                ));
+            }
+            if anomaly.history_size == 0 {
+                issues.push(ConfigIssue::new(
+                    "auth.login_anomaly.history_size",
+                    "must be greater than 0",
+                ));
+            }
+            if anomaly.max_speed_kmh.is_nan() || anomaly.max_speed_kmh <= 0.0 {
+                issues.push(ConfigIssue::new(
+                    "auth.login_anomaly.max_speed_kmh",
+                    "must be greater than 0",
+                ));
+            }
+            if anomaly.min_distance_km.is_nan() || anomaly.min_distance_km < 0.0 {
+                issues.push(ConfigIssue::new(
+                    "auth.login_anomaly.min_distance_km",
+                    "must not be negative",
+                ));
+            }
+        }
+    }
+
+    fn validate_session(&self, issues: &mut Vec<ConfigIssue>) {
+        let session = &self.session;
+
+        if session.heartbeat_timeout_seconds <= session.heartbeat_interval_seconds {
+            issues.push(ConfigIssue::new(
+                "session.heartbeat_timeout_seconds",
+                format!(
+                    "({}) must be greater than session.heartbeat_interval_seconds ({})",
+                    session.heartbeat_timeout_seconds, session.heartbeat_interval_seconds
+                ),
+            ));
+        }
+
+        if session.idle_warning_lead_minutes > session.idle_grace_minutes {
+            issues.push(ConfigIssue::new(
+                "session.idle_warning_lead_minutes",
+                format!(
+                    "({}) must not exceed session.idle_grace_minutes ({})",
+                    session.idle_warning_lead_minutes, session.idle_grace_minutes
+                ),
+            ));
+        }
+    }
+
+    fn validate_storage(&self, issues: &mut Vec<ConfigIssue>) {
+        let storage = &self.storage;
+
+        match storage.default_provider.as_str() {
+            "local" => {
+                if storage.local.root_path.trim().is_empty() {
+                    issues.push(ConfigIssue::new(
+                        "storage.local.root_path",
+                        "required when storage.default_provider is 'local'",
+                    ));
+                }
+            }
+            "s3" => {
+                if !storage.s3.enabled {
+                    issues.push(ConfigIssue::new(
+                        "storage.s3.enabled",
+                        "must be true when storage.default_provider is 's3'",
+                    ));
+                }
+                if storage.s3.bucket.trim().is_empty() {
+                    issues.push(ConfigIssue::new(
+                        "storage.s3.bucket",
+                        "required when storage.default_provider is 's3'",
+                    ));
+                }
+            }
+            other => issues.push(ConfigIssue::new(
+                "storage.default_provider",
+                format!(
+                    "unknown provider '{other}' (expected one of: {})",
+                    STORAGE_PROVIDERS.join(", ")
+                ),
+            )),
+        }
+
+        if storage.chunk_size_bytes == 0 {
+            issues.push(ConfigIssue::new(
+                "storage.chunk_size_bytes",
+                "must be greater than 0",
+            ));
+        } else if storage.chunk_size_bytes > storage.max_upload_size_bytes {
+            issues.push(ConfigIssue::new(
+                "storage.chunk_size_bytes",
+                "must not exceed storage.max_upload_size_bytes",
+            ));
+        }
+        if storage.upload_session_ttl_hours == 0 {
+            issues.push(ConfigIssue::new(
+                "storage.upload_session_ttl_hours",
+                "must be greater than 0",
+            ));
+        }
+        let direct = &storage.direct_transfer;
+        if direct.enabled {
+            if !(1..=604_800).contains(&direct.url_expiry_seconds) {
+                issues.push(ConfigIssue::new(
+                    "storage.direct_transfer.url_expiry_seconds",
+                    "must be between 1 and 604800 (7 days)",
+                ));
+            }
+            if direct.finalize_within_minutes * 60 < direct.url_expiry_seconds {
+                issues.push(ConfigIssue::new(
+                    "storage.direct_transfer.finalize_within_minutes",
+                    "must cover storage.direct_transfer.url_expiry_seconds",
+                ));
+            }
+        }
+        if storage.health_check.interval_seconds == 0 {
+            issues.push(ConfigIssue::new(
+                "storage.health_check.interval_seconds",
+                "must be greater than 0",
+            ));
+        }
+        if storage.health_check.timeout_seconds == 0 {
+            issues.push(ConfigIssue::new(
+                "storage.health_check.timeout_seconds",
+                "must be greater than 0",
+            ));
+        }
+        let mut provider_types: Vec<&String> = storage.retry.providers.keys().collect();
+        provider_types.sort();
+        let policies = std::iter::once(("storage.retry.default".to_string(), None)).chain(
+            provider_types.into_iter().map(|provider_type| {
+                (
+                    format!("storage.retry.providers.{}", provider_type),
+                    Some(provider_type.as_str()),
+                )
+            }),
+        );
+        for (field, provider_type) in policies {
+            if let Some(provider_type) = provider_type
+                && !STORAGE_PROVIDER_TYPES.contains(&provider_type)
+            {
+                issues.push(ConfigIssue::new(
+                    field,
+                    format!("must be one of: {}", STORAGE_PROVIDER_TYPES.join(", ")),
+                ));
+                continue;
+            }
+            let policy = match provider_type {
+                Some(provider_type) => storage.retry.for_provider(provider_type),
+                None => storage.retry.default.clone(),
+            };
+            if !(1..=10).contains(&policy.max_attempts) {
+                issues.push(ConfigIssue::new(
+                    format!("{}.max_attempts", field),
+                    "must be between 1 and 10",
+                ));
+            }
+            if policy.base_delay_ms > policy.max_delay_ms {
+                issues.push(ConfigIssue::new(
+                    format!("{}.base_delay_ms", field),
+                    "must not exceed max_delay_ms",
+                ));
+            }
+            for (name, seconds) in [
+                ("read_timeout_seconds", policy.read_timeout_seconds),
+                ("write_timeout_seconds", policy.write_timeout_seconds),
+                ("delete_timeout_seconds", policy.delete_timeout_seconds),
+                ("list_timeout_seconds", policy.list_timeout_seconds),
+            ] {
+                if seconds == 0 {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.{}", field, name),
+                        "must be greater than 0",
+                    ));
+                }
+            }
+        }
+        if storage.migration.batch_size == 0 {
+            issues.push(ConfigIssue::new(
+                "storage.migration.batch_size",
+                "must be greater than 0",
+            ));
+        }
+        if storage.migration.max_passes == 0 {
+            issues.push(ConfigIssue::new(
+                "storage.migration.max_passes",
+                "must be greater than 0",
+            ));
+        }
+        if storage.migration.slice_seconds == 0 {
+            issues.push(ConfigIssue::new(
+                "storage.migration.slice_seconds",
+                "must be greater than 0",
+            ));
+        }
+        if storage.document_preview.enabled && storage.document_preview.timeout_seconds == 0 {
+            issues.push(ConfigIssue::new(
+                "storage.document_preview.timeout_seconds",
+                "must be greater than 0",
+            ));
+        }
+        if storage.document_preview.enabled && storage.document_preview.max_pages == 0 {
+            issues.push(ConfigIssue::new(
+                "storage.document_preview.max_pages",
+                "must be greater than 0",
+            ));
+        }
+        if storage.document_preview.enabled && storage.document_preview.concurrency == 0 {
+            issues.push(ConfigIssue::new(
+                "storage.document_preview.concurrency",
+                "must be greater than 0",
+            ));
+        }
+        if storage.antivirus.enabled && storage.antivirus.clamd_address.trim().is_empty() {
+            issues.push(ConfigIssue::new(
+                "storage.antivirus.clamd_address",
+                "must be set when antivirus is enabled",
+            ));
+        }
+        if storage.antivirus.enabled && storage.antivirus.timeout_seconds == 0 {
+            issues.push(ConfigIssue::new(
+                "storage.antivirus.timeout_seconds",
+                "must be greater than 0",
+            ));
+        }
+        if storage.antivirus.enabled && storage.antivirus.max_concurrent_scans == 0 {
+            issues.push(ConfigIssue::new(
+                "storage.antivirus.max_concurrent_scans",
+                "must be greater than 0",
+            ));
+        }
+        if storage.max_versions_per_file == 0 {
+            issues.push(ConfigIssue::new(
+                "storage.max_versions_per_file",
+                "must be greater than 0",
+            ));
+        }
+        if storage.thumbnail_pregen.enabled && storage.thumbnail_pregen.concurrency == 0 {
+            issues.push(ConfigIssue::new(
+                "storage.thumbnail_pregen.concurrency",
+                "must be greater than 0 when thumbnail pre-generation is enabled",
+            ));
+        }
+        let thumbnails = &storage.thumbnails;
+        if let Some((name, _)) = thumbnails
+            .sizes
+            .iter()
+            .find(|(_, edge)| !(1..=MAX_THUMBNAIL_EDGE).contains(*edge))
+        {
+            issues.push(ConfigIssue::new(
+                format!("storage.thumbnails.sizes.{name}"),
+                format!("must be between 1 and {MAX_THUMBNAIL_EDGE} pixels"),
+            ));
+        }
+        if !thumbnails.sizes.contains_key(&thumbnails.default_size) {
+            issues.push(ConfigIssue::new(
+                "storage.thumbnails.default_size",
+                format!("'{}' is not one of the sizes", thumbnails.default_size),
+            ));
+        }
+        if thumbnails.formats.is_empty() {
+            issues.push(ConfigIssue::new(
+                "storage.thumbnails.formats",
+                "must list at least one format",
+            ));
+        }
+        if !(1..=100).contains(&thumbnails.quality) {
+            issues.push(ConfigIssue::new(
+                "storage.thumbnails.quality",
+                "must be between 1 and 100",
+            ));
+        }
+        let conversions = &storage.conversions;
+        for (name, profile) in &conversions.profiles {
+            if !(1..=4).contains(&profile.concurrency) {
+                issues.push(ConfigIssue::new(
+                    format!("storage.conversions.profiles.{name}.concurrency"),
+                    "must be between 1 and 4",
+                ));
+            }
+        }
+        if !conversions
+            .profiles
+            .contains_key(&conversions.default_profile)
+        {
+            issues.push(ConfigIssue::new(
+                "storage.conversions.default_profile",
+                format!(
+                    "'{}' is not one of the profiles",
+                    conversions.default_profile
+                ),
+            ));
+        }
+        if let Some(name) = storage
+            .thumbnail_pregen
+            .sizes
+            .iter()
+            .find(|name| !thumbnails.sizes.contains_key(*name))
+        {
+            issues.push(ConfigIssue::new(
+                "storage.thumbnail_pregen.sizes",
+                format!("'{name}' is not one of storage.thumbnails.sizes"),
+            ));
+        }
+
+        let policy = &storage.upload_policy;
+        let mut type_lists = vec![
+            (
+                "storage.upload_policy.default.allowed_types".to_string(),
+                &policy.default.allowed_types,
+            ),
+            (
+                "storage.upload_policy.default.denied_types".to_string(),
+                &policy.default.denied_types,
+            ),
+        ];
+        for (role, over) in &policy.roles {
+            if !ROLES.contains(&role.as_str()) {
+                issues.push(ConfigIssue::new(
+                    format!("storage.upload_policy.roles.{}", role),
+                    format!("unknown role, expected one of: {}", ROLES.join(", ")),
+                ));
+            }
+            if let Some(types) = &over.allowed_types {
+                type_lists.push((
+                    format!("storage.upload_policy.roles.{}.allowed_types", role),
+                    types,
+                ));
+            }
+            if let Some(types) = &over.denied_types {
+                type_lists.push((
+                    format!("storage.upload_policy.roles.{}.denied_types", role),
+                    types,
+                ));
+            }
+        }
+        for (field, types) in type_lists {
+            if let Some(bad) = types
+                .iter()
+                .find(|t| !(t.len() > 1 && (t.starts_with('.') || t.contains('/'))))
+            {
+                issues.push(ConfigIssue::new(
+                    field,
+                    format!(
+                        "'{}' is neither a MIME type (type/subtype) nor an extension (.ext)",
+                        bad
+                    ),
+                ));
+            }
+        }
+    }
+
+    fn validate_license(&self, issues: &mut Vec<ConfigIssue>) {
+        let pool = &self.license.pool;
+
+        if pool.critical_threshold_percent > 100 {
+            issues.push(ConfigIssue::new(
+                "license.pool.critical_threshold_percent",
+                "must be at most 100",
+            ));
+        }
+        if pool.warning_threshold_percent >= pool.critical_threshold_percent {
+            issues.push(ConfigIssue::new(
+                "license.pool.warning_threshold_percent",
+                format!(
+                    "({}) must be below license.pool.critical_threshold_percent ({})",
+                    pool.warning_threshold_percent, pool.critical_threshold_percent
+                ),
+            ));
+        }
+        if self.license.borrow.enabled && self.license.borrow.max_borrow_hours == 0 {
+            issues.push(ConfigIssue::new(
+                "license.borrow.max_borrow_hours",
+                "must be greater than 0 when borrowing is enabled",
+            ));
+        }
+        let mut seen = Vec::new();
+        for (i, feature) in self.license.features.iter().enumerate() {
+            let name = feature.name.trim();
+            if name.is_empty() {
+                issues.push(ConfigIssue::new(
+                    format!("license.features[{}].name", i),
+                    "must not be empty",
+                ));
+            } else if seen.contains(&name) {
+                issues.push(ConfigIssue::new(
+                    format!("license.features[{}].name", i),
+                    format!("duplicate feature '{}'", name),
+                ));
+            } else {
+                seen.push(name);
+            }
+        }
+        let lease = &self.license.lease;
+        if lease.duration_seconds == 0 {
+            issues.push(ConfigIssue::new(
+                "license.lease.duration_seconds",
+                "must be greater than 0",
+            ));
+        }
+        if !(1..=99).contains(&lease.renew_at_percent) {
+            issues.push(ConfigIssue::new(
+                "license.lease.renew_at_percent",
+                "must be between 1 and 99",
+            ));
+        }
+    }
+
+    fn validate_worker(&self, issues: &mut Vec<ConfigIssue>) {
+        let worker = &self.worker;
+
+        if worker.enabled {
+            if worker.concurrency == 0 {
+                issues.push(ConfigIssue::new(
+                    "worker.concurrency",
+                    "must be greater than 0 when the worker is enabled",
+                ));
+            }
+            if worker.poll_interval_seconds == 0 {
+                issues.push(ConfigIssue::new(
+                    "worker.poll_interval_seconds",
+                    "must be greater than 0 when the worker is enabled",
+                ));
+            }
+        }
+
+        // The relay runs whether or not the worker does.
+        let outbox = &worker.outbox;
+        for (field, value) in [
+            ("worker.outbox.poll_interval_ms", outbox.poll_interval_ms),
+            ("worker.outbox.batch_size", u64::from(outbox.batch_size)),
+            ("worker.outbox.lease_seconds", outbox.lease_seconds),
+        ] {
+            if value == 0 {
+                issues.push(ConfigIssue::new(field, "must be greater than 0"));
+            }
+        }
+    }
+
+    fn validate_email(&self, issues: &mut Vec<ConfigIssue>) {
+        let email = &self.email;
+        if !email.enabled {
+            return;
+        }
+
+        if email.smtp_host.trim().is_empty() {
+            issues.push(ConfigIssue::new(
+                "email.smtp_host",
+                "required when email is enabled",
+            ));
+        }
+        if !email.from_address.contains('@') {
+            issues.push(ConfigIssue::new(
+                "email.from_address",
+                "must be an email address when email is enabled",
+            ));
+        }
+        if !EMAIL_SECURITY.contains(&email.security.as_str()) {
+            issues.push(ConfigIssue::new(
+                "email.security",
+                format!("must be one of: {}", EMAIL_SECURITY.join(", ")),
+            ));
+        }
+        if email.smtp_username.is_some() != email.smtp_password.is_some() {
+            issues.push(ConfigIssue::new(
+                "email.smtp_password",
+                "smtp_username and smtp_password must be set together",
+            ));
+        }
+        if email.max_attempts == 0 {
+            issues.push(ConfigIssue::new(
+                "email.max_attempts",
+                "must be greater than 0",
+            ));
+        }
+    }
+
+    fn validate_shares(&self, issues: &mut Vec<ConfigIssue>) {
+        let analytics = &self.shares.analytics;
+        if analytics.enabled && !(1..=1000).contains(&analytics.recent_limit) {
+            issues.push(ConfigIssue::new(
+                "shares.analytics.recent_limit",
+                "must be between 1 and 1000",
+            ));
+        }
+    }
+
+    fn validate_audit(&self, issues: &mut Vec<ConfigIssue>) {
+        let audit = &self.audit;
+        let retention = &audit.retention;
+        if retention.enabled {
+            if retention.retain_days == 0 {
+                issues.push(ConfigIssue::new(
+                    "audit.retention.retain_days",
+                    "must be greater than 0",
+                ));
+            }
+            if !(1..=100_000).contains(&retention.batch_size) {
+                issues.push(ConfigIssue::new(
+                    "audit.retention.batch_size",
+                    "must be between 1 and 100000",
+                ));
+            }
+            match &retention.archive {
+                AuditArchiveConfig::Storage { prefix, .. } => {
+                    if prefix.trim_matches('/').trim().is_empty() {
+                        issues.push(ConfigIssue::new(
+                            "audit.retention.archive.prefix",
+                            "must not be empty",
+                        ));
+                    }
+                }
+                AuditArchiveConfig::Directory { path } => {
+                    if path.trim().is_empty() {
+                        issues.push(ConfigIssue::new(
+                            "audit.retention.archive.path",
+                            "must not be empty",
+                        ));
+                    }
+                }
+            }
+        }
+
+        if audit.sinks.is_empty() {
+            return;
+        }
+
+        if audit.max_attempts == 0 {
+            issues.push(ConfigIssue::new(
+                "audit.max_attempts",
+                "must be greater than 0",
+            ));
+        }
+        let mut seen = Vec::new();
+        for (i, sink) in audit.sinks.iter().enumerate() {
+            let name = sink.name().trim();
+            if name.is_empty() {
+                issues.push(ConfigIssue::new(
+                    format!("audit.sinks[{}].name", i),
+                    "must not be empty",
+                ));
+            } else if seen.contains(&name) {
+                issues.push(ConfigIssue::new(
+                    format!("audit.sinks[{}].name", i),
+                    format!("duplicate sink '{}'", name),
+                ));
+            } else {
+                seen.push(name);
+            }
+
+            match sink {
+                AuditSinkConfig::Webhook {
+                    url,
+                    timeout_seconds,
+                    ..
+                } => {
+                    if !url.starts_with("http://") && !url.starts_with("https://") {
+                        issues.push(ConfigIssue::new(
+                            format!("audit.sinks[{}].url", i),
+                            "must be an http:// or https:// URL",
+                        ));
+                    }
+                    if *timeout_seconds == 0 {
+                        issues.push(ConfigIssue::new(
+                            format!("audit.sinks[{}].timeout_seconds", i),
+                            "must be greater than 0",
+                        ));
+                    }
+                }
+                AuditSinkConfig::Syslog {
+                    address, facility, ..
+                } => {
+                    if address
+                        .rsplit_once(':')
+                        .is_none_or(|(host, port)| host.is_empty() || port.parse::<u16>().is_err())
+                    {
+                        issues.push(ConfigIssue::new(
+                            format!("audit.sinks[{}].address", i),
+                            "must be host:port",
+                        ));
+                    }
+                    if *facility > 23 {
+                        issues.push(ConfigIssue::new(
+                            format!("audit.sinks[{}].facility", i),
+                            "must be at most 23",
+                        ));
+                    }
+                }
+                AuditSinkConfig::File { path, .. } => {
+                    if path.trim().is_empty() {
+                        issues.push(ConfigIssue::new(
+                            format!("audit.sinks[{}].path", i),
+                            "must not be empty",
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    fn validate_realtime(&self, issues: &mut Vec<ConfigIssue>) {
+        let coalescing = &self.realtime.notifications.upload_coalescing;
+        if coalescing.window_ms == 0 {
+            return;
+        }
+        if coalescing.threshold == 0 {
+            issues.push(ConfigIssue::new(
+                "realtime.notifications.upload_coalescing.threshold",
+                "must be greater than 0, so single uploads are still announced",
+            ));
+        }
+        if coalescing.max_delay_ms < coalescing.window_ms {
+            issues.push(ConfigIssue::new(
+                "realtime.notifications.upload_coalescing.max_delay_ms",
+                "must be at least window_ms",
+            ));
+        }
+    }
+
+    fn validate_http_client(&self, issues: &mut Vec<ConfigIssue>) {
+        let http = &self.http_client;
+        if http.timeout_seconds == 0 {
+            issues.push(ConfigIssue::new(
+                "http_client.timeout_seconds",
+                "must be greater than 0",
+            ));
+        }
+        if http.connect_timeout_seconds == 0 {
+            issues.push(ConfigIssue::new(
+                "http_client.connect_timeout_seconds",
+                "must be greater than 0",
+            ));
+        }
+        if http.retry_max_delay_ms < http.retry_base_delay_ms {
+            issues.push(ConfigIssue::new(
+                "http_client.retry_max_delay_ms",
+                "must be at least retry_base_delay_ms",
+            ));
+        }
+        if let Some(proxy) = &http.proxy
+            && !["http://", "https://", "socks5://"]
+                .iter()
+                .any(|scheme| proxy.starts_with(scheme))
+        {
+            issues.push(ConfigIssue::new(
+                "http_client.proxy",
+                "must be an http://, https:// or socks5:// URL",
+            ));
+        }
+        let limits = std::iter::once(("http_client.rate_limit".to_string(), &http.rate_limit))
+            .chain(
+                http.hosts
+                    .iter()
+                    .map(|(host, limit)| (format!("http_client.hosts.{}", host), limit)),
+            );
+        for (field, limit) in limits {
+            if !limit.requests_per_second.is_finite() || limit.requests_per_second < 0.0 {
+                issues.push(ConfigIssue::new(
+                    format!("{}.requests_per_second", field),
+                    "must be 0 or greater",
+                ));
+            } else if limit.requests_per_second > 0.0 && limit.burst == 0 {
+                issues.push(ConfigIssue::new(
+                    format!("{}.burst", field),
+                    "must be greater than 0 when rate limited",
+                ));
+            }
+        }
+    }
+
+    fn validate_secrets(&self, issues: &mut Vec<ConfigIssue>) {
+        for (path, secret) in self.secrets() {
+            match secret.source() {
+                Err(e) => issues.push(ConfigIssue::new(path, e)),
+                Ok(SecretSource::Literal(value)) if value.trim().is_empty() => {
+                    issues.push(ConfigIssue::new(path, "must not be empty"));
+                }
+                _ => {}
+            }
+        }
+        if self.references_secret_scheme("vault") && self.secrets.vault.address.trim().is_empty() {
+            issues.push(ConfigIssue::new(
+                "secrets.vault.address",
+                "required when a setting references vault:",
+            ));
+        }
+        if !matches!(
+            self.secrets.vault.token.source(),
+            Ok(SecretSource::Literal(_) | SecretSource::Env(_) | SecretSource::File(_))
+        ) {
+            issues.push(ConfigIssue::new(
+                "secrets.vault.token",
+                format!(
+                    "must be the token, an env: or a file: reference (not {})",
+                    REMOTE_SCHEMES.join("/")
+                ),
+            ));
+        }
+    }
+
+    fn validate_tracing(&self, issues: &mut Vec<ConfigIssue>) {
+        let tracing = &self.logging.tracing;
+        if !tracing.enabled {
+            return;
+        }
+        if !(tracing.otlp_endpoint.starts_with("http://")
+            || tracing.otlp_endpoint.starts_with("https://"))
+        {
+            issues.push(ConfigIssue::new(
+                "logging.tracing.otlp_endpoint",
+                "must be an http:// or https:// URL",
+            ));
+        }
+        if !(0.0..=1.0).contains(&tracing.sample_ratio) {
+            issues.push(ConfigIssue::new(
+                "logging.tracing.sample_ratio",
+                "must be between 0.0 and 1.0",
+            ));
+        }
+        if tracing.service_name.trim().is_empty() {
+            issues.push(ConfigIssue::new(
+                "logging.tracing.service_name",
+                "must not be empty",
+            ));
+        }
+        if tracing.export_timeout_seconds == 0 {
+            issues.push(ConfigIssue::new(
+                "logging.tracing.export_timeout_seconds",
+                "must be greater than 0",
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{
+        HostRateLimit, LicenseFeatureConfig, MetricsConfig, Secret, StorageRetryOverride,
+        UploadPolicyOverride,
+    };
+
+    fn base() -> AppConfig {
+        config::Config::builder()
+            .add_source(config::File::from_str(
+                include_str!("../../../../config/default.toml"),
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .and_then(|c| c.try_deserialize())
+            .expect("default.toml deserializes")
+    }
+
+    fn issue_fields(config: &AppConfig) -> Vec<String> {
+        config
+            .validate()
+            .err()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|i| i.field)
+            .collect()
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert_eq!(base().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_all_issues_are_reported() {
+        let mut config = base();
+        config.database.min_connections = 50;
+        config.auth.jwt_secret = Secret::new("");
+        assert_eq!(config.validate().unwrap_err().len(), 2);
+    }
+
+    #[test]
+    fn test_tls_requires_paths() {
+        let mut config = base();
+        config.server.tls.enabled = true;
+        config.server.tls.cert_path = String::new();
+        config.server.tls.key_path = String::new();
+        assert_eq!(
+            issue_fields(&config),
+            ["server.tls.cert_path", "server.tls.key_path"]
+        );
+    }
+
+    #[test]
+    fn test_metrics_port_must_differ() {
+        let mut config = base();
+        config.server.metrics.enabled = true;
+        config.server.metrics.admin_port = Some(config.server.port);
+        assert_eq!(issue_fields(&config), ["server.metrics.admin_port"]);
+    }
+
+    #[test]
+    fn test_metrics_default_to_local_listener() {
+        let metrics = base().server.metrics;
+        assert!(!metrics.public);
+        assert_eq!(metrics.admin_port, Some(9090));
+        assert_eq!(metrics.admin_host, "127.0.0.1");
+        assert!(!MetricsConfig::default().public);
+    }
+
+    #[test]
+    fn test_credentialed_cors_rejects_wildcard_origin() {
+        let config: AppConfig = config::Config::builder()
+            .add_source(config::File::from_str(
+                include_str!("../../../../config/default.toml"),
+                config::FileFormat::Toml,
+            ))
+            .add_source(config::File::from_str(
+                r#"
+                [server.cors]
+                allowed_origins = ["https://app.example.com"]
+                allow_credentials = true
+
+                [server.cors.public]
+                allowed_origins = ["*"]
+
+                [server.cors.admin]
+                allowed_origins = ["*"]
+                allow_credentials = true
+                "#,
+                config::FileFormat::Toml,
+            ))
+            .build()
+            .and_then(|c| c.try_deserialize())
+            .expect("CORS overlay deserializes");
+
+        let cors = &config.server.cors;
+        assert!(cors.policy(CorsGroup::Authenticated).allow_credentials);
+        assert!(!cors.policy(CorsGroup::Public).allow_credentials);
+        assert_eq!(
+            cors.policy(CorsGroup::Public).max_age_seconds,
+            3600,
+            "group fields default rather than inherit"
+        );
+        assert_eq!(issue_fields(&config), ["server.cors.admin.allowed_origins"]);
+    }
+
+    #[test]
+    fn test_trusted_proxies_must_be_networks() {
+        let mut config = base();
+        config.server.proxy.trusted = vec!["10.0.0.0/8".to_string(), "fd00::1".to_string()];
+        assert_eq!(config.validate(), Ok(()));
+
+        config.server.proxy.trusted.push("lb.internal".to_string());
+        assert_eq!(issue_fields(&config), ["server.proxy.trusted"]);
+    }
+
+    #[test]
+    fn test_grpc_port_must_differ() {
+        let mut config = base();
+        config.server.grpc.port = config.server.port;
+        assert!(issue_fields(&config).is_empty(), "ignored while disabled");
+        config.server.grpc.enabled = true;
+        assert_eq!(issue_fields(&config), ["server.grpc.port"]);
+    }
+
+    #[test]
+    fn test_idempotency_ttl_must_be_positive() {
+        let mut config = base();
+        config.server.idempotency.ttl_seconds = 0;
+        assert_eq!(issue_fields(&config), ["server.idempotency.ttl_seconds"]);
+        config.server.idempotency.enabled = false;
+        assert!(issue_fields(&config).is_empty(), "ignored while disabled");
+    }
+
+    #[test]
+    fn test_rate_limit_rules_must_be_positive() {
+        let mut config = base();
+        config.server.rate_limit.enabled = true;
+        config.server.rate_limit.upload.window_seconds = 0;
+        assert_eq!(issue_fields(&config), ["server.rate_limit.upload"]);
+    }
+
+    #[test]
+    fn test_quota_default_tier_must_exist() {
+        let mut config = base();
+        config.server.quotas.enabled = true;
+        config.server.quotas.default_tier = "gold".to_string();
+        config.server.quotas.flush_interval_seconds = 0;
+        assert_eq!(
+            issue_fields(&config),
+            [
+                "server.quotas.default_tier",
+                "server.quotas.flush_interval_seconds"
+            ]
+        );
+        config.server.quotas.enabled = false;
+        assert!(issue_fields(&config).is_empty(), "ignored while disabled");
+    }
+
+    #[test]
+    fn test_unknown_compression_algorithm() {
+        let mut config = base();
+        config.server.compression.enabled = true;
+        config.server.compression.algorithms = vec!["zstd".to_string()];
+        assert_eq!(issue_fields(&config), ["server.compression.algorithms"]);
+    }
+
+    #[test]
+    fn test_database_pool_bounds() {
+        let mut config = base();
+        config.database.min_connections = 30;
+        config.database.max_connections = 20;
+        assert_eq!(issue_fields(&config), ["database.min_connections"]);
+
+        config.database.min_connections = 0;
+        config.database.max_connections = 0;
+        assert_eq!(issue_fields(&config), ["database.max_connections"]);
+
+        config.database.max_connections = 1;
+        config.database.url = " ".to_string();
+        assert_eq!(issue_fields(&config), ["database.url"]);
+    }
+
+    #[test]
+    fn test_replica_urls() {
+        let mut config = base();
+        config.database.replica_urls = vec![
+            "postgres://replica:5432/wvsrs3".to_string(),
+            " ".to_string(),
+            config.database.url.clone(),
+        ];
+        assert_eq!(
+            issue_fields(&config),
+            ["database.replica_urls[1]", "database.replica_urls[2]"]
+        );
+    }
+
+    #[test]
+    fn test_cache_provider() {
+        let mut config = base();
+        config.cache.provider = "memcached".to_string();
+        assert_eq!(issue_fields(&config), ["cache.provider"]);
+    }
+
+    #[test]
+    fn test_cache_ttls() {
+        let mut config = base();
+        config.cache.ttl.session = 0;
+        config.cache.ttl.folder_tree_generation = 60;
+        assert_eq!(
+            issue_fields(&config),
+            ["cache.ttl.session", "cache.ttl.folder_tree_generation"]
+        );
+    }
+
+    #[test]
+    fn test_redis_provider_requires_url() {
+        let mut config = base();
+        config.cache.provider = "redis".to_string();
+        config.cache.redis.url = String::new();
+        assert_eq!(issue_fields(&config), ["cache.redis.url"]);
+
+        config.cache.provider = "layered".to_string();
+        config.cache.redis.url = "http://localhost:6379".to_string();
+        assert_eq!(issue_fields(&config), ["cache.redis.url"]);
+
+        config.cache.redis.url = "redis://localhost:6379".to_string();
+        config.cache.redis.pool_size = 0;
+        assert_eq!(issue_fields(&config), ["cache.redis.pool_size"]);
+    }
+
+    #[test]
+    fn test_jwt_secret_required() {
+        let mut config = base();
+        config.auth.jwt_secret = Secret::new("  ");
+        assert_eq!(issue_fields(&config), ["auth.jwt_secret"]);
+    }
+
+    #[test]
+    fn test_pepper_versions() {
+        let mut config = base();
+        let pepper = &mut config.auth.password_pepper;
+        pepper
+            .versions
+            .insert("1".to_string(), Secret::new("env:PEPPER_1"));
+        pepper.current = Some("1".to_string());
+        assert_eq!(config.validate(), Ok(()));
+
+        let pepper = &mut config.auth.password_pepper;
+        pepper.current = Some("2".to_string());
+        pepper
+            .versions
+            .insert("too-long-id".to_string(), Secret::new("env:PEPPER"));
+        assert_eq!(
+            issue_fields(&config),
+            [
+                "auth.password_pepper.versions.too-long-id",
+                "auth.password_pepper.current"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_impersonation_limits() {
+        let mut config = base();
+        config.auth.impersonation.default_minutes = 600;
+        config
+            .auth
+            .impersonation
+            .blocked_routes
+            .push("/api/files".to_string());
+        assert_eq!(
+            issue_fields(&config),
+            [
+                "auth.impersonation.default_minutes",
+                "auth.impersonation.blocked_routes"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_secret_references() {
+        let mut config = base();
+        config.auth.jwt_secret = Secret::new("vault:filehub/auth#jwt");
+        config
+            .auth
+            .password_pepper
+            .versions
+            .insert("1".to_string(), Secret::new("env:"));
+        assert_eq!(
+            issue_fields(&config),
+            ["auth.password_pepper.versions.1", "secrets.vault.address"]
+        );
+
+        config.auth.password_pepper.versions.clear();
+        config.secrets.vault.address = "https://vault:8200".to_string();
+        assert_eq!(config.validate(), Ok(()));
+
+        config.secrets.vault.token = Secret::new("aws-sm:vault-token");
+        assert_eq!(issue_fields(&config), ["secrets.vault.token"]);
+    }
+
+    #[test]
+    fn test_password_hash_memory_covers_lanes() {
+        let mut config = base();
+        config.auth.password_hash.parallelism = 4;
+        config.auth.password_hash.memory_kib = 16;
+        assert_eq!(issue_fields(&config), ["auth.password_hash.memory_kib"]);
+    }
+
+    #[test]
+    fn test_login_anomaly_checked_only_when_enabled() {
+        let mut config = base();
+        config.auth.login_anomaly.history_size = 0;
+        config.auth.login_anomaly.max_speed_kmh = 0.0;
+        assert!(issue_fields(&config).is_empty());
+
+        config.auth.login_anomaly.enabled = true;
+        assert_eq!(
+            issue_fields(&config),
+            [
+                "auth.login_anomaly.history_size",
+                "auth.login_anomaly.max_speed_kmh"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_heartbeat_timeout_exceeds_interval() {
+        let mut config = base();
+        config.session.heartbeat_timeout_seconds = config.session.heartbeat_interval_seconds;
+        assert_eq!(issue_fields(&config), ["session.heartbeat_timeout_seconds"]);
+    }
+
+    #[test]
+    fn test_idle_warning_lead_within_grace() {
+        let mut config = base();
+        config.session.idle_warning_lead_minutes = config.session.idle_grace_minutes + 1;
+        assert_eq!(issue_fields(&config), ["session.idle_warning_lead_minutes"]);
+    }
+
+    #[test]
+    fn test_storage_provider_requirements() {
+        let mut config = base();
+        config.storage.local.root_path = String::new();
+        assert_eq!(issue_fields(&config), ["storage.local.root_path"]);
+
+        let mut config = base();
+        config.storage.default_provider = "s3".to_string();
+        config.storage.s3.enabled = false;
+        config.storage.s3.bucket = String::new();
+        assert_eq!(
+            issue_fields(&config),
+            ["storage.s3.enabled", "storage.s3.bucket"]
+        );
+
+        config.storage.default_provider = "ftp".to_string();
+        assert_eq!(issue_fields(&config), ["storage.default_provider"]);
+    }
+
+    #[test]
+    fn test_thumbnail_pregen_concurrency() {
+        let mut config = base();
+        config.storage.thumbnail_pregen.concurrency = 0;
+        assert_eq!(
+            issue_fields(&config),
+            ["storage.thumbnail_pregen.concurrency"]
+        );
+
+        config.storage.thumbnail_pregen.enabled = false;
+        assert!(issue_fields(&config).is_empty());
+    }
+
+    #[test]
+    fn test_thumbnail_sizes_and_formats() {
+        let mut config = base();
+        config
+            .storage
+            .thumbnails
+            .sizes
+            .insert("huge".to_string(), 10_000);
+        config.storage.thumbnails.default_size = "tiny".to_string();
+        config.storage.thumbnails.formats.clear();
+        config.storage.thumbnails.quality = 0;
+        config.storage.thumbnail_pregen.sizes = vec!["tiny".to_string()];
+        assert_eq!(
+            issue_fields(&config),
+            [
+                "storage.thumbnails.sizes.huge",
+                "storage.thumbnails.default_size",
+                "storage.thumbnails.formats",
+                "storage.thumbnails.quality",
+                "storage.thumbnail_pregen.sizes",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_conversion_profiles() {
+        let mut config = base();
+        let conversions = &mut config.storage.conversions;
+        conversions
+            .profiles
+            .get_mut("fast-preview")
+            .unwrap()
+            .concurrency = 8;
+        conversions.default_profile = "draft".to_string();
+        assert_eq!(
+            issue_fields(&config),
+            [
+                "storage.conversions.profiles.fast-preview.concurrency",
+                "storage.conversions.default_profile",
+            ]
+        );
+
+        let conversions = &config.storage.conversions;
+        assert_eq!(
+            conversions.profile(Some("high-fidelity")).unwrap().0,
+            "high-fidelity"
+        );
+        let error = conversions.profile(None).unwrap_err();
+        assert!(error.contains("'draft'"));
+        assert!(error.contains("fast-preview, high-fidelity"));
+    }
+
+    #[test]
+    fn test_storage_retry_policies() {
+        let mut config = base();
+        let retry = &mut config.storage.retry;
+        assert_eq!(retry.for_provider("local").max_attempts, 1);
+        assert_eq!(retry.for_provider("s3"), retry.default);
+
+        retry.default.base_delay_ms = 10_000;
+        retry.providers.insert(
+            "s3".to_string(),
+            StorageRetryOverride {
+                max_attempts: Some(0),
+                write_timeout_seconds: Some(0),
+                ..Default::default()
+            },
+        );
+        retry
+            .providers
+            .insert("ftp".to_string(), StorageRetryOverride::default());
+        assert_eq!(
+            issue_fields(&config),
+            [
+                "storage.retry.default.base_delay_ms",
+                "storage.retry.providers.ftp",
+                "storage.retry.providers.local.base_delay_ms",
+                "storage.retry.providers.s3.max_attempts",
+                "storage.retry.providers.s3.base_delay_ms",
+                "storage.retry.providers.s3.write_timeout_seconds",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_upload_policy_types_and_roles() {
+        let mut config = base();
+        config.storage.upload_policy.default.denied_types = vec!["exe".to_string()];
+        config.storage.upload_policy.roles.insert(
+            "owner".to_string(),
+            UploadPolicyOverride {
+                allowed_types: Some(vec!["image/*".to_string(), ".png".to_string()]),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            issue_fields(&config),
+            [
+                "storage.upload_policy.roles.owner",
+                "storage.upload_policy.default.denied_types"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chunk_size_bounds() {
+        let mut config = base();
+        config.storage.chunk_size_bytes = 0;
+        assert_eq!(issue_fields(&config), ["storage.chunk_size_bytes"]);
+
+        config.storage.chunk_size_bytes = config.storage.max_upload_size_bytes + 1;
+        assert_eq!(issue_fields(&config), ["storage.chunk_size_bytes"]);
+    }
+
+    #[test]
+    fn test_direct_transfer_expiry() {
+        let mut config = base();
+        config.storage.direct_transfer.url_expiry_seconds = 7200;
+        assert_eq!(config.validate(), Ok(()));
+
+        config.storage.direct_transfer.enabled = true;
+        assert_eq!(
+            issue_fields(&config),
+            ["storage.direct_transfer.finalize_within_minutes"]
+        );
+        config.storage.direct_transfer.url_expiry_seconds = 0;
+        assert_eq!(
+            issue_fields(&config),
+            ["storage.direct_transfer.url_expiry_seconds"]
+        );
+    }
+
+    #[test]
+    fn test_license_thresholds() {
+        let mut config = base();
+        config.license.pool.warning_threshold_percent = 95;
+        config.license.pool.critical_threshold_percent = 90;
+        assert_eq!(
+            issue_fields(&config),
+            ["license.pool.warning_threshold_percent"]
+        );
+
+        config.license.pool.warning_threshold_percent = 80;
+        config.license.pool.critical_threshold_percent = 120;
+        assert_eq!(
+            issue_fields(&config),
+            ["license.pool.critical_threshold_percent"]
+        );
+    }
+
+    #[test]
+    fn test_license_features_need_unique_names() {
+        let mut config = base();
+        let feature = |name: &str| LicenseFeatureConfig {
+            name: name.to_string(),
+            roles: Vec::new(),
+            capabilities: Vec::new(),
+            admin_reserved_seats: None,
+        };
+        config.license.features = vec![feature("viewer"), feature(" "), feature("viewer")];
+        assert_eq!(
+            issue_fields(&config),
+            ["license.features[1].name", "license.features[2].name"]
+        );
+    }
+
+    #[test]
+    fn test_enabled_worker_requires_concurrency_and_interval() {
+        let mut config = base();
+        config.worker.enabled = true;
+        config.worker.concurrency = 0;
+        config.worker.poll_interval_seconds = 0;
+        assert_eq!(
+            issue_fields(&config),
+            ["worker.concurrency", "worker.poll_interval_seconds"]
+        );
+
+        config.worker.enabled = false;
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_audit_sinks() {
+        let mut config = base();
+        config.audit.sinks = vec![
+            AuditSinkConfig::Webhook {
+                name: "siem".to_string(),
+                url: "siem.example.com/ingest".to_string(),
+                headers: Default::default(),
+                timeout_seconds: 10,
+            },
+            AuditSinkConfig::Syslog {
+                name: "siem".to_string(),
+                address: "collector".to_string(),
+                facility: 13,
+            },
+            AuditSinkConfig::File {
+                name: "archive".to_string(),
+                path: "data/audit.jsonl".to_string(),
+            },
+        ];
+        assert_eq!(
+            issue_fields(&config),
+            [
+                "audit.sinks[0].url",
+                "audit.sinks[1].name",
+                "audit.sinks[1].address"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_audit_retention() {
+        let mut config = base();
+        config.audit.retention.retain_days = 0;
+        assert_eq!(config.validate(), Ok(()));
+
+        config.audit.retention.enabled = true;
+        config.audit.retention.batch_size = 0;
+        config.audit.retention.archive = AuditArchiveConfig::Directory {
+            path: " ".to_string(),
+        };
+        assert_eq!(
+            issue_fields(&config),
+            [
+                "audit.retention.retain_days",
+                "audit.retention.batch_size",
+                "audit.retention.archive.path"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_email_requires_smtp_settings() {
+        let mut config = base();
+        config.email.enabled = true;
+        config.email.smtp_username = Some("mailer".to_string());
+        assert_eq!(
+            issue_fields(&config),
+            [
+                "email.smtp_host",
+                "email.from_address",
+                "email.smtp_password"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_upload_coalescing_settings() {
+        let mut config = base();
+        config.realtime.notifications.upload_coalescing.threshold = 0;
+        config.realtime.notifications.upload_coalescing.max_delay_ms = 100;
+        assert_eq!(
+            issue_fields(&config),
+            [
+                "realtime.notifications.upload_coalescing.threshold",
+                "realtime.notifications.upload_coalescing.max_delay_ms"
+            ]
+        );
+
+        // Disabled coalescing is not checked.
+        config.realtime.notifications.upload_coalescing.window_ms = 0;
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_outbox_settings() {
+        let mut config = base();
+        config.worker.enabled = false;
+        config.worker.outbox.batch_size = 0;
+        config.worker.outbox.lease_seconds = 0;
+        assert_eq!(
+            issue_fields(&config),
+            ["worker.outbox.batch_size", "worker.outbox.lease_seconds"]
+        );
+    }
+
+    #[test]
+    fn test_http_client_settings() {
+        let mut config = base();
+        config.http_client.timeout_seconds = 0;
+        config.http_client.retry_max_delay_ms = 10;
+        config.http_client.proxy = Some("proxy.internal:3128".to_string());
+        config.http_client.hosts.insert(
+            "siem.example.com".to_string(),
+            HostRateLimit {
+                requests_per_second: 5.0,
+                burst: 0,
+            },
+        );
+        assert_eq!(
+            issue_fields(&config),
+            [
+                "http_client.timeout_seconds",
+                "http_client.retry_max_delay_ms",
+                "http_client.proxy",
+                "http_client.hosts.siem.example.com.burst"
+            ]
+        );
+
+        config.http_client = Default::default();
+        config.http_client.rate_limit.requests_per_second = 0.0;
+        config.http_client.rate_limit.burst = 0;
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_tracing_checked_only_when_enabled() {
+        let mut config = base();
+        config.logging.tracing.otlp_endpoint = "collector:4318".to_string();
+        config.logging.tracing.sample_ratio = 1.5;
+        assert_eq!(config.validate(), Ok(()));
+
+        config.logging.tracing.enabled = true;
+        assert_eq!(
+            issue_fields(&config),
+            [
+                "logging.tracing.otlp_endpoint",
+                "logging.tracing.sample_ratio"
+            ]
+        );
+    }
+}
@@ -14,6 +14,43 @@ pub struct WorkerConfig {
     /// Interval in seconds between job queue polls.
     #[serde(default = "default_poll_interval")]
     pub poll_interval_seconds: u64,
+    /// Relay of the domain event outbox.
+    #[serde(default)]
+    pub outbox: OutboxConfig,
+}
+
+/// Relay of the domain event outbox.
+///
+/// Events are recorded in the outbox with the change they describe and
+/// published by the relay, which is woken as soon as they commit and
+/// polls in case a wake-up is missed. Each row is leased to one relay
+/// at a time; one that is not marked delivered within the lease, because
+/// its relay stopped, is published again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxConfig {
+    /// Milliseconds between polls for undelivered events.
+    #[serde(default = "default_outbox_poll_interval")]
+    pub poll_interval_ms: u64,
+    /// Most events published per round.
+    #[serde(default = "default_outbox_batch_size")]
+    pub batch_size: u32,
+    /// Seconds an event is leased to the relay publishing it.
+    #[serde(default = "default_outbox_lease")]
+    pub lease_seconds: u64,
+    /// Hours delivered events are kept before cleanup deletes them.
+    #[serde(default = "default_outbox_retention")]
+    pub retain_delivered_hours: u64,
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_ms: default_outbox_poll_interval(),
+            batch_size: default_outbox_batch_size(),
+            lease_seconds: default_outbox_lease(),
+            retain_delivered_hours: default_outbox_retention(),
+        }
+    }
 }
 
 fn default_true() -> bool {
@@ -27,3 +64,19 @@ fn default_concurrency() -> usize {
 fn default_poll_interval() -> u64 {
     5
 }
+
+fn default_outbox_poll_interval() -> u64 {
+    1000
+}
+
+fn default_outbox_batch_size() -> u32 {
+    100
+}
+
+fn default_outbox_lease() -> u64 {
+    30
+}
+
+fn default_outbox_retention() -> u64 {
+    24
+}
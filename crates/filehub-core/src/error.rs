@@ -57,6 +57,14 @@ pub enum ErrorKind {
     BadRequest,
     /// The caller is not authorized to perform the action.
     Unauthorized,
+    /// The resource existed but is permanently unavailable.
+    Gone,
+    /// The request or uploaded content is larger than allowed.
+    PayloadTooLarge,
+    /// The uploaded content is of a type that is not allowed.
+    UnsupportedMediaType,
+    /// The request took longer than allowed and was abandoned.
+    Timeout,
 }
 
 impl fmt::Display for ErrorKind {
@@ -83,10 +91,259 @@ impl fmt::Display for ErrorKind {
             Self::Forbidden => write!(f, "FORBIDDEN"),
             Self::BadRequest => write!(f, "BAD_REQUEST"),
             Self::Unauthorized => write!(f, "UNAUTHORIZED"),
+            Self::Gone => write!(f, "GONE"),
+            Self::PayloadTooLarge => write!(f, "PAYLOAD_TOO_LARGE"),
+            Self::UnsupportedMediaType => write!(f, "UNSUPPORTED_MEDIA_TYPE"),
+            Self::Timeout => write!(f, "TIMEOUT"),
         }
     }
 }
 
+impl ErrorKind {
+    /// Every error kind, in declaration order.
+    pub const ALL: [ErrorKind; 25] = [
+        Self::NotFound,
+        Self::Authentication,
+        Self::Authorization,
+        Self::Validation,
+        Self::Conflict,
+        Self::RateLimit,
+        Self::Internal,
+        Self::Database,
+        Self::Cache,
+        Self::Storage,
+        Self::Configuration,
+        Self::License,
+        Self::Session,
+        Self::Plugin,
+        Self::Serialization,
+        Self::ExternalService,
+        Self::NotImplemented,
+        Self::ServiceUnavailable,
+        Self::Forbidden,
+        Self::BadRequest,
+        Self::Unauthorized,
+        Self::Gone,
+        Self::PayloadTooLarge,
+        Self::UnsupportedMediaType,
+        Self::Timeout,
+    ];
+
+    /// The stable code reported for errors of this kind that do not carry
+    /// a more specific one. See [`codes`].
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound => codes::RESOURCE_NOT_FOUND,
+            Self::Authentication => codes::AUTH_FAILED,
+            Self::Authorization => codes::AUTH_PERMISSION_DENIED,
+            Self::Validation => codes::REQUEST_VALIDATION_FAILED,
+            Self::Conflict => codes::RESOURCE_CONFLICT,
+            Self::RateLimit => codes::REQUEST_RATE_LIMITED,
+            Self::Internal => codes::SERVER_INTERNAL_ERROR,
+            Self::Database => codes::SERVER_DATABASE_ERROR,
+            Self::Cache => codes::SERVER_CACHE_ERROR,
+            Self::Storage => codes::STORAGE_ERROR,
+            Self::Configuration => codes::SERVER_CONFIGURATION_ERROR,
+            Self::License => codes::LICENSE_ERROR,
+            Self::Session => codes::SESSION_ERROR,
+            Self::Plugin => codes::PLUGIN_ERROR,
+            Self::Serialization => codes::SERVER_SERIALIZATION_ERROR,
+            Self::ExternalService => codes::UPSTREAM_SERVICE_ERROR,
+            Self::NotImplemented => codes::SERVER_NOT_IMPLEMENTED,
+            Self::ServiceUnavailable => codes::SERVER_UNAVAILABLE,
+            Self::Forbidden => codes::AUTH_FORBIDDEN,
+            Self::BadRequest => codes::REQUEST_INVALID,
+            Self::Unauthorized => codes::AUTH_UNAUTHORIZED,
+            Self::Gone => codes::RESOURCE_GONE,
+            Self::PayloadTooLarge => codes::REQUEST_TOO_LARGE,
+            Self::UnsupportedMediaType => codes::REQUEST_UNSUPPORTED_MEDIA_TYPE,
+            Self::Timeout => codes::REQUEST_TIMEOUT,
+        }
+    }
+}
+
+/// Stable, machine-readable error codes returned as `code` in API error
+/// bodies.
+///
+/// Codes are `UPPER_SNAKE_CASE` and namespaced by their leading segment
+/// (`AUTH_`, `FILE_`, `QUOTA_`, ...). Once published a code is never
+/// renamed or reused; clients may match on it, whereas the message text
+/// may change at any time. Every [`ErrorKind`] has a default code (see
+/// [`ErrorKind::code`]); call sites attach a more specific one with
+/// [`AppError::with_code`].
+pub mod codes {
+    // ── Kind defaults ────────────────────────────────────────────
+    /// The requested resource does not exist.
+    pub const RESOURCE_NOT_FOUND: &str = "RESOURCE_NOT_FOUND";
+    /// The resource conflicts with existing state.
+    pub const RESOURCE_CONFLICT: &str = "RESOURCE_CONFLICT";
+    /// The resource is no longer available.
+    pub const RESOURCE_GONE: &str = "RESOURCE_GONE";
+    /// Authentication failed.
+    pub const AUTH_FAILED: &str = "AUTH_FAILED";
+    /// The caller lacks the required permission.
+    pub const AUTH_PERMISSION_DENIED: &str = "AUTH_PERMISSION_DENIED";
+    /// The action is forbidden for the caller.
+    pub const AUTH_FORBIDDEN: &str = "AUTH_FORBIDDEN";
+    /// The request is not authenticated.
+    pub const AUTH_UNAUTHORIZED: &str = "AUTH_UNAUTHORIZED";
+    /// The request body or parameters failed validation.
+    pub const REQUEST_VALIDATION_FAILED: &str = "REQUEST_VALIDATION_FAILED";
+    /// The request is malformed.
+    pub const REQUEST_INVALID: &str = "REQUEST_INVALID";
+    /// The caller exceeded a rate limit.
+    pub const REQUEST_RATE_LIMITED: &str = "REQUEST_RATE_LIMITED";
+    /// The request body is larger than allowed.
+    pub const REQUEST_TOO_LARGE: &str = "REQUEST_TOO_LARGE";
+    /// The request body is of a type that is not allowed.
+    pub const REQUEST_UNSUPPORTED_MEDIA_TYPE: &str = "REQUEST_UNSUPPORTED_MEDIA_TYPE";
+    /// The request was not handled within its time limit.
+    pub const REQUEST_TIMEOUT: &str = "REQUEST_TIMEOUT";
+    /// An unexpected server-side failure.
+    pub const SERVER_INTERNAL_ERROR: &str = "SERVER_INTERNAL_ERROR";
+    /// A database operation failed.
+    pub const SERVER_DATABASE_ERROR: &str = "SERVER_DATABASE_ERROR";
+    /// A cache operation failed.
+    pub const SERVER_CACHE_ERROR: &str = "SERVER_CACHE_ERROR";
+    /// The server is misconfigured.
+    pub const SERVER_CONFIGURATION_ERROR: &str = "SERVER_CONFIGURATION_ERROR";
+    /// Encoding or decoding data failed.
+    pub const SERVER_SERIALIZATION_ERROR: &str = "SERVER_SERIALIZATION_ERROR";
+    /// The operation is not implemented.
+    pub const SERVER_NOT_IMPLEMENTED: &str = "SERVER_NOT_IMPLEMENTED";
+    /// The server is temporarily unavailable.
+    pub const SERVER_UNAVAILABLE: &str = "SERVER_UNAVAILABLE";
+    /// A storage backend operation failed.
+    pub const STORAGE_ERROR: &str = "STORAGE_ERROR";
+    /// A license check failed.
+    pub const LICENSE_ERROR: &str = "LICENSE_ERROR";
+    /// A session operation failed.
+    pub const SESSION_ERROR: &str = "SESSION_ERROR";
+    /// A plugin failed.
+    pub const PLUGIN_ERROR: &str = "PLUGIN_ERROR";
+    /// An upstream service call failed.
+    pub const UPSTREAM_SERVICE_ERROR: &str = "UPSTREAM_SERVICE_ERROR";
+
+    // ── Specific codes ───────────────────────────────────────────
+    /// Username or password is wrong.
+    pub const AUTH_INVALID_CREDENTIALS: &str = "AUTH_INVALID_CREDENTIALS";
+    /// The current password supplied for a change is wrong.
+    pub const AUTH_INVALID_PASSWORD: &str = "AUTH_INVALID_PASSWORD";
+    /// The bearer token is missing or malformed.
+    pub const AUTH_TOKEN_INVALID: &str = "AUTH_TOKEN_INVALID";
+    /// The bearer token has expired.
+    pub const AUTH_TOKEN_EXPIRED: &str = "AUTH_TOKEN_EXPIRED";
+    /// The bearer token has been revoked.
+    pub const AUTH_TOKEN_REVOKED: &str = "AUTH_TOKEN_REVOKED";
+    /// The session has expired or been terminated.
+    pub const SESSION_EXPIRED: &str = "SESSION_EXPIRED";
+    /// The account has been disabled by an administrator.
+    pub const AUTH_ACCOUNT_DISABLED: &str = "AUTH_ACCOUNT_DISABLED";
+    /// The login must be re-verified with a second factor.
+    pub const AUTH_MFA_REQUIRED: &str = "AUTH_MFA_REQUIRED";
+    /// The operation is not allowed in an impersonation session.
+    pub const AUTH_IMPERSONATION_BLOCKED: &str = "AUTH_IMPERSONATION_BLOCKED";
+    /// The file does not exist.
+    pub const FILE_NOT_FOUND: &str = "FILE_NOT_FOUND";
+    /// The upload exceeds the maximum file size.
+    pub const FILE_TOO_LARGE: &str = "FILE_TOO_LARGE";
+    /// The file was quarantined by the virus scanner.
+    pub const FILE_QUARANTINED: &str = "FILE_QUARANTINED";
+    /// The upload policy does not allow the file's type.
+    pub const FILE_TYPE_NOT_ALLOWED: &str = "FILE_TYPE_NOT_ALLOWED";
+    /// The folder does not exist.
+    pub const FOLDER_NOT_FOUND: &str = "FOLDER_NOT_FOUND";
+    /// The file is within its folder's retention period.
+    pub const RETENTION_ACTIVE: &str = "RETENTION_ACTIVE";
+    /// The file is under legal hold.
+    pub const RETENTION_LEGAL_HOLD: &str = "RETENTION_LEGAL_HOLD";
+    /// The storage backend cannot hand out presigned URLs.
+    pub const STORAGE_PRESIGN_UNSUPPORTED: &str = "STORAGE_PRESIGN_UNSUPPORTED";
+    /// A storage operation kept failing until its retries ran out.
+    pub const STORAGE_RETRIES_EXHAUSTED: &str = "STORAGE_RETRIES_EXHAUSTED";
+    /// The storage quota would be exceeded.
+    pub const QUOTA_EXCEEDED: &str = "QUOTA_EXCEEDED";
+    /// The caller's API call or bandwidth quota for the period is used up.
+    pub const API_QUOTA_EXCEEDED: &str = "API_QUOTA_EXCEEDED";
+    /// The share link password is wrong.
+    pub const SHARE_INVALID_PASSWORD: &str = "SHARE_INVALID_PASSWORD";
+    /// The share link is password protected and no valid access grant
+    /// was presented.
+    pub const SHARE_PASSWORD_REQUIRED: &str = "SHARE_PASSWORD_REQUIRED";
+    /// The share link has expired.
+    pub const SHARE_LINK_EXPIRED: &str = "SHARE_LINK_EXPIRED";
+    /// The share link has reached its download limit.
+    pub const SHARE_LINK_EXHAUSTED: &str = "SHARE_LINK_EXHAUSTED";
+    /// The share link has been revoked.
+    pub const SHARE_LINK_REVOKED: &str = "SHARE_LINK_REVOKED";
+    /// The user already has a pending access request on the resource.
+    pub const SHARE_ACCESS_REQUEST_PENDING: &str = "SHARE_ACCESS_REQUEST_PENDING";
+    /// The `Idempotency-Key` header is malformed.
+    pub const IDEMPOTENCY_KEY_INVALID: &str = "IDEMPOTENCY_KEY_INVALID";
+    /// The `Idempotency-Key` was already used for a different request.
+    pub const IDEMPOTENCY_KEY_REUSED: &str = "IDEMPOTENCY_KEY_REUSED";
+    /// A request with the same `Idempotency-Key` has not finished yet.
+    pub const IDEMPOTENCY_REQUEST_IN_PROGRESS: &str = "IDEMPOTENCY_REQUEST_IN_PROGRESS";
+
+    /// Every code defined above.
+    pub const ALL: &[&str] = &[
+        RESOURCE_NOT_FOUND,
+        RESOURCE_CONFLICT,
+        RESOURCE_GONE,
+        AUTH_FAILED,
+        AUTH_PERMISSION_DENIED,
+        AUTH_FORBIDDEN,
+        AUTH_UNAUTHORIZED,
+        REQUEST_VALIDATION_FAILED,
+        REQUEST_INVALID,
+        REQUEST_RATE_LIMITED,
+        REQUEST_TOO_LARGE,
+        REQUEST_UNSUPPORTED_MEDIA_TYPE,
+        REQUEST_TIMEOUT,
+        SERVER_INTERNAL_ERROR,
+        SERVER_DATABASE_ERROR,
+        SERVER_CACHE_ERROR,
+        SERVER_CONFIGURATION_ERROR,
+        SERVER_SERIALIZATION_ERROR,
+        SERVER_NOT_IMPLEMENTED,
+        SERVER_UNAVAILABLE,
+        STORAGE_ERROR,
+        LICENSE_ERROR,
+        SESSION_ERROR,
+        PLUGIN_ERROR,
+        UPSTREAM_SERVICE_ERROR,
+        AUTH_INVALID_CREDENTIALS,
+        AUTH_INVALID_PASSWORD,
+        AUTH_TOKEN_INVALID,
+        AUTH_TOKEN_EXPIRED,
+        AUTH_TOKEN_REVOKED,
+        SESSION_EXPIRED,
+        AUTH_ACCOUNT_DISABLED,
+        AUTH_MFA_REQUIRED,
+        AUTH_IMPERSONATION_BLOCKED,
+        FILE_NOT_FOUND,
+        FILE_TOO_LARGE,
+        FILE_QUARANTINED,
+        FILE_TYPE_NOT_ALLOWED,
+        FOLDER_NOT_FOUND,
+        RETENTION_ACTIVE,
+        RETENTION_LEGAL_HOLD,
+        STORAGE_PRESIGN_UNSUPPORTED,
+        STORAGE_RETRIES_EXHAUSTED,
+        QUOTA_EXCEEDED,
+        API_QUOTA_EXCEEDED,
+        SHARE_INVALID_PASSWORD,
+        SHARE_PASSWORD_REQUIRED,
+        SHARE_LINK_EXPIRED,
+        SHARE_LINK_EXHAUSTED,
+        SHARE_LINK_REVOKED,
+        SHARE_ACCESS_REQUEST_PENDING,
+        IDEMPOTENCY_KEY_INVALID,
+        IDEMPOTENCY_KEY_REUSED,
+        IDEMPOTENCY_REQUEST_IN_PROGRESS,
+    ];
+}
+
 /// The unified application error used throughout FileHub.
 ///
 /// All crate-specific errors are mapped into `AppError` using `From` impls
@@ -97,6 +354,8 @@ impl fmt::Display for ErrorKind {
 pub struct AppError {
     /// The category of error.
     pub kind: ErrorKind,
+    /// Stable machine-readable code (see [`codes`]).
+    pub code: &'static str,
     /// A human-readable error message.
     pub message: String,
     /// Optional underlying cause.
@@ -109,6 +368,7 @@ impl AppError {
     pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
         Self {
             kind,
+            code: kind.code(),
             message: message.into(),
             source: None,
         }
@@ -122,11 +382,18 @@ impl AppError {
     ) -> Self {
         Self {
             kind,
+            code: kind.code(),
             message: message.into(),
             source: Some(Box::new(source)),
         }
     }
 
+    /// Replace the kind's default code with a more specific one.
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = code;
+        self
+    }
+
     /// Create a not-found error.
     pub fn not_found(message: impl Into<String>) -> Self {
         Self::new(ErrorKind::NotFound, message)
@@ -221,6 +488,26 @@ impl AppError {
     pub fn unauthorized(message: impl Into<String>) -> Self {
         Self::new(ErrorKind::Unauthorized, message)
     }
+
+    /// Create a gone error.
+    pub fn gone(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Gone, message)
+    }
+
+    /// Create a payload too large error.
+    pub fn payload_too_large(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::PayloadTooLarge, message)
+    }
+
+    /// Create an unsupported media type error.
+    pub fn unsupported_media_type(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::UnsupportedMediaType, message)
+    }
+
+    /// Create a timeout error.
+    pub fn timeout(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Timeout, message)
+    }
 }
 
 impl IntoResponse for AppError {
@@ -233,8 +520,13 @@ impl IntoResponse for AppError {
             ErrorKind::Unauthorized => (StatusCode::UNAUTHORIZED, "UNAUTHORIZED"),
             ErrorKind::Forbidden => (StatusCode::FORBIDDEN, "FORBIDDEN"),
             ErrorKind::NotFound => (StatusCode::NOT_FOUND, "NOT_FOUND"),
+            ErrorKind::Gone => (StatusCode::GONE, "GONE"),
             ErrorKind::Conflict => (StatusCode::CONFLICT, "CONFLICT"),
             ErrorKind::RateLimit => (StatusCode::TOO_MANY_REQUESTS, "RATE_LIMITED"),
+            ErrorKind::PayloadTooLarge => (StatusCode::PAYLOAD_TOO_LARGE, "PAYLOAD_TOO_LARGE"),
+            ErrorKind::UnsupportedMediaType => {
+                (StatusCode::UNSUPPORTED_MEDIA_TYPE, "UNSUPPORTED_MEDIA_TYPE")
+            }
             ErrorKind::Database => (StatusCode::INTERNAL_SERVER_ERROR, "DATABASE_ERROR"),
             ErrorKind::Cache => (StatusCode::INTERNAL_SERVER_ERROR, "CACHE_ERROR"),
             ErrorKind::Storage => (StatusCode::INTERNAL_SERVER_ERROR, "STORAGE_ERROR"),
@@ -250,6 +542,7 @@ impl IntoResponse for AppError {
             ErrorKind::ServiceUnavailable => {
                 (StatusCode::SERVICE_UNAVAILABLE, "SERVICE_UNAVAILABLE")
             }
+            ErrorKind::Timeout => (StatusCode::GATEWAY_TIMEOUT, "GATEWAY_TIMEOUT"),
             ErrorKind::Internal => {
                 tracing::error!(error = %self.message, "Internal server error");
                 (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR")
@@ -258,8 +551,11 @@ impl IntoResponse for AppError {
 
         let body = ApiErrorResponse {
             error: error_code.to_string(),
+            code: self.code.to_string(),
+            status: status.as_u16(),
             message: self.message.clone(),
             details: None,
+            trace_id: crate::telemetry::current_trace_id(),
         };
 
         (status, Json(body)).into_response()
@@ -270,6 +566,7 @@ impl Clone for AppError {
     fn clone(&self) -> Self {
         Self {
             kind: self.kind,
+            code: self.code,
             message: self.message.clone(),
             source: None,
         }
@@ -301,3 +598,47 @@ impl From<config::ConfigError> for AppError {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_every_kind_has_a_unique_code() {
+        let mut seen = HashSet::new();
+        for kind in ErrorKind::ALL {
+            let code = kind.code();
+            assert!(!code.is_empty(), "{kind} has an empty code");
+            assert!(seen.insert(code), "{kind} reuses code {code}");
+        }
+    }
+
+    #[test]
+    fn test_codes_are_unique_and_namespaced() {
+        let mut seen = HashSet::new();
+        for code in codes::ALL {
+            assert!(seen.insert(code), "duplicate code {code}");
+            assert!(
+                code.contains('_')
+                    && code
+                        .chars()
+                        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'),
+                "{code} is not a namespaced UPPER_SNAKE_CASE code"
+            );
+        }
+        for kind in ErrorKind::ALL {
+            assert!(codes::ALL.contains(&kind.code()), "{kind} code not listed");
+        }
+    }
+
+    #[test]
+    fn test_with_code_overrides_default() {
+        let err = AppError::not_found("File not found");
+        assert_eq!(err.code, codes::RESOURCE_NOT_FOUND);
+        let err = err.with_code(codes::FILE_NOT_FOUND);
+        assert_eq!(err.code, codes::FILE_NOT_FOUND);
+        assert_eq!(err.clone().code, codes::FILE_NOT_FOUND);
+    }
+}
@@ -0,0 +1,189 @@
+//! In-process domain event bus.
+//!
+//! Publishers never block: the bus is a bounded broadcast channel, and a
+//! subscriber that falls more than the buffer behind skips the oldest
+//! events. Every skipped event is counted in [`EventBus::dropped`].
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use super::{DomainEvent, EventCategory};
+
+/// Default number of buffered events per subscriber.
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// Cheaply clonable handle to the domain event bus.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    /// Broadcast sender shared by all handles.
+    sender: broadcast::Sender<Arc<DomainEvent>>,
+    /// Events skipped by lagging subscribers.
+    dropped: Arc<AtomicU64>,
+}
+
+impl EventBus {
+    /// Create a bus buffering up to `capacity` events per subscriber.
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            sender,
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Publish an event to every current subscriber.
+    ///
+    /// Returns the number of subscribers it was delivered to; publishing
+    /// with no subscribers is not an error.
+    pub fn publish(&self, event: DomainEvent) -> usize {
+        self.sender.send(Arc::new(event)).unwrap_or(0)
+    }
+
+    /// Subscribe to every event.
+    pub fn subscribe(&self) -> EventSubscription {
+        self.subscribe_to(&[])
+    }
+
+    /// Subscribe to events in the given categories only. An empty slice
+    /// subscribes to everything.
+    pub fn subscribe_to(&self, categories: &[EventCategory]) -> EventSubscription {
+        EventSubscription {
+            receiver: self.sender.subscribe(),
+            categories: categories.to_vec(),
+            dropped: Arc::clone(&self.dropped),
+        }
+    }
+
+    /// Number of active subscribers.
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Total events skipped by subscribers that fell behind.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+/// A filtered view of the event stream.
+#[derive(Debug)]
+pub struct EventSubscription {
+    /// Underlying broadcast receiver.
+    receiver: broadcast::Receiver<Arc<DomainEvent>>,
+    /// Categories to deliver (empty = all).
+    categories: Vec<EventCategory>,
+    /// Shared drop counter of the bus.
+    dropped: Arc<AtomicU64>,
+}
+
+impl EventSubscription {
+    /// Wait for the next matching event.
+    ///
+    /// Returns `None` once every [`EventBus`] handle has been dropped.
+    pub async fn recv(&mut self) -> Option<Arc<DomainEvent>> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) if self.matches(&event) => return Some(event),
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    self.dropped.fetch_add(skipped, Ordering::Relaxed);
+                    tracing::warn!(skipped, "Event subscriber lagged, oldest events dropped");
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    fn matches(&self, event: &DomainEvent) -> bool {
+        self.categories.is_empty() || self.categories.contains(&event.payload.category())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::events::{EventPayload, FileEvent, ShareEvent};
+
+    fn file_event() -> DomainEvent {
+        DomainEvent::new(
+            None,
+            EventPayload::File(FileEvent::Locked {
+                file_id: Uuid::new_v4(),
+                locked_by: Uuid::new_v4(),
+            }),
+        )
+    }
+
+    fn share_event() -> DomainEvent {
+        DomainEvent::new(
+            None,
+            EventPayload::Share(ShareEvent::Expired {
+                share_id: Uuid::new_v4(),
+            }),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_publish_without_subscribers() {
+        let bus = EventBus::default();
+        assert_eq!(bus.publish(file_event()), 0);
+    }
+
+    #[tokio::test]
+    async fn test_subscriber_filters_by_category() {
+        let bus = EventBus::new(8);
+        let mut shares = bus.subscribe_to(&[EventCategory::Share]);
+        let mut all = bus.subscribe();
+
+        bus.publish(file_event());
+        bus.publish(share_event());
+
+        let got = shares.recv().await.unwrap();
+        assert_eq!(got.payload.category(), EventCategory::Share);
+        assert_eq!(
+            all.recv().await.unwrap().payload.category(),
+            EventCategory::File
+        );
+        assert_eq!(
+            all.recv().await.unwrap().payload.category(),
+            EventCategory::Share
+        );
+    }
+
+    #[tokio::test]
+    async fn test_full_buffer_drops_oldest_and_counts() {
+        let bus = EventBus::new(2);
+        let mut sub = bus.subscribe();
+
+        let ids: Vec<Uuid> = (0..5)
+            .map(|_| {
+                let event = file_event();
+                let id = event.id;
+                bus.publish(event);
+                id
+            })
+            .collect();
+
+        assert_eq!(sub.recv().await.unwrap().id, ids[3]);
+        assert_eq!(sub.recv().await.unwrap().id, ids[4]);
+        assert_eq!(bus.dropped(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_recv_ends_when_bus_dropped() {
+        let bus = EventBus::new(4);
+        let mut sub = bus.subscribe();
+        drop(bus);
+        assert!(sub.recv().await.is_none());
+    }
+}
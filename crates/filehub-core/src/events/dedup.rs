@@ -0,0 +1,82 @@
+//! Recognising redelivered events.
+//!
+//! Events reach the bus through the outbox relay at least once: a relay
+//! that stops between publishing an event and recording its delivery
+//! publishes it again. Consumers whose effects are not idempotent skip
+//! events whose ID they have already seen.
+
+use std::collections::{HashSet, VecDeque};
+
+use uuid::Uuid;
+
+/// Default number of event IDs remembered.
+pub const DEFAULT_REMEMBERED: usize = 4096;
+
+/// The IDs of the most recent events a consumer handled.
+#[derive(Debug, Clone)]
+pub struct RecentEvents {
+    /// Most IDs remembered; the oldest are forgotten first.
+    capacity: usize,
+    /// IDs in the order they were seen.
+    order: VecDeque<Uuid>,
+    /// The same IDs, for lookup.
+    seen: HashSet<Uuid>,
+}
+
+impl RecentEvents {
+    /// Remember up to `capacity` event IDs.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            order: VecDeque::with_capacity(capacity),
+            seen: HashSet::with_capacity(capacity),
+        }
+    }
+
+    /// Record `id`; returns `false` if it was seen before.
+    pub fn first_sighting(&mut self, id: Uuid) -> bool {
+        if !self.seen.insert(id) {
+            return false;
+        }
+        if self.order.len() == self.capacity
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.seen.remove(&oldest);
+        }
+        self.order.push_back(id);
+        true
+    }
+}
+
+impl Default for RecentEvents {
+    fn default() -> Self {
+        Self::new(DEFAULT_REMEMBERED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeats_are_recognised() {
+        let mut recent = RecentEvents::new(8);
+        let id = Uuid::new_v4();
+        assert!(recent.first_sighting(id));
+        assert!(!recent.first_sighting(id));
+        assert!(recent.first_sighting(Uuid::new_v4()));
+    }
+
+    #[test]
+    fn test_oldest_are_forgotten() {
+        let mut recent = RecentEvents::new(2);
+        let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        for id in &ids {
+            assert!(recent.first_sighting(*id));
+        }
+        // The first ID was pushed out by the third.
+        assert!(recent.first_sighting(ids[0]));
+        assert!(!recent.first_sighting(ids[2]));
+    }
+}
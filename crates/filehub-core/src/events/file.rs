@@ -21,6 +21,12 @@ pub enum FileEvent {
         size_bytes: u64,
         /// The MIME type (if known).
         mime_type: Option<String>,
+        /// Username of the uploader.
+        #[serde(default)]
+        actor_name: String,
+        /// Owner of the folder, notified of uploads by others.
+        #[serde(default)]
+        folder_owner_id: Option<Uuid>,
     },
     /// A file was downloaded.
     Downloaded {
@@ -84,4 +90,13 @@ pub enum FileEvent {
         /// The new version number.
         version_number: i32,
     },
+    /// An earlier version was made current again.
+    VersionRestored {
+        /// The file ID.
+        file_id: Uuid,
+        /// The version whose content was restored.
+        restored_version: i32,
+        /// The file's version number after the restore.
+        version_number: i32,
+    },
 }
@@ -0,0 +1,23 @@
+//! Folder-related domain events.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Events related to folder operations.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum FolderEvent {
+    /// A folder was moved, with everything below it.
+    Moved {
+        /// The root of the moved subtree.
+        folder_id: Uuid,
+        /// The folder name.
+        name: String,
+        /// The previous parent (`None` at the storage root).
+        from_parent_id: Option<Uuid>,
+        /// The new parent (`None` at the storage root).
+        to_parent_id: Option<Uuid>,
+        /// Folders below the root whose paths were rewritten.
+        descendant_count: u64,
+    },
+}
@@ -2,9 +2,14 @@
 //!
 //! Events are dispatched through the event bus and consumed by
 //! the real-time engine, notification system, audit logger,
-//! and plugin hook framework.
+//! and plugin hook framework. Services record them in the database
+//! outbox with the change they describe, and the outbox relay publishes
+//! them, so an event may be delivered more than once (see [`dedup`]).
 
+pub mod bus;
+pub mod dedup;
 pub mod file;
+pub mod folder;
 pub mod session;
 pub mod share;
 pub mod system;
@@ -14,7 +19,10 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+pub use bus::{EventBus, EventSubscription};
+pub use dedup::RecentEvents;
 pub use file::FileEvent;
+pub use folder::FolderEvent;
 pub use session::SessionEvent;
 pub use share::ShareEvent;
 pub use system::SystemEvent;
@@ -29,8 +37,16 @@ pub struct DomainEvent {
     pub timestamp: DateTime<Utc>,
     /// The user who caused the event (if applicable).
     pub actor_id: Option<Uuid>,
+    /// The administrator acting as `actor_id`, if the event was caused
+    /// through an impersonation session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonator_id: Option<Uuid>,
     /// The event payload.
     pub payload: EventPayload,
+    /// W3C `traceparent` of the span that published the event, so
+    /// consumers can continue the trace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub traceparent: Option<String>,
 }
 
 /// Union of all domain event types.
@@ -39,6 +55,8 @@ pub struct DomainEvent {
 pub enum EventPayload {
     /// A file-related event.
     File(FileEvent),
+    /// A folder-related event.
+    Folder(FolderEvent),
     /// A user-related event.
     User(UserEvent),
     /// A share-related event.
@@ -49,6 +67,115 @@ pub enum EventPayload {
     System(SystemEvent),
 }
 
+/// Top-level event domain, used to filter bus subscriptions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EventCategory {
+    /// [`EventPayload::File`].
+    File,
+    /// [`EventPayload::Folder`].
+    Folder,
+    /// [`EventPayload::User`].
+    User,
+    /// [`EventPayload::Share`].
+    Share,
+    /// [`EventPayload::Session`].
+    Session,
+    /// [`EventPayload::System`].
+    System,
+}
+
+impl EventPayload {
+    /// The domain this event belongs to.
+    pub fn category(&self) -> EventCategory {
+        match self {
+            Self::File(_) => EventCategory::File,
+            Self::Folder(_) => EventCategory::Folder,
+            Self::User(_) => EventCategory::User,
+            Self::Share(_) => EventCategory::Share,
+            Self::Session(_) => EventCategory::Session,
+            Self::System(_) => EventCategory::System,
+        }
+    }
+
+    /// Dotted action name, e.g. `file.version_created`.
+    pub fn action(&self) -> String {
+        let value = serde_json::to_value(self).unwrap_or_default();
+        let domain = value["domain"].as_str().unwrap_or_default().to_lowercase();
+        let kind = value["event"]["type"].as_str().unwrap_or_default();
+
+        let mut action = domain;
+        action.push('.');
+        for (i, c) in kind.chars().enumerate() {
+            if c.is_ascii_uppercase() {
+                if i > 0 {
+                    action.push('_');
+                }
+                action.push(c.to_ascii_lowercase());
+            } else {
+                action.push(c);
+            }
+        }
+        action
+    }
+
+    /// The resource type and ID this event is about.
+    pub fn target(&self) -> (&'static str, Option<Uuid>) {
+        match self {
+            Self::File(e) => match e {
+                FileEvent::Copied { new_file_id, .. } => ("file", Some(*new_file_id)),
+                FileEvent::Uploaded { file_id, .. }
+                | FileEvent::Downloaded { file_id, .. }
+                | FileEvent::Updated { file_id, .. }
+                | FileEvent::Deleted { file_id, .. }
+                | FileEvent::Moved { file_id, .. }
+                | FileEvent::Locked { file_id, .. }
+                | FileEvent::Unlocked { file_id, .. }
+                | FileEvent::VersionCreated { file_id, .. }
+                | FileEvent::VersionRestored { file_id, .. } => ("file", Some(*file_id)),
+            },
+            Self::Folder(e) => match e {
+                FolderEvent::Moved { folder_id, .. } => ("folder", Some(*folder_id)),
+            },
+            Self::Share(e) => match e {
+                ShareEvent::Created { share_id, .. }
+                | ShareEvent::Accessed { share_id, .. }
+                | ShareEvent::Revoked { share_id, .. }
+                | ShareEvent::Downloaded { share_id, .. }
+                | ShareEvent::Expired { share_id } => ("share", Some(*share_id)),
+                ShareEvent::AccessRequested { request_id, .. }
+                | ShareEvent::AccessRequestDecided { request_id, .. } => {
+                    ("access_request", Some(*request_id))
+                }
+            },
+            Self::User(e) => match e {
+                UserEvent::Created { user_id, .. }
+                | UserEvent::Updated { user_id, .. }
+                | UserEvent::Deleted { user_id, .. }
+                | UserEvent::RoleChanged { user_id, .. }
+                | UserEvent::StatusChanged { user_id, .. }
+                | UserEvent::PasswordChanged { user_id }
+                | UserEvent::AccountLocked { user_id, .. } => ("user", Some(*user_id)),
+            },
+            Self::Session(e) => match e {
+                SessionEvent::LimitReached { user_id, .. } => ("user", Some(*user_id)),
+                SessionEvent::Created { session_id, .. }
+                | SessionEvent::Destroyed { session_id, .. }
+                | SessionEvent::Terminated { session_id, .. }
+                | SessionEvent::Expired { session_id, .. }
+                | SessionEvent::HeartbeatReceived { session_id }
+                | SessionEvent::Idle { session_id, .. }
+                | SessionEvent::SeatAllocated { session_id, .. }
+                | SessionEvent::SeatReleased { session_id, .. } => ("session", Some(*session_id)),
+            },
+            Self::System(e) => match e {
+                SystemEvent::StorageAdded { storage_id, .. }
+                | SystemEvent::StorageRemoved { storage_id, .. } => ("storage", Some(*storage_id)),
+                _ => ("system", None),
+            },
+        }
+    }
+}
+
 impl DomainEvent {
     /// Create a new domain event.
     pub fn new(actor_id: Option<Uuid>, payload: EventPayload) -> Self {
@@ -56,7 +183,58 @@ impl DomainEvent {
             id: Uuid::new_v4(),
             timestamp: Utc::now(),
             actor_id,
+            impersonator_id: crate::types::Impersonator::current().map(|i| i.id()),
             payload,
+            traceparent: crate::telemetry::current_traceparent(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_and_target() {
+        let file_id = Uuid::new_v4();
+        let payload = EventPayload::File(FileEvent::VersionCreated {
+            file_id,
+            version_number: 2,
+        });
+        assert_eq!(payload.category(), EventCategory::File);
+        assert_eq!(payload.action(), "file.version_created");
+        assert_eq!(payload.target(), ("file", Some(file_id)));
+
+        let folder_id = Uuid::new_v4();
+        let payload = EventPayload::Folder(FolderEvent::Moved {
+            folder_id,
+            name: "cad".to_string(),
+            from_parent_id: None,
+            to_parent_id: Some(Uuid::new_v4()),
+            descendant_count: 3,
+        });
+        assert_eq!(payload.category(), EventCategory::Folder);
+        assert_eq!(payload.action(), "folder.moved");
+        assert_eq!(payload.target(), ("folder", Some(folder_id)));
+
+        let request_id = Uuid::new_v4();
+        let payload = EventPayload::Share(ShareEvent::AccessRequestDecided {
+            request_id,
+            resource_type: "file".to_string(),
+            resource_id: file_id,
+            resource_name: "part.step".to_string(),
+            requester_id: Uuid::new_v4(),
+            permission: "editor".to_string(),
+            approved: true,
+            actor_name: "alice".to_string(),
+        });
+        assert_eq!(payload.action(), "share.access_request_decided");
+        assert_eq!(payload.target(), ("access_request", Some(request_id)));
+
+        let payload = EventPayload::System(SystemEvent::ServerStarted {
+            version: "1.0".to_string(),
+        });
+        assert_eq!(payload.action(), "system.server_started");
+        assert_eq!(payload.target(), ("system", None));
+    }
+}
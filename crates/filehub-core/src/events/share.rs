@@ -17,6 +17,15 @@ pub enum ShareEvent {
         resource_id: Uuid,
         /// The share type (public_link, private_link, user_share).
         share_type: String,
+        /// The user shared with (for user shares).
+        #[serde(default)]
+        shared_with: Option<Uuid>,
+        /// Name of the shared resource.
+        #[serde(default)]
+        resource_name: String,
+        /// Username of the user who shared it.
+        #[serde(default)]
+        actor_name: String,
     },
     /// A share was accessed.
     Accessed {
@@ -46,4 +55,40 @@ pub enum ShareEvent {
         /// The share ID.
         share_id: Uuid,
     },
+    /// A user asked the owner of a resource for more access.
+    AccessRequested {
+        /// The access request ID.
+        request_id: Uuid,
+        /// The resource type.
+        resource_type: String,
+        /// The resource ID.
+        resource_id: Uuid,
+        /// Name of the resource.
+        resource_name: String,
+        /// Owner of the resource, who decides.
+        owner_id: Uuid,
+        /// Permission level asked for.
+        permission: String,
+        /// Username of the requester.
+        actor_name: String,
+    },
+    /// The owner of a resource approved or denied an access request.
+    AccessRequestDecided {
+        /// The access request ID.
+        request_id: Uuid,
+        /// The resource type.
+        resource_type: String,
+        /// The resource ID.
+        resource_id: Uuid,
+        /// Name of the resource.
+        resource_name: String,
+        /// The user who asked.
+        requester_id: Uuid,
+        /// Permission level asked for.
+        permission: String,
+        /// Whether access was granted.
+        approved: bool,
+        /// Username of the user who decided.
+        actor_name: String,
+    },
 }
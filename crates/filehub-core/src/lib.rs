@@ -10,6 +10,7 @@ pub mod config;
 pub mod error;
 pub mod events;
 pub mod result;
+pub mod telemetry;
 pub mod traits;
 pub mod types;
 
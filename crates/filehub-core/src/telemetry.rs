@@ -0,0 +1,74 @@
+//! W3C trace context helpers.
+//!
+//! Spans meant for the trace exporter use the [`TARGET`] target, which the
+//! log output filters out. With tracing disabled no subscriber is
+//! interested in that target, so those spans are never created and the
+//! helpers here return early.
+
+use std::collections::HashMap;
+
+use opentelemetry::global;
+use opentelemetry::trace::TraceContextExt;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Target of spans recorded only for trace export.
+pub const TARGET: &str = "otel";
+
+/// Header carrying the W3C trace context.
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// The current span's context as a `traceparent` value, or `None` when
+/// there is no exported span in scope.
+pub fn current_traceparent() -> Option<String> {
+    let span = Span::current();
+    if span.is_disabled() {
+        return None;
+    }
+    let mut carrier = HashMap::new();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&span.context(), &mut carrier)
+    });
+    carrier.remove(TRACEPARENT_HEADER)
+}
+
+/// Continues the trace described by `traceparent` in `span`. Malformed
+/// values are ignored and the span starts a new trace.
+pub fn set_parent(span: &Span, traceparent: &str) {
+    if span.is_disabled() {
+        return;
+    }
+    let carrier = HashMap::from([(TRACEPARENT_HEADER.to_string(), traceparent.to_string())]);
+    let parent = global::get_text_map_propagator(|propagator| propagator.extract(&carrier));
+    if parent.span().span_context().is_valid() {
+        let _ = span.set_parent(parent);
+    }
+}
+
+/// Hex id of the trace the current span belongs to.
+pub fn current_trace_id() -> Option<String> {
+    let span = Span::current();
+    if span.is_disabled() {
+        return None;
+    }
+    let context = span.context();
+    let span_context = context.span().span_context().clone();
+    span_context
+        .is_valid()
+        .then(|| span_context.trace_id().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_context_without_a_subscriber() {
+        assert_eq!(current_traceparent(), None);
+        assert_eq!(current_trace_id(), None);
+        set_parent(
+            &Span::current(),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        );
+    }
+}
@@ -6,6 +6,15 @@ use async_trait::async_trait;
 
 use crate::result::AppResult;
 
+/// A token bucket after [`CacheProvider::take_token`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenBucket {
+    /// Whether a token was taken.
+    pub allowed: bool,
+    /// Tokens left in the bucket; fractional while it refills.
+    pub tokens: f64,
+}
+
 /// Trait for cache backends (Redis, in-memory, or layered).
 ///
 /// All values are serialized as strings (JSON). The cache provider
@@ -43,6 +52,17 @@ pub trait CacheProvider: Send + Sync + std::fmt::Debug + 'static {
     /// Set the TTL on an existing key.
     async fn expire(&self, key: &str, ttl: Duration) -> AppResult<bool>;
 
+    /// Take one token from the bucket under `key`, which holds at most
+    /// `capacity` tokens and refills from empty to full over `refill`.
+    /// A missing bucket is full. Refilling and taking are one atomic step
+    /// for every client of the backend.
+    async fn take_token(
+        &self,
+        key: &str,
+        capacity: u32,
+        refill: Duration,
+    ) -> AppResult<TokenBucket>;
+
     /// Get a typed value by deserializing from JSON.
     async fn get_json<T: serde::de::DeserializeOwned + Send>(
         &self,
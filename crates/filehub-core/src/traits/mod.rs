@@ -1,15 +1,19 @@
 //! Core traits defined in `filehub-core` and implemented by other crates.
 
 pub mod cache;
+pub mod clock;
 pub mod plugin;
 pub mod repository;
 pub mod seat_allocator;
+pub mod secret;
 pub mod service;
 pub mod storage;
 
-pub use cache::CacheProvider;
+pub use cache::{CacheProvider, TokenBucket};
+pub use clock::{Clock, MockClock, SystemClock};
 pub use plugin::{HookContext, HookHandler, HookResult, Plugin};
 pub use repository::Repository;
 pub use seat_allocator::SeatAllocator;
+pub use secret::SecretBackend;
 pub use service::Service;
-pub use storage::StorageProvider;
+pub use storage::{PresignedRequest, StorageProvider};
@@ -0,0 +1,19 @@
+//! Secret backend trait for secrets kept outside the configuration.
+
+use async_trait::async_trait;
+
+use crate::result::AppResult;
+
+/// A store that configuration secrets can reference, such as Vault or
+/// AWS Secrets Manager.
+///
+/// A backend serves references of the form `<scheme>:<path>#<field>`.
+#[async_trait]
+pub trait SecretBackend: Send + Sync + 'static {
+    /// Reference scheme served, e.g. `"vault"`.
+    fn scheme(&self) -> &'static str;
+
+    /// Fetches the secret at `path`, or one field of it when the secret
+    /// is structured.
+    async fn fetch(&self, path: &str, field: Option<&str>) -> AppResult<String>;
+}
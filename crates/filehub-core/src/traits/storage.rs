@@ -1,11 +1,14 @@
 //! Storage provider trait for pluggable file storage backends.
 
+use std::collections::BTreeMap;
 use std::pin::Pin;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use bytes::Bytes;
 use futures::Stream;
 
+use crate::error::{AppError, codes};
 use crate::result::AppResult;
 
 /// Metadata about a stored object.
@@ -25,6 +28,21 @@ pub struct StorageObjectMeta {
     pub checksum_sha256: Option<String>,
 }
 
+/// A request a client sends directly to the storage backend, authorized
+/// by the signature in its URL.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PresignedRequest {
+    /// HTTP method.
+    pub method: String,
+    /// Signed URL.
+    pub url: String,
+    /// Headers the client must send with exactly these values; they are
+    /// part of the signature.
+    pub headers: BTreeMap<String, String>,
+    /// When the signature stops being accepted.
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
 /// A byte stream type used for reading file contents.
 pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send>>;
 
@@ -44,6 +62,12 @@ pub trait StorageProvider: Send + Sync + std::fmt::Debug + 'static {
     /// Read a file and return its byte stream.
     async fn read(&self, path: &str) -> AppResult<ByteStream>;
 
+    /// Read `len` bytes starting at `offset` as a byte stream.
+    ///
+    /// Object stores should issue a ranged GET rather than fetching the
+    /// whole object.
+    async fn read_range(&self, path: &str, offset: u64, len: u64) -> AppResult<ByteStream>;
+
     /// Read a file into memory as a complete byte vector.
     async fn read_bytes(&self, path: &str) -> AppResult<Bytes>;
 
@@ -79,4 +103,37 @@ pub trait StorageProvider: Send + Sync + std::fmt::Debug + 'static {
 
     /// Get the total and used capacity of this storage backend.
     async fn capacity(&self) -> AppResult<(u64, u64)>;
+
+    /// Presign a PUT of exactly `content_length` bytes of `content_type`
+    /// to `path`, valid for `expires_in`.
+    ///
+    /// Backends without signed URLs keep this default, which fails with
+    /// [`codes::STORAGE_PRESIGN_UNSUPPORTED`].
+    async fn presign_upload(
+        &self,
+        _path: &str,
+        _content_type: &str,
+        _content_length: u64,
+        _expires_in: Duration,
+    ) -> AppResult<PresignedRequest> {
+        Err(presign_unsupported(self.provider_type()))
+    }
+
+    /// Presign a GET of `path`, valid for `expires_in`. The response
+    /// carries `content_disposition` when given.
+    async fn presign_download(
+        &self,
+        _path: &str,
+        _content_disposition: Option<&str>,
+        _expires_in: Duration,
+    ) -> AppResult<PresignedRequest> {
+        Err(presign_unsupported(self.provider_type()))
+    }
+}
+
+fn presign_unsupported(provider_type: &str) -> AppError {
+    AppError::not_implemented(format!(
+        "{provider_type} storage does not support direct transfers"
+    ))
+    .with_code(codes::STORAGE_PRESIGN_UNSUPPORTED)
 }
@@ -0,0 +1,118 @@
+//! Opaque, signed cursors for keyset pagination.
+//!
+//! A cursor records the sort key and ID of the last row a client has seen.
+//! Repositories resume after that position with a
+//! `WHERE (sort_key, id) > ($key, $id)` predicate, which stays stable when
+//! rows are inserted or deleted between requests.
+//!
+//! Cursors are HMAC-SHA256 signed so clients cannot forge positions.
+//! Call [`init_signing_key`] once at startup with a secret shared by all
+//! nodes; otherwise a random per-process key is used and cursors are only
+//! valid on the node that issued them.
+
+use std::sync::OnceLock;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+type HmacSha256 = Hmac<Sha256>;
+
+static SIGNING_KEY: OnceLock<Vec<u8>> = OnceLock::new();
+
+/// Sets the cursor signing key, derived from `secret`.
+///
+/// Only the first call has an effect.
+pub fn init_signing_key(secret: &str) {
+    let key = Sha256::new()
+        .chain_update(b"filehub-page-cursor:")
+        .chain_update(secret.as_bytes())
+        .finalize()
+        .to_vec();
+    let _ = SIGNING_KEY.set(key);
+}
+
+fn signing_key() -> &'static [u8] {
+    SIGNING_KEY.get_or_init(|| {
+        let mut key = Uuid::new_v4().as_bytes().to_vec();
+        key.extend_from_slice(Uuid::new_v4().as_bytes());
+        key
+    })
+}
+
+/// Position of the last row of a page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageCursor {
+    /// Sort key of the last row (e.g. a name or an RFC 3339 timestamp).
+    #[serde(rename = "k")]
+    pub key: String,
+    /// ID of the last row, breaking ties between equal sort keys.
+    #[serde(rename = "i")]
+    pub id: Uuid,
+}
+
+impl PageCursor {
+    /// Creates a cursor positioned after the given row.
+    pub fn new(key: impl Into<String>, id: Uuid) -> Self {
+        Self {
+            key: key.into(),
+            id,
+        }
+    }
+
+    /// Encodes the cursor as an opaque, signed token.
+    pub fn encode(&self) -> String {
+        let payload = serde_json::to_vec(self).unwrap_or_default();
+        let mut mac = HmacSha256::new_from_slice(signing_key()).expect("HMAC accepts any key size");
+        mac.update(&payload);
+        let signature = mac.finalize().into_bytes();
+
+        format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(&payload),
+            URL_SAFE_NO_PAD.encode(signature)
+        )
+    }
+
+    /// Decodes and verifies a token produced by [`PageCursor::encode`].
+    pub fn decode(token: &str) -> Result<Self, AppError> {
+        let invalid = || AppError::validation("Invalid pagination cursor");
+
+        let (payload, signature) = token.split_once('.').ok_or_else(invalid)?;
+        let payload = URL_SAFE_NO_PAD.decode(payload).map_err(|_| invalid())?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| invalid())?;
+
+        let mut mac = HmacSha256::new_from_slice(signing_key()).expect("HMAC accepts any key size");
+        mac.update(&payload);
+        mac.verify_slice(&signature).map_err(|_| invalid())?;
+
+        serde_json::from_slice(&payload).map_err(|_| invalid())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let cursor = PageCursor::new("report.pdf", Uuid::new_v4());
+        assert_eq!(PageCursor::decode(&cursor.encode()).unwrap(), cursor);
+    }
+
+    #[test]
+    fn test_tampered_cursor_is_rejected() {
+        let token = PageCursor::new("a", Uuid::nil()).encode();
+        let (_, signature) = token.split_once('.').unwrap();
+        let forged = PageCursor::new("z", Uuid::nil());
+        let forged_payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&forged).unwrap());
+
+        assert!(PageCursor::decode(&format!("{forged_payload}.{signature}")).is_err());
+        assert!(PageCursor::decode("garbage").is_err());
+    }
+}
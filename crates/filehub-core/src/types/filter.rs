@@ -1,6 +1,16 @@
 //! Filter types for dynamic query building.
 
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+/// Maximum nesting depth of a [`FilterNode`] tree.
+pub const MAX_FILTER_DEPTH: usize = 8;
+
+/// Maximum number of conditions in a [`FilterNode`] tree.
+pub const MAX_FILTER_CONDITIONS: usize = 50;
 
 /// Filter comparison operator.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -31,7 +41,7 @@ pub enum FilterOp {
 }
 
 /// A dynamic filter value that can represent various SQL types.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum FilterValue {
     /// A string value.
@@ -45,6 +55,7 @@ pub enum FilterValue {
     /// A list of string values (for `IN` operator).
     StringList(Vec<String>),
     /// Null / no value (for `IS NULL`, `IS NOT NULL`).
+    #[default]
     Null,
 }
 
@@ -56,6 +67,7 @@ pub struct FilterField {
     /// The comparison operator.
     pub op: FilterOp,
     /// The value to compare against.
+    #[serde(default)]
     pub value: FilterValue,
 }
 
@@ -79,3 +91,263 @@ impl FilterField {
         Self::new(field, FilterOp::ILike, FilterValue::String(pattern.into()))
     }
 }
+
+/// Value type of a filterable field, which decides the operators it accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    /// Free text (names, MIME types).
+    Text,
+    /// Integer or floating-point quantity.
+    Number,
+    /// Timestamp, given as RFC 3339 or `YYYY-MM-DD`.
+    Date,
+    /// UUID reference.
+    Id,
+    /// Multi-valued label set; `eq` tests membership.
+    Set,
+}
+
+impl FilterOp {
+    /// Whether the operator is meaningful for a field of the given kind.
+    pub fn supports(&self, kind: FieldKind) -> bool {
+        match self {
+            Self::Eq | Self::Ne => true,
+            Self::Gt | Self::Gte | Self::Lt | Self::Lte => {
+                matches!(kind, FieldKind::Number | FieldKind::Date)
+            }
+            Self::Like | Self::ILike => kind == FieldKind::Text,
+            Self::In => matches!(kind, FieldKind::Text | FieldKind::Id | FieldKind::Set),
+            Self::IsNull | Self::IsNotNull => kind != FieldKind::Set,
+        }
+    }
+
+    /// Wire name of the operator.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Eq => "eq",
+            Self::Ne => "ne",
+            Self::Gt => "gt",
+            Self::Gte => "gte",
+            Self::Lt => "lt",
+            Self::Lte => "lte",
+            Self::Like => "like",
+            Self::ILike => "i_like",
+            Self::In => "in",
+            Self::IsNull => "is_null",
+            Self::IsNotNull => "is_not_null",
+        }
+    }
+}
+
+impl FilterValue {
+    /// Interprets the value as a timestamp (RFC 3339, or a date at midnight UTC).
+    pub fn as_datetime(&self) -> Option<DateTime<Utc>> {
+        let Self::String(s) = self else {
+            return None;
+        };
+        DateTime::parse_from_rfc3339(s)
+            .map(|dt| dt.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                    .ok()
+                    .and_then(|d| d.and_hms_opt(0, 0, 0))
+                    .map(|dt| dt.and_utc())
+            })
+    }
+
+    /// Interprets the value as a UUID.
+    pub fn as_uuid(&self) -> Option<Uuid> {
+        match self {
+            Self::String(s) => Uuid::parse_str(s).ok(),
+            _ => None,
+        }
+    }
+
+    /// Checks that the value has the right shape for `op` on a `kind` field.
+    fn fits(&self, op: FilterOp, kind: FieldKind) -> bool {
+        match op {
+            FilterOp::IsNull | FilterOp::IsNotNull => matches!(self, Self::Null),
+            FilterOp::In => match self {
+                Self::StringList(items) => {
+                    !items.is_empty()
+                        && (kind != FieldKind::Id
+                            || items.iter().all(|i| Uuid::parse_str(i).is_ok()))
+                }
+                _ => false,
+            },
+            FilterOp::Like | FilterOp::ILike => matches!(self, Self::String(_)),
+            _ => match kind {
+                FieldKind::Text | FieldKind::Set => matches!(self, Self::String(_)),
+                FieldKind::Number => matches!(self, Self::Integer(_) | Self::Float(_)),
+                FieldKind::Date => self.as_datetime().is_some(),
+                FieldKind::Id => self.as_uuid().is_some(),
+            },
+        }
+    }
+}
+
+/// A boolean tree of filter conditions.
+///
+/// Serialized as `{"and": [...]}`, `{"or": [...]}`, or a bare
+/// [`FilterField`] object:
+///
+/// ```json
+/// { "and": [
+///     { "field": "type", "op": "like", "value": "image/%" },
+///     { "or": [
+///         { "field": "size", "op": "gte", "value": 1048576 },
+///         { "field": "tag", "op": "eq", "value": "review" }
+///     ] }
+/// ] }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FilterNode {
+    /// All children must match.
+    And {
+        /// Child nodes.
+        and: Vec<FilterNode>,
+    },
+    /// At least one child must match.
+    Or {
+        /// Child nodes.
+        or: Vec<FilterNode>,
+    },
+    /// A single field condition.
+    Condition(FilterField),
+}
+
+impl FilterNode {
+    /// Validates the tree against a field catalogue.
+    ///
+    /// `kind_of` maps a field name to its kind, returning `None` for fields
+    /// that cannot be filtered on. Rejects unknown fields, operators that
+    /// make no sense for the field (e.g. ranges on text), mistyped values,
+    /// empty groups, and trees beyond [`MAX_FILTER_DEPTH`] or
+    /// [`MAX_FILTER_CONDITIONS`].
+    pub fn validate<F>(&self, kind_of: F) -> Result<(), AppError>
+    where
+        F: Fn(&str) -> Option<FieldKind>,
+    {
+        let mut conditions = 0;
+        self.validate_node(&kind_of, 1, &mut conditions)
+    }
+
+    fn validate_node<F>(
+        &self,
+        kind_of: &F,
+        depth: usize,
+        conditions: &mut usize,
+    ) -> Result<(), AppError>
+    where
+        F: Fn(&str) -> Option<FieldKind>,
+    {
+        if depth > MAX_FILTER_DEPTH {
+            return Err(AppError::validation(format!(
+                "Filter is nested deeper than {MAX_FILTER_DEPTH} levels"
+            )));
+        }
+
+        match self {
+            Self::And { and: children } | Self::Or { or: children } => {
+                if children.is_empty() {
+                    return Err(AppError::validation("Filter groups must not be empty"));
+                }
+                children
+                    .iter()
+                    .try_for_each(|child| child.validate_node(kind_of, depth + 1, conditions))
+            }
+            Self::Condition(cond) => {
+                *conditions += 1;
+                if *conditions > MAX_FILTER_CONDITIONS {
+                    return Err(AppError::validation(format!(
+                        "Filter has more than {MAX_FILTER_CONDITIONS} conditions"
+                    )));
+                }
+
+                let kind = kind_of(&cond.field).ok_or_else(|| {
+                    AppError::validation(format!("Cannot filter on field '{}'", cond.field))
+                })?;
+                if !cond.op.supports(kind) {
+                    return Err(AppError::validation(format!(
+                        "Operator '{}' is not supported on field '{}'",
+                        cond.op.as_str(),
+                        cond.field
+                    )));
+                }
+                if !cond.value.fits(cond.op, kind) {
+                    return Err(AppError::validation(format!(
+                        "Invalid value for '{}' {}",
+                        cond.field,
+                        cond.op.as_str()
+                    )));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind_of(field: &str) -> Option<FieldKind> {
+        match field {
+            "name" => Some(FieldKind::Text),
+            "size" => Some(FieldKind::Number),
+            "created_at" => Some(FieldKind::Date),
+            "owner" => Some(FieldKind::Id),
+            "tag" => Some(FieldKind::Set),
+            _ => None,
+        }
+    }
+
+    fn parse(json: &str) -> FilterNode {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_validate_tree() {
+        let node = parse(
+            r#"{"and": [
+                {"field": "name", "op": "i_like", "value": "%plan%"},
+                {"or": [
+                    {"field": "size", "op": "gte", "value": 1024},
+                    {"field": "created_at", "op": "lt", "value": "2024-06-01"},
+                    {"field": "tag", "op": "in", "value": ["a", "b"]}
+                ]},
+                {"field": "owner", "op": "is_not_null"}
+            ]}"#,
+        );
+        assert!(matches!(node, FilterNode::And { ref and } if and.len() == 3));
+        assert!(node.validate(kind_of).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_nonsensical_combinations() {
+        let invalid = [
+            r#"{"field": "name", "op": "gt", "value": "a"}"#,
+            r#"{"field": "size", "op": "like", "value": "1%"}"#,
+            r#"{"field": "size", "op": "eq", "value": "big"}"#,
+            r#"{"field": "created_at", "op": "gte", "value": "yesterday"}"#,
+            r#"{"field": "owner", "op": "eq", "value": "not-a-uuid"}"#,
+            r#"{"field": "tag", "op": "is_null"}"#,
+            r#"{"field": "secret", "op": "eq", "value": "x"}"#,
+            r#"{"or": []}"#,
+        ];
+        for json in invalid {
+            assert!(parse(json).validate(kind_of).is_err(), "{json}");
+        }
+    }
+
+    #[test]
+    fn test_rejects_deep_trees() {
+        let mut node = FilterNode::Condition(FilterField::eq("name", "a"));
+        for _ in 0..MAX_FILTER_DEPTH {
+            node = FilterNode::And { and: vec![node] };
+        }
+        assert!(node.validate(kind_of).is_err());
+    }
+}
@@ -179,6 +179,29 @@ define_id!(
     PoolSnapshotId
 );
 
+define_id!(
+    /// Unique identifier for a tenant (an organization hosted on the
+    /// deployment).
+    TenantId
+);
+
+impl TenantId {
+    /// The tenant that owns all data of a single-organization deployment,
+    /// and every row created before tenants existed.
+    pub const DEFAULT: TenantId = TenantId(Uuid::from_u128(1));
+
+    /// [`TenantId::DEFAULT`], for serde defaults. `TenantId::default()`
+    /// is a fresh random id like every other id type.
+    pub fn default_tenant() -> Self {
+        Self::DEFAULT
+    }
+
+    /// Whether this is the default tenant.
+    pub fn is_default(&self) -> bool {
+        *self == Self::DEFAULT
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,4 +234,14 @@ mod tests {
         let parsed: UserId = serde_json::from_str(&json).expect("deserialize");
         assert_eq!(id, parsed);
     }
+
+    #[test]
+    fn test_default_tenant() {
+        assert_eq!(
+            TenantId::DEFAULT.to_string(),
+            "00000000-0000-0000-0000-000000000001"
+        );
+        assert!(TenantId::default_tenant().is_default());
+        assert!(!TenantId::new().is_default());
+    }
 }
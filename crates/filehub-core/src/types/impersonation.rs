@@ -0,0 +1,52 @@
+//! The administrator behind an impersonated request.
+//!
+//! Requests made with an impersonation session run inside
+//! [`Impersonator::scope`], so anything recorded on their behalf (domain
+//! events, audit entries) can name the administrator who really acted via
+//! [`Impersonator::current`], without threading it through every call.
+
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+tokio::task_local! {
+    static CURRENT: Impersonator;
+}
+
+/// The administrator acting as another user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Impersonator(pub Uuid);
+
+impl Impersonator {
+    /// The administrator's user ID.
+    pub fn id(&self) -> Uuid {
+        self.0
+    }
+
+    /// Returns the impersonator of the request being handled on this
+    /// task, if it is impersonated.
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(|i| *i).ok()
+    }
+
+    /// Runs `future` with `self` as the current impersonator.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT.scope(self, future).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_current_is_scoped() {
+        assert_eq!(Impersonator::current(), None);
+        let admin = Impersonator(Uuid::new_v4());
+        let seen = admin.scope(async { Impersonator::current() }).await;
+        assert_eq!(seen, Some(admin));
+        assert_eq!(Impersonator::current(), None);
+    }
+}
@@ -1,15 +1,21 @@
 //! Core type definitions used across the FileHub workspace.
 
+pub mod cursor;
 pub mod filter;
 pub mod id;
+pub mod impersonation;
 pub mod pagination;
+pub mod request_id;
 pub mod response;
 pub mod session_limit;
 pub mod sorting;
 
-pub use filter::{FilterField, FilterOp, FilterValue};
+pub use cursor::PageCursor;
+pub use filter::{FieldKind, FilterField, FilterNode, FilterOp, FilterValue};
 pub use id::*;
-pub use pagination::{PageRequest, PageResponse};
+pub use impersonation::Impersonator;
+pub use pagination::{PageMode, PageRequest, PageResponse};
+pub use request_id::RequestId;
 pub use response::ApiErrorResponse;
 pub use session_limit::SessionLimit;
 pub use sorting::{SortDirection, SortField};
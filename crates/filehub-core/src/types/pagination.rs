@@ -2,12 +2,19 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::cursor::PageCursor;
+use crate::error::AppError;
+
 /// Default page size.
 const DEFAULT_PAGE_SIZE: u64 = 25;
 /// Maximum page size.
 const MAX_PAGE_SIZE: u64 = 100;
 
 /// Request parameters for paginated queries.
+///
+/// Offset paging (`page`) is the default. Listings that support keyset
+/// paging switch to it when `cursor` is set: an empty cursor requests the
+/// first page, and each response's `next_cursor` continues from there.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PageRequest {
     /// Page number (1-based).
@@ -16,6 +23,18 @@ pub struct PageRequest {
     /// Number of items per page.
     #[serde(default = "default_page_size")]
     pub page_size: u64,
+    /// Opaque keyset cursor from a previous response's `next_cursor`.
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+/// How a listing should be paged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PageMode {
+    /// `LIMIT`/`OFFSET` paging by page number.
+    Offset,
+    /// Keyset paging, resuming after the given row (or from the start).
+    Cursor(Option<PageCursor>),
 }
 
 impl PageRequest {
@@ -24,6 +43,26 @@ impl PageRequest {
         Self {
             page: page.max(1),
             page_size: page_size.clamp(1, MAX_PAGE_SIZE),
+            cursor: None,
+        }
+    }
+
+    /// Create a keyset page request starting after `cursor` (or at the
+    /// beginning when `None`).
+    pub fn with_cursor(page_size: u64, cursor: Option<String>) -> Self {
+        Self {
+            page: 1,
+            page_size: page_size.clamp(1, MAX_PAGE_SIZE),
+            cursor: Some(cursor.unwrap_or_default()),
+        }
+    }
+
+    /// Resolves the paging mode, verifying the cursor if one is present.
+    pub fn mode(&self) -> Result<PageMode, AppError> {
+        match self.cursor.as_deref() {
+            None => Ok(PageMode::Offset),
+            Some("") => Ok(PageMode::Cursor(None)),
+            Some(token) => PageCursor::decode(token).map(|c| PageMode::Cursor(Some(c))),
         }
     }
 
@@ -43,11 +82,15 @@ impl Default for PageRequest {
         Self {
             page: 1,
             page_size: DEFAULT_PAGE_SIZE,
+            cursor: None,
         }
     }
 }
 
 /// Paginated response wrapper.
+///
+/// Keyset pages don't count rows: `page`, `total_items` and `total_pages`
+/// are left at zero, and `has_next` mirrors `next_cursor`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PageResponse<T: Serialize> {
     /// The items on this page.
@@ -64,6 +107,9 @@ pub struct PageResponse<T: Serialize> {
     pub has_next: bool,
     /// Whether there is a previous page.
     pub has_previous: bool,
+    /// Cursor for the next keyset page, if there is one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 impl<T: Serialize> PageResponse<T> {
@@ -82,6 +128,41 @@ impl<T: Serialize> PageResponse<T> {
             total_pages,
             has_next: page < total_pages,
             has_previous: page > 1,
+            next_cursor: None,
+        }
+    }
+
+    /// Builds a keyset page from rows fetched with `LIMIT page_size + 1`.
+    ///
+    /// The extra row only signals that another page exists; it is dropped
+    /// and the cursor is taken from the last row kept.
+    pub fn from_keyset(
+        mut rows: Vec<T>,
+        page_request: &PageRequest,
+        cursor_of: impl Fn(&T) -> PageCursor,
+    ) -> Self {
+        let page_size = page_request.page_size;
+        let has_next = rows.len() as u64 > page_size;
+        rows.truncate(page_size as usize);
+
+        let next_cursor = if has_next {
+            rows.last().map(|row| cursor_of(row).encode())
+        } else {
+            None
+        };
+
+        Self {
+            items: rows,
+            page: 0,
+            page_size,
+            total_items: 0,
+            total_pages: 0,
+            has_next,
+            has_previous: page_request
+                .cursor
+                .as_deref()
+                .is_some_and(|c| !c.is_empty()),
+            next_cursor,
         }
     }
 
@@ -95,6 +176,7 @@ impl<T: Serialize> PageResponse<T> {
             total_pages: 1,
             has_next: false,
             has_previous: false,
+            next_cursor: None,
         }
     }
 }
@@ -106,3 +188,32 @@ fn default_page() -> u64 {
 fn default_page_size() -> u64 {
     DEFAULT_PAGE_SIZE
 }
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_keyset_page_detects_next() {
+        let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        let request = PageRequest::with_cursor(2, None);
+        let page = PageResponse::from_keyset(ids.clone(), &request, |id| PageCursor::new("k", *id));
+
+        assert_eq!(page.items, ids[..2]);
+        assert!(page.has_next);
+        assert!(!page.has_previous);
+
+        let next = PageRequest::with_cursor(2, page.next_cursor);
+        assert_eq!(
+            next.mode().unwrap(),
+            PageMode::Cursor(Some(PageCursor::new("k", ids[1])))
+        );
+    }
+
+    #[test]
+    fn test_offset_is_default_mode() {
+        assert_eq!(PageRequest::default().mode().unwrap(), PageMode::Offset);
+    }
+}
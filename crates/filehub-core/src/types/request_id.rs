@@ -0,0 +1,85 @@
+//! Per-request correlation identifier.
+//!
+//! The HTTP layer assigns every request a [`RequestId`] and runs the
+//! handler inside [`RequestId::scope`], so code further down the call
+//! stack (services, plugins) can read it with [`RequestId::current`]
+//! without threading it through every signature.
+
+use std::fmt;
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Maximum accepted length of a client-supplied request id.
+const MAX_LEN: usize = 128;
+
+tokio::task_local! {
+    static CURRENT: RequestId;
+}
+
+/// Correlation id carried in the `X-Request-Id` header.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RequestId(String);
+
+impl RequestId {
+    /// Header used to receive and echo the request id.
+    pub const HEADER: &'static str = "x-request-id";
+
+    /// Generates a fresh request id.
+    pub fn generate() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+
+    /// Accepts a client-supplied id if it is short printable ASCII.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let valid = !value.is_empty()
+            && value.len() <= MAX_LEN
+            && value.bytes().all(|b| b.is_ascii_graphic());
+        valid.then(|| Self(value.to_string()))
+    }
+
+    /// Returns the id as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the id of the request being handled on this task, if any.
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(Clone::clone).ok()
+    }
+
+    /// Runs `future` with `self` as the current request id.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT.scope(self, future).await
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rejects_unsafe_values() {
+        assert_eq!(RequestId::parse(" abc-123 ").unwrap().as_str(), "abc-123");
+        assert!(RequestId::parse("").is_none());
+        assert!(RequestId::parse("has space").is_none());
+        assert!(RequestId::parse(&"x".repeat(MAX_LEN + 1)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_current_is_scoped() {
+        assert!(RequestId::current().is_none());
+        let id = RequestId::generate();
+        let seen = id.clone().scope(async { RequestId::current() }).await;
+        assert_eq!(seen, Some(id));
+    }
+}
@@ -5,11 +5,20 @@ use serde::{Deserialize, Serialize};
 /// Standard API error response body.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiErrorResponse {
-    /// Machine-readable error code.
+    /// Error category (the [`ErrorKind`](crate::error::ErrorKind) name).
     pub error: String,
+    /// Stable machine-readable code (see [`codes`](crate::error::codes)).
+    #[serde(default)]
+    pub code: String,
+    /// HTTP status code.
+    #[serde(default)]
+    pub status: u16,
     /// Human-readable message.
     pub message: String,
     /// Optional details.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<serde_json::Value>,
+    /// Trace id of the request, when traces are exported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
 }
@@ -1,7 +1,11 @@
 //! Sorting types for list endpoints.
 
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
 
+use crate::error::AppError;
+
 /// Sort direction.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -26,6 +30,36 @@ impl SortDirection {
             Self::Desc => "DESC",
         }
     }
+
+    /// Return the lowercase name of this direction.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Asc => "asc",
+            Self::Desc => "desc",
+        }
+    }
+}
+
+impl FromStr for SortDirection {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "asc" => Ok(Self::Asc),
+            "desc" => Ok(Self::Desc),
+            _ => Err(AppError::validation(format!(
+                "Invalid sort direction: '{s}'. Expected one of: asc, desc"
+            ))),
+        }
+    }
+}
+
+impl TryFrom<String> for SortDirection {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
 }
 
 /// A sort specification consisting of a field name and direction.
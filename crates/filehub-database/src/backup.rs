@@ -0,0 +1,293 @@
+//! Logical table export and import for backups.
+//!
+//! Tables are copied with `COPY ... (FORMAT binary)`, streamed chunk by
+//! chunk, so memory use does not grow with table size. The binary format is
+//! tied to the column layout, which is why a backup may only be restored
+//! into a database at the same migration version.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Read, Write};
+
+use futures::TryStreamExt;
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use filehub_core::error::{AppError, ErrorKind};
+
+/// Tables owned by the migration runner rather than the application.
+const SCHEMA_TABLES: &[&str] = &["_sqlx_migrations", "schema_migration_history"];
+
+/// Read buffer size used when streaming a table back in.
+const IMPORT_CHUNK: usize = 64 * 1024;
+
+/// Application tables in an order where every table comes after the tables
+/// its foreign keys reference.
+pub async fn backup_tables(pool: &PgPool) -> Result<Vec<String>, AppError> {
+    let tables: Vec<String> = sqlx::query_scalar(
+        "SELECT table_name::text FROM information_schema.tables \
+         WHERE table_schema = 'public' AND table_type = 'BASE TABLE'",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| db_error("Failed to list tables", e))?;
+
+    let references: Vec<(String, String)> = sqlx::query_as(
+        "SELECT c.conrelid::regclass::text, c.confrelid::regclass::text \
+         FROM pg_constraint c \
+         JOIN pg_namespace n ON n.oid = c.connamespace \
+         WHERE c.contype = 'f' AND n.nspname = 'public'",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| db_error("Failed to list foreign keys", e))?;
+
+    let tables = tables
+        .into_iter()
+        .filter(|t| !SCHEMA_TABLES.contains(&t.as_str()))
+        .collect();
+    Ok(dependency_order(tables, &references))
+}
+
+/// A stored object referenced by a file or one of its versions.
+#[derive(Debug, Clone, FromRow)]
+pub struct BlobRef {
+    /// Storage holding the object.
+    pub storage_id: Uuid,
+    /// Provider-relative path.
+    pub storage_path: String,
+    /// Size recorded in the database.
+    pub size_bytes: i64,
+    /// SHA-256 recorded in the database, if computed.
+    pub checksum_sha256: Option<String>,
+}
+
+/// Every object referenced by `files` and `file_versions`.
+pub async fn referenced_blobs(pool: &PgPool) -> Result<Vec<BlobRef>, AppError> {
+    sqlx::query_as::<_, BlobRef>(
+        "SELECT storage_id, storage_path, size_bytes, checksum_sha256 FROM files \
+         UNION \
+         SELECT f.storage_id, v.storage_path, v.size_bytes, v.checksum_sha256 \
+         FROM file_versions v JOIN files f ON f.id = v.file_id \
+         ORDER BY storage_id, storage_path",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| db_error("Failed to list stored objects", e))
+}
+
+/// Stream `table` into `out`, returning the number of bytes written.
+pub async fn export_table<W: Write>(
+    pool: &PgPool,
+    table: &str,
+    out: &mut W,
+) -> Result<u64, AppError> {
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|e| db_error("Failed to acquire connection", e))?;
+    let statement = format!("COPY {} TO STDOUT (FORMAT binary)", quote_ident(table));
+    let mut stream = conn
+        .copy_out_raw(&statement)
+        .await
+        .map_err(|e| db_error(&format!("Failed to export '{}'", table), e))?;
+
+    let mut written = 0u64;
+    while let Some(chunk) = stream
+        .try_next()
+        .await
+        .map_err(|e| db_error(&format!("Failed to export '{}'", table), e))?
+    {
+        out.write_all(&chunk).map_err(|e| {
+            AppError::with_source(ErrorKind::Internal, "Failed to write table data", e)
+        })?;
+        written += chunk.len() as u64;
+    }
+    Ok(written)
+}
+
+/// Tables among `tables` that contain at least one row.
+pub async fn non_empty_tables(pool: &PgPool, tables: &[String]) -> Result<Vec<String>, AppError> {
+    let mut non_empty = Vec::new();
+    for table in tables {
+        let has_rows: bool = sqlx::query_scalar(&format!(
+            "SELECT EXISTS (SELECT 1 FROM {})",
+            quote_ident(table)
+        ))
+        .fetch_one(pool)
+        .await
+        .map_err(|e| db_error(&format!("Failed to inspect '{}'", table), e))?;
+        if has_rows {
+            non_empty.push(table.clone());
+        }
+    }
+    Ok(non_empty)
+}
+
+/// A restore in progress. Nothing is visible to other connections until
+/// [`TableImport::commit`].
+pub struct TableImport {
+    tx: Transaction<'static, Postgres>,
+}
+
+impl TableImport {
+    /// Start a restore, emptying `truncate` first.
+    pub async fn begin(pool: &PgPool, truncate: &[String]) -> Result<Self, AppError> {
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to begin restore", e))?;
+        if !truncate.is_empty() {
+            let list: Vec<String> = truncate.iter().map(|t| quote_ident(t)).collect();
+            sqlx::raw_sql(&format!("TRUNCATE {} CASCADE", list.join(", ")))
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| db_error("Failed to truncate tables", e))?;
+        }
+        Ok(Self { tx })
+    }
+
+    /// Load `table` from data produced by [`export_table`]. Returns the
+    /// number of rows copied.
+    pub async fn import_table<R: Read>(
+        &mut self,
+        table: &str,
+        data: &mut R,
+    ) -> Result<u64, AppError> {
+        let statement = format!("COPY {} FROM STDIN (FORMAT binary)", quote_ident(table));
+        let mut copy = self
+            .tx
+            .copy_in_raw(&statement)
+            .await
+            .map_err(|e| db_error(&format!("Failed to import '{}'", table), e))?;
+
+        let mut buf = vec![0u8; IMPORT_CHUNK];
+        loop {
+            let n = match data.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) => {
+                    let _ = copy.abort("backup read failed").await;
+                    return Err(AppError::with_source(
+                        ErrorKind::Internal,
+                        format!("Failed to read backup data for '{}'", table),
+                        e,
+                    ));
+                }
+            };
+            copy.send(&buf[..n])
+                .await
+                .map_err(|e| db_error(&format!("Failed to import '{}'", table), e))?;
+        }
+        copy.finish()
+            .await
+            .map_err(|e| db_error(&format!("Failed to import '{}'", table), e))
+    }
+
+    /// Move serial sequences past the restored rows and commit.
+    pub async fn commit(mut self) -> Result<(), AppError> {
+        let serials: Vec<(String, String)> = sqlx::query_as(
+            "SELECT table_name::text, column_name::text FROM information_schema.columns \
+             WHERE table_schema = 'public' \
+             AND (column_default LIKE 'nextval(%' OR is_identity = 'YES')",
+        )
+        .fetch_all(&mut *self.tx)
+        .await
+        .map_err(|e| db_error("Failed to list sequences", e))?;
+
+        for (table, column) in serials {
+            let statement = format!(
+                "SELECT setval(pg_get_serial_sequence($1, $2), \
+                 COALESCE((SELECT MAX({col}) FROM {table}), 0) + 1, false)",
+                col = quote_ident(&column),
+                table = quote_ident(&table),
+            );
+            sqlx::query(&statement)
+                .bind(quote_ident(&table))
+                .bind(&column)
+                .execute(&mut *self.tx)
+                .await
+                .map_err(|e| db_error(&format!("Failed to reset sequence of '{}'", table), e))?;
+        }
+
+        self.tx
+            .commit()
+            .await
+            .map_err(|e| db_error("Failed to commit restore", e))
+    }
+}
+
+/// Order `tables` so referenced tables come first. Self-references are
+/// ignored; tables in a reference cycle keep alphabetical order at the end.
+fn dependency_order(tables: Vec<String>, references: &[(String, String)]) -> Vec<String> {
+    let mut pending: BTreeMap<String, BTreeSet<String>> = tables
+        .into_iter()
+        .map(|table| (table, BTreeSet::new()))
+        .collect();
+    for (table, referenced) in references {
+        if table != referenced
+            && pending.contains_key(referenced)
+            && let Some(deps) = pending.get_mut(table)
+        {
+            deps.insert(referenced.clone());
+        }
+    }
+
+    let mut ordered = Vec::with_capacity(pending.len());
+    while !pending.is_empty() {
+        let ready: Vec<String> = pending
+            .iter()
+            .filter(|(_, deps)| deps.is_empty())
+            .map(|(table, _)| table.clone())
+            .collect();
+        if ready.is_empty() {
+            ordered.extend(pending.into_keys());
+            break;
+        }
+        for table in &ready {
+            pending.remove(table);
+        }
+        for deps in pending.values_mut() {
+            for table in &ready {
+                deps.remove(table);
+            }
+        }
+        ordered.extend(ready);
+    }
+    ordered
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn db_error(message: &str, e: sqlx::Error) -> AppError {
+    AppError::with_source(ErrorKind::Database, message.to_string(), e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_dependency_order() {
+        let references = vec![
+            ("files".to_string(), "folders".to_string()),
+            ("files".to_string(), "users".to_string()),
+            ("folders".to_string(), "folders".to_string()),
+            ("folders".to_string(), "users".to_string()),
+            ("tags".to_string(), "users".to_string()),
+        ];
+        let order = dependency_order(names(&["files", "folders", "tags", "users"]), &references);
+        assert_eq!(order, names(&["users", "folders", "tags", "files"]));
+    }
+
+    #[test]
+    fn test_quote_ident() {
+        assert_eq!(quote_ident("users"), "\"users\"");
+        assert_eq!(quote_ident("we\"ird"), "\"we\"\"ird\"");
+    }
+}
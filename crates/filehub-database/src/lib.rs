@@ -3,8 +3,12 @@
 //! PostgreSQL database connection management and concrete repository
 //! implementations for all FileHub entities.
 
+pub mod backup;
 pub mod connection;
 pub mod migration;
 pub mod repositories;
+pub mod slow_query;
+pub mod tenant;
 
 pub use connection::DatabasePool;
+pub use tenant::{TenantOwned, TenantScope};
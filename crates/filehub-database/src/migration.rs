@@ -1,25 +1,409 @@
 //! Database migration runner.
+//!
+//! Migrations are paired `<version>_<name>.up.sql` / `.down.sql` files in
+//! `migrations/`. Every migration applied or reverted through this module is
+//! recorded in `schema_migration_history`, which is kept across rollbacks so
+//! the history stays auditable.
 
-use sqlx::PgPool;
-use tracing::info;
+use std::collections::HashMap;
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::migrate::{MigrationType, Migrator};
+use sqlx::{FromRow, PgPool};
+use tracing::{info, warn};
 
 use filehub_core::error::{AppError, ErrorKind};
 
+/// Migrations embedded from the workspace `migrations/` directory.
+static MIGRATOR: Migrator = sqlx::migrate!("../../migrations");
+
+/// Statements that lose data when a down migration runs.
+const DESTRUCTIVE_STATEMENTS: &[&str] = &[
+    "DROP TABLE",
+    "DROP COLUMN",
+    "DROP SCHEMA",
+    "TRUNCATE",
+    "DELETE FROM",
+];
+
+/// Direction a migration was executed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationDirection {
+    /// Applied.
+    Up,
+    /// Reverted.
+    Down,
+}
+
+impl MigrationDirection {
+    /// Name stored in the history table.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Down => "down",
+        }
+    }
+}
+
+/// A known migration and whether it is applied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationStatus {
+    /// Migration version (timestamp prefix of the file name).
+    pub version: i64,
+    /// Migration description.
+    pub description: String,
+    /// When the migration was applied, if it is.
+    pub applied_at: Option<DateTime<Utc>>,
+    /// Whether the migration has a down migration.
+    pub reversible: bool,
+}
+
+/// A migration that would run (or ran) in one direction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationStep {
+    /// Migration version.
+    pub version: i64,
+    /// Migration description.
+    pub description: String,
+    /// Direction of the step.
+    pub direction: MigrationDirection,
+    /// SQL executed by the step.
+    pub sql: String,
+    /// Whether the step drops or deletes data.
+    pub destructive: bool,
+}
+
+/// A recorded migration execution.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MigrationHistoryEntry {
+    /// History entry ID.
+    pub id: i64,
+    /// Migration version.
+    pub version: i64,
+    /// Migration description.
+    pub description: String,
+    /// `"up"` or `"down"`.
+    pub direction: String,
+    /// When the migration ran.
+    pub executed_at: DateTime<Utc>,
+    /// How long it took, in milliseconds.
+    pub execution_ms: i64,
+}
+
 /// Run all pending database migrations.
 pub async fn run_migrations(pool: &PgPool) -> Result<(), AppError> {
     info!("Running database migrations...");
 
-    sqlx::migrate!("../../migrations")
-        .run(pool)
-        .await
-        .map_err(|e| {
-            AppError::with_source(
-                ErrorKind::Database,
-                format!("Failed to run migrations: {e}"),
-                e,
-            )
-        })?;
+    ensure_history_table(pool).await?;
+    let before = applied_migrations(pool).await?;
+
+    MIGRATOR.run(pool).await.map_err(|e| {
+        AppError::with_source(
+            ErrorKind::Database,
+            format!("Failed to run migrations: {e}"),
+            e,
+        )
+    })?;
+
+    // sqlx records each migration it applies; copy the new ones into the
+    // history with the time and duration sqlx measured.
+    let applied: Vec<(i64, String, DateTime<Utc>, i64)> = sqlx::query_as(
+        "SELECT version, description, installed_on, execution_time \
+         FROM _sqlx_migrations WHERE success ORDER BY version",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| db_error("Failed to read applied migrations", e))?;
+
+    for (version, description, installed_on, execution_ns) in applied {
+        if before.contains_key(&version) {
+            continue;
+        }
+        record_history(
+            pool,
+            version,
+            &description,
+            MigrationDirection::Up,
+            installed_on,
+            execution_ns / 1_000_000,
+        )
+        .await?;
+    }
 
     info!("Database migrations completed successfully");
     Ok(())
 }
+
+/// List the migrations `run_migrations` would apply, in order.
+pub async fn pending_migrations(pool: &PgPool) -> Result<Vec<MigrationStep>, AppError> {
+    let applied = applied_migrations(pool).await?;
+    Ok(MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration() && !applied.contains_key(&m.version))
+        .map(|m| MigrationStep {
+            version: m.version,
+            description: m.description.to_string(),
+            direction: MigrationDirection::Up,
+            sql: m.sql.to_string(),
+            destructive: false,
+        })
+        .collect())
+}
+
+/// Version of the newest applied migration, or `None` for an empty database.
+pub async fn schema_version(pool: &PgPool) -> Result<Option<i64>, AppError> {
+    Ok(applied_migrations(pool).await?.into_keys().max())
+}
+
+/// List every known migration with its applied state.
+pub async fn migration_status(pool: &PgPool) -> Result<Vec<MigrationStatus>, AppError> {
+    let applied = applied_migrations(pool).await?;
+    Ok(MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| MigrationStatus {
+            version: m.version,
+            description: m.description.to_string(),
+            applied_at: applied.get(&m.version).copied(),
+            reversible: down_migration(m.version).is_some(),
+        })
+        .collect())
+}
+
+/// Plan a rollback of the last `steps` applied migrations, newest first.
+pub async fn plan_rollback(pool: &PgPool, steps: usize) -> Result<Vec<MigrationStep>, AppError> {
+    if steps == 0 {
+        return Err(AppError::validation("Rollback needs at least one step"));
+    }
+
+    let mut applied: Vec<i64> = applied_migrations(pool).await?.into_keys().collect();
+    applied.sort_unstable_by(|a, b| b.cmp(a));
+
+    if applied.len() < steps {
+        return Err(AppError::validation(format!(
+            "Cannot roll back {} migration(s): only {} applied",
+            steps,
+            applied.len()
+        )));
+    }
+
+    applied
+        .into_iter()
+        .take(steps)
+        .map(|version| {
+            let down = down_migration(version).ok_or_else(|| {
+                AppError::validation(format!("Migration {} has no down migration", version))
+            })?;
+            Ok(MigrationStep {
+                version,
+                description: down.description.to_string(),
+                direction: MigrationDirection::Down,
+                sql: down.sql.to_string(),
+                destructive: is_destructive(&down.sql),
+            })
+        })
+        .collect()
+}
+
+/// Roll back the last `steps` applied migrations, newest first.
+///
+/// Refuses to run if any step is destructive unless `force` is set. Each
+/// step runs in its own transaction; a failure stops the rollback with the
+/// earlier steps kept.
+pub async fn rollback(
+    pool: &PgPool,
+    steps: usize,
+    force: bool,
+) -> Result<Vec<MigrationStep>, AppError> {
+    let plan = plan_rollback(pool, steps).await?;
+
+    if !force && let Some(step) = plan.iter().find(|s| s.destructive) {
+        return Err(AppError::validation(format!(
+            "Rolling back migration {} ({}) drops data; pass --force to continue",
+            step.version, step.description
+        )));
+    }
+
+    ensure_history_table(pool).await?;
+
+    for step in &plan {
+        warn!(
+            version = step.version,
+            description = %step.description,
+            "Reverting database migration"
+        );
+        let started = Instant::now();
+
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| db_error("Failed to begin rollback transaction", e))?;
+        sqlx::raw_sql(&step.sql)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                AppError::with_source(
+                    ErrorKind::Database,
+                    format!("Failed to revert migration {}: {e}", step.version),
+                    e,
+                )
+            })?;
+        sqlx::query("DELETE FROM _sqlx_migrations WHERE version = $1")
+            .bind(step.version)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| db_error("Failed to unrecord migration", e))?;
+        sqlx::query(
+            "INSERT INTO schema_migration_history (version, description, direction, execution_ms) \
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(step.version)
+        .bind(&step.description)
+        .bind(MigrationDirection::Down.as_str())
+        .bind(started.elapsed().as_millis() as i64)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| db_error("Failed to record migration history", e))?;
+        tx.commit()
+            .await
+            .map_err(|e| db_error("Failed to commit rollback", e))?;
+    }
+
+    info!("Rolled back {} migration(s)", plan.len());
+    Ok(plan)
+}
+
+/// Most recent migration executions, newest first.
+pub async fn migration_history(
+    pool: &PgPool,
+    limit: i64,
+) -> Result<Vec<MigrationHistoryEntry>, AppError> {
+    ensure_history_table(pool).await?;
+    sqlx::query_as::<_, MigrationHistoryEntry>(
+        "SELECT * FROM schema_migration_history ORDER BY executed_at DESC, id DESC LIMIT $1",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| db_error("Failed to read migration history", e))
+}
+
+/// Whether running `sql` loses data.
+pub fn is_destructive(sql: &str) -> bool {
+    let normalized = sql
+        .lines()
+        .map(|line| line.split("--").next().unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_uppercase();
+    DESTRUCTIVE_STATEMENTS
+        .iter()
+        .any(|statement| normalized.contains(statement))
+}
+
+/// The down migration for a version, if one exists.
+fn down_migration(version: i64) -> Option<&'static sqlx::migrate::Migration> {
+    MIGRATOR
+        .iter()
+        .find(|m| m.version == version && m.migration_type == MigrationType::ReversibleDown)
+}
+
+/// Applied migration versions with the time they were applied.
+async fn applied_migrations(pool: &PgPool) -> Result<HashMap<i64, DateTime<Utc>>, AppError> {
+    let exists: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| db_error("Failed to check migration table", e))?;
+    if !exists {
+        return Ok(HashMap::new());
+    }
+
+    let rows: Vec<(i64, DateTime<Utc>)> =
+        sqlx::query_as("SELECT version, installed_on FROM _sqlx_migrations WHERE success")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| db_error("Failed to read applied migrations", e))?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Create the history table. It lives outside the migrations so reverting
+/// every migration still keeps the record.
+async fn ensure_history_table(pool: &PgPool) -> Result<(), AppError> {
+    sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS schema_migration_history (
+            id           BIGSERIAL PRIMARY KEY,
+            version      BIGINT NOT NULL,
+            description  TEXT NOT NULL,
+            direction    VARCHAR(4) NOT NULL CHECK (direction IN ('up', 'down')),
+            executed_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            execution_ms BIGINT NOT NULL DEFAULT 0
+        )",
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| db_error("Failed to create migration history table", e))?;
+    Ok(())
+}
+
+async fn record_history(
+    pool: &PgPool,
+    version: i64,
+    description: &str,
+    direction: MigrationDirection,
+    executed_at: DateTime<Utc>,
+    execution_ms: i64,
+) -> Result<(), AppError> {
+    sqlx::query(
+        "INSERT INTO schema_migration_history \
+         (version, description, direction, executed_at, execution_ms) \
+         VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(version)
+    .bind(description)
+    .bind(direction.as_str())
+    .bind(executed_at)
+    .bind(execution_ms)
+    .execute(pool)
+    .await
+    .map_err(|e| db_error("Failed to record migration history", e))?;
+    Ok(())
+}
+
+fn db_error(message: &str, e: sqlx::Error) -> AppError {
+    AppError::with_source(ErrorKind::Database, message, e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_migration_is_reversible() {
+        for migration in MIGRATOR
+            .iter()
+            .filter(|m| !m.migration_type.is_down_migration())
+        {
+            assert!(
+                down_migration(migration.version).is_some(),
+                "migration {} has no down migration",
+                migration.version
+            );
+        }
+    }
+
+    #[test]
+    fn test_destructive_statements_are_detected() {
+        assert!(is_destructive("DROP TABLE IF EXISTS tags;"));
+        assert!(is_destructive(
+            "ALTER TABLE license_checkouts\n    drop   column IF EXISTS lost_at;"
+        ));
+        assert!(!is_destructive("DROP INDEX IF EXISTS idx_files_name_trgm;"));
+        assert!(!is_destructive("-- DROP TABLE users\nDROP INDEX idx_a;"));
+    }
+}
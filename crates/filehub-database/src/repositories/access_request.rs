@@ -0,0 +1,152 @@
+//! Access request repository implementation.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use filehub_core::error::{AppError, ErrorKind};
+use filehub_core::events::DomainEvent;
+use filehub_core::result::AppResult;
+use filehub_entity::share::{AccessRequest, AccessRequestStatus, CreateAccessRequest};
+
+use crate::repositories::outbox::OutboxRepository;
+use crate::slow_query::TimedPool;
+
+/// Most requests returned by one listing.
+const LIST_LIMIT: i64 = 200;
+
+/// Repository for requests for more access to a resource.
+#[derive(Debug, Clone)]
+pub struct AccessRequestRepository {
+    pool: TimedPool,
+}
+
+impl AccessRequestRepository {
+    /// Create a new access request repository.
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool: TimedPool::new(pool, "AccessRequestRepository"),
+        }
+    }
+
+    /// Find a request by ID.
+    pub async fn find_by_id(&self, id: Uuid) -> AppResult<Option<AccessRequest>> {
+        sqlx::query_as::<_, AccessRequest>("SELECT * FROM access_requests WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to find access request", e)
+            })
+    }
+
+    /// Create a pending request, recording the event `announce` makes of
+    /// it in the outbox. Returns `None` if the requester already has a
+    /// pending request on the resource.
+    pub async fn create(
+        &self,
+        data: &CreateAccessRequest,
+        announce: impl FnOnce(&AccessRequest) -> DomainEvent + Send,
+    ) -> AppResult<Option<AccessRequest>> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to begin transaction", e)
+        })?;
+        let Some(request) = sqlx::query_as::<_, AccessRequest>(
+            "INSERT INTO access_requests \
+             (resource_type, resource_id, requester_id, owner_id, permission, message) \
+             VALUES ($1, $2, $3, $4, $5, $6) \
+             ON CONFLICT DO NOTHING RETURNING *",
+        )
+        .bind(data.resource_type)
+        .bind(data.resource_id)
+        .bind(data.requester_id)
+        .bind(data.owner_id)
+        .bind(data.permission)
+        .bind(&data.message)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to create access request", e)
+        })?
+        else {
+            return Ok(None);
+        };
+        OutboxRepository::enqueue(&mut tx, &[announce(&request)]).await?;
+
+        tx.commit().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to commit access request", e)
+        })?;
+        Ok(Some(request))
+    }
+
+    /// Requests awaiting or decided by `owner_id`, newest first, optionally
+    /// only those with `status`.
+    pub async fn find_for_owner(
+        &self,
+        owner_id: Uuid,
+        status: Option<AccessRequestStatus>,
+    ) -> AppResult<Vec<AccessRequest>> {
+        sqlx::query_as::<_, AccessRequest>(
+            "SELECT * FROM access_requests \
+             WHERE owner_id = $1 AND ($2::access_request_status IS NULL OR status = $2) \
+             ORDER BY created_at DESC LIMIT $3",
+        )
+        .bind(owner_id)
+        .bind(status)
+        .bind(LIST_LIMIT)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to list access requests", e)
+        })
+    }
+
+    /// Requests made by `requester_id`, newest first.
+    pub async fn find_by_requester(&self, requester_id: Uuid) -> AppResult<Vec<AccessRequest>> {
+        sqlx::query_as::<_, AccessRequest>(
+            "SELECT * FROM access_requests WHERE requester_id = $1 \
+             ORDER BY created_at DESC LIMIT $2",
+        )
+        .bind(requester_id)
+        .bind(LIST_LIMIT)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to list access requests", e)
+        })
+    }
+
+    /// Record the decision on a pending request, and `event` in the
+    /// outbox with it. Returns `None` if the request was no longer pending.
+    pub async fn decide(
+        &self,
+        id: Uuid,
+        status: AccessRequestStatus,
+        decided_by: Uuid,
+        event: &DomainEvent,
+    ) -> AppResult<Option<AccessRequest>> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to begin transaction", e)
+        })?;
+        let Some(request) = sqlx::query_as::<_, AccessRequest>(
+            "UPDATE access_requests SET status = $2, decided_by = $3, decided_at = NOW() \
+             WHERE id = $1 AND status = 'pending' RETURNING *",
+        )
+        .bind(id)
+        .bind(status)
+        .bind(decided_by)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to decide access request", e)
+        })?
+        else {
+            return Ok(None);
+        };
+        OutboxRepository::enqueue(&mut tx, std::slice::from_ref(event)).await?;
+
+        tx.commit().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to commit access decision", e)
+        })?;
+        Ok(Some(request))
+    }
+}
@@ -0,0 +1,188 @@
+//! API usage and quota tier repository implementation.
+
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use filehub_core::error::{AppError, ErrorKind};
+use filehub_core::result::AppResult;
+use filehub_entity::user::{MonthlyUsage, Usage, UserQuotaTier};
+
+use crate::slow_query::TimedPool;
+
+/// A user's usage in the current day and month, with their assigned tier.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct UsageTotals {
+    /// The user.
+    pub user_id: Uuid,
+    /// Assigned tier, if any.
+    pub tier: Option<String>,
+    /// Calls made in the day.
+    pub day_calls: i64,
+    /// Bytes transferred in the day.
+    pub day_bytes: i64,
+    /// Calls made in the month, including the day.
+    pub month_calls: i64,
+    /// Bytes transferred in the month, including the day.
+    pub month_bytes: i64,
+}
+
+/// Repository for per-user API usage counters and quota tier assignments.
+#[derive(Debug, Clone)]
+pub struct ApiUsageRepository {
+    pool: TimedPool,
+}
+
+impl ApiUsageRepository {
+    /// Create a new API usage repository.
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool: TimedPool::new(pool, "ApiUsageRepository"),
+        }
+    }
+
+    /// Add usage to the daily counters. Deltas are added, not set, so
+    /// every node can flush its own counts.
+    pub async fn add_daily(&self, deltas: &[(Uuid, NaiveDate, Usage)]) -> AppResult<()> {
+        if deltas.is_empty() {
+            return Ok(());
+        }
+        let user_ids: Vec<Uuid> = deltas.iter().map(|(user_id, _, _)| *user_id).collect();
+        let days: Vec<NaiveDate> = deltas.iter().map(|(_, day, _)| *day).collect();
+        let calls: Vec<i64> = deltas.iter().map(|(_, _, u)| to_db(u.calls)).collect();
+        let bytes: Vec<i64> = deltas.iter().map(|(_, _, u)| to_db(u.bytes)).collect();
+
+        sqlx::query(
+            "INSERT INTO api_usage_daily (user_id, day, calls, bytes) \
+             SELECT * FROM UNNEST($1::uuid[], $2::date[], $3::bigint[], $4::bigint[]) \
+             ON CONFLICT (user_id, day) DO UPDATE SET \
+                calls = api_usage_daily.calls + EXCLUDED.calls, \
+                bytes = api_usage_daily.bytes + EXCLUDED.bytes",
+        )
+        .bind(&user_ids)
+        .bind(&days)
+        .bind(&calls)
+        .bind(&bytes)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to record API usage", e))?;
+        Ok(())
+    }
+
+    /// Usage of each user on `day` and in the month up to it, with their
+    /// assigned tiers.
+    pub async fn totals(&self, user_ids: &[Uuid], day: NaiveDate) -> AppResult<Vec<UsageTotals>> {
+        let month = filehub_entity::user::UsagePeriod::Month.start(day);
+        sqlx::query_as::<_, UsageTotals>(
+            "SELECT u.id AS user_id, t.tier, \
+                COALESCE(SUM(d.calls) FILTER (WHERE d.day = $2), 0)::bigint AS day_calls, \
+                COALESCE(SUM(d.bytes) FILTER (WHERE d.day = $2), 0)::bigint AS day_bytes, \
+                (COALESCE(SUM(d.calls), 0) + COALESCE(m.calls, 0))::bigint AS month_calls, \
+                (COALESCE(SUM(d.bytes), 0) + COALESCE(m.bytes, 0))::bigint AS month_bytes \
+             FROM UNNEST($1::uuid[]) AS u(id) \
+             LEFT JOIN user_quota_tiers t ON t.user_id = u.id \
+             LEFT JOIN api_usage_monthly m ON m.user_id = u.id AND m.month = $3 \
+             LEFT JOIN api_usage_daily d ON d.user_id = u.id AND d.day >= $3 AND d.day <= $2 \
+             GROUP BY u.id, t.tier, m.calls, m.bytes",
+        )
+        .bind(user_ids)
+        .bind(day)
+        .bind(month)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to load API usage", e))
+    }
+
+    /// A user's usage per month, most recent first.
+    pub async fn monthly_history(
+        &self,
+        user_id: Uuid,
+        months: i64,
+    ) -> AppResult<Vec<MonthlyUsage>> {
+        sqlx::query_as::<_, MonthlyUsage>(
+            "SELECT month, SUM(calls)::bigint AS calls, SUM(bytes)::bigint AS bytes FROM ( \
+                SELECT month, calls, bytes FROM api_usage_monthly WHERE user_id = $1 \
+                UNION ALL \
+                SELECT date_trunc('month', day)::date, calls, bytes \
+                FROM api_usage_daily WHERE user_id = $1 \
+             ) usage GROUP BY month ORDER BY month DESC LIMIT $2",
+        )
+        .bind(user_id)
+        .bind(months)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to load API usage history", e)
+        })
+    }
+
+    /// Fold the daily counters of days before `before` into monthly
+    /// totals; returns how many monthly totals were written.
+    pub async fn roll_up_before(&self, before: NaiveDate) -> AppResult<u64> {
+        let result = sqlx::query(
+            "WITH moved AS ( \
+                DELETE FROM api_usage_daily WHERE day < $1 \
+                RETURNING user_id, day, calls, bytes \
+             ) INSERT INTO api_usage_monthly (user_id, month, calls, bytes) \
+             SELECT user_id, date_trunc('month', day)::date, SUM(calls), SUM(bytes) \
+             FROM moved GROUP BY 1, 2 \
+             ON CONFLICT (user_id, month) DO UPDATE SET \
+                calls = api_usage_monthly.calls + EXCLUDED.calls, \
+                bytes = api_usage_monthly.bytes + EXCLUDED.bytes",
+        )
+        .bind(before)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to roll up API usage", e)
+        })?;
+        Ok(result.rows_affected())
+    }
+
+    /// A user's assigned tier.
+    pub async fn find_tier(&self, user_id: Uuid) -> AppResult<Option<UserQuotaTier>> {
+        sqlx::query_as::<_, UserQuotaTier>("SELECT * FROM user_quota_tiers WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to find quota tier", e))
+    }
+
+    /// Assign a tier to a user, replacing any assigned before.
+    pub async fn set_tier(
+        &self,
+        user_id: Uuid,
+        tier: &str,
+        assigned_by: Uuid,
+    ) -> AppResult<UserQuotaTier> {
+        sqlx::query_as::<_, UserQuotaTier>(
+            "INSERT INTO user_quota_tiers (user_id, tier, assigned_by) VALUES ($1, $2, $3) \
+             ON CONFLICT (user_id) DO UPDATE SET \
+                tier = EXCLUDED.tier, assigned_by = EXCLUDED.assigned_by, assigned_at = NOW() \
+             RETURNING *",
+        )
+        .bind(user_id)
+        .bind(tier)
+        .bind(assigned_by)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to set quota tier", e))
+    }
+
+    /// Remove a user's assigned tier; returns whether there was one.
+    pub async fn clear_tier(&self, user_id: Uuid) -> AppResult<bool> {
+        let result = sqlx::query("DELETE FROM user_quota_tiers WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to clear quota tier", e)
+            })?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// Counters are `BIGINT`; saturate rather than wrap.
+fn to_db(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
@@ -1,5 +1,7 @@
 //! Audit log repository implementation.
 
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt, TryStreamExt};
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -7,17 +9,95 @@ use filehub_core::error::{AppError, ErrorKind};
 use filehub_core::result::AppResult;
 use filehub_core::types::pagination::{PageRequest, PageResponse};
 use filehub_entity::audit::model::{AuditLogEntry, CreateAuditLogEntry};
+use filehub_entity::audit::{AuditArchive, CreateAuditArchive};
+
+use crate::connection::DatabasePool;
+use crate::slow_query::TimedPool;
+
+/// Rows buffered between the export query and its consumer.
+const EXPORT_BUFFER: usize = 256;
+
+/// Filter for [`AuditLogRepository::export`]. Every field maps to an
+/// indexed column (see the `audit_log` migrations).
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    /// Only entries by this actor.
+    pub actor_id: Option<Uuid>,
+    /// Only this action.
+    pub action: Option<String>,
+    /// Only this target type.
+    pub target_type: Option<String>,
+    /// Only this target.
+    pub target_id: Option<Uuid>,
+    /// Only entries at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Only entries before this time.
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl AuditFilter {
+    /// The SQL selecting matching entries, oldest first.
+    fn select_sql(&self) -> String {
+        let mut conditions = Vec::new();
+        let mut next = 1;
+        let mut param = |column: &str, op: &str| {
+            conditions.push(format!("{column} {op} ${next}"));
+            next += 1;
+        };
+        if self.actor_id.is_some() {
+            param("actor_id", "=");
+        }
+        if self.action.is_some() {
+            param("action", "=");
+        }
+        if self.target_type.is_some() {
+            param("target_type", "=");
+        }
+        if self.target_id.is_some() {
+            param("target_id", "=");
+        }
+        if self.since.is_some() {
+            param("created_at", ">=");
+        }
+        if self.until.is_some() {
+            param("created_at", "<");
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
+        };
+        format!(
+            "SELECT id, actor_id, impersonator_id, action, target_type, target_id, details, \
+             host(ip_address) AS ip_address, user_agent, created_at \
+             FROM audit_log{where_clause} ORDER BY created_at, id"
+        )
+    }
+}
 
 /// Repository for audit log entries.
 #[derive(Debug, Clone)]
 pub struct AuditLogRepository {
-    pool: PgPool,
+    pool: TimedPool,
+    /// Pool for reads that may be served by a replica.
+    reads: DatabasePool,
 }
 
 impl AuditLogRepository {
     /// Create a new audit log repository.
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            reads: DatabasePool::from_pool(pool.clone()),
+            pool: TimedPool::new(pool, "AuditLogRepository"),
+        }
+    }
+
+    /// Route this repository's stale-tolerant reads through `db`'s read
+    /// replicas.
+    pub fn with_read_replicas(mut self, db: DatabasePool) -> Self {
+        self.reads = db;
+        self
     }
 
     /// Find an audit entry by ID.
@@ -117,8 +197,10 @@ impl AuditLogRepository {
     /// Create an audit log entry.
     pub async fn create(&self, data: &CreateAuditLogEntry) -> AppResult<AuditLogEntry> {
         sqlx::query_as::<_, AuditLogEntry>(
-            "INSERT INTO audit_log (actor_id, action, target_type, target_id, details, ip_address, user_agent) \
-             VALUES ($1, $2, $3, $4, $5, $6::INET, $7) RETURNING *"
+            "INSERT INTO audit_log (actor_id, action, target_type, target_id, details, ip_address, user_agent, impersonator_id) \
+             VALUES ($1, $2, $3, $4, $5, $6::INET, $7, $8) \
+             RETURNING id, actor_id, impersonator_id, action, target_type, target_id, details, \
+             host(ip_address) AS ip_address, user_agent, created_at"
         )
             .bind(data.actor_id)
             .bind(&data.action)
@@ -127,11 +209,40 @@ impl AuditLogRepository {
             .bind(&data.details)
             .bind(&data.ip_address)
             .bind(&data.user_agent)
+            .bind(data.impersonator_id)
             .fetch_one(&self.pool)
             .await
             .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to create audit entry", e))
     }
 
+    /// Create the audit entry of domain event `event_id`. Returns `None`,
+    /// writing nothing, if the event was already recorded.
+    pub async fn create_for_event(
+        &self,
+        event_id: Uuid,
+        data: &CreateAuditLogEntry,
+    ) -> AppResult<Option<AuditLogEntry>> {
+        sqlx::query_as::<_, AuditLogEntry>(
+            "INSERT INTO audit_log (actor_id, action, target_type, target_id, details, ip_address, user_agent, impersonator_id, event_id) \
+             VALUES ($1, $2, $3, $4, $5, $6::INET, $7, $8, $9) \
+             ON CONFLICT (event_id) WHERE event_id IS NOT NULL DO NOTHING \
+             RETURNING id, actor_id, impersonator_id, action, target_type, target_id, details, \
+             host(ip_address) AS ip_address, user_agent, created_at"
+        )
+            .bind(data.actor_id)
+            .bind(&data.action)
+            .bind(&data.target_type)
+            .bind(data.target_id)
+            .bind(&data.details)
+            .bind(&data.ip_address)
+            .bind(&data.user_agent)
+            .bind(data.impersonator_id)
+            .bind(event_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to create audit entry", e))
+    }
+
     /// Count occurrences of an action since a specific time.
     pub async fn count_actions_since(
         &self,
@@ -152,19 +263,21 @@ impl AuditLogRepository {
     }
     /// Count audit entries since a specific time.
     pub async fn count_since(&self, since: chrono::DateTime<chrono::Utc>) -> AppResult<i64> {
-        let count: i64 =
-            sqlx::query_scalar("SELECT COUNT(*) FROM audit_log WHERE created_at >= $1")
-                .bind(since)
-                .fetch_one(&self.pool)
-                .await
-                .map_err(|e| {
-                    AppError::with_source(
-                        ErrorKind::Database,
-                        "Failed to count recent audit entries",
-                        e,
-                    )
-                })?;
-        Ok(count)
+        self.reads
+            .read_only(|pool| async move {
+                sqlx::query_scalar("SELECT COUNT(*) FROM audit_log WHERE created_at >= $1")
+                    .bind(since)
+                    .fetch_one(&pool)
+                    .await
+                    .map_err(|e| {
+                        AppError::with_source(
+                            ErrorKind::Database,
+                            "Failed to count recent audit entries",
+                            e,
+                        )
+                    })
+            })
+            .await
     }
 
     /// Find since a specific time.
@@ -186,4 +299,164 @@ impl AuditLogRepository {
                 })?;
         Ok(entries)
     }
+
+    /// Find up to `limit` entries created before `before`, oldest first,
+    /// leaving out entries about a file or folder under a legal hold.
+    pub async fn find_archivable(
+        &self,
+        before: DateTime<Utc>,
+        limit: i64,
+    ) -> AppResult<Vec<AuditLogEntry>> {
+        sqlx::query_as::<_, AuditLogEntry>(
+            "WITH RECURSIVE held AS ( \
+                SELECT folder_id AS id FROM folder_retention_policies WHERE legal_hold \
+                UNION \
+                SELECT f.id FROM folders f INNER JOIN held h ON f.parent_id = h.id \
+             ) SELECT a.id, a.actor_id, a.impersonator_id, a.action, a.target_type, a.target_id, \
+                a.details, host(a.ip_address) AS ip_address, a.user_agent, a.created_at \
+             FROM audit_log a \
+             WHERE a.created_at < $1 \
+             AND NOT EXISTS (SELECT 1 FROM held h \
+                WHERE a.target_type = 'folder' AND h.id = a.target_id) \
+             AND NOT EXISTS (SELECT 1 FROM files f INNER JOIN held h ON h.id = f.folder_id \
+                WHERE a.target_type = 'file' AND f.id = a.target_id) \
+             ORDER BY a.created_at, a.id LIMIT $2",
+        )
+        .bind(before)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(
+                ErrorKind::Database,
+                "Failed to find archivable audit entries",
+                e,
+            )
+        })
+    }
+
+    /// Record a verified archive and delete the entries it holds, in one
+    /// transaction. Nothing is deleted unless every one of `ids` is.
+    pub async fn record_archive(
+        &self,
+        archive: &CreateAuditArchive,
+        ids: &[Uuid],
+    ) -> AppResult<AuditArchive> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to begin transaction", e)
+        })?;
+
+        let recorded = sqlx::query_as::<_, AuditArchive>(
+            "INSERT INTO audit_archives \
+                (storage_id, path, row_count, size_bytes, sha256, first_entry_at, last_entry_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+        )
+        .bind(archive.storage_id)
+        .bind(&archive.path)
+        .bind(archive.row_count)
+        .bind(archive.size_bytes)
+        .bind(&archive.sha256)
+        .bind(archive.first_entry_at)
+        .bind(archive.last_entry_at)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to record audit archive", e)
+        })?;
+
+        let deleted = sqlx::query("DELETE FROM audit_log WHERE id = ANY($1)")
+            .bind(ids)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                AppError::with_source(
+                    ErrorKind::Database,
+                    "Failed to delete archived audit entries",
+                    e,
+                )
+            })?
+            .rows_affected();
+        if deleted != ids.len() as u64 || deleted != archive.row_count as u64 {
+            return Err(AppError::internal(format!(
+                "Archive {} holds {} audit entries but {} matched; nothing was deleted",
+                archive.path, archive.row_count, deleted
+            )));
+        }
+
+        tx.commit().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to commit audit archive", e)
+        })?;
+        Ok(recorded)
+    }
+
+    /// Stream every entry matching `filter`, oldest first.
+    ///
+    /// Rows are fetched with a cursor on a background task and handed over
+    /// through a bounded buffer, so memory use stays flat however many
+    /// entries match. Dropping the stream stops the query.
+    pub fn export(
+        &self,
+        filter: AuditFilter,
+    ) -> impl Stream<Item = AppResult<AuditLogEntry>> + Send + 'static {
+        let (tx, rx) = tokio::sync::mpsc::channel(EXPORT_BUFFER);
+        // Not timed: an export runs as long as its consumer reads.
+        let pool: PgPool = (*self.pool).clone();
+        tokio::spawn(async move {
+            let sql = filter.select_sql();
+            let mut query = sqlx::query_as::<_, AuditLogEntry>(&sql);
+            if let Some(actor_id) = filter.actor_id {
+                query = query.bind(actor_id);
+            }
+            if let Some(action) = &filter.action {
+                query = query.bind(action);
+            }
+            if let Some(target_type) = &filter.target_type {
+                query = query.bind(target_type);
+            }
+            if let Some(target_id) = filter.target_id {
+                query = query.bind(target_id);
+            }
+            if let Some(since) = filter.since {
+                query = query.bind(since);
+            }
+            if let Some(until) = filter.until {
+                query = query.bind(until);
+            }
+
+            let mut rows = query.fetch(&pool).map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to export audit log", e)
+            });
+            while let Some(row) = rows.next().await {
+                let failed = row.is_err();
+                if tx.send(row).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        futures::stream::unfold(
+            rx,
+            |mut rx| async move { rx.recv().await.map(|row| (row, rx)) },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_sql_numbers_parameters_in_bind_order() {
+        let filter = AuditFilter {
+            action: Some("file.upload".to_string()),
+            since: Some(Utc::now()),
+            until: Some(Utc::now()),
+            ..AuditFilter::default()
+        };
+        let sql = filter.select_sql();
+        assert!(
+            sql.contains("WHERE action = $1 AND created_at >= $2 AND created_at < $3 ORDER BY")
+        );
+        assert!(!AuditFilter::default().select_sql().contains("WHERE"));
+    }
 }
@@ -1,25 +1,93 @@
 //! File repository implementation.
 
-use sqlx::PgPool;
+use futures::Stream;
+use sqlx::{PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
 
 use filehub_core::error::{AppError, ErrorKind};
+use filehub_core::events::DomainEvent;
 use filehub_core::result::AppResult;
-use filehub_core::types::pagination::{PageRequest, PageResponse};
+use filehub_core::types::filter::{FieldKind, FilterField, FilterNode, FilterOp, FilterValue};
+use filehub_core::types::pagination::{PageMode, PageRequest, PageResponse};
 use filehub_entity::file::chunk::ChunkedUpload;
 use filehub_entity::file::model::{CreateFile, File};
-use filehub_entity::file::version::FileVersion;
+use filehub_entity::file::naming::candidate_names;
+use filehub_entity::file::version::{FileVersion, FileVersionInfo};
+use filehub_entity::folder::preferences::FolderSort;
+use filehub_entity::tag::Tag;
+
+use crate::connection::DatabasePool;
+use crate::repositories::outbox::OutboxRepository;
+use crate::slow_query::TimedPool;
+use crate::tenant::TenantScope;
+
+/// Rows fetched per query by [`FileRepository::export`].
+const EXPORT_BATCH: i64 = 500;
+
+/// Rows buffered between the export queries and their consumer.
+const EXPORT_BUFFER: usize = 256;
+
+/// Criteria for [`FileRepository::search`].
+#[derive(Debug, Clone, Default)]
+pub struct FileSearchCriteria {
+    /// Free-text query (web-search syntax: quotes, `OR`, `-term`).
+    pub text: Option<String>,
+    /// Restrict to a folder.
+    pub folder_id: Option<Uuid>,
+    /// Restrict to a storage backend.
+    pub storage_id: Option<Uuid>,
+    /// MIME type prefix (e.g. `"image/"`).
+    pub mime_prefix: Option<String>,
+    /// Restrict to an owner.
+    pub owner_id: Option<Uuid>,
+    /// Minimum size in bytes.
+    pub min_size: Option<i64>,
+    /// Maximum size in bytes.
+    pub max_size: Option<i64>,
+    /// Structured filter tree; validate with [`file_filter_kind`] first.
+    pub filter: Option<FilterNode>,
+    /// Whose tags the `tag` filter field refers to; any owner's if unset.
+    pub tag_owner_id: Option<Uuid>,
+    /// Tenant the search is restricted to.
+    pub tenant: Option<TenantScope>,
+}
+
+impl FileSearchCriteria {
+    /// The trimmed query text, if non-empty.
+    fn text(&self) -> Option<&str> {
+        self.text
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+    }
+}
+
+/// Makes the event announcing a written file, which is recorded in the
+/// outbox in the same transaction as the write.
+pub type Announce<'a> = &'a (dyn Fn(&File) -> DomainEvent + Send + Sync);
 
 /// Repository for file CRUD and query operations.
 #[derive(Debug, Clone)]
 pub struct FileRepository {
-    pool: PgPool,
+    pool: TimedPool,
+    /// Pool for reads that may be served by a replica.
+    reads: DatabasePool,
 }
 
 impl FileRepository {
     /// Create a new file repository.
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            reads: DatabasePool::from_pool(pool.clone()),
+            pool: TimedPool::new(pool, "FileRepository"),
+        }
+    }
+
+    /// Route this repository's stale-tolerant reads through `db`'s read
+    /// replicas.
+    pub fn with_read_replicas(mut self, db: DatabasePool) -> Self {
+        self.reads = db;
+        self
     }
 
     /// Find a file by ID.
@@ -31,21 +99,47 @@ impl FileRepository {
             .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to find file", e))
     }
 
-    /// List files in a folder with pagination.
+    /// List files in a folder with pagination, in `sort` order.
     pub async fn find_by_folder(
         &self,
         folder_id: Uuid,
         page: &PageRequest,
+        sort: &FolderSort,
     ) -> AppResult<PageResponse<File>> {
+        let column = sort.file_column();
+        if let PageMode::Cursor(after) = page.mode()? {
+            if let Some(cursor) = &after {
+                sort.check_cursor(column, cursor)?;
+            }
+            let (after_key, after_id) = after.map_or((None, None), |c| (Some(c.key), Some(c.id)));
+            let files = sqlx::query_as::<_, File>(&format!(
+                "SELECT * FROM files WHERE folder_id = $1 AND {} ORDER BY {} LIMIT $4",
+                sort.after(column, 2),
+                sort.order_by(column.0)
+            ))
+            .bind(folder_id)
+            .bind(after_key)
+            .bind(after_id)
+            .bind(page.limit() as i64 + 1)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to list files", e))?;
+
+            return Ok(PageResponse::from_keyset(files, page, |f| {
+                sort.file_cursor(f)
+            }));
+        }
+
         let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM files WHERE folder_id = $1")
             .bind(folder_id)
             .fetch_one(&self.pool)
             .await
             .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to count files", e))?;
 
-        let files = sqlx::query_as::<_, File>(
-            "SELECT * FROM files WHERE folder_id = $1 ORDER BY name ASC LIMIT $2 OFFSET $3",
-        )
+        let files = sqlx::query_as::<_, File>(&format!(
+            "SELECT * FROM files WHERE folder_id = $1 ORDER BY {} LIMIT $2 OFFSET $3",
+            sort.order_by(column.0)
+        ))
         .bind(folder_id)
         .bind(page.limit() as i64)
         .bind(page.offset() as i64)
@@ -61,6 +155,26 @@ impl FileRepository {
         ))
     }
 
+    /// All files directly inside any of `folder_ids`.
+    pub async fn find_in_folders(&self, folder_ids: &[Uuid]) -> AppResult<Vec<File>> {
+        sqlx::query_as::<_, File>(
+            "SELECT * FROM files WHERE folder_id = ANY($1) ORDER BY folder_id, name, id",
+        )
+        .bind(folder_ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to list files", e))
+    }
+
+    /// IDs of all files directly inside any of `folder_ids`.
+    pub async fn find_ids_in_folders(&self, folder_ids: &[Uuid]) -> AppResult<Vec<Uuid>> {
+        sqlx::query_scalar::<_, Uuid>("SELECT id FROM files WHERE folder_id = ANY($1)")
+            .bind(folder_ids)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to list files", e))
+    }
+
     /// Find a file by folder ID and name (for duplicate checking).
     pub async fn find_by_folder_and_name(
         &self,
@@ -119,101 +233,292 @@ impl FileRepository {
         Ok(())
     }
 
-    /// Full-text search across file names and metadata descriptions.
+    /// Ranked search over file names and metadata.
+    ///
+    /// Multi-word queries use the `search_vector` full-text index and are
+    /// ordered by `ts_rank`. A single-word query additionally matches
+    /// substrings of the file name through the trigram index, so partial
+    /// names like `"repo"` still find `"quarterly-report.pdf"`.
+    ///
+    /// Served by a read replica when one is configured.
     pub async fn search(
         &self,
-        query: &str,
-        storage_id: Option<Uuid>,
-        page: &PageRequest,
-    ) -> AppResult<PageResponse<File>> {
-        let ts_query = query.split_whitespace().collect::<Vec<_>>().join(" & ");
+        criteria: &FileSearchCriteria,
+        limit: i64,
+        offset: i64,
+    ) -> AppResult<Vec<File>> {
+        self.reads
+            .read_only(|pool| async move {
+                let mut qb = QueryBuilder::<Postgres>::new("SELECT files.* FROM files WHERE TRUE");
+                push_search_conditions(&mut qb, criteria)?;
 
-        if let Some(sid) = storage_id {
-            let total: i64 = sqlx::query_scalar(
-                "SELECT COUNT(*) FROM files \
-                 WHERE storage_id = $1 AND to_tsvector('english', name || ' ' || COALESCE(metadata->>'description', '')) @@ to_tsquery('english', $2)"
-            )
-                .bind(sid)
-                .bind(&ts_query)
-                .fetch_one(&self.pool)
-                .await
-                .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to count search results", e))?;
+                match criteria.text() {
+                    Some(text) => {
+                        qb.push(
+                            " ORDER BY ts_rank(search_vector, websearch_to_tsquery('english', ",
+                        )
+                        .push_bind(text.to_string())
+                        .push(")) DESC, similarity(name, ")
+                        .push_bind(text.to_string())
+                        .push(") DESC, name ASC, id ASC");
+                    }
+                    None => {
+                        qb.push(" ORDER BY name ASC, id ASC");
+                    }
+                }
+                qb.push(" LIMIT ")
+                    .push_bind(limit)
+                    .push(" OFFSET ")
+                    .push_bind(offset);
 
-            let files = sqlx::query_as::<_, File>(
-                "SELECT * FROM files \
-                 WHERE storage_id = $1 AND to_tsvector('english', name || ' ' || COALESCE(metadata->>'description', '')) @@ to_tsquery('english', $2) \
-                 ORDER BY ts_rank(to_tsvector('english', name || ' ' || COALESCE(metadata->>'description', '')), to_tsquery('english', $2)) DESC \
-                 LIMIT $3 OFFSET $4"
-            )
-                .bind(sid)
-                .bind(&ts_query)
-                .bind(page.limit() as i64)
-                .bind(page.offset() as i64)
-                .fetch_all(&self.pool)
-                .await
-                .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to search files", e))?;
+                qb.build_query_as::<File>()
+                    .fetch_all(&pool)
+                    .await
+                    .map_err(|e| {
+                        AppError::with_source(ErrorKind::Database, "Failed to search files", e)
+                    })
+            })
+            .await
+    }
 
-            Ok(PageResponse::new(
-                files,
-                page.page,
-                page.page_size,
-                total as u64,
-            ))
-        } else {
-            let total: i64 = sqlx::query_scalar(
-                "SELECT COUNT(*) FROM files \
-                 WHERE to_tsvector('english', name || ' ' || COALESCE(metadata->>'description', '')) @@ to_tsquery('english', $1)"
-            )
-                .bind(&ts_query)
-                .fetch_one(&self.pool)
-                .await
-                .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to count search results", e))?;
+    /// Count files matching search criteria (replica-served, like `search`).
+    pub async fn count_search(&self, criteria: &FileSearchCriteria) -> AppResult<u64> {
+        self.reads
+            .read_only(|pool| async move {
+                let mut qb = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM files WHERE TRUE");
+                push_search_conditions(&mut qb, criteria)?;
 
-            let files = sqlx::query_as::<_, File>(
-                "SELECT * FROM files \
-                 WHERE to_tsvector('english', name || ' ' || COALESCE(metadata->>'description', '')) @@ to_tsquery('english', $1) \
-                 ORDER BY ts_rank(to_tsvector('english', name || ' ' || COALESCE(metadata->>'description', '')), to_tsquery('english', $1)) DESC \
-                 LIMIT $2 OFFSET $3"
-            )
-                .bind(&ts_query)
-                .bind(page.limit() as i64)
-                .bind(page.offset() as i64)
-                .fetch_all(&self.pool)
-                .await
-                .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to search files", e))?;
+                let total: i64 = qb
+                    .build_query_scalar()
+                    .fetch_one(&pool)
+                    .await
+                    .map_err(|e| {
+                        AppError::with_source(
+                            ErrorKind::Database,
+                            "Failed to count search results",
+                            e,
+                        )
+                    })?;
+                Ok(total as u64)
+            })
+            .await
+    }
 
-            Ok(PageResponse::new(
-                files,
-                page.page,
-                page.page_size,
-                total as u64,
-            ))
-        }
+    /// Stream every file matching `criteria`, in id order.
+    ///
+    /// Files are read in batches of [`EXPORT_BATCH`], each batch a short
+    /// query resuming after the last id of the one before, so no
+    /// connection or transaction is held while the consumer is slow. Rows
+    /// are handed over through a bounded buffer, so memory use stays flat
+    /// however many files match; dropping the stream stops the reads. The
+    /// text criterion filters but does not rank.
+    ///
+    /// Served by a read replica when one is configured.
+    pub fn export(
+        &self,
+        criteria: FileSearchCriteria,
+    ) -> impl Stream<Item = AppResult<File>> + Send + 'static {
+        let (tx, rx) = tokio::sync::mpsc::channel(EXPORT_BUFFER);
+        let reads = self.reads.clone();
+        tokio::spawn(async move {
+            let mut after: Option<Uuid> = None;
+            loop {
+                let batch = reads
+                    .read_only(|pool| {
+                        let criteria = &criteria;
+                        async move {
+                            let mut qb = QueryBuilder::<Postgres>::new(
+                                "SELECT files.* FROM files WHERE TRUE",
+                            );
+                            push_search_conditions(&mut qb, criteria)?;
+                            if let Some(after) = after {
+                                qb.push(" AND files.id > ").push_bind(after);
+                            }
+                            qb.push(" ORDER BY files.id ASC LIMIT ")
+                                .push_bind(EXPORT_BATCH);
+                            qb.build_query_as::<File>()
+                                .fetch_all(&pool)
+                                .await
+                                .map_err(|e| {
+                                    AppError::with_source(
+                                        ErrorKind::Database,
+                                        "Failed to export files",
+                                        e,
+                                    )
+                                })
+                        }
+                    })
+                    .await;
+
+                let files = match batch {
+                    Ok(files) => files,
+                    Err(e) => {
+                        let _ = tx.send(Err(e)).await;
+                        return;
+                    }
+                };
+                let done = (files.len() as i64) < EXPORT_BATCH;
+                after = files.last().map(|f| f.id);
+                for file in files {
+                    if tx.send(Ok(file)).await.is_err() {
+                        return;
+                    }
+                }
+                if done {
+                    return;
+                }
+            }
+        });
+
+        futures::stream::unfold(
+            rx,
+            |mut rx| async move { rx.recv().await.map(|row| (row, rx)) },
+        )
     }
 
     /// Create a new file record.
-    pub async fn create(&self, data: &CreateFile) -> AppResult<File> {
-        sqlx::query_as::<_, File>(
-            "INSERT INTO files (folder_id, storage_id, name, storage_path, mime_type, size_bytes, checksum_sha256, metadata, owner_id) \
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING *"
+    pub async fn create(
+        &self,
+        data: &CreateFile,
+        announce: Option<Announce<'_>>,
+    ) -> AppResult<File> {
+        self.insert(data, announce).await.map_err(|e| {
+            if is_name_taken(&e) {
+                AppError::conflict(format!(
+                    "File '{}' already exists in this folder",
+                    data.name
+                ))
+            } else {
+                e.into_app_error("Failed to create file")
+            }
+        })
+    }
+
+    /// Create a new file record under the first free name among
+    /// `data.name`, `name (2).ext`, `name (3).ext`, ... The unique
+    /// constraint on folder and name decides, so concurrent callers never
+    /// get the same name.
+    pub async fn create_with_free_name(
+        &self,
+        data: &CreateFile,
+        announce: Option<Announce<'_>>,
+    ) -> AppResult<File> {
+        let mut candidate = data.clone();
+        for name in candidate_names(&data.name) {
+            candidate.name = name;
+            match self.insert(&candidate, announce).await {
+                Ok(file) => return Ok(file),
+                Err(e) if is_name_taken(&e) => continue,
+                Err(e) => return Err(e.into_app_error("Failed to create file")),
+            }
+        }
+        Err(AppError::conflict(format!(
+            "No free name for '{}' in this folder",
+            data.name
+        )))
+    }
+
+    async fn insert(
+        &self,
+        data: &CreateFile,
+        announce: Option<Announce<'_>>,
+    ) -> Result<File, InsertError> {
+        let mut tx = self.pool.begin().await?;
+        let file = sqlx::query_as::<_, File>(
+            "INSERT INTO files (folder_id, storage_id, name, storage_path, mime_type, declared_mime_type, size_bytes, checksum_sha256, metadata, owner_id) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING *"
         )
             .bind(data.folder_id)
             .bind(data.storage_id)
             .bind(&data.name)
             .bind(&data.storage_path)
             .bind(&data.mime_type)
+            .bind(&data.declared_mime_type)
             .bind(data.size_bytes)
             .bind(&data.checksum_sha256)
             .bind(&data.metadata)
             .bind(data.owner_id)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *tx)
+            .await?;
+        if let Some(announce) = announce {
+            OutboxRepository::enqueue(&mut tx, &[announce(&file)])
+                .await
+                .map_err(InsertError::Outbox)?;
+        }
+        tx.commit().await?;
+        Ok(file)
+    }
+
+    /// Replace a file's content with `content`'s storage path, size,
+    /// checksum and type, bumping its version number. With `keep_version`
+    /// the replaced content is recorded as a version first. Returns `None`
+    /// if the file no longer exists.
+    pub async fn replace_content(
+        &self,
+        file_id: Uuid,
+        content: &CreateFile,
+        keep_version: bool,
+        replaced_by: Uuid,
+        announce: Option<Announce<'_>>,
+    ) -> AppResult<Option<File>> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to begin transaction", e)
+        })?;
+
+        let Some(current) =
+            sqlx::query_as::<_, File>("SELECT * FROM files WHERE id = $1 FOR UPDATE")
+                .bind(file_id)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(|e| {
+                    AppError::with_source(ErrorKind::Database, "Failed to lock file", e)
+                })?
+        else {
+            return Ok(None);
+        };
+
+        if keep_version {
+            sqlx::query(
+                "INSERT INTO file_versions (file_id, version_number, storage_path, size_bytes, checksum_sha256, created_by, comment) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (file_id, version_number) DO NOTHING",
+            )
+            .bind(file_id)
+            .bind(current.current_version)
+            .bind(&current.storage_path)
+            .bind(current.size_bytes)
+            .bind(&current.checksum_sha256)
+            .bind(replaced_by)
+            .bind("Overwritten by a file of the same name")
+            .execute(&mut *tx)
             .await
-            .map_err(|e| match e {
-                sqlx::Error::Database(ref db_err) if db_err.constraint() == Some("files_folder_id_name_key") => {
-                    AppError::conflict(format!("File '{}' already exists in this folder", data.name))
-                }
-                _ => AppError::with_source(ErrorKind::Database, "Failed to create file", e),
-            })
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to keep current version", e)
+            })?;
+        }
+
+        let replaced = sqlx::query_as::<_, File>(
+            "UPDATE files SET storage_path = $2, size_bytes = $3, checksum_sha256 = $4, \
+             mime_type = COALESCE($5, mime_type), \
+             declared_mime_type = COALESCE($6, declared_mime_type), \
+             current_version = current_version + 1, updated_at = NOW() WHERE id = $1 RETURNING *",
+        )
+        .bind(file_id)
+        .bind(&content.storage_path)
+        .bind(content.size_bytes)
+        .bind(&content.checksum_sha256)
+        .bind(&content.mime_type)
+        .bind(&content.declared_mime_type)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to replace file", e))?;
+        if let Some(announce) = announce {
+            OutboxRepository::enqueue(&mut tx, &[announce(&replaced)]).await?;
+        }
+
+        tx.commit().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to commit file replacement", e)
+        })?;
+        Ok(Some(replaced))
     }
 
     /// Update file metadata.
@@ -315,6 +620,99 @@ impl FileRepository {
         .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to list file versions", e))
     }
 
+    /// List versions of a file, newest first, with their authors' names.
+    pub async fn find_version_history(&self, file_id: Uuid) -> AppResult<Vec<FileVersionInfo>> {
+        sqlx::query_as::<_, FileVersionInfo>(
+            "SELECT v.*, u.username AS author_username, u.display_name AS author_display_name \
+             FROM file_versions v LEFT JOIN users u ON u.id = v.created_by \
+             WHERE v.file_id = $1 ORDER BY v.version_number DESC",
+        )
+        .bind(file_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to list file versions", e))
+    }
+
+    /// Find a file version by its ID.
+    pub async fn find_version_by_id(&self, id: Uuid) -> AppResult<Option<FileVersion>> {
+        sqlx::query_as::<_, FileVersion>("SELECT * FROM file_versions WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to find file version", e)
+            })
+    }
+
+    /// Make `version`'s content current again.
+    ///
+    /// In one transaction, the file's current content is kept as a version
+    /// numbered `current_version` (unless one exists), and the file is
+    /// pointed at the restored content with its version number bumped.
+    /// The event `announce` makes of the restored file is recorded in the
+    /// outbox in the same transaction. Returns `None` if the file no
+    /// longer exists.
+    pub async fn restore_version(
+        &self,
+        file_id: Uuid,
+        version: &FileVersion,
+        restored_by: Uuid,
+        comment: &str,
+        announce: impl FnOnce(&File) -> DomainEvent + Send,
+    ) -> AppResult<Option<File>> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to begin transaction", e)
+        })?;
+
+        let Some(current) =
+            sqlx::query_as::<_, File>("SELECT * FROM files WHERE id = $1 FOR UPDATE")
+                .bind(file_id)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(|e| {
+                    AppError::with_source(ErrorKind::Database, "Failed to lock file", e)
+                })?
+        else {
+            return Ok(None);
+        };
+
+        sqlx::query(
+            "INSERT INTO file_versions (file_id, version_number, storage_path, size_bytes, checksum_sha256, created_by, comment) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (file_id, version_number) DO NOTHING",
+        )
+        .bind(file_id)
+        .bind(current.current_version)
+        .bind(&current.storage_path)
+        .bind(current.size_bytes)
+        .bind(&current.checksum_sha256)
+        .bind(restored_by)
+        .bind(comment)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to keep current version", e)
+        })?;
+
+        let restored = sqlx::query_as::<_, File>(
+            "UPDATE files SET storage_path = $2, size_bytes = $3, checksum_sha256 = $4, \
+             current_version = current_version + 1, updated_at = NOW() \
+             WHERE id = $1 RETURNING *",
+        )
+        .bind(file_id)
+        .bind(&version.storage_path)
+        .bind(version.size_bytes)
+        .bind(&version.checksum_sha256)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to restore version", e))?;
+        OutboxRepository::enqueue(&mut tx, &[announce(&restored)]).await?;
+
+        tx.commit().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to commit version restore", e)
+        })?;
+        Ok(Some(restored))
+    }
+
     /// Find a specific version of a file.
     pub async fn find_version(
         &self,
@@ -359,13 +757,21 @@ impl FileRepository {
     }
 
     /// Delete old file versions exceeding the retention limit per file.
+    /// Versions of files in a folder under legal hold, directly or through
+    /// an ancestor, are kept.
     pub async fn delete_old_versions(&self, max_versions: i64) -> AppResult<u64> {
         let result = sqlx::query(
-            "DELETE FROM file_versions WHERE id IN (\
+            "WITH RECURSIVE held AS (\
+                SELECT folder_id AS id FROM folder_retention_policies WHERE legal_hold \
+                UNION \
+                SELECT f.id FROM folders f INNER JOIN held h ON f.parent_id = h.id\
+             ) \
+             DELETE FROM file_versions WHERE id IN (\
                 SELECT id FROM (\
-                    SELECT id, ROW_NUMBER() OVER (PARTITION BY file_id ORDER BY version_number DESC) as r_num \
+                    SELECT id, file_id, ROW_NUMBER() OVER (PARTITION BY file_id ORDER BY version_number DESC) as r_num \
                     FROM file_versions\
-                ) t WHERE t.r_num > $1\
+                ) t WHERE t.r_num > $1 \
+                AND t.file_id NOT IN (SELECT id FROM files WHERE folder_id IN (SELECT id FROM held))\
              )",
         )
         .bind(max_versions)
@@ -424,6 +830,41 @@ impl FileRepository {
             .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to create chunked upload", e))
     }
 
+    /// Create a direct upload session, for a file the client uploads
+    /// straight to `object_path` on the storage backend.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_direct_upload(
+        &self,
+        user_id: Uuid,
+        storage_id: Uuid,
+        target_folder_id: Uuid,
+        file_name: &str,
+        file_size: i64,
+        mime_type: &str,
+        object_path: &str,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> AppResult<ChunkedUpload> {
+        sqlx::query_as::<_, ChunkedUpload>(
+            "INSERT INTO chunked_uploads \
+             (user_id, storage_id, target_folder_id, file_name, file_size, mime_type, \
+              chunk_size, total_chunks, temp_path, expires_at, direct) \
+             VALUES ($1, $2, $3, $4, $5, $6, 0, 0, $7, $8, TRUE) RETURNING *",
+        )
+        .bind(user_id)
+        .bind(storage_id)
+        .bind(target_folder_id)
+        .bind(file_name)
+        .bind(file_size)
+        .bind(mime_type)
+        .bind(object_path)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to create direct upload", e)
+        })
+    }
+
     /// Update uploaded chunks list.
     pub async fn update_chunked_upload_chunks(
         &self,
@@ -472,6 +913,48 @@ impl FileRepository {
         Ok(())
     }
 
+    /// Record a received chunk and its SHA-256, pushing the session expiry
+    /// out to `expires_at`. Returns `false` without changing anything if
+    /// the chunk was already recorded or the session is no longer
+    /// accepting chunks.
+    pub async fn record_chunk(
+        &self,
+        upload_id: Uuid,
+        chunk_number: i32,
+        checksum_sha256: &str,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> AppResult<bool> {
+        let result = sqlx::query(
+            "UPDATE chunked_uploads SET \
+             uploaded_chunks = uploaded_chunks || to_jsonb($2::int), \
+             chunk_checksums = chunk_checksums || jsonb_build_object($2::text, $3::text), \
+             expires_at = $4 \
+             WHERE id = $1 AND status = 'uploading' AND NOT chunk_checksums ? ($2::text)",
+        )
+        .bind(upload_id)
+        .bind(chunk_number)
+        .bind(checksum_sha256)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to record chunk", e))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Move an upload from `uploading` to `assembling`. Returns `false` if
+    /// it was not uploading, e.g. because another request is assembling it.
+    pub async fn begin_assembly(&self, upload_id: Uuid) -> AppResult<bool> {
+        let result = sqlx::query(
+            "UPDATE chunked_uploads SET status = 'assembling' \
+             WHERE id = $1 AND status = 'uploading'",
+        )
+        .bind(upload_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to start assembly", e))?;
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Complete a chunked upload.
     pub async fn complete_chunked_upload(&self, upload_id: Uuid) -> AppResult<()> {
         sqlx::query(
@@ -495,10 +978,11 @@ impl FileRepository {
         Ok(result.rows_affected())
     }
 
-    /// Find all expired chunked uploads (status = uploading AND expires_at < NOW())
+    /// Find chunked uploads that are still open (uploading or assembling) past their expiry
     pub async fn find_expired_uploads(&self) -> AppResult<Vec<ChunkedUpload>> {
         sqlx::query_as::<_, ChunkedUpload>(
-            "SELECT * FROM chunked_uploads WHERE status = 'uploading' AND expires_at < NOW()",
+            "SELECT * FROM chunked_uploads \
+             WHERE status IN ('uploading', 'assembling') AND expires_at < NOW()",
         )
         .fetch_all(&self.pool)
         .await
@@ -521,11 +1005,16 @@ impl FileRepository {
 
     /// Count total files.
     pub async fn count_all(&self) -> AppResult<i64> {
-        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM files")
-            .fetch_one(&self.pool)
+        self.reads
+            .read_only(|pool| async move {
+                sqlx::query_scalar("SELECT COUNT(*) FROM files")
+                    .fetch_one(&pool)
+                    .await
+                    .map_err(|e| {
+                        AppError::with_source(ErrorKind::Database, "Failed to count files", e)
+                    })
+            })
             .await
-            .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to count files", e))?;
-        Ok(count)
     }
 
     /// Count files created since a specific time.
@@ -533,14 +1022,17 @@ impl FileRepository {
         &self,
         since: chrono::DateTime<chrono::Utc>,
     ) -> AppResult<i64> {
-        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM files WHERE created_at >= $1")
-            .bind(since)
-            .fetch_one(&self.pool)
+        self.reads
+            .read_only(|pool| async move {
+                sqlx::query_scalar("SELECT COUNT(*) FROM files WHERE created_at >= $1")
+                    .bind(since)
+                    .fetch_one(&pool)
+                    .await
+                    .map_err(|e| {
+                        AppError::with_source(ErrorKind::Database, "Failed to count new files", e)
+                    })
+            })
             .await
-            .map_err(|e| {
-                AppError::with_source(ErrorKind::Database, "Failed to count new files", e)
-            })?;
-        Ok(count)
     }
 
     /// Total size of all files in bytes.
@@ -554,6 +1046,209 @@ impl FileRepository {
         Ok(size)
     }
 
+    // -- Storage migration --
+
+    /// Files on a storage, in id order, after `after`.
+    pub async fn find_on_storage_after(
+        &self,
+        storage_id: Uuid,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> AppResult<Vec<File>> {
+        sqlx::query_as::<_, File>(
+            "SELECT * FROM files WHERE storage_id = $1 \
+             AND ($2::uuid IS NULL OR id > $2) ORDER BY id ASC LIMIT $3",
+        )
+        .bind(storage_id)
+        .bind(after)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to list files on storage", e)
+        })
+    }
+
+    /// Number of files on a storage, and the bytes of those files plus
+    /// their stored versions.
+    pub async fn storage_footprint(&self, storage_id: Uuid) -> AppResult<(i64, i64)> {
+        sqlx::query_as::<_, (i64, i64)>(
+            "SELECT \
+                (SELECT COUNT(*) FROM files WHERE storage_id = $1), \
+                (SELECT COALESCE(SUM(size_bytes), 0) FROM files WHERE storage_id = $1)::BIGINT \
+                + (SELECT COALESCE(SUM(v.size_bytes), 0) FROM file_versions v \
+                   JOIN files f ON f.id = v.file_id \
+                   WHERE f.storage_id = $1 AND v.storage_path <> f.storage_path)::BIGINT",
+        )
+        .bind(storage_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to measure storage usage", e)
+        })
+    }
+
+    /// Point a file at another storage, provided it is still on `from` and
+    /// has not changed since `seen_updated_at`. Returns whether it moved.
+    pub async fn repoint_storage(
+        &self,
+        file_id: Uuid,
+        from: Uuid,
+        to: Uuid,
+        seen_updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> AppResult<bool> {
+        let result = sqlx::query(
+            "UPDATE files SET storage_id = $3 \
+             WHERE id = $1 AND storage_id = $2 AND updated_at = $4 \
+             AND COALESCE(is_locked, false) = false",
+        )
+        .bind(file_id)
+        .bind(from)
+        .bind(to)
+        .bind(seen_updated_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to update file storage", e)
+        })?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // -- Expiry --
+
+    /// Set or clear a file's expiry, resetting the expiry warning.
+    /// Returns `None` if the file no longer exists.
+    pub async fn set_expiry(
+        &self,
+        file_id: Uuid,
+        expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> AppResult<Option<File>> {
+        sqlx::query_as::<_, File>(
+            "UPDATE files SET expires_at = $2, expiry_notified_at = NULL, updated_at = NOW() \
+             WHERE id = $1 RETURNING *",
+        )
+        .bind(file_id)
+        .bind(expires_at)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to set file expiry", e))
+    }
+
+    /// Files expiring by `before`, not yet expired, whose owner has not
+    /// been warned, soonest first.
+    pub async fn find_expiring(
+        &self,
+        before: chrono::DateTime<chrono::Utc>,
+        limit: i64,
+    ) -> AppResult<Vec<File>> {
+        sqlx::query_as::<_, File>(
+            "SELECT * FROM files \
+             WHERE expires_at > NOW() AND expires_at <= $1 AND expiry_notified_at IS NULL \
+             ORDER BY expires_at LIMIT $2",
+        )
+        .bind(before)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to find expiring files", e))
+    }
+
+    /// Record that the owner was warned of the expiry at `expires_at`.
+    /// Returns `false` if the expiry changed or the warning was recorded
+    /// meanwhile.
+    pub async fn mark_expiry_notified(
+        &self,
+        file_id: Uuid,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> AppResult<bool> {
+        let result = sqlx::query(
+            "UPDATE files SET expiry_notified_at = NOW() \
+             WHERE id = $1 AND expires_at = $2 AND expiry_notified_at IS NULL",
+        )
+        .bind(file_id)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to record expiry notice", e)
+        })?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Files whose expiry has passed, oldest expiry first.
+    pub async fn find_expired(&self, limit: i64) -> AppResult<Vec<File>> {
+        sqlx::query_as::<_, File>(
+            "SELECT * FROM files WHERE expires_at <= NOW() ORDER BY expires_at LIMIT $1",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to find expired files", e))
+    }
+
+    /// Delete a file provided its expiry has still passed. Returns `false`
+    /// if it was extended, cleared or deleted meanwhile.
+    pub async fn delete_expired(&self, file_id: Uuid) -> AppResult<bool> {
+        let result = sqlx::query("DELETE FROM files WHERE id = $1 AND expires_at <= NOW()")
+            .bind(file_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to delete expired file", e)
+            })?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // -- Quarantine --
+
+    /// Flag a file as quarantined and point it, and any version sharing
+    /// its content, at `quarantine_path`, provided it is still stored at
+    /// `storage_path` and not already quarantined. Returns the updated
+    /// file, or `None` if it changed or was quarantined meanwhile.
+    pub async fn quarantine(
+        &self,
+        file_id: Uuid,
+        storage_path: &str,
+        quarantine_path: &str,
+        reason: &str,
+    ) -> AppResult<Option<File>> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to begin transaction", e)
+        })?;
+
+        let Some(file) = sqlx::query_as::<_, File>(
+            "UPDATE files SET storage_path = $3, quarantined_at = NOW(), quarantine_reason = $4 \
+             WHERE id = $1 AND storage_path = $2 AND quarantined_at IS NULL RETURNING *",
+        )
+        .bind(file_id)
+        .bind(storage_path)
+        .bind(quarantine_path)
+        .bind(reason)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to quarantine file", e))?
+        else {
+            return Ok(None);
+        };
+
+        sqlx::query(
+            "UPDATE file_versions SET storage_path = $3 WHERE file_id = $1 AND storage_path = $2",
+        )
+        .bind(file_id)
+        .bind(storage_path)
+        .bind(quarantine_path)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to quarantine file versions", e)
+        })?;
+
+        tx.commit().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to commit quarantine", e)
+        })?;
+        Ok(Some(file))
+    }
+
     // -- Maintenance --
 
     /// Rebuild search indexes (PostgreSQL specific).
@@ -583,3 +1278,233 @@ impl FileRepository {
         Ok(count as u64)
     }
 }
+
+/// Whether `e` is a violation of the unique file name per folder.
+/// Why [`FileRepository::insert`] failed.
+#[derive(Debug)]
+enum InsertError {
+    /// The insert or its transaction failed.
+    Database(sqlx::Error),
+    /// The announcing event could not be recorded.
+    Outbox(AppError),
+}
+
+impl From<sqlx::Error> for InsertError {
+    fn from(e: sqlx::Error) -> Self {
+        Self::Database(e)
+    }
+}
+
+impl InsertError {
+    fn into_app_error(self, message: &str) -> AppError {
+        match self {
+            Self::Database(e) => AppError::with_source(ErrorKind::Database, message, e),
+            Self::Outbox(e) => e,
+        }
+    }
+}
+
+fn is_name_taken(e: &InsertError) -> bool {
+    matches!(e, InsertError::Database(sqlx::Error::Database(db_err)) if db_err.constraint() == Some("files_folder_id_name_key"))
+}
+
+/// Appends `AND ...` predicates for the search criteria.
+fn push_search_conditions(
+    qb: &mut QueryBuilder<'_, Postgres>,
+    criteria: &FileSearchCriteria,
+) -> AppResult<()> {
+    if let Some(tenant) = &criteria.tenant {
+        tenant.push_predicate(qb, "files.tenant_id");
+    }
+    if let Some(text) = criteria.text() {
+        qb.push(" AND (search_vector @@ websearch_to_tsquery('english', ")
+            .push_bind(text.to_string())
+            .push(")");
+        if !text.contains(char::is_whitespace) {
+            qb.push(" OR name ILIKE ")
+                .push_bind(format!("%{}%", escape_like(text)));
+        }
+        qb.push(")");
+    }
+    if let Some(folder_id) = criteria.folder_id {
+        qb.push(" AND folder_id = ").push_bind(folder_id);
+    }
+    if let Some(storage_id) = criteria.storage_id {
+        qb.push(" AND storage_id = ").push_bind(storage_id);
+    }
+    if let Some(prefix) = &criteria.mime_prefix {
+        qb.push(" AND mime_type LIKE ")
+            .push_bind(format!("{}%", escape_like(prefix)));
+    }
+    if let Some(owner_id) = criteria.owner_id {
+        qb.push(" AND owner_id = ").push_bind(owner_id);
+    }
+    if let Some(min) = criteria.min_size {
+        qb.push(" AND size_bytes >= ").push_bind(min);
+    }
+    if let Some(max) = criteria.max_size {
+        qb.push(" AND size_bytes <= ").push_bind(max);
+    }
+    if let Some(filter) = &criteria.filter {
+        qb.push(" AND ");
+        push_filter(qb, filter, criteria.tag_owner_id)?;
+    }
+    Ok(())
+}
+
+/// Filterable file fields: public name, column, and kind.
+///
+/// `tag` matches the normalized names of tags attached through `file_tags`.
+const FILE_FILTER_FIELDS: &[(&str, &str, FieldKind)] = &[
+    ("name", "name", FieldKind::Text),
+    ("type", "mime_type", FieldKind::Text),
+    ("size", "size_bytes", FieldKind::Number),
+    ("created_at", "created_at", FieldKind::Date),
+    ("updated_at", "updated_at", FieldKind::Date),
+    ("owner", "owner_id", FieldKind::Id),
+    ("folder", "folder_id", FieldKind::Id),
+    ("storage", "storage_id", FieldKind::Id),
+    ("tag", "tags", FieldKind::Set),
+];
+
+/// Returns the kind of a filterable file field, or `None` if the field
+/// cannot be filtered on. Pass to [`FilterNode::validate`].
+pub fn file_filter_kind(field: &str) -> Option<FieldKind> {
+    FILE_FILTER_FIELDS
+        .iter()
+        .find(|(name, _, _)| *name == field)
+        .map(|(_, _, kind)| *kind)
+}
+
+/// Appends a filter tree as a parenthesized predicate.
+///
+/// Column names come from [`FILE_FILTER_FIELDS`] only; every value is bound.
+fn push_filter(
+    qb: &mut QueryBuilder<'_, Postgres>,
+    node: &FilterNode,
+    tag_owner_id: Option<Uuid>,
+) -> AppResult<()> {
+    match node {
+        FilterNode::And { and: children } | FilterNode::Or { or: children } => {
+            let joiner = if matches!(node, FilterNode::And { .. }) {
+                " AND "
+            } else {
+                " OR "
+            };
+            qb.push("(");
+            for (i, child) in children.iter().enumerate() {
+                if i > 0 {
+                    qb.push(joiner);
+                }
+                push_filter(qb, child, tag_owner_id)?;
+            }
+            qb.push(")");
+            Ok(())
+        }
+        FilterNode::Condition(cond) => push_condition(qb, cond, tag_owner_id),
+    }
+}
+
+fn push_condition(
+    qb: &mut QueryBuilder<'_, Postgres>,
+    cond: &FilterField,
+    tag_owner_id: Option<Uuid>,
+) -> AppResult<()> {
+    let invalid = || AppError::validation(format!("Invalid filter on '{}'", cond.field));
+    let (_, column, kind) = FILE_FILTER_FIELDS
+        .iter()
+        .find(|(name, _, _)| *name == cond.field)
+        .ok_or_else(invalid)?;
+
+    if *kind == FieldKind::Set {
+        let normalize = |name: &str| Tag::normalize(name).ok_or_else(invalid);
+        let (negate, names) = match (cond.op, &cond.value) {
+            (FilterOp::Eq, FilterValue::String(v)) => (false, vec![normalize(v)?]),
+            (FilterOp::Ne, FilterValue::String(v)) => (true, vec![normalize(v)?]),
+            (FilterOp::In, FilterValue::StringList(v)) => (
+                false,
+                v.iter().map(|n| normalize(n)).collect::<AppResult<_>>()?,
+            ),
+            _ => return Err(invalid()),
+        };
+        qb.push(if negate { "(NOT EXISTS (" } else { "(EXISTS (" })
+            .push(
+                "SELECT 1 FROM file_tags ft JOIN tags t ON t.id = ft.tag_id \
+                 WHERE ft.file_id = files.id AND t.normalized_name = ANY(",
+            )
+            .push_bind(names)
+            .push(")");
+        if let Some(owner_id) = tag_owner_id {
+            qb.push(" AND t.owner_id = ").push_bind(owner_id);
+        }
+        qb.push("))");
+        return Ok(());
+    }
+
+    let comparison = match cond.op {
+        FilterOp::IsNull => {
+            qb.push(format!("({column} IS NULL)"));
+            return Ok(());
+        }
+        FilterOp::IsNotNull => {
+            qb.push(format!("({column} IS NOT NULL)"));
+            return Ok(());
+        }
+        FilterOp::In => {
+            qb.push(format!("({column} = ANY("));
+            match (kind, &cond.value) {
+                (FieldKind::Id, FilterValue::StringList(v)) => {
+                    let ids = v
+                        .iter()
+                        .map(|s| Uuid::parse_str(s).map_err(|_| invalid()))
+                        .collect::<AppResult<Vec<_>>>()?;
+                    qb.push_bind(ids);
+                }
+                (FieldKind::Text, FilterValue::StringList(v)) => {
+                    qb.push_bind(v.clone());
+                }
+                _ => return Err(invalid()),
+            }
+            qb.push("))");
+            return Ok(());
+        }
+        FilterOp::Eq => "=",
+        FilterOp::Ne => "IS DISTINCT FROM",
+        FilterOp::Gt => ">",
+        FilterOp::Gte => ">=",
+        FilterOp::Lt => "<",
+        FilterOp::Lte => "<=",
+        FilterOp::Like => "LIKE",
+        FilterOp::ILike => "ILIKE",
+    };
+
+    qb.push(format!("({column} {comparison} "));
+    match (kind, &cond.value) {
+        (FieldKind::Text, FilterValue::String(v)) => {
+            qb.push_bind(v.clone());
+        }
+        (FieldKind::Number, FilterValue::Integer(v)) => {
+            qb.push_bind(*v);
+        }
+        (FieldKind::Number, FilterValue::Float(v)) => {
+            qb.push_bind(*v);
+        }
+        (FieldKind::Date, value) => {
+            qb.push_bind(value.as_datetime().ok_or_else(invalid)?);
+        }
+        (FieldKind::Id, value) => {
+            qb.push_bind(value.as_uuid().ok_or_else(invalid)?);
+        }
+        _ => return Err(invalid()),
+    }
+    qb.push(")");
+    Ok(())
+}
+
+/// Escapes `LIKE` wildcards so user input matches literally.
+fn escape_like(input: &str) -> String {
+    input
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
@@ -4,20 +4,28 @@ use sqlx::PgPool;
 use uuid::Uuid;
 
 use filehub_core::error::{AppError, ErrorKind};
+use filehub_core::events::DomainEvent;
 use filehub_core::result::AppResult;
-use filehub_core::types::pagination::{PageRequest, PageResponse};
-use filehub_entity::folder::model::{CreateFolder, Folder};
+use filehub_core::types::pagination::{PageMode, PageRequest, PageResponse};
+use filehub_entity::folder::model::{CreateFolder, Folder, RebasedFolder};
+use filehub_entity::folder::preferences::FolderSort;
+use filehub_entity::folder::tree::FolderTreeRow;
+
+use crate::repositories::outbox::OutboxRepository;
+use crate::slow_query::TimedPool;
 
 /// Repository for folder CRUD and tree queries.
 #[derive(Debug, Clone)]
 pub struct FolderRepository {
-    pool: PgPool,
+    pool: TimedPool,
 }
 
 impl FolderRepository {
     /// Create a new folder repository.
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool: TimedPool::new(pool, "FolderRepository"),
+        }
     }
 
     /// Find a folder by ID.
@@ -52,12 +60,39 @@ impl FolderRepository {
         .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to list root folders", e))
     }
 
-    /// List direct children of a folder.
+    /// List direct children of a folder in `sort` order.
     pub async fn find_children(
         &self,
         parent_id: Uuid,
         page: &PageRequest,
+        sort: &FolderSort,
     ) -> AppResult<PageResponse<Folder>> {
+        let column = sort.folder_column();
+        if let PageMode::Cursor(after) = page.mode()? {
+            if let Some(cursor) = &after {
+                sort.check_cursor(column, cursor)?;
+            }
+            let (after_key, after_id) = after.map_or((None, None), |c| (Some(c.key), Some(c.id)));
+            let folders = sqlx::query_as::<_, Folder>(&format!(
+                "SELECT * FROM folders WHERE parent_id = $1 AND {} ORDER BY {} LIMIT $4",
+                sort.after(column, 2),
+                sort.order_by(column.0)
+            ))
+            .bind(parent_id)
+            .bind(after_key)
+            .bind(after_id)
+            .bind(page.limit() as i64 + 1)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to list children", e)
+            })?;
+
+            return Ok(PageResponse::from_keyset(folders, page, |f| {
+                sort.folder_cursor(f)
+            }));
+        }
+
         let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM folders WHERE parent_id = $1")
             .bind(parent_id)
             .fetch_one(&self.pool)
@@ -66,9 +101,10 @@ impl FolderRepository {
                 AppError::with_source(ErrorKind::Database, "Failed to count children", e)
             })?;
 
-        let folders = sqlx::query_as::<_, Folder>(
-            "SELECT * FROM folders WHERE parent_id = $1 ORDER BY name ASC LIMIT $2 OFFSET $3",
-        )
+        let folders = sqlx::query_as::<_, Folder>(&format!(
+            "SELECT * FROM folders WHERE parent_id = $1 ORDER BY {} LIMIT $2 OFFSET $3",
+            sort.order_by(column.0)
+        ))
         .bind(parent_id)
         .bind(page.limit() as i64)
         .bind(page.offset() as i64)
@@ -99,6 +135,45 @@ impl FolderRepository {
         .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to list descendants", e))
     }
 
+    /// The subtree rooted at a folder with child and file counts, in
+    /// breadth-first order, at most `limit` folders.
+    ///
+    /// The root comes first; a missing root gives no rows.
+    pub async fn find_subtree(&self, root_id: Uuid, limit: i64) -> AppResult<Vec<FolderTreeRow>> {
+        sqlx::query_as::<_, FolderTreeRow>(
+            "WITH RECURSIVE tree AS ( \
+                SELECT id, parent_id, name, path, depth FROM folders WHERE id = $1 \
+                UNION ALL \
+                SELECT f.id, f.parent_id, f.name, f.path, f.depth \
+                FROM folders f INNER JOIN tree t ON f.parent_id = t.id \
+             ) \
+             SELECT t.id, t.parent_id, t.name, t.path, t.depth, \
+                    (SELECT COUNT(*) FROM folders c WHERE c.parent_id = t.id) AS child_count, \
+                    (SELECT COUNT(*) FROM files fi WHERE fi.folder_id = t.id) AS file_count \
+             FROM tree t ORDER BY (t.id != $1), t.depth ASC, t.name ASC, t.id ASC LIMIT $2",
+        )
+        .bind(root_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to load subtree", e))
+    }
+
+    /// The children of the given folders with child and file counts, as
+    /// one level of a tree.
+    pub async fn find_tree_level(&self, parent_ids: &[Uuid]) -> AppResult<Vec<FolderTreeRow>> {
+        sqlx::query_as::<_, FolderTreeRow>(
+            "SELECT f.id, f.parent_id, f.name, f.path, f.depth, \
+                    (SELECT COUNT(*) FROM folders c WHERE c.parent_id = f.id) AS child_count, \
+                    (SELECT COUNT(*) FROM files fi WHERE fi.folder_id = f.id) AS file_count \
+             FROM folders f WHERE f.parent_id = ANY($1) ORDER BY f.name ASC, f.id ASC",
+        )
+        .bind(parent_ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to load tree level", e))
+    }
+
     /// Get the ancestor chain from a folder up to the root.
     pub async fn find_ancestors(&self, folder_id: Uuid) -> AppResult<Vec<Folder>> {
         sqlx::query_as::<_, Folder>(
@@ -106,7 +181,7 @@ impl FolderRepository {
                 SELECT * FROM folders WHERE id = $1 \
                 UNION ALL \
                 SELECT f.* FROM folders f INNER JOIN ancestors a ON f.id = a.parent_id \
-             ) SELECT * FROM ancestors ORDER BY depth ASC",
+             ) SELECT * FROM ancestors ORDER BY depth DESC",
         )
         .bind(folder_id)
         .fetch_all(&self.pool)
@@ -162,6 +237,45 @@ impl FolderRepository {
         .ok_or_else(|| AppError::not_found(format!("Folder {} not found", folder.id)))
     }
 
+    /// Attach a permission template to a folder, or detach it with `None`.
+    pub async fn set_permission_template(
+        &self,
+        folder_id: Uuid,
+        template_id: Option<Uuid>,
+    ) -> AppResult<Folder> {
+        sqlx::query_as::<_, Folder>(
+            "UPDATE folders SET permission_template_id = $2, updated_at = NOW() \
+             WHERE id = $1 RETURNING *",
+        )
+        .bind(folder_id)
+        .bind(template_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to set permission template", e)
+        })?
+        .ok_or_else(|| AppError::not_found(format!("Folder {folder_id} not found")))
+    }
+
+    /// Replace a folder's display metadata, or clear it with `None`.
+    pub async fn set_metadata(
+        &self,
+        folder_id: Uuid,
+        metadata: Option<&serde_json::Value>,
+    ) -> AppResult<Folder> {
+        sqlx::query_as::<_, Folder>(
+            "UPDATE folders SET metadata = $2, updated_at = NOW() WHERE id = $1 RETURNING *",
+        )
+        .bind(folder_id)
+        .bind(metadata)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to set folder metadata", e)
+        })?
+        .ok_or_else(|| AppError::not_found(format!("Folder {folder_id} not found")))
+    }
+
     /// Update paths of all descendant folders.
     pub async fn update_children_paths(
         &self,
@@ -227,6 +341,74 @@ impl FolderRepository {
         .ok_or_else(|| AppError::not_found(format!("Folder {folder_id} not found")))
     }
 
+    /// Move a folder and its subtree in one transaction: `root_id` gets
+    /// `new_parent_id` as its parent, and every folder in `rebased` (see
+    /// [`rebase_subtree`](filehub_entity::folder::rebase_subtree)) its new
+    /// path and depth, and `event` is recorded in the outbox. Returns the
+    /// moved root.
+    pub async fn move_subtree(
+        &self,
+        root_id: Uuid,
+        new_parent_id: Option<Uuid>,
+        rebased: &[RebasedFolder],
+        event: &DomainEvent,
+    ) -> AppResult<Folder> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to begin transaction", e)
+        })?;
+
+        sqlx::query("UPDATE folders SET parent_id = $2 WHERE id = $1")
+            .bind(root_id)
+            .bind(new_parent_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to move folder", e))?;
+
+        let ids: Vec<Uuid> = rebased.iter().map(|r| r.id).collect();
+        let paths: Vec<&str> = rebased.iter().map(|r| r.path.as_str()).collect();
+        let depths: Vec<i32> = rebased.iter().map(|r| r.depth).collect();
+        sqlx::query(
+            "UPDATE folders f SET path = u.path, depth = u.depth, updated_at = NOW() \
+             FROM UNNEST($1::uuid[], $2::text[], $3::int[]) AS u(id, path, depth) \
+             WHERE f.id = u.id",
+        )
+        .bind(&ids)
+        .bind(&paths)
+        .bind(&depths)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_err)
+                if db_err.constraint() == Some("folders_storage_id_path_key") =>
+            {
+                AppError::conflict("A folder with this name already exists in the destination")
+            }
+            _ => AppError::with_source(ErrorKind::Database, "Failed to rewrite folder paths", e),
+        })?;
+
+        let folder = sqlx::query_as::<_, Folder>("SELECT * FROM folders WHERE id = $1")
+            .bind(root_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to find folder", e))?
+            .ok_or_else(|| AppError::not_found(format!("Folder {root_id} not found")))?;
+        OutboxRepository::enqueue(&mut tx, std::slice::from_ref(event)).await?;
+
+        tx.commit().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to commit folder move", e)
+        })?;
+        Ok(folder)
+    }
+
+    /// IDs of the files directly in any of `folder_ids`.
+    pub async fn find_file_ids_in(&self, folder_ids: &[Uuid]) -> AppResult<Vec<Uuid>> {
+        sqlx::query_scalar("SELECT id FROM files WHERE folder_id = ANY($1)")
+            .bind(folder_ids)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to list file ids", e))
+    }
+
     /// Delete a folder (cascades to children and files).
     pub async fn delete(&self, folder_id: Uuid) -> AppResult<bool> {
         let result = sqlx::query("DELETE FROM folders WHERE id = $1")
@@ -0,0 +1,83 @@
+//! Folder preference repository implementation.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use filehub_core::error::{AppError, ErrorKind};
+use filehub_core::result::AppResult;
+use filehub_entity::folder::preferences::FolderPreferences;
+
+use crate::slow_query::TimedPool;
+
+/// Repository for users' saved folder views.
+#[derive(Debug, Clone)]
+pub struct FolderPreferenceRepository {
+    pool: TimedPool,
+}
+
+impl FolderPreferenceRepository {
+    /// Create a new folder preference repository.
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool: TimedPool::new(pool, "FolderPreferenceRepository"),
+        }
+    }
+
+    /// A user's preferences for a folder.
+    pub async fn find(
+        &self,
+        user_id: Uuid,
+        folder_id: Uuid,
+    ) -> AppResult<Option<FolderPreferences>> {
+        sqlx::query_as::<_, FolderPreferences>(
+            "SELECT * FROM folder_preferences WHERE user_id = $1 AND folder_id = $2",
+        )
+        .bind(user_id)
+        .bind(folder_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to get folder preferences", e)
+        })
+    }
+
+    /// Save a user's preferences for a folder.
+    pub async fn upsert(&self, preferences: &FolderPreferences) -> AppResult<FolderPreferences> {
+        sqlx::query_as::<_, FolderPreferences>(
+            "INSERT INTO folder_preferences \
+             (user_id, folder_id, sort_field, sort_direction, view_mode, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, NOW()) \
+             ON CONFLICT (user_id, folder_id) DO UPDATE SET \
+             sort_field = $3, sort_direction = $4, view_mode = $5, updated_at = NOW() \
+             RETURNING *",
+        )
+        .bind(preferences.user_id)
+        .bind(preferences.folder_id)
+        .bind(preferences.sort_field.as_str())
+        .bind(preferences.sort_direction.as_str())
+        .bind(preferences.view_mode.as_str())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to save folder preferences", e)
+        })
+    }
+
+    /// Forget a user's preferences for a folder. Returns whether there
+    /// were any.
+    pub async fn delete(&self, user_id: Uuid, folder_id: Uuid) -> AppResult<bool> {
+        sqlx::query("DELETE FROM folder_preferences WHERE user_id = $1 AND folder_id = $2")
+            .bind(user_id)
+            .bind(folder_id)
+            .execute(&self.pool)
+            .await
+            .map(|r| r.rows_affected() > 0)
+            .map_err(|e| {
+                AppError::with_source(
+                    ErrorKind::Database,
+                    "Failed to delete folder preferences",
+                    e,
+                )
+            })
+    }
+}
@@ -10,16 +10,20 @@ use filehub_core::types::pagination::{PageRequest, PageResponse};
 use filehub_entity::job::model::{CreateJob, Job};
 use filehub_entity::job::status::JobStatus;
 
+use crate::slow_query::TimedPool;
+
 /// Repository for background job CRUD and queue operations.
 #[derive(Debug, Clone)]
 pub struct JobRepository {
-    pool: PgPool,
+    pool: TimedPool,
 }
 
 impl JobRepository {
     /// Create a new job repository.
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool: TimedPool::new(pool, "JobRepository"),
+        }
     }
 
     /// Find a job by ID.
@@ -141,6 +145,21 @@ impl JobRepository {
         Ok(())
     }
 
+    /// Put the jobs a worker is still running back in the queue, undoing
+    /// their attempt, so another worker picks them up.
+    pub async fn requeue_running(&self, worker_id: &str) -> AppResult<u64> {
+        let result = sqlx::query(
+            "UPDATE jobs SET status = 'pending', started_at = NULL, worker_id = NULL, \
+             attempts = GREATEST(COALESCE(attempts, 1) - 1, 0), updated_at = NOW() \
+             WHERE worker_id = $1 AND status = 'running'",
+        )
+        .bind(worker_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to requeue jobs", e))?;
+        Ok(result.rows_affected())
+    }
+
     /// Cancel a job.
     pub async fn cancel(&self, job_id: Uuid) -> AppResult<()> {
         sqlx::query(
@@ -176,4 +195,18 @@ impl JobRepository {
             })?;
         Ok(count)
     }
+
+    /// When the oldest pending job that is already due became due, if
+    /// any; a worker that keeps up leaves none waiting for long.
+    pub async fn oldest_due_pending(&self) -> AppResult<Option<DateTime<Utc>>> {
+        sqlx::query_scalar(
+            "SELECT MIN(COALESCE(scheduled_at, created_at)) FROM jobs \
+             WHERE status = 'pending' AND (scheduled_at IS NULL OR scheduled_at <= NOW())",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to find oldest pending job", e)
+        })
+    }
 }
@@ -1,5 +1,6 @@
 //! License checkout repository implementation.
 
+use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -7,16 +8,20 @@ use filehub_core::error::{AppError, ErrorKind};
 use filehub_core::result::AppResult;
 use filehub_entity::license::model::LicenseCheckout;
 
+use crate::slow_query::TimedPool;
+
 /// Repository for license checkout records.
 #[derive(Debug, Clone)]
 pub struct LicenseCheckoutRepository {
-    pool: PgPool,
+    pool: TimedPool,
 }
 
 impl LicenseCheckoutRepository {
     /// Create a new license checkout repository.
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool: TimedPool::new(pool, "LicenseCheckoutRepository"),
+        }
     }
 
     /// Find a checkout by ID.
@@ -95,6 +100,143 @@ impl LicenseCheckoutRepository {
             .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to create checkout", e))
     }
 
+    /// Create a borrow record. Borrows are not tied to a session.
+    pub async fn create_borrow(
+        &self,
+        user_id: Uuid,
+        feature_name: &str,
+        checkout_token: &str,
+        borrowed_until: DateTime<Utc>,
+        ip_address: Option<&str>,
+    ) -> AppResult<LicenseCheckout> {
+        sqlx::query_as::<_, LicenseCheckout>(
+            "INSERT INTO license_checkouts \
+             (user_id, feature_name, checkout_token, borrowed_until, ip_address) \
+             VALUES ($1, $2, $3, $4, $5::INET) RETURNING *",
+        )
+        .bind(user_id)
+        .bind(feature_name)
+        .bind(checkout_token)
+        .bind(borrowed_until)
+        .bind(ip_address)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to create borrow", e))
+    }
+
+    /// Find a user's active borrows.
+    pub async fn find_active_borrows_by_user(
+        &self,
+        user_id: Uuid,
+    ) -> AppResult<Vec<LicenseCheckout>> {
+        sqlx::query_as::<_, LicenseCheckout>(
+            "SELECT * FROM license_checkouts \
+             WHERE user_id = $1 AND is_active = TRUE AND borrowed_until IS NOT NULL \
+             ORDER BY borrowed_until",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to find user borrows", e))
+    }
+
+    /// Find active borrows whose window ended at or before `now`.
+    pub async fn find_expired_borrows(
+        &self,
+        now: DateTime<Utc>,
+    ) -> AppResult<Vec<LicenseCheckout>> {
+        sqlx::query_as::<_, LicenseCheckout>(
+            "SELECT * FROM license_checkouts \
+             WHERE is_active = TRUE AND borrowed_until <= $1",
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to find expired borrows", e)
+        })
+    }
+
+    /// Find active session checkouts (excluding borrows), for lease renewal.
+    pub async fn find_active_leases(&self) -> AppResult<Vec<LicenseCheckout>> {
+        sqlx::query_as::<_, LicenseCheckout>(
+            "SELECT * FROM license_checkouts \
+             WHERE is_active = TRUE AND borrowed_until IS NULL AND session_id IS NOT NULL",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to find active leases", e))
+    }
+
+    /// Record a successful lease renewal.
+    pub async fn mark_renewed(&self, checkout_ids: &[Uuid]) -> AppResult<()> {
+        sqlx::query("UPDATE license_checkouts SET renewed_at = NOW() WHERE id = ANY($1)")
+            .bind(checkout_ids)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to mark leases renewed", e)
+            })?;
+        Ok(())
+    }
+
+    /// Mark a checkout's seat as lost after a failed renewal.
+    ///
+    /// Closes the checkout and clears the session's seat allocation so seat
+    /// reconciliation stops counting it.
+    pub async fn mark_lost(&self, checkout_id: Uuid) -> AppResult<()> {
+        sqlx::query(
+            "WITH lost AS ( \
+                 UPDATE license_checkouts \
+                 SET is_active = FALSE, checked_in_at = NOW(), lost_at = NOW() \
+                 WHERE id = $1 AND is_active = TRUE \
+                 RETURNING session_id \
+             ) \
+             UPDATE sessions SET seat_allocated_at = NULL \
+             WHERE id IN (SELECT session_id FROM lost)",
+        )
+        .bind(checkout_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to mark seat lost", e))?;
+        Ok(())
+    }
+
+    /// Count active borrows.
+    pub async fn count_active_borrows(&self) -> AppResult<u32> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM license_checkouts \
+             WHERE is_active = TRUE AND borrowed_until IS NOT NULL",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to count active borrows", e)
+        })?;
+        Ok(count as u32)
+    }
+
+    /// Count active checkouts per feature as `(feature, active, borrowed)`.
+    pub async fn count_active_by_feature(&self) -> AppResult<Vec<(String, u32, u32)>> {
+        let rows: Vec<(String, i64, i64)> = sqlx::query_as(
+            "SELECT feature_name, COUNT(*), COUNT(borrowed_until) FROM license_checkouts \
+             WHERE is_active = TRUE GROUP BY feature_name",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(
+                ErrorKind::Database,
+                "Failed to count active checkouts by feature",
+                e,
+            )
+        })?;
+        Ok(rows
+            .into_iter()
+            .map(|(feature, active, borrowed)| (feature, active as u32, borrowed as u32))
+            .collect())
+    }
+
     /// Check in a license (set is_active = false).
     pub async fn checkin(&self, checkout_id: Uuid) -> AppResult<()> {
         sqlx::query(
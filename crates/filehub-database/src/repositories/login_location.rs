@@ -0,0 +1,65 @@
+//! Login location repository implementation.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use filehub_core::error::{AppError, ErrorKind};
+use filehub_core::result::AppResult;
+use filehub_entity::session::{CreateLoginLocation, LoginLocation};
+
+use crate::slow_query::TimedPool;
+
+/// Repository for the per-user login location history.
+#[derive(Debug, Clone)]
+pub struct LoginLocationRepository {
+    pool: TimedPool,
+}
+
+impl LoginLocationRepository {
+    /// Create a new login location repository.
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool: TimedPool::new(pool, "LoginLocationRepository"),
+        }
+    }
+
+    /// The user's most recent login locations, newest first.
+    pub async fn find_recent_by_user(
+        &self,
+        user_id: Uuid,
+        limit: i64,
+    ) -> AppResult<Vec<LoginLocation>> {
+        sqlx::query_as::<_, LoginLocation>(
+            "SELECT * FROM login_locations WHERE user_id = $1 \
+             ORDER BY created_at DESC LIMIT $2",
+        )
+        .bind(user_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to find login locations", e)
+        })
+    }
+
+    /// Record a login location.
+    pub async fn create(&self, data: &CreateLoginLocation) -> AppResult<LoginLocation> {
+        sqlx::query_as::<_, LoginLocation>(
+            "INSERT INTO login_locations \
+             (user_id, session_id, ip_address, country_code, region, latitude, longitude) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+        )
+        .bind(data.user_id)
+        .bind(data.session_id)
+        .bind(data.ip_address)
+        .bind(&data.country_code)
+        .bind(&data.region)
+        .bind(data.latitude)
+        .bind(data.longitude)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to record login location", e)
+        })
+    }
+}
@@ -1,29 +1,51 @@
 //! Repository implementations for all FileHub entities.
 
+pub mod access_request;
+pub mod api_usage;
 pub mod audit;
 pub mod file;
 pub mod folder;
+pub mod folder_preference;
 pub mod job;
 pub mod license;
+pub mod login_location;
 pub mod notification;
+pub mod outbox;
 pub mod permission;
+pub mod permission_template;
 pub mod pool_snapshot;
+pub mod public_resource;
+pub mod retention;
+pub mod saved_search;
 pub mod session;
 pub mod session_limit;
 pub mod share;
 pub mod storage;
+pub mod storage_migration;
+pub mod tag;
 pub mod user;
 
+pub use access_request::AccessRequestRepository;
+pub use api_usage::{ApiUsageRepository, UsageTotals};
 pub use audit::AuditLogRepository;
 pub use file::FileRepository;
 pub use folder::FolderRepository;
+pub use folder_preference::FolderPreferenceRepository;
 pub use job::JobRepository;
 pub use license::LicenseCheckoutRepository;
+pub use login_location::LoginLocationRepository;
 pub use notification::NotificationRepository;
+pub use outbox::{OUTBOX_CHANNEL, OutboxBacklog, OutboxEntry, OutboxListener, OutboxRepository};
 pub use permission::AclRepository;
+pub use permission_template::PermissionTemplateRepository;
 pub use pool_snapshot::PoolSnapshotRepository;
+pub use public_resource::PublicResourceRepository;
+pub use retention::RetentionPolicyRepository;
+pub use saved_search::SavedSearchRepository;
 pub use session::SessionRepository;
 pub use session_limit::SessionLimitRepository;
 pub use share::ShareRepository;
 pub use storage::StorageRepository;
+pub use storage_migration::{MigrationBatchProgress, StorageMigrationRepository};
+pub use tag::TagRepository;
 pub use user::UserRepository;
@@ -1,24 +1,32 @@
 //! Notification repository implementation.
 
+use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use filehub_core::error::{AppError, ErrorKind};
 use filehub_core::result::AppResult;
-use filehub_core::types::pagination::{PageRequest, PageResponse};
+use filehub_core::types::cursor::PageCursor;
+use filehub_core::types::pagination::{PageMode, PageRequest, PageResponse};
+use filehub_entity::notification::broadcast::{BroadcastReceipt, BroadcastTarget};
+use filehub_entity::notification::digest::NotificationDigest;
 use filehub_entity::notification::model::{AdminBroadcast, Notification};
 use filehub_entity::notification::preference::NotificationPreference;
 
+use crate::slow_query::TimedPool;
+
 /// Repository for notification CRUD operations.
 #[derive(Debug, Clone)]
 pub struct NotificationRepository {
-    pool: PgPool,
+    pool: TimedPool,
 }
 
 impl NotificationRepository {
     /// Create a new notification repository.
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool: TimedPool::new(pool, "NotificationRepository"),
+        }
     }
 
     /// List notifications for a user.
@@ -27,6 +35,34 @@ impl NotificationRepository {
         user_id: Uuid,
         page: &PageRequest,
     ) -> AppResult<PageResponse<Notification>> {
+        if let PageMode::Cursor(after) = page.mode()? {
+            let (before_time, before_id) = match after {
+                Some(c) => {
+                    let time = DateTime::parse_from_rfc3339(&c.key)
+                        .map_err(|_| AppError::validation("Invalid pagination cursor"))?
+                        .with_timezone(&Utc);
+                    (Some(time), Some(c.id))
+                }
+                None => (None, None),
+            };
+            let notifs = sqlx::query_as::<_, Notification>(
+                "SELECT * FROM notifications WHERE user_id = $1 AND (is_dismissed IS NULL OR is_dismissed = FALSE) \
+                 AND ($2::timestamptz IS NULL OR (created_at, id) < ($2, $3)) \
+                 ORDER BY created_at DESC, id DESC LIMIT $4"
+            )
+                .bind(user_id)
+                .bind(before_time)
+                .bind(before_id)
+                .bind(page.limit() as i64 + 1)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to list notifications", e))?;
+
+            return Ok(PageResponse::from_keyset(notifs, page, |n| {
+                PageCursor::new(n.created_at.to_rfc3339(), n.id)
+            }));
+        }
+
         let total: i64 = sqlx::query_scalar(
             "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND (is_dismissed IS NULL OR is_dismissed = FALSE)"
         )
@@ -37,7 +73,7 @@ impl NotificationRepository {
 
         let notifs = sqlx::query_as::<_, Notification>(
             "SELECT * FROM notifications WHERE user_id = $1 AND (is_dismissed IS NULL OR is_dismissed = FALSE) \
-             ORDER BY created_at DESC LIMIT $2 OFFSET $3"
+             ORDER BY created_at DESC, id DESC LIMIT $2 OFFSET $3"
         )
             .bind(user_id)
             .bind(page.limit() as i64)
@@ -169,6 +205,89 @@ impl NotificationRepository {
         .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to upsert preferences", e))
     }
 
+    /// Count a notification into the recipient's digest for its event type
+    /// and scope, opening the digest with `flush_at` if none is pending.
+    pub async fn add_to_digest(
+        &self,
+        notification: &Notification,
+        scope_type: &str,
+        scope_id: Uuid,
+        scope_name: Option<&str>,
+        flush_at: DateTime<Utc>,
+    ) -> AppResult<NotificationDigest> {
+        sqlx::query_as::<_, NotificationDigest>(
+            "INSERT INTO notification_digests \
+             (user_id, category, event_type, scope_type, scope_id, scope_name, last_title, last_message, last_actor_id, flush_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) \
+             ON CONFLICT (user_id, event_type, scope_type, scope_id) DO UPDATE SET \
+                item_count = notification_digests.item_count + 1, \
+                scope_name = COALESCE(EXCLUDED.scope_name, notification_digests.scope_name), \
+                last_title = EXCLUDED.last_title, \
+                last_message = EXCLUDED.last_message, \
+                last_actor_id = EXCLUDED.last_actor_id, \
+                last_at = NOW() \
+             RETURNING *",
+        )
+        .bind(notification.user_id)
+        .bind(&notification.category)
+        .bind(&notification.event_type)
+        .bind(scope_type)
+        .bind(scope_id)
+        .bind(scope_name)
+        .bind(&notification.title)
+        .bind(&notification.message)
+        .bind(notification.actor_id)
+        .bind(flush_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to add to digest", e))
+    }
+
+    /// Replace every digest due at `now` with one stored notification.
+    ///
+    /// Runs in one transaction, so a digest is either delivered or kept.
+    pub async fn flush_due_digests(&self, now: DateTime<Utc>) -> AppResult<Vec<Notification>> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to begin transaction", e)
+        })?;
+
+        let due = sqlx::query_as::<_, NotificationDigest>(
+            "DELETE FROM notification_digests WHERE flush_at <= $1 RETURNING *",
+        )
+        .bind(now)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to take due digests", e))?;
+
+        let mut delivered = Vec::with_capacity(due.len());
+        for digest in &due {
+            let notification = sqlx::query_as::<_, Notification>(
+                "INSERT INTO notifications (user_id, category, event_type, title, message, payload, priority, actor_id, resource_type, resource_id) \
+                 VALUES ($1, $2, $3, $4, $5, $6, 'normal', $7, $8, $9) RETURNING *",
+            )
+            .bind(digest.user_id)
+            .bind(&digest.category)
+            .bind(&digest.event_type)
+            .bind(digest.title())
+            .bind(digest.message())
+            .bind(digest.payload())
+            .bind(digest.last_actor_id)
+            .bind(&digest.scope_type)
+            .bind(digest.scope_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to store digest notification", e)
+            })?;
+            delivered.push(notification);
+        }
+
+        tx.commit().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to commit digests", e)
+        })?;
+        Ok(delivered)
+    }
+
     /// Clean up old notifications.
     pub async fn cleanup_old(&self, before: chrono::DateTime<chrono::Utc>) -> AppResult<u64> {
         let result = sqlx::query("DELETE FROM notifications WHERE created_at < $1")
@@ -217,12 +336,13 @@ impl NotificationRepository {
     /// Create a broadcast message.
     pub async fn create_broadcast(&self, broadcast: &AdminBroadcast) -> AppResult<AdminBroadcast> {
         sqlx::query_as::<_, AdminBroadcast>(
-            "INSERT INTO admin_broadcasts (id, admin_id, target, title, message, severity, persistent, action_type, action_payload, delivered_count, created_at) \
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) RETURNING *"
+            "INSERT INTO admin_broadcasts (id, admin_id, target, target_user_ids, title, message, severity, persistent, action_type, action_payload, delivered_count, require_ack, scheduled_at, sent_at, expires_at, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16) RETURNING *"
         )
         .bind(broadcast.id)
         .bind(broadcast.admin_id)
         .bind(&broadcast.target)
+        .bind(&broadcast.target_user_ids)
         .bind(&broadcast.title)
         .bind(&broadcast.message)
         .bind(&broadcast.severity)
@@ -230,12 +350,177 @@ impl NotificationRepository {
         .bind(&broadcast.action_type)
         .bind(&broadcast.action_payload)
         .bind(broadcast.delivered_count)
+        .bind(broadcast.require_ack)
+        .bind(broadcast.scheduled_at)
+        .bind(broadcast.sent_at)
+        .bind(broadcast.expires_at)
         .bind(broadcast.created_at)
         .fetch_one(&self.pool)
         .await
         .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to create broadcast", e))
     }
 
+    /// Find a broadcast by ID.
+    pub async fn find_broadcast(&self, id: Uuid) -> AppResult<Option<AdminBroadcast>> {
+        sqlx::query_as::<_, AdminBroadcast>("SELECT * FROM admin_broadcasts WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to find broadcast", e))
+    }
+
+    /// Mark a scheduled broadcast as sent, keeping it pending until
+    /// `expires_at`. Returns `None` if it was already sent, so concurrent
+    /// workers send it once.
+    pub async fn mark_broadcast_sent(
+        &self,
+        id: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<Option<AdminBroadcast>> {
+        sqlx::query_as::<_, AdminBroadcast>(
+            "UPDATE admin_broadcasts SET sent_at = NOW(), expires_at = $2 \
+             WHERE id = $1 AND sent_at IS NULL RETURNING *",
+        )
+        .bind(id)
+        .bind(expires_at)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to mark broadcast sent", e))
+    }
+
+    /// Sent, unexpired broadcasts a user is in the audience of and still
+    /// has to see: never delivered to them, or awaiting their
+    /// acknowledgement. Oldest first.
+    pub async fn find_pending_broadcasts(
+        &self,
+        user_id: Uuid,
+        role: &str,
+    ) -> AppResult<Vec<AdminBroadcast>> {
+        sqlx::query_as::<_, AdminBroadcast>(
+            "SELECT b.* FROM admin_broadcasts b \
+             LEFT JOIN broadcast_receipts r ON r.broadcast_id = b.id AND r.user_id = $1 \
+             WHERE b.sent_at IS NOT NULL AND b.expires_at > NOW() \
+               AND (b.target = 'all' OR b.target = 'role:' || $2 \
+                    OR (b.target = 'users' AND $1 = ANY(b.target_user_ids))) \
+               AND (r.user_id IS NULL OR (b.require_ack AND r.acknowledged_at IS NULL)) \
+             ORDER BY b.sent_at",
+        )
+        .bind(user_id)
+        .bind(role)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to find pending broadcasts", e)
+        })
+    }
+
+    /// Record that a broadcast reached these users, and refresh its
+    /// delivered count.
+    pub async fn record_broadcast_deliveries(
+        &self,
+        broadcast_id: Uuid,
+        user_ids: &[Uuid],
+    ) -> AppResult<()> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to begin transaction", e)
+        })?;
+
+        sqlx::query(
+            "INSERT INTO broadcast_receipts (broadcast_id, user_id) \
+             SELECT $1, UNNEST($2::uuid[]) ON CONFLICT DO NOTHING",
+        )
+        .bind(broadcast_id)
+        .bind(user_ids)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            AppError::with_source(
+                ErrorKind::Database,
+                "Failed to record broadcast delivery",
+                e,
+            )
+        })?;
+
+        sqlx::query(
+            "UPDATE admin_broadcasts SET delivered_count = \
+             (SELECT COUNT(*) FROM broadcast_receipts WHERE broadcast_id = $1) WHERE id = $1",
+        )
+        .bind(broadcast_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            AppError::with_source(
+                ErrorKind::Database,
+                "Failed to count broadcast deliveries",
+                e,
+            )
+        })?;
+
+        tx.commit().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to commit transaction", e)
+        })
+    }
+
+    /// Record a user's acknowledgement of a broadcast. Returns `false` if
+    /// the broadcast does not exist or does not require acknowledgement.
+    pub async fn acknowledge_broadcast(
+        &self,
+        broadcast_id: Uuid,
+        user_id: Uuid,
+    ) -> AppResult<bool> {
+        let result = sqlx::query(
+            "INSERT INTO broadcast_receipts (broadcast_id, user_id, acknowledged_at) \
+             SELECT id, $2, NOW() FROM admin_broadcasts WHERE id = $1 AND require_ack \
+             ON CONFLICT (broadcast_id, user_id) DO UPDATE \
+             SET acknowledged_at = COALESCE(broadcast_receipts.acknowledged_at, NOW())",
+        )
+        .bind(broadcast_id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to acknowledge broadcast", e)
+        })?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Delivery receipts of a broadcast.
+    pub async fn find_broadcast_receipts(
+        &self,
+        broadcast_id: Uuid,
+    ) -> AppResult<Vec<BroadcastReceipt>> {
+        sqlx::query_as::<_, BroadcastReceipt>(
+            "SELECT * FROM broadcast_receipts WHERE broadcast_id = $1 ORDER BY delivered_at",
+        )
+        .bind(broadcast_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to find broadcast receipts", e)
+        })
+    }
+
+    /// Active users in a broadcast's audience.
+    pub async fn find_broadcast_audience(&self, target: &BroadcastTarget) -> AppResult<Vec<Uuid>> {
+        let role = match target {
+            BroadcastTarget::Role(role) => Some(role.as_str()),
+            _ => None,
+        };
+        sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM users WHERE status = 'active' \
+               AND ($1::text IS NULL OR role::text = $1) \
+               AND ($2::uuid[] IS NULL OR id = ANY($2)) \
+             ORDER BY username",
+        )
+        .bind(role)
+        .bind(target.user_ids())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to find broadcast audience", e)
+        })
+    }
+
     /// Find recent broadcasts.
     pub async fn find_broadcasts(&self, limit: i64) -> AppResult<Vec<AdminBroadcast>> {
         sqlx::query_as::<_, AdminBroadcast>(
@@ -0,0 +1,193 @@
+//! Domain event outbox repository implementation.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sqlx::postgres::PgListener;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use filehub_core::error::{AppError, ErrorKind};
+use filehub_core::events::DomainEvent;
+use filehub_core::result::AppResult;
+
+use crate::slow_query::TimedPool;
+
+/// Channel notified when events are recorded, on commit.
+pub const OUTBOX_CHANNEL: &str = "filehub_outbox";
+
+/// An undelivered event claimed by a relay.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OutboxEntry {
+    /// The event's ID.
+    pub id: Uuid,
+    /// The serialized [`DomainEvent`].
+    pub event: serde_json::Value,
+    /// Times the event has been claimed, this time included.
+    pub attempts: i32,
+    /// When the event was recorded.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Events waiting to be delivered.
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+pub struct OutboxBacklog {
+    /// Undelivered events.
+    pub pending: i64,
+    /// When the oldest of them was recorded.
+    pub oldest: Option<DateTime<Utc>>,
+}
+
+/// Wakes a relay when events are recorded.
+#[derive(Debug)]
+pub struct OutboxListener {
+    listener: PgListener,
+}
+
+impl OutboxListener {
+    /// Wait until a transaction recording events commits.
+    pub async fn notified(&mut self) -> AppResult<()> {
+        self.listener.recv().await.map(|_| ()).map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Outbox notifications failed", e)
+        })
+    }
+}
+
+/// Repository for the transactional event outbox.
+#[derive(Debug, Clone)]
+pub struct OutboxRepository {
+    pool: TimedPool,
+}
+
+impl OutboxRepository {
+    /// Create a new outbox repository.
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool: TimedPool::new(pool, "OutboxRepository"),
+        }
+    }
+
+    /// Record `events` on `conn`, inside the transaction of the change
+    /// they describe, and wake the relays once it commits.
+    pub async fn enqueue(conn: &mut PgConnection, events: &[DomainEvent]) -> AppResult<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<Uuid> = events.iter().map(|e| e.id).collect();
+        let bodies = events
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::internal(format!("Failed to serialize event: {e}")))?;
+        let timestamps: Vec<DateTime<Utc>> = events.iter().map(|e| e.timestamp).collect();
+
+        sqlx::query(
+            "INSERT INTO outbox (id, event, created_at) \
+             SELECT * FROM UNNEST($1::uuid[], $2::jsonb[], $3::timestamptz[]) \
+             ON CONFLICT (id) DO NOTHING",
+        )
+        .bind(&ids)
+        .bind(&bodies)
+        .bind(&timestamps)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to record events", e))?;
+
+        sqlx::query("SELECT pg_notify($1, '')")
+            .bind(OUTBOX_CHANNEL)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to notify outbox relays", e)
+            })?;
+        Ok(())
+    }
+
+    /// Listen for newly recorded events on a dedicated connection.
+    pub async fn listen(&self) -> AppResult<OutboxListener> {
+        let mut listener = PgListener::connect_with(&self.pool).await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to connect outbox listener", e)
+        })?;
+        listener.listen(OUTBOX_CHANNEL).await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to listen for outbox events", e)
+        })?;
+        Ok(OutboxListener { listener })
+    }
+
+    /// Lease up to `limit` undelivered events, oldest first, for `lease`.
+    /// Events leased to another relay are skipped until their lease ends.
+    pub async fn claim(&self, limit: i64, lease: Duration) -> AppResult<Vec<OutboxEntry>> {
+        let mut entries = sqlx::query_as::<_, OutboxEntry>(
+            "UPDATE outbox SET attempts = attempts + 1, \
+             locked_until = NOW() + make_interval(secs => $2) \
+             WHERE id IN ( \
+                 SELECT id FROM outbox \
+                 WHERE delivered_at IS NULL AND (locked_until IS NULL OR locked_until < NOW()) \
+                 ORDER BY created_at LIMIT $1 FOR UPDATE SKIP LOCKED \
+             ) \
+             RETURNING id, event, attempts, created_at",
+        )
+        .bind(limit)
+        .bind(lease.as_secs_f64())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to claim events", e))?;
+        entries.sort_by_key(|entry| entry.created_at);
+        Ok(entries)
+    }
+
+    /// Mark events delivered.
+    pub async fn mark_delivered(&self, ids: &[Uuid]) -> AppResult<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        sqlx::query(
+            "UPDATE outbox SET delivered_at = NOW(), locked_until = NULL WHERE id = ANY($1)",
+        )
+        .bind(ids)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to mark events delivered", e)
+        })?;
+        Ok(())
+    }
+
+    /// Give up on an event that can never be delivered, keeping the
+    /// reason; it is cleaned up like a delivered one.
+    pub async fn discard(&self, id: Uuid, reason: &str) -> AppResult<()> {
+        sqlx::query(
+            "UPDATE outbox SET delivered_at = NOW(), locked_until = NULL, last_error = $2 \
+             WHERE id = $1",
+        )
+        .bind(id)
+        .bind(reason)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to discard event", e))?;
+        Ok(())
+    }
+
+    /// Delete events delivered before `before`.
+    pub async fn delete_delivered_before(&self, before: DateTime<Utc>) -> AppResult<u64> {
+        let result = sqlx::query("DELETE FROM outbox WHERE delivered_at < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to delete delivered events", e)
+            })?;
+        Ok(result.rows_affected())
+    }
+
+    /// Count undelivered events and find the oldest.
+    pub async fn backlog(&self) -> AppResult<OutboxBacklog> {
+        sqlx::query_as::<_, OutboxBacklog>(
+            "SELECT COUNT(*) AS pending, MIN(created_at) AS oldest \
+             FROM outbox WHERE delivered_at IS NULL",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to read outbox backlog", e))
+    }
+}
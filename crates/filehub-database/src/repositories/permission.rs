@@ -8,16 +8,20 @@ use filehub_core::result::AppResult;
 use filehub_entity::permission::acl::AclPermission;
 use filehub_entity::permission::model::{AclEntry, ResourceType};
 
+use crate::slow_query::TimedPool;
+
 /// Repository for ACL entry CRUD and permission resolution.
 #[derive(Debug, Clone)]
 pub struct AclRepository {
-    pool: PgPool,
+    pool: TimedPool,
 }
 
 impl AclRepository {
     /// Create a new ACL repository.
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool: TimedPool::new(pool, "AclRepository"),
+        }
     }
 
     /// Find an ACL entry by ID.
@@ -259,4 +263,107 @@ impl AclRepository {
             AppError::with_source(ErrorKind::Database, "Failed to find public ACL entries", e)
         })
     }
+
+    /// Of `folder_ids`, those with an active blocking entry that applies to
+    /// the principal: the user's own or a public one. `None` stands for the
+    /// public principal.
+    pub async fn find_blocking(
+        &self,
+        folder_ids: &[Uuid],
+        user_id: Option<Uuid>,
+    ) -> AppResult<Vec<Uuid>> {
+        sqlx::query_scalar::<_, Uuid>(
+            "SELECT DISTINCT resource_id FROM acl_entries \
+             WHERE resource_type = 'folder' AND resource_id = ANY($1) \
+             AND inheritance = 'block' \
+             AND (is_anyone = TRUE OR ($2::uuid IS NOT NULL AND user_id = $2)) \
+             AND (expires_at IS NULL OR expires_at > NOW())",
+        )
+        .bind(folder_ids)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(
+                ErrorKind::Database,
+                "Failed to find blocking ACL entries",
+                e,
+            )
+        })
+    }
+
+    /// Creates one entry for the principal on each of `folder_ids` and
+    /// `file_ids`, in one transaction. Resources where the principal
+    /// already has an entry are left alone. Returns the number created.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_batch(
+        &self,
+        folder_ids: &[Uuid],
+        file_ids: &[Uuid],
+        user_id: Option<Uuid>,
+        is_anyone: bool,
+        permission: AclPermission,
+        inheritance: filehub_entity::permission::acl::AclInheritance,
+        granted_by: Uuid,
+        expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> AppResult<u64> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to begin transaction", e)
+        })?;
+
+        let mut created = 0;
+        for (resource_type, ids) in [
+            (ResourceType::Folder, folder_ids),
+            (ResourceType::File, file_ids),
+        ] {
+            if ids.is_empty() {
+                continue;
+            }
+            created += sqlx::query(
+                "INSERT INTO acl_entries (resource_type, resource_id, user_id, is_anyone, permission, inheritance, granted_by, expires_at) \
+                 SELECT $1, r.id, $3, $4, $5, $6, $7, $8 FROM UNNEST($2::uuid[]) AS r(id) \
+                 WHERE NOT EXISTS ( \
+                     SELECT 1 FROM acl_entries a \
+                     WHERE a.resource_type = $1 AND a.resource_id = r.id \
+                     AND (($3::uuid IS NOT NULL AND a.user_id = $3) OR ($3 IS NULL AND a.is_anyone)) \
+                 )",
+            )
+            .bind(resource_type)
+            .bind(ids)
+            .bind(user_id)
+            .bind(is_anyone)
+            .bind(permission)
+            .bind(inheritance)
+            .bind(granted_by)
+            .bind(expires_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to create ACL entries", e)
+            })?
+            .rows_affected();
+        }
+
+        tx.commit().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to commit ACL entries", e)
+        })?;
+        Ok(created)
+    }
+
+    /// Number of distinct principals with an active entry on any of
+    /// `folder_ids` or `file_ids`; the public principal counts as one.
+    pub async fn count_principals(&self, folder_ids: &[Uuid], file_ids: &[Uuid]) -> AppResult<i64> {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(DISTINCT COALESCE(user_id, '00000000-0000-0000-0000-000000000000'::uuid)) \
+             FROM acl_entries \
+             WHERE ((resource_type = 'folder' AND resource_id = ANY($1)) \
+                 OR (resource_type = 'file' AND resource_id = ANY($2))) \
+             AND (expires_at IS NULL OR expires_at > NOW())",
+        )
+        .bind(folder_ids)
+        .bind(file_ids)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to count ACL principals", e))
+    }
 }
@@ -0,0 +1,140 @@
+//! Permission template repository implementation.
+
+use sqlx::PgPool;
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use filehub_core::error::{AppError, ErrorKind};
+use filehub_core::result::AppResult;
+use filehub_entity::permission::{PermissionTemplate, TemplateEntry};
+
+use crate::slow_query::TimedPool;
+
+/// Repository for permission templates.
+#[derive(Debug, Clone)]
+pub struct PermissionTemplateRepository {
+    pool: TimedPool,
+}
+
+impl PermissionTemplateRepository {
+    /// Create a new permission template repository.
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool: TimedPool::new(pool, "PermissionTemplateRepository"),
+        }
+    }
+
+    /// Find a template by ID.
+    pub async fn find_by_id(&self, id: Uuid) -> AppResult<Option<PermissionTemplate>> {
+        sqlx::query_as::<_, PermissionTemplate>("SELECT * FROM permission_templates WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to find permission template", e)
+            })
+    }
+
+    /// Find a template by name.
+    pub async fn find_by_name(&self, name: &str) -> AppResult<Option<PermissionTemplate>> {
+        sqlx::query_as::<_, PermissionTemplate>(
+            "SELECT * FROM permission_templates WHERE name = $1",
+        )
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to find permission template", e)
+        })
+    }
+
+    /// List all templates by name.
+    pub async fn list(&self) -> AppResult<Vec<PermissionTemplate>> {
+        sqlx::query_as::<_, PermissionTemplate>(
+            "SELECT * FROM permission_templates ORDER BY name ASC",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(
+                ErrorKind::Database,
+                "Failed to list permission templates",
+                e,
+            )
+        })
+    }
+
+    /// Create a template.
+    pub async fn create(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        entries: &[TemplateEntry],
+        created_by: Uuid,
+    ) -> AppResult<PermissionTemplate> {
+        sqlx::query_as::<_, PermissionTemplate>(
+            "INSERT INTO permission_templates (name, description, entries, created_by) \
+             VALUES ($1, $2, $3, $4) RETURNING *",
+        )
+        .bind(name)
+        .bind(description)
+        .bind(Json(entries))
+        .bind(created_by)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| unique_name_error(e, name, "Failed to create permission template"))
+    }
+
+    /// Replace a template's name, description and entries.
+    pub async fn update(
+        &self,
+        id: Uuid,
+        name: &str,
+        description: Option<&str>,
+        entries: &[TemplateEntry],
+    ) -> AppResult<PermissionTemplate> {
+        sqlx::query_as::<_, PermissionTemplate>(
+            "UPDATE permission_templates \
+             SET name = $2, description = $3, entries = $4, updated_at = NOW() \
+             WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .bind(name)
+        .bind(description)
+        .bind(Json(entries))
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| unique_name_error(e, name, "Failed to update permission template"))?
+        .ok_or_else(|| AppError::not_found(format!("Permission template {id} not found")))
+    }
+
+    /// Delete a template, detaching it from every folder.
+    pub async fn delete(&self, id: Uuid) -> AppResult<bool> {
+        let result = sqlx::query("DELETE FROM permission_templates WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::with_source(
+                    ErrorKind::Database,
+                    "Failed to delete permission template",
+                    e,
+                )
+            })?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// Maps a duplicate name to a conflict.
+fn unique_name_error(e: sqlx::Error, name: &str, context: &str) -> AppError {
+    match e {
+        sqlx::Error::Database(ref db_err)
+            if db_err.constraint() == Some("permission_templates_name_key") =>
+        {
+            AppError::conflict(format!(
+                "A permission template named '{name}' already exists"
+            ))
+        }
+        _ => AppError::with_source(ErrorKind::Database, context, e),
+    }
+}
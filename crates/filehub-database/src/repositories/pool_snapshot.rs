@@ -1,23 +1,29 @@
 //! Pool snapshot repository implementation.
 
+use chrono::{DateTime, Utc};
 use sqlx::PgPool;
+use sqlx::types::Json;
 use uuid::Uuid;
 
 use filehub_core::error::{AppError, ErrorKind};
 use filehub_core::result::AppResult;
 use filehub_core::types::pagination::{PageRequest, PageResponse};
-use filehub_entity::license::pool::PoolSnapshot;
+use filehub_entity::license::pool::{CreatePoolSnapshot, PoolSnapshot};
+
+use crate::slow_query::TimedPool;
 
 /// Repository for license pool snapshots.
 #[derive(Debug, Clone)]
 pub struct PoolSnapshotRepository {
-    pool: PgPool,
+    pool: TimedPool,
 }
 
 impl PoolSnapshotRepository {
     /// Create a new pool snapshot repository.
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool: TimedPool::new(pool, "PoolSnapshotRepository"),
+        }
     }
 
     /// Find the latest snapshot.
@@ -67,37 +73,49 @@ impl PoolSnapshotRepository {
         ))
     }
 
-    /// Create a new snapshot.
-    pub async fn create(
+    /// Snapshots taken in `[from, to]`, oldest first, at most `limit`.
+    pub async fn find_between(
         &self,
-        total_seats: i32,
-        checked_out: i32,
-        available: i32,
-        admin_reserved: i32,
-        active_sessions: i32,
-        drift_detected: bool,
-        drift_detail: Option<&serde_json::Value>,
-        source: &str,
-    ) -> AppResult<PoolSnapshot> {
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: i64,
+    ) -> AppResult<Vec<PoolSnapshot>> {
+        sqlx::query_as::<_, PoolSnapshot>(
+            "SELECT * FROM pool_snapshots WHERE created_at BETWEEN $1 AND $2 \
+             ORDER BY created_at ASC LIMIT $3",
+        )
+        .bind(from)
+        .bind(to)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to list snapshots", e))
+    }
+
+    /// Create a new snapshot.
+    pub async fn create(&self, data: &CreatePoolSnapshot) -> AppResult<PoolSnapshot> {
         sqlx::query_as::<_, PoolSnapshot>(
-            "INSERT INTO pool_snapshots (total_seats, checked_out, available, admin_reserved, active_sessions, drift_detected, drift_detail, source) \
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *"
+            "INSERT INTO pool_snapshots (total_seats, checked_out, available, admin_reserved, active_sessions, drift_detected, drift_detail, source, borrowed, drift, features) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) RETURNING *"
         )
-            .bind(total_seats)
-            .bind(checked_out)
-            .bind(available)
-            .bind(admin_reserved)
-            .bind(active_sessions)
-            .bind(drift_detected)
-            .bind(drift_detail)
-            .bind(source)
+            .bind(data.total_seats)
+            .bind(data.checked_out)
+            .bind(data.available)
+            .bind(data.admin_reserved)
+            .bind(data.active_sessions)
+            .bind(data.drift_detected)
+            .bind(&data.drift_detail)
+            .bind(&data.source)
+            .bind(data.borrowed)
+            .bind(data.drift)
+            .bind(Json(&data.features))
             .fetch_one(&self.pool)
             .await
             .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to create snapshot", e))
     }
 
     /// Clean up old snapshots.
-    pub async fn cleanup_old(&self, before: chrono::DateTime<chrono::Utc>) -> AppResult<u64> {
+    pub async fn cleanup_old(&self, before: DateTime<Utc>) -> AppResult<u64> {
         let result = sqlx::query("DELETE FROM pool_snapshots WHERE created_at < $1")
             .bind(before)
             .execute(&self.pool)
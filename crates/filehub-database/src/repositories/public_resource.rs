@@ -0,0 +1,108 @@
+//! Public (anonymously readable) resource repository implementation.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use filehub_core::error::{AppError, ErrorKind};
+use filehub_core::result::AppResult;
+use filehub_entity::permission::model::ResourceType;
+
+use crate::slow_query::TimedPool;
+
+/// Repository for files and folders marked world-readable.
+#[derive(Debug, Clone)]
+pub struct PublicResourceRepository {
+    pool: TimedPool,
+}
+
+impl PublicResourceRepository {
+    /// Create a new public resource repository.
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool: TimedPool::new(pool, "PublicResourceRepository"),
+        }
+    }
+
+    /// Mark a file or folder public; marking it again is a no-op.
+    pub async fn publish(
+        &self,
+        resource_type: ResourceType,
+        resource_id: Uuid,
+        published_by: Uuid,
+    ) -> AppResult<()> {
+        sqlx::query(
+            "INSERT INTO public_resources (resource_type, resource_id, published_by) \
+             VALUES ($1, $2, $3) ON CONFLICT (resource_type, resource_id) DO NOTHING",
+        )
+        .bind(resource_type)
+        .bind(resource_id)
+        .bind(published_by)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to publish resource", e))?;
+        Ok(())
+    }
+
+    /// Remove a public mark; returns whether there was one.
+    pub async fn unpublish(
+        &self,
+        resource_type: ResourceType,
+        resource_id: Uuid,
+    ) -> AppResult<bool> {
+        let result = sqlx::query(
+            "DELETE FROM public_resources WHERE resource_type = $1 AND resource_id = $2",
+        )
+        .bind(resource_type)
+        .bind(resource_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to unpublish resource", e)
+        })?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Whether a folder is public, being marked itself or lying under a
+    /// marked folder.
+    pub async fn is_folder_public(&self, folder_id: Uuid) -> AppResult<bool> {
+        sqlx::query_scalar::<_, bool>(
+            "WITH RECURSIVE ancestors AS ( \
+                SELECT id, parent_id FROM folders WHERE id = $1 \
+                UNION ALL \
+                SELECT f.id, f.parent_id FROM folders f INNER JOIN ancestors a ON f.id = a.parent_id \
+             ) SELECT EXISTS ( \
+                SELECT 1 FROM public_resources p INNER JOIN ancestors a ON p.resource_id = a.id \
+                WHERE p.resource_type = 'folder' \
+             )",
+        )
+        .bind(folder_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to check public folder", e)
+        })
+    }
+
+    /// Whether a file is public, being marked itself or lying in a public
+    /// folder.
+    pub async fn is_file_public(&self, file_id: Uuid) -> AppResult<bool> {
+        sqlx::query_scalar::<_, bool>(
+            "WITH RECURSIVE ancestors AS ( \
+                SELECT fo.id, fo.parent_id FROM folders fo \
+                INNER JOIN files fi ON fi.folder_id = fo.id WHERE fi.id = $1 \
+                UNION ALL \
+                SELECT f.id, f.parent_id FROM folders f INNER JOIN ancestors a ON f.id = a.parent_id \
+             ) SELECT EXISTS ( \
+                SELECT 1 FROM public_resources \
+                WHERE resource_type = 'file' AND resource_id = $1 \
+             ) OR EXISTS ( \
+                SELECT 1 FROM public_resources p INNER JOIN ancestors a ON p.resource_id = a.id \
+                WHERE p.resource_type = 'folder' \
+             )",
+        )
+        .bind(file_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to check public file", e))
+    }
+}
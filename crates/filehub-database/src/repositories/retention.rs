@@ -0,0 +1,128 @@
+//! Folder retention policy repository implementation.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use filehub_core::error::{AppError, ErrorKind};
+use filehub_core::result::AppResult;
+use filehub_entity::folder::RetentionPolicy;
+
+use crate::slow_query::TimedPool;
+
+/// Repository for folder retention policies.
+#[derive(Debug, Clone)]
+pub struct RetentionPolicyRepository {
+    pool: TimedPool,
+}
+
+impl RetentionPolicyRepository {
+    /// Create a new retention policy repository.
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool: TimedPool::new(pool, "RetentionPolicyRepository"),
+        }
+    }
+
+    /// Find the policy attached to a folder.
+    pub async fn find(&self, folder_id: Uuid) -> AppResult<Option<RetentionPolicy>> {
+        sqlx::query_as::<_, RetentionPolicy>(
+            "SELECT * FROM folder_retention_policies WHERE folder_id = $1",
+        )
+        .bind(folder_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to find retention policy", e)
+        })
+    }
+
+    /// Find the policies attached to a folder and its ancestors.
+    pub async fn find_inherited(&self, folder_id: Uuid) -> AppResult<Vec<RetentionPolicy>> {
+        sqlx::query_as::<_, RetentionPolicy>(
+            "WITH RECURSIVE ancestors AS ( \
+                SELECT id, parent_id FROM folders WHERE id = $1 \
+                UNION ALL \
+                SELECT f.id, f.parent_id FROM folders f INNER JOIN ancestors a ON f.id = a.parent_id \
+             ) SELECT p.* FROM folder_retention_policies p \
+             INNER JOIN ancestors a ON p.folder_id = a.id",
+        )
+        .bind(folder_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to find retention policies", e)
+        })
+    }
+
+    /// Find the policies attached to any of `folder_ids`.
+    pub async fn find_for_folders(&self, folder_ids: &[Uuid]) -> AppResult<Vec<RetentionPolicy>> {
+        sqlx::query_as::<_, RetentionPolicy>(
+            "SELECT * FROM folder_retention_policies WHERE folder_id = ANY($1)",
+        )
+        .bind(folder_ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to find retention policies", e)
+        })
+    }
+
+    /// Attach a policy to a folder, replacing any it has.
+    pub async fn upsert(
+        &self,
+        folder_id: Uuid,
+        retention_days: i32,
+        legal_hold: bool,
+        legal_hold_reason: Option<&str>,
+        updated_by: Uuid,
+    ) -> AppResult<RetentionPolicy> {
+        sqlx::query_as::<_, RetentionPolicy>(
+            "INSERT INTO folder_retention_policies \
+                (folder_id, retention_days, legal_hold, legal_hold_reason, updated_by) \
+             VALUES ($1, $2, $3, $4, $5) \
+             ON CONFLICT (folder_id) DO UPDATE SET \
+                retention_days = EXCLUDED.retention_days, \
+                legal_hold = EXCLUDED.legal_hold, \
+                legal_hold_reason = EXCLUDED.legal_hold_reason, \
+                updated_by = EXCLUDED.updated_by, \
+                updated_at = NOW() \
+             RETURNING *",
+        )
+        .bind(folder_id)
+        .bind(retention_days)
+        .bind(legal_hold)
+        .bind(legal_hold_reason)
+        .bind(updated_by)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to save retention policy", e)
+        })
+    }
+
+    /// Detach the policy from a folder.
+    pub async fn delete(&self, folder_id: Uuid) -> AppResult<bool> {
+        let result = sqlx::query("DELETE FROM folder_retention_policies WHERE folder_id = $1")
+            .bind(folder_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to delete retention policy", e)
+            })?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Creation time of the newest file in each of `folder_ids` that has
+    /// files.
+    pub async fn newest_files(&self, folder_ids: &[Uuid]) -> AppResult<Vec<(Uuid, DateTime<Utc>)>> {
+        sqlx::query_as::<_, (Uuid, DateTime<Utc>)>(
+            "SELECT folder_id, MAX(created_at) FROM files \
+             WHERE folder_id = ANY($1) GROUP BY folder_id",
+        )
+        .bind(folder_ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to find newest files", e))
+    }
+}
@@ -0,0 +1,87 @@
+//! Saved search repository implementation.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use filehub_core::error::{AppError, ErrorKind};
+use filehub_core::result::AppResult;
+use filehub_entity::file::SavedSearch;
+
+use crate::slow_query::TimedPool;
+
+/// Repository for saved file searches.
+#[derive(Debug, Clone)]
+pub struct SavedSearchRepository {
+    pool: TimedPool,
+}
+
+impl SavedSearchRepository {
+    /// Create a new saved search repository.
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool: TimedPool::new(pool, "SavedSearchRepository"),
+        }
+    }
+
+    /// Find a saved search by ID.
+    pub async fn find_by_id(&self, id: Uuid) -> AppResult<Option<SavedSearch>> {
+        sqlx::query_as::<_, SavedSearch>("SELECT * FROM saved_searches WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to find saved search", e)
+            })
+    }
+
+    /// List a user's saved searches by name.
+    pub async fn find_by_owner(&self, owner_id: Uuid) -> AppResult<Vec<SavedSearch>> {
+        sqlx::query_as::<_, SavedSearch>(
+            "SELECT * FROM saved_searches WHERE owner_id = $1 ORDER BY name ASC",
+        )
+        .bind(owner_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to list saved searches", e))
+    }
+
+    /// Save a new search.
+    pub async fn create(
+        &self,
+        owner_id: Uuid,
+        name: &str,
+        query: &str,
+        filter: Option<&serde_json::Value>,
+    ) -> AppResult<SavedSearch> {
+        sqlx::query_as::<_, SavedSearch>(
+            "INSERT INTO saved_searches (owner_id, name, query, filter) \
+             VALUES ($1, $2, $3, $4) RETURNING *",
+        )
+        .bind(owner_id)
+        .bind(name)
+        .bind(query)
+        .bind(filter)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_err)
+                if db_err.constraint() == Some("saved_searches_owner_name_key") =>
+            {
+                AppError::conflict(format!("A saved search named '{}' already exists", name))
+            }
+            _ => AppError::with_source(ErrorKind::Database, "Failed to save search", e),
+        })
+    }
+
+    /// Delete a saved search.
+    pub async fn delete(&self, id: Uuid) -> AppResult<bool> {
+        let result = sqlx::query("DELETE FROM saved_searches WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to delete saved search", e)
+            })?;
+        Ok(result.rows_affected() > 0)
+    }
+}
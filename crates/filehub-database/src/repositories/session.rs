@@ -9,16 +9,20 @@ use filehub_core::result::AppResult;
 use filehub_core::types::pagination::{PageRequest, PageResponse};
 use filehub_entity::session::model::{CreateSession, Session};
 
+use crate::slow_query::TimedPool;
+
 /// Repository for session CRUD and query operations.
 #[derive(Debug, Clone)]
 pub struct SessionRepository {
-    pool: PgPool,
+    pool: TimedPool,
 }
 
 impl SessionRepository {
     /// Create a new session repository.
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool: TimedPool::new(pool, "SessionRepository"),
+        }
     }
 
     /// Find a session by ID.
@@ -72,10 +76,12 @@ impl SessionRepository {
             .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to find active sessions", e))
     }
 
-    /// Count active sessions for a user.
+    /// Count active sessions for a user, not counting impersonation
+    /// sessions.
     pub async fn count_active_by_user(&self, user_id: Uuid) -> AppResult<i64> {
         let count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM sessions WHERE user_id = $1 AND terminated_at IS NULL AND expires_at > NOW()"
+            "SELECT COUNT(*) FROM sessions WHERE user_id = $1 AND terminated_at IS NULL AND expires_at > NOW() \
+             AND impersonator_id IS NULL"
         )
             .bind(user_id)
             .fetch_one(&self.pool)
@@ -84,6 +90,20 @@ impl SessionRepository {
         Ok(count)
     }
 
+    /// Count active sessions that hold a license seat.
+    pub async fn count_seated_active(&self) -> AppResult<i64> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM sessions \
+             WHERE terminated_at IS NULL AND expires_at > NOW() AND seat_allocated_at IS NOT NULL",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to count seated sessions", e)
+        })?;
+        Ok(count)
+    }
+
     /// Count all active sessions system-wide.
     pub async fn count_all_active(&self) -> AppResult<i64> {
         let count: i64 = sqlx::query_scalar(
@@ -101,10 +121,12 @@ impl SessionRepository {
         Ok(count)
     }
 
-    /// Find the oldest active session for a user.
+    /// Find the oldest active session for a user, other than an
+    /// impersonation session.
     pub async fn find_oldest_by_user(&self, user_id: Uuid) -> AppResult<Option<Session>> {
         sqlx::query_as::<_, Session>(
             "SELECT * FROM sessions WHERE user_id = $1 AND terminated_at IS NULL AND expires_at > NOW() \
+             AND impersonator_id IS NULL \
              ORDER BY created_at ASC LIMIT 1"
         )
             .bind(user_id)
@@ -113,10 +135,12 @@ impl SessionRepository {
             .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to find oldest session", e))
     }
 
-    /// Find the most idle active session for a user.
+    /// Find the most idle active session for a user, other than an
+    /// impersonation session.
     pub async fn find_most_idle_by_user(&self, user_id: Uuid) -> AppResult<Option<Session>> {
         sqlx::query_as::<_, Session>(
             "SELECT * FROM sessions WHERE user_id = $1 AND terminated_at IS NULL AND expires_at > NOW() \
+             AND impersonator_id IS NULL \
              ORDER BY last_activity ASC LIMIT 1"
         )
             .bind(user_id)
@@ -165,11 +189,28 @@ impl SessionRepository {
         })
     }
 
+    /// List active impersonation sessions, newest first.
+    pub async fn find_active_impersonations(&self) -> AppResult<Vec<Session>> {
+        sqlx::query_as::<_, Session>(
+            "SELECT * FROM sessions WHERE impersonator_id IS NOT NULL \
+             AND terminated_at IS NULL AND expires_at > NOW() ORDER BY created_at DESC",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(
+                ErrorKind::Database,
+                "Failed to find impersonation sessions",
+                e,
+            )
+        })
+    }
+
     /// Create a new session.
     pub async fn create(&self, data: &CreateSession) -> AppResult<Session> {
         sqlx::query_as::<_, Session>(
-            "INSERT INTO sessions (user_id, token_hash, refresh_token_hash, ip_address, user_agent, device_info, expires_at) \
-             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *"
+            "INSERT INTO sessions (user_id, token_hash, refresh_token_hash, ip_address, user_agent, device_info, expires_at, impersonator_id) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING *"
         )
             .bind(data.user_id)
             .bind(&data.token_hash)
@@ -178,19 +219,43 @@ impl SessionRepository {
             .bind(&data.user_agent)
             .bind(&data.device_info)
             .bind(data.expires_at)
+            .bind(data.impersonator_id)
             .fetch_one(&self.pool)
             .await
             .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to create session", e))
     }
 
-    /// Update last activity timestamp.
+    /// Update last activity timestamp. Clears a pending idle warning and
+    /// restores the presence it set.
     pub async fn update_last_activity(&self, session_id: Uuid) -> AppResult<()> {
-        sqlx::query("UPDATE sessions SET last_activity = NOW() WHERE id = $1")
+        sqlx::query(
+            "UPDATE sessions SET last_activity = NOW(), idle_warned_at = NULL, \
+             presence_status = CASE WHEN idle_warned_at IS NULL THEN presence_status \
+             ELSE 'active' END \
+             WHERE id = $1",
+        )
+        .bind(session_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to update last activity", e)
+        })?;
+        Ok(())
+    }
+
+    /// Move the expiry of a session, e.g. when its tokens are refreshed.
+    pub async fn update_expires_at(
+        &self,
+        session_id: Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query("UPDATE sessions SET expires_at = $2 WHERE id = $1")
             .bind(session_id)
+            .bind(expires_at)
             .execute(&self.pool)
             .await
             .map_err(|e| {
-                AppError::with_source(ErrorKind::Database, "Failed to update last activity", e)
+                AppError::with_source(ErrorKind::Database, "Failed to update session expiry", e)
             })?;
         Ok(())
     }
@@ -394,6 +459,47 @@ impl SessionRepository {
         .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to find idle sessions", e))
     }
 
+    /// Flag sessions inactive since before `cutoff` as warned of idle
+    /// termination and mark them away. Returns only the sessions flagged by
+    /// this call, so a warning is sent once however often this runs.
+    pub async fn mark_idle_warned(&self, cutoff: DateTime<Utc>) -> AppResult<Vec<Session>> {
+        sqlx::query_as::<_, Session>(
+            "UPDATE sessions SET idle_warned_at = NOW(), presence_status = 'away' \
+             WHERE last_activity < $1 AND terminated_at IS NULL AND idle_warned_at IS NULL \
+             AND expires_at > NOW() \
+             RETURNING *",
+        )
+        .bind(cutoff)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to mark idle sessions", e))
+    }
+
+    /// Terminate sessions inactive since before `idle_cutoff` whose idle
+    /// warning was sent before `warned_before`. Activity since the warning
+    /// clears it, so such sessions are left alone.
+    pub async fn terminate_idle(
+        &self,
+        idle_cutoff: DateTime<Utc>,
+        warned_before: DateTime<Utc>,
+        reason: &str,
+    ) -> AppResult<Vec<Session>> {
+        sqlx::query_as::<_, Session>(
+            "UPDATE sessions SET terminated_reason = $3, terminated_at = NOW(), \
+             presence_status = 'offline' \
+             WHERE last_activity < $1 AND idle_warned_at < $2 AND terminated_at IS NULL \
+             RETURNING *",
+        )
+        .bind(idle_cutoff)
+        .bind(warned_before)
+        .bind(reason)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to terminate idle sessions", e)
+        })
+    }
+
     /// Set WebSocket connection state.
     pub async fn set_ws_connected(
         &self,
@@ -7,16 +7,20 @@ use filehub_core::error::{AppError, ErrorKind};
 use filehub_core::result::AppResult;
 use filehub_entity::session::limit::UserSessionLimit;
 
+use crate::slow_query::TimedPool;
+
 /// Repository for user session limit CRUD operations.
 #[derive(Debug, Clone)]
 pub struct SessionLimitRepository {
-    pool: PgPool,
+    pool: TimedPool,
 }
 
 impl SessionLimitRepository {
     /// Create a new session limit repository.
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool: TimedPool::new(pool, "SessionLimitRepository"),
+        }
     }
 
     /// Find a session limit override for a specific user.
@@ -4,20 +4,27 @@ use sqlx::PgPool;
 use uuid::Uuid;
 
 use filehub_core::error::{AppError, ErrorKind};
+use filehub_core::events::DomainEvent;
 use filehub_core::result::AppResult;
 use filehub_core::types::pagination::{PageRequest, PageResponse};
 use filehub_entity::share::model::{CreateShare, Share};
+use filehub_entity::share::{CreateShareAccess, ShareAccess, ShareAccessKind, ShareStats};
+
+use crate::repositories::outbox::OutboxRepository;
+use crate::slow_query::TimedPool;
 
 /// Repository for share CRUD and token lookup operations.
 #[derive(Debug, Clone)]
 pub struct ShareRepository {
-    pool: PgPool,
+    pool: TimedPool,
 }
 
 impl ShareRepository {
     /// Create a new share repository.
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool: TimedPool::new(pool, "ShareRepository"),
+        }
     }
 
     /// Find a share by ID.
@@ -105,9 +112,38 @@ impl ShareRepository {
         ))
     }
 
-    /// Create a new share.
-    pub async fn create(&self, data: &CreateShare) -> AppResult<Share> {
+    /// List user shares with `user_id` on any of `resource_ids`, active or not.
+    pub async fn find_shared_with_user_on(
+        &self,
+        user_id: Uuid,
+        resource_ids: &[Uuid],
+    ) -> AppResult<Vec<Share>> {
         sqlx::query_as::<_, Share>(
+            "SELECT * FROM shares WHERE shared_with = $1 AND resource_id = ANY($2) \
+             ORDER BY created_at DESC",
+        )
+        .bind(user_id)
+        .bind(resource_ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to list shares on resources", e)
+        })
+    }
+
+    /// Create a new share in one transaction with its link token, if
+    /// `sign` makes one from the created share, and the event `announce`
+    /// makes, which is recorded in the outbox.
+    pub async fn create(
+        &self,
+        data: &CreateShare,
+        sign: impl FnOnce(&Share) -> Option<String> + Send,
+        announce: impl FnOnce(&Share) -> DomainEvent + Send,
+    ) -> AppResult<Share> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to begin transaction", e)
+        })?;
+        let mut share = sqlx::query_as::<_, Share>(
             "INSERT INTO shares (share_type, resource_type, resource_id, created_by, token, password_hash, \
              shared_with, permission, allow_download, max_downloads, expires_at) \
              VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) RETURNING *"
@@ -123,9 +159,28 @@ impl ShareRepository {
             .bind(data.allow_download)
             .bind(data.max_downloads)
             .bind(data.expires_at)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *tx)
             .await
-            .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to create share", e))
+            .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to create share", e))?;
+
+        if let Some(token) = sign(&share) {
+            share = sqlx::query_as::<_, Share>(
+                "UPDATE shares SET token = $2 WHERE id = $1 RETURNING *",
+            )
+            .bind(share.id)
+            .bind(token)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to set share token", e)
+            })?;
+        }
+        OutboxRepository::enqueue(&mut tx, &[announce(&share)]).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to commit share", e))?;
+        Ok(share)
     }
 
     /// Increment download count.
@@ -141,6 +196,41 @@ impl ShareRepository {
         Ok(row.0)
     }
 
+    /// Count one download through the link `token` of a file share,
+    /// atomically with the checks that the link is still current: the share
+    /// is active, allows downloads, is not expired, is under its download limit and
+    /// `token` has not been replaced. Returns `None`, counting nothing, if any check fails.
+    pub async fn claim_download(&self, share_id: Uuid, token: &str) -> AppResult<Option<Share>> {
+        sqlx::query_as::<_, Share>(
+            "UPDATE shares SET download_count = COALESCE(download_count, 0) + 1, \
+             last_accessed = NOW() \
+             WHERE id = $1 AND token = $2 AND resource_type = 'file' \
+             AND is_active IS NOT FALSE \
+             AND allow_download IS NOT FALSE \
+             AND (expires_at IS NULL OR expires_at > NOW()) \
+             AND (max_downloads IS NULL OR COALESCE(download_count, 0) < max_downloads) \
+             RETURNING *",
+        )
+        .bind(share_id)
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to claim download", e))
+    }
+
+    /// Set the link token of a share.
+    pub async fn set_token(&self, share_id: Uuid, token: &str) -> AppResult<Share> {
+        sqlx::query_as::<_, Share>("UPDATE shares SET token = $2 WHERE id = $1 RETURNING *")
+            .bind(share_id)
+            .bind(token)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to set share token", e)
+            })?
+            .ok_or_else(|| AppError::not_found(format!("Share {} not found", share_id)))
+    }
+
     /// Update last accessed timestamp.
     pub async fn update_last_accessed(
         &self,
@@ -162,7 +252,7 @@ impl ShareRepository {
     pub async fn update(&self, share: &Share) -> AppResult<Share> {
         sqlx::query_as::<_, Share>(
             "UPDATE shares SET permission = $2, allow_download = $3, max_downloads = $4, \
-             expires_at = $5, is_active = $6 \
+             expires_at = $5, is_active = $6, token = $7 \
              WHERE id = $1 RETURNING *",
         )
         .bind(share.id)
@@ -171,6 +261,7 @@ impl ShareRepository {
         .bind(share.max_downloads)
         .bind(share.expires_at)
         .bind(share.is_active)
+        .bind(&share.token)
         .fetch_optional(&self.pool)
         .await
         .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to update share", e))?
@@ -189,6 +280,67 @@ impl ShareRepository {
         Ok(result.rows_affected() > 0)
     }
 
+    /// Record a public access to a share link.
+    pub async fn record_access(&self, data: &CreateShareAccess) -> AppResult<()> {
+        sqlx::query(
+            "INSERT INTO share_accesses (share_id, kind, accessed_at, ip_address, user_agent, country) \
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(data.share_id)
+        .bind(data.kind)
+        .bind(data.accessed_at)
+        .bind(&data.ip_address)
+        .bind(&data.user_agent)
+        .bind(&data.country)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to record share access", e))?;
+        Ok(())
+    }
+
+    /// Access counts of a share with its `recent_limit` latest accesses.
+    pub async fn access_stats(&self, share_id: Uuid, recent_limit: i64) -> AppResult<ShareStats> {
+        let (views, downloads, unique_visitors, last_accessed_at): (
+            i64,
+            i64,
+            i64,
+            Option<chrono::DateTime<chrono::Utc>>,
+        ) = sqlx::query_as(
+            "SELECT COUNT(*) FILTER (WHERE kind = $2), COUNT(*) FILTER (WHERE kind = $3), \
+             COUNT(DISTINCT ip_address), MAX(accessed_at) \
+             FROM share_accesses WHERE share_id = $1",
+        )
+        .bind(share_id)
+        .bind(ShareAccessKind::View)
+        .bind(ShareAccessKind::Download)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to count share accesses", e)
+        })?;
+
+        let recent = sqlx::query_as::<_, ShareAccess>(
+            "SELECT * FROM share_accesses WHERE share_id = $1 \
+             ORDER BY accessed_at DESC LIMIT $2",
+        )
+        .bind(share_id)
+        .bind(recent_limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to list share accesses", e)
+        })?;
+
+        Ok(ShareStats {
+            share_id,
+            views,
+            downloads,
+            unique_visitors,
+            last_accessed_at,
+            recent,
+        })
+    }
+
     /// Delete a share.
     pub async fn delete(&self, share_id: Uuid) -> AppResult<bool> {
         let result = sqlx::query("DELETE FROM shares WHERE id = $1")
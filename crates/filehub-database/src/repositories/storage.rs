@@ -7,16 +7,31 @@ use filehub_core::error::{AppError, ErrorKind};
 use filehub_core::result::AppResult;
 use filehub_entity::storage::model::{CreateStorage, Storage};
 
+use crate::connection::DatabasePool;
+use crate::slow_query::TimedPool;
+
 /// Repository for storage backend CRUD operations.
 #[derive(Debug, Clone)]
 pub struct StorageRepository {
-    pool: PgPool,
+    pool: TimedPool,
+    /// Pool for reads that may be served by a replica.
+    reads: DatabasePool,
 }
 
 impl StorageRepository {
     /// Create a new storage repository.
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            reads: DatabasePool::from_pool(pool.clone()),
+            pool: TimedPool::new(pool, "StorageRepository"),
+        }
+    }
+
+    /// Route this repository's stale-tolerant reads through `db`'s read
+    /// replicas.
+    pub fn with_read_replicas(mut self, db: DatabasePool) -> Self {
+        self.reads = db;
+        self
     }
 
     /// Find a storage by ID.
@@ -147,21 +162,34 @@ impl StorageRepository {
 
     /// Get total used bytes across all storages.
     pub async fn total_used_bytes(&self) -> AppResult<i64> {
-        let total: i64 = sqlx::query_scalar("SELECT COALESCE(SUM(used_bytes), 0) FROM storages")
-            .fetch_one(&self.pool)
+        self.reads
+            .read_only(|pool| async move {
+                sqlx::query_scalar("SELECT COALESCE(SUM(used_bytes), 0) FROM storages")
+                    .fetch_one(&pool)
+                    .await
+                    .map_err(|e| {
+                        AppError::with_source(
+                            ErrorKind::Database,
+                            "Failed to calculate total storage usage",
+                            e,
+                        )
+                    })
+            })
             .await
-            .map_err(|e| {
-                AppError::with_source(
-                    ErrorKind::Database,
-                    "Failed to calculate total storage usage",
-                    e,
-                )
-            })?;
-        Ok(total)
     }
 
-    /// Find all storages with usage data (alias for find_all for now as it includes used_bytes).
+    /// Find all storages with usage data for reporting (may be served by a
+    /// read replica).
     pub async fn find_all_with_usage(&self) -> AppResult<Vec<Storage>> {
-        self.find_all().await
+        self.reads
+            .read_only(|pool| async move {
+                sqlx::query_as::<_, Storage>("SELECT * FROM storages ORDER BY name ASC")
+                    .fetch_all(&pool)
+                    .await
+                    .map_err(|e| {
+                        AppError::with_source(ErrorKind::Database, "Failed to list storages", e)
+                    })
+            })
+            .await
     }
 }
@@ -0,0 +1,222 @@
+//! Storage migration repository implementation.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use filehub_core::error::{AppError, ErrorKind};
+use filehub_core::result::AppResult;
+use filehub_entity::storage::{CreateStorageMigration, StorageMigration, StorageMigrationStatus};
+
+use crate::slow_query::TimedPool;
+
+/// Counters from one batch, added to the migration's totals.
+#[derive(Debug, Clone, Default)]
+pub struct MigrationBatchProgress {
+    /// Last file visited.
+    pub cursor_file_id: Option<Uuid>,
+    /// Files moved.
+    pub files_copied: i64,
+    /// Bytes copied, versions included.
+    pub bytes_copied: i64,
+    /// Files left for a later pass.
+    pub files_skipped: i64,
+    /// Files that failed to copy or verify.
+    pub files_failed: i64,
+    /// Most recent error, if any.
+    pub last_error: Option<String>,
+}
+
+/// Repository for storage migrations.
+#[derive(Debug, Clone)]
+pub struct StorageMigrationRepository {
+    pool: TimedPool,
+}
+
+impl StorageMigrationRepository {
+    /// Create a new storage migration repository.
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool: TimedPool::new(pool, "StorageMigrationRepository"),
+        }
+    }
+
+    /// Find a migration by ID.
+    pub async fn find_by_id(&self, id: Uuid) -> AppResult<Option<StorageMigration>> {
+        sqlx::query_as::<_, StorageMigration>("SELECT * FROM storage_migrations WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Database, "Failed to find storage migration", e)
+            })
+    }
+
+    /// List migrations, newest first.
+    pub async fn find_recent(&self, limit: i64) -> AppResult<Vec<StorageMigration>> {
+        sqlx::query_as::<_, StorageMigration>(
+            "SELECT * FROM storage_migrations ORDER BY created_at DESC LIMIT $1",
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to list storage migrations", e)
+        })
+    }
+
+    /// Create a pending migration. Fails with a conflict if the source
+    /// already has an unfinished one.
+    pub async fn create(&self, data: &CreateStorageMigration) -> AppResult<StorageMigration> {
+        sqlx::query_as::<_, StorageMigration>(
+            "INSERT INTO storage_migrations \
+             (source_storage_id, target_storage_id, batch_size, bytes_per_second, \
+              files_total, bytes_total, created_by) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+        )
+        .bind(data.source_storage_id)
+        .bind(data.target_storage_id)
+        .bind(data.batch_size)
+        .bind(data.bytes_per_second)
+        .bind(data.files_total)
+        .bind(data.bytes_total)
+        .bind(data.created_by)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_err)
+                if db_err.constraint() == Some("idx_storage_migrations_active_source") =>
+            {
+                AppError::conflict("A migration out of this storage is already in progress")
+            }
+            _ => {
+                AppError::with_source(ErrorKind::Database, "Failed to create storage migration", e)
+            }
+        })
+    }
+
+    /// Mark a pending or running migration as running. Returns `None` when
+    /// it has already finished or been cancelled.
+    pub async fn mark_running(&self, id: Uuid) -> AppResult<Option<StorageMigration>> {
+        sqlx::query_as::<_, StorageMigration>(
+            "UPDATE storage_migrations SET status = 'running', \
+             started_at = COALESCE(started_at, NOW()), updated_at = NOW() \
+             WHERE id = $1 AND status IN ('pending', 'running') RETURNING *",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to start storage migration", e)
+        })
+    }
+
+    /// Add a batch's counters and move the cursor. Returns the updated
+    /// migration, whose status tells the runner whether it was cancelled
+    /// meanwhile.
+    pub async fn record_batch(
+        &self,
+        id: Uuid,
+        progress: &MigrationBatchProgress,
+    ) -> AppResult<StorageMigration> {
+        sqlx::query_as::<_, StorageMigration>(
+            "UPDATE storage_migrations SET \
+             cursor_file_id = COALESCE($2, cursor_file_id), \
+             files_copied = files_copied + $3, bytes_copied = bytes_copied + $4, \
+             files_skipped = files_skipped + $5, files_failed = files_failed + $6, \
+             last_error = COALESCE($7, last_error), updated_at = NOW() \
+             WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .bind(progress.cursor_file_id)
+        .bind(progress.files_copied)
+        .bind(progress.bytes_copied)
+        .bind(progress.files_skipped)
+        .bind(progress.files_failed)
+        .bind(&progress.last_error)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(
+                ErrorKind::Database,
+                "Failed to record storage migration progress",
+                e,
+            )
+        })
+    }
+
+    /// Start the next pass from the beginning of the source, clearing the
+    /// per-pass counters.
+    pub async fn start_next_pass(&self, id: Uuid) -> AppResult<()> {
+        sqlx::query(
+            "UPDATE storage_migrations SET pass = pass + 1, cursor_file_id = NULL, \
+             files_skipped = 0, files_failed = 0, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(
+                ErrorKind::Database,
+                "Failed to restart storage migration",
+                e,
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Move a migration to a terminal status. Only unfinished migrations
+    /// are updated; returns whether this one was.
+    pub async fn finish(
+        &self,
+        id: Uuid,
+        status: StorageMigrationStatus,
+        error: Option<&str>,
+    ) -> AppResult<bool> {
+        let result = sqlx::query(
+            "UPDATE storage_migrations SET status = $2, last_error = COALESCE($3, last_error), \
+             completed_at = NOW(), updated_at = NOW() \
+             WHERE id = $1 AND status IN ('pending', 'running')",
+        )
+        .bind(id)
+        .bind(status)
+        .bind(error)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to finish storage migration", e)
+        })?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Put a failed or cancelled migration, or a running one that has not
+    /// recorded progress since `stale_before`, back to pending so it can
+    /// be resumed from its cursor.
+    pub async fn reopen(
+        &self,
+        id: Uuid,
+        stale_before: DateTime<Utc>,
+    ) -> AppResult<Option<StorageMigration>> {
+        sqlx::query_as::<_, StorageMigration>(
+            "UPDATE storage_migrations SET status = 'pending', completed_at = NULL, \
+             updated_at = NOW() \
+             WHERE id = $1 AND (status IN ('failed', 'cancelled') \
+                OR (status = 'running' AND updated_at < $2)) \
+             RETURNING *",
+        )
+        .bind(id)
+        .bind(stale_before)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_err)
+                if db_err.constraint() == Some("idx_storage_migrations_active_source") =>
+            {
+                AppError::conflict("Another migration out of this storage is in progress")
+            }
+            _ => {
+                AppError::with_source(ErrorKind::Database, "Failed to resume storage migration", e)
+            }
+        })
+    }
+}
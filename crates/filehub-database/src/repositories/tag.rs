@@ -0,0 +1,148 @@
+//! Tag repository implementation.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use filehub_core::error::{AppError, ErrorKind};
+use filehub_core::result::AppResult;
+use filehub_entity::file::File;
+use filehub_entity::tag::Tag;
+
+use crate::slow_query::TimedPool;
+
+/// Repository for tags and file-tag associations.
+#[derive(Debug, Clone)]
+pub struct TagRepository {
+    pool: TimedPool,
+}
+
+impl TagRepository {
+    /// Create a new tag repository.
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool: TimedPool::new(pool, "TagRepository"),
+        }
+    }
+
+    /// Find a tag by ID.
+    pub async fn find_by_id(&self, id: Uuid) -> AppResult<Option<Tag>> {
+        sqlx::query_as::<_, Tag>("SELECT * FROM tags WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to find tag", e))
+    }
+
+    /// List a user's tags by name.
+    pub async fn find_by_owner(&self, owner_id: Uuid) -> AppResult<Vec<Tag>> {
+        sqlx::query_as::<_, Tag>(
+            "SELECT * FROM tags WHERE owner_id = $1 ORDER BY normalized_name ASC",
+        )
+        .bind(owner_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to list tags", e))
+    }
+
+    /// Return the owner's tag with this normalized name, creating it if needed.
+    pub async fn find_or_create(
+        &self,
+        owner_id: Uuid,
+        name: &str,
+        normalized_name: &str,
+    ) -> AppResult<Tag> {
+        sqlx::query_as::<_, Tag>(
+            "INSERT INTO tags (owner_id, name, normalized_name) VALUES ($1, $2, $3) \
+             ON CONFLICT (owner_id, normalized_name) DO UPDATE SET name = tags.name \
+             RETURNING *",
+        )
+        .bind(owner_id)
+        .bind(name)
+        .bind(normalized_name)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to create tag", e))
+    }
+
+    /// Rename a tag.
+    pub async fn rename(&self, id: Uuid, name: &str, normalized_name: &str) -> AppResult<Tag> {
+        sqlx::query_as::<_, Tag>(
+            "UPDATE tags SET name = $2, normalized_name = $3 WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .bind(name)
+        .bind(normalized_name)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db_err)
+                if db_err.constraint() == Some("tags_owner_normalized_name_key") =>
+            {
+                AppError::conflict(format!("A tag named '{}' already exists", name))
+            }
+            _ => AppError::with_source(ErrorKind::Database, "Failed to rename tag", e),
+        })
+    }
+
+    /// Delete a tag and all of its file associations.
+    pub async fn delete(&self, id: Uuid) -> AppResult<bool> {
+        let result = sqlx::query("DELETE FROM tags WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to delete tag", e))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Attach a tag to a file. Attaching twice is a no-op.
+    pub async fn attach(&self, file_id: Uuid, tag_id: Uuid) -> AppResult<()> {
+        sqlx::query(
+            "INSERT INTO file_tags (file_id, tag_id) VALUES ($1, $2) \
+             ON CONFLICT (file_id, tag_id) DO NOTHING",
+        )
+        .bind(file_id)
+        .bind(tag_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to tag file", e))?;
+        Ok(())
+    }
+
+    /// Detach a tag from a file.
+    pub async fn detach(&self, file_id: Uuid, tag_id: Uuid) -> AppResult<bool> {
+        let result = sqlx::query("DELETE FROM file_tags WHERE file_id = $1 AND tag_id = $2")
+            .bind(file_id)
+            .bind(tag_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to untag file", e))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// List the owner's tags attached to a file.
+    pub async fn find_for_file(&self, owner_id: Uuid, file_id: Uuid) -> AppResult<Vec<Tag>> {
+        sqlx::query_as::<_, Tag>(
+            "SELECT t.* FROM tags t JOIN file_tags ft ON ft.tag_id = t.id \
+             WHERE ft.file_id = $1 AND t.owner_id = $2 ORDER BY t.normalized_name ASC",
+        )
+        .bind(file_id)
+        .bind(owner_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to list file tags", e))
+    }
+
+    /// List files carrying a tag, most recently tagged first.
+    pub async fn find_files(&self, tag_id: Uuid, limit: i64, offset: i64) -> AppResult<Vec<File>> {
+        sqlx::query_as::<_, File>(
+            "SELECT f.* FROM files f JOIN file_tags ft ON ft.file_id = f.id \
+             WHERE ft.tag_id = $1 ORDER BY ft.created_at DESC, f.id ASC LIMIT $2 OFFSET $3",
+        )
+        .bind(tag_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to list tagged files", e))
+    }
+}
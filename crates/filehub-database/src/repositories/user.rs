@@ -10,16 +10,31 @@ use filehub_core::types::pagination::{PageRequest, PageResponse};
 use filehub_entity::user::model::{CreateUser, UpdateUser};
 use filehub_entity::user::{User, UserRole, UserStatus};
 
+use crate::connection::DatabasePool;
+use crate::slow_query::TimedPool;
+
 /// Repository for user CRUD and query operations.
 #[derive(Debug, Clone)]
 pub struct UserRepository {
-    pool: PgPool,
+    pool: TimedPool,
+    /// Pool for reads that may be served by a replica.
+    reads: DatabasePool,
 }
 
 impl UserRepository {
     /// Create a new user repository.
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            reads: DatabasePool::from_pool(pool.clone()),
+            pool: TimedPool::new(pool, "UserRepository"),
+        }
+    }
+
+    /// Route this repository's stale-tolerant reads through `db`'s read
+    /// replicas.
+    pub fn with_read_replicas(mut self, db: DatabasePool) -> Self {
+        self.reads = db;
+        self
     }
 
     /// Find a user by primary key.
@@ -232,6 +247,50 @@ impl UserRepository {
         })
     }
 
+    /// Create several users in one transaction: either all are created or,
+    /// on the first failure, none are.
+    pub async fn create_batch(&self, users: &[CreateUser]) -> AppResult<Vec<User>> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to begin transaction", e)
+        })?;
+
+        let mut created = Vec::with_capacity(users.len());
+        for data in users {
+            let user = sqlx::query_as::<_, User>(
+                "INSERT INTO users (username, email, password_hash, display_name, role, created_by) \
+                 VALUES ($1, $2, $3, $4, $5, $6) \
+                 RETURNING *",
+            )
+            .bind(&data.username)
+            .bind(&data.email)
+            .bind(&data.password_hash)
+            .bind(&data.display_name)
+            .bind(data.role)
+            .bind(data.created_by)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(ref db_err)
+                    if db_err.constraint() == Some("users_username_key") =>
+                {
+                    AppError::conflict(format!("Username '{}' already exists", data.username))
+                }
+                sqlx::Error::Database(ref db_err)
+                    if db_err.constraint() == Some("users_email_key") =>
+                {
+                    AppError::conflict("Email already in use".to_string())
+                }
+                _ => AppError::with_source(ErrorKind::Database, "Failed to create user", e),
+            })?;
+            created.push(user);
+        }
+
+        tx.commit().await.map_err(|e| {
+            AppError::with_source(ErrorKind::Database, "Failed to commit user batch", e)
+        })?;
+        Ok(created)
+    }
+
     /// Update a user's profile fields.
     pub async fn update(&self, data: &UpdateUser) -> AppResult<User> {
         sqlx::query_as::<_, User>(
@@ -364,22 +423,30 @@ impl UserRepository {
 
     /// Count total users.
     pub async fn count_all(&self) -> AppResult<i64> {
-        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
-            .fetch_one(&self.pool)
+        self.reads
+            .read_only(|pool| async move {
+                sqlx::query_scalar("SELECT COUNT(*) FROM users")
+                    .fetch_one(&pool)
+                    .await
+                    .map_err(|e| {
+                        AppError::with_source(ErrorKind::Database, "Failed to count users", e)
+                    })
+            })
             .await
-            .map_err(|e| AppError::with_source(ErrorKind::Database, "Failed to count users", e))?;
-        Ok(count)
     }
 
     /// Count users created since a specific time.
     pub async fn count_created_since(&self, since: DateTime<Utc>) -> AppResult<i64> {
-        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE created_at >= $1")
-            .bind(since)
-            .fetch_one(&self.pool)
+        self.reads
+            .read_only(|pool| async move {
+                sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE created_at >= $1")
+                    .bind(since)
+                    .fetch_one(&pool)
+                    .await
+                    .map_err(|e| {
+                        AppError::with_source(ErrorKind::Database, "Failed to count new users", e)
+                    })
+            })
             .await
-            .map_err(|e| {
-                AppError::with_source(ErrorKind::Database, "Failed to count new users", e)
-            })?;
-        Ok(count)
     }
 }
@@ -0,0 +1,294 @@
+//! Slow-query logging.
+//!
+//! Repositories hold a [`TimedPool`] instead of a bare [`PgPool`]. It
+//! derefs to the pool, so transactions and helpers taking `&PgPool` keep
+//! working, and implements [`Executor`], so `.fetch_*(&self.pool)` is timed
+//! without touching the query code. Statements that run longer than the
+//! threshold set by [`set_threshold`] are logged at warn with the SQL
+//! (string literals redacted; bound parameters are never logged), the
+//! duration, the repository and the current request id.
+//!
+//! When traces are exported each statement also gets a client span.
+//!
+//! Statements in progress, including ones still waiting for a pooled
+//! connection, are counted for the pool statistics (see [`in_flight`]).
+
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+use futures::future::BoxFuture;
+use futures::stream::{self, BoxStream, StreamExt};
+use sqlx::postgres::{PgQueryResult, PgRow, PgStatement, PgTypeInfo};
+use sqlx::{Describe, Either, Execute, Executor, PgPool, Postgres};
+use tracing::{Instrument, Span};
+
+use filehub_core::telemetry;
+use filehub_core::types::RequestId;
+
+/// Slow-query threshold in milliseconds; `0` disables logging.
+static THRESHOLD_MS: AtomicU64 = AtomicU64::new(0);
+
+/// Set the process-wide slow-query threshold. `None` disables logging.
+pub fn set_threshold(threshold_ms: Option<u64>) {
+    THRESHOLD_MS.store(threshold_ms.unwrap_or(0), Ordering::Relaxed);
+}
+
+/// Statements issued through a [`TimedPool`] and not yet finished.
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of repository statements in progress, whether running or
+/// waiting for a connection.
+pub fn in_flight() -> usize {
+    IN_FLIGHT.load(Ordering::Relaxed)
+}
+
+/// Counts one statement in [`IN_FLIGHT`] while alive.
+struct InFlight;
+
+impl InFlight {
+    fn start() -> Self {
+        IN_FLIGHT.fetch_add(1, Ordering::Relaxed);
+        Self
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        IN_FLIGHT.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+fn threshold() -> Option<Duration> {
+    match THRESHOLD_MS.load(Ordering::Relaxed) {
+        0 => None,
+        ms => Some(Duration::from_millis(ms)),
+    }
+}
+
+/// A [`PgPool`] whose queries are checked against the slow-query threshold.
+#[derive(Debug, Clone)]
+pub struct TimedPool {
+    /// The wrapped pool.
+    pool: PgPool,
+    /// Repository name reported with slow queries.
+    repository: &'static str,
+}
+
+impl TimedPool {
+    /// Wrap `pool` for the repository named `repository`.
+    pub fn new(pool: PgPool, repository: &'static str) -> Self {
+        Self { pool, repository }
+    }
+}
+
+impl Deref for TimedPool {
+    type Target = PgPool;
+
+    fn deref(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+/// Timing state for one statement.
+struct QueryTimer {
+    repository: &'static str,
+    sql: String,
+    request_id: Option<RequestId>,
+    started: Instant,
+}
+
+impl QueryTimer {
+    /// Start timing `sql`, or return `None` when logging is disabled.
+    fn start(repository: &'static str, sql: &str) -> Option<Self> {
+        threshold()?;
+        Some(Self {
+            repository,
+            sql: sql.to_string(),
+            request_id: RequestId::current(),
+            started: Instant::now(),
+        })
+    }
+
+    fn finish(self) {
+        let elapsed = self.started.elapsed();
+        if threshold().is_none_or(|limit| elapsed < limit) {
+            return;
+        }
+        let sql = redact(&self.sql);
+        tracing::warn!(
+            repository = self.repository,
+            request_id = self.request_id.as_ref().map(RequestId::as_str),
+            elapsed_ms = elapsed.as_millis() as u64,
+            sql = %sql,
+            "Slow query"
+        );
+    }
+}
+
+/// Span for one statement; disabled unless traces are exported.
+fn query_span(repository: &'static str, sql: &str) -> Span {
+    tracing::info_span!(
+        target: telemetry::TARGET,
+        "db.query",
+        otel.name = operation(sql),
+        otel.kind = "client",
+        db.system.name = "postgresql",
+        db.operation.name = operation(sql),
+        db.query.text = %redact(sql),
+        repository,
+    )
+}
+
+/// The statement's leading keyword, e.g. `SELECT`.
+fn operation(sql: &str) -> &str {
+    sql.split_whitespace().next().unwrap_or_default()
+}
+
+impl<'c> Executor<'c> for &'c TimedPool {
+    type Database = Postgres;
+
+    fn fetch_many<'e, 'q: 'e, E>(
+        self,
+        query: E,
+    ) -> BoxStream<'e, Result<Either<PgQueryResult, PgRow>, sqlx::Error>>
+    where
+        'c: 'e,
+        E: 'q + Execute<'q, Postgres>,
+    {
+        let in_flight = InFlight::start();
+        let span = query_span(self.repository, query.sql());
+        let mut timer = QueryTimer::start(self.repository, query.sql());
+        if timer.is_none() && span.is_disabled() {
+            return self
+                .pool
+                .fetch_many(query)
+                .inspect(move |_| {
+                    let _ = &in_flight;
+                })
+                .boxed();
+        }
+        let mut rows = self.pool.fetch_many(query);
+        stream::poll_fn(move |cx| {
+            let _in_flight = &in_flight;
+            let _entered = span.enter();
+            let next = rows.poll_next_unpin(cx);
+            if let Poll::Ready(None) = next
+                && let Some(timer) = timer.take()
+            {
+                timer.finish();
+            }
+            next
+        })
+        .boxed()
+    }
+
+    fn fetch_optional<'e, 'q: 'e, E>(
+        self,
+        query: E,
+    ) -> BoxFuture<'e, Result<Option<PgRow>, sqlx::Error>>
+    where
+        'c: 'e,
+        E: 'q + Execute<'q, Postgres>,
+    {
+        let in_flight = InFlight::start();
+        let span = query_span(self.repository, query.sql());
+        let timer = QueryTimer::start(self.repository, query.sql());
+        let fut = self.pool.fetch_optional(query);
+        if timer.is_none() && span.is_disabled() {
+            return Box::pin(async move {
+                let _in_flight = in_flight;
+                fut.await
+            });
+        }
+        Box::pin(
+            async move {
+                let _in_flight = in_flight;
+                let result = fut.await;
+                if let Some(timer) = timer {
+                    timer.finish();
+                }
+                result
+            }
+            .instrument(span),
+        )
+    }
+
+    fn prepare_with<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+        parameters: &'e [PgTypeInfo],
+    ) -> BoxFuture<'e, Result<PgStatement<'q>, sqlx::Error>>
+    where
+        'c: 'e,
+    {
+        self.pool.prepare_with(sql, parameters)
+    }
+
+    fn describe<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+    ) -> BoxFuture<'e, Result<Describe<Postgres>, sqlx::Error>>
+    where
+        'c: 'e,
+    {
+        self.pool.describe(sql)
+    }
+}
+
+/// Collapse whitespace and replace string literals with `'?'`.
+///
+/// Bound parameters appear as `$n` and are never logged; this guards
+/// against values formatted directly into the statement text.
+fn redact(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    let mut pending_space = false;
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            pending_space = !out.is_empty();
+            continue;
+        }
+        if pending_space {
+            out.push(' ');
+            pending_space = false;
+        }
+        if c == '\'' {
+            // Skip to the closing quote; `''` is an escaped quote.
+            while let Some(inner) = chars.next() {
+                if inner == '\'' {
+                    if chars.peek() == Some(&'\'') {
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+            }
+            out.push_str("'?'");
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_literals_and_whitespace() {
+        assert_eq!(
+            redact("SELECT *\n    FROM users\n    WHERE name = 'o''brien' AND id = $1"),
+            "SELECT * FROM users WHERE name = '?' AND id = $1"
+        );
+        assert_eq!(redact("  SELECT 1  "), "SELECT 1");
+    }
+
+    #[test]
+    fn test_operation_is_leading_keyword() {
+        assert_eq!(operation("\n  UPDATE files SET name = $1"), "UPDATE");
+        assert_eq!(operation(""), "");
+    }
+}
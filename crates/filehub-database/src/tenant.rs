@@ -0,0 +1,143 @@
+//! Tenant scoping contract for repositories.
+//!
+//! Rows of tenant-owned tables (`users`, `storages`, `folders`, `files`,
+//! `shares`) carry a `tenant_id`. A repository method serving a request
+//! takes the caller's [`TenantScope`] and must:
+//!
+//! 1. restrict every statement on a tenant-owned table to the scope, with
+//!    [`TenantScope::push_predicate`] for built queries or by binding
+//!    [`TenantScope::tenant_id`] to a `tenant_id = $n` clause;
+//! 2. pass what it fetched through [`TenantScope::admit`] or
+//!    [`TenantScope::retain`] before returning it, so a missing predicate
+//!    drops rows instead of leaking them.
+//!
+//! Rows of another tenant are reported as absent, never as forbidden, so
+//! ids cannot be probed across tenants.
+
+use sqlx::{Postgres, QueryBuilder};
+
+use filehub_core::types::TenantId;
+use filehub_entity::file::File;
+use filehub_entity::folder::Folder;
+use filehub_entity::share::Share;
+use filehub_entity::storage::Storage;
+use filehub_entity::user::User;
+
+/// A row that belongs to exactly one tenant.
+pub trait TenantOwned {
+    /// The owning tenant.
+    fn tenant_id(&self) -> TenantId;
+}
+
+macro_rules! tenant_owned {
+    ($($ty:ty),* $(,)?) => {
+        $(impl TenantOwned for $ty {
+            fn tenant_id(&self) -> TenantId {
+                TenantId::from_uuid(self.tenant_id)
+            }
+        })*
+    };
+}
+
+tenant_owned!(User, Storage, Folder, File, Share);
+
+/// The tenant a repository call is restricted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TenantScope {
+    tenant_id: TenantId,
+}
+
+impl TenantScope {
+    /// Scope to one tenant.
+    pub fn new(tenant_id: TenantId) -> Self {
+        Self { tenant_id }
+    }
+
+    /// The tenant in scope.
+    pub fn tenant_id(&self) -> TenantId {
+        self.tenant_id
+    }
+
+    /// Append `AND <column> = <tenant>` to a query being built. `column`
+    /// is the qualified column when the query joins, e.g. `"f.tenant_id"`.
+    pub fn push_predicate(&self, qb: &mut QueryBuilder<'_, Postgres>, column: &str) {
+        qb.push(" AND ")
+            .push(column)
+            .push(" = ")
+            .push_bind(self.tenant_id.into_uuid());
+    }
+
+    /// Whether a row belongs to the tenant in scope.
+    pub fn owns<T: TenantOwned>(&self, row: &T) -> bool {
+        row.tenant_id() == self.tenant_id
+    }
+
+    /// A row fetched by key, if it belongs to the tenant in scope.
+    pub fn admit<T: TenantOwned>(&self, row: Option<T>) -> Option<T> {
+        row.filter(|row| self.owns(row))
+    }
+
+    /// The rows that belong to the tenant in scope. Dropping any is a bug
+    /// in the query, which is logged.
+    pub fn retain<T: TenantOwned>(&self, mut rows: Vec<T>) -> Vec<T> {
+        let fetched = rows.len();
+        rows.retain(|row| self.owns(row));
+        if rows.len() != fetched {
+            tracing::error!(
+                tenant_id = %self.tenant_id,
+                dropped = fetched - rows.len(),
+                "Query returned rows of another tenant"
+            );
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sqlx::Execute;
+
+    struct Row {
+        tenant: TenantId,
+        name: &'static str,
+    }
+
+    impl TenantOwned for Row {
+        fn tenant_id(&self) -> TenantId {
+            self.tenant
+        }
+    }
+
+    fn row(tenant: TenantId, name: &'static str) -> Row {
+        Row { tenant, name }
+    }
+
+    #[test]
+    fn test_scope_never_returns_other_tenants_rows() {
+        let (a, b) = (TenantId::new(), TenantId::new());
+        let rows = || vec![row(a, "a1"), row(b, "b1"), row(a, "a2"), row(b, "b2")];
+
+        let scope = TenantScope::new(a);
+        let names: Vec<_> = scope.retain(rows()).iter().map(|r| r.name).collect();
+        assert_eq!(names, ["a1", "a2"]);
+        let other = TenantScope::new(b).retain(rows());
+        assert!(other.iter().all(|r| r.tenant == b));
+
+        assert!(scope.admit(Some(row(b, "b1"))).is_none());
+        assert!(scope.admit(Some(row(a, "a1"))).is_some());
+        assert!(scope.admit(None::<Row>).is_none());
+    }
+
+    #[test]
+    fn test_push_predicate_binds_tenant() {
+        let mut qb = QueryBuilder::<Postgres>::new("SELECT f.* FROM files f WHERE TRUE");
+        TenantScope::new(TenantId::DEFAULT).push_predicate(&mut qb, "f.tenant_id");
+        qb.push(" ORDER BY f.name");
+        assert_eq!(
+            qb.build().sql(),
+            "SELECT f.* FROM files f WHERE TRUE AND f.tenant_id = $1 ORDER BY f.name"
+        );
+    }
+}
@@ -0,0 +1,49 @@
+//! Archives of audit entries removed from the database by retention.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// One archive of audit entries, written as gzipped NDJSON in the
+/// [`AuditEvent`](super::AuditEvent) schema.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AuditArchive {
+    /// Unique archive identifier.
+    pub id: Uuid,
+    /// Storage holding the archive; `None` for an archive directory.
+    pub storage_id: Option<Uuid>,
+    /// Path within the storage or directory.
+    pub path: String,
+    /// Entries in the archive.
+    pub row_count: i64,
+    /// Compressed size in bytes.
+    pub size_bytes: i64,
+    /// Hex SHA-256 of the compressed archive.
+    pub sha256: String,
+    /// Time of the oldest archived entry.
+    pub first_entry_at: DateTime<Utc>,
+    /// Time of the newest archived entry.
+    pub last_entry_at: DateTime<Utc>,
+    /// When the archive was written.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Data recorded for a written and verified archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAuditArchive {
+    /// Storage holding the archive; `None` for an archive directory.
+    pub storage_id: Option<Uuid>,
+    /// Path within the storage or directory.
+    pub path: String,
+    /// Entries in the archive.
+    pub row_count: i64,
+    /// Compressed size in bytes.
+    pub size_bytes: i64,
+    /// Hex SHA-256 of the compressed archive.
+    pub sha256: String,
+    /// Time of the oldest archived entry.
+    pub first_entry_at: DateTime<Utc>,
+    /// Time of the newest archived entry.
+    pub last_entry_at: DateTime<Utc>,
+}
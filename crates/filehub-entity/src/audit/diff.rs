@@ -0,0 +1,149 @@
+//! Field-level change sets recorded on audit entries.
+
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+
+/// Key under an audit entry's `details` holding the change set.
+pub const CHANGES_KEY: &str = "changes";
+
+/// Fields whose values are never written to the audit log. A change to one
+/// is still recorded, with both sides replaced by [`REDACTED`].
+pub const REDACTED_FIELDS: &[&str] = &[
+    "password",
+    "password_hash",
+    "token",
+    "token_hash",
+    "access_token",
+    "refresh_token",
+    "secret",
+    "secret_key",
+    "api_key",
+    "pepper",
+];
+
+/// Placeholder for a redacted value.
+pub const REDACTED: &str = "[redacted]";
+
+/// Fields that change on every update and say nothing about what changed.
+const IGNORED_FIELDS: &[&str] = &["updated_at"];
+
+/// Changed top-level fields between two versions of an entity, as
+/// `{field: {"old": .., "new": ..}}`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuditDiff(Map<String, Value>);
+
+impl AuditDiff {
+    /// Compare the serialized forms of `before` and `after`. Only fields
+    /// whose value differs are kept; nested objects are compared as a whole.
+    pub fn between<T: Serialize>(before: &T, after: &T) -> Self {
+        let before = serde_json::to_value(before).unwrap_or_default();
+        let after = serde_json::to_value(after).unwrap_or_default();
+        let empty = Map::new();
+        let before = before.as_object().unwrap_or(&empty);
+        let after = after.as_object().unwrap_or(&empty);
+
+        let mut changes = Map::new();
+        let fields = before
+            .keys()
+            .chain(after.keys().filter(|k| !before.contains_key(*k)));
+        for field in fields {
+            if IGNORED_FIELDS.contains(&field.as_str()) {
+                continue;
+            }
+            let old = before.get(field).unwrap_or(&Value::Null);
+            let new = after.get(field).unwrap_or(&Value::Null);
+            if old == new {
+                continue;
+            }
+            let change = if is_redacted(field) {
+                json!({ "old": REDACTED, "new": REDACTED })
+            } else {
+                json!({ "old": old, "new": new })
+            };
+            changes.insert(field.clone(), change);
+        }
+        Self(changes)
+    }
+
+    /// Read the change set back from an audit entry's `details`.
+    pub fn from_details(details: &Value) -> Option<Self> {
+        details
+            .get(CHANGES_KEY)
+            .and_then(Value::as_object)
+            .map(|changes| Self(changes.clone()))
+    }
+
+    /// Whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The change set as audit entry `details`.
+    pub fn into_details(self) -> Value {
+        json!({ CHANGES_KEY: self.0 })
+    }
+
+    /// One `field: old → new` line per change.
+    pub fn summary(&self) -> Vec<String> {
+        self.0
+            .iter()
+            .map(|(field, change)| {
+                format!(
+                    "{field}: {} → {}",
+                    display(&change["old"]),
+                    display(&change["new"])
+                )
+            })
+            .collect()
+    }
+}
+
+fn is_redacted(field: &str) -> bool {
+    REDACTED_FIELDS.contains(&field)
+        || field.ends_with("_hash")
+        || field.ends_with("_token")
+        || field.ends_with("_secret")
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::Null => "(none)".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_keeps_changed_fields_and_redacts_secrets() {
+        let before = json!({
+            "role": "user",
+            "email": "a@example.com",
+            "password_hash": "old",
+            "updated_at": "2024-01-01T00:00:00Z",
+        });
+        let after = json!({
+            "role": "admin",
+            "email": "a@example.com",
+            "password_hash": "new",
+            "updated_at": "2024-01-02T00:00:00Z",
+        });
+        let diff = AuditDiff::between(&before, &after);
+        let mut summary = diff.summary();
+        summary.sort();
+        assert_eq!(
+            summary,
+            vec![
+                "password_hash: [redacted] → [redacted]",
+                "role: user → admin"
+            ]
+        );
+
+        let details = diff.clone().into_details();
+        assert_eq!(AuditDiff::from_details(&details), Some(diff));
+        assert!(AuditDiff::between(&before, &before).is_empty());
+    }
+}
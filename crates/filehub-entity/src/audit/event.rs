@@ -0,0 +1,182 @@
+//! The audit event schema shipped to external sinks.
+//!
+//! Unlike [`AuditLogEntry`], which mirrors the `audit_log` table, this is a
+//! published format that SIEM parsers are written against. Fields may be
+//! added within a version; renaming or removing one needs a new
+//! [`AUDIT_EVENT_VERSION`].
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::model::AuditLogEntry;
+
+/// Version of the audit event schema.
+pub const AUDIT_EVENT_VERSION: u32 = 1;
+
+/// One audit event, in the shipped JSON schema.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    /// Schema version, [`AUDIT_EVENT_VERSION`] when written.
+    pub version: u32,
+    /// Audit entry ID, stable across redeliveries.
+    pub id: Uuid,
+    /// When the action occurred.
+    pub timestamp: DateTime<Utc>,
+    /// The action that was performed (e.g. `"file.upload"`).
+    pub action: String,
+    /// Who performed it.
+    pub actor: AuditEventActor,
+    /// What it was performed on.
+    pub target: AuditEventTarget,
+    /// Action-specific details, `null` if none.
+    pub details: serde_json::Value,
+}
+
+/// The actor of an [`AuditEvent`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEventActor {
+    /// User ID; the nil UUID for an anonymous visitor.
+    pub id: Uuid,
+    /// Whether the actor was an anonymous visitor. Omitted otherwise.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub anonymous: bool,
+    /// The administrator acting as the user, if impersonating. Omitted
+    /// otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonator_id: Option<Uuid>,
+    /// Client IP address, if known.
+    pub ip_address: Option<String>,
+    /// Client User-Agent, if known.
+    pub user_agent: Option<String>,
+}
+
+/// The target of an [`AuditEvent`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEventTarget {
+    /// Resource type (e.g. `"file"`, `"user"`).
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Resource ID, if the action targets a single resource.
+    pub id: Option<Uuid>,
+}
+
+impl From<&AuditLogEntry> for AuditEvent {
+    fn from(entry: &AuditLogEntry) -> Self {
+        Self {
+            version: AUDIT_EVENT_VERSION,
+            id: entry.id,
+            timestamp: entry.created_at,
+            action: entry.action.clone(),
+            actor: AuditEventActor {
+                id: entry.actor_id.unwrap_or(Uuid::nil()),
+                anonymous: entry.actor_id.is_none(),
+                impersonator_id: entry.impersonator_id,
+                ip_address: entry.ip_address.clone(),
+                user_agent: entry.user_agent.clone(),
+            },
+            target: AuditEventTarget {
+                kind: entry.target_type.clone(),
+                id: entry.target_id,
+            },
+            details: entry.details.clone().unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn test_schema_is_stable() {
+        let entry = AuditLogEntry {
+            id: Uuid::nil(),
+            actor_id: Some(Uuid::max()),
+            impersonator_id: None,
+            action: "file.upload".to_string(),
+            target_type: "file".to_string(),
+            target_id: Some(Uuid::nil()),
+            details: Some(json!({ "size": 42 })),
+            ip_address: Some("203.0.113.7".to_string()),
+            user_agent: None,
+            created_at: "2024-05-01T12:00:00Z".parse().unwrap(),
+        };
+        assert_eq!(
+            serde_json::to_value(AuditEvent::from(&entry)).unwrap(),
+            json!({
+                "version": 1,
+                "id": "00000000-0000-0000-0000-000000000000",
+                "timestamp": "2024-05-01T12:00:00Z",
+                "action": "file.upload",
+                "actor": {
+                    "id": "ffffffff-ffff-ffff-ffff-ffffffffffff",
+                    "ip_address": "203.0.113.7",
+                    "user_agent": null
+                },
+                "target": { "type": "file", "id": "00000000-0000-0000-0000-000000000000" },
+                "details": { "size": 42 }
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_details_are_null() {
+        let entry = AuditLogEntry {
+            id: Uuid::new_v4(),
+            actor_id: Some(Uuid::new_v4()),
+            impersonator_id: None,
+            action: "session.terminate".to_string(),
+            target_type: "session".to_string(),
+            target_id: None,
+            details: None,
+            ip_address: None,
+            user_agent: None,
+            created_at: Utc::now(),
+        };
+        let event = AuditEvent::from(&entry);
+        assert!(event.details.is_null());
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(serde_json::from_str::<AuditEvent>(&json).unwrap(), event);
+    }
+
+    #[test]
+    fn test_impersonator_is_named() {
+        let admin = Uuid::new_v4();
+        let entry = AuditLogEntry {
+            id: Uuid::new_v4(),
+            actor_id: Some(Uuid::new_v4()),
+            impersonator_id: Some(admin),
+            action: "file.delete".to_string(),
+            target_type: "file".to_string(),
+            target_id: None,
+            details: None,
+            ip_address: None,
+            user_agent: None,
+            created_at: Utc::now(),
+        };
+        let json = serde_json::to_value(AuditEvent::from(&entry)).unwrap();
+        assert_eq!(json["actor"]["impersonator_id"], json!(admin));
+    }
+
+    #[test]
+    fn test_anonymous_actor_is_flagged() {
+        let entry = AuditLogEntry {
+            id: Uuid::new_v4(),
+            actor_id: None,
+            impersonator_id: None,
+            action: "public.file_downloaded".to_string(),
+            target_type: "file".to_string(),
+            target_id: Some(Uuid::new_v4()),
+            details: None,
+            ip_address: Some("198.51.100.4".to_string()),
+            user_agent: None,
+            created_at: Utc::now(),
+        };
+        let json = serde_json::to_value(AuditEvent::from(&entry)).unwrap();
+        assert_eq!(json["actor"]["id"], json!(Uuid::nil()));
+        assert_eq!(json["actor"]["anonymous"], json!(true));
+    }
+}
@@ -1,5 +1,11 @@
 //! Audit log domain entities.
 
+pub mod archive;
+pub mod diff;
+pub mod event;
 pub mod model;
 
+pub use archive::{AuditArchive, CreateAuditArchive};
+pub use diff::AuditDiff;
+pub use event::{AUDIT_EVENT_VERSION, AuditEvent, AuditEventActor, AuditEventTarget};
 pub use model::AuditLogEntry;
@@ -10,8 +10,14 @@ use uuid::Uuid;
 pub struct AuditLogEntry {
     /// Unique audit entry identifier.
     pub id: Uuid,
-    /// The user who performed the action.
-    pub actor_id: Uuid,
+    /// The user who performed the action; `None` for an anonymous
+    /// visitor.
+    pub actor_id: Option<Uuid>,
+    /// The administrator acting as `actor_id`, if the action was
+    /// performed in an impersonation session.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub impersonator_id: Option<Uuid>,
     /// The action that was performed (e.g., `"file.upload"`, `"session.terminate"`).
     pub action: String,
     /// The type of target resource (e.g., `"file"`, `"user"`, `"session"`).
@@ -31,8 +37,11 @@ pub struct AuditLogEntry {
 /// Data required to create a new audit log entry.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateAuditLogEntry {
-    /// The user who performed the action.
-    pub actor_id: Uuid,
+    /// The user who performed the action; `None` for an anonymous
+    /// visitor.
+    pub actor_id: Option<Uuid>,
+    /// The administrator acting as the actor, if impersonating.
+    pub impersonator_id: Option<Uuid>,
     /// The action performed.
     pub action: String,
     /// Target resource type.
@@ -63,10 +63,20 @@ pub struct ChunkedUpload {
     pub total_chunks: i32,
     /// Array of completed chunk numbers (JSON array).
     pub uploaded_chunks: serde_json::Value,
+    /// SHA-256 of each received chunk, keyed by chunk number.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub chunk_checksums: serde_json::Value,
     /// Expected SHA-256 checksum of the final assembled file.
     pub checksum_sha256: Option<String>,
-    /// Temporary storage path for chunk data.
+    /// Temporary storage path for chunk data; for a direct upload, the
+    /// path the client uploads the object to.
     pub temp_path: String,
+    /// Whether the client uploads the whole file straight to the storage
+    /// backend through a presigned URL instead of sending chunks.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub direct: bool,
     /// Current upload status.
     pub status: String,
     /// When the upload session was created.
@@ -93,6 +103,29 @@ impl ChunkedUpload {
         self.uploaded_count() as i32 >= self.total_chunks
     }
 
+    /// SHA-256 recorded for a received chunk.
+    pub fn chunk_checksum(&self, chunk_number: i32) -> Option<&str> {
+        self.chunk_checksums
+            .get(chunk_number.to_string())
+            .and_then(|v| v.as_str())
+    }
+
+    /// Chunk numbers not yet received, in order.
+    pub fn missing_chunks(&self) -> Vec<i32> {
+        let received = self.uploaded_chunk_numbers();
+        (0..self.total_chunks)
+            .filter(|n| !received.contains(n))
+            .collect()
+    }
+
+    /// Exact size chunk `chunk_number` must have. Every chunk is
+    /// `chunk_size` bytes except the last, which holds the remainder.
+    pub fn expected_chunk_size(&self, chunk_number: i32) -> i64 {
+        let chunk_size = self.chunk_size as i64;
+        let start = chunk_number as i64 * chunk_size;
+        (self.file_size - start).clamp(0, chunk_size)
+    }
+
     /// Calculate the upload progress as a percentage (0-100).
     pub fn progress_percent(&self) -> f64 {
         if self.total_chunks <= 0 {
@@ -101,3 +134,43 @@ impl ChunkedUpload {
         (self.uploaded_count() as f64 / self.total_chunks as f64) * 100.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upload(file_size: i64, chunk_size: i32, received: &[i32]) -> ChunkedUpload {
+        let total_chunks = ((file_size + chunk_size as i64 - 1) / chunk_size as i64).max(1) as i32;
+        ChunkedUpload {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            storage_id: Uuid::new_v4(),
+            target_folder_id: Uuid::new_v4(),
+            file_name: "data.bin".to_string(),
+            file_size,
+            mime_type: None,
+            chunk_size,
+            total_chunks,
+            uploaded_chunks: serde_json::json!(received),
+            chunk_checksums: serde_json::json!({ "0": "abc" }),
+            checksum_sha256: None,
+            temp_path: "temp/uploads/x".to_string(),
+            direct: false,
+            status: ChunkStatus::Uploading.to_string(),
+            created_at: Utc::now(),
+            expires_at: Utc::now(),
+            completed_at: None,
+        }
+    }
+
+    #[test]
+    fn test_missing_chunks_and_sizes() {
+        let upload = upload(25, 10, &[0, 2]);
+        assert_eq!(upload.total_chunks, 3);
+        assert_eq!(upload.missing_chunks(), vec![1]);
+        assert_eq!(upload.expected_chunk_size(0), 10);
+        assert_eq!(upload.expected_chunk_size(2), 5);
+        assert_eq!(upload.chunk_checksum(0), Some("abc"));
+        assert_eq!(upload.chunk_checksum(1), None);
+    }
+}
@@ -3,9 +3,12 @@
 pub mod chunk;
 pub mod metadata;
 pub mod model;
+pub mod naming;
+pub mod saved_search;
 pub mod version;
 
 pub use chunk::{ChunkStatus, ChunkedUpload};
 pub use metadata::FileMetadata;
 pub use model::{CreateFile, File};
-pub use version::FileVersion;
+pub use saved_search::SavedSearch;
+pub use version::{FileVersion, FileVersionInfo};
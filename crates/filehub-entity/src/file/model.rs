@@ -1,6 +1,6 @@
 //! File entity model.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
@@ -10,6 +10,8 @@ use uuid::Uuid;
 pub struct File {
     /// Unique file identifier.
     pub id: Uuid,
+    /// Tenant that owns the file.
+    pub tenant_id: Uuid,
     /// The folder containing this file.
     pub folder_id: Uuid,
     /// The storage backend where the file is physically stored.
@@ -18,8 +20,11 @@ pub struct File {
     pub name: String,
     /// The path within the storage provider.
     pub storage_path: String,
-    /// MIME type of the file.
+    /// MIME type of the file, sniffed from its content when enabled.
     pub mime_type: Option<String>,
+    /// MIME type the client declared on upload.
+    #[serde(default)]
+    pub declared_mime_type: Option<String>,
     /// File size in bytes.
     pub size_bytes: i64,
     /// SHA-256 checksum of the file content.
@@ -36,6 +41,18 @@ pub struct File {
     pub locked_at: Option<DateTime<Utc>>,
     /// The file owner.
     pub owner_id: Uuid,
+    /// When the virus scanner quarantined the file.
+    #[serde(default)]
+    pub quarantined_at: Option<DateTime<Utc>>,
+    /// Why the file was quarantined.
+    #[serde(default)]
+    pub quarantine_reason: Option<String>,
+    /// When the file is deleted automatically, if ever.
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// When the owner was warned of the coming expiry.
+    #[serde(default)]
+    pub expiry_notified_at: Option<DateTime<Utc>>,
     /// When the file was created.
     pub created_at: DateTime<Utc>,
     /// When the file was last updated.
@@ -48,6 +65,25 @@ impl File {
         self.is_locked.unwrap_or(false)
     }
 
+    /// Check if the file has been quarantined.
+    pub fn is_quarantined(&self) -> bool {
+        self.quarantined_at.is_some()
+    }
+
+    /// Check if the file's expiry has passed at `now`.
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+
+    /// Whether the owner should now be warned that the file expires,
+    /// `notice` ahead of time and only once.
+    pub fn expiry_notice_due(&self, now: DateTime<Utc>, notice: Duration) -> bool {
+        self.expiry_notified_at.is_none()
+            && self
+                .expires_at
+                .is_some_and(|at| at > now && at - notice <= now)
+    }
+
     /// Get the file extension (lowercase), if any.
     pub fn extension(&self) -> Option<String> {
         self.name
@@ -56,6 +92,17 @@ impl File {
             .filter(|ext| *ext != self.name)
             .map(|ext| ext.to_lowercase())
     }
+
+    /// Strong entity tag for HTTP caching.
+    ///
+    /// Uses the content checksum when known, otherwise the file ID and
+    /// current version number (which changes on every content update).
+    pub fn etag(&self) -> String {
+        match &self.checksum_sha256 {
+            Some(checksum) => format!("\"{checksum}\""),
+            None => format!("\"{}-v{}\"", self.id, self.current_version),
+        }
+    }
 }
 
 /// Data required to create a new file record.
@@ -69,8 +116,10 @@ pub struct CreateFile {
     pub name: String,
     /// The path within the storage provider.
     pub storage_path: String,
-    /// MIME type.
+    /// MIME type, as resolved from the content.
     pub mime_type: Option<String>,
+    /// MIME type the client declared.
+    pub declared_mime_type: Option<String>,
     /// File size in bytes.
     pub size_bytes: i64,
     /// SHA-256 checksum.
@@ -80,3 +129,58 @@ pub struct CreateFile {
     /// The file owner.
     pub owner_id: Uuid,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(expires_at: Option<DateTime<Utc>>) -> File {
+        let now = Utc::now();
+        File {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            folder_id: Uuid::new_v4(),
+            storage_id: Uuid::new_v4(),
+            name: "scratch.txt".to_string(),
+            storage_path: "scratch.txt".to_string(),
+            mime_type: None,
+            declared_mime_type: None,
+            size_bytes: 0,
+            checksum_sha256: None,
+            metadata: None,
+            current_version: 1,
+            is_locked: None,
+            locked_by: None,
+            locked_at: None,
+            owner_id: Uuid::new_v4(),
+            quarantined_at: None,
+            quarantine_reason: None,
+            expires_at,
+            expiry_notified_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let now = Utc::now();
+        assert!(!file(None).is_expired(now));
+        assert!(!file(Some(now + Duration::minutes(1))).is_expired(now));
+        assert!(file(Some(now)).is_expired(now));
+    }
+
+    #[test]
+    fn test_expiry_notice_is_sent_once_within_the_window() {
+        let now = Utc::now();
+        let notice = Duration::hours(24);
+        assert!(!file(None).expiry_notice_due(now, notice));
+        assert!(!file(Some(now + Duration::hours(25))).expiry_notice_due(now, notice));
+        assert!(!file(Some(now - Duration::hours(1))).expiry_notice_due(now, notice));
+
+        let mut soon = file(Some(now + Duration::hours(2)));
+        assert!(soon.expiry_notice_due(now, notice));
+        soon.expiry_notified_at = Some(now);
+        assert!(!soon.expiry_notice_due(now, notice));
+    }
+}
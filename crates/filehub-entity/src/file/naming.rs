@@ -0,0 +1,44 @@
+//! Alternative names for files whose name is taken.
+
+/// Renames tried before giving up on finding a free name.
+pub const MAX_RENAME_ATTEMPTS: u32 = 100;
+
+/// The `n`th alternative of `name`: `report.pdf` becomes `report (2).pdf`,
+/// `README` becomes `README (2)`. The number goes before the last
+/// extension; a leading dot does not start one.
+pub fn numbered_name(name: &str, n: u32) -> String {
+    match name.rfind('.') {
+        Some(dot) if dot > 0 => format!("{} ({n}){}", &name[..dot], &name[dot..]),
+        _ => format!("{name} ({n})"),
+    }
+}
+
+/// Names to try for a file called `name`, in order: `name` itself, then
+/// its numbered alternatives from 2.
+pub fn candidate_names(name: &str) -> impl Iterator<Item = String> + '_ {
+    std::iter::once(name.to_string())
+        .chain((2..=MAX_RENAME_ATTEMPTS).map(move |n| numbered_name(name, n)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_goes_before_extension() {
+        assert_eq!(numbered_name("report.pdf", 2), "report (2).pdf");
+        assert_eq!(numbered_name("archive.tar.gz", 3), "archive.tar (3).gz");
+        assert_eq!(numbered_name("README", 2), "README (2)");
+        assert_eq!(numbered_name(".env", 2), ".env (2)");
+    }
+
+    #[test]
+    fn test_candidates_start_with_the_name() {
+        let names: Vec<String> = candidate_names("a.txt").take(3).collect();
+        assert_eq!(names, ["a.txt", "a (2).txt", "a (3).txt"]);
+        assert_eq!(
+            candidate_names("a.txt").count(),
+            MAX_RENAME_ATTEMPTS as usize
+        );
+    }
+}
@@ -0,0 +1,25 @@
+//! Saved search entity.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A named file search a user can re-run.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SavedSearch {
+    /// Unique identifier.
+    pub id: Uuid,
+    /// The user who saved the search.
+    pub owner_id: Uuid,
+    /// Display name, unique per owner.
+    pub name: String,
+    /// Full-text query (may be empty).
+    pub query: String,
+    /// Structured filter tree as JSON (`FilterNode`).
+    pub filter: Option<serde_json::Value>,
+    /// When the search was saved.
+    pub created_at: DateTime<Utc>,
+    /// When the search was last changed.
+    pub updated_at: DateTime<Utc>,
+}
@@ -27,3 +27,26 @@ pub struct FileVersion {
     /// Optional comment describing the change.
     pub comment: Option<String>,
 }
+
+impl FileVersion {
+    /// Strong entity tag for HTTP caching of this version's content.
+    pub fn etag(&self) -> String {
+        match &self.checksum_sha256 {
+            Some(checksum) => format!("\"{checksum}\""),
+            None => format!("\"{}-v{}\"", self.file_id, self.version_number),
+        }
+    }
+}
+
+/// A version together with its author, as listed in version history.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FileVersionInfo {
+    /// The version.
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub version: FileVersion,
+    /// Username of `created_by`, if the user still exists.
+    pub author_username: Option<String>,
+    /// Display name of `created_by`, if set.
+    pub author_display_name: Option<String>,
+}
@@ -0,0 +1,120 @@
+//! Folder display metadata value object.
+
+use serde::{Deserialize, Serialize};
+
+use filehub_core::error::AppError;
+
+/// Color labels a folder may carry.
+pub const FOLDER_COLORS: &[&str] = &[
+    "red", "orange", "yellow", "green", "teal", "blue", "purple", "pink", "gray",
+];
+
+/// Icons a folder may carry.
+pub const FOLDER_ICONS: &[&str] = &[
+    "folder",
+    "documents",
+    "images",
+    "video",
+    "audio",
+    "archive",
+    "code",
+    "cad",
+    "shared",
+    "star",
+];
+
+/// How a folder is displayed, stored as JSON alongside it and shown to
+/// everyone who can see the folder.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderMetadata {
+    /// Color label, one of [`FOLDER_COLORS`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Icon, one of [`FOLDER_ICONS`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// Whether the folder is pinned.
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+impl FolderMetadata {
+    /// Check the color and icon against the allowed sets.
+    pub fn validate(&self) -> Result<(), AppError> {
+        if let Some(color) = &self.color
+            && !FOLDER_COLORS.contains(&color.as_str())
+        {
+            return Err(AppError::validation(format!(
+                "Invalid folder color: '{color}'. Expected one of: {}",
+                FOLDER_COLORS.join(", ")
+            )));
+        }
+        if let Some(icon) = &self.icon
+            && !FOLDER_ICONS.contains(&icon.as_str())
+        {
+            return Err(AppError::validation(format!(
+                "Invalid folder icon: '{icon}'. Expected one of: {}",
+                FOLDER_ICONS.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether nothing is set, so the folder displays as default.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Convert to a `serde_json::Value`.
+    pub fn to_json_value(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    /// Parse from a `serde_json::Value`.
+    pub fn from_json_value(value: &serde_json::Value) -> Self {
+        serde_json::from_value(value.clone()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_against_allowed_sets() {
+        let metadata = FolderMetadata {
+            color: Some("teal".to_string()),
+            icon: Some("cad".to_string()),
+            pinned: true,
+        };
+        assert!(metadata.validate().is_ok());
+
+        let bad_color = FolderMetadata {
+            color: Some("#ff0000".to_string()),
+            ..metadata.clone()
+        };
+        assert!(bad_color.validate().unwrap_err().message.contains("color"));
+
+        let bad_icon = FolderMetadata {
+            icon: Some("rocket".to_string()),
+            ..metadata
+        };
+        assert!(bad_icon.validate().unwrap_err().message.contains("icon"));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let metadata = FolderMetadata {
+            color: Some("blue".to_string()),
+            icon: None,
+            pinned: true,
+        };
+        let value = metadata.to_json_value();
+        assert_eq!(
+            value,
+            serde_json::json!({ "color": "blue", "pinned": true })
+        );
+        assert_eq!(FolderMetadata::from_json_value(&value), metadata);
+        assert!(FolderMetadata::from_json_value(&serde_json::json!({})).is_empty());
+    }
+}
@@ -1,7 +1,15 @@
 //! Folder domain entities.
 
+pub mod metadata;
 pub mod model;
+pub mod preferences;
+pub mod retention;
 pub mod tree;
 
-pub use model::{CreateFolder, Folder};
-pub use tree::{FolderNode, FolderTree};
+pub use metadata::{FOLDER_COLORS, FOLDER_ICONS, FolderMetadata};
+pub use model::{CreateFolder, Folder, RebasedFolder, rebase_subtree};
+pub use preferences::{
+    FolderPreferences, FolderSort, FolderSortField, UpdateFolderPreferences, ViewMode,
+};
+pub use retention::{EffectiveRetention, RetentionBlock, RetentionPolicy};
+pub use tree::{FolderNode, FolderTree, FolderTreeRow};
@@ -1,5 +1,7 @@
 //! Folder entity model.
 
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -10,6 +12,8 @@ use uuid::Uuid;
 pub struct Folder {
     /// Unique folder identifier.
     pub id: Uuid,
+    /// Tenant that owns the folder.
+    pub tenant_id: Uuid,
     /// The storage backend this folder resides on.
     pub storage_id: Uuid,
     /// Parent folder ID (null for root folders).
@@ -22,6 +26,15 @@ pub struct Folder {
     pub depth: i32,
     /// The folder owner.
     pub owner_id: Uuid,
+    /// Permission template applied to folders created directly inside
+    /// this one.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub permission_template_id: Option<Uuid>,
+    /// Display metadata, a [`FolderMetadata`](super::FolderMetadata).
+    #[serde(default)]
+    #[sqlx(default)]
+    pub metadata: Option<serde_json::Value>,
     /// When the folder was created.
     pub created_at: DateTime<Utc>,
     /// When the folder was last updated.
@@ -35,6 +48,63 @@ impl Folder {
     }
 }
 
+/// Where one folder of a moved subtree ends up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RebasedFolder {
+    /// Folder ID.
+    pub id: Uuid,
+    /// New materialized path.
+    pub path: String,
+    /// New depth.
+    pub depth: i32,
+}
+
+/// Computes the new path and depth of every folder in the subtree rooted
+/// at `root` when it moves under `new_parent` (`None` for the storage
+/// root). The root comes first, then its descendants parents-first.
+///
+/// Paths are rebuilt from names along `parent_id` links rather than by
+/// swapping the old path prefix, so a subtree with a stale path is
+/// repaired by the move. `descendants` not connected to `root` are ignored.
+pub fn rebase_subtree(
+    root: &Folder,
+    descendants: &[Folder],
+    new_parent: Option<&Folder>,
+) -> Vec<RebasedFolder> {
+    let mut children: HashMap<Uuid, Vec<&Folder>> = HashMap::new();
+    for folder in descendants {
+        if let Some(parent_id) = folder.parent_id {
+            children.entry(parent_id).or_default().push(folder);
+        }
+    }
+
+    let (path, depth) = match new_parent {
+        Some(parent) => (format!("{}/{}", parent.path, root.name), parent.depth + 1),
+        None => (format!("/{}", root.name), 0),
+    };
+    let mut rebased = vec![RebasedFolder {
+        id: root.id,
+        path,
+        depth,
+    }];
+    let mut next = 0;
+    while next < rebased.len() {
+        let (parent_id, parent_path, parent_depth) = {
+            let parent = &rebased[next];
+            (parent.id, parent.path.clone(), parent.depth)
+        };
+        for child in children.remove(&parent_id).unwrap_or_default() {
+            rebased.push(RebasedFolder {
+                id: child.id,
+                path: format!("{parent_path}/{}", child.name),
+                depth: parent_depth + 1,
+            });
+        }
+        next += 1;
+    }
+    rebased
+}
+
 /// Data required to create a new folder.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateFolder {
@@ -51,3 +121,77 @@ pub struct CreateFolder {
     /// The folder owner.
     pub owner_id: Uuid,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folder(name: &str, parent: Option<&Folder>) -> Folder {
+        let (path, depth) = match parent {
+            Some(p) => (format!("{}/{name}", p.path), p.depth + 1),
+            None => (format!("/{name}"), 0),
+        };
+        Folder {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::nil(),
+            storage_id: Uuid::nil(),
+            parent_id: parent.map(|p| p.id),
+            name: name.to_string(),
+            path,
+            depth,
+            owner_id: Uuid::nil(),
+            permission_template_id: None,
+            metadata: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_rebase_deep_subtree() {
+        // /projects/2024/cad/parts/bolts and /projects/2024/cad/drawings,
+        // moved under /archive/old.
+        let projects = folder("projects", None);
+        let year = folder("2024", Some(&projects));
+        let cad = folder("cad", Some(&year));
+        let parts = folder("parts", Some(&cad));
+        let bolts = folder("bolts", Some(&parts));
+        let drawings = folder("drawings", Some(&cad));
+        let archive = folder("archive", None);
+        let old = folder("old", Some(&archive));
+
+        let descendants = vec![bolts.clone(), parts.clone(), drawings.clone()];
+        let rebased = rebase_subtree(&cad, &descendants, Some(&old));
+
+        let by_id: HashMap<Uuid, &RebasedFolder> = rebased.iter().map(|r| (r.id, r)).collect();
+        assert_eq!(rebased.len(), 4);
+        assert_eq!(rebased[0].id, cad.id);
+        assert_eq!(by_id[&cad.id].path, "/archive/old/cad");
+        assert_eq!(by_id[&cad.id].depth, 2);
+        assert_eq!(by_id[&parts.id].path, "/archive/old/cad/parts");
+        assert_eq!(by_id[&bolts.id].path, "/archive/old/cad/parts/bolts");
+        assert_eq!(by_id[&bolts.id].depth, 4);
+        assert_eq!(by_id[&drawings.id].path, "/archive/old/cad/drawings");
+
+        // Back to the storage root.
+        let rebased = rebase_subtree(&cad, &descendants, None);
+        assert_eq!(rebased[0].path, "/cad");
+        assert_eq!(rebased[0].depth, 0);
+        assert!(
+            rebased
+                .iter()
+                .any(|r| r.id == bolts.id && r.path == "/cad/parts/bolts" && r.depth == 2)
+        );
+    }
+
+    #[test]
+    fn test_rebase_repairs_stale_paths() {
+        let root = folder("docs", None);
+        let mut child = folder("specs", Some(&root));
+        child.path = "/old-name/specs".to_string();
+        let target = folder("team", None);
+
+        let rebased = rebase_subtree(&root, &[child.clone()], Some(&target));
+        assert_eq!(rebased[1].path, "/team/docs/specs");
+    }
+}
@@ -0,0 +1,341 @@
+//! Per-user folder view preferences.
+//!
+//! A user's saved [`FolderSort`] orders a folder's subfolders and files
+//! whenever a listing does not ask for an order of its own. Listings page
+//! by offset or by keyset in any sort order; a keyset cursor carries the
+//! sort key of its last row, so it only resumes a listing in the order it
+//! was issued for.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use filehub_core::error::AppError;
+use filehub_core::types::SortDirection;
+use filehub_core::types::cursor::PageCursor;
+
+use crate::file::model::File;
+
+use super::model::Folder;
+
+/// What a folder listing is sorted by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FolderSortField {
+    /// Name.
+    #[default]
+    Name,
+    /// File size; folders have none and are listed by name.
+    Size,
+    /// When the item was created.
+    CreatedAt,
+    /// When the item was last updated.
+    UpdatedAt,
+}
+
+impl FolderSortField {
+    /// Return the field as a string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Size => "size",
+            Self::CreatedAt => "created_at",
+            Self::UpdatedAt => "updated_at",
+        }
+    }
+}
+
+impl FromStr for FolderSortField {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "name" => Ok(Self::Name),
+            "size" => Ok(Self::Size),
+            "created_at" => Ok(Self::CreatedAt),
+            "updated_at" => Ok(Self::UpdatedAt),
+            _ => Err(AppError::validation(format!(
+                "Invalid sort field: '{s}'. Expected one of: name, size, created_at, updated_at"
+            ))),
+        }
+    }
+}
+
+impl TryFrom<String> for FolderSortField {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// How a folder's contents are laid out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewMode {
+    /// One row per item.
+    #[default]
+    List,
+    /// Thumbnails in a grid.
+    Grid,
+}
+
+impl ViewMode {
+    /// Return the view mode as a string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::List => "list",
+            Self::Grid => "grid",
+        }
+    }
+}
+
+impl FromStr for ViewMode {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "list" => Ok(Self::List),
+            "grid" => Ok(Self::Grid),
+            _ => Err(AppError::validation(format!(
+                "Invalid view mode: '{s}'. Expected one of: list, grid"
+            ))),
+        }
+    }
+}
+
+impl TryFrom<String> for ViewMode {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Order of a folder listing; ties are broken by ID in the same
+/// direction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderSort {
+    /// Field sorted by.
+    #[serde(default)]
+    pub field: FolderSortField,
+    /// Sort direction.
+    #[serde(default)]
+    pub direction: SortDirection,
+}
+
+impl FolderSort {
+    /// The order a listing asks for with `sort_by` and `sort_dir`, or
+    /// `None` when it asks for neither. A direction alone sorts by name.
+    pub fn from_params(
+        sort_by: Option<&str>,
+        sort_dir: Option<&str>,
+    ) -> Result<Option<Self>, AppError> {
+        if sort_by.is_none() && sort_dir.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            field: sort_by.map(str::parse).transpose()?.unwrap_or_default(),
+            direction: sort_dir.map(str::parse).transpose()?.unwrap_or_default(),
+        }))
+    }
+
+    /// Column and SQL type of the sort key of files.
+    pub fn file_column(&self) -> (&'static str, &'static str) {
+        match self.field {
+            FolderSortField::Name => ("name", "text"),
+            FolderSortField::Size => ("size_bytes", "bigint"),
+            FolderSortField::CreatedAt => ("created_at", "timestamptz"),
+            FolderSortField::UpdatedAt => ("updated_at", "timestamptz"),
+        }
+    }
+
+    /// Column and SQL type of the sort key of folders.
+    pub fn folder_column(&self) -> (&'static str, &'static str) {
+        match self.field {
+            FolderSortField::Size => ("name", "text"),
+            _ => self.file_column(),
+        }
+    }
+
+    /// `ORDER BY` clause on `column`.
+    pub fn order_by(&self, column: &str) -> String {
+        let direction = self.direction.as_sql();
+        format!("{column} {direction}, id {direction}")
+    }
+
+    /// Predicate resuming after the cursor row, with the cursor's key
+    /// bound at `$key_param` (NULL for the first page) and its ID at the
+    /// parameter after it.
+    pub fn after(&self, (column, sql_type): (&str, &str), key_param: usize) -> String {
+        let op = match self.direction {
+            SortDirection::Asc => ">",
+            SortDirection::Desc => "<",
+        };
+        let id_param = key_param + 1;
+        format!(
+            "(${key_param}::text IS NULL OR ({column}, id) {op} (${key_param}::{sql_type}, ${id_param}))"
+        )
+    }
+
+    /// Check that a cursor's key is of the type this order sorts `column`
+    /// by, so it cannot resume a listing in another order.
+    pub fn check_cursor(
+        &self,
+        (_, sql_type): (&str, &str),
+        cursor: &PageCursor,
+    ) -> Result<(), AppError> {
+        let valid = match sql_type {
+            "bigint" => cursor.key.parse::<i64>().is_ok(),
+            "timestamptz" => DateTime::parse_from_rfc3339(&cursor.key).is_ok(),
+            _ => true,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(AppError::validation(
+                "Cursor does not belong to this sort order",
+            ))
+        }
+    }
+
+    /// Cursor positioned after a file.
+    pub fn file_cursor(&self, file: &File) -> PageCursor {
+        let key = match self.field {
+            FolderSortField::Name => file.name.clone(),
+            FolderSortField::Size => file.size_bytes.to_string(),
+            FolderSortField::CreatedAt => timestamp_key(file.created_at),
+            FolderSortField::UpdatedAt => timestamp_key(file.updated_at),
+        };
+        PageCursor::new(key, file.id)
+    }
+
+    /// Cursor positioned after a folder.
+    pub fn folder_cursor(&self, folder: &Folder) -> PageCursor {
+        let key = match self.field {
+            FolderSortField::Name | FolderSortField::Size => folder.name.clone(),
+            FolderSortField::CreatedAt => timestamp_key(folder.created_at),
+            FolderSortField::UpdatedAt => timestamp_key(folder.updated_at),
+        };
+        PageCursor::new(key, folder.id)
+    }
+}
+
+/// A timestamp as a cursor key, at the database's microsecond precision.
+fn timestamp_key(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// A user's saved view of one folder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct FolderPreferences {
+    /// The user.
+    pub user_id: Uuid,
+    /// The folder.
+    pub folder_id: Uuid,
+    /// Field the folder's contents are sorted by.
+    #[sqlx(try_from = "String")]
+    pub sort_field: FolderSortField,
+    /// Direction the folder's contents are sorted in.
+    #[sqlx(try_from = "String")]
+    pub sort_direction: SortDirection,
+    /// How the folder's contents are laid out.
+    #[sqlx(try_from = "String")]
+    pub view_mode: ViewMode,
+    /// When the preferences were last changed.
+    pub updated_at: DateTime<Utc>,
+}
+
+impl FolderPreferences {
+    /// The saved sort order.
+    pub fn sort(&self) -> FolderSort {
+        FolderSort {
+            field: self.sort_field,
+            direction: self.sort_direction,
+        }
+    }
+}
+
+/// New preferences for a folder; unset fields keep their saved value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateFolderPreferences {
+    /// Field to sort by.
+    #[serde(default)]
+    pub sort_field: Option<FolderSortField>,
+    /// Direction to sort in.
+    #[serde(default)]
+    pub sort_direction: Option<SortDirection>,
+    /// Layout.
+    #[serde(default)]
+    pub view_mode: Option<ViewMode>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_from_params() {
+        assert_eq!(FolderSort::from_params(None, None).unwrap(), None);
+        assert_eq!(
+            FolderSort::from_params(None, Some("DESC")).unwrap(),
+            Some(FolderSort {
+                field: FolderSortField::Name,
+                direction: SortDirection::Desc,
+            })
+        );
+        assert_eq!(
+            FolderSort::from_params(Some("size"), None)
+                .unwrap()
+                .unwrap()
+                .field,
+            FolderSortField::Size
+        );
+        assert!(FolderSort::from_params(Some("owner"), None).is_err());
+        assert!(FolderSort::from_params(None, Some("up")).is_err());
+    }
+
+    #[test]
+    fn test_sql_for_sort() {
+        let sort = FolderSort {
+            field: FolderSortField::Size,
+            direction: SortDirection::Desc,
+        };
+        assert_eq!(
+            sort.order_by(sort.file_column().0),
+            "size_bytes DESC, id DESC"
+        );
+        assert_eq!(
+            sort.after(sort.file_column(), 2),
+            "($2::text IS NULL OR (size_bytes, id) < ($2::bigint, $3))"
+        );
+        // Folders have no size.
+        assert_eq!(sort.folder_column(), ("name", "text"));
+        assert_eq!(
+            FolderSort::default().after(FolderSort::default().folder_column(), 2),
+            "($2::text IS NULL OR (name, id) > ($2::text, $3))"
+        );
+    }
+
+    #[test]
+    fn test_cursor_must_match_sort() {
+        let by_date = FolderSort {
+            field: FolderSortField::CreatedAt,
+            direction: SortDirection::Asc,
+        };
+        let column = by_date.file_column();
+        let name_cursor = PageCursor::new("report.pdf", Uuid::nil());
+        assert!(by_date.check_cursor(column, &name_cursor).is_err());
+
+        let date_cursor = PageCursor::new(timestamp_key(Utc::now()), Uuid::nil());
+        assert!(by_date.check_cursor(column, &date_cursor).is_ok());
+        assert!(
+            FolderSort::default()
+                .check_cursor(FolderSort::default().file_column(), &date_cursor)
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! Folder retention policies.
+//!
+//! A policy on a folder covers the folder and everything below it,
+//! including folders created later: the policies of a folder and all its
+//! ancestors combine into its [`EffectiveRetention`], the longest period
+//! and any legal hold winning.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Retention policy attached to a folder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct RetentionPolicy {
+    /// Folder the policy is attached to.
+    pub folder_id: Uuid,
+    /// Days a file must be kept after it was created; 0 for none.
+    pub retention_days: i32,
+    /// Whether deletion is blocked regardless of age.
+    pub legal_hold: bool,
+    /// Why the hold was placed.
+    pub legal_hold_reason: Option<String>,
+    /// Who last changed the policy.
+    pub updated_by: Option<Uuid>,
+    /// When the policy was attached.
+    pub created_at: DateTime<Utc>,
+    /// When the policy was last changed.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Retention in force for one folder, from its own policy and its
+/// ancestors'.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EffectiveRetention {
+    /// Longest retention period in days.
+    pub retention_days: i32,
+    /// Folder whose legal hold applies, if any.
+    pub legal_hold: Option<Uuid>,
+}
+
+/// Why a deletion is refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionBlock {
+    /// A legal hold on the given folder.
+    LegalHold(Uuid),
+    /// The retention period runs until the given time.
+    RetainedUntil(DateTime<Utc>),
+}
+
+impl EffectiveRetention {
+    /// Combines the policies of a folder and its ancestors.
+    pub fn combine<'a>(policies: impl IntoIterator<Item = &'a RetentionPolicy>) -> Self {
+        policies
+            .into_iter()
+            .fold(Self::default(), |effective, policy| effective.and(policy))
+    }
+
+    /// Adds the policy of a folder below the ones already combined.
+    pub fn and(mut self, policy: &RetentionPolicy) -> Self {
+        self.retention_days = self.retention_days.max(policy.retention_days);
+        if policy.legal_hold && self.legal_hold.is_none() {
+            self.legal_hold = Some(policy.folder_id);
+        }
+        self
+    }
+
+    /// Whether nothing is retained.
+    pub fn is_none(&self) -> bool {
+        self.retention_days <= 0 && self.legal_hold.is_none()
+    }
+
+    /// Why a file created at `created_at` may not be deleted at `now`, if
+    /// it may not.
+    pub fn blocks(&self, created_at: DateTime<Utc>, now: DateTime<Utc>) -> Option<RetentionBlock> {
+        if let Some(folder_id) = self.legal_hold {
+            return Some(RetentionBlock::LegalHold(folder_id));
+        }
+        let until = created_at + Duration::days(self.retention_days.max(0) as i64);
+        (until > now).then_some(RetentionBlock::RetainedUntil(until))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(days: i32, hold: bool) -> RetentionPolicy {
+        RetentionPolicy {
+            folder_id: Uuid::new_v4(),
+            retention_days: days,
+            legal_hold: hold,
+            legal_hold_reason: None,
+            updated_by: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_combine_takes_the_strictest() {
+        let parent = policy(365, false);
+        let child = policy(30, true);
+        let effective = EffectiveRetention::combine([&parent, &child]);
+        assert_eq!(effective.retention_days, 365);
+        assert_eq!(effective.legal_hold, Some(child.folder_id));
+
+        assert!(EffectiveRetention::combine([]).is_none());
+        assert!(EffectiveRetention::combine([&policy(0, false)]).is_none());
+    }
+
+    #[test]
+    fn test_retention_period() {
+        let now = Utc::now();
+        let effective = EffectiveRetention::combine([&policy(10, false)]);
+        assert_eq!(
+            effective.blocks(now - Duration::days(3), now),
+            Some(RetentionBlock::RetainedUntil(now + Duration::days(7)))
+        );
+        assert_eq!(effective.blocks(now - Duration::days(11), now), None);
+    }
+
+    #[test]
+    fn test_legal_hold_ignores_age() {
+        let now = Utc::now();
+        let hold = policy(0, true);
+        let effective = EffectiveRetention::combine([&hold]);
+        assert_eq!(
+            effective.blocks(now - Duration::days(10_000), now),
+            Some(RetentionBlock::LegalHold(hold.folder_id))
+        );
+    }
+}
@@ -1,6 +1,9 @@
 //! Folder tree structures for hierarchical display.
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
 use uuid::Uuid;
 
 /// A node in a folder tree.
@@ -18,6 +21,10 @@ pub struct FolderNode {
     pub child_count: u64,
     /// Number of files in this folder.
     pub file_count: u64,
+    /// Whether some child folders are missing from `children` because the
+    /// tree reached its node cap. `child_count` still counts them.
+    #[serde(default)]
+    pub truncated: bool,
     /// Child folder nodes.
     pub children: Vec<FolderNode>,
 }
@@ -29,6 +36,10 @@ pub struct FolderTree {
     pub roots: Vec<FolderNode>,
     /// Total number of folders in the tree.
     pub total_folders: u64,
+    /// Whether the tree reached its node cap and some folders were left
+    /// out; see [`FolderNode::truncated`].
+    #[serde(default)]
+    pub truncated: bool,
 }
 
 impl FolderTree {
@@ -37,6 +48,190 @@ impl FolderTree {
         Self {
             roots: Vec::new(),
             total_folders: 0,
+            truncated: false,
+        }
+    }
+
+    /// Assembles the tree rooted at the first of `rows`, keeping at most
+    /// `max_nodes` folders.
+    ///
+    /// `rows` must be in breadth-first order, every folder after its
+    /// parent, as returned by the subtree query. Pass up to `max_nodes + 1`
+    /// rows; any beyond `max_nodes` only mark the tree as truncated. Rows
+    /// whose parent is not in the tree are ignored.
+    pub fn assemble(mut rows: Vec<FolderTreeRow>, max_nodes: usize) -> Self {
+        let truncated = rows.len() > max_nodes;
+        rows.truncate(max_nodes);
+        if rows.is_empty() {
+            return Self {
+                truncated,
+                ..Self::empty()
+            };
+        }
+
+        // Parents always come first, so one pass links every child and
+        // building in reverse finishes each node's children before it.
+        let mut index: HashMap<Uuid, usize> = HashMap::with_capacity(rows.len());
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); rows.len()];
+        for (i, row) in rows.iter().enumerate() {
+            if i > 0 {
+                match row.parent_id.and_then(|p| index.get(&p)) {
+                    Some(&parent) => children[parent].push(i),
+                    None => continue,
+                }
+            }
+            index.insert(row.id, i);
+        }
+
+        let mut nodes: Vec<Option<FolderNode>> = Vec::with_capacity(rows.len());
+        nodes.resize_with(rows.len(), || None);
+        let mut total_folders = 0;
+        for (i, row) in rows.into_iter().enumerate().rev() {
+            if i > 0 && index.get(&row.id) != Some(&i) {
+                continue;
+            }
+            let kids: Vec<FolderNode> = children[i]
+                .iter()
+                .filter_map(|&child| nodes[child].take())
+                .collect();
+            let child_count = row.child_count.max(0) as u64;
+            nodes[i] = Some(FolderNode {
+                id: row.id,
+                name: row.name,
+                path: row.path,
+                depth: row.depth,
+                child_count,
+                file_count: row.file_count.max(0) as u64,
+                truncated: (kids.len() as u64) < child_count,
+                children: kids,
+            });
+            total_folders += 1;
+        }
+
+        Self {
+            roots: nodes[0].take().into_iter().collect(),
+            total_folders,
+            truncated,
         }
     }
 }
+
+/// One folder of a subtree, as loaded to build a tree.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FolderTreeRow {
+    /// Folder ID.
+    pub id: Uuid,
+    /// Parent folder ID.
+    pub parent_id: Option<Uuid>,
+    /// Folder name.
+    pub name: String,
+    /// Full path.
+    pub path: String,
+    /// Depth level.
+    pub depth: i32,
+    /// Number of child folders.
+    pub child_count: i64,
+    /// Number of files in the folder.
+    pub file_count: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A breadth-first subtree of `count` folders where each folder has up
+    /// to `fanout` children, as the subtree query would return it.
+    fn fixture(count: usize, fanout: usize) -> Vec<FolderTreeRow> {
+        let mut rows: Vec<FolderTreeRow> = Vec::with_capacity(count);
+        for i in 0..count {
+            let parent = (i > 0).then(|| (i - 1) / fanout);
+            let (parent_id, path, depth) = match parent {
+                Some(p) => (
+                    Some(rows[p].id),
+                    format!("{}/f{i}", rows[p].path),
+                    rows[p].depth + 1,
+                ),
+                None => (None, "/root".to_string(), 0),
+            };
+            let children = (fanout * i + 1..=fanout * i + fanout)
+                .filter(|&c| c < count)
+                .count();
+            rows.push(FolderTreeRow {
+                id: Uuid::new_v4(),
+                parent_id,
+                name: format!("f{i}"),
+                path,
+                depth,
+                child_count: children as i64,
+                file_count: (i % 3) as i64,
+            });
+        }
+        rows
+    }
+
+    fn count_nodes(node: &FolderNode) -> u64 {
+        1 + node.children.iter().map(count_nodes).sum::<u64>()
+    }
+
+    #[test]
+    fn test_assemble_small_tree() {
+        let rows = fixture(7, 2);
+        let root_id = rows[0].id;
+        let tree = FolderTree::assemble(rows, 100);
+
+        assert!(!tree.truncated);
+        assert_eq!(tree.total_folders, 7);
+        let root = &tree.roots[0];
+        assert_eq!(root.id, root_id);
+        assert_eq!(root.child_count, 2);
+        assert_eq!(root.children[0].name, "f1");
+        assert_eq!(root.children[1].children[1].name, "f6");
+        assert_eq!(root.children[1].children[1].file_count, 0);
+        assert!(!root.truncated);
+    }
+
+    #[test]
+    fn test_assemble_truncates_at_cap() {
+        let rows = fixture(10, 3);
+        let tree = FolderTree::assemble(rows, 6);
+
+        assert!(tree.truncated);
+        assert_eq!(tree.total_folders, 6);
+        let root = &tree.roots[0];
+        assert!(!root.truncated);
+        // f1 keeps f4 and f5 of its three children; f2 and f3 keep none.
+        assert_eq!(root.children[0].children.len(), 2);
+        assert_eq!(root.children[0].child_count, 3);
+        assert!(root.children[0].truncated);
+        assert!(root.children[1].truncated);
+    }
+
+    #[test]
+    fn test_assemble_empty_and_orphans() {
+        assert!(FolderTree::assemble(Vec::new(), 10).roots.is_empty());
+
+        let mut rows = fixture(3, 2);
+        rows[2].parent_id = Some(Uuid::new_v4());
+        let tree = FolderTree::assemble(rows, 10);
+        assert_eq!(tree.total_folders, 2);
+    }
+
+    #[test]
+    fn test_assemble_deep_chain() {
+        let tree = FolderTree::assemble(fixture(2_000, 1), 5_000);
+        assert_eq!(tree.total_folders, 2_000);
+        assert_eq!(count_nodes(&tree.roots[0]), 2_000);
+    }
+
+    #[test]
+    fn test_assemble_100k_nodes() {
+        let rows = fixture(100_000, 10);
+        let tree = FolderTree::assemble(rows.clone(), 100_000);
+        assert!(!tree.truncated);
+        assert_eq!(count_nodes(&tree.roots[0]), 100_000);
+
+        let capped = FolderTree::assemble(rows, 10_000);
+        assert!(capped.truncated);
+        assert_eq!(count_nodes(&capped.roots[0]), 10_000);
+    }
+}
@@ -3,6 +3,10 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use filehub_core::config::ThumbnailFormat;
+
+use crate::audit::AuditEvent;
+
 /// Typed payloads for known job types.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "job_type")]
@@ -22,18 +26,54 @@ pub enum JobPayload {
     CadConversion {
         /// File ID to convert.
         file_id: Uuid,
-        /// Source format.
-        source_format: String,
-        /// Target format.
-        target_format: String,
+        /// File name, as shown to users.
+        file_name: String,
+        /// Local path of the staged source file.
+        source_path: String,
+        /// Output formats requested.
+        #[serde(default)]
+        targets: Vec<String>,
+        /// Conversion profile; the configured default if unset.
+        #[serde(default)]
+        profile: Option<String>,
+        /// Primary file of an assembly.
+        #[serde(default)]
+        primary_name: Option<String>,
+        /// Directory the outputs go under, one subdirectory per profile.
+        #[serde(default)]
+        output_dir: Option<String>,
     },
     /// Generate a thumbnail.
     #[serde(rename = "thumbnail_generation")]
     ThumbnailGeneration {
         /// File ID.
         file_id: Uuid,
-        /// Requested sizes.
+        /// Longest edges to render, in pixels.
         sizes: Vec<u32>,
+        /// Formats to render each size in; empty renders the configured
+        /// formats.
+        #[serde(default)]
+        formats: Vec<ThumbnailFormat>,
+    },
+    /// Render page previews of a PDF or office document.
+    #[serde(rename = "document_preview")]
+    DocumentPreview {
+        /// File ID.
+        file_id: Uuid,
+    },
+    /// Send a scheduled admin broadcast.
+    #[serde(rename = "admin_broadcast")]
+    AdminBroadcast {
+        /// Broadcast ID.
+        broadcast_id: Uuid,
+    },
+    /// Ship an audit event to an external sink.
+    #[serde(rename = "audit_ship")]
+    AuditShip {
+        /// Name of the configured sink.
+        sink: String,
+        /// The event.
+        event: AuditEvent,
     },
     /// Clean up expired sessions.
     #[serde(rename = "session_cleanup")]
@@ -56,6 +96,9 @@ pub enum JobPayload {
     /// Clean up old notifications.
     #[serde(rename = "notification_cleanup")]
     NotificationCleanup,
+    /// Record a public access to a share link.
+    #[serde(rename = "share_access")]
+    ShareAccess(crate::share::CreateShareAccess),
     /// Cross-storage file transfer.
     #[serde(rename = "storage_transfer")]
     StorageTransfer {
@@ -66,4 +109,10 @@ pub enum JobPayload {
         /// Destination storage.
         target_storage_id: Uuid,
     },
+    /// Run one pass of a storage migration.
+    #[serde(rename = "storage_migration")]
+    StorageMigration {
+        /// Migration ID.
+        migration_id: Uuid,
+    },
 }
@@ -16,4 +16,5 @@ pub mod presence;
 pub mod session;
 pub mod share;
 pub mod storage;
+pub mod tag;
 pub mod user;
@@ -6,4 +6,7 @@ pub mod pool;
 
 pub use checkout::CheckoutToken;
 pub use model::LicenseCheckout;
-pub use pool::{PoolSnapshot, PoolStatus};
+pub use pool::{
+    CreatePoolSnapshot, FeatureSnapshot, PoolSnapshot, PoolStatus, PoolUtilization,
+    UtilizationPoint,
+};
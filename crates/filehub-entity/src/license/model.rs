@@ -26,6 +26,12 @@ pub struct LicenseCheckout {
     pub ip_address: Option<String>,
     /// Whether this checkout is still active.
     pub is_active: Option<bool>,
+    /// End of the borrow window for an offline borrow (None = session checkout).
+    pub borrowed_until: Option<DateTime<Utc>>,
+    /// When the lease was last renewed.
+    pub renewed_at: Option<DateTime<Utc>>,
+    /// When renewal failed and the seat was marked lost.
+    pub lost_at: Option<DateTime<Utc>>,
 }
 
 impl LicenseCheckout {
@@ -33,4 +39,9 @@ impl LicenseCheckout {
     pub fn is_currently_active(&self) -> bool {
         self.is_active.unwrap_or(false) && self.checked_in_at.is_none()
     }
+
+    /// Check if this checkout is an offline borrow.
+    pub fn is_borrow(&self) -> bool {
+        self.borrowed_until.is_some()
+    }
 }
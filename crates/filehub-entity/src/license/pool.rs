@@ -3,6 +3,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use sqlx::types::Json;
 use uuid::Uuid;
 
 /// Live status of the license seat pool.
@@ -16,12 +17,39 @@ pub struct PoolStatus {
     pub available: i32,
     /// Seats reserved for admins.
     pub admin_reserved: i32,
+    /// Seats held by offline borrows (included in `checked_out`).
+    #[serde(default)]
+    pub borrowed: i32,
     /// Number of active sessions in the database.
     pub active_sessions: i32,
     /// Whether drift was detected between pool and sessions.
     pub drift_detected: bool,
     /// Usage as a percentage.
     pub usage_percent: f64,
+    /// Per-feature breakdown; the fields above are totals across these.
+    #[serde(default)]
+    pub features: Vec<FeaturePoolStatus>,
+}
+
+/// Live status of one licensed feature's seat pool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeaturePoolStatus {
+    /// Feature name on the license server.
+    pub feature: String,
+    /// Total seats for the feature (-1 for a star license).
+    pub total_seats: i32,
+    /// Seats currently checked out.
+    pub checked_out: i32,
+    /// Seats available for checkout.
+    pub available: i32,
+    /// Seats reserved for admins.
+    pub admin_reserved: i32,
+    /// Seats held by offline borrows (included in `checked_out`).
+    pub borrowed: i32,
+    /// Active checkouts recorded in the database.
+    pub active_sessions: i32,
+    /// Usage as a percentage.
+    pub usage_percent: f64,
 }
 
 impl PoolStatus {
@@ -59,4 +87,319 @@ pub struct PoolSnapshot {
     pub source: String,
     /// When the snapshot was taken.
     pub created_at: DateTime<Utc>,
+    /// Seats held by offline borrows (included in `checked_out`).
+    pub borrowed: i32,
+    /// Seats the allocator counted as checked out minus the active
+    /// sessions in the database, before any correction.
+    pub drift: i32,
+    /// Per-feature breakdown; empty for pools without features.
+    pub features: Json<Vec<FeatureSnapshot>>,
+}
+
+/// One feature's seat counts in a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureSnapshot {
+    /// Feature name on the license server.
+    pub feature: String,
+    /// Total seats for the feature (-1 for a star license).
+    pub total_seats: i32,
+    /// Seats checked out.
+    pub checked_out: i32,
+    /// Seats available for checkout.
+    pub available: i32,
+    /// Seats held by offline borrows.
+    pub borrowed: i32,
+    /// Active checkouts recorded in the database.
+    pub active_sessions: i32,
+}
+
+impl From<&FeaturePoolStatus> for FeatureSnapshot {
+    fn from(status: &FeaturePoolStatus) -> Self {
+        Self {
+            feature: status.feature.clone(),
+            total_seats: status.total_seats,
+            checked_out: status.checked_out,
+            available: status.available,
+            borrowed: status.borrowed,
+            active_sessions: status.active_sessions,
+        }
+    }
+}
+
+/// Data for recording a pool snapshot.
+#[derive(Debug, Clone)]
+pub struct CreatePoolSnapshot {
+    /// Total seats.
+    pub total_seats: i32,
+    /// Checked out seats.
+    pub checked_out: i32,
+    /// Available seats.
+    pub available: i32,
+    /// Admin reserved seats.
+    pub admin_reserved: i32,
+    /// Seats held by offline borrows.
+    pub borrowed: i32,
+    /// Active sessions.
+    pub active_sessions: i32,
+    /// Allocator count minus database count.
+    pub drift: i32,
+    /// Whether drift was detected, in total or for any feature.
+    pub drift_detected: bool,
+    /// Details about drift.
+    pub drift_detail: Option<serde_json::Value>,
+    /// Per-feature breakdown.
+    pub features: Vec<FeatureSnapshot>,
+    /// What took the snapshot.
+    pub source: String,
+}
+
+impl CreatePoolSnapshot {
+    /// A snapshot of a live pool status.
+    pub fn from_status(
+        status: &PoolStatus,
+        drift_detail: Option<serde_json::Value>,
+        source: &str,
+    ) -> Self {
+        Self {
+            total_seats: status.total_seats,
+            checked_out: status.checked_out,
+            available: status.available,
+            admin_reserved: status.admin_reserved,
+            borrowed: status.borrowed,
+            active_sessions: status.active_sessions,
+            drift: status.checked_out.saturating_sub(status.active_sessions),
+            drift_detected: status.drift_detected,
+            drift_detail,
+            features: status.features.iter().map(FeatureSnapshot::from).collect(),
+            source: source.to_string(),
+        }
+    }
+}
+
+/// Seat utilization over a time window, shaped for charting: the current
+/// state plus one point per snapshot, oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolUtilization {
+    /// Start of the window.
+    pub from: DateTime<Utc>,
+    /// End of the window.
+    pub to: DateTime<Utc>,
+    /// Live pool status, if a license pool is configured.
+    pub current: Option<PoolStatus>,
+    /// Every feature appearing in `points`, sorted.
+    pub features: Vec<String>,
+    /// Snapshots in the window.
+    pub points: Vec<UtilizationPoint>,
+}
+
+/// Seat counts at one point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UtilizationPoint {
+    /// When the snapshot was taken.
+    pub at: DateTime<Utc>,
+    /// Total seats.
+    pub total_seats: i32,
+    /// Checked out seats.
+    pub checked_out: i32,
+    /// Available seats.
+    pub available: i32,
+    /// Seats held by offline borrows.
+    pub borrowed: i32,
+    /// Admin reserved seats.
+    pub admin_reserved: i32,
+    /// Active sessions in the database.
+    pub active_sessions: i32,
+    /// Allocator count minus database count.
+    pub drift: i32,
+    /// Usage as a percentage; 0 for star licenses.
+    pub usage_percent: f64,
+    /// What took the snapshot.
+    pub source: String,
+    /// Per-feature breakdown.
+    pub features: Vec<FeatureSnapshot>,
+}
+
+impl From<PoolSnapshot> for UtilizationPoint {
+    fn from(snapshot: PoolSnapshot) -> Self {
+        Self {
+            at: snapshot.created_at,
+            total_seats: snapshot.total_seats,
+            checked_out: snapshot.checked_out,
+            available: snapshot.available,
+            borrowed: snapshot.borrowed,
+            admin_reserved: snapshot.admin_reserved,
+            active_sessions: snapshot.active_sessions,
+            drift: snapshot.drift,
+            usage_percent: usage_percent(snapshot.checked_out, snapshot.total_seats),
+            source: snapshot.source,
+            features: snapshot.features.0,
+        }
+    }
+}
+
+impl PoolUtilization {
+    /// Builds the utilization of a window from its snapshots.
+    pub fn new(
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        current: Option<PoolStatus>,
+        snapshots: Vec<PoolSnapshot>,
+    ) -> Self {
+        let mut points: Vec<UtilizationPoint> =
+            snapshots.into_iter().map(UtilizationPoint::from).collect();
+        points.sort_by_key(|p| p.at);
+        let mut features: Vec<String> = points
+            .iter()
+            .flat_map(|p| p.features.iter().map(|f| f.feature.clone()))
+            .collect();
+        features.sort();
+        features.dedup();
+        Self {
+            from,
+            to,
+            current,
+            features,
+            points,
+        }
+    }
+
+    /// The points as CSV, one row per point. Each feature adds
+    /// `<feature>_checked_out`, `<feature>_available` and
+    /// `<feature>_borrowed` columns, empty where the point lacks it.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "at,total_seats,checked_out,available,borrowed,admin_reserved,\
+             active_sessions,drift,usage_percent,source",
+        );
+        for feature in &self.features {
+            out.push_str(&format!(
+                ",{0}_checked_out,{0}_available,{0}_borrowed",
+                csv_field(feature)
+            ));
+        }
+        out.push('\n');
+
+        for p in &self.points {
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{:.1},{}",
+                p.at.to_rfc3339(),
+                p.total_seats,
+                p.checked_out,
+                p.available,
+                p.borrowed,
+                p.admin_reserved,
+                p.active_sessions,
+                p.drift,
+                p.usage_percent,
+                csv_field(&p.source)
+            ));
+            for feature in &self.features {
+                match p.features.iter().find(|f| &f.feature == feature) {
+                    Some(f) => out.push_str(&format!(
+                        ",{},{},{}",
+                        f.checked_out, f.available, f.borrowed
+                    )),
+                    None => out.push_str(",,,"),
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Checked-out seats as a percentage of the total; 0 without a limit.
+fn usage_percent(checked_out: i32, total_seats: i32) -> f64 {
+    if total_seats <= 0 {
+        0.0
+    } else {
+        checked_out as f64 / total_seats as f64 * 100.0
+    }
+}
+
+/// Quotes a CSV field if it needs it.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(minute: u32, checked_out: i32, features: Vec<FeatureSnapshot>) -> PoolSnapshot {
+        PoolSnapshot {
+            id: Uuid::new_v4(),
+            total_seats: 10,
+            checked_out,
+            available: 10 - checked_out,
+            admin_reserved: 1,
+            active_sessions: checked_out,
+            drift_detected: Some(false),
+            drift_detail: None,
+            source: "sync".to_string(),
+            created_at: DateTime::parse_from_rfc3339(&format!("2026-01-01T00:{minute:02}:00Z"))
+                .unwrap()
+                .with_timezone(&Utc),
+            borrowed: 0,
+            drift: 0,
+            features: Json(features),
+        }
+    }
+
+    fn feature(name: &str, checked_out: i32) -> FeatureSnapshot {
+        FeatureSnapshot {
+            feature: name.to_string(),
+            total_seats: 5,
+            checked_out,
+            available: 5 - checked_out,
+            borrowed: 1,
+            active_sessions: checked_out,
+        }
+    }
+
+    #[test]
+    fn test_points_are_ordered_and_features_collected() {
+        let utilization = PoolUtilization::new(
+            Utc::now(),
+            Utc::now(),
+            None,
+            vec![
+                snapshot(5, 4, vec![feature("viewer", 1), feature("cad", 3)]),
+                snapshot(1, 5, vec![feature("cad", 5)]),
+            ],
+        );
+        assert_eq!(utilization.features, ["cad", "viewer"]);
+        assert_eq!(utilization.points[0].checked_out, 5);
+        assert_eq!(utilization.points[0].usage_percent, 50.0);
+        assert_eq!(utilization.points[1].checked_out, 4);
+    }
+
+    #[test]
+    fn test_csv_has_a_column_group_per_feature() {
+        let utilization = PoolUtilization::new(
+            Utc::now(),
+            Utc::now(),
+            None,
+            vec![
+                snapshot(1, 5, vec![feature("cad", 5)]),
+                snapshot(2, 4, vec![feature("cad", 3), feature("viewer", 1)]),
+            ],
+        );
+        let csv = utilization.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with(
+            "source,cad_checked_out,cad_available,cad_borrowed,\
+             viewer_checked_out,viewer_available,viewer_borrowed"
+        ));
+        assert_eq!(
+            lines[1],
+            "2026-01-01T00:01:00+00:00,10,5,5,0,1,5,0,50.0,sync,5,0,1,,,"
+        );
+        assert!(lines[2].ends_with(",3,2,1,1,4,1"));
+    }
 }
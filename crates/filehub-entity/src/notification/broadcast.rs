@@ -0,0 +1,274 @@
+//! Admin broadcast audiences, delivery receipts and acknowledgement
+//! reports.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use filehub_core::error::AppError;
+
+use crate::user::role::UserRole;
+
+use super::model::AdminBroadcast;
+
+/// Who an admin broadcast is for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BroadcastTarget {
+    /// Every user.
+    All,
+    /// Users with this role.
+    Role(UserRole),
+    /// These users.
+    Users(Vec<Uuid>),
+}
+
+impl BroadcastTarget {
+    /// Parses a target as written by an admin: `all`, `role:<role>` or
+    /// `users` together with `user_ids`.
+    pub fn parse(target: &str, user_ids: &[Uuid]) -> Result<Self, AppError> {
+        let parsed = match target.split_once(':') {
+            Some(("role", role)) => Self::Role(role.parse()?),
+            None if target == "all" => Self::All,
+            None if target == "users" => Self::Users(user_ids.to_vec()),
+            _ => {
+                return Err(AppError::validation(format!(
+                    "Unknown broadcast target '{target}', expected 'all', 'role:<role>' or 'users'"
+                )));
+            }
+        };
+        if let Self::Users(ids) = &parsed
+            && ids.is_empty()
+        {
+            return Err(AppError::validation(
+                "A broadcast to 'users' needs at least one user id",
+            ));
+        }
+        if !matches!(parsed, Self::Users(_)) && !user_ids.is_empty() {
+            return Err(AppError::validation(
+                "User ids are only accepted with the 'users' target",
+            ));
+        }
+        Ok(parsed)
+    }
+
+    /// Reads the target back from the `target` and `target_user_ids`
+    /// columns.
+    pub fn from_columns(target: &str, user_ids: Option<&[Uuid]>) -> Option<Self> {
+        match target.split_once(':') {
+            Some(("role", role)) => role.parse().ok().map(Self::Role),
+            None if target == "all" => Some(Self::All),
+            None if target == "users" => Some(Self::Users(user_ids.unwrap_or_default().to_vec())),
+            _ => None,
+        }
+    }
+
+    /// Value of the `target` column.
+    pub fn as_column(&self) -> String {
+        match self {
+            Self::All => "all".to_string(),
+            Self::Role(role) => format!("role:{role}"),
+            Self::Users(_) => "users".to_string(),
+        }
+    }
+
+    /// Value of the `target_user_ids` column.
+    pub fn user_ids(&self) -> Option<&[Uuid]> {
+        match self {
+            Self::Users(ids) => Some(ids),
+            _ => None,
+        }
+    }
+
+    /// Whether a user with `role` is in the audience.
+    pub fn includes(&self, user_id: Uuid, role: &UserRole) -> bool {
+        match self {
+            Self::All => true,
+            Self::Role(target) => target == role,
+            Self::Users(ids) => ids.contains(&user_id),
+        }
+    }
+}
+
+/// The content of an admin broadcast.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BroadcastMessage {
+    /// Title.
+    pub title: String,
+    /// Message body.
+    pub message: String,
+    /// Severity: "info", "warning", "critical".
+    pub severity: String,
+    /// Whether clients keep it on screen until dismissed.
+    pub persistent: bool,
+}
+
+/// One user's delivery of, and acknowledgement of, a broadcast.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BroadcastReceipt {
+    /// The broadcast.
+    pub broadcast_id: Uuid,
+    /// The recipient.
+    pub user_id: Uuid,
+    /// When it was first pushed to one of the user's connections.
+    pub delivered_at: DateTime<Utc>,
+    /// When the user acknowledged it.
+    pub acknowledged_at: Option<DateTime<Utc>>,
+}
+
+/// Who has and hasn't seen a broadcast.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BroadcastAckReport {
+    /// The broadcast.
+    pub broadcast_id: Uuid,
+    /// Whether acknowledgement is required.
+    pub require_ack: bool,
+    /// Number of users in the audience.
+    pub recipients: usize,
+    /// Users it was delivered to.
+    pub delivered: Vec<Uuid>,
+    /// Users who acknowledged it.
+    pub acknowledged: Vec<Uuid>,
+    /// Users in the audience who have not yet seen it: not acknowledged
+    /// when acknowledgement is required, otherwise not delivered.
+    pub pending: Vec<Uuid>,
+}
+
+impl BroadcastAckReport {
+    /// Builds the report from the current audience and the receipts.
+    /// Receipts of users who have since left the audience still count as
+    /// delivered and acknowledged.
+    pub fn build(
+        broadcast: &AdminBroadcast,
+        audience: &[Uuid],
+        receipts: &[BroadcastReceipt],
+    ) -> Self {
+        let by_user: HashMap<Uuid, &BroadcastReceipt> =
+            receipts.iter().map(|r| (r.user_id, r)).collect();
+        let delivered: Vec<Uuid> = receipts.iter().map(|r| r.user_id).collect();
+        let acknowledged: Vec<Uuid> = receipts
+            .iter()
+            .filter(|r| r.acknowledged_at.is_some())
+            .map(|r| r.user_id)
+            .collect();
+        let pending = audience
+            .iter()
+            .copied()
+            .filter(|user_id| match by_user.get(user_id) {
+                None => true,
+                Some(receipt) => broadcast.require_ack && receipt.acknowledged_at.is_none(),
+            })
+            .collect();
+
+        Self {
+            broadcast_id: broadcast.id,
+            require_ack: broadcast.require_ack,
+            recipients: audience.len(),
+            delivered,
+            acknowledged,
+            pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn broadcast(require_ack: bool) -> AdminBroadcast {
+        AdminBroadcast {
+            id: Uuid::new_v4(),
+            admin_id: Uuid::new_v4(),
+            target: "all".to_string(),
+            target_user_ids: None,
+            title: "Maintenance".to_string(),
+            message: "Down at 22:00".to_string(),
+            severity: "warning".to_string(),
+            persistent: false,
+            action_type: None,
+            action_payload: None,
+            delivered_count: 0,
+            require_ack,
+            scheduled_at: None,
+            sent_at: Some(Utc::now()),
+            expires_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn receipt(broadcast: &AdminBroadcast, user_id: Uuid, acked: bool) -> BroadcastReceipt {
+        BroadcastReceipt {
+            broadcast_id: broadcast.id,
+            user_id,
+            delivered_at: Utc::now(),
+            acknowledged_at: acked.then(Utc::now),
+        }
+    }
+
+    #[test]
+    fn test_parse_target() {
+        let id = Uuid::new_v4();
+        assert_eq!(
+            BroadcastTarget::parse("all", &[]).unwrap(),
+            BroadcastTarget::All
+        );
+        assert_eq!(
+            BroadcastTarget::parse("role:manager", &[]).unwrap(),
+            BroadcastTarget::Role(UserRole::Manager)
+        );
+        assert_eq!(
+            BroadcastTarget::parse("users", &[id]).unwrap(),
+            BroadcastTarget::Users(vec![id])
+        );
+        assert!(BroadcastTarget::parse("users", &[]).is_err());
+        assert!(BroadcastTarget::parse("all", &[id]).is_err());
+        assert!(BroadcastTarget::parse("role:owner", &[]).is_err());
+        assert!(BroadcastTarget::parse("group:admins", &[]).is_err());
+    }
+
+    #[test]
+    fn test_target_round_trips_through_columns() {
+        let id = Uuid::new_v4();
+        for target in [
+            BroadcastTarget::All,
+            BroadcastTarget::Role(UserRole::Viewer),
+            BroadcastTarget::Users(vec![id]),
+        ] {
+            let read = BroadcastTarget::from_columns(&target.as_column(), target.user_ids());
+            assert_eq!(read, Some(target));
+        }
+        assert_eq!(BroadcastTarget::from_columns("group:admins", None), None);
+    }
+
+    #[test]
+    fn test_includes() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        assert!(BroadcastTarget::All.includes(a, &UserRole::Viewer));
+        let admins = BroadcastTarget::Role(UserRole::Admin);
+        assert!(admins.includes(a, &UserRole::Admin));
+        assert!(!admins.includes(a, &UserRole::Manager));
+        let users = BroadcastTarget::Users(vec![a]);
+        assert!(users.includes(a, &UserRole::Viewer));
+        assert!(!users.includes(b, &UserRole::Admin));
+    }
+
+    #[test]
+    fn test_ack_report() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        let acked = broadcast(true);
+        let receipts = [receipt(&acked, a, true), receipt(&acked, b, false)];
+        let report = BroadcastAckReport::build(&acked, &[a, b, c], &receipts);
+        assert_eq!(report.recipients, 3);
+        assert_eq!(report.delivered, [a, b]);
+        assert_eq!(report.acknowledged, [a]);
+        assert_eq!(report.pending, [b, c]);
+
+        let plain = broadcast(false);
+        let receipts = [receipt(&plain, a, false)];
+        let report = BroadcastAckReport::build(&plain, &[a, b], &receipts);
+        assert_eq!(report.pending, [b]);
+    }
+}
@@ -22,6 +22,8 @@ pub enum NotificationCategory {
     License,
     /// Job completion notifications.
     Job,
+    /// Security alerts, e.g. a quarantined upload.
+    Security,
 }
 
 impl NotificationCategory {
@@ -36,6 +38,7 @@ impl NotificationCategory {
             Self::Broadcast => "broadcast",
             Self::License => "license",
             Self::Job => "job",
+            Self::Security => "security",
         }
     }
 }
@@ -0,0 +1,121 @@
+//! Pending notification digest entity.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Notifications of one event type and scope collected for a user until
+/// the end of their digest window.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NotificationDigest {
+    /// The recipient user.
+    pub user_id: Uuid,
+    /// Category of the collected notifications.
+    pub category: String,
+    /// Event type of the collected notifications.
+    pub event_type: String,
+    /// Kind of resource the notifications are grouped under (e.g. "folder").
+    pub scope_type: String,
+    /// The resource the notifications are grouped under.
+    pub scope_id: Uuid,
+    /// Display name of the scope, if known.
+    pub scope_name: Option<String>,
+    /// Number of notifications collected.
+    pub item_count: i32,
+    /// Title of the most recent notification.
+    pub last_title: String,
+    /// Message of the most recent notification.
+    pub last_message: String,
+    /// Actor of the most recent notification.
+    pub last_actor_id: Option<Uuid>,
+    /// When the first notification was collected.
+    pub first_at: DateTime<Utc>,
+    /// When the most recent notification was collected.
+    pub last_at: DateTime<Utc>,
+    /// When the digest is due for delivery.
+    pub flush_at: DateTime<Utc>,
+}
+
+impl NotificationDigest {
+    /// Title of the notification the digest is delivered as.
+    ///
+    /// A digest holding a single notification keeps its original title.
+    pub fn title(&self) -> String {
+        if self.item_count <= 1 {
+            return self.last_title.clone();
+        }
+        let scope = match &self.scope_name {
+            Some(name) if !name.is_empty() => format!("{} {}", capitalize(&self.scope_type), name),
+            _ => format!("a {}", self.scope_type),
+        };
+        format!("{} changes in {}", self.item_count, scope)
+    }
+
+    /// Message of the notification the digest is delivered as.
+    pub fn message(&self) -> String {
+        if self.item_count <= 1 {
+            self.last_message.clone()
+        } else {
+            format!("Latest: {}", self.last_title)
+        }
+    }
+
+    /// Structured payload describing what was coalesced.
+    pub fn payload(&self) -> serde_json::Value {
+        serde_json::json!({
+            "digest": {
+                "count": self.item_count,
+                "event_type": self.event_type,
+                "scope_type": self.scope_type,
+                "scope_id": self.scope_id,
+                "scope_name": self.scope_name,
+                "first_at": self.first_at,
+                "last_at": self.last_at,
+            }
+        })
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(count: i32, scope_name: Option<&str>) -> NotificationDigest {
+        NotificationDigest {
+            user_id: Uuid::new_v4(),
+            category: "file".to_string(),
+            event_type: "file_uploaded".to_string(),
+            scope_type: "folder".to_string(),
+            scope_id: Uuid::new_v4(),
+            scope_name: scope_name.map(str::to_string),
+            item_count: count,
+            last_title: "report.pdf uploaded".to_string(),
+            last_message: "alice uploaded report.pdf".to_string(),
+            last_actor_id: None,
+            first_at: Utc::now(),
+            last_at: Utc::now(),
+            flush_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_digest_summary() {
+        let many = digest(3, Some("Designs"));
+        assert_eq!(many.title(), "3 changes in Folder Designs");
+        assert_eq!(many.message(), "Latest: report.pdf uploaded");
+        assert_eq!(digest(3, None).title(), "3 changes in a folder");
+
+        let single = digest(1, Some("Designs"));
+        assert_eq!(single.title(), "report.pdf uploaded");
+        assert_eq!(single.message(), "alice uploaded report.pdf");
+    }
+}
@@ -1,9 +1,15 @@
 //! Notification domain entities.
 
+pub mod broadcast;
 pub mod category;
+pub mod digest;
 pub mod model;
 pub mod preference;
+pub mod template;
 
+pub use broadcast::{BroadcastAckReport, BroadcastMessage, BroadcastReceipt, BroadcastTarget};
 pub use category::NotificationCategory;
+pub use digest::NotificationDigest;
 pub use model::Notification;
 pub use preference::NotificationPreference;
+pub use template::NotificationTemplates;
@@ -63,8 +63,13 @@ pub struct AdminBroadcast {
     pub id: Uuid,
     /// Admin who created it.
     pub admin_id: Uuid,
-    /// Target audience (e.g., "all", "group:admins").
+    /// Target audience: "all", "role:<role>" or "users"; see
+    /// [`BroadcastTarget`](super::broadcast::BroadcastTarget).
     pub target: String,
+    /// Recipients when `target` is "users".
+    #[serde(default)]
+    #[sqlx(default)]
+    pub target_user_ids: Option<Vec<Uuid>>,
     /// Broadcast title.
     pub title: String,
     /// Broadcast message.
@@ -79,6 +84,32 @@ pub struct AdminBroadcast {
     pub action_payload: Option<serde_json::Value>,
     /// Number of users who received it (if tracked).
     pub delivered_count: i32,
+    /// Whether recipients must acknowledge it.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub require_ack: bool,
+    /// When it is to be sent; `None` to send immediately.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub scheduled_at: Option<DateTime<Utc>>,
+    /// When it was sent.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub sent_at: Option<DateTime<Utc>>,
+    /// Until when users who connect still receive it.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub expires_at: Option<DateTime<Utc>>,
     /// Creation timestamp.
     pub created_at: DateTime<Utc>,
 }
+
+impl AdminBroadcast {
+    /// The parsed audience, or `None` for an unrecognised target.
+    pub fn audience(&self) -> Option<super::broadcast::BroadcastTarget> {
+        super::broadcast::BroadcastTarget::from_columns(
+            &self.target,
+            self.target_user_ids.as_deref(),
+        )
+    }
+}
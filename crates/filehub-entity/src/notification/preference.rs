@@ -18,6 +18,11 @@ pub struct NotificationPreference {
     ///   "file": { "enabled": true, "realtime": true, "email": false },
     ///   "share": { "enabled": true, "realtime": true, "email": true },
     ///   "session": { "enabled": true, "realtime": true, "email": false },
+    ///   "digest": { "enabled": true, "window_minutes": 15 },
+    ///   "locale": "de",
+    ///   "event_types": {
+    ///     "share_created": { "realtime": false, "stored": true }
+    ///   },
     ///   ...
     /// }
     /// ```
@@ -40,6 +45,33 @@ pub struct CategoryPreference {
     pub email: bool,
 }
 
+/// Per-channel overrides for one event type. Unset channels fall back to
+/// the category setting, so event types nobody configured stay enabled.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventTypePreference {
+    /// Deliver via real-time WebSocket.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub realtime: Option<bool>,
+    /// Keep in the stored notification list.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stored: Option<bool>,
+    /// Deliver via email.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<bool>,
+}
+
+/// Digest settings: batchable notifications are coalesced per event type
+/// and scope, and delivered once per window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestPreference {
+    /// Whether batchable notifications are collected into digests.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Length of the collection window in minutes.
+    #[serde(default = "default_digest_window")]
+    pub window_minutes: u32,
+}
+
 impl NotificationPreference {
     /// Create default preferences for a user.
     pub fn default_for_user(user_id: Uuid) -> Self {
@@ -54,6 +86,78 @@ impl NotificationPreference {
             updated_at: Some(Utc::now()),
         }
     }
+
+    /// The digest settings, or the defaults if absent or malformed.
+    pub fn digest(&self) -> DigestPreference {
+        self.preferences
+            .get("digest")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// The locale notifications are worded in, if the user chose one.
+    pub fn locale(&self) -> Option<&str> {
+        self.preferences
+            .get("locale")
+            .and_then(|v| v.as_str())
+            .filter(|locale| !locale.is_empty())
+    }
+
+    /// The overrides for `event_type`, if any were set.
+    pub fn event_type(&self, event_type: &str) -> Option<EventTypePreference> {
+        self.preferences
+            .get("event_types")?
+            .get(event_type)
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+
+    /// Set the overrides for `event_type`; `None` restores the defaults.
+    pub fn set_event_type(&mut self, event_type: &str, preference: Option<EventTypePreference>) {
+        let mut event_types = self
+            .preferences
+            .get("event_types")
+            .and_then(|v| v.as_object())
+            .cloned()
+            .unwrap_or_default();
+        match preference {
+            Some(preference) => {
+                event_types.insert(
+                    event_type.to_string(),
+                    serde_json::to_value(preference).unwrap_or_default(),
+                );
+            }
+            None => {
+                event_types.remove(event_type);
+            }
+        }
+
+        let event_types = serde_json::Value::Object(event_types);
+        match self.preferences.as_object_mut() {
+            Some(root) => {
+                root.insert("event_types".to_string(), event_types);
+            }
+            None => self.preferences = serde_json::json!({ "event_types": event_types }),
+        }
+    }
+}
+
+impl DigestPreference {
+    /// Longest accepted window (one day).
+    pub const MAX_WINDOW_MINUTES: u32 = 24 * 60;
+
+    /// The window length, clamped to 1 minute .. [`Self::MAX_WINDOW_MINUTES`].
+    pub fn window(&self) -> chrono::Duration {
+        chrono::Duration::minutes(self.window_minutes.clamp(1, Self::MAX_WINDOW_MINUTES) as i64)
+    }
+}
+
+impl Default for DigestPreference {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_minutes: default_digest_window(),
+        }
+    }
 }
 
 impl Default for CategoryPreference {
@@ -69,3 +173,7 @@ impl Default for CategoryPreference {
 fn default_true() -> bool {
     true
 }
+
+fn default_digest_window() -> u32 {
+    15
+}
@@ -0,0 +1,421 @@
+//! Notification text templates.
+//!
+//! The title and message of an event notification are rendered from the
+//! templates of its event type. `{{name}}` placeholders are filled from
+//! the event; the names each event type offers are listed with its
+//! built-in wording. Operators override the wording per event type and add
+//! localized variants under `"<event_type>.<locale>"`. A recipient's
+//! locale (`de-AT`) falls back to its language (`de`), then to the plain
+//! override and finally to the built-in wording.
+
+use std::collections::HashMap;
+
+use filehub_core::config::NotificationTemplateConfig;
+use filehub_core::error::AppError;
+
+/// Placeholders offered by file notifications.
+const FILE_VARIABLES: &[&str] = &["event_type", "actor_name", "file_name"];
+
+/// Placeholders offered by coalesced upload notifications.
+const FILE_BATCH_VARIABLES: &[&str] = &["event_type", "actor_name", "count"];
+
+/// Placeholders offered by share notifications.
+const SHARE_VARIABLES: &[&str] = &["event_type", "actor_name", "resource_name"];
+
+/// Placeholders offered by access request notifications.
+const ACCESS_VARIABLES: &[&str] = &["event_type", "actor_name", "permission", "resource_name"];
+
+/// Placeholders offered by session notifications.
+const SESSION_VARIABLES: &[&str] = &["event_type", "username", "details"];
+
+/// Built-in wording: event type, its placeholders, title and message.
+/// `*_event` and `access_request` are used for event types without
+/// templates of their own.
+const BUILTIN: &[(&str, &[&str], &str, &str)] = &[
+    (
+        "file_created",
+        FILE_VARIABLES,
+        "File uploaded",
+        "{{actor_name}} uploaded '{{file_name}}'",
+    ),
+    (
+        "file_updated",
+        FILE_VARIABLES,
+        "File updated",
+        "{{actor_name}} updated '{{file_name}}'",
+    ),
+    (
+        "file_deleted",
+        FILE_VARIABLES,
+        "File deleted",
+        "{{actor_name}} deleted '{{file_name}}'",
+    ),
+    (
+        "file_moved",
+        FILE_VARIABLES,
+        "File moved",
+        "{{actor_name}} moved '{{file_name}}'",
+    ),
+    (
+        "files_created",
+        FILE_BATCH_VARIABLES,
+        "Files uploaded",
+        "{{actor_name}} uploaded {{count}} files",
+    ),
+    (
+        "file_event",
+        FILE_VARIABLES,
+        "File event",
+        "{{actor_name}} performed '{{event_type}}' on '{{file_name}}'",
+    ),
+    (
+        "share_created",
+        SHARE_VARIABLES,
+        "New share",
+        "{{actor_name}} shared '{{resource_name}}' with you",
+    ),
+    (
+        "share_accessed",
+        SHARE_VARIABLES,
+        "Share accessed",
+        "Your share of '{{resource_name}}' was accessed",
+    ),
+    (
+        "share_event",
+        SHARE_VARIABLES,
+        "Share event",
+        "Share event on '{{resource_name}}'",
+    ),
+    (
+        "access_requested",
+        ACCESS_VARIABLES,
+        "Access requested",
+        "{{actor_name}} requested {{permission}} access to '{{resource_name}}'",
+    ),
+    (
+        "access_approved",
+        ACCESS_VARIABLES,
+        "Access granted",
+        "{{actor_name}} granted you {{permission}} access to '{{resource_name}}'",
+    ),
+    (
+        "access_denied",
+        ACCESS_VARIABLES,
+        "Access request declined",
+        "{{actor_name}} declined your request for {{permission}} access to '{{resource_name}}'",
+    ),
+    (
+        "access_request",
+        ACCESS_VARIABLES,
+        "Access request",
+        "Access request on '{{resource_name}}'",
+    ),
+    (
+        "session_created",
+        SESSION_VARIABLES,
+        "New session",
+        "User '{{username}}' logged in",
+    ),
+    (
+        "session_terminated",
+        SESSION_VARIABLES,
+        "Session terminated",
+        "Session for '{{username}}' was terminated: {{details}}",
+    ),
+    (
+        "session_expired",
+        SESSION_VARIABLES,
+        "Session expired",
+        "Session for '{{username}}' expired",
+    ),
+    (
+        "session_event",
+        SESSION_VARIABLES,
+        "Session event",
+        "Session event for '{{username}}': {{details}}",
+    ),
+];
+
+/// One piece of a parsed template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// Literal text.
+    Text(String),
+    /// A placeholder, by name.
+    Value(String),
+}
+
+/// A parsed template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    /// Parse `source`, allowing only the placeholders in `variables`.
+    pub fn parse(source: &str, variables: &[&str]) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut rest = source;
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                segments.push(Segment::Text(rest[..start].to_string()));
+            }
+            let after = &rest[start + 2..];
+            let end = after
+                .find("}}")
+                .ok_or_else(|| format!("unclosed placeholder at '{{{{{}'", after))?;
+            let name = after[..end].trim();
+            if !variables.contains(&name) {
+                return Err(format!(
+                    "unknown placeholder '{{{{{}}}}}'; available: {}",
+                    name,
+                    variables.join(", ")
+                ));
+            }
+            segments.push(Segment::Value(name.to_string()));
+            rest = &after[end + 2..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+        Ok(Self { segments })
+    }
+
+    /// Render with `values`; placeholders without a value render empty.
+    pub fn render(&self, values: &[(&str, &str)]) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => out.push_str(text),
+                Segment::Value(name) => {
+                    if let Some((_, value)) = values.iter().find(|(n, _)| n == name) {
+                        out.push_str(value);
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Title and message templates of one event type or locale.
+#[derive(Debug, Clone, Default)]
+struct TemplatePair {
+    title: Option<Template>,
+    message: Option<Template>,
+}
+
+/// The notification templates in force: the built-in wording and the
+/// operator's overrides.
+#[derive(Debug, Clone)]
+pub struct NotificationTemplates {
+    /// Built-in templates by event type.
+    builtin: HashMap<&'static str, (Template, Template)>,
+    /// Overrides by `event_type` or `event_type.locale`, locale lowercase.
+    overrides: HashMap<String, TemplatePair>,
+}
+
+impl NotificationTemplates {
+    /// The built-in wording only.
+    pub fn builtin() -> Self {
+        let builtin = BUILTIN
+            .iter()
+            .map(|(event_type, variables, title, message)| {
+                let parse = |source| {
+                    Template::parse(source, variables).expect("built-in templates are valid")
+                };
+                (*event_type, (parse(title), parse(message)))
+            })
+            .collect();
+        Self {
+            builtin,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// The built-in wording with `overrides` applied. Every override is
+    /// checked; the error lists all that are broken.
+    pub fn from_config(
+        overrides: &HashMap<String, NotificationTemplateConfig>,
+    ) -> Result<Self, AppError> {
+        let mut templates = Self::builtin();
+        let mut problems = Vec::new();
+        let mut keys: Vec<&String> = overrides.keys().collect();
+        keys.sort();
+        for key in keys {
+            let config = &overrides[key];
+            let (event_type, locale) = match key.split_once('.') {
+                Some((event_type, locale)) => (event_type, Some(locale)),
+                None => (key.as_str(), None),
+            };
+            let Some((_, variables, _, _)) = BUILTIN.iter().find(|(e, ..)| *e == event_type) else {
+                problems.push(format!("{}: unknown event type '{}'", key, event_type));
+                continue;
+            };
+            if locale.is_some_and(|locale| locale.is_empty()) {
+                problems.push(format!("{}: empty locale", key));
+                continue;
+            }
+
+            let mut parse = |field: &str, source: &Option<String>| {
+                let source = source.as_deref()?;
+                Template::parse(source, variables)
+                    .map_err(|e| problems.push(format!("{}.{}: {}", key, field, e)))
+                    .ok()
+            };
+            let pair = TemplatePair {
+                title: parse("title", &config.title),
+                message: parse("message", &config.message),
+            };
+            let key = match locale {
+                Some(locale) => format!("{}.{}", event_type, locale.to_lowercase()),
+                None => event_type.to_string(),
+            };
+            templates.overrides.insert(key, pair);
+        }
+
+        if problems.is_empty() {
+            Ok(templates)
+        } else {
+            Err(AppError::configuration(format!(
+                "Invalid notification templates ({} problem(s)):\n  - {}",
+                problems.len(),
+                problems.join("\n  - ")
+            )))
+        }
+    }
+
+    /// Whether `event_type` has templates.
+    pub fn knows(&self, event_type: &str) -> bool {
+        self.builtin.contains_key(event_type)
+    }
+
+    /// Render the title and message of `event_type` for a recipient with
+    /// `locale`. Returns `None` for an event type without templates.
+    pub fn render(
+        &self,
+        event_type: &str,
+        locale: Option<&str>,
+        values: &[(&str, &str)],
+    ) -> Option<(String, String)> {
+        let (title, message) = self.builtin.get(event_type)?;
+
+        let locale = locale.map(str::to_lowercase);
+        let mut keys = Vec::with_capacity(3);
+        if let Some(locale) = &locale {
+            keys.push(format!("{}.{}", event_type, locale));
+            if let Some((language, _)) = locale.split_once(['-', '_']) {
+                keys.push(format!("{}.{}", event_type, language));
+            }
+        }
+        keys.push(event_type.to_string());
+        let candidates: Vec<&TemplatePair> = keys
+            .iter()
+            .filter_map(|key| self.overrides.get(key))
+            .collect();
+
+        let title = candidates
+            .iter()
+            .find_map(|pair| pair.title.as_ref())
+            .unwrap_or(title);
+        let message = candidates
+            .iter()
+            .find_map(|pair| pair.message.as_ref())
+            .unwrap_or(message);
+        Some((title.render(values), message.render(values)))
+    }
+}
+
+impl Default for NotificationTemplates {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(
+        entries: &[(&str, Option<&str>, Option<&str>)],
+    ) -> HashMap<String, NotificationTemplateConfig> {
+        entries
+            .iter()
+            .map(|(key, title, message)| {
+                (
+                    key.to_string(),
+                    NotificationTemplateConfig {
+                        title: title.map(str::to_string),
+                        message: message.map(str::to_string),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    const VALUES: &[(&str, &str)] = &[("actor_name", "alice"), ("resource_name", "plans.dwg")];
+
+    #[test]
+    fn test_builtin_wording() {
+        let templates = NotificationTemplates::builtin();
+        assert_eq!(
+            templates.render("share_created", None, VALUES),
+            Some((
+                "New share".to_string(),
+                "alice shared 'plans.dwg' with you".to_string()
+            ))
+        );
+        assert_eq!(templates.render("share_unknown", None, VALUES), None);
+    }
+
+    #[test]
+    fn test_locale_falls_back_to_language_then_default() {
+        let templates = NotificationTemplates::from_config(&config(&[
+            (
+                "share_created.de",
+                Some("Neue Freigabe"),
+                Some("{{ actor_name }} hat '{{resource_name}}' freigegeben"),
+            ),
+            (
+                "share_created",
+                None,
+                Some("{{actor_name}} shared {{resource_name}}"),
+            ),
+        ]))
+        .unwrap();
+
+        let (title, message) = templates
+            .render("share_created", Some("de-AT"), VALUES)
+            .unwrap();
+        assert_eq!(title, "Neue Freigabe");
+        assert_eq!(message, "alice hat 'plans.dwg' freigegeben");
+
+        let (title, message) = templates
+            .render("share_created", Some("fr"), VALUES)
+            .unwrap();
+        assert_eq!(title, "New share");
+        assert_eq!(message, "alice shared plans.dwg");
+    }
+
+    #[test]
+    fn test_broken_templates_are_all_reported() {
+        let error = NotificationTemplates::from_config(&config(&[
+            ("share_created", Some("{{file_name}}"), None),
+            ("share_created.de", None, Some("{{actor_name")),
+            ("upload_done", Some("Done"), None),
+        ]))
+        .unwrap_err();
+        assert!(error.message.contains("3 problem(s)"));
+        assert!(
+            error
+                .message
+                .contains("share_created.title: unknown placeholder")
+        );
+        assert!(
+            error
+                .message
+                .contains("share_created.de.message: unclosed placeholder")
+        );
+        assert!(error.message.contains("unknown event type 'upload_done'"));
+    }
+}
@@ -3,7 +3,9 @@
 pub mod acl;
 pub mod action;
 pub mod model;
+pub mod template;
 
 pub use acl::{AclInheritance, AclPermission};
 pub use action::PermissionAction;
 pub use model::{AclEntry, ResourceType};
+pub use template::{PermissionTemplate, TemplateChange, TemplateEntry};
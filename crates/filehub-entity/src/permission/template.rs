@@ -0,0 +1,226 @@
+//! Permission templates: named sets of ACL entries applied to folders.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use filehub_core::error::AppError;
+
+use super::acl::{AclInheritance, AclPermission};
+use super::model::AclEntry;
+
+/// One ACL entry of a template, granted to a user or to anyone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateEntry {
+    /// User granted the permission (None if `is_anyone` is true).
+    #[serde(default)]
+    pub user_id: Option<Uuid>,
+    /// Whether the permission is granted to anyone.
+    #[serde(default)]
+    pub is_anyone: bool,
+    /// The permission level.
+    pub permission: AclPermission,
+    /// Inheritance behavior; `block` keeps the folder's descendants from
+    /// inheriting entries of folders above it.
+    #[serde(default = "default_inheritance")]
+    pub inheritance: AclInheritance,
+}
+
+fn default_inheritance() -> AclInheritance {
+    AclInheritance::Inherit
+}
+
+impl TemplateEntry {
+    /// Whether `entry` is granted to the same principal as this one.
+    pub fn same_principal(&self, entry: &AclEntry) -> bool {
+        if self.is_anyone {
+            entry.is_public()
+        } else {
+            !entry.is_public() && entry.user_id == self.user_id
+        }
+    }
+}
+
+/// A named, reusable set of ACL entries.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PermissionTemplate {
+    /// Unique template identifier.
+    pub id: Uuid,
+    /// Unique template name.
+    pub name: String,
+    /// What the template is for.
+    pub description: Option<String>,
+    /// Entries applied to a folder.
+    pub entries: Json<Vec<TemplateEntry>>,
+    /// Admin who created the template.
+    pub created_by: Option<Uuid>,
+    /// When the template was created.
+    pub created_at: DateTime<Utc>,
+    /// When the template was last updated.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A change to a folder's ACL that applying a template makes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateChange {
+    /// Grant an entry the folder does not have.
+    Create(TemplateEntry),
+    /// Bring an existing entry of the same principal in line with the
+    /// template, clearing any expiry.
+    Update {
+        /// The existing entry.
+        entry_id: Uuid,
+        /// Permission level from the template.
+        permission: AclPermission,
+        /// Inheritance behavior from the template.
+        inheritance: AclInheritance,
+    },
+}
+
+/// Checks that every entry names exactly one principal and that no
+/// principal appears twice.
+pub fn validate_entries(entries: &[TemplateEntry]) -> Result<(), AppError> {
+    for (i, entry) in entries.iter().enumerate() {
+        if entry.is_anyone == entry.user_id.is_some() {
+            return Err(AppError::validation(format!(
+                "Template entry {i} must have either a user_id or is_anyone"
+            )));
+        }
+        let duplicate = entries[..i]
+            .iter()
+            .any(|e| e.is_anyone == entry.is_anyone && e.user_id == entry.user_id);
+        if duplicate {
+            return Err(AppError::validation(format!(
+                "Template entry {i} repeats the principal of an earlier entry"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The changes that bring a folder's `existing` entries in line with a
+/// template.
+///
+/// Entries of principals the template does not mention are left alone,
+/// and applying the same template twice changes nothing the second time.
+pub fn plan_application(entries: &[TemplateEntry], existing: &[AclEntry]) -> Vec<TemplateChange> {
+    entries
+        .iter()
+        .filter_map(
+            |entry| match existing.iter().find(|e| entry.same_principal(e)) {
+                None => Some(TemplateChange::Create(entry.clone())),
+                Some(current)
+                    if current.permission != entry.permission
+                        || current.inheritance != entry.inheritance
+                        || current.expires_at.is_some() =>
+                {
+                    Some(TemplateChange::Update {
+                        entry_id: current.id,
+                        permission: entry.permission,
+                        inheritance: entry.inheritance,
+                    })
+                }
+                Some(_) => None,
+            },
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permission::model::ResourceType;
+
+    fn user_entry(user_id: Uuid, permission: AclPermission) -> TemplateEntry {
+        TemplateEntry {
+            user_id: Some(user_id),
+            is_anyone: false,
+            permission,
+            inheritance: AclInheritance::Inherit,
+        }
+    }
+
+    fn acl(user_id: Option<Uuid>, permission: AclPermission) -> AclEntry {
+        AclEntry {
+            id: Uuid::new_v4(),
+            resource_type: ResourceType::Folder,
+            resource_id: Uuid::new_v4(),
+            user_id,
+            is_anyone: Some(user_id.is_none()),
+            permission,
+            inheritance: AclInheritance::Inherit,
+            granted_by: Uuid::nil(),
+            expires_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_validate_entries() {
+        let user = Uuid::new_v4();
+        assert!(validate_entries(&[user_entry(user, AclPermission::Editor)]).is_ok());
+
+        let no_principal = TemplateEntry {
+            user_id: None,
+            ..user_entry(user, AclPermission::Viewer)
+        };
+        assert!(validate_entries(&[no_principal]).is_err());
+
+        let both = TemplateEntry {
+            is_anyone: true,
+            ..user_entry(user, AclPermission::Viewer)
+        };
+        assert!(validate_entries(&[both]).is_err());
+
+        let repeated = [
+            user_entry(user, AclPermission::Editor),
+            user_entry(user, AclPermission::Viewer),
+        ];
+        assert!(validate_entries(&repeated).is_err());
+    }
+
+    #[test]
+    fn test_plan_creates_updates_and_keeps() {
+        let (editor, viewer, other) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let anyone = TemplateEntry {
+            user_id: None,
+            is_anyone: true,
+            permission: AclPermission::Viewer,
+            inheritance: AclInheritance::Block,
+        };
+        let template = [
+            user_entry(editor, AclPermission::Editor),
+            user_entry(viewer, AclPermission::Viewer),
+            anyone.clone(),
+        ];
+        let downgraded = acl(Some(editor), AclPermission::Viewer);
+        let existing = [
+            downgraded.clone(),
+            acl(Some(viewer), AclPermission::Viewer),
+            acl(Some(other), AclPermission::Owner),
+        ];
+
+        let plan = plan_application(&template, &existing);
+        assert_eq!(
+            plan,
+            vec![
+                TemplateChange::Update {
+                    entry_id: downgraded.id,
+                    permission: AclPermission::Editor,
+                    inheritance: AclInheritance::Inherit,
+                },
+                TemplateChange::Create(anyone),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_is_idempotent() {
+        let user = Uuid::new_v4();
+        let template = [user_entry(user, AclPermission::Editor)];
+        let existing = [acl(Some(user), AclPermission::Editor)];
+        assert!(plan_application(&template, &existing).is_empty());
+    }
+}
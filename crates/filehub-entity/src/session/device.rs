@@ -0,0 +1,132 @@
+//! Device description derived from a session's User-Agent.
+
+use serde::{Deserialize, Serialize};
+
+/// Kind of device a session was opened from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceKind {
+    /// Desktop or laptop browser.
+    Desktop,
+    /// Phone.
+    Mobile,
+    /// Tablet.
+    Tablet,
+    /// Script, CLI or other non-browser client.
+    Client,
+    /// Nothing recognisable in the User-Agent.
+    Unknown,
+}
+
+/// Device information stored in `sessions.device_info`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    /// Human-readable label, e.g. `"Firefox on Windows"`.
+    pub label: String,
+    /// Browser or client name, if recognised.
+    #[serde(default)]
+    pub browser: Option<String>,
+    /// Operating system, if recognised.
+    #[serde(default)]
+    pub os: Option<String>,
+    /// Device kind.
+    #[serde(default = "default_kind")]
+    pub kind: DeviceKind,
+}
+
+/// Browser/client tokens, most specific first (Edge and Opera also
+/// announce Chrome, Chrome also announces Safari).
+const BROWSERS: &[(&str, &str)] = &[
+    ("Edg/", "Edge"),
+    ("OPR/", "Opera"),
+    ("Firefox/", "Firefox"),
+    ("Chrome/", "Chrome"),
+    ("CriOS/", "Chrome"),
+    ("Safari/", "Safari"),
+    ("curl/", "curl"),
+    ("python-requests/", "Python"),
+    ("filehub-cli", "FileHub CLI"),
+];
+
+/// Operating system tokens, most specific first (Android announces Linux).
+const SYSTEMS: &[(&str, &str)] = &[
+    ("Windows", "Windows"),
+    ("iPhone", "iOS"),
+    ("iPad", "iPadOS"),
+    ("Android", "Android"),
+    ("Mac OS X", "macOS"),
+    ("CrOS", "ChromeOS"),
+    ("Linux", "Linux"),
+];
+
+impl DeviceInfo {
+    /// Derive device information from a User-Agent header.
+    pub fn from_user_agent(user_agent: Option<&str>) -> Self {
+        let ua = user_agent.unwrap_or_default();
+        let browser = find(ua, BROWSERS);
+        let os = find(ua, SYSTEMS);
+
+        let kind = if ua.contains("iPad") || ua.contains("Tablet") {
+            DeviceKind::Tablet
+        } else if ua.contains("Mobi") || ua.contains("iPhone") {
+            DeviceKind::Mobile
+        } else if os.is_some() {
+            DeviceKind::Desktop
+        } else if browser.is_some() {
+            DeviceKind::Client
+        } else {
+            DeviceKind::Unknown
+        };
+
+        let label = match (browser, os) {
+            (Some(browser), Some(os)) => format!("{browser} on {os}"),
+            (Some(browser), None) => browser.to_string(),
+            (None, Some(os)) => format!("Unknown browser on {os}"),
+            (None, None) => "Unknown device".to_string(),
+        };
+
+        Self {
+            label,
+            browser: browser.map(String::from),
+            os: os.map(String::from),
+            kind,
+        }
+    }
+}
+
+fn find(ua: &str, tokens: &[(&str, &'static str)]) -> Option<&'static str> {
+    tokens
+        .iter()
+        .find(|(token, _)| ua.contains(token))
+        .map(|(_, name)| *name)
+}
+
+fn default_kind() -> DeviceKind {
+    DeviceKind::Unknown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_user_agent() {
+        let edge = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 \
+                    (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.0.0";
+        let device = DeviceInfo::from_user_agent(Some(edge));
+        assert_eq!(device.label, "Edge on Windows");
+        assert_eq!(device.kind, DeviceKind::Desktop);
+
+        let iphone = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) \
+                      AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 Mobile/15E148 Safari/604.1";
+        let device = DeviceInfo::from_user_agent(Some(iphone));
+        assert_eq!(device.label, "Safari on iOS");
+        assert_eq!(device.kind, DeviceKind::Mobile);
+
+        let device = DeviceInfo::from_user_agent(Some("curl/8.4.0"));
+        assert_eq!(device.label, "curl");
+        assert_eq!(device.kind, DeviceKind::Client);
+
+        assert_eq!(DeviceInfo::from_user_agent(None).label, "Unknown device");
+    }
+}
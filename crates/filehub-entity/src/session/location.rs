@@ -0,0 +1,48 @@
+//! Login location history entity.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Where a user logged in from, as resolved by GeoIP at login time.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LoginLocation {
+    /// Unique identifier.
+    pub id: Uuid,
+    /// The user who logged in.
+    pub user_id: Uuid,
+    /// The session the login created.
+    pub session_id: Option<Uuid>,
+    /// Client IP address.
+    pub ip_address: std::net::IpAddr,
+    /// ISO 3166-1 alpha-2 country code.
+    pub country_code: String,
+    /// First-level subdivision (state, province), if known.
+    pub region: Option<String>,
+    /// Approximate latitude in degrees.
+    pub latitude: Option<f64>,
+    /// Approximate longitude in degrees.
+    pub longitude: Option<f64>,
+    /// When the login happened.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Data required to record a login location.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateLoginLocation {
+    /// The user who logged in.
+    pub user_id: Uuid,
+    /// The session the login created.
+    pub session_id: Option<Uuid>,
+    /// Client IP address.
+    pub ip_address: std::net::IpAddr,
+    /// ISO 3166-1 alpha-2 country code.
+    pub country_code: String,
+    /// First-level subdivision, if known.
+    pub region: Option<String>,
+    /// Approximate latitude in degrees.
+    pub latitude: Option<f64>,
+    /// Approximate longitude in degrees.
+    pub longitude: Option<f64>,
+}
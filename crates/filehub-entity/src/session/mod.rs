@@ -1,9 +1,13 @@
 //! Session domain entities.
 
+pub mod device;
 pub mod limit;
+pub mod location;
 pub mod model;
 pub mod token;
 
+pub use device::{DeviceInfo, DeviceKind};
 pub use limit::UserSessionLimit;
+pub use location::{CreateLoginLocation, LoginLocation};
 pub use model::Session;
 pub use token::{AccessToken, RefreshToken, TokenPair};
@@ -7,6 +7,8 @@ use uuid::Uuid;
 
 use crate::presence::PresenceStatus;
 
+use super::device::DeviceInfo;
+
 /// An active user session.
 ///
 /// Sessions are created on login and destroyed on logout, expiry,
@@ -36,6 +38,12 @@ pub struct Session {
     /// Reference to the session that was kicked to make room for this one.
     pub overflow_kicked: Option<Uuid>,
 
+    // -- Impersonation --
+    /// The administrator acting as `user_id`, for impersonation sessions.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub impersonator_id: Option<Uuid>,
+
     // -- Presence & WebSocket --
     /// Current presence status.
     pub presence_status: Option<PresenceStatus>,
@@ -43,6 +51,10 @@ pub struct Session {
     pub ws_connected: Option<bool>,
     /// When the WebSocket connection was established.
     pub ws_connected_at: Option<DateTime<Utc>>,
+    /// When the user was warned of idle termination; cleared by activity.
+    #[serde(default)]
+    #[sqlx(default)]
+    pub idle_warned_at: Option<DateTime<Utc>>,
 
     // -- Termination --
     /// The admin who terminated this session (if applicable).
@@ -77,6 +89,11 @@ impl Session {
         self.expires_at <= Utc::now()
     }
 
+    /// Check whether an administrator opened this session as the user.
+    pub fn is_impersonation(&self) -> bool {
+        self.impersonator_id.is_some()
+    }
+
     /// Check whether a WebSocket connection is currently active.
     pub fn is_ws_connected(&self) -> bool {
         self.ws_connected.unwrap_or(false)
@@ -86,6 +103,15 @@ impl Session {
     pub fn idle_seconds(&self) -> i64 {
         (Utc::now() - self.last_activity).num_seconds().max(0)
     }
+
+    /// The device this session was opened from, as stored at login or,
+    /// for older sessions, derived from the User-Agent.
+    pub fn device(&self) -> DeviceInfo {
+        self.device_info
+            .as_ref()
+            .and_then(|info| serde_json::from_value(info.clone()).ok())
+            .unwrap_or_else(|| DeviceInfo::from_user_agent(self.user_agent.as_deref()))
+    }
 }
 
 /// Data required to create a new session.
@@ -105,4 +131,6 @@ pub struct CreateSession {
     pub device_info: Option<serde_json::Value>,
     /// When the session expires.
     pub expires_at: DateTime<Utc>,
+    /// The administrator acting as the user, for impersonation sessions.
+    pub impersonator_id: Option<Uuid>,
 }
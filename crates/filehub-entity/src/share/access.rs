@@ -0,0 +1,70 @@
+//! Share link access records and statistics.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// What a visitor did with a share link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "share_access_kind", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ShareAccessKind {
+    /// The link was opened.
+    View,
+    /// The shared file was downloaded.
+    Download,
+}
+
+/// One public access to a share link.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ShareAccess {
+    /// Record identifier.
+    pub id: Uuid,
+    /// The share accessed.
+    pub share_id: Uuid,
+    /// View or download.
+    pub kind: ShareAccessKind,
+    /// When the access happened.
+    pub accessed_at: DateTime<Utc>,
+    /// Client address, unless IP capture is disabled.
+    pub ip_address: Option<String>,
+    /// Client `User-Agent`.
+    pub user_agent: Option<String>,
+    /// ISO 3166-1 alpha-2 country of the client, if known.
+    pub country: Option<String>,
+}
+
+/// Data required to record an access.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateShareAccess {
+    /// The share accessed.
+    pub share_id: Uuid,
+    /// View or download.
+    pub kind: ShareAccessKind,
+    /// When the access happened.
+    pub accessed_at: DateTime<Utc>,
+    /// Client address.
+    pub ip_address: Option<String>,
+    /// Client `User-Agent`.
+    pub user_agent: Option<String>,
+    /// Client country.
+    pub country: Option<String>,
+}
+
+/// Access statistics of a share link.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareStats {
+    /// The share.
+    pub share_id: Uuid,
+    /// Times the link was opened.
+    pub views: i64,
+    /// Downloads through the link.
+    pub downloads: i64,
+    /// Distinct client addresses, among accesses with a recorded address.
+    pub unique_visitors: i64,
+    /// Most recent access, if any.
+    pub last_accessed_at: Option<DateTime<Utc>>,
+    /// Most recent accesses, newest first.
+    pub recent: Vec<ShareAccess>,
+}
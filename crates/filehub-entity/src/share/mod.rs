@@ -1,9 +1,13 @@
 //! Share domain entities.
 
+pub mod access;
 pub mod invite;
 pub mod link;
 pub mod model;
+pub mod request;
 
+pub use access::{CreateShareAccess, ShareAccess, ShareAccessKind, ShareStats};
 pub use invite::ShareInvite;
 pub use link::ShareLink;
 pub use model::{CreateShare, Share, ShareType};
+pub use request::{AccessRequest, AccessRequestStatus, CreateAccessRequest};
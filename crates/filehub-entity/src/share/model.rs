@@ -26,6 +26,8 @@ pub enum ShareType {
 pub struct Share {
     /// Unique share identifier.
     pub id: Uuid,
+    /// Tenant that owns the share.
+    pub tenant_id: Uuid,
     /// Type of share.
     pub share_type: ShareType,
     /// Type of resource being shared.
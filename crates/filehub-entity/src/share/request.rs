@@ -0,0 +1,66 @@
+//! Requests for more access to a shared resource.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::permission::acl::AclPermission;
+use crate::permission::model::ResourceType;
+
+/// Where an access request stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "access_request_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AccessRequestStatus {
+    /// Waiting for the owner.
+    Pending,
+    /// Granted by the owner.
+    Approved,
+    /// Turned down by the owner.
+    Denied,
+}
+
+/// A user's request for more access to a file or folder.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AccessRequest {
+    /// Request identifier.
+    pub id: Uuid,
+    /// Type of the resource.
+    pub resource_type: ResourceType,
+    /// ID of the resource.
+    pub resource_id: Uuid,
+    /// User asking for access.
+    pub requester_id: Uuid,
+    /// Owner of the resource, who decides.
+    pub owner_id: Uuid,
+    /// Permission level asked for.
+    pub permission: AclPermission,
+    /// Note from the requester.
+    pub message: Option<String>,
+    /// Where the request stands.
+    pub status: AccessRequestStatus,
+    /// User who decided.
+    pub decided_by: Option<Uuid>,
+    /// When it was decided.
+    pub decided_at: Option<DateTime<Utc>>,
+    /// When it was made.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Data required to create an access request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAccessRequest {
+    /// Type of the resource.
+    pub resource_type: ResourceType,
+    /// ID of the resource.
+    pub resource_id: Uuid,
+    /// User asking for access.
+    pub requester_id: Uuid,
+    /// Owner of the resource.
+    pub owner_id: Uuid,
+    /// Permission level asked for.
+    pub permission: AclPermission,
+    /// Note from the requester.
+    pub message: Option<String>,
+}
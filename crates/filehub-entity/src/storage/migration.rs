@@ -0,0 +1,95 @@
+//! Storage migration entity model.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Status of a storage migration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "storage_migration_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum StorageMigrationStatus {
+    /// Created; waiting for the worker.
+    Pending,
+    /// A worker is copying files.
+    Running,
+    /// Every file was moved to the target.
+    Completed,
+    /// Gave up with files still on the source.
+    Failed,
+    /// Stopped by an administrator.
+    Cancelled,
+}
+
+impl StorageMigrationStatus {
+    /// Whether the migration has stopped for good.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+}
+
+/// A migration of every file on one storage backend to another.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct StorageMigration {
+    /// Unique migration identifier.
+    pub id: Uuid,
+    /// Storage files are moved off.
+    pub source_storage_id: Uuid,
+    /// Storage files are moved to.
+    pub target_storage_id: Uuid,
+    /// Current status.
+    pub status: StorageMigrationStatus,
+    /// Files handled between progress updates.
+    pub batch_size: i32,
+    /// Copy rate limit; 0 is unlimited.
+    pub bytes_per_second: i64,
+    /// Pass over the source, starting at 1. Each pass retries the files
+    /// the previous one left behind.
+    pub pass: i32,
+    /// Last file visited in the current pass.
+    pub cursor_file_id: Option<Uuid>,
+    /// Files on the source when the migration was created.
+    pub files_total: i64,
+    /// Bytes of those files and their versions.
+    pub bytes_total: i64,
+    /// Files moved so far.
+    pub files_copied: i64,
+    /// Bytes copied so far, versions included.
+    pub bytes_copied: i64,
+    /// Files skipped in the current pass because they were in use.
+    pub files_skipped: i64,
+    /// Files that failed to copy or verify in the current pass.
+    pub files_failed: i64,
+    /// Most recent per-file or fatal error.
+    pub last_error: Option<String>,
+    /// Administrator who started it.
+    pub created_by: Option<Uuid>,
+    /// When it was created.
+    pub created_at: DateTime<Utc>,
+    /// When a worker first picked it up.
+    pub started_at: Option<DateTime<Utc>>,
+    /// When it reached a terminal status.
+    pub completed_at: Option<DateTime<Utc>>,
+    /// When progress was last recorded.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Data required to create a storage migration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateStorageMigration {
+    /// Storage files are moved off.
+    pub source_storage_id: Uuid,
+    /// Storage files are moved to.
+    pub target_storage_id: Uuid,
+    /// Files handled between progress updates.
+    pub batch_size: i32,
+    /// Copy rate limit; 0 is unlimited.
+    pub bytes_per_second: i64,
+    /// Files on the source.
+    pub files_total: i64,
+    /// Bytes of those files and their versions.
+    pub bytes_total: i64,
+    /// Administrator starting it.
+    pub created_by: Option<Uuid>,
+}
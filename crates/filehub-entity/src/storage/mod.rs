@@ -1,9 +1,11 @@
 //! Storage domain entities.
 
+pub mod migration;
 pub mod model;
 pub mod provider;
 pub mod quota;
 
+pub use migration::{CreateStorageMigration, StorageMigration, StorageMigrationStatus};
 pub use model::{CreateStorage, Storage};
 pub use provider::StorageProviderType;
 pub use quota::StorageQuota;
@@ -27,6 +27,8 @@ pub enum StorageStatus {
 pub struct Storage {
     /// Unique storage identifier.
     pub id: Uuid,
+    /// Tenant that owns the storage.
+    pub tenant_id: Uuid,
     /// Human-readable name.
     pub name: String,
     /// Description of this storage.
@@ -0,0 +1,5 @@
+//! Tag domain entities.
+
+pub mod model;
+
+pub use model::Tag;
@@ -0,0 +1,58 @@
+//! Tag entity model.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Maximum length of a tag name, in characters.
+pub const MAX_TAG_LEN: usize = 64;
+
+/// A user-defined label that can be attached to files.
+///
+/// Tags are scoped to their owner: two users may each have a `review` tag,
+/// and neither sees the other's tagging.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Tag {
+    /// Unique identifier.
+    pub id: Uuid,
+    /// The user who owns this tag.
+    pub owner_id: Uuid,
+    /// Display name, as first entered.
+    pub name: String,
+    /// Lookup key: trimmed, whitespace-collapsed, lowercase.
+    pub normalized_name: String,
+    /// When the tag was created.
+    pub created_at: DateTime<Utc>,
+}
+
+impl Tag {
+    /// Normalizes a tag name for case-insensitive matching.
+    ///
+    /// Returns `None` for empty names or names longer than [`MAX_TAG_LEN`].
+    pub fn normalize(name: &str) -> Option<String> {
+        let normalized = name
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        let len = normalized.chars().count();
+        (len > 0 && len <= MAX_TAG_LEN).then_some(normalized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            Tag::normalize("  Needs   Review ").as_deref(),
+            Some("needs review")
+        );
+        assert_eq!(Tag::normalize("CAD"), Tag::normalize("cad"));
+        assert!(Tag::normalize("   ").is_none());
+        assert!(Tag::normalize(&"x".repeat(MAX_TAG_LEN + 1)).is_none());
+    }
+}
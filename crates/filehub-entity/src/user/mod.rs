@@ -1,9 +1,11 @@
 //! User domain entities.
 
 pub mod model;
+pub mod quota;
 pub mod role;
 pub mod status;
 
 pub use model::User;
+pub use quota::{MonthlyUsage, Usage, UsagePeriod, UserQuotaTier};
 pub use role::UserRole;
 pub use status::UserStatus;
@@ -13,6 +13,8 @@ use super::status::UserStatus;
 pub struct User {
     /// Unique user identifier.
     pub id: Uuid,
+    /// Tenant that owns the user.
+    pub tenant_id: Uuid,
     /// Unique login name.
     pub username: String,
     /// Email address (optional).
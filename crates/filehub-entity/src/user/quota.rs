@@ -0,0 +1,169 @@
+//! API usage quota entities.
+
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use filehub_core::config::QuotaTier;
+
+/// A quota tier assigned to a user, overriding the default tier.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserQuotaTier {
+    /// The user on the tier.
+    pub user_id: Uuid,
+    /// Tier name, a key of `server.quotas.tiers`.
+    pub tier: String,
+    /// The admin who assigned the tier.
+    pub assigned_by: Option<Uuid>,
+    /// When the tier was assigned.
+    pub assigned_at: DateTime<Utc>,
+}
+
+/// Usage of one user over a calendar month, rolled up from daily rows.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MonthlyUsage {
+    /// First day of the month.
+    pub month: NaiveDate,
+    /// API calls made.
+    pub calls: i64,
+    /// Request and response body bytes transferred.
+    pub bytes: i64,
+}
+
+/// API calls and bytes transferred.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Usage {
+    /// API calls made.
+    pub calls: u64,
+    /// Request and response body bytes transferred.
+    pub bytes: u64,
+}
+
+impl Usage {
+    /// Usage of `calls` calls transferring `bytes` bytes.
+    pub fn new(calls: u64, bytes: u64) -> Self {
+        Self { calls, bytes }
+    }
+
+    /// Usage read from database counters, which are never negative.
+    pub fn from_db(calls: i64, bytes: i64) -> Self {
+        Self::new(calls.max(0) as u64, bytes.max(0) as u64)
+    }
+
+    /// Adds `other` to this usage.
+    pub fn add(&mut self, other: Usage) {
+        self.calls = self.calls.saturating_add(other.calls);
+        self.bytes = self.bytes.saturating_add(other.bytes);
+    }
+
+    /// Whether a tier's allowance for `period` is used up, so no further
+    /// call is allowed in it.
+    pub fn exhausts(&self, tier: &QuotaTier, period: UsagePeriod) -> bool {
+        let limit = period.limit(tier);
+        (limit.calls > 0 && self.calls >= limit.calls)
+            || (limit.bytes > 0 && self.bytes >= limit.bytes)
+    }
+}
+
+/// A quota period, in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsagePeriod {
+    /// A calendar day.
+    Day,
+    /// A calendar month.
+    Month,
+}
+
+impl UsagePeriod {
+    /// Both periods, shortest first.
+    pub const ALL: [UsagePeriod; 2] = [UsagePeriod::Day, UsagePeriod::Month];
+
+    /// Name used in responses.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Month => "month",
+        }
+    }
+
+    /// A tier's allowance for this period; 0 is unlimited.
+    pub fn limit(&self, tier: &QuotaTier) -> Usage {
+        match self {
+            Self::Day => Usage::new(tier.daily_calls, tier.daily_bytes),
+            Self::Month => Usage::new(tier.monthly_calls, tier.monthly_bytes),
+        }
+    }
+
+    /// First day of the period containing `day`.
+    pub fn start(&self, day: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Day => day,
+            Self::Month => day.with_day(1).unwrap_or(day),
+        }
+    }
+
+    /// When the period containing `now` ends and its usage resets.
+    pub fn resets_at(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let start = self.start(now.date_naive());
+        let next = match self {
+            Self::Day => start.checked_add_days(Days::new(1)),
+            Self::Month => start.checked_add_months(Months::new(1)),
+        };
+        next.unwrap_or(start).and_time(Default::default()).and_utc()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tier() -> QuotaTier {
+        QuotaTier {
+            daily_calls: 10,
+            monthly_calls: 100,
+            daily_bytes: 0,
+            monthly_bytes: 1000,
+        }
+    }
+
+    #[test]
+    fn test_exhaustion_per_period() {
+        let tier = tier();
+        assert!(!Usage::new(9, 5000).exhausts(&tier, UsagePeriod::Day));
+        assert!(Usage::new(10, 0).exhausts(&tier, UsagePeriod::Day));
+        // Daily bytes are unlimited; monthly bytes are not.
+        assert!(Usage::new(50, 1000).exhausts(&tier, UsagePeriod::Month));
+        assert!(!Usage::new(99, 999).exhausts(&tier, UsagePeriod::Month));
+        assert!(!Usage::new(u64::MAX, u64::MAX).exhausts(&QuotaTier::default(), UsagePeriod::Day));
+    }
+
+    #[test]
+    fn test_period_resets() {
+        let now = DateTime::parse_from_rfc3339("2024-12-31T18:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            UsagePeriod::Day.resets_at(now).to_rfc3339(),
+            "2025-01-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            UsagePeriod::Month.resets_at(now).to_rfc3339(),
+            "2025-01-01T00:00:00+00:00"
+        );
+        let day = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        assert_eq!(
+            UsagePeriod::Month.start(day),
+            NaiveDate::from_ymd_opt(2024, 2, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_usage_saturates() {
+        let mut usage = Usage::new(u64::MAX, 1);
+        usage.add(Usage::new(1, 2));
+        assert_eq!(usage, Usage::new(u64::MAX, 3));
+        assert_eq!(Usage::from_db(-1, 7), Usage::new(0, 7));
+    }
+}
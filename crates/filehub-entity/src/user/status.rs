@@ -11,7 +11,9 @@ use std::str::FromStr;
 pub enum UserStatus {
     /// Account is active and can log in.
     Active,
-    /// Account is deactivated by an admin.
+    /// Account is deactivated ("disabled") by an admin. The user keeps
+    /// their files and audit history but cannot log in.
+    #[serde(alias = "disabled")]
     Inactive,
     /// Account is locked due to failed login attempts.
     Locked,
@@ -45,11 +47,30 @@ impl FromStr for UserStatus {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "active" => Ok(Self::Active),
-            "inactive" => Ok(Self::Inactive),
+            "inactive" | "disabled" => Ok(Self::Inactive),
             "locked" => Ok(Self::Locked),
             _ => Err(filehub_core::AppError::validation(format!(
-                "Invalid user status: '{s}'. Expected one of: active, inactive, locked"
+                "Invalid user status: '{s}'. Expected one of: active, inactive (or disabled), locked"
             ))),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_is_inactive() {
+        assert_eq!(
+            "disabled".parse::<UserStatus>().unwrap(),
+            UserStatus::Inactive
+        );
+        assert_eq!(
+            serde_json::from_str::<UserStatus>("\"disabled\"").unwrap(),
+            UserStatus::Inactive
+        );
+        assert_eq!(UserStatus::Inactive.to_string(), "inactive");
+        assert!(!UserStatus::Inactive.can_login());
+    }
+}
@@ -0,0 +1,289 @@
+//! The shared HTTP client and its request builder.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Body, Method, Response};
+use serde::Serialize;
+use tracing::Instrument;
+
+use filehub_core::config::HttpClientConfig;
+use filehub_core::error::AppError;
+use filehub_core::telemetry::{self, TRACEPARENT_HEADER};
+
+use crate::limiter::HostLimiter;
+use crate::retry::{backoff, is_retryable_error, is_retryable_status, retry_after};
+use crate::stats::HttpStats;
+
+/// Timeout and retry settings of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestOptions {
+    /// Total time allowed for one attempt.
+    pub timeout: Duration,
+    /// Retries after a retryable failure.
+    pub max_retries: u32,
+}
+
+/// Outbound HTTP client shared by every integration.
+///
+/// Clones share the connection pool, rate limits and statistics. A caller
+/// needing another timeout or retry count takes a copy with
+/// [`with_options`](Self::with_options) or sets them per request; proxy
+/// and TLS settings cannot be overridden.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    /// Pooled client carrying proxy, TLS and connect settings.
+    inner: reqwest::Client,
+    /// Defaults of requests made through this handle.
+    options: RequestOptions,
+    /// Delay before the first retry.
+    retry_base_delay: Duration,
+    /// Upper bound of a retry delay.
+    retry_max_delay: Duration,
+    /// Per-host rate limits.
+    limiter: Arc<HostLimiter>,
+    /// Per-host statistics.
+    stats: Arc<HttpStats>,
+}
+
+impl HttpClient {
+    /// Builds the client from `[http_client]`.
+    pub fn new(config: &HttpClientConfig) -> Result<Self, AppError> {
+        let mut builder = reqwest::Client::builder()
+            .user_agent(config.user_agent.as_str())
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .connect_timeout(Duration::from_secs(config.connect_timeout_seconds))
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout_seconds))
+            .danger_accept_invalid_certs(config.accept_invalid_certs);
+
+        if let Some(proxy) = &config.proxy {
+            let proxy = reqwest::Proxy::all(proxy.as_str())
+                .map_err(|e| {
+                    AppError::configuration(format!("Invalid http_client.proxy '{}': {}", proxy, e))
+                })?
+                .no_proxy(reqwest::NoProxy::from_string(&config.no_proxy.join(",")));
+            builder = builder.proxy(proxy);
+        }
+        if let Some(path) = &config.ca_cert_path {
+            let pem = std::fs::read(path).map_err(|e| {
+                AppError::configuration(format!(
+                    "Failed to read http_client.ca_cert_path '{}': {}",
+                    path, e
+                ))
+            })?;
+            let certs = reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| {
+                AppError::configuration(format!("Invalid certificates in '{}': {}", path, e))
+            })?;
+            builder = builder.tls_certs_merge(certs);
+        }
+
+        let inner = builder.build().map_err(|e| {
+            AppError::configuration(format!("Failed to build the HTTP client: {}", e))
+        })?;
+
+        Ok(Self {
+            inner,
+            options: RequestOptions {
+                timeout: Duration::from_secs(config.timeout_seconds),
+                max_retries: config.max_retries,
+            },
+            retry_base_delay: Duration::from_millis(config.retry_base_delay_ms),
+            retry_max_delay: Duration::from_millis(config.retry_max_delay_ms),
+            limiter: Arc::new(HostLimiter::new(config.rate_limit, config.hosts.clone())),
+            stats: Arc::new(HttpStats::default()),
+        })
+    }
+
+    /// A handle whose requests default to `options`, sharing everything
+    /// else with this one.
+    pub fn with_options(&self, options: RequestOptions) -> Self {
+        Self {
+            options,
+            ..self.clone()
+        }
+    }
+
+    /// Defaults of requests made through this handle.
+    pub fn options(&self) -> RequestOptions {
+        self.options
+    }
+
+    /// Per-destination statistics, shared by all handles.
+    pub fn stats(&self) -> &HttpStats {
+        &self.stats
+    }
+
+    /// Starts a GET request.
+    pub fn get(&self, url: &str) -> HttpRequest {
+        self.request(Method::GET, url)
+    }
+
+    /// Starts a POST request.
+    pub fn post(&self, url: &str) -> HttpRequest {
+        self.request(Method::POST, url)
+    }
+
+    /// Starts a PUT request.
+    pub fn put(&self, url: &str) -> HttpRequest {
+        self.request(Method::PUT, url)
+    }
+
+    /// Starts a DELETE request.
+    pub fn delete(&self, url: &str) -> HttpRequest {
+        self.request(Method::DELETE, url)
+    }
+
+    /// Starts a request with any method.
+    pub fn request(&self, method: Method, url: &str) -> HttpRequest {
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .unwrap_or_default();
+        HttpRequest {
+            builder: self.inner.request(method, url),
+            options: self.options,
+            client: self.clone(),
+            host,
+        }
+    }
+}
+
+/// A request being built; sent with [`send`](Self::send).
+#[derive(Debug)]
+pub struct HttpRequest {
+    /// Client the request is sent through.
+    client: HttpClient,
+    /// The underlying request.
+    builder: reqwest::RequestBuilder,
+    /// Destination host, keying rate limits and statistics.
+    host: String,
+    /// Timeout and retries of this request.
+    options: RequestOptions,
+}
+
+impl HttpRequest {
+    /// Adds a header.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.builder = self.builder.header(name, value);
+        self
+    }
+
+    /// Adds headers.
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.builder = self.builder.headers(headers);
+        self
+    }
+
+    /// Sets a JSON body.
+    pub fn json<T: Serialize + ?Sized>(mut self, body: &T) -> Self {
+        self.builder = self.builder.json(body);
+        self
+    }
+
+    /// Sets a body. A streamed body cannot be replayed, so the request is
+    /// then never retried.
+    pub fn body(mut self, body: impl Into<Body>) -> Self {
+        self.builder = self.builder.body(body);
+        self
+    }
+
+    /// Overrides the timeout of each attempt.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = timeout;
+        self
+    }
+
+    /// Overrides the number of retries.
+    pub fn retries(mut self, max_retries: u32) -> Self {
+        self.options.max_retries = max_retries;
+        self
+    }
+
+    /// Sends the request, waiting for the host's rate limit before each
+    /// attempt and retrying transport errors and 429/502/503/504
+    /// responses with backoff.
+    ///
+    /// Returns the last response whatever its status; only a request that
+    /// never got one is an error. When traces are exported the request
+    /// carries the current `traceparent`.
+    pub async fn send(self) -> Result<Response, AppError> {
+        let Self {
+            client,
+            builder,
+            host,
+            options,
+        } = self;
+        let span = tracing::info_span!(
+            target: telemetry::TARGET,
+            "http.client",
+            otel.name = %format_args!("HTTP {host}"),
+            otel.kind = "client",
+            server.address = %host,
+            http.response.status_code = tracing::field::Empty,
+            http.request.resend_count = tracing::field::Empty,
+        );
+        let started = Instant::now();
+        let mut current = builder.timeout(options.timeout);
+        if let Some(traceparent) = span.in_scope(telemetry::current_traceparent) {
+            current = current.header(TRACEPARENT_HEADER, traceparent);
+        }
+        let mut retries = 0;
+        let mut throttled = false;
+
+        let outcome = async {
+            loop {
+                let next = if retries < options.max_retries {
+                    current.try_clone()
+                } else {
+                    None
+                };
+                throttled |= client.limiter.acquire(&host).await;
+                let outcome = current.send().await;
+
+                let delay = match &outcome {
+                    Ok(response) if is_retryable_status(response.status()) => Some(
+                        retry_after(response.headers(), client.retry_max_delay).unwrap_or_else(
+                            || backoff(retries, client.retry_base_delay, client.retry_max_delay),
+                        ),
+                    ),
+                    Err(e) if is_retryable_error(e) => Some(backoff(
+                        retries,
+                        client.retry_base_delay,
+                        client.retry_max_delay,
+                    )),
+                    _ => None,
+                };
+                match (delay, next) {
+                    (Some(delay), Some(next)) => {
+                        tracing::debug!(
+                            host = %host,
+                            retry = retries + 1,
+                            delay_ms = delay.as_millis() as u64,
+                            "Retrying outbound request"
+                        );
+                        tokio::time::sleep(delay).await;
+                        retries += 1;
+                        current = next;
+                    }
+                    _ => break outcome,
+                }
+            }
+        }
+        .instrument(span.clone())
+        .await;
+        if let Ok(response) = &outcome {
+            span.record("http.response.status_code", response.status().as_u16());
+        }
+        if retries > 0 {
+            span.record("http.request.resend_count", retries);
+        }
+
+        let success = matches!(&outcome, Ok(response) if response.status().is_success());
+        client
+            .stats
+            .record(&host, success, retries, throttled, started.elapsed());
+        outcome.map_err(|e| AppError::internal(format!("Request to '{}' failed: {}", host, e)))
+    }
+}
@@ -0,0 +1,15 @@
+//! # filehub-http
+//!
+//! Shared outbound HTTP client for FileHub integrations. Every call made
+//! through [`HttpClient`] goes through one connection pool and picks up
+//! the configured proxy and TLS settings, per-host rate limiting, retries
+//! with backoff, and per-destination statistics.
+
+pub mod client;
+pub mod limiter;
+pub mod retry;
+pub mod secrets;
+pub mod stats;
+
+pub use client::{HttpClient, HttpRequest, RequestOptions};
+pub use stats::{DestinationStats, HttpStats};
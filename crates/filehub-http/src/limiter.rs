@@ -0,0 +1,154 @@
+//! Per-host token-bucket rate limiting of outbound requests.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use filehub_core::config::HostRateLimit;
+
+/// A token bucket refilled at a steady rate up to its burst size.
+#[derive(Debug)]
+struct Bucket {
+    /// Tokens available, fractional while refilling.
+    tokens: f64,
+    /// When `tokens` was last brought up to date.
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(limit: HostRateLimit, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(limit.burst),
+            updated: now,
+        }
+    }
+
+    /// Takes a token if one is available, otherwise returns how long
+    /// until one is.
+    fn take(&mut self, limit: HostRateLimit, now: Instant) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * limit.requests_per_second).min(f64::from(limit.burst));
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (1.0 - self.tokens) / limit.requests_per_second,
+            ))
+        }
+    }
+}
+
+/// Rate limits requests per destination host.
+#[derive(Debug)]
+pub struct HostLimiter {
+    /// Limit of hosts without an override.
+    default: HostRateLimit,
+    /// Limits of specific hosts.
+    overrides: HashMap<String, HostRateLimit>,
+    /// Buckets by host, created on first use.
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl HostLimiter {
+    /// Creates a limiter applying `default` to every host not in
+    /// `overrides`.
+    pub fn new(default: HostRateLimit, overrides: HashMap<String, HostRateLimit>) -> Self {
+        Self {
+            default,
+            overrides,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The limit applied to `host`.
+    pub fn limit_for(&self, host: &str) -> HostRateLimit {
+        self.overrides.get(host).copied().unwrap_or(self.default)
+    }
+
+    /// Takes a token for `host` at `now`, or returns how long to wait
+    /// before asking again.
+    pub fn try_acquire(&self, host: &str, now: Instant) -> Option<Duration> {
+        let limit = self.limit_for(host);
+        if limit.requests_per_second <= 0.0 {
+            return None;
+        }
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        buckets
+            .entry(host.to_string())
+            .or_insert_with(|| Bucket::new(limit, now))
+            .take(limit, now)
+    }
+
+    /// Waits until a request to `host` may be sent. Returns whether it had
+    /// to wait.
+    pub async fn acquire(&self, host: &str) -> bool {
+        let mut waited = false;
+        while let Some(wait) = self.try_acquire(host, Instant::now()) {
+            waited = true;
+            tokio::time::sleep(wait).await;
+        }
+        waited
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(requests_per_second: f64, burst: u32) -> HostRateLimit {
+        HostRateLimit {
+            requests_per_second,
+            burst,
+        }
+    }
+
+    #[test]
+    fn test_burst_then_steady_rate() {
+        let limiter = HostLimiter::new(limit(2.0, 3), HashMap::new());
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(limiter.try_acquire("a.example", start), None);
+        }
+        assert_eq!(
+            limiter.try_acquire("a.example", start),
+            Some(Duration::from_millis(500))
+        );
+
+        let later = start + Duration::from_millis(500);
+        assert_eq!(limiter.try_acquire("a.example", later), None);
+        assert!(limiter.try_acquire("a.example", later).is_some());
+    }
+
+    #[test]
+    fn test_hosts_are_limited_separately() {
+        let limiter = HostLimiter::new(limit(1.0, 1), HashMap::new());
+        let now = Instant::now();
+        assert_eq!(limiter.try_acquire("a.example", now), None);
+        assert!(limiter.try_acquire("a.example", now).is_some());
+        assert_eq!(limiter.try_acquire("b.example", now), None);
+    }
+
+    #[test]
+    fn test_overrides_and_unlimited_hosts() {
+        let overrides = HashMap::from([("slow.example".to_string(), limit(1.0, 1))]);
+        let limiter = HostLimiter::new(limit(0.0, 0), overrides);
+        let now = Instant::now();
+        for _ in 0..100 {
+            assert_eq!(limiter.try_acquire("fast.example", now), None);
+        }
+        assert_eq!(limiter.try_acquire("slow.example", now), None);
+        assert!(limiter.try_acquire("slow.example", now).is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_acquire_waits_for_a_token() {
+        let limiter = HostLimiter::new(limit(10.0, 1), HashMap::new());
+        assert!(!limiter.acquire("a.example").await);
+        let start = tokio::time::Instant::now();
+        assert!(limiter.acquire("a.example").await);
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,86 @@
+//! When and after how long a failed request is retried.
+
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+
+/// Whether a response with `status` is worth retrying: the destination
+/// is rate limiting or temporarily unavailable.
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Whether a request that failed without a response is worth retrying:
+/// it timed out or could not connect.
+pub fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_connect()
+}
+
+/// Delay before retry number `retry` (0 for the first): `base` doubled per
+/// retry, at most `max`.
+pub fn backoff(retry: u32, base: Duration, max: Duration) -> Duration {
+    base.saturating_mul(2u32.saturating_pow(retry)).min(max)
+}
+
+/// The delay asked for by a `Retry-After` header in seconds, at most
+/// `max`. HTTP dates are not supported and yield `None`.
+pub fn retry_after(headers: &HeaderMap, max: Duration) -> Option<Duration> {
+    let seconds: u64 = headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds).min(max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!is_retryable_status(StatusCode::BAD_REQUEST));
+        assert!(!is_retryable_status(StatusCode::OK));
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let base = Duration::from_millis(200);
+        let max = Duration::from_secs(1);
+        assert_eq!(backoff(0, base, max), Duration::from_millis(200));
+        assert_eq!(backoff(1, base, max), Duration::from_millis(400));
+        assert_eq!(backoff(2, base, max), Duration::from_millis(800));
+        assert_eq!(backoff(3, base, max), max);
+        assert_eq!(backoff(40, base, max), max);
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let max = Duration::from_secs(10);
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers, max), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("3"));
+        assert_eq!(retry_after(&headers, max), Some(Duration::from_secs(3)));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers, max), Some(max));
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers, max), None);
+    }
+}
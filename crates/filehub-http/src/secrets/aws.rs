@@ -0,0 +1,135 @@
+//! AWS Secrets Manager backend for `aws-sm:` references.
+
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use aws_config::{BehaviorVersion, Region};
+use aws_credential_types::provider::{ProvideCredentials, SharedCredentialsProvider};
+use aws_sigv4::http_request::{SignableBody, SignableRequest, SigningSettings, sign};
+use aws_sigv4::sign::v4;
+use aws_smithy_runtime_api::client::identity::Identity;
+use reqwest::header::{HeaderName, HeaderValue};
+
+use filehub_core::config::AwsSecretsConfig;
+use filehub_core::error::AppError;
+use filehub_core::result::AppResult;
+use filehub_core::traits::SecretBackend;
+
+use crate::HttpClient;
+
+/// Signing name of the service.
+const SERVICE: &str = "secretsmanager";
+
+/// Reads secret strings from AWS Secrets Manager.
+#[derive(Debug)]
+pub struct AwsSecretsBackend {
+    client: HttpClient,
+    credentials: SharedCredentialsProvider,
+    region: String,
+    endpoint: String,
+}
+
+impl AwsSecretsBackend {
+    /// Loads the region and credentials from the AWS provider chain.
+    pub async fn new(config: &AwsSecretsConfig, client: HttpClient) -> Result<Self, AppError> {
+        let mut loader = aws_config::defaults(BehaviorVersion::latest());
+        if let Some(region) = &config.region {
+            loader = loader.region(Region::new(region.clone()));
+        }
+        let sdk = loader.load().await;
+        let region = sdk.region().map(|r| r.to_string()).ok_or_else(|| {
+            AppError::configuration("No AWS region found; set secrets.aws.region")
+        })?;
+        let credentials = sdk
+            .credentials_provider()
+            .ok_or_else(|| AppError::configuration("No AWS credentials provider found"))?;
+        let endpoint = config
+            .endpoint_url
+            .as_deref()
+            .map(|url| url.trim_end_matches('/').to_string())
+            .unwrap_or_else(|| format!("https://{SERVICE}.{region}.amazonaws.com"));
+
+        Ok(Self {
+            client,
+            credentials,
+            region,
+            endpoint,
+        })
+    }
+}
+
+#[async_trait]
+impl SecretBackend for AwsSecretsBackend {
+    fn scheme(&self) -> &'static str {
+        "aws-sm"
+    }
+
+    async fn fetch(&self, path: &str, field: Option<&str>) -> AppResult<String> {
+        let identity: Identity = self
+            .credentials
+            .provide_credentials()
+            .await
+            .map_err(|e| AppError::configuration(format!("no AWS credentials: {e}")))?
+            .into();
+        let body = serde_json::json!({ "SecretId": path }).to_string();
+        let url = format!("{}/", self.endpoint);
+        let headers = [
+            ("content-type", "application/x-amz-json-1.1"),
+            ("x-amz-target", "secretsmanager.GetSecretValue"),
+        ];
+
+        let params = v4::SigningParams::builder()
+            .identity(&identity)
+            .region(&self.region)
+            .name(SERVICE)
+            .time(SystemTime::now())
+            .settings(SigningSettings::default())
+            .build()
+            .map_err(|e| AppError::internal(format!("AWS request signing failed: {e}")))?
+            .into();
+        let signable = SignableRequest::new(
+            "POST",
+            url.as_str(),
+            headers.into_iter(),
+            SignableBody::Bytes(body.as_bytes()),
+        )
+        .and_then(|request| sign(request, &params))
+        .map_err(|e| AppError::internal(format!("AWS request signing failed: {e}")))?;
+        let (instructions, _) = signable.into_parts();
+
+        let mut request = self.client.post(&url).body(body.clone());
+        for (name, value) in headers.into_iter().chain(instructions.headers()) {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| AppError::internal(format!("Invalid signing header: {e}")))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|e| AppError::internal(format!("Invalid signing header: {e}")))?;
+            request = request.header(name, value);
+        }
+        let response = request.send().await?;
+
+        let status = response.status();
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::internal(format!("invalid Secrets Manager response: {e}")))?;
+        if !status.is_success() {
+            let kind = body["__type"].as_str().unwrap_or("error");
+            let message = body["message"]
+                .as_str()
+                .or_else(|| body["Message"].as_str())
+                .unwrap_or_default();
+            return Err(AppError::internal(format!("{status} {kind}: {message}")));
+        }
+
+        let secret = body["SecretString"]
+            .as_str()
+            .ok_or_else(|| AppError::validation("secret has no string value"))?;
+        let Some(field) = field else {
+            return Ok(secret.to_string());
+        };
+        serde_json::from_str::<serde_json::Value>(secret)
+            .ok()
+            .and_then(|value| value.get(field)?.as_str().map(str::to_string))
+            .ok_or_else(|| AppError::not_found(format!("secret has no string field '{field}'")))
+    }
+}
@@ -0,0 +1,45 @@
+//! Secret backends reached over HTTP, and resolution of the secrets
+//! referenced from configuration at startup.
+//!
+//! Backends are only built for schemes the configuration references, and
+//! only in builds with their feature: `vault` for Vault, `aws-secrets` for
+//! AWS Secrets Manager.
+
+#[cfg(feature = "aws-secrets")]
+pub mod aws;
+#[cfg(feature = "vault")]
+pub mod vault;
+
+use filehub_core::config::{AppConfig, SecretResolver};
+use filehub_core::error::AppError;
+
+/// Resolves every secret `config` references, failing if one cannot be.
+pub async fn resolve_config_secrets(config: &mut AppConfig) -> Result<(), AppError> {
+    let resolver = resolver(config).await?;
+    config.resolve_secrets(&resolver).await
+}
+
+/// A resolver with a backend for each remote scheme `config` references.
+pub async fn resolver(config: &AppConfig) -> Result<SecretResolver, AppError> {
+    #[allow(unused_mut)]
+    let mut resolver = SecretResolver::new();
+
+    #[cfg(feature = "vault")]
+    if config.references_secret_scheme("vault") {
+        let client = crate::HttpClient::new(&config.http_client)?;
+        let backend = vault::VaultBackend::new(&config.secrets.vault, client).await?;
+        resolver = resolver.with_backend(std::sync::Arc::new(backend));
+    }
+
+    #[cfg(feature = "aws-secrets")]
+    if config.references_secret_scheme("aws-sm") {
+        let client = crate::HttpClient::new(&config.http_client)?;
+        let backend = aws::AwsSecretsBackend::new(&config.secrets.aws, client).await?;
+        resolver = resolver.with_backend(std::sync::Arc::new(backend));
+    }
+
+    #[cfg(not(any(feature = "vault", feature = "aws-secrets")))]
+    let _ = config;
+
+    Ok(resolver)
+}
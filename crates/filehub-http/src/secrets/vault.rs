@@ -0,0 +1,100 @@
+//! HashiCorp Vault KV v2 backend for `vault:` references.
+
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use reqwest::header::{HeaderName, HeaderValue};
+
+use filehub_core::config::{SecretResolver, VaultConfig};
+use filehub_core::error::AppError;
+use filehub_core::result::AppResult;
+use filehub_core::traits::SecretBackend;
+
+use crate::HttpClient;
+
+/// Field read when a reference names none.
+const DEFAULT_FIELD: &str = "value";
+
+/// Reads secrets from a Vault KV v2 engine.
+#[derive(Debug)]
+pub struct VaultBackend {
+    client: HttpClient,
+    address: String,
+    mount: String,
+    namespace: Option<HeaderValue>,
+    token: HeaderValue,
+}
+
+impl VaultBackend {
+    /// Connects to the configured Vault, resolving its token.
+    pub async fn new(config: &VaultConfig, client: HttpClient) -> Result<Self, AppError> {
+        let mut token = config.token.clone();
+        SecretResolver::new()
+            .resolve(&mut token)
+            .await
+            .map_err(|e| AppError::configuration(format!("secrets.vault.token: {e}")))?;
+        let mut token = HeaderValue::from_str(token.expose())
+            .map_err(|_| AppError::configuration("secrets.vault.token is not a valid token"))?;
+        token.set_sensitive(true);
+        let namespace = config
+            .namespace
+            .as_deref()
+            .map(HeaderValue::from_str)
+            .transpose()
+            .map_err(|_| AppError::configuration("Invalid secrets.vault.namespace"))?;
+
+        Ok(Self {
+            client,
+            address: config.address.trim_end_matches('/').to_string(),
+            mount: config.mount.trim_matches('/').to_string(),
+            namespace,
+            token,
+        })
+    }
+}
+
+#[async_trait]
+impl SecretBackend for VaultBackend {
+    fn scheme(&self) -> &'static str {
+        "vault"
+    }
+
+    async fn fetch(&self, path: &str, field: Option<&str>) -> AppResult<String> {
+        let url = format!(
+            "{}/v1/{}/data/{}",
+            self.address,
+            self.mount,
+            path.trim_matches('/')
+        );
+        let mut request = self
+            .client
+            .get(&url)
+            .header(HeaderName::from_static("x-vault-token"), self.token.clone());
+        if let Some(namespace) = &self.namespace {
+            request = request.header(
+                HeaderName::from_static("x-vault-namespace"),
+                namespace.clone(),
+            );
+        }
+        let response = request.send().await?;
+
+        match response.status() {
+            status if status.is_success() => {}
+            StatusCode::NOT_FOUND => return Err(AppError::not_found("no secret at this path")),
+            StatusCode::FORBIDDEN => {
+                return Err(AppError::forbidden("the token may not read this path"));
+            }
+            status => return Err(AppError::internal(format!("Vault returned {status}"))),
+        }
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::internal(format!("invalid Vault response: {e}")))?;
+
+        let field = field.unwrap_or(DEFAULT_FIELD);
+        body.pointer("/data/data")
+            .and_then(|data| data.get(field))
+            .and_then(|value| value.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| AppError::not_found(format!("secret has no string field '{field}'")))
+    }
+}
@@ -0,0 +1,86 @@
+//! Per-destination request statistics.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Counters of requests to one destination host since startup.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DestinationStats {
+    /// Destination host.
+    pub host: String,
+    /// Requests made, each counted once however many attempts it took.
+    pub requests: u64,
+    /// Requests that ended in a transport error or a non-2xx response.
+    pub failures: u64,
+    /// Attempts repeated after a retryable failure.
+    pub retries: u64,
+    /// Requests delayed by the host's rate limit.
+    pub throttled: u64,
+    /// Total time spent on requests, retries included, in seconds.
+    pub latency_seconds: f64,
+}
+
+/// Statistics of every destination the client has called.
+#[derive(Debug, Default)]
+pub struct HttpStats {
+    /// Counters by host.
+    hosts: Mutex<HashMap<String, DestinationStats>>,
+}
+
+impl HttpStats {
+    /// Records one completed request to `host`.
+    pub fn record(
+        &self,
+        host: &str,
+        success: bool,
+        retries: u32,
+        throttled: bool,
+        elapsed: Duration,
+    ) {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = hosts
+            .entry(host.to_string())
+            .or_insert_with(|| DestinationStats {
+                host: host.to_string(),
+                ..Default::default()
+            });
+        stats.requests += 1;
+        stats.failures += u64::from(!success);
+        stats.retries += u64::from(retries);
+        stats.throttled += u64::from(throttled);
+        stats.latency_seconds += elapsed.as_secs_f64();
+    }
+
+    /// Current counters of every destination, ordered by host.
+    pub fn snapshot(&self) -> Vec<DestinationStats> {
+        let hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        let mut snapshot: Vec<DestinationStats> = hosts.values().cloned().collect();
+        snapshot.sort_by(|a, b| a.host.cmp(&b.host));
+        snapshot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_snapshot() {
+        let stats = HttpStats::default();
+        stats.record("b.example", true, 0, false, Duration::from_millis(100));
+        stats.record("a.example", false, 2, true, Duration::from_millis(250));
+        stats.record("b.example", false, 1, false, Duration::from_millis(50));
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[0].host, "a.example");
+        assert_eq!(snapshot[0].retries, 2);
+        assert_eq!(snapshot[0].throttled, 1);
+        let b = &snapshot[1];
+        assert_eq!((b.requests, b.failures, b.retries), (2, 1, 1));
+        assert!((b.latency_seconds - 0.15).abs() < 1e-9);
+    }
+}
@@ -4,10 +4,18 @@ use std::sync::Arc;
 
 use uuid::Uuid;
 
+use filehub_core::types::RequestId;
+
 /// Context passed to plugins providing access to FileHub services.
 ///
 /// Plugins receive this when handling hooks, giving them access to
 /// caching, database queries, and notification dispatch.
+///
+/// Hooks fired while serving an HTTP request run inside that request's
+/// scope, so [`PluginContext::request_id`] returns its `X-Request-Id` and
+/// `tracing` events emitted by the plugin are attached to the request span.
+/// Include the id in any outbound calls or external logs to keep a
+/// request's journey correlated.
 #[derive(Clone)]
 pub struct PluginContext {
     /// Cache accessor.
@@ -20,6 +28,14 @@ pub struct PluginContext {
     pub jobs: Arc<dyn PluginJobService>,
 }
 
+impl PluginContext {
+    /// Returns the correlation id of the HTTP request that triggered the
+    /// current hook, or `None` for background work (jobs, timers).
+    pub fn request_id(&self) -> Option<RequestId> {
+        RequestId::current()
+    }
+}
+
 impl std::fmt::Debug for PluginContext {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("PluginContext").finish()
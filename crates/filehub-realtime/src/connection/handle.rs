@@ -11,6 +11,7 @@ use filehub_core::types::id::{SessionId, UserId};
 use filehub_entity::user::role::UserRole;
 
 use crate::message::types::OutboundMessage;
+use crate::message::version::ProtocolVersion;
 
 /// Unique connection identifier
 pub type ConnectionId = Uuid;
@@ -33,6 +34,8 @@ pub struct ConnectionHandle {
     pub username: String,
     /// Sender for outbound messages
     pub sender: mpsc::Sender<OutboundMessage>,
+    /// Message schema version negotiated in the handshake
+    pub protocol: ProtocolVersion,
     /// Channels this connection is subscribed to
     pub subscriptions: tokio::sync::RwLock<Vec<String>>,
     /// When the connection was established
@@ -53,6 +56,7 @@ impl ConnectionHandle {
         user_role: UserRole,
         username: String,
         sender: mpsc::Sender<OutboundMessage>,
+        protocol: ProtocolVersion,
     ) -> Self {
         let now = Utc::now();
         Self {
@@ -62,6 +66,7 @@ impl ConnectionHandle {
             user_role,
             username,
             sender,
+            protocol,
             subscriptions: tokio::sync::RwLock::new(Vec::new()),
             connected_at: now,
             last_activity: tokio::sync::RwLock::new(now),
@@ -70,11 +75,16 @@ impl ConnectionHandle {
         }
     }
 
-    /// Send an outbound message to this connection
+    /// Send an outbound message to this connection, as its protocol
+    /// version can read it. Messages the version has no counterpart for
+    /// are dropped and count as not sent.
     pub async fn send(&self, msg: OutboundMessage) -> bool {
         if !self.is_alive() {
             return false;
         }
+        let Some(msg) = self.protocol.adapt(msg) else {
+            return false;
+        };
         match self.sender.try_send(msg) {
             Ok(_) => true,
             Err(mpsc::error::TrySendError::Full(_)) => {
@@ -146,6 +156,7 @@ impl ConnectionHandle {
             session_id: self.session_id,
             username: self.username.clone(),
             role: self.user_role.clone(),
+            protocol_version: self.protocol.number(),
             connected_at: self.connected_at,
             last_activity: *self.last_activity.read().await,
             subscriptions: self.subscriptions.read().await.clone(),
@@ -167,6 +178,8 @@ pub struct ConnectionInfo {
     pub username: String,
     /// Role
     pub role: UserRole,
+    /// Negotiated message schema version
+    pub protocol_version: u16,
     /// Connected at
     pub connected_at: DateTime<Utc>,
     /// Last activity
@@ -2,7 +2,6 @@
 
 use std::sync::Arc;
 
-use serde_json;
 use tokio::sync::mpsc;
 use tracing;
 use uuid::Uuid;
@@ -10,7 +9,12 @@ use uuid::Uuid;
 use filehub_core::types::id::{SessionId, UserId};
 use filehub_entity::user::role::UserRole;
 
+use crate::message::builder::build_error;
+use crate::message::serializer::{Frame, Versioned};
 use crate::message::types::{InboundMessage, OutboundMessage};
+use crate::message::validator::{validate_inbound, validate_version};
+use crate::message::version::ProtocolVersion;
+use crate::presence::viewers::is_viewer_channel;
 
 use super::handle::{ConnectionHandle, ConnectionId, ConnectionInfo};
 use super::pool::ConnectionPool;
@@ -36,17 +40,47 @@ impl ConnectionManager {
         }
     }
 
-    /// Handle inbound message from connection
-    pub async fn handle_inbound(&self, connection_id: &Uuid, text: &str) {
-        let msg: InboundMessage = match serde_json::from_str(text) {
+    /// Handle inbound message from connection. The frame is decoded by
+    /// its type and validated before it is acted on; a message claiming a
+    /// protocol version the connection does not speak is answered with an
+    /// `UNSUPPORTED_PROTOCOL_VERSION` error.
+    ///
+    /// Acknowledgements are returned for the caller to route to whatever
+    /// was acknowledged, presence updates for the caller to record, and
+    /// subscriptions to file and folder channels for
+    /// the caller to check against the resource's permissions before
+    /// [`subscribe`](Self::subscribe).
+    pub async fn handle_inbound(
+        &self,
+        connection_id: &Uuid,
+        frame: &Frame,
+    ) -> Option<InboundMessage> {
+        let Versioned { v, message: msg } = match frame.decode::<Versioned<InboundMessage>>() {
             Ok(m) => m,
             Err(e) => {
                 tracing::warn!(%connection_id, error = %e, "Failed to parse inbound message");
-                return;
+                return None;
             }
         };
+        let negotiated = self
+            .pool
+            .get(*connection_id)
+            .map_or(ProtocolVersion::CURRENT, |handle| handle.protocol);
+        if let Err(e) = validate_version(v, negotiated) {
+            tracing::warn!(%connection_id, error = %e.message, "Unsupported protocol version");
+            let error = build_error("UNSUPPORTED_PROTOCOL_VERSION", &e.message, None);
+            self.send_to_connection(*connection_id, error).await;
+            return None;
+        }
+        if let Err(e) = validate_inbound(&msg) {
+            tracing::warn!(%connection_id, error = %e.message, "Invalid inbound message");
+            return None;
+        }
 
         match msg {
+            InboundMessage::Subscribe { ref channel } if is_viewer_channel(channel) => {
+                return Some(msg);
+            }
             InboundMessage::Subscribe { channel } => {
                 if let Err(e) = self.subscribe(*connection_id, &channel).await {
                     tracing::warn!(%connection_id, channel, error = %e, "Subscription failed");
@@ -65,14 +99,16 @@ impl ConnectionManager {
                     handle.record_pong().await;
                 }
             }
+            InboundMessage::Ack { .. } | InboundMessage::PresenceUpdate { .. } => return Some(msg),
             _ => {
                 // Ignore other messages for now
                 tracing::debug!(%connection_id, ?msg, "Unhandled inbound message");
             }
         }
+        None
     }
 
-    /// Register a new connection.
+    /// Register a new connection speaking message schema `protocol`.
     ///
     /// Returns `None` if the user already has max connections.
     pub fn register(
@@ -82,6 +118,7 @@ impl ConnectionManager {
         user_role: UserRole,
         username: String,
         sender: mpsc::Sender<OutboundMessage>,
+        protocol: ProtocolVersion,
     ) -> Option<Arc<ConnectionHandle>> {
         let current = self.pool.user_connection_count(user_id);
         if current >= self.max_per_user {
@@ -100,22 +137,24 @@ impl ConnectionManager {
             user_role,
             username.clone(),
             sender,
+            protocol,
         ));
 
         self.pool.add(Arc::clone(&handle));
 
         tracing::info!(
-            "Connection registered: id={}, user='{}', session={}",
+            "Connection registered: id={}, user='{}', session={}, protocol={}",
             handle.id,
             username,
-            session_id
+            session_id,
+            protocol
         );
 
         Some(handle)
     }
 
     /// Unregister a connection
-    pub fn unregister(&self, connection_id: ConnectionId) {
+    pub async fn unregister(&self, connection_id: ConnectionId) {
         if let Some(handle) = self.pool.remove(connection_id) {
             handle.mark_dead();
             tracing::info!(
@@ -123,6 +162,7 @@ impl ConnectionManager {
                 connection_id,
                 handle.username
             );
+            self.announce_departure(&handle).await;
         }
     }
 
@@ -147,6 +187,11 @@ impl ConnectionManager {
         }
     }
 
+    /// Get all connections subscribed to a channel
+    pub async fn subscribed_to(&self, channel: &str) -> Vec<Arc<ConnectionHandle>> {
+        self.pool.subscribed_to(channel).await
+    }
+
     /// Send to all connections subscribed to a channel
     pub async fn send_to_channel(&self, channel: &str, msg: OutboundMessage) {
         let conns = self.pool.subscribed_to(channel).await;
@@ -162,7 +207,26 @@ impl ConnectionManager {
         }
     }
 
-    /// Subscribe a connection to a channel
+    /// Send to every connection accepted by `include`; returns the users
+    /// reached, each once
+    pub async fn send_where(
+        &self,
+        msg: OutboundMessage,
+        include: impl Fn(&ConnectionHandle) -> bool,
+    ) -> Vec<UserId> {
+        let mut reached = Vec::new();
+        for conn in self.pool.all_connections() {
+            if include(&conn) && conn.send(msg.clone()).await && !reached.contains(&conn.user_id) {
+                reached.push(conn.user_id);
+            }
+        }
+        reached
+    }
+
+    /// Subscribe a connection to a channel.
+    ///
+    /// The first of a user's connections to subscribe to a file or folder
+    /// channel announces them to it as a viewer.
     pub async fn subscribe(
         &self,
         connection_id: ConnectionId,
@@ -174,15 +238,73 @@ impl ConnectionManager {
             return Err("Max subscriptions reached");
         }
 
-        Ok(handle.subscribe(channel).await)
+        let added = handle.subscribe(channel).await;
+        if added && is_viewer_channel(channel) && !self.viewing_elsewhere(&handle, channel).await {
+            let msg = OutboundMessage::ViewerJoined {
+                channel: channel.to_string(),
+                user_id: handle.user_id.into_uuid(),
+                username: handle.username.clone(),
+                timestamp: chrono::Utc::now(),
+            };
+            self.send_to_channel(channel, msg).await;
+        }
+        Ok(added)
     }
 
     /// Unsubscribe a connection from a channel
     pub async fn unsubscribe(&self, connection_id: ConnectionId, channel: &str) -> bool {
-        if let Some(handle) = self.pool.get(connection_id) {
-            handle.unsubscribe(channel).await
-        } else {
-            false
+        let Some(handle) = self.pool.get(connection_id) else {
+            return false;
+        };
+        let removed = handle.unsubscribe(channel).await;
+        if removed && is_viewer_channel(channel) {
+            self.announce_left(&handle, channel).await;
+        }
+        removed
+    }
+
+    /// Announce a removed connection's user as no longer viewing the files
+    /// and folders it was subscribed to, unless another of their
+    /// connections still is.
+    async fn announce_departure(&self, handle: &ConnectionHandle) {
+        let channels = handle.subscriptions.read().await.clone();
+        for channel in channels.iter().filter(|c| is_viewer_channel(c)) {
+            self.announce_left(handle, channel).await;
+        }
+    }
+
+    /// Send [`OutboundMessage::ViewerLeft`] for `handle`'s user on
+    /// `channel`, unless another of their connections is subscribed.
+    async fn announce_left(&self, handle: &ConnectionHandle, channel: &str) {
+        if self.viewing_elsewhere(handle, channel).await {
+            return;
+        }
+        let msg = OutboundMessage::ViewerLeft {
+            channel: channel.to_string(),
+            user_id: handle.user_id.into_uuid(),
+            username: handle.username.clone(),
+            timestamp: chrono::Utc::now(),
+        };
+        self.send_to_channel(channel, msg).await;
+    }
+
+    /// Whether another live connection of `handle`'s user is subscribed to
+    /// `channel`
+    async fn viewing_elsewhere(&self, handle: &ConnectionHandle, channel: &str) -> bool {
+        for conn in self.pool.get_user_connections(handle.user_id) {
+            if conn.id != handle.id && conn.is_alive() && conn.is_subscribed(channel).await {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Send a message to every connection of a session
+    pub async fn send_to_session(&self, session_id: SessionId, msg: OutboundMessage) {
+        for conn in self.pool.all_connections() {
+            if conn.session_id == session_id {
+                conn.send(msg.clone()).await;
+            }
         }
     }
 
@@ -195,14 +317,19 @@ impl ConnectionManager {
         };
 
         let conns = self.pool.all_connections();
+        let mut closed = Vec::new();
         for conn in conns {
             if conn.session_id == session_id {
                 conn.send(msg.clone()).await;
                 conn.mark_dead();
+                closed.push(conn);
             }
         }
 
         self.pool.prune_dead();
+        for conn in closed {
+            self.announce_departure(&conn).await;
+        }
     }
 
     /// Get all connections for a user
@@ -231,8 +358,18 @@ impl ConnectionManager {
     }
 
     /// Prune dead connections
-    pub fn prune_dead(&self) -> usize {
-        self.pool.prune_dead()
+    pub async fn prune_dead(&self) -> usize {
+        let dead: Vec<Arc<ConnectionHandle>> = self
+            .pool
+            .all_connections()
+            .into_iter()
+            .filter(|conn| !conn.is_alive())
+            .collect();
+        let count = self.pool.prune_dead();
+        for conn in dead {
+            self.announce_departure(&conn).await;
+        }
+        count
     }
 
     /// Get info for all connections (admin view)
@@ -244,3 +381,73 @@ impl ConnectionManager {
         infos
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connect(
+        manager: &ConnectionManager,
+        protocol: ProtocolVersion,
+    ) -> (Arc<ConnectionHandle>, mpsc::Receiver<OutboundMessage>) {
+        let (tx, rx) = mpsc::channel(16);
+        let handle = manager
+            .register(
+                UserId::new(),
+                SessionId::new(),
+                UserRole::Viewer,
+                format!("user-{protocol}"),
+                tx,
+                protocol,
+            )
+            .unwrap();
+        (handle, rx)
+    }
+
+    #[tokio::test]
+    async fn test_v1_client_never_receives_v2_only_messages() {
+        let manager = ConnectionManager::new(5, 10);
+        let channel = format!("file:{}", Uuid::new_v4());
+        let (old, mut old_rx) = connect(&manager, ProtocolVersion::V1);
+        let (new, mut new_rx) = connect(&manager, ProtocolVersion::V2);
+
+        manager.subscribe(old.id, &channel).await.unwrap();
+        manager.subscribe(new.id, &channel).await.unwrap();
+        manager
+            .broadcast(OutboundMessage::ServerDraining {
+                reconnect_after_ms: 1000,
+                deadline: chrono::Utc::now(),
+            })
+            .await;
+        manager.unsubscribe(new.id, &channel).await;
+
+        let mut old_types = Vec::new();
+        while let Ok(msg) = old_rx.try_recv() {
+            old_types.push(serde_json::to_value(&msg).unwrap()["type"].clone());
+        }
+        // Both arrivals, the drain and the v2 client's departure are all
+        // v2-only; the v1 client saw none of them.
+        assert!(old_types.is_empty(), "v1 client received {old_types:?}");
+
+        let mut new_types = Vec::new();
+        while let Ok(msg) = new_rx.try_recv() {
+            new_types.push(serde_json::to_value(&msg).unwrap()["type"].clone());
+        }
+        assert_eq!(new_types, ["viewer_joined", "server_draining"]);
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_inbound_version_is_answered_with_error() {
+        let manager = ConnectionManager::new(5, 10);
+        let (old, mut old_rx) = connect(&manager, ProtocolVersion::V1);
+
+        let frame = Frame::Text(r#"{"type":"heartbeat","v":2}"#.to_string());
+        assert!(manager.handle_inbound(&old.id, &frame).await.is_none());
+
+        let Ok(OutboundMessage::Error { code, message, .. }) = old_rx.try_recv() else {
+            panic!("expected an error message");
+        };
+        assert_eq!(code, "UNSUPPORTED_PROTOCOL_VERSION");
+        assert!(message.contains("negotiated version 1"));
+    }
+}
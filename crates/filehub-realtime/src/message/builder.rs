@@ -3,6 +3,8 @@
 use chrono::Utc;
 use uuid::Uuid;
 
+use filehub_entity::notification::model::AdminBroadcast;
+
 use super::types::OutboundMessage;
 
 /// Build a notification outbound message
@@ -97,12 +99,28 @@ pub fn build_admin_broadcast(
         message: message.to_string(),
         severity: severity.to_string(),
         persistent,
+        require_ack: false,
         action_type: None,
         action_payload: None,
         timestamp: Utc::now(),
     }
 }
 
+/// Build the message for a stored admin broadcast
+pub fn build_stored_broadcast(broadcast: &AdminBroadcast) -> OutboundMessage {
+    OutboundMessage::AdminBroadcast {
+        broadcast_id: broadcast.id,
+        title: broadcast.title.clone(),
+        message: broadcast.message.clone(),
+        severity: broadcast.severity.clone(),
+        persistent: broadcast.persistent,
+        require_ack: broadcast.require_ack,
+        action_type: broadcast.action_type.clone(),
+        action_payload: broadcast.action_payload.clone(),
+        timestamp: broadcast.sent_at.unwrap_or_else(Utc::now),
+    }
+}
+
 /// Build an error message
 pub fn build_error(code: &str, message: &str, request_id: Option<String>) -> OutboundMessage {
     OutboundMessage::Error {
@@ -5,6 +5,8 @@ pub mod envelope;
 pub mod serializer;
 pub mod types;
 pub mod validator;
+pub mod version;
 
 pub use envelope::MessageEnvelope;
 pub use types::{InboundMessage, OutboundMessage};
+pub use version::ProtocolVersion;
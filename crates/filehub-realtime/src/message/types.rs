@@ -78,6 +78,15 @@ pub enum OutboundMessage {
         timestamp: DateTime<Utc>,
     },
 
+    /// The server is shutting down; the client should close this
+    /// connection and reconnect, which reaches another node
+    ServerDraining {
+        /// Suggested delay before reconnecting, in milliseconds
+        reconnect_after_ms: u64,
+        /// When remaining connections will be dropped
+        deadline: DateTime<Utc>,
+    },
+
     // ── Subscription responses ───────────────────────────────
     /// Subscription confirmed
     Subscribed {
@@ -120,6 +129,21 @@ pub enum OutboundMessage {
         timestamp: DateTime<Utc>,
     },
 
+    /// Files were uploaded to a folder in bulk; sent in place of their
+    /// `FileCreated` messages
+    FilesAdded {
+        /// Folder they were added to
+        folder_id: Uuid,
+        /// Number of files
+        count: u64,
+        /// Who uploaded them
+        actor_id: Uuid,
+        /// Actor username
+        actor_name: String,
+        /// Timestamp
+        timestamp: DateTime<Utc>,
+    },
+
     /// A file was updated (metadata or content)
     FileUpdated {
         /// File ID
@@ -443,6 +467,32 @@ pub enum OutboundMessage {
         timestamp: DateTime<Utc>,
     },
 
+    /// A user started viewing a file or folder: their first connection
+    /// subscribed to its channel
+    ViewerJoined {
+        /// Channel name
+        channel: String,
+        /// User ID
+        user_id: Uuid,
+        /// Username
+        username: String,
+        /// Timestamp
+        timestamp: DateTime<Utc>,
+    },
+
+    /// A user stopped viewing a file or folder: their last connection
+    /// subscribed to its channel left it
+    ViewerLeft {
+        /// Channel name
+        channel: String,
+        /// User ID
+        user_id: Uuid,
+        /// Username
+        username: String,
+        /// Timestamp
+        timestamp: DateTime<Utc>,
+    },
+
     // ── Session events (admin) ───────────────────────────────
     /// A new session was created (admin channel)
     SessionCreated {
@@ -470,6 +520,17 @@ pub enum OutboundMessage {
         terminated_at: DateTime<Utc>,
     },
 
+    /// The session will be terminated for inactivity unless the client
+    /// shows activity (any message, including a heartbeat) first
+    SessionIdleWarning {
+        /// Session ID
+        session_id: SessionId,
+        /// When the session will be terminated
+        logout_at: DateTime<Utc>,
+        /// Whole minutes left, for display
+        minutes_remaining: i64,
+    },
+
     /// Session count updated (admin channel)
     SessionCountUpdated {
         /// Active session count
@@ -542,6 +603,10 @@ pub enum OutboundMessage {
         severity: String,
         /// Whether it should persist on screen
         persistent: bool,
+        /// Whether the client must acknowledge it with an `ack` carrying
+        /// the broadcast ID
+        #[serde(default)]
+        require_ack: bool,
         /// Optional action
         action_type: Option<String>,
         /// Action payload
@@ -1,6 +1,7 @@
 //! Message validation for inbound WebSocket messages.
 
 use super::types::InboundMessage;
+use super::version::ProtocolVersion;
 use crate::channel::types::ChannelType;
 
 /// Validation error
@@ -10,6 +11,35 @@ pub struct ValidationError {
     pub message: String,
 }
 
+/// Validate the schema version an inbound message claims: it must be one
+/// the server speaks, and no newer than the connection negotiated.
+/// Messages claiming none are read as the negotiated version.
+pub fn validate_version(
+    claimed: Option<u16>,
+    negotiated: ProtocolVersion,
+) -> Result<(), ValidationError> {
+    let Some(number) = claimed else {
+        return Ok(());
+    };
+    match ProtocolVersion::from_number(number) {
+        Some(version) if version <= negotiated => Ok(()),
+        Some(_) => Err(ValidationError {
+            message: format!(
+                "Message claims protocol version {number}, but this connection negotiated \
+                 version {}; reconnect with version={number}",
+                negotiated.number()
+            ),
+        }),
+        None => Err(ValidationError {
+            message: format!(
+                "Unsupported protocol version {number}; supported versions are {} to {}",
+                ProtocolVersion::OLDEST.number(),
+                ProtocolVersion::CURRENT.number()
+            ),
+        }),
+    }
+}
+
 /// Validate an inbound message
 pub fn validate_inbound(msg: &InboundMessage) -> Result<(), ValidationError> {
     match msg {
@@ -46,3 +76,20 @@ pub fn validate_inbound(msg: &InboundMessage) -> Result<(), ValidationError> {
         _ => Ok(()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_version() {
+        assert!(validate_version(None, ProtocolVersion::V1).is_ok());
+        assert!(validate_version(Some(1), ProtocolVersion::V2).is_ok());
+        assert!(validate_version(Some(2), ProtocolVersion::V2).is_ok());
+
+        let newer = validate_version(Some(2), ProtocolVersion::V1).unwrap_err();
+        assert!(newer.message.contains("negotiated version 1"));
+        let unknown = validate_version(Some(7), ProtocolVersion::V2).unwrap_err();
+        assert!(unknown.message.contains("Unsupported protocol version 7"));
+    }
+}
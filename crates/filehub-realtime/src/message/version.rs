@@ -0,0 +1,222 @@
+//! Message schema versions and downgrading for older clients.
+//!
+//! A client names the newest schema it understands with the `version`
+//! query parameter of the WebSocket handshake; clients that predate
+//! negotiation send none and get version 1. The server speaks the newest
+//! version both sides know, tags every outbound message with it (`"v"`),
+//! and rewrites or drops messages the client's version has no type for.
+//!
+//! | Message type           | Since | Sent to a v1 client as              |
+//! |------------------------|-------|-------------------------------------|
+//! | all others             | 1     | unchanged                           |
+//! | `server_draining`      | 2     | dropped; the client is disconnected |
+//! |                        |       | at the deadline and reconnects      |
+//! | `files_added`          | 2     | dropped; only the first uploads of  |
+//! |                        |       | a burst arrive as `file_created`    |
+//! | `viewer_joined`/`_left`| 2     | dropped                             |
+//! | `session_idle_warning` | 2     | `notification` in the `session`     |
+//! |                        |       | category                            |
+//!
+//! Fields added to version 1 types since (such as `require_ack` on
+//! `admin_broadcast`) are sent to every client; v1 clients ignore them.
+
+use std::fmt;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::types::OutboundMessage;
+
+/// A message schema version.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProtocolVersion {
+    /// The schema before negotiation existed.
+    #[default]
+    V1,
+    /// Adds drain, bulk upload, viewer and idle warning messages.
+    V2,
+}
+
+impl ProtocolVersion {
+    /// Oldest version the server still speaks.
+    pub const OLDEST: Self = Self::V1;
+    /// Newest version the server speaks.
+    pub const CURRENT: Self = Self::V2;
+
+    /// Version for a version number, if the server speaks it.
+    pub fn from_number(number: u16) -> Option<Self> {
+        match number {
+            1 => Some(Self::V1),
+            2 => Some(Self::V2),
+            _ => None,
+        }
+    }
+
+    /// Version number on the wire.
+    pub fn number(self) -> u16 {
+        match self {
+            Self::V1 => 1,
+            Self::V2 => 2,
+        }
+    }
+
+    /// Version to speak with a client offering `offered`: the newest
+    /// version both understand, or v1 if it offered none.
+    pub fn negotiate(offered: Option<u16>) -> Result<Self, UnsupportedVersion> {
+        match offered {
+            None => Ok(Self::V1),
+            Some(n) if n > Self::CURRENT.number() => Ok(Self::CURRENT),
+            Some(n) => Self::from_number(n).ok_or(UnsupportedVersion(n)),
+        }
+    }
+
+    /// `msg` as a client of this version can read it, or `None` if it
+    /// has no counterpart.
+    pub fn adapt(self, msg: OutboundMessage) -> Option<OutboundMessage> {
+        if introduced_in(&msg) <= self {
+            return Some(msg);
+        }
+        match msg {
+            OutboundMessage::SessionIdleWarning {
+                session_id,
+                logout_at,
+                minutes_remaining,
+            } => Some(OutboundMessage::Notification {
+                id: Uuid::new_v4(),
+                category: "session".to_string(),
+                event_type: "session_idle_warning".to_string(),
+                title: "Session about to expire".to_string(),
+                message: format!(
+                    "You will be logged out for inactivity in {minutes_remaining} minute(s)"
+                ),
+                payload: Some(serde_json::json!({
+                    "session_id": session_id,
+                    "logout_at": logout_at,
+                })),
+                priority: "high".to_string(),
+                actor_id: None,
+                actor_name: None,
+                resource_type: None,
+                resource_id: None,
+                timestamp: Utc::now(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}", self.number())
+    }
+}
+
+/// A version number the server does not speak.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedVersion(pub u16);
+
+impl fmt::Display for UnsupportedVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "protocol version {} is not supported; supported versions are {} to {}",
+            self.0,
+            ProtocolVersion::OLDEST.number(),
+            ProtocolVersion::CURRENT.number()
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedVersion {}
+
+/// The version that introduced `msg`'s type.
+pub fn introduced_in(msg: &OutboundMessage) -> ProtocolVersion {
+    match msg {
+        OutboundMessage::ServerDraining { .. }
+        | OutboundMessage::FilesAdded { .. }
+        | OutboundMessage::ViewerJoined { .. }
+        | OutboundMessage::ViewerLeft { .. }
+        | OutboundMessage::SessionIdleWarning { .. } => ProtocolVersion::V2,
+        _ => ProtocolVersion::V1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use filehub_core::types::id::SessionId;
+
+    fn viewer_joined() -> OutboundMessage {
+        OutboundMessage::ViewerJoined {
+            channel: format!("file:{}", Uuid::new_v4()),
+            user_id: Uuid::new_v4(),
+            username: "alice".to_string(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(ProtocolVersion::negotiate(None), Ok(ProtocolVersion::V1));
+        assert_eq!(ProtocolVersion::negotiate(Some(1)), Ok(ProtocolVersion::V1));
+        assert_eq!(ProtocolVersion::negotiate(Some(2)), Ok(ProtocolVersion::V2));
+        // A newer client is spoken to in the newest version we know.
+        assert_eq!(
+            ProtocolVersion::negotiate(Some(9)),
+            Ok(ProtocolVersion::CURRENT)
+        );
+        assert_eq!(
+            ProtocolVersion::negotiate(Some(0)),
+            Err(UnsupportedVersion(0))
+        );
+    }
+
+    #[test]
+    fn test_v2_messages_pass_to_v2() {
+        let msg = ProtocolVersion::V2.adapt(viewer_joined());
+        assert!(matches!(msg, Some(OutboundMessage::ViewerJoined { .. })));
+    }
+
+    #[test]
+    fn test_v2_only_messages_are_dropped_for_v1() {
+        let now = Utc::now();
+        let v2_only = [
+            viewer_joined(),
+            OutboundMessage::ServerDraining {
+                reconnect_after_ms: 1000,
+                deadline: now,
+            },
+            OutboundMessage::FilesAdded {
+                folder_id: Uuid::new_v4(),
+                count: 12,
+                actor_id: Uuid::new_v4(),
+                actor_name: "alice".to_string(),
+                timestamp: now,
+            },
+        ];
+        for msg in v2_only {
+            assert_eq!(introduced_in(&msg), ProtocolVersion::V2);
+            assert!(ProtocolVersion::V1.adapt(msg).is_none());
+        }
+    }
+
+    #[test]
+    fn test_idle_warning_downgrades_to_notification() {
+        let msg = ProtocolVersion::V1.adapt(OutboundMessage::SessionIdleWarning {
+            session_id: SessionId::from(Uuid::new_v4()),
+            logout_at: Utc::now(),
+            minutes_remaining: 5,
+        });
+        let Some(OutboundMessage::Notification {
+            event_type,
+            message,
+            ..
+        }) = msg
+        else {
+            panic!("expected a notification");
+        };
+        assert_eq!(event_type, "session_idle_warning");
+        assert!(message.contains("5 minute"));
+    }
+}
@@ -0,0 +1,468 @@
+//! Bridge from the domain event bus to WebSocket channels.
+//!
+//! File and folder events are pushed to the affected `file:` and `folder:`
+//! channels, share events to their `share:` channel. Domain events carry IDs only, so
+//! names the event does not include are sent empty and resolved by clients.
+//! Dispatch continues the trace of the request that published the event.
+//!
+//! A user share and access requests are also notified to the user they
+//! concern, through their notification preferences and in their locale,
+//! as are uploads to the owner of the folder uploaded to.
+//!
+//! Bulk uploads are coalesced (see [`super::coalesce`]): past the
+//! threshold of a burst, uploads are announced as one `files_added`
+//! message per folder and one notification to its owner. The domain
+//! events themselves are untouched, so audit still sees every upload.
+//!
+//! Events redelivered by the outbox relay are recognised by their ID and
+//! dispatched once.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use tokio::task::JoinHandle;
+use tracing::Instrument;
+use uuid::Uuid;
+
+use filehub_core::config::UploadCoalescingConfig;
+use filehub_core::events::{
+    DomainEvent, EventBus, EventCategory, EventPayload, FileEvent, FolderEvent, RecentEvents,
+    ShareEvent,
+};
+use filehub_core::telemetry;
+use filehub_core::types::id::UserId;
+
+use crate::channel::types::ChannelType;
+use crate::message::types::OutboundMessage;
+
+use super::coalesce::{Admission, BurstKey, Flush, UploadCoalescer};
+use super::dispatcher::NotificationDispatcher;
+use super::formatter::{self, NotificationDraft};
+
+/// Subscribe the dispatcher to file, folder and share events on `bus`,
+/// coalescing uploads as `coalescing` configures.
+pub fn spawn_event_bridge(
+    bus: &EventBus,
+    dispatcher: Arc<NotificationDispatcher>,
+    coalescing: UploadCoalescingConfig,
+) -> JoinHandle<()> {
+    let mut events = bus.subscribe_to(&[
+        EventCategory::File,
+        EventCategory::Folder,
+        EventCategory::Share,
+    ]);
+    let mut coalescer = UploadCoalescer::new(&coalescing);
+    let mut recent = RecentEvents::default();
+    let window = Duration::from_millis(coalescing.window_ms);
+    tokio::spawn(async move {
+        loop {
+            let event = tokio::select! {
+                event = events.recv() => match event {
+                    Some(event) => event,
+                    None => break,
+                },
+                () = sleep_until(coalescer.next_due()) => {
+                    for flush in coalescer.flush_due(Instant::now()) {
+                        dispatch_flush(&dispatcher, flush).await;
+                    }
+                    continue;
+                }
+            };
+            if !recent.first_sighting(event.id) {
+                continue;
+            }
+            let now = Instant::now();
+            for flush in coalescer.flush_due(now) {
+                dispatch_flush(&dispatcher, flush).await;
+            }
+            if admit_upload(&mut coalescer, &event, now) == Admission::Hold {
+                continue;
+            }
+
+            let span = tracing::info_span!(
+                target: telemetry::TARGET,
+                "realtime.dispatch",
+                otel.kind = "consumer",
+                event.id = %event.id,
+                event.action = %event.payload.action(),
+            );
+            if let Some(traceparent) = &event.traceparent {
+                telemetry::set_parent(&span, traceparent);
+            }
+            async {
+                for (channel, msg) in channel_messages(&event) {
+                    dispatcher
+                        .dispatch_to_channel(&channel.to_channel_name(), msg)
+                        .await;
+                }
+                for (user_id, draft) in user_notifications(&event) {
+                    dispatcher
+                        .dispatch_draft_to_user(UserId::from(user_id), draft)
+                        .await;
+                }
+            }
+            .instrument(span)
+            .await;
+        }
+        // End every burst still going.
+        for flush in coalescer.flush_due(Instant::now() + window) {
+            dispatch_flush(&dispatcher, flush).await;
+        }
+        tracing::debug!("Event bus closed, realtime bridge stopped");
+    })
+}
+
+/// Wait until `due`, or forever if nothing is due.
+async fn sleep_until(due: Option<Instant>) {
+    match due {
+        Some(due) => tokio::time::sleep_until(due.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Count an upload event into its burst; other events are always sent.
+fn admit_upload(coalescer: &mut UploadCoalescer, event: &DomainEvent, now: Instant) -> Admission {
+    let EventPayload::File(FileEvent::Uploaded {
+        folder_id,
+        actor_name,
+        folder_owner_id,
+        ..
+    }) = &event.payload
+    else {
+        return Admission::Send;
+    };
+    let key = BurstKey {
+        folder_id: *folder_id,
+        actor_id: event.actor_id.unwrap_or(Uuid::nil()),
+    };
+    coalescer.admit(key, actor_name, *folder_owner_id, now)
+}
+
+/// Announce held uploads.
+async fn dispatch_flush(dispatcher: &NotificationDispatcher, flush: Flush) {
+    let Flush {
+        key,
+        actor_name,
+        channel_count,
+        notify,
+    } = flush;
+    if let Some(count) = channel_count {
+        let msg = OutboundMessage::FilesAdded {
+            folder_id: key.folder_id,
+            count,
+            actor_id: key.actor_id,
+            actor_name: actor_name.clone(),
+            timestamp: Utc::now(),
+        };
+        dispatcher
+            .dispatch_to_channel(&ChannelType::Folder(key.folder_id).to_channel_name(), msg)
+            .await;
+    }
+    if let Some((owner_id, count)) = notify {
+        let draft = formatter::files_notification(count, &actor_name, key.actor_id, key.folder_id);
+        dispatcher
+            .dispatch_draft_to_user(UserId::from(owner_id), draft)
+            .await;
+    }
+}
+
+/// Map a domain event to the notifications it sends to individual users.
+fn user_notifications(event: &DomainEvent) -> Vec<(Uuid, NotificationDraft)> {
+    let actor_id = event.actor_id.unwrap_or(Uuid::nil());
+    let share = match &event.payload {
+        EventPayload::File(FileEvent::Uploaded {
+            file_id,
+            name,
+            actor_name,
+            folder_owner_id: Some(owner_id),
+            ..
+        }) if *owner_id != actor_id => {
+            return vec![(
+                *owner_id,
+                formatter::file_notification("file_created", name, actor_name, actor_id, *file_id),
+            )];
+        }
+        EventPayload::Share(share) => share,
+        _ => return Vec::new(),
+    };
+
+    match share {
+        ShareEvent::Created {
+            share_id,
+            shared_with: Some(recipient),
+            resource_name,
+            actor_name,
+            ..
+        } => vec![(
+            *recipient,
+            formatter::share_notification(
+                "share_created",
+                resource_name,
+                actor_name,
+                actor_id,
+                *share_id,
+            ),
+        )],
+        ShareEvent::AccessRequested {
+            request_id,
+            resource_name,
+            owner_id,
+            permission,
+            actor_name,
+            ..
+        } => vec![(
+            *owner_id,
+            formatter::access_request_notification(
+                "access_requested",
+                resource_name,
+                permission,
+                actor_name,
+                actor_id,
+                *request_id,
+            ),
+        )],
+        ShareEvent::AccessRequestDecided {
+            request_id,
+            resource_name,
+            requester_id,
+            permission,
+            approved,
+            actor_name,
+            ..
+        } => vec![(
+            *requester_id,
+            formatter::access_request_notification(
+                if *approved {
+                    "access_approved"
+                } else {
+                    "access_denied"
+                },
+                resource_name,
+                permission,
+                actor_name,
+                actor_id,
+                *request_id,
+            ),
+        )],
+        _ => Vec::new(),
+    }
+}
+
+/// Map a domain event to the channel messages it produces.
+fn channel_messages(event: &DomainEvent) -> Vec<(ChannelType, OutboundMessage)> {
+    let actor_id = event.actor_id.unwrap_or(Uuid::nil());
+    let timestamp = event.timestamp;
+
+    match &event.payload {
+        EventPayload::File(file) => match file {
+            FileEvent::Uploaded {
+                file_id,
+                folder_id,
+                name,
+                size_bytes,
+                mime_type,
+                actor_name,
+                ..
+            } => vec![(
+                ChannelType::Folder(*folder_id),
+                OutboundMessage::FileCreated {
+                    file_id: *file_id,
+                    file_name: name.clone(),
+                    folder_id: *folder_id,
+                    actor_id,
+                    actor_name: actor_name.clone(),
+                    size_bytes: *size_bytes as i64,
+                    mime_type: mime_type.clone(),
+                    timestamp,
+                },
+            )],
+            FileEvent::Updated {
+                file_id,
+                changed_fields,
+            } => vec![(
+                ChannelType::File(*file_id),
+                OutboundMessage::FileUpdated {
+                    file_id: *file_id,
+                    file_name: String::new(),
+                    changes: changed_fields.clone(),
+                    actor_id,
+                    actor_name: String::new(),
+                    version: None,
+                    timestamp,
+                },
+            )],
+            FileEvent::Deleted {
+                file_id,
+                name,
+                folder_id,
+            } => {
+                let msg = OutboundMessage::FileDeleted {
+                    file_id: *file_id,
+                    file_name: name.clone(),
+                    folder_id: *folder_id,
+                    actor_id,
+                    actor_name: String::new(),
+                    timestamp,
+                };
+                vec![
+                    (ChannelType::File(*file_id), msg.clone()),
+                    (ChannelType::Folder(*folder_id), msg),
+                ]
+            }
+            FileEvent::Moved {
+                file_id,
+                from_folder_id,
+                to_folder_id,
+            } => {
+                let msg = OutboundMessage::FileMoved {
+                    file_id: *file_id,
+                    file_name: String::new(),
+                    from_folder_id: *from_folder_id,
+                    to_folder_id: *to_folder_id,
+                    actor_id,
+                    timestamp,
+                };
+                vec![
+                    (ChannelType::File(*file_id), msg.clone()),
+                    (ChannelType::Folder(*from_folder_id), msg.clone()),
+                    (ChannelType::Folder(*to_folder_id), msg),
+                ]
+            }
+            FileEvent::Copied {
+                source_file_id,
+                new_file_id,
+                to_folder_id,
+            } => vec![(
+                ChannelType::Folder(*to_folder_id),
+                OutboundMessage::FileCopied {
+                    source_file_id: *source_file_id,
+                    new_file_id: *new_file_id,
+                    file_name: String::new(),
+                    to_folder_id: *to_folder_id,
+                    actor_id,
+                    timestamp,
+                },
+            )],
+            FileEvent::Locked { file_id, locked_by } => vec![(
+                ChannelType::File(*file_id),
+                OutboundMessage::FileLocked {
+                    file_id: *file_id,
+                    file_name: String::new(),
+                    locked_by: *locked_by,
+                    locked_by_name: String::new(),
+                    timestamp,
+                },
+            )],
+            FileEvent::Unlocked {
+                file_id,
+                unlocked_by,
+            } => vec![(
+                ChannelType::File(*file_id),
+                OutboundMessage::FileUnlocked {
+                    file_id: *file_id,
+                    file_name: String::new(),
+                    unlocked_by: *unlocked_by,
+                    timestamp,
+                },
+            )],
+            FileEvent::VersionCreated {
+                file_id,
+                version_number,
+            } => vec![(
+                ChannelType::File(*file_id),
+                OutboundMessage::FileVersionCreated {
+                    file_id: *file_id,
+                    file_name: String::new(),
+                    version: *version_number,
+                    actor_id,
+                    comment: None,
+                    timestamp,
+                },
+            )],
+            FileEvent::VersionRestored {
+                file_id,
+                version_number,
+                ..
+            } => vec![(
+                ChannelType::File(*file_id),
+                OutboundMessage::FileUpdated {
+                    file_id: *file_id,
+                    file_name: String::new(),
+                    changes: vec!["content".to_string()],
+                    actor_id,
+                    actor_name: String::new(),
+                    version: Some(*version_number),
+                    timestamp,
+                },
+            )],
+            FileEvent::Downloaded { .. } => Vec::new(),
+        },
+        EventPayload::Folder(folder) => match folder {
+            FolderEvent::Moved {
+                folder_id,
+                name,
+                from_parent_id,
+                to_parent_id,
+                ..
+            } => {
+                let msg = OutboundMessage::FolderMoved {
+                    folder_id: *folder_id,
+                    folder_name: name.clone(),
+                    from_parent_id: *from_parent_id,
+                    to_parent_id: *to_parent_id,
+                    actor_id,
+                    timestamp,
+                };
+                let mut messages = vec![(ChannelType::Folder(*folder_id), msg.clone())];
+                for parent_id in [from_parent_id, to_parent_id].into_iter().flatten() {
+                    messages.push((ChannelType::Folder(*parent_id), msg.clone()));
+                }
+                messages
+            }
+        },
+        EventPayload::Share(share) => match share {
+            ShareEvent::Accessed {
+                share_id,
+                ip_address,
+            } => vec![(
+                ChannelType::Share(*share_id),
+                OutboundMessage::ShareAccessed {
+                    share_id: *share_id,
+                    resource_name: String::new(),
+                    accessor: ip_address.clone().unwrap_or_default(),
+                    download_count: 0,
+                    timestamp,
+                },
+            )],
+            ShareEvent::Downloaded {
+                share_id,
+                download_count,
+                ..
+            } => vec![(
+                ChannelType::Share(*share_id),
+                OutboundMessage::ShareAccessed {
+                    share_id: *share_id,
+                    resource_name: String::new(),
+                    accessor: String::new(),
+                    download_count: *download_count,
+                    timestamp,
+                },
+            )],
+            ShareEvent::Revoked { share_id, .. } => vec![(
+                ChannelType::Share(*share_id),
+                OutboundMessage::ShareRevoked {
+                    share_id: *share_id,
+                    resource_name: String::new(),
+                    actor_id,
+                    timestamp,
+                },
+            )],
+            ShareEvent::Created { .. }
+            | ShareEvent::Expired { .. }
+            | ShareEvent::AccessRequested { .. }
+            | ShareEvent::AccessRequestDecided { .. } => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,285 @@
+//! Coalescing of bulk uploads into one update per folder.
+//!
+//! Uploads by one user to one folder form a burst while each arrives
+//! within the window of the previous one. The first uploads of a burst,
+//! up to the threshold, are announced one by one, so a trickle of uploads
+//! is never delayed. Later ones are held: their channel update goes out
+//! as one count when the burst ends, or once they have waited the
+//! maximum delay, and the folder owner is notified once, with the total,
+//! when the burst ends.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+use filehub_core::config::UploadCoalescingConfig;
+
+/// Uploads of one user to one folder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BurstKey {
+    /// Folder uploaded to.
+    pub folder_id: Uuid,
+    /// Uploader.
+    pub actor_id: Uuid,
+}
+
+/// What to do with one upload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admission {
+    /// Announce it on its own.
+    Send,
+    /// Leave it to a later [`Flush`].
+    Hold,
+}
+
+/// Held uploads due to be announced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Flush {
+    /// Folder and uploader.
+    pub key: BurstKey,
+    /// Username of the uploader.
+    pub actor_name: String,
+    /// Uploads held since the last channel update, if any are.
+    pub channel_count: Option<u64>,
+    /// Folder owner to notify and the uploads held over the whole burst,
+    /// once it has ended.
+    pub notify: Option<(Uuid, u64)>,
+}
+
+/// One ongoing burst.
+#[derive(Debug)]
+struct Burst {
+    /// Username of the uploader.
+    actor_name: String,
+    /// Owner of the folder.
+    folder_owner_id: Option<Uuid>,
+    /// When the latest upload arrived.
+    last_upload: Instant,
+    /// Uploads in the burst.
+    uploads: u64,
+    /// Uploads held over the whole burst.
+    held: u64,
+    /// Uploads held since the last channel update.
+    unsent: u64,
+    /// When the oldest of the `unsent` uploads arrived.
+    unsent_since: Option<Instant>,
+}
+
+/// Tracks upload bursts per folder and uploader.
+#[derive(Debug)]
+pub struct UploadCoalescer {
+    /// Longest gap between uploads of a burst; zero disables coalescing.
+    window: Duration,
+    /// Uploads of a burst announced individually.
+    threshold: u64,
+    /// Longest wait of a held upload for its channel update.
+    max_delay: Duration,
+    /// Ongoing bursts.
+    bursts: HashMap<BurstKey, Burst>,
+}
+
+impl UploadCoalescer {
+    /// Create a coalescer with the given settings
+    pub fn new(config: &UploadCoalescingConfig) -> Self {
+        Self {
+            window: Duration::from_millis(config.window_ms),
+            threshold: u64::from(config.threshold),
+            max_delay: Duration::from_millis(config.max_delay_ms),
+            bursts: HashMap::new(),
+        }
+    }
+
+    /// Whether uploads are coalesced at all
+    pub fn is_enabled(&self) -> bool {
+        !self.window.is_zero()
+    }
+
+    /// Count an upload into its burst and decide whether to announce it.
+    ///
+    /// Bursts that ended before `now` must have been taken with
+    /// [`flush_due`](Self::flush_due) first, or their held uploads count
+    /// into the new burst.
+    pub fn admit(
+        &mut self,
+        key: BurstKey,
+        actor_name: &str,
+        folder_owner_id: Option<Uuid>,
+        now: Instant,
+    ) -> Admission {
+        if !self.is_enabled() {
+            return Admission::Send;
+        }
+        let burst = self.bursts.entry(key).or_insert_with(|| Burst {
+            actor_name: actor_name.to_string(),
+            folder_owner_id,
+            last_upload: now,
+            uploads: 0,
+            held: 0,
+            unsent: 0,
+            unsent_since: None,
+        });
+        burst.last_upload = now;
+        burst.uploads += 1;
+        if burst.uploads <= self.threshold {
+            return Admission::Send;
+        }
+        burst.held += 1;
+        burst.unsent += 1;
+        burst.unsent_since.get_or_insert(now);
+        Admission::Hold
+    }
+
+    /// Take the announcements due at `now`, forgetting bursts that ended.
+    pub fn flush_due(&mut self, now: Instant) -> Vec<Flush> {
+        let mut flushes = Vec::new();
+        let (window, max_delay) = (self.window, self.max_delay);
+        self.bursts.retain(|key, burst| {
+            let ended = now.saturating_duration_since(burst.last_upload) >= window;
+            let overdue = burst
+                .unsent_since
+                .is_some_and(|since| now.saturating_duration_since(since) >= max_delay);
+
+            let channel_count = (burst.unsent > 0 && (ended || overdue)).then(|| {
+                burst.unsent_since = None;
+                std::mem::take(&mut burst.unsent)
+            });
+            let notify = match burst.folder_owner_id {
+                Some(owner) if ended && burst.held > 0 && owner != key.actor_id => {
+                    Some((owner, burst.held))
+                }
+                _ => None,
+            };
+            if channel_count.is_some() || notify.is_some() {
+                flushes.push(Flush {
+                    key: *key,
+                    actor_name: burst.actor_name.clone(),
+                    channel_count,
+                    notify,
+                });
+            }
+            !ended
+        });
+        flushes
+    }
+
+    /// When the next announcement or burst end is due, if any burst is
+    /// ongoing
+    pub fn next_due(&self) -> Option<Instant> {
+        self.bursts
+            .values()
+            .flat_map(|burst| {
+                let ends = burst.last_upload + self.window;
+                let overdue = burst.unsent_since.map(|since| since + self.max_delay);
+                std::iter::once(ends).chain(overdue)
+            })
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coalescer() -> UploadCoalescer {
+        UploadCoalescer::new(&UploadCoalescingConfig {
+            window_ms: 1000,
+            threshold: 2,
+            max_delay_ms: 5000,
+        })
+    }
+
+    fn ms(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_trickle_is_sent_individually() {
+        let mut coalescer = coalescer();
+        let key = BurstKey {
+            folder_id: Uuid::new_v4(),
+            actor_id: Uuid::new_v4(),
+        };
+        let start = Instant::now();
+        for i in 0..5 {
+            // Each upload arrives after the previous burst ended.
+            let now = ms(start, i * 1500);
+            assert!(coalescer.flush_due(now).is_empty());
+            assert_eq!(
+                coalescer.admit(key, "alice", Some(Uuid::new_v4()), now),
+                Admission::Send
+            );
+        }
+    }
+
+    #[test]
+    fn test_burst_collapses_into_one_update_and_notification() {
+        let mut coalescer = coalescer();
+        let owner = Uuid::new_v4();
+        let key = BurstKey {
+            folder_id: Uuid::new_v4(),
+            actor_id: Uuid::new_v4(),
+        };
+        let start = Instant::now();
+
+        let admissions: Vec<Admission> = (0..10)
+            .map(|i| coalescer.admit(key, "alice", Some(owner), ms(start, i * 100)))
+            .collect();
+        assert_eq!(&admissions[..2], [Admission::Send, Admission::Send]);
+        assert!(admissions[2..].iter().all(|a| *a == Admission::Hold));
+
+        assert!(coalescer.flush_due(ms(start, 1500)).is_empty());
+        assert_eq!(coalescer.next_due(), Some(ms(start, 1900)));
+        assert_eq!(
+            coalescer.flush_due(ms(start, 1900)),
+            [Flush {
+                key,
+                actor_name: "alice".to_string(),
+                channel_count: Some(8),
+                notify: Some((owner, 8)),
+            }]
+        );
+        assert_eq!(coalescer.next_due(), None);
+    }
+
+    #[test]
+    fn test_long_burst_updates_channel_every_max_delay() {
+        let mut coalescer = coalescer();
+        let key = BurstKey {
+            folder_id: Uuid::new_v4(),
+            actor_id: Uuid::new_v4(),
+        };
+        let start = Instant::now();
+        let mut flushes = Vec::new();
+        // One upload every 500 ms for 12 s.
+        for i in 0..24 {
+            let now = ms(start, i * 500);
+            flushes.extend(coalescer.flush_due(now));
+            coalescer.admit(key, "alice", Some(key.actor_id), now);
+        }
+        flushes.extend(coalescer.flush_due(ms(start, 20_000)));
+
+        let counts: Vec<u64> = flushes.iter().filter_map(|f| f.channel_count).collect();
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts.iter().sum::<u64>(), 22);
+        // Uploading to one's own folder notifies no one.
+        assert!(flushes.iter().all(|f| f.notify.is_none()));
+    }
+
+    #[test]
+    fn test_disabled_sends_everything() {
+        let mut coalescer = UploadCoalescer::new(&UploadCoalescingConfig {
+            window_ms: 0,
+            ..Default::default()
+        });
+        let key = BurstKey {
+            folder_id: Uuid::nil(),
+            actor_id: Uuid::nil(),
+        };
+        let now = Instant::now();
+        for _ in 0..100 {
+            assert_eq!(coalescer.admit(key, "bob", None, now), Admission::Send);
+        }
+        assert_eq!(coalescer.next_due(), None);
+    }
+}
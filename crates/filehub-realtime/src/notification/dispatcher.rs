@@ -2,19 +2,26 @@
 
 use std::sync::Arc;
 
+use async_trait::async_trait;
 use chrono::Utc;
 use tracing;
 use uuid::Uuid;
 
 use filehub_core::config::NotificationRealtimeConfig;
 use filehub_core::types::id::UserId;
+use filehub_entity::notification::{Notification, NotificationPreference, NotificationTemplates};
+use filehub_service::notification::DigestSink;
 use filehub_service::notification::service::NotificationService;
+use filehub_service::session::SessionAudit;
 
 use crate::connection::manager::ConnectionManager;
 use crate::message::types::OutboundMessage;
 
 use super::dedup::EventDeduplicator;
+use super::formatter::NotificationDraft;
 use super::persistence;
+use super::preferences::{self, DeliveryChannel, UserPreferences};
+use super::priority::NotificationPriority;
 
 /// Dispatches notifications to online users via WS and persists for offline users.
 #[derive(Debug)]
@@ -23,8 +30,12 @@ pub struct NotificationDispatcher {
     connections: Arc<ConnectionManager>,
     /// Notification service for persistence
     notification_service: Arc<NotificationService>,
+    /// Audit log, for muted security notifications
+    audit: Arc<SessionAudit>,
     /// Event deduplicator
     dedup: EventDeduplicator,
+    /// Wording of event notifications
+    templates: Arc<NotificationTemplates>,
     /// Configuration
     config: NotificationRealtimeConfig,
 }
@@ -34,36 +45,185 @@ impl NotificationDispatcher {
     pub fn new(
         connections: Arc<ConnectionManager>,
         notification_service: Arc<NotificationService>,
+        audit: Arc<SessionAudit>,
+        templates: Arc<NotificationTemplates>,
         config: NotificationRealtimeConfig,
     ) -> Self {
         Self {
             connections,
             notification_service,
+            audit,
             dedup: EventDeduplicator::new(config.batch_window_ms),
+            templates,
             config,
         }
     }
 
     /// Dispatch a notification to a specific user.
     ///
-    /// If the user is online, sends via WebSocket.
-    /// If offline and `persist_for_offline` is enabled, saves to database.
+    /// Notifications are first checked against the user's preferences per
+    /// event type and channel. Batchable ones then go into the user's
+    /// digest when they have digests enabled. Otherwise, if the user is
+    /// online and realtime delivery is allowed, sends via WebSocket; if not
+    /// and `persist_for_offline` is enabled, saves to database. Offline
+    /// users who opted in are also sent an email.
     pub async fn dispatch_to_user(&self, user_id: UserId, msg: OutboundMessage) {
-        if self.connections.is_online(user_id) {
+        let Some(notification) = persistence::to_stored(user_id, &msg) else {
+            self.deliver(user_id, msg, true, true).await;
+            return;
+        };
+        let prefs = self.preferences_for(user_id).await;
+        self.dispatch_stored(user_id, msg, notification, &prefs)
+            .await;
+    }
+
+    /// Word `draft` in the user's locale, then dispatch it as
+    /// [`dispatch_to_user`](Self::dispatch_to_user) does.
+    pub async fn dispatch_draft_to_user(&self, user_id: UserId, draft: NotificationDraft) {
+        let prefs = self.preferences_for(user_id).await;
+        let msg = draft.render(&self.templates, prefs.locale());
+        let Some(notification) = persistence::to_stored(user_id, &msg) else {
+            self.deliver(user_id, msg, true, true).await;
+            return;
+        };
+        self.dispatch_stored(user_id, msg, notification, &prefs)
+            .await;
+    }
+
+    /// The user's preferences, or the defaults if they cannot be loaded.
+    async fn preferences_for(&self, user_id: UserId) -> NotificationPreference {
+        match self
+            .notification_service
+            .preferences_for(user_id.into_uuid())
+            .await
+        {
+            Ok(prefs) => prefs,
+            Err(e) => {
+                tracing::warn!("Failed to load preferences for {}: {}", user_id, e);
+                NotificationPreference::default_for_user(user_id.into_uuid())
+            }
+        }
+    }
+
+    /// Dispatch a notification that can be stored, as the user's
+    /// preferences `prefs` allow.
+    async fn dispatch_stored(
+        &self,
+        user_id: UserId,
+        msg: OutboundMessage,
+        notification: Notification,
+        prefs: &NotificationPreference,
+    ) {
+        let channels = UserPreferences::from_stored(&prefs.preferences);
+        let category = notification.category.as_str();
+        let event_type = notification.event_type.as_str();
+        let priority = notification.priority.as_deref().unwrap_or("normal");
+
+        let realtime = channels.should_deliver(category, priority)
+            && channels.allows(category, event_type, DeliveryChannel::Realtime);
+        let stored = channels.allows(category, event_type, DeliveryChannel::Stored);
+        let email = self.notification_service.email_enabled()
+            && channels.allows(category, event_type, DeliveryChannel::Email);
+        if !realtime && !stored && !email {
+            tracing::trace!("Notification '{}' muted by {}", event_type, user_id);
+            if preferences::is_security_relevant(category, priority) {
+                self.audit_suppressed(&notification).await;
+            }
+            return;
+        }
+
+        if NotificationPriority::from_str_value(priority).can_batch()
+            && self.collect_into_digest(&notification, prefs).await
+        {
+            return;
+        }
+
+        if email && !self.connections.is_online(user_id) {
+            self.queue_email(&notification).await;
+        }
+        self.deliver(user_id, msg, realtime, stored).await;
+    }
+
+    /// Queue an email; failures are logged, as the other channels still apply.
+    async fn queue_email(&self, notification: &Notification) {
+        if let Err(e) = self.notification_service.queue_email(notification).await {
+            tracing::error!(
+                "Failed to queue email for user {}: {}",
+                notification.user_id,
+                e
+            );
+        }
+    }
+
+    /// Send over the WebSocket or store, as the allowed channels permit.
+    async fn deliver(&self, user_id: UserId, msg: OutboundMessage, realtime: bool, stored: bool) {
+        if realtime && self.connections.is_online(user_id) {
             self.connections.send_to_user(user_id, msg).await;
-        } else if self.config.persist_for_offline {
-            if let Err(e) =
+        } else if stored
+            && self.config.persist_for_offline
+            && let Err(e) =
                 persistence::persist_for_offline(&self.notification_service, user_id, &msg).await
-            {
-                tracing::error!(
-                    "Failed to persist notification for offline user {}: {}",
-                    user_id,
+        {
+            tracing::error!(
+                "Failed to persist notification for offline user {}: {}",
+                user_id,
+                e
+            );
+        }
+    }
+
+    /// Hand a batchable notification to the user's digest. Returns `true`
+    /// when it was collected.
+    async fn collect_into_digest(
+        &self,
+        notification: &Notification,
+        prefs: &NotificationPreference,
+    ) -> bool {
+        match self
+            .notification_service
+            .collect_into_digest(notification, prefs)
+            .await
+        {
+            Ok(collected) => collected,
+            Err(e) => {
+                // Deliver undigested rather than drop it.
+                tracing::warn!(
+                    "Failed to digest notification for {}: {}",
+                    notification.user_id,
                     e
                 );
+                false
             }
         }
     }
 
+    /// Record that a security-relevant notification was muted, so it is
+    /// not lost entirely.
+    async fn audit_suppressed(&self, notification: &Notification) {
+        let details = serde_json::json!({
+            "category": notification.category,
+            "event_type": notification.event_type,
+            "priority": notification.priority,
+            "title": notification.title,
+            "reason": "muted",
+        });
+        if let Err(e) = self
+            .audit
+            .log_event(
+                notification.user_id,
+                "notification.suppressed",
+                "notification",
+                Some(notification.id),
+                Some(details),
+                None,
+                None,
+            )
+            .await
+        {
+            tracing::error!("Failed to audit muted notification: {}", e);
+        }
+    }
+
     /// Dispatch to multiple users
     pub async fn dispatch_to_users(&self, user_ids: &[Uuid], msg: OutboundMessage) {
         for uid in user_ids {
@@ -116,6 +276,7 @@ impl NotificationDispatcher {
             message: message.to_string(),
             severity: severity.to_string(),
             persistent,
+            require_ack: false,
             action_payload: None,
             action_type: None,
             timestamp: Utc::now(),
@@ -123,3 +284,39 @@ impl NotificationDispatcher {
         self.broadcast(msg).await;
     }
 }
+
+#[async_trait]
+impl DigestSink for NotificationDispatcher {
+    /// Push a flushed digest to the user if online, or email it if they
+    /// are offline and opted in; it is already stored.
+    async fn deliver(&self, notification: &Notification) {
+        let user_id = UserId::from(notification.user_id);
+        if self.connections.is_online(user_id) {
+            self.connections
+                .send_to_user(user_id, persistence::to_message(notification))
+                .await;
+            return;
+        }
+        if !self.notification_service.email_enabled() {
+            return;
+        }
+
+        match self
+            .notification_service
+            .preferences_for(notification.user_id)
+            .await
+        {
+            Ok(prefs) => {
+                let channels = UserPreferences::from_stored(&prefs.preferences);
+                if channels.allows(
+                    &notification.category,
+                    &notification.event_type,
+                    DeliveryChannel::Email,
+                ) {
+                    self.queue_email(notification).await;
+                }
+            }
+            Err(e) => tracing::warn!("Failed to load preferences for {}: {}", user_id, e),
+        }
+    }
+}
@@ -1,137 +1,199 @@
 //! Format domain events into notification messages.
+//!
+//! Events become [`NotificationDraft`]s, which are worded from the
+//! [`NotificationTemplates`] once the recipient's locale is known.
 
 use chrono::Utc;
 use uuid::Uuid;
 
+use filehub_entity::notification::NotificationTemplates;
+
 use crate::message::types::OutboundMessage;
 
-/// Format a file event into a user notification
-pub fn format_file_notification(
+/// A notification waiting to be worded for its recipient
+#[derive(Debug, Clone)]
+pub struct NotificationDraft {
+    /// Notification category
+    category: &'static str,
+    /// Event type, which names its templates
+    event_type: String,
+    /// Templates used when the event type has none of its own
+    fallback: &'static str,
+    /// Values of the template placeholders
+    values: Vec<(&'static str, String)>,
+    /// Notification priority
+    priority: &'static str,
+    /// Who caused the event
+    actor_id: Option<Uuid>,
+    /// Display name of the actor
+    actor_name: Option<String>,
+    /// Type of the resource concerned
+    resource_type: &'static str,
+    /// The resource concerned
+    resource_id: Uuid,
+}
+
+impl NotificationDraft {
+    /// Word the notification with `templates` for a recipient with `locale`
+    pub fn render(
+        &self,
+        templates: &NotificationTemplates,
+        locale: Option<&str>,
+    ) -> OutboundMessage {
+        let key = if templates.knows(&self.event_type) {
+            self.event_type.as_str()
+        } else {
+            self.fallback
+        };
+        let values: Vec<(&str, &str)> = self
+            .values
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+            .collect();
+        let (title, message) = templates
+            .render(key, locale, &values)
+            .unwrap_or_else(|| (self.event_type.clone(), String::new()));
+
+        OutboundMessage::Notification {
+            id: Uuid::new_v4(),
+            category: self.category.to_string(),
+            event_type: self.event_type.clone(),
+            title,
+            message,
+            payload: None,
+            priority: self.priority.to_string(),
+            actor_id: self.actor_id,
+            actor_name: self.actor_name.clone(),
+            resource_type: Some(self.resource_type.to_string()),
+            resource_id: Some(self.resource_id),
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+/// Draft a file event as a user notification
+pub fn file_notification(
     event_type: &str,
     file_name: &str,
     actor_name: &str,
     actor_id: Uuid,
     file_id: Uuid,
-) -> OutboundMessage {
-    let (title, message) = match event_type {
-        "file_created" => (
-            "File uploaded".to_string(),
-            format!("{} uploaded '{}'", actor_name, file_name),
-        ),
-        "file_updated" => (
-            "File updated".to_string(),
-            format!("{} updated '{}'", actor_name, file_name),
-        ),
-        "file_deleted" => (
-            "File deleted".to_string(),
-            format!("{} deleted '{}'", actor_name, file_name),
-        ),
-        "file_moved" => (
-            "File moved".to_string(),
-            format!("{} moved '{}'", actor_name, file_name),
-        ),
-        _ => (
-            "File event".to_string(),
-            format!(
-                "{} performed '{}' on '{}'",
-                actor_name, event_type, file_name
-            ),
-        ),
-    };
-
-    OutboundMessage::Notification {
-        id: Uuid::new_v4(),
-        category: "file".to_string(),
+) -> NotificationDraft {
+    NotificationDraft {
+        category: "file",
         event_type: event_type.to_string(),
-        title,
-        message,
-        payload: None,
-        priority: "normal".to_string(),
+        fallback: "file_event",
+        values: vec![
+            ("event_type", event_type.to_string()),
+            ("actor_name", actor_name.to_string()),
+            ("file_name", file_name.to_string()),
+        ],
+        priority: "normal",
         actor_id: Some(actor_id),
         actor_name: Some(actor_name.to_string()),
-        resource_type: Some("file".to_string()),
-        resource_id: Some(file_id),
-        timestamp: Utc::now(),
+        resource_type: "file",
+        resource_id: file_id,
     }
 }
 
-/// Format a share event into a notification
-pub fn format_share_notification(
+/// Draft a burst of uploads to a folder as one notification
+pub fn files_notification(
+    count: u64,
+    actor_name: &str,
+    actor_id: Uuid,
+    folder_id: Uuid,
+) -> NotificationDraft {
+    NotificationDraft {
+        category: "file",
+        event_type: "files_created".to_string(),
+        fallback: "files_created",
+        values: vec![
+            ("event_type", "files_created".to_string()),
+            ("actor_name", actor_name.to_string()),
+            ("count", count.to_string()),
+        ],
+        priority: "normal",
+        actor_id: Some(actor_id),
+        actor_name: Some(actor_name.to_string()),
+        resource_type: "folder",
+        resource_id: folder_id,
+    }
+}
+
+/// Draft a share event as a notification
+pub fn share_notification(
     event_type: &str,
     resource_name: &str,
     actor_name: &str,
     actor_id: Uuid,
     share_id: Uuid,
-) -> OutboundMessage {
-    let (title, message) = match event_type {
-        "share_created" => (
-            "New share".to_string(),
-            format!("{} shared '{}' with you", actor_name, resource_name),
-        ),
-        "share_accessed" => (
-            "Share accessed".to_string(),
-            format!("Your share of '{}' was accessed", resource_name),
-        ),
-        _ => (
-            "Share event".to_string(),
-            format!("Share event on '{}'", resource_name),
-        ),
-    };
+) -> NotificationDraft {
+    NotificationDraft {
+        category: "share",
+        event_type: event_type.to_string(),
+        fallback: "share_event",
+        values: vec![
+            ("event_type", event_type.to_string()),
+            ("actor_name", actor_name.to_string()),
+            ("resource_name", resource_name.to_string()),
+        ],
+        priority: "normal",
+        actor_id: Some(actor_id),
+        actor_name: Some(actor_name.to_string()),
+        resource_type: "share",
+        resource_id: share_id,
+    }
+}
 
-    OutboundMessage::Notification {
-        id: Uuid::new_v4(),
-        category: "share".to_string(),
+/// Draft an access request event as a notification for the other
+/// party: `access_requested` goes to the owner, `access_approved` and
+/// `access_denied` to the requester.
+pub fn access_request_notification(
+    event_type: &str,
+    resource_name: &str,
+    permission: &str,
+    actor_name: &str,
+    actor_id: Uuid,
+    request_id: Uuid,
+) -> NotificationDraft {
+    NotificationDraft {
+        category: "share",
         event_type: event_type.to_string(),
-        title,
-        message,
-        payload: None,
-        priority: "normal".to_string(),
+        fallback: "access_request",
+        values: vec![
+            ("event_type", event_type.to_string()),
+            ("actor_name", actor_name.to_string()),
+            ("permission", permission.to_string()),
+            ("resource_name", resource_name.to_string()),
+        ],
+        priority: "normal",
         actor_id: Some(actor_id),
         actor_name: Some(actor_name.to_string()),
-        resource_type: Some("share".to_string()),
-        resource_id: Some(share_id),
-        timestamp: Utc::now(),
+        resource_type: "access_request",
+        resource_id: request_id,
     }
 }
 
-/// Format a session event into an admin notification
-pub fn format_session_notification(
+/// Draft a session event as an admin notification
+pub fn session_notification(
     event_type: &str,
     username: &str,
     session_id: Uuid,
     details: &str,
-) -> OutboundMessage {
-    let (title, message) = match event_type {
-        "session_created" => (
-            "New session".to_string(),
-            format!("User '{}' logged in", username),
-        ),
-        "session_terminated" => (
-            "Session terminated".to_string(),
-            format!("Session for '{}' was terminated: {}", username, details),
-        ),
-        "session_expired" => (
-            "Session expired".to_string(),
-            format!("Session for '{}' expired", username),
-        ),
-        _ => (
-            "Session event".to_string(),
-            format!("Session event for '{}': {}", username, details),
-        ),
-    };
-
-    OutboundMessage::Notification {
-        id: Uuid::new_v4(),
-        category: "session".to_string(),
+) -> NotificationDraft {
+    NotificationDraft {
+        category: "session",
         event_type: event_type.to_string(),
-        title,
-        message,
-        payload: None,
-        priority: "high".to_string(),
+        fallback: "session_event",
+        values: vec![
+            ("event_type", event_type.to_string()),
+            ("username", username.to_string()),
+            ("details", details.to_string()),
+        ],
+        priority: "high",
         actor_id: None,
         actor_name: Some(username.to_string()),
-        resource_type: Some("session".to_string()),
-        resource_id: Some(session_id),
-        timestamp: Utc::now(),
+        resource_type: "session",
+        resource_id: session_id,
     }
 }
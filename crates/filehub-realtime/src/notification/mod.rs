@@ -1,5 +1,7 @@
 //! Notification dispatch system.
 
+pub mod bridge;
+pub mod coalesce;
 pub mod dedup;
 pub mod dispatcher;
 pub mod formatter;
@@ -7,4 +9,5 @@ pub mod persistence;
 pub mod preferences;
 pub mod priority;
 
+pub use bridge::spawn_event_bridge;
 pub use dispatcher::NotificationDispatcher;
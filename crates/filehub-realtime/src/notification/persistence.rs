@@ -14,7 +14,21 @@ pub async fn persist_for_offline(
     user_id: UserId,
     msg: &OutboundMessage,
 ) -> Result<(), AppError> {
-    if let OutboundMessage::Notification {
+    if let Some(notification) = to_stored(user_id, msg) {
+        notification_service
+            .create_notification(notification)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// The stored form of a `Notification` message addressed to `user_id`.
+pub fn to_stored(
+    user_id: UserId,
+    msg: &OutboundMessage,
+) -> Option<filehub_entity::notification::model::Notification> {
+    let OutboundMessage::Notification {
         id,
         category,
         event_type,
@@ -28,30 +42,49 @@ pub async fn persist_for_offline(
         timestamp,
         ..
     } = msg
-    {
-        let notification = filehub_entity::notification::model::Notification {
-            id: *id,
-            user_id: user_id.into_uuid(),
-            category: category.clone(),
-            event_type: event_type.clone(),
-            title: title.clone(),
-            message: message.clone(),
-            payload: payload.clone(),
-            priority: Some(priority.clone()),
-            is_read: Some(false),
-            read_at: None,
-            is_dismissed: Some(false),
-            actor_id: *actor_id,
-            resource_type: resource_type.clone(),
-            resource_id: *resource_id,
-            created_at: *timestamp,
-            expires_at: None,
-        };
+    else {
+        return None;
+    };
 
-        notification_service
-            .create_notification(notification)
-            .await?;
-    }
+    Some(filehub_entity::notification::model::Notification {
+        id: *id,
+        user_id: user_id.into_uuid(),
+        category: category.clone(),
+        event_type: event_type.clone(),
+        title: title.clone(),
+        message: message.clone(),
+        payload: payload.clone(),
+        priority: Some(priority.clone()),
+        is_read: Some(false),
+        read_at: None,
+        is_dismissed: Some(false),
+        actor_id: *actor_id,
+        resource_type: resource_type.clone(),
+        resource_id: *resource_id,
+        created_at: *timestamp,
+        expires_at: None,
+    })
+}
 
-    Ok(())
+/// The `Notification` message for a stored notification.
+pub fn to_message(
+    notification: &filehub_entity::notification::model::Notification,
+) -> OutboundMessage {
+    OutboundMessage::Notification {
+        id: notification.id,
+        category: notification.category.clone(),
+        event_type: notification.event_type.clone(),
+        title: notification.title.clone(),
+        message: notification.message.clone(),
+        payload: notification.payload.clone(),
+        priority: notification
+            .priority
+            .clone()
+            .unwrap_or_else(|| "normal".to_string()),
+        actor_id: notification.actor_id,
+        actor_name: None,
+        resource_type: notification.resource_type.clone(),
+        resource_id: notification.resource_id,
+        timestamp: notification.created_at,
+    }
 }
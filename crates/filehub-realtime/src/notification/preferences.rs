@@ -4,11 +4,18 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use filehub_entity::notification::preference::EventTypePreference;
+
+/// Keys of the stored preferences object that are not categories.
+const NON_CATEGORY_KEYS: &[&str] = &["muted", "dnd", "digest", "event_types", "locale"];
+
 /// User notification preferences
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserPreferences {
     /// Per-category settings
     pub categories: HashMap<String, CategoryPreference>,
+    /// Per-event-type channel overrides
+    pub event_types: HashMap<String, EventTypePreference>,
     /// Global mute
     pub muted: bool,
     /// Do not disturb mode
@@ -19,15 +26,36 @@ pub struct UserPreferences {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CategoryPreference {
     /// Whether this category is enabled
+    #[serde(default = "default_true")]
     pub enabled: bool,
     /// Minimum priority to show ("low", "normal", "high", etc.)
+    #[serde(default = "default_min_priority")]
     pub min_priority: String,
+    /// Whether to deliver via WebSocket
+    #[serde(default = "default_true")]
+    pub realtime: bool,
+    /// Whether to deliver via email
+    #[serde(default)]
+    pub email: bool,
+}
+
+/// A way of delivering a notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryChannel {
+    /// Pushed over the WebSocket
+    Realtime,
+    /// Kept in the stored notification list
+    Stored,
+    /// Sent by email
+    Email,
 }
 
 impl Default for UserPreferences {
     fn default() -> Self {
         Self {
             categories: HashMap::new(),
+            event_types: HashMap::new(),
             muted: false,
             dnd: false,
         }
@@ -35,6 +63,33 @@ impl Default for UserPreferences {
 }
 
 impl UserPreferences {
+    /// Build from the stored preferences JSON. Malformed entries are
+    /// ignored so they fall back to the defaults.
+    pub fn from_stored(preferences: &serde_json::Value) -> Self {
+        let mut prefs = Self::default();
+        let Some(map) = preferences.as_object() else {
+            return prefs;
+        };
+
+        prefs.muted = map.get("muted").and_then(|v| v.as_bool()).unwrap_or(false);
+        prefs.dnd = map.get("dnd").and_then(|v| v.as_bool()).unwrap_or(false);
+        if let Some(event_types) = map.get("event_types").and_then(|v| v.as_object()) {
+            prefs.event_types = event_types
+                .iter()
+                .filter_map(|(k, v)| Some((k.clone(), serde_json::from_value(v.clone()).ok()?)))
+                .collect();
+        }
+        for (key, value) in map {
+            if NON_CATEGORY_KEYS.contains(&key.as_str()) {
+                continue;
+            }
+            if let Ok(pref) = serde_json::from_value(value.clone()) {
+                prefs.categories.insert(key.clone(), pref);
+            }
+        }
+        prefs
+    }
+
     /// Check if a notification should be delivered based on preferences
     pub fn should_deliver(&self, category: &str, priority: &str) -> bool {
         if self.muted {
@@ -55,6 +110,41 @@ impl UserPreferences {
             true // Default: deliver if no preference set
         }
     }
+
+    /// Whether `event_type` may be delivered over `channel`.
+    ///
+    /// The event type's override wins; otherwise the category decides.
+    /// Event types and categories without settings are enabled, except
+    /// email, which is opt-in.
+    pub fn allows(&self, category: &str, event_type: &str, channel: DeliveryChannel) -> bool {
+        let explicit = self
+            .event_types
+            .get(event_type)
+            .and_then(|pref| match channel {
+                DeliveryChannel::Realtime => pref.realtime,
+                DeliveryChannel::Stored => pref.stored,
+                DeliveryChannel::Email => pref.email,
+            });
+        if let Some(allowed) = explicit {
+            return allowed;
+        }
+
+        match self.categories.get(category) {
+            Some(pref) if !pref.enabled => false,
+            Some(pref) => match channel {
+                DeliveryChannel::Realtime => pref.realtime,
+                DeliveryChannel::Stored => true,
+                DeliveryChannel::Email => pref.email,
+            },
+            None => channel != DeliveryChannel::Email,
+        }
+    }
+}
+
+/// Whether a notification matters for security (session revocation,
+/// forced logout, alerts). These are audited even when muted.
+pub fn is_security_relevant(category: &str, priority: &str) -> bool {
+    matches!(category, "session" | "security") || priority_gte(priority, "urgent")
 }
 
 /// Compare priorities: is `a` >= `b`?
@@ -71,3 +161,11 @@ fn priority_gte(a: &str, b: &str) -> bool {
     };
     val(a) >= val(b)
 }
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_min_priority() -> String {
+    "low".to_string()
+}
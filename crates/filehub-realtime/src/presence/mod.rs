@@ -3,5 +3,6 @@
 pub mod activity;
 pub mod status;
 pub mod tracker;
+pub mod viewers;
 
 pub use tracker::PresenceTracker;
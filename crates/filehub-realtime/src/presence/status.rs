@@ -31,6 +31,28 @@ impl PresenceStatus {
         }
     }
 
+    /// Rank when a user's connections are rolled up into one status:
+    /// active > idle > away > dnd > offline
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Active => 4,
+            Self::Idle => 3,
+            Self::Away => 2,
+            Self::Dnd => 1,
+            Self::Offline => 0,
+        }
+    }
+
+    /// The status a user shows with connections in `statuses`: the one of
+    /// highest precedence, or offline without any
+    pub fn roll_up<'a>(statuses: impl IntoIterator<Item = &'a PresenceStatus>) -> Self {
+        statuses
+            .into_iter()
+            .max_by_key(|status| status.precedence())
+            .cloned()
+            .unwrap_or(Self::Offline)
+    }
+
     /// Convert to string
     pub fn as_str(&self) -> &str {
         match self {
@@ -1,4 +1,12 @@
 //! Presence tracker — manages user online/offline/status state.
+//!
+//! A user connected from several devices has a status per connection.
+//! Subscribers only see the roll-up of those, by the precedence in
+//! [`PresenceStatus::roll_up`], and only hear about it when it changes:
+//! a device going idle or away leaves the user active while another
+//! device is, and the user goes offline with the last connection.
+
+use std::collections::HashMap;
 
 use chrono::Utc;
 use dashmap::DashMap;
@@ -12,7 +20,9 @@ use super::status::PresenceStatus;
 /// Tracks presence state for all users.
 #[derive(Debug)]
 pub struct PresenceTracker {
-    /// User ID → current status
+    /// User ID → connection ID → status of that connection
+    connections: DashMap<Uuid, HashMap<Uuid, PresenceStatus>>,
+    /// User ID → rolled-up status
     statuses: DashMap<Uuid, PresenceStatus>,
     /// User ID → username (cached)
     usernames: DashMap<Uuid, String>,
@@ -24,63 +34,94 @@ impl PresenceTracker {
     /// Create a new presence tracker
     pub fn new() -> Self {
         Self {
+            connections: DashMap::new(),
             statuses: DashMap::new(),
             usernames: DashMap::new(),
             activity: ActivityTracker::new(),
         }
     }
 
-    /// Mark a user as online
-    pub fn set_online(&self, user_id: Uuid, username: &str) -> OutboundMessage {
-        self.statuses.insert(user_id, PresenceStatus::Active);
+    /// Record a new connection of a user, as active.
+    ///
+    /// Returns the message for subscribers: `UserOnline` for the user's
+    /// first connection, `PresenceChanged` if the connection changes the
+    /// rolled-up status, otherwise nothing.
+    pub fn set_online(
+        &self,
+        user_id: Uuid,
+        connection_id: Uuid,
+        username: &str,
+    ) -> Option<OutboundMessage> {
         self.usernames.insert(user_id, username.to_string());
         self.activity.record(user_id);
 
-        OutboundMessage::UserOnline {
-            user_id,
-            username: username.to_string(),
-            timestamp: Utc::now(),
+        let was_online = self.is_online(user_id);
+        let changed = self.set_connection(user_id, connection_id, PresenceStatus::Active);
+        if !was_online {
+            return Some(OutboundMessage::UserOnline {
+                user_id,
+                username: username.to_string(),
+                timestamp: Utc::now(),
+            });
         }
+        changed.map(|status| self.changed(user_id, status))
     }
 
-    /// Mark a user as offline
-    pub fn set_offline(&self, user_id: Uuid) -> OutboundMessage {
+    /// Forget a closed connection of a user.
+    ///
+    /// Returns `UserOffline` for the user's last connection,
+    /// `PresenceChanged` if the remaining connections roll up to another
+    /// status, otherwise nothing.
+    pub fn set_offline(&self, user_id: Uuid, connection_id: Uuid) -> Option<OutboundMessage> {
+        let remaining = {
+            let mut entry = self.connections.get_mut(&user_id)?;
+            entry.remove(&connection_id)?;
+            entry.len()
+        };
+        if remaining > 0 {
+            return self
+                .roll_up(user_id)
+                .map(|status| self.changed(user_id, status));
+        }
+
+        self.connections.remove_if(&user_id, |_, c| c.is_empty());
+        self.statuses.remove(&user_id);
+        self.activity.remove(user_id);
         let username = self
             .usernames
             .remove(&user_id)
             .map(|(_, n)| n)
             .unwrap_or_else(|| "unknown".to_string());
 
-        self.statuses.remove(&user_id);
-        self.activity.remove(user_id);
-
-        OutboundMessage::UserOffline {
+        Some(OutboundMessage::UserOffline {
             user_id,
             username,
             timestamp: Utc::now(),
-        }
+        })
     }
 
-    /// Update a user's status
-    pub fn update_status(&self, user_id: Uuid, status: PresenceStatus) -> OutboundMessage {
-        let username = self
-            .usernames
+    /// Update the status of one of a user's connections.
+    ///
+    /// Returns `PresenceChanged` if the rolled-up status changes.
+    pub fn update_status(
+        &self,
+        user_id: Uuid,
+        connection_id: Uuid,
+        status: PresenceStatus,
+    ) -> Option<OutboundMessage> {
+        if !self
+            .connections
             .get(&user_id)
-            .map(|r| r.value().clone())
-            .unwrap_or_else(|| "unknown".to_string());
-
-        self.statuses.insert(user_id, status.clone());
-        self.activity.record(user_id);
-
-        OutboundMessage::PresenceChanged {
-            user_id,
-            username,
-            status: status.as_str().to_string(),
-            timestamp: Utc::now(),
+            .is_some_and(|c| c.contains_key(&connection_id))
+        {
+            return None;
         }
+        self.activity.record(user_id);
+        self.set_connection(user_id, connection_id, status)
+            .map(|status| self.changed(user_id, status))
     }
 
-    /// Get a user's current status
+    /// Get a user's current status, rolled up over their connections
     pub fn get_status(&self, user_id: Uuid) -> PresenceStatus {
         self.statuses
             .get(&user_id)
@@ -88,6 +129,14 @@ impl PresenceTracker {
             .unwrap_or(PresenceStatus::Offline)
     }
 
+    /// Statuses of a user's connections
+    pub fn connection_statuses(&self, user_id: Uuid) -> HashMap<Uuid, PresenceStatus> {
+        self.connections
+            .get(&user_id)
+            .map(|r| r.value().clone())
+            .unwrap_or_default()
+    }
+
     /// Check if a user is online
     pub fn is_online(&self, user_id: Uuid) -> bool {
         self.statuses.contains_key(&user_id)
@@ -122,6 +171,44 @@ impl PresenceTracker {
     pub fn record_activity(&self, user_id: Uuid) {
         self.activity.record(user_id);
     }
+
+    /// Set one connection's status; returns the new rolled-up status if
+    /// it changed
+    fn set_connection(
+        &self,
+        user_id: Uuid,
+        connection_id: Uuid,
+        status: PresenceStatus,
+    ) -> Option<PresenceStatus> {
+        self.connections
+            .entry(user_id)
+            .or_default()
+            .insert(connection_id, status);
+        self.roll_up(user_id)
+    }
+
+    /// Recompute a user's rolled-up status; returns it if it changed
+    fn roll_up(&self, user_id: Uuid) -> Option<PresenceStatus> {
+        let status = PresenceStatus::roll_up(self.connections.get(&user_id)?.values());
+        let previous = self.statuses.insert(user_id, status.clone());
+        (previous.as_ref() != Some(&status)).then_some(status)
+    }
+
+    /// The message announcing a user's new rolled-up status
+    fn changed(&self, user_id: Uuid, status: PresenceStatus) -> OutboundMessage {
+        let username = self
+            .usernames
+            .get(&user_id)
+            .map(|r| r.value().clone())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        OutboundMessage::PresenceChanged {
+            user_id,
+            username,
+            status: status.as_str().to_string(),
+            timestamp: Utc::now(),
+        }
+    }
 }
 
 impl Default for PresenceTracker {
@@ -140,3 +227,68 @@ pub struct OnlineUser {
     /// Presence status
     pub status: PresenceStatus,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changed_to(message: Option<OutboundMessage>) -> Option<String> {
+        match message {
+            Some(OutboundMessage::PresenceChanged { status, .. }) => Some(status),
+            Some(other) => panic!("unexpected message {:?}", other),
+            None => None,
+        }
+    }
+
+    #[test]
+    fn test_two_connections_roll_up() {
+        let tracker = PresenceTracker::new();
+        let (user, phone, desktop) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        assert!(matches!(
+            tracker.set_online(user, phone, "alice"),
+            Some(OutboundMessage::UserOnline { .. })
+        ));
+        assert!(tracker.set_online(user, desktop, "alice").is_none());
+
+        // The phone idling leaves the desktop active.
+        assert_eq!(
+            changed_to(tracker.update_status(user, phone, PresenceStatus::Idle)),
+            None
+        );
+        assert_eq!(tracker.get_status(user), PresenceStatus::Active);
+
+        // Only when both are away from active does the roll-up move.
+        assert_eq!(
+            changed_to(tracker.update_status(user, desktop, PresenceStatus::Dnd)),
+            Some("idle".to_string())
+        );
+        assert_eq!(
+            changed_to(tracker.update_status(user, phone, PresenceStatus::Away)),
+            Some("away".to_string())
+        );
+
+        // The phone disconnecting leaves the user on the desktop.
+        assert_eq!(
+            changed_to(tracker.set_offline(user, phone)),
+            Some("dnd".to_string())
+        );
+        assert!(tracker.is_online(user));
+        assert_eq!(tracker.connection_statuses(user).len(), 1);
+
+        assert!(matches!(
+            tracker.set_offline(user, desktop),
+            Some(OutboundMessage::UserOffline { .. })
+        ));
+        assert_eq!(tracker.get_status(user), PresenceStatus::Offline);
+        assert!(tracker.set_offline(user, desktop).is_none());
+    }
+
+    #[test]
+    fn test_roll_up_precedence() {
+        use PresenceStatus::*;
+        assert_eq!(PresenceStatus::roll_up(&[Dnd, Idle, Away]), Idle);
+        assert_eq!(PresenceStatus::roll_up(&[Offline, Dnd]), Dnd);
+        assert_eq!(PresenceStatus::roll_up(&[]), Offline);
+    }
+}
@@ -0,0 +1,66 @@
+//! Active viewers of files and folders, read off channel subscriptions.
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use filehub_entity::user::role::UserRole;
+
+use crate::channel::types::ChannelType;
+use crate::connection::manager::ConnectionManager;
+
+use super::status::PresenceStatus;
+use super::tracker::PresenceTracker;
+
+/// A user viewing a file or folder.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveViewer {
+    /// User ID
+    pub user_id: Uuid,
+    /// Username
+    pub username: String,
+    /// Role, for the caller to check the viewer's own access
+    #[serde(skip)]
+    pub role: UserRole,
+    /// Presence status
+    pub status: PresenceStatus,
+    /// Number of the user's connections (tabs) viewing it
+    pub connections: usize,
+}
+
+/// Whether subscribers of `channel` are viewers: file and folder channels.
+pub fn is_viewer_channel(channel: &str) -> bool {
+    matches!(
+        ChannelType::parse(channel),
+        Some(ChannelType::File(_) | ChannelType::Folder(_))
+    )
+}
+
+/// Online users subscribed to `channel`, each once however many of their
+/// connections are, ordered by username.
+pub async fn active_viewers(
+    connections: &ConnectionManager,
+    presence: &PresenceTracker,
+    channel: &str,
+) -> Vec<ActiveViewer> {
+    let mut viewers: Vec<ActiveViewer> = Vec::new();
+    for conn in connections.subscribed_to(channel).await {
+        let user_id = conn.user_id.into_uuid();
+        if let Some(viewer) = viewers.iter_mut().find(|v| v.user_id == user_id) {
+            viewer.connections += 1;
+            continue;
+        }
+        let status = presence.get_status(user_id);
+        if status == PresenceStatus::Offline {
+            continue;
+        }
+        viewers.push(ActiveViewer {
+            user_id,
+            username: conn.username.clone(),
+            role: conn.user_role,
+            status,
+            connections: 1,
+        });
+    }
+    viewers.sort_by(|a, b| a.username.cmp(&b.username));
+    viewers
+}
@@ -6,14 +6,20 @@ use tracing;
 
 use filehub_auth::jwt::decoder::JwtDecoder;
 use filehub_core::config::RealtimeConfig;
+use filehub_database::repositories::job::JobRepository;
+use filehub_database::repositories::notification::NotificationRepository;
 use filehub_database::repositories::session::SessionRepository;
+use filehub_entity::notification::NotificationTemplates;
 use filehub_service::notification::service::NotificationService;
+use filehub_service::session::SessionAudit;
 
 use crate::channel::registry::ChannelRegistry;
+use crate::channel::types::ChannelType;
 use crate::connection::manager::ConnectionManager;
 use crate::metrics::EngineMetrics;
 use crate::notification::dispatcher::NotificationDispatcher;
 use crate::presence::tracker::PresenceTracker;
+use crate::presence::viewers::{self, ActiveViewer};
 use crate::session_control::monitor::SessionMonitor;
 
 /// Core realtime engine holding all subsystems.
@@ -41,11 +47,16 @@ pub struct RealtimeEngine {
 
 impl RealtimeEngine {
     /// Create and initialize the realtime engine.
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         config: &RealtimeConfig,
         jwt_decoder: Arc<JwtDecoder>,
         session_repo: Arc<SessionRepository>,
+        notification_repo: Arc<NotificationRepository>,
+        job_repo: Arc<JobRepository>,
         notification_service: Arc<NotificationService>,
+        audit: Arc<SessionAudit>,
+        templates: Arc<NotificationTemplates>,
     ) -> Self {
         let metrics = Arc::new(EngineMetrics::new());
         let channels = Arc::new(ChannelRegistry::new(config.channel_buffer_size));
@@ -57,9 +68,16 @@ impl RealtimeEngine {
         let notifications = Arc::new(NotificationDispatcher::new(
             Arc::clone(&connections),
             notification_service,
+            audit,
+            templates,
             config.notifications.clone(),
         ));
-        let session_monitor = Arc::new(SessionMonitor::new(Arc::clone(&connections)));
+        let session_monitor = Arc::new(SessionMonitor::new(
+            Arc::clone(&connections),
+            notification_repo,
+            job_repo,
+            config.broadcast_pending_minutes,
+        ));
 
         tracing::info!(
             "Realtime engine created: max_conn_per_user={}, channel_buf={}, max_subs={}",
@@ -80,4 +98,15 @@ impl RealtimeEngine {
             session_repo,
         }
     }
+
+    /// Online users viewing the file or folder of `channel`. They are not
+    /// checked against its permissions; that is left to the caller.
+    pub async fn active_viewers(&self, channel: &ChannelType) -> Vec<ActiveViewer> {
+        viewers::active_viewers(
+            &self.connections,
+            &self.presence,
+            &channel.to_channel_name(),
+        )
+        .await
+    }
 }
@@ -4,6 +4,9 @@ use std::sync::Arc;
 
 use uuid::Uuid;
 
+use filehub_entity::notification::BroadcastTarget;
+use filehub_entity::notification::model::AdminBroadcast;
+
 use crate::connection::manager::ConnectionManager;
 use crate::message::builder;
 
@@ -22,3 +25,22 @@ pub async fn send_broadcast(
     connections.broadcast(msg).await;
     total
 }
+
+/// Send a stored broadcast to every connection of its audience.
+///
+/// Returns the users reached.
+pub async fn send_to_audience(
+    connections: &Arc<ConnectionManager>,
+    broadcast: &AdminBroadcast,
+    target: &BroadcastTarget,
+) -> Vec<Uuid> {
+    let msg = builder::build_stored_broadcast(broadcast);
+    connections
+        .send_where(msg, |conn| {
+            target.includes(conn.user_id.into_uuid(), &conn.user_role)
+        })
+        .await
+        .into_iter()
+        .map(|user_id| user_id.into_uuid())
+        .collect()
+}
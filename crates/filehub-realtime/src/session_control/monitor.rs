@@ -2,24 +2,56 @@
 
 use std::sync::Arc;
 
-use chrono::Utc;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use crate::connection::handle::ConnectionInfo;
+use filehub_core::error::AppError;
+use filehub_database::repositories::job::JobRepository;
+use filehub_database::repositories::notification::NotificationRepository;
+use filehub_entity::job::model::CreateJob;
+use filehub_entity::job::payload::JobPayload;
+use filehub_entity::job::status::JobPriority;
+use filehub_entity::notification::model::AdminBroadcast;
+use filehub_entity::notification::{BroadcastAckReport, BroadcastMessage, BroadcastTarget};
+use filehub_service::notification::BroadcastSink;
+use filehub_service::notification::broadcast::ADMIN_BROADCAST_JOB_TYPE;
+
+use crate::connection::handle::{ConnectionHandle, ConnectionInfo};
 use crate::connection::manager::ConnectionManager;
+use crate::message::builder;
 use crate::message::types::OutboundMessage;
 
+use super::broadcast::send_to_audience;
+
 /// Admin session monitor
 #[derive(Debug)]
 pub struct SessionMonitor {
     /// Connection manager
     connections: Arc<ConnectionManager>,
+    /// Broadcast storage
+    notification_repo: Arc<NotificationRepository>,
+    /// Job queue, for scheduled broadcasts
+    job_repo: Arc<JobRepository>,
+    /// How long a sent broadcast stays pending for users who connect
+    broadcast_pending: Duration,
 }
 
 impl SessionMonitor {
     /// Create a new session monitor
-    pub fn new(connections: Arc<ConnectionManager>) -> Self {
-        Self { connections }
+    pub fn new(
+        connections: Arc<ConnectionManager>,
+        notification_repo: Arc<NotificationRepository>,
+        job_repo: Arc<JobRepository>,
+        broadcast_pending_minutes: u64,
+    ) -> Self {
+        Self {
+            connections,
+            notification_repo,
+            job_repo,
+            broadcast_pending: Duration::minutes(broadcast_pending_minutes as i64),
+        }
     }
 
     /// Get all active connection info for admin view
@@ -45,6 +77,192 @@ impl SessionMonitor {
             timestamp: Utc::now(),
         }
     }
+
+    /// Store a broadcast and send it to the connected part of `target`.
+    ///
+    /// Users in the audience who connect while it is pending receive it
+    /// then; with `require_ack` it is re-sent on each of their connects
+    /// until they acknowledge it.
+    pub async fn broadcast(
+        &self,
+        admin_id: Uuid,
+        target: BroadcastTarget,
+        message: BroadcastMessage,
+        require_ack: bool,
+    ) -> Result<AdminBroadcast, AppError> {
+        let now = Utc::now();
+        let mut broadcast = new_broadcast(admin_id, &target, message, require_ack);
+        broadcast.sent_at = Some(now);
+        broadcast.expires_at = Some(now + self.broadcast_pending);
+        let mut broadcast = self.notification_repo.create_broadcast(&broadcast).await?;
+
+        broadcast.delivered_count = self.deliver(&broadcast).await as i32;
+        Ok(broadcast)
+    }
+
+    /// Store a broadcast to be sent by the worker at `at`.
+    pub async fn schedule(
+        &self,
+        admin_id: Uuid,
+        target: BroadcastTarget,
+        message: BroadcastMessage,
+        require_ack: bool,
+        at: DateTime<Utc>,
+    ) -> Result<AdminBroadcast, AppError> {
+        let mut broadcast = new_broadcast(admin_id, &target, message, require_ack);
+        broadcast.scheduled_at = Some(at);
+        let broadcast = self.notification_repo.create_broadcast(&broadcast).await?;
+
+        let payload = JobPayload::AdminBroadcast {
+            broadcast_id: broadcast.id,
+        };
+        let job = CreateJob {
+            job_type: ADMIN_BROADCAST_JOB_TYPE.to_string(),
+            queue: "default".to_string(),
+            priority: JobPriority::High,
+            payload: serde_json::to_value(&payload)
+                .map_err(|e| AppError::internal(format!("Invalid broadcast job: {e}")))?,
+            max_attempts: 3,
+            scheduled_at: Some(at),
+            created_by: Some(admin_id),
+        };
+        self.job_repo.create(&job).await?;
+
+        tracing::info!(
+            "Broadcast {} scheduled for {} (target={})",
+            broadcast.id,
+            at,
+            broadcast.target
+        );
+        Ok(broadcast)
+    }
+
+    /// Send a sent broadcast to its connected audience and record who it
+    /// reached. Returns the number of users reached.
+    pub async fn deliver(&self, broadcast: &AdminBroadcast) -> usize {
+        let Some(target) = broadcast.audience() else {
+            tracing::warn!(
+                "Broadcast {} has unknown target '{}', not delivered",
+                broadcast.id,
+                broadcast.target
+            );
+            return 0;
+        };
+
+        let reached = send_to_audience(&self.connections, broadcast, &target).await;
+        if let Err(e) = self
+            .notification_repo
+            .record_broadcast_deliveries(broadcast.id, &reached)
+            .await
+        {
+            tracing::warn!(
+                "Failed to record deliveries of broadcast {}: {}",
+                broadcast.id,
+                e
+            );
+        }
+        reached.len()
+    }
+
+    /// Send a newly connected user the broadcasts still pending for them
+    pub async fn deliver_pending(&self, handle: &ConnectionHandle) {
+        let user_id = handle.user_id.into_uuid();
+        let pending = match self
+            .notification_repo
+            .find_pending_broadcasts(user_id, handle.user_role.as_str())
+            .await
+        {
+            Ok(pending) => pending,
+            Err(e) => {
+                tracing::warn!("Failed to load pending broadcasts for {}: {}", user_id, e);
+                return;
+            }
+        };
+
+        for broadcast in pending {
+            if !handle
+                .send(builder::build_stored_broadcast(&broadcast))
+                .await
+            {
+                continue;
+            }
+            if let Err(e) = self
+                .notification_repo
+                .record_broadcast_deliveries(broadcast.id, &[user_id])
+                .await
+            {
+                tracing::warn!(
+                    "Failed to record delivery of broadcast {}: {}",
+                    broadcast.id,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Record a user's acknowledgement of a broadcast. Returns `false` if
+    /// the broadcast does not exist or needs no acknowledgement.
+    pub async fn acknowledge(&self, broadcast_id: Uuid, user_id: Uuid) -> Result<bool, AppError> {
+        self.notification_repo
+            .acknowledge_broadcast(broadcast_id, user_id)
+            .await
+    }
+
+    /// Who has and hasn't seen a broadcast
+    pub async fn ack_report(&self, broadcast_id: Uuid) -> Result<BroadcastAckReport, AppError> {
+        let broadcast = self
+            .notification_repo
+            .find_broadcast(broadcast_id)
+            .await?
+            .ok_or_else(|| AppError::not_found(format!("Broadcast {broadcast_id} not found")))?;
+        let audience = match broadcast.audience() {
+            Some(target) => {
+                self.notification_repo
+                    .find_broadcast_audience(&target)
+                    .await?
+            }
+            None => Vec::new(),
+        };
+        let receipts = self
+            .notification_repo
+            .find_broadcast_receipts(broadcast_id)
+            .await?;
+        Ok(BroadcastAckReport::build(&broadcast, &audience, &receipts))
+    }
+}
+
+#[async_trait]
+impl BroadcastSink for SessionMonitor {
+    async fn deliver(&self, broadcast: &AdminBroadcast) -> usize {
+        SessionMonitor::deliver(self, broadcast).await
+    }
+}
+
+/// An unsent broadcast row
+fn new_broadcast(
+    admin_id: Uuid,
+    target: &BroadcastTarget,
+    message: BroadcastMessage,
+    require_ack: bool,
+) -> AdminBroadcast {
+    AdminBroadcast {
+        id: Uuid::new_v4(),
+        admin_id,
+        target: target.as_column(),
+        target_user_ids: target.user_ids().map(<[Uuid]>::to_vec),
+        title: message.title,
+        message: message.message,
+        severity: message.severity,
+        persistent: message.persistent,
+        action_type: None,
+        action_payload: None,
+        delivered_count: 0,
+        require_ack,
+        scheduled_at: None,
+        sent_at: None,
+        expires_at: None,
+        created_at: Utc::now(),
+    }
 }
 
 /// Real-time session statistics
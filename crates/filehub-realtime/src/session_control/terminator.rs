@@ -2,11 +2,16 @@
 
 use std::sync::Arc;
 
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use tracing;
+use uuid::Uuid;
 
 use filehub_core::types::id::SessionId;
+use filehub_service::session::IdleSessionNotifier;
 
 use crate::connection::manager::ConnectionManager;
+use crate::message::types::OutboundMessage;
 
 /// Terminate a session via WebSocket.
 ///
@@ -35,3 +40,21 @@ pub async fn terminate_sessions_ws(
         terminate_session_ws(connections, *sid, reason).await;
     }
 }
+
+#[async_trait]
+impl IdleSessionNotifier for ConnectionManager {
+    async fn warn_idle(&self, session_id: Uuid, logout_at: DateTime<Utc>) {
+        let remaining = (logout_at - Utc::now()).num_seconds().max(0);
+        let msg = OutboundMessage::SessionIdleWarning {
+            session_id: SessionId::from(session_id),
+            logout_at,
+            minutes_remaining: (remaining + 59) / 60,
+        };
+        self.send_to_session(SessionId::from(session_id), msg).await;
+    }
+
+    async fn idle_terminated(&self, session_id: Uuid, reason: &str) {
+        self.close_session(SessionId::from(session_id), reason)
+            .await;
+    }
+}
@@ -4,6 +4,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use filehub_core::types::{RequestId, TenantId};
+use filehub_database::TenantScope;
 use filehub_entity::user::UserRole;
 
 /// Context for the current authenticated request.
@@ -14,6 +16,10 @@ use filehub_entity::user::UserRole;
 pub struct RequestContext {
     /// The authenticated user's ID.
     pub user_id: Uuid,
+    /// The tenant the user belongs to; every repository call made for
+    /// this request is scoped to it.
+    #[serde(default = "TenantId::default_tenant")]
+    pub tenant_id: TenantId,
     /// The current session ID.
     pub session_id: Uuid,
     /// The user's role at the time the JWT was issued.
@@ -26,6 +32,12 @@ pub struct RequestContext {
     pub user_agent: Option<String>,
     /// When the request was received.
     pub request_time: DateTime<Utc>,
+    /// Correlation id of the HTTP request, when known.
+    #[serde(default)]
+    pub request_id: Option<RequestId>,
+    /// The administrator acting as the user, in an impersonation session.
+    #[serde(default)]
+    pub impersonator_id: Option<Uuid>,
 }
 
 impl RequestContext {
@@ -40,15 +52,66 @@ impl RequestContext {
     ) -> Self {
         Self {
             user_id,
+            tenant_id: TenantId::DEFAULT,
             session_id,
             role,
             username,
             ip_address,
             user_agent,
             request_time: Utc::now(),
+            request_id: RequestId::current(),
+            impersonator_id: None,
         }
     }
 
+    /// Creates the context of an anonymous visitor reading public
+    /// content. It has no user or session: both IDs are nil and the role
+    /// is the least privileged one.
+    pub fn anonymous(ip_address: String, user_agent: Option<String>) -> Self {
+        Self::new(
+            Uuid::nil(),
+            Uuid::nil(),
+            UserRole::Viewer,
+            "anonymous".to_string(),
+            ip_address,
+            user_agent,
+        )
+    }
+
+    /// Returns whether the request was made without signing in.
+    pub fn is_anonymous(&self) -> bool {
+        self.user_id.is_nil()
+    }
+
+    /// Sets the tenant of the request. Contexts start in the default
+    /// tenant.
+    pub fn with_tenant(mut self, tenant_id: TenantId) -> Self {
+        self.tenant_id = tenant_id;
+        self
+    }
+
+    /// Repository scope for this request's tenant.
+    pub fn tenant_scope(&self) -> TenantScope {
+        TenantScope::new(self.tenant_id)
+    }
+
+    /// Sets the request correlation id.
+    pub fn with_request_id(mut self, request_id: RequestId) -> Self {
+        self.request_id = Some(request_id);
+        self
+    }
+
+    /// Marks the request as made by `admin_id` acting as the user.
+    pub fn with_impersonator(mut self, admin_id: Uuid) -> Self {
+        self.impersonator_id = Some(admin_id);
+        self
+    }
+
+    /// Returns whether an administrator is acting as the user.
+    pub fn is_impersonated(&self) -> bool {
+        self.impersonator_id.is_some()
+    }
+
     /// Returns whether the current user is an admin.
     pub fn is_admin(&self) -> bool {
         matches!(self.role, UserRole::Admin)
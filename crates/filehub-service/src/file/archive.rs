@@ -0,0 +1,214 @@
+//! Streaming ZIP archives of folder trees.
+//!
+//! The archive is written by the synchronous `zip` writer on a blocking
+//! thread, in streaming mode (sizes and CRCs go in data descriptors after
+//! each entry), and handed to the caller chunk by chunk through a bounded
+//! channel. File contents are read from storage as the archive is written,
+//! so memory use does not depend on the folder size.
+
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use bytes::{Bytes, BytesMut};
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use futures::StreamExt;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use filehub_core::traits::storage::ByteStream;
+use filehub_entity::file::File;
+use filehub_storage::manager::StorageManager;
+
+/// Bytes collected before a chunk is handed to the response.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Chunks buffered between the writer thread and the response.
+const CHANNEL_DEPTH: usize = 8;
+
+/// A file placed in an archive.
+#[derive(Debug)]
+pub struct ArchiveEntry {
+    /// Path inside the archive.
+    pub path: String,
+    /// The file.
+    pub file: File,
+}
+
+/// Hands out unique archive paths. Paths are compared case-insensitively
+/// so an archive extracts the same on case-insensitive filesystems.
+#[derive(Debug, Default)]
+pub struct EntryNames {
+    taken: HashSet<String>,
+}
+
+impl EntryNames {
+    /// Claims a path for `name` inside `dir` (empty for the archive root),
+    /// renaming `report.pdf` to `report (1).pdf` and so on if it is taken.
+    pub fn claim(&mut self, dir: &str, name: &str) -> String {
+        let name = sanitize_segment(name);
+        let (stem, ext) = match name.rfind('.') {
+            Some(i) if i > 0 => name.split_at(i),
+            _ => (name.as_str(), ""),
+        };
+
+        let mut n = 0;
+        loop {
+            let candidate = if n == 0 {
+                name.clone()
+            } else {
+                format!("{stem} ({n}){ext}")
+            };
+            let path = if dir.is_empty() {
+                candidate
+            } else {
+                format!("{dir}/{candidate}")
+            };
+            if self.taken.insert(path.to_lowercase()) {
+                return path;
+            }
+            n += 1;
+        }
+    }
+}
+
+/// Makes `name` safe as a single path segment: separators, characters
+/// Windows rejects and control characters become `_`, and names that
+/// would navigate (`.`, `..`) or vanish are replaced.
+pub fn sanitize_segment(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim_end_matches(['.', ' ']).trim_start();
+    if cleaned.is_empty() {
+        "_".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+/// Streams a ZIP archive holding `dirs` (as directory entries) and
+/// `entries`. A storage failure mid-way ends the stream with an error,
+/// which aborts the response.
+pub fn stream_archive(
+    storage: Arc<StorageManager>,
+    dirs: Vec<String>,
+    entries: Vec<ArchiveEntry>,
+) -> ByteStream {
+    let (tx, rx) = mpsc::channel(CHANNEL_DEPTH);
+    let handle = Handle::current();
+
+    tokio::task::spawn_blocking(move || {
+        let errors = tx.clone();
+        if let Err(e) = write_archive(&handle, &storage, &dirs, &entries, ChannelWriter::new(tx)) {
+            tracing::warn!("ZIP archive aborted: {}", e);
+            let _ = errors.blocking_send(Err(e));
+        }
+    });
+
+    Box::pin(futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    }))
+}
+
+fn write_archive(
+    handle: &Handle,
+    storage: &StorageManager,
+    dirs: &[String],
+    entries: &[ArchiveEntry],
+    out: ChannelWriter,
+) -> io::Result<()> {
+    let mut zip = ZipWriter::new_stream(out);
+
+    for dir in dirs {
+        zip.add_directory(dir.as_str(), SimpleFileOptions::default())
+            .map_err(io::Error::other)?;
+    }
+
+    for entry in entries {
+        let file = &entry.file;
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .large_file(file.size_bytes >= u32::MAX as i64)
+            .last_modified_time(zip_time(file.updated_at));
+        zip.start_file(entry.path.as_str(), options)
+            .map_err(io::Error::other)?;
+
+        let mut content = handle
+            .block_on(async {
+                storage
+                    .get(&file.storage_id)
+                    .await?
+                    .read(&file.storage_path)
+                    .await
+            })
+            .map_err(|e| io::Error::other(format!("Failed to read '{}': {}", entry.path, e)))?;
+        while let Some(chunk) = handle.block_on(content.next()) {
+            zip.write_all(&chunk?)?;
+        }
+    }
+
+    zip.finish().map_err(io::Error::other)?.flush()
+}
+
+/// Modification time in ZIP's DOS format, which cannot represent dates
+/// before 1980.
+fn zip_time(at: DateTime<Utc>) -> zip::DateTime {
+    zip::DateTime::from_date_and_time(
+        at.year().clamp(1980, 2107) as u16,
+        at.month() as u8,
+        at.day() as u8,
+        at.hour() as u8,
+        at.minute() as u8,
+        at.second() as u8,
+    )
+    .unwrap_or_default()
+}
+
+/// Collects archive bytes into chunks and sends them to the response.
+/// A closed channel (client gone) fails the next write, which stops the
+/// archive.
+struct ChannelWriter {
+    tx: mpsc::Sender<io::Result<Bytes>>,
+    buf: BytesMut,
+}
+
+impl ChannelWriter {
+    fn new(tx: mpsc::Sender<io::Result<Bytes>>) -> Self {
+        Self {
+            tx,
+            buf: BytesMut::with_capacity(CHUNK_SIZE),
+        }
+    }
+
+    fn send(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let chunk = self.buf.split().freeze();
+        self.tx
+            .blocking_send(Ok(chunk))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "download cancelled"))
+    }
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        if self.buf.len() >= CHUNK_SIZE {
+            self.send()?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send()
+    }
+}
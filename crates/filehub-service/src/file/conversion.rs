@@ -0,0 +1,211 @@
+//! CAD conversion requests.
+//!
+//! A request copies the file out of storage and queues a conversion job
+//! for the worker, with one of the configured conversion profiles.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use uuid::Uuid;
+
+use filehub_auth::acl::EffectivePermissionResolver;
+use filehub_core::config::ConversionConfig;
+use filehub_core::error::{AppError, ErrorKind, codes};
+use filehub_database::repositories::file::FileRepository;
+use filehub_database::repositories::job::JobRepository;
+use filehub_entity::file::File;
+use filehub_entity::job::model::CreateJob;
+use filehub_entity::job::payload::JobPayload;
+use filehub_entity::job::status::JobPriority;
+use filehub_entity::permission::{AclPermission, ResourceType};
+use filehub_storage::manager::StorageManager;
+
+use crate::context::RequestContext;
+use crate::file::download::ensure_not_quarantined;
+
+/// Job type of CAD conversion.
+pub const CAD_CONVERSION_JOB_TYPE: &str = "cad_conversion";
+
+/// Queues CAD conversions of files.
+#[derive(Clone)]
+pub struct ConversionRequestService {
+    /// File repository.
+    file_repo: Arc<FileRepository>,
+    /// Storage manager.
+    storage: Arc<StorageManager>,
+    /// Permission resolver.
+    perm_resolver: Arc<EffectivePermissionResolver>,
+    /// Queue for conversion jobs.
+    job_repo: Arc<JobRepository>,
+    /// Conversion settings, with the profiles.
+    config: ConversionConfig,
+    /// Root of local data; sources are staged and outputs written under it.
+    data_root: PathBuf,
+}
+
+impl std::fmt::Debug for ConversionRequestService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConversionRequestService").finish()
+    }
+}
+
+/// A conversion request.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ConversionRequest {
+    /// Conversion profile; the configured default if unset.
+    pub profile: Option<String>,
+    /// Output formats; the converter's own if empty.
+    #[serde(default)]
+    pub targets: Vec<String>,
+}
+
+/// A queued conversion.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuedConversion {
+    /// Job doing the conversion.
+    pub job_id: Uuid,
+    /// Profile it runs with.
+    pub profile: String,
+}
+
+impl ConversionRequestService {
+    /// Creates a new conversion request service.
+    pub fn new(
+        file_repo: Arc<FileRepository>,
+        storage: Arc<StorageManager>,
+        perm_resolver: Arc<EffectivePermissionResolver>,
+        job_repo: Arc<JobRepository>,
+        config: ConversionConfig,
+        data_root: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            file_repo,
+            storage,
+            perm_resolver,
+            job_repo,
+            config,
+            data_root: data_root.into(),
+        }
+    }
+
+    /// Queues a conversion of a file the caller may view. An unknown
+    /// profile is rejected before anything is queued.
+    #[tracing::instrument(target = "otel", name = "ConversionRequestService::request", skip_all)]
+    pub async fn request(
+        &self,
+        ctx: &RequestContext,
+        file_id: Uuid,
+        req: ConversionRequest,
+    ) -> Result<QueuedConversion, AppError> {
+        if !self.config.enabled {
+            return Err(AppError::not_found("CAD conversions are not enabled"));
+        }
+        let (profile, _) = self
+            .config
+            .profile(req.profile.as_deref())
+            .map_err(AppError::validation)?;
+        let profile = profile.to_string();
+
+        let file = self.viewable(ctx, file_id).await?;
+        let source_path = self.stage(&file).await?;
+
+        let payload = JobPayload::CadConversion {
+            file_id: file.id,
+            file_name: file.name.clone(),
+            source_path: source_path.to_string_lossy().into_owned(),
+            targets: req.targets,
+            profile: Some(profile.clone()),
+            primary_name: None,
+            output_dir: Some(
+                self.data_root
+                    .join("cache")
+                    .join("conversions")
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+        };
+        let job = CreateJob {
+            job_type: CAD_CONVERSION_JOB_TYPE.to_string(),
+            queue: "default".to_string(),
+            priority: JobPriority::Normal,
+            payload: serde_json::to_value(&payload)
+                .map_err(|e| AppError::internal(format!("Invalid conversion job: {e}")))?,
+            max_attempts: 1,
+            scheduled_at: None,
+            created_by: Some(ctx.user_id),
+        };
+        let job = self
+            .job_repo
+            .create(&job)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to queue conversion: {e}")))?;
+
+        info!(file_id = %file.id, job_id = %job.id, profile = %profile, "Queued CAD conversion");
+        Ok(QueuedConversion {
+            job_id: job.id,
+            profile,
+        })
+    }
+
+    /// Copies a file out of storage to where the worker can read it, under
+    /// a directory of its own. Returns the path of the copy.
+    async fn stage(&self, file: &File) -> Result<PathBuf, AppError> {
+        // The converter picks the reader by extension, so the name is kept.
+        let name = Path::new(&file.name)
+            .file_name()
+            .ok_or_else(|| AppError::validation("File has no usable name"))?;
+        let dir = self
+            .data_root
+            .join("conversions")
+            .join("sources")
+            .join(Uuid::new_v4().to_string());
+        tokio::fs::create_dir_all(&dir).await.map_err(|e| {
+            AppError::with_source(ErrorKind::Storage, "Failed to stage conversion source", e)
+        })?;
+        let source = dir.join(name);
+
+        let content = match self.storage.get(&file.storage_id).await {
+            Ok(provider) => provider.read(&file.storage_path).await,
+            Err(e) => Err(e),
+        }
+        .map_err(|e| AppError::storage(format!("Failed to read file: {}", e.message)))?;
+        let mut reader = tokio_util::io::StreamReader::new(content.map_err(std::io::Error::other));
+        let mut out = tokio::fs::File::create(&source).await.map_err(|e| {
+            AppError::with_source(ErrorKind::Storage, "Failed to stage conversion source", e)
+        })?;
+        tokio::io::copy(&mut reader, &mut out).await.map_err(|e| {
+            AppError::with_source(ErrorKind::Storage, "Failed to stage conversion source", e)
+        })?;
+        Ok(source)
+    }
+
+    /// Loads a file the caller may view and whose content is not
+    /// quarantined.
+    async fn viewable(&self, ctx: &RequestContext, file_id: Uuid) -> Result<File, AppError> {
+        let file = self
+            .file_repo
+            .find_by_id(file_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+            .ok_or_else(|| {
+                AppError::not_found("File not found").with_code(codes::FILE_NOT_FOUND)
+            })?;
+
+        self.perm_resolver
+            .require_permission(
+                ctx.user_id,
+                &ctx.role,
+                ResourceType::File,
+                file_id,
+                file.owner_id,
+                Some(file.folder_id),
+                AclPermission::Viewer,
+            )
+            .await?;
+        ensure_not_quarantined(&file)?;
+        Ok(file)
+    }
+}
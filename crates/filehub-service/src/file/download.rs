@@ -1,18 +1,25 @@
 //! File download service — streams file content with ACL enforcement.
 
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use bytes::Bytes;
 use uuid::Uuid;
 
 use filehub_auth::acl::EffectivePermissionResolver;
-use filehub_core::error::AppError;
+use filehub_core::config::{DirectTransferConfig, ZipDownloadConfig};
+use filehub_core::error::{AppError, codes};
+use filehub_core::traits::storage::{ByteStream, PresignedRequest};
 use filehub_database::repositories::file::FileRepository;
-use filehub_entity::file::File;
+use filehub_database::repositories::folder::FolderRepository;
+use filehub_entity::file::{File, FileVersion};
 use filehub_entity::permission::{AclPermission, ResourceType};
 use filehub_storage::manager::StorageManager;
 
 use crate::context::RequestContext;
+use crate::file::archive::{self, ArchiveEntry, EntryNames};
+use crate::session::SessionAudit;
 
 /// Handles file downloads with ACL checking and streaming.
 #[derive(Clone)]
@@ -23,6 +30,14 @@ pub struct DownloadService {
     storage: Arc<StorageManager>,
     /// Permission resolver.
     perm_resolver: Arc<EffectivePermissionResolver>,
+    /// Folder repository, for folder archives.
+    folder_repo: Arc<FolderRepository>,
+    /// Audit service.
+    audit: Arc<SessionAudit>,
+    /// Folder archive limits.
+    zip_limits: ZipDownloadConfig,
+    /// Presigned download settings; disabled unless set.
+    direct_transfer: DirectTransferConfig,
 }
 
 impl std::fmt::Debug for DownloadService {
@@ -31,6 +46,28 @@ impl std::fmt::Debug for DownloadService {
     }
 }
 
+/// A folder packed as a ZIP archive, streamed as it is read.
+pub struct FolderArchive {
+    /// Suggested filename for Content-Disposition.
+    pub filename: String,
+    /// Files in the archive.
+    pub file_count: usize,
+    /// Total size of the files, before compression.
+    pub total_bytes: u64,
+    /// Archive content.
+    pub stream: ByteStream,
+}
+
+impl std::fmt::Debug for FolderArchive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FolderArchive")
+            .field("filename", &self.filename)
+            .field("file_count", &self.file_count)
+            .field("total_bytes", &self.total_bytes)
+            .finish()
+    }
+}
+
 /// Result containing file metadata and content bytes for a download.
 #[derive(Debug)]
 pub struct DownloadResult {
@@ -50,26 +87,51 @@ impl DownloadService {
         file_repo: Arc<FileRepository>,
         storage: Arc<StorageManager>,
         perm_resolver: Arc<EffectivePermissionResolver>,
+        folder_repo: Arc<FolderRepository>,
+        audit: Arc<SessionAudit>,
+        zip_limits: ZipDownloadConfig,
     ) -> Self {
         Self {
             file_repo,
             storage,
             perm_resolver,
+            folder_repo,
+            audit,
+            zip_limits,
+            direct_transfer: DirectTransferConfig::default(),
         }
     }
 
+    /// Allows presigned downloads as configured in
+    /// `storage.direct_transfer`.
+    pub fn with_direct_transfer(mut self, config: DirectTransferConfig) -> Self {
+        self.direct_transfer = config;
+        self
+    }
+
     /// Downloads a file, checking viewer permission.
+    #[tracing::instrument(target = "otel", name = "DownloadService::download", skip_all)]
     pub async fn download(
         &self,
         ctx: &RequestContext,
         file_id: Uuid,
     ) -> Result<DownloadResult, AppError> {
+        let file = self.resolve(ctx, file_id).await?;
+        self.read(file).await
+    }
+
+    /// Loads file metadata after checking viewer permission, without
+    /// touching storage. Used to answer conditional and HEAD requests.
+    #[tracing::instrument(target = "otel", name = "DownloadService::resolve", skip_all)]
+    pub async fn resolve(&self, ctx: &RequestContext, file_id: Uuid) -> Result<File, AppError> {
         let file = self
             .file_repo
             .find_by_id(file_id)
             .await
             .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .ok_or_else(|| AppError::not_found("File not found"))?;
+            .ok_or_else(|| {
+                AppError::not_found("File not found").with_code(codes::FILE_NOT_FOUND)
+            })?;
 
         self.perm_resolver
             .require_permission(
@@ -82,7 +144,41 @@ impl DownloadService {
                 AclPermission::Viewer,
             )
             .await?;
+        ensure_not_quarantined(&file)?;
+
+        Ok(file)
+    }
 
+    /// Presigns a download of a file straight from its storage backend,
+    /// after the same checks as [`download`](Self::download).
+    #[tracing::instrument(target = "otel", name = "DownloadService::presign_download", skip_all)]
+    pub async fn presign_download(
+        &self,
+        ctx: &RequestContext,
+        file_id: Uuid,
+    ) -> Result<PresignedRequest, AppError> {
+        if !self.direct_transfer.enabled {
+            return Err(AppError::not_implemented("Direct transfers are disabled")
+                .with_code(codes::STORAGE_PRESIGN_UNSUPPORTED));
+        }
+        let file = self.resolve(ctx, file_id).await?;
+        let provider = self.storage.get(&file.storage_id).await?;
+        let disposition = format!(
+            "attachment; filename=\"{}\"",
+            file.name.replace(['"', '\\'], "_")
+        );
+        provider
+            .presign_download(
+                &file.storage_path,
+                Some(&disposition),
+                Duration::from_secs(self.direct_transfer.url_expiry_seconds),
+            )
+            .await
+    }
+
+    /// Reads the content of an already-resolved file.
+    #[tracing::instrument(target = "otel", name = "DownloadService::read", skip_all)]
+    pub async fn read(&self, file: File) -> Result<DownloadResult, AppError> {
         let data = self
             .storage
             .read(&file.storage_id, &file.storage_path)
@@ -102,31 +198,60 @@ impl DownloadService {
         })
     }
 
+    /// Streams `len` bytes of an already-resolved file starting at `offset`.
+    #[tracing::instrument(target = "otel", name = "DownloadService::read_range", skip_all)]
+    pub async fn read_range(
+        &self,
+        file: &File,
+        offset: u64,
+        len: u64,
+    ) -> Result<ByteStream, AppError> {
+        self.storage
+            .read_range(&file.storage_id, &file.storage_path, offset, len)
+            .await
+            .map_err(|e| AppError::internal(format!("Storage read failed: {e}")))
+    }
+
+    /// Streams `len` bytes of an already-resolved file version.
+    #[tracing::instrument(
+        target = "otel",
+        name = "DownloadService::read_version_range",
+        skip_all
+    )]
+    pub async fn read_version_range(
+        &self,
+        file: &File,
+        version: &FileVersion,
+        offset: u64,
+        len: u64,
+    ) -> Result<ByteStream, AppError> {
+        self.storage
+            .read_range(&file.storage_id, &version.storage_path, offset, len)
+            .await
+            .map_err(|e| AppError::internal(format!("Storage read failed: {e}")))
+    }
+
     /// Downloads a specific version of a file.
+    #[tracing::instrument(target = "otel", name = "DownloadService::download_version", skip_all)]
     pub async fn download_version(
         &self,
         ctx: &RequestContext,
         file_id: Uuid,
         version_number: i32,
     ) -> Result<DownloadResult, AppError> {
-        let file = self
-            .file_repo
-            .find_by_id(file_id)
-            .await
-            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .ok_or_else(|| AppError::not_found("File not found"))?;
+        let (file, version) = self.resolve_version(ctx, file_id, version_number).await?;
+        self.read_version(file, &version).await
+    }
 
-        self.perm_resolver
-            .require_permission(
-                ctx.user_id,
-                &ctx.role,
-                ResourceType::File,
-                file_id,
-                file.owner_id,
-                Some(file.folder_id),
-                AclPermission::Viewer,
-            )
-            .await?;
+    /// Loads file and version metadata after checking viewer permission.
+    #[tracing::instrument(target = "otel", name = "DownloadService::resolve_version", skip_all)]
+    pub async fn resolve_version(
+        &self,
+        ctx: &RequestContext,
+        file_id: Uuid,
+        version_number: i32,
+    ) -> Result<(File, FileVersion), AppError> {
+        let file = self.resolve(ctx, file_id).await?;
 
         let version = self
             .file_repo
@@ -135,6 +260,16 @@ impl DownloadService {
             .map_err(|e| AppError::internal(format!("Database error: {e}")))?
             .ok_or_else(|| AppError::not_found(format!("Version {version_number} not found")))?;
 
+        Ok((file, version))
+    }
+
+    /// Reads the content of an already-resolved file version.
+    #[tracing::instrument(target = "otel", name = "DownloadService::read_version", skip_all)]
+    pub async fn read_version(
+        &self,
+        file: File,
+        version: &FileVersion,
+    ) -> Result<DownloadResult, AppError> {
         let provider = self
             .storage
             .get(&file.storage_id)
@@ -159,7 +294,28 @@ impl DownloadService {
         })
     }
 
+    /// Reads a file reached through a share link. No permission check;
+    /// the caller has already authorized the link.
+    #[tracing::instrument(target = "otel", name = "DownloadService::read_shared", skip_all)]
+    pub async fn read_shared(&self, file_id: Uuid) -> Result<DownloadResult, AppError> {
+        let file = self
+            .file_repo
+            .find_by_id(file_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+            .ok_or_else(|| {
+                AppError::not_found("File not found").with_code(codes::FILE_NOT_FOUND)
+            })?;
+        ensure_not_quarantined(&file)?;
+        self.read(file).await
+    }
+
     /// Downloads a file via a share token (no auth context required).
+    #[tracing::instrument(
+        target = "otel",
+        name = "DownloadService::download_via_share",
+        skip_all
+    )]
     pub async fn download_via_share(
         &self,
         file_id: Uuid,
@@ -173,7 +329,10 @@ impl DownloadService {
             .find_by_id(file_id)
             .await
             .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .ok_or_else(|| AppError::not_found("File not found"))?;
+            .ok_or_else(|| {
+                AppError::not_found("File not found").with_code(codes::FILE_NOT_FOUND)
+            })?;
+        ensure_not_quarantined(&file)?;
 
         let provider = self
             .storage
@@ -195,4 +354,172 @@ impl DownloadService {
             content_type,
         })
     }
+
+    /// Packs a folder and everything below it that the user may view into
+    /// a ZIP archive. Subfolders and files without viewer permission are
+    /// left out. The archive limits are checked before anything is read,
+    /// so an oversized request fails with an error instead of a truncated
+    /// download.
+    #[tracing::instrument(target = "otel", name = "DownloadService::zip_folder", skip_all)]
+    pub async fn zip_folder(
+        &self,
+        ctx: &RequestContext,
+        folder_id: Uuid,
+    ) -> Result<FolderArchive, AppError> {
+        let root = self
+            .folder_repo
+            .find_by_id(folder_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found("Folder not found").with_code(codes::FOLDER_NOT_FOUND)
+            })?;
+
+        self.perm_resolver
+            .require_permission(
+                ctx.user_id,
+                &ctx.role,
+                ResourceType::Folder,
+                root.id,
+                root.owner_id,
+                root.parent_id,
+                AclPermission::Viewer,
+            )
+            .await?;
+
+        let mut names = EntryNames::default();
+        let root_dir = names.claim("", &root.name);
+        let mut dirs = vec![root_dir.clone()];
+        let mut dir_paths = HashMap::from([(root.id, root_dir)]);
+
+        // Descendants come parent first, so a folder whose parent was left
+        // out is skipped along with its whole subtree.
+        for folder in self.folder_repo.find_descendants(root.id).await? {
+            let Some(parent_dir) = folder.parent_id.and_then(|p| dir_paths.get(&p)).cloned() else {
+                continue;
+            };
+            if !self
+                .can_view(
+                    ctx,
+                    ResourceType::Folder,
+                    folder.id,
+                    folder.owner_id,
+                    folder.parent_id,
+                )
+                .await?
+            {
+                continue;
+            }
+            let dir = names.claim(&parent_dir, &folder.name);
+            dirs.push(dir.clone());
+            dir_paths.insert(folder.id, dir);
+        }
+
+        let folder_ids: Vec<Uuid> = dir_paths.keys().copied().collect();
+        let mut entries = Vec::new();
+        let mut total_bytes = 0u64;
+        for file in self.file_repo.find_in_folders(&folder_ids).await? {
+            if file.is_quarantined() {
+                continue;
+            }
+            if !self
+                .can_view(
+                    ctx,
+                    ResourceType::File,
+                    file.id,
+                    file.owner_id,
+                    Some(file.folder_id),
+                )
+                .await?
+            {
+                continue;
+            }
+
+            total_bytes += file.size_bytes.max(0) as u64;
+            self.check_zip_limits(entries.len() as u64 + 1, total_bytes)?;
+
+            let path = names.claim(&dir_paths[&file.folder_id], &file.name);
+            entries.push(ArchiveEntry { path, file });
+        }
+
+        let file_count = entries.len();
+        let details = serde_json::json!({
+            "folder_name": root.name,
+            "files": file_count,
+            "total_bytes": total_bytes,
+        });
+        if let Err(e) = self
+            .audit
+            .log_event(
+                ctx.user_id,
+                "folder.downloaded",
+                "folder",
+                Some(root.id),
+                Some(details),
+                Some(&ctx.ip_address),
+                ctx.user_agent.as_deref(),
+            )
+            .await
+        {
+            tracing::warn!(folder_id = %root.id, error = %e, "Failed to audit folder download");
+        }
+
+        Ok(FolderArchive {
+            filename: format!("{}.zip", archive::sanitize_segment(&root.name)),
+            file_count,
+            total_bytes,
+            stream: archive::stream_archive(Arc::clone(&self.storage), dirs, entries),
+        })
+    }
+
+    async fn can_view(
+        &self,
+        ctx: &RequestContext,
+        resource_type: ResourceType,
+        resource_id: Uuid,
+        owner_id: Uuid,
+        parent_folder_id: Option<Uuid>,
+    ) -> Result<bool, AppError> {
+        let permission = self
+            .perm_resolver
+            .resolve(
+                ctx.user_id,
+                &ctx.role,
+                resource_type,
+                resource_id,
+                owner_id,
+                parent_folder_id,
+                AclPermission::Viewer,
+            )
+            .await?;
+        Ok(permission.granted)
+    }
+
+    fn check_zip_limits(&self, files: u64, bytes: u64) -> Result<(), AppError> {
+        let limits = &self.zip_limits;
+        if limits.max_files > 0 && files > limits.max_files {
+            return Err(AppError::validation(format!(
+                "Folder has more than {} files; download it in parts",
+                limits.max_files
+            )));
+        }
+        if limits.max_total_bytes > 0 && bytes > limits.max_total_bytes {
+            return Err(AppError::validation(format!(
+                "Folder exceeds the {} byte download limit; download it in parts",
+                limits.max_total_bytes
+            ))
+            .with_code(codes::FILE_TOO_LARGE));
+        }
+        Ok(())
+    }
+}
+
+/// Refuses access to the content of a quarantined file.
+pub(crate) fn ensure_not_quarantined(file: &File) -> Result<(), AppError> {
+    if file.is_quarantined() {
+        return Err(
+            AppError::forbidden("File has been quarantined by the virus scanner")
+                .with_code(codes::FILE_QUARANTINED),
+        );
+    }
+    Ok(())
 }
@@ -0,0 +1,96 @@
+//! File inventory export encoding.
+
+use std::str::FromStr;
+
+use filehub_core::error::AppError;
+use filehub_entity::file::File;
+
+use crate::session::audit_export::csv_field;
+
+/// File format of a file inventory export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileExportFormat {
+    /// Comma-separated values with a header row.
+    Csv,
+    /// One JSON object per line.
+    Ndjson,
+}
+
+/// CSV columns, in order.
+const CSV_COLUMNS: &[&str] = &[
+    "id",
+    "name",
+    "folder_id",
+    "storage_id",
+    "owner_id",
+    "mime_type",
+    "size_bytes",
+    "checksum_sha256",
+    "current_version",
+    "created_at",
+    "updated_at",
+];
+
+impl FileExportFormat {
+    /// MIME type of the export.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    /// File extension of the export.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+        }
+    }
+
+    /// Text written before the first file.
+    pub fn header(&self) -> String {
+        match self {
+            Self::Csv => format!("{}\n", CSV_COLUMNS.join(",")),
+            Self::Ndjson => String::new(),
+        }
+    }
+
+    /// One file as a line of the export, including the line break.
+    pub fn encode(&self, file: &File) -> Result<String, AppError> {
+        match self {
+            Self::Csv => {
+                let fields = [
+                    file.id.to_string(),
+                    file.name.clone(),
+                    file.folder_id.to_string(),
+                    file.storage_id.to_string(),
+                    file.owner_id.to_string(),
+                    file.mime_type.clone().unwrap_or_default(),
+                    file.size_bytes.to_string(),
+                    file.checksum_sha256.clone().unwrap_or_default(),
+                    file.current_version.to_string(),
+                    file.created_at.to_rfc3339(),
+                    file.updated_at.to_rfc3339(),
+                ];
+                let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+                Ok(format!("{}\n", line.join(",")))
+            }
+            Self::Ndjson => Ok(format!("{}\n", serde_json::to_string(file)?)),
+        }
+    }
+}
+
+impl FromStr for FileExportFormat {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "ndjson" | "jsonl" | "json" => Ok(Self::Ndjson),
+            other => Err(AppError::validation(format!(
+                "Unknown export format '{other}'; expected csv or ndjson"
+            ))),
+        }
+    }
+}
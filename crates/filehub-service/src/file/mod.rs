@@ -1,14 +1,21 @@
 //! File management services — CRUD, upload, download, preview, search, versioning.
 
+pub mod archive;
+pub mod conversion;
 pub mod download;
+pub mod export;
 pub mod preview;
+pub mod processing;
 pub mod search;
 pub mod service;
 pub mod upload;
 pub mod version;
 
+pub use conversion::ConversionRequestService;
 pub use download::DownloadService;
+pub use export::FileExportFormat;
 pub use preview::PreviewService;
+pub use processing::{ProcessingStats, UploadProcessor};
 pub use search::SearchService;
 pub use service::FileService;
 pub use upload::UploadService;
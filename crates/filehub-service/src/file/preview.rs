@@ -1,18 +1,47 @@
 //! Preview and thumbnail generation service.
 
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use bytes::Bytes;
+use futures::TryStreamExt;
+use serde::Serialize;
+use tracing::{info, warn};
 use uuid::Uuid;
 
 use filehub_auth::acl::EffectivePermissionResolver;
+use filehub_cache::keys::{self, CacheKey};
 use filehub_cache::provider::CacheManager;
-use filehub_core::{error::AppError, traits::CacheProvider};
+use filehub_core::{
+    config::{DocumentPreviewConfig, ThumbnailConfig, ThumbnailFormat},
+    error::{AppError, ErrorKind, codes},
+    traits::CacheProvider,
+};
 use filehub_database::repositories::file::FileRepository;
+use filehub_database::repositories::job::JobRepository;
+use filehub_entity::file::File;
+use filehub_entity::job::model::CreateJob;
+use filehub_entity::job::payload::JobPayload;
+use filehub_entity::job::status::JobPriority;
 use filehub_entity::permission::{AclPermission, ResourceType};
 use filehub_storage::manager::StorageManager;
+use filehub_storage::thumbnail::{
+    CachedPreview, DocumentKind, DocumentRenderer, PreviewCache, negotiate_format,
+    render_thumbnails,
+};
 
 use crate::context::RequestContext;
+use crate::file::download::ensure_not_quarantined;
+
+/// Job type of eager thumbnail generation.
+pub const THUMBNAIL_JOB_TYPE: &str = "thumbnail_generation";
+
+/// Job type of document page rendering.
+pub const DOCUMENT_PREVIEW_JOB_TYPE: &str = "document_preview";
+
+/// Edge of the single page shown for an image.
+const IMAGE_PAGE_SIZE: u32 = 1024;
 
 /// Generates and serves file previews/thumbnails.
 #[derive(Clone)]
@@ -25,6 +54,31 @@ pub struct PreviewService {
     perm_resolver: Arc<EffectivePermissionResolver>,
     /// Cache for storing generated thumbnails.
     cache: Arc<CacheManager>,
+    /// Thumbnail sizes and formats.
+    thumbnails: ThumbnailConfig,
+    /// Document page rendering; `None` when document previews are off.
+    documents: Option<Arc<DocumentPreviews>>,
+}
+
+/// What document page rendering needs.
+#[derive(Debug)]
+struct DocumentPreviews {
+    /// Queue for render jobs.
+    job_repo: Arc<JobRepository>,
+    /// External tool runner.
+    renderer: DocumentRenderer,
+    /// Rendered pages by content hash.
+    cache: PreviewCache,
+    /// Limits.
+    config: DocumentPreviewConfig,
+}
+
+impl DocumentPreviews {
+    /// A claim older than this belongs to a render that died, so the
+    /// document is queued again. Covers both tool runs and queue delay.
+    fn stale_after(&self) -> Duration {
+        Duration::from_secs(self.config.timeout_seconds * 2 + 300)
+    }
 }
 
 impl std::fmt::Debug for PreviewService {
@@ -42,6 +96,55 @@ pub struct PreviewResult {
     pub content_type: String,
 }
 
+/// Whether a file's page previews can be shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentPreviewStatus {
+    /// Pages can be fetched.
+    Ready,
+    /// Pages are being rendered; ask again shortly.
+    Pending,
+    /// The file has no preview.
+    Unavailable,
+}
+
+/// Page previews of a file.
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentPreview {
+    /// Whether pages can be fetched.
+    pub status: DocumentPreviewStatus,
+    /// Number of pages, numbered from 1, when ready.
+    pub pages: u32,
+    /// Why there is no preview, when unavailable.
+    pub reason: Option<String>,
+}
+
+impl DocumentPreview {
+    fn ready(pages: u32) -> Self {
+        Self {
+            status: DocumentPreviewStatus::Ready,
+            pages,
+            reason: None,
+        }
+    }
+
+    fn pending() -> Self {
+        Self {
+            status: DocumentPreviewStatus::Pending,
+            pages: 0,
+            reason: None,
+        }
+    }
+
+    fn unavailable(reason: impl Into<String>) -> Self {
+        Self {
+            status: DocumentPreviewStatus::Unavailable,
+            pages: 0,
+            reason: Some(reason.into()),
+        }
+    }
+}
+
 impl PreviewService {
     /// Creates a new preview service.
     pub fn new(
@@ -55,22 +158,265 @@ impl PreviewService {
             storage,
             perm_resolver,
             cache,
+            thumbnails: ThumbnailConfig::default(),
+            documents: None,
         }
     }
 
-    /// Gets or generates a preview/thumbnail for a file.
+    /// Sets the thumbnail sizes and formats.
+    pub fn with_thumbnails(mut self, config: &ThumbnailConfig) -> Self {
+        self.thumbnails = config.clone();
+        self
+    }
+
+    /// Enables page previews of PDFs and office documents: renders are
+    /// queued on `job_repo` and their pages kept under `cache_root`.
+    pub fn with_documents(
+        mut self,
+        job_repo: Arc<JobRepository>,
+        config: &DocumentPreviewConfig,
+        cache_root: impl Into<PathBuf>,
+    ) -> Self {
+        self.documents = Some(Arc::new(DocumentPreviews {
+            job_repo,
+            renderer: DocumentRenderer::new(config),
+            cache: PreviewCache::new(cache_root),
+            config: config.clone(),
+        }));
+        self
+    }
+
+    /// Gets or generates a thumbnail of a file. `size` names one of the
+    /// configured sizes (the default size if `None`); the format is the
+    /// one `accept` prefers among those configured.
+    #[tracing::instrument(target = "otel", name = "PreviewService::get_preview", skip_all)]
     pub async fn get_preview(
         &self,
         ctx: &RequestContext,
         file_id: Uuid,
-        size: Option<u32>,
+        size: Option<&str>,
+        accept: Option<&str>,
     ) -> Result<PreviewResult, AppError> {
+        let size = size.unwrap_or(&self.thumbnails.default_size);
+        let edge = self.thumbnails.edge(size).ok_or_else(|| {
+            let names: Vec<&str> = self.thumbnails.sizes.keys().map(String::as_str).collect();
+            AppError::validation(format!(
+                "Unknown thumbnail size '{size}' (expected one of: {})",
+                names.join(", ")
+            ))
+        })?;
+        let format = negotiate_format(accept, &self.thumbnails.formats);
+
+        let file = self.viewable(ctx, file_id).await?;
+        let data = self.thumbnail(&file, edge, format).await?;
+
+        Ok(PreviewResult {
+            data,
+            content_type: format.mime_type().to_string(),
+        })
+    }
+
+    /// Describes the page previews of a file, queueing their rendering on
+    /// first request. Images have a single page; files that cannot be
+    /// previewed are reported as unavailable rather than as an error.
+    #[tracing::instrument(target = "otel", name = "PreviewService::document_preview", skip_all)]
+    pub async fn document_preview(
+        &self,
+        ctx: &RequestContext,
+        file_id: Uuid,
+    ) -> Result<DocumentPreview, AppError> {
+        let file = self.viewable(ctx, file_id).await?;
+
+        if is_image(&file) {
+            return Ok(DocumentPreview::ready(1));
+        }
+        let Some(kind) = DocumentKind::detect(file.mime_type.as_deref(), &file.name) else {
+            return Ok(DocumentPreview::unavailable(
+                "No preview is available for this file type",
+            ));
+        };
+        let Some(documents) = &self.documents else {
+            return Ok(DocumentPreview::unavailable(
+                "Document previews are not enabled",
+            ));
+        };
+        if file.size_bytes.max(0) as u64 > documents.config.max_file_size_bytes {
+            return Ok(DocumentPreview::unavailable("File is too large to preview"));
+        }
+
+        let key = content_key(&file);
+        match documents
+            .cache
+            .lookup(&key, documents.stale_after())
+            .await?
+        {
+            CachedPreview::Ready(manifest) => Ok(DocumentPreview::ready(manifest.pages)),
+            CachedPreview::Failed(_) => Ok(DocumentPreview::unavailable(
+                "The document could not be rendered",
+            )),
+            CachedPreview::Pending => Ok(DocumentPreview::pending()),
+            CachedPreview::Missing => {
+                if documents.cache.claim(&key, documents.stale_after()).await? {
+                    queue_document(documents, &file, kind).await?;
+                }
+                Ok(DocumentPreview::pending())
+            }
+        }
+    }
+
+    /// Returns page `page` (from 1) of a file's preview as PNG.
+    #[tracing::instrument(target = "otel", name = "PreviewService::document_page", skip_all)]
+    pub async fn document_page(
+        &self,
+        ctx: &RequestContext,
+        file_id: Uuid,
+        page: u32,
+    ) -> Result<PreviewResult, AppError> {
+        let file = self.viewable(ctx, file_id).await?;
+
+        if is_image(&file) {
+            if page != 1 {
+                return Err(AppError::not_found(format!("Page {page} not found")));
+            }
+            return Ok(PreviewResult {
+                data: self
+                    .thumbnail(&file, IMAGE_PAGE_SIZE, ThumbnailFormat::Png)
+                    .await?,
+                content_type: "image/png".to_string(),
+            });
+        }
+
+        let documents = self
+            .documents
+            .as_ref()
+            .ok_or_else(|| AppError::not_found("Document previews are not enabled"))?;
+        let key = content_key(&file);
+        let CachedPreview::Ready(manifest) = documents
+            .cache
+            .lookup(&key, documents.stale_after())
+            .await?
+        else {
+            return Err(AppError::not_found("Preview is not ready"));
+        };
+        if page == 0 || page > manifest.pages {
+            return Err(AppError::not_found(format!("Page {page} not found")));
+        }
+
+        let data = tokio::fs::read(documents.cache.page_path(&key, page)?)
+            .await
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::Storage, "Failed to read preview page", e)
+            })?;
+        Ok(PreviewResult {
+            data: Bytes::from(data),
+            content_type: "image/png".to_string(),
+        })
+    }
+
+    /// Renders the pages of a document into the preview cache. Returns the
+    /// number of pages. A document that cannot be rendered is recorded as
+    /// such so it is not retried. No permission check; callers are trusted
+    /// background jobs.
+    #[tracing::instrument(target = "otel", name = "PreviewService::render_document", skip_all)]
+    pub async fn render_document(&self, file_id: Uuid) -> Result<u32, AppError> {
+        let documents = self
+            .documents
+            .as_ref()
+            .ok_or_else(|| AppError::configuration("Document previews are not enabled"))?;
         let file = self
             .file_repo
             .find_by_id(file_id)
             .await
             .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .ok_or_else(|| AppError::not_found("File not found"))?;
+            .ok_or_else(|| {
+                AppError::not_found("File not found").with_code(codes::FILE_NOT_FOUND)
+            })?;
+        ensure_not_quarantined(&file)?;
+        let kind = DocumentKind::detect(file.mime_type.as_deref(), &file.name)
+            .ok_or_else(|| AppError::validation("File is not a previewable document"))?;
+
+        let key = content_key(&file);
+        if let CachedPreview::Ready(manifest) = documents
+            .cache
+            .lookup(&key, documents.stale_after())
+            .await?
+        {
+            return Ok(manifest.pages);
+        }
+
+        let scratch = documents.cache.scratch_dir().await?;
+        let rendered = self.render_into(documents, &file, kind, &scratch).await;
+        let stored = match rendered {
+            Ok(pages) => documents.cache.store(&key, &pages).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = tokio::fs::remove_dir_all(&scratch).await {
+            warn!(path = %scratch.display(), error = %e, "Failed to remove preview scratch directory");
+        }
+
+        match stored {
+            Ok(manifest) => {
+                info!(file_id = %file.id, pages = manifest.pages, "Rendered document preview");
+                Ok(manifest.pages)
+            }
+            // The tools rejected the document; rendering it again would
+            // fail the same way.
+            Err(e) if e.kind == ErrorKind::ExternalService => {
+                if let Err(mark) = documents.cache.mark_failed(&key, &e.message).await {
+                    warn!(file_id = %file.id, error = %mark, "Failed to record preview failure");
+                }
+                Err(e)
+            }
+            // Storage trouble; the next request queues it again.
+            Err(e) => {
+                if let Err(release) = documents.cache.release(&key).await {
+                    warn!(file_id = %file.id, error = %release, "Failed to release preview claim");
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Copies the document out of storage into `scratch` and renders it.
+    async fn render_into(
+        &self,
+        documents: &DocumentPreviews,
+        file: &File,
+        kind: DocumentKind,
+        scratch: &std::path::Path,
+    ) -> Result<Vec<PathBuf>, AppError> {
+        // LibreOffice picks the import filter by extension.
+        let ext = file.extension().unwrap_or_else(|| "pdf".to_string());
+        let source = scratch.join(format!("source.{ext}"));
+
+        let content = match self.storage.get(&file.storage_id).await {
+            Ok(provider) => provider.read(&file.storage_path).await,
+            Err(e) => Err(e),
+        }
+        .map_err(|e| AppError::storage(format!("Failed to read document: {}", e.message)))?;
+        let mut reader = tokio_util::io::StreamReader::new(content.map_err(std::io::Error::other));
+        let mut out = tokio::fs::File::create(&source).await.map_err(|e| {
+            AppError::with_source(ErrorKind::Storage, "Failed to stage document", e)
+        })?;
+        tokio::io::copy(&mut reader, &mut out).await.map_err(|e| {
+            AppError::with_source(ErrorKind::Storage, "Failed to stage document", e)
+        })?;
+        drop(out);
+
+        documents.renderer.render(kind, &source, scratch).await
+    }
+
+    /// Loads a file the caller may view and whose content is not
+    /// quarantined.
+    async fn viewable(&self, ctx: &RequestContext, file_id: Uuid) -> Result<File, AppError> {
+        let file = self
+            .file_repo
+            .find_by_id(file_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+            .ok_or_else(|| {
+                AppError::not_found("File not found").with_code(codes::FILE_NOT_FOUND)
+            })?;
 
         self.perm_resolver
             .require_permission(
@@ -83,29 +429,88 @@ impl PreviewService {
                 AclPermission::Viewer,
             )
             .await?;
+        ensure_not_quarantined(&file)?;
+        Ok(file)
+    }
 
-        let thumb_size = size.unwrap_or(256);
-        let cache_key = format!("preview:{}:{}", file_id, thumb_size);
-
-        // Check cache
-        if let Ok(Some(cached_b64)) = self.cache.get(&cache_key).await {
-            if let Ok(cached) =
-                base64::Engine::decode(&base64::engine::general_purpose::STANDARD, cached_b64)
-            {
-                return Ok(PreviewResult {
-                    data: Bytes::from(cached),
-                    content_type: "image/png".to_string(),
-                });
-            }
-        }
+    /// Renders and caches thumbnails of a freshly uploaded file so the
+    /// first preview is served from cache: every size in `sizes`, in each
+    /// of `formats` (the configured formats if empty). Returns the number
+    /// of variants rendered. No permission check; callers are trusted
+    /// background jobs.
+    #[tracing::instrument(target = "otel", name = "PreviewService::pregenerate", skip_all)]
+    pub async fn pregenerate(
+        &self,
+        file_id: Uuid,
+        sizes: &[u32],
+        formats: &[ThumbnailFormat],
+    ) -> Result<usize, AppError> {
+        let file = self
+            .file_repo
+            .find_by_id(file_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+            .ok_or_else(|| {
+                AppError::not_found("File not found").with_code(codes::FILE_NOT_FOUND)
+            })?;
+        ensure_not_quarantined(&file)?;
 
-        // Check if file is an image
-        let mime = file
-            .mime_type
-            .as_deref()
-            .unwrap_or("application/octet-stream");
+        let formats = if formats.is_empty() {
+            &self.thumbnails.formats
+        } else {
+            formats
+        };
+        let variants: Vec<(u32, ThumbnailFormat)> = sizes
+            .iter()
+            .flat_map(|&edge| formats.iter().map(move |&format| (edge, format)))
+            .collect();
+        self.thumbnails_of(&file, &variants).await?;
+        Ok(variants.len())
+    }
+
+    /// Returns the cached thumbnail of `file`, rendering and caching it on
+    /// a miss.
+    async fn thumbnail(
+        &self,
+        file: &File,
+        edge: u32,
+        format: ThumbnailFormat,
+    ) -> Result<Bytes, AppError> {
+        let mut rendered = self.thumbnails_of(file, &[(edge, format)]).await?;
+        Ok(rendered.remove(0))
+    }
+
+    /// Returns thumbnail variants of `file`, in order. Cached variants are
+    /// served from the cache; the rest are rendered from one read of the
+    /// original and cached, each under its own size and format.
+    async fn thumbnails_of(
+        &self,
+        file: &File,
+        variants: &[(u32, ThumbnailFormat)],
+    ) -> Result<Vec<Bytes>, AppError> {
+        let mut found: Vec<Option<Bytes>> = Vec::with_capacity(variants.len());
+        for &(edge, format) in variants {
+            let cached = match self.cache.get(&thumbnail_key(file, edge, format)).await {
+                Ok(Some(b64)) => {
+                    base64::Engine::decode(&base64::engine::general_purpose::STANDARD, b64)
+                        .ok()
+                        .map(Bytes::from)
+                }
+                _ => None,
+            };
+            found.push(cached);
+        }
+        let missing: Vec<(u32, ThumbnailFormat)> = variants
+            .iter()
+            .zip(&found)
+            .filter(|(_, cached)| cached.is_none())
+            .map(|(&variant, _)| variant)
+            .collect();
+        if missing.is_empty() {
+            return Ok(found.into_iter().flatten().collect());
+        }
 
-        if !mime.starts_with("image/") {
+        if !is_image(file) {
             return Err(AppError::validation(
                 "Preview is only available for image files",
             ));
@@ -118,40 +523,80 @@ impl PreviewService {
             .await
             .map_err(|e| AppError::internal(format!("Storage read failed: {e}")))?;
 
-        // Generate thumbnail (basic resize)
-        let thumbnail = self.generate_thumbnail(&original, thumb_size)?;
-
-        // Cache thumbnail for 1 hour (encode as base64)
-        let thumbnail_b64 =
-            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &thumbnail);
-        let _ = self
-            .cache
-            .set(
-                &cache_key,
-                &thumbnail_b64,
-                std::time::Duration::from_secs(3600),
-            )
-            .await;
+        // Decoding and resizing are CPU-bound; keep them off the runtime.
+        let quality = self.thumbnails.quality;
+        let to_render = missing.clone();
+        let rendered =
+            tokio::task::spawn_blocking(move || render_thumbnails(&original, &to_render, quality))
+                .await
+                .map_err(|e| AppError::internal(format!("Thumbnail task panicked: {e}")))??;
 
-        Ok(PreviewResult {
-            data: Bytes::from(thumbnail),
-            content_type: "image/png".to_string(),
-        })
+        // Cache rendered thumbnails, encoded as base64
+        let mut rendered = missing.into_iter().zip(rendered);
+        let mut result = Vec::with_capacity(variants.len());
+        for cached in found {
+            if let Some(data) = cached {
+                result.push(data);
+                continue;
+            }
+            let Some(((edge, format), data)) = rendered.next() else {
+                break;
+            };
+            let b64 = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &data);
+            let _ = self
+                .cache
+                .put(&thumbnail_key(file, edge, format), &b64)
+                .await;
+            result.push(Bytes::from(data));
+        }
+        Ok(result)
     }
+}
 
-    /// Generates a thumbnail from raw image bytes.
-    fn generate_thumbnail(&self, data: &[u8], max_size: u32) -> Result<Vec<u8>, AppError> {
-        let img = image::load_from_memory(data)
-            .map_err(|e| AppError::internal(format!("Failed to decode image: {e}")))?;
-
-        let thumb = img.thumbnail(max_size, max_size);
+/// Cache key of a thumbnail variant.
+fn thumbnail_key(file: &File, edge: u32, format: ThumbnailFormat) -> CacheKey {
+    keys::thumbnail(file.id, file.current_version, edge, format)
+}
 
-        let mut buf = Vec::new();
-        let mut cursor = std::io::Cursor::new(&mut buf);
-        thumb
-            .write_to(&mut cursor, image::ImageFormat::Png)
-            .map_err(|e| AppError::internal(format!("Failed to encode thumbnail: {e}")))?;
+/// Whether the file is previewed as an image.
+fn is_image(file: &File) -> bool {
+    file.mime_type
+        .as_deref()
+        .is_some_and(|mime| mime.starts_with("image/"))
+}
 
-        Ok(buf)
+/// Preview cache key: the content hash, so files and versions with the
+/// same content share one render. Files without a recorded hash fall back
+/// to their id and version.
+fn content_key(file: &File) -> String {
+    match &file.checksum_sha256 {
+        Some(checksum) => checksum.to_ascii_lowercase(),
+        None => format!("{}-v{}", file.id, file.current_version),
     }
 }
+
+/// Queues the rendering of a document.
+async fn queue_document(
+    documents: &DocumentPreviews,
+    file: &File,
+    kind: DocumentKind,
+) -> Result<(), AppError> {
+    let payload = JobPayload::DocumentPreview { file_id: file.id };
+    let job = CreateJob {
+        job_type: DOCUMENT_PREVIEW_JOB_TYPE.to_string(),
+        queue: "default".to_string(),
+        priority: JobPriority::Normal,
+        payload: serde_json::to_value(&payload)
+            .map_err(|e| AppError::internal(format!("Invalid document preview job: {e}")))?,
+        max_attempts: 1,
+        scheduled_at: None,
+        created_by: None,
+    };
+    documents
+        .job_repo
+        .create(&job)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to queue document preview: {e}")))?;
+    tracing::debug!(file_id = %file.id, kind = ?kind, "Queued document preview");
+    Ok(())
+}
@@ -0,0 +1,103 @@
+//! Concurrency limit on post-upload processing.
+//!
+//! Assembling a chunked upload and running `AfterUpload` hooks are CPU and
+//! memory heavy; during bulk uploads only a fixed number run at a time and
+//! the rest wait for a slot. Permits and counters are released by guards,
+//! so a task that panics gives its slot back.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tokio::sync::Semaphore;
+use tracing::warn;
+
+/// Point-in-time counts of post-upload processing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ProcessingStats {
+    /// Uploads being processed.
+    pub in_flight: usize,
+    /// Uploads waiting for a slot.
+    pub queued: usize,
+    /// Uploads processed at the same time at most.
+    pub limit: usize,
+}
+
+/// Runs post-upload work at most `limit` at a time.
+#[derive(Debug, Clone)]
+pub struct UploadProcessor {
+    /// One permit per processing slot.
+    permits: Arc<Semaphore>,
+    /// Uploads being processed.
+    in_flight: Arc<AtomicUsize>,
+    /// Uploads waiting for a permit.
+    queued: Arc<AtomicUsize>,
+    /// Configured limit.
+    limit: usize,
+}
+
+impl UploadProcessor {
+    /// Creates a processor running at most `limit` (at least 1) tasks.
+    pub fn new(limit: usize) -> Self {
+        let limit = limit.max(1);
+        Self {
+            permits: Arc::new(Semaphore::new(limit)),
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            queued: Arc::new(AtomicUsize::new(0)),
+            limit,
+        }
+    }
+
+    /// Current counts.
+    pub fn stats(&self) -> ProcessingStats {
+        ProcessingStats {
+            in_flight: self.in_flight.load(Ordering::Relaxed),
+            queued: self.queued.load(Ordering::Relaxed),
+            limit: self.limit,
+        }
+    }
+
+    /// Waits for a slot, then runs `work` to completion.
+    pub async fn run<F: Future>(&self, work: F) -> F::Output {
+        let permit = {
+            let _queued = CountGuard::new(&self.queued);
+            Arc::clone(&self.permits).acquire_owned().await
+        };
+        if permit.is_err() {
+            // The semaphore is never closed; run unthrottled rather than fail.
+            warn!("Upload processing semaphore closed");
+        }
+        let _in_flight = CountGuard::new(&self.in_flight);
+        work.await
+    }
+
+    /// Runs `work` in the background once a slot is free. Returns
+    /// immediately; the upload does not wait for its processing.
+    pub fn spawn<F>(&self, work: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let processor = self.clone();
+        tokio::spawn(async move { processor.run(work).await });
+    }
+}
+
+/// Increments a counter for as long as it lives.
+struct CountGuard {
+    counter: Arc<AtomicUsize>,
+}
+
+impl CountGuard {
+    fn new(counter: &Arc<AtomicUsize>) -> Self {
+        counter.fetch_add(1, Ordering::Relaxed);
+        Self {
+            counter: Arc::clone(counter),
+        }
+    }
+}
+
+impl Drop for CountGuard {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -2,20 +2,41 @@
 
 use std::sync::Arc;
 
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
 use uuid::Uuid;
 
+use filehub_auth::acl::EffectivePermissionResolver;
 use filehub_core::error::AppError;
+use filehub_core::types::filter::FilterNode;
 use filehub_core::types::pagination::{PageRequest, PageResponse};
-use filehub_database::repositories::file::FileRepository;
-use filehub_entity::file::File;
+use filehub_database::repositories::file::{FileRepository, FileSearchCriteria, file_filter_kind};
+use filehub_database::repositories::saved_search::SavedSearchRepository;
+use filehub_entity::file::{File, SavedSearch};
+use filehub_entity::permission::{AclPermission, ResourceType};
 
 use crate::context::RequestContext;
 
+use super::export::FileExportFormat;
+
+/// Upper bound on ranked candidates examined for a non-admin search.
+///
+/// Visibility is resolved per file after ranking, so non-admin result
+/// sets (and their totals) cover at most this many top-ranked matches.
+pub const MAX_SEARCH_CANDIDATES: i64 = 1000;
+
+/// Maximum length of a saved search name.
+pub const MAX_SAVED_SEARCH_NAME_LEN: usize = 255;
+
 /// File search service with full-text and filter support.
 #[derive(Debug, Clone)]
 pub struct SearchService {
     /// File repository.
     file_repo: Arc<FileRepository>,
+    /// Saved search repository.
+    saved_repo: Arc<SavedSearchRepository>,
+    /// Permission resolver.
+    perm_resolver: Arc<EffectivePermissionResolver>,
 }
 
 /// Search request parameters.
@@ -35,30 +56,316 @@ pub struct SearchRequest {
     pub min_size: Option<i64>,
     /// Maximum file size in bytes.
     pub max_size: Option<i64>,
+    /// Structured filter tree.
+    #[serde(default)]
+    pub filter: Option<FilterNode>,
+}
+
+impl SearchRequest {
+    fn criteria(&self) -> FileSearchCriteria {
+        FileSearchCriteria {
+            text: Some(self.query.clone()),
+            folder_id: self.folder_id,
+            storage_id: self.storage_id,
+            mime_prefix: self.mime_type.clone(),
+            owner_id: self.owner_id,
+            min_size: self.min_size,
+            max_size: self.max_size,
+            filter: self.filter.clone(),
+            tag_owner_id: None,
+            tenant: None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), AppError> {
+        if self.query.trim().is_empty() && self.folder_id.is_none() && self.filter.is_none() {
+            return Err(AppError::validation(
+                "Search query, folder or filter is required",
+            ));
+        }
+        if let Some(filter) = &self.filter {
+            filter.validate(file_filter_kind)?;
+        }
+        Ok(())
+    }
+}
+
+/// Filters of a file inventory export; all optional.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct FileExportRequest {
+    /// Full-text query.
+    #[serde(default)]
+    pub query: Option<String>,
+    /// Filter by folder ID.
+    #[serde(default)]
+    pub folder_id: Option<Uuid>,
+    /// Filter by storage ID.
+    #[serde(default)]
+    pub storage_id: Option<Uuid>,
+    /// Filter by MIME type prefix (e.g., "image/").
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    /// Filter by owner.
+    #[serde(default)]
+    pub owner_id: Option<Uuid>,
+    /// Minimum file size in bytes.
+    #[serde(default)]
+    pub min_size: Option<i64>,
+    /// Maximum file size in bytes.
+    #[serde(default)]
+    pub max_size: Option<i64>,
+    /// Structured filter tree.
+    #[serde(default)]
+    pub filter: Option<FilterNode>,
+}
+
+impl FileExportRequest {
+    /// The repository criteria for the export, after validating the filter.
+    pub fn criteria(&self) -> Result<FileSearchCriteria, AppError> {
+        if let Some(filter) = &self.filter {
+            filter.validate(file_filter_kind)?;
+        }
+        Ok(FileSearchCriteria {
+            text: self.query.clone(),
+            folder_id: self.folder_id,
+            storage_id: self.storage_id,
+            mime_prefix: self.mime_type.clone(),
+            owner_id: self.owner_id,
+            min_size: self.min_size,
+            max_size: self.max_size,
+            filter: self.filter.clone(),
+            tag_owner_id: None,
+            tenant: None,
+        })
+    }
 }
 
 impl SearchService {
     /// Creates a new search service.
-    pub fn new(file_repo: Arc<FileRepository>) -> Self {
-        Self { file_repo }
+    pub fn new(
+        file_repo: Arc<FileRepository>,
+        saved_repo: Arc<SavedSearchRepository>,
+        perm_resolver: Arc<EffectivePermissionResolver>,
+    ) -> Self {
+        Self {
+            file_repo,
+            saved_repo,
+            perm_resolver,
+        }
     }
 
-    /// Searches files using full-text search and optional filters.
+    /// Searches files, ranked by relevance, returning only files the caller
+    /// can view.
+    #[tracing::instrument(target = "otel", name = "SearchService::search", skip_all)]
     pub async fn search(
         &self,
-        _ctx: &RequestContext,
+        ctx: &RequestContext,
         req: SearchRequest,
         page: PageRequest,
     ) -> Result<PageResponse<File>, AppError> {
-        if req.query.trim().is_empty() && req.folder_id.is_none() {
-            return Err(AppError::validation(
-                "Search query or folder filter is required",
-            ));
+        req.validate()?;
+
+        let tenant = ctx.tenant_scope();
+        let criteria = FileSearchCriteria {
+            tag_owner_id: Some(ctx.user_id),
+            tenant: Some(tenant),
+            ..req.criteria()
+        };
+
+        if ctx.is_admin() {
+            let total = self.file_repo.count_search(&criteria).await?;
+            let files = self
+                .file_repo
+                .search(&criteria, page.limit() as i64, page.offset() as i64)
+                .await?;
+            let files = tenant.retain(files);
+            return Ok(PageResponse::new(files, page.page, page.page_size, total));
         }
 
-        self.file_repo
-            .search(&req.query, req.storage_id, &page)
+        let candidates = self
+            .file_repo
+            .search(&criteria, MAX_SEARCH_CANDIDATES, 0)
+            .await?;
+        let visible = self.filter_visible(ctx, tenant.retain(candidates)).await?;
+
+        let total = visible.len() as u64;
+        let items = visible
+            .into_iter()
+            .skip(page.offset() as usize)
+            .take(page.limit() as usize)
+            .collect();
+
+        Ok(PageResponse::new(items, page.page, page.page_size, total))
+    }
+
+    /// Streams every file matching `req` that the caller can view, in id
+    /// order, encoded as `format`. Files are read, checked and encoded as
+    /// the stream is consumed, so the listing is never held in memory.
+    #[tracing::instrument(target = "otel", name = "SearchService::export", skip_all)]
+    pub fn export(
+        &self,
+        ctx: &RequestContext,
+        req: FileExportRequest,
+        format: FileExportFormat,
+    ) -> Result<impl Stream<Item = Result<Bytes, AppError>> + Send + 'static, AppError> {
+        let criteria = FileSearchCriteria {
+            tag_owner_id: Some(ctx.user_id),
+            tenant: Some(ctx.tenant_scope()),
+            ..req.criteria()?
+        };
+
+        let resolver = Arc::clone(&self.perm_resolver);
+        let (user_id, role, admin) = (ctx.user_id, ctx.role, ctx.is_admin());
+        let files = self.file_repo.export(criteria).filter_map(move |row| {
+            let resolver = Arc::clone(&resolver);
+            async move {
+                let file = match row {
+                    Ok(file) => file,
+                    Err(e) => return Some(Err(e)),
+                };
+                if admin {
+                    return Some(Ok(file));
+                }
+                let permission = resolver
+                    .resolve(
+                        user_id,
+                        &role,
+                        ResourceType::File,
+                        file.id,
+                        file.owner_id,
+                        Some(file.folder_id),
+                        AclPermission::Viewer,
+                    )
+                    .await;
+                match permission {
+                    Ok(p) if p.granted => Some(Ok(file)),
+                    Ok(_) => None,
+                    Err(e) => Some(Err(e)),
+                }
+            }
+        });
+
+        let header = format.header();
+        let header = futures::stream::iter((!header.is_empty()).then(|| Ok(Bytes::from(header))));
+        let rows = files.map(move |row| format.encode(&row?).map(Bytes::from));
+        Ok(header.chain(rows))
+    }
+
+    /// Saves a named search for the caller.
+    #[tracing::instrument(target = "otel", name = "SearchService::save_search", skip_all)]
+    pub async fn save_search(
+        &self,
+        ctx: &RequestContext,
+        name: &str,
+        query: &str,
+        filter: Option<FilterNode>,
+    ) -> Result<SavedSearch, AppError> {
+        let name = name.trim();
+        if name.is_empty() || name.len() > MAX_SAVED_SEARCH_NAME_LEN {
+            return Err(AppError::validation(format!(
+                "Saved search name must be 1-{MAX_SAVED_SEARCH_NAME_LEN} characters"
+            )));
+        }
+
+        let req = SearchRequest {
+            query: query.to_string(),
+            folder_id: None,
+            storage_id: None,
+            mime_type: None,
+            owner_id: None,
+            min_size: None,
+            max_size: None,
+            filter,
+        };
+        req.validate()?;
+
+        let filter = req
+            .filter
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|e| AppError::internal(format!("Failed to serialize filter: {e}")))?;
+
+        self.saved_repo
+            .create(ctx.user_id, name, &req.query, filter.as_ref())
             .await
-            .map_err(|e| AppError::internal(format!("Search failed: {e}")))
+    }
+
+    /// Lists the caller's saved searches.
+    #[tracing::instrument(target = "otel", name = "SearchService::list_saved", skip_all)]
+    pub async fn list_saved(&self, ctx: &RequestContext) -> Result<Vec<SavedSearch>, AppError> {
+        self.saved_repo.find_by_owner(ctx.user_id).await
+    }
+
+    /// Deletes one of the caller's saved searches.
+    #[tracing::instrument(target = "otel", name = "SearchService::delete_saved", skip_all)]
+    pub async fn delete_saved(&self, ctx: &RequestContext, id: Uuid) -> Result<(), AppError> {
+        self.find_saved(ctx, id).await?;
+        self.saved_repo.delete(id).await?;
+        Ok(())
+    }
+
+    /// Re-runs one of the caller's saved searches.
+    #[tracing::instrument(target = "otel", name = "SearchService::run_saved", skip_all)]
+    pub async fn run_saved(
+        &self,
+        ctx: &RequestContext,
+        id: Uuid,
+        page: PageRequest,
+    ) -> Result<PageResponse<File>, AppError> {
+        let saved = self.find_saved(ctx, id).await?;
+        let filter = saved
+            .filter
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| AppError::validation(format!("Saved filter is no longer valid: {e}")))?;
+
+        let req = SearchRequest {
+            query: saved.query,
+            folder_id: None,
+            storage_id: None,
+            mime_type: None,
+            owner_id: None,
+            min_size: None,
+            max_size: None,
+            filter,
+        };
+        self.search(ctx, req, page).await
+    }
+
+    /// Loads a saved search owned by the caller.
+    async fn find_saved(&self, ctx: &RequestContext, id: Uuid) -> Result<SavedSearch, AppError> {
+        self.saved_repo
+            .find_by_id(id)
+            .await?
+            .filter(|s| s.owner_id == ctx.user_id)
+            .ok_or_else(|| AppError::not_found("Saved search not found"))
+    }
+
+    /// Keeps files the caller has at least viewer access to, preserving rank order.
+    async fn filter_visible(
+        &self,
+        ctx: &RequestContext,
+        files: Vec<File>,
+    ) -> Result<Vec<File>, AppError> {
+        let mut visible = Vec::with_capacity(files.len());
+        for file in files {
+            let permission = self
+                .perm_resolver
+                .resolve(
+                    ctx.user_id,
+                    &ctx.role,
+                    ResourceType::File,
+                    file.id,
+                    file.owner_id,
+                    Some(file.folder_id),
+                    AclPermission::Viewer,
+                )
+                .await?;
+            if permission.granted {
+                visible.push(file);
+            }
+        }
+        Ok(visible)
     }
 }
@@ -2,29 +2,67 @@
 
 use std::sync::Arc;
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use tracing::info;
 use uuid::Uuid;
 
 use filehub_auth::acl::EffectivePermissionResolver;
-use filehub_core::error::AppError;
+use filehub_core::config::NameCollision;
+use filehub_core::error::{AppError, codes};
 use filehub_core::types::pagination::{PageRequest, PageResponse};
 use filehub_database::repositories::file::FileRepository;
 use filehub_database::repositories::folder::FolderRepository;
+use filehub_database::repositories::tag::TagRepository;
 use filehub_entity::file::{CreateFile, File};
+use filehub_entity::folder::FolderSort;
 use filehub_entity::permission::{AclPermission, ResourceType};
+use filehub_entity::tag::Tag;
+use filehub_entity::user::role::UserRole;
+use filehub_plugin::hooks::definitions::{HookPayload, HookPoint};
+use filehub_plugin::manager::PluginManager;
 
 use crate::context::RequestContext;
+use crate::folder::{FolderPreferenceStore, FolderTreeCache, RetentionService};
+use crate::session::SessionAudit;
+
+/// Maximum number of files accepted by a single bulk operation.
+pub const MAX_BULK_FILES: usize = 500;
+
+/// Upper bound on tagged files examined when listing a tag's files.
+pub const MAX_TAGGED_FILES: i64 = 1000;
 
 /// Handles core file CRUD with ACL permission checks.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct FileService {
     /// File repository.
     file_repo: Arc<FileRepository>,
     /// Folder repository (for parent lookups).
     folder_repo: Arc<FolderRepository>,
+    /// Tag repository.
+    tag_repo: Arc<TagRepository>,
     /// Permission resolver.
     perm_resolver: Arc<EffectivePermissionResolver>,
+    /// Plugin manager for firing hooks.
+    plugin_manager: Arc<PluginManager>,
+    /// Audit log service.
+    audit: Arc<SessionAudit>,
+    /// Cached folder trees, whose file counts change with files.
+    tree_cache: Option<FolderTreeCache>,
+    /// Strategy of copies that do not choose one.
+    name_collision: NameCollision,
+    /// Whether content replaced by an overwriting copy is kept as a version.
+    keep_versions: bool,
+    /// Refuses deleting retained files; `None` deletes freely.
+    retention: Option<RetentionService>,
+    /// Users' saved folder views; `None` lists by name unless asked
+    /// otherwise.
+    folder_preferences: Option<FolderPreferenceStore>,
+}
+
+impl std::fmt::Debug for FileService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileService").finish()
+    }
 }
 
 /// Data for updating a file's metadata.
@@ -50,6 +88,67 @@ pub struct CopyFileRequest {
     pub target_folder_id: Uuid,
     /// New file name (optional — defaults to original).
     pub new_name: Option<String>,
+    /// What to do if the name is taken in the target folder; `None` uses
+    /// the configured default.
+    #[serde(default)]
+    pub on_conflict: Option<NameCollision>,
+}
+
+/// Action applied to every file of a bulk operation.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum BulkAction {
+    /// Delete the files.
+    Delete,
+    /// Move the files into a folder.
+    Move {
+        /// Target folder ID.
+        target_folder_id: Uuid,
+    },
+    /// Copy the files into a folder, keeping their names.
+    Copy {
+        /// Target folder ID.
+        target_folder_id: Uuid,
+    },
+}
+
+impl BulkAction {
+    /// Audit action name for the aggregated entry.
+    fn audit_action(&self) -> &'static str {
+        match self {
+            Self::Delete => "file.bulk_delete",
+            Self::Move { .. } => "file.bulk_move",
+            Self::Copy { .. } => "file.bulk_copy",
+        }
+    }
+}
+
+/// Outcome for a single file in a bulk operation.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BulkItemResult {
+    /// The file the action was applied to.
+    pub file_id: Uuid,
+    /// Whether the action succeeded for this file.
+    pub success: bool,
+    /// The resulting file (moved file or new copy); absent for deletes.
+    pub file: Option<File>,
+    /// Error code when the action failed.
+    pub error_code: Option<String>,
+    /// Error message when the action failed.
+    pub error: Option<String>,
+}
+
+/// Aggregated outcome of a bulk operation.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct BulkOperationResult {
+    /// Identifier shared by the audit entry and per-file hooks.
+    pub operation_id: Uuid,
+    /// Number of files the action succeeded for.
+    pub succeeded: usize,
+    /// Number of files the action failed for.
+    pub failed: usize,
+    /// Per-file results, in request order.
+    pub items: Vec<BulkItemResult>,
 }
 
 impl FileService {
@@ -57,28 +156,82 @@ impl FileService {
     pub fn new(
         file_repo: Arc<FileRepository>,
         folder_repo: Arc<FolderRepository>,
+        tag_repo: Arc<TagRepository>,
         perm_resolver: Arc<EffectivePermissionResolver>,
+        plugin_manager: Arc<PluginManager>,
+        audit: Arc<SessionAudit>,
     ) -> Self {
         Self {
             file_repo,
             folder_repo,
+            tag_repo,
             perm_resolver,
+            plugin_manager,
+            audit,
+            tree_cache: None,
+            name_collision: NameCollision::Reject,
+            keep_versions: true,
+            retention: None,
+            folder_preferences: None,
         }
     }
 
-    /// Lists files in a folder with pagination, enforcing viewer permission.
+    /// Sets what a copy onto a taken name does unless the request says,
+    /// and whether content it overwrites is kept as a version.
+    pub fn with_name_collision(mut self, default: NameCollision, keep_versions: bool) -> Self {
+        self.name_collision = default;
+        self.keep_versions = keep_versions;
+        self
+    }
+
+    /// Drops cached folder trees of a storage whenever its files change.
+    pub fn with_tree_cache(mut self, tree_cache: FolderTreeCache) -> Self {
+        self.tree_cache = Some(tree_cache);
+        self
+    }
+
+    /// Refuses deleting, or overwriting without keeping a version, files
+    /// under a retention policy or legal hold.
+    pub fn with_retention(mut self, retention: RetentionService) -> Self {
+        self.retention = Some(retention);
+        self
+    }
+
+    /// Lists files in the caller's saved order for their folder.
+    pub fn with_folder_preferences(mut self, preferences: FolderPreferenceStore) -> Self {
+        self.folder_preferences = Some(preferences);
+        self
+    }
+
+    /// Drops the cached folder trees of the storage of a folder. A file's
+    /// own storage can differ from its folder's, so the folder is looked up.
+    async fn invalidate_trees(&self, folder_id: Uuid) {
+        let Some(cache) = &self.tree_cache else {
+            return;
+        };
+        if let Ok(Some(folder)) = self.folder_repo.find_by_id(folder_id).await {
+            cache.invalidate(folder.storage_id).await;
+        }
+    }
+
+    /// Lists files in a folder with pagination, enforcing viewer permission,
+    /// in the `sort` order or else the caller's saved one.
+    #[tracing::instrument(target = "otel", name = "FileService::list_files", skip_all)]
     pub async fn list_files(
         &self,
         ctx: &RequestContext,
         folder_id: Uuid,
         page: PageRequest,
+        sort: Option<FolderSort>,
     ) -> Result<PageResponse<File>, AppError> {
         let folder = self
             .folder_repo
             .find_by_id(folder_id)
             .await
             .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .ok_or_else(|| AppError::not_found("Folder not found"))?;
+            .ok_or_else(|| {
+                AppError::not_found("Folder not found").with_code(codes::FOLDER_NOT_FOUND)
+            })?;
 
         self.perm_resolver
             .require_permission(
@@ -92,20 +245,27 @@ impl FileService {
             )
             .await?;
 
+        let sort = match &self.folder_preferences {
+            Some(preferences) => preferences.sort_for(ctx.user_id, folder_id, sort).await,
+            None => sort.unwrap_or_default(),
+        };
         self.file_repo
-            .find_by_folder(folder_id, &page)
+            .find_by_folder(folder_id, &page, &sort)
             .await
             .map_err(|e| AppError::internal(format!("Failed to list files: {e}")))
     }
 
     /// Gets a single file's details, enforcing viewer permission.
+    #[tracing::instrument(target = "otel", name = "FileService::get_file", skip_all)]
     pub async fn get_file(&self, ctx: &RequestContext, file_id: Uuid) -> Result<File, AppError> {
         let file = self
             .file_repo
             .find_by_id(file_id)
             .await
             .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .ok_or_else(|| AppError::not_found("File not found"))?;
+            .ok_or_else(|| {
+                AppError::not_found("File not found").with_code(codes::FILE_NOT_FOUND)
+            })?;
 
         self.perm_resolver
             .require_permission(
@@ -122,7 +282,26 @@ impl FileService {
         Ok(file)
     }
 
+    /// Whether another user may view a file, e.g. to show them as a
+    /// co-viewer. Resolution errors count as no.
+    #[tracing::instrument(target = "otel", name = "FileService::can_view", skip_all)]
+    pub async fn can_view(&self, file: &File, user_id: Uuid, role: &UserRole) -> bool {
+        self.perm_resolver
+            .resolve(
+                user_id,
+                role,
+                ResourceType::File,
+                file.id,
+                file.owner_id,
+                Some(file.folder_id),
+                AclPermission::Viewer,
+            )
+            .await
+            .is_ok_and(|p| p.granted)
+    }
+
     /// Updates a file's metadata, enforcing editor permission.
+    #[tracing::instrument(target = "otel", name = "FileService::update_file", skip_all)]
     pub async fn update_file(
         &self,
         ctx: &RequestContext,
@@ -168,6 +347,7 @@ impl FileService {
     }
 
     /// Moves a file to a different folder, enforcing editor permission on both source and target.
+    #[tracing::instrument(target = "otel", name = "FileService::move_file", skip_all)]
     pub async fn move_file(
         &self,
         ctx: &RequestContext,
@@ -222,6 +402,8 @@ impl FileService {
             .update(&file)
             .await
             .map_err(|e| AppError::internal(format!("Failed to move file: {e}")))?;
+        self.invalidate_trees(old_folder).await;
+        self.invalidate_trees(req.target_folder_id).await;
 
         info!(
             user_id = %ctx.user_id,
@@ -235,6 +417,7 @@ impl FileService {
     }
 
     /// Copies a file to another folder, creating a new file record.
+    #[tracing::instrument(target = "otel", name = "FileService::copy_file", skip_all)]
     pub async fn copy_file(
         &self,
         ctx: &RequestContext,
@@ -266,19 +449,41 @@ impl FileService {
             .await?;
 
         let new_name = req.new_name.unwrap_or_else(|| source.name.clone());
+        let strategy = req.on_conflict.unwrap_or(self.name_collision);
 
-        // Check for name conflict
-        if self
+        let existing = self
             .file_repo
             .find_by_folder_and_name(req.target_folder_id, &new_name)
             .await
-            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .is_some()
-        {
-            return Err(AppError::conflict(format!(
-                "A file named '{new_name}' already exists in the target folder"
-            )));
-        }
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?;
+        let replace = match (strategy, existing) {
+            (_, None) | (NameCollision::Rename, Some(_)) => None,
+            (NameCollision::Reject, Some(_)) => {
+                return Err(AppError::conflict(format!(
+                    "A file named '{new_name}' already exists in the target folder"
+                )));
+            }
+            (NameCollision::Overwrite, Some(existing)) => {
+                if existing.id == file_id {
+                    return Err(AppError::validation("A file cannot be copied onto itself"));
+                }
+                let existing = self
+                    .get_file_with_permission(ctx, existing.id, AclPermission::Editor)
+                    .await?;
+                if existing.is_locked.unwrap_or(false)
+                    && existing.locked_by != Some(ctx.user_id)
+                    && !ctx.is_admin()
+                {
+                    return Err(AppError::conflict("File is locked by another user"));
+                }
+                if let Some(retention) = &self.retention
+                    && !self.keep_versions
+                {
+                    retention.check_file_delete(ctx, &existing).await?;
+                }
+                Some(existing)
+            }
+        };
 
         let new_file = CreateFile {
             folder_id: req.target_folder_id,
@@ -286,18 +491,44 @@ impl FileService {
             name: new_name,
             storage_path: source.storage_path.clone(),
             mime_type: source.mime_type.clone(),
+            declared_mime_type: source.declared_mime_type.clone(),
             size_bytes: source.size_bytes,
             checksum_sha256: source.checksum_sha256.clone(),
             metadata: source.metadata.clone(),
             owner_id: ctx.user_id,
         };
 
-        let new_file = self.file_repo.create(&new_file).await?;
+        let replaced = match &replace {
+            Some(existing) => {
+                self.file_repo
+                    .replace_content(
+                        existing.id,
+                        &new_file,
+                        self.keep_versions,
+                        ctx.user_id,
+                        None,
+                    )
+                    .await?
+            }
+            None => None,
+        };
+        let new_file = match replaced {
+            Some(file) => file,
+            None if strategy == NameCollision::Rename => {
+                self.file_repo
+                    .create_with_free_name(&new_file, None)
+                    .await?
+            }
+            None => self.file_repo.create(&new_file, None).await?,
+        };
+        self.invalidate_trees(new_file.folder_id).await;
 
         info!(
             user_id = %ctx.user_id,
             source_id = %file_id,
             new_id = %new_file.id,
+            name = %new_file.name,
+            on_conflict = %strategy,
             "File copied"
         );
 
@@ -305,6 +536,7 @@ impl FileService {
     }
 
     /// Deletes a file, enforcing owner or editor permission.
+    #[tracing::instrument(target = "otel", name = "FileService::delete_file", skip_all)]
     pub async fn delete_file(&self, ctx: &RequestContext, file_id: Uuid) -> Result<(), AppError> {
         let file = self
             .get_file_with_permission(ctx, file_id, AclPermission::Editor)
@@ -314,18 +546,67 @@ impl FileService {
         {
             return Err(AppError::conflict("File is locked by another user"));
         }
+        if let Some(retention) = &self.retention {
+            retention.check_file_delete(ctx, &file).await?;
+        }
 
         self.file_repo
             .delete(file_id)
             .await
             .map_err(|e| AppError::internal(format!("Failed to delete file: {e}")))?;
+        self.invalidate_trees(file.folder_id).await;
 
         info!(user_id = %ctx.user_id, file_id = %file_id, "File deleted");
 
         Ok(())
     }
 
+    /// Sets when a file is deleted automatically, or clears it with
+    /// `None`. The expiry applies to the file with all its versions; a
+    /// retention policy on its folder postpones the delete.
+    #[tracing::instrument(target = "otel", name = "FileService::set_expiry", skip_all)]
+    pub async fn set_expiry(
+        &self,
+        ctx: &RequestContext,
+        file_id: Uuid,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<File, AppError> {
+        let file = self
+            .get_file_with_permission(ctx, file_id, AclPermission::Editor)
+            .await?;
+        if expires_at.is_some_and(|at| at <= Utc::now()) {
+            return Err(AppError::validation("Expiry must be in the future"));
+        }
+
+        let updated = self
+            .file_repo
+            .set_expiry(file_id, expires_at)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found("File not found").with_code(codes::FILE_NOT_FOUND)
+            })?;
+        self.audit
+            .log_update(
+                ctx,
+                "file.expiry_updated",
+                "file",
+                file_id,
+                &serde_json::json!({ "expires_at": file.expires_at }),
+                &serde_json::json!({ "expires_at": updated.expires_at }),
+            )
+            .await;
+
+        info!(
+            user_id = %ctx.user_id,
+            file_id = %file_id,
+            expires_at = ?updated.expires_at,
+            "File expiry set"
+        );
+        Ok(updated)
+    }
+
     /// Locks a file for exclusive editing.
+    #[tracing::instrument(target = "otel", name = "FileService::lock_file", skip_all)]
     pub async fn lock_file(&self, ctx: &RequestContext, file_id: Uuid) -> Result<File, AppError> {
         let mut file = self
             .get_file_with_permission(ctx, file_id, AclPermission::Editor)
@@ -354,6 +635,7 @@ impl FileService {
     }
 
     /// Unlocks a file (owner of lock or admin).
+    #[tracing::instrument(target = "otel", name = "FileService::unlock_file", skip_all)]
     pub async fn unlock_file(&self, ctx: &RequestContext, file_id: Uuid) -> Result<File, AppError> {
         let mut file = self.get_file(ctx, file_id).await?;
 
@@ -382,6 +664,304 @@ impl FileService {
         Ok(file)
     }
 
+    /// Applies one action to many files.
+    ///
+    /// Each file is processed independently with its own permission check,
+    /// so a failure is reported in that file's result instead of aborting
+    /// the batch. Duplicate ids are processed once. A single aggregated
+    /// audit entry is written, and the per-file hook fires for every file
+    /// the action succeeded for.
+    #[tracing::instrument(target = "otel", name = "FileService::bulk_operation", skip_all)]
+    pub async fn bulk_operation(
+        &self,
+        ctx: &RequestContext,
+        file_ids: Vec<Uuid>,
+        action: BulkAction,
+    ) -> Result<BulkOperationResult, AppError> {
+        let mut seen = std::collections::HashSet::new();
+        let file_ids: Vec<Uuid> = file_ids.into_iter().filter(|id| seen.insert(*id)).collect();
+
+        if file_ids.is_empty() {
+            return Err(AppError::validation("No files given"));
+        }
+        if file_ids.len() > MAX_BULK_FILES {
+            return Err(AppError::validation(format!(
+                "A bulk operation is limited to {MAX_BULK_FILES} files"
+            )));
+        }
+
+        let operation_id = Uuid::new_v4();
+        let mut items = Vec::with_capacity(file_ids.len());
+
+        for file_id in file_ids {
+            let item = match self.apply_bulk_action(ctx, file_id, &action).await {
+                Ok((file, payload)) => {
+                    let payload = payload.with_uuid("bulk_operation_id", operation_id);
+                    self.plugin_manager
+                        .dispatcher()
+                        .fire_and_forget(&payload)
+                        .await;
+                    BulkItemResult {
+                        file_id,
+                        success: true,
+                        file,
+                        error_code: None,
+                        error: None,
+                    }
+                }
+                Err(e) => BulkItemResult {
+                    file_id,
+                    success: false,
+                    file: None,
+                    error_code: Some(e.code.to_string()),
+                    error: Some(e.message),
+                },
+            };
+            items.push(item);
+        }
+
+        let succeeded = items.iter().filter(|i| i.success).count();
+        let failed = items.len() - succeeded;
+
+        let details = serde_json::json!({
+            "operation_id": operation_id,
+            "action": action,
+            "succeeded": items.iter().filter(|i| i.success).map(|i| i.file_id).collect::<Vec<_>>(),
+            "failed": items.iter().filter(|i| !i.success).map(|i| i.file_id).collect::<Vec<_>>(),
+        });
+        if let Err(e) = self
+            .audit
+            .log_event(
+                ctx.user_id,
+                action.audit_action(),
+                "file",
+                None,
+                Some(details),
+                Some(&ctx.ip_address),
+                ctx.user_agent.as_deref(),
+            )
+            .await
+        {
+            tracing::warn!(error = %e, operation_id = %operation_id, "Failed to audit bulk operation");
+        }
+
+        info!(
+            user_id = %ctx.user_id,
+            operation_id = %operation_id,
+            action = action.audit_action(),
+            succeeded,
+            failed,
+            "Bulk file operation completed"
+        );
+
+        Ok(BulkOperationResult {
+            operation_id,
+            succeeded,
+            failed,
+            items,
+        })
+    }
+
+    /// Applies a bulk action to one file, returning the resulting file and
+    /// the hook payload describing the change.
+    async fn apply_bulk_action(
+        &self,
+        ctx: &RequestContext,
+        file_id: Uuid,
+        action: &BulkAction,
+    ) -> Result<(Option<File>, HookPayload), AppError> {
+        match action {
+            BulkAction::Delete => {
+                let file = self
+                    .get_file_with_permission(ctx, file_id, AclPermission::Editor)
+                    .await?;
+                self.delete_file(ctx, file_id).await?;
+                let payload = HookPayload::new(HookPoint::AfterDelete)
+                    .with_actor(ctx.user_id)
+                    .with_uuid("file_id", file.id)
+                    .with_uuid("folder_id", file.folder_id)
+                    .with_string("name", &file.name);
+                Ok((None, payload))
+            }
+            BulkAction::Move { target_folder_id } => {
+                let before = self
+                    .get_file_with_permission(ctx, file_id, AclPermission::Editor)
+                    .await?;
+                let file = self
+                    .move_file(
+                        ctx,
+                        file_id,
+                        MoveFileRequest {
+                            target_folder_id: *target_folder_id,
+                        },
+                    )
+                    .await?;
+                let payload = HookPayload::new(HookPoint::OnFileMove)
+                    .with_actor(ctx.user_id)
+                    .with_uuid("file_id", file.id)
+                    .with_uuid("from_folder_id", before.folder_id)
+                    .with_uuid("to_folder_id", file.folder_id);
+                Ok((Some(file), payload))
+            }
+            BulkAction::Copy { target_folder_id } => {
+                let file = self
+                    .copy_file(
+                        ctx,
+                        file_id,
+                        CopyFileRequest {
+                            target_folder_id: *target_folder_id,
+                            new_name: None,
+                            on_conflict: None,
+                        },
+                    )
+                    .await?;
+                let payload = HookPayload::new(HookPoint::OnFileCopy)
+                    .with_actor(ctx.user_id)
+                    .with_uuid("source_file_id", file_id)
+                    .with_uuid("new_file_id", file.id)
+                    .with_uuid("folder_id", file.folder_id);
+                Ok((Some(file), payload))
+            }
+        }
+    }
+
+    /// Lists the caller's tags.
+    #[tracing::instrument(target = "otel", name = "FileService::list_tags", skip_all)]
+    pub async fn list_tags(&self, ctx: &RequestContext) -> Result<Vec<Tag>, AppError> {
+        self.tag_repo.find_by_owner(ctx.user_id).await
+    }
+
+    /// Creates a tag for the caller, or returns the existing one with the
+    /// same normalized name.
+    #[tracing::instrument(target = "otel", name = "FileService::create_tag", skip_all)]
+    pub async fn create_tag(&self, ctx: &RequestContext, name: &str) -> Result<Tag, AppError> {
+        let normalized = normalize_tag(name)?;
+        self.tag_repo
+            .find_or_create(ctx.user_id, name.trim(), &normalized)
+            .await
+    }
+
+    /// Renames one of the caller's tags.
+    #[tracing::instrument(target = "otel", name = "FileService::rename_tag", skip_all)]
+    pub async fn rename_tag(
+        &self,
+        ctx: &RequestContext,
+        tag_id: Uuid,
+        name: &str,
+    ) -> Result<Tag, AppError> {
+        self.find_own_tag(ctx, tag_id).await?;
+        let normalized = normalize_tag(name)?;
+        self.tag_repo.rename(tag_id, name.trim(), &normalized).await
+    }
+
+    /// Deletes one of the caller's tags, detaching it from all files.
+    #[tracing::instrument(target = "otel", name = "FileService::delete_tag", skip_all)]
+    pub async fn delete_tag(&self, ctx: &RequestContext, tag_id: Uuid) -> Result<(), AppError> {
+        self.find_own_tag(ctx, tag_id).await?;
+        self.tag_repo.delete(tag_id).await?;
+        Ok(())
+    }
+
+    /// Lists the caller's tags on a file, enforcing viewer permission.
+    #[tracing::instrument(target = "otel", name = "FileService::file_tags", skip_all)]
+    pub async fn file_tags(
+        &self,
+        ctx: &RequestContext,
+        file_id: Uuid,
+    ) -> Result<Vec<Tag>, AppError> {
+        self.get_file_with_permission(ctx, file_id, AclPermission::Viewer)
+            .await?;
+        self.tag_repo.find_for_file(ctx.user_id, file_id).await
+    }
+
+    /// Tags a file, creating the caller's tag if needed.
+    ///
+    /// Tags are personal, so viewer permission on the file is enough.
+    #[tracing::instrument(target = "otel", name = "FileService::add_tag", skip_all)]
+    pub async fn add_tag(
+        &self,
+        ctx: &RequestContext,
+        file_id: Uuid,
+        name: &str,
+    ) -> Result<Tag, AppError> {
+        self.get_file_with_permission(ctx, file_id, AclPermission::Viewer)
+            .await?;
+        let tag = self.create_tag(ctx, name).await?;
+        self.tag_repo.attach(file_id, tag.id).await?;
+
+        info!(user_id = %ctx.user_id, file_id = %file_id, tag = %tag.normalized_name, "File tagged");
+
+        Ok(tag)
+    }
+
+    /// Removes one of the caller's tags from a file.
+    #[tracing::instrument(target = "otel", name = "FileService::remove_tag", skip_all)]
+    pub async fn remove_tag(
+        &self,
+        ctx: &RequestContext,
+        file_id: Uuid,
+        tag_id: Uuid,
+    ) -> Result<(), AppError> {
+        self.find_own_tag(ctx, tag_id).await?;
+        if !self.tag_repo.detach(file_id, tag_id).await? {
+            return Err(AppError::not_found("File does not have this tag"));
+        }
+        Ok(())
+    }
+
+    /// Lists files carrying one of the caller's tags, keeping only files
+    /// the caller can still view.
+    #[tracing::instrument(target = "otel", name = "FileService::files_by_tag", skip_all)]
+    pub async fn files_by_tag(
+        &self,
+        ctx: &RequestContext,
+        tag_id: Uuid,
+        page: PageRequest,
+    ) -> Result<PageResponse<File>, AppError> {
+        self.find_own_tag(ctx, tag_id).await?;
+
+        let candidates = self
+            .tag_repo
+            .find_files(tag_id, MAX_TAGGED_FILES, 0)
+            .await?;
+        let mut visible = Vec::with_capacity(candidates.len());
+        for file in candidates {
+            let permission = self
+                .perm_resolver
+                .resolve(
+                    ctx.user_id,
+                    &ctx.role,
+                    ResourceType::File,
+                    file.id,
+                    file.owner_id,
+                    Some(file.folder_id),
+                    AclPermission::Viewer,
+                )
+                .await?;
+            if permission.granted {
+                visible.push(file);
+            }
+        }
+
+        let total = visible.len() as u64;
+        let items = visible
+            .into_iter()
+            .skip(page.offset() as usize)
+            .take(page.limit() as usize)
+            .collect();
+
+        Ok(PageResponse::new(items, page.page, page.page_size, total))
+    }
+
+    /// Loads a tag owned by the caller.
+    async fn find_own_tag(&self, ctx: &RequestContext, tag_id: Uuid) -> Result<Tag, AppError> {
+        self.tag_repo
+            .find_by_id(tag_id)
+            .await?
+            .filter(|t| t.owner_id == ctx.user_id)
+            .ok_or_else(|| AppError::not_found("Tag not found"))
+    }
+
     /// Internal helper — loads a file and checks the required ACL permission.
     async fn get_file_with_permission(
         &self,
@@ -394,7 +974,9 @@ impl FileService {
             .find_by_id(file_id)
             .await
             .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .ok_or_else(|| AppError::not_found("File not found"))?;
+            .ok_or_else(|| {
+                AppError::not_found("File not found").with_code(codes::FILE_NOT_FOUND)
+            })?;
 
         self.perm_resolver
             .require_permission(
@@ -411,3 +993,13 @@ impl FileService {
         Ok(file)
     }
 }
+
+/// Normalizes a user-supplied tag name or rejects it.
+fn normalize_tag(name: &str) -> Result<String, AppError> {
+    Tag::normalize(name).ok_or_else(|| {
+        AppError::validation(format!(
+            "Tag names must be 1-{} characters",
+            filehub_entity::tag::model::MAX_TAG_LEN
+        ))
+    })
+}
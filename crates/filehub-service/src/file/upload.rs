@@ -1,24 +1,38 @@
 //! File upload service — simple (single request) and chunked upload flows.
 
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use bytes::Bytes;
-use chrono::Utc;
-use tracing::info;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use tokio::io::AsyncReadExt;
+use tracing::{info, warn};
 use uuid::Uuid;
 
 use filehub_auth::acl::EffectivePermissionResolver;
-use filehub_core::config::StorageConfig;
-use filehub_core::error::AppError;
+use filehub_core::config::{NameCollision, StorageConfig};
+use filehub_core::error::{AppError, ErrorKind, codes};
+use filehub_core::events::{DomainEvent, EventPayload, FileEvent};
+use filehub_core::traits::storage::PresignedRequest;
 use filehub_database::repositories::file::FileRepository;
 use filehub_database::repositories::folder::FolderRepository;
-use filehub_entity::file::{CreateFile, File};
+use filehub_database::repositories::job::JobRepository;
+use filehub_entity::file::{ChunkStatus, ChunkedUpload, CreateFile, File};
+use filehub_entity::job::{CreateJob, JobPayload, JobPriority};
 use filehub_entity::permission::{AclPermission, ResourceType};
 use filehub_plugin::hooks::definitions::{HookPayload, HookPoint};
 use filehub_plugin::manager::PluginManager;
+use filehub_storage::hashing::{ContentDigest, ContentHasher, HashingStream, sha256_hex};
 use filehub_storage::manager::StorageManager;
+use filehub_storage::sniff::{self, SNIFF_LEN};
+use filehub_storage::upload_policy::UploadRules;
 
 use crate::context::RequestContext;
+use crate::file::preview::THUMBNAIL_JOB_TYPE;
+use crate::file::processing::{ProcessingStats, UploadProcessor};
+use crate::folder::FolderTreeCache;
 
 /// Handles both simple and chunked file uploads.
 #[derive(Clone)]
@@ -35,6 +49,13 @@ pub struct UploadService {
     config: StorageConfig,
     /// Plugin manager for firing hooks.
     plugin_manager: Arc<PluginManager>,
+    /// Job repository for thumbnail pre-generation; `None` leaves
+    /// thumbnails to be rendered on first preview.
+    thumbnail_jobs: Option<Arc<JobRepository>>,
+    /// Cached folder trees, whose file counts change with uploads.
+    tree_cache: Option<FolderTreeCache>,
+    /// Limit on concurrent assembly and `AfterUpload` processing.
+    processor: UploadProcessor,
 }
 
 impl std::fmt::Debug for UploadService {
@@ -69,6 +90,29 @@ pub struct InitiateUploadResponse {
     pub total_chunks: i32,
 }
 
+/// Progress of a chunked upload session.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct UploadStatus {
+    /// Upload session ID.
+    pub upload_id: Uuid,
+    /// File name.
+    pub file_name: String,
+    /// Total file size in bytes.
+    pub file_size: i64,
+    /// Size of each chunk.
+    pub chunk_size: i64,
+    /// Total number of chunks.
+    pub total_chunks: i32,
+    /// Chunks received and verified, in order.
+    pub received_chunks: Vec<i32>,
+    /// Chunks still to send, in order.
+    pub missing_chunks: Vec<i32>,
+    /// Session status.
+    pub status: String,
+    /// When the session expires unless another chunk arrives.
+    pub expires_at: DateTime<Utc>,
+}
+
 /// Simple upload parameters (single request with full file body).
 #[derive(Debug, Clone)]
 pub struct SimpleUploadParams {
@@ -80,6 +124,69 @@ pub struct SimpleUploadParams {
     pub mime_type: Option<String>,
     /// File content bytes.
     pub data: Bytes,
+    /// What to do if the name is taken; `None` uses the configured default.
+    pub on_conflict: Option<NameCollision>,
+}
+
+/// Parameters for an upload from a `multipart/form-data` form.
+#[derive(Debug)]
+pub struct FormUploadParams {
+    /// Target folder ID.
+    pub folder_id: Uuid,
+    /// File name.
+    pub file_name: String,
+    /// MIME type.
+    pub mime_type: Option<String>,
+    /// The file part.
+    pub content: FormContent,
+    /// Description stored in the file's metadata.
+    pub description: Option<String>,
+    /// What to do if the name is taken; `None` uses the configured default.
+    pub on_conflict: Option<NameCollision>,
+}
+
+/// Where the file part of a form upload was received to.
+#[derive(Debug)]
+pub enum FormContent {
+    /// Small enough to keep in memory; stored as a simple upload.
+    Bytes(Bytes),
+    /// Spooled to a local file; stored through a chunked upload session.
+    Spooled {
+        /// Path of the spooled part, owned by the caller.
+        path: PathBuf,
+        /// Size in bytes.
+        size_bytes: u64,
+    },
+}
+
+/// Request for a presigned upload straight to the storage backend.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DirectUploadRequest {
+    /// Target folder ID.
+    pub folder_id: Uuid,
+    /// File name.
+    pub file_name: String,
+    /// Exact file size in bytes; the signed URL accepts no other.
+    pub file_size: i64,
+    /// MIME type.
+    pub mime_type: Option<String>,
+    /// What to do if the name is taken; `None` uses the configured
+    /// default. Only `reject` is checked before the upload; the strategy
+    /// applied is the one given when finalizing.
+    #[serde(default)]
+    pub on_conflict: Option<NameCollision>,
+}
+
+/// Response to a presigned upload request.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DirectUploadResponse {
+    /// Upload session ID, passed to finalize once the object is uploaded.
+    pub upload_id: Uuid,
+    /// The request that uploads the file.
+    pub upload: PresignedRequest,
+    /// When the session and any uploaded object are discarded unless
+    /// finalized.
+    pub finalize_by: DateTime<Utc>,
 }
 
 impl UploadService {
@@ -92,6 +199,7 @@ impl UploadService {
         config: StorageConfig,
         plugin_manager: Arc<PluginManager>,
     ) -> Self {
+        let processor = UploadProcessor::new(config.upload_processing.concurrency);
         Self {
             file_repo,
             folder_repo,
@@ -99,22 +207,66 @@ impl UploadService {
             perm_resolver,
             config,
             plugin_manager,
+            thumbnail_jobs: None,
+            tree_cache: None,
+            processor,
         }
     }
 
+    /// Queues thumbnail generation after each upload, as configured in
+    /// `storage.thumbnail_pregen`.
+    pub fn with_thumbnail_jobs(mut self, job_repo: Arc<JobRepository>) -> Self {
+        self.thumbnail_jobs = Some(job_repo);
+        self
+    }
+
+    /// Drops cached folder trees of a storage whenever a file is uploaded
+    /// to it.
+    pub fn with_tree_cache(mut self, tree_cache: FolderTreeCache) -> Self {
+        self.tree_cache = Some(tree_cache);
+        self
+    }
+
+    /// Drops the cached folder trees of a storage.
+    async fn invalidate_trees(&self, storage_id: Uuid) {
+        if let Some(cache) = &self.tree_cache {
+            cache.invalidate(storage_id).await;
+        }
+    }
+
+    /// Checks a file's declared name and type against the caller's upload
+    /// policy, so a simple upload can be refused before its body is read.
+    /// The size and content are checked by [`simple_upload`](Self::simple_upload).
+    pub fn check_declared_type(
+        &self,
+        ctx: &RequestContext,
+        file_name: &str,
+        mime_type: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.upload_rules(ctx)
+            .check_declared(file_name, mime_type, 0)
+    }
+
+    /// The upload policy of the caller's role.
+    pub fn upload_rules(&self, ctx: &RequestContext) -> UploadRules {
+        UploadRules::for_role(&self.config, ctx.role.as_str())
+    }
+
     /// Performs a simple (single-request) file upload.
+    #[tracing::instrument(target = "otel", name = "UploadService::simple_upload", skip_all)]
     pub async fn simple_upload(
         &self,
         ctx: &RequestContext,
         params: SimpleUploadParams,
     ) -> Result<File, AppError> {
-        // Check size limit
-        if params.data.len() as u64 > self.config.max_upload_size_bytes {
-            return Err(AppError::validation(format!(
-                "File exceeds maximum upload size of {} bytes",
-                self.config.max_upload_size_bytes
-            )));
-        }
+        // Check size and type against the role's upload policy
+        let rules = self.upload_rules(ctx);
+        rules.check_declared(
+            &params.file_name,
+            params.mime_type.as_deref(),
+            params.data.len() as u64,
+        )?;
+        rules.check_content(&params.data)?;
 
         // Verify folder exists and user has editor permission
         let folder = self
@@ -136,90 +288,179 @@ impl UploadService {
             )
             .await?;
 
-        // Check for name conflict
-        if self
-            .file_repo
-            .find_by_folder_and_name(params.folder_id, &params.file_name)
-            .await
-            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .is_some()
-        {
-            return Err(AppError::conflict(format!(
-                "A file named '{}' already exists in this folder",
-                params.file_name
-            )));
-        }
+        let strategy = self.collision(params.on_conflict);
+        let existing = self
+            .collision_target(ctx, params.folder_id, &params.file_name, strategy)
+            .await?;
 
-        // Write to storage
+        // Write to storage, avoiding the folder's provider if it is down;
+        // a replacement goes where the file's versions are
+        let storage_id = match &existing {
+            Some(existing) => existing.storage_id,
+            None => self.storage.select_for_write(folder.storage_id).await?,
+        };
         let file_id = Uuid::new_v4();
         let storage_path = format!("{}/{}/{}", folder.path, file_id, params.file_name);
 
-        self.storage
-            .write(&folder.storage_id, &storage_path, params.data.clone())
+        // Hash the content as it is written
+        let head = params.data.slice(..params.data.len().min(SNIFF_LEN));
+        let (stream, digest) = HashingStream::once(params.data, self.content_hasher());
+        let written = self
+            .storage
+            .write_stream(&storage_id, &storage_path, stream.boxed())
             .await
             .map_err(|e| AppError::internal(format!("Storage write failed: {e}")))?;
+        let digest = digest.finish();
+        self.verify_written(&storage_id, &storage_path, written, &digest, None)
+            .await?;
 
         // Create file record
         let file_record = CreateFile {
             folder_id: params.folder_id,
-            storage_id: folder.storage_id,
+            storage_id,
+            mime_type: self.content_type(&head, &params.file_name, params.mime_type.as_deref()),
+            declared_mime_type: params.mime_type,
             name: params.file_name,
             storage_path,
-            mime_type: params.mime_type,
-            size_bytes: params.data.len() as i64,
-            checksum_sha256: None,
-            metadata: Some(serde_json::json!({})),
+            size_bytes: digest.size_bytes as i64,
+            checksum_sha256: Some(digest.sha256.clone()),
+            metadata: Some(content_metadata(&digest)),
             owner_id: ctx.user_id,
         };
 
         let file = self
-            .file_repo
-            .create(&file_record)
-            .await
-            .map_err(|e| AppError::internal(format!("Failed to create file record: {e}")))?;
+            .store_record(
+                ctx,
+                &file_record,
+                strategy,
+                existing.as_ref(),
+                Some(folder.owner_id),
+            )
+            .await?;
+        self.invalidate_trees(folder.storage_id).await;
 
         info!(
             user_id = %ctx.user_id,
             file_id = %file.id,
             name = %file.name,
             size = file.size_bytes,
+            on_conflict = %strategy,
             "Simple upload completed"
         );
 
-        // Fire AfterUpload hook
-        let mut payload = HookPayload::new(HookPoint::AfterUpload)
-            .with_uuid("file_id", file.id)
-            .with_uuid("folder_id", file.folder_id)
-            .with_uuid("owner_id", file.owner_id)
-            .with_string("name", &file.name)
-            .with_string("storage_path", &file.storage_path)
-            .with_int("size_bytes", file.size_bytes);
+        self.after_upload(&file);
 
-        if let Some(mime) = &file.mime_type {
-            payload = payload.with_string("mime_type", mime);
-        }
+        Ok(file)
+    }
 
-        self.plugin_manager
-            .dispatcher()
-            .fire_and_forget(&payload)
-            .await;
+    /// Uploads the file part of a form, recording `description` in its
+    /// metadata. Parts larger than one chunk arrive spooled and go through
+    /// a chunked upload session, so they are verified and assembled like
+    /// any other large upload.
+    #[tracing::instrument(target = "otel", name = "UploadService::form_upload", skip_all)]
+    pub async fn form_upload(
+        &self,
+        ctx: &RequestContext,
+        params: FormUploadParams,
+    ) -> Result<File, AppError> {
+        let file = match params.content {
+            FormContent::Bytes(data) => {
+                self.simple_upload(
+                    ctx,
+                    SimpleUploadParams {
+                        folder_id: params.folder_id,
+                        file_name: params.file_name,
+                        mime_type: params.mime_type,
+                        data,
+                        on_conflict: params.on_conflict,
+                    },
+                )
+                .await?
+            }
+            FormContent::Spooled { path, size_bytes } => {
+                self.upload_spooled(
+                    ctx,
+                    InitiateUploadRequest {
+                        folder_id: params.folder_id,
+                        file_name: params.file_name,
+                        file_size: size_bytes as i64,
+                        mime_type: params.mime_type,
+                        checksum_sha256: None,
+                    },
+                    &path,
+                    params.on_conflict,
+                )
+                .await?
+            }
+        };
 
-        Ok(file)
+        let Some(description) = params.description else {
+            return Ok(file);
+        };
+        let mut metadata = file
+            .metadata
+            .clone()
+            .filter(serde_json::Value::is_object)
+            .unwrap_or_else(|| serde_json::json!({}));
+        metadata["description"] = serde_json::Value::String(description);
+        self.file_repo.update_metadata(file.id, &metadata).await
+    }
+
+    /// Sends a spooled file through a chunked upload session.
+    async fn upload_spooled(
+        &self,
+        ctx: &RequestContext,
+        req: InitiateUploadRequest,
+        path: &std::path::Path,
+        on_conflict: Option<NameCollision>,
+    ) -> Result<File, AppError> {
+        let file_size = req.file_size;
+        let session = self.initiate_chunked_upload(ctx, req).await?;
+        let mut spooled = tokio::fs::File::open(path)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to open spooled upload: {e}")))?;
+
+        // A session left behind by a failure here expires with the others
+        for chunk_number in 0..session.total_chunks {
+            let offset = chunk_number as i64 * session.chunk_size;
+            let len = session.chunk_size.min(file_size - offset).max(0) as usize;
+            let mut chunk = vec![0; len];
+            spooled
+                .read_exact(&mut chunk)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to read spooled upload: {e}")))?;
+            self.upload_chunk(
+                ctx,
+                session.upload_id,
+                chunk_number,
+                Bytes::from(chunk),
+                None,
+            )
+            .await?;
+        }
+
+        self.complete_chunked_upload(ctx, session.upload_id, on_conflict)
+            .await
     }
 
     /// Initiates a chunked upload session.
+    #[tracing::instrument(
+        target = "otel",
+        name = "UploadService::initiate_chunked_upload",
+        skip_all
+    )]
     pub async fn initiate_chunked_upload(
         &self,
         ctx: &RequestContext,
         req: InitiateUploadRequest,
     ) -> Result<InitiateUploadResponse, AppError> {
-        // Check size limit
-        if req.file_size as u64 > self.config.max_upload_size_bytes {
-            return Err(AppError::validation(format!(
-                "File exceeds maximum upload size of {} bytes",
-                self.config.max_upload_size_bytes
-            )));
-        }
+        // Check size and type against the role's upload policy; the
+        // content is checked when its first chunk arrives
+        self.upload_rules(ctx).check_declared(
+            &req.file_name,
+            req.mime_type.as_deref(),
+            req.file_size.max(0) as u64,
+        )?;
 
         // Verify folder and permission
         let folder = self
@@ -245,15 +486,15 @@ impl UploadService {
         let total_chunks = ((req.file_size as f64) / (chunk_size as f64)).ceil() as i32;
         let total_chunks = if total_chunks == 0 { 1 } else { total_chunks };
 
-        let upload_id = Uuid::new_v4();
-        let temp_path = format!("temp/uploads/{}", upload_id);
-        let now = Utc::now();
-        let expires_at = now + chrono::Duration::hours(24);
+        let storage_id = self.storage.select_for_write(folder.storage_id).await?;
+        let temp_path = format!("temp/uploads/{}", Uuid::new_v4());
+        let expires_at = self.upload_expiry();
 
-        self.file_repo
+        let upload = self
+            .file_repo
             .create_chunked_upload(
                 ctx.user_id,
-                folder.storage_id,
+                storage_id,
                 req.folder_id,
                 &req.file_name,
                 req.file_size,
@@ -266,6 +507,7 @@ impl UploadService {
             )
             .await
             .map_err(|e| AppError::internal(format!("Failed to create upload session: {e}")))?;
+        let upload_id = upload.id;
 
         info!(
             user_id = %ctx.user_id,
@@ -281,28 +523,419 @@ impl UploadService {
         })
     }
 
+    /// Starts a direct upload: the client PUTs the file to the returned
+    /// signed URL, bypassing the server, then calls
+    /// [`finalize_direct_upload`](Self::finalize_direct_upload).
+    ///
+    /// Only the declared size and type are checked against the upload
+    /// policy; the content never passes through the server.
+    #[tracing::instrument(target = "otel", name = "UploadService::presign_upload", skip_all)]
+    pub async fn presign_upload(
+        &self,
+        ctx: &RequestContext,
+        req: DirectUploadRequest,
+    ) -> Result<DirectUploadResponse, AppError> {
+        let direct = &self.config.direct_transfer;
+        if !direct.enabled {
+            return Err(AppError::not_implemented("Direct transfers are disabled")
+                .with_code(codes::STORAGE_PRESIGN_UNSUPPORTED));
+        }
+        if req.file_size < 0 {
+            return Err(AppError::validation("File size must not be negative"));
+        }
+        self.upload_rules(ctx).check_declared(
+            &req.file_name,
+            req.mime_type.as_deref(),
+            req.file_size as u64,
+        )?;
+
+        let folder = self
+            .folder_repo
+            .find_by_id(req.folder_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+            .ok_or_else(|| AppError::not_found("Target folder not found"))?;
+
+        self.perm_resolver
+            .require_permission(
+                ctx.user_id,
+                &ctx.role,
+                ResourceType::Folder,
+                req.folder_id,
+                folder.owner_id,
+                folder.parent_id,
+                AclPermission::Editor,
+            )
+            .await?;
+        if self.collision(req.on_conflict) == NameCollision::Reject {
+            self.check_name_free(req.folder_id, &req.file_name).await?;
+        }
+
+        let storage_id = self.storage.select_for_write(folder.storage_id).await?;
+        let provider = self.storage.get(&storage_id).await?;
+        let object_path = format!("{}/{}/{}", folder.path, Uuid::new_v4(), req.file_name);
+        let mime_type = req
+            .mime_type
+            .as_deref()
+            .unwrap_or("application/octet-stream");
+
+        // Sign before recording the session, so a backend without signed
+        // URLs leaves nothing behind
+        let upload = provider
+            .presign_upload(
+                &object_path,
+                mime_type,
+                req.file_size as u64,
+                Duration::from_secs(direct.url_expiry_seconds),
+            )
+            .await?;
+
+        let finalize_by =
+            Utc::now() + chrono::Duration::minutes(direct.finalize_within_minutes as i64);
+        let session = self
+            .file_repo
+            .create_direct_upload(
+                ctx.user_id,
+                storage_id,
+                req.folder_id,
+                &req.file_name,
+                req.file_size,
+                mime_type,
+                &object_path,
+                finalize_by,
+            )
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to create upload session: {e}")))?;
+
+        info!(
+            user_id = %ctx.user_id,
+            upload_id = %session.id,
+            size = req.file_size,
+            "Direct upload presigned"
+        );
+
+        Ok(DirectUploadResponse {
+            upload_id: session.id,
+            upload,
+            finalize_by,
+        })
+    }
+
+    /// Finalizes a direct upload once the client has uploaded the object,
+    /// creating the file record.
+    ///
+    /// An object of the wrong size is deleted so the client can upload
+    /// again while the session lasts.
+    #[tracing::instrument(
+        target = "otel",
+        name = "UploadService::finalize_direct_upload",
+        skip_all
+    )]
+    pub async fn finalize_direct_upload(
+        &self,
+        ctx: &RequestContext,
+        upload_id: Uuid,
+        on_conflict: Option<NameCollision>,
+    ) -> Result<File, AppError> {
+        let upload = self.find_own_upload(ctx, upload_id).await?;
+
+        if !upload.direct {
+            return Err(AppError::validation(
+                "Not a direct upload; complete it instead",
+            ));
+        }
+        if upload.status != ChunkStatus::Uploading.as_str() {
+            return Err(AppError::conflict("Upload is not in uploading state"));
+        }
+        if upload.expires_at < Utc::now() {
+            return Err(AppError::gone("Upload session has expired"));
+        }
+
+        let provider = self.storage.get(&upload.storage_id).await?;
+        let object = provider.metadata(&upload.temp_path).await.map_err(|e| {
+            if e.kind == ErrorKind::NotFound {
+                AppError::validation("The file has not been uploaded yet")
+            } else {
+                AppError::internal(format!("Failed to inspect uploaded file: {e}"))
+            }
+        })?;
+        if object.size_bytes != upload.file_size.max(0) as u64 {
+            let _ = provider.delete(&upload.temp_path).await;
+            return Err(AppError::validation(format!(
+                "Uploaded file is {} bytes, expected {}",
+                object.size_bytes, upload.file_size
+            )));
+        }
+
+        if !self
+            .file_repo
+            .begin_assembly(upload_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+        {
+            return Err(AppError::conflict("Upload is already being finalized"));
+        }
+
+        let strategy = self.collision(on_conflict);
+        match self.record_direct_upload(ctx, &upload, strategy).await {
+            Ok(file) => {
+                self.after_upload(&file);
+                Ok(file)
+            }
+            Err(e) => {
+                let _ = self
+                    .file_repo
+                    .update_chunked_upload_status(upload_id, ChunkStatus::Uploading.as_str())
+                    .await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Creates the file record of a directly uploaded object and closes
+    /// its session.
+    async fn record_direct_upload(
+        &self,
+        ctx: &RequestContext,
+        upload: &ChunkedUpload,
+        strategy: NameCollision,
+    ) -> Result<File, AppError> {
+        let existing = self
+            .collision_target(ctx, upload.target_folder_id, &upload.file_name, strategy)
+            .await?;
+        if let Some(existing) = &existing
+            && existing.storage_id != upload.storage_id
+        {
+            return Err(AppError::conflict(format!(
+                "'{}' is on another storage and cannot be overwritten by a direct upload",
+                upload.file_name
+            )));
+        }
+
+        let file_record = CreateFile {
+            folder_id: upload.target_folder_id,
+            storage_id: upload.storage_id,
+            name: upload.file_name.clone(),
+            storage_path: upload.temp_path.clone(),
+            // The content never passes through here; go by the name
+            mime_type: self.content_type(&[], &upload.file_name, upload.mime_type.as_deref()),
+            declared_mime_type: upload.mime_type.clone(),
+            size_bytes: upload.file_size,
+            checksum_sha256: None,
+            metadata: Some(serde_json::json!({})),
+            owner_id: ctx.user_id,
+        };
+
+        let folder_owner_id = self
+            .folder_repo
+            .find_by_id(upload.target_folder_id)
+            .await
+            .ok()
+            .flatten()
+            .map(|folder| folder.owner_id);
+        let file = self
+            .store_record(
+                ctx,
+                &file_record,
+                strategy,
+                existing.as_ref(),
+                folder_owner_id,
+            )
+            .await?;
+        self.invalidate_trees(upload.storage_id).await;
+
+        self.file_repo
+            .complete_chunked_upload(upload.id)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to complete upload: {e}")))?;
+
+        info!(
+            user_id = %ctx.user_id,
+            file_id = %file.id,
+            name = %file.name,
+            size = file.size_bytes,
+            "Direct upload finalized"
+        );
+
+        Ok(file)
+    }
+
+    /// Fails if the folder already has a file named `file_name`.
+    async fn check_name_free(&self, folder_id: Uuid, file_name: &str) -> Result<(), AppError> {
+        if self
+            .file_repo
+            .find_by_folder_and_name(folder_id, file_name)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+            .is_some()
+        {
+            return Err(AppError::conflict(format!(
+                "A file named '{file_name}' already exists in this folder"
+            )));
+        }
+        Ok(())
+    }
+
+    /// The strategy a request asked for, or the configured default.
+    fn collision(&self, requested: Option<NameCollision>) -> NameCollision {
+        requested.unwrap_or(self.config.name_collision)
+    }
+
+    /// The file an upload of `file_name` replaces under `strategy`, if
+    /// any. Fails if the name is taken and the strategy is `Reject`, or if
+    /// the file to overwrite may not be edited by the caller.
+    async fn collision_target(
+        &self,
+        ctx: &RequestContext,
+        folder_id: Uuid,
+        file_name: &str,
+        strategy: NameCollision,
+    ) -> Result<Option<File>, AppError> {
+        match strategy {
+            NameCollision::Reject => {
+                self.check_name_free(folder_id, file_name).await?;
+                Ok(None)
+            }
+            // The free name is picked when the record is created
+            NameCollision::Rename => Ok(None),
+            NameCollision::Overwrite => {
+                let Some(existing) = self
+                    .file_repo
+                    .find_by_folder_and_name(folder_id, file_name)
+                    .await?
+                else {
+                    return Ok(None);
+                };
+                self.perm_resolver
+                    .require_permission(
+                        ctx.user_id,
+                        &ctx.role,
+                        ResourceType::File,
+                        existing.id,
+                        existing.owner_id,
+                        Some(existing.folder_id),
+                        AclPermission::Editor,
+                    )
+                    .await?;
+                if existing.is_locked.unwrap_or(false)
+                    && existing.locked_by != Some(ctx.user_id)
+                    && !ctx.is_admin()
+                {
+                    return Err(AppError::conflict(format!(
+                        "'{file_name}' is locked by another user"
+                    )));
+                }
+                Ok(Some(existing))
+            }
+        }
+    }
+
+    /// Creates the record of an uploaded file, or replaces the content of
+    /// `existing` when overwriting. The replaced content is kept as a
+    /// version unless versioning is off (`max_versions_per_file = 0`).
+    /// The `file.uploaded` event is recorded with the write, naming
+    /// `folder_owner_id` as the owner of the folder uploaded to.
+    async fn store_record(
+        &self,
+        ctx: &RequestContext,
+        record: &CreateFile,
+        strategy: NameCollision,
+        existing: Option<&File>,
+        folder_owner_id: Option<Uuid>,
+    ) -> Result<File, AppError> {
+        let announce = |file: &File| {
+            DomainEvent::new(
+                Some(ctx.user_id),
+                EventPayload::File(FileEvent::Uploaded {
+                    file_id: file.id,
+                    folder_id: file.folder_id,
+                    storage_id: file.storage_id,
+                    name: file.name.clone(),
+                    size_bytes: file.size_bytes.max(0) as u64,
+                    mime_type: file.mime_type.clone(),
+                    actor_name: ctx.username.clone(),
+                    folder_owner_id,
+                }),
+            )
+        };
+        if let Some(existing) = existing {
+            let keep_version = self.config.max_versions_per_file > 0;
+            if let Some(file) = self
+                .file_repo
+                .replace_content(
+                    existing.id,
+                    record,
+                    keep_version,
+                    ctx.user_id,
+                    Some(&announce),
+                )
+                .await?
+            {
+                return Ok(file);
+            }
+            // Deleted meanwhile: store it as a new file
+        }
+        match strategy {
+            NameCollision::Rename => {
+                self.file_repo
+                    .create_with_free_name(record, Some(&announce))
+                    .await
+            }
+            NameCollision::Reject | NameCollision::Overwrite => {
+                self.file_repo.create(record, Some(&announce)).await
+            }
+        }
+    }
+
+    /// Reports which chunks of an upload session have arrived, so a client
+    /// can resume from the gaps after a crash.
+    #[tracing::instrument(target = "otel", name = "UploadService::upload_status", skip_all)]
+    pub async fn upload_status(
+        &self,
+        ctx: &RequestContext,
+        upload_id: Uuid,
+    ) -> Result<UploadStatus, AppError> {
+        let upload = self.find_own_upload(ctx, upload_id).await?;
+        let mut received = upload.uploaded_chunk_numbers();
+        received.sort_unstable();
+        received.dedup();
+
+        Ok(UploadStatus {
+            upload_id: upload.id,
+            file_name: upload.file_name.clone(),
+            file_size: upload.file_size,
+            chunk_size: upload.chunk_size as i64,
+            total_chunks: upload.total_chunks,
+            missing_chunks: upload.missing_chunks(),
+            received_chunks: received,
+            status: upload.status,
+            expires_at: upload.expires_at,
+        })
+    }
+
     /// Uploads a single chunk.
+    ///
+    /// The chunk must have exactly its expected size, and match
+    /// `checksum_sha256` if the client sent one. Re-sending a chunk that
+    /// already arrived with the same content is accepted and ignored, so
+    /// a client can safely retry; different content is a conflict.
+    #[tracing::instrument(target = "otel", name = "UploadService::upload_chunk", skip_all)]
     pub async fn upload_chunk(
         &self,
         ctx: &RequestContext,
         upload_id: Uuid,
         chunk_number: i32,
         data: Bytes,
+        checksum_sha256: Option<&str>,
     ) -> Result<(), AppError> {
-        let upload = self
-            .file_repo
-            .find_chunked_upload(upload_id)
-            .await
-            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .ok_or_else(|| AppError::not_found("Upload session not found"))?;
+        let upload = self.find_own_upload(ctx, upload_id).await?;
 
-        if upload.user_id != ctx.user_id {
-            return Err(AppError::forbidden(
-                "Upload session belongs to another user",
+        if upload.direct {
+            return Err(AppError::validation(
+                "Direct uploads are sent to the storage backend, not in chunks",
             ));
         }
-
-        if upload.status != "uploading" {
+        if upload.status != ChunkStatus::Uploading.as_str() {
             return Err(AppError::conflict(
                 "Upload session is not in uploading state",
             ));
@@ -315,19 +948,61 @@ impl UploadService {
             )));
         }
 
-        // Write chunk to temp storage
-        let chunk_path = format!("{}/chunk_{:06}", upload.temp_path, chunk_number);
+        let expected_size = upload.expected_chunk_size(chunk_number);
+        if data.len() as i64 != expected_size {
+            return Err(AppError::validation(format!(
+                "Chunk {chunk_number} must be {expected_size} bytes, got {}",
+                data.len()
+            )));
+        }
+
+        let actual = sha256_hex(&data);
+        if let Some(expected) = checksum_sha256
+            && !expected.eq_ignore_ascii_case(&actual)
+        {
+            return Err(AppError::validation(format!(
+                "Chunk {chunk_number} checksum mismatch: expected {expected}, got {actual}"
+            )));
+        }
+
+        if let Some(existing) = upload.chunk_checksum(chunk_number) {
+            return same_chunk(chunk_number, existing, &actual);
+        }
+
+        if chunk_number == 0 {
+            self.upload_rules(ctx).check_content(&data)?;
+        }
+
+        // Chunks are stored under their hash so a conflicting concurrent
+        // upload of the same chunk cannot overwrite the accepted data.
+        let chunk_path = chunk_path(&upload, chunk_number, &actual);
         self.storage
             .write(&upload.storage_id, &chunk_path, data)
             .await
             .map_err(|e| AppError::internal(format!("Failed to write chunk: {e}")))?;
 
-        // Update uploaded_chunks list
-        self.file_repo
-            .add_uploaded_chunk(upload_id, chunk_number)
+        let recorded = self
+            .file_repo
+            .record_chunk(upload_id, chunk_number, &actual, self.upload_expiry())
             .await
             .map_err(|e| AppError::internal(format!("Failed to update chunk status: {e}")))?;
 
+        if !recorded {
+            // Another request recorded this chunk first, or the session
+            // left the uploading state meanwhile.
+            let current = self.find_own_upload(ctx, upload_id).await?;
+            let result = match current.chunk_checksum(chunk_number) {
+                Some(existing) => same_chunk(chunk_number, existing, &actual),
+                None => Err(AppError::conflict(
+                    "Upload session is not in uploading state",
+                )),
+            };
+            if result.is_err() {
+                let _ = self.storage.delete(&upload.storage_id, &chunk_path).await;
+            }
+            return result;
+        }
+
         info!(
             upload_id = %upload_id,
             chunk = chunk_number,
@@ -338,113 +1013,177 @@ impl UploadService {
     }
 
     /// Completes a chunked upload — verifies all chunks and assembles the file.
+    #[tracing::instrument(
+        target = "otel",
+        name = "UploadService::complete_chunked_upload",
+        skip_all
+    )]
     pub async fn complete_chunked_upload(
         &self,
         ctx: &RequestContext,
         upload_id: Uuid,
+        on_conflict: Option<NameCollision>,
     ) -> Result<File, AppError> {
-        let upload = self
-            .file_repo
-            .find_chunked_upload(upload_id)
-            .await
-            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .ok_or_else(|| AppError::not_found("Upload session not found"))?;
+        let upload = self.find_own_upload(ctx, upload_id).await?;
 
-        if upload.user_id != ctx.user_id {
-            return Err(AppError::forbidden(
-                "Upload session belongs to another user",
+        if upload.direct {
+            return Err(AppError::validation(
+                "Direct uploads are finalized, not completed",
             ));
         }
-
-        if upload.status != "uploading" {
+        if upload.status != ChunkStatus::Uploading.as_str() {
             return Err(AppError::conflict("Upload is not in uploading state"));
         }
 
-        // Verify all chunks are present
-        let uploaded: Vec<i32> =
-            serde_json::from_value(upload.uploaded_chunks.clone()).unwrap_or_default();
-
-        let expected: Vec<i32> = (0..upload.total_chunks).collect();
-        let mut sorted_uploaded = uploaded.clone();
-        sorted_uploaded.sort();
-        sorted_uploaded.dedup();
-
-        if sorted_uploaded != expected {
-            let missing: Vec<i32> = expected
-                .iter()
-                .filter(|c| !sorted_uploaded.contains(c))
-                .copied()
-                .collect();
+        // Every chunk must be present with a verified checksum
+        let missing: Vec<i32> = (0..upload.total_chunks)
+            .filter(|&n| upload.chunk_checksum(n).is_none())
+            .collect();
+        if !missing.is_empty() {
             return Err(AppError::validation(format!(
                 "Missing chunks: {:?}",
                 missing
             )));
         }
 
-        // Assemble chunks into final file
+        if !self
+            .file_repo
+            .begin_assembly(upload_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+        {
+            return Err(AppError::conflict("Upload is already being assembled"));
+        }
+
+        let strategy = self.collision(on_conflict);
+        match self
+            .processor
+            .run(self.assemble(ctx, &upload, strategy))
+            .await
+        {
+            Ok(file) => {
+                self.after_upload(&file);
+                Ok(file)
+            }
+            Err(e) => {
+                // Leave the session resumable; the client may re-send
+                // chunks or retry completion.
+                let _ = self
+                    .file_repo
+                    .update_chunked_upload_status(upload_id, ChunkStatus::Uploading.as_str())
+                    .await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Reads back and verifies every chunk, writes the assembled file and
+    /// creates its record.
+    async fn assemble(
+        &self,
+        ctx: &RequestContext,
+        upload: &ChunkedUpload,
+        strategy: NameCollision,
+    ) -> Result<File, AppError> {
         let folder = self
             .folder_repo
             .find_by_id(upload.target_folder_id)
             .await
             .map_err(|e| AppError::internal(format!("Database error: {e}")))?
             .ok_or_else(|| AppError::not_found("Target folder not found"))?;
+        let existing = self
+            .collision_target(ctx, upload.target_folder_id, &upload.file_name, strategy)
+            .await?;
+        // A replacement goes where the file's versions are
+        let storage_id = existing
+            .as_ref()
+            .map_or(upload.storage_id, |existing| existing.storage_id);
 
         let file_id = Uuid::new_v4();
         let storage_path = format!("{}/{}/{}", folder.path, file_id, upload.file_name);
 
-        // Read all chunks and assemble
-        let mut assembled = Vec::with_capacity(upload.file_size as usize);
-        for chunk_num in 0..upload.total_chunks {
-            let chunk_path = format!("{}/chunk_{:06}", upload.temp_path, chunk_num);
-            let chunk_data = self
-                .storage
-                .read(&upload.storage_id, &chunk_path)
-                .await
-                .map_err(|e| {
-                    AppError::internal(format!("Failed to read chunk {chunk_num}: {e}"))
-                })?;
-            assembled.extend_from_slice(&chunk_data);
-        }
-
-        // Write assembled file
-        self.storage
-            .write(
-                &upload.storage_id,
+        // Stream the chunks in order into the assembled file, checking
+        // each against the checksum it was accepted with and hashing the
+        // whole file on the way; nothing is buffered or read back
+        let checksums: Vec<String> = (0..upload.total_chunks)
+            .map(|n| upload.chunk_checksum(n).unwrap_or_default().to_string())
+            .collect();
+        let chunk_paths: Vec<String> = (0..upload.total_chunks)
+            .map(|n| chunk_path(upload, n, &checksums[n as usize]))
+            .collect();
+        let storage = Arc::clone(&self.storage);
+        let temp_storage_id = upload.storage_id;
+        let chunks = futures::stream::iter(chunk_paths).then(move |path| {
+            let storage = Arc::clone(&storage);
+            async move {
+                storage
+                    .read(&temp_storage_id, &path)
+                    .await
+                    .map_err(|e| std::io::Error::other(format!("Failed to read {path}: {e}")))
+            }
+        });
+        let (stream, digest) = HashingStream::new(Box::pin(chunks), self.content_hasher());
+        let written = self
+            .storage
+            .write_stream(
+                &storage_id,
                 &storage_path,
-                Bytes::from(assembled.clone()),
+                stream.verify_chunks(checksums).keep_head(SNIFF_LEN).boxed(),
             )
-            .await
+            .await;
+        if let Some(chunk_num) = digest.corrupt_chunk() {
+            let _ = self.storage.delete(&storage_id, &storage_path).await;
+            return Err(AppError::internal(format!(
+                "Chunk {chunk_num} is corrupted in temporary storage; upload it again"
+            )));
+        }
+        let written = written
             .map_err(|e| AppError::internal(format!("Failed to write assembled file: {e}")))?;
+        let head = digest.head();
+        let digest = digest.finish();
+        self.verify_written(
+            &storage_id,
+            &storage_path,
+            written,
+            &digest,
+            upload.checksum_sha256.as_deref(),
+        )
+        .await?;
 
         // Create file record
         let file_record = CreateFile {
             folder_id: upload.target_folder_id,
-            storage_id: upload.storage_id,
+            storage_id,
             name: upload.file_name.clone(),
             storage_path,
-            mime_type: upload.mime_type.clone(),
-            size_bytes: assembled.len() as i64,
-            checksum_sha256: upload.checksum_sha256.clone(),
-            metadata: Some(serde_json::json!({})),
+            mime_type: self.content_type(&head, &upload.file_name, upload.mime_type.as_deref()),
+            declared_mime_type: upload.mime_type.clone(),
+            size_bytes: upload.file_size,
+            checksum_sha256: Some(digest.sha256.clone()),
+            metadata: Some(content_metadata(&digest)),
             owner_id: ctx.user_id,
         };
 
         let file = self
-            .file_repo
-            .create(&file_record)
-            .await
-            .map_err(|e| AppError::internal(format!("Failed to create file record: {e}")))?;
+            .store_record(
+                ctx,
+                &file_record,
+                strategy,
+                existing.as_ref(),
+                Some(folder.owner_id),
+            )
+            .await?;
+        self.invalidate_trees(folder.storage_id).await;
 
         // Mark upload as completed
         self.file_repo
-            .complete_chunked_upload(upload_id)
+            .complete_chunked_upload(upload.id)
             .await
             .map_err(|e| AppError::internal(format!("Failed to complete upload: {e}")))?;
 
         // Cleanup temp chunks (best effort)
-        for chunk_num in 0..upload.total_chunks {
-            let chunk_path = format!("{}/chunk_{:06}", upload.temp_path, chunk_num);
-            let _ = self.storage.delete(&upload.storage_id, &chunk_path).await;
+        if let Ok(provider) = self.storage.get(&upload.storage_id).await {
+            let _ = provider.delete_dir(&upload.temp_path).await;
         }
 
         info!(
@@ -456,10 +1195,101 @@ impl UploadService {
             "Chunked upload completed and assembled"
         );
 
-        // Fire AfterUpload hook
+        Ok(file)
+    }
+
+    /// Type a new file is stored and served with: sniffed from the leading
+    /// bytes `head` if configured, otherwise as the client declared it.
+    fn content_type(&self, head: &[u8], file_name: &str, declared: Option<&str>) -> Option<String> {
+        if self.config.sniff_content_type {
+            Some(sniff::content_type(head, file_name).to_string())
+        } else {
+            declared.map(String::from)
+        }
+    }
+
+    /// Hasher for new uploads, with CRC32C if configured.
+    fn content_hasher(&self) -> ContentHasher {
+        ContentHasher::new(self.config.crc32c)
+    }
+
+    /// Checks a freshly written file against its streamed digest: every
+    /// hashed byte must have been written, and the content must match the
+    /// checksum the client declared, if any. A file that fails is deleted.
+    async fn verify_written(
+        &self,
+        storage_id: &Uuid,
+        storage_path: &str,
+        written: u64,
+        digest: &ContentDigest,
+        expected_sha256: Option<&str>,
+    ) -> Result<(), AppError> {
+        let error = if written != digest.size_bytes {
+            AppError::internal(format!(
+                "Storage wrote {written} of {} bytes",
+                digest.size_bytes
+            ))
+        } else if let Some(expected) = expected_sha256
+            && !expected.eq_ignore_ascii_case(&digest.sha256)
+        {
+            AppError::validation(format!(
+                "File checksum mismatch: expected {expected}, got {}",
+                digest.sha256
+            ))
+        } else {
+            return Ok(());
+        };
+        let _ = self.storage.delete(storage_id, storage_path).await;
+        Err(error)
+    }
+
+    /// Loads an upload session owned by the requesting user.
+    async fn find_own_upload(
+        &self,
+        ctx: &RequestContext,
+        upload_id: Uuid,
+    ) -> Result<ChunkedUpload, AppError> {
+        let upload = self
+            .file_repo
+            .find_chunked_upload(upload_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+            .ok_or_else(|| AppError::not_found("Upload session not found"))?;
+
+        if upload.user_id != ctx.user_id {
+            return Err(AppError::forbidden(
+                "Upload session belongs to another user",
+            ));
+        }
+        Ok(upload)
+    }
+
+    /// Expiry of an upload session that received a chunk now.
+    fn upload_expiry(&self) -> DateTime<Utc> {
+        Utc::now() + chrono::Duration::hours(self.config.upload_session_ttl_hours as i64)
+    }
+
+    /// Counts of uploads being processed and waiting to be.
+    pub fn processing_stats(&self) -> ProcessingStats {
+        self.processor.stats()
+    }
+
+    /// Schedules the `AfterUpload` hook and thumbnail pre-generation,
+    /// which run once a processing slot is free. Neither can fail the
+    /// upload.
+    fn after_upload(&self, file: &File) {
+        let service = self.clone();
+        let file = file.clone();
+        self.processor
+            .spawn(async move { service.process_upload(&file).await });
+    }
+
+    /// Fires the `AfterUpload` hook and queues thumbnail pre-generation.
+    async fn process_upload(&self, file: &File) {
         let mut payload = HookPayload::new(HookPoint::AfterUpload)
             .with_uuid("file_id", file.id)
             .with_uuid("folder_id", file.folder_id)
+            .with_uuid("storage_id", file.storage_id)
             .with_uuid("owner_id", file.owner_id)
             .with_string("name", &file.name)
             .with_string("storage_path", &file.storage_path)
@@ -474,6 +1304,73 @@ impl UploadService {
             .fire_and_forget(&payload)
             .await;
 
-        Ok(file)
+        if let Err(e) = self.queue_thumbnails(file).await {
+            warn!(file_id = %file.id, error = %e, "Failed to queue thumbnail generation");
+        }
+    }
+
+    /// Queues eager thumbnail generation if enabled for the file's type
+    /// and size.
+    async fn queue_thumbnails(&self, file: &File) -> Result<(), AppError> {
+        let Some(job_repo) = &self.thumbnail_jobs else {
+            return Ok(());
+        };
+        let pregen = &self.config.thumbnail_pregen;
+        if !pregen.applies_to(file.mime_type.as_deref(), file.size_bytes.max(0) as u64) {
+            return Ok(());
+        }
+
+        let thumbnails = &self.config.thumbnails;
+        let payload = JobPayload::ThumbnailGeneration {
+            file_id: file.id,
+            sizes: pregen
+                .sizes
+                .iter()
+                .filter_map(|name| thumbnails.edge(name))
+                .collect(),
+            formats: thumbnails.formats.clone(),
+        };
+        let job = CreateJob {
+            job_type: THUMBNAIL_JOB_TYPE.to_string(),
+            queue: "default".to_string(),
+            priority: JobPriority::Low,
+            payload: serde_json::to_value(&payload)
+                .map_err(|e| AppError::internal(format!("Invalid thumbnail job: {e}")))?,
+            max_attempts: 1,
+            scheduled_at: None,
+            created_by: None,
+        };
+        job_repo
+            .create(&job)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to queue thumbnail job: {e}")))?;
+        Ok(())
+    }
+}
+
+/// Temporary path of a chunk, named by its content hash.
+fn chunk_path(upload: &ChunkedUpload, chunk_number: i32, checksum: &str) -> String {
+    format!(
+        "{}/chunk_{:06}_{}",
+        upload.temp_path, chunk_number, checksum
+    )
+}
+
+/// Accepts a re-sent chunk only if it matches what was recorded.
+fn same_chunk(chunk_number: i32, existing: &str, actual: &str) -> Result<(), AppError> {
+    if existing == actual {
+        Ok(())
+    } else {
+        Err(AppError::conflict(format!(
+            "Chunk {chunk_number} was already uploaded with different content"
+        )))
+    }
+}
+
+/// Metadata of a new upload: its CRC32C, when one was computed.
+fn content_metadata(digest: &ContentDigest) -> serde_json::Value {
+    match digest.crc32c_hex() {
+        Some(crc32c) => serde_json::json!({ "crc32c": crc32c }),
+        None => serde_json::json!({}),
     }
 }
@@ -6,9 +6,10 @@ use tracing::info;
 use uuid::Uuid;
 
 use filehub_auth::acl::EffectivePermissionResolver;
-use filehub_core::error::AppError;
+use filehub_core::error::{AppError, codes};
+use filehub_core::events::{DomainEvent, EventPayload, FileEvent};
 use filehub_database::repositories::file::FileRepository;
-use filehub_entity::file::FileVersion;
+use filehub_entity::file::{File, FileVersion, FileVersionInfo};
 use filehub_entity::permission::{AclPermission, ResourceType};
 
 use crate::context::RequestContext;
@@ -34,18 +35,21 @@ impl VersionService {
         }
     }
 
-    /// Lists all versions of a file.
-    pub async fn list_versions(
+    /// Lists all versions of a file, newest first, with who created each.
+    #[tracing::instrument(target = "otel", name = "VersionService::list", skip_all)]
+    pub async fn list(
         &self,
         ctx: &RequestContext,
         file_id: Uuid,
-    ) -> Result<Vec<FileVersion>, AppError> {
+    ) -> Result<Vec<FileVersionInfo>, AppError> {
         let file = self
             .file_repo
             .find_by_id(file_id)
             .await
             .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .ok_or_else(|| AppError::not_found("File not found"))?;
+            .ok_or_else(|| {
+                AppError::not_found("File not found").with_code(codes::FILE_NOT_FOUND)
+            })?;
 
         self.perm_resolver
             .require_permission(
@@ -60,12 +64,13 @@ impl VersionService {
             .await?;
 
         self.file_repo
-            .find_versions(file_id)
+            .find_version_history(file_id)
             .await
             .map_err(|e| AppError::internal(format!("Failed to list versions: {e}")))
     }
 
     /// Creates a new version snapshot of the current file state.
+    #[tracing::instrument(target = "otel", name = "VersionService::create_version", skip_all)]
     pub async fn create_version(
         &self,
         ctx: &RequestContext,
@@ -77,7 +82,9 @@ impl VersionService {
             .find_by_id(file_id)
             .await
             .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .ok_or_else(|| AppError::not_found("File not found"))?;
+            .ok_or_else(|| {
+                AppError::not_found("File not found").with_code(codes::FILE_NOT_FOUND)
+            })?;
 
         self.perm_resolver
             .require_permission(
@@ -120,4 +127,80 @@ impl VersionService {
 
         Ok(version)
     }
+
+    /// Makes an earlier version the current content of the file.
+    ///
+    /// History is kept: the content being replaced becomes a version of
+    /// its own, and the file gets a new version number.
+    #[tracing::instrument(target = "otel", name = "VersionService::restore", skip_all)]
+    pub async fn restore(
+        &self,
+        ctx: &RequestContext,
+        file_id: Uuid,
+        version_id: Uuid,
+    ) -> Result<File, AppError> {
+        let file = self
+            .file_repo
+            .find_by_id(file_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+            .ok_or_else(|| {
+                AppError::not_found("File not found").with_code(codes::FILE_NOT_FOUND)
+            })?;
+
+        self.perm_resolver
+            .require_permission(
+                ctx.user_id,
+                &ctx.role,
+                ResourceType::File,
+                file_id,
+                file.owner_id,
+                Some(file.folder_id),
+                AclPermission::Editor,
+            )
+            .await?;
+
+        if file.is_locked.unwrap_or(false) && file.locked_by != Some(ctx.user_id) && !ctx.is_admin()
+        {
+            return Err(AppError::conflict("File is locked by another user"));
+        }
+
+        let version = self
+            .file_repo
+            .find_version_by_id(version_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+            .filter(|v| v.file_id == file_id)
+            .ok_or_else(|| AppError::not_found("Version not found"))?;
+
+        let comment = format!("Replaced by restore of version {}", version.version_number);
+        let restored_version = version.version_number;
+        let restored = self
+            .file_repo
+            .restore_version(file_id, &version, ctx.user_id, &comment, |restored| {
+                DomainEvent::new(
+                    Some(ctx.user_id),
+                    EventPayload::File(FileEvent::VersionRestored {
+                        file_id,
+                        restored_version,
+                        version_number: restored.current_version,
+                    }),
+                )
+            })
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to restore version: {e}")))?
+            .ok_or_else(|| {
+                AppError::not_found("File not found").with_code(codes::FILE_NOT_FOUND)
+            })?;
+
+        info!(
+            user_id = %ctx.user_id,
+            file_id = %file_id,
+            restored_version = version.version_number,
+            version = restored.current_version,
+            "File version restored"
+        );
+
+        Ok(restored)
+    }
 }
@@ -1,7 +1,13 @@
 //! Folder management and tree services.
 
+pub mod preferences;
+pub mod retention;
 pub mod service;
 pub mod tree;
+pub mod tree_cache;
 
+pub use preferences::FolderPreferenceStore;
+pub use retention::RetentionService;
 pub use service::FolderService;
 pub use tree::TreeService;
+pub use tree_cache::FolderTreeCache;
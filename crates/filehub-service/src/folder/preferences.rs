@@ -0,0 +1,141 @@
+//! Cache-backed store of users' saved folder views.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use tracing::debug;
+use uuid::Uuid;
+
+use filehub_cache::keys;
+use filehub_cache::provider::CacheManager;
+use filehub_core::error::AppError;
+use filehub_core::traits::cache::CacheProvider;
+use filehub_database::repositories::FolderPreferenceRepository;
+use filehub_entity::folder::{FolderPreferences, FolderSort, UpdateFolderPreferences};
+
+/// Users' saved folder views, read through the cache.
+///
+/// Listings look up the caller's preferences on every request, so the
+/// absence of preferences is cached too. Reads fill the cache only where
+/// it holds nothing and writes overwrite it, so a read racing a write
+/// cannot cache what the write replaced.
+#[derive(Debug, Clone)]
+pub struct FolderPreferenceStore {
+    /// Preference repository.
+    repo: Arc<FolderPreferenceRepository>,
+    /// Cache backend; `None` reads the database every time.
+    cache: Option<Arc<CacheManager>>,
+}
+
+impl FolderPreferenceStore {
+    /// Creates a preference store.
+    pub fn new(repo: Arc<FolderPreferenceRepository>) -> Self {
+        Self { repo, cache: None }
+    }
+
+    /// Caches preferences in `cache`.
+    pub fn with_cache(mut self, cache: Arc<CacheManager>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// A user's preferences for a folder, if they saved any.
+    pub async fn get(
+        &self,
+        user_id: Uuid,
+        folder_id: Uuid,
+    ) -> Result<Option<FolderPreferences>, AppError> {
+        let key = keys::folder_preferences(user_id, folder_id);
+        if let Some(cache) = &self.cache
+            && let Ok(Some(cached)) = cache.get(&key).await
+            && let Ok(preferences) = serde_json::from_str(&cached)
+        {
+            return Ok(preferences);
+        }
+
+        let preferences =
+            self.repo.find(user_id, folder_id).await.map_err(|e| {
+                AppError::internal(format!("Failed to load folder preferences: {e}"))
+            })?;
+        if let (Some(cache), Ok(serialized)) = (&self.cache, serde_json::to_string(&preferences)) {
+            let _ = cache.put_nx(&key, &serialized).await;
+        }
+        Ok(preferences)
+    }
+
+    /// The order to list a folder in for a user: `requested` if the
+    /// listing asked for one, else the user's saved order, else by name.
+    /// The saved order is best effort; failing to load it lists by name.
+    pub async fn sort_for(
+        &self,
+        user_id: Uuid,
+        folder_id: Uuid,
+        requested: Option<FolderSort>,
+    ) -> FolderSort {
+        if let Some(sort) = requested {
+            return sort;
+        }
+        match self.get(user_id, folder_id).await {
+            Ok(preferences) => preferences.map(|p| p.sort()).unwrap_or_default(),
+            Err(e) => {
+                debug!(%user_id, %folder_id, error = %e, "Listing without saved sort order");
+                FolderSort::default()
+            }
+        }
+    }
+
+    /// Saves a user's preferences for a folder, keeping saved values of
+    /// fields `update` leaves unset.
+    pub async fn set(
+        &self,
+        user_id: Uuid,
+        folder_id: Uuid,
+        update: UpdateFolderPreferences,
+    ) -> Result<FolderPreferences, AppError> {
+        let current = self.get(user_id, folder_id).await?;
+        let sort = current.as_ref().map(|p| p.sort()).unwrap_or_default();
+        let preferences = FolderPreferences {
+            user_id,
+            folder_id,
+            sort_field: update.sort_field.unwrap_or(sort.field),
+            sort_direction: update.sort_direction.unwrap_or(sort.direction),
+            view_mode: update
+                .view_mode
+                .or(current.map(|p| p.view_mode))
+                .unwrap_or_default(),
+            updated_at: Utc::now(),
+        };
+        let saved =
+            self.repo.upsert(&preferences).await.map_err(|e| {
+                AppError::internal(format!("Failed to save folder preferences: {e}"))
+            })?;
+        self.store(user_id, folder_id, &Some(saved.clone())).await;
+        Ok(saved)
+    }
+
+    /// Forgets a user's preferences for a folder. Returns whether there
+    /// were any.
+    pub async fn clear(&self, user_id: Uuid, folder_id: Uuid) -> Result<bool, AppError> {
+        let deleted =
+            self.repo.delete(user_id, folder_id).await.map_err(|e| {
+                AppError::internal(format!("Failed to delete folder preferences: {e}"))
+            })?;
+        self.store(user_id, folder_id, &None).await;
+        Ok(deleted)
+    }
+
+    /// Overwrites the cached preferences.
+    async fn store(&self, user_id: Uuid, folder_id: Uuid, preferences: &Option<FolderPreferences>) {
+        let Some(cache) = &self.cache else {
+            return;
+        };
+        let key = keys::folder_preferences(user_id, folder_id);
+        let result = match serde_json::to_string(preferences) {
+            Ok(serialized) => cache.put(&key, &serialized).await,
+            Err(_) => cache.delete(&key).await,
+        };
+        if let Err(e) = result {
+            debug!(%user_id, %folder_id, error = %e, "Failed to cache folder preferences");
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! Folder retention policies — minimum retention periods and legal holds
+//! that block deletion, for records management.
+//!
+//! A policy covers its folder and everything below it, including folders
+//! created later (see [`EffectiveRetention`]). Deletes refused because of
+//! a policy are audited, as are policy changes.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use uuid::Uuid;
+
+use filehub_core::error::{AppError, codes};
+use filehub_database::repositories::folder::FolderRepository;
+use filehub_database::repositories::retention::RetentionPolicyRepository;
+use filehub_entity::file::File;
+use filehub_entity::folder::{EffectiveRetention, Folder, RetentionBlock, RetentionPolicy};
+
+use crate::context::RequestContext;
+use crate::session::SessionAudit;
+
+/// Longest retention period accepted, in days (100 years).
+const MAX_RETENTION_DAYS: i32 = 36_500;
+
+/// Manages retention policies and checks deletes against them.
+#[derive(Debug, Clone)]
+pub struct RetentionService {
+    /// Retention policy repository.
+    retention_repo: Arc<RetentionPolicyRepository>,
+    /// Folder repository.
+    folder_repo: Arc<FolderRepository>,
+    /// Audit log for policy changes and refused deletes.
+    audit: Arc<SessionAudit>,
+}
+
+/// Request to attach or replace a folder's retention policy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetRetentionRequest {
+    /// Days a file must be kept after it was created; 0 for none.
+    #[serde(default)]
+    pub retention_days: i32,
+    /// Block all deletion until the hold is lifted.
+    #[serde(default)]
+    pub legal_hold: bool,
+    /// Why the hold is placed; required with `legal_hold`.
+    #[serde(default)]
+    pub legal_hold_reason: Option<String>,
+}
+
+/// A folder's own policy and the retention in force for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderRetention {
+    /// Policy attached to the folder itself.
+    pub policy: Option<RetentionPolicy>,
+    /// Longest retention period from the folder and its ancestors.
+    pub retention_days: i32,
+    /// Folder whose legal hold covers this one, if any.
+    pub legal_hold_folder_id: Option<Uuid>,
+}
+
+impl RetentionService {
+    /// Creates a new retention service.
+    pub fn new(
+        retention_repo: Arc<RetentionPolicyRepository>,
+        folder_repo: Arc<FolderRepository>,
+        audit: Arc<SessionAudit>,
+    ) -> Self {
+        Self {
+            retention_repo,
+            folder_repo,
+            audit,
+        }
+    }
+
+    /// Returns a folder's policy and the retention in force for it.
+    #[tracing::instrument(target = "otel", name = "RetentionService::get", skip_all)]
+    pub async fn get(
+        &self,
+        ctx: &RequestContext,
+        folder_id: Uuid,
+    ) -> Result<FolderRetention, AppError> {
+        require_admin(ctx)?;
+        self.find_folder(folder_id).await?;
+
+        let inherited = self.retention_repo.find_inherited(folder_id).await?;
+        let effective = EffectiveRetention::combine(&inherited);
+        Ok(FolderRetention {
+            policy: inherited.into_iter().find(|p| p.folder_id == folder_id),
+            retention_days: effective.retention_days,
+            legal_hold_folder_id: effective.legal_hold,
+        })
+    }
+
+    /// Attaches a policy to a folder, replacing any it has.
+    #[tracing::instrument(target = "otel", name = "RetentionService::set", skip_all)]
+    pub async fn set(
+        &self,
+        ctx: &RequestContext,
+        folder_id: Uuid,
+        req: SetRetentionRequest,
+    ) -> Result<RetentionPolicy, AppError> {
+        require_admin(ctx)?;
+        if !(0..=MAX_RETENTION_DAYS).contains(&req.retention_days) {
+            return Err(AppError::validation(format!(
+                "Retention must be between 0 and {MAX_RETENTION_DAYS} days"
+            )));
+        }
+        let reason = req
+            .legal_hold_reason
+            .as_deref()
+            .map(str::trim)
+            .filter(|r| !r.is_empty());
+        if req.legal_hold && reason.is_none() {
+            return Err(AppError::validation(
+                "A reason is required for a legal hold",
+            ));
+        }
+        self.find_folder(folder_id).await?;
+
+        let before = self.retention_repo.find(folder_id).await?;
+        let policy = self
+            .retention_repo
+            .upsert(
+                folder_id,
+                req.retention_days,
+                req.legal_hold,
+                reason.filter(|_| req.legal_hold),
+                ctx.user_id,
+            )
+            .await?;
+        self.audit
+            .log_update(
+                ctx,
+                "folder.retention_updated",
+                "folder",
+                folder_id,
+                &before,
+                &Some(policy.clone()),
+            )
+            .await;
+
+        info!(
+            admin_id = %ctx.user_id,
+            folder_id = %folder_id,
+            retention_days = policy.retention_days,
+            legal_hold = policy.legal_hold,
+            "Retention policy set"
+        );
+        Ok(policy)
+    }
+
+    /// Detaches the policy from a folder.
+    #[tracing::instrument(target = "otel", name = "RetentionService::remove", skip_all)]
+    pub async fn remove(&self, ctx: &RequestContext, folder_id: Uuid) -> Result<(), AppError> {
+        require_admin(ctx)?;
+        let before = self
+            .retention_repo
+            .find(folder_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Folder has no retention policy"))?;
+        self.retention_repo.delete(folder_id).await?;
+        self.audit
+            .log_update(
+                ctx,
+                "folder.retention_removed",
+                "folder",
+                folder_id,
+                &Some(before),
+                &None,
+            )
+            .await;
+
+        info!(admin_id = %ctx.user_id, folder_id = %folder_id, "Retention policy removed");
+        Ok(())
+    }
+
+    /// Refuses deleting `file` while it is retained.
+    #[tracing::instrument(
+        target = "otel",
+        name = "RetentionService::check_file_delete",
+        skip_all
+    )]
+    pub async fn check_file_delete(
+        &self,
+        ctx: &RequestContext,
+        file: &File,
+    ) -> Result<(), AppError> {
+        let inherited = self.retention_repo.find_inherited(file.folder_id).await?;
+        let effective = EffectiveRetention::combine(&inherited);
+        match effective.blocks(file.created_at, Utc::now()) {
+            None => Ok(()),
+            Some(block) => Err(self.refuse(ctx, "file", file.id, block).await),
+        }
+    }
+
+    /// Refuses deleting `folder` while it or anything below it holds a
+    /// retained file, or while it is under legal hold.
+    #[tracing::instrument(
+        target = "otel",
+        name = "RetentionService::check_folder_delete",
+        skip_all
+    )]
+    pub async fn check_folder_delete(
+        &self,
+        ctx: &RequestContext,
+        folder: &Folder,
+    ) -> Result<(), AppError> {
+        let inherited = self.retention_repo.find_inherited(folder.id).await?;
+        let descendants = self.folder_repo.find_descendants(folder.id).await?;
+        let descendant_ids: Vec<Uuid> = descendants.iter().map(|f| f.id).collect();
+        let own: HashMap<Uuid, RetentionPolicy> = self
+            .retention_repo
+            .find_for_folders(&descendant_ids)
+            .await?
+            .into_iter()
+            .map(|p| (p.folder_id, p))
+            .collect();
+
+        // Parents come before children, so each folder's parent is
+        // resolved by the time it is reached
+        let mut effective = HashMap::new();
+        effective.insert(folder.id, EffectiveRetention::combine(&inherited));
+        for child in &descendants {
+            let parent = child
+                .parent_id
+                .and_then(|id| effective.get(&id))
+                .cloned()
+                .unwrap_or_default();
+            let combined = match own.get(&child.id) {
+                Some(policy) => parent.and(policy),
+                None => parent,
+            };
+            effective.insert(child.id, combined);
+        }
+
+        if let Some(holder) = effective.values().find_map(|e| e.legal_hold) {
+            return Err(self
+                .refuse(ctx, "folder", folder.id, RetentionBlock::LegalHold(holder))
+                .await);
+        }
+
+        let retained: Vec<Uuid> = effective
+            .iter()
+            .filter(|(_, e)| !e.is_none())
+            .map(|(id, _)| *id)
+            .collect();
+        if retained.is_empty() {
+            return Ok(());
+        }
+        let now = Utc::now();
+        let block = self
+            .retention_repo
+            .newest_files(&retained)
+            .await?
+            .into_iter()
+            .filter_map(|(folder_id, newest)| effective.get(&folder_id)?.blocks(newest, now))
+            .max_by_key(|block| match block {
+                RetentionBlock::RetainedUntil(until) => Some(*until),
+                RetentionBlock::LegalHold(_) => None,
+            });
+        match block {
+            None => Ok(()),
+            Some(block) => Err(self.refuse(ctx, "folder", folder.id, block).await),
+        }
+    }
+
+    /// Audits a refused delete and returns the error for it.
+    async fn refuse(
+        &self,
+        ctx: &RequestContext,
+        target_type: &str,
+        target_id: Uuid,
+        block: RetentionBlock,
+    ) -> AppError {
+        let (details, error) = match block {
+            RetentionBlock::LegalHold(folder_id) => (
+                serde_json::json!({ "reason": "legal_hold", "hold_folder_id": folder_id }),
+                AppError::conflict("Deletion is blocked by a legal hold")
+                    .with_code(codes::RETENTION_LEGAL_HOLD),
+            ),
+            RetentionBlock::RetainedUntil(until) => (
+                serde_json::json!({ "reason": "retention", "retained_until": until }),
+                AppError::conflict(format!(
+                    "Deletion is blocked by a retention policy until {}",
+                    until.to_rfc3339()
+                ))
+                .with_code(codes::RETENTION_ACTIVE),
+            ),
+        };
+        if let Err(e) = self
+            .audit
+            .log_event(
+                ctx.user_id,
+                &format!("{target_type}.delete_blocked"),
+                target_type,
+                Some(target_id),
+                Some(details),
+                Some(&ctx.ip_address),
+                ctx.user_agent.as_deref(),
+            )
+            .await
+        {
+            tracing::warn!(error = %e, target_id = %target_id, "Failed to audit blocked delete");
+        }
+        info!(
+            user_id = %ctx.user_id,
+            target_type,
+            target_id = %target_id,
+            "Delete blocked by retention"
+        );
+        error
+    }
+
+    async fn find_folder(&self, folder_id: Uuid) -> Result<Folder, AppError> {
+        self.folder_repo
+            .find_by_id(folder_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found("Folder not found").with_code(codes::FOLDER_NOT_FOUND)
+            })
+    }
+}
+
+fn require_admin(ctx: &RequestContext) -> Result<(), AppError> {
+    if ctx.is_admin() {
+        Ok(())
+    } else {
+        Err(AppError::forbidden(
+            "Only admins can manage retention policies",
+        ))
+    }
+}
@@ -8,13 +8,24 @@ use tracing::info;
 use uuid::Uuid;
 
 use filehub_auth::acl::EffectivePermissionResolver;
-use filehub_core::error::AppError;
+use filehub_core::error::{AppError, codes};
+use filehub_core::events::{DomainEvent, EventPayload, FolderEvent};
 use filehub_database::repositories::folder::FolderRepository;
 use filehub_database::repositories::storage::StorageRepository;
-use filehub_entity::folder::{CreateFolder, Folder};
+use filehub_entity::folder::{
+    CreateFolder, Folder, FolderMetadata, FolderPreferences, FolderSort, UpdateFolderPreferences,
+    rebase_subtree,
+};
 use filehub_entity::permission::{AclPermission, ResourceType};
+use filehub_entity::user::role::UserRole;
 
 use crate::context::RequestContext;
+use crate::permission::PermissionTemplateService;
+use crate::session::SessionAudit;
+
+use super::preferences::FolderPreferenceStore;
+use super::retention::RetentionService;
+use super::tree_cache::FolderTreeCache;
 
 /// Manages folder CRUD operations.
 #[derive(Debug, Clone)]
@@ -25,6 +36,19 @@ pub struct FolderService {
     storage_repo: Arc<StorageRepository>,
     /// Permission resolver.
     perm_resolver: Arc<EffectivePermissionResolver>,
+    /// Audit log, for field changes.
+    audit: Arc<SessionAudit>,
+    /// Cached folder trees, dropped on every change.
+    tree_cache: Option<FolderTreeCache>,
+    /// Applies parents' permission templates to new folders; `None`
+    /// leaves new folders with inherited entries only.
+    templates: Option<PermissionTemplateService>,
+    /// Refuses deleting folders holding retained files; `None` deletes
+    /// freely.
+    retention: Option<RetentionService>,
+    /// Users' saved folder views; `None` lists by name unless asked
+    /// otherwise.
+    preferences: Option<FolderPreferenceStore>,
 }
 
 /// Request to create a new folder.
@@ -51,15 +75,55 @@ impl FolderService {
         folder_repo: Arc<FolderRepository>,
         storage_repo: Arc<StorageRepository>,
         perm_resolver: Arc<EffectivePermissionResolver>,
+        audit: Arc<SessionAudit>,
     ) -> Self {
         Self {
             folder_repo,
             storage_repo,
             perm_resolver,
+            audit,
+            tree_cache: None,
+            templates: None,
+            retention: None,
+            preferences: None,
+        }
+    }
+
+    /// Applies the permission template attached to a folder's parent when
+    /// the folder is created.
+    pub fn with_permission_templates(mut self, templates: PermissionTemplateService) -> Self {
+        self.templates = Some(templates);
+        self
+    }
+
+    /// Refuses deleting folders that hold files under a retention policy
+    /// or legal hold.
+    pub fn with_retention(mut self, retention: RetentionService) -> Self {
+        self.retention = Some(retention);
+        self
+    }
+
+    /// Drops cached folder trees of a storage whenever its folders change.
+    pub fn with_tree_cache(mut self, tree_cache: FolderTreeCache) -> Self {
+        self.tree_cache = Some(tree_cache);
+        self
+    }
+
+    /// Saves users' folder views and lists folders in their saved order.
+    pub fn with_preferences(mut self, preferences: FolderPreferenceStore) -> Self {
+        self.preferences = Some(preferences);
+        self
+    }
+
+    /// Drops the cached folder trees of a storage.
+    async fn invalidate_trees(&self, storage_id: Uuid) {
+        if let Some(cache) = &self.tree_cache {
+            cache.invalidate(storage_id).await;
         }
     }
 
     /// Lists root folders for a storage.
+    #[tracing::instrument(target = "otel", name = "FolderService::list_root_folders", skip_all)]
     pub async fn list_root_folders(
         &self,
         _ctx: &RequestContext,
@@ -72,6 +136,7 @@ impl FolderService {
     }
 
     /// Gets a folder by ID.
+    #[tracing::instrument(target = "otel", name = "FolderService::get_folder", skip_all)]
     pub async fn get_folder(
         &self,
         ctx: &RequestContext,
@@ -82,7 +147,9 @@ impl FolderService {
             .find_by_id(folder_id)
             .await
             .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .ok_or_else(|| AppError::not_found("Folder not found"))?;
+            .ok_or_else(|| {
+                AppError::not_found("Folder not found").with_code(codes::FOLDER_NOT_FOUND)
+            })?;
 
         self.perm_resolver
             .require_permission(
@@ -99,20 +166,140 @@ impl FolderService {
         Ok(folder)
     }
 
-    /// Lists children of a folder.
+    /// Whether another user may view a folder, e.g. to show them as a
+    /// co-viewer. Resolution errors count as no.
+    #[tracing::instrument(target = "otel", name = "FolderService::can_view", skip_all)]
+    pub async fn can_view(&self, folder: &Folder, user_id: Uuid, role: &UserRole) -> bool {
+        self.perm_resolver
+            .resolve(
+                user_id,
+                role,
+                ResourceType::Folder,
+                folder.id,
+                folder.owner_id,
+                folder.parent_id,
+                AclPermission::Viewer,
+            )
+            .await
+            .is_ok_and(|p| p.granted)
+    }
+
+    /// Lists children of a folder, in the `sort` order or else the
+    /// caller's saved one.
+    #[tracing::instrument(target = "otel", name = "FolderService::list_children", skip_all)]
     pub async fn list_children(
         &self,
-        _ctx: &RequestContext,
+        ctx: &RequestContext,
         folder_id: Uuid,
         page: PageRequest,
+        sort: Option<FolderSort>,
     ) -> Result<PageResponse<Folder>, AppError> {
+        let sort = match &self.preferences {
+            Some(preferences) => preferences.sort_for(ctx.user_id, folder_id, sort).await,
+            None => sort.unwrap_or_default(),
+        };
         self.folder_repo
-            .find_children(folder_id, &page)
+            .find_children(folder_id, &page, &sort)
             .await
             .map_err(|e| AppError::internal(format!("Failed to list children: {e}")))
     }
 
+    /// Gets a folder's display metadata.
+    #[tracing::instrument(target = "otel", name = "FolderService::get_metadata", skip_all)]
+    pub async fn get_metadata(
+        &self,
+        ctx: &RequestContext,
+        folder_id: Uuid,
+    ) -> Result<FolderMetadata, AppError> {
+        let folder = self.get_folder(ctx, folder_id).await?;
+        Ok(folder
+            .metadata
+            .as_ref()
+            .map(FolderMetadata::from_json_value)
+            .unwrap_or_default())
+    }
+
+    /// Replaces a folder's display metadata. Needs editor access; the
+    /// color and icon must be from the allowed sets.
+    #[tracing::instrument(target = "otel", name = "FolderService::set_metadata", skip_all)]
+    pub async fn set_metadata(
+        &self,
+        ctx: &RequestContext,
+        folder_id: Uuid,
+        metadata: FolderMetadata,
+    ) -> Result<Folder, AppError> {
+        metadata.validate()?;
+        let before = self.get_folder(ctx, folder_id).await?;
+        self.perm_resolver
+            .require_permission(
+                ctx.user_id,
+                &ctx.role,
+                ResourceType::Folder,
+                folder_id,
+                before.owner_id,
+                before.parent_id,
+                AclPermission::Editor,
+            )
+            .await?;
+
+        let value = (!metadata.is_empty()).then(|| metadata.to_json_value());
+        let folder = self
+            .folder_repo
+            .set_metadata(folder_id, value.as_ref())
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to update folder: {e}")))?;
+        self.audit
+            .log_update(ctx, "folder.updated", "folder", folder_id, &before, &folder)
+            .await;
+        Ok(folder)
+    }
+
+    /// Gets the caller's saved view of a folder, if any.
+    #[tracing::instrument(target = "otel", name = "FolderService::get_preferences", skip_all)]
+    pub async fn get_preferences(
+        &self,
+        ctx: &RequestContext,
+        folder_id: Uuid,
+    ) -> Result<Option<FolderPreferences>, AppError> {
+        let store = self.preference_store()?;
+        self.get_folder(ctx, folder_id).await?;
+        store.get(ctx.user_id, folder_id).await
+    }
+
+    /// Saves the caller's view of a folder.
+    #[tracing::instrument(target = "otel", name = "FolderService::set_preferences", skip_all)]
+    pub async fn set_preferences(
+        &self,
+        ctx: &RequestContext,
+        folder_id: Uuid,
+        update: UpdateFolderPreferences,
+    ) -> Result<FolderPreferences, AppError> {
+        let store = self.preference_store()?;
+        self.get_folder(ctx, folder_id).await?;
+        store.set(ctx.user_id, folder_id, update).await
+    }
+
+    /// Forgets the caller's view of a folder.
+    #[tracing::instrument(target = "otel", name = "FolderService::clear_preferences", skip_all)]
+    pub async fn clear_preferences(
+        &self,
+        ctx: &RequestContext,
+        folder_id: Uuid,
+    ) -> Result<bool, AppError> {
+        let store = self.preference_store()?;
+        self.get_folder(ctx, folder_id).await?;
+        store.clear(ctx.user_id, folder_id).await
+    }
+
+    /// The preference store, if preferences are kept.
+    fn preference_store(&self) -> Result<&FolderPreferenceStore, AppError> {
+        self.preferences
+            .as_ref()
+            .ok_or_else(|| AppError::service_unavailable("Folder preferences are not available"))
+    }
+
     /// Creates a new folder.
+    #[tracing::instrument(target = "otel", name = "FolderService::create_folder", skip_all)]
     pub async fn create_folder(
         &self,
         ctx: &RequestContext,
@@ -130,8 +317,9 @@ impl FolderService {
             .ok_or_else(|| AppError::not_found("Storage not found"))?;
 
         // Determine path and depth
+        let mut parent = None;
         let (path, depth) = if let Some(parent_id) = req.parent_id {
-            let parent = self.get_folder(ctx, parent_id).await?;
+            let found = self.get_folder(ctx, parent_id).await?;
 
             // Check editor permission on parent
             self.perm_resolver
@@ -140,14 +328,16 @@ impl FolderService {
                     &ctx.role,
                     ResourceType::Folder,
                     parent_id,
-                    parent.owner_id,
-                    parent.parent_id,
+                    found.owner_id,
+                    found.parent_id,
                     AclPermission::Editor,
                 )
                 .await?;
 
-            let path = format!("{}/{}", parent.path, req.name);
-            (path, parent.depth + 1)
+            let path = format!("{}/{}", found.path, req.name);
+            let depth = found.depth + 1;
+            parent = Some(found);
+            (path, depth)
         } else {
             (format!("/{}", req.name), 0)
         };
@@ -180,6 +370,11 @@ impl FolderService {
             .create(&folder_record)
             .await
             .map_err(|e| AppError::internal(format!("Failed to create folder: {e}")))?;
+        self.invalidate_trees(folder.storage_id).await;
+
+        if let (Some(templates), Some(parent)) = (&self.templates, &parent) {
+            templates.apply_inherited(ctx, parent, &folder).await?;
+        }
 
         info!(
             user_id = %ctx.user_id,
@@ -192,6 +387,7 @@ impl FolderService {
     }
 
     /// Renames / updates a folder.
+    #[tracing::instrument(target = "otel", name = "FolderService::update_folder", skip_all)]
     pub async fn update_folder(
         &self,
         ctx: &RequestContext,
@@ -216,6 +412,7 @@ impl FolderService {
             )
             .await?;
 
+        let before = folder.clone();
         // Update path
         let old_path = folder.path.clone();
         folder.name = new_name.to_string();
@@ -238,6 +435,10 @@ impl FolderService {
             .update_children_paths(&old_path, &folder.path)
             .await
             .map_err(|e| AppError::internal(format!("Failed to update child paths: {e}")))?;
+        self.invalidate_trees(folder.storage_id).await;
+        self.audit
+            .log_update(ctx, "folder.updated", "folder", folder_id, &before, &folder)
+            .await;
 
         info!(
             user_id = %ctx.user_id,
@@ -249,14 +450,22 @@ impl FolderService {
         Ok(folder)
     }
 
-    /// Moves a folder to a new parent.
+    /// Moves a folder, with everything below it, to a new parent.
+    ///
+    /// The caller needs editor access to both the folder and the
+    /// destination. Moving a folder into itself or one of its descendants,
+    /// or to another storage, is refused. The paths and depths of the whole
+    /// subtree are rewritten in one transaction, cached permissions of the
+    /// moved folders and their files are dropped (their inherited ACLs
+    /// change), and a `folder.moved` event is published for the subtree root.
+    #[tracing::instrument(target = "otel", name = "FolderService::move_folder", skip_all)]
     pub async fn move_folder(
         &self,
         ctx: &RequestContext,
         folder_id: Uuid,
         req: MoveFolderRequest,
     ) -> Result<Folder, AppError> {
-        let mut folder = self.get_folder(ctx, folder_id).await?;
+        let folder = self.get_folder(ctx, folder_id).await?;
 
         // Cannot move to self
         if folder_id == req.new_parent_id {
@@ -282,7 +491,9 @@ impl FolderService {
             .find_by_id(req.new_parent_id)
             .await
             .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .ok_or_else(|| AppError::not_found("Target folder not found"))?;
+            .ok_or_else(|| {
+                AppError::not_found("Target folder not found").with_code(codes::FOLDER_NOT_FOUND)
+            })?;
 
         self.perm_resolver
             .require_permission(
@@ -296,6 +507,12 @@ impl FolderService {
             )
             .await?;
 
+        if target.storage_id != folder.storage_id {
+            return Err(AppError::validation(
+                "Cannot move a folder to a different storage",
+            ));
+        }
+
         // Check for circular reference
         let target_ancestors = self
             .folder_repo
@@ -309,34 +526,70 @@ impl FolderService {
             ));
         }
 
-        let old_path = folder.path.clone();
-        folder.parent_id = Some(req.new_parent_id);
-        folder.path = format!("{}/{}", target.path, folder.name);
-        folder.depth = target.depth + 1;
-        folder.updated_at = Utc::now();
-
-        self.folder_repo
-            .update(&folder)
+        let descendants = self
+            .folder_repo
+            .find_descendants(folder_id)
             .await
-            .map_err(|e| AppError::internal(format!("Failed to move folder: {e}")))?;
+            .map_err(|e| AppError::internal(format!("Failed to get descendants: {e}")))?;
+        let rebased = rebase_subtree(&folder, &descendants, Some(&target));
 
-        // Update child paths
-        self.folder_repo
-            .update_children_paths(&old_path, &folder.path)
-            .await
-            .map_err(|e| AppError::internal(format!("Failed to update child paths: {e}")))?;
+        let event = DomainEvent::new(
+            Some(ctx.user_id),
+            EventPayload::Folder(FolderEvent::Moved {
+                folder_id,
+                name: folder.name.clone(),
+                from_parent_id: folder.parent_id,
+                to_parent_id: Some(req.new_parent_id),
+                descendant_count: descendants.len() as u64,
+            }),
+        );
+        let before = folder.clone();
+        let moved = self
+            .folder_repo
+            .move_subtree(folder_id, Some(req.new_parent_id), &rebased, &event)
+            .await?;
+        self.invalidate_trees(moved.storage_id).await;
+        self.audit
+            .log_update(ctx, "folder.moved", "folder", folder_id, &before, &moved)
+            .await;
+
+        let folder_ids: Vec<Uuid> = rebased.iter().map(|r| r.id).collect();
+        self.invalidate_permissions(&folder_ids).await;
 
         info!(
             user_id = %ctx.user_id,
             folder_id = %folder_id,
             new_parent = %req.new_parent_id,
+            descendants = descendants.len(),
             "Folder moved"
         );
 
-        Ok(folder)
+        Ok(moved)
+    }
+
+    /// Drops cached permissions of `folder_ids` and the files in them.
+    async fn invalidate_permissions(&self, folder_ids: &[Uuid]) {
+        for folder_id in folder_ids {
+            let _ = self
+                .perm_resolver
+                .invalidate_resource_cache(ResourceType::Folder, *folder_id)
+                .await;
+        }
+        match self.folder_repo.find_file_ids_in(folder_ids).await {
+            Ok(file_ids) => {
+                for file_id in file_ids {
+                    let _ = self
+                        .perm_resolver
+                        .invalidate_resource_cache(ResourceType::File, file_id)
+                        .await;
+                }
+            }
+            Err(e) => tracing::warn!(error = %e, "Failed to list files of moved folders"),
+        }
     }
 
     /// Deletes a folder and all its contents.
+    #[tracing::instrument(target = "otel", name = "FolderService::delete_folder", skip_all)]
     pub async fn delete_folder(
         &self,
         ctx: &RequestContext,
@@ -355,11 +608,15 @@ impl FolderService {
                 AclPermission::Owner,
             )
             .await?;
+        if let Some(retention) = &self.retention {
+            retention.check_folder_delete(ctx, &folder).await?;
+        }
 
         self.folder_repo
             .delete(folder_id)
             .await
             .map_err(|e| AppError::internal(format!("Failed to delete folder: {e}")))?;
+        self.invalidate_trees(folder.storage_id).await;
 
         info!(
             user_id = %ctx.user_id,
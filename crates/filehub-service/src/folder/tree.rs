@@ -2,81 +2,136 @@
 
 use std::sync::Arc;
 
+use futures::stream::{self, BoxStream, StreamExt};
 use uuid::Uuid;
 
-use filehub_core::error::AppError;
+use filehub_core::error::{AppError, codes};
 use filehub_database::repositories::folder::FolderRepository;
-use filehub_entity::folder::{Folder, FolderNode};
+use filehub_entity::folder::{Folder, FolderTree, FolderTreeRow};
 
 use crate::context::RequestContext;
 
+use super::tree_cache::FolderTreeCache;
+
+/// Most folders in a tree returned by [`TreeService::get_tree`]; larger
+/// trees are truncated, deepest levels first.
+pub const MAX_TREE_NODES: usize = 10_000;
+
+/// Levels of a folder tree, the root alone first, then each level's
+/// children.
+pub type TreeLevels = BoxStream<'static, Result<Vec<FolderTreeRow>, AppError>>;
+
 /// Builds folder trees and resolves paths.
 #[derive(Debug, Clone)]
 pub struct TreeService {
     /// Folder repository.
     folder_repo: Arc<FolderRepository>,
+    /// Loaded subtrees, when caching is enabled.
+    cache: Option<FolderTreeCache>,
 }
 
 impl TreeService {
     /// Creates a new tree service.
     pub fn new(folder_repo: Arc<FolderRepository>) -> Self {
-        Self { folder_repo }
+        Self {
+            folder_repo,
+            cache: None,
+        }
     }
 
-    /// Builds the complete folder tree starting from a root folder.
+    /// Caches loaded subtrees until something in their storage changes.
+    pub fn with_cache(mut self, cache: FolderTreeCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Builds the folder tree starting from a root folder, of at most
+    /// [`MAX_TREE_NODES`] folders.
     pub async fn get_tree(
         &self,
         _ctx: &RequestContext,
         folder_id: Uuid,
-    ) -> Result<FolderNode, AppError> {
+    ) -> Result<FolderTree, AppError> {
+        let rows = match &self.cache {
+            Some(cache) => {
+                let root = self.find_folder(folder_id).await?;
+                let generation = cache.generation(root.storage_id).await;
+                let cached = match &generation {
+                    Some(generation) => cache.get(generation, folder_id).await,
+                    None => None,
+                };
+                match cached {
+                    Some(rows) => rows,
+                    None => {
+                        let rows = self.load_subtree(folder_id).await?;
+                        if let Some(generation) = &generation {
+                            cache.put(generation, folder_id, &rows).await;
+                        }
+                        rows
+                    }
+                }
+            }
+            None => self.load_subtree(folder_id).await?,
+        };
+
+        if rows.is_empty() {
+            return Err(folder_not_found());
+        }
+        Ok(FolderTree::assemble(rows, MAX_TREE_NODES))
+    }
+
+    /// Streams the folder tree starting from a root folder level by level,
+    /// for trees too large for [`get_tree`](Self::get_tree). Each level
+    /// is loaded only once the previous one has been consumed.
+    pub async fn tree_levels(
+        &self,
+        _ctx: &RequestContext,
+        folder_id: Uuid,
+    ) -> Result<TreeLevels, AppError> {
         let root = self
             .folder_repo
-            .find_by_id(folder_id)
+            .find_subtree(folder_id, 1)
             .await
-            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .ok_or_else(|| AppError::not_found("Folder not found"))?;
+            .map_err(|e| AppError::internal(format!("Failed to load folder tree: {e}")))?;
+        if root.is_empty() {
+            return Err(folder_not_found());
+        }
 
-        let descendants = self
-            .folder_repo
-            .find_descendants(folder_id)
-            .await
-            .map_err(|e| AppError::internal(format!("Failed to get descendants: {e}")))?;
+        let repo = Arc::clone(&self.folder_repo);
+        let parents = with_children(&root);
+        let rest = stream::try_unfold(parents, move |parents| {
+            let repo = Arc::clone(&repo);
+            async move {
+                if parents.is_empty() {
+                    return Ok(None);
+                }
+                let level = repo
+                    .find_tree_level(&parents)
+                    .await
+                    .map_err(|e| AppError::internal(format!("Failed to load folder tree: {e}")))?;
+                let next = with_children(&level);
+                Ok(Some((level, next)))
+            }
+        });
+        Ok(stream::once(async { Ok(root) }).chain(rest).boxed())
+    }
 
-        // Fetch file counts
-        let mut ids = vec![folder_id];
-        ids.extend(descendants.iter().map(|f| f.id));
-        let file_counts = self
-            .folder_repo
-            .count_files_batch(&ids)
+    /// Loads the subtree rows at `folder_id`, one more than the node cap
+    /// so that truncation shows.
+    async fn load_subtree(&self, folder_id: Uuid) -> Result<Vec<FolderTreeRow>, AppError> {
+        self.folder_repo
+            .find_subtree(folder_id, MAX_TREE_NODES as i64 + 1)
             .await
-            .map_err(|e| AppError::internal(format!("Failed to get file counts: {e}")))?;
-
-        let tree = self.build_tree(root, &descendants, &file_counts);
-        Ok(tree)
+            .map_err(|e| AppError::internal(format!("Failed to load folder tree: {e}")))
     }
 
-    /// Builds a tree from a flat list of folders.
-    fn build_tree(
-        &self,
-        root: Folder,
-        all_folders: &[Folder],
-        file_counts: &std::collections::HashMap<Uuid, u64>,
-    ) -> FolderNode {
-        let children: Vec<FolderNode> = all_folders
-            .iter()
-            .filter(|f| f.parent_id == Some(root.id))
-            .map(|child| self.build_tree(child.clone(), all_folders, file_counts))
-            .collect();
-
-        FolderNode {
-            id: root.id,
-            name: root.name,
-            path: root.path,
-            depth: root.depth,
-            child_count: children.len() as u64,
-            file_count: *file_counts.get(&root.id).unwrap_or(&0),
-            children,
-        }
+    /// Looks up a folder, failing if it does not exist.
+    async fn find_folder(&self, folder_id: Uuid) -> Result<Folder, AppError> {
+        self.folder_repo
+            .find_by_id(folder_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+            .ok_or_else(folder_not_found)
     }
 
     /// Resolves a path string to a folder ID.
@@ -114,3 +169,17 @@ impl TreeService {
         Ok(breadcrumbs)
     }
 }
+
+/// The not-found error for a tree's root.
+fn folder_not_found() -> AppError {
+    AppError::not_found("Folder not found").with_code(codes::FOLDER_NOT_FOUND)
+}
+
+/// Ids of the folders in `level` that have children.
+fn with_children(level: &[FolderTreeRow]) -> Vec<Uuid> {
+    level
+        .iter()
+        .filter(|row| row.child_count > 0)
+        .map(|row| row.id)
+        .collect()
+}
@@ -0,0 +1,83 @@
+//! Cache of loaded folder subtrees.
+
+use std::sync::Arc;
+
+use tracing::debug;
+use uuid::Uuid;
+
+use filehub_cache::keys;
+use filehub_cache::provider::CacheManager;
+use filehub_core::traits::cache::CacheProvider;
+use filehub_entity::folder::FolderTreeRow;
+
+/// Caches the rows of loaded subtrees, per storage.
+///
+/// Entries are keyed by a generation of their storage, which
+/// [`invalidate`](Self::invalidate) replaces on any change to the storage's
+/// folders or their files, orphaning every subtree cached before it. A
+/// subtree loaded while the generation changes is stored under the old
+/// one and never read. The generation's TTL (`cache.ttl.folder_tree_generation`)
+/// is validated to be at least the subtrees' (`cache.ttl.folder_tree`).
+///
+/// The flat rows are cached rather than the assembled tree, whose nesting
+/// can exceed what the JSON decoder accepts.
+#[derive(Debug, Clone)]
+pub struct FolderTreeCache {
+    /// Cache backend.
+    cache: Arc<CacheManager>,
+}
+
+impl FolderTreeCache {
+    /// Creates a tree cache.
+    pub fn new(cache: Arc<CacheManager>) -> Self {
+        Self { cache }
+    }
+
+    /// The storage's current generation, starting one if there is none.
+    pub async fn generation(&self, storage_id: Uuid) -> Option<String> {
+        let key = keys::folder_tree_generation(storage_id);
+        if let Ok(Some(generation)) = self.cache.get(&key).await {
+            return Some(generation);
+        }
+        let generation = Uuid::new_v4().to_string();
+        match self.cache.put_nx(&key, &generation).await {
+            Ok(true) => Some(generation),
+            // Someone else started one first.
+            Ok(false) => self.cache.get(&key).await.ok().flatten(),
+            Err(_) => None,
+        }
+    }
+
+    /// The cached rows of the subtree at `root_id`.
+    pub async fn get(&self, generation: &str, root_id: Uuid) -> Option<Vec<FolderTreeRow>> {
+        let cached = self
+            .cache
+            .get(&keys::folder_tree_rows(generation, root_id))
+            .await
+            .ok()??;
+        serde_json::from_str(&cached).ok()
+    }
+
+    /// Caches the rows of the subtree at `root_id`, under the generation
+    /// read before they were loaded.
+    pub async fn put(&self, generation: &str, root_id: Uuid, rows: &[FolderTreeRow]) {
+        if let Ok(serialized) = serde_json::to_string(rows) {
+            let _ = self
+                .cache
+                .put(&keys::folder_tree_rows(generation, root_id), &serialized)
+                .await;
+        }
+    }
+
+    /// Drops every cached subtree of a storage.
+    pub async fn invalidate(&self, storage_id: Uuid) {
+        let generation = Uuid::new_v4().to_string();
+        if let Err(e) = self
+            .cache
+            .put(&keys::folder_tree_generation(storage_id), &generation)
+            .await
+        {
+            debug!(%storage_id, error = %e, "Failed to invalidate folder trees");
+        }
+    }
+}
@@ -20,13 +20,16 @@ pub mod user;
 
 pub use context::RequestContext;
 pub use file::{
-    DownloadService, FileService, PreviewService, SearchService, UploadService, VersionService,
+    ConversionRequestService, DownloadService, FileService, PreviewService, SearchService,
+    UploadService, VersionService,
 };
-pub use folder::{FolderService, TreeService};
+pub use folder::{FolderService, RetentionService, TreeService};
 pub use notification::{NotificationRules, NotificationService};
-pub use permission::PermissionService;
+pub use permission::{PermissionService, PermissionTemplateService};
 pub use report::WeeklyReportService;
-pub use session::{SessionAudit, SessionService, TerminationService};
-pub use share::{AccessService, LinkService, ShareService};
+pub use session::{ImpersonationService, SessionAudit, SessionService, TerminationService};
+pub use share::{
+    AccessRequestService, AccessService, LinkService, PublicAccessService, ShareService,
+};
 pub use storage::{StorageService, TransferService};
-pub use user::{AdminUserService, UserService};
+pub use user::{AdminUserService, QuotaService, UserService};
@@ -0,0 +1,16 @@
+//! Scheduled admin broadcast delivery.
+
+use async_trait::async_trait;
+
+use filehub_entity::notification::model::AdminBroadcast;
+
+/// Job type of a scheduled admin broadcast.
+pub const ADMIN_BROADCAST_JOB_TYPE: &str = "admin_broadcast";
+
+/// Pushes a broadcast that has just been marked sent to its connected
+/// audience. Decouples the scheduled-broadcast job from `filehub-realtime`.
+#[async_trait]
+pub trait BroadcastSink: Send + Sync + std::fmt::Debug {
+    /// Deliver a sent broadcast; returns the number of users reached.
+    async fn deliver(&self, broadcast: &AdminBroadcast) -> usize;
+}
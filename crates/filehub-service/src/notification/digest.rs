@@ -0,0 +1,76 @@
+//! Notification digest rules — which notifications may be batched and how
+//! they are grouped.
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use filehub_entity::notification::Notification;
+
+/// Categories that are always delivered immediately (forced logout,
+/// session revocation, security alerts).
+const IMMEDIATE_CATEGORIES: &[&str] = &["session", "security"];
+
+/// Where a digested notification is grouped, e.g. the folder a file lives in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestScope {
+    /// Kind of resource (e.g. "folder").
+    pub scope_type: String,
+    /// Resource ID.
+    pub scope_id: Uuid,
+    /// Display name, if the notification carried one.
+    pub scope_name: Option<String>,
+}
+
+impl DigestScope {
+    /// Derive the scope of `notification`: its parent folder when the
+    /// payload names one, otherwise the resource itself. Notifications
+    /// without a resource cannot be grouped.
+    pub fn of(notification: &Notification) -> Option<Self> {
+        let payload = notification.payload.as_ref();
+        let folder_id = payload
+            .and_then(|p| p.get("folder_id"))
+            .and_then(|v| v.as_str())
+            .and_then(|s| Uuid::parse_str(s).ok());
+        if let Some(folder_id) = folder_id {
+            return Some(Self {
+                scope_type: "folder".to_string(),
+                scope_id: folder_id,
+                scope_name: payload
+                    .and_then(|p| p.get("folder_name"))
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+            });
+        }
+
+        Some(Self {
+            scope_type: notification.resource_type.clone()?,
+            scope_id: notification.resource_id?,
+            scope_name: payload
+                .and_then(|p| p.get("resource_name"))
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+        })
+    }
+}
+
+/// Whether `notification` must bypass digests: anything above normal
+/// priority, and security-relevant categories.
+pub fn is_immediate(notification: &Notification) -> bool {
+    let batchable_priority = matches!(
+        notification
+            .priority
+            .as_deref()
+            .map(str::to_lowercase)
+            .as_deref(),
+        None | Some("low" | "normal")
+    );
+    !batchable_priority || IMMEDIATE_CATEGORIES.contains(&notification.category.as_str())
+}
+
+/// Receives digest notifications once they are stored, e.g. to push them
+/// to online users. Decouples the flush job from `filehub-realtime`.
+#[async_trait]
+pub trait DigestSink: Send + Sync + std::fmt::Debug {
+    /// Deliver a stored digest notification.
+    async fn deliver(&self, notification: &Notification);
+}
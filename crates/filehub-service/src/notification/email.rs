@@ -0,0 +1,110 @@
+//! Notification email rendering.
+//!
+//! Emails are rendered from fixed text and HTML templates. Values are
+//! substituted into `{{name}}` placeholders; in the HTML template they are
+//! escaped first.
+
+use filehub_entity::notification::Notification;
+
+/// Job type of a queued notification email.
+pub const EMAIL_JOB_TYPE: &str = "notification_email";
+
+const TEXT_TEMPLATE: &str = "\
+{{title}}
+
+{{message}}
+
+--
+You received this because email notifications are enabled for \"{{event_type}}\".
+Manage your notification settings: {{preferences_url}}
+";
+
+const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<body style="font-family: sans-serif; color: #222;">
+  <h2 style="margin-bottom: 8px;">{{title}}</h2>
+  <p>{{message}}</p>
+  <hr style="border: none; border-top: 1px solid #ddd;">
+  <p style="font-size: 12px; color: #777;">
+    You received this because email notifications are enabled for "{{event_type}}".
+    <a href="{{preferences_url}}">Manage or unsubscribe</a>.
+  </p>
+</body>
+</html>
+"#;
+
+/// A rendered notification email.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedEmail {
+    /// Subject line.
+    pub subject: String,
+    /// Plain-text body.
+    pub text: String,
+    /// HTML body.
+    pub html: String,
+}
+
+/// Render `notification` as an email linking to `preferences_url`.
+pub fn render(notification: &Notification, preferences_url: &str) -> RenderedEmail {
+    let values = [
+        ("title", notification.title.as_str()),
+        ("message", notification.message.as_str()),
+        ("event_type", notification.event_type.as_str()),
+        ("preferences_url", preferences_url),
+    ];
+
+    RenderedEmail {
+        subject: format!("[FileHub] {}", single_line(&notification.title)),
+        text: fill(TEXT_TEMPLATE, &values, |v| v.to_string()),
+        html: fill(HTML_TEMPLATE, &values, escape_html),
+    }
+}
+
+/// Substitute placeholders in one pass, so values are never re-scanned.
+fn fill(template: &str, values: &[(&str, &str)], encode: impl Fn(&str) -> String) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let value = after.find("}}").and_then(|end| {
+            let name = &after[..end];
+            values
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, value)| (end, value))
+        });
+        match value {
+            Some((end, value)) => {
+                out.push_str(&encode(value));
+                rest = &after[end + 2..];
+            }
+            None => {
+                out.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn escape_html(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Header values must not contain line breaks.
+fn single_line(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
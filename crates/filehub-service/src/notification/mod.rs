@@ -1,7 +1,12 @@
 //! Notification service and subscriber resolution rules.
 
+pub mod broadcast;
+pub mod digest;
+pub mod email;
 pub mod rules;
 pub mod service;
 
+pub use broadcast::BroadcastSink;
+pub use digest::DigestSink;
 pub use rules::NotificationRules;
 pub use service::NotificationService;
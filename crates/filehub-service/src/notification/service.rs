@@ -2,29 +2,87 @@
 
 use std::sync::Arc;
 
+use chrono::Utc;
 use uuid::Uuid;
 
 use filehub_core::error::AppError;
 use filehub_core::types::pagination::{PageRequest, PageResponse};
+use filehub_database::repositories::job::JobRepository;
 use filehub_database::repositories::notification::NotificationRepository;
+use filehub_entity::job::model::CreateJob;
+use filehub_entity::job::status::JobPriority;
+use filehub_entity::notification::preference::EventTypePreference;
 use filehub_entity::notification::{Notification, NotificationPreference};
 
 use crate::context::RequestContext;
 
+use super::digest::{self, DigestScope};
+use super::email::EMAIL_JOB_TYPE;
+
 /// Manages user notifications and preferences.
 #[derive(Debug, Clone)]
 pub struct NotificationService {
     /// Notification repository.
     notif_repo: Arc<NotificationRepository>,
+    /// Job repository for queued emails; `None` when email is off.
+    email_jobs: Option<Arc<JobRepository>>,
+    /// Delivery attempts per queued email.
+    email_max_attempts: i32,
 }
 
 impl NotificationService {
     /// Creates a new notification service.
     pub fn new(notif_repo: Arc<NotificationRepository>) -> Self {
-        Self { notif_repo }
+        Self {
+            notif_repo,
+            email_jobs: None,
+            email_max_attempts: 1,
+        }
+    }
+
+    /// Enables the email channel: emails are queued as jobs on `job_repo`
+    /// and attempted up to `max_attempts` times.
+    pub fn with_email(mut self, job_repo: Arc<JobRepository>, max_attempts: u32) -> Self {
+        self.email_jobs = Some(job_repo);
+        self.email_max_attempts = max_attempts.max(1) as i32;
+        self
+    }
+
+    /// Whether the email channel is enabled.
+    pub fn email_enabled(&self) -> bool {
+        self.email_jobs.is_some()
+    }
+
+    /// Queues `notification` for delivery by email. Does nothing when the
+    /// email channel is disabled.
+    #[tracing::instrument(target = "otel", name = "NotificationService::queue_email", skip_all)]
+    pub async fn queue_email(&self, notification: &Notification) -> Result<(), AppError> {
+        let Some(job_repo) = &self.email_jobs else {
+            return Ok(());
+        };
+
+        let job = CreateJob {
+            job_type: EMAIL_JOB_TYPE.to_string(),
+            queue: "default".to_string(),
+            priority: JobPriority::Normal,
+            payload: serde_json::json!({ "notification": notification }),
+            max_attempts: self.email_max_attempts,
+            scheduled_at: None,
+            created_by: None,
+        };
+        job_repo
+            .create(&job)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to queue notification email: {e}")))?;
+        Ok(())
     }
 
     /// Lists notifications for the current user.
+    #[tracing::instrument(
+        target = "otel",
+        name = "NotificationService::list_notifications",
+        skip_all
+    )]
     pub async fn list_notifications(
         &self,
         ctx: &RequestContext,
@@ -37,6 +95,7 @@ impl NotificationService {
     }
 
     /// Gets the unread notification count.
+    #[tracing::instrument(target = "otel", name = "NotificationService::unread_count", skip_all)]
     pub async fn unread_count(&self, ctx: &RequestContext) -> Result<i64, AppError> {
         self.notif_repo
             .count_unread(ctx.user_id)
@@ -45,6 +104,7 @@ impl NotificationService {
     }
 
     /// Marks a notification as read.
+    #[tracing::instrument(target = "otel", name = "NotificationService::mark_read", skip_all)]
     pub async fn mark_read(
         &self,
         ctx: &RequestContext,
@@ -57,6 +117,7 @@ impl NotificationService {
     }
 
     /// Marks all notifications as read for the current user.
+    #[tracing::instrument(target = "otel", name = "NotificationService::mark_all_read", skip_all)]
     pub async fn mark_all_read(&self, ctx: &RequestContext) -> Result<i64, AppError> {
         self.notif_repo
             .mark_all_read(ctx.user_id)
@@ -65,6 +126,7 @@ impl NotificationService {
     }
 
     /// Dismisses (soft-deletes) a notification.
+    #[tracing::instrument(target = "otel", name = "NotificationService::dismiss", skip_all)]
     pub async fn dismiss(
         &self,
         ctx: &RequestContext,
@@ -77,6 +139,11 @@ impl NotificationService {
     }
 
     /// Creates a new notification for a user.
+    #[tracing::instrument(
+        target = "otel",
+        name = "NotificationService::create_notification",
+        skip_all
+    )]
     pub async fn create_notification(
         &self,
         notification: Notification,
@@ -99,28 +166,130 @@ impl NotificationService {
     }
 
     /// Gets the user's notification preferences.
+    #[tracing::instrument(
+        target = "otel",
+        name = "NotificationService::get_preferences",
+        skip_all
+    )]
     pub async fn get_preferences(
         &self,
         ctx: &RequestContext,
     ) -> Result<NotificationPreference, AppError> {
+        self.preferences_for(ctx.user_id).await
+    }
+
+    /// Gets a user's notification preferences, or the defaults if they
+    /// never saved any.
+    #[tracing::instrument(
+        target = "otel",
+        name = "NotificationService::preferences_for",
+        skip_all
+    )]
+    pub async fn preferences_for(&self, user_id: Uuid) -> Result<NotificationPreference, AppError> {
         let prefs = self
             .notif_repo
-            .get_preferences(ctx.user_id)
+            .get_preferences(user_id)
             .await
             .map_err(|e| AppError::internal(format!("Failed to get preferences: {e}")))?;
 
-        Ok(prefs.unwrap_or_else(|| NotificationPreference::default_for_user(ctx.user_id)))
+        Ok(prefs.unwrap_or_else(|| NotificationPreference::default_for_user(user_id)))
     }
 
     /// Updates the user's notification preferences.
+    #[tracing::instrument(
+        target = "otel",
+        name = "NotificationService::update_preferences",
+        skip_all
+    )]
     pub async fn update_preferences(
         &self,
         ctx: &RequestContext,
         preferences: serde_json::Value,
     ) -> Result<NotificationPreference, AppError> {
+        if !preferences.is_object() {
+            return Err(AppError::validation("Preferences must be a JSON object"));
+        }
         self.notif_repo
             .upsert_preferences(ctx.user_id, &preferences)
             .await
             .map_err(|e| AppError::internal(format!("Failed to update preferences: {e}")))
     }
+
+    /// Sets the delivery channels for one event type; `None` restores the
+    /// defaults for it.
+    #[tracing::instrument(
+        target = "otel",
+        name = "NotificationService::set_event_type_preference",
+        skip_all
+    )]
+    pub async fn set_event_type_preference(
+        &self,
+        ctx: &RequestContext,
+        event_type: &str,
+        preference: Option<EventTypePreference>,
+    ) -> Result<NotificationPreference, AppError> {
+        if event_type.is_empty() || event_type.len() > 100 {
+            return Err(AppError::validation(
+                "Event type must be between 1 and 100 characters",
+            ));
+        }
+
+        let mut prefs = self.preferences_for(ctx.user_id).await?;
+        prefs.set_event_type(event_type, preference);
+        self.update_preferences(ctx, prefs.preferences).await
+    }
+
+    /// Collect `notification` into the recipient's digest when their
+    /// preferences `prefs` enable digests. Returns `true` when it was collected; the caller
+    /// must then not deliver or store it.
+    ///
+    /// Immediate notifications (see [`digest::is_immediate`]) and those
+    /// without a resource to group under are never collected.
+    #[tracing::instrument(
+        target = "otel",
+        name = "NotificationService::collect_into_digest",
+        skip_all
+    )]
+    pub async fn collect_into_digest(
+        &self,
+        notification: &Notification,
+        prefs: &NotificationPreference,
+    ) -> Result<bool, AppError> {
+        if digest::is_immediate(notification) {
+            return Ok(false);
+        }
+        let Some(scope) = DigestScope::of(notification) else {
+            return Ok(false);
+        };
+
+        let digest = prefs.digest();
+        if !digest.enabled {
+            return Ok(false);
+        }
+
+        self.notif_repo
+            .add_to_digest(
+                notification,
+                &scope.scope_type,
+                scope.scope_id,
+                scope.scope_name.as_deref(),
+                Utc::now() + digest.window(),
+            )
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to add to digest: {e}")))?;
+        Ok(true)
+    }
+
+    /// Store one notification per digest whose window has closed.
+    #[tracing::instrument(
+        target = "otel",
+        name = "NotificationService::flush_due_digests",
+        skip_all
+    )]
+    pub async fn flush_due_digests(&self) -> Result<Vec<Notification>, AppError> {
+        self.notif_repo
+            .flush_due_digests(Utc::now())
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to flush digests: {e}")))
+    }
 }
@@ -0,0 +1,188 @@
+//! Permission introspection — explains why a user can or cannot access a
+//! resource.
+//!
+//! Read-only: nothing here touches the permission cache, so the answer
+//! reflects the database as it is now, not what a user may still have
+//! cached.
+
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use filehub_auth::acl::EffectivePermissionResolver;
+use filehub_auth::acl::trace::{PermissionTrace, TraceOutcome, TraceShare, TraceStage, TraceStep};
+use filehub_auth::rbac::RbacEnforcer;
+use filehub_auth::rbac::policies::SystemPermission;
+use filehub_core::error::AppError;
+use filehub_database::repositories::file::FileRepository;
+use filehub_database::repositories::folder::FolderRepository;
+use filehub_database::repositories::share::ShareRepository;
+use filehub_database::repositories::storage::StorageRepository;
+use filehub_database::repositories::user::UserRepository;
+use filehub_entity::permission::{AclPermission, ResourceType};
+
+/// What to explain.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ExplainRequest {
+    /// The user whose access is in question.
+    pub user_id: Uuid,
+    /// Type of the target resource.
+    pub resource_type: ResourceType,
+    /// The target resource.
+    pub resource_id: Uuid,
+    /// Permission level to check for.
+    pub required: AclPermission,
+    /// System permission to check the user's role for as well. Reported
+    /// as its own step; it does not change the resource decision.
+    pub system_permission: Option<SystemPermission>,
+}
+
+/// Builds permission decision traces.
+#[derive(Debug, Clone)]
+pub struct PermissionExplainer {
+    /// User repository.
+    user_repo: Arc<UserRepository>,
+    /// File repository.
+    file_repo: Arc<FileRepository>,
+    /// Folder repository.
+    folder_repo: Arc<FolderRepository>,
+    /// Storage repository.
+    storage_repo: Arc<StorageRepository>,
+    /// Share repository.
+    share_repo: Arc<ShareRepository>,
+    /// RBAC enforcer.
+    rbac: Arc<RbacEnforcer>,
+    /// Permission resolver.
+    perm_resolver: Arc<EffectivePermissionResolver>,
+}
+
+impl PermissionExplainer {
+    /// Creates a new permission explainer.
+    pub fn new(
+        user_repo: Arc<UserRepository>,
+        file_repo: Arc<FileRepository>,
+        folder_repo: Arc<FolderRepository>,
+        storage_repo: Arc<StorageRepository>,
+        share_repo: Arc<ShareRepository>,
+        rbac: Arc<RbacEnforcer>,
+        perm_resolver: Arc<EffectivePermissionResolver>,
+    ) -> Self {
+        Self {
+            user_repo,
+            file_repo,
+            folder_repo,
+            storage_repo,
+            share_repo,
+            rbac,
+            perm_resolver,
+        }
+    }
+
+    /// Resolves the user's permission on the resource and returns every
+    /// check that went into the decision.
+    pub async fn explain(&self, req: &ExplainRequest) -> Result<PermissionTrace, AppError> {
+        let user = self
+            .user_repo
+            .find_by_id(req.user_id)
+            .await?
+            .ok_or_else(|| AppError::not_found(format!("User {} not found", req.user_id)))?;
+
+        // Owner and parent, as the services pass them to the resolver, and
+        // the folders above the resource for the share lookup.
+        let (owner_id, parent_folder_id, ancestry) = match req.resource_type {
+            ResourceType::File => {
+                let file = self
+                    .file_repo
+                    .find_by_id(req.resource_id)
+                    .await?
+                    .ok_or_else(|| AppError::not_found("File not found"))?;
+                let ancestry = self.folder_repo.get_ancestry(file.folder_id).await?;
+                (file.owner_id, Some(file.folder_id), ancestry)
+            }
+            ResourceType::Folder => {
+                let folder = self
+                    .folder_repo
+                    .find_by_id(req.resource_id)
+                    .await?
+                    .ok_or_else(|| AppError::not_found("Folder not found"))?;
+                let ancestry = self.folder_repo.get_ancestry(folder.id).await?;
+                (folder.owner_id, folder.parent_id, ancestry)
+            }
+            ResourceType::Storage => {
+                let storage = self
+                    .storage_repo
+                    .find_by_id(req.resource_id)
+                    .await?
+                    .ok_or_else(|| AppError::not_found("Storage not found"))?;
+                (storage.created_by.unwrap_or_default(), None, Vec::new())
+            }
+        };
+
+        let mut trace = self
+            .perm_resolver
+            .explain(
+                user.id,
+                &user.role,
+                req.resource_type,
+                req.resource_id,
+                owner_id,
+                parent_folder_id,
+                req.required,
+            )
+            .await?;
+
+        if let Some(permission) = &req.system_permission {
+            let decision = self.rbac.explain(&user.role, permission);
+            let name = serde_json::to_value(permission)
+                .ok()
+                .and_then(|v| v.as_str().map(String::from))
+                .unwrap_or_else(|| format!("{permission:?}"));
+            let (outcome, verb) = if decision.granted {
+                (TraceOutcome::Granted, "includes")
+            } else {
+                (TraceOutcome::Denied, "does not include")
+            };
+            trace.steps.insert(
+                0,
+                TraceStep::new(
+                    TraceStage::Rbac,
+                    outcome,
+                    format!("role '{}' {verb} system permission '{name}'", user.role),
+                ),
+            );
+        }
+
+        let mut resource_ids = vec![req.resource_id];
+        resource_ids.extend(ancestry.into_iter().filter(|id| *id != req.resource_id));
+        let shares = self
+            .share_repo
+            .find_shared_with_user_on(user.id, &resource_ids)
+            .await?;
+        let mut step = TraceStep::new(
+            TraceStage::Share,
+            TraceOutcome::Skipped,
+            if shares.is_empty() {
+                "no user shares with the user on the resource or its folders".to_string()
+            } else {
+                format!(
+                    "{} user share(s) found; shares are not part of the effective permission",
+                    shares.len()
+                )
+            },
+        );
+        step.shares = shares
+            .iter()
+            .map(|share| TraceShare {
+                share_id: share.id,
+                share_type: share.share_type,
+                resource_type: share.resource_type,
+                resource_id: share.resource_id,
+                permission: share.permission,
+                valid: share.is_valid(),
+            })
+            .collect();
+        trace.steps.push(step);
+
+        Ok(trace)
+    }
+}
@@ -1,5 +1,9 @@
 //! ACL permission management service.
 
+pub mod explain;
 pub mod service;
+pub mod template;
 
+pub use explain::PermissionExplainer;
 pub use service::PermissionService;
+pub use template::PermissionTemplateService;
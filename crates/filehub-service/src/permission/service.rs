@@ -1,15 +1,23 @@
 //! ACL permission management — add, update, remove ACL entries.
+//!
+//! A grant over a folder subtree is normally a single entry on its root,
+//! which the inheritance resolver carries down to every folder and file
+//! not cut off by a blocking entry. Explicit grants write an entry on
+//! every node instead, in one transaction.
 
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use chrono::Utc;
 use tracing::info;
 use uuid::Uuid;
 
-use filehub_auth::acl::EffectivePermissionResolver;
+use filehub_auth::acl::{EffectivePermissionResolver, subtree_reach};
 use filehub_auth::rbac::RbacEnforcer;
 use filehub_auth::rbac::policies::SystemPermission;
-use filehub_core::error::AppError;
+use filehub_core::error::{AppError, codes};
+use filehub_database::repositories::file::FileRepository;
+use filehub_database::repositories::folder::FolderRepository;
 use filehub_database::repositories::permission::AclRepository;
 use filehub_entity::permission::{AclEntry, AclInheritance, AclPermission, ResourceType};
 
@@ -20,6 +28,10 @@ use crate::context::RequestContext;
 pub struct PermissionService {
     /// ACL repository.
     acl_repo: Arc<AclRepository>,
+    /// Folder repository, for subtrees.
+    folder_repo: Arc<FolderRepository>,
+    /// File repository, for the files of subtrees.
+    file_repo: Arc<FileRepository>,
     /// RBAC enforcer.
     rbac: Arc<RbacEnforcer>,
     /// Permission resolver (for cache invalidation).
@@ -52,21 +64,75 @@ pub struct UpdateAclEntryRequest {
     pub expires_at: Option<Option<chrono::DateTime<Utc>>>,
 }
 
+/// Request to grant a permission over a folder subtree.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RecursiveGrantRequest {
+    /// User ID to grant permission to (None for public).
+    pub user_id: Option<Uuid>,
+    /// Whether this is a public (anyone) entry.
+    pub is_anyone: bool,
+    /// Permission level.
+    pub permission: AclPermission,
+    /// Expiration time.
+    pub expires_at: Option<chrono::DateTime<Utc>>,
+    /// Write an entry on every folder and file of the subtree rather than
+    /// one inherited entry on its root. Explicit entries also reach below
+    /// blocking entries.
+    #[serde(default)]
+    pub explicit: bool,
+}
+
+/// What a grant over a subtree changed.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct RecursiveGrantReport {
+    /// Root of the subtree.
+    pub folder_id: Uuid,
+    /// ACL entries created or updated.
+    pub entries_written: u64,
+    /// Folders the grant applies to, the root included.
+    pub folders: usize,
+    /// Files the grant applies to.
+    pub files: usize,
+    /// Folders the inherited grant does not reach because of a blocking
+    /// entry above them. Always empty for explicit grants.
+    pub cut_off: Vec<Uuid>,
+}
+
+/// What a recompute of a subtree's effective permissions covered.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct RecomputeReport {
+    /// Root of the subtree.
+    pub folder_id: Uuid,
+    /// Folders whose cached permissions were dropped, the root included.
+    pub folders: usize,
+    /// Files whose cached permissions were dropped.
+    pub files: usize,
+    /// Principals with entries in the subtree.
+    pub principals: i64,
+    /// Whether the whole permission cache was dropped at once.
+    pub full_flush: bool,
+}
+
 impl PermissionService {
     /// Creates a new permission service.
     pub fn new(
         acl_repo: Arc<AclRepository>,
+        folder_repo: Arc<FolderRepository>,
+        file_repo: Arc<FileRepository>,
         rbac: Arc<RbacEnforcer>,
         perm_resolver: Arc<EffectivePermissionResolver>,
     ) -> Self {
         Self {
             acl_repo,
+            folder_repo,
+            file_repo,
             rbac,
             perm_resolver,
         }
     }
 
     /// Gets all ACL entries for a resource.
+    #[tracing::instrument(target = "otel", name = "PermissionService::get_entries", skip_all)]
     pub async fn get_entries(
         &self,
         _ctx: &RequestContext,
@@ -80,6 +146,7 @@ impl PermissionService {
     }
 
     /// Adds a new ACL entry.
+    #[tracing::instrument(target = "otel", name = "PermissionService::add_entry", skip_all)]
     pub async fn add_entry(
         &self,
         ctx: &RequestContext,
@@ -125,6 +192,7 @@ impl PermissionService {
     }
 
     /// Updates an existing ACL entry.
+    #[tracing::instrument(target = "otel", name = "PermissionService::update_entry", skip_all)]
     pub async fn update_entry(
         &self,
         ctx: &RequestContext,
@@ -173,6 +241,7 @@ impl PermissionService {
     }
 
     /// Removes an ACL entry.
+    #[tracing::instrument(target = "otel", name = "PermissionService::remove_entry", skip_all)]
     pub async fn remove_entry(&self, ctx: &RequestContext, entry_id: Uuid) -> Result<(), AppError> {
         if !ctx.is_admin() {
             self.rbac
@@ -204,4 +273,190 @@ impl PermissionService {
 
         Ok(())
     }
+
+    /// Grants a permission on a folder and everything below it.
+    ///
+    /// By default this writes or updates a single inherited entry on the
+    /// folder; descendants pick it up through inheritance. With
+    /// `explicit`, every folder and file of the subtree gets its own entry
+    /// in one transaction, skipping those where the principal already has
+    /// one. Either way the subtree's cached permissions are recomputed.
+    #[tracing::instrument(target = "otel", name = "PermissionService::grant_recursive", skip_all)]
+    pub async fn grant_recursive(
+        &self,
+        ctx: &RequestContext,
+        folder_id: Uuid,
+        req: RecursiveGrantRequest,
+    ) -> Result<RecursiveGrantReport, AppError> {
+        self.require_manage(ctx)?;
+        if req.is_anyone == req.user_id.is_some() {
+            return Err(AppError::validation(
+                "Grant either a user or anyone, not both",
+            ));
+        }
+        let (folders, parents) = self.subtree(folder_id).await?;
+
+        let mut report = RecursiveGrantReport {
+            folder_id,
+            ..Default::default()
+        };
+        let reached = if req.explicit {
+            let files = self.file_repo.find_ids_in_folders(&folders).await?;
+            report.entries_written = self
+                .acl_repo
+                .create_batch(
+                    &folders,
+                    &files,
+                    req.user_id,
+                    req.is_anyone,
+                    req.permission,
+                    AclInheritance::Inherit,
+                    ctx.user_id,
+                    req.expires_at,
+                )
+                .await?;
+            folders
+        } else {
+            self.write_root_entry(ctx, folder_id, &req).await?;
+            report.entries_written = 1;
+
+            let blocking: HashSet<Uuid> = self
+                .acl_repo
+                .find_blocking(&folders[1..], req.user_id)
+                .await?
+                .into_iter()
+                .collect();
+            let reach = subtree_reach(folder_id, &parents, &blocking);
+            report.cut_off = reach.cut_off;
+            reach.reached
+        };
+        report.folders = reached.len();
+        report.files = self.file_repo.find_ids_in_folders(&reached).await?.len();
+
+        self.recompute_effective(ctx, folder_id).await?;
+
+        info!(
+            admin_id = %ctx.user_id,
+            folder_id = %folder_id,
+            user_id = ?req.user_id,
+            permission = ?req.permission,
+            explicit = req.explicit,
+            entries = report.entries_written,
+            folders = report.folders,
+            files = report.files,
+            cut_off = report.cut_off.len(),
+            "Permission granted over subtree"
+        );
+        Ok(report)
+    }
+
+    /// Drops the cached effective permissions of a folder and everything
+    /// below it in one pass, so the next checks resolve them afresh.
+    #[tracing::instrument(
+        target = "otel",
+        name = "PermissionService::recompute_effective",
+        skip_all
+    )]
+    pub async fn recompute_effective(
+        &self,
+        ctx: &RequestContext,
+        folder_id: Uuid,
+    ) -> Result<RecomputeReport, AppError> {
+        self.require_manage(ctx)?;
+        let (folders, _) = self.subtree(folder_id).await?;
+        let files = self.file_repo.find_ids_in_folders(&folders).await?;
+        let principals = self.acl_repo.count_principals(&folders, &files).await?;
+
+        let resources: Vec<(ResourceType, Uuid)> = folders
+            .iter()
+            .map(|id| (ResourceType::Folder, *id))
+            .chain(files.iter().map(|id| (ResourceType::File, *id)))
+            .collect();
+        let full_flush = self.perm_resolver.invalidate_resources(&resources).await;
+
+        info!(
+            folder_id = %folder_id,
+            folders = folders.len(),
+            files = files.len(),
+            principals,
+            full_flush,
+            "Effective permissions recomputed"
+        );
+        Ok(RecomputeReport {
+            folder_id,
+            folders: folders.len(),
+            files: files.len(),
+            principals,
+            full_flush,
+        })
+    }
+
+    /// Creates the principal's inherited entry on a folder, or brings an
+    /// existing one in line with `req`.
+    async fn write_root_entry(
+        &self,
+        ctx: &RequestContext,
+        folder_id: Uuid,
+        req: &RecursiveGrantRequest,
+    ) -> Result<AclEntry, AppError> {
+        let existing = self
+            .acl_repo
+            .find_by_resource(ResourceType::Folder, folder_id)
+            .await?
+            .into_iter()
+            .find(|e| e.user_id == req.user_id && e.is_anyone.unwrap_or(false) == req.is_anyone);
+
+        match existing {
+            Some(mut entry) => {
+                entry.permission = req.permission;
+                entry.inheritance = AclInheritance::Inherit;
+                entry.expires_at = req.expires_at;
+                self.acl_repo.update(&entry).await
+            }
+            None => {
+                self.acl_repo
+                    .create(
+                        ResourceType::Folder,
+                        folder_id,
+                        req.user_id,
+                        req.is_anyone,
+                        req.permission,
+                        AclInheritance::Inherit,
+                        ctx.user_id,
+                        req.expires_at,
+                    )
+                    .await
+            }
+        }
+    }
+
+    /// The folders of the subtree rooted at `folder_id`, root first, and
+    /// the descendants with their parents, parents first.
+    async fn subtree(
+        &self,
+        folder_id: Uuid,
+    ) -> Result<(Vec<Uuid>, Vec<(Uuid, Option<Uuid>)>), AppError> {
+        self.folder_repo
+            .find_by_id(folder_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found("Folder not found").with_code(codes::FOLDER_NOT_FOUND)
+            })?;
+        let descendants = self.folder_repo.find_descendants(folder_id).await?;
+
+        let mut folders = Vec::with_capacity(descendants.len() + 1);
+        folders.push(folder_id);
+        folders.extend(descendants.iter().map(|f| f.id));
+        let parents = descendants.iter().map(|f| (f.id, f.parent_id)).collect();
+        Ok((folders, parents))
+    }
+
+    /// Admins can always manage permissions; others need PermissionManageAll.
+    fn require_manage(&self, ctx: &RequestContext) -> Result<(), AppError> {
+        if !ctx.is_admin() {
+            self.rbac
+                .require_permission(&ctx.role, &SystemPermission::PermissionManageAll)?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,361 @@
+//! Permission templates — named ACL entry sets attached to folders and
+//! applied to the folders created inside them.
+//!
+//! Applying a template only adds or adjusts ACL entries on the target
+//! folder; it never removes entries of other principals. Resolution is
+//! unchanged: entries still cascade to descendants unless a `block` entry
+//! stops them, and the highest permission along the chain wins, so a
+//! template cannot take away access a user inherits from above unless it
+//! carries a `block` entry.
+
+use std::sync::Arc;
+
+use tracing::info;
+use uuid::Uuid;
+
+use filehub_auth::acl::EffectivePermissionResolver;
+use filehub_core::error::{AppError, codes};
+use filehub_database::repositories::folder::FolderRepository;
+use filehub_database::repositories::permission::AclRepository;
+use filehub_database::repositories::permission_template::PermissionTemplateRepository;
+use filehub_entity::folder::Folder;
+use filehub_entity::permission::template::{plan_application, validate_entries};
+use filehub_entity::permission::{PermissionTemplate, ResourceType, TemplateChange, TemplateEntry};
+
+use crate::context::RequestContext;
+
+/// Manages permission templates and applies them to folders.
+#[derive(Debug, Clone)]
+pub struct PermissionTemplateService {
+    /// Template repository.
+    template_repo: Arc<PermissionTemplateRepository>,
+    /// Folder repository.
+    folder_repo: Arc<FolderRepository>,
+    /// ACL repository.
+    acl_repo: Arc<AclRepository>,
+    /// Permission resolver (for cache invalidation).
+    perm_resolver: Arc<EffectivePermissionResolver>,
+}
+
+/// Request to create or replace a template.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TemplateRequest {
+    /// Unique template name.
+    pub name: String,
+    /// What the template is for.
+    pub description: Option<String>,
+    /// Entries applied to a folder.
+    pub entries: Vec<TemplateEntry>,
+}
+
+/// What applying a template changed.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ApplySummary {
+    /// Folders the template was applied to.
+    pub folders: usize,
+    /// ACL entries created.
+    pub created: usize,
+    /// Existing ACL entries brought in line with the template.
+    pub updated: usize,
+}
+
+impl PermissionTemplateService {
+    /// Creates a new permission template service.
+    pub fn new(
+        template_repo: Arc<PermissionTemplateRepository>,
+        folder_repo: Arc<FolderRepository>,
+        acl_repo: Arc<AclRepository>,
+        perm_resolver: Arc<EffectivePermissionResolver>,
+    ) -> Self {
+        Self {
+            template_repo,
+            folder_repo,
+            acl_repo,
+            perm_resolver,
+        }
+    }
+
+    /// Lists all templates.
+    pub async fn list(&self, ctx: &RequestContext) -> Result<Vec<PermissionTemplate>, AppError> {
+        require_admin(ctx)?;
+        self.template_repo.list().await
+    }
+
+    /// Gets a template by ID.
+    pub async fn get(
+        &self,
+        ctx: &RequestContext,
+        template_id: Uuid,
+    ) -> Result<PermissionTemplate, AppError> {
+        require_admin(ctx)?;
+        self.find(template_id).await
+    }
+
+    /// Gets a template by name.
+    pub async fn get_by_name(
+        &self,
+        ctx: &RequestContext,
+        name: &str,
+    ) -> Result<PermissionTemplate, AppError> {
+        require_admin(ctx)?;
+        self.template_repo
+            .find_by_name(name)
+            .await?
+            .ok_or_else(|| AppError::not_found(format!("Permission template '{name}' not found")))
+    }
+
+    /// Creates a template.
+    pub async fn create(
+        &self,
+        ctx: &RequestContext,
+        req: TemplateRequest,
+    ) -> Result<PermissionTemplate, AppError> {
+        require_admin(ctx)?;
+        validate_request(&req)?;
+        let template = self
+            .template_repo
+            .create(
+                req.name.trim(),
+                req.description.as_deref(),
+                &req.entries,
+                ctx.user_id,
+            )
+            .await?;
+
+        info!(
+            admin_id = %ctx.user_id,
+            template_id = %template.id,
+            name = %template.name,
+            "Permission template created"
+        );
+        Ok(template)
+    }
+
+    /// Replaces a template. Folders it was already applied to keep their
+    /// entries until it is applied again.
+    pub async fn update(
+        &self,
+        ctx: &RequestContext,
+        template_id: Uuid,
+        req: TemplateRequest,
+    ) -> Result<PermissionTemplate, AppError> {
+        require_admin(ctx)?;
+        validate_request(&req)?;
+        let template = self
+            .template_repo
+            .update(
+                template_id,
+                req.name.trim(),
+                req.description.as_deref(),
+                &req.entries,
+            )
+            .await?;
+
+        info!(
+            admin_id = %ctx.user_id,
+            template_id = %template_id,
+            "Permission template updated"
+        );
+        Ok(template)
+    }
+
+    /// Deletes a template and detaches it from its folders. Entries it
+    /// created stay in place.
+    pub async fn delete(&self, ctx: &RequestContext, template_id: Uuid) -> Result<(), AppError> {
+        require_admin(ctx)?;
+        if !self.template_repo.delete(template_id).await? {
+            return Err(AppError::not_found("Permission template not found"));
+        }
+
+        info!(
+            admin_id = %ctx.user_id,
+            template_id = %template_id,
+            "Permission template deleted"
+        );
+        Ok(())
+    }
+
+    /// Attaches a template to a folder, so that it is applied to every
+    /// folder created directly inside it, or detaches it with `None`.
+    /// The folder's own entries are not touched.
+    pub async fn attach(
+        &self,
+        ctx: &RequestContext,
+        folder_id: Uuid,
+        template_id: Option<Uuid>,
+    ) -> Result<Folder, AppError> {
+        require_admin(ctx)?;
+        if let Some(template_id) = template_id {
+            self.find(template_id).await?;
+        }
+        let folder = self
+            .folder_repo
+            .set_permission_template(folder_id, template_id)
+            .await?;
+
+        info!(
+            admin_id = %ctx.user_id,
+            folder_id = %folder_id,
+            template_id = ?template_id,
+            "Permission template attached"
+        );
+        Ok(folder)
+    }
+
+    /// Applies a template to a folder and, if `recursive`, to every folder
+    /// below it.
+    pub async fn apply(
+        &self,
+        ctx: &RequestContext,
+        template_id: Uuid,
+        folder_id: Uuid,
+        recursive: bool,
+    ) -> Result<ApplySummary, AppError> {
+        require_admin(ctx)?;
+        let template = self.find(template_id).await?;
+        self.folder_repo
+            .find_by_id(folder_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found("Folder not found").with_code(codes::FOLDER_NOT_FOUND)
+            })?;
+
+        let mut folder_ids = vec![folder_id];
+        if recursive {
+            let descendants = self.folder_repo.find_descendants(folder_id).await?;
+            folder_ids.extend(descendants.into_iter().map(|f| f.id));
+        }
+
+        let mut summary = ApplySummary::default();
+        for id in folder_ids {
+            self.apply_to_folder(ctx, &template.entries, id, &mut summary)
+                .await?;
+        }
+
+        info!(
+            admin_id = %ctx.user_id,
+            template_id = %template_id,
+            folder_id = %folder_id,
+            folders = summary.folders,
+            created = summary.created,
+            updated = summary.updated,
+            "Permission template applied"
+        );
+        Ok(summary)
+    }
+
+    /// Applies the template attached to `parent`, if any, to `folder`,
+    /// just created inside it.
+    pub async fn apply_inherited(
+        &self,
+        ctx: &RequestContext,
+        parent: &Folder,
+        folder: &Folder,
+    ) -> Result<(), AppError> {
+        let Some(template_id) = parent.permission_template_id else {
+            return Ok(());
+        };
+        // Detached concurrently: nothing to apply
+        let Some(template) = self.template_repo.find_by_id(template_id).await? else {
+            return Ok(());
+        };
+
+        let mut summary = ApplySummary::default();
+        self.apply_to_folder(ctx, &template.entries, folder.id, &mut summary)
+            .await?;
+
+        info!(
+            folder_id = %folder.id,
+            template_id = %template_id,
+            created = summary.created,
+            "Applied parent's permission template"
+        );
+        Ok(())
+    }
+
+    /// Brings one folder's entries in line with `entries`.
+    async fn apply_to_folder(
+        &self,
+        ctx: &RequestContext,
+        entries: &[TemplateEntry],
+        folder_id: Uuid,
+        summary: &mut ApplySummary,
+    ) -> Result<(), AppError> {
+        let existing = self
+            .acl_repo
+            .find_by_resource(ResourceType::Folder, folder_id)
+            .await?;
+
+        for change in plan_application(entries, &existing) {
+            match change {
+                TemplateChange::Create(entry) => {
+                    self.acl_repo
+                        .create(
+                            ResourceType::Folder,
+                            folder_id,
+                            entry.user_id,
+                            entry.is_anyone,
+                            entry.permission,
+                            entry.inheritance,
+                            ctx.user_id,
+                            None,
+                        )
+                        .await?;
+                    summary.created += 1;
+                }
+                TemplateChange::Update {
+                    entry_id,
+                    permission,
+                    inheritance,
+                } => {
+                    let Some(mut current) = existing.iter().find(|e| e.id == entry_id).cloned()
+                    else {
+                        continue;
+                    };
+                    current.permission = permission;
+                    current.inheritance = inheritance;
+                    current.expires_at = None;
+                    self.acl_repo.update(&current).await?;
+                    summary.updated += 1;
+                }
+            }
+        }
+        summary.folders += 1;
+
+        let _ = self
+            .perm_resolver
+            .invalidate_resource_cache(ResourceType::Folder, folder_id)
+            .await;
+        Ok(())
+    }
+
+    /// Loads a template.
+    async fn find(&self, template_id: Uuid) -> Result<PermissionTemplate, AppError> {
+        self.template_repo
+            .find_by_id(template_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Permission template not found"))
+    }
+}
+
+/// Templates are managed by admins only.
+fn require_admin(ctx: &RequestContext) -> Result<(), AppError> {
+    if ctx.is_admin() {
+        Ok(())
+    } else {
+        Err(AppError::forbidden(
+            "Only admins can manage permission templates",
+        ))
+    }
+}
+
+/// Checks a template's name and entries.
+fn validate_request(req: &TemplateRequest) -> Result<(), AppError> {
+    let name = req.name.trim();
+    if name.is_empty() || name.len() > 255 {
+        return Err(AppError::validation(
+            "Template name must be 1 to 255 characters",
+        ));
+    }
+    validate_entries(&req.entries)
+}
@@ -2,30 +2,51 @@
 
 use std::sync::Arc;
 
+use bytes::Bytes;
 use filehub_entity::audit::model::CreateAuditLogEntry;
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 
 use filehub_core::error::AppError;
+use filehub_core::events::{DomainEvent, EventBus, EventCategory};
+use filehub_core::types::Impersonator;
 use filehub_core::types::pagination::{PageRequest, PageResponse};
-use filehub_database::repositories::audit::AuditLogRepository;
-use filehub_entity::audit::AuditLogEntry;
+use filehub_database::repositories::audit::{AuditFilter, AuditLogRepository};
+use filehub_entity::audit::{AuditDiff, AuditLogEntry};
 
 use crate::context::RequestContext;
 
+use super::audit_export::AuditExportFormat;
+use super::audit_sink::AuditShipper;
+
 /// Session and general audit log service.
 #[derive(Debug, Clone)]
 pub struct SessionAudit {
     /// Audit log repository.
     audit_repo: Arc<AuditLogRepository>,
+    /// Copies events to external sinks, if any are configured.
+    shipper: Option<AuditShipper>,
 }
 
 impl SessionAudit {
     /// Creates a new session audit service.
     pub fn new(audit_repo: Arc<AuditLogRepository>) -> Self {
-        Self { audit_repo }
+        Self {
+            audit_repo,
+            shipper: None,
+        }
+    }
+
+    /// Also ships every logged event to external sinks.
+    pub fn with_shipper(mut self, shipper: AuditShipper) -> Self {
+        self.shipper = Some(shipper);
+        self
     }
 
-    /// Logs an audit event.
+    /// Logs an audit event. Inside an impersonated request the
+    /// administrator acting as `actor_id` is recorded with it.
     pub async fn log_event(
         &self,
         actor_id: Uuid,
@@ -37,7 +58,8 @@ impl SessionAudit {
         user_agent: Option<&str>,
     ) -> Result<AuditLogEntry, AppError> {
         let entry_record = CreateAuditLogEntry {
-            actor_id,
+            actor_id: Some(actor_id),
+            impersonator_id: Impersonator::current().map(|i| i.id()),
             action: action.to_string(),
             target_type: target_type.to_string(),
             target_id,
@@ -45,11 +67,129 @@ impl SessionAudit {
             ip_address: ip_address.map(String::from),
             user_agent: user_agent.map(String::from),
         };
+        self.create(&entry_record).await
+    }
 
-        self.audit_repo
-            .create(&entry_record)
+    /// Logs an event of an anonymous visitor, who is identified only by
+    /// the request's IP address and user agent.
+    pub async fn log_anonymous(
+        &self,
+        action: &str,
+        target_type: &str,
+        target_id: Option<Uuid>,
+        details: Option<serde_json::Value>,
+        ip_address: &str,
+        user_agent: Option<&str>,
+    ) -> Result<AuditLogEntry, AppError> {
+        let entry_record = CreateAuditLogEntry {
+            actor_id: None,
+            impersonator_id: None,
+            action: action.to_string(),
+            target_type: target_type.to_string(),
+            target_id,
+            details,
+            ip_address: Some(ip_address.to_string()),
+            user_agent: user_agent.map(String::from),
+        };
+        self.create(&entry_record).await
+    }
+
+    /// Stores `entry_record` and ships it.
+    async fn create(&self, entry_record: &CreateAuditLogEntry) -> Result<AuditLogEntry, AppError> {
+        let entry = self
+            .audit_repo
+            .create(entry_record)
             .await
-            .map_err(|e| AppError::internal(format!("Failed to log audit event: {e}")))
+            .map_err(|e| AppError::internal(format!("Failed to log audit event: {e}")))?;
+        if let Some(shipper) = &self.shipper {
+            shipper.ship(&entry).await;
+        }
+        Ok(entry)
+    }
+
+    /// Records an update of `target_type`/`target_id` by the request's user,
+    /// with the changed fields of `before` → `after` as details (see
+    /// [`AuditDiff`]). Nothing is written when no audited field changed.
+    /// Failures are logged rather than returned so auditing never fails
+    /// the update itself.
+    pub async fn log_update<T: Serialize>(
+        &self,
+        ctx: &RequestContext,
+        action: &str,
+        target_type: &str,
+        target_id: Uuid,
+        before: &T,
+        after: &T,
+    ) {
+        let diff = AuditDiff::between(before, after);
+        if diff.is_empty() {
+            return;
+        }
+        if let Err(e) = self
+            .log_event(
+                ctx.user_id,
+                action,
+                target_type,
+                Some(target_id),
+                Some(diff.into_details()),
+                Some(&ctx.ip_address),
+                ctx.user_agent.as_deref(),
+            )
+            .await
+        {
+            tracing::warn!(error = %e, action, target_id = %target_id, "Failed to audit update");
+        }
+    }
+
+    /// Records file, share, and user events from `bus` in the audit log.
+    ///
+    /// Events without an actor (system-initiated) are skipped, since every
+    /// audit entry must be attributed to a user.
+    pub fn spawn_event_sink(self: Arc<Self>, bus: &EventBus) -> JoinHandle<()> {
+        let mut events = bus.subscribe_to(&[
+            EventCategory::File,
+            EventCategory::Share,
+            EventCategory::User,
+        ]);
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                if let Err(e) = self.log_domain_event(&event).await {
+                    tracing::warn!(event_id = %event.id, "Failed to audit domain event: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Writes one domain event as an audit entry, once however often it
+    /// is delivered.
+    async fn log_domain_event(&self, event: &DomainEvent) -> Result<(), AppError> {
+        let Some(actor_id) = event.actor_id else {
+            return Ok(());
+        };
+        let (target_type, target_id) = event.payload.target();
+        let details = serde_json::to_value(&event.payload)?;
+
+        // The sink runs on its own task, so the impersonator comes from
+        // the event rather than the current request
+        let entry = CreateAuditLogEntry {
+            actor_id: Some(actor_id),
+            impersonator_id: event.impersonator_id,
+            action: event.payload.action(),
+            target_type: target_type.to_string(),
+            target_id,
+            details: Some(details["event"].clone()),
+            ip_address: None,
+            user_agent: None,
+        };
+        let created = self
+            .audit_repo
+            .create_for_event(event.id, &entry)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to log audit event: {e}")))?;
+        if let (Some(entry), Some(shipper)) = (created, &self.shipper) {
+            shipper.ship(&entry).await;
+        }
+        Ok(())
     }
 
     /// Searches the audit log.
@@ -67,4 +207,21 @@ impl SessionAudit {
             .await
             .map_err(|e| AppError::internal(format!("Audit search failed: {e}")))
     }
+
+    /// Streams every entry matching `filter`, oldest first, encoded as
+    /// `format`. Entries are read and encoded as the stream is consumed.
+    pub fn export(
+        &self,
+        _ctx: &RequestContext,
+        filter: AuditFilter,
+        format: AuditExportFormat,
+    ) -> impl Stream<Item = Result<Bytes, AppError>> + Send + 'static {
+        let header = format.header();
+        let header = futures::stream::iter((!header.is_empty()).then(|| Ok(Bytes::from(header))));
+        let rows = self.audit_repo.export(filter).map(move |row| {
+            let entry = row.map_err(|e| AppError::internal(format!("Audit export failed: {e}")))?;
+            format.encode(&entry).map(Bytes::from)
+        });
+        header.chain(rows)
+    }
 }
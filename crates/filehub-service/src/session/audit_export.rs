@@ -0,0 +1,105 @@
+//! Audit log export encoding.
+
+use std::str::FromStr;
+
+use filehub_core::error::AppError;
+use filehub_entity::audit::AuditLogEntry;
+
+/// File format of an audit export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditExportFormat {
+    /// Comma-separated values with a header row.
+    Csv,
+    /// One JSON object per line.
+    Ndjson,
+}
+
+/// CSV columns, in order.
+const CSV_COLUMNS: &[&str] = &[
+    "id",
+    "created_at",
+    "actor_id",
+    "action",
+    "target_type",
+    "target_id",
+    "ip_address",
+    "user_agent",
+    "details",
+];
+
+impl AuditExportFormat {
+    /// MIME type of the export.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    /// File extension of the export.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+        }
+    }
+
+    /// Text written before the first entry.
+    pub fn header(&self) -> String {
+        match self {
+            Self::Csv => format!("{}\n", CSV_COLUMNS.join(",")),
+            Self::Ndjson => String::new(),
+        }
+    }
+
+    /// One entry as a line of the export, including the line break.
+    pub fn encode(&self, entry: &AuditLogEntry) -> Result<String, AppError> {
+        match self {
+            Self::Csv => {
+                let details = entry
+                    .details
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?
+                    .unwrap_or_default();
+                let fields = [
+                    entry.id.to_string(),
+                    entry.created_at.to_rfc3339(),
+                    entry.actor_id.map(|id| id.to_string()).unwrap_or_default(),
+                    entry.action.clone(),
+                    entry.target_type.clone(),
+                    entry.target_id.map(|id| id.to_string()).unwrap_or_default(),
+                    entry.ip_address.clone().unwrap_or_default(),
+                    entry.user_agent.clone().unwrap_or_default(),
+                    details,
+                ];
+                let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+                Ok(format!("{}\n", line.join(",")))
+            }
+            Self::Ndjson => Ok(format!("{}\n", serde_json::to_string(entry)?)),
+        }
+    }
+}
+
+impl FromStr for AuditExportFormat {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "ndjson" | "jsonl" | "json" => Ok(Self::Ndjson),
+            other => Err(AppError::validation(format!(
+                "Unknown export format '{other}'; expected csv or ndjson"
+            ))),
+        }
+    }
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break.
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,87 @@
+//! Shipping audit events to external sinks.
+//!
+//! [`SessionAudit`](super::SessionAudit) writes every event to the database
+//! first and then queues one `audit_ship` job per configured sink, so a
+//! slow or unreachable SIEM never delays the request that caused the event
+//! and never costs the database copy.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use filehub_core::config::AuditConfig;
+use filehub_core::error::AppError;
+use filehub_database::repositories::job::JobRepository;
+use filehub_entity::audit::{AuditEvent, AuditLogEntry};
+use filehub_entity::job::{CreateJob, JobPayload, JobPriority};
+
+/// Job type of a queued audit event delivery.
+pub const AUDIT_SHIP_JOB_TYPE: &str = "audit_ship";
+
+/// An external destination for audit events.
+#[async_trait]
+pub trait AuditSink: Send + Sync + std::fmt::Debug {
+    /// Configured sink name.
+    fn name(&self) -> &str;
+
+    /// Deliver one event. An error is retried by the worker.
+    async fn write(&self, event: &AuditEvent) -> Result<(), AppError>;
+}
+
+/// Queues audit events for the configured external sinks.
+#[derive(Debug, Clone)]
+pub struct AuditShipper {
+    /// Job queue.
+    job_repo: Arc<JobRepository>,
+    /// Names of the sinks every event goes to.
+    sinks: Vec<String>,
+    /// Delivery attempts per event and sink.
+    max_attempts: i32,
+}
+
+impl AuditShipper {
+    /// Creates a shipper for the sinks in `config`.
+    pub fn new(job_repo: Arc<JobRepository>, config: &AuditConfig) -> Self {
+        Self {
+            job_repo,
+            sinks: config.sinks.iter().map(|s| s.name().to_string()).collect(),
+            max_attempts: config.max_attempts as i32,
+        }
+    }
+
+    /// Queues `entry` for every sink. Failures are logged, since the
+    /// entry is already stored.
+    pub async fn ship(&self, entry: &AuditLogEntry) {
+        let event = AuditEvent::from(entry);
+        for sink in &self.sinks {
+            if let Err(e) = self.queue(sink, &event).await {
+                tracing::error!(
+                    sink = %sink,
+                    audit_id = %entry.id,
+                    error = %e,
+                    "Failed to queue audit event for shipping"
+                );
+            }
+        }
+    }
+
+    /// Queues one delivery.
+    async fn queue(&self, sink: &str, event: &AuditEvent) -> Result<(), AppError> {
+        let payload = JobPayload::AuditShip {
+            sink: sink.to_string(),
+            event: event.clone(),
+        };
+        let job = CreateJob {
+            job_type: AUDIT_SHIP_JOB_TYPE.to_string(),
+            queue: "default".to_string(),
+            priority: JobPriority::Normal,
+            payload: serde_json::to_value(&payload)
+                .map_err(|e| AppError::internal(format!("Invalid audit ship job: {e}")))?,
+            max_attempts: self.max_attempts,
+            scheduled_at: None,
+            created_by: None,
+        };
+        self.job_repo.create(&job).await?;
+        Ok(())
+    }
+}
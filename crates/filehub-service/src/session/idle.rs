@@ -0,0 +1,16 @@
+//! Idle session warnings — decouples the idle check job from
+//! `filehub-realtime`.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Tells a session's live connections about idle termination.
+#[async_trait]
+pub trait IdleSessionNotifier: Send + Sync + std::fmt::Debug {
+    /// Warn that the session ends at `logout_at` unless it sees activity.
+    async fn warn_idle(&self, session_id: Uuid, logout_at: DateTime<Utc>);
+
+    /// Close the session's connections after it was terminated for idleness.
+    async fn idle_terminated(&self, session_id: Uuid, reason: &str);
+}
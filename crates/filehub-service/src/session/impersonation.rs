@@ -0,0 +1,217 @@
+//! Admin impersonation — acting as another user to see what they see.
+
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use tracing::info;
+use uuid::Uuid;
+
+use filehub_auth::rbac::RbacEnforcer;
+use filehub_auth::rbac::policies::SystemPermission;
+use filehub_auth::session::{ImpersonationResult, SessionManager};
+use filehub_core::config::ImpersonationConfig;
+use filehub_core::error::{AppError, codes};
+use filehub_entity::session::Session;
+
+use crate::context::RequestContext;
+
+use super::SessionAudit;
+
+/// Opens, lists and ends impersonation sessions.
+#[derive(Clone)]
+pub struct ImpersonationService {
+    /// Session manager that creates and terminates the sessions.
+    session_manager: Arc<SessionManager>,
+    /// RBAC enforcer.
+    rbac: Arc<RbacEnforcer>,
+    /// Audit log for the start and end of each session.
+    audit: Arc<SessionAudit>,
+    /// Impersonation settings.
+    config: ImpersonationConfig,
+}
+
+impl std::fmt::Debug for ImpersonationService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ImpersonationService")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+/// Request to impersonate a user.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct StartImpersonationRequest {
+    /// Why the administrator needs to act as the user; audited.
+    pub reason: String,
+    /// Session length in minutes; `auth.impersonation.default_minutes`
+    /// if absent.
+    #[serde(default)]
+    pub duration_minutes: Option<u32>,
+}
+
+impl ImpersonationService {
+    /// Creates a new impersonation service.
+    pub fn new(
+        session_manager: Arc<SessionManager>,
+        rbac: Arc<RbacEnforcer>,
+        audit: Arc<SessionAudit>,
+        config: ImpersonationConfig,
+    ) -> Self {
+        Self {
+            session_manager,
+            rbac,
+            audit,
+            config,
+        }
+    }
+
+    /// Opens a session in which the requesting administrator acts as
+    /// `user_id`. The session is not handed out unless its start could be
+    /// audited.
+    #[tracing::instrument(target = "otel", name = "ImpersonationService::start", skip_all)]
+    pub async fn start(
+        &self,
+        ctx: &RequestContext,
+        user_id: Uuid,
+        req: StartImpersonationRequest,
+    ) -> Result<ImpersonationResult, AppError> {
+        if !self.config.enabled {
+            return Err(AppError::forbidden("Impersonation is disabled"));
+        }
+        if ctx.is_impersonated() {
+            return Err(
+                AppError::forbidden("Cannot impersonate from an impersonation session")
+                    .with_code(codes::AUTH_IMPERSONATION_BLOCKED),
+            );
+        }
+        self.rbac
+            .require_permission(&ctx.role, &SystemPermission::UserImpersonate)?;
+
+        let reason = req.reason.trim();
+        if reason.is_empty() {
+            return Err(AppError::validation("A reason is required to impersonate"));
+        }
+        let minutes = req.duration_minutes.unwrap_or(self.config.default_minutes);
+        if minutes == 0 || minutes > self.config.max_minutes {
+            return Err(AppError::validation(format!(
+                "Duration must be between 1 and {} minutes",
+                self.config.max_minutes
+            )));
+        }
+
+        let ip = ctx.ip_address.parse().unwrap_or(IpAddr::from([0, 0, 0, 0]));
+        let result = self
+            .session_manager
+            .impersonate(
+                ctx.user_id,
+                ctx.tenant_id,
+                user_id,
+                chrono::Duration::minutes(minutes as i64),
+                ip,
+                ctx.user_agent.as_deref(),
+            )
+            .await?;
+
+        let details = serde_json::json!({
+            "session_id": result.session.id,
+            "username": result.user.username,
+            "reason": reason,
+            "expires_at": result.expires_at,
+        });
+        if let Err(e) = self
+            .audit
+            .log_event(
+                ctx.user_id,
+                "user.impersonation_started",
+                "user",
+                Some(user_id),
+                Some(details),
+                Some(&ctx.ip_address),
+                ctx.user_agent.as_deref(),
+            )
+            .await
+        {
+            let _ = self
+                .session_manager
+                .admin_terminate(result.session.id, ctx.user_id, "Impersonation not audited")
+                .await;
+            return Err(e);
+        }
+
+        info!(
+            admin_id = %ctx.user_id,
+            user_id = %user_id,
+            session_id = %result.session.id,
+            minutes = minutes,
+            "Impersonation started"
+        );
+
+        Ok(result)
+    }
+
+    /// Lists active impersonation sessions.
+    #[tracing::instrument(target = "otel", name = "ImpersonationService::list", skip_all)]
+    pub async fn list(&self, ctx: &RequestContext) -> Result<Vec<Session>, AppError> {
+        self.rbac
+            .require_permission(&ctx.role, &SystemPermission::SessionViewAll)?;
+
+        self.session_manager.active_impersonations().await
+    }
+
+    /// Ends an impersonation session before it expires.
+    #[tracing::instrument(target = "otel", name = "ImpersonationService::end", skip_all)]
+    pub async fn end(
+        &self,
+        ctx: &RequestContext,
+        session_id: Uuid,
+        reason: &str,
+    ) -> Result<(), AppError> {
+        self.rbac
+            .require_permission(&ctx.role, &SystemPermission::SessionTerminate)?;
+
+        let session = self
+            .session_manager
+            .active_impersonations()
+            .await?
+            .into_iter()
+            .find(|s| s.id == session_id)
+            .ok_or_else(|| AppError::not_found("Impersonation session not found"))?;
+
+        self.session_manager
+            .admin_terminate(
+                session.id,
+                ctx.user_id,
+                &format!("Impersonation ended: {reason}"),
+            )
+            .await?;
+
+        let details = serde_json::json!({
+            "session_id": session.id,
+            "impersonator_id": session.impersonator_id,
+            "reason": reason,
+        });
+        if let Err(e) = self
+            .audit
+            .log_event(
+                ctx.user_id,
+                "user.impersonation_ended",
+                "user",
+                Some(session.user_id),
+                Some(details),
+                Some(&ctx.ip_address),
+                ctx.user_agent.as_deref(),
+            )
+            .await
+        {
+            tracing::warn!(error = %e, "Failed to audit end of impersonation");
+        }
+
+        info!(
+            admin_id = %ctx.user_id,
+            session_id = %session_id,
+            "Impersonation ended by admin"
+        );
+
+        Ok(())
+    }
+}
@@ -1,9 +1,17 @@
 //! Session management services for admin operations.
 
 pub mod audit;
+pub mod audit_export;
+pub mod audit_sink;
+pub mod idle;
+pub mod impersonation;
 pub mod service;
 pub mod termination;
 
 pub use audit::SessionAudit;
+pub use audit_export::AuditExportFormat;
+pub use audit_sink::{AuditShipper, AuditSink};
+pub use idle::IdleSessionNotifier;
+pub use impersonation::ImpersonationService;
 pub use service::SessionService;
 pub use termination::TerminationService;
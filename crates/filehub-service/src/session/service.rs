@@ -1,36 +1,124 @@
-//! Session listing and management for admin views.
+//! Session listing and management for admin views and for users' own
+//! sessions.
 
 use std::sync::Arc;
 
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use uuid::Uuid;
 
 use filehub_auth::rbac::RbacEnforcer;
 use filehub_auth::rbac::policies::SystemPermission;
-use filehub_auth::session::SessionStore;
+use filehub_auth::session::{SessionManager, SessionStore};
 use filehub_core::error::AppError;
-use filehub_entity::session::Session;
+use filehub_entity::session::{DeviceInfo, Session};
 
 use crate::context::RequestContext;
 
-/// Admin session viewing and management service.
-#[derive(Debug, Clone)]
+/// Session viewing and management service.
+#[derive(Clone)]
 pub struct SessionService {
     /// Session store.
     session_store: Arc<SessionStore>,
+    /// Session manager, for revocation.
+    session_manager: Arc<SessionManager>,
     /// RBAC enforcer.
     rbac: Arc<RbacEnforcer>,
 }
 
+impl std::fmt::Debug for SessionService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionService").finish()
+    }
+}
+
+/// One of a user's own sessions, as shown in "where am I logged in".
+#[derive(Debug, Clone, Serialize)]
+pub struct UserSessionInfo {
+    /// Session ID.
+    pub id: Uuid,
+    /// Device the session was opened from.
+    pub device: DeviceInfo,
+    /// Client IP at login.
+    pub ip_address: String,
+    /// Raw User-Agent at login.
+    pub user_agent: Option<String>,
+    /// Login time.
+    pub created_at: DateTime<Utc>,
+    /// Last recorded activity.
+    pub last_activity: DateTime<Utc>,
+    /// Absolute expiry.
+    pub expires_at: DateTime<Utc>,
+    /// Whether this is the session making the request.
+    pub current: bool,
+}
+
+impl UserSessionInfo {
+    /// Describes `session`, flagged as current when it is `current_id`.
+    pub fn new(session: &Session, current_id: Uuid) -> Self {
+        Self {
+            id: session.id,
+            device: session.device(),
+            ip_address: session.ip_address.to_string(),
+            user_agent: session.user_agent.clone(),
+            created_at: session.created_at,
+            last_activity: session.last_activity,
+            expires_at: session.expires_at,
+            current: session.id == current_id,
+        }
+    }
+}
+
 impl SessionService {
     /// Creates a new session service.
-    pub fn new(session_store: Arc<SessionStore>, rbac: Arc<RbacEnforcer>) -> Self {
+    pub fn new(
+        session_store: Arc<SessionStore>,
+        session_manager: Arc<SessionManager>,
+        rbac: Arc<RbacEnforcer>,
+    ) -> Self {
         Self {
             session_store,
+            session_manager,
             rbac,
         }
     }
 
+    /// Lists the requesting user's active sessions, most recently active
+    /// first, with the current one flagged.
+    #[tracing::instrument(target = "otel", name = "SessionService::list_for_user", skip_all)]
+    pub async fn list_for_user(
+        &self,
+        ctx: &RequestContext,
+    ) -> Result<Vec<UserSessionInfo>, AppError> {
+        let mut sessions: Vec<UserSessionInfo> = self
+            .session_store
+            .find_active_by_user(ctx.user_id)
+            .await?
+            .iter()
+            .map(|session| UserSessionInfo::new(session, ctx.session_id))
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.last_activity));
+        Ok(sessions)
+    }
+
+    /// Ends one of the requesting user's other sessions. The current
+    /// session is refused; logging out ends it.
+    #[tracing::instrument(target = "otel", name = "SessionService::revoke", skip_all)]
+    pub async fn revoke(&self, ctx: &RequestContext, session_id: Uuid) -> Result<(), AppError> {
+        if session_id == ctx.session_id {
+            return Err(AppError::validation(
+                "Cannot revoke the current session; log out instead",
+            ));
+        }
+        self.session_manager.revoke(session_id, ctx.user_id).await
+    }
+
     /// Lists all active sessions (admin).
+    #[tracing::instrument(
+        target = "otel",
+        name = "SessionService::list_active_sessions",
+        skip_all
+    )]
     pub async fn list_active_sessions(
         &self,
         ctx: &RequestContext,
@@ -42,6 +130,7 @@ impl SessionService {
     }
 
     /// Gets details for a specific session (admin).
+    #[tracing::instrument(target = "otel", name = "SessionService::get_session", skip_all)]
     pub async fn get_session(
         &self,
         ctx: &RequestContext,
@@ -57,6 +146,11 @@ impl SessionService {
     }
 
     /// Gets active session count for a user.
+    #[tracing::instrument(
+        target = "otel",
+        name = "SessionService::count_user_sessions",
+        skip_all
+    )]
     pub async fn count_user_sessions(
         &self,
         ctx: &RequestContext,
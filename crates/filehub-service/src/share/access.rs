@@ -1,91 +1,380 @@
-//! Share access control — validates share tokens and enforces share restrictions.
+//! Share access control — validates share links and enforces share restrictions.
+//!
+//! Signed links (see [`LinkService`]) are checked for forgery and expiry
+//! from the token alone. A download is then counted with one conditional
+//! update that also re-checks revocation, expiry and the download limit,
+//! so the common case needs no separate lookup. Links issued before links
+//! were signed are still resolved through the database.
+//!
+//! Each view and download is also recorded for the link owner's analytics.
+//! The record is queued as a worker job from a detached task, so it never
+//! delays the response.
 
 use std::sync::Arc;
 
-use chrono::Utc;
+use chrono::{Duration, Utc};
+use uuid::Uuid;
 
 use filehub_auth::password::PasswordHasher;
-use filehub_core::error::AppError;
+use filehub_core::config::ShareAnalyticsConfig;
+use filehub_core::error::{AppError, codes};
+use filehub_database::repositories::job::JobRepository;
 use filehub_database::repositories::share::ShareRepository;
-use filehub_entity::share::Share;
+use filehub_entity::job::{CreateJob, JobPayload, JobPriority};
+use filehub_entity::permission::ResourceType;
+use filehub_entity::share::{CreateShareAccess, Share, ShareAccessKind, ShareStats};
+
+use super::link::{self, LinkClaims, LinkService};
+use crate::context::RequestContext;
+use crate::session::SessionAudit;
+
+/// Job type that records a share link access.
+pub const SHARE_ACCESS_JOB_TYPE: &str = "share_access";
+
+/// How long an access grant lasts after the share password was verified.
+const GRANT_TTL_MINUTES: i64 = 15;
+
+/// Where a public request came from, for the audit log.
+#[derive(Debug, Clone, Copy)]
+pub struct Visitor<'a> {
+    /// Client address.
+    pub ip_address: &'a str,
+    /// `User-Agent` header, if sent.
+    pub user_agent: Option<&'a str>,
+}
+
+/// A share unlocked with its password.
+#[derive(Debug, Clone)]
+pub struct UnlockedShare {
+    /// The share.
+    pub share: Share,
+    /// Access grant to present when downloading.
+    pub access_token: String,
+    /// When the grant stops working.
+    pub expires_at: chrono::DateTime<Utc>,
+}
 
 /// Handles public share access validation.
 #[derive(Debug, Clone)]
 pub struct AccessService {
     /// Share repository.
     share_repo: Arc<ShareRepository>,
+    /// Link token signing and verification.
+    link_service: Arc<LinkService>,
     /// Password hasher for verification.
     hasher: Arc<PasswordHasher>,
+    /// Audit log for public accesses.
+    audit: Arc<SessionAudit>,
+    /// Job queue for access analytics; `None` disables recording.
+    access_jobs: Option<Arc<JobRepository>>,
+    /// What access analytics record.
+    analytics: ShareAnalyticsConfig,
 }
 
 impl AccessService {
     /// Creates a new access service.
-    pub fn new(share_repo: Arc<ShareRepository>, hasher: Arc<PasswordHasher>) -> Self {
-        Self { share_repo, hasher }
+    pub fn new(
+        share_repo: Arc<ShareRepository>,
+        link_service: Arc<LinkService>,
+        hasher: Arc<PasswordHasher>,
+        audit: Arc<SessionAudit>,
+    ) -> Self {
+        Self {
+            share_repo,
+            link_service,
+            hasher,
+            audit,
+            access_jobs: None,
+            analytics: ShareAnalyticsConfig::default(),
+        }
+    }
+
+    /// Records link accesses for analytics through the worker, as
+    /// configured in `shares.analytics`.
+    pub fn with_analytics(
+        mut self,
+        job_repo: Arc<JobRepository>,
+        config: ShareAnalyticsConfig,
+    ) -> Self {
+        self.access_jobs = Some(job_repo);
+        self.analytics = config;
+        self
     }
 
-    /// Validates a share token and returns the share if valid.
-    pub async fn validate_token(&self, token: &str) -> Result<Share, AppError> {
+    /// Access statistics of a share link (only creator or admin can view).
+    #[tracing::instrument(target = "otel", name = "AccessService::share_stats", skip_all)]
+    pub async fn share_stats(
+        &self,
+        ctx: &RequestContext,
+        share_id: Uuid,
+    ) -> Result<ShareStats, AppError> {
         let share = self
             .share_repo
-            .find_by_token(token)
+            .find_by_id(share_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Share not found"))?;
+        if share.created_by != ctx.user_id && !ctx.is_admin() {
+            return Err(AppError::forbidden("You can only view your own shares"));
+        }
+
+        self.share_repo
+            .access_stats(share.id, i64::from(self.analytics.recent_limit))
             .await
-            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .ok_or_else(|| AppError::not_found("Invalid or expired share link"))?;
+    }
 
-        self.validate_share(&share)?;
+    /// Validates a share link and returns the share if it is usable.
+    #[tracing::instrument(target = "otel", name = "AccessService::validate_token", skip_all)]
+    pub async fn validate_token(
+        &self,
+        token: &str,
+        visitor: Visitor<'_>,
+    ) -> Result<Share, AppError> {
+        let share = self.load(token).await?;
 
-        // Update last accessed
         let _ = self
             .share_repo
             .update_last_accessed(share.id, Utc::now())
             .await;
+        self.audit_access(&share, token, "share.link_viewed", visitor)
+            .await;
+        self.record_access(share.id, ShareAccessKind::View, visitor);
 
         Ok(share)
     }
 
-    /// Verifies a password for a password-protected share.
-    pub async fn verify_password(&self, token: &str, password: &str) -> Result<Share, AppError> {
-        let share = self.validate_token(token).await?;
+    /// Verifies the password of a password-protected share and issues an
+    /// access grant for downloading through the link.
+    #[tracing::instrument(target = "otel", name = "AccessService::verify_password", skip_all)]
+    pub async fn verify_password(
+        &self,
+        token: &str,
+        password: &str,
+        visitor: Visitor<'_>,
+    ) -> Result<UnlockedShare, AppError> {
+        let share = self.load(token).await?;
+
+        if let Some(ref hash) = share.password_hash
+            && !self.hasher.verify_password(password, hash)?
+        {
+            self.audit_access(&share, token, "share.link_password_failed", visitor)
+                .await;
+            return Err(AppError::unauthorized("Invalid share password")
+                .with_code(codes::SHARE_INVALID_PASSWORD));
+        }
+
+        let claims = LinkClaims {
+            share_id: share.id,
+            expires_at: share.expires_at,
+            password_protected: true,
+        };
+        let (access_token, expires_at) = self
+            .link_service
+            .grant(&claims, Duration::minutes(GRANT_TTL_MINUTES));
+        self.audit_access(&share, token, "share.link_unlocked", visitor)
+            .await;
+
+        Ok(UnlockedShare {
+            share,
+            access_token,
+            expires_at,
+        })
+    }
 
-        if let Some(ref hash) = share.password_hash {
-            let valid = self.hasher.verify_password(password, hash)?;
-            if !valid {
-                return Err(AppError::unauthorized("Invalid share password"));
+    /// Authorizes one download of the file behind a share link and counts
+    /// it against the link's download limit. Password-protected links need the
+    /// `access_token` issued by [`verify_password`](Self::verify_password).
+    #[tracing::instrument(target = "otel", name = "AccessService::authorize_download", skip_all)]
+    pub async fn authorize_download(
+        &self,
+        token: &str,
+        access_token: Option<&str>,
+        visitor: Visitor<'_>,
+    ) -> Result<Share, AppError> {
+        let share_id = if LinkService::is_signed(token) {
+            let claims = self.link_service.verify(token)?;
+            if claims.password_protected {
+                self.require_grant(claims.share_id, access_token)?;
             }
+            claims.share_id
+        } else {
+            let share = self.load_legacy(token).await?;
+            if share.password_hash.is_some() {
+                self.require_grant(share.id, access_token)?;
+            }
+            share.id
+        };
+
+        let share = match self.share_repo.claim_download(share_id, token).await? {
+            Some(share) => share,
+            None => return Err(self.explain_refusal(share_id, token).await),
+        };
+
+        self.audit_access(&share, token, "share.link_downloaded", visitor)
+            .await;
+        self.record_access(share.id, ShareAccessKind::Download, visitor);
+
+        Ok(share)
+    }
+
+    /// The share behind a link, if still usable, without recording an
+    /// access.
+    pub async fn linked_share(&self, token: &str) -> Result<Share, AppError> {
+        self.load(token).await
+    }
+
+    /// Loads the share behind a link and checks it is still usable.
+    async fn load(&self, token: &str) -> Result<Share, AppError> {
+        if !LinkService::is_signed(token) {
+            return self.load_legacy(token).await;
         }
 
+        let claims = self.link_service.verify(token)?;
+        let share = self
+            .share_repo
+            .find_by_id(claims.share_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Invalid share link"))?;
+        check_current(&share, token)?;
         Ok(share)
     }
 
-    /// Records a download against a share (for download counting).
-    pub async fn record_download(&self, share_id: uuid::Uuid) -> Result<i32, AppError> {
-        self.share_repo
-            .increment_download_count(share_id)
-            .await
-            .map_err(|e| AppError::internal(format!("Failed to record download: {e}")))
+    /// Resolves an unsigned token issued before links were signed.
+    async fn load_legacy(&self, token: &str) -> Result<Share, AppError> {
+        let share = self
+            .share_repo
+            .find_by_token(token)
+            .await?
+            .ok_or_else(|| AppError::not_found("Invalid share link"))?;
+        check_current(&share, token)?;
+        Ok(share)
     }
 
-    /// Validates share is active, not expired, and within download limits.
-    fn validate_share(&self, share: &Share) -> Result<(), AppError> {
-        if share.is_active.unwrap_or(false) {
-            return Err(AppError::not_found("Share link has been deactivated"));
+    fn require_grant(&self, share_id: Uuid, access_token: Option<&str>) -> Result<(), AppError> {
+        if access_token.is_some_and(|grant| self.link_service.check_grant(share_id, grant)) {
+            Ok(())
+        } else {
+            Err(
+                AppError::unauthorized("This share link requires a password")
+                    .with_code(codes::SHARE_PASSWORD_REQUIRED),
+            )
         }
+    }
 
-        if let Some(expires) = share.expires_at {
-            if expires <= Utc::now() {
-                return Err(AppError::not_found("Share link has expired"));
-            }
+    /// Works out why a download was not counted. Only reached when the
+    /// link is no longer usable, so the extra lookup is off the common path.
+    async fn explain_refusal(&self, share_id: Uuid, token: &str) -> AppError {
+        let share = match self.share_repo.find_by_id(share_id).await {
+            Ok(Some(share)) => share,
+            Ok(None) => return AppError::not_found("Invalid share link"),
+            Err(e) => return e,
+        };
+        if let Err(e) = check_current(&share, token) {
+            return e;
+        }
+        if share.resource_type != ResourceType::File {
+            return AppError::validation("Only file share links can be downloaded");
+        }
+        if !share.downloads_allowed() {
+            return AppError::forbidden("Downloads are disabled for this share link");
+        }
+        // Another download took the last slot between the checks.
+        exhausted()
+    }
+
+    /// Queues an analytics record of an access without waiting for it.
+    fn record_access(&self, share_id: Uuid, kind: ShareAccessKind, visitor: Visitor<'_>) {
+        let Some(job_repo) = self.access_jobs.clone() else {
+            return;
+        };
+        if !self.analytics.enabled {
+            return;
         }
 
-        if let Some(max) = share.max_downloads {
-            if share.download_count.unwrap_or(0) >= max {
-                return Err(AppError::not_found(
-                    "Share link has reached its download limit",
-                ));
+        let access = CreateShareAccess {
+            share_id,
+            kind,
+            accessed_at: Utc::now(),
+            ip_address: self
+                .analytics
+                .record_ip
+                .then(|| visitor.ip_address.to_string()),
+            user_agent: visitor.user_agent.map(String::from),
+            // No GeoIP lookup is available yet; once there is one,
+            // `record_country` decides whether its result is kept.
+            country: None,
+        };
+        tokio::spawn(async move {
+            let job = CreateJob {
+                job_type: SHARE_ACCESS_JOB_TYPE.to_string(),
+                queue: "default".to_string(),
+                priority: JobPriority::Low,
+                payload: match serde_json::to_value(JobPayload::ShareAccess(access)) {
+                    Ok(payload) => payload,
+                    Err(e) => {
+                        tracing::warn!(share_id = %share_id, error = %e, "Invalid share access job");
+                        return;
+                    }
+                },
+                max_attempts: 3,
+                scheduled_at: None,
+                created_by: None,
+            };
+            if let Err(e) = job_repo.create(&job).await {
+                tracing::warn!(share_id = %share_id, error = %e, "Failed to queue share access record");
             }
+        });
+    }
+
+    /// Records a public access. Failures are logged rather than returned
+    /// so auditing never blocks access.
+    async fn audit_access(&self, share: &Share, token: &str, action: &str, visitor: Visitor<'_>) {
+        let details = serde_json::json!({
+            "link": link::hash_link_id(token),
+            "resource_type": share.resource_type,
+            "resource_id": share.resource_id,
+            "download_count": share.download_count,
+        });
+        if let Err(e) = self
+            .audit
+            .log_event(
+                share.created_by,
+                action,
+                "share",
+                Some(share.id),
+                Some(details),
+                Some(visitor.ip_address),
+                visitor.user_agent,
+            )
+            .await
+        {
+            tracing::warn!(share_id = %share.id, error = %e, "Failed to audit share access");
         }
+    }
+}
 
-        Ok(())
+/// Checks that `token` is still the live link of an active share that has
+/// neither expired nor run out of downloads.
+fn check_current(share: &Share, token: &str) -> Result<(), AppError> {
+    if !share.is_active.unwrap_or(true) || share.token.as_deref() != Some(token) {
+        return Err(
+            AppError::gone("Share link has been revoked").with_code(codes::SHARE_LINK_REVOKED)
+        );
     }
+
+    if share.expires_at.is_some_and(|at| at <= Utc::now()) {
+        return Err(AppError::gone("Share link has expired").with_code(codes::SHARE_LINK_EXPIRED));
+    }
+
+    if let Some(max) = share.max_downloads
+        && share.download_count.unwrap_or(0) >= max
+    {
+        return Err(exhausted());
+    }
+
+    Ok(())
+}
+
+fn exhausted() -> AppError {
+    AppError::gone("Share link has reached its download limit")
+        .with_code(codes::SHARE_LINK_EXHAUSTED)
 }
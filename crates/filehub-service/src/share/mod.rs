@@ -2,8 +2,12 @@
 
 pub mod access;
 pub mod link;
+pub mod public;
+pub mod request;
 pub mod service;
 
 pub use access::AccessService;
 pub use link::LinkService;
-pub use service::ShareService;
+pub use public::PublicAccessService;
+pub use request::AccessRequestService;
+pub use service::{ShareService, SharedResource};
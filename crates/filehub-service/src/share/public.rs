@@ -0,0 +1,277 @@
+//! Public access — anonymous, read-only access to files and folders marked
+//! world-readable.
+//!
+//! Owners mark a file or folder public; a public folder makes everything
+//! below it public too. Anonymous visitors can then read, but never
+//! change, that content without signing in. Content that is not public is
+//! reported as not found, so its existence is not revealed. Every
+//! anonymous read is audited with the visitor's IP address.
+
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use filehub_auth::acl::EffectivePermissionResolver;
+use filehub_core::error::{AppError, codes};
+use filehub_core::types::{PageRequest, PageResponse};
+use filehub_database::repositories::file::FileRepository;
+use filehub_database::repositories::folder::FolderRepository;
+use filehub_database::repositories::public_resource::PublicResourceRepository;
+use filehub_entity::file::File;
+use filehub_entity::folder::{Folder, FolderSort};
+use filehub_entity::permission::{AclPermission, ResourceType};
+
+use crate::context::RequestContext;
+use crate::file::DownloadService;
+use crate::file::download::DownloadResult;
+use crate::session::SessionAudit;
+
+/// Serves public content to anonymous visitors and manages public marks.
+#[derive(Debug, Clone)]
+pub struct PublicAccessService {
+    /// Public marks.
+    public_repo: Arc<PublicResourceRepository>,
+    /// Folder repository.
+    folder_repo: Arc<FolderRepository>,
+    /// File repository.
+    file_repo: Arc<FileRepository>,
+    /// Reads file content.
+    download_service: Arc<DownloadService>,
+    /// Permission resolver, for marking content public.
+    perm_resolver: Arc<EffectivePermissionResolver>,
+    /// Audit log.
+    audit: Arc<SessionAudit>,
+}
+
+impl PublicAccessService {
+    /// Creates a new public access service.
+    pub fn new(
+        public_repo: Arc<PublicResourceRepository>,
+        folder_repo: Arc<FolderRepository>,
+        file_repo: Arc<FileRepository>,
+        download_service: Arc<DownloadService>,
+        perm_resolver: Arc<EffectivePermissionResolver>,
+        audit: Arc<SessionAudit>,
+    ) -> Self {
+        Self {
+            public_repo,
+            folder_repo,
+            file_repo,
+            download_service,
+            perm_resolver,
+            audit,
+        }
+    }
+
+    /// Gets a public folder.
+    #[tracing::instrument(target = "otel", name = "PublicAccessService::get_folder", skip_all)]
+    pub async fn get_folder(
+        &self,
+        ctx: &RequestContext,
+        folder_id: Uuid,
+    ) -> Result<Folder, AppError> {
+        let folder = self.public_folder(folder_id).await?;
+        self.audit_read(ctx, "public.folder_viewed", "folder", folder_id)
+            .await;
+        Ok(folder)
+    }
+
+    /// Lists the subfolders of a public folder.
+    #[tracing::instrument(target = "otel", name = "PublicAccessService::list_folders", skip_all)]
+    pub async fn list_folders(
+        &self,
+        ctx: &RequestContext,
+        folder_id: Uuid,
+        page: PageRequest,
+        sort: Option<FolderSort>,
+    ) -> Result<PageResponse<Folder>, AppError> {
+        self.public_folder(folder_id).await?;
+        let children = self
+            .folder_repo
+            .find_children(folder_id, &page, &sort.unwrap_or_default())
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to list children: {e}")))?;
+        self.audit_read(ctx, "public.folder_listed", "folder", folder_id)
+            .await;
+        Ok(children)
+    }
+
+    /// Lists the files of a public folder.
+    #[tracing::instrument(target = "otel", name = "PublicAccessService::list_files", skip_all)]
+    pub async fn list_files(
+        &self,
+        ctx: &RequestContext,
+        folder_id: Uuid,
+        page: PageRequest,
+        sort: Option<FolderSort>,
+    ) -> Result<PageResponse<File>, AppError> {
+        self.public_folder(folder_id).await?;
+        let files = self
+            .file_repo
+            .find_by_folder(folder_id, &page, &sort.unwrap_or_default())
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to list files: {e}")))?;
+        self.audit_read(ctx, "public.folder_listed", "folder", folder_id)
+            .await;
+        Ok(files)
+    }
+
+    /// Gets a public file's metadata.
+    #[tracing::instrument(target = "otel", name = "PublicAccessService::get_file", skip_all)]
+    pub async fn get_file(&self, ctx: &RequestContext, file_id: Uuid) -> Result<File, AppError> {
+        let file = self.public_file(file_id).await?;
+        self.audit_read(ctx, "public.file_viewed", "file", file_id)
+            .await;
+        Ok(file)
+    }
+
+    /// Reads a public file's content.
+    #[tracing::instrument(target = "otel", name = "PublicAccessService::download", skip_all)]
+    pub async fn download(
+        &self,
+        ctx: &RequestContext,
+        file_id: Uuid,
+    ) -> Result<DownloadResult, AppError> {
+        self.public_file(file_id).await?;
+        let result = self.download_service.read_shared(file_id).await?;
+        self.audit_read(ctx, "public.file_downloaded", "file", file_id)
+            .await;
+        Ok(result)
+    }
+
+    /// Marks a file or folder public, or removes the mark. Needs owner
+    /// access to it.
+    #[tracing::instrument(target = "otel", name = "PublicAccessService::set_public", skip_all)]
+    pub async fn set_public(
+        &self,
+        ctx: &RequestContext,
+        resource_type: ResourceType,
+        resource_id: Uuid,
+        public: bool,
+    ) -> Result<(), AppError> {
+        let (owner_id, parent_id) = match resource_type {
+            ResourceType::File => {
+                let file = self.find_file(resource_id).await?;
+                (file.owner_id, Some(file.folder_id))
+            }
+            ResourceType::Folder => {
+                let folder = self.find_folder(resource_id).await?;
+                (folder.owner_id, folder.parent_id)
+            }
+            ResourceType::Storage => {
+                return Err(AppError::validation(
+                    "Only files and folders can be made public",
+                ));
+            }
+        };
+        self.perm_resolver
+            .require_permission(
+                ctx.user_id,
+                &ctx.role,
+                resource_type,
+                resource_id,
+                owner_id,
+                parent_id,
+                AclPermission::Owner,
+            )
+            .await?;
+
+        let target_type = match resource_type {
+            ResourceType::File => "file",
+            _ => "folder",
+        };
+        let action = if public {
+            self.public_repo
+                .publish(resource_type, resource_id, ctx.user_id)
+                .await?;
+            format!("public.{target_type}_published")
+        } else {
+            self.public_repo
+                .unpublish(resource_type, resource_id)
+                .await?;
+            format!("public.{target_type}_unpublished")
+        };
+
+        if let Err(e) = self
+            .audit
+            .log_event(
+                ctx.user_id,
+                &action,
+                target_type,
+                Some(resource_id),
+                None,
+                Some(&ctx.ip_address),
+                ctx.user_agent.as_deref(),
+            )
+            .await
+        {
+            tracing::warn!(%resource_id, error = %e, "Failed to audit public mark change");
+        }
+        Ok(())
+    }
+
+    /// Finds a folder, if it is public.
+    async fn public_folder(&self, folder_id: Uuid) -> Result<Folder, AppError> {
+        if !self.public_repo.is_folder_public(folder_id).await? {
+            return Err(folder_not_found());
+        }
+        self.find_folder(folder_id).await
+    }
+
+    /// Finds a file, if it is public.
+    async fn public_file(&self, file_id: Uuid) -> Result<File, AppError> {
+        if !self.public_repo.is_file_public(file_id).await? {
+            return Err(file_not_found());
+        }
+        self.find_file(file_id).await
+    }
+
+    async fn find_folder(&self, folder_id: Uuid) -> Result<Folder, AppError> {
+        self.folder_repo
+            .find_by_id(folder_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+            .ok_or_else(folder_not_found)
+    }
+
+    async fn find_file(&self, file_id: Uuid) -> Result<File, AppError> {
+        self.file_repo
+            .find_by_id(file_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+            .ok_or_else(file_not_found)
+    }
+
+    /// Audits an anonymous read. A failure to audit is logged, not
+    /// returned, like other read audits.
+    async fn audit_read(
+        &self,
+        ctx: &RequestContext,
+        action: &str,
+        target_type: &str,
+        target_id: Uuid,
+    ) {
+        if let Err(e) = self
+            .audit
+            .log_anonymous(
+                action,
+                target_type,
+                Some(target_id),
+                None,
+                &ctx.ip_address,
+                ctx.user_agent.as_deref(),
+            )
+            .await
+        {
+            tracing::warn!(%target_id, error = %e, "Failed to audit public access");
+        }
+    }
+}
+
+fn folder_not_found() -> AppError {
+    AppError::not_found("Folder not found").with_code(codes::FOLDER_NOT_FOUND)
+}
+
+fn file_not_found() -> AppError {
+    AppError::not_found("File not found").with_code(codes::FILE_NOT_FOUND)
+}
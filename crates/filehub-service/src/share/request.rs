@@ -0,0 +1,313 @@
+//! Access requests — a user who can see a file or folder asks its owner
+//! for more access.
+//!
+//! A request can be made by anyone who can already view the resource:
+//! through their own permissions, a user share, or a share link they
+//! present. Each user has at most one pending request per resource. The
+//! owner (or an admin) approves or denies it; an approval grants the access
+//! through [`ShareService::grant_access`]. Requests and decisions are
+//! published as share events, which notify the other party and are written
+//! to the audit log.
+
+use std::sync::Arc;
+
+use tracing::info;
+use uuid::Uuid;
+
+use filehub_auth::acl::EffectivePermissionResolver;
+use filehub_core::error::{AppError, codes};
+use filehub_core::events::ShareEvent;
+use filehub_database::repositories::access_request::AccessRequestRepository;
+use filehub_entity::permission::{AclPermission, ResourceType};
+use filehub_entity::share::{AccessRequest, AccessRequestStatus, CreateAccessRequest};
+
+use super::access::AccessService;
+use super::service::{ShareService, SharedResource, share_event};
+use crate::context::RequestContext;
+
+/// Longest note a requester may attach.
+const MAX_MESSAGE_CHARS: usize = 1000;
+
+/// Request for more access to a resource.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RequestAccess {
+    /// Type of the resource.
+    pub resource_type: ResourceType,
+    /// ID of the resource.
+    pub resource_id: Uuid,
+    /// Permission level asked for.
+    pub permission: AclPermission,
+    /// Note to the owner.
+    pub message: Option<String>,
+    /// Share link the requester viewed the resource through, if they have
+    /// no access of their own.
+    pub share_token: Option<String>,
+}
+
+/// Creates and decides access requests.
+#[derive(Debug, Clone)]
+pub struct AccessRequestService {
+    /// Access request repository.
+    request_repo: Arc<AccessRequestRepository>,
+    /// Share service, for resources, user shares and grants.
+    shares: Arc<ShareService>,
+    /// Link access, for requests made from a share link.
+    links: Arc<AccessService>,
+    /// Permission resolver, for the requester's current access.
+    resolver: Arc<EffectivePermissionResolver>,
+}
+
+impl AccessRequestService {
+    /// Creates a new access request service.
+    pub fn new(
+        request_repo: Arc<AccessRequestRepository>,
+        shares: Arc<ShareService>,
+        links: Arc<AccessService>,
+        resolver: Arc<EffectivePermissionResolver>,
+    ) -> Self {
+        Self {
+            request_repo,
+            shares,
+            links,
+            resolver,
+        }
+    }
+
+    /// Asks the owner of a resource for more access.
+    #[tracing::instrument(
+        target = "otel",
+        name = "AccessRequestService::request_access",
+        skip_all
+    )]
+    pub async fn request_access(
+        &self,
+        ctx: &RequestContext,
+        req: RequestAccess,
+    ) -> Result<AccessRequest, AppError> {
+        if req.permission == AclPermission::Owner {
+            return Err(AppError::validation("Ownership cannot be requested"));
+        }
+        let message = req
+            .message
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty());
+        if message
+            .as_ref()
+            .is_some_and(|m| m.chars().count() > MAX_MESSAGE_CHARS)
+        {
+            return Err(AppError::validation(format!(
+                "message must be at most {MAX_MESSAGE_CHARS} characters"
+            )));
+        }
+
+        let resource = self
+            .shares
+            .resource(req.resource_type, req.resource_id)
+            .await?;
+        if resource.owner_id == ctx.user_id {
+            return Err(AppError::validation("You own this resource"));
+        }
+
+        match self
+            .current_access(ctx, &resource, req.share_token.as_deref())
+            .await?
+        {
+            None => {
+                return Err(AppError::forbidden(
+                    "You need to be able to view a resource to request access to it",
+                ));
+            }
+            Some(current) if current.has_at_least(&req.permission) => {
+                return Err(AppError::conflict(format!(
+                    "You already have {current} access"
+                )));
+            }
+            Some(_) => {}
+        }
+
+        let request = self
+            .request_repo
+            .create(
+                &CreateAccessRequest {
+                    resource_type: resource.resource_type,
+                    resource_id: resource.resource_id,
+                    requester_id: ctx.user_id,
+                    owner_id: resource.owner_id,
+                    permission: req.permission,
+                    message,
+                },
+                |request| {
+                    share_event(
+                        ctx,
+                        ShareEvent::AccessRequested {
+                            request_id: request.id,
+                            resource_type: resource.resource_type.to_string(),
+                            resource_id: resource.resource_id,
+                            resource_name: resource.name.clone(),
+                            owner_id: resource.owner_id,
+                            permission: request.permission.to_string(),
+                            actor_name: ctx.username.clone(),
+                        },
+                    )
+                },
+            )
+            .await?
+            .ok_or_else(|| {
+                AppError::conflict("You already have a pending request for this resource")
+                    .with_code(codes::SHARE_ACCESS_REQUEST_PENDING)
+            })?;
+
+        info!(
+            user_id = %ctx.user_id,
+            request_id = %request.id,
+            resource_id = %request.resource_id,
+            permission = %request.permission,
+            "Access requested"
+        );
+
+        Ok(request)
+    }
+
+    /// Requests awaiting the current user's decision, or all of those
+    /// made to them when `pending_only` is false.
+    #[tracing::instrument(target = "otel", name = "AccessRequestService::incoming", skip_all)]
+    pub async fn incoming(
+        &self,
+        ctx: &RequestContext,
+        pending_only: bool,
+    ) -> Result<Vec<AccessRequest>, AppError> {
+        let status = pending_only.then_some(AccessRequestStatus::Pending);
+        self.request_repo.find_for_owner(ctx.user_id, status).await
+    }
+
+    /// Requests made by the current user.
+    #[tracing::instrument(target = "otel", name = "AccessRequestService::outgoing", skip_all)]
+    pub async fn outgoing(&self, ctx: &RequestContext) -> Result<Vec<AccessRequest>, AppError> {
+        self.request_repo.find_by_requester(ctx.user_id).await
+    }
+
+    /// Approves or denies a pending request; only the owner of the
+    /// resource or an admin may. Approving grants the requested access.
+    #[tracing::instrument(target = "otel", name = "AccessRequestService::decide", skip_all)]
+    pub async fn decide(
+        &self,
+        ctx: &RequestContext,
+        request_id: Uuid,
+        approve: bool,
+    ) -> Result<AccessRequest, AppError> {
+        let request = self
+            .request_repo
+            .find_by_id(request_id)
+            .await?
+            .filter(|r| {
+                r.owner_id == ctx.user_id || r.requester_id == ctx.user_id || ctx.is_admin()
+            })
+            .ok_or_else(|| AppError::not_found("Access request not found"))?;
+        if request.owner_id != ctx.user_id && !ctx.is_admin() {
+            return Err(AppError::forbidden(
+                "Only the owner can decide on this request",
+            ));
+        }
+        if request.status != AccessRequestStatus::Pending {
+            return Err(AppError::conflict("The request has already been decided"));
+        }
+
+        let resource = self
+            .shares
+            .resource(request.resource_type, request.resource_id)
+            .await?;
+        if approve {
+            self.shares
+                .grant_access(ctx, &resource, request.requester_id, request.permission)
+                .await?;
+        }
+
+        let status = if approve {
+            AccessRequestStatus::Approved
+        } else {
+            AccessRequestStatus::Denied
+        };
+        let event = share_event(
+            ctx,
+            ShareEvent::AccessRequestDecided {
+                request_id: request.id,
+                resource_type: resource.resource_type.to_string(),
+                resource_id: resource.resource_id,
+                resource_name: resource.name,
+                requester_id: request.requester_id,
+                permission: request.permission.to_string(),
+                approved: approve,
+                actor_name: ctx.username.clone(),
+            },
+        );
+        let request = self
+            .request_repo
+            .decide(request.id, status, ctx.user_id, &event)
+            .await?
+            .ok_or_else(|| AppError::conflict("The request has already been decided"))?;
+
+        info!(
+            user_id = %ctx.user_id,
+            request_id = %request.id,
+            approved = approve,
+            "Access request decided"
+        );
+
+        Ok(request)
+    }
+
+    /// The requester's current access to `resource`, or `None` if they
+    /// cannot view it.
+    async fn current_access(
+        &self,
+        ctx: &RequestContext,
+        resource: &SharedResource,
+        share_token: Option<&str>,
+    ) -> Result<Option<AclPermission>, AppError> {
+        let mut best: Option<AclPermission> = None;
+        let mut consider = |permission: AclPermission| {
+            if best.is_none_or(|b| !b.has_at_least(&permission)) {
+                best = Some(permission);
+            }
+        };
+
+        for level in [
+            AclPermission::Editor,
+            AclPermission::Commenter,
+            AclPermission::Viewer,
+        ] {
+            let resolved = self
+                .resolver
+                .resolve(
+                    ctx.user_id,
+                    &ctx.role,
+                    resource.resource_type,
+                    resource.resource_id,
+                    resource.owner_id,
+                    resource.parent_folder_id,
+                    level,
+                )
+                .await?;
+            if resolved.granted {
+                consider(level);
+                break;
+            }
+        }
+        for share in self.shares.shares_with(resource, ctx.user_id).await? {
+            consider(share.permission);
+        }
+        if let Some(token) = share_token {
+            let share = self.links.linked_share(token).await?;
+            if share.resource_type != resource.resource_type
+                || share.resource_id != resource.resource_id
+            {
+                return Err(AppError::validation(
+                    "The share link is not for this resource",
+                ));
+            }
+            consider(share.permission);
+        }
+
+        Ok(best)
+    }
+}
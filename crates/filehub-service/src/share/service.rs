@@ -6,27 +6,58 @@ use chrono::Utc;
 use tracing::info;
 use uuid::Uuid;
 
+use filehub_auth::acl::EffectivePermissionResolver;
 use filehub_auth::password::PasswordHasher;
 use filehub_core::error::AppError;
+use filehub_core::events::{DomainEvent, EventPayload, ShareEvent};
 use filehub_core::types::pagination::{PageRequest, PageResponse};
+use filehub_database::repositories::file::FileRepository;
+use filehub_database::repositories::folder::FolderRepository;
+use filehub_database::repositories::permission::AclRepository;
 use filehub_database::repositories::share::ShareRepository;
-use filehub_entity::permission::AclPermission;
+use filehub_entity::permission::{AclEntry, AclInheritance, AclPermission, ResourceType};
 use filehub_entity::share::{CreateShare, Share, ShareType};
 
-use super::link::LinkService;
+use super::link::{LinkClaims, LinkService};
 use crate::context::RequestContext;
 
 /// Manages share creation, listing, and revocation.
+///
+/// Created shares are published on the event bus, which notifies the
+/// recipient of a user share.
 #[derive(Debug, Clone)]
 pub struct ShareService {
     /// Share repository.
     share_repo: Arc<ShareRepository>,
-    /// Link service for token generation.
+    /// File repository, for the owner and name of shared files.
+    file_repo: Arc<FileRepository>,
+    /// Folder repository, for the owner and name of shared folders.
+    folder_repo: Arc<FolderRepository>,
+    /// ACL repository, for granted access.
+    acl_repo: Arc<AclRepository>,
+    /// Permission resolver, whose cache is cleared on grants.
+    resolver: Arc<EffectivePermissionResolver>,
+    /// Link service for signing link tokens.
     link_service: Arc<LinkService>,
     /// Password hasher for password-protected shares.
     hasher: Arc<PasswordHasher>,
 }
 
+/// Owner and location of a file or folder.
+#[derive(Debug, Clone)]
+pub struct SharedResource {
+    /// Type of the resource.
+    pub resource_type: ResourceType,
+    /// ID of the resource.
+    pub resource_id: Uuid,
+    /// Name of the resource.
+    pub name: String,
+    /// Owner of the resource.
+    pub owner_id: Uuid,
+    /// Folder containing the resource, for permission inheritance.
+    pub parent_folder_id: Option<Uuid>,
+}
+
 /// Request to create a new share.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CreateShareRequest {
@@ -69,17 +100,153 @@ impl ShareService {
     /// Creates a new share service.
     pub fn new(
         share_repo: Arc<ShareRepository>,
+        file_repo: Arc<FileRepository>,
+        folder_repo: Arc<FolderRepository>,
+        acl_repo: Arc<AclRepository>,
+        resolver: Arc<EffectivePermissionResolver>,
         link_service: Arc<LinkService>,
         hasher: Arc<PasswordHasher>,
     ) -> Self {
         Self {
             share_repo,
+            file_repo,
+            folder_repo,
+            acl_repo,
+            resolver,
             link_service,
             hasher,
         }
     }
 
+    /// Loads the file or folder a share or access request is about.
+    pub async fn resource(
+        &self,
+        resource_type: ResourceType,
+        resource_id: Uuid,
+    ) -> Result<SharedResource, AppError> {
+        match resource_type {
+            ResourceType::File => {
+                let file = self
+                    .file_repo
+                    .find_by_id(resource_id)
+                    .await?
+                    .ok_or_else(|| AppError::not_found("File not found"))?;
+                Ok(SharedResource {
+                    resource_type,
+                    resource_id,
+                    name: file.name,
+                    owner_id: file.owner_id,
+                    parent_folder_id: Some(file.folder_id),
+                })
+            }
+            ResourceType::Folder => {
+                let folder = self
+                    .folder_repo
+                    .find_by_id(resource_id)
+                    .await?
+                    .ok_or_else(|| AppError::not_found("Folder not found"))?;
+                Ok(SharedResource {
+                    resource_type,
+                    resource_id,
+                    name: folder.name,
+                    owner_id: folder.owner_id,
+                    parent_folder_id: folder.parent_id,
+                })
+            }
+            ResourceType::Storage => Err(AppError::validation(
+                "Only files and folders can be shared with users",
+            )),
+        }
+    }
+
+    /// Active user shares of `resource` with `user_id`.
+    pub async fn shares_with(
+        &self,
+        resource: &SharedResource,
+        user_id: Uuid,
+    ) -> Result<Vec<Share>, AppError> {
+        let shares = self
+            .share_repo
+            .find_shared_with_user_on(user_id, &[resource.resource_id])
+            .await?;
+        Ok(shares
+            .into_iter()
+            .filter(|share| share.resource_type == resource.resource_type && share.is_valid())
+            .collect())
+    }
+
+    /// Grants `user_id` at least `permission` on `resource`, on behalf of
+    /// its owner or an admin.
+    ///
+    /// User shares are not part of the effective permission, so the grant
+    /// is a direct ACL entry; an existing lower entry is raised rather than
+    /// duplicated. The user's shares of the resource are raised to match.
+    #[tracing::instrument(target = "otel", name = "ShareService::grant_access", skip_all)]
+    pub async fn grant_access(
+        &self,
+        ctx: &RequestContext,
+        resource: &SharedResource,
+        user_id: Uuid,
+        permission: AclPermission,
+    ) -> Result<AclEntry, AppError> {
+        if resource.owner_id != ctx.user_id && !ctx.is_admin() {
+            return Err(AppError::forbidden(
+                "Only the owner can grant access to this resource",
+            ));
+        }
+
+        let existing = self
+            .acl_repo
+            .find_user_permission(resource.resource_type, resource.resource_id, user_id)
+            .await?
+            .filter(|entry| entry.user_id == Some(user_id));
+        let entry = match existing {
+            Some(entry) if entry.permission.has_at_least(&permission) => entry,
+            Some(entry) => {
+                self.acl_repo
+                    .update_permission(entry.id, permission)
+                    .await?
+            }
+            None => {
+                self.acl_repo
+                    .create(
+                        resource.resource_type,
+                        resource.resource_id,
+                        Some(user_id),
+                        false,
+                        permission,
+                        AclInheritance::Inherit,
+                        ctx.user_id,
+                        None,
+                    )
+                    .await?
+            }
+        };
+
+        for mut share in self.shares_with(resource, user_id).await? {
+            if !share.permission.has_at_least(&permission) {
+                share.permission = permission;
+                self.share_repo.update(&share).await?;
+            }
+        }
+
+        // A folder's contents inherit the grant, so all of the user's
+        // cached checks may be stale.
+        let _ = self.resolver.invalidate_user_cache(user_id).await;
+
+        info!(
+            user_id = %ctx.user_id,
+            grantee = %user_id,
+            resource_id = %resource.resource_id,
+            permission = %permission,
+            "Access granted"
+        );
+
+        Ok(entry)
+    }
+
     /// Lists shares created by the current user.
+    #[tracing::instrument(target = "otel", name = "ShareService::list_shares", skip_all)]
     pub async fn list_shares(
         &self,
         ctx: &RequestContext,
@@ -92,18 +259,12 @@ impl ShareService {
     }
 
     /// Creates a new share.
+    #[tracing::instrument(target = "otel", name = "ShareService::create_share", skip_all)]
     pub async fn create_share(
         &self,
         ctx: &RequestContext,
         req: CreateShareRequest,
     ) -> Result<Share, AppError> {
-        let token = match req.share_type {
-            ShareType::PublicLink | ShareType::PrivateLink => {
-                Some(self.link_service.generate_token())
-            }
-            ShareType::UserShare => None,
-        };
-
         let password_hash = if let Some(ref password) = req.password {
             Some(self.hasher.hash_password(password)?)
         } else {
@@ -115,13 +276,22 @@ impl ShareService {
                 "shared_with is required for user shares",
             ));
         }
+        if req.share_type == ShareType::PrivateLink && password_hash.is_none() {
+            return Err(AppError::validation(
+                "password is required for private links",
+            ));
+        }
+        if req.max_downloads.is_some_and(|max| max < 1) {
+            return Err(AppError::validation("max_downloads must be at least 1"));
+        }
 
         let share = CreateShare {
             share_type: req.share_type,
             resource_type: req.resource_type,
             resource_id: req.resource_id,
             password_hash,
-            token,
+            // Link tokens sign the share ID, so they are set once it exists.
+            token: None,
             shared_with: req.shared_with,
             permission: req.permission,
             allow_download: req.allow_download,
@@ -130,9 +300,37 @@ impl ShareService {
             created_by: ctx.user_id,
         };
 
+        let resource_name = match share.share_type {
+            ShareType::UserShare => self
+                .resource(share.resource_type, share.resource_id)
+                .await
+                .map(|resource| resource.name)
+                .unwrap_or_default(),
+            _ => String::new(),
+        };
         let share = self
             .share_repo
-            .create(&share)
+            .create(
+                &share,
+                |created| {
+                    (created.share_type != ShareType::UserShare)
+                        .then(|| self.link_service.sign(&link_claims(created)))
+                },
+                |created| {
+                    share_event(
+                        ctx,
+                        ShareEvent::Created {
+                            share_id: created.id,
+                            resource_type: created.resource_type.to_string(),
+                            resource_id: created.resource_id,
+                            share_type: share_type_name(created.share_type).to_string(),
+                            shared_with: created.shared_with.filter(|id| *id != ctx.user_id),
+                            resource_name,
+                            actor_name: ctx.username.clone(),
+                        },
+                    )
+                },
+            )
             .await
             .map_err(|e| AppError::internal(format!("Failed to create share: {e}")))?;
 
@@ -147,6 +345,7 @@ impl ShareService {
     }
 
     /// Gets a share by ID (only creator or admin can view).
+    #[tracing::instrument(target = "otel", name = "ShareService::get_share", skip_all)]
     pub async fn get_share(&self, ctx: &RequestContext, share_id: Uuid) -> Result<Share, AppError> {
         let share = self
             .share_repo
@@ -163,6 +362,7 @@ impl ShareService {
     }
 
     /// Updates a share.
+    #[tracing::instrument(target = "otel", name = "ShareService::update_share", skip_all)]
     pub async fn update_share(
         &self,
         ctx: &RequestContext,
@@ -171,20 +371,35 @@ impl ShareService {
     ) -> Result<Share, AppError> {
         let mut share = self.get_share(ctx, share_id).await?;
 
-        share.allow_download = req.allow_download;
-        share.is_active = req.is_active;
+        if req.max_downloads.flatten().is_some_and(|max| max < 1) {
+            return Err(AppError::validation("max_downloads must be at least 1"));
+        }
 
+        if let Some(allow_download) = req.allow_download {
+            share.allow_download = Some(allow_download);
+        }
+        if let Some(is_active) = req.is_active {
+            share.is_active = Some(is_active);
+        }
         if let Some(permission) = req.permission {
             share.permission = permission;
         }
         if let Some(max_downloads) = req.max_downloads {
             share.max_downloads = max_downloads;
         }
-        if let Some(expires_at) = req.expires_at {
+        if let Some(expires_at) = req.expires_at
+            && expires_at != share.expires_at
+        {
             share.expires_at = expires_at;
+            // The expiry is signed into the link, so changing it issues a
+            // new link and the old one stops working.
+            if share.share_type != ShareType::UserShare {
+                share.token = Some(self.link_service.sign(&link_claims(&share)));
+            }
         }
 
-        self.share_repo
+        let share = self
+            .share_repo
             .update(&share)
             .await
             .map_err(|e| AppError::internal(format!("Failed to update share: {e}")))?;
@@ -199,6 +414,7 @@ impl ShareService {
     }
 
     /// Revokes (deactivates) a share.
+    #[tracing::instrument(target = "otel", name = "ShareService::revoke_share", skip_all)]
     pub async fn revoke_share(&self, ctx: &RequestContext, share_id: Uuid) -> Result<(), AppError> {
         let share = self.get_share(ctx, share_id).await?;
 
@@ -216,3 +432,26 @@ impl ShareService {
         Ok(())
     }
 }
+
+/// A share event caused by `ctx`'s user.
+pub(crate) fn share_event(ctx: &RequestContext, event: ShareEvent) -> DomainEvent {
+    DomainEvent::new(Some(ctx.user_id), EventPayload::Share(event))
+}
+
+/// Claims signed into the link token of `share`.
+fn link_claims(share: &Share) -> LinkClaims {
+    LinkClaims {
+        share_id: share.id,
+        expires_at: share.expires_at,
+        password_protected: share.password_hash.is_some(),
+    }
+}
+
+/// Name of a share type as stored.
+fn share_type_name(share_type: ShareType) -> &'static str {
+    match share_type {
+        ShareType::PublicLink => "public_link",
+        ShareType::PrivateLink => "private_link",
+        ShareType::UserShare => "user_share",
+    }
+}
@@ -0,0 +1,541 @@
+//! Background migration of every file on one storage backend to another.
+//!
+//! A migration walks the source's files in id order, a batch at a time.
+//! Each file's blob and its version blobs are copied to the same paths on
+//! the target and read back to compare SHA-256 digests, then the file's
+//! `storage_id` is switched with a conditional update that fails if the
+//! file changed meanwhile. Locked and recently modified files are left for
+//! the next pass. Progress is recorded after every batch, so a migration
+//! interrupted by a restart resumes from its cursor.
+//!
+//! Blobs are not deleted from the source. Uploads keep going to the
+//! source while it is the default storage, so switch the default first.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use filehub_auth::rbac::policies::SystemPermission;
+use filehub_core::error::AppError;
+use filehub_database::repositories::storage_migration::MigrationBatchProgress;
+use filehub_entity::file::File;
+use filehub_entity::job::model::CreateJob;
+use filehub_entity::job::payload::JobPayload;
+use filehub_entity::job::status::JobPriority;
+use filehub_entity::storage::{CreateStorageMigration, StorageMigration, StorageMigrationStatus};
+use filehub_storage::BandwidthThrottle;
+use filehub_storage::hashing::sha256_hex;
+
+use crate::context::RequestContext;
+
+use super::transfer::TransferService;
+
+/// Job type of a storage migration pass.
+pub const STORAGE_MIGRATION_JOB_TYPE: &str = "storage_migration";
+
+/// Migrations listed by [`TransferService::list_migrations`].
+const MIGRATION_LIST_LIMIT: i64 = 50;
+
+/// Request to migrate every file off a storage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartMigrationRequest {
+    /// Storage files are moved off.
+    pub source_storage_id: Uuid,
+    /// Storage files are moved to.
+    pub target_storage_id: Uuid,
+    /// Only report what would be copied.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Files per batch; `storage.migration.batch_size` if unset.
+    pub batch_size: Option<u32>,
+    /// Copy rate limit; `storage.migration.bytes_per_second` if unset.
+    pub bytes_per_second: Option<u64>,
+}
+
+/// What a migration has to copy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationPlan {
+    /// Storage files are moved off.
+    pub source_storage_id: Uuid,
+    /// Storage files are moved to.
+    pub target_storage_id: Uuid,
+    /// Files on the source.
+    pub files: i64,
+    /// Bytes of those files and their versions.
+    pub bytes: i64,
+}
+
+/// Result of starting a migration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartMigrationResponse {
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// What the migration copies.
+    pub plan: MigrationPlan,
+    /// The created migration; `None` on a dry run.
+    pub migration: Option<StorageMigration>,
+}
+
+/// How a worker's turn on a migration ended.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum MigrationRunOutcome {
+    /// The migration was finished or cancelled; nothing was done.
+    Stopped,
+    /// The time slice ran out; the next slice is queued.
+    Continued,
+    /// The pass left files behind; the next pass is queued.
+    NextPass {
+        /// Pass that will run next.
+        pass: i32,
+        /// Seconds until it runs.
+        after_seconds: u64,
+    },
+    /// The migration reached a terminal status.
+    Finished {
+        /// That status.
+        status: StorageMigrationStatus,
+    },
+}
+
+impl TransferService {
+    /// Starts migrating every file on one storage to another, or on a dry
+    /// run reports how many files and bytes that involves.
+    pub async fn start_migration(
+        &self,
+        ctx: &RequestContext,
+        req: StartMigrationRequest,
+    ) -> Result<StartMigrationResponse, AppError> {
+        self.rbac
+            .require_permission(&ctx.role, &SystemPermission::StorageTransfer)?;
+
+        if req.source_storage_id == req.target_storage_id {
+            return Err(AppError::validation(
+                "Source and target storage must be different",
+            ));
+        }
+        for id in [req.source_storage_id, req.target_storage_id] {
+            self.storage_repo
+                .find_by_id(id)
+                .await
+                .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+                .ok_or_else(|| AppError::not_found(format!("Storage {id} not found")))?;
+            // The provider must be loaded for the worker to reach it.
+            self.storage.get(&id).await?;
+        }
+        let batch_size = req.batch_size.unwrap_or(self.migration_config.batch_size);
+        if batch_size == 0 {
+            return Err(AppError::validation("batch_size must be greater than 0"));
+        }
+
+        let (files, bytes) = self
+            .file_repo
+            .storage_footprint(req.source_storage_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to measure source: {e}")))?;
+        let plan = MigrationPlan {
+            source_storage_id: req.source_storage_id,
+            target_storage_id: req.target_storage_id,
+            files,
+            bytes,
+        };
+        if req.dry_run {
+            return Ok(StartMigrationResponse {
+                dry_run: true,
+                plan,
+                migration: None,
+            });
+        }
+
+        let migration = self
+            .migration_repo
+            .create(&CreateStorageMigration {
+                source_storage_id: req.source_storage_id,
+                target_storage_id: req.target_storage_id,
+                batch_size: batch_size.min(i32::MAX as u32) as i32,
+                bytes_per_second: req
+                    .bytes_per_second
+                    .unwrap_or(self.migration_config.bytes_per_second)
+                    .min(i64::MAX as u64) as i64,
+                files_total: files,
+                bytes_total: bytes,
+                created_by: Some(ctx.user_id),
+            })
+            .await?;
+        self.queue_migration(migration.id, None, Some(ctx.user_id))
+            .await?;
+
+        if let Err(e) = self
+            .audit
+            .log_event(
+                ctx.user_id,
+                "storage.migration_started",
+                "storage",
+                Some(migration.source_storage_id),
+                Some(serde_json::json!({
+                    "migration_id": migration.id,
+                    "target_storage_id": migration.target_storage_id,
+                    "files": files,
+                    "bytes": bytes,
+                })),
+                Some(&ctx.ip_address),
+                ctx.user_agent.as_deref(),
+            )
+            .await
+        {
+            warn!(error = %e, "Failed to audit storage migration start");
+        }
+
+        info!(
+            user_id = %ctx.user_id,
+            migration_id = %migration.id,
+            source = %migration.source_storage_id,
+            target = %migration.target_storage_id,
+            files,
+            bytes,
+            "Storage migration started"
+        );
+
+        Ok(StartMigrationResponse {
+            dry_run: false,
+            plan,
+            migration: Some(migration),
+        })
+    }
+
+    /// Gets a migration.
+    pub async fn get_migration(
+        &self,
+        ctx: &RequestContext,
+        id: Uuid,
+    ) -> Result<StorageMigration, AppError> {
+        self.rbac
+            .require_permission(&ctx.role, &SystemPermission::StorageTransfer)?;
+        self.find_migration(id).await
+    }
+
+    /// Lists recent migrations, newest first.
+    pub async fn list_migrations(
+        &self,
+        ctx: &RequestContext,
+    ) -> Result<Vec<StorageMigration>, AppError> {
+        self.rbac
+            .require_permission(&ctx.role, &SystemPermission::StorageTransfer)?;
+        self.migration_repo.find_recent(MIGRATION_LIST_LIMIT).await
+    }
+
+    /// Cancels an unfinished migration. A batch in progress completes;
+    /// files already moved stay on the target.
+    pub async fn cancel_migration(
+        &self,
+        ctx: &RequestContext,
+        id: Uuid,
+    ) -> Result<StorageMigration, AppError> {
+        self.rbac
+            .require_permission(&ctx.role, &SystemPermission::StorageTransfer)?;
+        self.find_migration(id).await?;
+        if !self
+            .migration_repo
+            .finish(id, StorageMigrationStatus::Cancelled, None)
+            .await?
+        {
+            return Err(AppError::conflict("The migration has already finished"));
+        }
+
+        if let Err(e) = self
+            .audit
+            .log_event(
+                ctx.user_id,
+                "storage.migration_cancelled",
+                "storage_migration",
+                Some(id),
+                None,
+                Some(&ctx.ip_address),
+                ctx.user_agent.as_deref(),
+            )
+            .await
+        {
+            warn!(error = %e, "Failed to audit storage migration cancel");
+        }
+
+        self.find_migration(id).await
+    }
+
+    /// Resumes a failed or cancelled migration from its cursor, or one
+    /// whose worker stopped recording progress (e.g. a crashed node).
+    pub async fn resume_migration(
+        &self,
+        ctx: &RequestContext,
+        id: Uuid,
+    ) -> Result<StorageMigration, AppError> {
+        self.rbac
+            .require_permission(&ctx.role, &SystemPermission::StorageTransfer)?;
+        self.find_migration(id).await?;
+
+        let stale = Duration::from_secs(self.migration_config.slice_seconds.saturating_mul(2));
+        let stale_before = Utc::now()
+            - chrono::Duration::from_std(stale).unwrap_or_else(|_| chrono::Duration::hours(1));
+        let migration = self
+            .migration_repo
+            .reopen(id, stale_before)
+            .await?
+            .ok_or_else(|| {
+                AppError::conflict("Only failed, cancelled or stalled migrations can be resumed")
+            })?;
+        self.queue_migration(id, None, Some(ctx.user_id)).await?;
+
+        if let Err(e) = self
+            .audit
+            .log_event(
+                ctx.user_id,
+                "storage.migration_resumed",
+                "storage_migration",
+                Some(id),
+                Some(serde_json::json!({ "pass": migration.pass })),
+                Some(&ctx.ip_address),
+                ctx.user_agent.as_deref(),
+            )
+            .await
+        {
+            warn!(error = %e, "Failed to audit storage migration resume");
+        }
+
+        Ok(migration)
+    }
+
+    /// Runs a migration for up to `storage.migration.slice_seconds` and
+    /// queues whatever comes next. No permission check; callers are
+    /// trusted background jobs.
+    pub async fn run_migration(&self, id: Uuid) -> Result<MigrationRunOutcome, AppError> {
+        let Some(mut migration) = self.migration_repo.mark_running(id).await? else {
+            return Ok(MigrationRunOutcome::Stopped);
+        };
+        let throttle = BandwidthThrottle::new(migration.bytes_per_second.max(0) as u64);
+        let deadline = Instant::now() + Duration::from_secs(self.migration_config.slice_seconds);
+        let settle = chrono::Duration::seconds(
+            self.migration_config.settle_seconds.min(i64::MAX as u64) as i64,
+        );
+
+        loop {
+            let files = self
+                .file_repo
+                .find_on_storage_after(
+                    migration.source_storage_id,
+                    migration.cursor_file_id,
+                    i64::from(migration.batch_size.max(1)),
+                )
+                .await?;
+            if files.is_empty() {
+                return self.end_pass(&migration).await;
+            }
+
+            let settled_before = Utc::now() - settle;
+            let mut progress = MigrationBatchProgress::default();
+            for file in &files {
+                progress.cursor_file_id = Some(file.id);
+                if file.is_file_locked() || file.updated_at > settled_before {
+                    progress.files_skipped += 1;
+                    continue;
+                }
+                match self.migrate_file(&migration, file, &throttle).await {
+                    Ok(Some(bytes)) => {
+                        progress.files_copied += 1;
+                        progress.bytes_copied += bytes as i64;
+                    }
+                    // Changed while being copied.
+                    Ok(None) => progress.files_skipped += 1,
+                    Err(e) => {
+                        warn!(
+                            migration_id = %id,
+                            file_id = %file.id,
+                            "Failed to migrate file: {e}"
+                        );
+                        progress.files_failed += 1;
+                        progress.last_error = Some(format!("File {}: {}", file.id, e.message));
+                    }
+                }
+            }
+
+            migration = self.migration_repo.record_batch(id, &progress).await?;
+            if migration.status != StorageMigrationStatus::Running {
+                return Ok(MigrationRunOutcome::Stopped);
+            }
+            if Instant::now() >= deadline {
+                self.queue_migration(id, None, migration.created_by).await?;
+                return Ok(MigrationRunOutcome::Continued);
+            }
+        }
+    }
+
+    /// Marks a migration failed, e.g. after its job ran out of attempts.
+    pub async fn fail_migration(&self, id: Uuid, error: &str) -> Result<(), AppError> {
+        self.migration_repo
+            .finish(id, StorageMigrationStatus::Failed, Some(error))
+            .await?;
+        Ok(())
+    }
+
+    async fn find_migration(&self, id: Uuid) -> Result<StorageMigration, AppError> {
+        self.migration_repo
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Storage migration not found"))
+    }
+
+    /// Finishes the migration when nothing is left on the source, or
+    /// queues another pass for the files left behind.
+    async fn end_pass(
+        &self,
+        migration: &StorageMigration,
+    ) -> Result<MigrationRunOutcome, AppError> {
+        let (remaining, _) = self
+            .file_repo
+            .storage_footprint(migration.source_storage_id)
+            .await?;
+
+        let status = if remaining == 0 {
+            StorageMigrationStatus::Completed
+        } else if migration.pass >= self.migration_config.max_passes.min(i32::MAX as u32) as i32 {
+            StorageMigrationStatus::Failed
+        } else {
+            self.migration_repo.start_next_pass(migration.id).await?;
+            let delay = Duration::from_secs(self.migration_config.retry_delay_seconds);
+            self.queue_migration(migration.id, Some(delay), migration.created_by)
+                .await?;
+            info!(
+                migration_id = %migration.id,
+                remaining,
+                "Storage migration pass {} done; retrying files left behind",
+                migration.pass
+            );
+            return Ok(MigrationRunOutcome::NextPass {
+                pass: migration.pass + 1,
+                after_seconds: delay.as_secs(),
+            });
+        };
+
+        let error = (status == StorageMigrationStatus::Failed).then(|| {
+            format!(
+                "{remaining} file(s) are still on the source after {} passes",
+                migration.pass
+            )
+        });
+        self.migration_repo
+            .finish(migration.id, status, error.as_deref())
+            .await?;
+        info!(
+            migration_id = %migration.id,
+            status = ?status,
+            remaining,
+            "Storage migration finished"
+        );
+        Ok(MigrationRunOutcome::Finished { status })
+    }
+
+    /// Copies a file's blobs and points it at the target. Returns the
+    /// bytes copied, or `None` if the file changed before it could be
+    /// switched over.
+    async fn migrate_file(
+        &self,
+        migration: &StorageMigration,
+        file: &File,
+        throttle: &BandwidthThrottle,
+    ) -> Result<Option<u64>, AppError> {
+        let versions = self.file_repo.find_versions(file.id).await?;
+        let mut blobs = vec![(file.storage_path.as_str(), file.checksum_sha256.as_deref())];
+        for version in &versions {
+            if !blobs.iter().any(|(path, _)| *path == version.storage_path) {
+                blobs.push((&version.storage_path, version.checksum_sha256.as_deref()));
+            }
+        }
+
+        let mut copied = 0;
+        for (path, checksum) in blobs {
+            copied += self
+                .copy_verified(migration, path, checksum, throttle)
+                .await?;
+        }
+
+        let moved = self
+            .file_repo
+            .repoint_storage(
+                file.id,
+                migration.source_storage_id,
+                migration.target_storage_id,
+                file.updated_at,
+            )
+            .await?;
+        Ok(moved.then_some(copied))
+    }
+
+    /// Copies one blob and checks that the target holds exactly what the
+    /// source does, and what was recorded at upload when known.
+    async fn copy_verified(
+        &self,
+        migration: &StorageMigration,
+        path: &str,
+        checksum: Option<&str>,
+        throttle: &BandwidthThrottle,
+    ) -> Result<u64, AppError> {
+        let data = self
+            .storage
+            .read(&migration.source_storage_id, path)
+            .await?;
+        let digest = sha256_hex(&data);
+        if let Some(expected) = checksum
+            && !expected.eq_ignore_ascii_case(&digest)
+        {
+            return Err(AppError::internal(format!(
+                "Checksum mismatch on the source for {path}"
+            )));
+        }
+
+        let len = data.len() as u64;
+        throttle.consume(len).await;
+        self.storage
+            .write(&migration.target_storage_id, path, data)
+            .await?;
+
+        let written = self
+            .storage
+            .read(&migration.target_storage_id, path)
+            .await?;
+        if sha256_hex(&written) != digest {
+            return Err(AppError::internal(format!(
+                "Checksum mismatch after copying {path}"
+            )));
+        }
+        Ok(len)
+    }
+
+    /// Queues a migration job, after `delay` if given.
+    async fn queue_migration(
+        &self,
+        migration_id: Uuid,
+        delay: Option<Duration>,
+        created_by: Option<Uuid>,
+    ) -> Result<(), AppError> {
+        let payload = JobPayload::StorageMigration { migration_id };
+        let scheduled_at =
+            delay.and_then(|d| chrono::Duration::from_std(d).ok().map(|d| Utc::now() + d));
+        let job = CreateJob {
+            job_type: STORAGE_MIGRATION_JOB_TYPE.to_string(),
+            queue: "default".to_string(),
+            priority: JobPriority::Low,
+            payload: serde_json::to_value(&payload)
+                .map_err(|e| AppError::internal(format!("Invalid migration job: {e}")))?,
+            max_attempts: 3,
+            scheduled_at,
+            created_by,
+        };
+        self.job_repo
+            .create(&job)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to queue migration job: {e}")))?;
+        Ok(())
+    }
+}
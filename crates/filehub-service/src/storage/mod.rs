@@ -1,7 +1,12 @@
-//! Storage management and cross-storage transfer services.
+//! Storage management, cross-storage transfer and migration services.
 
+pub mod migration;
 pub mod service;
 pub mod transfer;
 
+pub use migration::{
+    MigrationPlan, MigrationRunOutcome, STORAGE_MIGRATION_JOB_TYPE, StartMigrationRequest,
+    StartMigrationResponse,
+};
 pub use service::StorageService;
 pub use transfer::TransferService;
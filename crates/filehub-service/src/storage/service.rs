@@ -47,6 +47,7 @@ impl StorageService {
     }
 
     /// Lists all available storages.
+    #[tracing::instrument(target = "otel", name = "StorageService::list_storages", skip_all)]
     pub async fn list_storages(&self, ctx: &RequestContext) -> Result<Vec<Storage>, AppError> {
         self.rbac
             .require_permission(&ctx.role, &SystemPermission::StorageView)?;
@@ -58,6 +59,7 @@ impl StorageService {
     }
 
     /// Gets a specific storage.
+    #[tracing::instrument(target = "otel", name = "StorageService::get_storage", skip_all)]
     pub async fn get_storage(
         &self,
         ctx: &RequestContext,
@@ -74,6 +76,7 @@ impl StorageService {
     }
 
     /// Gets usage statistics for a storage.
+    #[tracing::instrument(target = "otel", name = "StorageService::get_usage", skip_all)]
     pub async fn get_usage(
         &self,
         ctx: &RequestContext,
@@ -7,18 +7,36 @@ use uuid::Uuid;
 
 use filehub_auth::rbac::RbacEnforcer;
 use filehub_auth::rbac::policies::SystemPermission;
+use filehub_core::config::StorageMigrationConfig;
 use filehub_core::error::AppError;
+use filehub_database::repositories::file::FileRepository;
+use filehub_database::repositories::job::JobRepository;
+use filehub_database::repositories::storage::StorageRepository;
+use filehub_database::repositories::storage_migration::StorageMigrationRepository;
 use filehub_storage::manager::StorageManager;
 
 use crate::context::RequestContext;
+use crate::session::SessionAudit;
 
 /// Handles file transfers between storage backends.
 #[derive(Clone)]
 pub struct TransferService {
     /// Storage manager.
-    storage: Arc<StorageManager>,
+    pub(super) storage: Arc<StorageManager>,
     /// RBAC enforcer.
-    rbac: Arc<RbacEnforcer>,
+    pub(super) rbac: Arc<RbacEnforcer>,
+    /// Storage repository.
+    pub(super) storage_repo: Arc<StorageRepository>,
+    /// File repository.
+    pub(super) file_repo: Arc<FileRepository>,
+    /// Storage migration repository.
+    pub(super) migration_repo: Arc<StorageMigrationRepository>,
+    /// Job repository, for queueing migration passes.
+    pub(super) job_repo: Arc<JobRepository>,
+    /// Audit logger.
+    pub(super) audit: Arc<SessionAudit>,
+    /// Migration defaults and pacing.
+    pub(super) migration_config: StorageMigrationConfig,
 }
 
 impl std::fmt::Debug for TransferService {
@@ -42,8 +60,27 @@ pub struct TransferRequest {
 
 impl TransferService {
     /// Creates a new transfer service.
-    pub fn new(storage: Arc<StorageManager>, rbac: Arc<RbacEnforcer>) -> Self {
-        Self { storage, rbac }
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        storage: Arc<StorageManager>,
+        rbac: Arc<RbacEnforcer>,
+        storage_repo: Arc<StorageRepository>,
+        file_repo: Arc<FileRepository>,
+        migration_repo: Arc<StorageMigrationRepository>,
+        job_repo: Arc<JobRepository>,
+        audit: Arc<SessionAudit>,
+        migration_config: StorageMigrationConfig,
+    ) -> Self {
+        Self {
+            storage,
+            rbac,
+            storage_repo,
+            file_repo,
+            migration_repo,
+            job_repo,
+            audit,
+            migration_config,
+        }
     }
 
     /// Transfers a file between storage backends.
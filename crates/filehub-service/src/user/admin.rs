@@ -1,19 +1,27 @@
 //! Admin user management — CRUD, role changes, status changes, password resets.
 
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use tracing::info;
 use uuid::Uuid;
 
-use filehub_auth::password::{PasswordHasher, PasswordValidator};
+use filehub_auth::password::{PasswordHasher, PasswordValidator, generate_password};
 use filehub_auth::rbac::RbacEnforcer;
 use filehub_auth::rbac::policies::SystemPermission;
+use filehub_auth::session::SessionManager;
 use filehub_core::error::AppError;
 use filehub_core::types::pagination::{PageRequest, PageResponse};
 use filehub_database::repositories::user::UserRepository;
+use filehub_entity::user::model::CreateUser;
 use filehub_entity::user::{User, UserRole, UserStatus};
 
+use super::import::{
+    CsvUserRow, ImportOptions, ImportReport, ImportRowResult, ImportRowStatus, is_plausible_email,
+    parse_users_csv,
+};
 use crate::context::RequestContext;
+use crate::session::SessionAudit;
 
 /// Handles administrative user management operations.
 #[derive(Debug, Clone)]
@@ -26,6 +34,11 @@ pub struct AdminUserService {
     validator: Arc<PasswordValidator>,
     /// RBAC enforcer.
     rbac: Arc<RbacEnforcer>,
+    /// Audit log, for field changes.
+    audit: Arc<SessionAudit>,
+    /// Session manager, to revoke the sessions of disabled users. Absent
+    /// outside the server (e.g. in the CLI).
+    sessions: Option<Arc<SessionManager>>,
 }
 
 /// Request to create a new user.
@@ -59,15 +72,24 @@ impl AdminUserService {
         hasher: Arc<PasswordHasher>,
         validator: Arc<PasswordValidator>,
         rbac: Arc<RbacEnforcer>,
+        audit: Arc<SessionAudit>,
     ) -> Self {
         Self {
             user_repo,
             hasher,
             validator,
             rbac,
+            audit,
+            sessions: None,
         }
     }
 
+    /// Revokes sessions through `sessions` when a user is disabled.
+    pub fn with_session_manager(mut self, sessions: Arc<SessionManager>) -> Self {
+        self.sessions = Some(sessions);
+        self
+    }
+
     /// Lists all users with pagination.
     pub async fn list_users(
         &self,
@@ -88,11 +110,7 @@ impl AdminUserService {
         self.rbac
             .require_permission(&ctx.role, &SystemPermission::UserRead)?;
 
-        self.user_repo
-            .find_by_id(user_id)
-            .await
-            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
-            .ok_or_else(|| AppError::not_found("User not found"))
+        self.find_user(user_id).await
     }
 
     /// Creates a new user.
@@ -187,6 +205,7 @@ impl AdminUserService {
             }
         }
 
+        let before = self.find_user(user_id).await?;
         let update_data = filehub_entity::user::model::UpdateUser {
             id: user_id,
             email: req.email,
@@ -198,6 +217,9 @@ impl AdminUserService {
             .update(&update_data)
             .await
             .map_err(|e| AppError::internal(format!("Failed to update user: {e}")))?;
+        self.audit
+            .log_update(ctx, "user.updated", "user", user_id, &before, &user)
+            .await;
 
         info!(admin_id = %ctx.user_id, target_id = %user_id, "User updated by admin");
 
@@ -218,11 +240,15 @@ impl AdminUserService {
             return Err(AppError::forbidden("Cannot change your own role"));
         }
 
+        let before = self.find_user(user_id).await?;
         let user = self
             .user_repo
             .update_role(user_id, new_role.clone())
             .await
             .map_err(|e| AppError::internal(format!("Failed to change role: {e}")))?;
+        self.audit
+            .log_update(ctx, "user.role_changed", "user", user_id, &before, &user)
+            .await;
 
         info!(
             admin_id = %ctx.user_id,
@@ -234,8 +260,13 @@ impl AdminUserService {
         Ok(user)
     }
 
-    /// Changes a user's status (active, inactive, locked).
-    pub async fn change_status(
+    /// Sets a user's status (active, inactive/disabled, locked).
+    ///
+    /// Disabling or locking a user ends all their sessions and frees their
+    /// license seat at once; their files and audit trail are kept.
+    /// Re-enabling allows logging in again but does not revive old
+    /// sessions.
+    pub async fn set_status(
         &self,
         ctx: &RequestContext,
         user_id: Uuid,
@@ -248,6 +279,7 @@ impl AdminUserService {
             return Err(AppError::forbidden("Cannot change your own status"));
         }
 
+        let before = self.find_user(user_id).await?;
         if new_status == UserStatus::Active {
             self.user_repo
                 .reset_failed_attempts(user_id)
@@ -257,14 +289,23 @@ impl AdminUserService {
 
         let user = self
             .user_repo
-            .update_status(user_id, new_status.clone())
+            .update_status(user_id, new_status)
             .await
             .map_err(|e| AppError::internal(format!("Failed to change status: {e}")))?;
+        self.audit
+            .log_update(ctx, "user.status_changed", "user", user_id, &before, &user)
+            .await;
+
+        let revoked = match &self.sessions {
+            Some(sessions) => sessions.apply_status_change(&user, ctx.user_id).await?,
+            None => 0,
+        };
 
         info!(
             admin_id = %ctx.user_id,
             target_id = %user_id,
             new_status = ?new_status,
+            revoked_sessions = revoked,
             "User status changed"
         );
 
@@ -323,4 +364,224 @@ impl AdminUserService {
 
         Ok(())
     }
+
+    /// Creates users from a CSV file (see [`super::import`]).
+    ///
+    /// Every row is validated first; invalid rows, duplicates within the
+    /// file and rows clashing with existing users are reported as failed
+    /// and the rest are created in transactions of `batch_size` rows. A
+    /// batch that fails on insert is rolled back and all its rows are
+    /// reported with the error. Errors are returned only when the file as
+    /// a whole cannot be read.
+    pub async fn import_csv(
+        &self,
+        ctx: &RequestContext,
+        csv: &str,
+        options: ImportOptions,
+    ) -> Result<ImportReport, AppError> {
+        self.rbac
+            .require_permission(&ctx.role, &SystemPermission::UserCreate)?;
+
+        let rows = parse_users_csv(csv)?;
+        let mut results = Vec::with_capacity(rows.len());
+        // Valid rows waiting to be created: result index, insert data and
+        // the generated password, if any.
+        let mut pending: Vec<(usize, CreateUser, Option<String>)> = Vec::new();
+        let mut seen_emails: HashSet<String> = HashSet::new();
+        let mut seen_usernames: HashSet<String> = HashSet::new();
+
+        for row in rows {
+            let username = row.username.clone().unwrap_or_else(|| row.email.clone());
+            let mut result = ImportRowResult {
+                line: row.line,
+                email: row.email.clone(),
+                username: username.clone(),
+                status: ImportRowStatus::Failed,
+                user_id: None,
+                temporary_password: None,
+                error: None,
+            };
+
+            let checked = self
+                .check_import_row(&row, &username, &mut seen_emails, &mut seen_usernames)
+                .await;
+            match checked {
+                Ok((role, password, generated)) => {
+                    // Hashing is the slow part; a dry run has no use for it.
+                    let password_hash = if options.dry_run {
+                        String::new()
+                    } else {
+                        self.hasher.hash_password(&password)?
+                    };
+                    result.status = ImportRowStatus::WouldCreate;
+                    pending.push((
+                        results.len(),
+                        CreateUser {
+                            username,
+                            email: Some(row.email.clone()),
+                            password_hash,
+                            display_name: Some(row.name.clone()),
+                            role,
+                            created_by: Some(ctx.user_id),
+                        },
+                        generated.then_some(password),
+                    ));
+                }
+                Err(e) => result.error = Some(e.message),
+            }
+            results.push(result);
+        }
+
+        if !options.dry_run {
+            for batch in pending.chunks(options.batch_size.max(1)) {
+                let data: Vec<CreateUser> = batch.iter().map(|(_, d, _)| d.clone()).collect();
+                match self.user_repo.create_batch(&data).await {
+                    Ok(users) => {
+                        for ((idx, _, password), user) in batch.iter().zip(users) {
+                            let result = &mut results[*idx];
+                            result.status = ImportRowStatus::Created;
+                            result.user_id = Some(user.id);
+                            result.temporary_password = password.clone();
+                        }
+                    }
+                    Err(e) => {
+                        for (idx, _, _) in batch {
+                            let result = &mut results[*idx];
+                            result.status = ImportRowStatus::Failed;
+                            result.error = Some(format!("Batch rolled back: {}", e.message));
+                        }
+                    }
+                }
+            }
+        }
+
+        let created = results
+            .iter()
+            .filter(|r| r.status != ImportRowStatus::Failed)
+            .count();
+        let report = ImportReport {
+            dry_run: options.dry_run,
+            total: results.len(),
+            created,
+            failed: results.len() - created,
+            rows: results,
+        };
+
+        if !report.dry_run {
+            let details = serde_json::json!({
+                "total": report.total,
+                "created": report.created,
+                "failed": report.failed,
+                "user_ids": report
+                    .rows
+                    .iter()
+                    .filter_map(|r| r.user_id)
+                    .collect::<Vec<_>>(),
+            });
+            if let Err(e) = self
+                .audit
+                .log_event(
+                    ctx.user_id,
+                    "user.imported",
+                    "user",
+                    None,
+                    Some(details),
+                    Some(&ctx.ip_address),
+                    ctx.user_agent.as_deref(),
+                )
+                .await
+            {
+                tracing::warn!(error = %e, "Failed to audit user import");
+            }
+        }
+
+        info!(
+            admin_id = %ctx.user_id,
+            total = report.total,
+            created = report.created,
+            failed = report.failed,
+            dry_run = report.dry_run,
+            "Users imported from CSV"
+        );
+
+        Ok(report)
+    }
+
+    /// Validates one import row and returns its role, the password to set
+    /// and whether that password was generated.
+    async fn check_import_row(
+        &self,
+        row: &CsvUserRow,
+        username: &str,
+        seen_emails: &mut HashSet<String>,
+        seen_usernames: &mut HashSet<String>,
+    ) -> Result<(UserRole, String, bool), AppError> {
+        if !is_plausible_email(&row.email) {
+            return Err(AppError::validation(format!(
+                "Invalid email address '{}'",
+                row.email
+            )));
+        }
+        if username.len() < 3 {
+            return Err(AppError::validation(
+                "Username must be at least 3 characters",
+            ));
+        }
+        if row.name.is_empty() {
+            return Err(AppError::validation("Name is required"));
+        }
+        let role: UserRole = row.role.parse()?;
+
+        if !seen_emails.insert(row.email.to_lowercase()) {
+            return Err(AppError::conflict(format!(
+                "Email '{}' appears more than once in the file",
+                row.email
+            )));
+        }
+        if !seen_usernames.insert(username.to_lowercase()) {
+            return Err(AppError::conflict(format!(
+                "Username '{username}' appears more than once in the file"
+            )));
+        }
+        if self
+            .user_repo
+            .find_by_email(&row.email)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+            .is_some()
+        {
+            return Err(AppError::conflict(format!(
+                "Email '{}' is already in use",
+                row.email
+            )));
+        }
+        if self
+            .user_repo
+            .find_by_username(username)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+            .is_some()
+        {
+            return Err(AppError::conflict(format!(
+                "Username '{username}' is already taken"
+            )));
+        }
+
+        match &row.password {
+            Some(password) => {
+                self.validator.validate(password)?;
+                Ok((role, password.clone(), false))
+            }
+            None => Ok((role, generate_password(), true)),
+        }
+    }
+
+    /// Loads a user without a permission check.
+    async fn find_user(&self, user_id: Uuid) -> Result<User, AppError> {
+        self.user_repo
+            .find_by_id(user_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+            .ok_or_else(|| AppError::not_found("User not found"))
+    }
 }
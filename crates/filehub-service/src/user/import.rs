@@ -0,0 +1,223 @@
+//! Bulk user import from CSV.
+//!
+//! The file needs a header row. `email`, `name` and `role` columns are
+//! required; `password` and `username` are optional. Rows without a
+//! password get a generated temporary one, and rows without a username use
+//! the email address. Column names are matched case-insensitively and may
+//! appear in any order.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use filehub_core::error::AppError;
+
+/// Rows created per transaction unless the caller asks otherwise.
+pub const DEFAULT_IMPORT_BATCH_SIZE: usize = 100;
+
+/// How an import runs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportOptions {
+    /// Validate every row and report what would happen without creating
+    /// any user.
+    pub dry_run: bool,
+    /// Rows created per transaction. A failing row rolls back its batch.
+    pub batch_size: usize,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            batch_size: DEFAULT_IMPORT_BATCH_SIZE,
+        }
+    }
+}
+
+/// What happened to one row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportRowStatus {
+    /// The user was created.
+    Created,
+    /// The row is valid; nothing was created because of `dry_run`.
+    WouldCreate,
+    /// The row was rejected or its batch failed.
+    Failed,
+}
+
+impl ImportRowStatus {
+    /// Name used in JSON and in the CLI table.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::WouldCreate => "would_create",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// Result for one data row of the file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRowResult {
+    /// Line number in the file, counting the header as line 1.
+    pub line: usize,
+    /// Email from the row, as given.
+    pub email: String,
+    /// Username the user was (or would be) created with.
+    pub username: String,
+    /// Outcome.
+    pub status: ImportRowStatus,
+    /// ID of the created user.
+    pub user_id: Option<Uuid>,
+    /// Password generated for the row, when the file had none. Only
+    /// returned for created users.
+    pub temporary_password: Option<String>,
+    /// Why the row failed.
+    pub error: Option<String>,
+}
+
+/// Outcome of a whole import.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+    /// Data rows in the file.
+    pub total: usize,
+    /// Rows created, or that would be created on a dry run.
+    pub created: usize,
+    /// Rows that failed.
+    pub failed: usize,
+    /// Per-row results, in file order.
+    pub rows: Vec<ImportRowResult>,
+}
+
+/// One data row, by column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvUserRow {
+    /// Line number in the file.
+    pub line: usize,
+    /// `email` column.
+    pub email: String,
+    /// `name` column.
+    pub name: String,
+    /// `role` column.
+    pub role: String,
+    /// `password` column, if present and not empty.
+    pub password: Option<String>,
+    /// `username` column, if present and not empty.
+    pub username: Option<String>,
+}
+
+/// Parses an import file into rows. Fails only when the file as a whole
+/// is unusable (no header, missing columns, unterminated quote); row-level
+/// problems are left to validation. Blank lines are skipped.
+pub fn parse_users_csv(input: &str) -> Result<Vec<CsvUserRow>, AppError> {
+    let records = parse_records(input)?;
+    let mut records = records
+        .into_iter()
+        .filter(|(_, fields)| !(fields.len() == 1 && fields[0].trim().is_empty()));
+
+    let (_, header) = records
+        .next()
+        .ok_or_else(|| AppError::validation("The import file is empty"))?;
+    let header: Vec<String> = header
+        .iter()
+        .map(|h| h.trim().trim_start_matches('\u{feff}').to_ascii_lowercase())
+        .collect();
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let required = |name: &str| {
+        column(name)
+            .ok_or_else(|| AppError::validation(format!("The import file has no '{name}' column")))
+    };
+    let email = required("email")?;
+    let name = required("name")?;
+    let role = required("role")?;
+    let password = column("password");
+    let username = column("username");
+
+    Ok(records
+        .map(|(line, fields)| {
+            let get = |idx: usize| fields.get(idx).map(|f| f.trim()).unwrap_or_default();
+            let optional = |idx: Option<usize>| {
+                idx.map(get)
+                    .filter(|value| !value.is_empty())
+                    .map(String::from)
+            };
+            CsvUserRow {
+                line,
+                email: get(email).to_string(),
+                name: get(name).to_string(),
+                role: get(role).to_string(),
+                password: optional(password),
+                username: optional(username),
+            }
+        })
+        .collect())
+}
+
+/// Splits CSV text into records with their starting line numbers.
+/// Handles RFC 4180 quoting, including quoted line breaks.
+fn parse_records(input: &str) -> Result<Vec<(usize, Vec<String>)>, AppError> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            ',' => fields.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut fields)));
+                line += 1;
+                record_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(AppError::validation(format!(
+            "Unterminated quoted field starting on line {record_line}"
+        )));
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((record_line, fields));
+    }
+    Ok(records)
+}
+
+/// Basic shape check for an email address: one `@`, a non-empty local
+/// part and a dotted domain, no whitespace.
+pub fn is_plausible_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.contains('@')
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !email.chars().any(char::is_whitespace)
+}
@@ -1,7 +1,11 @@
 //! User profile and admin user management services.
 
 pub mod admin;
+pub mod import;
+pub mod quota;
 pub mod service;
 
 pub use admin::AdminUserService;
+pub use import::{ImportOptions, ImportReport, ImportRowResult, ImportRowStatus};
+pub use quota::{QuotaExceeded, QuotaService, UsageReport};
 pub use service::UserService;
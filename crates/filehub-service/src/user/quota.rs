@@ -0,0 +1,468 @@
+//! API usage quotas — per-user call and bandwidth accounting against the
+//! allowances of the user's tier.
+//!
+//! Each node counts the calls and bytes of its own requests in memory and
+//! flushes them to the database as additive deltas every
+//! `server.quotas.flush_interval_seconds`, and once more on shutdown. A
+//! user's usage is what the database held at the last flush plus what
+//! this node has counted since, so other nodes' traffic is seen within a
+//! flush interval and a restart loses nothing that was flushed.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use filehub_auth::rbac::RbacEnforcer;
+use filehub_auth::rbac::policies::SystemPermission;
+use filehub_core::config::{QuotaConfig, QuotaTier};
+use filehub_core::error::AppError;
+use filehub_database::repositories::api_usage::{ApiUsageRepository, UsageTotals};
+use filehub_database::repositories::user::UserRepository;
+use filehub_entity::user::{MonthlyUsage, Usage, UsagePeriod};
+
+use crate::context::RequestContext;
+use crate::session::SessionAudit;
+
+/// Users idle this long with nothing left to flush are forgotten.
+const IDLE_EVICTION: Duration = Duration::from_secs(15 * 60);
+
+/// Months of history in a usage report.
+const HISTORY_MONTHS: i64 = 12;
+
+/// A call refused because a quota period is used up.
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaExceeded {
+    /// The period whose allowance is used up.
+    pub period: UsagePeriod,
+    /// The user's tier.
+    pub tier: String,
+    /// The tier's allowance for the period.
+    pub limit: Usage,
+    /// When the period's usage resets.
+    pub resets_at: DateTime<Utc>,
+}
+
+impl QuotaExceeded {
+    /// Seconds until the period resets, at least 1.
+    pub fn retry_after(&self) -> u64 {
+        (self.resets_at - Utc::now()).num_seconds().max(1) as u64
+    }
+}
+
+/// Usage of one quota period.
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodUsage {
+    /// The period.
+    pub period: UsagePeriod,
+    /// Calls and bytes used so far.
+    pub used: Usage,
+    /// The tier's allowance; 0 is unlimited.
+    pub limit: Usage,
+    /// When the period's usage resets.
+    pub resets_at: DateTime<Utc>,
+}
+
+/// A user's quota tier and consumption.
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageReport {
+    /// Whether quotas are enforced.
+    pub enabled: bool,
+    /// The user's tier.
+    pub tier: String,
+    /// Usage of the current day and month.
+    pub periods: Vec<PeriodUsage>,
+    /// Flushed usage per month, most recent first.
+    pub history: Vec<MonthlyUsage>,
+}
+
+/// What a node knows of one user's usage.
+#[derive(Debug)]
+struct UserUsage {
+    /// Assigned tier, if any.
+    tier: Option<String>,
+    /// Whether the stored totals have been read from the database.
+    loaded: bool,
+    /// Day the stored totals are for.
+    day: NaiveDate,
+    /// Flushed usage of `day`.
+    stored_day: Usage,
+    /// Flushed usage of the month of `day`.
+    stored_month: Usage,
+    /// Counted since the last flush, by day.
+    pending: BTreeMap<NaiveDate, Usage>,
+    /// Being written by a flush in progress, by day.
+    flushing: BTreeMap<NaiveDate, Usage>,
+    /// When a call was last counted.
+    last_seen: Instant,
+}
+
+impl UserUsage {
+    fn new(today: NaiveDate) -> Self {
+        Self {
+            tier: None,
+            loaded: false,
+            day: today,
+            stored_day: Usage::default(),
+            stored_month: Usage::default(),
+            pending: BTreeMap::new(),
+            flushing: BTreeMap::new(),
+            last_seen: Instant::now(),
+        }
+    }
+
+    /// Takes the stored totals and tier from a database read.
+    fn store(&mut self, totals: &UsageTotals, day: NaiveDate) {
+        self.tier = totals.tier.clone();
+        self.loaded = true;
+        self.day = day;
+        self.stored_day = Usage::from_db(totals.day_calls, totals.day_bytes);
+        self.stored_month = Usage::from_db(totals.month_calls, totals.month_bytes);
+    }
+
+    /// Usage of the period containing `today`.
+    fn usage(&self, period: UsagePeriod, today: NaiveDate) -> Usage {
+        let start = period.start(today);
+        let mut usage = if period.start(self.day) == start {
+            match period {
+                UsagePeriod::Day => self.stored_day,
+                UsagePeriod::Month => self.stored_month,
+            }
+        } else {
+            Usage::default()
+        };
+        for (day, counted) in self.pending.iter().chain(&self.flushing) {
+            if period.start(*day) == start {
+                usage.add(*counted);
+            }
+        }
+        usage
+    }
+}
+
+/// Counts API usage and enforces quota tiers.
+#[derive(Debug)]
+pub struct QuotaService {
+    /// Usage counters and tier assignments.
+    usage_repo: Arc<ApiUsageRepository>,
+    /// User repository, to check the users tiers are assigned to.
+    user_repo: Arc<UserRepository>,
+    /// RBAC enforcer.
+    rbac: Arc<RbacEnforcer>,
+    /// Audit log.
+    audit: Arc<SessionAudit>,
+    /// Quota settings.
+    config: QuotaConfig,
+    /// Usage of recently active users.
+    users: Mutex<HashMap<Uuid, UserUsage>>,
+}
+
+impl QuotaService {
+    /// Creates a new quota service.
+    pub fn new(
+        usage_repo: Arc<ApiUsageRepository>,
+        user_repo: Arc<UserRepository>,
+        rbac: Arc<RbacEnforcer>,
+        audit: Arc<SessionAudit>,
+        config: QuotaConfig,
+    ) -> Self {
+        Self {
+            usage_repo,
+            user_repo,
+            rbac,
+            audit,
+            config,
+            users: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether quotas are counted and enforced.
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Checks whether a user may make another call. A user whose usage
+    /// cannot be read is let through.
+    pub async fn check(&self, user_id: Uuid) -> Option<QuotaExceeded> {
+        if !self.config.enabled {
+            return None;
+        }
+        if let Err(e) = self.ensure_loaded(user_id).await {
+            tracing::warn!(%user_id, error = %e, "API usage unavailable, quota not enforced");
+            return None;
+        }
+
+        let now = Utc::now();
+        let today = now.date_naive();
+        let users = self.lock();
+        let entry = users.get(&user_id)?;
+        let (tier_name, tier) = self.tier(entry.tier.as_deref());
+        UsagePeriod::ALL
+            .into_iter()
+            .find(|period| entry.usage(*period, today).exhausts(&tier, *period))
+            .map(|period| QuotaExceeded {
+                period,
+                tier: tier_name,
+                limit: period.limit(&tier),
+                resets_at: period.resets_at(now),
+            })
+    }
+
+    /// Counts one call transferring `bytes` bytes.
+    pub fn record(&self, user_id: Uuid, bytes: u64) {
+        if !self.config.enabled {
+            return;
+        }
+        let today = Utc::now().date_naive();
+        let mut users = self.lock();
+        let entry = users
+            .entry(user_id)
+            .or_insert_with(|| UserUsage::new(today));
+        entry
+            .pending
+            .entry(today)
+            .or_default()
+            .add(Usage::new(1, bytes));
+        entry.last_seen = Instant::now();
+    }
+
+    /// Writes counted usage to the database and refreshes what is known
+    /// of the users it belongs to.
+    pub async fn flush(&self) -> Result<(), AppError> {
+        let today = Utc::now().date_naive();
+        let (deltas, user_ids) = {
+            let mut users = self.lock();
+            users.retain(|_, entry| {
+                !entry.pending.is_empty()
+                    || !entry.flushing.is_empty()
+                    || entry.last_seen.elapsed() < IDLE_EVICTION
+            });
+            let mut deltas = Vec::new();
+            for (user_id, entry) in users.iter_mut() {
+                for (day, usage) in std::mem::take(&mut entry.pending) {
+                    entry.flushing.entry(day).or_default().add(usage);
+                }
+                deltas.extend(
+                    entry
+                        .flushing
+                        .iter()
+                        .map(|(day, usage)| (*user_id, *day, *usage)),
+                );
+            }
+            (deltas, users.keys().copied().collect::<Vec<_>>())
+        };
+
+        if let Err(e) = self.usage_repo.add_daily(&deltas).await {
+            // Keep the counts to write them with the next flush.
+            let mut users = self.lock();
+            for entry in users.values_mut() {
+                for (day, usage) in std::mem::take(&mut entry.flushing) {
+                    entry.pending.entry(day).or_default().add(usage);
+                }
+            }
+            return Err(e);
+        }
+
+        let totals = self.usage_repo.totals(&user_ids, today).await;
+        let mut users = self.lock();
+        match &totals {
+            Ok(totals) => {
+                for row in totals {
+                    if let Some(entry) = users.get_mut(&row.user_id) {
+                        entry.store(row, today);
+                        entry.flushing.clear();
+                    }
+                }
+            }
+            Err(_) => {
+                // Written but not re-read: count the writes as stored.
+                for entry in users.values_mut() {
+                    for (day, usage) in std::mem::take(&mut entry.flushing) {
+                        if day == entry.day {
+                            entry.stored_day.add(usage);
+                        }
+                        if UsagePeriod::Month.start(day) == UsagePeriod::Month.start(entry.day) {
+                            entry.stored_month.add(usage);
+                        }
+                    }
+                }
+            }
+        }
+        totals.map(|_| ())
+    }
+
+    /// Flushes usage every `flush_interval_seconds` until `cancel` fires.
+    pub async fn run_flusher(&self, mut cancel: watch::Receiver<bool>) {
+        if !self.config.enabled {
+            return;
+        }
+        let mut interval = tokio::time::interval(Duration::from_secs(
+            self.config.flush_interval_seconds.max(1),
+        ));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = cancel.changed() => {
+                    if *cancel.borrow() {
+                        break;
+                    }
+                }
+                _ = interval.tick() => {
+                    if let Err(e) = self.flush().await {
+                        tracing::warn!(error = %e, "Failed to flush API usage");
+                    }
+                }
+            }
+        }
+    }
+
+    /// The caller's tier and consumption.
+    #[tracing::instrument(target = "otel", name = "QuotaService::usage", skip_all)]
+    pub async fn usage(&self, ctx: &RequestContext) -> Result<UsageReport, AppError> {
+        let user_id = ctx.user_id;
+        let now = Utc::now();
+        let today = now.date_naive();
+
+        let history = self
+            .usage_repo
+            .monthly_history(user_id, HISTORY_MONTHS)
+            .await?;
+        let (assigned, day, month) = if self.config.enabled {
+            self.ensure_loaded(user_id).await?;
+            let users = self.lock();
+            match users.get(&user_id) {
+                Some(entry) => (
+                    entry.tier.clone(),
+                    entry.usage(UsagePeriod::Day, today),
+                    entry.usage(UsagePeriod::Month, today),
+                ),
+                None => (None, Usage::default(), Usage::default()),
+            }
+        } else {
+            let totals = self.usage_repo.totals(&[user_id], today).await?;
+            let mut entry = UserUsage::new(today);
+            if let Some(row) = totals.first() {
+                entry.store(row, today);
+            }
+            (
+                entry.tier.clone(),
+                entry.usage(UsagePeriod::Day, today),
+                entry.usage(UsagePeriod::Month, today),
+            )
+        };
+
+        let (tier_name, tier) = self.tier(assigned.as_deref());
+        let periods = [(UsagePeriod::Day, day), (UsagePeriod::Month, month)]
+            .into_iter()
+            .map(|(period, used)| PeriodUsage {
+                period,
+                used,
+                limit: period.limit(&tier),
+                resets_at: period.resets_at(now),
+            })
+            .collect();
+
+        Ok(UsageReport {
+            enabled: self.config.enabled,
+            tier: tier_name,
+            periods,
+            history,
+        })
+    }
+
+    /// Puts a user on a tier, or back on the default tier with `None`.
+    #[tracing::instrument(target = "otel", name = "QuotaService::set_tier", skip_all)]
+    pub async fn set_tier(
+        &self,
+        ctx: &RequestContext,
+        user_id: Uuid,
+        tier: Option<String>,
+    ) -> Result<(), AppError> {
+        self.rbac
+            .require_permission(&ctx.role, &SystemPermission::UserUpdate)?;
+
+        if let Some(ref tier) = tier
+            && !self.config.tiers.contains_key(tier)
+        {
+            return Err(AppError::validation(format!(
+                "Unknown quota tier '{tier}' (expected one of: {})",
+                self.config
+                    .tiers
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+        self.user_repo
+            .find_by_id(user_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Database error: {e}")))?
+            .ok_or_else(|| AppError::not_found("User not found"))?;
+
+        let before = self.usage_repo.find_tier(user_id).await?.map(|t| t.tier);
+        match tier {
+            Some(ref tier) => {
+                self.usage_repo.set_tier(user_id, tier, ctx.user_id).await?;
+            }
+            None => {
+                self.usage_repo.clear_tier(user_id).await?;
+            }
+        }
+        if let Some(entry) = self.lock().get_mut(&user_id) {
+            entry.tier = tier.clone();
+        }
+
+        self.audit
+            .log_update(
+                ctx,
+                "user.quota_tier_changed",
+                "user",
+                user_id,
+                &serde_json::json!({ "tier": before }),
+                &serde_json::json!({ "tier": tier }),
+            )
+            .await;
+        tracing::info!(admin_id = %ctx.user_id, target_id = %user_id, ?tier, "Quota tier changed");
+        Ok(())
+    }
+
+    /// Reads a user's flushed usage and tier, the first time they are seen.
+    async fn ensure_loaded(&self, user_id: Uuid) -> Result<(), AppError> {
+        if self.lock().get(&user_id).is_some_and(|entry| entry.loaded) {
+            return Ok(());
+        }
+        let today = Utc::now().date_naive();
+        let totals = self.usage_repo.totals(&[user_id], today).await?;
+        let mut users = self.lock();
+        let entry = users
+            .entry(user_id)
+            .or_insert_with(|| UserUsage::new(today));
+        if !entry.loaded
+            && let Some(row) = totals.first()
+        {
+            entry.store(row, today);
+        }
+        Ok(())
+    }
+
+    /// The tier a user is on: the assigned one if it is still configured,
+    /// otherwise the default.
+    fn tier(&self, assigned: Option<&str>) -> (String, QuotaTier) {
+        let name = assigned
+            .filter(|name| self.config.tiers.contains_key(*name))
+            .unwrap_or(&self.config.default_tier);
+        let tier = self.config.tiers.get(name).copied().unwrap_or_default();
+        (name.to_string(), tier)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<Uuid, UserUsage>> {
+        self.users
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -6,11 +6,12 @@ use chrono::Utc;
 use tracing::info;
 
 use filehub_auth::password::{PasswordHasher, PasswordValidator};
-use filehub_core::error::AppError;
+use filehub_core::error::{AppError, codes};
 use filehub_database::repositories::user::UserRepository;
 use filehub_entity::user::{User, model::UpdateUser};
 
 use crate::context::RequestContext;
+use crate::session::SessionAudit;
 
 /// Handles user self-service operations.
 #[derive(Debug, Clone)]
@@ -21,6 +22,8 @@ pub struct UserService {
     hasher: Arc<PasswordHasher>,
     /// Password validator.
     validator: Arc<PasswordValidator>,
+    /// Audit log, for field changes.
+    audit: Arc<SessionAudit>,
 }
 
 /// Data for updating a user's own profile.
@@ -38,15 +41,18 @@ impl UserService {
         user_repo: Arc<UserRepository>,
         hasher: Arc<PasswordHasher>,
         validator: Arc<PasswordValidator>,
+        audit: Arc<SessionAudit>,
     ) -> Self {
         Self {
             user_repo,
             hasher,
             validator,
+            audit,
         }
     }
 
     /// Gets the current user's full profile.
+    #[tracing::instrument(target = "otel", name = "UserService::get_profile", skip_all)]
     pub async fn get_profile(&self, ctx: &RequestContext) -> Result<User, AppError> {
         self.user_repo
             .find_by_id(ctx.user_id)
@@ -56,12 +62,14 @@ impl UserService {
     }
 
     /// Updates the current user's profile fields.
+    #[tracing::instrument(target = "otel", name = "UserService::update_profile", skip_all)]
     pub async fn update_profile(
         &self,
         ctx: &RequestContext,
         req: UpdateProfileRequest,
     ) -> Result<User, AppError> {
-        let mut user = self.get_profile(ctx).await?;
+        let before = self.get_profile(ctx).await?;
+        let mut user = before.clone();
 
         if let Some(display_name) = req.display_name {
             if display_name.trim().is_empty() {
@@ -102,6 +110,9 @@ impl UserService {
             .update(&updated_user)
             .await
             .map_err(|e| AppError::internal(format!("Failed to update profile: {e}")))?;
+        self.audit
+            .log_update(ctx, "user.updated", "user", user.id, &before, &user)
+            .await;
 
         info!(user_id = %ctx.user_id, "Profile updated");
 
@@ -109,6 +120,7 @@ impl UserService {
     }
 
     /// Changes the current user's password.
+    #[tracing::instrument(target = "otel", name = "UserService::change_password", skip_all)]
     pub async fn change_password(
         &self,
         ctx: &RequestContext,
@@ -122,7 +134,8 @@ impl UserService {
             .hasher
             .verify_password(current_password, &user.password_hash)?;
         if !valid {
-            return Err(AppError::unauthorized("Current password is incorrect"));
+            return Err(AppError::unauthorized("Current password is incorrect")
+                .with_code(codes::AUTH_INVALID_PASSWORD));
         }
 
         // Validate new password
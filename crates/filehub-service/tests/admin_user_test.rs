@@ -0,0 +1,168 @@
+//! Account status changes and the sessions they revoke, against
+//! PostgreSQL.
+
+mod common;
+
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+
+use filehub_auth::seat::allocator::{AllocationResult, PoolState};
+use filehub_auth::{
+    JwtDecoder, JwtEncoder, PasswordHasher, PasswordValidator, SeatAllocator, SessionLimiter,
+    SessionManager, SessionStore,
+};
+use filehub_core::config::{AuthConfig, SessionConfig};
+use filehub_core::error::AppError;
+use filehub_database::repositories::session::SessionRepository;
+use filehub_database::repositories::session_limit::SessionLimitRepository;
+use filehub_entity::user::{UserRole, UserStatus};
+use filehub_service::AdminUserService;
+
+use common::{Fixture, context};
+
+/// Grants every seat and keeps the keys released.
+#[derive(Debug, Default)]
+struct RecordingSeats {
+    released: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl SeatAllocator for RecordingSeats {
+    async fn try_allocate(
+        &self,
+        _user_key: &str,
+        _role: &str,
+    ) -> Result<AllocationResult, AppError> {
+        Ok(AllocationResult::Granted)
+    }
+
+    async fn release(&self, user_key: &str) -> Result<(), AppError> {
+        self.released.lock().unwrap().push(user_key.to_string());
+        Ok(())
+    }
+
+    async fn pool_state(&self) -> Result<PoolState, AppError> {
+        Ok(PoolState {
+            total_seats: 1000,
+            checked_out: 0,
+            available: 1000,
+            admin_reserved: 0,
+            active_sessions: 0,
+        })
+    }
+
+    async fn set_total_seats(&self, _total: u32) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    async fn set_admin_reserved(&self, _count: u32) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    async fn reconcile(&self, _actual_active_sessions: u32) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_disabling_a_user_revokes_sessions_for_good() {
+    let Some(fx) = Fixture::new().await else {
+        return;
+    };
+    let auth: AuthConfig = serde_json::from_value(serde_json::json!({})).unwrap();
+    let session_config: SessionConfig = serde_json::from_value(serde_json::json!({})).unwrap();
+
+    let seats = Arc::new(RecordingSeats::default());
+    let jwt_decoder = Arc::new(JwtDecoder::new(&auth, fx.cache.clone()));
+    let store = Arc::new(SessionStore::new(
+        Arc::new(SessionRepository::new(fx.pool.clone())),
+        session_config.clone(),
+    ));
+    let hasher = Arc::new(PasswordHasher::new());
+    let sessions = Arc::new(SessionManager::new(
+        Arc::new(JwtEncoder::new(&auth)),
+        jwt_decoder.clone(),
+        store.clone(),
+        fx.users.clone(),
+        hasher.clone(),
+        seats.clone(),
+        Arc::new(SessionLimiter::new(
+            Arc::new(SessionLimitRepository::new(fx.pool.clone())),
+            session_config.clone(),
+        )),
+        fx.cache.clone(),
+        auth.clone(),
+        session_config,
+    ));
+    let service = AdminUserService::new(
+        fx.users.clone(),
+        hasher,
+        Arc::new(PasswordValidator::new(&auth)),
+        fx.rbac.clone(),
+        fx.audit.clone(),
+    )
+    .with_session_manager(sessions);
+
+    let admin = context(&fx.user(UserRole::Admin).await);
+    let user = fx.user(UserRole::Viewer).await;
+    let bystander = fx.user(UserRole::Viewer).await;
+    let key = user.id.to_string();
+    let mut opened = Vec::new();
+    for (owner, n) in [(&user, 1), (&user, 2), (&bystander, 3)] {
+        let session = store
+            .create_session(
+                owner.id,
+                &format!("access-{n}-{}", owner.id),
+                &format!("refresh-{n}-{}", owner.id),
+                IpAddr::V4(Ipv4Addr::LOCALHOST),
+                None,
+                None,
+                Utc::now() + Duration::hours(1),
+            )
+            .await
+            .unwrap();
+        opened.push(session);
+    }
+
+    service
+        .set_status(&admin, user.id, UserStatus::Inactive)
+        .await
+        .unwrap();
+
+    // Both of the user's sessions end, each freeing its seat and token.
+    assert!(store.find_active_by_user(user.id).await.unwrap().is_empty());
+    assert_eq!(*seats.released.lock().unwrap(), vec![key.clone(); 2]);
+    for session in &opened[..2] {
+        let ended = store.find_by_id(session.id).await.unwrap().unwrap();
+        assert!(ended.terminated_at.is_some());
+        assert_eq!(ended.terminated_by, Some(admin.user_id));
+        assert!(jwt_decoder.is_session_blocked(&session.id).await.unwrap());
+    }
+    // Other users keep theirs.
+    assert_eq!(
+        store.find_active_by_user(bystander.id).await.unwrap().len(),
+        1
+    );
+    assert!(!jwt_decoder.is_session_blocked(&opened[2].id).await.unwrap());
+
+    // Enabling the account again does not revive what was revoked.
+    service
+        .set_status(&admin, user.id, UserStatus::Active)
+        .await
+        .unwrap();
+    assert!(store.find_active_by_user(user.id).await.unwrap().is_empty());
+    for session in &opened[..2] {
+        assert!(jwt_decoder.is_session_blocked(&session.id).await.unwrap());
+    }
+    assert_eq!(seats.released.lock().unwrap().len(), 2);
+
+    // Disabling a user without sessions still frees a seat they may hold.
+    service
+        .set_status(&admin, user.id, UserStatus::Inactive)
+        .await
+        .unwrap();
+    assert_eq!(*seats.released.lock().unwrap(), vec![key; 3]);
+}
@@ -0,0 +1,214 @@
+//! Shared fixtures for the service tests that run against PostgreSQL.
+//!
+//! The tests connect to `DATABASE_URL` and migrate it. They are skipped
+//! when it is not set. Every fixture gets its own users, storage and
+//! folders, so tests can share one database and run in parallel.
+
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use sqlx::PgPool;
+use sqlx::postgres::PgPoolOptions;
+use tokio::sync::OnceCell;
+use uuid::Uuid;
+
+use filehub_auth::acl::{AclChecker, AclInheritanceResolver, EffectivePermissionResolver};
+use filehub_auth::rbac::enforcer::RbacEnforcer;
+use filehub_cache::provider::CacheManager;
+use filehub_core::config::cache::MemoryCacheConfig;
+use filehub_database::repositories::audit::AuditLogRepository;
+use filehub_database::repositories::file::FileRepository;
+use filehub_database::repositories::folder::FolderRepository;
+use filehub_database::repositories::permission::AclRepository;
+use filehub_database::repositories::storage::StorageRepository;
+use filehub_database::repositories::user::UserRepository;
+use filehub_entity::file::{CreateFile, File};
+use filehub_entity::folder::{CreateFolder, Folder};
+use filehub_entity::storage::{CreateStorage, Storage, StorageProviderType};
+use filehub_entity::user::model::CreateUser;
+use filehub_entity::user::{User, UserRole};
+use filehub_service::{RequestContext, SessionAudit};
+
+static MIGRATED: OnceCell<()> = OnceCell::const_new();
+
+/// Connects to the test database, or returns `None` to skip the test.
+pub async fn database() -> Option<PgPool> {
+    let Some(url) = std::env::var("DATABASE_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+    else {
+        eprintln!("DATABASE_URL is not set; skipping database test");
+        return None;
+    };
+    MIGRATED
+        .get_or_init(|| async {
+            let pool = PgPoolOptions::new()
+                .max_connections(1)
+                .connect(&url)
+                .await
+                .expect("Failed to connect to the test database");
+            filehub_database::migration::run_migrations(&pool)
+                .await
+                .expect("Failed to run migrations");
+            pool.close().await;
+        })
+        .await;
+    let pool = PgPoolOptions::new()
+        .max_connections(8)
+        .connect(&url)
+        .await
+        .expect("Failed to connect to the test database");
+    Some(pool)
+}
+
+/// Repositories and the permission stack over one pool, with a storage
+/// and root folder owned by `owner`.
+pub struct Fixture {
+    pub pool: PgPool,
+    pub cache: Arc<CacheManager>,
+    pub users: Arc<UserRepository>,
+    pub files: Arc<FileRepository>,
+    pub folders: Arc<FolderRepository>,
+    pub storages: Arc<StorageRepository>,
+    pub acls: Arc<AclRepository>,
+    pub audit_repo: Arc<AuditLogRepository>,
+    pub audit: Arc<SessionAudit>,
+    pub rbac: Arc<RbacEnforcer>,
+    pub resolver: Arc<EffectivePermissionResolver>,
+    pub owner: User,
+    pub storage: Storage,
+    pub root: Folder,
+}
+
+impl Fixture {
+    /// Sets up a fresh owner, storage and root folder, or returns `None`
+    /// to skip the test.
+    pub async fn new() -> Option<Self> {
+        let pool = database().await?;
+        let cache = Arc::new(CacheManager::from_provider(Arc::new(
+            filehub_cache::memory::MemoryCacheProvider::new(
+                &MemoryCacheConfig {
+                    max_capacity: 10_000,
+                    time_to_live_seconds: 60,
+                },
+                60,
+            ),
+        )));
+        let users = Arc::new(UserRepository::new(pool.clone()));
+        let files = Arc::new(FileRepository::new(pool.clone()));
+        let folders = Arc::new(FolderRepository::new(pool.clone()));
+        let storages = Arc::new(StorageRepository::new(pool.clone()));
+        let acls = Arc::new(AclRepository::new(pool.clone()));
+        let audit_repo = Arc::new(AuditLogRepository::new(pool.clone()));
+        let audit = Arc::new(SessionAudit::new(Arc::clone(&audit_repo)));
+        let rbac = Arc::new(RbacEnforcer::new());
+        let resolver = Arc::new(EffectivePermissionResolver::new(
+            Arc::clone(&rbac),
+            Arc::new(AclChecker::new(Arc::clone(&acls))),
+            Arc::new(AclInheritanceResolver::new(
+                Arc::clone(&folders),
+                Arc::clone(&acls),
+            )),
+            Arc::clone(&cache),
+        ));
+
+        let owner = create_user(&users, UserRole::Manager).await;
+        let storage = storages
+            .create(&CreateStorage {
+                name: format!("test-{}", Uuid::new_v4()),
+                description: None,
+                provider_type: StorageProviderType::Local,
+                config: serde_json::json!({}),
+                is_default: false,
+                quota_bytes: None,
+                mount_path: None,
+                created_by: Some(owner.id),
+            })
+            .await
+            .expect("Failed to create storage");
+        let root = folders
+            .create(&CreateFolder {
+                storage_id: storage.id,
+                parent_id: None,
+                name: "root".to_string(),
+                path: "/".to_string(),
+                depth: 0,
+                owner_id: owner.id,
+            })
+            .await
+            .expect("Failed to create root folder");
+
+        Some(Self {
+            pool,
+            cache,
+            users,
+            files,
+            folders,
+            storages,
+            acls,
+            audit_repo,
+            audit,
+            rbac,
+            resolver,
+            owner,
+            storage,
+            root,
+        })
+    }
+
+    /// Creates another user.
+    pub async fn user(&self, role: UserRole) -> User {
+        create_user(&self.users, role).await
+    }
+
+    /// Creates a file record under `folder_id`, owned by the fixture
+    /// owner.
+    pub async fn file(&self, folder_id: Uuid, name: &str, storage_path: &str) -> File {
+        self.files
+            .create(
+                &CreateFile {
+                    folder_id,
+                    storage_id: self.storage.id,
+                    name: name.to_string(),
+                    storage_path: storage_path.to_string(),
+                    mime_type: Some("text/plain".to_string()),
+                    declared_mime_type: None,
+                    size_bytes: storage_path.len() as i64,
+                    checksum_sha256: Some(format!("sha-{storage_path}")),
+                    metadata: None,
+                    owner_id: self.owner.id,
+                },
+                None,
+            )
+            .await
+            .expect("Failed to create file")
+    }
+}
+
+/// The request context of `user`.
+pub fn context(user: &User) -> RequestContext {
+    RequestContext::new(
+        user.id,
+        Uuid::new_v4(),
+        user.role.clone(),
+        user.username.clone(),
+        "127.0.0.1".to_string(),
+        None,
+    )
+}
+
+async fn create_user(users: &UserRepository, role: UserRole) -> User {
+    let name = format!("user-{}", Uuid::new_v4().simple());
+    users
+        .create(&CreateUser {
+            username: name.clone(),
+            email: Some(format!("{name}@example.com")),
+            password_hash: "unused".to_string(),
+            display_name: None,
+            role,
+            created_by: None,
+        })
+        .await
+        .expect("Failed to create user")
+}
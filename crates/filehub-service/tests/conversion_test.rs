@@ -0,0 +1,118 @@
+//! Queuing CAD conversions with a profile, against PostgreSQL.
+
+mod common;
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+
+use filehub_core::config::ConversionConfig;
+use filehub_core::error::ErrorKind;
+use filehub_database::repositories::job::JobRepository;
+use filehub_entity::user::UserRole;
+use filehub_service::ConversionRequestService;
+use filehub_service::file::conversion::{CAD_CONVERSION_JOB_TYPE, ConversionRequest};
+use filehub_storage::StorageManager;
+use filehub_storage::providers::LocalStorageProvider;
+
+use common::{Fixture, context};
+
+fn request(profile: Option<&str>) -> ConversionRequest {
+    ConversionRequest {
+        profile: profile.map(String::from),
+        targets: vec!["vtfx".to_string()],
+    }
+}
+
+#[tokio::test]
+async fn test_conversion_request_resolves_profile() {
+    let Some(fx) = Fixture::new().await else {
+        return;
+    };
+    let root = tempfile::tempdir().unwrap();
+    let storage = Arc::new(StorageManager::new());
+    storage
+        .register(
+            fx.storage.id,
+            Arc::new(
+                LocalStorageProvider::new(root.path().join("blobs").to_str().unwrap())
+                    .await
+                    .unwrap(),
+            ),
+            true,
+        )
+        .await;
+    storage
+        .write(
+            &fx.storage.id,
+            "parts/bracket",
+            Bytes::from_static(b"solid"),
+        )
+        .await
+        .unwrap();
+    let file = fx.file(fx.root.id, "bracket.stp", "parts/bracket").await;
+
+    let jobs = Arc::new(JobRepository::new(fx.pool.clone()));
+    let data_root = root.path().join("data");
+    let config = ConversionConfig {
+        enabled: true,
+        ..Default::default()
+    };
+    let service = ConversionRequestService::new(
+        fx.files.clone(),
+        storage,
+        fx.resolver.clone(),
+        jobs.clone(),
+        config.clone(),
+        &data_root,
+    );
+    let owner = context(&fx.owner);
+
+    // An unknown profile is refused before anything is queued.
+    let err = service
+        .request(&owner, file.id, request(Some("ultra")))
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Validation);
+    assert!(err.message.contains("ultra"));
+    assert!(!data_root.join("conversions").exists());
+
+    // A named profile is queued as is; none at all gets the default.
+    let queued = service
+        .request(&owner, file.id, request(Some("high-fidelity")))
+        .await
+        .unwrap();
+    assert_eq!(queued.profile, "high-fidelity");
+    let job = jobs.find_by_id(queued.job_id).await.unwrap().unwrap();
+    assert_eq!(job.job_type, CAD_CONVERSION_JOB_TYPE);
+    assert_eq!(job.created_by, Some(fx.owner.id));
+    assert_eq!(job.payload["profile"], "high-fidelity");
+    assert_eq!(job.payload["file_name"], "bracket.stp");
+    assert_eq!(job.payload["targets"], serde_json::json!(["vtfx"]));
+    assert_eq!(
+        job.payload["output_dir"],
+        data_root
+            .join("cache")
+            .join("conversions")
+            .to_str()
+            .unwrap()
+    );
+    let source = job.payload["source_path"].as_str().unwrap();
+    assert!(source.starts_with(data_root.to_str().unwrap()));
+    assert!(source.ends_with("bracket.stp"));
+    assert_eq!(std::fs::read(source).unwrap(), b"solid");
+
+    let queued = service
+        .request(&owner, file.id, request(None))
+        .await
+        .unwrap();
+    assert_eq!(queued.profile, config.default_profile);
+
+    // Only those who may view the file can have it converted.
+    let stranger = context(&fx.user(UserRole::Viewer).await);
+    let err = service
+        .request(&stranger, file.id, request(None))
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind, ErrorKind::Forbidden);
+}
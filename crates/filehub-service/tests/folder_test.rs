@@ -0,0 +1,141 @@
+//! Permission templates applied to new folders, against PostgreSQL.
+
+mod common;
+
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use filehub_database::repositories::permission_template::PermissionTemplateRepository;
+use filehub_entity::folder::Folder;
+use filehub_entity::permission::{AclInheritance, AclPermission, ResourceType, TemplateEntry};
+use filehub_entity::user::{User, UserRole};
+use filehub_service::folder::service::CreateFolderRequest;
+use filehub_service::permission::template::TemplateRequest;
+use filehub_service::{FolderService, PermissionTemplateService};
+
+use common::{Fixture, context};
+
+/// Whether `user` holds at least `required` on `folder`.
+async fn allows(fx: &Fixture, user: &User, folder: &Folder, required: AclPermission) -> bool {
+    fx.resolver
+        .resolve(
+            user.id,
+            &user.role,
+            ResourceType::Folder,
+            folder.id,
+            folder.owner_id,
+            folder.parent_id,
+            required,
+        )
+        .await
+        .unwrap()
+        .granted
+}
+
+fn entry(user: &User, permission: AclPermission, inheritance: AclInheritance) -> TemplateEntry {
+    TemplateEntry {
+        user_id: Some(user.id),
+        is_anyone: false,
+        permission,
+        inheritance,
+    }
+}
+
+#[tokio::test]
+async fn test_child_folder_gets_parent_template() {
+    let Some(fx) = Fixture::new().await else {
+        return;
+    };
+    let admin = fx.user(UserRole::Admin).await;
+    let member = fx.user(UserRole::Viewer).await;
+    let outsider = fx.user(UserRole::Viewer).await;
+
+    // The outsider may edit everything under the root...
+    fx.acls
+        .create(
+            ResourceType::Folder,
+            fx.root.id,
+            Some(outsider.id),
+            false,
+            AclPermission::Editor,
+            AclInheritance::Inherit,
+            fx.owner.id,
+            None,
+        )
+        .await
+        .unwrap();
+
+    let templates = PermissionTemplateService::new(
+        Arc::new(PermissionTemplateRepository::new(fx.pool.clone())),
+        fx.folders.clone(),
+        fx.acls.clone(),
+        fx.resolver.clone(),
+    );
+    let folders = FolderService::new(
+        fx.folders.clone(),
+        fx.storages.clone(),
+        fx.resolver.clone(),
+        fx.audit.clone(),
+    )
+    .with_permission_templates(templates.clone());
+    let owner = context(&fx.owner);
+    let create = |parent: &Folder, name: &str| {
+        folders.create_folder(
+            &owner,
+            CreateFolderRequest {
+                storage_id: fx.storage.id,
+                parent_id: Some(parent.id),
+                name: name.to_string(),
+            },
+        )
+    };
+
+    // ...but project folders only let it look, and keep what it was given
+    // above from reaching their contents.
+    let template = templates
+        .create(
+            &context(&admin),
+            TemplateRequest {
+                name: format!("project-folder-{}", Uuid::new_v4().simple()),
+                description: None,
+                entries: vec![
+                    entry(&member, AclPermission::Editor, AclInheritance::Inherit),
+                    entry(&outsider, AclPermission::Viewer, AclInheritance::Block),
+                ],
+            },
+        )
+        .await
+        .unwrap();
+    let projects = create(&fx.root, "projects").await.unwrap();
+    templates
+        .attach(&context(&admin), projects.id, Some(template.id))
+        .await
+        .unwrap();
+
+    let apollo = create(&projects, "apollo").await.unwrap();
+    let specs = create(&apollo, "specs").await.unwrap();
+
+    let entries = fx
+        .acls
+        .find_by_resource(ResourceType::Folder, apollo.id)
+        .await
+        .unwrap();
+    assert_eq!(entries.len(), 2);
+
+    // The template is applied to the children, not to the folder holding it.
+    assert!(!allows(&fx, &member, &projects, AclPermission::Viewer).await);
+    assert!(allows(&fx, &member, &apollo, AclPermission::Editor).await);
+    assert!(allows(&fx, &member, &specs, AclPermission::Editor).await);
+
+    // The blocking folder still inherits the root's grant; only what lies
+    // below it is cut off from the root and sees the template's viewer.
+    assert!(allows(&fx, &outsider, &apollo, AclPermission::Editor).await);
+    assert!(!allows(&fx, &outsider, &specs, AclPermission::Editor).await);
+    assert!(allows(&fx, &outsider, &specs, AclPermission::Viewer).await);
+
+    // Folders outside the templated parent are untouched.
+    let other = create(&fx.root, "archive").await.unwrap();
+    assert!(!allows(&fx, &member, &other, AclPermission::Viewer).await);
+    assert!(allows(&fx, &outsider, &other, AclPermission::Editor).await);
+}
@@ -0,0 +1,80 @@
+//! Name collisions between concurrent uploads, against PostgreSQL.
+
+mod common;
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::future::join_all;
+
+use filehub_core::config::storage::{NameCollision, StorageConfig};
+use filehub_plugin::manager::PluginManager;
+use filehub_service::UploadService;
+use filehub_service::file::upload::SimpleUploadParams;
+use filehub_storage::StorageManager;
+use filehub_storage::providers::LocalStorageProvider;
+
+use common::{Fixture, context};
+
+const UPLOADS: usize = 8;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_same_name_uploads_get_distinct_names() {
+    let Some(fx) = Fixture::new().await else {
+        return;
+    };
+    let root = tempfile::tempdir().unwrap();
+    let storage = Arc::new(StorageManager::new());
+    storage
+        .register(
+            fx.storage.id,
+            Arc::new(
+                LocalStorageProvider::new(root.path().to_str().unwrap())
+                    .await
+                    .unwrap(),
+            ),
+            true,
+        )
+        .await;
+    let config: StorageConfig = serde_json::from_value(serde_json::json!({})).unwrap();
+    let service = UploadService::new(
+        fx.files.clone(),
+        fx.folders.clone(),
+        storage,
+        fx.resolver.clone(),
+        config,
+        Arc::new(PluginManager::new()),
+    );
+    let ctx = context(&fx.owner);
+    let upload = |on_conflict| {
+        service.simple_upload(
+            &ctx,
+            SimpleUploadParams {
+                folder_id: fx.root.id,
+                file_name: "report.txt".to_string(),
+                mime_type: Some("text/plain".to_string()),
+                data: Bytes::from_static(b"quarterly numbers"),
+                on_conflict,
+            },
+        )
+    };
+
+    let uploads = (0..UPLOADS).map(|_| upload(Some(NameCollision::Rename)));
+    let mut names: Vec<String> = join_all(uploads)
+        .await
+        .into_iter()
+        .map(|file| file.unwrap().name)
+        .collect();
+    names.sort();
+
+    let mut expected: Vec<String> = (2..=UPLOADS)
+        .map(|n| format!("report ({n}).txt"))
+        .chain(["report.txt".to_string()])
+        .collect();
+    expected.sort();
+    assert_eq!(names, expected);
+
+    // An upload that asks to reject still refuses the taken name.
+    let rejected = upload(Some(NameCollision::Reject)).await.unwrap_err();
+    assert_eq!(rejected.kind, filehub_core::error::ErrorKind::Conflict);
+}
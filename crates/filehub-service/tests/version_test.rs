@@ -0,0 +1,101 @@
+//! Version restore against PostgreSQL.
+
+mod common;
+
+use filehub_core::events::{DomainEvent, EventPayload, FileEvent};
+use filehub_entity::file::CreateFile;
+use filehub_service::VersionService;
+
+use common::{Fixture, context};
+
+#[tokio::test]
+async fn test_restore_repoints_content_and_keeps_history() {
+    let Some(fx) = Fixture::new().await else {
+        return;
+    };
+    let service = VersionService::new(fx.files.clone(), fx.resolver.clone());
+    let ctx = context(&fx.owner);
+
+    // v1, then v2 uploaded over it, keeping v1 as a version.
+    let file = fx.file(fx.root.id, "report.txt", "blobs/v1").await;
+    fx.files
+        .replace_content(
+            file.id,
+            &CreateFile {
+                storage_path: "blobs/v2".to_string(),
+                size_bytes: 20,
+                checksum_sha256: Some("sha-v2".to_string()),
+                ..file_content(&fx, &file)
+            },
+            true,
+            fx.owner.id,
+            None,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    let v1 = fx.files.find_version(file.id, 1).await.unwrap().unwrap();
+
+    let restored = service.restore(&ctx, file.id, v1.id).await.unwrap();
+
+    // The file points at v1's content again, under a new version number.
+    assert_eq!(restored.storage_path, "blobs/v1");
+    assert_eq!(restored.checksum_sha256.as_deref(), Some("sha-blobs/v1"));
+    assert_eq!(restored.size_bytes, v1.size_bytes);
+    assert_eq!(restored.current_version, 3);
+    let stored = fx.files.find_by_id(file.id).await.unwrap().unwrap();
+    assert_eq!(stored.storage_path, "blobs/v1");
+
+    // v1 is still there, and the replaced v2 became a version of its own.
+    let versions = fx.files.find_versions(file.id).await.unwrap();
+    let mut numbers: Vec<i32> = versions.iter().map(|v| v.version_number).collect();
+    numbers.sort();
+    assert_eq!(numbers, [1, 2]);
+    let v2 = versions.iter().find(|v| v.version_number == 2).unwrap();
+    assert_eq!(v2.storage_path, "blobs/v2");
+    assert_eq!(v2.checksum_sha256.as_deref(), Some("sha-v2"));
+    assert_eq!(
+        v2.comment.as_deref(),
+        Some("Replaced by restore of version 1")
+    );
+
+    // The restore was announced through the outbox.
+    let events: Vec<serde_json::Value> = sqlx::query_scalar(
+        "SELECT event FROM outbox WHERE event -> 'payload' -> 'event' ->> 'file_id' = $1",
+    )
+    .bind(file.id.to_string())
+    .fetch_all(&fx.pool)
+    .await
+    .unwrap();
+    let events: Vec<DomainEvent> = events
+        .into_iter()
+        .map(|e| serde_json::from_value(e).unwrap())
+        .collect();
+    assert!(
+        events.iter().any(|e| e.actor_id == Some(fx.owner.id)
+            && matches!(
+                e.payload,
+                EventPayload::File(FileEvent::VersionRestored {
+                    file_id,
+                    restored_version: 1,
+                    version_number: 3,
+                }) if file_id == file.id
+            )),
+        "no VersionRestored event in {events:?}"
+    );
+}
+
+fn file_content(fx: &Fixture, file: &filehub_entity::file::File) -> CreateFile {
+    CreateFile {
+        folder_id: file.folder_id,
+        storage_id: fx.storage.id,
+        name: file.name.clone(),
+        storage_path: file.storage_path.clone(),
+        mime_type: file.mime_type.clone(),
+        declared_mime_type: None,
+        size_bytes: file.size_bytes,
+        checksum_sha256: file.checksum_sha256.clone(),
+        metadata: None,
+        owner_id: file.owner_id,
+    }
+}
@@ -1,8 +1,9 @@
-//! Chunk assembler — concatenates chunks into a final file.
+//! Chunk assembler — streams chunks into a final file, hashing them on
+//! the way.
 
 use std::sync::Arc;
 
-use tokio::io::AsyncWriteExt;
+use futures::StreamExt;
 use uuid::Uuid;
 
 use filehub_core::error::{AppError, ErrorKind};
@@ -10,6 +11,7 @@ use filehub_core::result::AppResult;
 use filehub_core::traits::storage::StorageProvider;
 
 use super::upload::ChunkedUploadHandler;
+use crate::hashing::{ContentDigest, ContentHasher, HashingStream};
 
 /// Assembles uploaded chunks into a final file.
 #[derive(Debug, Clone)]
@@ -34,14 +36,16 @@ impl ChunkAssembler {
 
     /// Assemble all chunks into a single file at the target path.
     ///
-    /// Reads each chunk in order and writes them sequentially to the
-    /// target storage. Returns the total number of bytes written.
+    /// Streams each chunk in order straight to the target storage,
+    /// hashing it with `hasher` on the way, so the content is read and
+    /// hashed once. Returns the digest of the assembled file.
     pub async fn assemble(
         &self,
         upload_id: Uuid,
         total_chunks: i32,
         target_path: &str,
-    ) -> AppResult<u64> {
+        hasher: ContentHasher,
+    ) -> AppResult<ContentDigest> {
         tracing::info!(
             upload_id = %upload_id,
             total_chunks,
@@ -49,45 +53,32 @@ impl ChunkAssembler {
             "Assembling chunks"
         );
 
-        // Create a temporary local file to assemble into, then write to target.
-        let temp_dir = std::env::temp_dir().join(format!("filehub_assemble_{upload_id}"));
-        tokio::fs::create_dir_all(&temp_dir).await.map_err(|e| {
-            AppError::with_source(ErrorKind::Storage, "Failed to create temp assembly dir", e)
-        })?;
-
-        let temp_file_path = temp_dir.join("assembled");
-        let mut file = tokio::fs::File::create(&temp_file_path)
-            .await
-            .map_err(|e| {
-                AppError::with_source(ErrorKind::Storage, "Failed to create temp assembly file", e)
-            })?;
-
-        let mut total_bytes = 0u64;
-
-        for chunk_num in 0..total_chunks {
-            let chunk_data = self.upload_handler.read_chunk(upload_id, chunk_num).await?;
-            total_bytes += chunk_data.len() as u64;
-            file.write_all(&chunk_data).await.map_err(|e| {
-                AppError::with_source(ErrorKind::Storage, "Failed to write chunk to assembly", e)
-            })?;
-        }
-
-        file.flush().await.map_err(|e| {
-            AppError::with_source(ErrorKind::Storage, "Failed to flush assembly file", e)
-        })?;
-        drop(file);
-
-        // Read the assembled file and write to target storage.
-        let assembled_bytes = tokio::fs::read(&temp_file_path).await.map_err(|e| {
-            AppError::with_source(ErrorKind::Storage, "Failed to read assembled file", e)
-        })?;
-
-        self.target_provider
-            .write(target_path, bytes::Bytes::from(assembled_bytes))
+        let handler = self.upload_handler.clone();
+        let chunks = futures::stream::iter(0..total_chunks).then(move |chunk_num| {
+            let handler = handler.clone();
+            async move {
+                handler
+                    .read_chunk(upload_id, chunk_num)
+                    .await
+                    .map_err(|e| std::io::Error::other(e.to_string()))
+            }
+        });
+        let (stream, digest) = HashingStream::new(Box::pin(chunks), hasher);
+        let written = self
+            .target_provider
+            .write_stream(target_path, stream.boxed())
             .await?;
 
-        // Clean up temp directory.
-        let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+        let digest = digest.finish();
+        if written != digest.size_bytes {
+            return Err(AppError::new(
+                ErrorKind::Storage,
+                format!(
+                    "Assembled {} bytes but {written} were written",
+                    digest.size_bytes
+                ),
+            ));
+        }
 
         // Clean up chunks.
         self.upload_handler
@@ -96,10 +87,64 @@ impl ChunkAssembler {
 
         tracing::info!(
             upload_id = %upload_id,
-            bytes = total_bytes,
+            bytes = written,
+            sha256 = %digest.sha256,
             "Assembly complete"
         );
 
-        Ok(total_bytes)
+        Ok(digest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bytes::Bytes;
+
+    use crate::providers::local::LocalStorageProvider;
+
+    #[tokio::test]
+    async fn test_assembly_streams_chunks_through_one_hasher() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider: Arc<dyn StorageProvider> = Arc::new(
+            LocalStorageProvider::new(dir.path().to_str().unwrap())
+                .await
+                .unwrap(),
+        );
+        let handler = ChunkedUploadHandler::new(Arc::clone(&provider));
+        let upload_id = Uuid::new_v4();
+        for (n, part) in ["The quick brown fox ", "jumps over ", "the lazy dog"]
+            .into_iter()
+            .enumerate()
+        {
+            handler
+                .write_chunk(upload_id, n as i32, Bytes::from(part))
+                .await
+                .unwrap();
+        }
+
+        let assembler = ChunkAssembler::new(handler, Arc::clone(&provider));
+        let digest = assembler
+            .assemble(upload_id, 3, "out/fox.txt", ContentHasher::new(true))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            digest.sha256,
+            "d7a8fbb307d7809469ca9abcb0082e4f8d5651e46d3cdb762d02d0bf37c9e592"
+        );
+        assert_eq!(digest.size_bytes, 43);
+        assert!(digest.crc32c.is_some());
+        assert_eq!(
+            provider.read_bytes("out/fox.txt").await.unwrap(),
+            "The quick brown fox jumps over the lazy dog"
+        );
+        assert!(
+            !provider
+                .exists(&ChunkedUploadHandler::chunk_path(upload_id, 0))
+                .await
+                .unwrap()
+        );
     }
 }
@@ -0,0 +1,334 @@
+//! Streaming content hashing.
+//!
+//! Upload bytes pass through a [`HashingStream`] on their way to the
+//! storage writer, so content is hashed exactly once, as it is written.
+//! The resulting [`ContentDigest`] is the stored checksum and what the
+//! written file is verified against; nothing is read back to hash it.
+//!
+//! A chunked upload is finalized by streaming its chunks in order through
+//! one hasher: the file digest is built up chunk by chunk, while each
+//! chunk is checked against the digest it was accepted with. The stream
+//! can also keep the first bytes of the content, to sniff its type from.
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use crc::{CRC_32_ISCSI, Crc};
+use futures::Stream;
+use sha2::{Digest, Sha256};
+
+use filehub_core::traits::storage::ByteStream;
+
+/// CRC32C (Castagnoli), as used by S3 and GCS checksums.
+static CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+
+/// Lowercase hex SHA-256 of `data`, as stored in `checksum_sha256`.
+pub fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// Digests of a piece of content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentDigest {
+    /// Lowercase hex SHA-256.
+    pub sha256: String,
+    /// CRC32C, when it was asked for.
+    pub crc32c: Option<u32>,
+    /// Bytes hashed.
+    pub size_bytes: u64,
+}
+
+impl ContentDigest {
+    /// The CRC32C as 8 lowercase hex digits.
+    pub fn crc32c_hex(&self) -> Option<String> {
+        self.crc32c.map(|crc| format!("{crc:08x}"))
+    }
+}
+
+/// Incremental SHA-256 and, optionally, CRC32C of content.
+#[derive(Clone)]
+pub struct ContentHasher {
+    sha256: Sha256,
+    crc32c: Option<crc::Digest<'static, u32>>,
+    size_bytes: u64,
+}
+
+impl std::fmt::Debug for ContentHasher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContentHasher")
+            .field("crc32c", &self.crc32c.is_some())
+            .field("size_bytes", &self.size_bytes)
+            .finish()
+    }
+}
+
+impl Default for ContentHasher {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl ContentHasher {
+    /// A hasher computing SHA-256, and CRC32C if `crc32c` is set.
+    pub fn new(crc32c: bool) -> Self {
+        Self {
+            sha256: Sha256::new(),
+            crc32c: crc32c.then(|| CRC32C.digest()),
+            size_bytes: 0,
+        }
+    }
+
+    /// Hash the next piece of content.
+    pub fn update(&mut self, data: &[u8]) {
+        self.sha256.update(data);
+        if let Some(crc) = &mut self.crc32c {
+            crc.update(data);
+        }
+        self.size_bytes += data.len() as u64;
+    }
+
+    /// The digest of everything hashed.
+    pub fn finish(self) -> ContentDigest {
+        ContentDigest {
+            sha256: format!("{:x}", self.sha256.finalize()),
+            crc32c: self.crc32c.map(|crc| crc.finalize()),
+            size_bytes: self.size_bytes,
+        }
+    }
+}
+
+/// What a [`HashingStream`] has seen, shared with its [`DigestHandle`].
+#[derive(Debug)]
+struct HashState {
+    hasher: ContentHasher,
+    corrupt_chunk: Option<usize>,
+    /// Leading bytes of the content, up to `head_len`.
+    head: Vec<u8>,
+    head_len: usize,
+}
+
+/// Tees a byte stream into a [`ContentHasher`] as it is consumed.
+pub struct HashingStream {
+    inner: ByteStream,
+    state: Arc<Mutex<HashState>>,
+    /// Expected SHA-256 of each remaining item, when verifying chunks.
+    expected_chunks: Option<VecDeque<String>>,
+    /// Index of the next item.
+    next_chunk: usize,
+}
+
+impl std::fmt::Debug for HashingStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HashingStream")
+            .field("next_chunk", &self.next_chunk)
+            .finish()
+    }
+}
+
+/// Reads the digest of a [`HashingStream`] once it has been consumed.
+#[derive(Debug, Clone)]
+pub struct DigestHandle {
+    state: Arc<Mutex<HashState>>,
+}
+
+impl DigestHandle {
+    /// The digest of everything that passed through the stream.
+    pub fn finish(&self) -> ContentDigest {
+        self.lock().hasher.clone().finish()
+    }
+
+    /// Index of the chunk that did not match its expected digest, if the
+    /// stream ended because of one.
+    pub fn corrupt_chunk(&self) -> Option<usize> {
+        self.lock().corrupt_chunk
+    }
+
+    /// The leading bytes kept by [`HashingStream::keep_head`].
+    pub fn head(&self) -> Vec<u8> {
+        self.lock().head.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl HashingStream {
+    /// Wrap `inner`, hashing its bytes with `hasher` as they pass.
+    pub fn new(inner: ByteStream, hasher: ContentHasher) -> (Self, DigestHandle) {
+        let state = Arc::new(Mutex::new(HashState {
+            hasher,
+            corrupt_chunk: None,
+            head: Vec::new(),
+            head_len: 0,
+        }));
+        let stream = Self {
+            inner,
+            state: Arc::clone(&state),
+            expected_chunks: None,
+            next_chunk: 0,
+        };
+        (stream, DigestHandle { state })
+    }
+
+    /// Wrap a single buffer.
+    pub fn once(data: Bytes, hasher: ContentHasher) -> (Self, DigestHandle) {
+        let inner = futures::stream::once(async move { Ok(data) });
+        Self::new(Box::pin(inner), hasher)
+    }
+
+    /// Also check each item, a whole chunk, against the SHA-256 it was
+    /// accepted with. A chunk that does not match ends the stream with an
+    /// [`std::io::ErrorKind::InvalidData`] error.
+    pub fn verify_chunks(mut self, expected: Vec<String>) -> Self {
+        self.expected_chunks = Some(expected.into());
+        self
+    }
+
+    /// Also keep the first `len` bytes that pass, e.g.
+    /// [`SNIFF_LEN`](crate::sniff::SNIFF_LEN) to sniff the content type.
+    pub fn keep_head(self, len: usize) -> Self {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .head_len = len;
+        self
+    }
+
+    /// Box the stream for a storage writer.
+    pub fn boxed(self) -> ByteStream {
+        Box::pin(self)
+    }
+}
+
+impl Stream for HashingStream {
+    type Item = Result<Bytes, std::io::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let data = match self.inner.as_mut().poll_next(cx) {
+            Poll::Ready(Some(Ok(data))) => data,
+            other => return other,
+        };
+
+        let index = self.next_chunk;
+        self.next_chunk += 1;
+        let expected = self.expected_chunks.as_mut().and_then(VecDeque::pop_front);
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(expected) = expected
+            && sha256_hex(&data) != expected
+        {
+            state.corrupt_chunk = Some(index);
+            return Poll::Ready(Some(Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("chunk {index} does not match its checksum"),
+            ))));
+        }
+        state.hasher.update(&data);
+        let wanted = state.head_len.saturating_sub(state.head.len());
+        if wanted > 0 {
+            state
+                .head
+                .extend_from_slice(&data[..wanted.min(data.len())]);
+        }
+        Poll::Ready(Some(Ok(data)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::StreamExt;
+
+    /// SHA-256 of "The quick brown fox jumps over the lazy dog".
+    const FOX_SHA256: &str = "d7a8fbb307d7809469ca9abcb0082e4f8d5651e46d3cdb762d02d0bf37c9e592";
+
+    #[test]
+    fn test_known_vectors() {
+        let mut hasher = ContentHasher::new(true);
+        hasher.update(b"The quick brown fox ");
+        hasher.update(b"jumps over the lazy dog");
+        let digest = hasher.finish();
+        assert_eq!(digest.sha256, FOX_SHA256);
+        assert_eq!(digest.size_bytes, 43);
+
+        // CRC-32C check value of "123456789"
+        let mut hasher = ContentHasher::new(true);
+        hasher.update(b"123456789");
+        assert_eq!(hasher.finish().crc32c_hex().as_deref(), Some("e3069283"));
+
+        assert_eq!(ContentHasher::default().finish().crc32c, None);
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_is_hashed_as_it_passes() {
+        let parts: Vec<Result<Bytes, std::io::Error>> = vec![
+            Ok(Bytes::from_static(b"The quick brown fox ")),
+            Ok(Bytes::from_static(b"jumps over the lazy dog")),
+        ];
+        let (stream, digest) = HashingStream::new(
+            Box::pin(futures::stream::iter(parts)),
+            ContentHasher::default(),
+        );
+        let passed: Vec<Bytes> = stream.map(Result::unwrap).collect().await;
+
+        assert_eq!(
+            passed.concat(),
+            b"The quick brown fox jumps over the lazy dog"
+        );
+        assert_eq!(digest.finish().sha256, FOX_SHA256);
+    }
+
+    #[tokio::test]
+    async fn test_head_is_kept_across_items() {
+        let parts: Vec<Result<Bytes, std::io::Error>> = vec![
+            Ok(Bytes::from_static(b"The quick ")),
+            Ok(Bytes::from_static(b"brown fox")),
+        ];
+        let (stream, digest) = HashingStream::new(
+            Box::pin(futures::stream::iter(parts)),
+            ContentHasher::default(),
+        );
+        let _: Vec<_> = stream.keep_head(14).collect().await;
+
+        assert_eq!(digest.head(), b"The quick brow");
+        assert_eq!(digest.finish().size_bytes, 19);
+    }
+
+    #[tokio::test]
+    async fn test_chunk_mismatch_ends_stream() {
+        let chunks: Vec<Result<Bytes, std::io::Error>> = vec![
+            Ok(Bytes::from_static(b"first")),
+            Ok(Bytes::from_static(b"tampered")),
+            Ok(Bytes::from_static(b"third")),
+        ];
+        let expected = vec![
+            sha256_hex(b"first"),
+            sha256_hex(b"second"),
+            sha256_hex(b"third"),
+        ];
+        let (stream, digest) = HashingStream::new(
+            Box::pin(futures::stream::iter(chunks)),
+            ContentHasher::default(),
+        );
+        let mut stream = stream.verify_chunks(expected);
+
+        assert!(stream.next().await.unwrap().is_ok());
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(digest.corrupt_chunk(), Some(1));
+        assert_eq!(digest.finish().size_bytes, 5);
+    }
+}
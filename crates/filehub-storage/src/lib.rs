@@ -4,9 +4,17 @@
 //! S3-compatible object stores, and SMB shares.
 
 pub mod chunked;
+pub mod hashing;
 pub mod manager;
 pub mod providers;
+pub mod retry;
+pub mod sniff;
+pub mod throttle;
 pub mod thumbnail;
 pub mod transfer;
+pub mod upload_policy;
 
 pub use manager::StorageManager;
+pub use retry::RetryingProvider;
+pub use throttle::BandwidthThrottle;
+pub use upload_policy::UploadRules;
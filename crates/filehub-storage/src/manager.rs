@@ -1,15 +1,45 @@
 //! Storage manager — routes operations to the correct provider by storage ID.
+//!
+//! A background prober records each provider's health. Writes to a
+//! provider known to be unhealthy fail fast, and new uploads are placed
+//! on a healthy provider when the preferred one is down.
+//!
+//! With a retry configuration, providers are wrapped in a
+//! [`RetryingProvider`] on registration, under the policy of their type.
+//!
+//! Content operations are traced under the `otel` target, so they are
+//! exported when traces are and cost nothing otherwise.
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 
+use filehub_core::config::StorageRetryConfig;
 use filehub_core::error::AppError;
 use filehub_core::result::AppResult;
-use filehub_core::traits::storage::StorageProvider;
+use filehub_core::traits::storage::{ByteStream, StorageProvider};
+
+use crate::retry::{RetryStats, RetryStatsSnapshot, RetryingProvider};
+
+/// Result of the last health probe of a provider.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderHealth {
+    /// Storage ID.
+    pub storage_id: Uuid,
+    /// Whether the provider passed its health check.
+    pub healthy: bool,
+    /// When the provider was last probed.
+    pub last_checked: DateTime<Utc>,
+    /// Why the last probe failed.
+    pub error: Option<String>,
+}
 
 /// Central storage manager that holds references to all registered providers.
 #[derive(Debug, Clone)]
@@ -18,6 +48,14 @@ pub struct StorageManager {
     providers: Arc<RwLock<HashMap<Uuid, Arc<dyn StorageProvider>>>>,
     /// The default storage ID.
     default_id: Arc<RwLock<Option<Uuid>>>,
+    /// Last probe result per storage ID; providers not yet probed are
+    /// assumed healthy.
+    health: Arc<RwLock<HashMap<Uuid, ProviderHealth>>>,
+    /// Retry policies applied on registration; `None` registers
+    /// providers as they are.
+    retry: Option<Arc<StorageRetryConfig>>,
+    /// Retry counters per storage ID of wrapped providers.
+    retry_stats: Arc<RwLock<HashMap<Uuid, Arc<RetryStats>>>>,
 }
 
 impl StorageManager {
@@ -26,6 +64,18 @@ impl StorageManager {
         Self {
             providers: Arc::new(RwLock::new(HashMap::new())),
             default_id: Arc::new(RwLock::new(None)),
+            health: Arc::new(RwLock::new(HashMap::new())),
+            retry: None,
+            retry_stats: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Create a new empty storage manager that retries and time-limits
+    /// the operations of registered providers.
+    pub fn with_retry(config: StorageRetryConfig) -> Self {
+        Self {
+            retry: Some(Arc::new(config)),
+            ..Self::new()
         }
     }
 
@@ -36,6 +86,18 @@ impl StorageManager {
         provider: Arc<dyn StorageProvider>,
         is_default: bool,
     ) {
+        let provider = match &self.retry {
+            Some(retry) => {
+                let policy = retry.for_provider(provider.provider_type());
+                let wrapped = RetryingProvider::new(provider, policy);
+                self.retry_stats
+                    .write()
+                    .await
+                    .insert(storage_id, wrapped.stats());
+                Arc::new(wrapped)
+            }
+            None => provider,
+        };
         let mut providers = self.providers.write().await;
         providers.insert(storage_id, provider);
         if is_default {
@@ -48,6 +110,8 @@ impl StorageManager {
     pub async fn unregister(&self, storage_id: &Uuid) {
         let mut providers = self.providers.write().await;
         providers.remove(storage_id);
+        self.health.write().await.remove(storage_id);
+        self.retry_stats.write().await.remove(storage_id);
         let mut default = self.default_id.write().await;
         if default.as_ref() == Some(storage_id) {
             *default = None;
@@ -64,23 +128,104 @@ impl StorageManager {
     }
 
     /// Read a file from storage.
+    #[tracing::instrument(
+        target = "otel",
+        name = "storage.read",
+        skip_all,
+        fields(otel.kind = "client", storage_id = %storage_id, path = path)
+    )]
     pub async fn read(&self, storage_id: &Uuid, path: &str) -> AppResult<Bytes> {
         let provider = self.get(storage_id).await?;
         provider.read_bytes(path).await
     }
 
-    /// Write a file to storage.
-    pub async fn write(&self, storage_id: &Uuid, path: &str, data: Bytes) -> AppResult<()> {
+    /// Read a byte range of a file from storage as a stream.
+    #[tracing::instrument(
+        target = "otel",
+        name = "storage.read_range",
+        skip_all,
+        fields(otel.kind = "client", storage_id = %storage_id, path = path)
+    )]
+    pub async fn read_range(
+        &self,
+        storage_id: &Uuid,
+        path: &str,
+        offset: u64,
+        len: u64,
+    ) -> AppResult<ByteStream> {
         let provider = self.get(storage_id).await?;
+        provider.read_range(path, offset, len).await
+    }
+
+    /// Write a file to storage. Fails fast if the provider's last health
+    /// probe failed.
+    #[tracing::instrument(
+        target = "otel",
+        name = "storage.write",
+        skip_all,
+        fields(otel.kind = "client", storage_id = %storage_id, path = path)
+    )]
+    pub async fn write(&self, storage_id: &Uuid, path: &str, data: Bytes) -> AppResult<()> {
+        let provider = self.writable(storage_id).await?;
         provider.write(path, data).await
     }
 
+    /// Write a file to storage from a stream, returning the bytes written.
+    /// Fails fast if the provider's last health probe failed.
+    #[tracing::instrument(
+        target = "otel",
+        name = "storage.write_stream",
+        skip_all,
+        fields(otel.kind = "client", storage_id = %storage_id, path = path)
+    )]
+    pub async fn write_stream(
+        &self,
+        storage_id: &Uuid,
+        path: &str,
+        stream: ByteStream,
+    ) -> AppResult<u64> {
+        let provider = self.writable(storage_id).await?;
+        provider.write_stream(path, stream).await
+    }
+
+    /// The provider of `storage_id`, unless its last health probe failed.
+    async fn writable(&self, storage_id: &Uuid) -> AppResult<Arc<dyn StorageProvider>> {
+        let provider = self.get(storage_id).await?;
+        if let Some(health) = self.health.read().await.get(storage_id)
+            && !health.healthy
+        {
+            return Err(AppError::service_unavailable(format!(
+                "Storage provider {storage_id} is unavailable: {}",
+                health.error.as_deref().unwrap_or("health check failed")
+            )));
+        }
+        Ok(provider)
+    }
+
     /// Delete a file from storage.
+    #[tracing::instrument(
+        target = "otel",
+        name = "storage.delete",
+        skip_all,
+        fields(otel.kind = "client", storage_id = %storage_id, path = path)
+    )]
     pub async fn delete(&self, storage_id: &Uuid, path: &str) -> AppResult<()> {
         let provider = self.get(storage_id).await?;
         provider.delete(path).await
     }
 
+    /// Move a file within one storage.
+    #[tracing::instrument(
+        target = "otel",
+        name = "storage.rename",
+        skip_all,
+        fields(otel.kind = "client", storage_id = %storage_id, path = from)
+    )]
+    pub async fn rename(&self, storage_id: &Uuid, from: &str, to: &str) -> AppResult<()> {
+        let provider = self.get(storage_id).await?;
+        provider.rename(from, to).await
+    }
+
     /// Get the default storage provider.
     pub async fn get_default(&self) -> AppResult<(Uuid, Arc<dyn StorageProvider>)> {
         let default_id = {
@@ -97,6 +242,126 @@ impl StorageManager {
         providers.keys().cloned().collect()
     }
 
+    /// Whether a provider passed its last health probe (or has not been
+    /// probed yet).
+    pub async fn is_healthy(&self, storage_id: &Uuid) -> bool {
+        self.health
+            .read()
+            .await
+            .get(storage_id)
+            .is_none_or(|h| h.healthy)
+    }
+
+    /// Pick the provider a new file should be written to: `preferred` if
+    /// it is healthy, otherwise the default provider, otherwise any
+    /// healthy provider.
+    pub async fn select_for_write(&self, preferred: Uuid) -> AppResult<Uuid> {
+        if self.is_healthy(&preferred).await {
+            return Ok(preferred);
+        }
+
+        let default_id = *self.default_id.read().await;
+        let mut candidates: Vec<Uuid> = default_id.into_iter().collect();
+        let mut others = self.list_ids().await;
+        others.sort();
+        candidates.extend(others);
+
+        for candidate in candidates {
+            if candidate != preferred && self.is_healthy(&candidate).await {
+                tracing::warn!(
+                    preferred = %preferred,
+                    selected = %candidate,
+                    "Storage provider unhealthy, writing to an alternative"
+                );
+                return Ok(candidate);
+            }
+        }
+
+        Err(AppError::service_unavailable(format!(
+            "Storage provider {preferred} is unavailable and no healthy alternative is configured"
+        )))
+    }
+
+    /// Probe every registered provider once and record the results.
+    pub async fn probe_all(&self, timeout: Duration) {
+        let providers: Vec<(Uuid, Arc<dyn StorageProvider>)> = {
+            let providers = self.providers.read().await;
+            providers
+                .iter()
+                .map(|(id, p)| (*id, Arc::clone(p)))
+                .collect()
+        };
+
+        for (storage_id, provider) in providers {
+            let error = match tokio::time::timeout(timeout, provider.health_check()).await {
+                Ok(Ok(true)) => None,
+                Ok(Ok(false)) => Some("Provider reported unhealthy".to_string()),
+                Ok(Err(e)) => Some(e.to_string()),
+                Err(_) => Some(format!("Health check timed out after {timeout:?}")),
+            };
+            let healthy = error.is_none();
+
+            let mut health = self.health.write().await;
+            let was_healthy = health.get(&storage_id).is_none_or(|h| h.healthy);
+            match (&error, was_healthy) {
+                (Some(e), true) => {
+                    tracing::error!(storage_id = %storage_id, error = %e, "Storage provider unhealthy")
+                }
+                (None, false) => {
+                    tracing::info!(storage_id = %storage_id, "Storage provider recovered")
+                }
+                _ => {}
+            }
+            health.insert(
+                storage_id,
+                ProviderHealth {
+                    storage_id,
+                    healthy,
+                    last_checked: Utc::now(),
+                    error,
+                },
+            );
+        }
+    }
+
+    /// Probe all providers every `interval` until the task is aborted.
+    pub fn spawn_health_prober(
+        self: Arc<Self>,
+        interval: Duration,
+        timeout: Duration,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                self.probe_all(timeout).await;
+            }
+        })
+    }
+
+    /// Last probe result of every registered provider, ordered by ID.
+    /// Providers not probed yet are omitted.
+    pub async fn health_report(&self) -> Vec<ProviderHealth> {
+        let mut report: Vec<ProviderHealth> = self.health.read().await.values().cloned().collect();
+        report.sort_by_key(|h| h.storage_id);
+        report
+    }
+
+    /// Retry counters of every provider registered with a retry policy,
+    /// ordered by ID.
+    pub async fn retry_report(&self) -> Vec<(Uuid, RetryStatsSnapshot)> {
+        let mut report: Vec<(Uuid, RetryStatsSnapshot)> = self
+            .retry_stats
+            .read()
+            .await
+            .iter()
+            .map(|(id, stats)| (*id, stats.snapshot()))
+            .collect();
+        report.sort_by_key(|(id, _)| *id);
+        report
+    }
+
     /// Check health of all registered providers.
     pub async fn health_check_all(&self) -> HashMap<Uuid, bool> {
         let providers = self.providers.read().await;
@@ -114,3 +379,55 @@ impl Default for StorageManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::local::LocalStorageProvider;
+
+    #[tokio::test]
+    async fn test_unhealthy_provider_is_avoided() {
+        let primary_dir = tempfile::tempdir().unwrap();
+        let spare_dir = tempfile::tempdir().unwrap();
+        let primary_root = primary_dir.path().join("root");
+        let primary = LocalStorageProvider::new(primary_root.to_str().unwrap())
+            .await
+            .unwrap();
+        let spare = LocalStorageProvider::new(spare_dir.path().to_str().unwrap())
+            .await
+            .unwrap();
+
+        let manager = StorageManager::new();
+        let (primary_id, spare_id) = (Uuid::new_v4(), Uuid::new_v4());
+        manager.register(primary_id, Arc::new(primary), true).await;
+        manager.register(spare_id, Arc::new(spare), false).await;
+
+        manager.probe_all(Duration::from_secs(1)).await;
+        assert_eq!(
+            manager.select_for_write(primary_id).await.unwrap(),
+            primary_id
+        );
+
+        std::fs::remove_dir_all(&primary_root).unwrap();
+        manager.probe_all(Duration::from_secs(1)).await;
+
+        let report = manager.health_report().await;
+        let primary_health = report.iter().find(|h| h.storage_id == primary_id).unwrap();
+        assert!(!primary_health.healthy);
+        assert!(primary_health.error.is_some());
+        assert_eq!(
+            manager.select_for_write(primary_id).await.unwrap(),
+            spare_id
+        );
+
+        let err = manager
+            .write(&primary_id, "a.txt", Bytes::from("x"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind, filehub_core::error::ErrorKind::ServiceUnavailable);
+
+        std::fs::create_dir_all(&primary_root).unwrap();
+        manager.probe_all(Duration::from_secs(1)).await;
+        assert!(manager.is_healthy(&primary_id).await);
+    }
+}
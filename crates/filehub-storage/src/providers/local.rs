@@ -1,12 +1,13 @@
 //! Local filesystem storage provider.
 
+use std::io::SeekFrom;
 use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
 use bytes::Bytes;
 use futures::stream::StreamExt;
 use tokio::fs;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use tokio_util::io::ReaderStream;
 use tracing::debug;
 
@@ -84,6 +85,32 @@ impl StorageProvider for LocalStorageProvider {
         Ok(Box::pin(stream.map(|r| r.map(|b| b.into()))))
     }
 
+    async fn read_range(&self, path: &str, offset: u64, len: u64) -> AppResult<ByteStream> {
+        let full_path = self.resolve(path);
+        let mut file = fs::File::open(&full_path).await.map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                AppError::not_found(format!("File not found: {path}"))
+            } else {
+                AppError::with_source(
+                    ErrorKind::Storage,
+                    format!("Failed to open file: {path}"),
+                    e,
+                )
+            }
+        })?;
+
+        file.seek(SeekFrom::Start(offset)).await.map_err(|e| {
+            AppError::with_source(
+                ErrorKind::Storage,
+                format!("Failed to seek in file: {path}"),
+                e,
+            )
+        })?;
+
+        let stream = ReaderStream::new(file.take(len));
+        Ok(Box::pin(stream))
+    }
+
     async fn read_bytes(&self, path: &str) -> AppResult<Bytes> {
         let full_path = self.resolve(path);
         let data = fs::read(&full_path).await.map_err(|e| {
@@ -322,40 +349,7 @@ impl StorageProvider for LocalStorageProvider {
 
 /// Guess MIME type from a file path extension.
 fn mime_from_path(path: &str) -> Option<String> {
-    let ext = path.rsplit('.').next()?.to_lowercase();
-    let mime = match ext.as_str() {
-        "txt" => "text/plain",
-        "html" | "htm" => "text/html",
-        "css" => "text/css",
-        "js" => "application/javascript",
-        "json" => "application/json",
-        "xml" => "application/xml",
-        "pdf" => "application/pdf",
-        "zip" => "application/zip",
-        "gz" | "gzip" => "application/gzip",
-        "tar" => "application/x-tar",
-        "png" => "image/png",
-        "jpg" | "jpeg" => "image/jpeg",
-        "gif" => "image/gif",
-        "svg" => "image/svg+xml",
-        "webp" => "image/webp",
-        "mp4" => "video/mp4",
-        "mp3" => "audio/mpeg",
-        "wav" => "audio/wav",
-        "csv" => "text/csv",
-        "doc" => "application/msword",
-        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
-        "xls" => "application/vnd.ms-excel",
-        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
-        "ppt" => "application/vnd.ms-powerpoint",
-        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
-        "dwg" => "application/acad",
-        "dxf" => "application/dxf",
-        "step" | "stp" => "application/step",
-        "stl" => "application/sla",
-        _ => return None,
-    };
-    Some(mime.to_string())
+    crate::sniff::mime_from_extension(path).map(String::from)
 }
 
 #[cfg(test)]
@@ -381,6 +375,26 @@ mod tests {
         assert!(!provider.exists("test/file.txt").await.unwrap());
     }
 
+    #[tokio::test]
+    async fn test_read_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = LocalStorageProvider::new(dir.path().to_str().unwrap())
+            .await
+            .unwrap();
+
+        provider
+            .write("range.txt", Bytes::from("0123456789"))
+            .await
+            .unwrap();
+
+        let mut stream = provider.read_range("range.txt", 3, 4).await.unwrap();
+        let mut collected = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            collected.extend_from_slice(&chunk.unwrap());
+        }
+        assert_eq!(collected, b"3456");
+    }
+
     #[tokio::test]
     async fn test_list() {
         let dir = tempfile::tempdir().unwrap();
@@ -431,4 +445,21 @@ mod tests {
         assert_eq!(mime_from_path("img.PNG"), Some("image/png".into()));
         assert_eq!(mime_from_path("noext"), None);
     }
+
+    #[tokio::test]
+    async fn test_presign_is_unsupported() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = LocalStorageProvider::new(dir.path().to_str().unwrap())
+            .await
+            .unwrap();
+
+        let err = provider
+            .presign_upload("a.txt", "text/plain", 1, std::time::Duration::from_secs(60))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.code,
+            filehub_core::error::codes::STORAGE_PRESIGN_UNSUPPORTED
+        );
+    }
 }
@@ -7,3 +7,12 @@ pub mod s3;
 pub mod smb;
 
 pub use local::LocalStorageProvider;
+
+/// Provider types compiled into this build.
+pub const COMPILED_PROVIDERS: &[&str] = &[
+    "local",
+    #[cfg(feature = "s3")]
+    "s3",
+    #[cfg(feature = "smb")]
+    "smb",
+];
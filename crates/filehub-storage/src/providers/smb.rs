@@ -35,6 +35,11 @@ impl StorageProvider for SmbStorageProvider {
     async fn read(&self, _p: &str) -> AppResult<ByteStream> {
         Err(AppError::not_implemented("SMB read not yet implemented"))
     }
+    async fn read_range(&self, _p: &str, _o: u64, _l: u64) -> AppResult<ByteStream> {
+        Err(AppError::not_implemented(
+            "SMB read_range not yet implemented",
+        ))
+    }
     async fn read_bytes(&self, _p: &str) -> AppResult<Bytes> {
         Err(AppError::not_implemented(
             "SMB read_bytes not yet implemented",
@@ -0,0 +1,603 @@
+//! Retries and timeouts around a storage provider.
+//!
+//! [`RetryingProvider`] wraps a provider and runs each operation under
+//! the timeout of its kind, retrying failures of the classes its
+//! [`StorageRetryPolicy`] names with exponential backoff. An operation
+//! whose attempts run out fails with `STORAGE_RETRIES_EXHAUSTED`, carrying
+//! the last failure as its source.
+//!
+//! Only operations that can safely be repeated are retried. Writing
+//! bytes replaces the whole object, so a repeat leaves the same result;
+//! a streamed write consumes its stream and is attempted once. A delete
+//! or move that failed after the backend carried it out finds nothing to
+//! do on its retry, which counts as success.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde::Serialize;
+
+use filehub_core::config::{StorageErrorClass, StorageRetryPolicy};
+use filehub_core::error::{AppError, ErrorKind, codes};
+use filehub_core::result::AppResult;
+use filehub_core::traits::storage::{
+    ByteStream, PresignedRequest, StorageObjectMeta, StorageProvider,
+};
+
+/// Which timeout of the policy an operation runs under.
+#[derive(Debug, Clone, Copy)]
+enum Timeout {
+    Read,
+    Write,
+    Delete,
+    List,
+}
+
+/// Counters of a provider's retries, shared with the storage manager.
+#[derive(Debug, Default)]
+pub struct RetryStats {
+    retries: AtomicU64,
+    exhausted: AtomicU64,
+    timeouts: AtomicU64,
+}
+
+/// A point-in-time copy of [`RetryStats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RetryStatsSnapshot {
+    /// Attempts made after a failed one.
+    pub retries: u64,
+    /// Operations that failed on their last attempt.
+    pub exhausted: u64,
+    /// Attempts that ran into their timeout.
+    pub timeouts: u64,
+}
+
+impl RetryStats {
+    /// Current counts.
+    pub fn snapshot(&self) -> RetryStatsSnapshot {
+        RetryStatsSnapshot {
+            retries: self.retries.load(Ordering::Relaxed),
+            exhausted: self.exhausted.load(Ordering::Relaxed),
+            timeouts: self.timeouts.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A storage provider whose operations are retried and time-limited.
+#[derive(Debug)]
+pub struct RetryingProvider {
+    inner: Arc<dyn StorageProvider>,
+    policy: StorageRetryPolicy,
+    stats: Arc<RetryStats>,
+}
+
+impl RetryingProvider {
+    /// Wrap `inner` with `policy`.
+    pub fn new(inner: Arc<dyn StorageProvider>, policy: StorageRetryPolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            stats: Arc::new(RetryStats::default()),
+        }
+    }
+
+    /// The counters of this provider.
+    pub fn stats(&self) -> Arc<RetryStats> {
+        Arc::clone(&self.stats)
+    }
+
+    fn timeout(&self, kind: Timeout) -> Duration {
+        Duration::from_secs(match kind {
+            Timeout::Read => self.policy.read_timeout_seconds,
+            Timeout::Write => self.policy.write_timeout_seconds,
+            Timeout::Delete => self.policy.delete_timeout_seconds,
+            Timeout::List => self.policy.list_timeout_seconds,
+        })
+    }
+
+    /// Delay before retry number `retry` (0-based).
+    fn backoff(&self, retry: u32) -> Duration {
+        let delay = self
+            .policy
+            .base_delay_ms
+            .saturating_mul(1u64 << retry.min(20))
+            .min(self.policy.max_delay_ms);
+        Duration::from_millis(delay)
+    }
+
+    fn is_retryable(&self, error: &AppError) -> bool {
+        let class = match error.kind {
+            ErrorKind::Timeout => StorageErrorClass::Timeout,
+            ErrorKind::ServiceUnavailable | ErrorKind::ExternalService => {
+                StorageErrorClass::Unavailable
+            }
+            ErrorKind::Storage => StorageErrorClass::Backend,
+            _ => return false,
+        };
+        self.policy.retry_on.contains(&class)
+    }
+
+    /// Run one attempt under the timeout of `kind`.
+    async fn attempt<T>(
+        &self,
+        op: &str,
+        path: &str,
+        kind: Timeout,
+        fut: impl Future<Output = AppResult<T>>,
+    ) -> AppResult<T> {
+        let limit = self.timeout(kind);
+        match tokio::time::timeout(limit, fut).await {
+            Ok(result) => result,
+            Err(_) => {
+                self.stats.timeouts.fetch_add(1, Ordering::Relaxed);
+                Err(AppError::new(
+                    ErrorKind::Timeout,
+                    format!(
+                        "Storage {op} of '{path}' timed out after {}s",
+                        limit.as_secs()
+                    ),
+                ))
+            }
+        }
+    }
+
+    /// Run `op` until it succeeds, fails with a non-retryable error or
+    /// runs out of attempts. `call` gets the attempt number, from 1.
+    async fn run<T, F, Fut>(&self, op: &str, path: &str, kind: Timeout, mut call: F) -> AppResult<T>
+    where
+        F: FnMut(u32) -> Fut,
+        Fut: Future<Output = AppResult<T>>,
+    {
+        let max_attempts = self.policy.max_attempts.max(1);
+        let mut attempt = 1;
+        loop {
+            let error = match self.attempt(op, path, kind, call(attempt)).await {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+            if !self.is_retryable(&error) {
+                return Err(error);
+            }
+            if attempt >= max_attempts {
+                if max_attempts == 1 {
+                    return Err(error);
+                }
+                self.stats.exhausted.fetch_add(1, Ordering::Relaxed);
+                tracing::error!(
+                    provider = self.inner.provider_type(),
+                    op,
+                    path,
+                    attempts = attempt,
+                    error = %error,
+                    "Storage operation failed after retries"
+                );
+                return Err(AppError::with_source(
+                    ErrorKind::ServiceUnavailable,
+                    format!("Storage {op} of '{path}' failed after {attempt} attempts"),
+                    error,
+                )
+                .with_code(codes::STORAGE_RETRIES_EXHAUSTED));
+            }
+
+            let delay = self.backoff(attempt - 1);
+            tracing::warn!(
+                provider = self.inner.provider_type(),
+                op,
+                path,
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                error = %error,
+                "Storage operation failed, retrying"
+            );
+            self.stats.retries.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[async_trait]
+impl StorageProvider for RetryingProvider {
+    fn provider_type(&self) -> &str {
+        self.inner.provider_type()
+    }
+
+    async fn health_check(&self) -> AppResult<bool> {
+        // The prober applies its own timeout and wants the first failure.
+        self.inner.health_check().await
+    }
+
+    async fn read(&self, path: &str) -> AppResult<ByteStream> {
+        self.run("read", path, Timeout::Read, |_| self.inner.read(path))
+            .await
+    }
+
+    async fn read_range(&self, path: &str, offset: u64, len: u64) -> AppResult<ByteStream> {
+        self.run("read", path, Timeout::Read, |_| {
+            self.inner.read_range(path, offset, len)
+        })
+        .await
+    }
+
+    async fn read_bytes(&self, path: &str) -> AppResult<Bytes> {
+        self.run("read", path, Timeout::Read, |_| self.inner.read_bytes(path))
+            .await
+    }
+
+    async fn write(&self, path: &str, data: Bytes) -> AppResult<()> {
+        self.run("write", path, Timeout::Write, |_| {
+            self.inner.write(path, data.clone())
+        })
+        .await
+    }
+
+    async fn write_stream(&self, path: &str, stream: ByteStream) -> AppResult<u64> {
+        self.attempt(
+            "write",
+            path,
+            Timeout::Write,
+            self.inner.write_stream(path, stream),
+        )
+        .await
+    }
+
+    async fn delete(&self, path: &str) -> AppResult<()> {
+        self.run("delete", path, Timeout::Delete, |attempt| async move {
+            match self.inner.delete(path).await {
+                Err(e) if attempt > 1 && e.kind == ErrorKind::NotFound => Ok(()),
+                result => result,
+            }
+        })
+        .await
+    }
+
+    async fn delete_dir(&self, path: &str) -> AppResult<()> {
+        self.run("delete", path, Timeout::Delete, |attempt| async move {
+            match self.inner.delete_dir(path).await {
+                Err(e) if attempt > 1 && e.kind == ErrorKind::NotFound => Ok(()),
+                result => result,
+            }
+        })
+        .await
+    }
+
+    async fn copy(&self, from: &str, to: &str) -> AppResult<()> {
+        self.run("copy", from, Timeout::Write, |_| self.inner.copy(from, to))
+            .await
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> AppResult<()> {
+        self.run("move", from, Timeout::Write, |attempt| async move {
+            match self.inner.rename(from, to).await {
+                Err(e) if attempt > 1 && e.kind == ErrorKind::NotFound => {
+                    // The failed attempt may have moved it already.
+                    if self.inner.exists(to).await? {
+                        Ok(())
+                    } else {
+                        Err(e)
+                    }
+                }
+                result => result,
+            }
+        })
+        .await
+    }
+
+    async fn exists(&self, path: &str) -> AppResult<bool> {
+        self.run("exists", path, Timeout::Read, |_| self.inner.exists(path))
+            .await
+    }
+
+    async fn metadata(&self, path: &str) -> AppResult<StorageObjectMeta> {
+        self.run("metadata", path, Timeout::Read, |_| {
+            self.inner.metadata(path)
+        })
+        .await
+    }
+
+    async fn list(&self, path: &str) -> AppResult<Vec<StorageObjectMeta>> {
+        self.run("list", path, Timeout::List, |_| self.inner.list(path))
+            .await
+    }
+
+    async fn create_dir(&self, path: &str) -> AppResult<()> {
+        self.run("create_dir", path, Timeout::Write, |_| {
+            self.inner.create_dir(path)
+        })
+        .await
+    }
+
+    async fn capacity(&self) -> AppResult<(u64, u64)> {
+        self.run("capacity", "/", Timeout::List, |_| self.inner.capacity())
+            .await
+    }
+
+    async fn presign_upload(
+        &self,
+        path: &str,
+        content_type: &str,
+        content_length: u64,
+        expires_in: Duration,
+    ) -> AppResult<PresignedRequest> {
+        self.inner
+            .presign_upload(path, content_type, content_length, expires_in)
+            .await
+    }
+
+    async fn presign_download(
+        &self,
+        path: &str,
+        content_disposition: Option<&str>,
+        expires_in: Duration,
+    ) -> AppResult<PresignedRequest> {
+        self.inner
+            .presign_download(path, content_disposition, expires_in)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    use crate::providers::local::LocalStorageProvider;
+
+    /// A local provider whose first `failures` writes and listings fail
+    /// before doing the work, and deletes after it.
+    #[derive(Debug)]
+    struct Flaky {
+        inner: LocalStorageProvider,
+        failures: Mutex<u32>,
+        error: fn() -> AppError,
+    }
+
+    impl Flaky {
+        fn fail(&self) -> Option<AppError> {
+            let mut failures = self.failures.lock().unwrap();
+            (*failures > 0).then(|| {
+                *failures -= 1;
+                (self.error)()
+            })
+        }
+    }
+
+    #[async_trait]
+    impl StorageProvider for Flaky {
+        fn provider_type(&self) -> &str {
+            "s3"
+        }
+        async fn health_check(&self) -> AppResult<bool> {
+            self.inner.health_check().await
+        }
+        async fn read(&self, path: &str) -> AppResult<ByteStream> {
+            self.inner.read(path).await
+        }
+        async fn read_range(&self, path: &str, offset: u64, len: u64) -> AppResult<ByteStream> {
+            self.inner.read_range(path, offset, len).await
+        }
+        async fn read_bytes(&self, path: &str) -> AppResult<Bytes> {
+            self.inner.read_bytes(path).await
+        }
+        async fn write(&self, path: &str, data: Bytes) -> AppResult<()> {
+            if let Some(e) = self.fail() {
+                return Err(e);
+            }
+            self.inner.write(path, data).await
+        }
+        async fn write_stream(&self, path: &str, stream: ByteStream) -> AppResult<u64> {
+            if let Some(e) = self.fail() {
+                return Err(e);
+            }
+            self.inner.write_stream(path, stream).await
+        }
+        async fn delete(&self, path: &str) -> AppResult<()> {
+            self.inner.delete(path).await?;
+            self.fail().map_or(Ok(()), Err)
+        }
+        async fn delete_dir(&self, path: &str) -> AppResult<()> {
+            self.inner.delete_dir(path).await
+        }
+        async fn copy(&self, from: &str, to: &str) -> AppResult<()> {
+            self.inner.copy(from, to).await
+        }
+        async fn rename(&self, from: &str, to: &str) -> AppResult<()> {
+            self.inner.rename(from, to).await
+        }
+        async fn exists(&self, path: &str) -> AppResult<bool> {
+            self.inner.exists(path).await
+        }
+        async fn metadata(&self, path: &str) -> AppResult<StorageObjectMeta> {
+            self.inner.metadata(path).await
+        }
+        async fn list(&self, path: &str) -> AppResult<Vec<StorageObjectMeta>> {
+            if let Some(e) = self.fail() {
+                return Err(e);
+            }
+            self.inner.list(path).await
+        }
+        async fn create_dir(&self, path: &str) -> AppResult<()> {
+            self.inner.create_dir(path).await
+        }
+        async fn capacity(&self) -> AppResult<(u64, u64)> {
+            self.inner.capacity().await
+        }
+    }
+
+    async fn flaky(
+        dir: &tempfile::TempDir,
+        failures: u32,
+        error: fn() -> AppError,
+        policy: StorageRetryPolicy,
+    ) -> RetryingProvider {
+        let inner = LocalStorageProvider::new(dir.path().to_str().unwrap())
+            .await
+            .unwrap();
+        let flaky = Flaky {
+            inner,
+            failures: Mutex::new(failures),
+            error,
+        };
+        RetryingProvider::new(Arc::new(flaky), policy)
+    }
+
+    fn policy() -> StorageRetryPolicy {
+        StorageRetryPolicy {
+            base_delay_ms: 1,
+            max_delay_ms: 4,
+            ..Default::default()
+        }
+    }
+
+    fn blip() -> AppError {
+        AppError::storage("connection reset")
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = flaky(&dir, 2, blip, policy()).await;
+
+        provider.write("a.txt", Bytes::from("x")).await.unwrap();
+        assert_eq!(provider.read_bytes("a.txt").await.unwrap(), "x");
+        assert_eq!(
+            provider.stats().snapshot(),
+            RetryStatsSnapshot {
+                retries: 2,
+                exhausted: 0,
+                timeouts: 0
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_retries_are_distinct() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = flaky(&dir, 5, blip, policy()).await;
+
+        let error = provider.list("").await.unwrap_err();
+        assert_eq!(error.code, codes::STORAGE_RETRIES_EXHAUSTED);
+        assert_eq!(error.kind, ErrorKind::ServiceUnavailable);
+        assert_eq!(provider.stats().snapshot().retries, 2);
+        assert_eq!(provider.stats().snapshot().exhausted, 1);
+    }
+
+    #[tokio::test]
+    async fn test_only_listed_classes_are_retried() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = flaky(&dir, 1, || AppError::forbidden("denied"), policy()).await;
+        let error = provider.list("").await.unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Forbidden);
+
+        let policy = StorageRetryPolicy {
+            retry_on: vec![StorageErrorClass::Timeout],
+            ..policy()
+        };
+        let provider = flaky(&dir, 1, blip, policy).await;
+        assert_eq!(
+            provider.list("").await.unwrap_err().kind,
+            ErrorKind::Storage
+        );
+        assert_eq!(provider.stats().snapshot().retries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_delete_that_landed_is_not_reported_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("b.txt"), "y").unwrap();
+        let provider = flaky(&dir, 1, blip, policy()).await;
+        provider.delete("b.txt").await.unwrap();
+        assert!(!dir.path().join("b.txt").exists());
+        assert_eq!(provider.stats().snapshot().retries, 1);
+    }
+
+    #[tokio::test]
+    async fn test_streamed_write_is_attempted_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = flaky(&dir, 1, blip, policy()).await;
+        let stream: ByteStream = Box::pin(futures::stream::iter(vec![Ok(Bytes::from("x"))]));
+        assert_eq!(
+            provider
+                .write_stream("a.txt", stream)
+                .await
+                .unwrap_err()
+                .kind,
+            ErrorKind::Storage
+        );
+        assert_eq!(provider.stats().snapshot().retries, 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stuck_operation_times_out() {
+        #[derive(Debug)]
+        struct Stuck;
+
+        #[async_trait]
+        impl StorageProvider for Stuck {
+            fn provider_type(&self) -> &str {
+                "local"
+            }
+            async fn health_check(&self) -> AppResult<bool> {
+                Ok(true)
+            }
+            async fn read(&self, _: &str) -> AppResult<ByteStream> {
+                std::future::pending().await
+            }
+            async fn read_range(&self, _: &str, _: u64, _: u64) -> AppResult<ByteStream> {
+                std::future::pending().await
+            }
+            async fn read_bytes(&self, _: &str) -> AppResult<Bytes> {
+                std::future::pending().await
+            }
+            async fn write(&self, _: &str, _: Bytes) -> AppResult<()> {
+                std::future::pending().await
+            }
+            async fn write_stream(&self, _: &str, _: ByteStream) -> AppResult<u64> {
+                std::future::pending().await
+            }
+            async fn delete(&self, _: &str) -> AppResult<()> {
+                std::future::pending().await
+            }
+            async fn delete_dir(&self, _: &str) -> AppResult<()> {
+                std::future::pending().await
+            }
+            async fn copy(&self, _: &str, _: &str) -> AppResult<()> {
+                std::future::pending().await
+            }
+            async fn rename(&self, _: &str, _: &str) -> AppResult<()> {
+                std::future::pending().await
+            }
+            async fn exists(&self, _: &str) -> AppResult<bool> {
+                std::future::pending().await
+            }
+            async fn metadata(&self, _: &str) -> AppResult<StorageObjectMeta> {
+                std::future::pending().await
+            }
+            async fn list(&self, _: &str) -> AppResult<Vec<StorageObjectMeta>> {
+                std::future::pending().await
+            }
+            async fn create_dir(&self, _: &str) -> AppResult<()> {
+                std::future::pending().await
+            }
+            async fn capacity(&self) -> AppResult<(u64, u64)> {
+                std::future::pending().await
+            }
+        }
+
+        let provider = RetryingProvider::new(
+            Arc::new(Stuck),
+            StorageRetryPolicy {
+                max_attempts: 1,
+                ..Default::default()
+            },
+        );
+        let error = provider.read_bytes("a.txt").await.unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Timeout);
+        assert!(error.message.contains("after 30s"));
+        assert_eq!(provider.stats().snapshot().timeouts, 1);
+    }
+}
@@ -0,0 +1,156 @@
+//! Content type detection from magic bytes.
+//!
+//! The type of an upload is what its content says it is: its magic bytes
+//! when they are recognized, otherwise its extension, otherwise
+//! `application/octet-stream`. What the client declared is not trusted,
+//! so a renamed or mislabelled file is still served and previewed as what
+//! it really is.
+
+/// Bytes of a file's head that [`sniff`] looks at; no detected format
+/// needs more.
+pub const SNIFF_LEN: usize = 8192;
+
+/// A content type detected from a file's leading bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SniffedType {
+    /// MIME type, e.g. `image/png`.
+    pub mime_type: &'static str,
+    /// Usual extension without the dot, e.g. `png`.
+    pub extension: &'static str,
+}
+
+/// Detects the type of content starting with `head`. Returns `None` for
+/// formats without a signature, such as plain text.
+pub fn sniff(head: &[u8]) -> Option<SniffedType> {
+    let head = &head[..head.len().min(SNIFF_LEN)];
+    infer::get(head).map(|kind| SniffedType {
+        mime_type: kind.mime_type(),
+        extension: kind.extension(),
+    })
+}
+
+/// Type of content that is neither recognized nor named after a known
+/// extension.
+pub const OCTET_STREAM: &str = "application/octet-stream";
+
+/// Container formats that many document formats are stored in. When the
+/// magic bytes only reveal the container, the extension names the format.
+const CONTAINERS: &[&str] = &["application/zip", "application/x-ole-storage"];
+
+/// The MIME type of a file named `file_name` whose content starts with
+/// `head`.
+pub fn content_type(head: &[u8], file_name: &str) -> &'static str {
+    let by_extension = mime_from_extension(file_name);
+    match sniff(head) {
+        Some(sniffed) if CONTAINERS.contains(&sniffed.mime_type) => {
+            by_extension.unwrap_or(sniffed.mime_type)
+        }
+        Some(sniffed) => sniffed.mime_type,
+        None => by_extension.unwrap_or(OCTET_STREAM),
+    }
+}
+
+/// The MIME type usual for the extension of `file_name`, if it has a
+/// known one.
+pub fn mime_from_extension(file_name: &str) -> Option<&'static str> {
+    let name = file_name.rsplit('/').next().unwrap_or(file_name);
+    let (_, ext) = name.rsplit_once('.')?;
+    let mime = match ext.to_ascii_lowercase().as_str() {
+        "txt" => "text/plain",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" => "application/javascript",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" | "gzip" => "application/gzip",
+        "tar" => "application/x-tar",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "mp4" => "video/mp4",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "csv" => "text/csv",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xls" => "application/vnd.ms-excel",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "ppt" => "application/vnd.ms-powerpoint",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "dwg" => "application/acad",
+        "dxf" => "application/dxf",
+        "step" | "stp" => "application/step",
+        "stl" => "application/sla",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_known_signatures() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        assert_eq!(sniff(png).map(|t| t.mime_type), Some("image/png"));
+        assert_eq!(sniff(b"%PDF-1.7\n").map(|t| t.extension), Some("pdf"));
+        assert_eq!(sniff(b"plain text"), None);
+        assert_eq!(sniff(b""), None);
+    }
+
+    /// Start of a JPEG (JFIF) image.
+    const JPEG: &[u8] = b"\xff\xd8\xff\xe0\x00\x10JFIF\x00";
+    /// Start of a GIF image.
+    const GIF: &[u8] = b"GIF89a\x01\x00\x01\x00";
+    /// Start of a plain ZIP archive.
+    const ZIP: &[u8] = b"PK\x03\x04\x14\x00\x00\x00\x08\x00";
+
+    #[test]
+    fn test_content_type_of_common_formats() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        assert_eq!(content_type(png, "photo.png"), "image/png");
+        assert_eq!(content_type(JPEG, "photo.jpg"), "image/jpeg");
+        assert_eq!(content_type(GIF, "anim.gif"), "image/gif");
+        assert_eq!(content_type(b"%PDF-1.7\n", "report.pdf"), "application/pdf");
+        assert_eq!(content_type(ZIP, "bundle.zip"), "application/zip");
+    }
+
+    #[test]
+    fn test_content_wins_over_a_wrong_name() {
+        // A PNG renamed to .pdf is a PNG.
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        assert_eq!(content_type(png, "invoice.pdf"), "image/png");
+        // A PDF without an extension is still a PDF.
+        assert_eq!(content_type(b"%PDF-1.4\n", "scan"), "application/pdf");
+    }
+
+    #[test]
+    fn test_inconclusive_content_falls_back() {
+        assert_eq!(content_type(b"a,b\n1,2\n", "data.csv"), "text/csv");
+        assert_eq!(content_type(b"hello", "README.TXT"), "text/plain");
+        assert_eq!(content_type(b"hello", "README"), OCTET_STREAM);
+        assert_eq!(content_type(b"", "empty.bin"), OCTET_STREAM);
+    }
+
+    #[test]
+    fn test_container_formats_are_named_by_extension() {
+        assert_eq!(
+            content_type(ZIP, "budget.xlsx"),
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+        );
+        // An archive with an unknown extension stays an archive.
+        assert_eq!(content_type(ZIP, "backup.dat"), "application/zip");
+    }
+
+    #[test]
+    fn test_mime_from_extension() {
+        assert_eq!(mime_from_extension("a/b.v2/notes.txt"), Some("text/plain"));
+        assert_eq!(mime_from_extension("dir.d/noext"), None);
+        assert_eq!(mime_from_extension("txt"), None);
+    }
+}
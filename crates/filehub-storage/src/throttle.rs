@@ -0,0 +1,92 @@
+//! Bandwidth limiting for bulk copies between providers.
+
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Limits the average rate of a sequence of transfers.
+///
+/// Callers report each chunk they move through [`consume`](Self::consume),
+/// which sleeps for as long as the transfer is ahead of the configured
+/// rate. The rate is averaged from the first call, so a slow start leaves
+/// room for a later burst of at most the time already lost.
+#[derive(Debug)]
+pub struct BandwidthThrottle {
+    /// Allowed bytes per second; 0 disables the limit.
+    bytes_per_second: u64,
+    /// Start time and bytes reported since.
+    state: Mutex<Option<(Instant, u64)>>,
+}
+
+impl BandwidthThrottle {
+    /// Create a throttle allowing `bytes_per_second`; 0 is unlimited.
+    pub fn new(bytes_per_second: u64) -> Self {
+        Self {
+            bytes_per_second,
+            state: Mutex::new(None),
+        }
+    }
+
+    /// Whether a limit is set.
+    pub fn is_limited(&self) -> bool {
+        self.bytes_per_second > 0
+    }
+
+    /// Record `bytes` transferred and wait until the average rate is back
+    /// within the limit.
+    pub async fn consume(&self, bytes: u64) {
+        if !self.is_limited() {
+            return;
+        }
+        let wait = {
+            let mut state = self.state.lock().await;
+            let (started, sent) = state.get_or_insert_with(|| (Instant::now(), 0));
+            *sent = sent.saturating_add(bytes);
+            delay_for(self.bytes_per_second, *sent, started.elapsed())
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// How long to wait after sending `sent` bytes in `elapsed` to average
+/// `bytes_per_second`.
+fn delay_for(bytes_per_second: u64, sent: u64, elapsed: Duration) -> Duration {
+    if bytes_per_second == 0 {
+        return Duration::ZERO;
+    }
+    let due = Duration::from_secs_f64(sent as f64 / bytes_per_second as f64);
+    due.saturating_sub(elapsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_for() {
+        assert_eq!(delay_for(0, 1 << 30, Duration::ZERO), Duration::ZERO);
+        assert_eq!(
+            delay_for(1000, 2000, Duration::from_secs(1)),
+            Duration::from_secs(1)
+        );
+        assert_eq!(delay_for(1000, 500, Duration::from_secs(1)), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_consume_holds_the_rate() {
+        let throttle = BandwidthThrottle::new(1000);
+        let start = Instant::now();
+        for _ in 0..4 {
+            throttle.consume(500).await;
+        }
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+
+        let unlimited = BandwidthThrottle::new(0);
+        let start = Instant::now();
+        unlimited.consume(u64::MAX).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,497 @@
+//! Page previews of PDFs and office documents.
+//!
+//! [`DocumentRenderer`] turns a document on local disk into one PNG per
+//! page using external tools, and [`PreviewCache`] keeps the pages on disk
+//! keyed by the document's content hash, so a document is rendered once
+//! however many files or versions share its content.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use filehub_core::config::DocumentPreviewConfig;
+use filehub_core::error::{AppError, ErrorKind};
+use filehub_core::result::AppResult;
+
+/// Office formats LibreOffice converts to PDF, by extension.
+const OFFICE_EXTENSIONS: &[&str] = &[
+    "doc", "docx", "odt", "rtf", "xls", "xlsx", "ods", "csv", "ppt", "pptx", "odp",
+];
+
+/// Documents that can be previewed page by page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    /// Rendered directly.
+    Pdf,
+    /// Converted to PDF first.
+    Office,
+}
+
+impl DocumentKind {
+    /// Detects the kind from the MIME type, falling back to the file name's
+    /// extension. `None` for anything else.
+    pub fn detect(mime_type: Option<&str>, name: &str) -> Option<Self> {
+        if mime_type == Some("application/pdf") {
+            return Some(Self::Pdf);
+        }
+        let ext = Path::new(name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase)?;
+        if ext == "pdf" {
+            Some(Self::Pdf)
+        } else if OFFICE_EXTENSIONS.contains(&ext.as_str()) {
+            Some(Self::Office)
+        } else {
+            None
+        }
+    }
+}
+
+/// Renders documents to PNG pages with LibreOffice and `pdftoppm`.
+#[derive(Debug, Clone)]
+pub struct DocumentRenderer {
+    /// LibreOffice executable.
+    office_command: String,
+    /// `pdftoppm` executable.
+    pdf_command: String,
+    /// Limit on each tool run.
+    timeout: Duration,
+    /// Pages rendered per document.
+    max_pages: u32,
+    /// Rendering resolution.
+    resolution_dpi: u32,
+}
+
+impl DocumentRenderer {
+    /// Creates a renderer from the preview configuration.
+    pub fn new(config: &DocumentPreviewConfig) -> Self {
+        Self {
+            office_command: config.office_command.clone(),
+            pdf_command: config.pdf_command.clone(),
+            timeout: Duration::from_secs(config.timeout_seconds),
+            max_pages: config.max_pages.max(1),
+            resolution_dpi: config.resolution_dpi.max(1),
+        }
+    }
+
+    /// Renders `source` into `work_dir`, returning the page images in page
+    /// order. `work_dir` must exist and is left for the caller to remove.
+    pub async fn render(
+        &self,
+        kind: DocumentKind,
+        source: &Path,
+        work_dir: &Path,
+    ) -> AppResult<Vec<PathBuf>> {
+        let pdf = match kind {
+            DocumentKind::Pdf => source.to_path_buf(),
+            DocumentKind::Office => self.convert_to_pdf(source, work_dir).await?,
+        };
+
+        let prefix = work_dir.join("page");
+        self.run(
+            &self.pdf_command,
+            &[
+                "-png".as_ref(),
+                "-r".as_ref(),
+                self.resolution_dpi.to_string().as_ref(),
+                "-f".as_ref(),
+                "1".as_ref(),
+                "-l".as_ref(),
+                self.max_pages.to_string().as_ref(),
+                pdf.as_os_str(),
+                prefix.as_os_str(),
+            ],
+        )
+        .await?;
+
+        let pages = rendered_pages(work_dir).await?;
+        if pages.is_empty() {
+            return Err(AppError::new(
+                ErrorKind::ExternalService,
+                "PDF renderer produced no pages",
+            ));
+        }
+        Ok(pages)
+    }
+
+    /// Converts an office document to PDF in `work_dir`.
+    async fn convert_to_pdf(&self, source: &Path, work_dir: &Path) -> AppResult<PathBuf> {
+        // A private profile lets conversions run side by side; LibreOffice
+        // refuses to start while another instance holds the same profile.
+        let profile = format!(
+            "-env:UserInstallation=file://{}",
+            work_dir.join("profile").display()
+        );
+        self.run(
+            &self.office_command,
+            &[
+                profile.as_ref(),
+                "--headless".as_ref(),
+                "--convert-to".as_ref(),
+                "pdf".as_ref(),
+                "--outdir".as_ref(),
+                work_dir.as_os_str(),
+                source.as_os_str(),
+            ],
+        )
+        .await?;
+
+        let stem = source.file_stem().unwrap_or_default();
+        let pdf = work_dir.join(stem).with_extension("pdf");
+        if !tokio::fs::try_exists(&pdf).await.unwrap_or(false) {
+            return Err(AppError::new(
+                ErrorKind::ExternalService,
+                "Office converter produced no PDF",
+            ));
+        }
+        Ok(pdf)
+    }
+
+    /// Runs a tool to completion, killing it after the timeout.
+    async fn run(&self, program: &str, args: &[&std::ffi::OsStr]) -> AppResult<()> {
+        let mut command = tokio::process::Command::new(program);
+        command
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let child = command.spawn().map_err(|e| {
+            AppError::with_source(
+                ErrorKind::ExternalService,
+                format!("Failed to start {program}"),
+                e,
+            )
+        })?;
+        let output = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .map_err(|_| {
+                AppError::new(
+                    ErrorKind::ExternalService,
+                    format!("{program} timed out after {}s", self.timeout.as_secs()),
+                )
+            })?
+            .map_err(|e| {
+                AppError::with_source(ErrorKind::ExternalService, format!("{program} failed"), e)
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let detail: String = stderr.trim().chars().take(300).collect();
+            return Err(AppError::new(
+                ErrorKind::ExternalService,
+                format!("{program} exited with {}: {detail}", output.status),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Page images written by `pdftoppm` (`page-1.png`, or `page-01.png` for
+/// longer documents), in page order.
+async fn rendered_pages(work_dir: &Path) -> AppResult<Vec<PathBuf>> {
+    let mut pages = Vec::new();
+    let mut entries = tokio::fs::read_dir(work_dir).await.map_err(|e| {
+        AppError::with_source(ErrorKind::Storage, "Failed to list rendered pages", e)
+    })?;
+    while let Some(entry) = entries.next_entry().await.map_err(|e| {
+        AppError::with_source(ErrorKind::Storage, "Failed to list rendered pages", e)
+    })? {
+        let name = entry.file_name();
+        let number = name
+            .to_str()
+            .and_then(|n| n.strip_prefix("page-"))
+            .and_then(|n| n.strip_suffix(".png"))
+            .and_then(|n| n.parse::<u32>().ok());
+        if let Some(number) = number {
+            pages.push((number, entry.path()));
+        }
+    }
+    pages.sort_by_key(|(number, _)| *number);
+    Ok(pages.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Written once a document's pages are in the cache.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewManifest {
+    /// Number of page images.
+    pub pages: u32,
+    /// When the pages were rendered.
+    pub rendered_at: DateTime<Utc>,
+}
+
+/// State of a document in the preview cache.
+#[derive(Debug, Clone)]
+pub enum CachedPreview {
+    /// Pages are ready.
+    Ready(PreviewManifest),
+    /// Rendering failed; it is not retried.
+    Failed(String),
+    /// A render has been claimed and has not gone stale.
+    Pending,
+    /// Nothing is known about the document.
+    Missing,
+}
+
+/// Rendered pages on disk, one directory per content hash:
+///
+/// ```text
+/// <root>/<key>/manifest.json   pages ready
+/// <root>/<key>/page-<n>.png    page n, from 1
+/// <root>/<key>/failed.txt      rendering failed, with the reason
+/// <root>/<key>/pending         a render is queued or running
+/// ```
+#[derive(Debug, Clone)]
+pub struct PreviewCache {
+    /// Cache root directory.
+    root: PathBuf,
+}
+
+impl PreviewCache {
+    /// Creates a cache rooted at `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Looks up a document. A pending claim older than `stale_after` is
+    /// reported as missing so a crashed render is retried.
+    pub async fn lookup(&self, key: &str, stale_after: Duration) -> AppResult<CachedPreview> {
+        let dir = self.entry_dir(key)?;
+        if let Ok(manifest) = tokio::fs::read(dir.join("manifest.json")).await
+            && let Ok(manifest) = serde_json::from_slice(&manifest)
+        {
+            return Ok(CachedPreview::Ready(manifest));
+        }
+        if let Ok(reason) = tokio::fs::read_to_string(dir.join("failed.txt")).await {
+            return Ok(CachedPreview::Failed(reason));
+        }
+        let claimed_at = tokio::fs::metadata(dir.join("pending"))
+            .await
+            .and_then(|m| m.modified());
+        match claimed_at {
+            Ok(at) if at.elapsed().unwrap_or_default() < stale_after => Ok(CachedPreview::Pending),
+            _ => Ok(CachedPreview::Missing),
+        }
+    }
+
+    /// Claims the rendering of a document. Returns `false` if it is ready,
+    /// failed, or claimed by someone else less than `stale_after` ago.
+    pub async fn claim(&self, key: &str, stale_after: Duration) -> AppResult<bool> {
+        if !matches!(self.lookup(key, stale_after).await?, CachedPreview::Missing) {
+            return Ok(false);
+        }
+        let dir = self.entry_dir(key)?;
+        tokio::fs::create_dir_all(&dir).await.map_err(cache_error)?;
+        // A stale claim is taken over; a fresh one wins the race below.
+        let marker = dir.join("pending");
+        let _ = tokio::fs::remove_file(&marker).await;
+        match tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&marker)
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(false),
+            Err(e) => Err(cache_error(e)),
+        }
+    }
+
+    /// Moves rendered pages into the cache and marks the document ready.
+    pub async fn store(&self, key: &str, pages: &[PathBuf]) -> AppResult<PreviewManifest> {
+        let dir = self.entry_dir(key)?;
+        tokio::fs::create_dir_all(&dir).await.map_err(cache_error)?;
+        for (i, page) in pages.iter().enumerate() {
+            let target = dir.join(format!("page-{}.png", i + 1));
+            if tokio::fs::rename(page, &target).await.is_err() {
+                // Across file systems a rename fails; copy instead.
+                tokio::fs::copy(page, &target).await.map_err(cache_error)?;
+            }
+        }
+
+        let manifest = PreviewManifest {
+            pages: pages.len() as u32,
+            rendered_at: Utc::now(),
+        };
+        let json = serde_json::to_vec(&manifest)
+            .map_err(|e| AppError::with_source(ErrorKind::Serialization, "Invalid manifest", e))?;
+        tokio::fs::write(dir.join("manifest.json"), json)
+            .await
+            .map_err(cache_error)?;
+        let _ = tokio::fs::remove_file(dir.join("pending")).await;
+        Ok(manifest)
+    }
+
+    /// Records that a document cannot be rendered.
+    pub async fn mark_failed(&self, key: &str, reason: &str) -> AppResult<()> {
+        let dir = self.entry_dir(key)?;
+        tokio::fs::create_dir_all(&dir).await.map_err(cache_error)?;
+        tokio::fs::write(dir.join("failed.txt"), reason)
+            .await
+            .map_err(cache_error)?;
+        let _ = tokio::fs::remove_file(dir.join("pending")).await;
+        Ok(())
+    }
+
+    /// Drops a claim without recording an outcome, so the next request
+    /// claims the document again.
+    pub async fn release(&self, key: &str) -> AppResult<()> {
+        match tokio::fs::remove_file(self.entry_dir(key)?.join("pending")).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(cache_error(e)),
+        }
+    }
+
+    /// Path of page `page` (from 1) of a document.
+    pub fn page_path(&self, key: &str, page: u32) -> AppResult<PathBuf> {
+        Ok(self.entry_dir(key)?.join(format!("page-{page}.png")))
+    }
+
+    /// A fresh scratch directory for one render, inside the cache root so
+    /// pages can be moved in without copying.
+    pub async fn scratch_dir(&self) -> AppResult<PathBuf> {
+        let dir = self.root.join("tmp").join(uuid::Uuid::new_v4().to_string());
+        tokio::fs::create_dir_all(&dir).await.map_err(cache_error)?;
+        Ok(dir)
+    }
+
+    /// Directory of one document. Keys are content hashes or ids; anything
+    /// that could escape the root is refused.
+    fn entry_dir(&self, key: &str) -> AppResult<PathBuf> {
+        if key.is_empty()
+            || key == "tmp"
+            || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(AppError::validation(format!(
+                "Invalid preview cache key: {key}"
+            )));
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+fn cache_error(e: std::io::Error) -> AppError {
+    AppError::with_source(ErrorKind::Storage, "Preview cache I/O failed", e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_document_kind() {
+        assert_eq!(
+            DocumentKind::detect(Some("application/pdf"), "scan"),
+            Some(DocumentKind::Pdf)
+        );
+        assert_eq!(
+            DocumentKind::detect(None, "Report.PDF"),
+            Some(DocumentKind::Pdf)
+        );
+        assert_eq!(
+            DocumentKind::detect(Some("application/octet-stream"), "plan.xlsx"),
+            Some(DocumentKind::Office)
+        );
+        assert_eq!(DocumentKind::detect(Some("image/png"), "a.png"), None);
+        assert_eq!(DocumentKind::detect(None, "README"), None);
+    }
+
+    #[tokio::test]
+    async fn test_cache_claim_store_and_fail() {
+        let root = tempfile::tempdir().unwrap();
+        let cache = PreviewCache::new(root.path());
+        let stale = Duration::from_secs(600);
+
+        assert!(matches!(
+            cache.lookup("abc", stale).await.unwrap(),
+            CachedPreview::Missing
+        ));
+        assert!(cache.claim("abc", stale).await.unwrap());
+        assert!(!cache.claim("abc", stale).await.unwrap());
+        assert!(matches!(
+            cache.lookup("abc", stale).await.unwrap(),
+            CachedPreview::Pending
+        ));
+        // A claim that outlived its render is taken over.
+        assert!(cache.claim("abc", Duration::ZERO).await.unwrap());
+
+        let scratch = cache.scratch_dir().await.unwrap();
+        let pages: Vec<_> = (1..=2)
+            .map(|n| scratch.join(format!("page-{n}.png")))
+            .collect();
+        for page in &pages {
+            tokio::fs::write(page, b"png").await.unwrap();
+        }
+        let manifest = cache.store("abc", &pages).await.unwrap();
+        assert_eq!(manifest.pages, 2);
+        assert!(
+            matches!(cache.lookup("abc", stale).await.unwrap(), CachedPreview::Ready(m) if m.pages == 2)
+        );
+        assert!(cache.page_path("abc", 2).unwrap().exists());
+        assert!(!cache.claim("abc", stale).await.unwrap());
+
+        assert!(cache.claim("ghi", stale).await.unwrap());
+        cache.release("ghi").await.unwrap();
+        assert!(cache.claim("ghi", stale).await.unwrap());
+
+        cache.mark_failed("def", "corrupt").await.unwrap();
+        assert!(
+            matches!(cache.lookup("def", stale).await.unwrap(), CachedPreview::Failed(r) if r == "corrupt")
+        );
+    }
+
+    #[test]
+    fn test_cache_refuses_path_keys() {
+        let cache = PreviewCache::new("/tmp/previews");
+        assert!(cache.page_path("../etc", 1).is_err());
+        assert!(cache.page_path("a/b", 1).is_err());
+        assert!(cache.page_path("tmp", 1).is_err());
+        assert!(cache.page_path("0f3a-v2", 1).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rendered_pages_are_in_numeric_order() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["page-10.png", "page-02.png", "page-1.png", "source.pdf"] {
+            tokio::fs::write(dir.path().join(name), b"").await.unwrap();
+        }
+        let pages = rendered_pages(dir.path()).await.unwrap();
+        let names: Vec<_> = pages
+            .iter()
+            .map(|p| p.file_name().unwrap().to_str().unwrap().to_string())
+            .collect();
+        assert_eq!(names, ["page-1.png", "page-02.png", "page-10.png"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_renderer_kills_slow_tool() {
+        let renderer = DocumentRenderer::new(&DocumentPreviewConfig {
+            timeout_seconds: 1,
+            ..DocumentPreviewConfig::default()
+        });
+        let err = renderer.run("sleep", &["5".as_ref()]).await.unwrap_err();
+        assert_eq!(err.kind, ErrorKind::ExternalService);
+        assert!(err.message.contains("timed out"));
+    }
+
+    #[tokio::test]
+    async fn test_missing_tool_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let renderer = DocumentRenderer::new(&DocumentPreviewConfig {
+            pdf_command: "filehub-no-such-pdftoppm".to_string(),
+            ..DocumentPreviewConfig::default()
+        });
+        let source = dir.path().join("a.pdf");
+        let result = renderer
+            .render(DocumentKind::Pdf, &source, dir.path())
+            .await;
+        assert_eq!(result.unwrap_err().kind, ErrorKind::ExternalService);
+    }
+}
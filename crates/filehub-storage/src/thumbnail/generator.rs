@@ -3,11 +3,20 @@
 use std::sync::Arc;
 
 use bytes::Bytes;
+use image::DynamicImage;
+use image::codecs::avif::AvifEncoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
 
+use filehub_core::config::ThumbnailFormat;
 use filehub_core::error::{AppError, ErrorKind};
 use filehub_core::result::AppResult;
 use filehub_core::traits::storage::StorageProvider;
 
+/// AVIF encoder speed, 1 (slowest) to 10; thumbnails favour speed.
+const AVIF_SPEED: u8 = 8;
+
 /// Generates thumbnails for image files.
 #[derive(Debug, Clone)]
 pub struct ThumbnailGenerator {
@@ -15,14 +24,17 @@ pub struct ThumbnailGenerator {
     provider: Arc<dyn StorageProvider>,
     /// Thumbnail output directory path.
     output_dir: String,
+    /// Encoder quality for lossy formats.
+    quality: u8,
 }
 
 impl ThumbnailGenerator {
     /// Create a new thumbnail generator.
-    pub fn new(provider: Arc<dyn StorageProvider>, output_dir: &str) -> Self {
+    pub fn new(provider: Arc<dyn StorageProvider>, output_dir: &str, quality: u8) -> Self {
         Self {
             provider,
             output_dir: output_dir.to_string(),
+            quality,
         }
     }
 
@@ -34,7 +46,7 @@ impl ThumbnailGenerator {
         )
     }
 
-    /// Generate a thumbnail of the specified size.
+    /// Generate a thumbnail of the specified size and format.
     ///
     /// Returns the storage path of the generated thumbnail.
     pub async fn generate(
@@ -42,62 +54,248 @@ impl ThumbnailGenerator {
         source_path: &str,
         file_id: uuid::Uuid,
         size: u32,
+        format: ThumbnailFormat,
     ) -> AppResult<String> {
-        let source_bytes = self.provider.read_bytes(source_path).await?;
+        let paths = self
+            .generate_multiple(source_path, file_id, &[(size, format)])
+            .await?;
+        Ok(paths.into_iter().next().unwrap_or_default())
+    }
 
-        let thumbnail_bytes =
-            tokio::task::spawn_blocking(move || Self::resize_image(&source_bytes, size))
+    /// Generate thumbnails at multiple sizes and formats, decoding the
+    /// source once.
+    pub async fn generate_multiple(
+        &self,
+        source_path: &str,
+        file_id: uuid::Uuid,
+        variants: &[(u32, ThumbnailFormat)],
+    ) -> AppResult<Vec<String>> {
+        let source_bytes = self.provider.read_bytes(source_path).await?;
+        let owned = variants.to_vec();
+        let quality = self.quality;
+        let rendered =
+            tokio::task::spawn_blocking(move || render_thumbnails(&source_bytes, &owned, quality))
                 .await
                 .map_err(|e| {
                     AppError::with_source(ErrorKind::Internal, "Thumbnail task panicked", e)
                 })??;
 
-        let thumb_path = format!("{}/{}/{}x{}.jpg", self.output_dir, file_id, size, size);
+        let mut paths = Vec::with_capacity(variants.len());
+        for (&(size, format), data) in variants.iter().zip(rendered) {
+            let thumb_path = format!("{}/{}/{}.{}", self.output_dir, file_id, size, format);
+            self.provider.write(&thumb_path, Bytes::from(data)).await?;
+            tracing::debug!(
+                source = source_path,
+                size,
+                %format,
+                output = %thumb_path,
+                "Generated thumbnail"
+            );
+            paths.push(thumb_path);
+        }
+        Ok(paths)
+    }
+
+    /// Delete all thumbnails for a file.
+    pub async fn delete_thumbnails(&self, file_id: uuid::Uuid) -> AppResult<()> {
+        let dir = format!("{}/{}", self.output_dir, file_id);
+        self.provider.delete_dir(&dir).await
+    }
+}
 
-        self.provider.write(&thumb_path, thumbnail_bytes).await?;
+/// Renders `data` once per `(longest edge, format)` variant, in order.
+///
+/// The image is decoded once. Thumbnails keep the aspect ratio and are
+/// never larger than the source; an animated GIF yields its first frame.
+/// CPU-bound; call from a blocking task.
+pub fn render_thumbnails(
+    data: &[u8],
+    variants: &[(u32, ThumbnailFormat)],
+    quality: u8,
+) -> AppResult<Vec<Vec<u8>>> {
+    if data.is_empty() {
+        return Err(AppError::validation("Empty image data"));
+    }
+    // Decoding a GIF through the generic loader reads only its first frame
+    let img = image::load_from_memory(data)
+        .map_err(|e| AppError::validation(format!("Failed to decode image: {e}")))?;
 
-        tracing::debug!(
-            source = source_path,
-            size,
-            output = %thumb_path,
-            "Generated thumbnail"
-        );
+    variants
+        .iter()
+        .map(|&(edge, format)| encode(&fit(&img, edge), format, quality))
+        .collect()
+}
 
-        Ok(thumb_path)
+/// Scales `img` down so its longest edge is at most `edge`.
+fn fit(img: &DynamicImage, edge: u32) -> DynamicImage {
+    if img.width() <= edge && img.height() <= edge {
+        img.clone()
+    } else {
+        img.thumbnail(edge, edge)
     }
+}
+
+/// Encodes an image in `format`.
+fn encode(img: &DynamicImage, format: ThumbnailFormat, quality: u8) -> AppResult<Vec<u8>> {
+    let quality = quality.clamp(1, 100);
+    let mut buf = Vec::new();
+    let result = match format {
+        // JPEG has no alpha channel
+        ThumbnailFormat::Jpeg => DynamicImage::ImageRgb8(img.to_rgb8())
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut buf, quality)),
+        ThumbnailFormat::Webp => DynamicImage::ImageRgba8(img.to_rgba8())
+            .write_with_encoder(WebPEncoder::new_lossless(&mut buf)),
+        ThumbnailFormat::Avif => DynamicImage::ImageRgba8(img.to_rgba8()).write_with_encoder(
+            AvifEncoder::new_with_speed_quality(&mut buf, AVIF_SPEED, quality),
+        ),
+        ThumbnailFormat::Png => img.write_with_encoder(PngEncoder::new(&mut buf)),
+    };
+    result.map_err(|e| AppError::internal(format!("Failed to encode {format} thumbnail: {e}")))?;
+    Ok(buf)
+}
+
+/// Picks the thumbnail format for a request: the offered format the
+/// `Accept` header gives the highest quality value, earlier offers winning
+/// ties. Without an `Accept` header, or if it allows none of them, the
+/// first offered format is used.
+pub fn negotiate_format(accept: Option<&str>, offered: &[ThumbnailFormat]) -> ThumbnailFormat {
+    let first = offered.first().copied().unwrap_or(ThumbnailFormat::Jpeg);
+    let Some(accept) = accept.filter(|a| !a.trim().is_empty()) else {
+        return first;
+    };
+
+    let ranges: Vec<(String, f32)> = accept
+        .split(',')
+        .filter_map(|entry| {
+            let mut params = entry.split(';');
+            let range = params.next()?.trim().to_ascii_lowercase();
+            let q = params
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (!range.is_empty()).then_some((range, q))
+        })
+        .collect();
+
+    // The most specific matching range decides a format's quality value
+    let quality_of = |format: ThumbnailFormat| -> f32 {
+        let mime = format.mime_type();
+        let find = |pattern: &str| ranges.iter().find(|(r, _)| r == pattern).map(|(_, q)| *q);
+        find(mime)
+            .or_else(|| find("image/*"))
+            .or_else(|| find("*/*"))
+            .unwrap_or(0.0)
+    };
 
-    /// Resize an image to fit within the specified dimensions.
-    fn resize_image(data: &[u8], max_size: u32) -> AppResult<Bytes> {
-        // Basic implementation without the image crate dependency.
-        // In production, this would use the `image` crate for proper resizing.
-        // For now, we return the original image data to allow the system to compile
-        // and function, with proper resizing to be added when the image crate is
-        // integrated.
-        let _ = max_size;
-        if data.is_empty() {
-            return Err(AppError::validation("Empty image data"));
+    let mut best: Option<(ThumbnailFormat, f32)> = None;
+    for &format in offered {
+        let q = quality_of(format);
+        if q > 0.0 && best.is_none_or(|(_, best_q)| q > best_q) {
+            best = Some((format, q));
         }
-        Ok(Bytes::copy_from_slice(data))
     }
+    best.map_or(first, |(format, _)| format)
+}
 
-    /// Generate thumbnails at multiple sizes.
-    pub async fn generate_multiple(
-        &self,
-        source_path: &str,
-        file_id: uuid::Uuid,
-        sizes: &[u32],
-    ) -> AppResult<Vec<String>> {
-        let mut paths = Vec::new();
-        for &size in sizes {
-            let path = self.generate(source_path, file_id, size).await?;
-            paths.push(path);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::gif::GifEncoder;
+    use image::{Delay, Frame, ImageFormat, Rgba, RgbaImage};
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut buf = Vec::new();
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(
+            width,
+            height,
+            Rgba([10, 20, 30, 255]),
+        ))
+        .write_to(&mut std::io::Cursor::new(&mut buf), ImageFormat::Png)
+        .unwrap();
+        buf
+    }
+
+    fn decode(data: &[u8]) -> DynamicImage {
+        image::load_from_memory(data).unwrap()
+    }
+
+    #[test]
+    fn test_aspect_ratio_is_kept_without_upscaling() {
+        let rendered = render_thumbnails(
+            &png(400, 200),
+            &[(100, ThumbnailFormat::Png), (1000, ThumbnailFormat::Png)],
+            80,
+        )
+        .unwrap();
+        let small = decode(&rendered[0]);
+        assert_eq!((small.width(), small.height()), (100, 50));
+        let large = decode(&rendered[1]);
+        assert_eq!((large.width(), large.height()), (400, 200));
+    }
+
+    #[test]
+    fn test_each_format_is_encoded() {
+        let formats = [
+            ThumbnailFormat::Jpeg,
+            ThumbnailFormat::Webp,
+            ThumbnailFormat::Png,
+            ThumbnailFormat::Avif,
+        ];
+        let variants: Vec<_> = formats.iter().map(|&f| (16, f)).collect();
+        let rendered = render_thumbnails(&png(32, 32), &variants, 80).unwrap();
+        for (format, data) in formats.iter().zip(&rendered) {
+            let guessed = image::guess_format(data).unwrap();
+            assert_eq!(guessed.to_mime_type(), format.mime_type());
         }
-        Ok(paths)
     }
 
-    /// Delete all thumbnails for a file.
-    pub async fn delete_thumbnails(&self, file_id: uuid::Uuid) -> AppResult<()> {
-        let dir = format!("{}/{}", self.output_dir, file_id);
-        self.provider.delete_dir(&dir).await
+    #[test]
+    fn test_animated_gif_yields_first_frame() {
+        let mut gif = Vec::new();
+        {
+            let mut encoder = GifEncoder::new(&mut gif);
+            for color in [[255, 0, 0, 255], [0, 0, 255, 255]] {
+                let frame = Frame::from_parts(
+                    RgbaImage::from_pixel(8, 8, Rgba(color)),
+                    0,
+                    0,
+                    Delay::from_numer_denom_ms(100, 1),
+                );
+                encoder.encode_frame(frame).unwrap();
+            }
+        }
+        let rendered = render_thumbnails(&gif, &[(8, ThumbnailFormat::Png)], 80).unwrap();
+        let pixel = decode(&rendered[0]).to_rgba8().get_pixel(4, 4).0;
+        assert_eq!(pixel, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_format_negotiation() {
+        let offered = [ThumbnailFormat::Webp, ThumbnailFormat::Jpeg];
+        assert_eq!(negotiate_format(None, &offered), ThumbnailFormat::Webp);
+        assert_eq!(
+            negotiate_format(Some("image/jpeg"), &offered),
+            ThumbnailFormat::Jpeg
+        );
+        assert_eq!(
+            negotiate_format(Some("image/webp;q=0.5, image/*;q=0.9"), &offered),
+            ThumbnailFormat::Jpeg
+        );
+        // A browser image request
+        assert_eq!(
+            negotiate_format(
+                Some("image/avif,image/webp,image/apng,image/*,*/*;q=0.8"),
+                &[ThumbnailFormat::Avif, ThumbnailFormat::Jpeg]
+            ),
+            ThumbnailFormat::Avif
+        );
+        assert_eq!(
+            negotiate_format(Some("image/png"), &offered),
+            ThumbnailFormat::Webp
+        );
+        assert_eq!(
+            negotiate_format(Some("image/webp;q=0"), &offered),
+            ThumbnailFormat::Webp
+        );
     }
 }
@@ -1,5 +1,7 @@
 //! Thumbnail generation.
 
+pub mod document;
 pub mod generator;
 
-pub use generator::ThumbnailGenerator;
+pub use document::{CachedPreview, DocumentKind, DocumentRenderer, PreviewCache, PreviewManifest};
+pub use generator::{ThumbnailGenerator, negotiate_format, render_thumbnails};
@@ -0,0 +1,239 @@
+//! Enforcement of the per-role upload policy.
+
+use filehub_core::config::{StorageConfig, UploadPolicy};
+use filehub_core::error::{AppError, codes};
+
+use crate::sniff::sniff;
+
+/// The upload limits that apply to one role.
+#[derive(Debug, Clone)]
+pub struct UploadRules {
+    /// Role the rules were resolved for, for messages.
+    role: String,
+    /// Largest allowed file in bytes.
+    max_size_bytes: u64,
+    /// The role's policy.
+    policy: UploadPolicy,
+}
+
+impl UploadRules {
+    /// Resolves the rules of `role` from `config`.
+    pub fn for_role(config: &StorageConfig, role: &str) -> Self {
+        let policy = config.upload_policy.for_role(role);
+        let max_size_bytes = match policy.max_file_size_bytes {
+            0 => config.max_upload_size_bytes,
+            limit => limit.min(config.max_upload_size_bytes),
+        };
+        Self {
+            role: role.to_string(),
+            max_size_bytes,
+            policy,
+        }
+    }
+
+    /// Checks what the client declared, before any content is stored.
+    pub fn check_declared(
+        &self,
+        file_name: &str,
+        mime_type: Option<&str>,
+        size_bytes: u64,
+    ) -> Result<(), AppError> {
+        if size_bytes > self.max_size_bytes {
+            return Err(AppError::payload_too_large(format!(
+                "File is {} bytes; the limit for role '{}' is {} bytes",
+                size_bytes, self.role, self.max_size_bytes
+            ))
+            .with_code(codes::FILE_TOO_LARGE));
+        }
+        self.check_type(mime_type, extension(file_name), "")
+    }
+
+    /// Checks the type detected from the content starting with `head`,
+    /// which catches files renamed or declared as something they are not.
+    /// Content of no recognizable format passes; its declared type was
+    /// already checked.
+    pub fn check_content(&self, head: &[u8]) -> Result<(), AppError> {
+        match sniff(head) {
+            Some(detected) => self.check_type(
+                Some(detected.mime_type),
+                Some(detected.extension),
+                " (detected from content)",
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Applies the deny list, then the allow list, to a type known by
+    /// MIME type and/or extension.
+    fn check_type(
+        &self,
+        mime_type: Option<&str>,
+        extension: Option<&str>,
+        source: &str,
+    ) -> Result<(), AppError> {
+        let matches = |entry: &String| type_matches(entry, mime_type, extension);
+
+        if let Some(entry) = self.policy.denied_types.iter().find(|e| matches(e)) {
+            return Err(not_allowed(format!(
+                "File type '{}'{} is denied for role '{}'",
+                entry, source, self.role
+            )));
+        }
+        if !self.policy.allowed_types.is_empty() && !self.policy.allowed_types.iter().any(matches) {
+            let described = match (mime_type, extension) {
+                (Some(mime), _) => mime.to_string(),
+                (None, Some(ext)) => format!(".{ext}"),
+                (None, None) => "unknown".to_string(),
+            };
+            return Err(not_allowed(format!(
+                "File type '{}'{} is not allowed for role '{}'",
+                described, source, self.role
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Whether a policy entry (`image/png`, `image/*` or `.png`) matches a
+/// type.
+fn type_matches(entry: &str, mime_type: Option<&str>, extension: Option<&str>) -> bool {
+    if let Some(entry_ext) = entry.strip_prefix('.') {
+        return extension.is_some_and(|ext| ext.eq_ignore_ascii_case(entry_ext));
+    }
+    let Some(mime) = mime_type.map(|m| m.split(';').next().unwrap_or(m).trim()) else {
+        return false;
+    };
+    match entry.strip_suffix("/*") {
+        Some(family) => mime
+            .split_once('/')
+            .is_some_and(|(kind, _)| kind.eq_ignore_ascii_case(family)),
+        None => mime.eq_ignore_ascii_case(entry),
+    }
+}
+
+/// Extension of a file name, without the dot.
+fn extension(file_name: &str) -> Option<&str> {
+    file_name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext)
+        .filter(|ext| !ext.is_empty())
+}
+
+fn not_allowed(message: String) -> AppError {
+    AppError::unsupported_media_type(message).with_code(codes::FILE_TYPE_NOT_ALLOWED)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use filehub_core::config::UploadPolicyOverride;
+    use filehub_core::error::ErrorKind;
+
+    /// Start of a Windows PE executable.
+    const EXE: &[u8] = b"MZ\x90\x00\x03\x00\x00\x00\x04\x00\x00\x00\xff\xff\x00\x00";
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    fn config(default: UploadPolicy) -> StorageConfig {
+        let mut config: StorageConfig = serde_json::from_str("{}").unwrap();
+        config.max_upload_size_bytes = 1000;
+        config.upload_policy.default = default;
+        config
+    }
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_size_limit_is_the_smaller_of_policy_and_global() {
+        let mut config = config(UploadPolicy {
+            max_file_size_bytes: 100,
+            ..Default::default()
+        });
+        config.upload_policy.roles.insert(
+            "admin".to_string(),
+            UploadPolicyOverride {
+                max_file_size_bytes: Some(0),
+                ..Default::default()
+            },
+        );
+
+        let viewer = UploadRules::for_role(&config, "viewer");
+        assert!(viewer.check_declared("a.txt", None, 100).is_ok());
+        let err = viewer.check_declared("a.txt", None, 101).unwrap_err();
+        assert_eq!(err.kind, ErrorKind::PayloadTooLarge);
+        assert_eq!(err.code, codes::FILE_TOO_LARGE);
+
+        let admin = UploadRules::for_role(&config, "admin");
+        assert!(admin.check_declared("a.txt", None, 1000).is_ok());
+        assert!(admin.check_declared("a.txt", None, 1001).is_err());
+    }
+
+    #[test]
+    fn test_declared_type_against_lists() {
+        let config = config(UploadPolicy {
+            allowed_types: strings(&["image/*", ".dwg"]),
+            denied_types: strings(&["image/svg+xml"]),
+            ..Default::default()
+        });
+        let rules = UploadRules::for_role(&config, "creator");
+
+        assert!(rules.check_declared("a.png", Some("image/png"), 1).is_ok());
+        assert!(rules.check_declared("plan.DWG", None, 1).is_ok());
+        let err = rules
+            .check_declared("a.svg", Some("image/svg+xml"), 1)
+            .unwrap_err();
+        assert_eq!(err.kind, ErrorKind::UnsupportedMediaType);
+        assert_eq!(err.code, codes::FILE_TYPE_NOT_ALLOWED);
+        assert!(
+            rules
+                .check_declared("a.pdf", Some("application/pdf"), 1)
+                .is_err()
+        );
+        assert!(rules.check_declared("notes", None, 1).is_err());
+    }
+
+    #[test]
+    fn test_renamed_executable_is_caught_by_content() {
+        let config = config(UploadPolicy {
+            denied_types: strings(&[".exe", "application/vnd.microsoft.portable-executable"]),
+            ..Default::default()
+        });
+        let rules = UploadRules::for_role(&config, "viewer");
+
+        // Named and declared as an image, so the declared check passes...
+        assert!(
+            rules
+                .check_declared("cat.png", Some("image/png"), 16)
+                .is_ok()
+        );
+        // ...but the content gives it away.
+        let err = rules.check_content(EXE).unwrap_err();
+        assert_eq!(err.code, codes::FILE_TYPE_NOT_ALLOWED);
+        assert!(
+            err.message.contains("detected from content"),
+            "{}",
+            err.message
+        );
+
+        assert!(rules.check_content(PNG).is_ok());
+        assert!(rules.check_content(b"just text").is_ok());
+    }
+
+    #[test]
+    fn test_allow_list_applies_to_detected_type() {
+        let config = config(UploadPolicy {
+            allowed_types: strings(&["application/pdf", ".pdf"]),
+            ..Default::default()
+        });
+        let rules = UploadRules::for_role(&config, "viewer");
+        assert!(
+            rules
+                .check_declared("report.pdf", Some("application/pdf"), 16)
+                .is_ok()
+        );
+        assert!(rules.check_content(PNG).is_err());
+        assert!(rules.check_content(b"%PDF-1.7\n").is_ok());
+    }
+}
@@ -0,0 +1,61 @@
+//! Audit sink appending events to a JSON Lines file.
+
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use filehub_core::error::AppError;
+use filehub_entity::audit::AuditEvent;
+use filehub_service::session::AuditSink;
+
+/// Appends one JSON object per line. The file is reopened for every event,
+/// so external log rotation needs no signal.
+#[derive(Debug)]
+pub struct FileAuditSink {
+    /// Sink name
+    name: String,
+    /// File path
+    path: PathBuf,
+    /// Serializes appends so concurrent jobs never interleave lines
+    lock: Mutex<()>,
+}
+
+impl FileAuditSink {
+    /// Create a sink appending to `path`
+    pub fn new(name: &str, path: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            path: PathBuf::from(path),
+            lock: Mutex::new(()),
+        }
+    }
+}
+
+#[async_trait]
+impl AuditSink for FileAuditSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn write(&self, event: &AuditEvent) -> Result<(), AppError> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+
+        let _guard = self.lock.lock().await;
+        if let Some(parent) = self.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(&line).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+//! External audit sinks, built from `[[audit.sinks]]`.
+
+pub mod file;
+pub mod syslog;
+pub mod webhook;
+
+use std::sync::Arc;
+
+use filehub_core::config::{AuditConfig, AuditSinkConfig};
+use filehub_core::error::AppError;
+use filehub_http::HttpClient;
+use filehub_service::session::AuditSink;
+
+pub use file::FileAuditSink;
+pub use syslog::SyslogAuditSink;
+pub use webhook::WebhookAuditSink;
+
+/// Build every sink configured in `config`; webhooks post through `http`.
+pub fn build_sinks(
+    config: &AuditConfig,
+    http: &HttpClient,
+) -> Result<Vec<Arc<dyn AuditSink>>, AppError> {
+    config
+        .sinks
+        .iter()
+        .map(|sink| -> Result<Arc<dyn AuditSink>, AppError> {
+            Ok(match sink {
+                AuditSinkConfig::Webhook {
+                    name,
+                    url,
+                    headers,
+                    timeout_seconds,
+                } => Arc::new(WebhookAuditSink::new(
+                    http,
+                    name,
+                    url,
+                    headers,
+                    *timeout_seconds,
+                )?),
+                AuditSinkConfig::Syslog {
+                    name,
+                    address,
+                    facility,
+                } => Arc::new(SyslogAuditSink::new(name, address, *facility)),
+                AuditSinkConfig::File { name, path } => Arc::new(FileAuditSink::new(name, path)),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,78 @@
+//! Audit sink sending RFC 5424 syslog messages over UDP.
+
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use async_trait::async_trait;
+use tokio::net::UdpSocket;
+
+use filehub_core::error::AppError;
+use filehub_entity::audit::AuditEvent;
+use filehub_service::session::AuditSink;
+
+/// Syslog severity of audit messages ("informational")
+const SEVERITY_INFO: u8 = 6;
+
+/// Sends each event as one syslog datagram whose message is the event JSON
+#[derive(Debug)]
+pub struct SyslogAuditSink {
+    /// Sink name
+    name: String,
+    /// Collector address, `host:port`, resolved on each send
+    address: String,
+    /// Syslog facility number
+    facility: u8,
+}
+
+impl SyslogAuditSink {
+    /// Create a sink sending to the collector at `address`
+    pub fn new(name: &str, address: &str, facility: u8) -> Self {
+        Self {
+            name: name.to_string(),
+            address: address.to_string(),
+            facility,
+        }
+    }
+
+    /// Format `event` as an RFC 5424 message without structured data
+    fn format(&self, event: &AuditEvent) -> Result<String, AppError> {
+        let json = serde_json::to_string(event)?;
+        Ok(format!(
+            "<{}>1 {} - filehub - audit - {}",
+            u16::from(self.facility) * 8 + u16::from(SEVERITY_INFO),
+            event
+                .timestamp
+                .to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            json
+        ))
+    }
+}
+
+#[async_trait]
+impl AuditSink for SyslogAuditSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn write(&self, event: &AuditEvent) -> Result<(), AppError> {
+        let message = self.format(event)?;
+        let target = tokio::net::lookup_host(&self.address)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to resolve {}: {}", self.address, e)))?
+            .next()
+            .ok_or_else(|| AppError::internal(format!("{} did not resolve", self.address)))?;
+        let local: SocketAddr = if target.is_ipv4() {
+            (Ipv4Addr::UNSPECIFIED, 0).into()
+        } else {
+            (Ipv6Addr::UNSPECIFIED, 0).into()
+        };
+
+        let socket = UdpSocket::bind(local)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to open syslog socket: {}", e)))?;
+        socket
+            .send_to(message.as_bytes(), target)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to send syslog message: {}", e)))?;
+        Ok(())
+    }
+}
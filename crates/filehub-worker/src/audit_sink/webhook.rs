@@ -0,0 +1,90 @@
+//! Audit sink posting events to an HTTP endpoint.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use filehub_core::error::AppError;
+use filehub_entity::audit::AuditEvent;
+use filehub_http::{HttpClient, RequestOptions};
+use filehub_service::session::AuditSink;
+
+/// POSTs each event as a JSON body; any non-2xx response is a failure
+#[derive(Debug)]
+pub struct WebhookAuditSink {
+    /// Sink name
+    name: String,
+    /// Endpoint URL
+    url: String,
+    /// Configured request headers
+    headers: HeaderMap,
+    /// Shared HTTP client, with the sink's timeout
+    client: HttpClient,
+}
+
+impl WebhookAuditSink {
+    /// Create a sink posting to `url` through `client`
+    pub fn new(
+        client: &HttpClient,
+        name: &str,
+        url: &str,
+        headers: &HashMap<String, String>,
+        timeout_seconds: u64,
+    ) -> Result<Self, AppError> {
+        let mut header_map = HeaderMap::new();
+        for (key, value) in headers {
+            let key = HeaderName::from_bytes(key.as_bytes()).map_err(|e| {
+                AppError::configuration(format!(
+                    "Invalid header '{}' of audit sink '{}': {}",
+                    key, name, e
+                ))
+            })?;
+            let value = HeaderValue::from_str(value).map_err(|e| {
+                AppError::configuration(format!(
+                    "Invalid value of header '{}' of audit sink '{}': {}",
+                    key, name, e
+                ))
+            })?;
+            header_map.insert(key, value);
+        }
+
+        let client = client.with_options(RequestOptions {
+            timeout: Duration::from_secs(timeout_seconds),
+            ..client.options()
+        });
+
+        Ok(Self {
+            name: name.to_string(),
+            url: url.to_string(),
+            headers: header_map,
+            client,
+        })
+    }
+}
+
+#[async_trait]
+impl AuditSink for WebhookAuditSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn write(&self, event: &AuditEvent) -> Result<(), AppError> {
+        let response = self
+            .client
+            .post(&self.url)
+            .headers(self.headers.clone())
+            .json(event)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(AppError::internal(format!(
+                "Audit webhook returned {}",
+                status
+            )));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+//! API usage rollup job — folds daily usage of past months into monthly
+//! totals.
+//!
+//! Quotas only need the daily counters of the current month; older days
+//! are kept as one row per user and month for usage history.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::Value;
+
+use filehub_database::repositories::api_usage::ApiUsageRepository;
+use filehub_entity::job::model::Job;
+use filehub_entity::user::UsagePeriod;
+
+use crate::executor::{JobExecutionError, JobHandler};
+
+/// Handles the `api_usage_rollup` job
+#[derive(Debug)]
+pub struct ApiUsageRollupJobHandler {
+    /// API usage repository
+    usage_repo: Arc<ApiUsageRepository>,
+}
+
+impl ApiUsageRollupJobHandler {
+    /// Create a new API usage rollup job handler
+    pub fn new(usage_repo: Arc<ApiUsageRepository>) -> Self {
+        Self { usage_repo }
+    }
+}
+
+#[async_trait]
+impl JobHandler for ApiUsageRollupJobHandler {
+    fn job_type(&self) -> &str {
+        "api_usage_rollup"
+    }
+
+    async fn execute(&self, _job: &Job) -> Result<Option<Value>, JobExecutionError> {
+        let month_start = UsagePeriod::Month.start(Utc::now().date_naive());
+        let months = self
+            .usage_repo
+            .roll_up_before(month_start)
+            .await
+            .map_err(|e| JobExecutionError::Transient(format!("API usage rollup failed: {}", e)))?;
+
+        if months > 0 {
+            tracing::info!("Rolled up {} monthly API usage totals", months);
+        }
+        Ok(Some(serde_json::json!({
+            "task": "api_usage_rollup",
+            "months": months,
+        })))
+    }
+}
@@ -0,0 +1,323 @@
+//! Audit log retention job: archives old audit entries and deletes them.
+//!
+//! Entries past the retention period are written in batches to gzipped
+//! NDJSON archives in the audit event schema. Each archive is read back
+//! and its size, checksum and entries checked before the entries are
+//! deleted; a batch that fails any step stays in the database and is
+//! retried on the next run. Entries about a file or folder under a legal
+//! hold are never archived.
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{Duration, Utc};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use filehub_core::config::{AuditArchiveConfig, AuditRetentionConfig};
+use filehub_database::repositories::audit::AuditLogRepository;
+use filehub_entity::audit::{AuditEvent, AuditLogEntry, CreateAuditArchive};
+use filehub_entity::job::model::Job;
+use filehub_storage::manager::StorageManager;
+
+use crate::executor::{JobExecutionError, JobHandler};
+
+/// Most archives written per run, so one run cannot hold the worker for
+/// long after retention is first enabled on a large log.
+const MAX_ARCHIVES_PER_RUN: usize = 20;
+
+/// An encoded archive, before it is written.
+struct Encoded {
+    /// Gzipped NDJSON.
+    bytes: Bytes,
+    /// Hex SHA-256 of `bytes`.
+    sha256: String,
+}
+
+/// Handles the `audit_retention` job
+#[derive(Debug)]
+pub struct AuditRetentionJobHandler {
+    /// Audit log repository
+    audit_repo: Arc<AuditLogRepository>,
+    /// Storage providers, for archives kept in storage
+    storage_manager: Arc<StorageManager>,
+    /// Retention settings
+    config: AuditRetentionConfig,
+}
+
+impl AuditRetentionJobHandler {
+    /// Create a new audit retention job handler
+    pub fn new(
+        audit_repo: Arc<AuditLogRepository>,
+        storage_manager: Arc<StorageManager>,
+        config: AuditRetentionConfig,
+    ) -> Self {
+        Self {
+            audit_repo,
+            storage_manager,
+            config,
+        }
+    }
+
+    /// Archive one batch of entries and delete them; returns the path of
+    /// the archive
+    async fn archive(&self, entries: &[AuditLogEntry]) -> Result<String, JobExecutionError> {
+        let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+            return Err(JobExecutionError::Permanent(
+                "Empty audit archive".to_string(),
+            ));
+        };
+        let name = format!(
+            "{}/audit-{}-{}-{}.ndjson.gz",
+            first.created_at.format("%Y/%m"),
+            first.created_at.format("%Y%m%dT%H%M%SZ"),
+            last.created_at.format("%Y%m%dT%H%M%SZ"),
+            &Uuid::new_v4().simple().to_string()[..8]
+        );
+
+        let events: Vec<AuditEvent> = entries.iter().map(AuditEvent::from).collect();
+        let encoded = tokio::task::spawn_blocking(move || encode(&events))
+            .await
+            .map_err(|e| {
+                JobExecutionError::Transient(format!("Audit archive task failed: {}", e))
+            })??;
+
+        let (storage_id, path) = match &self.config.archive {
+            AuditArchiveConfig::Storage { storage_id, prefix } => {
+                let storage_id = match storage_id {
+                    Some(id) => *id,
+                    None => {
+                        self.storage_manager
+                            .get_default()
+                            .await
+                            .map_err(|e| {
+                                JobExecutionError::Transient(format!(
+                                    "No storage for audit archives: {}",
+                                    e
+                                ))
+                            })?
+                            .0
+                    }
+                };
+                (
+                    Some(storage_id),
+                    format!("{}/{}", prefix.trim_matches('/'), name),
+                )
+            }
+            AuditArchiveConfig::Directory { path } => (
+                None,
+                PathBuf::from(path)
+                    .join(&name)
+                    .to_string_lossy()
+                    .into_owned(),
+            ),
+        };
+
+        self.write(storage_id, &path, encoded.bytes.clone()).await?;
+        let stored = self.read(storage_id, &path).await?;
+        let ids: Vec<Uuid> = entries.iter().map(|entry| entry.id).collect();
+        let verified = {
+            let ids = ids.clone();
+            let expected = encoded.sha256.clone();
+            tokio::task::spawn_blocking(move || verify(&stored, &expected, &ids))
+                .await
+                .map_err(|e| {
+                    JobExecutionError::Transient(format!("Audit archive task failed: {}", e))
+                })?
+        };
+        if let Err(reason) = verified {
+            self.remove(storage_id, &path).await;
+            return Err(JobExecutionError::Transient(format!(
+                "Audit archive {} failed verification: {}",
+                path, reason
+            )));
+        }
+
+        let archive = CreateAuditArchive {
+            storage_id,
+            path: path.clone(),
+            row_count: entries.len() as i64,
+            size_bytes: encoded.bytes.len() as i64,
+            sha256: encoded.sha256,
+            first_entry_at: first.created_at,
+            last_entry_at: last.created_at,
+        };
+        if let Err(e) = self.audit_repo.record_archive(&archive, &ids).await {
+            // The entries are still in the database; the next run archives
+            // them again, so this copy is only a duplicate.
+            self.remove(storage_id, &path).await;
+            return Err(JobExecutionError::Transient(format!(
+                "Failed to delete archived audit entries: {}",
+                e
+            )));
+        }
+        Ok(path)
+    }
+
+    /// Write an archive
+    async fn write(
+        &self,
+        storage_id: Option<Uuid>,
+        path: &str,
+        data: Bytes,
+    ) -> Result<(), JobExecutionError> {
+        let result = match storage_id {
+            Some(storage_id) => self
+                .storage_manager
+                .write(&storage_id, path, data)
+                .await
+                .map_err(|e| e.to_string()),
+            None => async {
+                if let Some(parent) = std::path::Path::new(path).parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                tokio::fs::write(path, &data).await
+            }
+            .await
+            .map_err(|e| e.to_string()),
+        };
+        result.map_err(|e| {
+            JobExecutionError::Transient(format!("Failed to write audit archive {}: {}", path, e))
+        })
+    }
+
+    /// Read an archive back
+    async fn read(&self, storage_id: Option<Uuid>, path: &str) -> Result<Bytes, JobExecutionError> {
+        let result = match storage_id {
+            Some(storage_id) => self
+                .storage_manager
+                .read(&storage_id, path)
+                .await
+                .map_err(|e| e.to_string()),
+            None => tokio::fs::read(path)
+                .await
+                .map(Bytes::from)
+                .map_err(|e| e.to_string()),
+        };
+        result.map_err(|e| {
+            JobExecutionError::Transient(format!(
+                "Failed to read back audit archive {}: {}",
+                path, e
+            ))
+        })
+    }
+
+    /// Remove an archive that will not be recorded
+    async fn remove(&self, storage_id: Option<Uuid>, path: &str) {
+        let result = match storage_id {
+            Some(storage_id) => self
+                .storage_manager
+                .delete(&storage_id, path)
+                .await
+                .map_err(|e| e.to_string()),
+            None => tokio::fs::remove_file(path)
+                .await
+                .map_err(|e| e.to_string()),
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to remove unrecorded audit archive {}: {}", path, e);
+        }
+    }
+}
+
+/// Gzip `events` as NDJSON
+fn encode(events: &[AuditEvent]) -> Result<Encoded, JobExecutionError> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    for event in events {
+        serde_json::to_writer(&mut encoder, event).map_err(|e| {
+            JobExecutionError::Permanent(format!("Failed to encode audit entry: {}", e))
+        })?;
+        encoder.write_all(b"\n").map_err(|e| {
+            JobExecutionError::Permanent(format!("Failed to compress audit archive: {}", e))
+        })?;
+    }
+    let bytes = encoder.finish().map_err(|e| {
+        JobExecutionError::Permanent(format!("Failed to compress audit archive: {}", e))
+    })?;
+    Ok(Encoded {
+        sha256: format!("{:x}", Sha256::digest(&bytes)),
+        bytes: Bytes::from(bytes),
+    })
+}
+
+/// Check that a read-back archive is the one written and holds exactly
+/// the entries `ids`, in order
+fn verify(stored: &[u8], sha256: &str, ids: &[Uuid]) -> Result<(), String> {
+    let actual = format!("{:x}", Sha256::digest(stored));
+    if actual != sha256 {
+        return Err(format!("checksum {} does not match {}", actual, sha256));
+    }
+
+    let mut count = 0;
+    for line in BufReader::new(GzDecoder::new(stored)).lines() {
+        let line = line.map_err(|e| format!("unreadable: {}", e))?;
+        let event: AuditEvent =
+            serde_json::from_str(&line).map_err(|e| format!("line {}: {}", count + 1, e))?;
+        if ids.get(count) != Some(&event.id) {
+            return Err(format!(
+                "line {} holds unexpected entry {}",
+                count + 1,
+                event.id
+            ));
+        }
+        count += 1;
+    }
+    if count != ids.len() {
+        return Err(format!("{} entries instead of {}", count, ids.len()));
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl JobHandler for AuditRetentionJobHandler {
+    fn job_type(&self) -> &str {
+        "audit_retention"
+    }
+
+    async fn execute(&self, _job: &Job) -> Result<Option<Value>, JobExecutionError> {
+        if !self.config.enabled {
+            return Ok(Some(serde_json::json!({
+                "task": "audit_retention",
+                "skipped": true,
+            })));
+        }
+
+        let before = Utc::now() - Duration::days(self.config.retain_days as i64);
+        let batch_size = self.config.batch_size.max(1) as usize;
+        let mut archives = Vec::new();
+        let mut archived = 0;
+        while archives.len() < MAX_ARCHIVES_PER_RUN {
+            let entries = self
+                .audit_repo
+                .find_archivable(before, batch_size as i64)
+                .await
+                .map_err(|e| {
+                    JobExecutionError::Transient(format!("Audit retention failed: {}", e))
+                })?;
+            if entries.is_empty() {
+                break;
+            }
+
+            let path = self.archive(&entries).await?;
+            tracing::info!("Archived {} audit entries to {}", entries.len(), path);
+            archived += entries.len();
+            archives.push(path);
+            if entries.len() < batch_size {
+                break;
+            }
+        }
+
+        Ok(Some(serde_json::json!({
+            "task": "audit_retention",
+            "archived": archived,
+            "archives": archives,
+        })))
+    }
+}
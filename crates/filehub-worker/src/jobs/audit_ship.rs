@@ -0,0 +1,167 @@
+//! Audit event shipping job handler.
+//!
+//! Each `audit_ship` job delivers one event to one external sink. Failures
+//! are retried up to the job's `max_attempts`; when a delivery runs out of
+//! attempts the admins are notified, at most once per sink per alert
+//! interval. The database copy of the event is unaffected either way.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use filehub_database::repositories::notification::NotificationRepository;
+use filehub_entity::audit::AuditEvent;
+use filehub_entity::job::model::Job;
+use filehub_entity::job::payload::JobPayload;
+use filehub_entity::notification::{BroadcastTarget, NotificationCategory};
+use filehub_entity::user::UserRole;
+use filehub_service::session::AuditSink;
+use filehub_service::session::audit_sink::AUDIT_SHIP_JOB_TYPE;
+
+use crate::executor::{JobExecutionError, JobHandler};
+
+/// Delivers queued audit events to external sinks
+#[derive(Debug)]
+pub struct AuditShipJobHandler {
+    /// Configured sinks by name
+    sinks: HashMap<String, Arc<dyn AuditSink>>,
+    /// Notification repository, for failure alerts
+    notification_repo: Arc<NotificationRepository>,
+    /// Minimum time between two alerts about the same sink
+    alert_interval: Duration,
+    /// When each sink was last alerted about
+    last_alerts: Mutex<HashMap<String, Instant>>,
+}
+
+impl AuditShipJobHandler {
+    /// Create a handler delivering to `sinks`
+    pub fn new(
+        sinks: Vec<Arc<dyn AuditSink>>,
+        notification_repo: Arc<NotificationRepository>,
+        alert_interval_minutes: u64,
+    ) -> Self {
+        Self {
+            sinks: sinks
+                .into_iter()
+                .map(|sink| (sink.name().to_string(), sink))
+                .collect(),
+            notification_repo,
+            alert_interval: Duration::from_secs(alert_interval_minutes * 60),
+            last_alerts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Log a delivery that ran out of attempts and notify the admins,
+    /// unless they were notified about this sink recently
+    async fn alert(&self, sink: &str, event: &AuditEvent, error: &str) {
+        tracing::error!(
+            sink = %sink,
+            audit_id = %event.id,
+            action = %event.action,
+            "Audit event could not be shipped: {}",
+            error
+        );
+
+        {
+            let mut last_alerts = self
+                .last_alerts
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let now = Instant::now();
+            if last_alerts
+                .get(sink)
+                .is_some_and(|last| now.duration_since(*last) < self.alert_interval)
+            {
+                return;
+            }
+            last_alerts.insert(sink.to_string(), now);
+        }
+
+        let admins = match self
+            .notification_repo
+            .find_broadcast_audience(&BroadcastTarget::Role(UserRole::Admin))
+            .await
+        {
+            Ok(admins) => admins,
+            Err(e) => {
+                tracing::error!("Failed to find admins to alert: {}", e);
+                return;
+            }
+        };
+        let message = format!(
+            "Audit events could not be delivered to sink '{}': {}. They remain in the audit log.",
+            sink, error
+        );
+        let payload = serde_json::json!({
+            "sink": sink,
+            "audit_id": event.id,
+            "action": event.action,
+            "error": error,
+        });
+        for admin_id in admins {
+            if let Err(e) = self
+                .notification_repo
+                .create(
+                    admin_id,
+                    NotificationCategory::System.as_str(),
+                    "audit_sink_failed",
+                    "Audit shipping failed",
+                    &message,
+                    Some(&payload),
+                    Some("high"),
+                    None,
+                    Some("audit_log"),
+                    Some(event.id),
+                )
+                .await
+            {
+                tracing::error!("Failed to alert admin {}: {}", admin_id, e);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl JobHandler for AuditShipJobHandler {
+    fn job_type(&self) -> &str {
+        AUDIT_SHIP_JOB_TYPE
+    }
+
+    async fn execute(&self, job: &Job) -> Result<Option<Value>, JobExecutionError> {
+        let payload: JobPayload = serde_json::from_value(job.payload.clone()).map_err(|e| {
+            JobExecutionError::Permanent(format!("Invalid audit ship payload: {}", e))
+        })?;
+        let JobPayload::AuditShip { sink, event } = payload else {
+            return Err(JobExecutionError::Permanent(
+                "Not an audit ship payload".to_string(),
+            ));
+        };
+        let Some(target) = self.sinks.get(&sink) else {
+            return Err(JobExecutionError::Permanent(format!(
+                "Audit sink '{}' is not configured",
+                sink
+            )));
+        };
+
+        match target.write(&event).await {
+            Ok(()) => Ok(Some(
+                serde_json::json!({ "sink": sink, "audit_id": event.id }),
+            )),
+            // The runner gives up on transient failures at this point too.
+            Err(e) if job.attempts.unwrap_or(0) + 1 >= job.max_attempts.unwrap_or(0) => {
+                self.alert(&sink, &event, &e.message).await;
+                Err(JobExecutionError::Permanent(format!(
+                    "Shipping to '{}' failed: {}",
+                    sink, e
+                )))
+            }
+            Err(e) => Err(JobExecutionError::Transient(format!(
+                "Shipping to '{}' failed: {}",
+                sink, e
+            ))),
+        }
+    }
+}
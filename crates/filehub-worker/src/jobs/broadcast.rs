@@ -0,0 +1,86 @@
+//! Scheduled admin broadcast job handler.
+//!
+//! A broadcast scheduled for later is stored unsent with an
+//! `admin_broadcast` job due at its send time. The job marks it sent and
+//! hands it to the realtime engine for delivery to connected users; users
+//! who connect later pick it up while it is pending.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use serde_json::Value;
+
+use filehub_database::repositories::notification::NotificationRepository;
+use filehub_entity::job::model::Job;
+use filehub_entity::job::payload::JobPayload;
+use filehub_service::notification::BroadcastSink;
+use filehub_service::notification::broadcast::ADMIN_BROADCAST_JOB_TYPE;
+
+use crate::executor::{JobExecutionError, JobHandler};
+
+/// Sends scheduled admin broadcasts
+#[derive(Debug)]
+pub struct BroadcastJobHandler {
+    /// Notification repository, which stores broadcasts
+    notification_repo: Arc<NotificationRepository>,
+    /// Live delivery to connected users
+    sink: Arc<dyn BroadcastSink>,
+    /// How long a sent broadcast stays pending for users who connect
+    pending: Duration,
+}
+
+impl BroadcastJobHandler {
+    /// Create a handler keeping broadcasts pending for `pending_minutes`
+    pub fn new(
+        notification_repo: Arc<NotificationRepository>,
+        sink: Arc<dyn BroadcastSink>,
+        pending_minutes: u64,
+    ) -> Self {
+        Self {
+            notification_repo,
+            sink,
+            pending: Duration::minutes(pending_minutes as i64),
+        }
+    }
+}
+
+#[async_trait]
+impl JobHandler for BroadcastJobHandler {
+    fn job_type(&self) -> &str {
+        ADMIN_BROADCAST_JOB_TYPE
+    }
+
+    async fn execute(&self, job: &Job) -> Result<Option<Value>, JobExecutionError> {
+        let payload: JobPayload = serde_json::from_value(job.payload.clone()).map_err(|e| {
+            JobExecutionError::Permanent(format!("Invalid broadcast payload: {}", e))
+        })?;
+        let JobPayload::AdminBroadcast { broadcast_id } = payload else {
+            return Err(JobExecutionError::Permanent(
+                "Not an admin broadcast payload".to_string(),
+            ));
+        };
+
+        let sent = self
+            .notification_repo
+            .mark_broadcast_sent(broadcast_id, Utc::now() + self.pending)
+            .await
+            .map_err(|e| {
+                JobExecutionError::Transient(format!("Failed to send broadcast: {}", e))
+            })?;
+        // Already sent, or deleted since it was scheduled.
+        let Some(broadcast) = sent else {
+            return Ok(Some(
+                serde_json::json!({ "delivered": 0, "reason": "not_pending" }),
+            ));
+        };
+
+        let delivered = self.sink.deliver(&broadcast).await;
+        tracing::info!(
+            "Scheduled broadcast {} sent to {} connected users",
+            broadcast_id,
+            delivered
+        );
+        Ok(Some(serde_json::json!({ "delivered": delivered })))
+    }
+}
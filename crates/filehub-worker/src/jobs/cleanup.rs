@@ -11,6 +11,7 @@ use tracing;
 use filehub_database::repositories::file::FileRepository;
 use filehub_database::repositories::session::SessionRepository;
 use filehub_entity::job::model::Job;
+use filehub_storage::manager::StorageManager;
 
 use crate::executor::{JobExecutionError, JobHandler};
 
@@ -21,8 +22,12 @@ pub struct CleanupJobHandler {
     session_repo: Arc<SessionRepository>,
     /// File repository
     file_repo: Arc<FileRepository>,
+    /// Storage manager, which holds the chunks of upload sessions
+    storage: Arc<StorageManager>,
     /// Data root directory
     data_root: PathBuf,
+    /// Versions kept per file
+    max_versions_per_file: u32,
 }
 
 impl CleanupJobHandler {
@@ -30,12 +35,16 @@ impl CleanupJobHandler {
     pub fn new(
         session_repo: Arc<SessionRepository>,
         file_repo: Arc<FileRepository>,
+        storage: Arc<StorageManager>,
         data_root: PathBuf,
+        max_versions_per_file: u32,
     ) -> Self {
         Self {
             session_repo,
             file_repo,
+            storage,
             data_root,
+            max_versions_per_file,
         }
     }
 
@@ -57,7 +66,9 @@ impl CleanupJobHandler {
         }))
     }
 
-    /// Clean up expired chunked uploads
+    /// Clean up chunked upload sessions idle past their expiry, deleting
+    /// their chunks, or the unfinalized object of a direct upload, from
+    /// the storage they were written to
     async fn cleanup_chunks(&self) -> Result<Value, JobExecutionError> {
         tracing::info!("Running chunk cleanup");
 
@@ -68,11 +79,14 @@ impl CleanupJobHandler {
 
         let mut cleaned = 0;
         for upload in &expired {
-            let temp_path = PathBuf::from(&upload.temp_path);
-            if temp_path.exists() {
-                if let Err(e) = tokio::fs::remove_dir_all(&temp_path).await {
-                    tracing::warn!("Failed to remove temp dir for upload {}: {}", upload.id, e);
-                }
+            let removed = match self.storage.get(&upload.storage_id).await {
+                // A direct upload's temp path is the object itself
+                Ok(provider) if upload.direct => provider.delete(&upload.temp_path).await,
+                Ok(provider) => provider.delete_dir(&upload.temp_path).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = removed {
+                tracing::warn!("Failed to remove chunks of upload {}: {}", upload.id, e);
             }
             if let Err(e) = self.file_repo.delete_upload(upload.id).await {
                 tracing::warn!("Failed to delete upload record {}: {}", upload.id, e);
@@ -136,10 +150,9 @@ impl CleanupJobHandler {
     async fn cleanup_versions(&self) -> Result<Value, JobExecutionError> {
         tracing::info!("Running version cleanup");
 
-        let max_versions_per_file = 10;
         let count = self
             .file_repo
-            .delete_old_versions(max_versions_per_file)
+            .delete_old_versions(self.max_versions_per_file as i64)
             .await
             .map_err(|e| JobExecutionError::Transient(format!("Version cleanup failed: {}", e)))?;
 
@@ -148,7 +161,7 @@ impl CleanupJobHandler {
         Ok(serde_json::json!({
             "task": "version_cleanup",
             "versions_removed": count,
-            "max_versions_per_file": max_versions_per_file,
+            "max_versions_per_file": self.max_versions_per_file,
         }))
     }
 }
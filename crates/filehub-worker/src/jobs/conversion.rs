@@ -1,4 +1,8 @@
 //! CAD conversion job handler.
+//!
+//! A conversion job names a configured profile, or gets the default one;
+//! the handler resolves it to concrete options before converting, and
+//! keeps each profile's output apart so it can be cached per profile.
 
 use std::sync::Arc;
 
@@ -7,6 +11,7 @@ use serde_json::Value;
 use tracing;
 use uuid::Uuid;
 
+use filehub_core::config::{ConversionConfig, ConversionProfile};
 use filehub_core::error::AppError;
 use filehub_entity::job::model::Job;
 
@@ -15,13 +20,16 @@ use crate::executor::{JobExecutionError, JobHandler};
 /// Trait for conversion execution — decouples from plugin-cad-converter
 #[async_trait]
 pub trait ConversionService: Send + Sync + std::fmt::Debug {
-    /// Execute a conversion job
+    /// Execute a conversion job with the options of `profile`
+    #[allow(clippy::too_many_arguments)]
     async fn convert(
         &self,
         file_id: Uuid,
         file_name: &str,
         source_path: &str,
         targets: &[String],
+        profile: &ConversionProfile,
+        primary_name: Option<&str>,
         output_dir: &str,
         job_id: &str,
     ) -> Result<Value, AppError>;
@@ -32,12 +40,14 @@ pub trait ConversionService: Send + Sync + std::fmt::Debug {
 pub struct CadConversionJobHandler {
     /// Conversion service
     converter: Arc<dyn ConversionService>,
+    /// Conversion settings, with the profiles
+    config: ConversionConfig,
 }
 
 impl CadConversionJobHandler {
     /// Create a new CAD conversion job handler
-    pub fn new(converter: Arc<dyn ConversionService>) -> Self {
-        Self { converter }
+    pub fn new(converter: Arc<dyn ConversionService>, config: ConversionConfig) -> Self {
+        Self { converter, config }
     }
 }
 
@@ -83,33 +93,48 @@ impl JobHandler for CadConversionJobHandler {
             .and_then(|v| serde_json::from_value(v.clone()).ok())
             .unwrap_or_default();
 
+        let (profile_name, profile) = self
+            .config
+            .profile(job.payload.get("profile").and_then(|v| v.as_str()))
+            .map_err(|e| JobExecutionError::Permanent(format!("Cannot convert: {}", e)))?;
+
+        let primary_name = job.payload.get("primary_name").and_then(|v| v.as_str());
+
         let output_dir = job
             .payload
             .get("output_dir")
             .and_then(|v| v.as_str())
             .unwrap_or("./data/cache/conversions");
+        let output_dir = format!("{}/{}", output_dir.trim_end_matches('/'), profile_name);
 
         let job_id = job.id.to_string();
 
         tracing::info!(
-            "Starting CAD conversion: file='{}', targets={:?}",
+            "Starting CAD conversion: file='{}', targets={:?}, profile='{}'",
             file_name,
-            targets
+            targets,
+            profile_name
         );
 
-        let result = self
+        let mut result = self
             .converter
             .convert(
                 file_id,
                 file_name,
                 source_path,
                 &targets,
-                output_dir,
+                profile,
+                primary_name,
+                &output_dir,
                 &job_id,
             )
             .await
             .map_err(|e| JobExecutionError::Transient(format!("Conversion failed: {}", e)))?;
 
+        if let Some(result) = result.as_object_mut() {
+            result.insert("profile".to_string(), Value::from(profile_name));
+        }
+
         tracing::info!("CAD conversion completed for file '{}'", file_name);
 
         Ok(Some(result))
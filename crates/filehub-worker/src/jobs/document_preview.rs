@@ -0,0 +1,79 @@
+//! Document page preview rendering job handler.
+//!
+//! The first preview request for a PDF or office document queues a
+//! `document_preview` job; the pages are rendered here with external tools
+//! and cached by content hash. A failure is recorded in the cache and the
+//! document is reported as having no preview.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tokio::sync::Semaphore;
+
+use filehub_core::config::DocumentPreviewConfig;
+use filehub_core::error::ErrorKind;
+use filehub_entity::job::model::Job;
+use filehub_entity::job::payload::JobPayload;
+use filehub_service::file::PreviewService;
+use filehub_service::file::preview::DOCUMENT_PREVIEW_JOB_TYPE;
+
+use crate::executor::{JobExecutionError, JobHandler};
+
+/// Renders page previews of documents
+#[derive(Debug)]
+pub struct DocumentPreviewJobHandler {
+    /// Preview service, which owns rendering and the page cache
+    preview: Arc<PreviewService>,
+    /// Bounds concurrent renders; each one runs an external process
+    permits: Arc<Semaphore>,
+}
+
+impl DocumentPreviewJobHandler {
+    /// Create a handler rendering at most `config.concurrency` documents at once
+    pub fn new(preview: Arc<PreviewService>, config: &DocumentPreviewConfig) -> Self {
+        Self {
+            preview,
+            permits: Arc::new(Semaphore::new(config.concurrency.max(1))),
+        }
+    }
+}
+
+#[async_trait]
+impl JobHandler for DocumentPreviewJobHandler {
+    fn job_type(&self) -> &str {
+        DOCUMENT_PREVIEW_JOB_TYPE
+    }
+
+    async fn execute(&self, job: &Job) -> Result<Option<Value>, JobExecutionError> {
+        let payload: JobPayload = serde_json::from_value(job.payload.clone()).map_err(|e| {
+            JobExecutionError::Permanent(format!("Invalid document preview payload: {}", e))
+        })?;
+        let JobPayload::DocumentPreview { file_id } = payload else {
+            return Err(JobExecutionError::Permanent(
+                "Not a document preview payload".to_string(),
+            ));
+        };
+
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|e| JobExecutionError::Transient(format!("Semaphore closed: {}", e)))?;
+
+        match self.preview.render_document(file_id).await {
+            Ok(pages) => Ok(Some(serde_json::json!({ "pages": pages }))),
+            // Deleted before the job ran.
+            Err(e) if e.kind == ErrorKind::NotFound => Ok(Some(
+                serde_json::json!({ "pages": 0, "reason": "file_not_found" }),
+            )),
+            Err(e) => {
+                tracing::warn!("Document preview failed for file {}: {}", file_id, e);
+                Err(JobExecutionError::Permanent(format!(
+                    "Document preview failed: {}",
+                    e
+                )))
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Notification email delivery over SMTP.
+//!
+//! Emails are queued by the notification dispatcher as
+//! `notification_email` jobs, so a failing SMTP server only delays them:
+//! transient errors are retried up to the job's `max_attempts`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde_json::Value;
+
+use filehub_core::config::EmailConfig;
+use filehub_core::error::AppError;
+use filehub_database::repositories::user::UserRepository;
+use filehub_entity::job::model::Job;
+use filehub_entity::notification::Notification;
+use filehub_service::notification::email::{self, EMAIL_JOB_TYPE};
+
+use crate::executor::{JobExecutionError, JobHandler};
+
+/// Sends queued notification emails
+pub struct NotificationEmailHandler {
+    /// User repository, for recipient addresses
+    user_repo: Arc<UserRepository>,
+    /// SMTP transport
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    /// Sender mailbox
+    from: Mailbox,
+    /// Notification settings page linked from every email
+    preferences_url: String,
+}
+
+impl std::fmt::Debug for NotificationEmailHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NotificationEmailHandler")
+            .field("from", &self.from.to_string())
+            .finish()
+    }
+}
+
+impl NotificationEmailHandler {
+    /// Create a handler sending through the SMTP server in `config`
+    pub fn new(config: &EmailConfig, user_repo: Arc<UserRepository>) -> Result<Self, AppError> {
+        let host = config.smtp_host.as_str();
+        let builder = match config.security.as_str() {
+            "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(host),
+            "none" => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                host,
+            )),
+            _ => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host),
+        }
+        .map_err(|e| AppError::configuration(format!("Invalid SMTP settings: {}", e)))?;
+
+        let mut builder = builder
+            .port(config.smtp_port)
+            .timeout(Some(Duration::from_secs(config.timeout_seconds)));
+        if let (Some(username), Some(password)) = (&config.smtp_username, &config.smtp_password) {
+            builder = builder.credentials(Credentials::new(
+                username.clone(),
+                password.expose().to_string(),
+            ));
+        }
+
+        let address = config
+            .from_address
+            .parse()
+            .map_err(|e| AppError::configuration(format!("Invalid email.from_address: {}", e)))?;
+
+        Ok(Self {
+            user_repo,
+            transport: builder.build(),
+            from: Mailbox::new(Some(config.from_name.clone()), address),
+            preferences_url: config.preferences_url.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl JobHandler for NotificationEmailHandler {
+    fn job_type(&self) -> &str {
+        EMAIL_JOB_TYPE
+    }
+
+    async fn execute(&self, job: &Job) -> Result<Option<Value>, JobExecutionError> {
+        let notification: Notification =
+            serde_json::from_value(job.payload.get("notification").cloned().unwrap_or_default())
+                .map_err(|e| {
+                    JobExecutionError::Permanent(format!("Invalid email job payload: {}", e))
+                })?;
+
+        let user = self
+            .user_repo
+            .find_by_id(notification.user_id)
+            .await
+            .map_err(|e| JobExecutionError::Transient(format!("User lookup failed: {}", e)))?;
+        let Some(address) = user.and_then(|u| u.email).filter(|e| !e.is_empty()) else {
+            tracing::debug!(
+                "Skipping notification email: user {} has no email address",
+                notification.user_id
+            );
+            return Ok(Some(
+                serde_json::json!({ "sent": false, "reason": "no_address" }),
+            ));
+        };
+        let to: Mailbox = address.parse().map_err(|e| {
+            JobExecutionError::Permanent(format!("Invalid recipient '{}': {}", address, e))
+        })?;
+
+        let rendered = email::render(&notification, &self.preferences_url);
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(rendered.subject)
+            .multipart(MultiPart::alternative_plain_html(
+                rendered.text,
+                rendered.html,
+            ))
+            .map_err(|e| JobExecutionError::Permanent(format!("Failed to build email: {}", e)))?;
+
+        self.transport.send(message).await.map_err(|e| {
+            if e.is_permanent() {
+                JobExecutionError::Permanent(format!("SMTP rejected email: {}", e))
+            } else {
+                JobExecutionError::Transient(format!("SMTP delivery failed: {}", e))
+            }
+        })?;
+
+        tracing::debug!(
+            "Sent notification email '{}' to user {}",
+            notification.event_type,
+            notification.user_id
+        );
+        Ok(Some(serde_json::json!({ "sent": true })))
+    }
+}
@@ -0,0 +1,228 @@
+//! File expiry job: warns owners of files about to expire and deletes
+//! expired ones.
+//!
+//! A file is deleted with all its versions. A retention policy on its
+//! folder wins over the expiry: the file stays until the policy allows
+//! the delete, and goes on the first run after that.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use serde_json::Value;
+use tracing;
+
+use filehub_database::repositories::audit::AuditLogRepository;
+use filehub_database::repositories::file::FileRepository;
+use filehub_database::repositories::notification::NotificationRepository;
+use filehub_database::repositories::retention::RetentionPolicyRepository;
+use filehub_entity::audit::model::CreateAuditLogEntry;
+use filehub_entity::file::File;
+use filehub_entity::folder::EffectiveRetention;
+use filehub_entity::job::model::Job;
+use filehub_entity::notification::NotificationCategory;
+use filehub_service::notification::DigestSink;
+
+use crate::executor::{JobExecutionError, JobHandler};
+
+/// Files handled per run, for each of warning and deleting.
+const EXPIRY_BATCH: i64 = 500;
+
+/// Handles the `file_expiry` job
+#[derive(Debug)]
+pub struct FileExpiryJobHandler {
+    /// File repository
+    file_repo: Arc<FileRepository>,
+    /// Retention policies, which postpone expiry
+    retention_repo: Arc<RetentionPolicyRepository>,
+    /// Notification repository, for owner notices
+    notification_repo: Arc<NotificationRepository>,
+    /// Audit log of deleted files
+    audit_repo: Arc<AuditLogRepository>,
+    /// How long before expiry owners are warned
+    notice: Duration,
+    /// Optional live delivery of notices
+    sink: Option<Arc<dyn DigestSink>>,
+}
+
+impl FileExpiryJobHandler {
+    /// Create a new file expiry job handler
+    pub fn new(
+        file_repo: Arc<FileRepository>,
+        retention_repo: Arc<RetentionPolicyRepository>,
+        notification_repo: Arc<NotificationRepository>,
+        audit_repo: Arc<AuditLogRepository>,
+        notice_hours: u64,
+        sink: Option<Arc<dyn DigestSink>>,
+    ) -> Self {
+        Self {
+            file_repo,
+            retention_repo,
+            notification_repo,
+            audit_repo,
+            notice: Duration::hours(notice_hours as i64),
+            sink,
+        }
+    }
+
+    /// Warn the owners of files expiring within the notice period
+    async fn warn_owners(&self) -> Result<usize, JobExecutionError> {
+        let expiring = self
+            .file_repo
+            .find_expiring(Utc::now() + self.notice, EXPIRY_BATCH)
+            .await
+            .map_err(|e| JobExecutionError::Transient(format!("Expiry notice failed: {}", e)))?;
+
+        let mut warned = 0;
+        for file in &expiring {
+            let Some(expires_at) = file.expires_at else {
+                continue;
+            };
+            match self
+                .file_repo
+                .mark_expiry_notified(file.id, expires_at)
+                .await
+            {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    tracing::warn!("Failed to record expiry notice of file {}: {}", file.id, e);
+                    continue;
+                }
+            }
+            let message = format!(
+                "'{}' will be deleted on {}. Extend or clear its expiry to keep it.",
+                file.name,
+                expires_at.format("%Y-%m-%d %H:%M UTC")
+            );
+            self.notify(file, "file_expiring", "File expiring soon", &message)
+                .await;
+            warned += 1;
+        }
+        Ok(warned)
+    }
+
+    /// Delete expired files that no retention policy keeps
+    async fn delete_expired(&self) -> Result<(usize, usize), JobExecutionError> {
+        let expired = self
+            .file_repo
+            .find_expired(EXPIRY_BATCH)
+            .await
+            .map_err(|e| JobExecutionError::Transient(format!("File expiry failed: {}", e)))?;
+
+        let now = Utc::now();
+        let (mut deleted, mut retained) = (0, 0);
+        for file in &expired {
+            let policies = match self.retention_repo.find_inherited(file.folder_id).await {
+                Ok(policies) => policies,
+                Err(e) => {
+                    tracing::warn!("Failed to check retention of file {}: {}", file.id, e);
+                    continue;
+                }
+            };
+            if let Some(block) = EffectiveRetention::combine(&policies).blocks(file.created_at, now)
+            {
+                tracing::debug!("Expired file {} is retained: {:?}", file.id, block);
+                retained += 1;
+                continue;
+            }
+
+            match self.file_repo.delete_expired(file.id).await {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    tracing::warn!("Failed to delete expired file {}: {}", file.id, e);
+                    continue;
+                }
+            }
+            self.audit(file).await;
+            let message = format!("'{}' reached its expiry and was deleted.", file.name);
+            self.notify(file, "file_expired", "File expired", &message)
+                .await;
+            deleted += 1;
+        }
+        Ok((deleted, retained))
+    }
+
+    /// Notify a file's owner
+    async fn notify(&self, file: &File, event_type: &str, title: &str, message: &str) {
+        let payload = serde_json::json!({
+            "file_id": file.id,
+            "file_name": file.name,
+            "folder_id": file.folder_id,
+            "expires_at": file.expires_at,
+        });
+        match self
+            .notification_repo
+            .create(
+                file.owner_id,
+                NotificationCategory::File.as_str(),
+                event_type,
+                title,
+                message,
+                Some(&payload),
+                Some("normal"),
+                None,
+                Some("file"),
+                Some(file.id),
+            )
+            .await
+        {
+            Ok(notification) => {
+                if let Some(sink) = &self.sink {
+                    sink.deliver(&notification).await;
+                }
+            }
+            Err(e) => tracing::error!("Failed to notify owner of file {}: {}", file.id, e),
+        }
+    }
+
+    /// Record the delete, with the owner as actor
+    async fn audit(&self, file: &File) {
+        let entry = CreateAuditLogEntry {
+            actor_id: Some(file.owner_id),
+            impersonator_id: None,
+            action: "file.expired".to_string(),
+            target_type: "file".to_string(),
+            target_id: Some(file.id),
+            details: Some(serde_json::json!({
+                "name": file.name,
+                "folder_id": file.folder_id,
+                "expires_at": file.expires_at,
+            })),
+            ip_address: None,
+            user_agent: None,
+        };
+        if let Err(e) = self.audit_repo.create(&entry).await {
+            tracing::warn!("Failed to audit expiry of file {}: {}", file.id, e);
+        }
+    }
+}
+
+#[async_trait]
+impl JobHandler for FileExpiryJobHandler {
+    fn job_type(&self) -> &str {
+        "file_expiry"
+    }
+
+    async fn execute(&self, _job: &Job) -> Result<Option<Value>, JobExecutionError> {
+        let warned = self.warn_owners().await?;
+        let (deleted, retained) = self.delete_expired().await?;
+
+        if warned + deleted + retained > 0 {
+            tracing::info!(
+                "File expiry: warned {} owners, deleted {} files, {} retained",
+                warned,
+                deleted,
+                retained
+            );
+        }
+
+        Ok(Some(serde_json::json!({
+            "task": "file_expiry",
+            "warned": warned,
+            "deleted": deleted,
+            "retained": retained,
+        })))
+    }
+}
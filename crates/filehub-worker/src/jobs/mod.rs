@@ -1,17 +1,41 @@
 //! Built-in job handler implementations.
 
+pub mod api_usage;
+pub mod audit_retention;
+pub mod audit_ship;
+pub mod broadcast;
 pub mod cleanup;
 pub mod conversion;
+pub mod document_preview;
+#[cfg(feature = "email")]
+pub mod email;
+pub mod expiry;
 pub mod license;
 pub mod maintenance;
 pub mod notification;
+pub mod outbox_cleanup;
 pub mod presence;
 pub mod report;
+pub mod share;
+pub mod storage_migration;
+pub mod thumbnail;
 
+pub use api_usage::ApiUsageRollupJobHandler;
+pub use audit_retention::AuditRetentionJobHandler;
+pub use audit_ship::AuditShipJobHandler;
+pub use broadcast::BroadcastJobHandler;
 pub use cleanup::CleanupJobHandler;
 pub use conversion::CadConversionJobHandler;
+pub use document_preview::DocumentPreviewJobHandler;
+#[cfg(feature = "email")]
+pub use email::NotificationEmailHandler;
+pub use expiry::FileExpiryJobHandler;
 pub use license::LicenseJobHandler;
 pub use maintenance::MaintenanceJobHandler;
 pub use notification::NotificationJobHandler;
+pub use outbox_cleanup::OutboxCleanupJobHandler;
 pub use presence::PresenceJobHandler;
 pub use report::ReportJobHandler;
+pub use share::ShareAccessJobHandler;
+pub use storage_migration::StorageMigrationJobHandler;
+pub use thumbnail::ThumbnailJobHandler;
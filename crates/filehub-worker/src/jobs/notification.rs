@@ -9,6 +9,8 @@ use tracing;
 
 use filehub_database::repositories::notification::NotificationRepository;
 use filehub_entity::job::model::Job;
+use filehub_service::notification::DigestSink;
+use filehub_service::notification::service::NotificationService;
 
 use crate::executor::{JobExecutionError, JobHandler};
 
@@ -21,6 +23,10 @@ pub struct NotificationJobHandler {
     cleanup_after_days: i64,
     /// Max stored per user
     max_stored_per_user: i64,
+    /// Notification service, for digest flushing
+    notification_service: Arc<NotificationService>,
+    /// Optional live delivery of flushed digests
+    digest_sink: Option<Arc<dyn DigestSink>>,
 }
 
 impl NotificationJobHandler {
@@ -29,14 +35,42 @@ impl NotificationJobHandler {
         notification_repo: Arc<NotificationRepository>,
         cleanup_after_days: i64,
         max_stored_per_user: i64,
+        notification_service: Arc<NotificationService>,
+        digest_sink: Option<Arc<dyn DigestSink>>,
     ) -> Self {
         Self {
             notification_repo,
             cleanup_after_days,
             max_stored_per_user,
+            notification_service,
+            digest_sink,
         }
     }
 
+    /// Deliver every digest whose window has closed
+    async fn flush_digests(&self) -> Result<Value, JobExecutionError> {
+        let delivered = self
+            .notification_service
+            .flush_due_digests()
+            .await
+            .map_err(|e| JobExecutionError::Transient(format!("Digest flush failed: {}", e)))?;
+
+        if let Some(sink) = &self.digest_sink {
+            for notification in &delivered {
+                sink.deliver(notification).await;
+            }
+        }
+
+        if !delivered.is_empty() {
+            tracing::info!("Digest flush: delivered {} digests", delivered.len());
+        }
+
+        Ok(serde_json::json!({
+            "task": "digest_flush",
+            "delivered": delivered.len(),
+        }))
+    }
+
     /// Clean up old notifications
     async fn cleanup_notifications(&self) -> Result<Value, JobExecutionError> {
         tracing::info!(
@@ -116,6 +150,7 @@ impl JobHandler for NotificationJobHandler {
         let result = match task {
             "notification_cleanup" => self.cleanup_notifications().await?,
             "broadcast_cleanup" => self.cleanup_broadcasts().await?,
+            "digest_flush" => self.flush_digests().await?,
             _ => {
                 return Err(JobExecutionError::Permanent(format!(
                     "Unknown notification task: '{}'",
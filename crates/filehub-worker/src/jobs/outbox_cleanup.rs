@@ -0,0 +1,59 @@
+//! Outbox cleanup job — deletes delivered domain events.
+//!
+//! Delivered rows are kept for `worker.outbox.retain_delivered_hours`, so
+//! recent deliveries and discarded events can be inspected, then deleted.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use serde_json::Value;
+
+use filehub_core::config::OutboxConfig;
+use filehub_database::repositories::outbox::OutboxRepository;
+use filehub_entity::job::model::Job;
+
+use crate::executor::{JobExecutionError, JobHandler};
+
+/// Handles the `outbox_cleanup` job
+#[derive(Debug)]
+pub struct OutboxCleanupJobHandler {
+    /// Outbox repository
+    outbox_repo: Arc<OutboxRepository>,
+    /// Outbox settings
+    config: OutboxConfig,
+}
+
+impl OutboxCleanupJobHandler {
+    /// Create a new outbox cleanup job handler
+    pub fn new(outbox_repo: Arc<OutboxRepository>, config: OutboxConfig) -> Self {
+        Self {
+            outbox_repo,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl JobHandler for OutboxCleanupJobHandler {
+    fn job_type(&self) -> &str {
+        "outbox_cleanup"
+    }
+
+    async fn execute(&self, _job: &Job) -> Result<Option<Value>, JobExecutionError> {
+        let before = Utc::now() - Duration::hours(self.config.retain_delivered_hours as i64);
+        let deleted = self
+            .outbox_repo
+            .delete_delivered_before(before)
+            .await
+            .map_err(|e| JobExecutionError::Transient(format!("Outbox cleanup failed: {}", e)))?;
+
+        if deleted > 0 {
+            tracing::info!("Deleted {} delivered outbox events", deleted);
+        }
+        Ok(Some(serde_json::json!({
+            "task": "outbox_cleanup",
+            "deleted": deleted,
+        })))
+    }
+}
@@ -3,13 +3,15 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde_json::Value;
 use tracing;
 
+use filehub_core::config::SessionConfig;
 use filehub_database::repositories::session::SessionRepository;
 use filehub_entity::job::model::Job;
 use filehub_entity::presence::PresenceStatus;
+use filehub_service::session::IdleSessionNotifier;
 
 use crate::executor::{JobExecutionError, JobHandler};
 
@@ -123,35 +125,52 @@ impl JobHandler for PresenceJobHandler {
     }
 }
 
-/// Handler for idle_session_check job type
+/// Reason recorded on sessions ended by the idle check.
+const IDLE_TERMINATION_REASON: &str = "Idle timeout";
+
+/// Handler for idle_session_check job type.
+///
+/// Idle sessions go through three stages: after the idle timeout they are
+/// marked idle; `warning_lead_minutes` before the grace window ends they are
+/// warned over WebSocket and marked away; when the grace window ends, and
+/// the warning is at least `warning_lead_minutes` old, they are terminated.
+/// Activity at any point clears the warning (see
+/// `SessionRepository::update_last_activity`).
 #[derive(Debug)]
 pub struct IdleSessionCheckHandler {
     /// Session repository
     session_repo: Arc<SessionRepository>,
+    /// Optional realtime notifier for warnings and disconnects
+    notifier: Option<Arc<dyn IdleSessionNotifier>>,
     /// Idle timeout in minutes
     idle_timeout_minutes: i64,
+    /// Grace window after the idle timeout, in minutes
+    grace_minutes: i64,
+    /// Warning lead time before termination, in minutes
+    warning_lead_minutes: i64,
 }
 
 impl IdleSessionCheckHandler {
     /// Create a new idle session check handler
-    pub fn new(session_repo: Arc<SessionRepository>, idle_timeout_minutes: i64) -> Self {
+    pub fn new(
+        session_repo: Arc<SessionRepository>,
+        notifier: Option<Arc<dyn IdleSessionNotifier>>,
+        config: &SessionConfig,
+    ) -> Self {
         Self {
             session_repo,
-            idle_timeout_minutes,
+            notifier,
+            idle_timeout_minutes: config.idle_timeout_minutes as i64,
+            grace_minutes: config.idle_grace_minutes as i64,
+            warning_lead_minutes: config
+                .idle_warning_lead_minutes
+                .min(config.idle_grace_minutes) as i64,
         }
     }
-}
 
-#[async_trait]
-impl JobHandler for IdleSessionCheckHandler {
-    fn job_type(&self) -> &str {
-        "idle_session_check"
-    }
-
-    async fn execute(&self, _job: &Job) -> Result<Option<Value>, JobExecutionError> {
-        tracing::debug!("Running idle session check");
-
-        let idle_cutoff = Utc::now() - Duration::minutes(self.idle_timeout_minutes);
+    /// Mark sessions past the idle timeout as idle.
+    async fn mark_idle(&self, now: DateTime<Utc>) -> Result<usize, JobExecutionError> {
+        let idle_cutoff = now - Duration::minutes(self.idle_timeout_minutes);
 
         let idle_sessions = self
             .session_repo
@@ -162,8 +181,11 @@ impl JobHandler for IdleSessionCheckHandler {
             })?;
 
         let mut marked_idle = 0;
-
         for session in &idle_sessions {
+            // Already warned sessions stay away.
+            if session.idle_warned_at.is_some() {
+                continue;
+            }
             if let Err(e) = self
                 .session_repo
                 .update_presence(session.id, &PresenceStatus::Idle)
@@ -174,20 +196,91 @@ impl JobHandler for IdleSessionCheckHandler {
             }
             marked_idle += 1;
         }
+        Ok(marked_idle)
+    }
+
+    /// Warn sessions whose termination is `warning_lead_minutes` away.
+    async fn warn(&self, now: DateTime<Utc>) -> Result<usize, JobExecutionError> {
+        let total = self.idle_timeout_minutes + self.grace_minutes;
+        let warn_cutoff = now - Duration::minutes(total - self.warning_lead_minutes);
+
+        let warned = self
+            .session_repo
+            .mark_idle_warned(warn_cutoff)
+            .await
+            .map_err(|e| {
+                JobExecutionError::Transient(format!("Failed to warn idle sessions: {}", e))
+            })?;
+
+        if let Some(ref notifier) = self.notifier {
+            for session in &warned {
+                // Never earlier than a full lead time from now.
+                let logout_at = (session.last_activity + Duration::minutes(total))
+                    .max(now + Duration::minutes(self.warning_lead_minutes));
+                notifier.warn_idle(session.id, logout_at).await;
+            }
+        }
+        Ok(warned.len())
+    }
+
+    /// Terminate warned sessions that stayed idle through the grace window.
+    async fn terminate(&self, now: DateTime<Utc>) -> Result<usize, JobExecutionError> {
+        let idle_cutoff = now - Duration::minutes(self.idle_timeout_minutes + self.grace_minutes);
+        let warned_before = now - Duration::minutes(self.warning_lead_minutes);
+
+        let terminated = self
+            .session_repo
+            .terminate_idle(idle_cutoff, warned_before, IDLE_TERMINATION_REASON)
+            .await
+            .map_err(|e| {
+                JobExecutionError::Transient(format!("Failed to terminate idle sessions: {}", e))
+            })?;
+
+        if let Some(ref notifier) = self.notifier {
+            for session in &terminated {
+                notifier
+                    .idle_terminated(session.id, IDLE_TERMINATION_REASON)
+                    .await;
+            }
+        }
+        Ok(terminated.len())
+    }
+}
+
+#[async_trait]
+impl JobHandler for IdleSessionCheckHandler {
+    fn job_type(&self) -> &str {
+        "idle_session_check"
+    }
+
+    async fn execute(&self, _job: &Job) -> Result<Option<Value>, JobExecutionError> {
+        tracing::debug!("Running idle session check");
+
+        let now = Utc::now();
+        let marked_idle = self.mark_idle(now).await?;
+        let warned = self.warn(now).await?;
+        let terminated = self.terminate(now).await?;
 
-        if marked_idle > 0 {
+        if marked_idle + warned + terminated > 0 {
             tracing::info!(
-                "Idle session check: {} sessions marked as idle (timeout={}min)",
+                "Idle session check: {} marked idle, {} warned, {} terminated \
+                 (timeout={}min, grace={}min)",
                 marked_idle,
-                self.idle_timeout_minutes
+                warned,
+                terminated,
+                self.idle_timeout_minutes,
+                self.grace_minutes
             );
         }
 
         Ok(Some(serde_json::json!({
             "task": "idle_session_check",
-            "idle_sessions_found": idle_sessions.len(),
             "marked_idle": marked_idle,
+            "warned": warned,
+            "terminated": terminated,
             "idle_timeout_minutes": self.idle_timeout_minutes,
+            "idle_grace_minutes": self.grace_minutes,
+            "idle_warning_lead_minutes": self.warning_lead_minutes,
         })))
     }
 }
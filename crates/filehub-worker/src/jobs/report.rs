@@ -16,7 +16,10 @@ use filehub_entity::job::model::Job;
 
 use crate::executor::{JobExecutionError, JobHandler};
 
-/// Handles weekly report generation
+/// Handles weekly report generation.
+///
+/// Report queries tolerate replication lag, so the repositories serve them
+/// from a read replica when one is configured.
 #[derive(Debug)]
 pub struct ReportJobHandler {
     /// User repository
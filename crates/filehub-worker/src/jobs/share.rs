@@ -0,0 +1,55 @@
+//! Share link access recording job handler.
+//!
+//! Public share endpoints queue one `share_access` job per view or
+//! download instead of writing the record themselves, so analytics never
+//! slow down the response.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use filehub_database::repositories::share::ShareRepository;
+use filehub_entity::job::model::Job;
+use filehub_entity::job::payload::JobPayload;
+use filehub_service::share::access::SHARE_ACCESS_JOB_TYPE;
+
+use crate::executor::{JobExecutionError, JobHandler};
+
+/// Writes queued share link accesses
+#[derive(Debug)]
+pub struct ShareAccessJobHandler {
+    /// Share repository
+    share_repo: Arc<ShareRepository>,
+}
+
+impl ShareAccessJobHandler {
+    /// Create a new share access handler
+    pub fn new(share_repo: Arc<ShareRepository>) -> Self {
+        Self { share_repo }
+    }
+}
+
+#[async_trait]
+impl JobHandler for ShareAccessJobHandler {
+    fn job_type(&self) -> &str {
+        SHARE_ACCESS_JOB_TYPE
+    }
+
+    async fn execute(&self, job: &Job) -> Result<Option<Value>, JobExecutionError> {
+        let payload: JobPayload = serde_json::from_value(job.payload.clone()).map_err(|e| {
+            JobExecutionError::Permanent(format!("Invalid share access payload: {}", e))
+        })?;
+        let JobPayload::ShareAccess(access) = payload else {
+            return Err(JobExecutionError::Permanent(
+                "Not a share access payload".to_string(),
+            ));
+        };
+
+        self.share_repo
+            .record_access(&access)
+            .await
+            .map_err(|e| JobExecutionError::Transient(format!("Failed to record access: {}", e)))?;
+        Ok(None)
+    }
+}
@@ -0,0 +1,72 @@
+//! Storage migration job handler.
+//!
+//! Each job runs one time slice of a migration; the service queues the
+//! next slice or pass itself. When a slice keeps failing (e.g. the target
+//! is unreachable) the migration is marked failed once the job runs out of
+//! attempts, and can be resumed by an administrator.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use filehub_entity::job::model::Job;
+use filehub_entity::job::payload::JobPayload;
+use filehub_service::storage::{STORAGE_MIGRATION_JOB_TYPE, TransferService};
+
+use crate::executor::{JobExecutionError, JobHandler};
+
+/// Copies files between storage providers in the background
+#[derive(Debug)]
+pub struct StorageMigrationJobHandler {
+    /// Transfer service, which owns the migration logic
+    transfer: Arc<TransferService>,
+}
+
+impl StorageMigrationJobHandler {
+    /// Create a new storage migration handler
+    pub fn new(transfer: Arc<TransferService>) -> Self {
+        Self { transfer }
+    }
+}
+
+#[async_trait]
+impl JobHandler for StorageMigrationJobHandler {
+    fn job_type(&self) -> &str {
+        STORAGE_MIGRATION_JOB_TYPE
+    }
+
+    async fn execute(&self, job: &Job) -> Result<Option<Value>, JobExecutionError> {
+        let payload: JobPayload = serde_json::from_value(job.payload.clone()).map_err(|e| {
+            JobExecutionError::Permanent(format!("Invalid storage migration payload: {}", e))
+        })?;
+        let JobPayload::StorageMigration { migration_id } = payload else {
+            return Err(JobExecutionError::Permanent(
+                "Not a storage migration payload".to_string(),
+            ));
+        };
+
+        match self.transfer.run_migration(migration_id).await {
+            Ok(outcome) => Ok(serde_json::to_value(outcome).ok()),
+            // The runner gives up on transient failures at this point too.
+            Err(e) if job.attempts.unwrap_or(0) + 1 >= job.max_attempts.unwrap_or(0) => {
+                tracing::error!("Storage migration {} failed: {}", migration_id, e);
+                if let Err(mark) = self.transfer.fail_migration(migration_id, &e.message).await {
+                    tracing::error!(
+                        "Failed to mark storage migration {} failed: {}",
+                        migration_id,
+                        mark
+                    );
+                }
+                Err(JobExecutionError::Permanent(format!(
+                    "Storage migration failed: {}",
+                    e
+                )))
+            }
+            Err(e) => Err(JobExecutionError::Transient(format!(
+                "Storage migration slice failed: {}",
+                e
+            ))),
+        }
+    }
+}
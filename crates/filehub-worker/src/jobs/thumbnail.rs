@@ -0,0 +1,86 @@
+//! Eager thumbnail generation job handler.
+//!
+//! Uploads queue a `thumbnail_generation` job for supported types so the
+//! first preview of a new file is served from cache. A failure only marks
+//! the job failed; the preview falls back to lazy rendering.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tokio::sync::Semaphore;
+
+use filehub_core::config::ThumbnailPregenConfig;
+use filehub_core::error::ErrorKind;
+use filehub_entity::job::model::Job;
+use filehub_entity::job::payload::JobPayload;
+use filehub_service::file::PreviewService;
+use filehub_service::file::preview::THUMBNAIL_JOB_TYPE;
+
+use crate::executor::{JobExecutionError, JobHandler};
+
+/// Renders and caches thumbnails of uploaded files
+#[derive(Debug)]
+pub struct ThumbnailJobHandler {
+    /// Preview service, which owns rendering and the thumbnail cache
+    preview: Arc<PreviewService>,
+    /// Bounds concurrent rendering so a bulk upload cannot saturate the CPU
+    permits: Arc<Semaphore>,
+}
+
+impl ThumbnailJobHandler {
+    /// Create a handler rendering at most `config.concurrency` files at once
+    pub fn new(preview: Arc<PreviewService>, config: &ThumbnailPregenConfig) -> Self {
+        Self {
+            preview,
+            permits: Arc::new(Semaphore::new(config.concurrency.max(1))),
+        }
+    }
+}
+
+#[async_trait]
+impl JobHandler for ThumbnailJobHandler {
+    fn job_type(&self) -> &str {
+        THUMBNAIL_JOB_TYPE
+    }
+
+    async fn execute(&self, job: &Job) -> Result<Option<Value>, JobExecutionError> {
+        let payload: JobPayload = serde_json::from_value(job.payload.clone()).map_err(|e| {
+            JobExecutionError::Permanent(format!("Invalid thumbnail job payload: {}", e))
+        })?;
+        let JobPayload::ThumbnailGeneration {
+            file_id,
+            sizes,
+            formats,
+        } = payload
+        else {
+            return Err(JobExecutionError::Permanent(
+                "Not a thumbnail generation payload".to_string(),
+            ));
+        };
+
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|e| JobExecutionError::Transient(format!("Semaphore closed: {}", e)))?;
+
+        match self.preview.pregenerate(file_id, &sizes, &formats).await {
+            Ok(generated) => {
+                tracing::debug!("Generated {} thumbnail(s) for file {}", generated, file_id);
+                Ok(Some(serde_json::json!({ "generated": generated })))
+            }
+            // Deleted before the job ran.
+            Err(e) if e.kind == ErrorKind::NotFound => Ok(Some(
+                serde_json::json!({ "generated": 0, "reason": "file_not_found" }),
+            )),
+            Err(e) => {
+                tracing::warn!("Thumbnail generation failed for file {}: {}", file_id, e);
+                Err(JobExecutionError::Permanent(format!(
+                    "Thumbnail generation failed: {}",
+                    e
+                )))
+            }
+        }
+    }
+}
@@ -5,12 +5,17 @@
 //! - A cron scheduler for periodic maintenance tasks
 //! - A job executor that dispatches jobs to the correct handler
 //! - Built-in job implementations for cleanup, reports, and maintenance
+//! - External audit sinks fed by the audit shipping job
+//! - The outbox relay that publishes recorded domain events
 
+pub mod audit_sink;
 pub mod executor;
 pub mod jobs;
 pub mod queue;
+pub mod relay;
 pub mod runner;
 pub mod scheduler;
 
+pub use relay::OutboxRelay;
 pub use runner::WorkerRunner;
 pub use scheduler::CronScheduler;
@@ -148,6 +148,23 @@ impl JobQueue {
         Ok(())
     }
 
+    /// Return this worker's running jobs to the queue, e.g. when it is
+    /// stopped before they finish. Returns how many were requeued.
+    pub async fn requeue_running(&self) -> Result<u64, AppError> {
+        let count = self
+            .repo
+            .requeue_running(&self.worker_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to requeue jobs: {}", e)))?;
+
+        tracing::debug!(
+            "Requeued {} running jobs of worker '{}'",
+            count,
+            self.worker_id
+        );
+        Ok(count)
+    }
+
     /// Get queue statistics
     pub async fn stats(&self) -> Result<QueueStats, AppError> {
         let pending = self
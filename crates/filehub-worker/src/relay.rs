@@ -0,0 +1,124 @@
+//! Outbox relay — publishes recorded domain events on the event bus.
+//!
+//! Services record events in the outbox in the transaction of the change
+//! they describe; the relay leases undelivered rows, publishes them in
+//! the order they were recorded, and marks them delivered. A relay that
+//! stops in between leaves its rows to be leased again once the lease
+//! ends, so every event is published at least once and consumers skip
+//! IDs they have already handled.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tokio::time;
+
+use filehub_core::config::OutboxConfig;
+use filehub_core::error::AppError;
+use filehub_core::events::{DomainEvent, EventBus};
+use filehub_database::repositories::outbox::{OutboxListener, OutboxRepository};
+
+/// Relays outbox rows to the event bus
+#[derive(Debug)]
+pub struct OutboxRelay {
+    /// Outbox repository
+    outbox_repo: Arc<OutboxRepository>,
+    /// Bus the events are published on
+    bus: EventBus,
+    /// Relay settings
+    config: OutboxConfig,
+}
+
+impl OutboxRelay {
+    /// Create a new outbox relay
+    pub fn new(outbox_repo: Arc<OutboxRepository>, bus: EventBus, config: OutboxConfig) -> Self {
+        Self {
+            outbox_repo,
+            bus,
+            config,
+        }
+    }
+
+    /// Relay events until the cancel signal is received
+    pub async fn run(&self, mut cancel: watch::Receiver<bool>) {
+        let poll_interval = Duration::from_millis(self.config.poll_interval_ms.max(1));
+        let mut listener: Option<OutboxListener> = None;
+        tracing::info!(
+            "Outbox relay started with batch_size={}, poll_interval={}ms",
+            self.config.batch_size,
+            self.config.poll_interval_ms
+        );
+
+        loop {
+            if listener.is_none() {
+                match self.outbox_repo.listen().await {
+                    Ok(l) => listener = Some(l),
+                    Err(e) => tracing::debug!("Outbox relay polling only: {}", e),
+                }
+            }
+
+            loop {
+                match self.relay_batch().await {
+                    Ok(relayed) if relayed == self.config.batch_size as usize => continue,
+                    Ok(_) => break,
+                    Err(e) => {
+                        tracing::warn!("Outbox relay failed: {}", e);
+                        break;
+                    }
+                }
+            }
+
+            tokio::select! {
+                _ = cancel.changed() => {
+                    if *cancel.borrow() {
+                        break;
+                    }
+                }
+                _ = notified(&mut listener) => {}
+                _ = time::sleep(poll_interval) => {}
+            }
+        }
+
+        tracing::info!("Outbox relay shut down");
+    }
+
+    /// Publish one batch of undelivered events; returns how many were
+    /// leased
+    async fn relay_batch(&self) -> Result<usize, AppError> {
+        let lease = Duration::from_secs(self.config.lease_seconds.max(1));
+        let entries = self
+            .outbox_repo
+            .claim(i64::from(self.config.batch_size.max(1)), lease)
+            .await?;
+
+        let mut delivered = Vec::with_capacity(entries.len());
+        for entry in &entries {
+            match serde_json::from_value::<DomainEvent>(entry.event.clone()) {
+                Ok(event) => {
+                    self.bus.publish(event);
+                    delivered.push(entry.id);
+                }
+                Err(e) => {
+                    tracing::warn!("Discarding undecodable outbox event {}: {}", entry.id, e);
+                    self.outbox_repo.discard(entry.id, &e.to_string()).await?;
+                }
+            }
+        }
+        self.outbox_repo.mark_delivered(&delivered).await?;
+        Ok(entries.len())
+    }
+}
+
+/// Wait for a notification of new events; without a listener, or once it
+/// fails, only the poll interval wakes the relay.
+async fn notified(listener: &mut Option<OutboxListener>) {
+    match listener {
+        Some(l) => {
+            if let Err(e) = l.notified().await {
+                tracing::warn!("Outbox relay falling back to polling: {}", e);
+                *listener = None;
+            }
+        }
+        None => std::future::pending().await,
+    }
+}
@@ -7,7 +7,7 @@ use tokio::sync::watch;
 use tokio::time;
 use tracing;
 
-use filehub_core::config::WorkerConfig;
+use filehub_core::config::{AppConfig, WorkerConfig};
 
 use crate::executor::{JobExecutionError, JobExecutor};
 use crate::queue::JobQueue;
@@ -25,6 +25,10 @@ pub struct WorkerRunner {
     worker_id: String,
     /// Queues to poll (in priority order)
     queues: Vec<String>,
+    /// Reloaded configuration, for runtime concurrency changes
+    live_config: Option<watch::Receiver<Arc<AppConfig>>>,
+    /// How long in-flight jobs may run on after shutdown is signalled
+    drain_timeout: Duration,
 }
 
 impl WorkerRunner {
@@ -46,6 +50,8 @@ impl WorkerRunner {
                 "default".to_string(),
                 "maintenance".to_string(),
             ],
+            live_config: None,
+            drain_timeout: Duration::from_secs(30),
         }
     }
 
@@ -55,6 +61,19 @@ impl WorkerRunner {
         self
     }
 
+    /// Follow `worker.concurrency` in reloaded configuration
+    pub fn with_live_config(mut self, live_config: watch::Receiver<Arc<AppConfig>>) -> Self {
+        self.live_config = Some(live_config);
+        self
+    }
+
+    /// Set how long in-flight jobs may run on after shutdown is signalled;
+    /// jobs still running then are returned to the queue
+    pub fn with_drain_timeout(mut self, drain_timeout: Duration) -> Self {
+        self.drain_timeout = drain_timeout;
+        self
+    }
+
     /// Start the worker runner — runs until the cancel signal is received
     pub async fn run(&self, mut cancel: watch::Receiver<bool>) {
         tracing::info!(
@@ -68,10 +87,29 @@ impl WorkerRunner {
         let semaphore = Arc::new(tokio::sync::Semaphore::new(
             self.config.concurrency as usize,
         ));
+        let mut concurrency = self.config.concurrency;
+        let mut live_config = self.live_config.clone();
 
         let poll_interval = Duration::from_secs(self.config.poll_interval_seconds);
 
         loop {
+            // Picked up between polls so a reload never cancels a dequeue.
+            if let Some(rx) = &mut live_config
+                && rx.has_changed().unwrap_or(false)
+            {
+                let target = rx.borrow_and_update().worker.concurrency.max(1);
+                if target != concurrency {
+                    resize(&semaphore, concurrency, target);
+                    tracing::info!(
+                        "Worker '{}' concurrency changed from {} to {}",
+                        self.worker_id,
+                        concurrency,
+                        target
+                    );
+                    concurrency = target;
+                }
+            }
+
             tokio::select! {
                 _ = cancel.changed() => {
                     if *cancel.borrow() {
@@ -98,9 +136,45 @@ impl WorkerRunner {
             self.worker_id
         );
 
-        let max_permits = self.config.concurrency as u32;
-        let _ = tokio::time::timeout(Duration::from_secs(30), semaphore.acquire_many(max_permits))
-            .await;
+        let max_permits = concurrency as u32;
+        let deadline = time::Instant::now() + self.drain_timeout;
+        let drained = loop {
+            let remaining = deadline.saturating_duration_since(time::Instant::now());
+            if remaining.is_zero() {
+                break false;
+            }
+            match time::timeout(
+                remaining.min(Duration::from_secs(5)),
+                semaphore.acquire_many(max_permits),
+            )
+            .await
+            {
+                Ok(_) => break true,
+                Err(_) => tracing::info!(
+                    "Worker '{}' draining: {} jobs in flight, {}s left",
+                    self.worker_id,
+                    (max_permits as usize).saturating_sub(semaphore.available_permits()),
+                    deadline
+                        .saturating_duration_since(time::Instant::now())
+                        .as_secs()
+                ),
+            }
+        };
+
+        if !drained {
+            match self.queue.requeue_running().await {
+                Ok(count) => tracing::warn!(
+                    "Worker '{}' drain timed out; requeued {} running jobs",
+                    self.worker_id,
+                    count
+                ),
+                Err(e) => tracing::error!(
+                    "Worker '{}' drain timed out and requeue failed: {}",
+                    self.worker_id,
+                    e
+                ),
+            }
+        }
 
         tracing::info!("Worker '{}' shut down complete", self.worker_id);
     }
@@ -191,3 +265,21 @@ impl WorkerRunner {
         }
     }
 }
+
+/// Grows or shrinks the worker slot pool.
+///
+/// Shrinking retires permits as running jobs release them, so in-flight
+/// jobs are never interrupted.
+fn resize(semaphore: &Arc<tokio::sync::Semaphore>, current: usize, target: usize) {
+    if target > current {
+        semaphore.add_permits(target - current);
+    } else if target < current {
+        let semaphore = Arc::clone(semaphore);
+        let excess = (current - target) as u32;
+        tokio::spawn(async move {
+            if let Ok(permits) = semaphore.acquire_many(excess).await {
+                permits.forget();
+            }
+        });
+    }
+}
@@ -40,10 +40,15 @@ impl CronScheduler {
         self.register_chunk_cleanup().await?;
         self.register_temp_cleanup().await?;
         self.register_version_cleanup().await?;
+        self.register_file_expiry().await?;
+        self.register_audit_retention().await?;
+        self.register_outbox_cleanup().await?;
+        self.register_api_usage_rollup().await?;
         self.register_weekly_report().await?;
         self.register_pool_sync().await?;
         self.register_presence_reconciliation().await?;
         self.register_notification_cleanup().await?;
+        self.register_digest_flush().await?;
         self.register_idle_session_check().await?;
 
         tracing::info!("All scheduled tasks registered");
@@ -204,6 +209,136 @@ impl CronScheduler {
         Ok(())
     }
 
+    /// File expiry — every 15 minutes
+    async fn register_file_expiry(&self) -> Result<(), AppError> {
+        let queue = Arc::clone(&self.queue);
+        let job = CronJob::new_async("0 5/15 * * * *", move |_uuid, _lock| {
+            let queue = Arc::clone(&queue);
+            Box::pin(async move {
+                tracing::debug!("Scheduling file expiry job");
+                let params = JobCreateParams {
+                    job_type: "file_expiry".to_string(),
+                    queue: "maintenance".to_string(),
+                    priority: JobPriority::Normal,
+                    payload: serde_json::json!({"task": "file_expiry"}),
+                    max_attempts: 1,
+                    scheduled_at: None,
+                    created_by: None,
+                };
+                if let Err(e) = queue.enqueue(params).await {
+                    tracing::error!("Failed to enqueue file_expiry: {}", e);
+                }
+            })
+        })
+        .map_err(|e| AppError::internal(format!("Failed to create file_expiry schedule: {}", e)))?;
+
+        self.scheduler.add(job).await.map_err(|e| {
+            AppError::internal(format!("Failed to add file_expiry schedule: {}", e))
+        })?;
+
+        tracing::info!("Registered: file_expiry (every 15min)");
+        Ok(())
+    }
+
+    /// Audit log retention — daily at 2:30 AM
+    async fn register_audit_retention(&self) -> Result<(), AppError> {
+        let queue = Arc::clone(&self.queue);
+        let job = CronJob::new_async("0 30 2 * * *", move |_uuid, _lock| {
+            let queue = Arc::clone(&queue);
+            Box::pin(async move {
+                tracing::debug!("Scheduling audit retention job");
+                let params = JobCreateParams {
+                    job_type: "audit_retention".to_string(),
+                    queue: "maintenance".to_string(),
+                    priority: JobPriority::Low,
+                    payload: serde_json::json!({"task": "audit_retention"}),
+                    max_attempts: 1,
+                    scheduled_at: None,
+                    created_by: None,
+                };
+                if let Err(e) = queue.enqueue(params).await {
+                    tracing::error!("Failed to enqueue audit_retention: {}", e);
+                }
+            })
+        })
+        .map_err(|e| {
+            AppError::internal(format!("Failed to create audit_retention schedule: {}", e))
+        })?;
+
+        self.scheduler.add(job).await.map_err(|e| {
+            AppError::internal(format!("Failed to add audit_retention schedule: {}", e))
+        })?;
+
+        tracing::info!("Registered: audit_retention (daily 2:30AM)");
+        Ok(())
+    }
+
+    /// Delivered outbox events — hourly at :45
+    async fn register_outbox_cleanup(&self) -> Result<(), AppError> {
+        let queue = Arc::clone(&self.queue);
+        let job = CronJob::new_async("0 45 * * * *", move |_uuid, _lock| {
+            let queue = Arc::clone(&queue);
+            Box::pin(async move {
+                tracing::debug!("Scheduling outbox cleanup job");
+                let params = JobCreateParams {
+                    job_type: "outbox_cleanup".to_string(),
+                    queue: "maintenance".to_string(),
+                    priority: JobPriority::Low,
+                    payload: serde_json::json!({"task": "outbox_cleanup"}),
+                    max_attempts: 1,
+                    scheduled_at: None,
+                    created_by: None,
+                };
+                if let Err(e) = queue.enqueue(params).await {
+                    tracing::error!("Failed to enqueue outbox_cleanup: {}", e);
+                }
+            })
+        })
+        .map_err(|e| {
+            AppError::internal(format!("Failed to create outbox_cleanup schedule: {}", e))
+        })?;
+
+        self.scheduler.add(job).await.map_err(|e| {
+            AppError::internal(format!("Failed to add outbox_cleanup schedule: {}", e))
+        })?;
+
+        tracing::info!("Registered: outbox_cleanup (hourly at :45)");
+        Ok(())
+    }
+
+    /// API usage rollup — every day at 00:40
+    async fn register_api_usage_rollup(&self) -> Result<(), AppError> {
+        let queue = Arc::clone(&self.queue);
+        let job = CronJob::new_async("0 40 0 * * *", move |_uuid, _lock| {
+            let queue = Arc::clone(&queue);
+            Box::pin(async move {
+                tracing::debug!("Scheduling API usage rollup job");
+                let params = JobCreateParams {
+                    job_type: "api_usage_rollup".to_string(),
+                    queue: "maintenance".to_string(),
+                    priority: JobPriority::Low,
+                    payload: serde_json::json!({"task": "api_usage_rollup"}),
+                    max_attempts: 1,
+                    scheduled_at: None,
+                    created_by: None,
+                };
+                if let Err(e) = queue.enqueue(params).await {
+                    tracing::error!("Failed to enqueue api_usage_rollup: {}", e);
+                }
+            })
+        })
+        .map_err(|e| {
+            AppError::internal(format!("Failed to create api_usage_rollup schedule: {}", e))
+        })?;
+
+        self.scheduler.add(job).await.map_err(|e| {
+            AppError::internal(format!("Failed to add api_usage_rollup schedule: {}", e))
+        })?;
+
+        tracing::info!("Registered: api_usage_rollup (daily 00:40)");
+        Ok(())
+    }
+
     /// Weekly report — Monday at 8 AM
     async fn register_weekly_report(&self) -> Result<(), AppError> {
         let queue = Arc::clone(&self.queue);
@@ -308,6 +443,39 @@ impl CronScheduler {
         Ok(())
     }
 
+    /// Notification digest flush — every minute
+    async fn register_digest_flush(&self) -> Result<(), AppError> {
+        let queue = Arc::clone(&self.queue);
+        let job = CronJob::new_async("0 * * * * *", move |_uuid, _lock| {
+            let queue = Arc::clone(&queue);
+            Box::pin(async move {
+                tracing::debug!("Scheduling digest flush job");
+                let params = JobCreateParams {
+                    job_type: "notification_cleanup".to_string(),
+                    queue: "default".to_string(),
+                    priority: JobPriority::Normal,
+                    payload: serde_json::json!({"task": "digest_flush"}),
+                    max_attempts: 1,
+                    scheduled_at: None,
+                    created_by: None,
+                };
+                if let Err(e) = queue.enqueue(params).await {
+                    tracing::error!("Failed to enqueue digest_flush: {}", e);
+                }
+            })
+        })
+        .map_err(|e| {
+            AppError::internal(format!("Failed to create digest_flush schedule: {}", e))
+        })?;
+
+        self.scheduler.add(job).await.map_err(|e| {
+            AppError::internal(format!("Failed to add digest_flush schedule: {}", e))
+        })?;
+
+        tracing::info!("Registered: digest_flush (every 1min)");
+        Ok(())
+    }
+
     /// Notification cleanup — daily at 2 AM
     async fn register_notification_cleanup(&self) -> Result<(), AppError> {
         let queue = Arc::clone(&self.queue);
@@ -0,0 +1,181 @@
+//! Shared fixtures for the job tests that run against PostgreSQL.
+//!
+//! The tests connect to `DATABASE_URL` and migrate it. They are skipped
+//! when it is not set. Every fixture gets its own owner, storage and
+//! folder, so tests can share one database and run in parallel.
+
+#![allow(dead_code)]
+
+use sqlx::PgPool;
+use sqlx::postgres::PgPoolOptions;
+use tokio::sync::OnceCell;
+use uuid::Uuid;
+
+use filehub_database::repositories::file::FileRepository;
+use filehub_database::repositories::folder::FolderRepository;
+use filehub_database::repositories::storage::StorageRepository;
+use filehub_database::repositories::user::UserRepository;
+use filehub_entity::file::{CreateFile, File};
+use filehub_entity::folder::{CreateFolder, Folder};
+use filehub_entity::job::{Job, JobPriority, JobStatus};
+use filehub_entity::storage::{CreateStorage, StorageProviderType};
+use filehub_entity::user::model::CreateUser;
+use filehub_entity::user::{User, UserRole};
+
+static MIGRATED: OnceCell<()> = OnceCell::const_new();
+
+/// Connects to the test database, or returns `None` to skip the test.
+pub async fn database() -> Option<PgPool> {
+    let Some(url) = std::env::var("DATABASE_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+    else {
+        eprintln!("DATABASE_URL is not set; skipping database test");
+        return None;
+    };
+    MIGRATED
+        .get_or_init(|| async {
+            let pool = PgPoolOptions::new()
+                .max_connections(1)
+                .connect(&url)
+                .await
+                .expect("Failed to connect to the test database");
+            filehub_database::migration::run_migrations(&pool)
+                .await
+                .expect("Failed to run migrations");
+            pool.close().await;
+        })
+        .await;
+    let pool = PgPoolOptions::new()
+        .max_connections(8)
+        .connect(&url)
+        .await
+        .expect("Failed to connect to the test database");
+    Some(pool)
+}
+
+/// A folder owned by `owner` on a storage of its own.
+pub struct Fixture {
+    pub pool: PgPool,
+    pub files: FileRepository,
+    pub folders: FolderRepository,
+    pub owner: User,
+    pub storage_id: Uuid,
+    pub root: Folder,
+}
+
+impl Fixture {
+    /// Sets up a fresh owner, storage and root folder, or returns `None`
+    /// to skip the test.
+    pub async fn new() -> Option<Self> {
+        let pool = database().await?;
+        let name = format!("user-{}", Uuid::new_v4().simple());
+        let owner = UserRepository::new(pool.clone())
+            .create(&CreateUser {
+                username: name.clone(),
+                email: Some(format!("{name}@example.com")),
+                password_hash: "unused".to_string(),
+                display_name: None,
+                role: UserRole::Manager,
+                created_by: None,
+            })
+            .await
+            .expect("Failed to create user");
+        let storage = StorageRepository::new(pool.clone())
+            .create(&CreateStorage {
+                name: format!("test-{}", Uuid::new_v4()),
+                description: None,
+                provider_type: StorageProviderType::Local,
+                config: serde_json::json!({}),
+                is_default: false,
+                quota_bytes: None,
+                mount_path: None,
+                created_by: Some(owner.id),
+            })
+            .await
+            .expect("Failed to create storage");
+        let folders = FolderRepository::new(pool.clone());
+        let root = folders
+            .create(&CreateFolder {
+                storage_id: storage.id,
+                parent_id: None,
+                name: "root".to_string(),
+                path: "/".to_string(),
+                depth: 0,
+                owner_id: owner.id,
+            })
+            .await
+            .expect("Failed to create root folder");
+
+        Some(Self {
+            files: FileRepository::new(pool.clone()),
+            folders,
+            pool,
+            owner,
+            storage_id: storage.id,
+            root,
+        })
+    }
+
+    /// Creates a folder directly under the root.
+    pub async fn folder(&self, name: &str) -> Folder {
+        self.folders
+            .create(&CreateFolder {
+                storage_id: self.storage_id,
+                parent_id: Some(self.root.id),
+                name: name.to_string(),
+                path: format!("/{name}"),
+                depth: 1,
+                owner_id: self.owner.id,
+            })
+            .await
+            .expect("Failed to create folder")
+    }
+
+    /// Creates a file record under `folder_id`, owned by the fixture
+    /// owner.
+    pub async fn file(&self, folder_id: Uuid, name: &str) -> File {
+        self.files
+            .create(
+                &CreateFile {
+                    folder_id,
+                    storage_id: self.storage_id,
+                    name: name.to_string(),
+                    storage_path: format!("blobs/{}", Uuid::new_v4()),
+                    mime_type: Some("text/plain".to_string()),
+                    declared_mime_type: None,
+                    size_bytes: 1,
+                    checksum_sha256: None,
+                    metadata: None,
+                    owner_id: self.owner.id,
+                },
+                None,
+            )
+            .await
+            .expect("Failed to create file")
+    }
+}
+
+/// A running job of `job_type` with an empty payload.
+pub fn job(job_type: &str) -> Job {
+    let now = chrono::Utc::now();
+    Job {
+        id: Uuid::new_v4(),
+        job_type: job_type.to_string(),
+        queue: "default".to_string(),
+        priority: JobPriority::Normal,
+        payload: serde_json::json!({}),
+        result: None,
+        error_message: None,
+        status: JobStatus::Running,
+        attempts: Some(1),
+        max_attempts: Some(3),
+        scheduled_at: None,
+        started_at: Some(now),
+        completed_at: None,
+        created_by: None,
+        worker_id: None,
+        created_at: now,
+        updated_at: now,
+    }
+}
@@ -0,0 +1,120 @@
+//! The file expiry job, against PostgreSQL.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use filehub_database::repositories::audit::AuditLogRepository;
+use filehub_database::repositories::file::FileRepository;
+use filehub_database::repositories::notification::NotificationRepository;
+use filehub_database::repositories::retention::RetentionPolicyRepository;
+use filehub_entity::notification::Notification;
+use filehub_service::notification::DigestSink;
+use filehub_worker::executor::JobHandler;
+use filehub_worker::jobs::FileExpiryJobHandler;
+
+use common::{Fixture, job};
+
+/// Keeps every notification delivered to it.
+#[derive(Debug, Default)]
+struct RecordingSink(Mutex<Vec<Notification>>);
+
+impl RecordingSink {
+    /// Event types delivered about `file_id`.
+    fn events(&self, file_id: Uuid) -> Vec<String> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|n| n.resource_id == Some(file_id))
+            .map(|n| n.event_type.clone())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl DigestSink for RecordingSink {
+    async fn deliver(&self, notification: &Notification) {
+        self.0.lock().unwrap().push(notification.clone());
+    }
+}
+
+/// Event types stored for `user_id` about `file_id`.
+async fn stored_events(fx: &Fixture, user_id: Uuid, file_id: Uuid) -> Vec<String> {
+    sqlx::query_scalar(
+        "SELECT event_type FROM notifications WHERE user_id = $1 AND resource_id = $2 \
+         ORDER BY created_at",
+    )
+    .bind(user_id)
+    .bind(file_id)
+    .fetch_all(&fx.pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_expired_file_is_deleted_unless_retained() {
+    let Some(fx) = Fixture::new().await else {
+        return;
+    };
+    let sink = Arc::new(RecordingSink::default());
+    let retention = Arc::new(RetentionPolicyRepository::new(fx.pool.clone()));
+    let handler = FileExpiryJobHandler::new(
+        Arc::new(FileRepository::new(fx.pool.clone())),
+        Arc::clone(&retention),
+        Arc::new(NotificationRepository::new(fx.pool.clone())),
+        Arc::new(AuditLogRepository::new(fx.pool.clone())),
+        24,
+        Some(Arc::clone(&sink) as Arc<dyn DigestSink>),
+    );
+
+    let scratch = fx.file(fx.root.id, "scratch.txt").await;
+    let soon = fx.file(fx.root.id, "draft.txt").await;
+    let records = fx.folder("records").await;
+    retention
+        .upsert(records.id, 3650, false, None, fx.owner.id)
+        .await
+        .unwrap();
+    let kept = fx.file(records.id, "ledger.txt").await;
+
+    let past = Utc::now() - Duration::minutes(1);
+    fx.files.set_expiry(scratch.id, Some(past)).await.unwrap();
+    fx.files.set_expiry(kept.id, Some(past)).await.unwrap();
+    fx.files
+        .set_expiry(soon.id, Some(Utc::now() + Duration::hours(1)))
+        .await
+        .unwrap();
+
+    handler.execute(&job("file_expiry")).await.unwrap();
+
+    // The expired file is gone, and its owner is told.
+    assert!(fx.files.find_by_id(scratch.id).await.unwrap().is_none());
+    assert_eq!(
+        stored_events(&fx, fx.owner.id, scratch.id).await,
+        ["file_expired"]
+    );
+    assert_eq!(sink.events(scratch.id), ["file_expired"]);
+
+    // The owner of a file about to expire is warned once.
+    assert!(fx.files.find_by_id(soon.id).await.unwrap().is_some());
+    assert_eq!(sink.events(soon.id), ["file_expiring"]);
+
+    // Retention wins: the expired file under the policy stays.
+    let still_there = fx.files.find_by_id(kept.id).await.unwrap().unwrap();
+    assert_eq!(
+        still_there.expires_at.map(|at| at.timestamp()),
+        Some(past.timestamp())
+    );
+    assert!(sink.events(kept.id).is_empty());
+
+    handler.execute(&job("file_expiry")).await.unwrap();
+    assert_eq!(sink.events(soon.id), ["file_expiring"]);
+    assert!(fx.files.find_by_id(kept.id).await.unwrap().is_some());
+
+    // Leave nothing behind for later runs against this database.
+    fx.files.set_expiry(kept.id, None).await.unwrap();
+}
@@ -0,0 +1,339 @@
+//! Minimal clamd client speaking the `INSTREAM` command.
+//!
+//! The file is sent as a sequence of chunks, each prefixed with its length
+//! as a 4-byte big-endian integer, and terminated by a zero-length chunk.
+//! clamd answers with a single NUL-terminated line:
+//!
+//! - `stream: OK` — nothing found;
+//! - `stream: <signature> FOUND` — infected;
+//! - `<message> ERROR` — the scan failed (e.g. `StreamMaxLength` exceeded).
+//!
+//! Content is forwarded as it is read, so only one chunk is held in memory
+//! however large the file is.
+
+use std::future::Future;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::StreamExt;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use filehub_core::traits::storage::ByteStream;
+
+/// Largest chunk sent in one frame.
+const MAX_CHUNK: usize = 64 * 1024;
+
+/// Longest reply accepted from clamd.
+const MAX_REPLY: usize = 4096;
+
+/// Outcome of a completed scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanVerdict {
+    /// Nothing was found.
+    Clean,
+    /// clamd matched a signature.
+    Infected(String),
+}
+
+/// A scan that did not complete.
+#[derive(Debug, Error)]
+pub enum ScanError {
+    /// clamd could not be reached.
+    #[error("Cannot connect to clamd at {address}: {source}")]
+    Connect {
+        /// The configured address.
+        address: String,
+        /// Underlying error.
+        source: std::io::Error,
+    },
+    /// clamd stalled for longer than the timeout.
+    #[error("clamd timed out")]
+    Timeout,
+    /// The connection failed mid-scan.
+    #[error("clamd connection failed: {0}")]
+    Io(#[from] std::io::Error),
+    /// The file could not be read from storage.
+    #[error("Cannot read file for scanning: {0}")]
+    Source(std::io::Error),
+    /// clamd reported an error or answered something unexpected.
+    #[error("clamd error: {0}")]
+    Scanner(String),
+}
+
+/// Where clamd listens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ClamdAddress {
+    /// `host:port`.
+    Tcp(String),
+    /// Path of a Unix domain socket.
+    #[cfg(unix)]
+    Unix(std::path::PathBuf),
+}
+
+impl ClamdAddress {
+    fn parse(address: &str) -> Self {
+        #[cfg(unix)]
+        if let Some(path) = address.strip_prefix("unix:") {
+            return Self::Unix(path.into());
+        }
+        Self::Tcp(address.to_string())
+    }
+}
+
+/// Client for one clamd daemon.
+#[derive(Debug, Clone)]
+pub struct ClamdClient {
+    /// Configured address, for error messages.
+    display: String,
+    /// Parsed address.
+    address: ClamdAddress,
+    /// Limit on connecting, each write and waiting for the reply.
+    timeout: Duration,
+}
+
+impl ClamdClient {
+    /// Creates a client for `address` (`host:port` or `unix:/path`).
+    pub fn new(address: &str, timeout: Duration) -> Self {
+        Self {
+            display: address.to_string(),
+            address: ClamdAddress::parse(address),
+            timeout,
+        }
+    }
+
+    /// Scans `content` on a fresh connection.
+    pub async fn scan(&self, content: ByteStream) -> Result<ScanVerdict, ScanError> {
+        let connect_error = |source| ScanError::Connect {
+            address: self.display.clone(),
+            source,
+        };
+        match &self.address {
+            ClamdAddress::Tcp(addr) => {
+                let conn = within(self.timeout, tokio::net::TcpStream::connect(addr))
+                    .await?
+                    .map_err(connect_error)?;
+                instream(conn, content, self.timeout).await
+            }
+            #[cfg(unix)]
+            ClamdAddress::Unix(path) => {
+                let conn = within(self.timeout, tokio::net::UnixStream::connect(path))
+                    .await?
+                    .map_err(connect_error)?;
+                instream(conn, content, self.timeout).await
+            }
+        }
+    }
+}
+
+/// Runs the `INSTREAM` exchange on an open connection.
+async fn instream<C>(
+    mut conn: C,
+    mut content: ByteStream,
+    timeout: Duration,
+) -> Result<ScanVerdict, ScanError>
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+{
+    within(timeout, conn.write_all(b"zINSTREAM\0")).await??;
+
+    while let Some(chunk) = content.next().await {
+        let chunk = chunk.map_err(ScanError::Source)?;
+        for frame in frames(chunk) {
+            let len = (frame.len() as u32).to_be_bytes();
+            let sent = within(timeout, async {
+                conn.write_all(&len).await?;
+                conn.write_all(&frame).await
+            })
+            .await?;
+            if let Err(e) = sent {
+                // clamd hangs up once StreamMaxLength is exceeded; its
+                // reply says so more clearly than the broken pipe.
+                return match read_reply(&mut conn, timeout).await {
+                    Ok(reply) if !reply.is_empty() => parse_reply(&reply),
+                    _ => Err(e.into()),
+                };
+            }
+        }
+    }
+
+    within(timeout, async {
+        conn.write_all(&0u32.to_be_bytes()).await?;
+        conn.flush().await
+    })
+    .await??;
+
+    let reply = read_reply(&mut conn, timeout).await?;
+    parse_reply(&reply)
+}
+
+/// Splits a chunk read from storage into frames of at most [`MAX_CHUNK`].
+fn frames(mut chunk: Bytes) -> impl Iterator<Item = Bytes> {
+    std::iter::from_fn(move || {
+        if chunk.is_empty() {
+            return None;
+        }
+        let at = chunk.len().min(MAX_CHUNK);
+        Some(chunk.split_to(at))
+    })
+}
+
+/// Reads clamd's reply up to its terminating NUL or end of stream.
+async fn read_reply<C>(conn: &mut C, timeout: Duration) -> Result<String, ScanError>
+where
+    C: AsyncRead + Unpin,
+{
+    let mut reply = Vec::new();
+    let mut buf = [0u8; 256];
+    loop {
+        let n = within(timeout, conn.read(&mut buf)).await??;
+        if n == 0 {
+            break;
+        }
+        reply.extend_from_slice(&buf[..n]);
+        if let Some(end) = reply.iter().position(|&b| b == 0) {
+            reply.truncate(end);
+            break;
+        }
+        if reply.len() > MAX_REPLY {
+            return Err(ScanError::Scanner("Reply too long".to_string()));
+        }
+    }
+    Ok(String::from_utf8_lossy(&reply).trim().to_string())
+}
+
+/// Interprets clamd's reply line.
+fn parse_reply(reply: &str) -> Result<ScanVerdict, ScanError> {
+    let body = reply
+        .strip_prefix("stream:")
+        .map(str::trim)
+        .unwrap_or(reply);
+    if body == "OK" {
+        return Ok(ScanVerdict::Clean);
+    }
+    if let Some(signature) = body.strip_suffix(" FOUND") {
+        return Ok(ScanVerdict::Infected(signature.trim().to_string()));
+    }
+    if reply.is_empty() {
+        return Err(ScanError::Scanner("Empty reply".to_string()));
+    }
+    Err(ScanError::Scanner(reply.to_string()))
+}
+
+/// Applies the stall timeout to one step of the exchange.
+async fn within<T>(timeout: Duration, step: impl Future<Output = T>) -> Result<T, ScanError> {
+    tokio::time::timeout(timeout, step)
+        .await
+        .map_err(|_| ScanError::Timeout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tokio::io::DuplexStream;
+
+    fn content(chunks: Vec<&'static [u8]>) -> ByteStream {
+        Box::pin(futures::stream::iter(
+            chunks.into_iter().map(|c| Ok(Bytes::from_static(c))),
+        ))
+    }
+
+    /// Plays clamd: reads one `INSTREAM` request and answers `reply`.
+    /// Returns the frame lengths and the reassembled content.
+    async fn fake_clamd(mut conn: DuplexStream, reply: &'static str) -> (Vec<u32>, Vec<u8>) {
+        let mut command = [0u8; 10];
+        conn.read_exact(&mut command).await.unwrap();
+        assert_eq!(&command, b"zINSTREAM\0");
+
+        let (mut lens, mut data) = (Vec::new(), Vec::new());
+        loop {
+            let len = conn.read_u32().await.unwrap();
+            lens.push(len);
+            if len == 0 {
+                break;
+            }
+            let mut frame = vec![0u8; len as usize];
+            conn.read_exact(&mut frame).await.unwrap();
+            data.extend(frame);
+        }
+        conn.write_all(reply.as_bytes()).await.unwrap();
+        conn.write_all(b"\0").await.unwrap();
+        (lens, data)
+    }
+
+    #[tokio::test]
+    async fn test_instream_frames_content_and_terminates() {
+        let big: &'static [u8] = Box::leak(vec![7u8; MAX_CHUNK + 10].into_boxed_slice());
+        let (client, server) = tokio::io::duplex(8 * 1024);
+        let clamd = tokio::spawn(fake_clamd(server, "stream: OK"));
+
+        let verdict = instream(client, content(vec![b"abc", big]), Duration::from_secs(5))
+            .await
+            .unwrap();
+        let (lens, data) = clamd.await.unwrap();
+
+        assert_eq!(verdict, ScanVerdict::Clean);
+        assert_eq!(lens, [3, MAX_CHUNK as u32, 10, 0]);
+        assert_eq!(data.len(), 3 + big.len());
+        assert_eq!(&data[..3], b"abc");
+    }
+
+    #[tokio::test]
+    async fn test_instream_reports_signature() {
+        let (client, server) = tokio::io::duplex(1024);
+        let clamd = tokio::spawn(fake_clamd(server, "stream: Eicar-Signature FOUND"));
+
+        let verdict = instream(client, content(vec![b"X5O!P%"]), Duration::from_secs(5))
+            .await
+            .unwrap();
+        clamd.await.unwrap();
+
+        assert_eq!(verdict, ScanVerdict::Infected("Eicar-Signature".into()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_silent_clamd_times_out() {
+        let (client, _server) = tokio::io::duplex(1 << 20);
+        let result = instream(client, content(vec![b"abc"]), Duration::from_secs(5)).await;
+        assert!(matches!(result, Err(ScanError::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_clamd_is_a_connect_error() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        drop(listener);
+
+        let client = ClamdClient::new(&address, Duration::from_secs(5));
+        let result = client.scan(content(vec![b"abc"])).await;
+        assert!(matches!(result, Err(ScanError::Connect { .. })));
+    }
+
+    #[test]
+    fn test_parse_reply() {
+        assert_eq!(parse_reply("stream: OK").unwrap(), ScanVerdict::Clean);
+        assert_eq!(
+            parse_reply("stream: Win.Test.EICAR_HDB-1 FOUND").unwrap(),
+            ScanVerdict::Infected("Win.Test.EICAR_HDB-1".into())
+        );
+        assert!(matches!(
+            parse_reply("INSTREAM size limit exceeded. ERROR"),
+            Err(ScanError::Scanner(msg)) if msg.contains("size limit")
+        ));
+        assert!(parse_reply("").is_err());
+    }
+
+    #[test]
+    fn test_address_parsing() {
+        assert_eq!(
+            ClamdAddress::parse("clamav:3310"),
+            ClamdAddress::Tcp("clamav:3310".into())
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            ClamdAddress::parse("unix:/run/clamd.sock"),
+            ClamdAddress::Unix("/run/clamd.sock".into())
+        );
+    }
+}
@@ -0,0 +1,14 @@
+//! # Plugin Antivirus
+//!
+//! A FileHub plugin that scans uploads with a ClamAV daemon. Files are
+//! streamed to clamd from the `AfterUpload` hook; infected files are
+//! quarantined, and files that cannot be scanned are quarantined or let
+//! through according to the configured fail policy.
+
+pub mod clamd;
+pub mod plugin;
+pub mod scanner;
+
+pub use clamd::{ClamdClient, ScanError, ScanVerdict};
+pub use plugin::AntivirusPlugin;
+pub use scanner::{ScannedUpload, UploadScanner};
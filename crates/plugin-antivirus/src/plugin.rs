@@ -0,0 +1,179 @@
+//! FileHub plugin trait implementation and hook registration.
+
+use std::sync::Arc;
+
+use tracing::{debug, info};
+
+use filehub_plugin::{HookRegistry, prelude::*};
+
+use crate::scanner::{ScannedUpload, UploadScanner};
+
+/// Plugin name used for registration, logging, and hook results.
+const PLUGIN_NAME: &str = "antivirus";
+
+/// Plugin version from Cargo manifest.
+const PLUGIN_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Payload keys set by the upload service before `AfterUpload` fires.
+mod payload_keys {
+    /// UUID of the file entity.
+    pub const FILE_ID: &str = "file_id";
+    /// UUID of the storage backend.
+    pub const STORAGE_ID: &str = "storage_id";
+    /// Storage path the file was written to.
+    pub const STORAGE_PATH: &str = "storage_path";
+    /// UUID of the file owner.
+    pub const OWNER_ID: &str = "owner_id";
+    /// File name.
+    pub const NAME: &str = "name";
+}
+
+/// The antivirus plugin for FileHub.
+///
+/// Registers an `after_upload` handler that hands each upload to the
+/// [`UploadScanner`] in the background, so uploads complete without
+/// waiting for the scan and large files are not held to the hook timeout.
+#[derive(Debug)]
+pub struct AntivirusPlugin {
+    /// Shared scanner.
+    scanner: Arc<UploadScanner>,
+}
+
+impl AntivirusPlugin {
+    /// Creates the plugin around a configured scanner.
+    pub fn new(scanner: UploadScanner) -> Self {
+        Self {
+            scanner: Arc::new(scanner),
+        }
+    }
+
+    /// Register hooks with the FileHub hook registry.
+    pub async fn register_hooks(&self, registry: &HookRegistry) {
+        registry
+            .register(
+                HookPoint::AfterUpload,
+                SimpleHandlerAdapter::wrap(Arc::new(AfterUploadHandler {
+                    scanner: Arc::clone(&self.scanner),
+                })),
+            )
+            .await;
+
+        info!(plugin = PLUGIN_NAME, "Registered hooks: after_upload");
+    }
+}
+
+#[async_trait]
+impl Plugin for AntivirusPlugin {
+    fn info(&self) -> PluginInfo {
+        plugin_info!(
+            id: PLUGIN_NAME,
+            name: "Antivirus",
+            version: PLUGIN_VERSION,
+            description: "Scans uploads with ClamAV and quarantines infected files",
+            author: "FileHub Team"
+        )
+    }
+
+    async fn on_load(&self) -> Result<(), String> {
+        info!(plugin = PLUGIN_NAME, "Plugin loaded");
+        Ok(())
+    }
+
+    async fn on_start(&self) -> Result<(), String> {
+        info!(plugin = PLUGIN_NAME, "Plugin started");
+        Ok(())
+    }
+
+    async fn on_stop(&self) -> Result<(), String> {
+        info!(plugin = PLUGIN_NAME, "Plugin stopped");
+        Ok(())
+    }
+
+    async fn on_unload(&self) -> Result<(), String> {
+        info!(plugin = PLUGIN_NAME, "Plugin unloaded");
+        Ok(())
+    }
+
+    fn registered_hooks(&self) -> Vec<HookPoint> {
+        vec![HookPoint::AfterUpload]
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// Handles the `after_upload` hook point by queueing a scan.
+#[derive(Debug)]
+struct AfterUploadHandler {
+    /// Shared scanner.
+    scanner: Arc<UploadScanner>,
+}
+
+#[async_trait]
+impl SimpleHookHandler for AfterUploadHandler {
+    fn plugin_id(&self) -> &str {
+        PLUGIN_NAME
+    }
+
+    fn hook_point(&self) -> HookPoint {
+        HookPoint::AfterUpload
+    }
+
+    async fn handle(&self, payload: &HookPayload) -> HookResult {
+        let Some(upload) = scanned_upload(payload) else {
+            debug!(
+                hook = %payload.hook,
+                "after_upload payload missing file details, skipping scan"
+            );
+            return HookResult::continue_execution(PLUGIN_NAME);
+        };
+
+        let scanner = Arc::clone(&self.scanner);
+        tokio::spawn(async move { scanner.scan(upload).await });
+
+        HookResult::continue_execution(PLUGIN_NAME)
+    }
+}
+
+/// Reads the file to scan from an `after_upload` payload.
+fn scanned_upload(payload: &HookPayload) -> Option<ScannedUpload> {
+    Some(ScannedUpload {
+        file_id: payload.get_uuid(payload_keys::FILE_ID)?,
+        storage_id: payload.get_uuid(payload_keys::STORAGE_ID)?,
+        storage_path: payload.get_string(payload_keys::STORAGE_PATH)?.to_string(),
+        owner_id: payload.get_uuid(payload_keys::OWNER_ID)?,
+        name: payload
+            .get_string(payload_keys::NAME)
+            .unwrap_or_default()
+            .to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uuid::Uuid;
+
+    #[test]
+    fn test_scanned_upload_from_payload() {
+        let (file_id, storage_id, owner_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let payload = HookPayload::new(HookPoint::AfterUpload)
+            .with_uuid("file_id", file_id)
+            .with_uuid("storage_id", storage_id)
+            .with_uuid("owner_id", owner_id)
+            .with_string("storage_path", "files/a/b.bin")
+            .with_string("name", "b.bin");
+
+        let upload = scanned_upload(&payload).unwrap();
+        assert_eq!(upload.file_id, file_id);
+        assert_eq!(upload.storage_id, storage_id);
+        assert_eq!(upload.owner_id, owner_id);
+        assert_eq!(upload.storage_path, "files/a/b.bin");
+        assert_eq!(upload.name, "b.bin");
+
+        let partial = HookPayload::new(HookPoint::AfterUpload).with_uuid("file_id", file_id);
+        assert!(scanned_upload(&partial).is_none());
+    }
+}
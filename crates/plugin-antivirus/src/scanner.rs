@@ -0,0 +1,254 @@
+//! Scans uploaded files and quarantines what clamd flags.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Semaphore;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use filehub_core::config::{AntivirusConfig, ScanFailPolicy};
+use filehub_core::types::Impersonator;
+use filehub_database::repositories::audit::AuditLogRepository;
+use filehub_database::repositories::file::FileRepository;
+use filehub_database::repositories::notification::NotificationRepository;
+use filehub_entity::audit::model::CreateAuditLogEntry;
+use filehub_entity::notification::NotificationCategory;
+use filehub_storage::manager::StorageManager;
+
+use crate::clamd::{ClamdClient, ScanError, ScanVerdict};
+
+/// An uploaded file waiting to be scanned.
+#[derive(Debug, Clone)]
+pub struct ScannedUpload {
+    /// The file entity.
+    pub file_id: Uuid,
+    /// Storage backend holding the content.
+    pub storage_id: Uuid,
+    /// Path of the content within the storage.
+    pub storage_path: String,
+    /// Owner, who is notified and recorded as the audit actor.
+    pub owner_id: Uuid,
+    /// File name, for the notification.
+    pub name: String,
+}
+
+/// Streams uploads to clamd and quarantines infected files, or files that
+/// could not be scanned when the fail policy is closed.
+///
+/// Quarantining moves the content to `<quarantine_dir>/<file id>` on the
+/// same storage, flags the file so it can no longer be downloaded, and
+/// records a `file.quarantined` audit entry and a `security` notification
+/// for the owner.
+#[derive(Debug)]
+pub struct UploadScanner {
+    /// clamd connection settings.
+    client: ClamdClient,
+    /// Scanner configuration.
+    config: AntivirusConfig,
+    /// Storage manager, to read and move content.
+    storage: Arc<StorageManager>,
+    /// File repository, to flag quarantined files.
+    file_repo: Arc<FileRepository>,
+    /// Audit log repository.
+    audit_repo: Arc<AuditLogRepository>,
+    /// Notification repository.
+    notif_repo: Arc<NotificationRepository>,
+    /// Bounds concurrent scans.
+    permits: Semaphore,
+}
+
+impl UploadScanner {
+    /// Creates a scanner from the antivirus configuration.
+    pub fn new(
+        config: AntivirusConfig,
+        storage: Arc<StorageManager>,
+        file_repo: Arc<FileRepository>,
+        audit_repo: Arc<AuditLogRepository>,
+        notif_repo: Arc<NotificationRepository>,
+    ) -> Self {
+        Self {
+            client: ClamdClient::new(
+                &config.clamd_address,
+                Duration::from_secs(config.timeout_seconds),
+            ),
+            permits: Semaphore::new(config.max_concurrent_scans.max(1) as usize),
+            config,
+            storage,
+            file_repo,
+            audit_repo,
+            notif_repo,
+        }
+    }
+
+    /// Scans one upload and acts on the verdict. Never fails; problems are
+    /// logged.
+    pub async fn scan(&self, upload: ScannedUpload) {
+        let Ok(_permit) = self.permits.acquire().await else {
+            return;
+        };
+
+        match self.run_scan(&upload).await {
+            Ok(ScanVerdict::Clean) => {
+                debug!(file_id = %upload.file_id, "Upload scanned clean");
+            }
+            Ok(ScanVerdict::Infected(signature)) => {
+                warn!(
+                    file_id = %upload.file_id,
+                    signature = %signature,
+                    "Malware detected in upload"
+                );
+                let reason = format!("Malware detected: {signature}");
+                self.quarantine(&upload, &reason, Some(&signature)).await;
+            }
+            Err(e) => match self.config.fail_policy {
+                ScanFailPolicy::Closed => {
+                    warn!(
+                        file_id = %upload.file_id,
+                        error = %e,
+                        "Upload could not be scanned, quarantining"
+                    );
+                    let reason = format!("Virus scan failed: {e}");
+                    self.quarantine(&upload, &reason, None).await;
+                }
+                ScanFailPolicy::Open => {
+                    warn!(
+                        file_id = %upload.file_id,
+                        error = %e,
+                        "Upload could not be scanned, leaving it available"
+                    );
+                    let details = serde_json::json!({
+                        "name": upload.name,
+                        "error": e.to_string(),
+                    });
+                    self.audit(&upload, "file.scan_skipped", details).await;
+                }
+            },
+        }
+    }
+
+    /// Streams the file's content to clamd.
+    async fn run_scan(&self, upload: &ScannedUpload) -> Result<ScanVerdict, ScanError> {
+        let provider = self
+            .storage
+            .get(&upload.storage_id)
+            .await
+            .map_err(|e| ScanError::Source(std::io::Error::other(e.to_string())))?;
+        let content = provider
+            .read(&upload.storage_path)
+            .await
+            .map_err(|e| ScanError::Source(std::io::Error::other(e.to_string())))?;
+        self.client.scan(content).await
+    }
+
+    /// Moves the content aside and flags the file. If the content cannot
+    /// be moved the file is still flagged, which is what blocks access.
+    async fn quarantine(&self, upload: &ScannedUpload, reason: &str, signature: Option<&str>) {
+        let target = format!(
+            "{}/{}",
+            self.config.quarantine_dir.trim_end_matches('/'),
+            upload.file_id
+        );
+        let moved = match self
+            .storage
+            .rename(&upload.storage_id, &upload.storage_path, &target)
+            .await
+        {
+            Ok(()) => true,
+            Err(e) => {
+                error!(
+                    file_id = %upload.file_id,
+                    error = %e,
+                    "Failed to move file to quarantine, flagging it in place"
+                );
+                false
+            }
+        };
+        let path = if moved { &target } else { &upload.storage_path };
+
+        let flagged = self
+            .file_repo
+            .quarantine(upload.file_id, &upload.storage_path, path, reason)
+            .await;
+        let file = match flagged {
+            Ok(Some(file)) => file,
+            Ok(None) => {
+                info!(
+                    file_id = %upload.file_id,
+                    "File changed or was removed before it could be quarantined"
+                );
+                if moved {
+                    self.move_back(upload, &target).await;
+                }
+                return;
+            }
+            Err(e) => {
+                error!(file_id = %upload.file_id, error = %e, "Failed to flag quarantined file");
+                if moved {
+                    self.move_back(upload, &target).await;
+                }
+                return;
+            }
+        };
+
+        let details = serde_json::json!({
+            "name": file.name,
+            "reason": reason,
+            "signature": signature,
+            "quarantine_path": file.storage_path,
+        });
+        self.audit(upload, "file.quarantined", details.clone())
+            .await;
+
+        if let Err(e) = self
+            .notif_repo
+            .create(
+                upload.owner_id,
+                NotificationCategory::Security.as_str(),
+                "file_quarantined",
+                "File quarantined",
+                &format!("\"{}\" was quarantined. {reason}", file.name),
+                Some(&details),
+                Some("high"),
+                None,
+                Some("file"),
+                Some(file.id),
+            )
+            .await
+        {
+            warn!(file_id = %file.id, error = %e, "Failed to notify owner of quarantined file");
+        }
+    }
+
+    /// Undoes the move into quarantine of a file that was not flagged.
+    async fn move_back(&self, upload: &ScannedUpload, target: &str) {
+        if let Err(e) = self
+            .storage
+            .rename(&upload.storage_id, target, &upload.storage_path)
+            .await
+        {
+            error!(
+                file_id = %upload.file_id,
+                error = %e,
+                "Failed to move file back out of quarantine"
+            );
+        }
+    }
+
+    /// Records a scan outcome, with the owner as actor.
+    async fn audit(&self, upload: &ScannedUpload, action: &str, details: serde_json::Value) {
+        let entry = CreateAuditLogEntry {
+            actor_id: Some(upload.owner_id),
+            impersonator_id: Impersonator::current().map(|i| i.id()),
+            action: action.to_string(),
+            target_type: "file".to_string(),
+            target_id: Some(upload.file_id),
+            details: Some(details),
+            ip_address: None,
+            user_agent: None,
+        };
+        if let Err(e) = self.audit_repo.create(&entry).await {
+            warn!(file_id = %upload.file_id, error = %e, "Failed to audit virus scan");
+        }
+    }
+}
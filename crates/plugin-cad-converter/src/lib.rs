@@ -20,6 +20,7 @@ pub mod models;
 pub mod plugin;
 pub mod processor;
 pub mod scripting;
+pub mod service;
 
 pub use config::ConversionConfig;
 pub use error::ConversionError;
@@ -27,3 +28,4 @@ pub use jupiter::JupiterDiscovery;
 pub use models::{ConversionInput, ConversionMode, ConversionOptions, ConversionResult, FileType};
 pub use plugin::CadConverterPlugin;
 pub use processor::ConversionProcessor;
+pub use service::ProcessorConversionService;
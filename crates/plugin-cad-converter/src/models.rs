@@ -6,6 +6,8 @@ use std::sync::LazyLock;
 
 use serde::{Deserialize, Serialize};
 
+use filehub_core::config::{ConversionProfile, ConversionProfileMode};
+
 use crate::error::ConversionError;
 
 /// Normalize path to forward slashes for Python script embedding.
@@ -400,6 +402,23 @@ impl Default for ConversionOptions {
 }
 
 impl ConversionOptions {
+    /// The options of a configured profile, for a request naming
+    /// `primary_name` as its primary input.
+    pub fn from_profile(profile: &ConversionProfile, primary_name: Option<String>) -> Self {
+        let mode = match profile.mode {
+            ConversionProfileMode::Single => ConversionMode::Single,
+            ConversionProfileMode::Assembly => ConversionMode::Assembly,
+            ConversionProfileMode::Combine => ConversionMode::Combine,
+        };
+        Self {
+            mode: Some(mode),
+            primary_name,
+            delete_source: Some(profile.delete_source),
+            concurrency: Some(profile.concurrency),
+            scan_deeper: Some(profile.scan_deeper),
+        }
+    }
+
     /// Resolve the effective conversion mode.
     pub fn conversion_mode(&self) -> ConversionMode {
         self.mode.unwrap_or_default()
@@ -478,6 +497,22 @@ mod tests {
         assert!(cmd.contains("ImportBdf"));
     }
 
+    #[test]
+    fn test_options_from_profile() {
+        let profile = ConversionProfile {
+            mode: ConversionProfileMode::Assembly,
+            concurrency: 3,
+            scan_deeper: true,
+            delete_source: false,
+        };
+        let opts = ConversionOptions::from_profile(&profile, Some("top.iam".to_string()));
+        assert_eq!(opts.conversion_mode(), ConversionMode::Assembly);
+        assert_eq!(opts.concurrency(), 3);
+        assert!(opts.should_scan_deeper());
+        assert!(!opts.should_delete_source());
+        assert_eq!(opts.get_primary_name().unwrap(), "top.iam");
+    }
+
     #[test]
     fn test_options_assembly_no_primary() {
         let opts = ConversionOptions {
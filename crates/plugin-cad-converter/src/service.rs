@@ -0,0 +1,147 @@
+//! The worker's `ConversionService`, backed by the conversion processor.
+//!
+//! A conversion job names a profile; the worker resolves it and hands it
+//! over here, where it becomes the `ConversionOptions` of the run.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing::info;
+use uuid::Uuid;
+
+use filehub_core::config::ConversionProfile;
+use filehub_core::error::AppError;
+use filehub_worker::jobs::conversion::ConversionService;
+
+use crate::models::ConversionOptions;
+use crate::processor::ConversionProcessor;
+
+/// Output format the processor produces.
+const VTFX: &str = "vtfx";
+
+/// Runs conversion jobs through a [`ConversionProcessor`].
+#[derive(Debug, Clone)]
+pub struct ProcessorConversionService {
+    /// The conversion processor.
+    processor: Arc<ConversionProcessor>,
+}
+
+impl ProcessorConversionService {
+    /// Create a service running conversions on `processor`.
+    pub fn new(processor: Arc<ConversionProcessor>) -> Self {
+        Self { processor }
+    }
+}
+
+#[async_trait]
+impl ConversionService for ProcessorConversionService {
+    async fn convert(
+        &self,
+        file_id: Uuid,
+        file_name: &str,
+        source_path: &str,
+        targets: &[String],
+        profile: &ConversionProfile,
+        primary_name: Option<&str>,
+        output_dir: &str,
+        job_id: &str,
+    ) -> Result<Value, AppError> {
+        if let Some(target) = targets.iter().find(|t| !t.eq_ignore_ascii_case(VTFX)) {
+            return Err(AppError::validation(format!(
+                "Cannot convert to '{}': only '{}' is supported",
+                target, VTFX
+            )));
+        }
+
+        let options = ConversionOptions::from_profile(profile, primary_name.map(String::from));
+        let outputs = self
+            .processor
+            .execute_job_simple(
+                vec![source_path.to_string()],
+                output_dir.to_string(),
+                Some(options),
+            )
+            .await?;
+
+        info!(
+            file_id = %file_id,
+            job_id = %job_id,
+            outputs = outputs.len(),
+            "Converted '{}'",
+            file_name
+        );
+
+        Ok(serde_json::json!({
+            "file_id": file_id,
+            "file_name": file_name,
+            "outputs": outputs,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::ConversionConfig;
+
+    fn service(temp: &std::path::Path) -> ProcessorConversionService {
+        let config = ConversionConfig {
+            temp_root: Some(temp.join("work")),
+            ..Default::default()
+        };
+        ProcessorConversionService::new(Arc::new(
+            ConversionProcessor::new(config).expect("create processor"),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_convert_passes_vtfx_through() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("model.vtfx");
+        std::fs::write(&source, vec![7u8; 4096]).unwrap();
+        let output = temp.path().join("out");
+
+        let result = service(temp.path())
+            .convert(
+                Uuid::nil(),
+                "model.vtfx",
+                source.to_str().unwrap(),
+                &["VTFx".to_string()],
+                &ConversionProfile::default(),
+                None,
+                output.to_str().unwrap(),
+                "job-1",
+            )
+            .await
+            .expect("convert");
+
+        let outputs = result["outputs"].as_array().unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0]["original_name"], "model.vtfx");
+        let written = std::path::Path::new(outputs[0]["path"].as_str().unwrap());
+        assert!(written.starts_with(&output));
+        assert_eq!(std::fs::metadata(written).unwrap().len(), 4096);
+        assert!(source.exists());
+    }
+
+    #[tokio::test]
+    async fn test_convert_rejects_other_targets() {
+        let temp = tempfile::tempdir().unwrap();
+        let err = service(temp.path())
+            .convert(
+                Uuid::nil(),
+                "part.stp",
+                "/nowhere/part.stp",
+                &["obj".to_string()],
+                &ConversionProfile::default(),
+                None,
+                "/nowhere/out",
+                "job-2",
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind, filehub_core::error::ErrorKind::Validation);
+    }
+}
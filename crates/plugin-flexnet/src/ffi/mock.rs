@@ -5,22 +5,33 @@
 
 use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 
+use chrono::{DateTime, Utc};
 use tracing;
 
+/// Seats given to a feature that was never configured with `set_total_seats`
+pub const DEFAULT_SEATS: i32 = 10;
+
 /// Mock license manager that simulates DLL behavior in-memory.
+///
+/// Every feature has its own seat pool, as on a real license server.
 #[derive(Debug)]
 pub struct MockLicenseManager {
     /// Whether initialized
     initialized: Mutex<bool>,
     /// Active checkouts: feature -> set of session_ids
     checkouts: Mutex<HashMap<String, HashSet<String>>>,
+    /// Active borrows: token -> (feature, borrowed_until)
+    borrows: Mutex<HashMap<String, (String, DateTime<Utc>)>>,
     /// Total seats per feature (configurable for testing)
     total_seats: Mutex<HashMap<String, i32>>,
     /// Whether this is a star (unlimited) license
     is_star: Mutex<bool>,
     /// Simulated server info string
     server_info: Mutex<String>,
+    /// When set, every lease renewal fails (for testing)
+    fail_renewals: AtomicBool,
 }
 
 impl MockLicenseManager {
@@ -29,9 +40,11 @@ impl MockLicenseManager {
         Self {
             initialized: Mutex::new(false),
             checkouts: Mutex::new(HashMap::new()),
+            borrows: Mutex::new(HashMap::new()),
             total_seats: Mutex::new(HashMap::new()),
             is_star: Mutex::new(false),
             server_info: Mutex::new("MockServer@localhost".to_string()),
+            fail_renewals: AtomicBool::new(false),
         }
     }
 
@@ -47,6 +60,12 @@ impl MockLicenseManager {
         *star = is_star;
     }
 
+    /// Make lease renewals fail, simulating a server that dropped the
+    /// lease (for testing)
+    pub fn set_fail_renewals(&self, fail: bool) {
+        self.fail_renewals.store(fail, Ordering::Relaxed);
+    }
+
     /// Initialize the mock license manager
     pub fn initialize(&self, _override_path: Option<&str>) -> i32 {
         let mut init = self.initialized.lock().unwrap_or_else(|e| e.into_inner());
@@ -57,6 +76,8 @@ impl MockLicenseManager {
 
     /// Checkout a feature for a session
     pub fn checkout(&self, feature: &str, session_id: &str) -> i32 {
+        self.expire_borrows();
+
         let init = self.initialized.lock().unwrap_or_else(|e| e.into_inner());
         if !*init {
             tracing::error!("[MockLM] Not initialized");
@@ -68,7 +89,7 @@ impl MockLicenseManager {
         let is_star = *self.is_star.lock().unwrap_or_else(|e| e.into_inner());
 
         let total = self.total_seats.lock().unwrap_or_else(|e| e.into_inner());
-        let max_seats = total.get(feature).copied().unwrap_or(10);
+        let max_seats = total.get(feature).copied().unwrap_or(DEFAULT_SEATS);
         drop(total);
 
         let mut checkouts = self.checkouts.lock().unwrap_or_else(|e| e.into_inner());
@@ -133,8 +154,102 @@ impl MockLicenseManager {
         -1
     }
 
+    /// Renew the lease on an existing checkout. Fails if the session holds
+    /// no checkout or renewals are set to fail; a failed renewal drops the
+    /// checkout, as the server would once the lease runs out.
+    pub fn renew(&self, feature: &str, session_id: &str) -> i32 {
+        let fail = self.fail_renewals.load(Ordering::Relaxed);
+        let mut checkouts = self.checkouts.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(sessions) = checkouts.get_mut(feature) else {
+            return -1;
+        };
+        if !sessions.contains(session_id) {
+            tracing::warn!(
+                "[MockLM] Renew: session '{}' holds no lease for '{}'",
+                session_id,
+                feature
+            );
+            return -1;
+        }
+        if fail {
+            sessions.remove(session_id);
+            tracing::warn!(
+                "[MockLM] Renew failed for '{}', session '{}' lost its seat",
+                feature,
+                session_id
+            );
+            return -1;
+        }
+        tracing::debug!(
+            "[MockLM] Renewed '{}' for session '{}'",
+            feature,
+            session_id
+        );
+        0
+    }
+
+    /// Borrow a seat until `until`. The seat counts as used until it is
+    /// returned or the borrow window passes.
+    pub fn borrow(&self, feature: &str, token: &str, until: DateTime<Utc>) -> i32 {
+        let res = self.checkout(feature, token);
+        if res == 0 {
+            let mut borrows = self.borrows.lock().unwrap_or_else(|e| e.into_inner());
+            borrows.insert(token.to_string(), (feature.to_string(), until));
+            tracing::info!(
+                "[MockLM] Borrowed '{}' as '{}' until {}",
+                feature,
+                token,
+                until
+            );
+        }
+        res
+    }
+
+    /// Return a borrowed seat early
+    pub fn return_borrow(&self, feature: &str, token: &str) -> i32 {
+        let mut borrows = self.borrows.lock().unwrap_or_else(|e| e.into_inner());
+        if borrows.remove(token).is_none() {
+            tracing::warn!("[MockLM] Return: borrow '{}' not found", token);
+            return -1;
+        }
+        drop(borrows);
+        self.checkin(feature, token)
+    }
+
+    /// Number of seats currently borrowed for a feature
+    pub fn borrowed_count(&self, feature: &str) -> i32 {
+        self.expire_borrows();
+        let borrows = self.borrows.lock().unwrap_or_else(|e| e.into_inner());
+        borrows.values().filter(|(f, _)| f == feature).count() as i32
+    }
+
+    /// Release borrows whose window has passed, as the server would.
+    fn expire_borrows(&self) {
+        let now = Utc::now();
+        let mut borrows = self.borrows.lock().unwrap_or_else(|e| e.into_inner());
+        let expired: Vec<(String, String)> = borrows
+            .iter()
+            .filter(|(_, (_, until))| *until <= now)
+            .map(|(token, (feature, _))| (token.clone(), feature.clone()))
+            .collect();
+        if expired.is_empty() {
+            return;
+        }
+
+        let mut checkouts = self.checkouts.lock().unwrap_or_else(|e| e.into_inner());
+        for (token, feature) in expired {
+            borrows.remove(&token);
+            if let Some(sessions) = checkouts.get_mut(&feature) {
+                sessions.remove(&token);
+            }
+            tracing::info!("[MockLM] Borrow '{}' for '{}' expired", token, feature);
+        }
+    }
+
     /// Get token pool info: (total, used)
     pub fn get_token_pool(&self, feature: &str) -> (i32, i32, i32) {
+        self.expire_borrows();
+
         let init = self.initialized.lock().unwrap_or_else(|e| e.into_inner());
         if !*init {
             return (-1, 0, 0);
@@ -142,7 +257,7 @@ impl MockLicenseManager {
         drop(init);
 
         let total = self.total_seats.lock().unwrap_or_else(|e| e.into_inner());
-        let max_seats = total.get(feature).copied().unwrap_or(10);
+        let max_seats = total.get(feature).copied().unwrap_or(DEFAULT_SEATS);
         drop(total);
 
         let checkouts = self.checkouts.lock().unwrap_or_else(|e| e.into_inner());
@@ -166,6 +281,10 @@ impl MockLicenseManager {
 
     /// Release all checkouts
     pub fn release_all(&self) {
+        self.borrows
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
         let mut checkouts = self.checkouts.lock().unwrap_or_else(|e| e.into_inner());
         let total_released: usize = checkouts.values().map(|s| s.len()).sum();
         checkouts.clear();
@@ -186,3 +305,74 @@ impl Default for MockLicenseManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn manager(seats: i32) -> MockLicenseManager {
+        let mock = MockLicenseManager::new();
+        mock.set_total_seats("viewer", seats);
+        mock.initialize(None);
+        mock
+    }
+
+    #[test]
+    fn test_borrow_holds_a_seat_until_returned() {
+        let mock = manager(1);
+        let until = Utc::now() + Duration::hours(1);
+
+        assert_eq!(mock.borrow("viewer", "borrow:a", until), 0);
+        assert_eq!(mock.get_token_pool("viewer"), (0, 1, 1));
+        assert_eq!(mock.borrowed_count("viewer"), 1);
+        assert_ne!(mock.checkout("viewer", "session-1"), 0);
+
+        assert_eq!(mock.return_borrow("viewer", "borrow:a"), 0);
+        assert_eq!(mock.get_token_pool("viewer"), (0, 1, 0));
+        assert_ne!(mock.return_borrow("viewer", "borrow:a"), 0);
+    }
+
+    #[test]
+    fn test_renewal_failure_drops_the_seat() {
+        let mock = manager(2);
+        assert_eq!(mock.checkout("viewer", "session-1"), 0);
+        assert_eq!(mock.renew("viewer", "session-1"), 0);
+        assert_ne!(mock.renew("viewer", "session-2"), 0);
+
+        mock.set_fail_renewals(true);
+        assert_ne!(mock.renew("viewer", "session-1"), 0);
+        assert_eq!(mock.get_token_pool("viewer"), (0, 2, 0));
+    }
+
+    #[test]
+    fn test_features_have_independent_pools() {
+        let mock = manager(1);
+        mock.set_total_seats("editor", 2);
+
+        assert_eq!(mock.checkout("viewer", "session-1"), 0);
+        assert_ne!(mock.checkout("viewer", "session-2"), 0);
+        assert_eq!(mock.checkout("editor", "session-2"), 0);
+        assert_eq!(mock.checkout("editor", "session-3"), 0);
+
+        assert_eq!(mock.get_token_pool("viewer"), (0, 1, 1));
+        assert_eq!(mock.get_token_pool("editor"), (0, 2, 2));
+        assert_eq!(mock.get_token_pool("other"), (0, DEFAULT_SEATS, 0));
+
+        assert_eq!(mock.checkin("editor", "session-2"), 0);
+        assert_ne!(mock.checkin("viewer", "session-2"), 0);
+        assert_eq!(mock.get_token_pool("viewer"), (0, 1, 1));
+    }
+
+    #[test]
+    fn test_expired_borrow_is_released() {
+        let mock = manager(1);
+        let until = Utc::now() - Duration::seconds(1);
+
+        assert_eq!(mock.borrow("viewer", "borrow:a", until), 0);
+        assert_eq!(mock.borrowed_count("viewer"), 0);
+        assert_eq!(mock.get_token_pool("viewer"), (0, 1, 0));
+        assert_eq!(mock.checkout("viewer", "session-1"), 0);
+    }
+}
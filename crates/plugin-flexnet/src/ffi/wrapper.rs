@@ -9,6 +9,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
 use tracing::{info, warn};
 
 use super::bindings::{LM_SUCCESS, LicenseManagerCtx, LicenseProxyApi};
@@ -24,7 +25,7 @@ pub enum LicenseManagerWrapper {
     /// Real FFI implementation loaded from DLL
     Real(RealLicenseManager),
     /// Mock implementation for development/testing
-    Mock(MockLicenseManager),
+    Mock(Box<MockLicenseManager>),
 }
 
 /// Real license manager backed by `license_proxy.dll`
@@ -96,7 +97,7 @@ impl LicenseManagerWrapper {
 
     /// Create a mock license manager
     pub fn new_mock() -> Self {
-        Self::Mock(MockLicenseManager::new())
+        Self::Mock(Box::default())
     }
 
     /// Create the appropriate implementation based on configuration.
@@ -258,6 +259,66 @@ impl LicenseManagerWrapper {
         }
     }
 
+    /// Renew the lease on a session's checkout.
+    ///
+    /// The real implementation re-issues `LM_CheckOut`, which the license
+    /// server treats as a heartbeat for an existing checkout.
+    pub fn renew(&self, feature: &str, session_id: &str) -> Result<()> {
+        match self {
+            Self::Real(_) => self.checkout(feature, session_id),
+            Self::Mock(mock) => {
+                let res = mock.renew(feature, session_id);
+                if res != 0 {
+                    return Err(anyhow!(
+                        "Mock renew failed for feature='{}', session='{}'",
+                        feature,
+                        session_id
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Borrow a license seat for offline use until `until`.
+    ///
+    /// `license_proxy.dll` has no dedicated borrow call, so the real
+    /// implementation checks out under the borrow token and FileHub
+    /// returns it when the window ends (see `LicenseManager`).
+    pub fn borrow(&self, feature: &str, token: &str, until: DateTime<Utc>) -> Result<()> {
+        match self {
+            Self::Real(_) => self.checkout(feature, token),
+            Self::Mock(mock) => {
+                let res = mock.borrow(feature, token, until);
+                if res != 0 {
+                    return Err(anyhow!(
+                        "Mock borrow failed for feature='{}', token='{}'",
+                        feature,
+                        token
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Return a borrowed seat.
+    pub fn return_borrow(&self, feature: &str, token: &str) -> Result<()> {
+        match self {
+            Self::Real(_) => self.checkin(feature, token),
+            Self::Mock(mock) => {
+                let res = mock.return_borrow(feature, token);
+                if res != 0 {
+                    warn!(
+                        "Mock return warning for feature='{}', token='{}': code {}",
+                        feature, token, res
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
     /// Get the token pool status for a feature.
     ///
     /// Returns `(total_seats, used_seats)`.
@@ -23,8 +23,9 @@ use crate::license::manager::LicenseManager;
 ///
 /// Flow: `login → create session → checkout(feature, session_id)`
 ///
-/// If checkout fails (no seats), returns `HookAction::Halt` which
-/// causes the login to fail and the session to be rolled back.
+/// The optional `role` and `capability` payload fields pick which feature
+/// is checked out. If checkout fails (no seats), returns `HookAction::Halt`
+/// which causes the login to fail and the session to be rolled back.
 #[derive(Debug)]
 pub struct AfterLoginHook {
     /// License manager
@@ -65,6 +66,8 @@ impl SimpleHookHandler for AfterLoginHook {
         };
 
         let ip_address = payload.get_string("ip_address").map(|s| s.to_string());
+        let role = payload.get_string("role");
+        let capability = payload.get_string("capability");
 
         tracing::info!(
             "FlexNet after_login: checkout for user={}, session={}",
@@ -77,12 +80,18 @@ impl SimpleHookHandler for AfterLoginHook {
             .checkout(
                 UserId::from(user_id),
                 SessionId::from(session_id),
+                role,
+                capability,
                 ip_address,
             )
             .await
         {
             Ok(checkout) => {
-                tracing::info!("License checkout successful: session='{}'", session_id);
+                tracing::info!(
+                    "License checkout successful: feature='{}', session='{}'",
+                    checkout.feature_name,
+                    session_id
+                );
                 HookResult::continue_with_output(
                     "flexnet",
                     serde_json::json!({
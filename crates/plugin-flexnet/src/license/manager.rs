@@ -5,10 +5,11 @@
 
 use std::sync::Arc;
 
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 use tracing;
+use uuid::Uuid;
 
 use filehub_core::config::LicenseConfig;
 use filehub_core::error::AppError;
@@ -16,10 +17,14 @@ use filehub_core::types::id::{SessionId, UserId};
 use filehub_database::repositories::license::LicenseCheckoutRepository;
 use filehub_database::repositories::pool_snapshot::PoolSnapshotRepository;
 use filehub_entity::license::model::LicenseCheckout;
-use filehub_entity::license::pool::{PoolSnapshot, PoolStatus};
+use filehub_entity::license::pool::{
+    CreatePoolSnapshot, FeaturePoolStatus, PoolSnapshot, PoolStatus,
+};
 
 use crate::ffi::wrapper::LicenseManagerWrapper;
 
+use super::reservation::{ReservationConfig, ReservationManager};
+
 /// Manages all FlexNet license operations.
 ///
 /// Thread-safe — can be shared across handlers via `Arc<LicenseManager>`.
@@ -29,6 +34,8 @@ pub struct LicenseManager {
     wrapper: Arc<LicenseManagerWrapper>,
     /// License configuration
     config: LicenseConfig,
+    /// Admin seat reservations per feature
+    reservations: ReservationManager,
     /// License checkout repository for DB tracking
     checkout_repo: Arc<LicenseCheckoutRepository>,
     /// Pool snapshot repository
@@ -37,6 +44,15 @@ pub struct LicenseManager {
     cached_status: Arc<RwLock<Option<CachedPoolStatus>>>,
 }
 
+/// Result of one lease renewal pass.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LeaseRenewal {
+    /// Checkouts renewed successfully.
+    pub renewed: usize,
+    /// Checkouts whose renewal failed and whose seat was marked lost.
+    pub lost: Vec<Uuid>,
+}
+
 /// Cached pool status with expiry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct CachedPoolStatus {
@@ -54,9 +70,11 @@ impl LicenseManager {
         checkout_repo: Arc<LicenseCheckoutRepository>,
         snapshot_repo: Arc<PoolSnapshotRepository>,
     ) -> Self {
+        let reservations = ReservationManager::new(ReservationConfig::from_license(&config));
         Self {
             wrapper,
             config,
+            reservations,
             checkout_repo,
             snapshot_repo,
             cached_status: Arc::new(RwLock::new(None)),
@@ -130,15 +148,23 @@ impl LicenseManager {
     ///
     /// Called after session creation during the login flow:
     /// `login → create session → checkout(feature, session_id)`
+    ///
+    /// The feature is picked from the requested capability, then the
+    /// user's role (see [`LicenseConfig::feature_for`]). Non-admin users
+    /// are refused once only the feature's admin-reserved seats remain.
     pub async fn checkout(
         &self,
         user_id: UserId,
         session_id: SessionId,
+        role: Option<&str>,
+        capability: Option<&str>,
         ip_address: Option<String>,
     ) -> Result<LicenseCheckout, AppError> {
-        let feature = &self.config.feature_name;
+        let feature = self.config.feature_for(role, capability);
         let session_id_str = session_id.to_string();
 
+        self.check_reservation(feature, role)?;
+
         tracing::debug!(
             "Checking out license: feature='{}', user={}, session={}",
             feature,
@@ -188,35 +214,70 @@ impl LicenseManager {
         Ok(checkout)
     }
 
+    /// Refuse a non-admin checkout when only admin-reserved seats remain
+    fn check_reservation(&self, feature: &str, role: Option<&str>) -> Result<(), AppError> {
+        if self.reservations.reserved_seats_for(feature) == 0 || self.wrapper.is_star_license() {
+            return Ok(());
+        }
+
+        let (total, used) = self
+            .wrapper
+            .get_token_pool(feature)
+            .map_err(|e| AppError::internal(format!("Failed to get token pool: {}", e)))?;
+
+        if self
+            .reservations
+            .effective_available_for_role(feature, role, total - used)
+            <= 0
+        {
+            return Err(AppError::service_unavailable(format!(
+                "No '{}' license seats available outside the admin reservation",
+                feature
+            )));
+        }
+        Ok(())
+    }
+
     /// Checkin (release) a license for a session.
     ///
     /// Called during logout: `checkin(feature, session_id) → destroy session`
     pub async fn checkin_by_session(&self, session_id: SessionId) -> Result<(), AppError> {
-        let feature = &self.config.feature_name;
         let session_id_str = session_id.to_string();
 
-        tracing::debug!(
-            "Checking in license: feature='{}', session='{}'",
-            feature,
-            session_id_str
-        );
-
-        // Call DLL checkin
-        if let Err(e) = self.wrapper.checkin(feature, &session_id_str) {
-            tracing::warn!(
-                "DLL checkin warning for session '{}': {} — continuing with DB cleanup",
-                session_id_str,
-                e
-            );
-        }
-
-        // Update DB: mark checkout as checked in
         let active_checkouts = self
             .checkout_repo
             .find_active_by_session(session_id.into_uuid())
             .await
             .map_err(|e| AppError::internal(format!("Failed to find session checkouts: {}", e)))?;
 
+        // Without a DB record the checked-out feature is unknown, so release
+        // the session from every feature the DLL may hold it under
+        let features: Vec<&str> = if active_checkouts.is_empty() {
+            self.config.feature_names()
+        } else {
+            active_checkouts
+                .iter()
+                .map(|c| c.feature_name.as_str())
+                .collect()
+        };
+
+        for feature in &features {
+            tracing::debug!(
+                "Checking in license: feature='{}', session='{}'",
+                feature,
+                session_id_str
+            );
+
+            if let Err(e) = self.wrapper.checkin(feature, &session_id_str) {
+                tracing::warn!(
+                    "DLL checkin warning for session '{}': {} — continuing with DB cleanup",
+                    session_id_str,
+                    e
+                );
+            }
+        }
+
+        // Update DB: mark checkout as checked in
         for checkout in &active_checkouts {
             if let Err(e) = self.checkout_repo.checkin(checkout.id).await {
                 tracing::error!("Failed to update checkout record {}: {}", checkout.id, e);
@@ -228,14 +289,191 @@ impl LicenseManager {
         }
 
         tracing::info!(
-            "License checked in: feature='{}', session='{}'",
-            feature,
+            "License checked in: features={:?}, session='{}'",
+            features,
             session_id_str
         );
 
         Ok(())
     }
 
+    /// Renew the lease on every active session checkout.
+    ///
+    /// A checkout whose renewal fails is marked lost: its record is closed
+    /// and the session's seat allocation cleared, so `SeatReconciler`
+    /// reclaims the seat on its next pass.
+    pub async fn renew_leases(&self) -> Result<LeaseRenewal, AppError> {
+        let leases = self.checkout_repo.find_active_leases().await?;
+
+        let mut renewed = Vec::with_capacity(leases.len());
+        let mut lost = Vec::new();
+        for checkout in &leases {
+            let session_id = checkout
+                .session_id
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| checkout.checkout_token.clone());
+
+            match self.wrapper.renew(&checkout.feature_name, &session_id) {
+                Ok(()) => renewed.push(checkout.id),
+                Err(e) => {
+                    tracing::warn!(
+                        "License renewal failed for session '{}', marking seat lost: {}",
+                        session_id,
+                        e
+                    );
+                    if let Err(e) = self.checkout_repo.mark_lost(checkout.id).await {
+                        tracing::error!("Failed to mark checkout {} lost: {}", checkout.id, e);
+                    }
+                    lost.push(checkout.id);
+                }
+            }
+        }
+
+        if !renewed.is_empty() {
+            self.checkout_repo.mark_renewed(&renewed).await?;
+        }
+        if !lost.is_empty() {
+            self.invalidate_cache().await;
+        }
+
+        Ok(LeaseRenewal {
+            renewed: renewed.len(),
+            lost,
+        })
+    }
+
+    /// Borrow a seat for offline use.
+    ///
+    /// The seat is held without a session until [`return_borrow`] is called
+    /// or the borrow window ends, at which point pool sync reclaims it.
+    ///
+    /// [`return_borrow`]: Self::return_borrow
+    pub async fn borrow(
+        &self,
+        feature: &str,
+        user_id: UserId,
+        duration: Duration,
+        ip_address: Option<String>,
+    ) -> Result<LicenseCheckout, AppError> {
+        let borrow = &self.config.borrow;
+        if !borrow.enabled {
+            return Err(AppError::forbidden("License borrowing is disabled"));
+        }
+        if !self.config.has_feature(feature) {
+            return Err(AppError::validation(format!(
+                "Unknown license feature '{}'",
+                feature
+            )));
+        }
+        let max = Duration::hours(borrow.max_borrow_hours as i64);
+        if duration <= Duration::zero() || duration > max {
+            return Err(AppError::validation(format!(
+                "Borrow duration must be between 1 second and {} hours",
+                borrow.max_borrow_hours
+            )));
+        }
+
+        let token = format!("borrow:{}", Uuid::new_v4());
+        let until = Utc::now() + duration;
+
+        self.wrapper
+            .borrow(feature, &token, until)
+            .map_err(|e| AppError::service_unavailable(format!("License borrow failed: {}", e)))?;
+
+        let checkout = self
+            .checkout_repo
+            .create_borrow(
+                user_id.into_uuid(),
+                feature,
+                &token,
+                until,
+                ip_address.as_deref(),
+            )
+            .await
+            .map_err(|e| {
+                tracing::error!("DB borrow record failed, returning DLL borrow: {}", e);
+                if let Err(rollback_err) = self.wrapper.return_borrow(feature, &token) {
+                    tracing::error!("Rollback return also failed: {}", rollback_err);
+                }
+                AppError::internal(format!("Failed to save borrow record: {}", e))
+            })?;
+
+        self.invalidate_cache().await;
+
+        tracing::info!(
+            "License borrowed: feature='{}', user={}, until={}",
+            feature,
+            user_id,
+            until
+        );
+
+        Ok(checkout)
+    }
+
+    /// Return a borrowed seat before its window ends.
+    pub async fn return_borrow(&self, user_id: UserId, checkout_id: Uuid) -> Result<(), AppError> {
+        let checkout = self
+            .checkout_repo
+            .find_by_id(checkout_id)
+            .await?
+            .filter(|c| c.is_borrow() && c.user_id == user_id.into_uuid())
+            .ok_or_else(|| AppError::not_found("Borrow not found"))?;
+
+        if !checkout.is_currently_active() {
+            return Err(AppError::conflict("Borrow has already been returned"));
+        }
+
+        self.release_borrow(&checkout).await?;
+        self.invalidate_cache().await;
+
+        tracing::info!(
+            "License borrow returned: feature='{}', user={}",
+            checkout.feature_name,
+            user_id
+        );
+        Ok(())
+    }
+
+    /// List a user's active borrows.
+    pub async fn active_borrows(&self, user_id: UserId) -> Result<Vec<LicenseCheckout>, AppError> {
+        self.checkout_repo
+            .find_active_borrows_by_user(user_id.into_uuid())
+            .await
+    }
+
+    /// Return every borrow whose window has ended. Returns how many were
+    /// reclaimed.
+    pub async fn reclaim_expired_borrows(&self) -> Result<usize, AppError> {
+        let expired = self.checkout_repo.find_expired_borrows(Utc::now()).await?;
+
+        for checkout in &expired {
+            if let Err(e) = self.release_borrow(checkout).await {
+                tracing::error!("Failed to reclaim borrow {}: {}", checkout.id, e);
+            }
+        }
+
+        if !expired.is_empty() {
+            self.invalidate_cache().await;
+            tracing::info!("Reclaimed {} expired license borrows", expired.len());
+        }
+        Ok(expired.len())
+    }
+
+    /// Release a borrow on the license server and close its record.
+    async fn release_borrow(&self, checkout: &LicenseCheckout) -> Result<(), AppError> {
+        if let Err(e) = self
+            .wrapper
+            .return_borrow(&checkout.feature_name, &checkout.checkout_token)
+        {
+            tracing::warn!(
+                "DLL return warning for borrow '{}': {} — continuing with DB cleanup",
+                checkout.checkout_token,
+                e
+            );
+        }
+        self.checkout_repo.checkin(checkout.id).await
+    }
+
     /// Get the current pool status.
     ///
     /// Returns cached status if within TTL, otherwise queries the DLL.
@@ -256,72 +494,108 @@ impl LicenseManager {
     }
 
     /// Force sync pool status from the DLL.
+    ///
+    /// Queries every configured feature's pool; the returned status holds
+    /// the totals plus a per-feature breakdown.
     pub async fn sync_pool_status(&self) -> Result<PoolStatus, AppError> {
-        let feature = &self.config.feature_name;
-
-        let (total_seats, used_seats) = self
-            .wrapper
-            .get_token_pool(feature)
-            .map_err(|e| AppError::internal(format!("Failed to get token pool: {}", e)))?;
-
         let is_star = self.wrapper.is_star_license();
-        let available = if is_star {
-            i32::MAX
-        } else {
-            total_seats - used_seats
+
+        let db_counts = self
+            .checkout_repo
+            .count_active_by_feature()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to count active checkouts: {}", e)))?;
+        let db_count = |feature: &str| {
+            db_counts
+                .iter()
+                .find(|(f, _, _)| f == feature)
+                .map(|(_, active, borrowed)| (*active as i32, *borrowed as i32))
+                .unwrap_or((0, 0))
         };
 
-        let active_db_sessions =
-            self.checkout_repo.count_active().await.map_err(|e| {
-                AppError::internal(format!("Failed to count active checkouts: {}", e))
+        let mut features = Vec::new();
+        for feature in self.config.feature_names() {
+            let (total_seats, used_seats) = self.wrapper.get_token_pool(feature).map_err(|e| {
+                AppError::internal(format!("Failed to get token pool for '{}': {}", feature, e))
             })?;
+            let (active_sessions, borrowed) = db_count(feature);
+
+            features.push(FeaturePoolStatus {
+                feature: feature.to_string(),
+                total_seats: if is_star { -1 } else { total_seats },
+                checked_out: used_seats,
+                available: if is_star {
+                    i32::MAX
+                } else {
+                    total_seats - used_seats
+                },
+                admin_reserved: self.reservations.reserved_seats_for(feature),
+                borrowed,
+                active_sessions,
+                usage_percent: if is_star || total_seats == 0 {
+                    0.0
+                } else {
+                    (used_seats as f64 / total_seats as f64) * 100.0
+                },
+            });
+        }
 
-        let admin_reserved = if self.config.pool.admin_reserved_enabled {
-            self.config.pool.admin_reserved_seats as i32
-        } else {
-            0
+        let sum = |field: fn(&FeaturePoolStatus) -> i32| -> i32 {
+            features.iter().map(field).fold(0, i32::saturating_add)
         };
+        let total_seats = sum(|f| f.total_seats);
+        let used_seats = sum(|f| f.checked_out);
+        let active_db_sessions = sum(|f| f.active_sessions);
 
-        let drift_detected = !is_star && (used_seats - active_db_sessions as i32).abs() > 0;
+        let drift_detected =
+            !is_star && features.iter().any(|f| f.checked_out != f.active_sessions);
         let drift_detail = if drift_detected {
             Some(serde_json::json!({
                 "dll_used": used_seats,
                 "db_active": active_db_sessions,
-                "difference": used_seats - active_db_sessions as i32,
+                "difference": used_seats - active_db_sessions,
+                "features": features
+                    .iter()
+                    .filter(|f| f.checked_out != f.active_sessions)
+                    .map(|f| serde_json::json!({
+                        "feature": f.feature,
+                        "dll_used": f.checked_out,
+                        "db_active": f.active_sessions,
+                    }))
+                    .collect::<Vec<_>>(),
             }))
         } else {
             None
         };
 
-        let usage_percent = if is_star || total_seats == 0 {
-            0.0
-        } else {
-            (used_seats as f64 / total_seats as f64) * 100.0
-        };
-
         let status = PoolStatus {
             total_seats: if is_star { -1 } else { total_seats },
             checked_out: used_seats,
-            available,
-            admin_reserved,
-            active_sessions: active_db_sessions as i32,
+            available: if is_star {
+                i32::MAX
+            } else {
+                sum(|f| f.available)
+            },
+            admin_reserved: sum(|f| f.admin_reserved),
+            borrowed: sum(|f| f.borrowed),
+            active_sessions: active_db_sessions,
             drift_detected,
-            usage_percent,
+            usage_percent: if is_star || total_seats == 0 {
+                0.0
+            } else {
+                (used_seats as f64 / total_seats as f64) * 100.0
+            },
+            features,
         };
 
         // Save snapshot
         let _ = self
             .snapshot_repo
-            .create(
-                status.total_seats,
-                status.checked_out,
-                status.available,
-                status.admin_reserved,
-                status.active_sessions,
-                status.drift_detected,
-                drift_detail.as_ref(),
+            .create(&CreatePoolSnapshot::from_status(
+                &status,
+                drift_detail,
                 "sync",
-            )
+            ))
             .await;
 
         // Update cache
@@ -342,13 +616,7 @@ impl LicenseManager {
     pub async fn reconcile(&self) -> Result<PoolStatus, AppError> {
         tracing::info!("Starting pool reconciliation");
 
-        let feature = &self.config.feature_name;
-
-        // Get DLL state
-        let (_, used) = self
-            .wrapper
-            .get_token_pool(feature)
-            .map_err(|e| AppError::internal(format!("Failed to get DLL pool state: {}", e)))?;
+        self.reclaim_expired_borrows().await?;
 
         // Get DB state
         let active_checkouts =
@@ -356,12 +624,35 @@ impl LicenseManager {
                 AppError::internal(format!("Failed to find active checkouts: {}", e))
             })?;
 
-        let db_count = active_checkouts.len() as i32;
-        let drift = used - db_count;
+        for feature in self.config.feature_names() {
+            // Get DLL state
+            let (_, used) = self.wrapper.get_token_pool(feature).map_err(|e| {
+                AppError::internal(format!(
+                    "Failed to get DLL pool state for '{}': {}",
+                    feature, e
+                ))
+            })?;
+
+            let feature_checkouts: Vec<&LicenseCheckout> = active_checkouts
+                .iter()
+                .filter(|c| c.feature_name == feature)
+                .collect();
+            let db_count = feature_checkouts.len() as i32;
+            let drift = used - db_count;
+
+            if drift == 0 {
+                tracing::info!(
+                    "Pool reconciliation: no drift detected for '{}' (DLL={}, DB={})",
+                    feature,
+                    used,
+                    db_count
+                );
+                continue;
+            }
 
-        if drift != 0 {
             tracing::warn!(
-                "Pool drift detected: DLL reports {} used, DB has {} active (drift: {})",
+                "Pool drift detected for '{}': DLL reports {} used, DB has {} active (drift: {})",
+                feature,
                 used,
                 db_count,
                 drift
@@ -371,14 +662,15 @@ impl LicenseManager {
                 // DB has more checkouts than DLL — orphaned DB records
                 // These sessions may have been released by the DLL without DB update
                 tracing::info!(
-                    "Found {} orphaned DB checkout records, cleaning up",
-                    drift.abs()
+                    "Found {} orphaned DB checkout records for '{}', cleaning up",
+                    drift.abs(),
+                    feature
                 );
 
                 // We can't easily tell which DB records are orphaned without
                 // querying the DLL per-session, so we re-checkout all DB sessions
                 // to ensure consistency
-                for checkout in &active_checkouts {
+                for checkout in feature_checkouts {
                     let session_id_str = checkout
                         .session_id
                         .map(|s| s.to_string())
@@ -399,12 +691,6 @@ impl LicenseManager {
                     }
                 }
             }
-        } else {
-            tracing::info!(
-                "Pool reconciliation: no drift detected (DLL={}, DB={})",
-                used,
-                db_count
-            );
         }
 
         // Force fresh sync
@@ -441,11 +727,21 @@ impl LicenseManager {
         self.wrapper.get_server_info()
     }
 
-    /// Get the feature name from config
+    /// Get the default feature name from config
     pub fn feature_name(&self) -> &str {
         &self.config.feature_name
     }
 
+    /// Get every configured feature name, the default first
+    pub fn feature_names(&self) -> Vec<&str> {
+        self.config.feature_names()
+    }
+
+    /// Get the admin seat reservations
+    pub fn reservations(&self) -> &ReservationManager {
+        &self.reservations
+    }
+
     /// Release all licenses (emergency shutdown)
     pub fn release_all(&self) {
         self.wrapper.release_all();
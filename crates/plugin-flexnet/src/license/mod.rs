@@ -2,8 +2,10 @@
 
 pub mod manager;
 pub mod pool;
+pub mod renewal;
 pub mod reservation;
 
 pub use manager::LicenseManager;
 pub use pool::PoolSyncService;
+pub use renewal::LeaseRenewalService;
 pub use reservation::ReservationManager;
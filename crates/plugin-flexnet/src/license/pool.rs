@@ -9,7 +9,11 @@ use tracing;
 
 use super::manager::LicenseManager;
 
-/// Service that periodically syncs pool status from the DLL
+/// Service that periodically syncs every feature's pool status from the DLL
+/// and reclaims expired license borrows.
+///
+/// The latest status, with its per-feature breakdown, is served from the
+/// manager's cache via `LicenseManager::pool_status`.
 #[derive(Debug)]
 pub struct PoolSyncService {
     /// License manager reference
@@ -39,14 +43,37 @@ impl PoolSyncService {
         loop {
             tokio::select! {
                 _ = interval.tick() => {
+                    if let Err(e) = self.manager.reclaim_expired_borrows().await {
+                        tracing::error!("Borrow reclaim failed: {}", e);
+                    }
                     match self.manager.sync_pool_status().await {
                         Ok(status) => {
                             tracing::trace!(
-                                "Pool sync: total={}, used={}, available={}",
+                                "Pool sync: total={}, used={}, borrowed={}, available={}",
                                 status.total_seats,
                                 status.checked_out,
+                                status.borrowed,
                                 status.available
                             );
+                            let critical = self.manager.critical_threshold_percent() as f64;
+                            for feature in &status.features {
+                                tracing::trace!(
+                                    "Pool sync '{}': total={}, used={}, available={}",
+                                    feature.feature,
+                                    feature.total_seats,
+                                    feature.checked_out,
+                                    feature.available
+                                );
+                                if feature.usage_percent >= critical {
+                                    tracing::warn!(
+                                        "License feature '{}' at {:.1}% utilization ({}/{})",
+                                        feature.feature,
+                                        feature.usage_percent,
+                                        feature.checked_out,
+                                        feature.total_seats
+                                    );
+                                }
+                            }
                         }
                         Err(e) => {
                             tracing::error!("Pool sync failed: {}", e);
@@ -0,0 +1,67 @@
+//! Checkout lease renewal service.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tokio::time;
+use tracing;
+
+use super::manager::LicenseManager;
+
+/// Service that periodically renews active checkout leases so they do not
+/// lapse on the license server during long sessions
+#[derive(Debug)]
+pub struct LeaseRenewalService {
+    /// License manager reference
+    manager: Arc<LicenseManager>,
+    /// Renewal interval
+    interval: Duration,
+}
+
+impl LeaseRenewalService {
+    /// Create a new lease renewal service
+    pub fn new(manager: Arc<LicenseManager>, interval: Duration) -> Self {
+        Self { manager, interval }
+    }
+
+    /// Start the renewal loop (runs until cancelled)
+    pub async fn run(&self, mut cancel: watch::Receiver<bool>) {
+        tracing::info!(
+            "Lease renewal service started, interval={}s",
+            self.interval.as_secs()
+        );
+
+        let mut interval = time::interval(self.interval);
+        // The first tick fires immediately; checkouts were just made.
+        interval.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    match self.manager.renew_leases().await {
+                        Ok(result) if !result.lost.is_empty() => {
+                            tracing::warn!(
+                                "Lease renewal: renewed={}, lost={}",
+                                result.renewed,
+                                result.lost.len()
+                            );
+                        }
+                        Ok(result) => {
+                            tracing::trace!("Lease renewal: renewed={}", result.renewed);
+                        }
+                        Err(e) => {
+                            tracing::error!("Lease renewal failed: {}", e);
+                        }
+                    }
+                }
+                _ = cancel.changed() => {
+                    if *cancel.borrow() {
+                        tracing::info!("Lease renewal service shutting down");
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,8 +1,12 @@
 //! Admin seat reservation management.
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 use tracing;
 
+use filehub_core::config::LicenseConfig;
+
 /// Configuration for admin seat reservations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReservationConfig {
@@ -10,12 +14,31 @@ pub struct ReservationConfig {
     pub enabled: bool,
     /// Number of seats reserved for admins
     pub reserved_seats: i32,
+    /// Per-feature overrides of `reserved_seats`
+    #[serde(default)]
+    pub feature_seats: HashMap<String, i32>,
+}
+
+impl ReservationConfig {
+    /// Build the reservation config for every feature in a license config
+    pub fn from_license(config: &LicenseConfig) -> Self {
+        Self {
+            enabled: config.pool.admin_reserved_enabled,
+            reserved_seats: config.pool.admin_reserved_seats as i32,
+            feature_seats: config
+                .feature_names()
+                .into_iter()
+                .map(|f| (f.to_string(), config.admin_reserved_seats(f) as i32))
+                .collect(),
+        }
+    }
 }
 
 /// Manages admin seat reservations.
 ///
-/// When enabled, a number of seats are reserved exclusively for admin users.
-/// Non-admin users cannot checkout if only reserved seats remain.
+/// When enabled, a number of seats in each feature's pool are reserved
+/// exclusively for admin users. Non-admin users cannot checkout if only
+/// reserved seats remain.
 #[derive(Debug, Clone)]
 pub struct ReservationManager {
     /// Reservation configuration
@@ -42,6 +65,19 @@ impl ReservationManager {
         }
     }
 
+    /// Get the number of reserved seats in a feature's pool
+    pub fn reserved_seats_for(&self, feature: &str) -> i32 {
+        if self.config.enabled {
+            self.config
+                .feature_seats
+                .get(feature)
+                .copied()
+                .unwrap_or(self.config.reserved_seats)
+        } else {
+            0
+        }
+    }
+
     /// Calculate effective available seats for non-admin users.
     ///
     /// Subtracts reserved seats from the total available.
@@ -58,6 +94,23 @@ impl ReservationManager {
         total_available
     }
 
+    /// Calculate effective available seats in a feature's pool for a role.
+    ///
+    /// Admins may use reserved seats; every other role (or an unknown one)
+    /// may not.
+    pub fn effective_available_for_role(
+        &self,
+        feature: &str,
+        role: Option<&str>,
+        total_available: i32,
+    ) -> i32 {
+        if role.is_some_and(|r| r.eq_ignore_ascii_case("admin")) {
+            self.effective_available_for_admin(total_available)
+        } else {
+            (total_available - self.reserved_seats_for(feature)).max(0)
+        }
+    }
+
     /// Update reservation configuration
     pub fn update_config(&mut self, config: ReservationConfig) {
         tracing::info!(
@@ -19,6 +19,7 @@ use crate::hooks::{
 };
 use crate::license::manager::LicenseManager;
 use crate::license::pool::PoolSyncService;
+use crate::license::renewal::LeaseRenewalService;
 
 /// Plugin name used for registration, logging, and hook results.
 const PLUGIN_NAME: &str = "flexnet";
@@ -31,7 +32,7 @@ const PLUGIN_VERSION: &str = env!("CARGO_PKG_VERSION");
 pub struct FlexNetPlugin {
     /// License manager (set after initialization)
     manager: Arc<tokio::sync::RwLock<Option<Arc<LicenseManager>>>>,
-    /// Pool sync cancellation sender
+    /// Pool sync and lease renewal cancellation sender
     pool_sync_cancel: Arc<tokio::sync::RwLock<Option<tokio::sync::watch::Sender<bool>>>>,
 }
 
@@ -48,8 +49,9 @@ impl FlexNetPlugin {
 impl FlexNetPlugin {
     /// Initialize the plugin.
     ///
-    /// Loads the DLL (or mock), creates the license manager, starts pool sync,
-    /// and returns the license manager for use by other components.
+    /// Loads the DLL (or mock), creates the license manager, starts pool sync
+    /// and lease renewal, and returns the license manager for use by other
+    /// components.
     pub async fn initialize(
         &self,
         config: LicenseConfig,
@@ -73,7 +75,9 @@ impl FlexNetPlugin {
             tracing::warn!("FlexNet plugin using MOCK implementation");
             // Configure mock with default seats
             #[cfg(feature = "mock")]
-            wrapper.as_mock().set_total_seats(&config.feature_name, 10);
+            for feature in config.feature_names() {
+                wrapper.as_mock().set_total_seats(feature, 10);
+            }
         }
 
         // Create the license manager
@@ -92,9 +96,16 @@ impl FlexNetPlugin {
         let sync_service =
             PoolSyncService::new(Arc::clone(&manager), config.pool.refresh_interval_seconds);
 
+        let renewal_service =
+            LeaseRenewalService::new(Arc::clone(&manager), config.lease.renew_interval());
+        let renewal_cancel = rx.clone();
+
         tokio::spawn(async move {
             sync_service.run(rx).await;
         });
+        tokio::spawn(async move {
+            renewal_service.run(renewal_cancel).await;
+        });
 
         let mut cancel = self.pool_sync_cancel.write().await;
         *cancel = Some(tx);
@@ -0,0 +1,4 @@
+-- Revert: users
+DROP TABLE IF EXISTS users;
+DROP TYPE IF EXISTS user_status;
+DROP TYPE IF EXISTS user_role;
@@ -0,0 +1,3 @@
+-- Revert: sessions
+DROP TABLE IF EXISTS sessions;
+DROP TYPE IF EXISTS presence_status;
@@ -0,0 +1,4 @@
+-- Revert: storages
+DROP TABLE IF EXISTS storages;
+DROP TYPE IF EXISTS storage_status;
+DROP TYPE IF EXISTS storage_provider_type;
@@ -0,0 +1,9 @@
+-- Revert: folders, files, versions, chunked uploads and ACL entries
+DROP TABLE IF EXISTS acl_entries;
+DROP TYPE IF EXISTS acl_inheritance;
+DROP TYPE IF EXISTS acl_permission;
+DROP TYPE IF EXISTS resource_type;
+DROP TABLE IF EXISTS chunked_uploads;
+DROP TABLE IF EXISTS file_versions;
+DROP TABLE IF EXISTS files;
+DROP TABLE IF EXISTS folders;
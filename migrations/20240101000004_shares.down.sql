@@ -0,0 +1,3 @@
+-- Revert: shares
+DROP TABLE IF EXISTS shares;
+DROP TYPE IF EXISTS share_type;
@@ -0,0 +1,4 @@
+-- Revert: jobs
+DROP TABLE IF EXISTS jobs;
+DROP TYPE IF EXISTS job_priority;
+DROP TYPE IF EXISTS job_status;
@@ -0,0 +1,2 @@
+-- Revert: license checkouts
+DROP TABLE IF EXISTS license_checkouts;
@@ -0,0 +1,2 @@
+-- Revert: notifications
+DROP TABLE IF EXISTS notifications;
@@ -0,0 +1,2 @@
+-- Revert: notification preferences
+DROP TABLE IF EXISTS notification_preferences;
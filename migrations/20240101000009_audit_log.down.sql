@@ -0,0 +1,2 @@
+-- Revert: audit log
+DROP TABLE IF EXISTS audit_log;
@@ -0,0 +1,2 @@
+-- Revert: admin broadcasts
+DROP TABLE IF EXISTS admin_broadcasts;
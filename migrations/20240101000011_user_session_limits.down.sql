@@ -0,0 +1,2 @@
+-- Revert: per-user session limits
+DROP TABLE IF EXISTS user_session_limits;
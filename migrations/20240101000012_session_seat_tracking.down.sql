@@ -0,0 +1,2 @@
+-- Revert: pool snapshots
+DROP TABLE IF EXISTS pool_snapshots;
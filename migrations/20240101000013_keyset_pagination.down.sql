@@ -0,0 +1,4 @@
+-- Revert: keyset pagination indexes
+DROP INDEX IF EXISTS idx_notif_user_created_id;
+DROP INDEX IF EXISTS idx_folders_parent_name_id;
+DROP INDEX IF EXISTS idx_files_folder_name_id;
@@ -0,0 +1,4 @@
+-- Composite indexes backing keyset (cursor) pagination.
+CREATE INDEX IF NOT EXISTS idx_files_folder_name_id ON files(folder_id, name, id);
+CREATE INDEX IF NOT EXISTS idx_folders_parent_name_id ON folders(parent_id, name, id);
+CREATE INDEX IF NOT EXISTS idx_notif_user_created_id ON notifications(user_id, created_at DESC, id DESC);
@@ -0,0 +1,10 @@
+-- Revert: ranked file search. The pg_trgm extension is left installed.
+DROP INDEX IF EXISTS idx_files_name_trgm;
+DROP INDEX IF EXISTS idx_files_search_vector;
+DROP TRIGGER IF EXISTS trg_files_search_vector ON files;
+DROP FUNCTION IF EXISTS files_search_vector_update();
+ALTER TABLE files DROP COLUMN IF EXISTS search_vector;
+
+CREATE INDEX IF NOT EXISTS idx_files_search ON files USING gin(
+    to_tsvector('english', name || ' ' || COALESCE(metadata->>'description', ''))
+);
@@ -0,0 +1,29 @@
+-- Ranked full-text search over file names and metadata, plus trigram
+-- matching for partial file names.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+ALTER TABLE files ADD COLUMN IF NOT EXISTS search_vector tsvector;
+
+CREATE OR REPLACE FUNCTION files_search_vector_update() RETURNS trigger AS $$
+BEGIN
+    NEW.search_vector :=
+        setweight(to_tsvector('english', COALESCE(NEW.name, '')), 'A') ||
+        -- Split "quarterly_report-v2.pdf" into words as well.
+        setweight(to_tsvector('simple', regexp_replace(COALESCE(NEW.name, ''), '[._-]+', ' ', 'g')), 'A') ||
+        setweight(to_tsvector('english', COALESCE(NEW.metadata->>'description', '')), 'B') ||
+        setweight(jsonb_to_tsvector('english', COALESCE(NEW.metadata, '{}'::jsonb), '["string"]'), 'C');
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_files_search_vector ON files;
+CREATE TRIGGER trg_files_search_vector
+    BEFORE INSERT OR UPDATE OF name, metadata ON files
+    FOR EACH ROW EXECUTE FUNCTION files_search_vector_update();
+
+-- Backfill existing rows through the trigger.
+UPDATE files SET name = name;
+
+DROP INDEX IF EXISTS idx_files_search;
+CREATE INDEX IF NOT EXISTS idx_files_search_vector ON files USING gin(search_vector);
+CREATE INDEX IF NOT EXISTS idx_files_name_trgm ON files USING gin(name gin_trgm_ops);
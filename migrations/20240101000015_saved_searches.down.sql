@@ -0,0 +1,2 @@
+-- Revert: saved searches
+DROP TABLE IF EXISTS saved_searches;
@@ -0,0 +1,11 @@
+-- Named, re-runnable file searches
+CREATE TABLE IF NOT EXISTS saved_searches (
+    id              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    owner_id        UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name            VARCHAR(255) NOT NULL,
+    query           TEXT NOT NULL DEFAULT '',
+    filter          JSONB,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT saved_searches_owner_name_key UNIQUE (owner_id, name)
+);
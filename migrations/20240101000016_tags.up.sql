@@ -0,0 +1,19 @@
+-- Per-user file tags
+CREATE TABLE IF NOT EXISTS tags (
+    id              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    owner_id        UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name            VARCHAR(64) NOT NULL,
+    normalized_name VARCHAR(64) NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT tags_owner_normalized_name_key UNIQUE (owner_id, normalized_name)
+);
+
+CREATE TABLE IF NOT EXISTS file_tags (
+    file_id         UUID NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+    tag_id          UUID NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (file_id, tag_id)
+);
+
+-- The primary key serves file -> tags; this serves tag -> files.
+CREATE INDEX IF NOT EXISTS idx_file_tags_tag ON file_tags(tag_id, file_id);
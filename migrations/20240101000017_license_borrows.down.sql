@@ -0,0 +1,3 @@
+-- Revert: offline license borrowing. Active borrows lose their window.
+DROP INDEX IF EXISTS idx_lic_borrowed;
+ALTER TABLE license_checkouts DROP COLUMN IF EXISTS borrowed_until;
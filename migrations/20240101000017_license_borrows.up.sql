@@ -0,0 +1,7 @@
+-- Offline license borrowing: a borrowed checkout has no session and
+-- holds its seat until returned or borrowed_until passes.
+ALTER TABLE license_checkouts
+    ADD COLUMN IF NOT EXISTS borrowed_until TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_lic_borrowed ON license_checkouts(borrowed_until)
+    WHERE is_active = TRUE AND borrowed_until IS NOT NULL;
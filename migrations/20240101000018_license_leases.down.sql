@@ -0,0 +1,4 @@
+-- Revert: checkout lease tracking
+ALTER TABLE license_checkouts
+    DROP COLUMN IF EXISTS lost_at,
+    DROP COLUMN IF EXISTS renewed_at;
@@ -0,0 +1,5 @@
+-- License lease renewal: when each checkout was last renewed, and when a
+-- failed renewal caused its seat to be marked lost.
+ALTER TABLE license_checkouts
+    ADD COLUMN IF NOT EXISTS renewed_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS lost_at TIMESTAMPTZ;
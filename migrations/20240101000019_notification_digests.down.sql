@@ -0,0 +1,2 @@
+-- Revert: notification digests
+DROP TABLE IF EXISTS notification_digests;
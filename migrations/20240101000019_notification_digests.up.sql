@@ -0,0 +1,21 @@
+-- Notification digests: batchable notifications of one type and scope are
+-- counted here during the recipient's digest window, then flushed as a
+-- single notification.
+CREATE TABLE IF NOT EXISTS notification_digests (
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    category        VARCHAR(50) NOT NULL,
+    event_type      VARCHAR(100) NOT NULL,
+    scope_type      VARCHAR(50) NOT NULL,
+    scope_id        UUID NOT NULL,
+    scope_name      VARCHAR(255),
+    item_count      INTEGER NOT NULL DEFAULT 1,
+    last_title      VARCHAR(255) NOT NULL,
+    last_message    TEXT NOT NULL,
+    last_actor_id   UUID REFERENCES users(id) ON DELETE SET NULL,
+    first_at        TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_at         TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    flush_at        TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (user_id, event_type, scope_type, scope_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_digests_flush ON notification_digests(flush_at);
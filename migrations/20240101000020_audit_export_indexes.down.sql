@@ -0,0 +1,8 @@
+-- Revert: audit export indexes
+CREATE INDEX IF NOT EXISTS idx_audit_actor ON audit_log(actor_id);
+CREATE INDEX IF NOT EXISTS idx_audit_target ON audit_log(target_type, target_id);
+CREATE INDEX IF NOT EXISTS idx_audit_action ON audit_log(action);
+
+DROP INDEX IF EXISTS idx_audit_actor_time;
+DROP INDEX IF EXISTS idx_audit_action_time;
+DROP INDEX IF EXISTS idx_audit_target_time;
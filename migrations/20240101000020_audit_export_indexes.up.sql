@@ -0,0 +1,10 @@
+-- Audit export: filters are combined with a date range and results are
+-- ordered by time, so index each filter column together with created_at.
+-- These replace the single-column indexes they extend.
+CREATE INDEX IF NOT EXISTS idx_audit_actor_time ON audit_log(actor_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_audit_action_time ON audit_log(action, created_at);
+CREATE INDEX IF NOT EXISTS idx_audit_target_time ON audit_log(target_type, target_id, created_at);
+
+DROP INDEX IF EXISTS idx_audit_actor;
+DROP INDEX IF EXISTS idx_audit_action;
+DROP INDEX IF EXISTS idx_audit_target;
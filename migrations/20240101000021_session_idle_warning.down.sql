@@ -0,0 +1,3 @@
+-- Revert: session idle warning
+DROP INDEX IF EXISTS idx_sessions_idle_warned;
+ALTER TABLE sessions DROP COLUMN IF EXISTS idle_warned_at;
@@ -0,0 +1,6 @@
+-- Idle sessions are warned before they are terminated; activity clears the
+-- warning.
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS idle_warned_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_sessions_idle_warned ON sessions(idle_warned_at)
+    WHERE idle_warned_at IS NOT NULL AND terminated_at IS NULL;
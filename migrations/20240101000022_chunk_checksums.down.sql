@@ -0,0 +1,2 @@
+-- Revert: chunk checksums
+ALTER TABLE chunked_uploads DROP COLUMN IF EXISTS chunk_checksums;
@@ -0,0 +1,5 @@
+-- SHA-256 of every received chunk, keyed by chunk number, so a resumed
+-- upload can tell a retried chunk from a conflicting one and assembly can
+-- verify what it reads back.
+ALTER TABLE chunked_uploads
+    ADD COLUMN IF NOT EXISTS chunk_checksums JSONB NOT NULL DEFAULT '{}';
@@ -0,0 +1,3 @@
+-- Revert: share accesses
+DROP TABLE IF EXISTS share_accesses;
+DROP TYPE IF EXISTS share_access_kind;
@@ -0,0 +1,20 @@
+-- One row per public access to a share link, for the owner's analytics.
+-- IP address and country are NULL when privacy settings disable them.
+DO $$ BEGIN
+    CREATE TYPE share_access_kind AS ENUM ('view', 'download');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+CREATE TABLE IF NOT EXISTS share_accesses (
+    id          UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    share_id    UUID NOT NULL REFERENCES shares(id) ON DELETE CASCADE,
+    kind        share_access_kind NOT NULL,
+    accessed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ip_address  TEXT,
+    user_agent  TEXT,
+    country     VARCHAR(2)
+);
+
+CREATE INDEX IF NOT EXISTS idx_share_accesses_share_time
+    ON share_accesses(share_id, accessed_at DESC);
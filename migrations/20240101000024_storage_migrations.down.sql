@@ -0,0 +1,4 @@
+-- Revert: storage migrations
+DROP INDEX IF EXISTS idx_files_storage_id_id;
+DROP TABLE IF EXISTS storage_migrations;
+DROP TYPE IF EXISTS storage_migration_status;
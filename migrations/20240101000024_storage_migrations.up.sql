@@ -0,0 +1,45 @@
+-- Background migration of files from one storage backend to another.
+-- `cursor_file_id` is the last file visited in the current pass, so an
+-- interrupted migration resumes where it stopped. Files skipped or failed
+-- in a pass stay on the source and are picked up by the next pass.
+DO $$ BEGIN
+    CREATE TYPE storage_migration_status AS ENUM
+        ('pending', 'running', 'completed', 'failed', 'cancelled');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+CREATE TABLE IF NOT EXISTS storage_migrations (
+    id                UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    source_storage_id UUID NOT NULL REFERENCES storages(id) ON DELETE CASCADE,
+    target_storage_id UUID NOT NULL REFERENCES storages(id) ON DELETE CASCADE,
+    status            storage_migration_status NOT NULL DEFAULT 'pending',
+    batch_size        INTEGER NOT NULL,
+    bytes_per_second  BIGINT NOT NULL DEFAULT 0,
+    pass              INTEGER NOT NULL DEFAULT 1,
+    cursor_file_id    UUID,
+    files_total       BIGINT NOT NULL DEFAULT 0,
+    bytes_total       BIGINT NOT NULL DEFAULT 0,
+    files_copied      BIGINT NOT NULL DEFAULT 0,
+    bytes_copied      BIGINT NOT NULL DEFAULT 0,
+    files_skipped     BIGINT NOT NULL DEFAULT 0,
+    files_failed      BIGINT NOT NULL DEFAULT 0,
+    last_error        TEXT,
+    created_by        UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at        TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at        TIMESTAMPTZ,
+    completed_at      TIMESTAMPTZ,
+    updated_at        TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (source_storage_id <> target_storage_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_storage_migrations_created
+    ON storage_migrations(created_at DESC);
+
+-- At most one unfinished migration out of a storage at a time.
+CREATE UNIQUE INDEX IF NOT EXISTS idx_storage_migrations_active_source
+    ON storage_migrations(source_storage_id)
+    WHERE status IN ('pending', 'running');
+
+-- Paging through a storage's files by id.
+CREATE INDEX IF NOT EXISTS idx_files_storage_id_id ON files(storage_id, id);
@@ -0,0 +1,7 @@
+-- Revert: tenants
+ALTER TABLE shares DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE files DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE folders DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE storages DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE users DROP COLUMN IF EXISTS tenant_id;
+DROP TABLE IF EXISTS tenants;
@@ -0,0 +1,4 @@
+-- Revert: file_quarantine
+DROP INDEX IF EXISTS idx_files_quarantined;
+ALTER TABLE files DROP COLUMN IF EXISTS quarantine_reason;
+ALTER TABLE files DROP COLUMN IF EXISTS quarantined_at;
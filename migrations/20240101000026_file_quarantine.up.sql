@@ -0,0 +1,8 @@
+-- Files the virus scanner flagged. A quarantined file's content has been
+-- moved under the storage's quarantine directory and it can no longer be
+-- downloaded.
+ALTER TABLE files ADD COLUMN IF NOT EXISTS quarantined_at TIMESTAMPTZ;
+ALTER TABLE files ADD COLUMN IF NOT EXISTS quarantine_reason TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_files_quarantined
+    ON files(quarantined_at) WHERE quarantined_at IS NOT NULL;
@@ -0,0 +1,3 @@
+-- Revert: login_locations
+DROP INDEX IF EXISTS idx_login_locations_user;
+DROP TABLE IF EXISTS login_locations;
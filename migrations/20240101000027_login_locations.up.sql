@@ -0,0 +1,16 @@
+-- Where users logged in from, resolved with GeoIP at login time. Used to
+-- spot logins from a new country or after impossible travel.
+CREATE TABLE IF NOT EXISTS login_locations (
+    id            UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id       UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    session_id    UUID,
+    ip_address    INET NOT NULL,
+    country_code  VARCHAR(2) NOT NULL,
+    region        VARCHAR(255),
+    latitude      DOUBLE PRECISION,
+    longitude     DOUBLE PRECISION,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_login_locations_user
+    ON login_locations(user_id, created_at DESC);
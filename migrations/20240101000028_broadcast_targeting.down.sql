@@ -0,0 +1,8 @@
+-- Revert: broadcast_targeting
+DROP TABLE IF EXISTS broadcast_receipts;
+DROP INDEX IF EXISTS idx_admin_broadcasts_live;
+ALTER TABLE admin_broadcasts DROP COLUMN IF EXISTS expires_at;
+ALTER TABLE admin_broadcasts DROP COLUMN IF EXISTS sent_at;
+ALTER TABLE admin_broadcasts DROP COLUMN IF EXISTS scheduled_at;
+ALTER TABLE admin_broadcasts DROP COLUMN IF EXISTS require_ack;
+ALTER TABLE admin_broadcasts DROP COLUMN IF EXISTS target_user_ids;
@@ -0,0 +1,21 @@
+-- Targeted, scheduled and acknowledged admin broadcasts. `target` is
+-- 'all', 'role:<role>' or 'users' (with `target_user_ids`). A broadcast is
+-- delivered at `scheduled_at` (immediately when NULL) and stays pending
+-- for users who connect before `expires_at`.
+ALTER TABLE admin_broadcasts ADD COLUMN IF NOT EXISTS target_user_ids UUID[];
+ALTER TABLE admin_broadcasts ADD COLUMN IF NOT EXISTS require_ack BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE admin_broadcasts ADD COLUMN IF NOT EXISTS scheduled_at TIMESTAMPTZ;
+ALTER TABLE admin_broadcasts ADD COLUMN IF NOT EXISTS sent_at TIMESTAMPTZ;
+ALTER TABLE admin_broadcasts ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_admin_broadcasts_live
+    ON admin_broadcasts(expires_at) WHERE sent_at IS NOT NULL;
+
+-- Which users a broadcast reached, and which acknowledged it.
+CREATE TABLE IF NOT EXISTS broadcast_receipts (
+    broadcast_id    UUID NOT NULL REFERENCES admin_broadcasts(id) ON DELETE CASCADE,
+    user_id         UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    delivered_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    acknowledged_at TIMESTAMPTZ,
+    PRIMARY KEY (broadcast_id, user_id)
+);
@@ -0,0 +1,2 @@
+-- Revert: direct uploads
+ALTER TABLE chunked_uploads DROP COLUMN IF EXISTS direct;
@@ -0,0 +1,5 @@
+-- Uploads sent straight to the storage backend through a presigned URL.
+-- Such a session has no chunks; temp_path holds the object's final path
+-- and the object is deleted if the session expires unfinalized.
+ALTER TABLE chunked_uploads
+    ADD COLUMN IF NOT EXISTS direct BOOLEAN NOT NULL DEFAULT FALSE;
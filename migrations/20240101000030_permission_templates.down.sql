@@ -0,0 +1,4 @@
+-- Revert: permission_templates
+DROP INDEX IF EXISTS idx_folders_permission_template;
+ALTER TABLE folders DROP COLUMN IF EXISTS permission_template_id;
+DROP TABLE IF EXISTS permission_templates;
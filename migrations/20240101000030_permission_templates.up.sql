@@ -0,0 +1,18 @@
+-- Named, reusable sets of ACL entries. `entries` is a JSON array of
+-- {user_id, is_anyone, permission, inheritance}. A template attached to a
+-- folder is applied to every folder created directly inside it.
+CREATE TABLE IF NOT EXISTS permission_templates (
+    id          UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name        VARCHAR(255) NOT NULL UNIQUE,
+    description TEXT,
+    entries     JSONB NOT NULL DEFAULT '[]',
+    created_by  UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE folders ADD COLUMN IF NOT EXISTS permission_template_id UUID
+    REFERENCES permission_templates(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_folders_permission_template
+    ON folders(permission_template_id) WHERE permission_template_id IS NOT NULL;
@@ -0,0 +1,4 @@
+-- Revert: pool_snapshot_features
+ALTER TABLE pool_snapshots DROP COLUMN IF EXISTS features;
+ALTER TABLE pool_snapshots DROP COLUMN IF EXISTS drift;
+ALTER TABLE pool_snapshots DROP COLUMN IF EXISTS borrowed;
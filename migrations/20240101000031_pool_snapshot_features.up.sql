@@ -0,0 +1,7 @@
+-- Per-feature seat counts and the allocator/database drift in pool
+-- snapshots. `features` is a JSON array of {feature, total_seats,
+-- checked_out, available, borrowed, active_sessions}; `drift` is the
+-- allocator's checked-out count minus the database's active sessions.
+ALTER TABLE pool_snapshots ADD COLUMN IF NOT EXISTS borrowed INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE pool_snapshots ADD COLUMN IF NOT EXISTS drift INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE pool_snapshots ADD COLUMN IF NOT EXISTS features JSONB NOT NULL DEFAULT '[]';
@@ -0,0 +1,3 @@
+-- Revert: access requests
+DROP TABLE IF EXISTS access_requests;
+DROP TYPE IF EXISTS access_request_status;
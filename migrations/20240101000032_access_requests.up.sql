@@ -0,0 +1,28 @@
+-- Requests for more access to a file or folder, decided by its owner.
+-- A user has at most one pending request per resource.
+DO $$ BEGIN
+    CREATE TYPE access_request_status AS ENUM ('pending', 'approved', 'denied');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+CREATE TABLE IF NOT EXISTS access_requests (
+    id              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    resource_type   resource_type NOT NULL,
+    resource_id     UUID NOT NULL,
+    requester_id    UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    owner_id        UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    permission      acl_permission NOT NULL,
+    message         TEXT,
+    status          access_request_status NOT NULL DEFAULT 'pending',
+    decided_by      UUID REFERENCES users(id) ON DELETE SET NULL,
+    decided_at      TIMESTAMPTZ,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_access_requests_one_pending
+    ON access_requests(requester_id, resource_type, resource_id)
+    WHERE status = 'pending';
+
+CREATE INDEX IF NOT EXISTS idx_access_requests_owner
+    ON access_requests(owner_id, created_at DESC);
@@ -0,0 +1,4 @@
+-- Revert: impersonation
+DROP INDEX IF EXISTS idx_sessions_impersonator;
+ALTER TABLE audit_log DROP COLUMN IF EXISTS impersonator_id;
+ALTER TABLE sessions DROP COLUMN IF EXISTS impersonator_id;
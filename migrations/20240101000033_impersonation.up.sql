@@ -0,0 +1,7 @@
+-- Impersonation: sessions an administrator opened as another user, and
+-- the administrator behind audit entries recorded in them.
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS impersonator_id UUID REFERENCES users(id) ON DELETE CASCADE;
+ALTER TABLE audit_log ADD COLUMN IF NOT EXISTS impersonator_id UUID;
+
+CREATE INDEX IF NOT EXISTS idx_sessions_impersonator ON sessions(impersonator_id)
+    WHERE impersonator_id IS NOT NULL AND terminated_at IS NULL;
@@ -0,0 +1,2 @@
+-- Revert: retention_policies
+DROP TABLE IF EXISTS folder_retention_policies;
@@ -0,0 +1,12 @@
+-- Retention policies attached to folders. A policy covers the folder and
+-- everything below it; files may not be deleted until `retention_days`
+-- after they were created, or at all while `legal_hold` is set.
+CREATE TABLE IF NOT EXISTS folder_retention_policies (
+    folder_id         UUID PRIMARY KEY REFERENCES folders(id) ON DELETE CASCADE,
+    retention_days    INTEGER NOT NULL DEFAULT 0 CHECK (retention_days >= 0),
+    legal_hold        BOOLEAN NOT NULL DEFAULT FALSE,
+    legal_hold_reason TEXT,
+    updated_by        UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at        TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at        TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,4 @@
+-- Revert: file_expiry
+DROP INDEX IF EXISTS idx_files_expires_at;
+ALTER TABLE files DROP COLUMN IF EXISTS expiry_notified_at;
+ALTER TABLE files DROP COLUMN IF EXISTS expires_at;
@@ -0,0 +1,8 @@
+-- Files that delete themselves at a set time. The owner is warned once
+-- before that; `expiry_notified_at` records the warning and is cleared
+-- whenever the expiry changes.
+ALTER TABLE files ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+ALTER TABLE files ADD COLUMN IF NOT EXISTS expiry_notified_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_files_expires_at
+    ON files(expires_at) WHERE expires_at IS NOT NULL;
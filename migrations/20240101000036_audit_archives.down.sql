@@ -0,0 +1,3 @@
+-- Revert: audit_archives
+DROP INDEX IF EXISTS idx_audit_archives_range;
+DROP TABLE IF EXISTS audit_archives;
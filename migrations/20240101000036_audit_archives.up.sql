@@ -0,0 +1,17 @@
+-- Audit entries moved out of `audit_log` by the retention job. Each row
+-- describes one gzipped NDJSON archive; its size, checksum and row count
+-- were checked against a read-back before the entries were deleted.
+CREATE TABLE IF NOT EXISTS audit_archives (
+    id             UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    storage_id     UUID,          -- NULL for an archive directory
+    path           TEXT NOT NULL,
+    row_count      BIGINT NOT NULL,
+    size_bytes     BIGINT NOT NULL,
+    sha256         VARCHAR(64) NOT NULL,
+    first_entry_at TIMESTAMPTZ NOT NULL,
+    last_entry_at  TIMESTAMPTZ NOT NULL,
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_archives_range
+    ON audit_archives(first_entry_at, last_entry_at);
@@ -0,0 +1,4 @@
+-- Revert: folder_display
+DROP INDEX IF EXISTS idx_folder_preferences_folder;
+DROP TABLE IF EXISTS folder_preferences;
+ALTER TABLE folders DROP COLUMN IF EXISTS metadata;
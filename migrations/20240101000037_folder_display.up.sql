@@ -0,0 +1,17 @@
+-- Folder display metadata (color label, icon, pinned flag) shared by
+-- everyone who sees the folder, and per-user view preferences for a
+-- folder's listing.
+ALTER TABLE folders ADD COLUMN IF NOT EXISTS metadata JSONB;
+
+CREATE TABLE IF NOT EXISTS folder_preferences (
+    user_id        UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    folder_id      UUID NOT NULL REFERENCES folders(id) ON DELETE CASCADE,
+    sort_field     TEXT NOT NULL DEFAULT 'name',
+    sort_direction TEXT NOT NULL DEFAULT 'asc',
+    view_mode      TEXT NOT NULL DEFAULT 'list',
+    updated_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, folder_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_folder_preferences_folder
+    ON folder_preferences(folder_id);
@@ -0,0 +1,4 @@
+-- Revert: event_outbox
+DROP INDEX IF EXISTS idx_audit_event;
+ALTER TABLE audit_log DROP COLUMN IF EXISTS event_id;
+DROP TABLE IF EXISTS outbox;
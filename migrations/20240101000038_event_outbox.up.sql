@@ -0,0 +1,23 @@
+-- Transactional outbox: domain events are written here in the same
+-- transaction as the change they describe and published by the outbox
+-- relay, which leases rows so that each is relayed by one server at a
+-- time. Audit entries recorded from events carry the event ID, so a
+-- redelivered event is not audited twice.
+CREATE TABLE IF NOT EXISTS outbox (
+    id           UUID PRIMARY KEY,
+    event        JSONB NOT NULL,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    attempts     INT NOT NULL DEFAULT 0,
+    locked_until TIMESTAMPTZ,
+    delivered_at TIMESTAMPTZ,
+    last_error   TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_outbox_pending
+    ON outbox(created_at) WHERE delivered_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_outbox_delivered
+    ON outbox(delivered_at) WHERE delivered_at IS NOT NULL;
+
+ALTER TABLE audit_log ADD COLUMN IF NOT EXISTS event_id UUID;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_audit_event
+    ON audit_log(event_id) WHERE event_id IS NOT NULL;
@@ -0,0 +1,4 @@
+-- Revert: public_access
+DELETE FROM audit_log WHERE actor_id IS NULL;
+ALTER TABLE audit_log ALTER COLUMN actor_id SET NOT NULL;
+DROP TABLE IF EXISTS public_resources;
@@ -0,0 +1,11 @@
+-- Files and folders readable without signing in. Marking a folder makes
+-- its whole subtree public. Anonymous reads are audited without an actor.
+CREATE TABLE IF NOT EXISTS public_resources (
+    resource_type resource_type NOT NULL CHECK (resource_type IN ('file', 'folder')),
+    resource_id   UUID NOT NULL,
+    published_by  UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    published_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (resource_type, resource_id)
+);
+
+ALTER TABLE audit_log ALTER COLUMN actor_id DROP NOT NULL;
@@ -0,0 +1,4 @@
+-- Revert: api_quotas
+DROP TABLE IF EXISTS api_usage_monthly;
+DROP TABLE IF EXISTS api_usage_daily;
+DROP TABLE IF EXISTS user_quota_tiers;
@@ -0,0 +1,26 @@
+-- Per-user API usage quotas. Usage is counted per user and UTC day;
+-- days of past months are rolled up into monthly totals.
+CREATE TABLE IF NOT EXISTS user_quota_tiers (
+    user_id     UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    tier        VARCHAR(64) NOT NULL,
+    assigned_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    assigned_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS api_usage_daily (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    day     DATE NOT NULL,
+    calls   BIGINT NOT NULL DEFAULT 0,
+    bytes   BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, day)
+);
+
+CREATE TABLE IF NOT EXISTS api_usage_monthly (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    month   DATE NOT NULL,
+    calls   BIGINT NOT NULL DEFAULT 0,
+    bytes   BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, month)
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_usage_daily_day ON api_usage_daily(day);
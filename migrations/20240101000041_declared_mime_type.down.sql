@@ -0,0 +1,2 @@
+-- Revert: declared_mime_type
+ALTER TABLE files DROP COLUMN IF EXISTS declared_mime_type;
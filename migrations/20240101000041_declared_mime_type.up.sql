@@ -0,0 +1,4 @@
+-- Keep the client-declared content type apart from the sniffed one that
+-- files are served with. Existing files only have the declared type.
+ALTER TABLE files ADD COLUMN IF NOT EXISTS declared_mime_type VARCHAR(255);
+UPDATE files SET declared_mime_type = mime_type WHERE declared_mime_type IS NULL;
@@ -2,25 +2,43 @@
 //!
 //! Main entry point that wires all crates together and starts the server.
 
-use tracing;
-use tracing_subscriber::{EnvFilter, fmt};
+mod telemetry;
+
+use std::sync::OnceLock;
+
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use tracing::{self, Level};
+use tracing_subscriber::filter::{Directive, filter_fn};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::reload::Handle;
+use tracing_subscriber::{EnvFilter, Layer, Registry, fmt, reload};
 
 use filehub_core::config::AppConfig;
 use filehub_core::error::AppError;
 
 #[tokio::main]
 async fn main() {
-    let config = match load_configuration() {
+    let mut config = match load_configuration() {
         Ok(c) => c,
         Err(e) => {
             eprintln!("Failed to load configuration: {}", e);
             std::process::exit(1);
         }
     };
+    if let Err(e) = filehub_http::secrets::resolve_config_secrets(&mut config).await {
+        eprintln!("Failed to resolve secrets: {}", e);
+        std::process::exit(1);
+    }
 
-    init_logging(&config);
+    let tracer_provider = init_logging(&config);
 
-    if let Err(e) = run(config).await {
+    let result = run(config).await;
+    if let Some(provider) = tracer_provider
+        && let Err(e) = provider.shutdown()
+    {
+        eprintln!("Failed to flush traces: {}", e);
+    }
+    if let Err(e) = result {
         tracing::error!("Server error: {}", e);
         std::process::exit(1);
     }
@@ -32,26 +50,84 @@ fn load_configuration() -> Result<AppConfig, AppError> {
 
     tracing::info!("Loading configuration for environment: {}", env);
 
-    AppConfig::load(&env)
+    let config = AppConfig::load(&env)?;
+    config.validate().map_err(|issues| {
+        let list: Vec<String> = issues.iter().map(|i| format!("  - {}", i)).collect();
+        AppError::configuration(format!(
+            "Invalid configuration ({} problem(s)):\n{}",
+            issues.len(),
+            list.join("\n")
+        ))
+    })?;
+
+    Ok(config)
 }
 
-/// Initialize tracing/logging
-fn init_logging(config: &AppConfig) {
-    let filter =
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&config.logging.level));
-
-    match config.logging.format.as_str() {
-        "json" => {
-            fmt()
-                .json()
-                .with_env_filter(filter)
-                .with_thread_ids(true)
-                .init();
-        }
-        _ => {
-            fmt().pretty().with_env_filter(filter).init();
+/// Handle for swapping the log filter on configuration reload
+static LOG_FILTER: OnceLock<Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// Initialize tracing/logging, and trace export when enabled
+fn init_logging(config: &AppConfig) -> Option<SdkTracerProvider> {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(&config.logging.level))
+        .add_directive(exported_spans_off());
+    let (filter, handle) = reload::Layer::new(filter);
+    let _ = LOG_FILTER.set(handle);
+
+    let fmt_layer: Box<dyn Layer<Registry> + Send + Sync> = match config.logging.format.as_str() {
+        "json" => fmt::layer().json().with_thread_ids(true).boxed(),
+        _ => fmt::layer().pretty().boxed(),
+    };
+
+    // Without this layer nothing is interested in exported spans, so
+    // their callsites stay disabled
+    let (provider, otel_layer) = if config.logging.tracing.enabled {
+        match telemetry::init_tracer(&config.logging.tracing) {
+            Ok((provider, tracer)) => {
+                let layer = tracing_opentelemetry::layer()
+                    .with_tracer(tracer)
+                    .with_filter(filter_fn(|meta| {
+                        if meta.is_span() {
+                            *meta.level() <= Level::INFO
+                        } else {
+                            *meta.level() <= Level::WARN
+                        }
+                    }));
+                (Some(provider), Some(layer))
+            }
+            Err(e) => {
+                eprintln!("Trace export disabled: {}", e);
+                (None, None)
+            }
         }
-    }
+    } else {
+        (None, None)
+    };
+
+    tracing_subscriber::registry()
+        .with(fmt_layer.with_filter(filter))
+        .with(otel_layer)
+        .init();
+    provider
+}
+
+/// Keeps spans recorded only for trace export out of the log output
+fn exported_spans_off() -> Directive {
+    format!("{}=off", filehub_core::telemetry::TARGET)
+        .parse()
+        .expect("valid directive")
+}
+
+/// Replace the active log filter (used by configuration reload)
+fn set_log_level(directive: &str) -> Result<(), String> {
+    let filter = EnvFilter::try_new(directive)
+        .map_err(|e| e.to_string())?
+        .add_directive(exported_spans_off());
+    LOG_FILTER
+        .get()
+        .ok_or_else(|| "log filter is not reloadable".to_string())?
+        .reload(filter)
+        .map_err(|e| e.to_string())
 }
 
 /// Main server run function
@@ -69,5 +145,10 @@ async fn run(config: AppConfig) -> Result<(), AppError> {
     tracing::info!("Database migrations complete");
 
     // ── Step 2: Delegate to API crate ───────────────────────────
-    filehub_api::app::run_server(config, db_pool.into_pool()).await
+    let reload = filehub_api::reload::ReloadHooks {
+        load: Box::new(load_configuration),
+        set_log_level: Box::new(set_log_level),
+    };
+
+    filehub_api::app::run_server(config, db_pool, Some(reload)).await
 }
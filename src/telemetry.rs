@@ -0,0 +1,87 @@
+//! OpenTelemetry trace export.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use opentelemetry::global;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_http::{HttpClient, HttpError, Request, Response};
+use opentelemetry_otlp::{Protocol, SpanExporter, WithExportConfig, WithHttpConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{Sampler, SdkTracer, SdkTracerProvider};
+
+use filehub_core::config::TracingConfig;
+
+/// Builds the OTLP/HTTP trace pipeline and installs the W3C trace context
+/// propagator. The returned provider must be shut down on exit to flush
+/// buffered spans.
+pub fn init_tracer(config: &TracingConfig) -> Result<(SdkTracerProvider, SdkTracer), String> {
+    let timeout = Duration::from_secs(config.export_timeout_seconds);
+    let client = ExportClient {
+        client: reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| e.to_string())?,
+        runtime: tokio::runtime::Handle::current(),
+    };
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_http_client(client)
+        .with_protocol(Protocol::HttpBinary)
+        .with_endpoint(config.otlp_endpoint.clone())
+        .with_timeout(timeout)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            config.sample_ratio,
+        ))))
+        .with_resource(
+            Resource::builder()
+                .with_service_name(config.service_name.clone())
+                .build(),
+        )
+        .build();
+    let tracer = provider.tracer("filehub");
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    Ok((provider, tracer))
+}
+
+/// Sends export requests with the workspace's `reqwest`.
+#[derive(Debug)]
+struct ExportClient {
+    client: reqwest::Client,
+    /// The batch processor exports from its own thread, outside the
+    /// runtime `reqwest` needs, so requests are spawned onto it.
+    runtime: tokio::runtime::Handle,
+}
+
+#[async_trait]
+impl HttpClient for ExportClient {
+    async fn send_bytes(&self, request: Request<Bytes>) -> Result<Response<Bytes>, HttpError> {
+        let (parts, body) = request.into_parts();
+        let request = self
+            .client
+            .request(parts.method, parts.uri.to_string())
+            .headers(parts.headers)
+            .body(body);
+        let (status, headers, body) = self
+            .runtime
+            .spawn(async move {
+                let response = request.send().await?;
+                let status = response.status();
+                let headers = response.headers().clone();
+                let body = response.bytes().await?;
+                Ok::<_, reqwest::Error>((status, headers, body))
+            })
+            .await??;
+
+        let mut response = Response::builder().status(status).body(body)?;
+        *response.headers_mut() = headers;
+        Ok(response)
+    }
+}